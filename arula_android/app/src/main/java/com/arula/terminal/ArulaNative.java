@@ -2,6 +2,8 @@ package com.arula.terminal;
 
 import android.content.Context;
 import android.util.Log;
+import org.json.JSONException;
+import org.json.JSONObject;
 import java.util.concurrent.ConcurrentLinkedQueue;
 import java.util.concurrent.atomic.AtomicBoolean;
 
@@ -35,6 +37,11 @@ public class ArulaNative {
      */
     public static native void sendMessage(String message);
 
+    /**
+     * Stop the response being streamed; no more chunks of it arrive
+     */
+    public static native void cancelStream();
+
     /**
      * Set configuration provider
      */
@@ -144,7 +151,16 @@ public class ArulaNative {
      * Enhance configuration with Android-specific settings
      */
     private static String enhanceConfigForAndroid(Context context, String configJson) {
-        // Add Android paths, Termux integration, etc.
-        return configJson; // TODO: Implement Android-specific config
+        try {
+            JSONObject config = configJson == null || configJson.isEmpty()
+                    ? new JSONObject()
+                    : new JSONObject(configJson);
+            // The core keeps its config.json in app-private storage
+            config.put("files_dir", context.getFilesDir().getAbsolutePath());
+            return config.toString();
+        } catch (JSONException e) {
+            Log.e(TAG, "Invalid init config", e);
+            return configJson;
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+//! The agent behind `sendMessage`
+//!
+//! Messages run through arula_core's `ArulaAgent`, the same agent loop the
+//! CLI and desktop app use, with the Android tools (see `tools`). Its
+//! events reach Java through `callbacks`. The conversation is kept here
+//! between messages, and the configuration is read again for each one so
+//! settings changes apply straight away.
+
+use crate::platform::android::{
+    callbacks, create_android_tool_registry, AndroidCommandExecutor, AndroidConfig, AndroidContext,
+};
+use anyhow::Result;
+use arula_core::sdk::{AgentEvent, ArulaAgent, Message};
+use futures::StreamExt;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::runtime::Runtime;
+
+static RUNTIME: LazyLock<Runtime> =
+    LazyLock::new(|| Runtime::new().expect("Failed to start the async runtime"));
+
+/// The conversation so far
+static HISTORY: Mutex<Vec<Message>> = Mutex::new(Vec::new());
+
+/// Answer `prompt` in the background
+pub fn send(prompt: String) {
+    RUNTIME.spawn(async move {
+        if let Err(e) = run(&prompt).await {
+            log::error!("Agent run failed: {:#}", e);
+            callbacks::on_error(&format!("{:#}", e));
+        }
+    });
+}
+
+/// Forget the conversation
+pub fn clear_history() {
+    HISTORY.lock().unwrap().clear();
+}
+
+async fn run(prompt: &str) -> Result<()> {
+    callbacks::begin_stream();
+    let history = HISTORY.lock().unwrap().clone();
+    let executor = Arc::new(AndroidCommandExecutor::new(AndroidContext::new()));
+    let agent = ArulaAgent::builder()
+        .config(AndroidConfig::shared().current())
+        .tools(create_android_tool_registry(executor))
+        .history(history)
+        .build()?;
+
+    let mut events = agent.stream(prompt).await?;
+    let mut answer = String::new();
+    let mut compacted = None;
+    loop {
+        let event = tokio::select! {
+            event = events.next() => event,
+            _ = callbacks::cancelled() => break,
+        };
+        let Some(event) = event else {
+            break;
+        };
+        match event {
+            AgentEvent::Text(text) => {
+                answer.push_str(&text);
+                callbacks::on_stream_chunk(&text);
+            }
+            AgentEvent::ToolCall(call) => callbacks::on_tool_start(&call.name, &call.id),
+            AgentEvent::ToolResult(result) => {
+                let json = serde_json::to_string(&result).unwrap_or_default();
+                callbacks::on_tool_complete(&result.id, &json);
+            }
+            AgentEvent::LoopDetected(diagnosis) => {
+                callbacks::on_error(&format!("Stopped a repeating loop: {}", diagnosis));
+            }
+            AgentEvent::ContextCompacted { summary, .. } => compacted = Some(summary),
+            AgentEvent::Error(error) => {
+                callbacks::on_error(&error);
+                return Ok(());
+            }
+            _ => {}
+        }
+    }
+    callbacks::flush_stream();
+
+    let mut history = HISTORY.lock().unwrap();
+    if let Some(summary) = compacted {
+        *history = vec![Message::summary(&summary)];
+    }
+    history.push(Message::user(prompt));
+    history.push(Message::assistant(answer));
+    Ok(())
+}
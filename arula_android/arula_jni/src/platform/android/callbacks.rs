@@ -0,0 +1,167 @@
+//! Callback functions from Rust to Java
+//!
+//! Callbacks call ArulaNative's static `on*` methods through the JVM saved
+//! by `initialize`, from whichever thread they run on. Stream chunks are
+//! batched rather than sent one by one: they collect in a buffer that a
+//! flusher thread hands to Java every `FLUSH_INTERVAL`. A fast model then
+//! costs about twenty JNI calls a second instead of one per token, and
+//! while Java is still busy with one batch the next one grows instead of
+//! queueing more calls. Java can stop a stream with `cancelStream`; later
+//! chunks are dropped, and work waiting on `cancelled()` stops.
+
+use jni::objects::{GlobalRef, JClass, JValue};
+use jni::{JNIEnv, JavaVM};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Condvar, LazyLock, Mutex, Once, OnceLock};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// How long stream chunks collect before they're sent to Java
+const FLUSH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The JVM and ArulaNative's class, saved so any thread can call back
+struct JavaBridge {
+    vm: JavaVM,
+    class: GlobalRef,
+}
+
+static BRIDGE: OnceLock<JavaBridge> = OnceLock::new();
+
+/// Stream text not yet sent to Java
+static PENDING: Mutex<String> = Mutex::new(String::new());
+static PENDING_READY: Condvar = Condvar::new();
+/// Held while a batch is taken and delivered, so batches arrive in order
+static DELIVERY: Mutex<()> = Mutex::new(());
+static FLUSHER: Once = Once::new();
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static CANCEL: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Save the JVM and ArulaNative's class for the callbacks
+pub fn attach(env: &mut JNIEnv, class: &JClass) -> jni::errors::Result<()> {
+    let vm = env.get_java_vm()?;
+    let class = env.new_global_ref(class)?;
+    let _ = BRIDGE.set(JavaBridge { vm, class });
+    Ok(())
+}
+
+/// Call ArulaNative's static `method`, which takes `args` as strings
+fn call_java(method: &str, args: &[&str]) {
+    let Some(bridge) = BRIDGE.get() else {
+        return;
+    };
+    let result = bridge.vm.attach_current_thread_permanently().and_then(|mut env| {
+        env.with_local_frame(args.len() as i32 + 1, |env| {
+            let strings = args
+                .iter()
+                .map(|arg| env.new_string(arg))
+                .collect::<jni::errors::Result<Vec<_>>>()?;
+            let values: Vec<JValue> = strings.iter().map(|s| JValue::Object(s.as_ref())).collect();
+            let signature = format!("({})V", "Ljava/lang/String;".repeat(args.len()));
+            let class = <&JClass>::from(bridge.class.as_obj());
+
+            let outcome = env.call_static_method(class, method, &signature, &values);
+            if env.exception_check()? {
+                env.exception_describe()?;
+                env.exception_clear()?;
+            }
+            outcome.map(|_| ())
+        })
+    });
+    if let Err(e) = result {
+        log::error!("Failed to call ArulaNative.{}: {}", method, e);
+    }
+}
+
+/// Start a new stream, clearing an earlier cancellation
+pub fn begin_stream() {
+    CANCELLED.store(false, Ordering::SeqCst);
+}
+
+/// Stop the current stream: what's pending is dropped, and so is what
+/// comes after, until the next `begin_stream`
+pub fn cancel_stream() {
+    CANCELLED.store(true, Ordering::SeqCst);
+    PENDING.lock().unwrap().clear();
+    CANCEL.notify_waiters();
+}
+
+/// Whether Java cancelled the current stream
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
+
+/// Resolves once Java cancels the current stream
+pub async fn cancelled() {
+    loop {
+        let notified = CANCEL.notified();
+        if is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Send what's left of the stream now, before anything that must follow it
+pub fn flush_stream() {
+    let _delivery = DELIVERY.lock().unwrap();
+    let batch = std::mem::take(&mut *PENDING.lock().unwrap());
+    if !batch.is_empty() && !is_cancelled() {
+        call_java("onStreamChunk", &[&batch]);
+    }
+}
+
+/// Wait for stream text, let more collect for `FLUSH_INTERVAL`, send it
+fn flush_loop() {
+    loop {
+        {
+            let mut pending = PENDING.lock().unwrap();
+            while pending.is_empty() {
+                pending = PENDING_READY.wait(pending).unwrap();
+            }
+        }
+        std::thread::sleep(FLUSH_INTERVAL);
+        flush_stream();
+    }
+}
+
+pub fn on_message(message: &str) {
+    flush_stream();
+    log::info!("Message: {}", message);
+    call_java("onMessageReceived", &[message]);
+}
+
+pub fn on_stream_chunk(chunk: &str) {
+    if is_cancelled() {
+        return;
+    }
+    FLUSHER.call_once(|| {
+        let spawned = std::thread::Builder::new()
+            .name("arula-stream".into())
+            .spawn(flush_loop);
+        if let Err(e) = spawned {
+            log::error!("Failed to start the stream flusher: {}", e);
+        }
+    });
+    log::debug!("Stream: {}", chunk);
+    PENDING.lock().unwrap().push_str(chunk);
+    PENDING_READY.notify_one();
+}
+
+pub fn on_tool_start(tool_name: &str, tool_id: &str) {
+    flush_stream();
+    log::info!("Tool started: {} ({})", tool_name, tool_id);
+    call_java("onToolStart", &[tool_name, tool_id]);
+}
+
+pub fn on_tool_complete(tool_id: &str, result: &str) {
+    flush_stream();
+    log::info!("Tool completed: {} - {}", tool_id, result);
+    call_java("onToolComplete", &[tool_id, result]);
+}
+
+pub fn on_error(error: &str) {
+    flush_stream();
+    log::error!("Error: {}", error);
+    call_java("onError", &[error]);
+}
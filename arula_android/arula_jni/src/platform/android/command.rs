@@ -1,35 +1,92 @@
-//! Android command execution using Termux
-
+//! Android command execution
+//!
+//! Commands run in a shell from the app's sandbox: a bash bundled under
+//! `<files_dir>/usr/bin` when the app ships one, Termux's bash when it is
+//! installed and readable, otherwise the system `/system/bin/sh`. They
+//! start in the sandbox root with `HOME` and `TMPDIR` pointing into it.
+
+use crate::platform::android::filesystem::Sandbox;
 use crate::platform::android::{AndroidContext, callbacks};
 use anyhow::Result;
-use std::process::{Command, Stdio};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::Mutex;
 use tokio::process::Command as AsyncCommand;
 use tokio::io::{AsyncBufReadExt, BufReader};
 
-/// Android command executor using Termux environment
+/// Termux's bash, usable when Termux shares its files with the app
+const TERMUX_BASH: &str = "/data/data/com.termux/files/usr/bin/bash";
+/// The shell every Android device has
+const SYSTEM_SH: &str = "/system/bin/sh";
+
+/// Android command executor running in the app's sandbox
 pub struct AndroidCommandExecutor {
     ctx: AndroidContext,
-    shell: Arc<Mutex<String>>,
+    /// Shell chosen by the app, instead of the detected one
+    shell: Arc<StdMutex<Option<String>>>,
+    /// Working directory, inside the sandbox (the root when `None`)
+    cwd: Arc<StdMutex<Option<PathBuf>>>,
+    env: Arc<StdMutex<HashMap<String, String>>>,
 }
 
 impl AndroidCommandExecutor {
     pub fn new(ctx: AndroidContext) -> Self {
         Self {
             ctx,
-            shell: Arc::new(Mutex::new("/data/data/com.termux/files/usr/bin/bash".to_string())),
+            shell: Arc::new(StdMutex::new(None)),
+            cwd: Arc::new(StdMutex::new(None)),
+            env: Arc::new(StdMutex::new(HashMap::new())),
         }
     }
 
-    /// Execute a command synchronously
-    pub async fn execute_sync(&self, command: &str, args: &[&str]) -> Result<CommandResult> {
-        let shell = self.shell.lock().await;
+    /// Run commands with `shell` instead of the detected one
+    pub fn set_shell(&self, shell: &str) {
+        *self.shell.lock().unwrap() = Some(shell.to_string());
+    }
+
+    /// The shell commands run in
+    pub fn shell(&self) -> String {
+        if let Some(shell) = self.shell.lock().unwrap().clone() {
+            return shell;
+        }
+        detect_shell(Sandbox::current().root())
+    }
+
+    /// A shell command for `script`, in the working directory with the
+    /// sandbox environment
+    pub fn shell_command(&self, script: &str) -> std::process::Command {
+        let sandbox = Sandbox::current();
+        let root = sandbox.root().to_path_buf();
+        let cwd = self.cwd.lock().unwrap().clone().unwrap_or_else(|| root.clone());
+        let tmp = root.join("tmp");
+        if let Err(e) = std::fs::create_dir_all(&tmp) {
+            log::warn!("Failed to create {:?}: {}", tmp, e);
+        }
 
-        // Build command using Termux shell
-        let mut cmd = AsyncCommand::new(&*shell);
+        let mut path = root.join("usr/bin").to_string_lossy().to_string();
+        if let Ok(system_path) = std::env::var("PATH") {
+            path = format!("{}:{}", path, system_path);
+        }
+
+        let mut cmd = std::process::Command::new(self.shell());
         cmd.arg("-c")
-            .arg(format!("{} {}", command, args.join(" ")))
+            .arg(script)
+            .current_dir(&cwd)
+            .env("HOME", &root)
+            .env("TMPDIR", &tmp)
+            .env("PWD", &cwd)
+            .env("PATH", path)
+            .envs(self.env.lock().unwrap().iter())
+            .stdin(Stdio::null());
+        cmd
+    }
+
+    /// Execute a command synchronously
+    pub async fn execute_sync(&self, command: &str, args: &[&str]) -> Result<CommandResult> {
+        let mut cmd = AsyncCommand::from(self.shell_command(&format!("{} {}", command, args.join(" "))));
+        cmd.kill_on_drop(true)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
@@ -47,14 +104,14 @@ impl AndroidCommandExecutor {
         // Read stdout
         let stdout_reader = BufReader::new(stdout);
         let stdout_clone = Arc::clone(&stdout_lines);
-        tokio::spawn(async move {
+        let stdout_task = tokio::spawn(async move {
             let mut lines = stdout_clone.lock().await;
             let mut reader = stdout_reader.lines();
             while let Some(line) = reader.next_line().await.map_err(|e| {
                 log::error!("Error reading stdout: {}", e);
             })? {
                 // Send to callback before pushing
-                callbacks::on_stream_chunk(&line);
+                callbacks::on_stream_chunk(&format!("{}\n", line));
                 lines.push(line);
             }
             Ok::<(), ()>(())
@@ -63,22 +120,30 @@ impl AndroidCommandExecutor {
         // Read stderr
         let stderr_reader = BufReader::new(stderr);
         let stderr_clone = Arc::clone(&stderr_lines);
-        tokio::spawn(async move {
+        let stderr_task = tokio::spawn(async move {
             let mut lines = stderr_clone.lock().await;
             let mut reader = stderr_reader.lines();
             while let Some(line) = reader.next_line().await.map_err(|e| {
                 log::error!("Error reading stderr: {}", e);
             })? {
                 // Send error to callback before pushing
-                callbacks::on_stream_chunk(&format!("[ERROR] {}", &line));
+                callbacks::on_stream_chunk(&format!("[ERROR] {}\n", &line));
                 lines.push(line);
             }
             Ok::<(), ()>(())
         });
 
-        // Wait for command to complete
-        let status = child.wait().await
-            .map_err(|e| anyhow::anyhow!("Command execution error: {}", e))?;
+        // Wait for command to complete, or for Java to cancel the stream
+        let status = tokio::select! {
+            status = child.wait() => status,
+            _ = callbacks::cancelled() => {
+                let _ = child.kill().await;
+                child.wait().await
+            }
+        }
+        .map_err(|e| anyhow::anyhow!("Command execution error: {}", e))?;
+        let _ = stdout_task.await;
+        let _ = stderr_task.await;
 
         let stdout_output = {
             let lines = stdout_lines.lock().await;
@@ -115,15 +180,9 @@ impl AndroidCommandExecutor {
 
     /// Check if command exists
     pub async fn command_exists(&self, command: &str) -> bool {
-        let shell = self.shell.lock().await;
+        let mut cmd = AsyncCommand::from(self.shell_command(&format!("command -v {}", command)));
 
-        let output = AsyncCommand::new(&*shell)
-            .arg("-c")
-            .arg(format!("which {}", command))
-            .output()
-            .await;
-
-        match output {
+        match cmd.output().await {
             Ok(o) => o.status.success(),
             Err(_) => false,
         }
@@ -131,15 +190,12 @@ impl AndroidCommandExecutor {
 
     /// Get environment variables
     pub async fn get_env_var(&self, key: &str) -> Option<String> {
-        let shell = self.shell.lock().await;
-
-        let output = AsyncCommand::new(&*shell)
-            .arg("-c")
-            .arg(format!("echo -n ${}", key))
-            .output()
-            .await;
+        if let Some(value) = self.env.lock().unwrap().get(key) {
+            return Some(value.clone());
+        }
+        let mut cmd = AsyncCommand::from(self.shell_command(&format!("printf %s \"${}\"", key)));
 
-        match output {
+        match cmd.output().await {
             Ok(o) if o.status.success() => {
                 Some(String::from_utf8_lossy(&o.stdout).to_string())
             }
@@ -147,44 +203,31 @@ impl AndroidCommandExecutor {
         }
     }
 
-    /// Set environment variable
+    /// Set environment variable for the commands that follow
     pub async fn set_env_var(&self, key: &str, value: &str) -> Result<()> {
-        let shell = self.shell.lock().await;
-
-        let output = AsyncCommand::new(&*shell)
-            .arg("-c")
-            .arg(format!("export {}={}", key, value))
-            .status()
-            .await;
-
-        match output {
-            Ok(_) => Ok(()),
-            Err(e) => Err(anyhow::anyhow!("Failed to set env var: {}", e)),
+        if key.is_empty() || key.contains('=') {
+            return Err(anyhow::anyhow!("Invalid environment variable name: {}", key));
         }
+        self.env.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
     }
 
     /// Get current working directory
     pub async fn current_dir(&self) -> Result<String> {
-        self.get_env_var("PWD")
-            .await
-            .ok_or_else(|| anyhow::anyhow!("Failed to get current directory"))
+        let cwd = self.cwd.lock().unwrap().clone();
+        let cwd = cwd.unwrap_or_else(|| Sandbox::current().root().to_path_buf());
+        Ok(cwd.to_string_lossy().to_string())
     }
 
-    /// Change directory
+    /// Change directory, within the sandbox
     pub async fn change_dir(&self, path: &str) -> Result<()> {
-        let shell = self.shell.lock().await;
-
-        let output = AsyncCommand::new(&*shell)
-            .arg("-c")
-            .arg(format!("cd {}", path))
-            .status()
-            .await;
-
-        match output {
-            Ok(status) if status.success() => Ok(()),
-            Ok(_) => Err(anyhow::anyhow!("Failed to change directory to {}", path)),
-            Err(e) => Err(anyhow::anyhow!("Command error: {}", e)),
+        let mut cwd = self.cwd.lock().unwrap();
+        let dir = Sandbox::current().resolve_from(cwd.as_deref(), path)?;
+        if !dir.is_dir() {
+            return Err(anyhow::anyhow!("Failed to change directory to {}", path));
         }
+        *cwd = Some(dir);
+        Ok(())
     }
 
     /// Execute Termux-specific API commands
@@ -223,6 +266,23 @@ impl AndroidCommandExecutor {
     }
 }
 
+/// The first usable shell: bundled with the app, Termux's, or the system's
+fn detect_shell(root: &Path) -> String {
+    let bundled = root.join("usr/bin/bash");
+    let shell = [bundled.as_path(), Path::new(TERMUX_BASH)]
+        .into_iter()
+        .find(|shell| is_executable(shell))
+        .map(|shell| shell.to_string_lossy().to_string());
+    shell.unwrap_or_else(|| SYSTEM_SH.to_string())
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct CommandResult {
     pub exit_code: i32,
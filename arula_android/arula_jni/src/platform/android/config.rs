@@ -1,267 +1,351 @@
-//! Android configuration management using SharedPreferences
-
-use crate::platform::android::AndroidContext;
-use anyhow::Result;
-use serde::{Deserialize, Serialize};
+//! Android configuration management
+//!
+//! The app uses arula_core's `Config`, saved as config.json in the app's
+//! private files directory (passed as `files_dir` to `initialize`). Java
+//! sends either a whole config or the flat settings of `SettingsManager`;
+//! secrets (API keys, sync credentials, MCP and telemetry headers) are
+//! masked in what it gets back, and masked ones it sends back are kept as
+//! they were.
+
+use anyhow::{Context, Result};
+use arula_core::utils::config::Config;
+use serde_json::Value;
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// File the configuration is kept in, inside the files directory
+pub const CONFIG_FILE: &str = "config.json";
+/// Prefix of a masked secret
+const MASK: &str = "••••";
+
+static SHARED: LazyLock<AndroidConfig> = LazyLock::new(AndroidConfig::new);
 
-/// Android configuration backend using SharedPreferences
+/// Android configuration backend, persisted to app-private storage
+#[derive(Clone)]
 pub struct AndroidConfig {
-    ctx: AndroidContext,
-    cache: Arc<RwLock<HashMap<String, String>>>,
+    path: Arc<RwLock<Option<PathBuf>>>,
+    config: Arc<RwLock<Config>>,
 }
 
 impl AndroidConfig {
-    pub fn new(ctx: AndroidContext) -> Self {
+    pub fn new() -> Self {
         Self {
-            ctx,
-            cache: Arc::new(RwLock::new(HashMap::new())),
+            path: Arc::new(RwLock::new(None)),
+            config: Arc::new(RwLock::new(Config::default())),
         }
     }
 
-    /// Load configuration from SharedPreferences
-    pub async fn load(&self) -> Result<Config> {
-        let mut config = Config::default();
-
-        // In a real implementation, this would use SharedPreferences
-        // For now, we'll simulate with environment variables and defaults
-
-        // Active provider
-        if let Ok(provider) = std::env::var("ARULA_PROVIDER") {
-            config.active_provider = provider;
-        }
-
-        // API keys from environment
-        if let Ok(key) = std::env::var("OPENAI_API_KEY") {
-            config.providers.insert("openai".to_string(), ProviderConfig {
-                api_key: Some(key),
-                api_url: Some("https://api.openai.com/v1".to_string()),
-                model: Some("gpt-4".to_string()),
-                max_tokens: Some(4096),
-                temperature: Some(0.7),
-                ..Default::default()
-            });
-        }
-
-        if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
-            config.providers.insert("anthropic".to_string(), ProviderConfig {
-                api_key: Some(key),
-                api_url: Some("https://api.anthropic.com".to_string()),
-                model: Some("claude-3-opus-20240229".to_string()),
-                max_tokens: Some(4096),
-                temperature: Some(0.7),
-                ..Default::default()
-            });
-        }
+    /// The configuration the JNI calls work on
+    pub fn shared() -> &'static AndroidConfig {
+        &SHARED
+    }
 
-        if let Ok(key) = std::env::var("ZAI_API_KEY") {
-            config.providers.insert("zai".to_string(), ProviderConfig {
-                api_key: Some(key),
-                api_url: Some("https://z.ai/api".to_string()),
-                model: Some("glm-4".to_string()),
-                max_tokens: Some(4096),
-                temperature: Some(0.7),
-                ..Default::default()
-            });
-        }
+    /// Keep the configuration in `dir` (the app's files directory) and
+    /// load what was saved there
+    pub fn set_storage_dir(&self, dir: &Path) -> Result<Config> {
+        let path = dir.join(CONFIG_FILE);
+        *self.path.write().unwrap() = Some(path);
+        self.load()
+    }
 
-        // Cache the configuration
-        let json = serde_json::to_string(&config)?;
-        let mut cache = self.cache.write().await;
-        cache.insert("config".to_string(), json);
+    /// The file the configuration is saved in, once the files directory is known
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.read().unwrap().clone()
+    }
 
+    /// Load the saved configuration, or the defaults when there is none
+    pub fn load(&self) -> Result<Config> {
+        let path = self.path.read().unwrap().clone();
+        let config = match path {
+            Some(path) if path.exists() => Config::load_from_file(&path)
+                .with_context(|| format!("Failed to load {}", path.display()))?,
+            _ => Config::default(),
+        };
+        *self.config.write().unwrap() = config.clone();
         Ok(config)
     }
 
-    /// Save configuration to SharedPreferences
-    pub async fn save(&self, config: &Config) -> Result<()> {
-        let json = serde_json::to_string(config)?;
-
-        // In a real implementation, this would save to SharedPreferences
-        // For now, we'll just cache it
-        let mut cache = self.cache.write().await;
-        cache.insert("config".to_string(), json);
-
-        log::info!("Configuration saved to Android SharedPreferences");
+    /// Save `config` and make it the current one
+    pub fn save(&self, config: &Config) -> Result<()> {
+        if let Some(path) = self.path.read().unwrap().as_ref() {
+            config
+                .save_to_file(path)
+                .with_context(|| format!("Failed to save {}", path.display()))?;
+        } else {
+            log::warn!("No files directory yet; configuration is kept in memory only");
+        }
+        *self.config.write().unwrap() = config.clone();
+        log::info!("Configuration saved");
         Ok(())
     }
 
-    /// Get a configuration value
-    pub async fn get(&self, key: &str) -> Option<String> {
-        let cache = self.cache.read().await;
-        cache.get(key).cloned()
+    /// The current configuration
+    pub fn current(&self) -> Config {
+        self.config.read().unwrap().clone()
     }
 
-    /// Set a configuration value
-    pub async fn set(&self, key: &str, value: &str) -> Result<()> {
-        let mut cache = self.cache.write().await;
-        cache.insert(key.to_string(), value.to_string());
+    /// Apply settings sent from Java and save them
+    pub fn apply_json(&self, json: &str) -> Result<Config> {
+        let config = merge_settings(&self.current(), json)?;
+        self.save(&config)?;
+        Ok(config)
+    }
 
-        // In a real implementation, persist to SharedPreferences
-        log::debug!("Config set: {} = {}", key, value);
-        Ok(())
+    /// The current configuration as JSON, with secrets masked
+    pub fn masked_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&masked(&self.current()))?)
     }
 
     /// Get API key for provider
-    pub async fn get_api_key(&self, provider: &str) -> Option<String> {
+    pub fn get_api_key(&self, provider: &str) -> Option<String> {
         let env_key = format!("{}_API_KEY", provider.to_uppercase());
-        if let Some(key) = std::env::var(&env_key).ok() {
+        if let Ok(key) = std::env::var(&env_key) {
             return Some(key);
         }
-        self.get(&format!("{}.api_key", provider)).await
+        let config = self.config.read().unwrap();
+        config
+            .providers
+            .get(provider)
+            .map(|p| p.api_key.clone())
+            .filter(|key| !key.is_empty())
     }
 
     /// Get model for provider
-    pub async fn get_model(&self, provider: &str) -> String {
-        self.get(&format!("{}.model", provider)).await
-            .unwrap_or_else(|| self.default_model(provider))
+    pub fn get_model(&self, provider: &str) -> Option<String> {
+        let config = self.config.read().unwrap();
+        config.providers.get(provider).map(|p| p.model.clone())
     }
 
     /// Get API URL for provider
-    pub async fn get_api_url(&self, provider: &str) -> String {
-        self.get(&format!("{}.api_url", provider)).await
-            .unwrap_or_else(|| self.default_url(provider))
+    pub fn get_api_url(&self, provider: &str) -> Option<String> {
+        let config = self.config.read().unwrap();
+        config.providers.get(provider).and_then(|p| p.api_url.clone())
     }
 
-    /// Get maximum tokens for provider
-    pub async fn get_max_tokens(&self, provider: &str) -> u32 {
-        self.get(&format!("{}.max_tokens", provider)).await
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(4096)
+    /// Reset to the defaults and save them
+    pub fn clear(&self) -> Result<()> {
+        self.save(&Config::default())?;
+        log::info!("Configuration cleared");
+        Ok(())
     }
+}
 
-    /// Get temperature for provider
-    pub async fn get_temperature(&self, provider: &str) -> f32 {
-        self.get(&format!("{}.temperature", provider)).await
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.7)
+impl Default for AndroidConfig {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    /// Default model for provider
-    fn default_model(&self, provider: &str) -> String {
-        match provider {
-            "openai" => "gpt-4",
-            "anthropic" => "claude-3-opus-20240229",
-            "zai" => "glm-4",
-            "ollama" => "llama2",
-            _ => "gpt-3.5-turbo",
-        }.to_string()
+/// `current` updated with the settings in `json`: a whole config (with
+/// `providers`) replaces it, flat settings change the active provider
+pub fn merge_settings(current: &Config, json: &str) -> Result<Config> {
+    let value: Value = serde_json::from_str(json).context("Config is not valid JSON")?;
+    if value.get("providers").is_some() {
+        let mut config: Config =
+            serde_json::from_value(value).context("Config doesn't match ARULA's settings")?;
+        // Secrets come back masked from getConfig: keep the real ones
+        let mut current = current.clone();
+        let real: HashMap<String, String> = secrets_mut(&mut current)
+            .into_iter()
+            .map(|(path, secret)| (path, secret.clone()))
+            .collect();
+        for (path, secret) in secrets_mut(&mut config) {
+            if is_masked(secret) {
+                *secret = real.get(&path).cloned().unwrap_or_default();
+            }
+        }
+        return Ok(config);
     }
 
-    /// Default URL for provider
-    fn default_url(&self, provider: &str) -> String {
-        match provider {
-            "openai" => "https://api.openai.com/v1",
-            "anthropic" => "https://api.anthropic.com",
-            "zai" => "https://z.ai/api",
-            "ollama" => "http://localhost:11434",
-            _ => "https://api.openai.com/v1",
-        }.to_string()
+    let mut config = current.clone();
+    if let Some(provider) = value["active_provider"].as_str().filter(|p| !p.is_empty()) {
+        config.switch_provider(provider)?;
     }
-
-    /// Clear all configuration
-    pub async fn clear(&self) -> Result<()> {
-        let mut cache = self.cache.write().await;
-        cache.clear();
-
-        // In a real implementation, clear SharedPreferences
-        log::info!("Configuration cleared");
-        Ok(())
+    if let Some(model) = value["model"].as_str().filter(|m| !m.is_empty()) {
+        config.set_model(model);
+    }
+    if let Some(key) = value["api_key"].as_str().filter(|k| !is_masked(k)) {
+        config.set_api_key(key);
     }
+    if let Some(url) = value["api_url"].as_str().filter(|u| !u.is_empty()) {
+        config.set_api_url(url);
+    }
+    if let Some(provider) = config.get_active_provider_config_mut() {
+        if let Some(temperature) = value["temperature"].as_f64() {
+            provider.temperature = Some(temperature as f32);
+        }
+        if let Some(max_tokens) = value["max_tokens"].as_u64() {
+            provider.max_tokens = Some(max_tokens as u32);
+        }
+        if let Some(streaming) = value["streaming"].as_bool() {
+            provider.streaming = Some(streaming);
+        }
+        if let Some(thinking) = value["thinking_enabled"].as_bool() {
+            provider.thinking_enabled = Some(thinking);
+        }
+    }
+    Ok(config)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub active_provider: String,
-    pub providers: HashMap<String, ProviderConfig>,
-    pub ui: UiConfig,
-    pub system: SystemConfig,
+/// `config` with every secret masked
+pub fn masked(config: &Config) -> Config {
+    let mut config = config.clone();
+    for (_, secret) in secrets_mut(&mut config) {
+        *secret = mask_key(secret);
+    }
+    config
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        let mut providers = HashMap::new();
-        providers.insert("openai".to_string(), ProviderConfig::default());
-        providers.insert("anthropic".to_string(), ProviderConfig::default());
-
-        Self {
-            active_provider: "openai".to_string(),
-            providers,
-            ui: UiConfig::default(),
-            system: SystemConfig::default(),
+/// Every secret in `config` with where it is, e.g.
+/// `providers.openai.api_key`, so masking and restoring cover the same
+/// fields
+fn secrets_mut(config: &mut Config) -> Vec<(String, &mut String)> {
+    let mut secrets = Vec::new();
+    for (name, provider) in config.providers.iter_mut() {
+        secrets.push((format!("providers.{}.api_key", name), &mut provider.api_key));
+    }
+    for (name, profile) in config.profiles.iter_mut() {
+        if let Some(key) = profile.api_key.as_mut() {
+            secrets.push((format!("profiles.{}.api_key", name), key));
         }
     }
+    for (name, server) in config.mcp_servers.iter_mut() {
+        for (header, value) in server.headers.iter_mut() {
+            secrets.push((format!("mcp_servers.{}.headers.{}", name, header), value));
+        }
+    }
+    if let Some(sync) = config.sync.as_mut() {
+        let fields = [
+            ("password", &mut sync.password),
+            ("access_key", &mut sync.access_key),
+            ("secret_key", &mut sync.secret_key),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value.as_mut() {
+                secrets.push((format!("sync.{}", field), value));
+            }
+        }
+    }
+    if let Some(telemetry) = config.telemetry.as_mut() {
+        for (header, value) in telemetry.headers.iter_mut() {
+            secrets.push((format!("telemetry.headers.{}", header), value));
+        }
+    }
+    if let Some(ai) = config.ai.as_mut() {
+        secrets.push(("ai.api_key".to_string(), &mut ai.api_key));
+    }
+    secrets
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProviderConfig {
-    pub api_key: Option<String>,
-    pub api_url: Option<String>,
-    pub model: Option<String>,
-    pub max_tokens: Option<u32>,
-    pub temperature: Option<f32>,
-    pub timeout_seconds: Option<u64>,
-    pub enable_streaming: Option<bool>,
-    pub auto_execute_tools: Option<bool>,
-}
-
-impl Default for ProviderConfig {
-    fn default() -> Self {
-        Self {
-            api_key: None,
-            api_url: None,
-            model: None,
-            max_tokens: Some(4096),
-            temperature: Some(0.7),
-            timeout_seconds: Some(30),
-            enable_streaming: Some(true),
-            auto_execute_tools: Some(true),
-        }
+/// A secret shown only by its last four characters, e.g. "••••3f9a"
+fn mask_key(key: &str) -> String {
+    if key.is_empty() {
+        return String::new();
     }
+    let chars: Vec<char> = key.chars().collect();
+    if chars.len() <= 8 {
+        return MASK.to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}{}", MASK, tail)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UiConfig {
-    pub theme: String,
-    pub font_size: u16,
-    pub show_timestamps: bool,
-    pub auto_scroll: bool,
-    pub enable_notifications: bool,
-    pub vibrate_on_tool: bool,
+fn is_masked(key: &str) -> bool {
+    key.starts_with(MASK)
 }
 
-impl Default for UiConfig {
-    fn default() -> Self {
-        Self {
-            theme: "light".to_string(),
-            font_size: 14,
-            show_timestamps: true,
-            auto_scroll: true,
-            enable_notifications: true,
-            vibrate_on_tool: false,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arula_core::utils::config::{McpServerConfig, ProfileConfig};
+    use arula_core::utils::sync::{SyncBackendKind, SyncConfig};
+
+    fn config_with_secrets() -> Config {
+        let mut config = Config::new_for_test("openai", "gpt-4o", "", "sk-provider-0001");
+        config.profiles.insert(
+            "work".to_string(),
+            ProfileConfig {
+                api_key: Some("sk-profile-0002".to_string()),
+                ..Default::default()
+            },
+        );
+        config.mcp_servers.insert(
+            "search".to_string(),
+            McpServerConfig {
+                url: "https://mcp.example.com".to_string(),
+                headers: HashMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer mcp-token-0003".to_string(),
+                )]),
+                timeout: None,
+                retries: None,
+            },
+        );
+        config.sync = Some(SyncConfig {
+            backend: SyncBackendKind::Webdav,
+            url: Some("https://dav.example.com/arula".to_string()),
+            path: None,
+            username: Some("me".to_string()),
+            password: Some("webdav-pass-0004".to_string()),
+            bucket: None,
+            region: None,
+            prefix: None,
+            access_key: None,
+            secret_key: Some("s3-secret-key-0005".to_string()),
+            branch: None,
+            conflict: None,
+            sessions: None,
+        });
+        config
+    }
+
+    #[test]
+    fn test_masked_hides_every_secret() {
+        let config = config_with_secrets();
+        let json = serde_json::to_string(&masked(&config)).unwrap();
+        for secret in [
+            "sk-provider-0001",
+            "sk-profile-0002",
+            "mcp-token-0003",
+            "webdav-pass-0004",
+            "s3-secret-key-0005",
+        ] {
+            assert!(!json.contains(secret), "{} is not masked", secret);
         }
+        assert!(json.contains("••••0001"));
+        assert!(json.contains("••••0003"));
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SystemConfig {
-    pub log_level: String,
-    pub max_history: usize,
-    pub auto_save: bool,
-    pub export_format: String,
-    pub enable_termux_api: bool,
-}
+    #[test]
+    fn test_masked_round_trip_keeps_secrets() {
+        let config = config_with_secrets();
+        let mut sent: Value = serde_json::to_value(masked(&config)).unwrap();
+        sent["providers"]["openai"]["model"] = "gpt-4.1".into();
+
+        let merged = merge_settings(&config, &sent.to_string()).unwrap();
+        assert_eq!(merged.providers["openai"].model, "gpt-4.1");
+        assert_eq!(merged.providers["openai"].api_key, "sk-provider-0001");
+        assert_eq!(
+            merged.profiles["work"].api_key.as_deref(),
+            Some("sk-profile-0002")
+        );
+        assert_eq!(
+            merged.mcp_servers["search"].headers["Authorization"],
+            "Bearer mcp-token-0003"
+        );
+        let sync = merged.sync.unwrap();
+        assert_eq!(sync.password.as_deref(), Some("webdav-pass-0004"));
+        assert_eq!(sync.secret_key.as_deref(), Some("s3-secret-key-0005"));
+    }
 
-impl Default for SystemConfig {
-    fn default() -> Self {
-        Self {
-            log_level: "info".to_string(),
-            max_history: 1000,
-            auto_save: true,
-            export_format: "json".to_string(),
-            enable_termux_api: true,
-        }
+    #[test]
+    fn test_new_secrets_replace_old_ones() {
+        let config = config_with_secrets();
+        let mut sent: Value = serde_json::to_value(masked(&config)).unwrap();
+        sent["sync"]["password"] = "new-pass".into();
+
+        let merged = merge_settings(&config, &sent.to_string()).unwrap();
+        assert_eq!(merged.sync.unwrap().password.as_deref(), Some("new-pass"));
     }
-}
\ No newline at end of file
+}
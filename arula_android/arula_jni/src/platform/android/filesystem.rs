@@ -1,37 +1,156 @@
 //! Android filesystem implementation using scoped storage
+//!
+//! Everything the agent touches stays inside the app's sandbox: its
+//! private files directory (passed as `files_dir` to `initialize`), plus
+//! any folder the user granted through the storage access framework.
+//! Paths are resolved against the sandbox root, and any that lead outside
+//! it, through `..` or a symlink, are refused.
 
 use crate::platform::android::AndroidContext;
-use anyhow::Result;
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use anyhow::{bail, Result};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 use tokio::fs;
 
+static SANDBOX: LazyLock<RwLock<Sandbox>> =
+    LazyLock::new(|| RwLock::new(Sandbox::new(AndroidFileSystem::app_storage_path())));
+
+/// The folders file tools and commands may use
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    root: PathBuf,
+    granted: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            granted: Vec::new(),
+        }
+    }
+
+    /// The sandbox the app currently uses
+    pub fn current() -> Sandbox {
+        SANDBOX.read().unwrap().clone()
+    }
+
+    /// Make `dir` (the app's files directory) the sandbox root
+    pub fn set_root(dir: &Path) {
+        SANDBOX.write().unwrap().root = dir.to_path_buf();
+    }
+
+    /// Also allow `dir`, a folder the user granted access to
+    pub fn grant(dir: &Path) {
+        let mut sandbox = SANDBOX.write().unwrap();
+        if !sandbox.granted.iter().any(|d| d == dir) {
+            sandbox.granted.push(dir.to_path_buf());
+        }
+    }
+
+    /// Where relative paths start, and the home of commands
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` as an absolute path inside the sandbox; relative paths start
+    /// at `base` (the root when `None`)
+    pub fn resolve_from(&self, base: Option<&Path>, path: &str) -> Result<PathBuf> {
+        let path = Path::new(path.trim());
+        let joined = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            base.unwrap_or(&self.root).join(path)
+        };
+        let resolved = normalize(&joined);
+
+        let allowed = self.roots().any(|root| resolved.starts_with(normalize(root)));
+        if !allowed {
+            bail!("{} is outside the app's storage", path.display());
+        }
+        // A symlink inside the sandbox may still point out of it
+        let inside = real_path(&resolved, MAX_SYMLINKS).is_some_and(|real| {
+            self.roots().any(|root| match root.canonicalize() {
+                Ok(root) => real.starts_with(root),
+                Err(_) => real.starts_with(normalize(root)),
+            })
+        });
+        if !inside {
+            bail!("{} leads outside the app's storage", path.display());
+        }
+        Ok(resolved)
+    }
+
+    /// `path` as an absolute path inside the sandbox
+    pub fn resolve(&self, path: &str) -> Result<PathBuf> {
+        self.resolve_from(None, path)
+    }
+
+    fn roots(&self) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&self.root).chain(self.granted.iter())
+    }
+}
+
+/// `path` with `.` and `..` worked out, without touching the disk
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::CurDir => {}
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Symlinks followed before a path is taken to loop
+const MAX_SYMLINKS: usize = 40;
+
+/// Where `path` really leads: its nearest existing ancestor resolved, with
+/// dangling symlinks followed too since writing through one creates its
+/// target; `None` for a symlink loop
+fn real_path(path: &Path, links_left: usize) -> Option<PathBuf> {
+    for ancestor in path.ancestors() {
+        let rest = path.strip_prefix(ancestor).ok()?;
+        if let Ok(real) = ancestor.canonicalize() {
+            return Some(real.join(rest));
+        }
+        if let Ok(target) = std::fs::read_link(ancestor) {
+            let links_left = links_left.checked_sub(1)?;
+            let parent = ancestor.parent().unwrap_or(Path::new("/"));
+            return real_path(&normalize(&parent.join(target).join(rest)), links_left);
+        }
+    }
+    None
+}
+
 /// Android filesystem backend with scoped storage support
 pub struct AndroidFileSystem {
     ctx: AndroidContext,
-    base_path: Arc<Mutex<PathBuf>>,
 }
 
 impl AndroidFileSystem {
     pub fn new(ctx: AndroidContext) -> Self {
-        Self {
-            ctx,
-            base_path: Arc::new(Mutex::new(PathBuf::from("/storage/emulated/0"))),
-        }
+        Self { ctx }
     }
 
     /// Set the base storage directory
     pub async fn set_base_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let mut base_path = self.base_path.lock().await;
-        *base_path = path.as_ref().to_path_buf();
+        Sandbox::set_root(path.as_ref());
         Ok(())
     }
 
+    /// `path` inside the sandbox
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf> {
+        Sandbox::current().resolve(&path.as_ref().to_string_lossy())
+    }
+
     /// Read file content
     pub async fn read_file<P: AsRef<Path>>(&self, path: P) -> Result<String> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
 
         let content = fs::read_to_string(&full_path).await
             .map_err(|e| anyhow::anyhow!("Failed to read file {:?}: {}", full_path, e))?;
@@ -41,8 +160,7 @@ impl AndroidFileSystem {
 
     /// Write file content
     pub async fn write_file<P: AsRef<Path>>(&self, path: P, content: &str) -> Result<()> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
 
         // Create parent directories if they don't exist
         if let Some(parent) = full_path.parent() {
@@ -58,8 +176,7 @@ impl AndroidFileSystem {
 
     /// List directory contents
     pub async fn list_dir<P: AsRef<Path>>(&self, path: P) -> Result<Vec<String>> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
 
         let mut entries = Vec::new();
         let mut dir = fs::read_dir(&full_path).await
@@ -78,15 +195,15 @@ impl AndroidFileSystem {
 
     /// Check if path exists
     pub async fn exists<P: AsRef<Path>>(&self, path: P) -> bool {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
-        tokio::fs::metadata(full_path).await.is_ok()
+        match self.resolve(path) {
+            Ok(full_path) => tokio::fs::metadata(full_path).await.is_ok(),
+            Err(_) => false,
+        }
     }
 
     /// Get file metadata
     pub async fn metadata<P: AsRef<Path>>(&self, path: P) -> Result<FileMetadata> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
 
         let meta = fs::metadata(&full_path).await
             .map_err(|e| anyhow::anyhow!("Failed to get metadata for {:?}: {}", full_path, e))?;
@@ -104,8 +221,7 @@ impl AndroidFileSystem {
 
     /// Create directory
     pub async fn create_dir<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
 
         fs::create_dir_all(&full_path).await
             .map_err(|e| anyhow::anyhow!("Failed to create directory {:?}: {}", full_path, e))?;
@@ -115,8 +231,10 @@ impl AndroidFileSystem {
 
     /// Delete file or directory
     pub async fn delete<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let base_path = self.base_path.lock().await;
-        let full_path = base_path.join(path);
+        let full_path = self.resolve(path)?;
+        if Sandbox::current().roots().any(|root| normalize(root) == full_path) {
+            bail!("Refusing to delete the app's storage root");
+        }
 
         let meta = fs::metadata(&full_path).await;
 
@@ -133,9 +251,9 @@ impl AndroidFileSystem {
         Ok(())
     }
 
-    /// Get Android app-specific storage path
+    /// Get Android app-specific storage path (the default sandbox root,
+    /// until `initialize` passes the real one)
     pub fn app_storage_path() -> PathBuf {
-        // In a real implementation, this would get from Context
         PathBuf::from("/data/data/com.arula.terminal/files")
     }
 
@@ -161,4 +279,87 @@ pub struct FileMetadata {
     pub is_file: bool,
     pub size: u64,
     pub modified: Option<u64>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn sandbox() -> (TempDir, Sandbox) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("files");
+        std::fs::create_dir_all(root.join("notes")).unwrap();
+        (dir, Sandbox::new(root))
+    }
+
+    #[test]
+    fn test_relative_paths_stay_inside() {
+        let (_dir, sandbox) = sandbox();
+        let root = sandbox.root().to_path_buf();
+        assert_eq!(sandbox.resolve("notes/a.txt").unwrap(), root.join("notes/a.txt"));
+        assert_eq!(sandbox.resolve("notes/../b.txt").unwrap(), root.join("b.txt"));
+        assert_eq!(
+            sandbox.resolve_from(Some(&root.join("notes")), "./c.txt").unwrap(),
+            root.join("notes/c.txt")
+        );
+    }
+
+    #[test]
+    fn test_parent_dir_escapes_are_refused() {
+        let (_dir, sandbox) = sandbox();
+        assert!(sandbox.resolve("../outside.txt").is_err());
+        assert!(sandbox.resolve("notes/../../outside.txt").is_err());
+        assert!(sandbox.resolve("notes/../../files/../outside.txt").is_err());
+        let notes = sandbox.root().join("notes");
+        assert!(sandbox.resolve_from(Some(&notes), "../../outside.txt").is_err());
+    }
+
+    #[test]
+    fn test_absolute_paths_outside_root_are_refused() {
+        let (dir, sandbox) = sandbox();
+        assert!(sandbox.resolve("/etc/passwd").is_err());
+        assert!(sandbox.resolve(&dir.path().join("outside.txt").to_string_lossy()).is_err());
+        let inside = sandbox.root().join("notes/a.txt");
+        assert_eq!(sandbox.resolve(&inside.to_string_lossy()).unwrap(), inside);
+    }
+
+    #[test]
+    fn test_granted_folders_are_allowed() {
+        let (dir, mut sandbox) = sandbox();
+        let granted = dir.path().join("Documents");
+        std::fs::create_dir_all(&granted).unwrap();
+        let file = granted.join("report.md");
+        assert!(sandbox.resolve(&file.to_string_lossy()).is_err());
+        sandbox.granted.push(granted);
+        assert_eq!(sandbox.resolve(&file.to_string_lossy()).unwrap(), file);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_out_of_the_sandbox_are_refused() {
+        use std::os::unix::fs::symlink;
+        let (dir, sandbox) = sandbox();
+        let root = sandbox.root().to_path_buf();
+        let outside = dir.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+
+        symlink(&outside, root.join("escape")).unwrap();
+        assert!(sandbox.resolve("escape").is_err());
+        assert!(sandbox.resolve("escape/new.txt").is_err());
+
+        // Writing through a dangling link would create its target
+        symlink(outside.join("missing.txt"), root.join("dangling")).unwrap();
+        assert!(sandbox.resolve("dangling").is_err());
+        symlink("../outside/later.txt", root.join("relative")).unwrap();
+        assert!(sandbox.resolve("relative").is_err());
+
+        symlink(root.join("loop"), root.join("loop")).unwrap();
+        assert!(sandbox.resolve("loop").is_err());
+
+        symlink(root.join("notes"), root.join("shortcut")).unwrap();
+        assert!(sandbox.resolve("shortcut/a.txt").is_ok());
+        symlink(root.join("notes/todo.txt"), root.join("todo")).unwrap();
+        assert!(sandbox.resolve("todo").is_ok());
+    }
+}
@@ -1,28 +1,33 @@
 //! Android-specific platform implementations
 
 use anyhow::Result;
-use jni::{JNIEnv, objects::{JClass, JString, JObject}, sys::jobject};
+use jni::{JNIEnv, objects::{GlobalRef, JClass, JString, JObject}};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+pub mod agent;
 pub mod terminal;
 pub mod filesystem;
 pub mod command;
 pub mod config;
 pub mod notification;
+pub mod tools;
+pub mod callbacks;
 
 pub use terminal::AndroidTerminal;
 pub use filesystem::AndroidFileSystem;
 pub use command::AndroidCommandExecutor;
 pub use config::AndroidConfig;
 pub use notification::AndroidNotification;
+pub use tools::create_android_tool_registry;
 
 /// Android platform context
 #[derive(Clone)]
 pub struct AndroidContext {
     // Note: JVM is obtained from the JNI call, not stored
-    pub context: Arc<Mutex<Option<jobject>>>,
-    pub callback: Arc<Mutex<Option<jobject>>>,
+    pub context: Arc<Mutex<Option<GlobalRef>>>,
+    pub callback: Arc<Mutex<Option<GlobalRef>>>,
 }
 
 impl AndroidContext {
@@ -33,11 +38,11 @@ impl AndroidContext {
         }
     }
 
-    pub async fn set_context(&self, ctx: jobject) {
+    pub async fn set_context(&self, ctx: GlobalRef) {
         *self.context.lock().await = Some(ctx);
     }
 
-    pub async fn set_callback(&self, cb: jobject) {
+    pub async fn set_callback(&self, cb: GlobalRef) {
         *self.callback.lock().await = Some(cb);
     }
 }
@@ -65,7 +70,7 @@ impl AndroidPlatform {
             terminal: AndroidTerminal::new(ctx.clone()),
             filesystem: AndroidFileSystem::new(ctx.clone()),
             command: AndroidCommandExecutor::new(ctx.clone()),
-            config: AndroidConfig::new(ctx.clone()),
+            config: AndroidConfig::shared().clone(),
             notification: AndroidNotification::new(ctx),
         }
     }
@@ -89,13 +94,62 @@ impl AndroidPlatform {
     pub fn notification(&self) -> &AndroidNotification {
         &self.notification
     }
+
+    /// Why the tool called `name` can't run on Android, if it can't
+    pub fn unsupported_reason(name: &str) -> Option<&'static str> {
+        match name {
+            "git_commit" => Some("git isn't available in the app's sandbox"),
+            "run_tests" => Some("test toolchains aren't available in the app's sandbox"),
+            "visioneer" | "visioneer_macro" | "run_macro" => {
+                Some("apps can't capture or control the screen of other apps")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl arula_core::platform::PlatformBackend for AndroidPlatform {
+    fn name(&self) -> &str {
+        "android"
+    }
+
+    fn terminal_size(&self) -> Option<(u16, u16)> {
+        self.terminal.get_dimensions().ok()
+    }
+
+    fn working_dir(&self) -> PathBuf {
+        filesystem::Sandbox::current().root().to_path_buf()
+    }
+
+    fn resolve_path(&self, path: &Path) -> Result<PathBuf> {
+        self.filesystem.resolve(path)
+    }
+
+    fn shell_command(&self, script: &str) -> std::process::Command {
+        self.command.shell_command(script)
+    }
+
+    fn config_path(&self) -> PathBuf {
+        self.config
+            .path()
+            .unwrap_or_else(|| self.working_dir().join(config::CONFIG_FILE))
+    }
+
+    fn notify(&self, title: &str, message: &str) -> Result<()> {
+        callbacks::on_message(&format!("Notification: {} - {}", title, message));
+        Ok(())
+    }
+
+    fn unsupported_tool(&self, name: &str) -> Option<String> {
+        Self::unsupported_reason(name).map(str::to_string)
+    }
 }
 
 /// JNI exports for Android integration
 #[no_mangle]
 pub extern "C" fn Java_com_arula_terminal_ArulaNative_initialize<'local>(
     mut env: JNIEnv<'local>,
-    _class: JClass<'local>,
+    class: JClass<'local>,
     config_json: JString<'local>,
 ) -> bool {
     let config_str: String = match env.get_string(&config_json) {
@@ -114,7 +168,28 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_initialize<'local>(
             .with_tag("ArulaCore"),
     );
 
-    log::info!("Arula Android Core initialized with config: {}", config_str);
+    if let Err(e) = callbacks::attach(&mut env, &class) {
+        log::error!("Failed to set up callbacks to Java: {}", e);
+    }
+    // Tools reach the shell, files and notifications through the app
+    arula_core::platform::set(Arc::new(AndroidPlatform::new(AndroidContext::new())));
+
+    // Settings live in the app's private files directory, which is also
+    // the sandbox the agent's tools work in
+    let init: serde_json::Value = serde_json::from_str(&config_str).unwrap_or_default();
+    if let Some(dir) = init["files_dir"].as_str() {
+        filesystem::Sandbox::set_root(std::path::Path::new(dir));
+        std::env::set_var("HOME", dir);
+        if let Err(e) = std::env::set_current_dir(dir) {
+            log::warn!("Failed to enter {}: {}", dir, e);
+        }
+        if let Err(e) = AndroidConfig::shared().set_storage_dir(std::path::Path::new(dir)) {
+            log::error!("Failed to load configuration: {:#}", e);
+            callbacks::on_error(&format!("Failed to load configuration: {:#}", e));
+        }
+    }
+
+    log::info!("Arula Android Core initialized");
     true
 }
 
@@ -129,6 +204,7 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_sendMessage<'local>(
         Ok(msg) => {
             let msg_str: String = msg.into();
             log::info!("Sending message: {}", msg_str);
+            agent::send(msg_str);
         }
         Err(e) => {
             log::error!("Failed to get message string: {:?}", e);
@@ -138,11 +214,21 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_sendMessage<'local>(
 
 #[no_mangle]
 pub extern "C" fn Java_com_arula_terminal_ArulaNative_setConfig<'local>(
-    _env: JNIEnv<'local>,
+    mut env: JNIEnv<'local>,
     _class: JClass<'local>,
-    _config_json: JString<'local>,
+    config_json: JString<'local>,
 ) {
-    // Update configuration
+    let config_str: String = match env.get_string(&config_json) {
+        Ok(s) => s.into(),
+        Err(e) => {
+            log::error!("Failed to get config string: {:?}", e);
+            return;
+        }
+    };
+    if let Err(e) = AndroidConfig::shared().apply_json(&config_str) {
+        log::error!("Failed to apply configuration: {:#}", e);
+        callbacks::on_error(&format!("Failed to apply configuration: {:#}", e));
+    }
 }
 
 #[no_mangle]
@@ -150,8 +236,11 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_getConfig<'local>(
     mut env: JNIEnv<'local>,
     _class: JClass<'local>,
 ) -> JString<'local> {
-    // Return current configuration
-    let config = "{}";
+    // The effective configuration, with API keys masked
+    let config = AndroidConfig::shared().masked_json().unwrap_or_else(|e| {
+        log::error!("Failed to serialize configuration: {:#}", e);
+        "{}".to_string()
+    });
     match env.new_string(config) {
         Ok(s) => s,
         Err(_) => JString::default(),
@@ -164,9 +253,21 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_cleanup<'local>(
     _class: JClass<'local>,
 ) {
     // Cleanup resources
+    callbacks::flush_stream();
+    agent::clear_history();
     log::info!("Android Arula cleanup");
 }
 
+#[no_mangle]
+pub extern "C" fn Java_com_arula_terminal_ArulaNative_cancelStream<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+) {
+    // Stop the response being streamed; Java gets no more chunks of it
+    callbacks::cancel_stream();
+    log::info!("Stream cancelled");
+}
+
 #[no_mangle]
 pub extern "C" fn Java_com_arula_terminal_ArulaNative_setCallback<'local>(
     _env: JNIEnv<'local>,
@@ -176,31 +277,3 @@ pub extern "C" fn Java_com_arula_terminal_ArulaNative_setCallback<'local>(
     // Store callback for later use
     log::info!("Setting Android callback");
 }
-
-/// Callback functions from Rust to Java
-pub mod callbacks {
-    pub fn on_message(message: &str) {
-        // Call Java callback
-        log::info!("Message: {}", message);
-    }
-
-    pub fn on_stream_chunk(chunk: &str) {
-        // Call Java callback for streaming
-        log::debug!("Stream: {}", chunk);
-    }
-
-    pub fn on_tool_start(tool_name: &str, tool_id: &str) {
-        // Notify Java of tool execution
-        log::info!("Tool started: {} ({})", tool_name, tool_id);
-    }
-
-    pub fn on_tool_complete(tool_id: &str, result: &str) {
-        // Notify Java of tool completion
-        log::info!("Tool completed: {} - {}", tool_id, result);
-    }
-
-    pub fn on_error(error: &str) {
-        // Notify Java of error
-        log::error!("Error: {}", error);
-    }
-}
\ No newline at end of file
@@ -0,0 +1,213 @@
+//! The agent's tools on Android
+//!
+//! File tools are the built-in ones, with their paths checked against the
+//! app's sandbox (see `filesystem::Sandbox`). `execute_bash` runs through
+//! `AndroidCommandExecutor`. Tools that can't work on a phone stay in the
+//! list so the model knows about them, but answer with a structured
+//! `not_available_on_platform` error giving
+//! `AndroidPlatform::unsupported_reason`.
+
+use crate::platform::android::filesystem::Sandbox;
+use crate::platform::android::{AndroidCommandExecutor, AndroidPlatform};
+use arula_core::api::agent::{Tool, ToolRegistry, ToolSchema, ToolSchemaBuilder};
+use arula_core::tools::builtin::{
+    BashParams, FileEditTool, FileReadTool, FindFilesTool, FindSymbolTool, FindTodosTool,
+    GitCommitTool, ListDirectoryTool, RecallTool, RememberTool, RunTestsTool, SearchTool,
+    TaskCompleteTool, WebSearchTool, WriteFileTool,
+};
+use arula_core::tools::visioneer::VisioneerTool;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default and longest command run time, in seconds
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 300;
+
+/// The tools the agent gets on Android
+pub fn create_android_tool_registry(executor: Arc<AndroidCommandExecutor>) -> ToolRegistry {
+    let mut registry = ToolRegistry::new();
+
+    registry.register(Sandboxed::new(FileReadTool::new()));
+    registry.register(Sandboxed::new(FileEditTool::new()));
+    registry.register(Sandboxed::new(WriteFileTool::new()));
+    registry.register(Sandboxed::new(ListDirectoryTool::new()));
+    registry.register(Sandboxed::new(FindFilesTool::new()));
+    registry.register(Sandboxed::new(SearchTool::new()));
+    registry.register(Sandboxed::new(FindTodosTool::new()));
+    registry.register(Sandboxed::new(FindSymbolTool::new()));
+    registry.register(AndroidBashTool { executor });
+    registry.register(WebSearchTool::new());
+    registry.register(RememberTool::new());
+    registry.register(RecallTool::new());
+    registry.register(TaskCompleteTool::new());
+
+    registry.register(Unavailable::new(&GitCommitTool::new()));
+    registry.register(Unavailable::new(&RunTestsTool::new()));
+    registry.register(Unavailable::new(&VisioneerTool::new()));
+
+    registry
+}
+
+/// A file tool whose `path` must lie in the sandbox; relative paths start
+/// at the sandbox root, and a missing path means the root
+struct Sandboxed<T> {
+    inner: T,
+}
+
+impl<T: Tool> Sandboxed<T> {
+    fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<T: Tool> Tool for Sandboxed<T> {
+    type Params = Value;
+    type Result = Value;
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn schema(&self) -> ToolSchema {
+        self.inner.schema()
+    }
+
+    fn read_only(&self) -> bool {
+        self.inner.read_only()
+    }
+
+    fn budgeted(&self) -> bool {
+        self.inner.budgeted()
+    }
+
+    fn cache_key(&self, params: &Value) -> Option<String> {
+        self.inner.cache_key(params)
+    }
+
+    async fn execute(&self, mut params: Value) -> Result<Value, String> {
+        let path = params["path"].as_str().unwrap_or(".").to_string();
+        let resolved = Sandbox::current()
+            .resolve(&path)
+            .map_err(|e| e.to_string())?;
+        if let Some(params) = params.as_object_mut() {
+            params.insert("path".into(), json!(resolved.to_string_lossy()));
+        }
+
+        let params = serde_json::from_value(params)
+            .map_err(|e| format!("Invalid parameters for {}: {}", self.inner.name(), e))?;
+        let result = self.inner.execute(params).await?;
+        serde_json::to_value(result).map_err(|e| e.to_string())
+    }
+}
+
+/// `execute_bash` in the app's shell
+struct AndroidBashTool {
+    executor: Arc<AndroidCommandExecutor>,
+}
+
+#[async_trait]
+impl Tool for AndroidBashTool {
+    type Params = BashParams;
+    type Result = Value;
+
+    fn name(&self) -> &str {
+        "execute_bash"
+    }
+
+    fn description(&self) -> &str {
+        "Execute shell commands in the app's storage and return the output"
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new(
+            "execute_bash",
+            "Execute shell commands and return the output. Commands run in the app's \
+             storage on Android, with a limited set of programs",
+        )
+        .param("command", "string")
+        .description("command", "The shell command to execute")
+        .required("command")
+        .param("timeout_seconds", "integer")
+        .description("timeout_seconds", "Timeout in seconds for the command (default: 30, max: 300).")
+        .build()
+    }
+
+    async fn execute(&self, params: BashParams) -> Result<Value, String> {
+        let seconds = params
+            .timeout_seconds
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .min(MAX_TIMEOUT_SECS);
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            Duration::from_secs(seconds),
+            self.executor.execute_sync(&params.command, &[]),
+        )
+        .await
+        .map_err(|_| format!("Command timed out after {} seconds", seconds))?
+        .map_err(|e| e.to_string())?;
+
+        Ok(json!({
+            "stdout": result.stdout,
+            "stderr": result.stderr,
+            "exit_code": result.exit_code,
+            "success": result.success,
+            "duration_ms": started.elapsed().as_millis() as u64,
+        }))
+    }
+}
+
+/// A tool that exists elsewhere but not on Android
+struct Unavailable {
+    schema: ToolSchema,
+    reason: &'static str,
+}
+
+impl Unavailable {
+    fn new<T: Tool>(tool: &T) -> Self {
+        let reason = AndroidPlatform::unsupported_reason(tool.name())
+            .unwrap_or("it isn't supported on this platform");
+        let mut schema = tool.schema();
+        schema.description = format!("{} (Not available on Android: {}.)", schema.description, reason);
+        Self { schema, reason }
+    }
+}
+
+#[async_trait]
+impl Tool for Unavailable {
+    type Params = Value;
+    type Result = Value;
+
+    fn name(&self) -> &str {
+        &self.schema.name
+    }
+
+    fn description(&self) -> &str {
+        &self.schema.description
+    }
+
+    fn schema(&self) -> ToolSchema {
+        self.schema.clone()
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, _params: Value) -> Result<Value, String> {
+        // Tool errors reach the model as text, so the structure is JSON
+        Err(json!({
+            "error": "not_available_on_platform",
+            "tool": self.schema.name,
+            "platform": "android",
+            "reason": self.reason,
+        })
+        .to_string())
+    }
+}
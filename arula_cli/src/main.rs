@@ -3,7 +3,7 @@
 #![allow(private_interfaces)]
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(name = "arula")]
@@ -20,22 +20,184 @@ struct Cli {
     /// Enable debug mode
     #[arg(short, long)]
     debug: bool,
+
+    /// Configuration profile to use (see `profiles` in config.json)
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Model or alias to use (see `model_aliases` in config.json)
+    #[arg(long)]
+    model: Option<String>,
+
+    /// Icon glyphs to use (default: `icons` in config.json, or detected)
+    #[arg(long, value_parser = ["emoji", "nerd", "unicode", "ascii"])]
+    icons: Option<String>,
+
+    /// Screen-reader friendly output: ASCII icons, high-contrast colors,
+    /// no emoji or box drawing (default: `plain` in config.json)
+    #[arg(long)]
+    plain: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Manage the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Find and install community packs (prompts, scripts, WASM tools)
+    Pack {
+        /// Pack index URL (default: `packs.index_url` in config.json)
+        #[arg(long, global = true)]
+        index: Option<String>,
+
+        #[command(subcommand)]
+        action: PackAction,
+    },
+    /// Sync config, prompts and sessions with the backend in `sync`
+    Sync {
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Conflict strategy for this run: newest, local, remote or keep-both
+        #[arg(long)]
+        conflict: Option<String>,
+    },
+    /// Save config, prompts, memory and sessions to an encrypted archive
+    ExportProfile {
+        /// Archive to create
+        output: PathBuf,
+        /// Include API keys in the archive
+        #[arg(long)]
+        include_keys: bool,
+    },
+    /// Restore a profile archive made with export-profile
+    ImportProfile {
+        archive: PathBuf,
+        /// Replace existing files that differ from the archive
+        #[arg(long)]
+        overwrite: bool,
+    },
+    /// Summarize recent sessions, commits, commands and costs as Markdown
+    Digest {
+        /// Period to cover, e.g. 7d, 2w or 12h
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// Write the digest to this file instead of printing it
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Check the config, providers, optional programs and terminal, and
+    /// suggest fixes
+    Doctor,
+    /// Show release notes, by default everything since the version that
+    /// ran before the last update
+    Changelog {
+        /// Show changes in versions newer than this one
+        #[arg(long)]
+        since: Option<String>,
+        /// Only show these categories: feat, fix, perf, other
+        #[arg(long, value_delimiter = ',')]
+        category: Vec<String>,
+    },
+    /// Download and install the latest release, replacing this binary
+    Update {
+        /// Only report whether an update is available
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check config.json for errors without starting the app
+    Validate {
+        /// Config file to check (default: ~/.arula/config.json)
+        path: Option<PathBuf>,
+    },
 }
 
+#[derive(Subcommand)]
+enum PackAction {
+    /// Search the index by name, description or kind
+    Search {
+        /// Words to look for (default: list every pack)
+        query: Vec<String>,
+    },
+    /// Install a pack, optionally at a version (`name@1.2.0`)
+    Install {
+        spec: String,
+        /// Install even if it isn't signed by a trusted key
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// Update installed packs to their latest versions
+    Update {
+        /// Pack to update (default: all)
+        name: Option<String>,
+        /// Install even if it isn't signed by a trusted key
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+    /// List installed packs
+    List,
+    /// Remove an installed pack
+    Remove { name: String },
+}
+
+use arula_cli::ui::output::code_blocks::set_code_theme;
 use arula_cli::ui::output::OutputHandler;
 use arula_cli::ui::tui_app::TuiApp;
-use arula_core::utils::changelog::{Changelog, ChangelogType};
+use arula_core::storage::Storage;
+use arula_core::utils::changelog::{Category, Change, Changelog, ChangelogType};
+use arula_core::utils::config::Config;
+use arula_core::utils::digest::{parse_since, Digest};
+use arula_core::utils::doctor::{Report, Status};
+use arula_core::utils::config_validation::{validate_config_file, ConfigIssue, Severity};
+use arula_core::utils::icons::{set_icon_set, IconSet};
+use arula_core::utils::accessibility::{set_plain_mode, PLAIN_THEME};
+use arula_core::utils::themes::{set_active_theme, Theme};
+use arula_core::utils::manifest_watcher::ManifestWatcher;
+use arula_core::utils::packs::{parse_pack_spec, PackIndex, PackManager};
+use arula_core::utils::shutdown;
+use arula_core::utils::profile_archive::{ProfileArchive, ProfileLocations, PROFILE_PASSPHRASE_ENV};
+use arula_core::utils::sync::{ConflictStrategy, SyncAction, Syncer};
+use arula_core::utils::update::{self, UpdateCheck};
 use arula_core::{detect_project, is_ai_enhanced};
 use arula_core::App;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Print changelog from remote git or local file
-fn print_changelog() -> Result<()> {
-    // Fetch changelog (tries remote first, falls back to local)
-    let changelog = Changelog::fetch_from_remote().unwrap_or_else(|_| {
+/// Changelog from remote git or local file
+fn load_changelog() -> Changelog {
+    // Tries remote first, falls back to local
+    Changelog::fetch_from_remote().unwrap_or_else(|_| {
         Changelog::fetch_local()
             .unwrap_or_else(|_| Changelog::parse(&Changelog::default_changelog()))
-    });
+    })
+}
+
+/// Print changes grouped by category under a heading for each
+fn print_change_groups(groups: Vec<(Category, Vec<Change>)>, indent: &str) {
+    for (category, changes) in groups {
+        println!(
+            "{}{} {}",
+            indent,
+            category.emoji(),
+            console::style(category.label()).bold()
+        );
+        for change in changes {
+            println!("{}   {}", indent, change.display());
+        }
+    }
+}
+
+/// Print changelog from remote git or local file
+fn print_changelog() -> Result<()> {
+    let changelog = load_changelog();
 
     // Detect actual build type from git
     let build_type = Changelog::detect_build_type();
@@ -45,22 +207,30 @@ fn print_changelog() -> Result<()> {
         ChangelogType::Development => "⚙️  Development",
     };
 
+    // After an update, show everything since the version that ran before
+    let previous = Storage::with(|s| Changelog::record_run(s, env!("CARGO_PKG_VERSION")))
+        .ok()
+        .flatten();
+    let releases = match &previous {
+        Some(version) => changelog.releases_since(Some(version)),
+        None => changelog.releases.iter().filter(|r| r.is_unreleased()).collect(),
+    };
+
     // Print header
+    let since = previous.map(|v| format!(", since {}", v)).unwrap_or_default();
     println!(
         "{} {}",
         console::style("📋 What's New").cyan().bold(),
-        console::style(format!("({})", type_label)).dim()
+        console::style(format!("({}{})", type_label, since)).dim()
     );
 
     // Get recent changes (limit to 5)
-    let changes = changelog.get_recent_changes(5);
+    let groups = Changelog::group_by_category(releases, &[], 5);
 
-    if changes.is_empty() {
+    if groups.is_empty() {
         println!("{}", console::style("  • No recent changes").dim());
     } else {
-        for change in changes {
-            println!("  {}", change);
-        }
+        print_change_groups(groups, "  ");
     }
 
     Ok(())
@@ -201,10 +371,482 @@ fn print_conversation_starters() -> Result<()> {
     Ok(())
 }
 
+/// Print config problems, errors first
+fn print_config_issues(issues: &[ConfigIssue]) {
+    let (errors, warnings): (Vec<_>, Vec<_>) =
+        issues.iter().partition(|i| i.severity == Severity::Error);
+    for issue in errors {
+        eprintln!("  {} {}", console::style("✗").red().bold(), issue.describe());
+    }
+    for issue in warnings {
+        eprintln!("  {} {}", console::style("⚠").yellow(), issue.describe());
+    }
+}
+
+/// `arula config validate`: check a config file and exit non-zero on errors
+fn validate_config_command(path: Option<PathBuf>) -> Result<()> {
+    let path = path.unwrap_or_else(|| PathBuf::from(Config::get_config_path()));
+    let issues = validate_config_file(&path)?;
+    if issues.is_empty() {
+        println!(
+            "{} {}",
+            console::style("✓").green().bold(),
+            console::style(format!("{} is valid", path.display())).white()
+        );
+        return Ok(());
+    }
+
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    eprintln!(
+        "{} {}",
+        console::style(format!("{}:", path.display())).cyan().bold(),
+        console::style(format!(
+            "{} error(s), {} warning(s)",
+            errors,
+            issues.len() - errors
+        ))
+        .dim()
+    );
+    print_config_issues(&issues);
+    if errors > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// `arula pack ...`: search, install and update packs from the index
+async fn pack_command(index: Option<String>, action: PackAction) -> Result<()> {
+    let config = Config::load_or_default()?;
+    let index_url = index.unwrap_or_else(|| config.get_packs_index_url());
+    let mut manager = PackManager::open(config.get_trusted_pack_keys())?;
+
+    match action {
+        PackAction::Search { query } => {
+            let index = PackIndex::fetch(&index_url).await?;
+            let results = index.search(&query.join(" "));
+            if results.is_empty() {
+                println!("{}", console::style("No packs found").dim());
+            }
+            for pack in results {
+                let latest = pack.latest().map(|v| v.version.as_str()).unwrap_or("-");
+                let installed = manager
+                    .installed()
+                    .get(&pack.name)
+                    .map(|p| format!(" (installed {})", p.version))
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {}{}",
+                    console::style(&pack.name).cyan().bold(),
+                    console::style(latest).white(),
+                    console::style(format!("[{}]", pack.kind.as_str())).dim(),
+                    console::style(installed).green()
+                );
+                if !pack.description.is_empty() {
+                    println!("   {}", console::style(&pack.description).dim());
+                }
+            }
+        }
+        PackAction::Install {
+            spec,
+            allow_unsigned,
+        } => {
+            manager.allow_unsigned = allow_unsigned;
+            let (name, version) = parse_pack_spec(&spec);
+            let index = PackIndex::fetch(&index_url).await?;
+            let entry = index
+                .find(name)
+                .ok_or_else(|| anyhow::anyhow!("No pack named '{}' in {}", name, index_url))?;
+            let version = entry.version(version).ok_or_else(|| {
+                anyhow::anyhow!("{} has no version {}", name, version.unwrap_or("available"))
+            })?;
+            let installed = manager.install(&index_url, entry, version).await?;
+            print_installed(name, &installed.version, installed.signed_by.is_some());
+        }
+        PackAction::Update {
+            name,
+            allow_unsigned,
+        } => {
+            manager.allow_unsigned = allow_unsigned;
+            let index = PackIndex::fetch(&index_url).await?;
+            let updates: Vec<_> = manager
+                .updates(&index)
+                .into_iter()
+                .filter(|(pack, _, _)| name.as_ref().is_none_or(|n| n == pack))
+                .collect();
+            if updates.is_empty() {
+                println!("{}", console::style("All packs are up to date").dim());
+            }
+            for (pack, from, to) in updates {
+                let entry = index.find(&pack).expect("update comes from the index");
+                let version = entry.version(Some(&to)).expect("update version exists");
+                match manager.install(&index_url, entry, version).await {
+                    Ok(installed) => {
+                        let signed = installed.signed_by.is_some();
+                        print_installed(&pack, &format!("{} → {}", from, to), signed);
+                    }
+                    Err(e) => eprintln!("  {} {}: {:#}", console::style("✗").red().bold(), pack, e),
+                }
+            }
+        }
+        PackAction::List => {
+            if manager.installed().is_empty() {
+                println!("{}", console::style("No packs installed").dim());
+            }
+            for (name, pack) in manager.installed() {
+                println!(
+                    "{} {} {}{}",
+                    console::style(name).cyan().bold(),
+                    console::style(&pack.version).white(),
+                    console::style(format!("[{}]", pack.kind.as_str())).dim(),
+                    if pack.signed_by.is_some() { "" } else { " (unsigned)" }
+                );
+            }
+        }
+        PackAction::Remove { name } => {
+            if manager.uninstall(&name)? {
+                println!("{} Removed {}", console::style("✓").green().bold(), name);
+            } else {
+                println!("{}", console::style(format!("{} is not installed", name)).dim());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_installed(name: &str, version: &str, signed: bool) {
+    println!(
+        "{} Installed {} {}",
+        console::style("✓").green().bold(),
+        console::style(name).cyan().bold(),
+        console::style(version).white()
+    );
+    if !signed {
+        println!(
+            "  {} not signed by a trusted key",
+            console::style("⚠").yellow()
+        );
+    }
+}
+
+/// `arula sync`: two-way sync with the configured backend
+async fn sync_command(dry_run: bool, conflict: Option<String>) -> Result<()> {
+    let config = Config::load_or_default()?;
+    let sync = config.get_sync_config().ok_or_else(|| {
+        anyhow::anyhow!("Sync isn't set up; add a \"sync\" section to {}", Config::get_config_path())
+    })?;
+    let mut syncer = Syncer::from_config(&sync, &std::env::current_dir()?)?;
+    if let Some(conflict) = conflict {
+        let strategy = ConflictStrategy::parse(&conflict).ok_or_else(|| {
+            anyhow::anyhow!("Unknown conflict strategy '{}' (newest, local, remote, keep-both)", conflict)
+        })?;
+        syncer.set_strategy(strategy);
+    }
+
+    println!(
+        "{} {}",
+        console::style(if dry_run { "Checking" } else { "Syncing with" }).cyan().bold(),
+        console::style(syncer.backend().describe()).dim()
+    );
+    let report = syncer.sync(dry_run).await?;
+    for change in &report.changes {
+        let symbol = match change.action {
+            SyncAction::Push => console::style("↑").green(),
+            SyncAction::Pull => console::style("↓").cyan(),
+            SyncAction::DeleteRemote | SyncAction::DeleteLocal => console::style("✗").red(),
+            SyncAction::KeepBoth => console::style("⇅").yellow(),
+        };
+        let conflict = if change.conflict { " (conflict)" } else { "" };
+        println!(
+            "  {} {} {}",
+            symbol,
+            change.path,
+            console::style(format!("{}{}", change.action.as_str(), conflict)).dim()
+        );
+    }
+    println!(
+        "{} {} changed, {} unchanged, {} conflict(s){}",
+        console::style("✓").green().bold(),
+        report.changes.len(),
+        report.unchanged,
+        report.conflicts(),
+        if dry_run { " (dry run)" } else { "" }
+    );
+    Ok(())
+}
+
+/// Passphrase from `ARULA_PROFILE_PASSPHRASE`, or typed in without echo
+fn read_passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PROFILE_PASSPHRASE_ENV)
+        && !passphrase.is_empty()
+    {
+        return Ok(passphrase);
+    }
+    let passphrase = prompt_hidden("Passphrase: ")?;
+    if passphrase.is_empty() {
+        anyhow::bail!("The passphrase is empty");
+    }
+    if confirm && prompt_hidden("Repeat passphrase: ")? != passphrase {
+        anyhow::bail!("The passphrases don't match");
+    }
+    Ok(passphrase)
+}
+
+fn prompt_hidden(prompt: &str) -> Result<String> {
+    use crossterm::event::{read, Event, KeyCode, KeyEventKind, KeyModifiers};
+    use std::io::Write;
+
+    eprint!("{}", prompt);
+    std::io::stderr().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    let mut input = String::new();
+    let result = loop {
+        match read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err(anyhow::anyhow!("Cancelled"));
+                }
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    result.map(|_| input)
+}
+
+/// `arula export-profile`: write an encrypted archive of the profile
+fn export_profile_command(output: PathBuf, include_keys: bool) -> Result<()> {
+    let config = Config::load_or_default()?;
+    let config_json = serde_json::to_string_pretty(&config.exported(include_keys))?;
+    let locations = ProfileLocations::current(&std::env::current_dir()?)?;
+    let archive = ProfileArchive::collect(&locations, &config_json, include_keys)?;
+    archive.write(&output, &read_passphrase(true)?)?;
+
+    println!(
+        "{} Exported {} file(s) to {}",
+        console::style("✓").green().bold(),
+        archive.paths().count(),
+        console::style(output.display()).cyan()
+    );
+    if include_keys {
+        println!(
+            "  {} API keys are included; keep the archive and passphrase safe",
+            console::style("⚠").yellow()
+        );
+    }
+    Ok(())
+}
+
+/// `arula import-profile`: restore an archive made by export-profile
+fn import_profile_command(archive: PathBuf, overwrite: bool) -> Result<()> {
+    let profile = ProfileArchive::read(&archive, &read_passphrase(false)?)?;
+    let locations = ProfileLocations::current(&std::env::current_dir()?)?;
+    let report = profile.restore(&locations, overwrite)?;
+
+    // Loading moves imported API keys into the secret store
+    if report.written.iter().any(|p| p == "config.json") {
+        Config::load_or_default()?;
+    }
+    for path in &report.written {
+        println!("  {} {}", console::style("↓").cyan(), path);
+    }
+    for path in &report.skipped {
+        println!("  {} {} {}", console::style("-").dim(), path, console::style("(kept existing)").dim());
+    }
+    println!(
+        "{} Imported {} file(s) from {} ({} unchanged, {} skipped)",
+        console::style("✓").green().bold(),
+        report.written.len(),
+        profile.created_at.format("%Y-%m-%d %H:%M"),
+        report.unchanged,
+        report.skipped.len()
+    );
+    if !report.skipped.is_empty() && !overwrite {
+        println!(
+            "  {} run with --overwrite to replace existing files",
+            console::style("→").cyan()
+        );
+    }
+    Ok(())
+}
+
+/// `arula digest`: Markdown summary of the period from the local database
+fn digest_command(since: String, output: Option<PathBuf>) -> Result<()> {
+    let since = chrono::Utc::now() - parse_since(&since)?;
+    let markdown = Digest::collect(since)?.to_markdown();
+    match output {
+        Some(path) => {
+            std::fs::write(&path, markdown)?;
+            println!(
+                "{} Wrote the digest to {}",
+                console::style("✓").green().bold(),
+                console::style(path.display()).cyan()
+            );
+        }
+        None => print!("{}", markdown),
+    }
+    Ok(())
+}
+
+/// `arula doctor`: run the setup checks and print a report
+async fn doctor_command() -> Result<()> {
+    let report = Report::run(Path::new(&Config::get_config_path())).await;
+
+    let mut group = "";
+    for check in &report.checks {
+        if check.group != group {
+            group = check.group;
+            println!("{}", console::style(group).cyan().bold());
+        }
+        let mark = match check.status {
+            Status::Pass => console::style("✓").green().bold(),
+            Status::Warn => console::style("!").yellow().bold(),
+            Status::Fail => console::style("✗").red().bold(),
+        };
+        println!(
+            "  {} {} {}",
+            mark,
+            console::style(format!("{:<20}", check.name)).white(),
+            console::style(&check.detail).dim()
+        );
+        if let Some(fix) = &check.fix {
+            println!("      {} {}", console::style("→").cyan(), fix);
+        }
+    }
+
+    println!();
+    println!(
+        "{} passed, {} warning(s), {} failed",
+        report.count(Status::Pass),
+        report.count(Status::Warn),
+        report.count(Status::Fail)
+    );
+    if !report.healthy() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn changelog_command(since: Option<String>, category: Vec<String>) -> Result<()> {
+    let categories = category
+        .iter()
+        .map(|name| {
+            Category::parse(name).ok_or_else(|| {
+                anyhow::anyhow!("Unknown category '{}': use feat, fix, perf or other", name)
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let since = since.or_else(|| Storage::with(Changelog::previous_version).ok().flatten());
+
+    let changelog = load_changelog();
+    let mut shown = false;
+    for release in changelog.releases_since(since.as_deref()) {
+        let groups = Changelog::group_by_category([release], &categories, usize::MAX);
+        if groups.is_empty() {
+            continue;
+        }
+        let date = release.date.as_ref().map(|d| format!(" ({})", d)).unwrap_or_default();
+        println!(
+            "{}{}",
+            console::style(&release.version).cyan().bold(),
+            console::style(date).dim()
+        );
+        print_change_groups(groups, "  ");
+        println!();
+        shown = true;
+    }
+
+    if !shown {
+        match since {
+            Some(version) => println!("No changes since {}", version),
+            None => println!("No changes"),
+        }
+    }
+    Ok(())
+}
+
+/// `arula update`: compare with the latest release and, for release
+/// builds, install it
+async fn update_command(check_only: bool) -> Result<()> {
+    let config = Config::load_or_default()?;
+    let check = UpdateCheck::run(config.get_updates().repo()).await?;
+    let latest = check.release.version().to_string();
+
+    if !check.update_available() {
+        println!(
+            "{} ARULA {} is up to date",
+            console::style("✓").green().bold(),
+            check.current
+        );
+        return Ok(());
+    }
+    println!(
+        "{} {} → {}",
+        console::style("Update available:").cyan().bold(),
+        check.current,
+        console::style(&latest).green().bold()
+    );
+    if !check.release.html_url.is_empty() {
+        println!("  {}", console::style(&check.release.html_url).dim());
+    }
+    if check_only {
+        return Ok(());
+    }
+    if !check.can_self_update() {
+        println!(
+            "{}",
+            console::style(
+                "This is a custom or development build; pull and rebuild from source to update"
+            )
+            .yellow()
+        );
+        return Ok(());
+    }
+
+    println!("Downloading {}...", latest);
+    let path = update::install(&check.release).await?;
+    println!(
+        "{} Installed {} at {}",
+        console::style("✓").green().bold(),
+        latest,
+        path.display()
+    );
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    match cli.command {
+        Some(Command::Config {
+            action: ConfigAction::Validate { path },
+        }) => return validate_config_command(path),
+        Some(Command::Pack { index, action }) => return pack_command(index, action).await,
+        Some(Command::Sync { dry_run, conflict }) => return sync_command(dry_run, conflict).await,
+        Some(Command::ExportProfile {
+            output,
+            include_keys,
+        }) => return export_profile_command(output, include_keys),
+        Some(Command::ImportProfile { archive, overwrite }) => {
+            return import_profile_command(archive, overwrite);
+        }
+        Some(Command::Digest { since, output }) => return digest_command(since, output),
+        Some(Command::Doctor) => return doctor_command().await,
+        Some(Command::Changelog { since, category }) => {
+            return changelog_command(since, category);
+        }
+        Some(Command::Update { check }) => return update_command(check).await,
+        None => {}
+    }
+
     // Set debug environment variable if debug flag is enabled
     if cli.debug {
         unsafe {
@@ -212,14 +854,75 @@ async fn main() -> Result<()> {
         }
     }
 
-    // Initialize global logger
-    if let Err(e) = arula_core::utils::logger::init_global_logger() {
+    // Initialize global logger with the levels and rotation from the config
+    let startup_config = Config::load_or_default().ok();
+    let log_settings = startup_config
+        .as_ref()
+        .map(|config| config.get_logging().settings())
+        .unwrap_or_default();
+    if let Err(e) = arula_core::utils::logger::init_global_logger_with(log_settings) {
         eprintln!("⚠️ Failed to initialize logger: {}", e);
     }
 
+    // Request traces go to the OTLP collector until this is dropped at exit
+    let telemetry_config = startup_config
+        .map(|config| config.get_telemetry())
+        .unwrap_or_default();
+    let _telemetry = match arula_core::utils::telemetry::init(&telemetry_config) {
+        Ok(telemetry) => telemetry,
+        Err(e) => {
+            eprintln!("⚠️ Tracing disabled: {:#}", e);
+            None
+        }
+    };
+
     // Create app with debug flag
     let mut app = App::new()?.with_debug(cli.debug);
 
+    // Apply the selected profile before anything reads provider settings
+    let profile = cli
+        .profile
+        .or_else(|| std::env::var(Config::PROFILE_ENV).ok());
+    if let Some(profile) = profile.as_deref() {
+        app.config.use_profile(Some(profile))?;
+    }
+    if let Some(model) = cli.model.as_deref() {
+        app.config.use_model(model)?;
+    }
+    let plain = cli.plain || app.config.get_plain_mode();
+    set_plain_mode(plain);
+    let icons = cli
+        .icons
+        .as_deref()
+        .and_then(IconSet::from_name)
+        .unwrap_or_else(|| {
+            if plain {
+                IconSet::Ascii
+            } else {
+                app.config.get_icon_set()
+            }
+        });
+    set_icon_set(icons);
+    set_code_theme(app.config.get_appearance().code_theme.as_deref());
+    set_active_theme(if plain {
+        Theme::bundled(PLAIN_THEME).unwrap_or_default()
+    } else {
+        app.config.get_theme()
+    });
+    for error in &app.config.env_errors {
+        eprintln!("⚠️ Config: {}", error);
+    }
+    for warning in app.config.model_warnings() {
+        eprintln!("⚠️ Model: {}", warning);
+    }
+    if !app.config.validation_issues.is_empty() {
+        eprintln!(
+            "⚠️ Config problems in {} (check with `arula config validate`):",
+            Config::get_config_path()
+        );
+        print_config_issues(&app.config.validation_issues);
+    }
+
     // Initialize app components
     let _ = app.initialize_git_state().await;
     let _ = app.initialize_tool_registry().await;
@@ -230,11 +933,33 @@ async fn main() -> Result<()> {
     output.print_banner()?;
     println!();
     print_changelog()?;
+    if let Some(latest) = update::startup_notice(&app.config.get_updates()).await {
+        println!(
+            "{} {}",
+            console::style(format!("⬆️  ARULA {} is available.", latest)).green(),
+            console::style("Run `arula update` to install it.").dim()
+        );
+    }
     print_project_context()?;
     println!();
     print_conversation_starters()?;
     println!();
 
+    // Keep PROJECT.manifest in sync with build files while the TUI runs
+    let _manifest_watcher = if app.config.get_manifest_auto_refresh_enabled() {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        ManifestWatcher::start(&cwd)
+            .map_err(|e| eprintln!("⚠️ Manifest auto-refresh disabled: {}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    // Save and restore the terminal on SIGTERM/SIGHUP instead of dying mid-write
+    if let Err(e) = shutdown::listen() {
+        eprintln!("⚠️ Graceful shutdown disabled: {}", e);
+    }
+
     // Run TUI
     let mut tui = TuiApp::new(app)?;
     tui.run().await?;
@@ -0,0 +1,156 @@
+//! Getting the user's attention while the agent waits on them
+//!
+//! When a run stops for an answer (an `ask_question` call, a paused loop),
+//! the terminal bell rings once, the window title flashes until a key is
+//! pressed, and the terminal's progress indicator (OSC 9;4, shown in the tab
+//! or taskbar by Windows Terminal, ConEmu and others) turns to "paused" until
+//! the user answers. The title is pushed and popped with the xterm title
+//! stack, so the user's own title comes back afterwards.
+
+use arula_core::utils::config::AttentionConfig;
+use std::io::{Write, stdout};
+use std::time::{Duration, Instant};
+
+/// How long each phase of the title flash lasts
+const FLASH_INTERVAL: Duration = Duration::from_millis(700);
+
+const BELL: &str = "\x07";
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+const PROGRESS_PAUSED: &str = "\x1b]9;4;4;100\x07";
+const PROGRESS_CLEAR: &str = "\x1b]9;4;0;0\x07";
+
+/// What the agent is waiting on and since when
+#[derive(Debug, Default)]
+pub struct Attention {
+    waiting: Option<(String, Instant)>,
+    /// Whether the title is flashing, and whether it was pushed
+    flashing: bool,
+    title_pushed: bool,
+    /// Phase the title was last drawn in
+    shown_phase: Option<bool>,
+}
+
+impl Attention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The agent now waits on the user for `reason`
+    pub fn start(&mut self, reason: &str, config: &AttentionConfig) {
+        let mut out = String::new();
+        if config.bell() {
+            out.push_str(BELL);
+        }
+        if config.flash() {
+            if !self.title_pushed {
+                out.push_str(PUSH_TITLE);
+                self.title_pushed = true;
+            }
+            self.flashing = true;
+            self.shown_phase = None;
+        }
+        out.push_str(PROGRESS_PAUSED);
+        self.waiting = Some((reason.to_string(), Instant::now()));
+        emit(&out);
+        self.tick();
+    }
+
+    /// Advance the title flash; call on every frame
+    pub fn tick(&mut self) {
+        let Some((reason, since)) = &self.waiting else {
+            return;
+        };
+        if !self.flashing {
+            return;
+        }
+        let phase = flash_phase(since.elapsed());
+        if self.shown_phase != Some(phase) {
+            self.shown_phase = Some(phase);
+            emit(&set_title(&flash_title(phase, reason)));
+        }
+    }
+
+    /// The user is at the keyboard: stop flashing, keep waiting
+    pub fn acknowledge(&mut self) {
+        if self.flashing {
+            self.flashing = false;
+            self.restore_title();
+        }
+    }
+
+    /// The user answered or the run ended: back to normal
+    pub fn clear(&mut self) {
+        if self.waiting.take().is_none() {
+            return;
+        }
+        self.flashing = false;
+        self.restore_title();
+        emit(PROGRESS_CLEAR);
+    }
+
+    /// What the agent waits on and for how long
+    pub fn waiting(&self) -> Option<(&str, Duration)> {
+        self.waiting
+            .as_ref()
+            .map(|(reason, since)| (reason.as_str(), since.elapsed()))
+    }
+
+    fn restore_title(&mut self) {
+        if self.title_pushed {
+            self.title_pushed = false;
+            self.shown_phase = None;
+            emit(POP_TITLE);
+        }
+    }
+}
+
+impl Drop for Attention {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Which half of the flash cycle `elapsed` falls in
+fn flash_phase(elapsed: Duration) -> bool {
+    (elapsed.as_millis() / FLASH_INTERVAL.as_millis()).is_multiple_of(2)
+}
+
+/// Title for one phase of the flash
+fn flash_title(phase: bool, reason: &str) -> String {
+    if phase {
+        format!("⚠ ARULA needs you: {}", reason)
+    } else {
+        "ARULA".to_string()
+    }
+}
+
+fn set_title(title: &str) -> String {
+    // Control characters would end the sequence early
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]0;{}\x07", title)
+}
+
+fn emit(sequence: &str) {
+    let mut out = stdout();
+    let _ = out.write_all(sequence.as_bytes());
+    let _ = out.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flash_title() {
+        assert!(flash_phase(Duration::ZERO));
+        assert!(!flash_phase(FLASH_INTERVAL));
+        assert!(flash_phase(FLASH_INTERVAL * 2 + Duration::from_millis(1)));
+        assert_eq!(
+            flash_title(true, "a question"),
+            "⚠ ARULA needs you: a question"
+        );
+        assert_eq!(flash_title(false, "a question"), "ARULA");
+        assert_eq!(set_title("line\nbreak\x07"), "\x1b]0;linebreak\x07");
+    }
+}
@@ -3,6 +3,50 @@
 //! This module provides shared color conversion and utility functions
 //! for use across the ARULA CLI UI components.
 
+use arula_core::utils::themes::{active_theme, Rgb};
+use ratatui::style::Color;
+
+/// The active theme's colors as ratatui colors
+#[derive(Debug, Clone, Copy)]
+pub struct TuiTheme {
+    pub background: Color,
+    pub surface: Color,
+    pub border: Color,
+    pub text: Color,
+    pub muted: Color,
+    pub accent: Color,
+    pub accent_soft: Color,
+    pub success: Color,
+    pub warning: Color,
+    pub danger: Color,
+    pub info: Color,
+}
+
+impl TuiTheme {
+    pub fn active() -> Self {
+        let c = active_theme();
+        let rgb = |Rgb(r, g, b): Rgb| Color::Rgb(r, g, b);
+        Self {
+            background: rgb(c.background),
+            surface: rgb(c.surface),
+            border: rgb(c.border),
+            text: rgb(c.text),
+            muted: rgb(c.muted),
+            accent: rgb(c.accent),
+            accent_soft: rgb(c.accent_soft),
+            success: rgb(c.success),
+            warning: rgb(c.warning),
+            danger: rgb(c.danger),
+            info: rgb(c.info),
+        }
+    }
+}
+
+/// A theme color as a crossterm color, for history lines
+pub fn term_color(Rgb(r, g, b): Rgb) -> crossterm::style::Color {
+    crossterm::style::Color::Rgb { r, g, b }
+}
+
 /// Convert HSV color values to RGB
 ///
 /// # Arguments
@@ -0,0 +1,148 @@
+//! Command palette for slash commands
+//!
+//! Typing `/` at the start of the input lists the commands whose names
+//! fuzzy-match what follows, best match first, with their descriptions.
+//! `↑`/`↓` pick one, `Tab` completes it and `Enter` runs it (or completes it
+//! when it needs arguments); `Esc` hides the list. Commands registered by
+//! scripts and plugins are listed with the built-in ones.
+
+use crate::ui::slash_commands::SLASH_COMMANDS;
+
+/// Most commands shown at once; the list scrolls with the selection
+pub const MAX_VISIBLE: usize = 6;
+
+/// A command the palette can offer
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteEntry {
+    /// Name without the `/`
+    pub name: String,
+    /// Name with its arguments, e.g. `/commit [files...]`
+    pub usage: String,
+    pub description: String,
+}
+
+impl PaletteEntry {
+    /// An entry from a usage string starting with `/name`
+    pub fn new(usage: &str, description: &str) -> Self {
+        let name = usage
+            .trim_start_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_string();
+        Self {
+            name,
+            usage: usage.to_string(),
+            description: description.to_string(),
+        }
+    }
+
+    /// Whether the command can't run without arguments: its usage has a
+    /// `<placeholder>` outside `[optional]` parts
+    pub fn needs_args(&self) -> bool {
+        let mut depth = 0;
+        self.usage.chars().any(|c| {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                _ => {}
+            }
+            c == '<' && depth == 0
+        })
+    }
+}
+
+/// The built-in commands, in help order
+pub fn builtin_entries() -> Vec<PaletteEntry> {
+    SLASH_COMMANDS
+        .iter()
+        .map(|(usage, description)| PaletteEntry::new(usage, description))
+        .collect()
+}
+
+/// The command name being typed: the input after `/`, until a space
+pub fn typed_name(input: &str) -> Option<&str> {
+    let rest = input.strip_prefix('/')?;
+    (!rest.contains(char::is_whitespace)).then_some(rest)
+}
+
+/// How well `query` matches `name`: its characters must appear in order;
+/// a prefix, runs of consecutive characters and word starts score higher
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i32> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for ch in query.to_lowercase().chars() {
+        let found = position + name[position..].iter().position(|&c| c == ch)?;
+        score += 10;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 15;
+        }
+        if found == 0 || matches!(name[found - 1], '-' | '_') {
+            score += 20;
+        }
+        // Skipped characters count against the match
+        score -= (found - position) as i32;
+        previous = Some(found);
+        position = found + 1;
+    }
+    if previous.is_some() && previous == Some(query.chars().count().saturating_sub(1)) {
+        // The whole query is a prefix
+        score += 50;
+    }
+    Some(score)
+}
+
+/// The entries matching `query`, best first; equal scores keep their order
+pub fn filter(entries: Vec<PaletteEntry>, query: &str) -> Vec<PaletteEntry> {
+    let mut scored: Vec<(i32, PaletteEntry)> = entries
+        .into_iter()
+        .filter_map(|entry| Some((fuzzy_score(query, &entry.name)?, entry)))
+        .collect();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
+/// First of the `MAX_VISIBLE` rows shown so `selected` is among them
+pub fn first_visible(selected: usize, count: usize) -> usize {
+    (selected + 1)
+        .saturating_sub(MAX_VISIBLE)
+        .min(count.saturating_sub(MAX_VISIBLE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_filter() {
+        assert_eq!(typed_name("/com"), Some("com"));
+        assert_eq!(typed_name("/"), Some(""));
+        assert_eq!(typed_name("/commit a.rs"), None);
+        assert_eq!(typed_name("commit"), None);
+
+        let names = |query: &str| -> Vec<String> {
+            filter(builtin_entries(), query)
+                .into_iter()
+                .map(|e| e.name)
+                .collect()
+        };
+        assert_eq!(names("").len(), SLASH_COMMANDS.len());
+        assert_eq!(names("")[0], "help");
+        assert_eq!(names("comp")[0], "compact");
+        assert_eq!(names("el")[0], "edit-last");
+        assert!(names("cmt").contains(&"commit".to_string()));
+        assert!(names("xyz").is_empty());
+
+        let commit = PaletteEntry::new("/commit [files...]", "Commit");
+        assert_eq!(commit.name, "commit");
+        assert!(!commit.needs_args());
+        assert!(PaletteEntry::new("/steer <message>", "Steer").needs_args());
+        assert!(!PaletteEntry::new("/set [<setting> <value>]", "Set").needs_args());
+
+        assert_eq!(first_visible(0, 20), 0);
+        assert_eq!(first_visible(8, 20), 3);
+        assert_eq!(first_visible(2, 3), 0);
+    }
+}
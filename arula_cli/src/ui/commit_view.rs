@@ -0,0 +1,201 @@
+//! Confirmation dialog for AI-drafted commits
+//!
+//! Shows the staged files and the drafted commit message and lets the user
+//! commit, edit the message, ask for a new draft, or cancel:
+//!
+//! - `Enter`/`y` commit, `e` edit message, `r` regenerate, `n`/`Esc` cancel
+//! - While editing: type to change the message, `Enter` for a new line,
+//!   `Esc` to stop editing
+
+use anyhow::Result;
+use arula_core::utils::commit_message::{CommitDraft, is_conventional_commit};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use std::io::stdout;
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// What the user decided to do with a drafted commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommitDecision {
+    /// Commit with the (possibly edited) message
+    Commit(String),
+    /// Ask the model for a new draft
+    Regenerate,
+    /// Leave the changes staged without committing
+    Cancel,
+}
+
+/// Interactive commit confirmation dialog
+pub struct CommitView<'a> {
+    draft: &'a CommitDraft,
+    message: String,
+    editing: bool,
+}
+
+impl<'a> CommitView<'a> {
+    pub fn new(draft: &'a CommitDraft) -> Self {
+        Self {
+            draft,
+            message: draft.message.clone(),
+            editing: false,
+        }
+    }
+
+    /// Show the dialog until the user decides
+    pub fn show(&mut self) -> Result<CommitDecision> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<CommitDecision> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.editing {
+                match key.code {
+                    KeyCode::Esc => self.editing = false,
+                    KeyCode::Enter => self.message.push('\n'),
+                    KeyCode::Backspace => {
+                        self.message.pop();
+                    }
+                    KeyCode::Char(c) => self.message.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Enter | KeyCode::Char('y') if !self.message.trim().is_empty() => {
+                    return Ok(CommitDecision::Commit(self.message.trim().to_string()));
+                }
+                KeyCode::Char('e') => self.editing = true,
+                KeyCode::Char('r') => return Ok(CommitDecision::Regenerate),
+                KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                    return Ok(CommitDecision::Cancel);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let file_rows = self.draft.files.len().min(8) as u16 + 2;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(file_rows),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(f.area());
+
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let dim = Style::default().fg(Color::DarkGray);
+
+        // Staged files
+        let mut files: Vec<Line> = self
+            .draft
+            .files
+            .iter()
+            .take(7)
+            .map(|file| {
+                Line::from(vec![
+                    Span::styled("  + ", Style::default().fg(Color::Green)),
+                    Span::raw(file.clone()),
+                ])
+            })
+            .collect();
+        if self.draft.files.len() > 7 {
+            files.push(Line::styled(
+                format!("  ... and {} more", self.draft.files.len() - 7),
+                dim,
+            ));
+        }
+        let files = Paragraph::new(files).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(accent)
+                .title(format!(" Staged files ({}) ", self.draft.files.len())),
+        );
+        f.render_widget(files, chunks[0]);
+
+        // Commit message
+        let mut lines: Vec<Line> = self
+            .message
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 {
+                    Line::styled(
+                        line.to_string(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    Line::raw(line.to_string())
+                }
+            })
+            .collect();
+        if self.editing {
+            match lines.last_mut() {
+                Some(last) if !self.message.ends_with('\n') => {
+                    last.spans.push(Span::styled("█", accent))
+                }
+                _ => lines.push(Line::styled("█", accent)),
+            }
+        }
+        let title = if self.editing {
+            " Commit message (editing) "
+        } else {
+            " Commit message "
+        };
+        let message = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(if self.editing {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    accent
+                })
+                .title(title),
+        );
+        f.render_widget(message, chunks[1]);
+
+        // Footer: key help and convention warning
+        let mut footer = vec![Span::styled(
+            if self.editing {
+                "type to edit  Enter new line  Esc done"
+            } else {
+                "Enter/y commit  e edit  r regenerate  n/Esc cancel"
+            },
+            dim,
+        )];
+        if !is_conventional_commit(&self.message) {
+            footer.push(Span::styled(
+                "  ⚠ subject is not a conventional commit",
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        f.render_widget(Paragraph::new(Line::from(footer)), chunks[2]);
+    }
+}
@@ -8,6 +8,7 @@
 //! - Smooth orbital rotation - very unique!
 
 use super::colors::hsv_to_rgb;
+use arula_core::utils::style_packs::SpinnerPack;
 use crossterm::{
     cursor, execute,
     style::{Color, ResetColor, SetForegroundColor},
@@ -104,6 +105,8 @@ pub struct CustomSpinner {
     tx: Sender<Cmd>,
     handle: Option<thread::JoinHandle<()>>,
     state: Arc<Mutex<SpinnerState>>,
+    /// Frames from a style pack, replacing the orbital animation
+    pack: Option<SpinnerPack>,
 }
 
 impl Default for CustomSpinner {
@@ -119,12 +122,23 @@ impl CustomSpinner {
             tx: mpsc::channel().0,
             handle: None,
             state: Arc::new(Mutex::new(SpinnerState { running: false })),
+            pack: None,
         }
     }
 
+    /// Animate with a spinner pack's frames and speed instead of the orbit
+    pub fn with_pack(mut self, pack: &SpinnerPack) -> Self {
+        if !pack.frames.is_empty() {
+            self.pack = Some(pack.clone());
+        }
+        self
+    }
+
     /// Start the spinner with a message
     pub fn start(&mut self, message: &str) -> io::Result<()> {
-        self.start_with_speed(message, 100) // 100ms for smooth orbital motion
+        // 100ms for smooth orbital motion
+        let speed_ms = self.pack.as_ref().map_or(100, |p| p.interval_ms);
+        self.start_with_speed(message, speed_ms)
     }
 
     /// Start the spinner above the current line (for persistent input)
@@ -144,11 +158,12 @@ impl CustomSpinner {
         let state_clone = Arc::clone(&state);
 
         let label = label.to_string();
+        let frames = self.pack.as_ref().map(|p| p.frames.clone());
 
         let handle = thread::Builder::new()
             .name("arula-star-spinner".into())
             .spawn(move || {
-                if let Err(e) = run_star_spinner(label, speed_ms, rx, state_clone) {
+                if let Err(e) = run_star_spinner(label, frames, speed_ms, rx, state_clone) {
                     let _ = writeln!(io::stderr(), "spinner thread error: {:?}", e);
                 }
             })?;
@@ -235,6 +250,7 @@ impl Drop for CustomSpinner {
 /// Internal star pulse spinner loop
 fn run_star_spinner(
     mut label: String,
+    pack_frames: Option<Vec<String>>,
     speed_ms: u64,
     rx: Receiver<Cmd>,
     _state: Arc<Mutex<SpinnerState>>,
@@ -242,8 +258,10 @@ fn run_star_spinner(
     let mut index: i32 = 0;
     let mut stdout = io::stdout();
 
-    // Start with orbital frames
-    let mut current_frames: Vec<String> = ORBITAL_FRAMES.iter().map(|&s| s.to_string()).collect();
+    // Start with the pack's frames, played in order, or the orbital frames
+    let in_order = pack_frames.is_some();
+    let mut current_frames: Vec<String> = pack_frames
+        .unwrap_or_else(|| ORBITAL_FRAMES.iter().map(|&s| s.to_string()).collect());
     let mut transition_in_progress = false;
     let mut transition_type: Option<Transition> = None;
     let mut transition_frame_count = 0;
@@ -290,7 +308,8 @@ fn run_star_spinner(
 
         if last_draw.elapsed() >= frame_duration {
             // Random direction for organic breathing
-            index = (index + random_dir()).rem_euclid(current_frames.len() as i32);
+            let step = if in_order { 1 } else { random_dir() };
+            index = (index + step).rem_euclid(current_frames.len() as i32);
 
             let frame = &current_frames[index as usize];
 
@@ -0,0 +1,384 @@
+//! File browser for attaching files to the next message
+//!
+//! Browses the project directory, leaving out what `.gitignore` ignores,
+//! and marks files whose contents are sent along with the next message.
+//! Files the agent read or changed this session are flagged, and the
+//! folders holding them start expanded.
+//!
+//! - `↑`/`↓`/`j`/`k` move, `→`/`l` expand, `←`/`h` collapse or go to the parent
+//! - `Space`/`Enter` mark a file (or expand a folder), `c` clear the marks
+//! - `q`/`Esc` done
+
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, MouseEventKind};
+use ignore::WalkBuilder;
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::stdout;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// What the agent did with a file this session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Touch {
+    Read,
+    Changed,
+}
+
+/// Record that the agent's tool `name` touched the file in its `args`, with
+/// the path made relative to `root`
+pub fn record_touch(touched: &mut HashMap<PathBuf, Touch>, root: &Path, name: &str, args: &str) {
+    let touch = match name {
+        "read_file" => Touch::Read,
+        "write_file" | "edit_file" => Touch::Changed,
+        _ => return,
+    };
+    let Some(path) = serde_json::from_str::<serde_json::Value>(args)
+        .ok()
+        .and_then(|args| {
+            ["path", "file_path"]
+                .iter()
+                .find_map(|key| args[key].as_str().map(PathBuf::from))
+        })
+    else {
+        return;
+    };
+    let path = path.strip_prefix(root).unwrap_or(&path);
+    let path: PathBuf = path
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+    let entry = touched.entry(path).or_insert(touch);
+    *entry = (*entry).max(touch);
+}
+
+/// One visible entry of the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    /// Path relative to the root
+    path: PathBuf,
+    depth: usize,
+    is_dir: bool,
+}
+
+/// Full-screen file browser
+pub struct FileTreeView<'a> {
+    root: PathBuf,
+    touched: &'a HashMap<PathBuf, Touch>,
+    marked: BTreeSet<PathBuf>,
+    expanded: HashSet<PathBuf>,
+    rows: Vec<Row>,
+    selected: usize,
+}
+
+impl<'a> FileTreeView<'a> {
+    /// A browser of `root` with `marked` files already marked
+    pub fn new(root: &Path, touched: &'a HashMap<PathBuf, Touch>, marked: &[PathBuf]) -> Self {
+        // Open the folders holding touched and marked files
+        let expanded = touched
+            .keys()
+            .chain(marked)
+            .flat_map(|path| path.ancestors().skip(1))
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+            .collect();
+        let mut view = Self {
+            root: root.to_path_buf(),
+            touched,
+            marked: marked.iter().cloned().collect(),
+            expanded,
+            rows: Vec::new(),
+            selected: 0,
+        };
+        view.rebuild();
+        view
+    }
+
+    /// Show the browser until the user closes it; returns the marked files
+    pub fn show(&mut self) -> Result<Vec<PathBuf>> {
+        MenuUtils::run_overlay(|| self.run_loop())?;
+        Ok(self.marked.iter().cloned().collect())
+    }
+
+    fn run_loop(&mut self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                Event::Mouse(mouse) => {
+                    match mouse.kind {
+                        MouseEventKind::ScrollDown => self.move_by(3),
+                        MouseEventKind::ScrollUp => self.move_by(-3),
+                        _ => {}
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+                KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+                KeyCode::PageDown => self.move_by(10),
+                KeyCode::PageUp => self.move_by(-10),
+                KeyCode::Home | KeyCode::Char('g') => self.selected = 0,
+                KeyCode::End | KeyCode::Char('G') => {
+                    self.selected = self.rows.len().saturating_sub(1)
+                }
+                KeyCode::Right | KeyCode::Char('l') => self.set_expanded(true),
+                KeyCode::Left | KeyCode::Char('h') => self.collapse_or_parent(),
+                KeyCode::Enter | KeyCode::Char(' ') => self.activate(),
+                KeyCode::Char('c') => self.marked.clear(),
+                _ => {}
+            }
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        self.selected = self
+            .selected
+            .saturating_add_signed(delta)
+            .min(self.rows.len().saturating_sub(1));
+    }
+
+    /// Expand or collapse the selected folder
+    fn set_expanded(&mut self, expand: bool) {
+        let Some(row) = self.rows.get(self.selected).filter(|row| row.is_dir) else {
+            return;
+        };
+        let path = row.path.clone();
+        if expand {
+            self.expanded.insert(path);
+        } else {
+            self.expanded.remove(&path);
+        }
+        self.rebuild();
+    }
+
+    fn collapse_or_parent(&mut self) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+        if row.is_dir && self.expanded.contains(&row.path) {
+            self.set_expanded(false);
+        } else if let Some(parent) = row.path.parent()
+            && let Some(index) = self.rows.iter().position(|r| r.path == parent)
+        {
+            self.selected = index;
+        }
+    }
+
+    /// Mark or unmark the selected file, or open or close the selected folder
+    fn activate(&mut self) {
+        let Some(row) = self.rows.get(self.selected) else {
+            return;
+        };
+        if row.is_dir {
+            let expand = !self.expanded.contains(&row.path);
+            self.set_expanded(expand);
+        } else if !self.marked.remove(&row.path) {
+            self.marked.insert(row.path.clone());
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.rows.clear();
+        self.push_children(Path::new(""), 0);
+        self.selected = self.selected.min(self.rows.len().saturating_sub(1));
+    }
+
+    fn push_children(&mut self, dir: &Path, depth: usize) {
+        for row in list_dir(&self.root, dir, depth) {
+            let expand = row.is_dir && self.expanded.contains(&row.path);
+            let path = row.path.clone();
+            self.rows.push(row);
+            if expand {
+                self.push_children(&path, depth + 1);
+            }
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(f.area());
+
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let dim = Style::default().fg(Color::DarkGray);
+
+        // Keep the selection on screen
+        let height = chunks[0].height.saturating_sub(2) as usize;
+        let offset = (self.selected + 1).saturating_sub(height.max(1));
+
+        let lines: Vec<Line> = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, row)| {
+                let name = row
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let mut spans = vec![Span::raw("  ".repeat(row.depth + 1))];
+                if row.is_dir {
+                    let arrow = if self.expanded.contains(&row.path) {
+                        "▾"
+                    } else {
+                        "▸"
+                    };
+                    spans.push(Span::styled(format!("{} {}/", arrow, name), accent));
+                    if self.touched.keys().any(|path| path.starts_with(&row.path)) {
+                        spans.push(Span::styled("  •", dim));
+                    }
+                } else {
+                    let mark = if self.marked.contains(&row.path) {
+                        "[x] "
+                    } else {
+                        "[ ] "
+                    };
+                    spans.push(Span::styled(mark, dim));
+                    spans.push(Span::raw(name));
+                    match self.touched.get(&row.path) {
+                        Some(Touch::Changed) => spans.push(Span::styled(
+                            "  ● changed",
+                            Style::default().fg(Color::Yellow),
+                        )),
+                        Some(Touch::Read) => spans.push(Span::styled("  ○ read", dim)),
+                        None => {}
+                    }
+                }
+                let line = Line::from(spans);
+                if i == self.selected {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        let title = format!(
+            " Files · {} · {} marked for the next message ",
+            self.root.display(),
+            self.marked.len()
+        );
+        let body = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(accent)
+                .title(title),
+        );
+        f.render_widget(body, chunks[0]);
+
+        let footer = Line::styled(
+            "↑↓ move  →/← open/close  Space mark  c clear  ● changed ○ read by the agent  q done",
+            dim,
+        );
+        f.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+}
+
+/// Entries of `dir` (relative to `root`) that aren't ignored, folders first
+fn list_dir(root: &Path, dir: &Path, depth: usize) -> Vec<Row> {
+    let mut rows: Vec<Row> = WalkBuilder::new(root.join(dir))
+        .max_depth(Some(1))
+        .require_git(false)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() == 1)
+        .filter_map(|entry| {
+            let path = entry.path().strip_prefix(root).ok()?.to_path_buf();
+            Some(Row {
+                path,
+                depth,
+                is_dir: entry.file_type().is_some_and(|t| t.is_dir()),
+            })
+        })
+        .collect();
+    rows.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tree_respects_gitignore_and_opens_touched_folders() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/ui")).unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+        std::fs::write(root.join("src/ui/app.rs"), "").unwrap();
+
+        let mut touched = HashMap::new();
+        record_touch(
+            &mut touched,
+            root,
+            "read_file",
+            r#"{"path": "./src/ui/app.rs"}"#,
+        );
+        let absolute = root.join("src/ui/app.rs").display().to_string();
+        record_touch(
+            &mut touched,
+            root,
+            "edit_file",
+            &serde_json::json!({ "path": absolute }).to_string(),
+        );
+        record_touch(
+            &mut touched,
+            root,
+            "read_file",
+            r#"{"path": "src/ui/app.rs"}"#,
+        );
+        assert_eq!(
+            touched.get(Path::new("src/ui/app.rs")),
+            Some(&Touch::Changed)
+        );
+
+        let mut view = FileTreeView::new(root, &touched, &[]);
+        let paths: Vec<&Path> = view.rows.iter().map(|row| row.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                Path::new("src"),
+                Path::new("src/ui"),
+                Path::new("src/ui/app.rs"),
+                Path::new("README.md"),
+            ]
+        );
+
+        view.selected = 2;
+        view.activate();
+        view.selected = 0;
+        view.activate();
+        assert_eq!(view.rows.len(), 2);
+        assert_eq!(
+            view.marked.iter().collect::<Vec<_>>(),
+            [Path::new("src/ui/app.rs")]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Search through the chat history
+//!
+//! `/` in focus mode (or Ctrl+F anywhere) starts a search. Matches are
+//! found as the query is typed: the newest matching message is focused and
+//! the occurrences are highlighted in the history rows still on screen and
+//! in the focused message. `n` goes to the next older match, `N` back to a
+//! newer one, and the status line counts them. Case is ignored.
+
+use arula_core::utils::chat::ChatMessage;
+use ratatui::text::Line;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+use crate::ui::mouse::highlight;
+
+/// A search through the messages of the conversation
+#[derive(Debug, Default)]
+pub struct HistorySearch {
+    pub query: String,
+    /// Whether the query is still being typed
+    pub editing: bool,
+    /// Indexes of the matching messages, oldest first
+    matches: Vec<usize>,
+    /// Position in `matches` of the focused match
+    current: Option<usize>,
+}
+
+impl HistorySearch {
+    /// A search whose query is about to be typed
+    pub fn new() -> Self {
+        Self {
+            editing: true,
+            ..Self::default()
+        }
+    }
+
+    /// Find the messages among `candidates` (indexes into `messages`) that
+    /// contain the query, and go to the newest; returns its index
+    pub fn update(&mut self, messages: &[ChatMessage], candidates: &[usize]) -> Option<usize> {
+        let query = self.query.to_lowercase();
+        self.matches = if query.is_empty() {
+            Vec::new()
+        } else {
+            candidates
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    messages
+                        .get(i)
+                        .is_some_and(|m| m.content.to_lowercase().contains(&query))
+                })
+                .collect()
+        };
+        self.current = self.matches.len().checked_sub(1);
+        self.focused()
+    }
+
+    /// Go to the next older match, or back to a newer one, wrapping around;
+    /// returns the index of the message
+    pub fn step(&mut self, older: bool) -> Option<usize> {
+        let count = self.matches.len();
+        let current = self.current?;
+        self.current = Some(if older {
+            (current + count - 1) % count
+        } else {
+            (current + 1) % count
+        });
+        self.focused()
+    }
+
+    /// Index of the focused matching message
+    pub fn focused(&self) -> Option<usize> {
+        self.matches.get(self.current?).copied()
+    }
+
+    /// "2/5" counting from the newest match, or `None` without matches
+    pub fn counter(&self) -> Option<String> {
+        let current = self.current?;
+        Some(format!(
+            "{}/{}",
+            self.matches.len() - current,
+            self.matches.len()
+        ))
+    }
+
+    /// `line` with the occurrences of the query highlighted
+    pub fn highlight(&self, line: &Line<'static>) -> Line<'static> {
+        match_columns(line, &self.query)
+            .into_iter()
+            .fold(line.clone(), |line, columns| highlight(&line, columns))
+    }
+}
+
+/// Screen columns of each occurrence of `query` in `line`, ignoring case
+pub fn match_columns(line: &Line<'_>, query: &str) -> Vec<Range<u16>> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    // Each character, lowercased, with the column it starts at
+    let mut cells: Vec<(char, u16)> = Vec::new();
+    let mut column = 0u16;
+    for ch in line.spans.iter().flat_map(|span| span.content.chars()) {
+        for lower in ch.to_lowercase() {
+            cells.push((lower, column));
+        }
+        column = column.saturating_add(UnicodeWidthChar::width(ch).unwrap_or(0) as u16);
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= cells.len() {
+        let window = &cells[start..start + query.len()];
+        if window.iter().map(|(ch, _)| *ch).eq(query.iter().copied()) {
+            let end = cells
+                .get(start + query.len())
+                .map_or(column, |(_, column)| *column);
+            ranges.push(window[0].1..end);
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arula_core::utils::chat::MessageType;
+    use ratatui::text::Span;
+
+    #[test]
+    fn test_search_steps_through_matches_newest_first() {
+        let messages = vec![
+            ChatMessage::new(MessageType::User, "Fix the Parser".to_string()),
+            ChatMessage::new(MessageType::ToolResult, "parser.rs".to_string()),
+            ChatMessage::new(MessageType::Arula, "Done".to_string()),
+            ChatMessage::new(
+                MessageType::Arula,
+                "The parser now handles tabs".to_string(),
+            ),
+        ];
+        let mut search = HistorySearch::new();
+        search.query = "parser".to_string();
+        assert_eq!(search.update(&messages, &[0, 2, 3]), Some(3));
+        assert_eq!(search.counter().as_deref(), Some("1/2"));
+        assert_eq!(search.step(true), Some(0));
+        assert_eq!(search.counter().as_deref(), Some("2/2"));
+        assert_eq!(search.step(true), Some(3));
+        assert_eq!(search.step(false), Some(0));
+
+        search.query = "lexer".to_string();
+        assert_eq!(search.update(&messages, &[0, 2, 3]), None);
+        assert_eq!(search.counter(), None);
+
+        let line = Line::from(vec![Span::raw("▶ "), Span::raw("Ab ab AB")]);
+        assert_eq!(match_columns(&line, "ab"), [2..4, 5..7, 8..10]);
+        assert!(match_columns(&line, "").is_empty());
+    }
+}
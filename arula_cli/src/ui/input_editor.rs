@@ -0,0 +1,192 @@
+//! Multi-line prompt editing
+//!
+//! Enter sends the prompt; Shift+Enter, Alt+Enter or Ctrl+J start a new
+//! line. Pastes arrive in one piece through bracketed paste, and pasted code
+//! is wrapped in a Markdown fence tagged with its likely language. Ctrl+E
+//! hands the prompt to `$VISUAL`/`$EDITOR` for longer edits.
+
+use anyhow::Result;
+use crossterm::event::{
+    DisableBracketedPaste, DisableFocusChange, EnableBracketedPaste, EnableFocusChange,
+    KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+};
+use crossterm::execute;
+use std::io::stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ui::source_view::open_in_editor;
+
+/// Most input rows shown; longer prompts scroll with the cursor
+pub const MAX_INPUT_ROWS: usize = 8;
+
+static PASTE: AtomicBool = AtomicBool::new(false);
+static ENHANCED_KEYS: AtomicBool = AtomicBool::new(false);
+
+/// Turn bracketed paste on or off, along with the keyboard protocol that
+/// tells Shift+Enter apart from Enter on terminals that support it and the
+/// focus reports that decide when to send desktop notifications
+pub fn set_input_modes(on: bool) -> std::io::Result<()> {
+    if PASTE.swap(on, Ordering::Relaxed) == on {
+        return Ok(());
+    }
+    if on {
+        execute!(stdout(), EnableBracketedPaste, EnableFocusChange)?;
+        if crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false) {
+            execute!(
+                stdout(),
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+            )?;
+            ENHANCED_KEYS.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    } else {
+        if ENHANCED_KEYS.swap(false, Ordering::Relaxed) {
+            execute!(stdout(), PopKeyboardEnhancementFlags)?;
+        }
+        execute!(stdout(), DisableBracketedPaste, DisableFocusChange)
+    }
+}
+
+/// Whether bracketed paste is on
+pub fn input_modes_enabled() -> bool {
+    PASTE.load(Ordering::Relaxed)
+}
+
+/// Text to insert for a paste: line endings normalized, and code that spans
+/// several lines fenced (unless it already contains a fence)
+pub fn prepare_paste(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let trimmed = text.trim_matches('\n');
+    if !trimmed.contains('\n') || trimmed.contains("```") || !looks_like_code(trimmed) {
+        return text;
+    }
+    format!("```{}\n{}\n```\n", guess_language(trimmed), trimmed)
+}
+
+/// Whether most non-blank lines look like source code rather than prose
+fn looks_like_code(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let code = lines.iter().filter(|line| is_code_line(line)).count();
+    code * 2 > lines.len()
+}
+
+fn is_code_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    const ENDINGS: [&str; 7] = [";", "{", "}", ")", "(", "[", "],"];
+    const STARTS: [&str; 14] = [
+        "fn ",
+        "pub ",
+        "let ",
+        "use ",
+        "def ",
+        "class ",
+        "import ",
+        "from ",
+        "const ",
+        "function ",
+        "return ",
+        "#include",
+        "//",
+        "}",
+    ];
+    line.starts_with("    ")
+        || line.starts_with('\t')
+        || ENDINGS.iter().any(|e| trimmed.ends_with(e))
+        || STARTS.iter().any(|s| trimmed.starts_with(s))
+        || trimmed.contains(" => ")
+        || trimmed.contains(" = ") && !trimmed.ends_with('.')
+}
+
+/// Markdown fence tag for the likely language of `code`, or "" when unsure
+fn guess_language(code: &str) -> &'static str {
+    let has = |needle: &str| code.lines().any(|l| l.trim_start().starts_with(needle));
+    if code.starts_with("#!") && code.lines().next().is_some_and(|l| l.contains("sh")) {
+        "bash"
+    } else if has("fn ") || has("pub fn ") || has("impl ") || code.contains("let mut ") {
+        "rust"
+    } else if has("def ") || has("import ") && code.contains(':') && !code.contains(';') {
+        "python"
+    } else if has("func ") || has("package ") {
+        "go"
+    } else if has("function ") || has("const ") || code.contains("=> {") {
+        "javascript"
+    } else if has("#include") {
+        "c"
+    } else if code.starts_with('{') && code.contains("\":") {
+        "json"
+    } else {
+        ""
+    }
+}
+
+/// Row and column (in characters) of the cursor
+pub fn cursor_row_col(input: &str, cursor: usize) -> (usize, usize) {
+    let before: String = input.chars().take(cursor).collect();
+    let row = before.matches('\n').count();
+    let col = before.rsplit('\n').next().map_or(0, |l| l.chars().count());
+    (row, col)
+}
+
+/// Cursor position one row up or down, keeping the column where possible;
+/// `None` on the first or last row
+pub fn move_vertically(input: &str, cursor: usize, up: bool) -> Option<usize> {
+    let (row, col) = cursor_row_col(input, cursor);
+    let lines: Vec<&str> = input.split('\n').collect();
+    let target = if up { row.checked_sub(1)? } else { row + 1 };
+    let line = lines.get(target)?;
+    let start: usize = lines[..target].iter().map(|l| l.chars().count() + 1).sum();
+    Some(start + col.min(line.chars().count()))
+}
+
+/// Cursor positions of the start and end of the cursor's row
+pub fn row_bounds(input: &str, cursor: usize) -> (usize, usize) {
+    let (row, col) = cursor_row_col(input, cursor);
+    let len = input.split('\n').nth(row).map_or(0, |l| l.chars().count());
+    (cursor - col, cursor - col + len)
+}
+
+/// Edit `input` in the user's editor and return the result, without the
+/// trailing newline editors add
+pub fn edit_externally(input: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("arula-prompt-{}.md", std::process::id()));
+    std::fs::write(&path, input)?;
+    let line = input.lines().count().max(1);
+    let result = open_in_editor(&path, line).and_then(|_| Ok(std::fs::read_to_string(&path)?));
+    let _ = std::fs::remove_file(&path);
+    let mut edited = result?;
+    while edited.ends_with('\n') {
+        edited.pop();
+    }
+    Ok(edited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_and_cursor_movement() {
+        let rust = "fn main() {\r\n    println!(\"hi\");\r\n}\r\n";
+        assert_eq!(
+            prepare_paste(rust),
+            "```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n"
+        );
+        let python = "def add(a, b):\n    return a + b\n";
+        assert!(prepare_paste(python).starts_with("```python\n"));
+        // Prose and single lines go in as typed
+        let prose = "Please look at this.\nIt fails on startup.";
+        assert_eq!(prepare_paste(prose), prose);
+        assert_eq!(prepare_paste("let x = 1;"), "let x = 1;");
+        let fenced = "```\nlet x = 1;\nlet y = 2;\n```";
+        assert_eq!(prepare_paste(fenced), fenced);
+
+        let input = "first line\nab\nthird";
+        assert_eq!(cursor_row_col(input, 13), (1, 2));
+        assert_eq!(move_vertically(input, 8, false), Some(13));
+        assert_eq!(move_vertically(input, 13, false), Some(16));
+        assert_eq!(move_vertically(input, 13, true), Some(2));
+        assert_eq!(move_vertically(input, 2, true), None);
+        assert_eq!(move_vertically(input, 16, false), None);
+        assert_eq!(row_bounds(input, 12), (11, 13));
+    }
+}
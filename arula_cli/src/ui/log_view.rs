@@ -0,0 +1,269 @@
+//! Log viewer for `/logs tail`
+//!
+//! Shows the end of `~/.arula/logs/latest.log` and follows it as lines are
+//! written, picking up the new file after a rotation. Lines below the
+//! chosen level are hidden; continuation lines of a multi-line message
+//! share its level.
+//!
+//! - `l` cycle the minimum level, `f`/`End` follow, `Home` oldest line
+//! - `↑`/`↓`/`PgUp`/`PgDn` scroll, `q`/`Esc` close
+
+use anyhow::Result;
+use arula_core::utils::logger::{LogLevel, LogLine};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, stdout};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// How much of the end of the file is read when the viewer opens
+const INITIAL_BYTES: u64 = 256 * 1024;
+/// Lines kept in memory while following
+const MAX_LINES: usize = 5000;
+
+/// Levels `l` cycles through; `None` shows everything
+const LEVEL_FILTERS: [Option<LogLevel>; 4] = [
+    None,
+    Some(LogLevel::Info),
+    Some(LogLevel::Warn),
+    Some(LogLevel::Error),
+];
+
+/// One line of the file and the level of the entry it belongs to
+struct Entry {
+    level: Option<LogLevel>,
+    parsed: Option<LogLine>,
+    text: String,
+}
+
+/// Reads what was appended to a file since the last read
+struct Tail {
+    path: PathBuf,
+    offset: u64,
+    partial: String,
+    /// Reading starts mid-file, so the first line is probably cut off
+    skip_first: bool,
+}
+
+impl Tail {
+    fn open(path: &Path) -> Self {
+        let len = std::fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+        let offset = len.saturating_sub(INITIAL_BYTES);
+        Self {
+            path: path.to_path_buf(),
+            offset,
+            partial: String::new(),
+            skip_first: offset > 0,
+        }
+    }
+
+    /// Complete lines written since the last call
+    fn read_new(&mut self) -> Vec<String> {
+        let Ok(mut file) = File::open(&self.path) else {
+            return Vec::new();
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or_default();
+        if len < self.offset {
+            // Rotated: the file was replaced by a new, shorter one
+            self.offset = 0;
+            self.partial.clear();
+            self.skip_first = false;
+        }
+        let mut buf = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err() || file.read_to_end(&mut buf).is_err() {
+            return Vec::new();
+        }
+        self.offset += buf.len() as u64;
+
+        let text = std::mem::take(&mut self.partial) + String::from_utf8_lossy(&buf).as_ref();
+        let mut lines: Vec<String> = text.split('\n').map(str::to_string).collect();
+        self.partial = lines.pop().unwrap_or_default();
+        if self.skip_first && !lines.is_empty() {
+            self.skip_first = false;
+            lines.remove(0);
+        }
+        lines
+    }
+}
+
+/// Full-screen view following the log file
+pub struct LogView {
+    tail: Tail,
+    entries: Vec<Entry>,
+    filter: usize,
+    /// Lines scrolled up from the newest; 0 follows the file
+    from_bottom: usize,
+}
+
+impl LogView {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            tail: Tail::open(path),
+            entries: Vec::new(),
+            filter: 0,
+            from_bottom: 0,
+        }
+    }
+
+    /// Show the viewer until the user closes it
+    pub fn show(&mut self) -> Result<()> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            self.read_new_lines();
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let shown = self.visible().count();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Char('l') => {
+                    self.filter = (self.filter + 1) % LEVEL_FILTERS.len();
+                    self.from_bottom = 0;
+                }
+                KeyCode::Char('f') | KeyCode::End => self.from_bottom = 0,
+                KeyCode::Home => self.from_bottom = shown,
+                KeyCode::Up => self.from_bottom = (self.from_bottom + 1).min(shown),
+                KeyCode::Down => self.from_bottom = self.from_bottom.saturating_sub(1),
+                KeyCode::PageUp => self.from_bottom = (self.from_bottom + 20).min(shown),
+                KeyCode::PageDown => self.from_bottom = self.from_bottom.saturating_sub(20),
+                _ => {}
+            }
+        }
+    }
+
+    fn read_new_lines(&mut self) {
+        let lines = self.tail.read_new();
+        if lines.is_empty() {
+            return;
+        }
+        let before = self.visible().count();
+        for text in lines {
+            let parsed = LogLine::parse(&text);
+            let level = match &parsed {
+                Some(line) => Some(line.level),
+                None => self.entries.last().and_then(|entry| entry.level),
+            };
+            self.entries.push(Entry {
+                level,
+                parsed,
+                text,
+            });
+        }
+        if self.entries.len() > MAX_LINES {
+            self.entries.drain(..self.entries.len() - MAX_LINES);
+        }
+        // Keep the same lines in view while scrolled up
+        if self.from_bottom > 0 {
+            let added = self.visible().count().saturating_sub(before);
+            self.from_bottom += added;
+        }
+    }
+
+    fn visible(&self) -> impl Iterator<Item = &Entry> {
+        let min = LEVEL_FILTERS[self.filter];
+        self.entries
+            .iter()
+            .filter(move |entry| match (min, entry.level) {
+                (None, _) => true,
+                (Some(min), Some(level)) => level.severity() >= min.severity(),
+                (Some(_), None) => false,
+            })
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(f.area());
+        let height = chunks[0].height.saturating_sub(2) as usize;
+
+        let visible: Vec<&Entry> = self.visible().collect();
+        let end = visible.len().saturating_sub(self.from_bottom);
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = visible[start..end]
+            .iter()
+            .map(|e| render_entry(e))
+            .collect();
+
+        let level = match LEVEL_FILTERS[self.filter] {
+            Some(level) => format!("{} and above", level),
+            None => "all levels".to_string(),
+        };
+        let state = if self.from_bottom == 0 {
+            "following".to_string()
+        } else {
+            format!("{} line(s) up", self.from_bottom)
+        };
+        let title = format!(" {} · {} · {} ", self.tail.path.display(), level, state);
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let body = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(accent)
+                .title(title),
+        );
+        f.render_widget(body, chunks[0]);
+
+        let footer = Line::styled(
+            "l level  f follow  ↑↓ PgUp/PgDn scroll  Home oldest  q/Esc close",
+            Style::default().fg(Color::DarkGray),
+        );
+        f.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+}
+
+fn level_style(level: LogLevel) -> Style {
+    match level {
+        LogLevel::Debug => Style::default().fg(Color::DarkGray),
+        LogLevel::Info => Style::default().fg(Color::Green),
+        LogLevel::Warn => Style::default().fg(Color::Yellow),
+        LogLevel::Error => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+fn render_entry(entry: &Entry) -> Line<'_> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let Some(line) = &entry.parsed else {
+        return Line::styled(format!("    {}", entry.text), dim);
+    };
+    // Only the time of day; the date is rarely useful while tailing
+    let time = line
+        .timestamp
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or(&line.timestamp);
+    Line::from(vec![
+        Span::styled(format!("{} ", time), dim),
+        Span::styled(format!("{:<5} ", line.level), level_style(line.level)),
+        Span::styled(
+            format!("{} ", line.module),
+            Style::default().fg(Color::Cyan),
+        ),
+        Span::raw(line.message.as_str()),
+    ])
+}
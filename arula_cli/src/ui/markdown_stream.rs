@@ -1,28 +1,49 @@
 //! Markdown streaming support for real-time AI response rendering
 //! Based on codex-rs markdown_stream implementation
+//!
+//! Lines are committed as soon as they can no longer change. A GitHub-style
+//! table is the one block that can: a later row may widen a column, so its
+//! rows stay in the current block, re-flowed by [`MarkdownStream::pending`]
+//! on every chunk, and are committed once the table ends.
 
+use crate::ui::output::code_blocks::SpanHighlighter;
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::Line;
+use ratatui::text::{Line, Span};
 use std::collections::VecDeque;
+use unicode_width::UnicodeWidthStr;
+
+/// Bullets for list items by nesting depth
+const BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+/// Spaces of source indentation per list level
+const LIST_INDENT: usize = 2;
+
+/// Column alignment from a table's delimiter row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// A block that isn't committed yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    None,
+    /// A line with pipes that becomes a table header if a delimiter row follows
+    MaybeTable(String),
+    /// Rows of a table, including the delimiter row
+    Table(Vec<String>),
+}
 
 /// Markdown stream processor that handles incremental markdown rendering
 pub struct MarkdownStream {
     /// Buffered content waiting to be rendered
     buffer: String,
-    /// Completed lines ready for display
-    completed_lines: Vec<Line<'static>>,
-    /// Current line being built
-    current_line: Line<'static>,
-    /// Whether we're in a code block
-    in_code_block: bool,
-    /// Code block language
-    code_block_lang: String,
-    /// Whether we're in a list
-    in_list: bool,
-    /// Current list depth
-    list_depth: usize,
-    /// Whether we're in a blockquote
-    in_blockquote: bool,
+    /// The open block, re-rendered as rows arrive
+    block: Block,
+    /// Fence of the open code block and its highlighter
+    code: Option<(String, SpanHighlighter)>,
 }
 
 impl Default for MarkdownStream {
@@ -36,33 +57,38 @@ impl MarkdownStream {
     pub fn new() -> Self {
         Self {
             buffer: String::new(),
-            completed_lines: Vec::new(),
-            current_line: Line::default(),
-            in_code_block: false,
-            code_block_lang: String::new(),
-            in_list: false,
-            list_depth: 0,
-            in_blockquote: false,
+            block: Block::None,
+            code: None,
         }
     }
 
-    /// Push new content from the AI stream
+    /// Push new content from the AI stream, returning the lines committed by it
     pub fn push(&mut self, content: &str) -> Vec<Line<'static>> {
         self.buffer.push_str(content);
         self.process_buffer()
     }
 
+    /// The open block as it would render now, e.g. a table with the rows
+    /// received so far
+    pub fn pending(&self) -> Vec<Line<'static>> {
+        match &self.block {
+            Block::None => Vec::new(),
+            Block::MaybeTable(line) => vec![render_text_line(line)],
+            Block::Table(rows) => render_table(rows),
+        }
+    }
+
     /// Finalize the stream and return any remaining content
     pub fn finalize(&mut self) -> Vec<Line<'static>> {
-        let mut result = Vec::new();
+        let mut result = self.process_buffer();
 
-        // Process any remaining content
-        result.extend(self.process_buffer());
-
-        // Add the current line if it has content
-        if !self.current_line.spans.is_empty() {
-            result.push(self.current_line.clone());
-            self.current_line = Line::default();
+        let rest = std::mem::take(&mut self.buffer);
+        if !rest.trim().is_empty() {
+            result.extend(self.process_line(&rest));
+        }
+        result.extend(self.close_block());
+        if self.code.take().is_some() {
+            result.push(code_footer());
         }
 
         result
@@ -71,13 +97,8 @@ impl MarkdownStream {
     /// Clear all buffered content
     pub fn clear(&mut self) {
         self.buffer.clear();
-        self.completed_lines.clear();
-        self.current_line = Line::default();
-        self.in_code_block = false;
-        self.code_block_lang.clear();
-        self.in_list = false;
-        self.list_depth = 0;
-        self.in_blockquote = false;
+        self.block = Block::None;
+        self.code = None;
     }
 
     /// Process the buffer and return completed lines
@@ -87,78 +108,139 @@ impl MarkdownStream {
         // Process complete lines
         while let Some(newline_pos) = self.buffer.find('\n') {
             let line_content = self.buffer[..newline_pos].to_string();
-            self.buffer = self.buffer[newline_pos + 1..].to_string();
-
-            // Process the line
-            if let Some(line) = self.process_line(&line_content) {
-                result.push(line);
-            }
+            self.buffer.drain(..=newline_pos);
+            result.extend(self.process_line(&line_content));
         }
 
         result
     }
 
+    /// Commit the open block
+    fn close_block(&mut self) -> Vec<Line<'static>> {
+        let lines = self.pending();
+        self.block = Block::None;
+        lines
+    }
+
     /// Process a single line of markdown
-    fn process_line(&mut self, line: &str) -> Option<Line<'static>> {
+    fn process_line(&mut self, line: &str) -> Vec<Line<'static>> {
         let line = line.trim_end();
 
-        // Check for code block delimiter
-        if line.starts_with("```") {
-            self.in_code_block = !self.in_code_block;
-            if self.in_code_block {
-                self.code_block_lang = line[3..].to_string();
-            } else {
-                self.code_block_lang.clear();
+        if let Some((fence, highlighter)) = &mut self.code {
+            if line.trim_start().starts_with(fence.as_str())
+                && line.trim().chars().all(|c| c == '`' || c == '~')
+            {
+                self.code = None;
+                return vec![code_footer()];
             }
-            return None;
+            return vec![Line::from(highlighter.highlight(line))];
         }
 
-        // Skip processing while in code block
-        if self.in_code_block {
-            return Some(Line::styled(
-                line.to_string(),
-                Style::default().fg(Color::Rgb(180, 180, 180)),
-            ));
+        match std::mem::replace(&mut self.block, Block::None) {
+            Block::Table(mut rows) => {
+                if is_table_row(line) {
+                    rows.push(line.to_string());
+                    self.block = Block::Table(rows);
+                    return Vec::new();
+                }
+                let mut result = render_table(&rows);
+                result.extend(self.process_line(line));
+                return result;
+            }
+            Block::MaybeTable(header) => {
+                if is_delimiter_row(line) {
+                    self.block = Block::Table(vec![header, line.to_string()]);
+                    return Vec::new();
+                }
+                let mut result = vec![render_text_line(&header)];
+                result.extend(self.process_line(line));
+                return result;
+            }
+            Block::None => {}
         }
 
-        // Check for headers
-        if line.starts_with('#') {
-            return self.process_header(line);
+        // Check for code block delimiter
+        if let Some((fence, lang)) = parse_fence(line) {
+            let highlighter = SpanHighlighter::new(&lang);
+            self.code = Some((fence, highlighter));
+            return vec![code_header(&lang)];
         }
 
-        // Check for blockquote
-        if line.starts_with('>') {
-            self.in_blockquote = true;
-            return Some(Line::styled(
-                line[1..].trim().to_string(),
-                Style::default()
-                    .fg(Color::DarkGray)
-                    .add_modifier(Modifier::ITALIC),
-            ));
+        // Rows may only start a table when they look like one
+        if line.trim_start().starts_with('|') {
+            self.block = Block::Table(vec![line.to_string()]);
+            return Vec::new();
         }
-
-        // Check for list items
-        if self.is_list_item(line) {
-            return Some(Line::styled(
-                line.to_string(),
-                Style::default().fg(Color::White),
-            ));
+        if line.contains('|') && quote_depth(line).0 == 0 {
+            self.block = Block::MaybeTable(line.to_string());
+            return Vec::new();
         }
 
-        // Regular text
-        Some(Line::styled(line.to_string(), Style::default().fg(Color::White)))
+        vec![render_text_line(line)]
     }
+}
 
-    /// Process a markdown header
-    fn process_header(&mut self, line: &str) -> Option<Line<'static>> {
-        let hashes = line.chars().take_while(|&c| c == '#').count();
-        let content = line[hashes..].trim();
+/// Fence and language of a code block opening line
+fn parse_fence(line: &str) -> Option<(String, String)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let fence: String = trimmed.chars().take_while(|c| *c == marker).collect();
+    if fence.len() < 3 {
+        return None;
+    }
+    Some((fence.clone(), trimmed[fence.len()..].trim().to_string()))
+}
 
-        let style = match hashes {
-            1 => Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-            2 => Style::default()
+fn code_header(lang: &str) -> Line<'static> {
+    let label = if lang.is_empty() { "code" } else { lang };
+    Line::styled(
+        format!("┌─ {} ", label),
+        Style::default().fg(Color::DarkGray),
+    )
+}
+
+fn code_footer() -> Line<'static> {
+    Line::styled("└─".to_string(), Style::default().fg(Color::DarkGray))
+}
+
+/// Number of `>` markers starting the line and the text after them
+fn quote_depth(line: &str) -> (usize, &str) {
+    let mut depth = 0;
+    let mut rest = line.trim_start();
+    while let Some(inner) = rest.strip_prefix('>') {
+        depth += 1;
+        rest = inner.strip_prefix(' ').unwrap_or(inner);
+    }
+    if depth == 0 { (0, line) } else { (depth, rest) }
+}
+
+/// Render a line that isn't part of a code block or table
+fn render_text_line(line: &str) -> Line<'static> {
+    let (depth, text) = quote_depth(line);
+    if depth == 0 {
+        return Line::from(render_block_spans(line, Style::default().fg(Color::White)));
+    }
+
+    let mut spans = vec![Span::styled(
+        "│ ".repeat(depth),
+        Style::default().fg(Color::DarkGray),
+    )];
+    spans.extend(render_block_spans(
+        text,
+        Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC),
+    ));
+    Line::from(spans)
+}
+
+/// Spans for a header, list item, rule or paragraph line
+fn render_block_spans(line: &str, base: Style) -> Vec<Span<'static>> {
+    let trimmed = line.trim_start();
+
+    if let Some((level, text)) = parse_header(trimmed) {
+        let style = match level {
+            1 | 2 => Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
             3 => Style::default()
@@ -166,19 +248,287 @@ impl MarkdownStream {
                 .add_modifier(Modifier::BOLD),
             _ => Style::default().fg(Color::Green),
         };
+        return inline_spans(text, style);
+    }
+
+    if is_rule(trimmed) {
+        return vec![Span::styled(
+            "─".repeat(40),
+            Style::default().fg(Color::DarkGray),
+        )];
+    }
+
+    let indent = line.len() - trimmed.len();
+    if let Some((marker, text)) = parse_list_item(trimmed) {
+        let depth = indent / LIST_INDENT;
+        let marker = match marker {
+            Some(number) => number,
+            None => BULLETS[depth % BULLETS.len()].to_string(),
+        };
+        let mut spans = vec![
+            Span::raw("  ".repeat(depth)),
+            Span::styled(format!("{} ", marker), Style::default().fg(Color::Cyan)),
+        ];
+        spans.extend(inline_spans(text, base));
+        return spans;
+    }
+
+    // Continuation text of a list item keeps its indentation
+    let mut spans = Vec::new();
+    if indent > 0 {
+        spans.push(Span::raw(" ".repeat(indent)));
+    }
+    spans.extend(inline_spans(trimmed, base));
+    spans
+}
+
+/// Level and text of an ATX header
+fn parse_header(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((level, rest.trim()))
+    } else {
+        None
+    }
+}
+
+/// Whether a line is a thematic break such as `---` or `***`
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&marker| compact.chars().all(|c| c == marker))
+}
+
+/// Marker and text of a list item; the marker is `None` for bullets and the
+/// number (e.g. `2.`) for ordered items
+fn parse_list_item(line: &str) -> Option<(Option<String>, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = line.strip_prefix(bullet) {
+            return Some((None, text));
+        }
+    }
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let rest = &line[digits..];
+    let delimiter = rest.chars().next().filter(|c| *c == '.' || *c == ')')?;
+    let text = rest[1..].strip_prefix(' ')?;
+    Some((Some(format!("{}{}", &line[..digits], delimiter)), text))
+}
+
+/// Whether a line continues a table
+fn is_table_row(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && trimmed.contains('|')
+}
+
+/// Whether a line is a table's delimiter row, e.g. `|---|:--:|`
+fn is_delimiter_row(line: &str) -> bool {
+    let cells = split_row(line);
+    !cells.is_empty()
+        && line.contains('-')
+        && cells.iter().all(|cell| {
+            let cell = cell.trim_start_matches(':').trim_end_matches(':');
+            !cell.is_empty() && cell.chars().all(|c| c == '-')
+        })
+}
+
+/// Cells of a table row, without the outer pipes
+fn split_row(line: &str) -> Vec<String> {
+    let trimmed = line.trim();
+    let trimmed = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let trimmed = trimmed.strip_suffix('|').unwrap_or(trimmed);
+
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = trimmed.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn parse_alignment(cell: &str) -> Align {
+    match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => Align::Center,
+        (false, true) => Align::Right,
+        _ => Align::Left,
+    }
+}
 
-        Some(Line::styled(content.to_string(), style))
+/// Render table rows with aligned columns and box borders
+fn render_table(rows: &[String]) -> Vec<Line<'static>> {
+    let has_header = rows.len() > 1 && is_delimiter_row(&rows[1]);
+    let aligns: Vec<Align> = if has_header {
+        split_row(&rows[1])
+            .iter()
+            .map(|c| parse_alignment(c))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let cells: Vec<Vec<Vec<Span<'static>>>> = rows
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !(has_header && *i == 1))
+        .map(|(i, row)| {
+            let style = if has_header && i == 0 {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            split_row(row)
+                .iter()
+                .map(|cell| inline_spans(cell, style))
+                .collect()
+        })
+        .collect();
+
+    let columns = cells.iter().map(Vec::len).max().unwrap_or(0);
+    let mut widths = vec![1; columns];
+    for row in &cells {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(spans_width(cell));
+        }
+    }
+
+    let border = Style::default().fg(Color::DarkGray);
+    let rule = |left: &str, middle: &str, right: &str| {
+        let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+        Line::styled(
+            format!("{}{}{}", left, segments.join(middle), right),
+            border,
+        )
+    };
+
+    let mut lines = vec![rule("┌", "┬", "┐")];
+    for (i, row) in cells.into_iter().enumerate() {
+        let mut spans = Vec::new();
+        let mut row = row.into_iter();
+        for (column, width) in widths.iter().enumerate() {
+            let cell = row.next().unwrap_or_default();
+            let gap = width - spans_width(&cell);
+            let (before, after) = match aligns.get(column).copied().unwrap_or(Align::Left) {
+                Align::Left => (0, gap),
+                Align::Right => (gap, 0),
+                Align::Center => (gap / 2, gap - gap / 2),
+            };
+            spans.push(Span::styled("│ ", border));
+            spans.push(Span::raw(" ".repeat(before)));
+            spans.extend(cell);
+            spans.push(Span::raw(" ".repeat(after + 1)));
+        }
+        spans.push(Span::styled("│", border));
+        lines.push(Line::from(spans));
+        if has_header && i == 0 {
+            lines.push(rule("├", "┼", "┤"));
+        }
     }
+    lines.push(rule("└", "┴", "┘"));
+    lines
+}
 
-    /// Check if a line is a list item
-    fn is_list_item(&self, line: &str) -> bool {
-        let trimmed = line.trim();
-        trimmed.starts_with("- ")
-            || trimmed.starts_with("* ")
-            || trimmed.starts_with("+ ")
-            || trimmed.chars().next().map_or(false, |c| c.is_ascii_digit())
-                && trimmed.chars().nth(1) == Some('.')
+fn spans_width(spans: &[Span<'_>]) -> usize {
+    spans.iter().map(|s| s.content.width()).sum()
+}
+
+/// Spans for inline code, bold, italic, strikethrough and links
+fn inline_spans(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    let styled = [
+        (
+            "`",
+            "`",
+            Style::default()
+                .fg(Color::Rgb(230, 230, 230))
+                .bg(Color::Rgb(45, 45, 45)),
+        ),
+        (
+            "**",
+            "**",
+            base.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        (
+            "__",
+            "__",
+            base.fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        (
+            "~~",
+            "~~",
+            base.fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT),
+        ),
+        (
+            "*",
+            "*",
+            base.fg(Color::Cyan).add_modifier(Modifier::ITALIC),
+        ),
+    ];
+
+    'outer: while let Some(c) = rest.chars().next() {
+        for (open, close, style) in &styled {
+            if let Some(inner) = rest.strip_prefix(open)
+                && let Some(end) = inner.find(close)
+                && end > 0
+            {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.push(Span::styled(inner[..end].to_string(), *style));
+                rest = &inner[end + close.len()..];
+                continue 'outer;
+            }
+        }
+        if c == '['
+            && let Some(label_end) = rest.find("](")
+            && let Some(url_len) = rest[label_end + 2..].find(')')
+        {
+            if !plain.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut plain), base));
+            }
+            let label = &rest[1..label_end];
+            let url = &rest[label_end + 2..label_end + 2 + url_len];
+            spans.push(Span::styled(
+                label.to_string(),
+                base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+            ));
+            if url != label {
+                spans.push(Span::styled(
+                    format!(" ({})", url),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            rest = &rest[label_end + 3 + url_len..];
+            continue;
+        }
+        plain.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(plain, base));
     }
+    spans
 }
 
 /// Streaming markdown renderer with inline formatting
@@ -208,6 +558,11 @@ impl StreamingMarkdown {
         self.stream.push(&clean)
     }
 
+    /// The block still being received
+    pub fn pending(&self) -> Vec<Line<'static>> {
+        self.stream.pending()
+    }
+
     /// Finalize the stream and return any remaining lines
     pub fn finalize(&mut self) -> Vec<Line<'static>> {
         self.stream.finalize()
@@ -239,6 +594,14 @@ impl StreamingMarkdown {
 mod tests {
     use super::*;
 
+    fn text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    fn texts(lines: &[Line<'_>]) -> Vec<String> {
+        lines.iter().map(text).collect()
+    }
+
     #[test]
     fn test_markdown_stream_basic() {
         let mut stream = MarkdownStream::new();
@@ -257,17 +620,94 @@ mod tests {
     #[test]
     fn test_markdown_stream_code_block() {
         let mut stream = MarkdownStream::new();
-        stream.push("```rust\n");
-        stream.push("let x = 1;\n");
-        stream.push("```\n");
-        let lines = stream.finalize();
-        assert_eq!(lines.len(), 3);
+        let mut lines = stream.push("```rust\n");
+        lines.extend(stream.push("let x = 1;\n"));
+        lines.extend(stream.push("```\n"));
+        lines.extend(stream.finalize());
+        assert_eq!(texts(&lines), ["┌─ rust ", "let x = 1;", "└─"]);
+        assert!(lines[1].spans.len() > 1);
     }
 
     #[test]
     fn test_markdown_stream_headers() {
         let mut stream = MarkdownStream::new();
         let lines = stream.push("# Header 1\n");
-        assert_eq!(lines.len(), 1);
+        assert_eq!(texts(&lines), ["Header 1"]);
+    }
+
+    #[test]
+    fn test_table_reflows_until_it_ends() {
+        let mut stream = MarkdownStream::new();
+        assert!(
+            stream
+                .push("| Name | N |\n|---|--:|\n| a | 1 |\n")
+                .is_empty()
+        );
+        assert_eq!(
+            texts(&stream.pending()),
+            [
+                "┌──────┬───┐",
+                "│ Name │ N │",
+                "├──────┼───┤",
+                "│ a    │ 1 │",
+                "└──────┴───┘",
+            ]
+        );
+
+        // A wider row re-flows the whole table
+        assert!(stream.push("| longer | 10 |\n").is_empty());
+        assert_eq!(texts(&stream.pending())[3], "│ a      │  1 │");
+
+        let lines = stream.push("\nAfter\n");
+        assert_eq!(lines.len(), 8);
+        assert_eq!(text(&lines[4]), "│ longer │ 10 │");
+        assert_eq!(text(&lines[7]), "After");
+        assert!(stream.pending().is_empty());
+    }
+
+    #[test]
+    fn test_table_without_outer_pipes() {
+        let mut stream = MarkdownStream::new();
+        assert!(stream.push("a | b\n").is_empty());
+        assert!(stream.push("--- | ---\nx | y\n").is_empty());
+        assert_eq!(texts(&stream.finalize())[3], "│ x │ y │");
+
+        // A line with a pipe that isn't followed by a delimiter row is text
+        let lines = stream.push("either a | b\nplain\n");
+        assert_eq!(texts(&lines), ["either a | b", "plain"]);
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        let mut stream = MarkdownStream::new();
+        let lines = stream.push("- one\n  - two\n    - three\n      more\n1. first\n");
+        assert_eq!(
+            texts(&lines),
+            ["• one", "  ◦ two", "    ▪ three", "      more", "1. first"]
+        );
+    }
+
+    #[test]
+    fn test_nested_block_quotes() {
+        let mut stream = MarkdownStream::new();
+        let lines = stream.push("> outer\n> > inner **bold**\n> - item\n");
+        assert_eq!(texts(&lines), ["│ outer", "│ │ inner bold", "│ • item"]);
+        assert!(
+            lines[1]
+                .spans
+                .iter()
+                .any(|s| s.content == "bold" && s.style.add_modifier.contains(Modifier::BOLD))
+        );
+    }
+
+    #[test]
+    fn test_inline_spans() {
+        let spans = inline_spans("use `x` and [docs](https://d)", Style::default());
+        let contents: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(contents, ["use ", "x", " and ", "docs", " (https://d)"]);
+        assert_eq!(
+            text(&Line::from(inline_spans("2 * 3", Style::default()))),
+            "2 * 3"
+        );
     }
 }
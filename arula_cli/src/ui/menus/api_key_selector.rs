@@ -1,6 +1,7 @@
 //! API key configuration menu for ARULA CLI
 //! Matches modern menu pattern from provider_menu.rs and model_selector.rs
 
+use arula_core::utils::icons::Icon;
 use crate::app::App;
 use crate::ui::menus::common::{draw_modern_box, draw_selected_item};
 use crate::ui::output::OutputHandler;
@@ -197,7 +198,7 @@ impl ApiKeySelector {
 
         // Draw title/header (like original)
         let title_y = start_y + 1;
-        let title = "🔑 API Key Configuration";
+        let title = format!("{} API Key Configuration", Icon::Key);
         let title_x = if menu_width > title.len() as u16 {
             start_x + (menu_width - title.len() as u16) / 2
         } else {
@@ -0,0 +1,216 @@
+//! Branch tree picker for ARULA CLI
+//! Shows saved conversations with their branches nested under them
+
+use arula_core::utils::icons::Icon;
+use anyhow::Result;
+use console::style;
+use crossterm::{
+    cursor::MoveTo,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    terminal::{self, ClearType},
+    ExecutableCommand, QueueableCommand,
+};
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+use super::common::{draw_modern_box, draw_selected_item, MenuResult};
+use crate::app::App;
+use crate::utils::conversation::{conversation_tree, Conversation, ConversationSummary};
+
+/// Number of tree rows shown at once
+const VISIBLE_ROWS: usize = 12;
+
+pub struct BranchMenu {
+    selected_index: usize,
+    scroll_offset: usize,
+    /// Tree rows: depth and conversation
+    rows: Vec<(usize, ConversationSummary)>,
+}
+
+impl BranchMenu {
+    pub fn new() -> Self {
+        Self {
+            selected_index: 0,
+            scroll_offset: 0,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Show the tree and return the conversation to switch to, if any
+    pub fn show(&mut self, app: &App) -> Result<MenuResult> {
+        let summaries = Conversation::list_all(&std::env::current_dir()?)?;
+        self.rows = conversation_tree(&summaries)
+            .into_iter()
+            .map(|(depth, summary)| (depth, summary.clone()))
+            .collect();
+
+        // Start on the current conversation
+        let current_id = app
+            .current_conversation
+            .as_ref()
+            .map(|conv| conv.metadata.conversation_id.clone());
+        if let Some(index) = self
+            .rows
+            .iter()
+            .position(|(_, s)| Some(&s.conversation_id) == current_id.as_ref())
+        {
+            self.selected_index = index;
+            self.scroll_offset = index.saturating_sub(VISIBLE_ROWS - 1);
+        }
+
+        stdout().execute(terminal::Clear(ClearType::All))?;
+
+        // Clear any pending events
+        std::thread::sleep(Duration::from_millis(20));
+        while event::poll(Duration::from_millis(0))? {
+            let _ = event::read()?;
+        }
+
+        let result = loop {
+            self.render(current_id.as_deref())?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let key_event = match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+                Event::Resize(_, _) => {
+                    stdout().execute(terminal::Clear(ClearType::All))?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            match key_event.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.selected_index = self.selected_index.saturating_sub(1);
+                    if self.selected_index < self.scroll_offset {
+                        self.scroll_offset = self.selected_index;
+                    }
+                }
+                KeyCode::Down | KeyCode::Char('j')
+                    if self.selected_index + 1 < self.rows.len() =>
+                {
+                    self.selected_index += 1;
+                    if self.selected_index >= self.scroll_offset + VISIBLE_ROWS {
+                        self.scroll_offset = self.selected_index + 1 - VISIBLE_ROWS;
+                    }
+                }
+                KeyCode::Enter => {
+                    break match self.rows.get(self.selected_index) {
+                        Some((_, summary)) => {
+                            MenuResult::LoadConversation(summary.conversation_id.clone())
+                        }
+                        None => MenuResult::BackToMain,
+                    };
+                }
+                KeyCode::Esc | KeyCode::Char('q') => break MenuResult::BackToMain,
+                KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break MenuResult::BackToMain;
+                }
+                _ => {}
+            }
+            stdout().execute(terminal::Clear(ClearType::All))?;
+        };
+
+        stdout().execute(terminal::Clear(ClearType::All))?;
+        stdout().flush()?;
+        Ok(result)
+    }
+
+    /// One tree row: indentation, title, size and checkpoints
+    fn row_text(depth: usize, summary: &ConversationSummary, is_current: bool) -> String {
+        let indent = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}└─ ", "   ".repeat(depth - 1))
+        };
+        let marker = if is_current { "● " } else { "" };
+        let mut line = format!(
+            "{}{}{} ({} msgs)",
+            indent, marker, summary.title, summary.message_count
+        );
+        if let Some(point) = &summary.branch_point {
+            line.push_str(&format!(" from {}", point));
+        }
+        if !summary.checkpoints.is_empty() {
+            line.push_str(&format!(" ⚑ {}", summary.checkpoints.join(", ")));
+        }
+        line
+    }
+
+    fn render(&self, current_id: Option<&str>) -> Result<()> {
+        let (cols, rows) = terminal::size()?;
+
+        let menu_width = 90.min(cols.saturating_sub(4));
+        let menu_height = VISIBLE_ROWS as u16 + 6;
+        let start_x = (cols - menu_width) / 2;
+        let start_y = if rows > menu_height {
+            (rows - menu_height) / 2
+        } else {
+            0
+        };
+
+        draw_modern_box(start_x, start_y, menu_width, menu_height)?;
+
+        let title = format!("{} Conversation Branches", Icon::History);
+        let title_x = start_x + (menu_width / 2).saturating_sub(title.chars().count() as u16 / 2);
+        stdout()
+            .queue(MoveTo(title_x, start_y + 1))?
+            .queue(SetForegroundColor(Color::AnsiValue(
+                crate::utils::colors::MISC_ANSI,
+            )))?
+            .queue(Print(style(title).bold()))?
+            .queue(ResetColor)?;
+
+        let items_start_y = start_y + 3;
+        if self.rows.is_empty() {
+            stdout()
+                .queue(MoveTo(start_x + 4, items_start_y))?
+                .queue(SetForegroundColor(Color::Grey))?
+                .queue(Print("No saved conversations found."))?
+                .queue(ResetColor)?;
+        }
+
+        let max_width = (menu_width as usize).saturating_sub(8);
+        let end_index = (self.scroll_offset + VISIBLE_ROWS).min(self.rows.len());
+        for (i, (depth, summary)) in self.rows[self.scroll_offset..end_index].iter().enumerate() {
+            let index = self.scroll_offset + i;
+            let y = items_start_y + i as u16;
+            let is_current = Some(summary.conversation_id.as_str()) == current_id;
+            let text = Self::row_text(*depth, summary, is_current);
+
+            if index == self.selected_index {
+                draw_selected_item(start_x, y, menu_width, &text)?;
+            } else {
+                stdout()
+                    .queue(MoveTo(start_x + 4, y))?
+                    .queue(SetForegroundColor(Color::AnsiValue(
+                        crate::utils::colors::MISC_ANSI,
+                    )))?
+                    .queue(Print(text.chars().take(max_width).collect::<String>()))?
+                    .queue(ResetColor)?;
+            }
+        }
+
+        let help_text = "↑↓ Navigate • Enter Open • ESC Back • /branch <checkpoint> to branch";
+        let help_x = start_x + (menu_width / 2).saturating_sub(help_text.chars().count() as u16 / 2);
+        stdout()
+            .queue(MoveTo(help_x, start_y + menu_height - 1))?
+            .queue(SetForegroundColor(Color::AnsiValue(
+                crate::utils::colors::AI_HIGHLIGHT_ANSI,
+            )))?
+            .queue(Print(help_text))?
+            .queue(ResetColor)?;
+
+        stdout().flush()?;
+        Ok(())
+    }
+}
+
+impl Default for BranchMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -6,10 +6,14 @@
 //! - Shared drawing functions (box, item rendering)
 //! - Menu state management
 
+use arula_core::utils::icons::Icon;
 use anyhow::Result;
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{self, Event, KeyEvent, KeyEventKind},
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent,
+        MouseEventKind,
+    },
     style::{Print, ResetColor, SetForegroundColor},
     terminal::{self, size},
     ExecutableCommand, QueueableCommand,
@@ -30,6 +34,9 @@ pub enum MenuResult {
     ConfigurationUpdated,
     LoadConversation(String),
     NewConversation,
+    BranchCreated(String),
+    BranchSwitched(String),
+    GeneratePrDescription,
 }
 
 /// Internal menu action for flow control
@@ -80,6 +87,19 @@ impl MenuUtils {
         Ok(())
     }
 
+    /// Run a full-screen view on the alternate screen, then go back to the
+    /// chat TUI. Raw mode, which the chat TUI runs in, is re-enabled even
+    /// when the view or the terminal restore fails.
+    pub fn run_overlay<T>(run: impl FnOnce() -> Result<T>) -> Result<T> {
+        let result = Self::setup_terminal().and_then(|_| run());
+        let restored = Self::restore_terminal();
+        let raw_mode = terminal::enable_raw_mode();
+        let value = result?;
+        restored?;
+        raw_mode?;
+        Ok(value)
+    }
+
     /// Wait for key event with timeout
     pub fn wait_for_key(timeout_ms: u64) -> Result<Option<KeyEvent>> {
         if event::poll(Duration::from_millis(timeout_ms))? {
@@ -166,7 +186,7 @@ impl MenuUtils {
     /// Format menu item with original selection indicator
     pub fn format_menu_item(item: &str, selected: bool) -> String {
         if selected {
-            format!("▶ {}", item)
+            format!("{} {}", Icon::Prompt, item)
         } else {
             format!("  {}", item)
         }
@@ -215,6 +235,72 @@ impl MenuState {
     }
 }
 
+/// Where a menu draws its items, for finding the item under the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemArea {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub count: usize,
+    /// Item that can't be picked, e.g. a read-only field
+    pub disabled: Option<usize>,
+}
+
+impl ItemArea {
+    /// Items of a `width` by `height` menu box centered on a `cols` by
+    /// `rows` screen, listed from the third row inside the box
+    pub fn centered(cols: u16, rows: u16, width: u16, height: u16, count: usize) -> Self {
+        Self {
+            x: cols.saturating_sub(width) / 2,
+            y: rows.saturating_sub(height) / 2 + 3,
+            width,
+            count,
+            disabled: None,
+        }
+    }
+
+    pub fn with_disabled(mut self, index: usize) -> Self {
+        self.disabled = Some(index);
+        self
+    }
+
+    /// The item on screen cell `column`, `row`
+    pub fn item_at(&self, column: u16, row: u16) -> Option<usize> {
+        if column <= self.x || column >= self.x + self.width.saturating_sub(1) {
+            return None;
+        }
+        let index = row.checked_sub(self.y)? as usize;
+        (index < self.count && self.disabled != Some(index)).then_some(index)
+    }
+}
+
+impl MenuUtils {
+    /// Turn a mouse event over a list menu into the key that does the same:
+    /// the wheel moves the selection and a click selects an item and picks
+    /// it. Other events are returned as they are.
+    pub fn mouse_as_key(event: Event, items: &ItemArea, selected: &mut usize) -> Event {
+        let Event::Mouse(MouseEvent {
+            kind, column, row, ..
+        }) = event
+        else {
+            return event;
+        };
+        let code = match kind {
+            MouseEventKind::ScrollUp => KeyCode::Up,
+            MouseEventKind::ScrollDown => KeyCode::Down,
+            MouseEventKind::Down(MouseButton::Left) => match items.item_at(column, row) {
+                Some(index) => {
+                    *selected = index;
+                    KeyCode::Enter
+                }
+                None => return event,
+            },
+            _ => return event,
+        };
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+}
+
 // =============================================================================
 // Shared Drawing Functions
 // =============================================================================
@@ -293,7 +379,7 @@ pub fn draw_selected_item(x: u16, y: u16, width: u16, text: &str) -> Result<()>
     }
 
     // Draw text with proper spacing and primary color (NO background)
-    let display_text = format!("▶ {}", text);
+    let display_text = format!("{} {}", Icon::Prompt, text);
     let safe_text = if display_text.len() > width.saturating_sub(4) as usize {
         // Truncate if too long - use character boundaries, not byte boundaries
         let safe_len = width.saturating_sub(7) as usize;
@@ -303,7 +389,7 @@ pub fn draw_selected_item(x: u16, y: u16, width: u16, text: &str) -> Result<()>
             .nth(safe_len)
             .map(|(idx, _)| idx)
             .unwrap_or(text.len());
-        format!("▶ {}...", &text[..char_end])
+        format!("{} {}...", Icon::Prompt, &text[..char_end])
     } else {
         display_text
     };
@@ -368,3 +454,42 @@ pub fn clear_menu_area(x: u16, y: u16, width: u16, height: u16) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mouse_as_key() {
+        let items = ItemArea::centered(80, 24, 40, 8, 2).with_disabled(1);
+        assert_eq!((items.x, items.y), (20, 11));
+        let mouse = |kind, column, row| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+        let mut selected = 1;
+        let click = MouseEventKind::Down(MouseButton::Left);
+        assert_eq!(
+            MenuUtils::mouse_as_key(mouse(click, 30, 11), &items, &mut selected),
+            key(KeyCode::Enter)
+        );
+        assert_eq!(selected, 0);
+
+        // Disabled items, the box border and rows past the items aren't picked
+        for (column, row) in [(30, 12), (20, 11), (30, 13)] {
+            let event = mouse(click, column, row);
+            assert_eq!(MenuUtils::mouse_as_key(event.clone(), &items, &mut selected), event);
+        }
+        assert_eq!(
+            MenuUtils::mouse_as_key(mouse(MouseEventKind::ScrollDown, 0, 0), &items, &mut selected),
+            key(KeyCode::Down)
+        );
+        assert_eq!(selected, 0);
+    }
+}
@@ -1,11 +1,14 @@
 //! Configuration menu functionality for ARULA CLI
 
+use arula_core::utils::icons::Icon;
 use crate::app::App;
 use crate::ui::menus::api_key_selector::ApiKeySelector;
 use crate::ui::menus::common::{
-    draw_modern_box, draw_selected_item, MenuAction, MenuResult, MenuState, MenuUtils,
+    draw_modern_box, draw_selected_item, ItemArea, MenuAction, MenuResult, MenuState,
+    MenuUtils,
 };
 use crate::ui::menus::dialogs::Dialogs;
+use crate::ui::menus::generation_settings_menu::GenerationSettingsMenu;
 use crate::ui::menus::model_selector::ModelSelector;
 use crate::ui::menus::provider_menu::ProviderMenu;
 use crate::ui::menus::zai_endpoint_selector::ZaiEndpointSelector;
@@ -20,6 +23,10 @@ use crossterm::{
 use std::io::{stdout, Write};
 use std::time::Duration;
 
+/// Size of the menu box
+const MENU_WIDTH: u16 = 60;
+const MENU_HEIGHT: u16 = 14;
+
 /// Configuration menu options
 #[derive(Debug, Clone)]
 pub enum ConfigMenuItem {
@@ -31,6 +38,7 @@ pub enum ConfigMenuItem {
     ThinkingMode,
     WebSearch,
     OllamaTools,
+    GenerationSettings,
 }
 
 impl ConfigMenuItem {
@@ -43,6 +51,7 @@ impl ConfigMenuItem {
             ConfigMenuItem::ThinkingMode,
             ConfigMenuItem::WebSearch,
             ConfigMenuItem::OllamaTools,
+            ConfigMenuItem::GenerationSettings,
         ]
     }
 
@@ -67,6 +76,8 @@ impl ConfigMenuItem {
             items.push(ConfigMenuItem::OllamaTools);
         }
 
+        items.push(ConfigMenuItem::GenerationSettings);
+
         items
     }
 
@@ -80,6 +91,7 @@ impl ConfigMenuItem {
             ConfigMenuItem::ThinkingMode => "Thinking Mode",
             ConfigMenuItem::WebSearch => "Web Search",
             ConfigMenuItem::OllamaTools => "Ollama Tools",
+            ConfigMenuItem::GenerationSettings => "Generation Settings",
         }
     }
 
@@ -93,6 +105,7 @@ impl ConfigMenuItem {
             ConfigMenuItem::ThinkingMode => "Toggle thinking mode (show AI reasoning)",
             ConfigMenuItem::WebSearch => "Toggle web search provider (DuckDuckGo/Z.AI)",
             ConfigMenuItem::OllamaTools => "Enable/disable tool calling for Ollama models",
+            ConfigMenuItem::GenerationSettings => "Temperature, top_p and max tokens",
         }
     }
 }
@@ -105,6 +118,7 @@ pub struct ConfigMenu {
     model_selector: ModelSelector,
     api_key_selector: ApiKeySelector,
     zai_endpoint_selector: ZaiEndpointSelector,
+    generation_settings_menu: GenerationSettingsMenu,
     dialogs: Dialogs,
 }
 
@@ -123,6 +137,7 @@ impl ConfigMenu {
             model_selector: ModelSelector::new(),
             api_key_selector: ApiKeySelector::new(),
             zai_endpoint_selector: ZaiEndpointSelector::new(),
+            generation_settings_menu: GenerationSettingsMenu::new(),
             dialogs: Dialogs::new(),
         }
     }
@@ -186,7 +201,12 @@ impl ConfigMenu {
 
             // Wait for input event with timeout
             if crossterm::event::poll(Duration::from_millis(100))? {
-                match crossterm::event::read()? {
+                let event = MenuUtils::mouse_as_key(
+                    crossterm::event::read()?,
+                    &self.item_area(app)?,
+                    &mut self.state.selected_index,
+                );
+                match event {
                     Event::Key(key_event) => {
                         // Only handle key press events
                         if key_event.kind != KeyEventKind::Press {
@@ -287,6 +307,21 @@ impl ConfigMenu {
         }
     }
 
+    /// Where `render` draws the items; a read-only API URL can't be clicked
+    fn item_area(&self, app: &App) -> Result<ItemArea> {
+        let (cols, rows) = crossterm::terminal::size()?;
+        let menu_width = MENU_WIDTH.min(cols.saturating_sub(4));
+        let items = ItemArea::centered(cols, rows, menu_width, MENU_HEIGHT, self.items.len());
+        if app
+            .config
+            .is_field_editable(crate::utils::config::ProviderField::ApiUrl)
+        {
+            Ok(items)
+        } else {
+            Ok(items.with_disabled(2))
+        }
+    }
+
     /// Render the configuration menu with original styling (1:1 from overlay_menu.rs)
     fn render(&self, app: &App, _output: &mut OutputHandler) -> Result<()> {
         let (cols, rows) = crossterm::terminal::size()?;
@@ -297,7 +332,7 @@ impl ConfigMenu {
         }
 
         let config = app.get_config();
-        let menu_width = 60.min(cols.saturating_sub(4));
+        let menu_width = MENU_WIDTH.min(cols.saturating_sub(4));
 
         // Calculate max width for menu items (menu_width - 6 for padding and marker)
         let max_item_width = menu_width.saturating_sub(6) as usize;
@@ -398,7 +433,7 @@ impl ConfigMenu {
             ));
         }
 
-        let menu_height = 14; // Increased height to accommodate new menu items
+        let menu_height = MENU_HEIGHT;
         let start_x = (cols - menu_width) / 2;
         let start_y = (rows - menu_height) / 2;
 
@@ -411,7 +446,7 @@ impl ConfigMenu {
 
         // Draw title with modern styling
         let title_y = start_y + 1;
-        let title = "⚙ SETTINGS";
+        let title = format!("{} SETTINGS", Icon::Settings);
         let title_len = title.len() as u16;
         let title_x = if menu_width > title_len + 2 {
             start_x + menu_width / 2 - title_len / 2
@@ -565,6 +600,17 @@ impl ConfigMenu {
                     item.description().to_string(),
                 )
             }
+            ConfigMenuItem::GenerationSettings => {
+                let settings = app.config.get_generation_settings();
+                (
+                    Some(format!(
+                        "temp {} · {} tokens",
+                        settings.temperature(),
+                        settings.max_tokens()
+                    )),
+                    item.description().to_string(),
+                )
+            }
         }
     }
 
@@ -639,6 +685,13 @@ impl ConfigMenu {
                     self.toggle_ollama_tools(app, output)?;
                     Ok(MenuAction::Continue)
                 }
+                ConfigMenuItem::GenerationSettings => {
+                    self.generation_settings_menu.show(app, output)?;
+                    while crossterm::event::poll(Duration::from_millis(0))? {
+                        let _ = crossterm::event::read()?;
+                    }
+                    Ok(MenuAction::Continue)
+                }
             }
         } else {
             Ok(MenuAction::Continue)
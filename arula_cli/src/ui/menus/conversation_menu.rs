@@ -1,5 +1,7 @@
 //! Conversation history management menu
 
+use arula_core::storage::Storage;
+use arula_core::utils::icons::Icon;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use console::style;
@@ -330,7 +332,7 @@ impl ConversationMenu {
 
             // Draw title
             let title_y = start_y + 1;
-            let title = "📚 Conversation History";
+            let title = format!("{} Conversation History", Icon::History);
             let title_x = if menu_width > title.len() as u16 {
                 start_x + (menu_width - title.len() as u16) / 2
             } else {
@@ -528,6 +530,7 @@ impl ConversationMenu {
             if matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
                 let current_dir = std::env::current_dir()?;
                 Conversation::delete(&current_dir, conversation_id)?;
+                let _ = Storage::with(|s| s.remove_session(conversation_id));
 
                 // Reload conversation list
                 self.conversations = Conversation::list_all(&current_dir)?;
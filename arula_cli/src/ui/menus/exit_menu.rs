@@ -1,6 +1,6 @@
 //! Exit confirmation menu for ARULA CLI
 
-use crate::ui::menus::common::{draw_modern_box, draw_selected_item, MenuUtils};
+use crate::ui::menus::common::{draw_modern_box, draw_selected_item, ItemArea, MenuUtils};
 use crate::ui::output::OutputHandler;
 use crate::utils::colors::{ColorTheme, AI_HIGHLIGHT_ANSI, MISC_ANSI};
 use anyhow::Result;
@@ -12,6 +12,10 @@ use crossterm::{
 };
 use std::io::{stdout, Write};
 
+/// Size of the menu box
+const MENU_WIDTH: u16 = 40;
+const MENU_HEIGHT: u16 = 8;
+
 /// Exit confirmation menu handler
 pub struct ExitMenu {
     options: Vec<String>,
@@ -50,12 +54,25 @@ impl ExitMenu {
     fn run_menu_loop(&mut self) -> Result<bool> {
         let mut selected_index = 0; // 0 = Stay, 1 = Exit
 
+        let mut needs_render = true;
+
         loop {
             // Render menu
-            self.render(selected_index)?;
+            if needs_render {
+                self.render(selected_index)?;
+            }
+            needs_render = true;
 
             // Handle input
-            match event::read()? {
+            let (cols, rows) = terminal::size()?;
+            let items = ItemArea::centered(
+                cols,
+                rows,
+                MENU_WIDTH.min(cols.saturating_sub(4)),
+                MENU_HEIGHT,
+                self.options.len(),
+            );
+            match MenuUtils::mouse_as_key(event::read()?, &items, &mut selected_index) {
                 Event::Key(key_event) => {
                     if key_event.kind != KeyEventKind::Press {
                         continue;
@@ -97,6 +114,10 @@ impl ExitMenu {
                 Event::Resize(_, _) => {
                     // Continue loop to re-render
                 }
+                Event::Mouse(_) => {
+                    // Pointer moves don't change anything
+                    needs_render = false;
+                }
                 _ => {}
             }
         }
@@ -109,8 +130,8 @@ impl ExitMenu {
         // Clear screen
         stdout().execute(terminal::Clear(terminal::ClearType::All))?;
 
-        let menu_width = 40.min(cols.saturating_sub(4));
-        let menu_height = 8;
+        let menu_width = MENU_WIDTH.min(cols.saturating_sub(4));
+        let menu_height = MENU_HEIGHT;
         let start_x = (cols - menu_width) / 2;
         let start_y = (rows - menu_height) / 2;
 
@@ -0,0 +1,206 @@
+//! Generation settings page for ARULA CLI
+//! Edits temperature, top_p and max_tokens of the active provider
+
+use crate::app::App;
+use crate::ui::menus::common::draw_modern_box;
+use crate::ui::menus::dialogs::Dialogs;
+use crate::ui::output::OutputHandler;
+use anyhow::Result;
+use arula_core::utils::config::GenerationSettings;
+use arula_core::utils::icons::Icon;
+use console::style;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::{Print, ResetColor, SetForegroundColor},
+    terminal, ExecutableCommand, QueueableCommand,
+};
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+/// One editable row: setting name, label and hint
+const ROWS: [(&str, &str, &str); 3] = [
+    (
+        "temperature",
+        "Temperature",
+        "0.0-2.0, lower is more focused",
+    ),
+    ("top_p", "Top P", "0.0-1.0, unset leaves it to the provider"),
+    ("max_tokens", "Max Tokens", "Longest response, in tokens"),
+];
+
+/// Generation settings page
+pub struct GenerationSettingsMenu {
+    dialogs: Dialogs,
+}
+
+impl GenerationSettingsMenu {
+    pub fn new() -> Self {
+        Self {
+            dialogs: Dialogs::new(),
+        }
+    }
+
+    /// Show the page until ESC is pressed
+    pub fn show(&self, app: &mut App, output: &mut OutputHandler) -> Result<()> {
+        stdout().execute(terminal::Clear(terminal::ClearType::All))?;
+        let mut selected_idx = 0;
+
+        // Clear any pending events
+        std::thread::sleep(Duration::from_millis(20));
+        while event::poll(Duration::from_millis(0))? {
+            let _ = event::read()?;
+        }
+
+        loop {
+            self.render(&app.config.get_generation_settings(), selected_idx)?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let Event::Key(key_event) = event::read()? else {
+                continue;
+            };
+            if key_event.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let (name, label, hint) = ROWS[selected_idx];
+            match key_event.code {
+                KeyCode::Up => selected_idx = selected_idx.saturating_sub(1),
+                KeyCode::Down => selected_idx = (selected_idx + 1).min(ROWS.len() - 1),
+                KeyCode::Enter => {
+                    let current = current_value(&app.config.get_generation_settings(), name);
+                    let prompt = format!("{} ({}, 'default' to reset):", label, hint);
+                    if let Some(value) =
+                        self.dialogs
+                            .input_dialog(&prompt, current.as_deref(), output)?
+                    {
+                        self.apply(app, output, name, &value)?;
+                    }
+                }
+                KeyCode::Char('r') | KeyCode::Delete | KeyCode::Backspace => {
+                    self.apply(app, output, name, "default")?;
+                }
+                KeyCode::Esc => break,
+                KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => break,
+                _ => {}
+            }
+        }
+
+        stdout().execute(terminal::Clear(terminal::ClearType::All))?;
+        stdout().flush()?;
+        Ok(())
+    }
+
+    /// Save a setting and rebuild the client so the next request uses it
+    fn apply(
+        &self,
+        app: &mut App,
+        output: &mut OutputHandler,
+        name: &str,
+        value: &str,
+    ) -> Result<()> {
+        match app.config.set_generation_setting(name, value) {
+            Ok(()) => {
+                let _ = app.initialize_agent_client();
+                output.print_system(&format!(
+                    "{} Generation settings: {}",
+                    Icon::Success,
+                    app.config.get_generation_settings().summary()
+                ))?;
+            }
+            Err(e) => output.print_error(&e.to_string())?,
+        }
+        Ok(())
+    }
+
+    fn render(&self, settings: &GenerationSettings, selected_idx: usize) -> Result<()> {
+        let (cols, rows) = crossterm::terminal::size()?;
+
+        let menu_width = 64.min(cols.saturating_sub(4));
+        let menu_height = ROWS.len() as u16 * 2 + 6;
+
+        let start_x = (cols - menu_width) / 2;
+        let start_y = if rows > menu_height + 2 {
+            (rows - menu_height) / 2
+        } else {
+            1
+        };
+
+        stdout().execute(terminal::Clear(terminal::ClearType::All))?;
+        draw_modern_box(start_x, start_y, menu_width, menu_height)?;
+
+        let title = format!("{} GENERATION SETTINGS", Icon::Settings);
+        let title_x = start_x + (menu_width / 2).saturating_sub(title.chars().count() as u16 / 2);
+        stdout()
+            .queue(crossterm::cursor::MoveTo(title_x, start_y + 1))?
+            .queue(SetForegroundColor(crossterm::style::Color::AnsiValue(
+                crate::utils::colors::MISC_ANSI,
+            )))?
+            .queue(Print(style(title).bold()))?
+            .queue(ResetColor)?;
+
+        for (idx, (name, label, hint)) in ROWS.iter().enumerate() {
+            let y = start_y + 3 + idx as u16 * 2;
+            let value = current_value(settings, name).unwrap_or_else(|| default_label(name));
+            let color = if idx == selected_idx {
+                crate::utils::colors::PRIMARY_ANSI
+            } else {
+                crate::utils::colors::MISC_ANSI
+            };
+            let marker = if idx == selected_idx {
+                Icon::Prompt.to_string()
+            } else {
+                " ".to_string()
+            };
+
+            stdout()
+                .queue(crossterm::cursor::MoveTo(start_x + 3, y))?
+                .queue(SetForegroundColor(crossterm::style::Color::AnsiValue(
+                    color,
+                )))?
+                .queue(Print(format!("{} {:<14}{}", marker, label, value)))?
+                .queue(ResetColor)?
+                .queue(crossterm::cursor::MoveTo(start_x + 5, y + 1))?
+                .queue(Print(style(hint).dim()))?;
+        }
+
+        let help_text = "↑↓ Navigate • Enter Edit • R Reset • ESC Back";
+        stdout()
+            .queue(crossterm::cursor::MoveTo(
+                start_x + 2,
+                start_y + menu_height - 1,
+            ))?
+            .queue(SetForegroundColor(crossterm::style::Color::AnsiValue(
+                crate::utils::colors::AI_HIGHLIGHT_ANSI,
+            )))?
+            .queue(Print(help_text))?
+            .queue(ResetColor)?;
+
+        stdout().flush()?;
+        Ok(())
+    }
+}
+
+impl Default for GenerationSettingsMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The configured value of a setting, if set
+fn current_value(settings: &GenerationSettings, name: &str) -> Option<String> {
+    match name {
+        "temperature" => settings.temperature.map(|t| t.to_string()),
+        "top_p" => settings.top_p.map(|p| p.to_string()),
+        _ => settings.max_tokens.map(|m| m.to_string()),
+    }
+}
+
+fn default_label(name: &str) -> String {
+    match name {
+        "temperature" => format!("{} (default)", GenerationSettings::DEFAULT_TEMPERATURE),
+        "top_p" => "provider default".to_string(),
+        _ => format!("{} (default)", GenerationSettings::DEFAULT_MAX_TOKENS),
+    }
+}
@@ -0,0 +1,307 @@
+//! Git branch and pull request menu for ARULA CLI
+//!
+//! Creates and switches branches directly through `GitOps`. Generating a PR
+//! description needs the model, so it is handed back to the chat as
+//! `MenuResult::GeneratePrDescription` and runs as a background task there.
+
+use crate::ui::menus::common::{
+    MenuResult, MenuState, MenuUtils, draw_modern_box, draw_selected_item,
+};
+use crate::ui::menus::dialogs::Dialogs;
+use crate::ui::output::OutputHandler;
+use crate::utils::colors::ColorTheme;
+use anyhow::Result;
+use arula_core::utils::git_ops::GitOps;
+use crossterm::{
+    ExecutableCommand, QueueableCommand,
+    cursor::MoveTo,
+    event::{Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::{Print, ResetColor, SetForegroundColor},
+    terminal,
+};
+use std::future::Future;
+use std::io::{Write, stdout};
+use std::time::Duration;
+
+/// Git menu options
+#[derive(Debug, Clone, Copy)]
+enum GitMenuItem {
+    CreateBranch,
+    SwitchBranch,
+    GeneratePrDescription,
+}
+
+impl GitMenuItem {
+    fn all() -> Vec<Self> {
+        vec![
+            GitMenuItem::CreateBranch,
+            GitMenuItem::SwitchBranch,
+            GitMenuItem::GeneratePrDescription,
+        ]
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            GitMenuItem::CreateBranch => "➕ Create Branch",
+            GitMenuItem::SwitchBranch => "🔀 Switch Branch",
+            GitMenuItem::GeneratePrDescription => "📝 Generate PR Description",
+        }
+    }
+}
+
+/// Git branch/PR menu handler
+pub struct GitMenu {
+    state: MenuState,
+    items: Vec<GitMenuItem>,
+    dialogs: Dialogs,
+}
+
+impl Default for GitMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitMenu {
+    pub fn new() -> Self {
+        Self {
+            state: MenuState::new(),
+            items: GitMenuItem::all(),
+            dialogs: Dialogs::new(),
+        }
+    }
+
+    /// Display and handle the git menu
+    pub fn show(&mut self, output: &mut OutputHandler) -> Result<MenuResult> {
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let git = GitOps::new(dir);
+        if !block_on(git.is_git_repo()) {
+            self.dialogs.alert_dialog(
+                "Git",
+                "The current directory is not a git repository.",
+                output,
+            )?;
+            return Ok(MenuResult::Continue);
+        }
+
+        MenuUtils::setup_terminal()?;
+        let result = self.run_menu_loop(&git, output);
+        MenuUtils::restore_terminal()?;
+        result
+    }
+
+    fn run_menu_loop(&mut self, git: &GitOps, output: &mut OutputHandler) -> Result<MenuResult> {
+        let labels: Vec<&str> = self.items.iter().map(|item| item.label()).collect();
+        let mut branch = String::new();
+        let mut needs_render = true;
+
+        loop {
+            if needs_render {
+                branch = block_on(git.current_branch())
+                    .ok()
+                    .flatten()
+                    .unwrap_or_else(|| "detached HEAD".to_string());
+                render_list(
+                    &format!("🌿 GIT ({})", branch),
+                    &labels,
+                    self.state.selected_index,
+                    "↑↓ Navigate • Enter Select • ESC Back",
+                )?;
+                needs_render = false;
+            }
+
+            let Some(key) = read_key()? else {
+                continue;
+            };
+            needs_render = true;
+            match key {
+                KeyCode::Up => self.state.move_up(self.items.len()),
+                KeyCode::Down => self.state.move_down(self.items.len()),
+                KeyCode::Enter => {
+                    let result = match self.items[self.state.selected_index] {
+                        GitMenuItem::CreateBranch => self.create_branch(git, output)?,
+                        GitMenuItem::SwitchBranch => self.switch_branch(git, &branch, output)?,
+                        GitMenuItem::GeneratePrDescription => {
+                            Some(MenuResult::GeneratePrDescription)
+                        }
+                    };
+                    if let Some(result) = result {
+                        return Ok(result);
+                    }
+                    // Dialogs leave the alternate screen; re-enter it for the menu
+                    MenuUtils::setup_terminal()?;
+                }
+                KeyCode::Esc => return Ok(MenuResult::Continue),
+                _ => {}
+            }
+        }
+    }
+
+    /// Ask for a branch name and create it from HEAD
+    fn create_branch(
+        &self,
+        git: &GitOps,
+        output: &mut OutputHandler,
+    ) -> Result<Option<MenuResult>> {
+        let Some(name) = self
+            .dialogs
+            .input_dialog("New branch name:", None, output)?
+        else {
+            return Ok(None);
+        };
+        match block_on(git.create_branch(&name)) {
+            Ok(()) => Ok(Some(MenuResult::BranchCreated(name))),
+            Err(e) => {
+                self.dialogs
+                    .alert_dialog("Create Branch", &e.to_string(), output)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Pick a local branch and switch to it
+    fn switch_branch(
+        &self,
+        git: &GitOps,
+        current: &str,
+        output: &mut OutputHandler,
+    ) -> Result<Option<MenuResult>> {
+        let branches = block_on(git.list_branches())?;
+        let labels: Vec<String> = branches
+            .iter()
+            .map(|b| {
+                if b == current {
+                    format!("● {}", b)
+                } else {
+                    format!("  {}", b)
+                }
+            })
+            .collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+
+        let mut selected = branches.iter().position(|b| b == current).unwrap_or(0);
+        let mut needs_render = true;
+        loop {
+            if needs_render {
+                render_list(
+                    "🔀 SWITCH BRANCH",
+                    &labels,
+                    selected,
+                    "↑↓ Navigate • Enter Switch • ESC Back",
+                )?;
+                needs_render = false;
+            }
+            let Some(key) = read_key()? else {
+                continue;
+            };
+            needs_render = true;
+            match key {
+                KeyCode::Up => selected = selected.saturating_sub(1),
+                KeyCode::Down if selected + 1 < branches.len() => selected += 1,
+                KeyCode::Enter => break,
+                KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+
+        let Some(target) = branches.get(selected) else {
+            return Ok(None);
+        };
+        if target == current {
+            return Ok(None);
+        }
+        match block_on(git.switch_branch(target)) {
+            Ok(()) => Ok(Some(MenuResult::BranchSwitched(target.clone()))),
+            Err(e) => {
+                self.dialogs
+                    .alert_dialog("Switch Branch", &e.to_string(), output)?;
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Run a git operation from the synchronous menu code
+///
+/// Menus run on a runtime worker thread (the CLI uses the multi-threaded
+/// runtime), so the future is driven in place without starving other tasks.
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+/// Read the next key press; Ctrl+C is treated as Esc
+fn read_key() -> Result<Option<KeyCode>> {
+    if !crossterm::event::poll(Duration::from_millis(100))? {
+        return Ok(None);
+    }
+    match crossterm::event::read()? {
+        Event::Key(key) if key.kind == KeyEventKind::Press => {
+            if key.code == KeyCode::Char('c') && key.modifiers == KeyModifiers::CONTROL {
+                Ok(Some(KeyCode::Esc))
+            } else {
+                Ok(Some(key.code))
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Render a boxed list with a title and help line
+fn render_list(title: &str, labels: &[&str], selected: usize, help: &str) -> Result<()> {
+    let (cols, rows) = crossterm::terminal::size()?;
+    let menu_width = 50.min(cols.saturating_sub(4));
+    let visible = labels.len().min(rows.saturating_sub(6).max(1) as usize);
+    let menu_height = (visible + 4) as u16;
+    let start_x = (cols.saturating_sub(menu_width)) / 2;
+    let start_y = (rows.saturating_sub(menu_height)) / 2;
+    // Keep the selection on screen for long branch lists
+    let offset = selected.saturating_sub(visible.saturating_sub(1));
+
+    stdout().execute(MoveTo(0, 0))?;
+    stdout().execute(terminal::Clear(terminal::ClearType::FromCursorDown))?;
+    draw_modern_box(start_x, start_y, menu_width, menu_height)?;
+
+    let max_text_width = menu_width.saturating_sub(6) as usize;
+    let title = MenuUtils::truncate_text(title, max_text_width);
+    let title_width = title.chars().count() as u16;
+    stdout()
+        .queue(MoveTo(
+            start_x + menu_width.saturating_sub(title_width) / 2,
+            start_y + 1,
+        ))?
+        .queue(Print(ColorTheme::primary().bold().apply_to(title)))?;
+
+    for (row, (i, label)) in labels
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .enumerate()
+    {
+        let y = start_y + 3 + row as u16;
+        if i == selected {
+            draw_selected_item(start_x, y, menu_width, label)?;
+        } else {
+            stdout()
+                .queue(MoveTo(start_x + 4, y))?
+                .queue(SetForegroundColor(crossterm::style::Color::AnsiValue(
+                    crate::utils::colors::MISC_ANSI,
+                )))?
+                .queue(Print(MenuUtils::truncate_text(label, max_text_width)))?
+                .queue(ResetColor)?;
+        }
+    }
+
+    stdout()
+        .queue(MoveTo(start_x + 2, start_y + menu_height - 1))?
+        .queue(SetForegroundColor(crossterm::style::Color::AnsiValue(
+            crate::utils::colors::AI_HIGHLIGHT_ANSI,
+        )))?
+        .queue(Print(MenuUtils::truncate_text(
+            help,
+            menu_width.saturating_sub(4) as usize,
+        )))?
+        .queue(ResetColor)?;
+    stdout().flush()?;
+    Ok(())
+}
@@ -2,7 +2,7 @@
 
 use crate::app::App;
 use crate::ui::menus::common::{
-    draw_modern_box, draw_selected_item, MenuResult, MenuState, MenuUtils,
+    draw_modern_box, draw_selected_item, ItemArea, MenuResult, MenuState, MenuUtils,
 };
 use crate::ui::output::OutputHandler;
 use crate::utils::colors::ColorTheme;
@@ -23,6 +23,7 @@ pub enum MainMenuItem {
     ContinueChat,
     InitProject,
     Conversations,
+    Git,
     Settings,
     InfoHelp,
     ClearChat,
@@ -34,6 +35,7 @@ impl MainMenuItem {
             MainMenuItem::ContinueChat,
             MainMenuItem::InitProject,
             MainMenuItem::Conversations,
+            MainMenuItem::Git,
             MainMenuItem::Settings,
             MainMenuItem::InfoHelp,
             MainMenuItem::ClearChat,
@@ -45,6 +47,7 @@ impl MainMenuItem {
             MainMenuItem::ContinueChat => "⦿ Continue Chat",
             MainMenuItem::InitProject => "📝 Create Project Manifest",
             MainMenuItem::Conversations => "📚 Conversations",
+            MainMenuItem::Git => "🌿 Git Branches & PRs",
             MainMenuItem::Settings => "⚙ Configuration",
             MainMenuItem::InfoHelp => "ℹ Info & Help",
             MainMenuItem::ClearChat => "Ⓒ Clear Chat",
@@ -58,6 +61,7 @@ impl MainMenuItem {
                 "Create a PROJECT.manifest file for AI quick understanding"
             }
             MainMenuItem::Conversations => "View, load, or manage saved conversations",
+            MainMenuItem::Git => "Create or switch branches and draft PR descriptions",
             MainMenuItem::Settings => "Configure AI provider and configuration",
             MainMenuItem::InfoHelp => "View help and session information",
             MainMenuItem::ClearChat => "Clear conversation history",
@@ -65,6 +69,10 @@ impl MainMenuItem {
     }
 }
 
+/// Size of the menu box
+const MENU_WIDTH: u16 = 50;
+const MENU_HEIGHT: u16 = 12;
+
 /// Main menu handler
 pub struct MainMenu {
     state: MenuState,
@@ -129,7 +137,12 @@ impl MainMenu {
 
             // Wait for input event with timeout
             if crossterm::event::poll(Duration::from_millis(100))? {
-                match crossterm::event::read()? {
+                let event = MenuUtils::mouse_as_key(
+                    crossterm::event::read()?,
+                    &self.item_area()?,
+                    &mut self.state.selected_index,
+                );
+                match event {
                     Event::Key(key_event) => {
                         // Only handle key press events to avoid double-processing
                         if key_event.kind != crossterm::event::KeyEventKind::Press {
@@ -183,11 +196,18 @@ impl MainMenu {
         }
     }
 
+    /// Where `render` draws the items
+    fn item_area(&self) -> Result<ItemArea> {
+        let (cols, rows) = crossterm::terminal::size()?;
+        let menu_width = MENU_WIDTH.min(cols.saturating_sub(4));
+        Ok(ItemArea::centered(cols, rows, menu_width, MENU_HEIGHT, self.items.len()))
+    }
+
     /// Render the main menu with original styling (1:1 from original overlay_menu.rs)
     fn render(&self, _output: &mut OutputHandler) -> Result<()> {
         let (cols, rows) = crossterm::terminal::size()?;
-        let menu_width = 50.min(cols.saturating_sub(4));
-        let menu_height = 11; // Increased by 1 for new menu item
+        let menu_width = MENU_WIDTH.min(cols.saturating_sub(4));
+        let menu_height = MENU_HEIGHT;
         let start_x = if cols > menu_width {
             (cols - menu_width) / 2
         } else {
@@ -324,6 +344,15 @@ impl MainMenu {
                     // Return the result from conversation menu (could be LoadConversation, NewConversation, or BackToMain)
                     Ok(result)
                 }
+                MainMenuItem::Git => {
+                    // Show git branch/PR submenu
+                    use crate::ui::menus::GitMenu;
+                    let mut git_menu = GitMenu::new();
+                    let result = git_menu.show(output)?;
+
+                    // Return the result from git menu (branch changes or a PR description request)
+                    Ok(result)
+                }
                 MainMenuItem::Settings => {
                     // Show configuration submenu
                     use crate::ui::menus::ConfigMenu;
@@ -651,7 +680,7 @@ impl MainMenu {
         _app: &mut App,
         output: &mut OutputHandler,
     ) -> Result<MenuResult> {
-        use arula_core::generate_auto_manifest;
+        use arula_core::generate_indexed_manifest;
         use std::fs;
         use std::path::PathBuf;
 
@@ -742,7 +771,7 @@ impl MainMenu {
                                         // Auto-generate manifest
                                         let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
                                         if let Some(project) = detect_project(&cwd) {
-                                            let manifest_content = generate_auto_manifest(&project);
+                                            let manifest_content = generate_indexed_manifest(&project, &cwd);
                                             let manifest_path = cwd.join("PROJECT.manifest");
                                             fs::write(&manifest_path, manifest_content)?;
                                             output.print_system("✓ Auto-generated PROJECT.manifest")?;
@@ -27,6 +27,25 @@ impl ModelSelector {
         }
     }
 
+    /// Switch to `model` (or the model of an alias) and warn about settings
+    /// it can't support
+    fn apply_model(app: &mut App, output: &mut OutputHandler, model: &str) -> Result<()> {
+        app.set_model(model);
+        let resolved = app.config.get_model();
+        if resolved == model {
+            output.print_system(&format!("✅ Model set to: {}", model))?;
+        } else {
+            output.print_system(&format!(
+                "✅ Model set to: {} ({}/{})",
+                model, app.config.active_provider, resolved
+            ))?;
+        }
+        for warning in app.config.model_warnings() {
+            output.print_system(&format!("⚠️ {}", warning))?;
+        }
+        Ok(())
+    }
+
     /// Show the model selector menu
     pub fn show_model_selector(&mut self, app: &mut App, output: &mut OutputHandler) -> Result<()> {
         // Clear screen once when entering submenu to avoid artifacts (like original overlay_menu.rs)
@@ -39,8 +58,7 @@ impl ModelSelector {
         // For custom provider, use text input instead of selector
         if provider.to_lowercase() == "custom" {
             if let Some(model) = self.show_text_input("Enter model name", &current_model, output)? {
-                app.set_model(&model);
-                output.print_system(&format!("✅ Model set to: {}", model))?;
+                Self::apply_model(app, output, &model)?;
             }
             return Ok(());
         }
@@ -91,8 +109,7 @@ impl ModelSelector {
                 if let Some(model) =
                     self.show_text_input("Enter model name", &current_config.get_model(), output)?
                 {
-                    app.set_model(&model);
-                    output.print_system(&format!("✅ Model set to: {}", model))?;
+                    Self::apply_model(app, output, &model)?;
                 }
                 return Ok(());
             }
@@ -376,19 +393,14 @@ impl ModelSelector {
                                             &current_model,
                                             output
                                         )? {
-                                            app.set_model(&custom_model);
-                                            output.print_system(&format!("✅ Model set to: {}", custom_model))?;
+                                            Self::apply_model(app, output, &custom_model)?;
                                         }
                                         // Clear screen before exiting
                                         stdout().execute(terminal::Clear(terminal::ClearType::All))?;
                                         stdout().flush()?;
                                         break;
                                     } else {
-                                        app.set_model(selected);
-                                        output.print_system(&format!(
-                                            "✅ Model set to: {}",
-                                            selected
-                                        ))?;
+                                        Self::apply_model(app, output, selected)?;
                                     }
                                 }
                                 // Clear screen before exiting
@@ -1,6 +1,7 @@
 //! Z.AI endpoint selection menu for ARULA CLI
 //! Allows selecting between Coding Plan and Anthropic Compatible endpoints
 
+use arula_core::utils::icons::Icon;
 use crate::app::App;
 use crate::ui::menus::common::draw_modern_box;
 use crate::ui::output::OutputHandler;
@@ -149,7 +150,7 @@ impl ZaiEndpointSelector {
 
         // Draw title
         let title_y = start_y + 1;
-        let title = "⚙ Z.AI ENDPOINT";
+        let title = format!("{} Z.AI ENDPOINT", Icon::Settings);
         let title_len = title.len() as u16;
         let title_x = if menu_width > title_len + 2 {
             start_x + menu_width / 2 - title_len / 2
@@ -177,7 +178,7 @@ impl ZaiEndpointSelector {
                 endpoint.name.clone()
             };
 
-            let text = format!("▶ {} ({})", display_name, endpoint.description);
+            let text = format!("{} {} ({})", Icon::Prompt, display_name, endpoint.description);
             let color = if idx == selected_idx {
                 SetForegroundColor(crossterm::style::Color::AnsiValue(
                     crate::utils::colors::PRIMARY_ANSI,
@@ -1,15 +1,30 @@
+pub mod attention;
 pub mod colors;
+pub mod command_palette;
+pub mod commit_view;
 pub mod custom_spinner;
 pub mod custom_terminal;
 pub mod effects;
+pub mod file_tree_view;
+pub mod history_search;
+pub mod input_editor;
 pub mod input_handler;
+pub mod log_view;
 pub mod markdown_stream;
 pub mod menus;
-pub mod notifications;
+pub mod mouse;
+pub use arula_core::utils::notifications;
 pub mod output;
+pub mod pr_description_view;
+pub mod presentation_view;
 pub mod response_display;
 pub mod scroll_history;
+pub mod slash_commands;
+pub mod source_view;
+pub mod stats_view;
 
+pub mod tool_panel;
 pub mod tui;
 pub mod tui_app;
+pub mod walkthrough_view;
 pub mod widgets;
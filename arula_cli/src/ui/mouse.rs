@@ -0,0 +1,218 @@
+//! Mouse support for the TUI
+//!
+//! With capture on (`mouse`, default: true) the wheel moves through the
+//! conversation, dragging over the chat history selects text and copies it
+//! on release, and menu items can be clicked. Turning it off gives the mouse
+//! back to the terminal for its own selection and scrollback.
+
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::collections::VecDeque;
+use std::io::{self, stdout};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+use unicode_width::UnicodeWidthChar;
+
+static CAPTURED: AtomicBool = AtomicBool::new(false);
+
+/// Turn mouse capture on or off
+pub fn set_mouse_capture(on: bool) -> io::Result<()> {
+    if CAPTURED.swap(on, Ordering::Relaxed) == on {
+        return Ok(());
+    }
+    if on {
+        execute!(stdout(), EnableMouseCapture)
+    } else {
+        execute!(stdout(), DisableMouseCapture)
+    }
+}
+
+/// Whether mouse events are being captured
+pub fn mouse_captured() -> bool {
+    CAPTURED.load(Ordering::Relaxed)
+}
+
+/// A drag over the screen, from where the button went down to where the
+/// pointer is now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// Column and row the drag started at
+    pub anchor: (u16, u16),
+    /// Column and row the pointer is at
+    pub head: (u16, u16),
+}
+
+impl Selection {
+    pub fn new(column: u16, row: u16) -> Self {
+        Self {
+            anchor: (column, row),
+            head: (column, row),
+        }
+    }
+
+    /// Whether the drag covers no cells yet
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+
+    /// Start and end in reading order, end inclusive
+    fn ordered(&self) -> ((u16, u16), (u16, u16)) {
+        let (a, h) = (self.anchor, self.head);
+        if (a.1, a.0) <= (h.1, h.0) {
+            (a, h)
+        } else {
+            (h, a)
+        }
+    }
+
+    /// Screen rows the selection touches
+    pub fn rows(&self) -> Range<u16> {
+        let (start, end) = self.ordered();
+        start.1..end.1 + 1
+    }
+
+    /// Columns of `row` inside the selection, like text selection in a
+    /// terminal: whole rows in the middle, partial rows at either end
+    pub fn columns(&self, row: u16) -> Option<Range<u16>> {
+        if self.is_empty() || !self.rows().contains(&row) {
+            return None;
+        }
+        let (start, end) = self.ordered();
+        let from = if row == start.1 { start.0 } else { 0 };
+        let to = if row == end.1 { end.0 + 1 } else { u16::MAX };
+        Some(from..to)
+    }
+}
+
+/// The chat history rows on screen above the viewport, newest last
+///
+/// History goes to the terminal's scrollback, which can't be read back, so
+/// the rows are recorded as they're printed to find the text under a drag.
+#[derive(Debug, Default)]
+pub struct ScreenText {
+    rows: VecDeque<Line<'static>>,
+}
+
+impl ScreenText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record rows printed above the viewport; `visible` is how many fit
+    pub fn push(&mut self, rows: Vec<Line<'static>>, visible: u16) {
+        self.rows.extend(rows);
+        let excess = self.rows.len().saturating_sub(visible as usize);
+        self.rows.drain(..excess);
+    }
+
+    /// Record `count` blank rows, e.g. where the viewport used to be
+    pub fn push_blank(&mut self, count: u16, visible: u16) {
+        self.push(vec![Line::default(); count as usize], visible);
+    }
+
+    /// Forget the rows after the screen was cleared or reflowed
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    /// The line on screen row `row`, when the viewport starts at `top`
+    pub fn row(&self, row: u16, top: u16) -> Option<&Line<'static>> {
+        let from_bottom = top.checked_sub(row)? as usize;
+        let index = self.rows.len().checked_sub(from_bottom)?;
+        self.rows.get(index)
+    }
+
+    /// Text under `selection`, one line per row with trailing spaces trimmed
+    pub fn text(&self, selection: &Selection, top: u16) -> String {
+        selection
+            .rows()
+            .filter_map(|row| {
+                let columns = selection.columns(row)?;
+                let line = self.row(row, top)?;
+                let text: String = cells(line)
+                    .filter(|(column, _, _)| columns.contains(column))
+                    .map(|(_, ch, _)| ch)
+                    .collect();
+                Some(text.trim_end().to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// `line` with the cells in `columns` shown reversed
+pub fn highlight(line: &Line<'static>, columns: Range<u16>) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (column, ch, style) in cells(line) {
+        let style = if columns.contains(&column) {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        };
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(ch),
+            _ => spans.push(Span::styled(ch.to_string(), style)),
+        }
+    }
+    let mut out = Line::from(spans);
+    out.style = line.style;
+    out
+}
+
+/// Each character of `line` with the column it starts at and its style
+fn cells<'a>(line: &'a Line<'static>) -> impl Iterator<Item = (u16, char, Style)> + 'a {
+    let mut column = 0u16;
+    line.spans
+        .iter()
+        .flat_map(move |span| span.content.chars().map(move |ch| (span.style, ch)))
+        .map(move |(style, ch)| {
+            let start = column;
+            column = column.saturating_add(UnicodeWidthChar::width(ch).unwrap_or(0) as u16);
+            (start, ch, style)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_selection_text_from_screen_rows() {
+        let mut screen = ScreenText::new();
+        screen.push(
+            vec![
+                Line::from("first row is gone"),
+                Line::from("› fix the parser"),
+                Line::from(vec![
+                    Span::styled("▶ ", Style::default().fg(Color::Blue)),
+                    Span::raw("Done, tests pass."),
+                ]),
+            ],
+            2,
+        );
+        // Viewport starts at row 10, so the history fills rows 8 and 9
+        assert!(screen.row(7, 10).is_none());
+
+        let mut selection = Selection::new(2, 8);
+        assert!(selection.is_empty());
+        selection.head = (7, 9);
+        assert_eq!(screen.text(&selection, 10), "fix the parser\n▶ Done,");
+
+        // Dragging backwards selects the same cells
+        let reversed = Selection {
+            anchor: (7, 9),
+            head: (2, 8),
+        };
+        assert_eq!(screen.text(&reversed, 10), screen.text(&selection, 10));
+
+        let line = screen.row(9, 10).unwrap();
+        let lit = highlight(line, 2..6);
+        assert_eq!(lit.spans.len(), 3);
+        assert_eq!(lit.spans[1].content, "Done");
+        assert!(lit.spans[1].style.add_modifier.contains(Modifier::REVERSED));
+        assert_eq!(lit.spans[0].style.fg, Some(Color::Blue));
+    }
+}
@@ -4,11 +4,13 @@
 //! Per Tokio best practices, this avoids blocking the async runtime during
 //! resource loading.
 
-use std::sync::OnceLock;
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use std::sync::{OnceLock, RwLock};
 use syntect::{
     easy::HighlightLines,
-    highlighting::{Theme, ThemeSet},
-    parsing::SyntaxSet,
+    highlighting::{FontStyle, Theme, ThemeSet},
+    parsing::{SyntaxReference, SyntaxSet},
     util::as_24_bit_terminal_escaped,
 };
 
@@ -50,6 +52,53 @@ pub fn get_theme_set() -> &'static ThemeSet {
 /// Default theme name for syntax highlighting
 pub const DEFAULT_THEME: &str = "base16-ocean.dark";
 
+/// Theme used when the terminal has a light background
+pub const LIGHT_THEME: &str = "InspiredGitHub";
+
+/// Theme from `appearance.code_theme`, if set
+static CODE_THEME: RwLock<Option<String>> = RwLock::new(None);
+
+/// Use `name` for code blocks from now on (`None` to pick one from the
+/// terminal background)
+pub fn set_code_theme(name: Option<&str>) {
+    if let Ok(mut theme) = CODE_THEME.write() {
+        *theme = name.map(str::to_string);
+    }
+}
+
+/// The configured theme, or one matching the terminal background
+pub fn code_theme() -> String {
+    CODE_THEME
+        .read()
+        .ok()
+        .and_then(|theme| theme.clone())
+        .filter(|name| get_theme_set().themes.contains_key(name))
+        .unwrap_or_else(|| {
+            theme_for_background(std::env::var("COLORFGBG").ok().as_deref()).to_string()
+        })
+}
+
+/// Pick a theme from `COLORFGBG` (`fg;bg`), where background 7 or 15 is light
+fn theme_for_background(colorfgbg: Option<&str>) -> &'static str {
+    let background = colorfgbg
+        .and_then(|value| value.rsplit(';').next())
+        .and_then(|bg| bg.parse::<u8>().ok());
+    match background {
+        Some(7 | 15) => LIGHT_THEME,
+        _ => DEFAULT_THEME,
+    }
+}
+
+/// Syntax for a fence language such as `rs`, `rust` or `Rust`
+fn find_syntax(language: &str) -> &'static SyntaxReference {
+    let syntax_set = get_syntax_set();
+    syntax_set
+        .find_syntax_by_extension(language)
+        .or_else(|| syntax_set.find_syntax_by_name(language))
+        .or_else(|| syntax_set.find_syntax_by_token(language))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
 /// Code highlighter with caching and efficient rendering
 pub struct CodeHighlighter {
     theme_name: String,
@@ -63,13 +112,13 @@ impl CodeHighlighter {
         }
     }
 
-    /// Create a code highlighter with the default theme
+    /// Create a code highlighter with the configured or detected theme
     pub fn default_theme() -> Self {
-        Self::new(DEFAULT_THEME)
+        Self::new(&code_theme())
     }
 
     /// Get the current theme
-    pub fn get_theme(&self) -> &Theme {
+    pub fn get_theme(&self) -> &'static Theme {
         let theme_set = get_theme_set();
         theme_set
             .themes
@@ -92,12 +141,7 @@ impl CodeHighlighter {
         let theme = self.get_theme();
 
         // Find syntax for language, fallback to plain text
-        let syntax = syntax_set
-            .find_syntax_by_extension(language)
-            .or_else(|| syntax_set.find_syntax_by_name(language))
-            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
-
-        let mut highlighter = HighlightLines::new(syntax, theme);
+        let mut highlighter = HighlightLines::new(find_syntax(language), theme);
         let mut output = String::with_capacity(code.len() * 2);
 
         for line in code.lines() {
@@ -122,14 +166,7 @@ impl CodeHighlighter {
     /// Highlight a single line (for streaming)
     pub fn highlight_line(&self, line: &str, language: &str) -> String {
         let syntax_set = get_syntax_set();
-        let theme = self.get_theme();
-
-        let syntax = syntax_set
-            .find_syntax_by_extension(language)
-            .or_else(|| syntax_set.find_syntax_by_name(language))
-            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
-
-        let mut highlighter = HighlightLines::new(syntax, theme);
+        let mut highlighter = HighlightLines::new(find_syntax(language), self.get_theme());
 
         match highlighter.highlight_line(line, syntax_set) {
             Ok(ranges) => {
@@ -165,6 +202,42 @@ impl Default for CodeHighlighter {
     }
 }
 
+/// Line-by-line highlighting into ratatui spans, for code that streams in
+///
+/// Keeps the parser state between lines, so constructs spanning several
+/// lines (block comments, strings) stay colored correctly.
+pub struct SpanHighlighter {
+    lines: HighlightLines<'static>,
+}
+
+impl SpanHighlighter {
+    /// Highlighter for `language` using the configured or detected theme
+    pub fn new(language: &str) -> Self {
+        let theme = CodeHighlighter::default_theme().get_theme();
+        Self {
+            lines: HighlightLines::new(find_syntax(language), theme),
+        }
+    }
+
+    /// Spans for the next line of the block
+    pub fn highlight(&mut self, line: &str) -> Vec<Span<'static>> {
+        match self.lines.highlight_line(line, get_syntax_set()) {
+            Ok(ranges) => ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let fg = style.foreground;
+                    let mut span_style = Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b));
+                    if style.font_style.contains(FontStyle::BOLD) {
+                        span_style = span_style.add_modifier(ratatui::style::Modifier::BOLD);
+                    }
+                    Span::styled(text.to_string(), span_style)
+                })
+                .collect(),
+            Err(_) => vec![Span::raw(line.to_string())],
+        }
+    }
+}
+
 /// Format code block with box drawing characters
 ///
 /// Creates a bordered code block suitable for terminal display.
@@ -253,6 +326,29 @@ mod tests {
         assert!(!highlighted.is_empty());
     }
 
+    #[test]
+    fn test_theme_for_background() {
+        assert_eq!(theme_for_background(Some("0;15")), LIGHT_THEME);
+        assert_eq!(theme_for_background(Some("15;default;0")), DEFAULT_THEME);
+        assert_eq!(theme_for_background(None), DEFAULT_THEME);
+    }
+
+    #[test]
+    fn test_span_highlighter() {
+        let mut highlighter = SpanHighlighter::new("rust");
+        let spans = highlighter.highlight("/* open");
+        let comment = spans[0].style.fg;
+        let spans = highlighter.highlight("still comment */ fn main() {}");
+        // The comment carries over from the previous line
+        assert_eq!(spans[0].style.fg, comment);
+        assert!(spans.len() > 2);
+        assert!(
+            spans
+                .iter()
+                .all(|span| matches!(span.style.fg, Some(Color::Rgb(..))))
+        );
+    }
+
     #[test]
     fn test_supported_languages() {
         let langs = CodeHighlighter::supported_languages();
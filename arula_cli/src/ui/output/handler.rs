@@ -8,6 +8,7 @@ use super::markdown::MarkdownStreamer;
 use super::spinners::{SpinnerManager, SpinnerStyle};
 use super::tool_display;
 use crate::api::api::Usage;
+use arula_core::utils::accessibility::{plain_if_enabled, plain_mode};
 use console::style;
 use crossterm::terminal;
 use std::io::{self, Write};
@@ -80,6 +81,11 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
+        if plain_mode() {
+            writeln!(handle, "\nARULA - Autonomous AI CLI Assistant\n")?;
+            return handle.flush();
+        }
+
         writeln!(
             handle,
             "\n{}",
@@ -171,7 +177,7 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
-        writeln!(handle, "{} {}", style("ℹ").blue(), style(message).dim())?;
+        writeln!(handle, "{} {}", style(status_prefix("ℹ", "Info:")).blue(), style(message).dim())?;
         handle.flush()
     }
 
@@ -180,7 +186,7 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
-        writeln!(handle, "{} {}", style("✓").green(), style(message).green())?;
+        writeln!(handle, "{} {}", style(status_prefix("✓", "OK:")).green(), style(message).green())?;
         handle.flush()
     }
 
@@ -189,7 +195,7 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
-        writeln!(handle, "{} {}", style("✗").red(), style(message).red())?;
+        writeln!(handle, "{} {}", style(status_prefix("✗", "Error:")).red(), style(message).red())?;
         handle.flush()
     }
 
@@ -201,7 +207,7 @@ impl OutputHandler {
         writeln!(
             handle,
             "{} {}",
-            style("⚠").yellow(),
+            style(status_prefix("⚠", "Warning:")).yellow(),
             style(message).yellow()
         )?;
         handle.flush()
@@ -211,6 +217,20 @@ impl OutputHandler {
     // User/AI Message Display
     // ========================================================================
 
+    /// Print a day separator, e.g. `──── Yesterday ────`
+    pub fn print_day_separator(&self, label: &str) -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+
+        let separator = if plain_mode() {
+            format!("{}:", label)
+        } else {
+            format!("──── {} ────", label)
+        };
+        writeln!(handle, "\n{}", style(separator).dim())?;
+        handle.flush()
+    }
+
     /// Print a user message
     pub fn print_user_message(&self, message: &str) -> io::Result<()> {
         let stdout = io::stdout();
@@ -242,6 +262,7 @@ impl OutputHandler {
             self.start_ai_stream()?;
         }
 
+        let chunk = &plain_if_enabled(chunk);
         self.stream_buffer.push_str(chunk);
         self.markdown_streamer.process_chunk(chunk)?;
 
@@ -272,7 +293,7 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
-        writeln!(handle, "\n{}", message)?;
+        writeln!(handle, "\n{}", plain_if_enabled(message))?;
         handle.flush()
     }
 
@@ -287,7 +308,7 @@ impl OutputHandler {
 
         let formatted = tool_display::format_tool_call_box(tool_name, arguments);
         // Add extra spacing before tool calls for better readability
-        writeln!(handle, "\n\n{}", formatted)?;
+        writeln!(handle, "\n\n{}", plain_if_enabled(&formatted))?;
         handle.flush()
     }
 
@@ -302,7 +323,7 @@ impl OutputHandler {
         let mut handle = stdout.lock();
 
         let formatted = tool_display::format_tool_result_box(tool_name, result, success);
-        writeln!(handle, "{}", formatted)?;
+        writeln!(handle, "{}", plain_if_enabled(&formatted))?;
         handle.flush()
     }
 
@@ -317,7 +338,7 @@ impl OutputHandler {
         let mut handle = stdout.lock();
 
         let formatted = tool_display::format_detailed_result(tool_name, result, success);
-        writeln!(handle, "{}", formatted)?;
+        writeln!(handle, "{}", plain_if_enabled(&formatted))?;
         handle.flush()
     }
 
@@ -340,6 +361,11 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
+        if plain_mode() {
+            // Screen readers skip the frame; the code itself is left as is
+            writeln!(handle, "{} code:\n{}", language, code.trim_end())?;
+            return handle.flush();
+        }
         let formatted = super::code_blocks::format_code_box(code, language, self.terminal_width());
         write!(handle, "{}", formatted)?;
         handle.flush()
@@ -389,27 +415,32 @@ impl OutputHandler {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
 
-        writeln!(handle, "\n{}", style("─".repeat(40)).dim())?;
+        let (rule, bullet) = if plain_mode() {
+            (String::new(), "-")
+        } else {
+            ("─".repeat(40), "•")
+        };
+        writeln!(handle, "\n{}", style(&rule).dim())?;
         writeln!(handle, "{}", style("Usage Statistics:").dim())?;
         writeln!(
             handle,
             "  {} Prompt tokens: {}",
-            style("•").dim(),
+            style(bullet).dim(),
             style(usage.prompt_tokens).cyan()
         )?;
         writeln!(
             handle,
             "  {} Completion tokens: {}",
-            style("•").dim(),
+            style(bullet).dim(),
             style(usage.completion_tokens).cyan()
         )?;
         writeln!(
             handle,
             "  {} Total tokens: {}",
-            style("•").dim(),
+            style(bullet).dim(),
             style(usage.total_tokens).bold().cyan()
         )?;
-        writeln!(handle, "{}", style("─".repeat(40)).dim())?;
+        writeln!(handle, "{}", style(&rule).dim())?;
 
         handle.flush()
     }
@@ -421,7 +452,7 @@ impl OutputHandler {
     /// Print debug message (only if debug mode enabled)
     pub fn debug(&self, message: &str) {
         if self.debug {
-            println!("{} {}", style("🔧 DEBUG:").dim(), message);
+            println!("{} {}", style(plain_if_enabled("🔧 DEBUG:")).dim(), message);
         }
     }
 
@@ -436,6 +467,11 @@ impl OutputHandler {
     }
 }
 
+/// `symbol`, or the word `label` in plain mode
+fn status_prefix(symbol: &'static str, label: &'static str) -> &'static str {
+    if plain_mode() { label } else { symbol }
+}
+
 impl Default for OutputHandler {
     fn default() -> Self {
         Self::new()
@@ -45,9 +45,9 @@ pub mod tool_display;
 pub use handler::OutputHandler;
 
 // Additional exports available via submodules:
-// code_blocks::{CodeHighlighter, get_syntax_set, get_theme_set, format_code_box}
+// code_blocks::{CodeHighlighter, SpanHighlighter, set_code_theme, get_syntax_set, get_theme_set, format_code_box}
 // markdown::{MarkdownStreamer, render_markdown, render_markdown_inline}
-// spinners::{SpinnerStyle, SpinnerManager, create_spinner, create_progress_bar}
+// spinners::{SpinnerStyle, SpinnerManager, create_spinner, create_progress_bar, create_progress_bar_with}
 // tool_display::{format_tool_call_box, format_tool_result_box, get_tool_icon}
 
 /// Terminal width constant (can be made dynamic)
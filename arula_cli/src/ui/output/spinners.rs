@@ -132,13 +132,21 @@ pub fn create_simple_spinner(message: &str) -> ProgressBar {
 /// progress.finish_with_message("Complete!");
 /// ```
 pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
+    create_progress_bar_with(total, message, "█▓░")
+}
+
+/// Create a progress bar drawn with a style pack's characters
+///
+/// `chars` uses indicatif's `progress_chars` format (see
+/// [`arula_core::utils::style_packs::ProgressStyle`]).
+pub fn create_progress_bar_with(total: u64, message: &str, chars: &str) -> ProgressBar {
     let bar = ProgressBar::new(total);
 
     bar.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.cyan} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
             .expect("Invalid progress bar template")
-            .progress_chars("█▓░"),
+            .progress_chars(chars),
     );
 
     bar.set_message(message.to_string());
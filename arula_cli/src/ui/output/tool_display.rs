@@ -3,38 +3,13 @@
 //! Provides consistent, visually appealing display for tool calls
 //! and their results in the terminal.
 
+use arula_core::utils::icons::Icon;
 use console::style;
 use serde_json::Value;
 
-/// Icons for different tool types
-pub mod icons {
-    pub const BASH: &str = "⚙️";
-    pub const FILE_READ: &str = "📖";
-    pub const FILE_WRITE: &str = "✍️";
-    pub const FILE_EDIT: &str = "✏️";
-    pub const DIRECTORY: &str = "📂";
-    pub const SEARCH: &str = "🔍";
-    pub const WEB: &str = "🌐";
-    pub const QUESTION: &str = "❓";
-    pub const SUCCESS: &str = "✅";
-    pub const ERROR: &str = "❌";
-    pub const TOOL: &str = "🔧";
-    pub const LOADING: &str = "⏳";
-}
-
-/// Get the appropriate icon for a tool
+/// Get the appropriate icon for a tool in the active icon set
 pub fn get_tool_icon(tool_name: &str) -> &'static str {
-    match tool_name {
-        "execute_bash" => icons::BASH,
-        "read_file" => icons::FILE_READ,
-        "write_file" => icons::FILE_WRITE,
-        "edit_file" => icons::FILE_EDIT,
-        "list_directory" => icons::DIRECTORY,
-        "search_files" => icons::SEARCH,
-        "web_search" => icons::WEB,
-        "ask_question" => icons::QUESTION,
-        _ => icons::TOOL,
-    }
+    Icon::for_tool(tool_name).glyph()
 }
 
 /// Format a tool call for display
@@ -139,9 +114,9 @@ pub fn format_tool_call_box(tool_name: &str, arguments: &str) -> String {
 /// Creates a summary of the tool execution result.
 pub fn format_tool_result_box(tool_name: &str, result: &Value, success: bool) -> String {
     let status_icon = if success {
-        icons::SUCCESS
+        Icon::Success.glyph()
     } else {
-        icons::ERROR
+        Icon::Error.glyph()
     };
     let status_style = if success {
         style(status_icon).green()
@@ -304,9 +279,9 @@ mod tests {
 
     #[test]
     fn test_get_tool_icon() {
-        assert_eq!(get_tool_icon("execute_bash"), icons::BASH);
-        assert_eq!(get_tool_icon("read_file"), icons::FILE_READ);
-        assert_eq!(get_tool_icon("unknown_tool"), icons::TOOL);
+        assert_eq!(get_tool_icon("execute_bash"), Icon::Shell.glyph());
+        assert_eq!(get_tool_icon("read_file"), Icon::FileRead.glyph());
+        assert_eq!(get_tool_icon("unknown_tool"), Icon::Tool.glyph());
     }
 
     #[test]
@@ -0,0 +1,159 @@
+//! Viewer for AI-drafted pull request descriptions
+//!
+//! Shows the drafted Markdown and lets the user copy it, save it to
+//! `PR_DESCRIPTION.md`, ask for a new draft, or close the view:
+//!
+//! - `c` copy to clipboard, `w` write file, `r` regenerate, `q`/`Esc` close
+//! - `↑`/`↓`/`PgUp`/`PgDn` scroll
+
+use anyhow::Result;
+use arula_core::utils::pr_description::{PR_DESCRIPTION_FILE, PrDescription};
+use base64::Engine;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use std::io::{Write, stdout};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// What the user chose to do with a drafted description
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrDescriptionAction {
+    /// Copy the description to the clipboard
+    Copy,
+    /// Write the description to `PR_DESCRIPTION.md`
+    Save,
+    /// Ask the model for a new draft
+    Regenerate,
+    /// Close without doing anything
+    Close,
+}
+
+/// Full-screen viewer for a drafted PR description
+pub struct PrDescriptionView<'a> {
+    description: &'a PrDescription,
+    scroll: u16,
+}
+
+impl<'a> PrDescriptionView<'a> {
+    pub fn new(description: &'a PrDescription) -> Self {
+        Self {
+            description,
+            scroll: 0,
+        }
+    }
+
+    /// Show the view until the user picks an action
+    pub fn show(&mut self) -> Result<PrDescriptionAction> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<PrDescriptionAction> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+        let line_count = self.description.markdown.lines().count() as u16;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('c') => return Ok(PrDescriptionAction::Copy),
+                KeyCode::Char('w') => return Ok(PrDescriptionAction::Save),
+                KeyCode::Char('r') => return Ok(PrDescriptionAction::Regenerate),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(PrDescriptionAction::Close),
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = (self.scroll + 1).min(line_count),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::PageDown => self.scroll = (self.scroll + 10).min(line_count),
+                _ => {}
+            }
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(f.area());
+
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let dim = Style::default().fg(Color::DarkGray);
+
+        let lines: Vec<Line> = self
+            .description
+            .markdown
+            .lines()
+            .map(|line| {
+                if line.starts_with('#') {
+                    Line::styled(line.to_string(), accent.add_modifier(Modifier::BOLD))
+                } else {
+                    Line::raw(line.to_string())
+                }
+            })
+            .collect();
+        let title = format!(
+            " PR: {} → {} ({} commit(s)) ",
+            self.description.branch, self.description.base, self.description.commit_count
+        );
+        let body = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(accent)
+                    .title(title),
+            );
+        f.render_widget(body, chunks[0]);
+
+        let footer = Line::from(vec![Span::styled(
+            format!(
+                "c copy  w write {}  r regenerate  ↑↓ scroll  q/Esc close",
+                PR_DESCRIPTION_FILE
+            ),
+            dim,
+        )]);
+        f.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+}
+
+/// Copy text to the system clipboard
+///
+/// Uses the platform clipboard when one is available, and otherwise an
+/// OSC 52 escape sequence, which works in most modern terminals including
+/// over SSH. The clipboard handle is kept for the whole session because
+/// X11 and Wayland drop the contents together with it.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    static CLIPBOARD: OnceLock<Mutex<Option<arboard::Clipboard>>> = OnceLock::new();
+    let clipboard = CLIPBOARD.get_or_init(|| Mutex::new(arboard::Clipboard::new().ok()));
+    if let Ok(mut clipboard) = clipboard.lock()
+        && let Some(clipboard) = clipboard.as_mut()
+        && clipboard.set_text(text).is_ok()
+    {
+        return Ok(());
+    }
+
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut out = stdout();
+    write!(out, "\x1b]52;c;{}\x07", encoded)?;
+    out.flush()?;
+    Ok(())
+}
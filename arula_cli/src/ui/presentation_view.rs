@@ -0,0 +1,277 @@
+//! Full-screen presentation of the conversation for demos and teaching
+//!
+//! Shows one user or AI message per slide with wide margins and extra line
+//! spacing. Tool calls, tool output and system notices are left out.
+//!
+//! - `→`/`Space`/`n`/`l` next message, `←`/`p`/`h` previous message
+//! - `g`/`Home` first, `G`/`End` last
+//! - `↑`/`↓`/`j`/`k` or the wheel scroll, `q`/`Esc` close
+
+use anyhow::Result;
+use arula_core::utils::chat::{ChatMessage, MessageType};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, MouseEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Wrap},
+};
+use std::io::stdout;
+use std::time::Duration;
+
+use crate::ui::markdown_stream::MarkdownStream;
+use crate::ui::menus::common::MenuUtils;
+
+/// Widest the message text gets, in columns
+const MAX_TEXT_WIDTH: u16 = 90;
+
+/// One message of the presentation
+struct Slide {
+    from_user: bool,
+    lines: Vec<Line<'static>>,
+}
+
+/// Interactive presentation pager
+pub struct PresentationView {
+    slides: Vec<Slide>,
+    index: usize,
+    scroll: u16,
+}
+
+impl PresentationView {
+    /// Slides for the user and AI messages of `messages`, starting at the
+    /// one at `start` in `messages` (or the first)
+    pub fn new(messages: &[ChatMessage], start: Option<usize>) -> Self {
+        let mut slides = Vec::new();
+        let mut index = 0;
+        for (i, message) in messages.iter().enumerate() {
+            if message.content.trim().is_empty() {
+                continue;
+            }
+            let from_user = match message.message_type {
+                MessageType::User => true,
+                MessageType::Arula => false,
+                _ => continue,
+            };
+            if start.is_some_and(|start| start >= i) {
+                index = slides.len();
+            }
+            let lines = if from_user {
+                spaced_text(&message.content)
+            } else {
+                spaced_markdown(&message.content)
+            };
+            slides.push(Slide { from_user, lines });
+        }
+        Self {
+            slides,
+            index,
+            scroll: 0,
+        }
+    }
+
+    /// Whether there's anything to present
+    pub fn is_empty(&self) -> bool {
+        self.slides.is_empty()
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.index = index.min(self.slides.len().saturating_sub(1));
+        self.scroll = 0;
+    }
+
+    /// Show the presentation until the user closes it
+    pub fn show(&mut self) -> Result<()> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                Event::Mouse(mouse) => {
+                    match mouse.kind {
+                        MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_add(2),
+                        MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(2),
+                        _ => {}
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Right | KeyCode::Char('n') | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                    self.go_to(self.index + 1)
+                }
+                KeyCode::Left | KeyCode::Char('p') | KeyCode::Char('h') => {
+                    self.go_to(self.index.saturating_sub(1))
+                }
+                KeyCode::Home | KeyCode::Char('g') => self.go_to(0),
+                KeyCode::End | KeyCode::Char('G') => self.go_to(self.slides.len()),
+                KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                _ => {}
+            }
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let Some(slide) = self.slides.get(self.index) else {
+            return;
+        };
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(f.area());
+        let dim = Style::default().fg(Color::DarkGray);
+
+        // Position, top right
+        let position = Paragraph::new(Line::styled(
+            format!("{} / {} ", self.index + 1, self.slides.len()),
+            dim,
+        ))
+        .alignment(ratatui::layout::Alignment::Right);
+        f.render_widget(position, chunks[0]);
+
+        // Speaker and message, in a centered column
+        let (speaker, color) = if slide.from_user {
+            ("You", Color::Cyan)
+        } else {
+            ("Arula", Color::Green)
+        };
+        let speaker = Paragraph::new(Line::from(Span::styled(
+            speaker,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )));
+        f.render_widget(speaker, text_column(chunks[1]));
+
+        let body = Paragraph::new(slide.lines.clone())
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        f.render_widget(body, text_column(chunks[2]));
+
+        let footer = Paragraph::new(Line::styled(
+            "←/→ previous/next  g/G first/last  ↑/↓ scroll  q close",
+            dim,
+        ))
+        .alignment(ratatui::layout::Alignment::Center);
+        f.render_widget(footer, chunks[3]);
+    }
+}
+
+/// `area` narrowed to a centered column with a margin on both sides
+fn text_column(area: Rect) -> Rect {
+    let margin = (area.width / 8).max(2);
+    let width = area.width.saturating_sub(margin * 2).min(MAX_TEXT_WIDTH);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        width,
+        ..area
+    }
+}
+
+/// Plain text with a blank line after every line
+fn spaced_text(text: &str) -> Vec<Line<'static>> {
+    let lines = text
+        .trim()
+        .lines()
+        .flat_map(|line| [Line::raw(line.to_string()), Line::default()]);
+    collapse_blank_lines(lines.collect())
+}
+
+/// Rendered markdown with a blank line after every line of prose; code
+/// blocks keep their spacing
+fn spaced_markdown(markdown: &str) -> Vec<Line<'static>> {
+    let mut stream = MarkdownStream::new();
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for source in markdown.trim().lines() {
+        let fence = source.trim_start().starts_with("```");
+        let code = in_code || fence;
+        if fence {
+            in_code = !in_code;
+        }
+        for line in stream.push(&format!("{}\n", source)) {
+            lines.push(line);
+            if !code {
+                lines.push(Line::default());
+            }
+        }
+    }
+    lines.extend(stream.finalize());
+    collapse_blank_lines(lines)
+}
+
+/// At most two blank lines in a row, so paragraphs stand apart from lines
+/// and none at the end
+fn collapse_blank_lines(lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    let is_blank = |line: &Line| line.spans.iter().all(|span| span.content.trim().is_empty());
+    let mut out: Vec<Line<'static>> = Vec::new();
+    let mut blanks = 0;
+    for line in lines {
+        if is_blank(&line) {
+            blanks += 1;
+            if blanks > 2 {
+                continue;
+            }
+        } else {
+            blanks = 0;
+        }
+        out.push(line);
+    }
+    while out.last().is_some_and(is_blank) {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slides_skip_tool_noise() {
+        let messages = vec![
+            ChatMessage::new(MessageType::User, "Fix the parser".to_string()),
+            ChatMessage::new(MessageType::ToolCall, "read_file".to_string()),
+            ChatMessage::new(MessageType::ToolResult, "fn parse() {}".to_string()),
+            ChatMessage::new(MessageType::Arula, "Fixed.\nTests pass.".to_string()),
+            ChatMessage::new(MessageType::System, "Saved".to_string()),
+        ];
+        let view = PresentationView::new(&messages, None);
+        assert_eq!(view.slides.len(), 2);
+        assert!(view.slides[0].from_user);
+        assert_eq!(view.index, 0);
+
+        // Starting from a tool message lands on the message before it
+        let view = PresentationView::new(&messages, Some(2));
+        assert_eq!(view.index, 0);
+        let view = PresentationView::new(&messages, Some(3));
+        assert_eq!(view.index, 1);
+
+        let text: Vec<String> = spaced_text("one\n\ntwo\nthree\n")
+            .iter()
+            .map(|line| line.to_string())
+            .collect();
+        assert_eq!(text, ["one", "", "", "two", "", "three"]);
+    }
+}
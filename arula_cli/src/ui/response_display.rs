@@ -121,6 +121,7 @@ impl ResponseDisplay {
                 tool_call_id,
                 success,
                 result,
+                ..
             } => {
                 // Handle result logic if needed or just wait for `handle_tool_result` call
                 // Actually `update` receives `response`.
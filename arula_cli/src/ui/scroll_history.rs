@@ -2,9 +2,12 @@
 //! Uses ANSI scroll regions to push text ABOVE the TUI viewport.
 
 use crossterm::{
-    cursor::MoveTo,
+    cursor::{MoveTo, RestorePosition, SavePosition},
     queue,
-    style::{Color, Colors, Print, SetBackgroundColor, SetColors, SetForegroundColor},
+    style::{
+        Attribute, Color, Colors, Print, SetAttribute, SetBackgroundColor, SetColors,
+        SetForegroundColor,
+    },
     terminal::{Clear, ClearType},
     Command,
 };
@@ -66,6 +69,24 @@ impl HistoryLine {
         }
     }
 
+    /// Convert a rendered line, keeping its colors, bold and dim
+    pub fn from_line(line: &Line<'_>) -> Self {
+        let spans = line
+            .spans
+            .iter()
+            .map(|span| {
+                let style = line.style.patch(span.style);
+                HistorySpan {
+                    text: span.content.to_string(),
+                    fg: style.fg.map(to_crossterm_color),
+                    bold: style.add_modifier.contains(Modifier::BOLD),
+                    dim: style.add_modifier.contains(Modifier::DIM),
+                }
+            })
+            .collect();
+        Self { spans }
+    }
+
     pub fn to_line(&self) -> Line<'_> {
         let spans: Vec<RSpan> = self
             .spans
@@ -160,50 +181,7 @@ pub fn insert_history_lines(
         let wrapped = wrap_line(&owned, width);
         for wrapped_line in wrapped {
             queue!(writer, Print("\r\n"))?;
-            queue!(
-                writer,
-                Clear(ClearType::UntilNewLine),
-                SetColors(Colors::new(
-                    wrapped_line
-                        .style
-                        .fg
-                        .map(to_crossterm_color)
-                        .unwrap_or(crossterm::style::Color::Reset),
-                    wrapped_line
-                        .style
-                        .bg
-                        .map(to_crossterm_color)
-                        .unwrap_or(crossterm::style::Color::Reset)
-                ))
-            )?;
-
-            let merged_spans: Vec<RSpan> = wrapped_line
-                .spans
-                .iter()
-                .map(|s| RSpan {
-                    content: s.content.clone(),
-                    style: s.style.patch(wrapped_line.style),
-                })
-                .collect();
-
-            for span in merged_spans {
-                queue!(
-                    writer,
-                    SetForegroundColor(
-                        span.style
-                            .fg
-                            .map(to_crossterm_color)
-                            .unwrap_or(crossterm::style::Color::Reset)
-                    ),
-                    SetBackgroundColor(
-                        span.style
-                            .bg
-                            .map(to_crossterm_color)
-                            .unwrap_or(crossterm::style::Color::Reset)
-                    ),
-                    Print(span.content)
-                )?;
-            }
+            print_row(writer, &wrapped_line)?;
         }
     }
 
@@ -220,6 +198,69 @@ pub fn insert_history_lines(
     Ok(())
 }
 
+/// Rows `lines` take up on a screen `width` columns wide, as
+/// [`insert_history_lines`] prints them
+pub fn wrap_history_lines(lines: &[Line<'_>], width: u16) -> Vec<Line<'static>> {
+    let width = width.max(1) as usize;
+    lines
+        .iter()
+        .flat_map(|line| wrap_line(&line_to_static(line), width))
+        .collect()
+}
+
+/// Print `line` over screen row `row` again, leaving the cursor where it was
+pub fn repaint_history_row(writer: &mut impl Write, row: u16, line: &Line<'_>) -> std::io::Result<()> {
+    queue!(writer, SavePosition, MoveTo(0, row))?;
+    print_row(writer, line)?;
+    queue!(writer, SetAttribute(Attribute::Reset), RestorePosition)?;
+    writer.flush()
+}
+
+/// Clear the rest of the current row and print `line` with its colors
+fn print_row(writer: &mut impl Write, line: &Line<'_>) -> std::io::Result<()> {
+    queue!(
+        writer,
+        Clear(ClearType::UntilNewLine),
+        SetColors(Colors::new(
+            line.style
+                .fg
+                .map(to_crossterm_color)
+                .unwrap_or(crossterm::style::Color::Reset),
+            line.style
+                .bg
+                .map(to_crossterm_color)
+                .unwrap_or(crossterm::style::Color::Reset)
+        ))
+    )?;
+
+    for span in &line.spans {
+        let style = span.style.patch(line.style);
+        let reverse = if style.add_modifier.contains(Modifier::REVERSED) {
+            Attribute::Reverse
+        } else {
+            Attribute::NoReverse
+        };
+        queue!(
+            writer,
+            SetForegroundColor(
+                style
+                    .fg
+                    .map(to_crossterm_color)
+                    .unwrap_or(crossterm::style::Color::Reset)
+            ),
+            SetBackgroundColor(
+                style
+                    .bg
+                    .map(to_crossterm_color)
+                    .unwrap_or(crossterm::style::Color::Reset)
+            ),
+            SetAttribute(reverse),
+            Print(&span.content)
+        )?;
+    }
+    Ok(())
+}
+
 /// Wrap a line to the given width using word boundaries where possible.
 fn wrap_line(line: &Line<'static>, width: usize) -> Vec<Line<'static>> {
     if width == 0 {
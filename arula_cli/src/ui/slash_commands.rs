@@ -0,0 +1,392 @@
+//! Slash command parsing for the TUI input line
+//!
+//! Messages starting with `/` are interpreted as commands instead of being
+//! sent to the model. Parsing lives here; execution is handled by `TuiApp`.
+
+/// A parsed slash command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// `/help` - list available commands
+    Help,
+    /// `/walkthrough <base>..<head>` - commit-by-commit branch explanation
+    Walkthrough(String),
+    /// `/commit [files...]` - stage files and commit with a drafted message
+    Commit(Vec<String>),
+    /// `/architecture [mermaid|dot]` - regenerate the project architecture map
+    Architecture(String),
+    /// `/grounded [on|off]` - answer questions only from cited repository code
+    Grounded(String),
+    /// `/source [n]` - open a source cited by the last grounded answer
+    Source(Option<usize>),
+    /// `/run [n]` - run a code block from the last response in a scratch project
+    Run(Option<usize>),
+    /// `/profile [list|switch <name>]` - show or change the configuration profile
+    Profile(String),
+    /// `/goto <time>` - select the message closest to a time
+    Goto(String),
+    /// `/timestamps [on|off]` - show the time next to messages
+    Timestamps(String),
+    /// `/mouse [on|off]` - capture the mouse, or leave it to the terminal
+    Mouse(String),
+    /// `/spinners [name|progress <name>]` - preview or pick spinner and progress styles
+    Spinners(String),
+    /// `/theme [name]` - list the color themes, or switch to one
+    Theme(String),
+    /// `/set [<setting> <value>]` - show or change temperature, top_p and max_tokens
+    Set(String),
+    /// `/checkpoint [name]` - mark the current point of the conversation
+    Checkpoint(String),
+    /// `/branch [checkpoint]` - branch from a checkpoint, or browse the branch tree
+    Branch(String),
+    /// `/scripts [reload]` - list the loaded user scripts, or reload them
+    Scripts(String),
+    /// `/plugins [reload]` - list the installed WASM plugins, or reload them
+    Plugins(String),
+    /// `/edit-last [message]` - replace the last message and regenerate the reply
+    EditLast(String),
+    /// `/regenerate [temperature]` - ask for a new reply to the last message
+    Regenerate(String),
+    /// `/compact` - summarize older messages to free up context
+    Compact,
+    /// `/steer <message>` - redirect the running agent without cancelling it
+    Steer(String),
+    /// `/session` - the conversation's title, size and rolling brief
+    Session,
+    /// `/stats` - counts, tool usage and token trends of this and recent sessions
+    Stats,
+    /// `/present` - step through the conversation one message at a time
+    Present,
+    /// `/files` - browse the project and attach files to the next message
+    Files,
+    /// `/export [file]` - write the conversation to a Markdown file
+    Export(String),
+    /// `/copy [last|code [n]]` - copy the last response or one of its code blocks
+    Copy(String),
+    /// `/debug [last-error|cache]` - details of the last provider error, or tool cache statistics
+    Debug(String),
+    /// `/criteria [add <check>|clear]` - success criteria checked when a run ends
+    Criteria(String),
+    /// `/voice` - record speech and put the transcript in the input
+    Voice,
+    /// `/speak [on|off|stop]` - read the last reply aloud, or every reply
+    Speak(String),
+    /// `/logs [tail|path]` - follow the log file, or show where it is
+    Logs(String),
+    /// A command this list doesn't know: name and arguments. User scripts may provide it.
+    Unknown(String, String),
+}
+
+/// Available commands with their usage and description, shown by `/help`
+pub const SLASH_COMMANDS: &[(&str, &str)] = &[
+    ("/help", "Show available commands"),
+    (
+        "/walkthrough <base>..<head>",
+        "Explain a branch commit by commit",
+    ),
+    (
+        "/commit [files...]",
+        "Commit staged changes with an AI-drafted message",
+    ),
+    (
+        "/architecture [mermaid|dot]",
+        "Write an architecture diagram of the project",
+    ),
+    (
+        "/grounded [on|off]",
+        "Answer questions strictly from cited repository code",
+    ),
+    ("/source [n]", "Open a source cited by the last grounded answer"),
+    (
+        "/run [n]",
+        "Run a code block from the last response in a scratch project",
+    ),
+    (
+        "/profile [list|switch <name>]",
+        "Show or switch the configuration profile",
+    ),
+    (
+        "/goto <time>",
+        "Jump to the message closest to a time (14:30, yesterday 9am, 2h ago)",
+    ),
+    ("/timestamps [on|off]", "Show the time next to messages"),
+    (
+        "/mouse [on|off]",
+        "Wheel through messages and drag to copy; off leaves selection to the terminal",
+    ),
+    (
+        "/spinners [name|progress <name>]",
+        "Preview spinner and progress bar styles, or pick one",
+    ),
+    ("/theme [name]", "List color themes, or switch to one"),
+    (
+        "/set [<setting> <value>]",
+        "Set temperature, top_p or max_tokens (e.g. /set temperature 0.2)",
+    ),
+    (
+        "/checkpoint [name]",
+        "Mark this point of the conversation to branch from later",
+    ),
+    (
+        "/branch [checkpoint]",
+        "Branch from a checkpoint, or browse the conversation tree",
+    ),
+    (
+        "/scripts [reload]",
+        "List the scripts in ~/.arula/scripts and what they add, or reload them",
+    ),
+    (
+        "/plugins [reload]",
+        "List the WASM plugins in ~/.arula/plugins and what they may access, or reload them",
+    ),
+    (
+        "/edit-last [message]",
+        "Edit your last message and regenerate the reply",
+    ),
+    (
+        "/regenerate [temperature]",
+        "Get a new reply to your last message, optionally at another temperature",
+    ),
+    (
+        "/compact",
+        "Summarize older messages so the conversation fits the context window",
+    ),
+    (
+        "/steer <message>",
+        "While the agent works, change its course without stopping it",
+    ),
+    (
+        "/session",
+        "Show this conversation's details and its brief of what you're doing",
+    ),
+    (
+        "/stats",
+        "Chart messages, tokens, cost, latency and tool use of this session and the last 30 days",
+    ),
+    (
+        "/present",
+        "Present the conversation full-screen, one message at a time, without tool output",
+    ),
+    (
+        "/files",
+        "Browse the project and attach files to your next message (Ctrl+O)",
+    ),
+    (
+        "/export [file]",
+        "Save the conversation as Markdown, titled by its brief",
+    ),
+    (
+        "/copy [last|code [n]]",
+        "Copy the last response, or its code block n (Ctrl+Y cycles through them)",
+    ),
+    (
+        "/debug [last-error|cache]",
+        "Show the last provider error in full, or tool result cache statistics",
+    ),
+    (
+        "/criteria [add <check>|clear]",
+        "Set checks that decide when a task is done: tests, file <path>, cmd <command>",
+    ),
+    (
+        "/voice",
+        "Record speech and put the transcript in the input to review; Enter stops, Esc cancels",
+    ),
+    (
+        "/speak [on|off|stop]",
+        "Read the last reply aloud; on/off reads every reply, stop (or Esc) stops",
+    ),
+    (
+        "/logs [tail|path]",
+        "Follow the log file with level filtering, or show where the logs are kept",
+    ),
+];
+
+/// Parse an input line into a slash command
+///
+/// Returns `None` when the input is not a command (doesn't start with `/`).
+pub fn parse_slash_command(input: &str) -> Option<SlashCommand> {
+    let input = input.trim();
+    let rest = input.strip_prefix('/')?;
+    let (name, args) = match rest.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (rest, ""),
+    };
+
+    let command = match name.to_lowercase().as_str() {
+        "help" | "?" => SlashCommand::Help,
+        "walkthrough" | "wt" => SlashCommand::Walkthrough(args.to_string()),
+        "commit" | "ci" => {
+            SlashCommand::Commit(args.split_whitespace().map(str::to_string).collect())
+        }
+        "architecture" | "arch" => SlashCommand::Architecture(args.to_string()),
+        "grounded" | "ask" => SlashCommand::Grounded(args.to_lowercase()),
+        "source" | "src" => {
+            let number = args.trim_start_matches('[').trim_end_matches(']');
+            SlashCommand::Source(number.parse().ok())
+        }
+        "run" => SlashCommand::Run(args.parse().ok()),
+        "profile" | "profiles" => SlashCommand::Profile(args.to_string()),
+        "goto" | "go" => SlashCommand::Goto(args.to_string()),
+        "timestamps" | "time" => SlashCommand::Timestamps(args.to_lowercase()),
+        "mouse" => SlashCommand::Mouse(args.to_lowercase()),
+        "spinners" | "spinner" => SlashCommand::Spinners(args.to_lowercase()),
+        "theme" | "themes" => SlashCommand::Theme(args.to_lowercase()),
+        "set" => SlashCommand::Set(args.to_lowercase()),
+        "checkpoint" | "cp" => SlashCommand::Checkpoint(args.to_string()),
+        "branch" => SlashCommand::Branch(args.to_string()),
+        "scripts" => SlashCommand::Scripts(args.to_lowercase()),
+        "plugins" | "plugin" => SlashCommand::Plugins(args.to_lowercase()),
+        "edit-last" | "edit" => SlashCommand::EditLast(args.to_string()),
+        "regenerate" | "regen" => SlashCommand::Regenerate(args.to_string()),
+        "compact" => SlashCommand::Compact,
+        "steer" => SlashCommand::Steer(args.to_string()),
+        "session" | "info" => SlashCommand::Session,
+        "stats" | "statistics" => SlashCommand::Stats,
+        "present" | "presentation" => SlashCommand::Present,
+        "files" | "attach" => SlashCommand::Files,
+        "export" => SlashCommand::Export(args.to_string()),
+        "copy" | "yank" => SlashCommand::Copy(args.to_lowercase()),
+        "debug" => SlashCommand::Debug(args.to_lowercase()),
+        "criteria" | "done-when" => SlashCommand::Criteria(args.to_string()),
+        "voice" | "mic" => SlashCommand::Voice,
+        "speak" | "tts" => SlashCommand::Speak(args.to_lowercase()),
+        "logs" | "log" => SlashCommand::Logs(args.to_lowercase()),
+        _ => SlashCommand::Unknown(name.to_string(), args.to_string()),
+    };
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_not_a_command() {
+        assert_eq!(parse_slash_command("hello /world"), None);
+        assert_eq!(parse_slash_command(""), None);
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_slash_command("/help"), Some(SlashCommand::Help));
+        assert_eq!(
+            parse_slash_command("/walkthrough main..feature"),
+            Some(SlashCommand::Walkthrough("main..feature".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("  /WT  main..  "),
+            Some(SlashCommand::Walkthrough("main..".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/commit src/a.rs src/b.rs"),
+            Some(SlashCommand::Commit(vec![
+                "src/a.rs".to_string(),
+                "src/b.rs".to_string()
+            ]))
+        );
+        assert_eq!(parse_slash_command("/commit"), Some(SlashCommand::Commit(vec![])));
+        assert_eq!(
+            parse_slash_command("/arch dot"),
+            Some(SlashCommand::Architecture("dot".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/grounded ON"),
+            Some(SlashCommand::Grounded("on".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/src [2]"),
+            Some(SlashCommand::Source(Some(2)))
+        );
+        assert_eq!(parse_slash_command("/source"), Some(SlashCommand::Source(None)));
+        assert_eq!(parse_slash_command("/run 2"), Some(SlashCommand::Run(Some(2))));
+        assert_eq!(
+            parse_slash_command("/profile switch work"),
+            Some(SlashCommand::Profile("switch work".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/goto yesterday 9am"),
+            Some(SlashCommand::Goto("yesterday 9am".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/timestamps Off"),
+            Some(SlashCommand::Timestamps("off".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/mouse OFF"),
+            Some(SlashCommand::Mouse("off".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/spinner Progress thin"),
+            Some(SlashCommand::Spinners("progress thin".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/theme Solarized"),
+            Some(SlashCommand::Theme("solarized".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/steer skip the docs, Focus on the test"),
+            Some(SlashCommand::Steer("skip the docs, Focus on the test".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/set Temperature 0.2"),
+            Some(SlashCommand::Set("temperature 0.2".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/cp before refactor"),
+            Some(SlashCommand::Checkpoint("before refactor".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/branch"),
+            Some(SlashCommand::Branch(String::new()))
+        );
+        assert_eq!(
+            parse_slash_command("/scripts Reload"),
+            Some(SlashCommand::Scripts("reload".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/plugins"),
+            Some(SlashCommand::Plugins(String::new()))
+        );
+        assert_eq!(
+            parse_slash_command("/edit-last  Fix the parser "),
+            Some(SlashCommand::EditLast("Fix the parser".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/regen 1.2"),
+            Some(SlashCommand::Regenerate("1.2".to_string()))
+        );
+        assert_eq!(parse_slash_command("/compact"), Some(SlashCommand::Compact));
+        assert_eq!(parse_slash_command("/info"), Some(SlashCommand::Session));
+        assert_eq!(parse_slash_command("/present"), Some(SlashCommand::Present));
+        assert_eq!(parse_slash_command("/stats"), Some(SlashCommand::Stats));
+        assert_eq!(
+            parse_slash_command("/logs tail"),
+            Some(SlashCommand::Logs("tail".to_string()))
+        );
+        assert_eq!(parse_slash_command("/files"), Some(SlashCommand::Files));
+        assert_eq!(parse_slash_command("/mic"), Some(SlashCommand::Voice));
+        assert_eq!(
+            parse_slash_command("/tts On"),
+            Some(SlashCommand::Speak("on".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/export notes/Parser.md"),
+            Some(SlashCommand::Export("notes/Parser.md".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/copy code 2"),
+            Some(SlashCommand::Copy("code 2".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/debug Last-Error"),
+            Some(SlashCommand::Debug("last-error".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/criteria add cmd cargo build"),
+            Some(SlashCommand::Criteria("add cmd cargo build".to_string()))
+        );
+        assert_eq!(
+            parse_slash_command("/nope some args"),
+            Some(SlashCommand::Unknown("nope".to_string(), "some args".to_string()))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+//! Viewer for the sources cited by a grounded answer
+//!
+//! Lists the numbered sources and opens the cited region of a file with the
+//! surrounding code:
+//!
+//! - list: `↑`/`↓` or `1`-`9` select, `Enter` open, `q`/`Esc` close
+//! - region: `↑`/`↓`/`PgUp`/`PgDn` scroll, `e` open in `$EDITOR`, `Esc` back
+
+use anyhow::Result;
+use arula_core::utils::grounded::Snippet;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+use crate::ui::input_editor::{input_modes_enabled, set_input_modes};
+use crate::ui::mouse::{mouse_captured, set_mouse_capture};
+
+/// Lines of surrounding code shown above and below a cited region
+const CONTEXT_LINES: usize = 10;
+
+/// What the user chose to do in the source view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceAction {
+    /// Open the source with this index in an external editor
+    Edit(usize),
+    /// Close the view
+    Close,
+}
+
+/// Full-screen viewer for cited sources
+pub struct SourceView<'a> {
+    sources: &'a [Snippet],
+    root: PathBuf,
+    selected: usize,
+    /// Whether the selected region is open (otherwise the list is shown)
+    open: bool,
+    scroll: u16,
+}
+
+impl<'a> SourceView<'a> {
+    /// Create a view; `open` starts on that source's region instead of the list
+    pub fn new(sources: &'a [Snippet], root: &Path, open: Option<usize>) -> Self {
+        Self {
+            sources,
+            root: root.to_path_buf(),
+            selected: open.unwrap_or(0).min(sources.len().saturating_sub(1)),
+            open: open.is_some(),
+            scroll: 0,
+        }
+    }
+
+    /// Show the view until the user closes it or asks for an editor
+    pub fn show(&mut self) -> Result<SourceAction> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<SourceAction> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+        let opened_directly = self.open;
+        let mut region = self.load_region();
+
+        loop {
+            terminal.draw(|f| {
+                if self.open {
+                    self.render_region(f, &region)
+                } else {
+                    self.render_list(f)
+                }
+            })?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            if self.open {
+                let max_scroll = region.len() as u16;
+                match key.code {
+                    KeyCode::Char('e') => return Ok(SourceAction::Edit(self.selected)),
+                    KeyCode::Char('q') => return Ok(SourceAction::Close),
+                    KeyCode::Esc | KeyCode::Backspace if opened_directly => {
+                        return Ok(SourceAction::Close);
+                    }
+                    KeyCode::Esc | KeyCode::Backspace => self.open = false,
+                    KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                    KeyCode::Down => self.scroll = (self.scroll + 1).min(max_scroll),
+                    KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.scroll = (self.scroll + 10).min(max_scroll),
+                    _ => {}
+                }
+            } else {
+                match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(SourceAction::Close),
+                    KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                    KeyCode::Down if self.selected + 1 < self.sources.len() => self.selected += 1,
+                    KeyCode::Char(c @ '1'..='9') => {
+                        let index = c as usize - '1' as usize;
+                        if index < self.sources.len() {
+                            self.selected = index;
+                            self.open_selected(&mut region);
+                        }
+                    }
+                    KeyCode::Enter => self.open_selected(&mut region),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn open_selected(&mut self, region: &mut Vec<(usize, String)>) {
+        self.open = true;
+        *region = self.load_region();
+    }
+
+    /// Read the selected source's lines plus surrounding context
+    ///
+    /// Falls back to the retrieved snippet text if the file can't be read.
+    /// The scroll position starts a few lines above the cited region.
+    fn load_region(&mut self) -> Vec<(usize, String)> {
+        let Some(source) = self.sources.get(self.selected) else {
+            return Vec::new();
+        };
+        let first = source.start_line.saturating_sub(CONTEXT_LINES).max(1);
+        let lines: Vec<(usize, String)> =
+            match std::fs::read_to_string(self.root.join(&source.path)) {
+                Ok(content) => content
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| (i + 1, line.to_string()))
+                    .skip(first - 1)
+                    .take(source.end_line + CONTEXT_LINES + 1 - first)
+                    .collect(),
+                Err(_) => source
+                    .text
+                    .lines()
+                    .enumerate()
+                    .map(|(i, line)| (source.start_line + i, line.to_string()))
+                    .collect(),
+            };
+        self.scroll = lines
+            .iter()
+            .position(|(n, _)| *n >= source.start_line.saturating_sub(3))
+            .unwrap_or(0) as u16;
+        lines
+    }
+
+    fn render_list(&self, f: &mut ratatui::Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(f.area());
+
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let dim = Style::default().fg(Color::DarkGray);
+
+        let lines: Vec<Line> = self
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| {
+                let marker = Span::styled(format!(" [{}] ", i + 1), accent);
+                let label = Span::raw(source.label());
+                if i == self.selected {
+                    Line::from(vec![marker, label])
+                        .style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    Line::from(vec![marker, label])
+                }
+            })
+            .collect();
+        let body = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(accent)
+                .title(" Sources "),
+        );
+        f.render_widget(body, chunks[0]);
+
+        let footer = Line::from(vec![Span::styled(
+            "↑↓/1-9 select  Enter open  q/Esc close",
+            dim,
+        )]);
+        f.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+
+    fn render_region(&self, f: &mut ratatui::Frame, region: &[(usize, String)]) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(3), Constraint::Length(1)])
+            .split(f.area());
+
+        let accent = Style::default().fg(Color::Rgb(70, 130, 180));
+        let dim = Style::default().fg(Color::DarkGray);
+        let Some(source) = self.sources.get(self.selected) else {
+            return;
+        };
+        let width = region.last().map(|(n, _)| n.to_string().len()).unwrap_or(1);
+
+        let lines: Vec<Line> = region
+            .iter()
+            .map(|(n, text)| {
+                let cited = (source.start_line..=source.end_line).contains(n);
+                let gutter = if cited { "▌" } else { " " };
+                let number_style = if cited { accent } else { dim };
+                let text_style = if cited {
+                    Style::default()
+                } else {
+                    Style::default().add_modifier(Modifier::DIM)
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:>width$} {}", n, gutter), number_style),
+                    Span::styled(text.replace('\t', "    "), text_style),
+                ])
+            })
+            .collect();
+        let title = format!(" [{}] {} ", self.selected + 1, source.label());
+        let body = Paragraph::new(lines).scroll((self.scroll, 0)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(accent)
+                .title(title),
+        );
+        f.render_widget(body, chunks[0]);
+
+        let footer = Line::from(vec![Span::styled(
+            "↑↓ scroll  e open in $EDITOR  Esc back  q close",
+            dim,
+        )]);
+        f.render_widget(Paragraph::new(footer), chunks[1]);
+    }
+}
+
+/// Open a file at a line in the user's editor (`$VISUAL`, `$EDITOR`, or `vi`)
+///
+/// Uses the `+<line>` argument understood by vi, nano, emacs and most
+/// terminal editors. Raw mode is suspended while the editor runs.
+pub fn open_in_editor(path: &Path, line: usize) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+
+    // The editor gets the mouse and keyboard as it expects them while it runs
+    let mouse = mouse_captured();
+    let input_modes = input_modes_enabled();
+    set_mouse_capture(false)?;
+    set_input_modes(false)?;
+    crossterm::terminal::disable_raw_mode()?;
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(format!("+{}", line))
+        .arg(path)
+        .status();
+    crossterm::terminal::enable_raw_mode()?;
+    set_mouse_capture(mouse)?;
+    set_input_modes(input_modes)?;
+
+    let status = status?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
@@ -0,0 +1,335 @@
+//! Usage dashboard for `/stats`
+//!
+//! Charts the last 30 days from the local usage ledger: responses per day
+//! as bars, token use, cost and response latency as sparklines, and how
+//! often each tool ran. A header sums up the current session and its
+//! busiest files; recently saved sessions are listed under the tools.
+//!
+//! - `q`/`Esc` close
+
+use anyhow::Result;
+use arula_core::utils::stats::{self, DayUsage, StatsReport, TREND_DAYS};
+use chrono::Local;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Bar, BarChart, BarGroup, Block, Borders, Paragraph, Sparkline},
+};
+use std::io::stdout;
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// Tools shown in the frequency chart
+const MAX_TOOLS: usize = 10;
+
+/// Full-screen usage dashboard
+pub struct StatsView<'a> {
+    report: &'a StatsReport,
+}
+
+impl<'a> StatsView<'a> {
+    pub fn new(report: &'a StatsReport) -> Self {
+        Self { report }
+    }
+
+    /// Show the dashboard until the user closes it
+    pub fn show(&self) -> Result<()> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Press
+                && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(5),
+                Constraint::Min(8),
+                Constraint::Length(5),
+                Constraint::Length(1),
+            ])
+            .split(f.area());
+        let charts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(rows[1]);
+        let sparklines = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 3); 3])
+            .split(rows[2]);
+
+        self.render_summary(f, rows[0]);
+        self.render_responses(f, charts[0]);
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(4),
+                Constraint::Length(self.report.recent_sessions.len().max(1) as u16 + 2),
+            ])
+            .split(charts[1]);
+        self.render_tools(f, side[0]);
+        self.render_sessions(f, side[1]);
+        self.render_sparklines(f, &sparklines);
+
+        let footer = Line::styled("q/Esc close", Style::default().fg(Color::DarkGray));
+        f.render_widget(Paragraph::new(footer), rows[3]);
+    }
+
+    fn render_summary(&self, f: &mut ratatui::Frame, area: Rect) {
+        let report = self.report;
+        let session = &report.session;
+        let dim = Style::default().fg(Color::DarkGray);
+
+        let mut this_session = vec![
+            Span::styled("This session  ", dim),
+            Span::raw(format!(
+                "{} message(s) · {} tool call(s), {} failed",
+                session.user_messages + session.assistant_messages,
+                session.tool_calls(),
+                session.tool_failures()
+            )),
+        ];
+        if let Some(latency) = session.average_latency {
+            this_session.push(Span::raw(format!(
+                " · replies after {:.1}s",
+                latency.as_secs_f64()
+            )));
+        }
+
+        let files = session
+            .files
+            .iter()
+            .map(|(path, touches)| format!("{} ({}×)", path, touches))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let busiest = vec![
+            Span::styled("Busiest files ", dim),
+            if files.is_empty() {
+                Span::styled("none yet", dim)
+            } else {
+                Span::styled(files, Style::default().fg(Color::Cyan))
+            },
+        ];
+
+        let responses: u64 = report.trend.iter().map(|day| day.responses).sum();
+        let tokens: u64 = report.trend.iter().map(|day| day.tokens).sum();
+        let cost: f64 = report.trend.iter().map(|day| day.cost).sum();
+        let mut last_days = vec![
+            Span::styled(format!("{:<14}", format!("Last {} days", TREND_DAYS)), dim),
+            Span::raw(format!(
+                "{} response(s) · {} tokens · ${:.2} estimated",
+                responses,
+                stats::format_tokens(tokens),
+                cost
+            )),
+        ];
+        if let Some(latency) = report.average_latency() {
+            last_days.push(Span::raw(format!(
+                " · {:.1}s per response",
+                latency.as_secs_f64()
+            )));
+        }
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Rgb(70, 130, 180)))
+            .title(Span::styled(
+                " Statistics ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+        f.render_widget(
+            Paragraph::new(vec![
+                Line::from(this_session),
+                Line::from(busiest),
+                Line::from(last_days),
+            ])
+            .block(block),
+            area,
+        );
+    }
+
+    /// Responses per day, as many of the latest days as fit
+    fn render_responses(&self, f: &mut ratatui::Frame, area: Rect) {
+        let inner_width = area.width.saturating_sub(2);
+        let (bar_width, gap) = (2, 1);
+        let fit = ((inner_width + gap) / (bar_width + gap)) as usize;
+        let days = &self.report.trend[self.report.trend.len().saturating_sub(fit)..];
+
+        let bars: Vec<Bar> = days
+            .iter()
+            .map(|day| {
+                Bar::default()
+                    .value(day.responses)
+                    .label(Line::from(day.day.format("%d").to_string()))
+                    .text_value(String::new())
+            })
+            .collect();
+        let chart = BarChart::default()
+            .block(titled(format!("Responses per day, {} days", days.len())))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(gap)
+            .bar_style(Style::default().fg(Color::Cyan))
+            .label_style(Style::default().fg(Color::DarkGray));
+        f.render_widget(chart, area);
+    }
+
+    /// Calls per tool as horizontal bars
+    fn render_tools(&self, f: &mut ratatui::Frame, area: Rect) {
+        let tools = &self.report.tools;
+        let label_width = tools
+            .iter()
+            .take(MAX_TOOLS)
+            .map(|tool| tool.tool.chars().count())
+            .max()
+            .unwrap_or_default();
+        let bars: Vec<Bar> = tools
+            .iter()
+            .take(MAX_TOOLS)
+            .map(|tool| {
+                let mut text = tool.calls.to_string();
+                if tool.failures > 0 {
+                    text.push_str(&format!(" ({} failed)", tool.failures));
+                }
+                Bar::default()
+                    .value(tool.calls)
+                    .label(Line::from(format!(
+                        "{:<width$} ",
+                        tool.tool,
+                        width = label_width
+                    )))
+                    .text_value(text)
+            })
+            .collect();
+
+        let block = titled(format!("Tool calls, {} days", TREND_DAYS));
+        if bars.is_empty() {
+            let empty = Paragraph::new(Line::styled(
+                "No tool calls yet",
+                Style::default().fg(Color::DarkGray),
+            ));
+            f.render_widget(empty.block(block), area);
+            return;
+        }
+        let chart = BarChart::default()
+            .block(block)
+            .direction(Direction::Horizontal)
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(1)
+            .bar_gap(0)
+            .bar_style(Style::default().fg(Color::Magenta))
+            .value_style(Style::default().fg(Color::White));
+        f.render_widget(chart, area);
+    }
+
+    /// Recently saved sessions, newest first
+    fn render_sessions(&self, f: &mut ratatui::Frame, area: Rect) {
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut lines: Vec<Line> = self
+            .report
+            .recent_sessions
+            .iter()
+            .map(|record| {
+                Line::from(vec![
+                    Span::styled(
+                        format!(
+                            "{:<7}",
+                            record.updated_at.with_timezone(&Local).format("%b %-d")
+                        ),
+                        dim,
+                    ),
+                    Span::raw(format!("{:>4} msg  ", record.message_count)),
+                    Span::raw(record.title.clone()),
+                ])
+            })
+            .collect();
+        if lines.is_empty() {
+            lines.push(Line::styled("No saved sessions yet", dim));
+        }
+        f.render_widget(
+            Paragraph::new(lines).block(titled("Recent sessions".to_string())),
+            area,
+        );
+    }
+
+    /// Tokens, cost and latency per day
+    fn render_sparklines(&self, f: &mut ratatui::Frame, areas: &[Rect]) {
+        let trend = &self.report.trend;
+        let series = |value: fn(&DayUsage) -> u64| -> Vec<u64> {
+            // Newest days on the right, as many as fit
+            let fit = areas[0].width.saturating_sub(2) as usize;
+            trend[trend.len().saturating_sub(fit)..]
+                .iter()
+                .map(value)
+                .collect()
+        };
+
+        let tokens: u64 = trend.iter().map(|day| day.tokens).sum();
+        let cost: f64 = trend.iter().map(|day| day.cost).sum();
+        let latency = self
+            .report
+            .average_latency()
+            .map(|latency| format!("avg {:.1}s", latency.as_secs_f64()))
+            .unwrap_or_else(|| "none recorded".to_string());
+
+        let charts = [
+            (
+                format!("Tokens · {}", stats::format_tokens(tokens)),
+                series(|day| day.tokens),
+                Color::Green,
+            ),
+            (
+                format!("Cost · ${:.2}", cost),
+                series(|day| (day.cost * 100.0).round() as u64),
+                Color::Yellow,
+            ),
+            (
+                format!("Latency · {}", latency),
+                series(|day| {
+                    day.average_latency()
+                        .map(|latency| latency.as_millis() as u64)
+                        .unwrap_or_default()
+                }),
+                Color::Cyan,
+            ),
+        ];
+        for ((title, data, color), area) in charts.into_iter().zip(areas) {
+            let sparkline = Sparkline::default()
+                .block(titled(title))
+                .data(&data)
+                .style(Style::default().fg(color));
+            f.render_widget(sparkline, *area);
+        }
+    }
+}
+
+fn titled(title: String) -> Block<'static> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(format!(" {} ", title))
+}
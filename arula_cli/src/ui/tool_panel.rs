@@ -0,0 +1,183 @@
+//! Tool activity panel
+//!
+//! An optional pane on the right of the viewport that lists the session's
+//! tool executions while the chat stays on the left: the tool, a summary of
+//! its arguments, how long it ran and the first line of its output. Ctrl+T
+//! shows or hides it, and the choice is saved with the conversation.
+
+use arula_core::utils::icons::Icon;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Rows the viewport keeps while the panel is shown
+pub const PANEL_HEIGHT: u16 = 12;
+
+/// Narrowest screen the panel is shown on
+const MIN_SCREEN_WIDTH: u16 = 80;
+
+/// Executions kept in the panel
+const MAX_ENTRIES: usize = 50;
+
+/// One tool execution
+#[derive(Debug, Clone)]
+struct ToolActivity {
+    id: String,
+    name: String,
+    args: String,
+    started_at: Instant,
+    /// Run time and whether it succeeded, once finished
+    outcome: Option<(Duration, bool)>,
+    /// First line of the result
+    output: Option<String>,
+}
+
+/// Tool executions of the session, oldest first
+#[derive(Debug, Default)]
+pub struct ToolPanel {
+    entries: VecDeque<ToolActivity>,
+}
+
+impl ToolPanel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a tool call that just started; `args` is a short summary
+    pub fn start(&mut self, id: &str, name: &str, args: String) {
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.push_back(ToolActivity {
+            id: id.to_string(),
+            name: name.to_string(),
+            args,
+            started_at: Instant::now(),
+            outcome: None,
+            output: None,
+        });
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Record the result of the tool call `id`
+    pub fn finish(&mut self, id: &str, success: bool, output: &str, duration: Duration) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.id == id) {
+            entry.outcome = Some((duration, success));
+            entry.output = output
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(str::to_string);
+        }
+    }
+
+    /// Forget the executions, e.g. when another conversation is opened
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// The newest executions that fit in `height` rows, newest last;
+    /// running ones are marked with `spinner`
+    pub fn lines(&self, height: u16, spinner: &str) -> Vec<Line<'static>> {
+        if self.entries.is_empty() {
+            return vec![Line::styled(
+                "No tool calls yet",
+                Style::default().fg(Color::DarkGray),
+            )];
+        }
+        let mut lines: Vec<Line<'static>> = Vec::new();
+        for entry in self.entries.iter().rev() {
+            let entry_lines = entry_lines(entry, spinner);
+            if lines.len() + entry_lines.len() > height as usize {
+                break;
+            }
+            lines.splice(0..0, entry_lines);
+        }
+        lines
+    }
+}
+
+/// Whether a screen `width` columns wide has room for the panel
+pub fn fits(width: u16) -> bool {
+    width >= MIN_SCREEN_WIDTH
+}
+
+/// `area` split into the chat on the left and the panel on the right
+pub fn split(area: Rect) -> (Rect, Rect) {
+    let width = (area.width / 3).clamp(30, 60);
+    let chunks = Layout::horizontal([Constraint::Min(0), Constraint::Length(width)]).split(area);
+    (chunks[0], chunks[1])
+}
+
+fn entry_lines(entry: &ToolActivity, spinner: &str) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let (glyph, color, elapsed) = match entry.outcome {
+        None => (
+            spinner.to_string(),
+            Color::Yellow,
+            entry.started_at.elapsed(),
+        ),
+        Some((duration, true)) => (Icon::Success.to_string(), Color::Green, duration),
+        Some((duration, false)) => (Icon::Error.to_string(), Color::Red, duration),
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("{} ", glyph), Style::default().fg(color)),
+        Span::styled(
+            entry.name.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(format!(" {}", format_elapsed(elapsed)), dim),
+    ])];
+    if !entry.args.is_empty() {
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(entry.args.clone(), Style::default().fg(Color::Gray)),
+        ]));
+    }
+    if let Some(output) = &entry.output {
+        lines.push(Line::from(vec![
+            Span::styled("  ↳ ", dim),
+            Span::styled(
+                output.clone(),
+                Style::default().fg(color).add_modifier(Modifier::DIM),
+            ),
+        ]));
+    }
+    lines
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panel_keeps_newest_entries_that_fit() {
+        let mut panel = ToolPanel::new();
+        panel.start("1", "Read", "src/main.rs".to_string());
+        panel.finish("1", true, "\n120 lines\nmore", Duration::from_millis(40));
+        panel.start("2", "Shell", String::new());
+
+        let text: Vec<String> = panel.lines(10, "*").iter().map(Line::to_string).collect();
+        assert_eq!(text.len(), 4);
+        assert!(text[0].ends_with("Read 40ms"));
+        assert_eq!(text[1], "  src/main.rs");
+        assert_eq!(text[2], "  ↳ 120 lines");
+        assert!(text[3].starts_with("* Shell"));
+
+        // Only the running call fits in two rows
+        assert_eq!(panel.lines(2, "*").len(), 1);
+
+        panel.clear();
+        assert_eq!(panel.lines(10, "*")[0].to_string(), "No tool calls yet");
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Result;
 use console::strip_ansi_codes;
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
-    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
     execute,
     style::Color,
     terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
@@ -16,21 +16,91 @@ use ratatui::{
     Frame, Terminal, TerminalOptions, Viewport,
 };
 use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
 use std::io::{self, Stdout};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
+use arula_core::api::provider_error;
 use arula_core::app::AiResponse;
 use arula_core::prelude::detect_project;
+use arula_core::storage::{CommitRecord, Storage};
+use arula_core::tools::builtin::bash::BashResult;
+use arula_core::tools::result_cache::session_cache;
+use arula_core::tools::wasm_plugins::{plugins_dir, MANIFEST_FILE};
+use arula_core::utils::command_explain::explain as explain_command;
+use arula_core::utils::commit_message::{prepare_commit, CommitDraft};
+use arula_core::utils::file_lock::SessionInUse;
+use arula_core::utils::file_mentions::{complete_mention, mention_at, FileIndex};
+use arula_core::utils::git_ops::GitOps;
+use arula_core::utils::success_criteria::Criterion;
+use arula_core::utils::reference_check::{annotate, ReferenceChecker};
+use arula_core::utils::code_lint::{extract_code_blocks, lint_code_blocks, BlockLint};
+use arula_core::utils::accessibility::{plain_mode, to_plain, PLAIN_SPINNER};
+use arula_core::utils::code_runner::{run_code_block, RunEvent, RunKind};
+use arula_core::utils::brief;
+use arula_core::utils::stats::StatsReport;
+use arula_core::utils::compaction::KEEP_TURNS;
+use arula_core::utils::config::{Config, GenerationSettings};
+use arula_core::utils::error_help::{explain, ErrorFix};
+use arula_core::utils::config_watcher::{ConfigReload, ConfigWatcher};
+use arula_core::utils::notifications::{NotificationEvent, NotificationManager};
+use arula_core::utils::shutdown;
+use arula_core::utils::hooks::HookEvent;
+use arula_core::utils::icons::{set_icon_set, Icon};
+use arula_core::utils::logger;
+use arula_core::utils::scripting::scripts_dir;
+use arula_core::utils::style_packs::SpinnerPack;
+use arula_core::utils::themes::{available_themes, set_active_theme, themes_dir, Theme};
+use arula_core::utils::speech::{self, Playback, Utterance};
+use arula_core::utils::voice::{self, Recording};
+use arula_core::utils::grounded::{answer_grounded, number_citations, GroundedAnswer, Snippet};
+use arula_core::utils::pr_description::{generate_pr_description, PrDescription};
+use arula_core::utils::postprocess::PostProcessor;
+use arula_core::utils::architecture::{build_architecture_map, DiagramFormat, NodeKind};
+use arula_core::utils::walkthrough::{generate_walkthrough, parse_range, Walkthrough, WalkthroughProgress};
 use arula_core::App;
 use regex::Regex;
-use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, OnceLock};
 use termimad::MadSkin;
+use tokio::sync::mpsc;
 
-use crate::ui::menus::common::MenuResult;
+use crate::ui::menus::branch_menu::BranchMenu;
+use crate::ui::menus::common::{MenuResult, MenuUtils};
 use crate::ui::menus::main_menu::MainMenu;
+use crate::ui::menus::model_selector::ModelSelector;
+use crate::ui::menus::ConfigMenu;
 use crate::ui::output::OutputHandler;
-use crate::ui::scroll_history::{insert_history_lines, HistoryLine, HistorySpan};
-use arula_core::utils::chat::MessageType;
+use crate::ui::markdown_stream::MarkdownStream;
+use crate::ui::file_tree_view::{self, FileTreeView, Touch};
+use crate::ui::command_palette::{
+    self as palette, builtin_entries, filter, first_visible, typed_name, PaletteEntry,
+};
+use crate::ui::attention::Attention;
+use crate::ui::history_search::HistorySearch;
+use crate::ui::input_editor::{
+    cursor_row_col, edit_externally, move_vertically, prepare_paste, row_bounds, set_input_modes,
+    MAX_INPUT_ROWS,
+};
+use crate::ui::presentation_view::PresentationView;
+use crate::ui::output::code_blocks::set_code_theme;
+use crate::ui::colors::{term_color, TuiTheme};
+use crate::ui::mouse::{highlight, set_mouse_capture, ScreenText, Selection};
+use crate::ui::tool_panel::{self, ToolPanel};
+use crate::ui::scroll_history::{
+    insert_history_lines, repaint_history_row, wrap_history_lines, HistoryLine, HistorySpan,
+};
+use crate::ui::slash_commands::{parse_slash_command, SlashCommand, SLASH_COMMANDS};
+use crate::ui::commit_view::{CommitDecision, CommitView};
+use crate::ui::pr_description_view::{copy_to_clipboard, PrDescriptionAction, PrDescriptionView};
+use crate::ui::source_view::{open_in_editor, SourceAction, SourceView};
+use crate::ui::stats_view::StatsView;
+use crate::ui::log_view::LogView;
+use crate::ui::walkthrough_view::WalkthroughView;
+use arula_core::utils::chat::{ChatMessage, MessageType};
+use arula_core::utils::time::{clock_time, closest_to, day_label, parse_time_target, relative_time};
+use chrono::{Local, NaiveDate, Utc};
 
 /// Tool execution status
 #[derive(Clone)]
@@ -54,6 +124,20 @@ pub enum ToolState {
 /// The TUI viewport height (input + info line)
 const VIEWPORT_HEIGHT: u16 = 2;
 
+/// Content lines of the focused message shown in focus mode
+const FOCUS_PREVIEW_LINES: usize = 6;
+/// Lines of a streaming table shown before it is committed to history
+const STREAM_PREVIEW_LINES: usize = 8;
+/// Earlier prompts loaded for Up/Down recall
+const PROMPT_HISTORY_LIMIT: usize = 500;
+/// Last lines of stdout and of stderr shown under a finished command
+const COMMAND_OUTPUT_LINES: usize = 6;
+/// Sent when the user lets a run the loop watchdog paused go on
+const LOOP_CONTINUE_PROMPT: &str =
+    "Continue. If you were repeating a step, try a different approach.";
+/// Files offered for an `@` mention
+const MAX_MENTIONS: usize = 50;
+
 /// Application state (separate from terminal for borrow checker)
 struct AppState {
     input: String,
@@ -62,6 +146,10 @@ struct AppState {
     thinking_content: String,
     thinking_expanded: bool,
     stream_collector: StreamCollector,
+    /// Renders streamed lines, holding back tables until they end
+    markdown: MarkdownStream,
+    /// Post-processes streamed lines before they are rendered
+    postprocessor: PostProcessor,
     active_tools: Vec<ToolExecution>,
     current_response: String,
     pending_history: Vec<HistoryLine>,
@@ -78,6 +166,100 @@ struct AppState {
     fetching_starters: bool,
     /// Currently selected starter index (for keyboard navigation)
     selected_starter: Option<usize>,
+    /// Status label for a running background command (e.g. /walkthrough)
+    background_status: Option<String>,
+    /// Progress receiver for an in-flight walkthrough
+    walkthrough_rx: Option<mpsc::UnboundedReceiver<WalkthroughProgress>>,
+    /// Most recent walkthrough, reopened by `/walkthrough` without arguments
+    last_walkthrough: Option<Walkthrough>,
+    /// Receiver for an in-flight `/commit` message draft
+    commit_rx: Option<mpsc::UnboundedReceiver<Result<CommitDraft, String>>>,
+    /// Receiver for an in-flight PR description draft (Git menu)
+    pr_description_rx: Option<mpsc::UnboundedReceiver<Result<PrDescription, String>>>,
+    /// Whether questions are answered only from cited repository code (`/grounded`)
+    grounded_mode: bool,
+    /// Receiver for an in-flight grounded answer
+    grounded_rx: Option<mpsc::UnboundedReceiver<Result<GroundedAnswer, String>>>,
+    /// Sources of the last grounded answer, opened by `/source <n>`
+    last_sources: Vec<Snippet>,
+    /// Workspace snapshot used to flag references to missing files, symbols and flags
+    reference_checker: Option<Arc<ReferenceChecker>>,
+    /// Receiver for a reference checker being rebuilt in the background
+    reference_checker_rx: Option<mpsc::UnboundedReceiver<ReferenceChecker>>,
+    /// Receiver for code block lint results of the last response
+    code_lint_rx: Option<mpsc::UnboundedReceiver<Vec<BlockLint>>>,
+    /// Full text of the last streamed response, for `/run` and `/copy`
+    last_response: String,
+    /// Code block of the last response copied last with Ctrl+Y
+    copied_block: Option<usize>,
+    /// Events from an in-flight `/run`
+    run_rx: Option<mpsc::UnboundedReceiver<RunEvent>>,
+    /// Index in `app.messages` of the message selected in focus mode (Esc, then j/k)
+    focused_message: Option<usize>,
+    /// Whether the input holds an edit of the last message, which replaces it when sent
+    editing_prompt: bool,
+    /// Day of the last user message shown, for day separators
+    last_message_date: Option<NaiveDate>,
+    /// Reloads config.json when it's edited while running
+    config_watcher: Option<ConfigWatcher>,
+    /// Active spinner pack (`appearance.spinner`)
+    spinner: SpinnerPack,
+    /// Earlier prompts, newest first
+    prompt_history: Vec<String>,
+    /// Position in `prompt_history` while recalling with Up/Down
+    history_index: Option<usize>,
+    /// Input typed before recalling started
+    history_draft: String,
+    /// Fix offered for the last failed request, applied with Tab
+    error_fix: Option<ErrorFix>,
+    /// The loop watchdog paused the last run: Enter continues it, Esc stops
+    loop_paused: bool,
+    /// Session another instance has open: Enter opens it read-only
+    read_only_offer: Option<String>,
+    /// Microphone recording started by `/voice`: Enter stops it, Esc cancels
+    voice: Option<Recording>,
+    /// Receiver for an in-flight transcription
+    voice_rx: Option<mpsc::UnboundedReceiver<Result<String, String>>>,
+    /// A reply being read aloud; Esc or `/speak stop` stops it
+    speech: Option<Playback>,
+    /// Receiver for speech being prepared
+    speech_rx: Option<mpsc::UnboundedReceiver<Result<Arc<Utterance>, String>>>,
+    /// Prompts entered while a response was streaming, sent in order
+    queued_prompts: VecDeque<String>,
+    /// Last key press or streamed response, for the idle session brief
+    last_activity: Instant,
+    /// Receiver for a session brief being written in the background
+    brief_rx: Option<mpsc::UnboundedReceiver<Result<String, String>>>,
+    /// Conversation messages the last brief request covered
+    brief_covers: usize,
+    /// History rows on screen, for selecting with the mouse
+    screen_text: ScreenText,
+    /// Drag over the history in progress
+    selection: Option<Selection>,
+    /// Tool executions of the session, for the tool activity panel
+    tool_panel: ToolPanel,
+    /// Whether the tool activity panel is shown (Ctrl+T)
+    show_tool_panel: bool,
+    /// Files the agent read or changed this session, for the file browser
+    touched_files: HashMap<PathBuf, Touch>,
+    /// Search through the history (`/` in focus mode, Ctrl+F)
+    search: Option<HistorySearch>,
+    /// Selection in the command palette or `@` file list, with the input
+    /// it was made for
+    completion_selection: Option<(String, usize)>,
+    /// Input the command palette or `@` file list was closed for with Esc
+    completion_hidden_for: Option<String>,
+    /// Desktop notifications for work finishing while the terminal is
+    /// unfocused
+    notifier: NotificationManager,
+    /// When the current response started streaming
+    run_started: Instant,
+    /// When the running batch of tool calls started
+    tools_started: Option<Instant>,
+    /// Error reported by the current run, for its notification
+    run_error: Option<String>,
+    /// Bell, title flash and progress state while the agent waits on the user
+    attention: Attention,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,10 +267,16 @@ enum HistoryKind {
     User,
     Ai,
     Tool,
+    System,
 }
 
 impl AppState {
     fn new(app: App, width: u16, height: u16) -> Self {
+        let spinner = spinner_pack(&app.config);
+        let notifier = NotificationManager::new(
+            Arc::new(AtomicBool::new(true)),
+            app.config.get_notifications(),
+        );
         Self {
             input: String::new(),
             input_cursor: 0,
@@ -96,6 +284,8 @@ impl AppState {
             thinking_content: String::new(),
             thinking_expanded: false,
             stream_collector: StreamCollector::new(),
+            markdown: MarkdownStream::new(),
+            postprocessor: PostProcessor::new(&Default::default()),
             active_tools: Vec::new(),
             current_response: String::new(),
             pending_history: Vec::new(),
@@ -109,19 +299,100 @@ impl AppState {
             conversation_starters: Vec::new(),
             fetching_starters: false,
             selected_starter: None,
+            background_status: None,
+            walkthrough_rx: None,
+            last_walkthrough: None,
+            commit_rx: None,
+            pr_description_rx: None,
+            grounded_mode: false,
+            grounded_rx: None,
+            last_sources: Vec::new(),
+            reference_checker: None,
+            reference_checker_rx: None,
+            code_lint_rx: None,
+            last_response: String::new(),
+            copied_block: None,
+            run_rx: None,
+            focused_message: None,
+            editing_prompt: false,
+            last_message_date: None,
+            config_watcher: ConfigWatcher::start(Path::new(&Config::get_config_path()))
+                .map_err(|e| logger::warn(&format!("Config hot-reload disabled: {}", e)))
+                .ok(),
+            spinner,
+            prompt_history: Storage::with(|s| s.history(PROMPT_HISTORY_LIMIT)).unwrap_or_default(),
+            history_index: None,
+            history_draft: String::new(),
+            error_fix: None,
+            loop_paused: false,
+            read_only_offer: None,
+            voice: None,
+            voice_rx: None,
+            speech: None,
+            speech_rx: None,
+            queued_prompts: VecDeque::new(),
+            last_activity: Instant::now(),
+            brief_rx: None,
+            brief_covers: 0,
+            screen_text: ScreenText::new(),
+            selection: None,
+            tool_panel: ToolPanel::new(),
+            show_tool_panel: false,
+            touched_files: HashMap::new(),
+            search: None,
+            completion_selection: None,
+            completion_hidden_for: None,
+            notifier,
+            run_started: Instant::now(),
+            tools_started: None,
+            run_error: None,
+            attention: Attention::new(),
         }
     }
 
     fn add_user_message(&mut self, message: &str) {
+        self.add_day_separator();
         let clean = clean_text(message);
+        let mut spans = vec![
+            HistorySpan::new(format!("{} You: ", Icon::Prompt)).fg(Color::Cyan).bold(),
+            HistorySpan::new(clean),
+        ];
+        if let Some(time) = self.timestamp_span() {
+            spans.push(time);
+        }
+        self.push_history(HistoryKind::User, HistoryLine::new(spans));
+        self.last_ai_message = None;
+    }
+
+    /// Separate messages on a new day with a `──── Today ────` line
+    fn add_day_separator(&mut self) {
+        let now = Local::now();
+        let today = now.date_naive();
+        if self.last_message_date == Some(today) {
+            return;
+        }
+        self.last_message_date = Some(today);
         self.push_history(
-            HistoryKind::User,
+            HistoryKind::System,
             HistoryLine::new(vec![
-                HistorySpan::new("▶ You: ").fg(Color::Cyan).bold(),
-                HistorySpan::new(clean),
+                HistorySpan::new(format!(
+                    "──── {} · {} ────",
+                    day_label(today, today),
+                    now.format("%A, %B %-d")
+                ))
+                .fg(Color::DarkGrey)
+                .dim(),
             ]),
         );
-        self.last_ai_message = None;
+    }
+
+    /// Dim clock time appended to messages when timestamps are enabled
+    fn timestamp_span(&self) -> Option<HistorySpan> {
+        self.app.config.get_show_timestamps_enabled().then(|| {
+            HistorySpan::new(format!("  {}", clock_time(Local::now())))
+                .fg(Color::DarkGrey)
+                .dim()
+        })
     }
 
     fn add_ai_message(&mut self, message: &str) {
@@ -140,10 +411,14 @@ impl AppState {
         let mut lines = text.lines();
 
         if let Some(first) = lines.next() {
-            self.push_history(
-                HistoryKind::Ai,
-                HistoryLine::new(vec![HistorySpan::new(first.to_string())]),
-            );
+            let mut spans = vec![HistorySpan::new(first.to_string())];
+            // Time the start of each response segment
+            if self.last_history_kind != Some(HistoryKind::Ai)
+                && let Some(time) = self.timestamp_span()
+            {
+                spans.push(time);
+            }
+            self.push_history(HistoryKind::Ai, HistoryLine::new(spans));
         }
 
         for line in lines {
@@ -159,19 +434,247 @@ impl AppState {
         self.last_ai_message = Some(message);
     }
 
+    /// Add a streamed AI line, flagging references that don't exist in the workspace
+    ///
+    /// The line is post-processed first and may be dropped (a watermark).
+    /// It then goes through the markdown renderer, which holds table rows
+    /// back until the table ends. Unverified references get an inline `⚠`
+    /// marker and a warning line below.
+    fn add_checked_ai_message(&mut self, message: &str) {
+        let Some(message) = self.postprocessor.process_line(message) else {
+            return;
+        };
+        let message = message.as_str();
+        let unverified = match &self.reference_checker {
+            Some(checker) if self.app.config.get_verify_references_enabled() => {
+                checker.check(message)
+            }
+            _ => Vec::new(),
+        };
+        let message = if unverified.is_empty() {
+            message.to_string()
+        } else {
+            annotate(message, &unverified)
+        };
+        let lines = self.markdown.push(&format!("{}\n", message));
+        self.push_ai_lines(lines);
+
+        for reference in unverified {
+            self.push_history(
+                HistoryKind::Ai,
+                HistoryLine::new(vec![
+                    HistorySpan::new("      ⚠ ").fg(Color::Yellow).bold(),
+                    HistorySpan::new(reference.text.clone()).fg(Color::Yellow),
+                    HistorySpan::new(format!(" — {}", reference.problem()))
+                        .fg(Color::Yellow)
+                        .dim(),
+                ]),
+            );
+        }
+    }
+
+    /// Add lines rendered by the markdown stream, timing the first line of a
+    /// response segment
+    fn push_ai_lines(&mut self, lines: Vec<Line<'static>>) {
+        for line in lines {
+            let mut line = HistoryLine::from_line(&line);
+            if self.last_history_kind != Some(HistoryKind::Ai) {
+                if line.spans.iter().all(|span| span.text.trim().is_empty()) {
+                    continue;
+                }
+                if let Some(time) = self.timestamp_span() {
+                    line.spans.push(time);
+                }
+            }
+            self.push_history(HistoryKind::Ai, line);
+        }
+    }
+
+    /// Commit the rest of the streamed text, including an unfinished table
+    fn flush_stream(&mut self) {
+        for line in self.stream_collector.finalize() {
+            self.add_checked_ai_message(&line);
+        }
+        let lines = self.markdown.finalize();
+        self.push_ai_lines(lines);
+    }
+
+    /// Copy `text`, reporting the result as `<what> copied to clipboard`
+    fn copy_text(&mut self, text: &str, what: &str) {
+        match copy_to_clipboard(text) {
+            Ok(()) => self.add_system_message(&format!("✓ {} copied to clipboard", what)),
+            Err(e) => self.add_error_message(&format!("Failed to copy: {}", e)),
+        }
+    }
+
+    /// Copy code block `index` of the last response
+    fn copy_code_block(&mut self, index: usize) {
+        let blocks = extract_code_blocks(&self.last_response);
+        let Some(block) = blocks.get(index) else {
+            self.add_error_message("The last response has no code blocks");
+            return;
+        };
+        let language = if block.language.is_empty() {
+            "text"
+        } else {
+            &block.language
+        };
+        let what = format!(
+            "Code block {}/{} ({}, {} lines)",
+            index + 1,
+            blocks.len(),
+            language,
+            block.code.lines().count()
+        );
+        let code = block.code.clone();
+        self.copy_text(&code, &what);
+        self.copied_block = Some(index);
+    }
+
+    /// Copy the code block after the one copied last, wrapping around
+    fn copy_next_code_block(&mut self) {
+        let count = extract_code_blocks(&self.last_response).len();
+        let next = match self.copied_block {
+            Some(index) if count > 0 => (index + 1) % count,
+            _ => 0,
+        };
+        self.copy_code_block(next);
+    }
+
+    /// Add warnings for response code blocks that failed their syntax checks
+    fn add_code_lint_warnings(&mut self, lints: Vec<BlockLint>) {
+        for lint in lints {
+            self.push_history(
+                HistoryKind::Ai,
+                HistoryLine::new(vec![
+                    HistorySpan::new("      ⚠ ").fg(Color::Yellow).bold(),
+                    HistorySpan::new(lint.summary()).fg(Color::Yellow),
+                ]),
+            );
+            for issue in &lint.issues {
+                self.push_history(
+                    HistoryKind::Ai,
+                    HistoryLine::new(vec![
+                        HistorySpan::new("        "),
+                        HistorySpan::new(issue.describe()).fg(Color::Yellow).dim(),
+                    ]),
+                );
+            }
+        }
+    }
+
     fn add_tool_message(&mut self, name: &str, args: &str) {
         let clean_args = clean_text(args);
         self.push_history(
             HistoryKind::Tool,
             HistoryLine::new(vec![
-                HistorySpan::new("🔧 Tool: ").fg(Color::Magenta).bold(),
+                HistorySpan::new(format!("{} Tool: ", Icon::for_tool(name)))
+                    .fg(Color::Magenta)
+                    .bold(),
                 HistorySpan::new(name).bold(),
                 HistorySpan::new(format!(" {}", clean_args)).dim(),
             ]),
         );
+        if name == "execute_bash" && self.app.config.get_explain_commands_enabled() {
+            self.add_command_explanation(args);
+        }
+    }
+
+    /// Say in plain English what a shell command from the model does
+    fn add_command_explanation(&mut self, args: &str) {
+        let Some(command) = serde_json::from_str::<serde_json::Value>(args)
+            .ok()
+            .and_then(|args| args["command"].as_str().map(str::to_string))
+        else {
+            return;
+        };
+        let explanation = explain_command(&command);
+        if !explanation.steps.is_empty() {
+            self.push_history(
+                HistoryKind::Tool,
+                HistoryLine::new(vec![
+                    HistorySpan::new("   ↳ ").dim(),
+                    HistorySpan::new(explanation.summary()),
+                ]),
+            );
+        }
+        for warning in explanation.warnings {
+            self.push_history(
+                HistoryKind::Tool,
+                HistoryLine::new(vec![
+                    HistorySpan::new(format!("   {} ", Icon::Warning)).fg(Color::Yellow).bold(),
+                    HistorySpan::new(warning).fg(Color::Yellow),
+                ]),
+            );
+        }
+    }
+
+    /// Show the tail of a finished command's stdout and stderr, then its
+    /// exit code and run time
+    fn add_command_report(&mut self, report: &BashResult) {
+        for (output, stderr) in [(&report.stdout, false), (&report.stderr, true)] {
+            let lines: Vec<&str> = output.trim_end().lines().collect();
+            let skipped = lines.len().saturating_sub(COMMAND_OUTPUT_LINES);
+            if skipped > 0 {
+                self.push_history(
+                    HistoryKind::Tool,
+                    HistoryLine::new(vec![
+                        HistorySpan::new(format!("  │ ... {} more lines", skipped)).dim(),
+                    ]),
+                );
+            }
+            for line in &lines[skipped..] {
+                let text = HistorySpan::new(clean_text(line));
+                self.push_history(
+                    HistoryKind::Tool,
+                    HistoryLine::new(vec![
+                        HistorySpan::new("  │ ").dim(),
+                        if stderr { text.fg(Color::Red) } else { text.dim() },
+                    ]),
+                );
+            }
+        }
+        let (mark, color) = if report.success {
+            (Icon::Success, Color::Green)
+        } else {
+            (Icon::Error, Color::Red)
+        };
+        let mut spans = vec![
+            HistorySpan::new(format!("  {} ", mark)).fg(color).bold(),
+            HistorySpan::new(report.status()).fg(color),
+        ];
+        if report.truncated() {
+            spans.push(HistorySpan::new(" (output truncated)").dim());
+        }
+        self.push_history(HistoryKind::Tool, HistoryLine::new(spans));
+    }
+
+    fn add_system_message(&mut self, message: &str) {
+        self.push_history(
+            HistoryKind::System,
+            HistoryLine::new(vec![
+                HistorySpan::new(format!("{} ", Icon::Info)).fg(Color::Blue).bold(),
+                HistorySpan::new(clean_text(message)),
+            ]),
+        );
+    }
+
+    fn add_error_message(&mut self, message: &str) {
+        self.push_history(
+            HistoryKind::System,
+            HistoryLine::new(vec![
+                HistorySpan::new(format!("{} ", Icon::Error)).fg(Color::Red).bold(),
+                HistorySpan::new(clean_text(message)).fg(Color::Red),
+            ]),
+        );
     }
 
-    fn push_history(&mut self, kind: HistoryKind, line: HistoryLine) {
+    fn push_history(&mut self, kind: HistoryKind, mut line: HistoryLine) {
+        if plain_mode() {
+            for span in &mut line.spans {
+                span.text = to_plain(&span.text);
+            }
+        }
         if let Some(last) = self.last_history_kind {
             if last != kind {
                 self.pending_history.push(HistoryLine::plain(""));
@@ -181,8 +684,380 @@ impl AppState {
         self.last_history_kind = Some(kind);
     }
 
+    /// Indices in `app.messages` of the user and AI messages, oldest first
+    fn focusable_messages(&self) -> Vec<usize> {
+        self.app
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| matches!(m.message_type, MessageType::User | MessageType::Arula))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Replace the input with the next older or newer prompt from history,
+    /// returning to what was typed after the newest
+    fn recall_prompt(&mut self, older: bool) {
+        let next = match (self.history_index, older) {
+            (None, true) if !self.prompt_history.is_empty() => Some(0),
+            (Some(i), true) => Some((i + 1).min(self.prompt_history.len() - 1)),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+            (None, _) => return,
+        };
+        if self.history_index.is_none() {
+            self.history_draft = self.input.clone();
+        }
+        self.input = match next {
+            Some(i) => self.prompt_history[i].clone(),
+            None => std::mem::take(&mut self.history_draft),
+        };
+        self.input_cursor = self.input.chars().count();
+        self.history_index = next;
+    }
+
+    /// Insert `text` at the cursor
+    fn insert_text(&mut self, text: &str) {
+        let byte_pos = self
+            .input
+            .char_indices()
+            .nth(self.input_cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.input.len());
+        self.input.insert_str(byte_pos, text);
+        self.input_cursor += text.chars().count();
+    }
+
+    /// Add a sent prompt to the history and the local database
+    fn remember_prompt(&mut self, prompt: &str) {
+        self.history_index = None;
+        self.history_draft.clear();
+        if prompt.trim().is_empty() || self.prompt_history.first().map(String::as_str) == Some(prompt) {
+            return;
+        }
+        self.prompt_history.insert(0, prompt.to_string());
+        self.prompt_history.truncate(PROMPT_HISTORY_LIMIT);
+        if let Err(e) = Storage::with(|s| s.add_history(prompt)) {
+            logger::warn(&format!("Couldn't save prompt history: {}", e));
+        }
+    }
+
+    /// Select the next older or newer message, starting from the latest
+    fn move_focus(&mut self, older: bool) {
+        let messages = self.focusable_messages();
+        let position = self
+            .focused_message
+            .and_then(|focused| messages.iter().position(|i| *i == focused));
+        let next = match position {
+            None => messages.len().checked_sub(1),
+            Some(p) if older => Some(p.saturating_sub(1)),
+            Some(p) => Some((p + 1).min(messages.len() - 1)),
+        };
+        self.focused_message = next.map(|p| messages[p]);
+    }
+
+    fn focused(&self) -> Option<&ChatMessage> {
+        self.app.messages.get(self.focused_message?)
+    }
+
+    /// The prompt to re-run for the focused message: the message itself, or
+    /// the user message an AI reply answered
+    fn focused_prompt(&self) -> Option<String> {
+        let index = self.focused_message?;
+        self.app
+            .messages
+            .get(..=index)?
+            .iter()
+            .rev()
+            .find(|m| m.message_type == MessageType::User)
+            .map(|m| m.content.clone())
+    }
+
+    /// The block still streaming in, e.g. a table re-flowed as rows arrive
+    fn stream_preview_lines(&self) -> Vec<Line<'static>> {
+        if !self.is_waiting {
+            return Vec::new();
+        }
+        let mut lines = self.markdown.pending();
+        let skip = lines.len().saturating_sub(STREAM_PREVIEW_LINES);
+        lines.drain(..skip);
+        lines
+    }
+
+    /// Prompts waiting for the current turn, shown above the input
+    fn queued_lines(&self) -> Vec<Line<'static>> {
+        let theme = TuiTheme::active();
+        let width = (self.screen_width as usize).saturating_sub(16).max(20);
+        self.queued_prompts
+            .iter()
+            .map(|prompt| {
+                let preview: String = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+                let preview = if preview.chars().count() > width {
+                    format!("{}…", preview.chars().take(width - 1).collect::<String>())
+                } else {
+                    preview
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{} queued ", Icon::Prompt),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::styled(
+                        preview,
+                        Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    /// Commands matching the `/name` being typed, best first; empty when
+    /// the palette is closed
+    fn palette(&self) -> Vec<PaletteEntry> {
+        if self.focused_message.is_some()
+            || self.completion_hidden_for.as_deref() == Some(self.input.as_str())
+        {
+            return Vec::new();
+        }
+        let Some(query) = typed_name(&self.input) else {
+            return Vec::new();
+        };
+        let mut entries = builtin_entries();
+        entries.extend(
+            self.app
+                .scripts
+                .commands()
+                .iter()
+                .map(|c| PaletteEntry::new(&format!("/{}", c.name), &c.description)),
+        );
+        entries.extend(self.app.plugins.plugins().filter_map(|p| {
+            let command = p.manifest.command.as_ref()?;
+            Some(PaletteEntry::new(
+                &format!("/{} [args]", command),
+                &p.manifest.description,
+            ))
+        }));
+        filter(entries, query)
+    }
+
+    /// Index of the selected palette entry or mentioned file
+    fn completion_selected(&self) -> usize {
+        match &self.completion_selection {
+            Some((input, index)) if *input == self.input => *index,
+            _ => 0,
+        }
+    }
+
+    /// Move the palette or file list selection, wrapping around
+    fn move_completion_selection(&mut self, up: bool, count: usize) {
+        let selected = self.completion_selected();
+        let selected = if up {
+            (selected + count - 1) % count
+        } else {
+            (selected + 1) % count
+        };
+        self.completion_selection = Some((self.input.clone(), selected));
+    }
+
+    fn handle_palette_key(&mut self, code: KeyCode) {
+        let entries = self.palette();
+        match code {
+            KeyCode::Up | KeyCode::Down => {
+                self.move_completion_selection(code == KeyCode::Up, entries.len())
+            }
+            KeyCode::Tab => {
+                if let Some(entry) = entries.get(self.completion_selected()) {
+                    self.complete_palette(entry);
+                }
+            }
+            KeyCode::Esc => self.completion_hidden_for = Some(self.input.clone()),
+            _ => {}
+        }
+    }
+
+    /// Put the selected command in the input, ready for its arguments
+    fn complete_palette(&mut self, entry: &PaletteEntry) {
+        self.input = format!("/{} ", entry.name);
+        self.input_cursor = self.input.chars().count();
+    }
+
+    /// The command palette, shown above the input
+    fn palette_lines(&self) -> Vec<Line<'static>> {
+        let entries = self.palette();
+        if entries.is_empty() {
+            return Vec::new();
+        }
+        let theme = TuiTheme::active();
+        let selected = self.completion_selected().min(entries.len() - 1);
+        let first = first_visible(selected, entries.len());
+        let width = entries
+            .iter()
+            .map(|e| e.usage.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(32);
+        entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(palette::MAX_VISIBLE)
+            .map(|(i, entry)| {
+                let (marker, style) = if i == selected {
+                    ("▸ ", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+                } else {
+                    ("  ", Style::default().fg(theme.text))
+                };
+                Line::from(vec![
+                    Span::styled(format!("{}{:<width$}  ", marker, entry.usage), style),
+                    Span::styled(
+                        entry.description.clone(),
+                        Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+                    ),
+                ])
+            })
+            .collect()
+    }
+
+    /// Project files matching the `@path` being typed, best first; empty
+    /// when no mention is being typed or the list was closed
+    fn mention_suggestions(&self) -> Vec<String> {
+        if self.focused_message.is_some()
+            || self.completion_hidden_for.as_deref() == Some(self.input.as_str())
+        {
+            return Vec::new();
+        }
+        let Some(mention) = mention_at(&self.input, self.input_cursor) else {
+            return Vec::new();
+        };
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        FileIndex::shared(&root).complete(&mention.query, MAX_MENTIONS)
+    }
+
+    fn handle_mention_key(&mut self, code: KeyCode) {
+        let files = self.mention_suggestions();
+        match code {
+            KeyCode::Up | KeyCode::Down => {
+                self.move_completion_selection(code == KeyCode::Up, files.len())
+            }
+            KeyCode::Tab => {
+                if let Some(path) = files.get(self.completion_selected()) {
+                    self.complete_mention(path);
+                }
+            }
+            KeyCode::Esc => self.completion_hidden_for = Some(self.input.clone()),
+            _ => {}
+        }
+    }
+
+    /// Replace the mention being typed with `@path`
+    fn complete_mention(&mut self, path: &str) {
+        if let Some(mention) = mention_at(&self.input, self.input_cursor) {
+            let (input, cursor) = complete_mention(&self.input, &mention, path);
+            self.input = input;
+            self.input_cursor = cursor;
+        }
+    }
+
+    /// Files offered for the `@` mention, shown above the input
+    fn mention_lines(&self) -> Vec<Line<'static>> {
+        let files = self.mention_suggestions();
+        if files.is_empty() {
+            return Vec::new();
+        }
+        let theme = TuiTheme::active();
+        let selected = self.completion_selected().min(files.len() - 1);
+        let first = first_visible(selected, files.len());
+        files
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(palette::MAX_VISIBLE)
+            .map(|(i, path)| {
+                let (marker, style) = if i == selected {
+                    ("▸ @", Style::default().fg(theme.accent).add_modifier(Modifier::BOLD))
+                } else {
+                    ("  @", Style::default().fg(theme.text))
+                };
+                Line::from(Span::styled(format!("{}{}", marker, path), style))
+            })
+            .collect()
+    }
+
+    /// Preview of the focused message, shown above the input
+    fn focus_lines(&self) -> Vec<Line<'static>> {
+        let Some(message) = self.focused() else {
+            return Vec::new();
+        };
+        let theme = TuiTheme::active();
+        let border = Style::default().fg(theme.border);
+        let (label, color) = match message.message_type {
+            MessageType::User => ("You", theme.accent),
+            _ => ("ARULA", theme.info),
+        };
+        let messages = self.focusable_messages();
+        let position = messages
+            .iter()
+            .position(|i| Some(*i) == self.focused_message)
+            .map_or(0, |p| p + 1);
+
+        let mut lines = vec![Line::from(vec![
+            Span::styled("┌", border),
+            Span::styled(
+                format!(" Message {}/{} · {} ", position, messages.len(), label),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("┐ ", border),
+            Span::styled(
+                format!(
+                    "{} · {}",
+                    clock_time(message.timestamp),
+                    relative_time(message.timestamp.with_timezone(&Utc))
+                ),
+                Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+            ),
+        ])];
+
+        let content = clean_text(&message.content);
+        let content_lines: Vec<&str> = content.trim().lines().collect();
+        let width = (self.screen_width as usize).saturating_sub(4);
+        // While searching, start the preview just above the first match
+        let search = self.search.as_ref().filter(|s| !s.query.is_empty());
+        let skip = search
+            .and_then(|search| {
+                let query = search.query.to_lowercase();
+                content_lines
+                    .iter()
+                    .position(|line| line.to_lowercase().contains(&query))
+            })
+            .map_or(0, |line| line.saturating_sub(1));
+        for line in content_lines.iter().skip(skip).take(FOCUS_PREVIEW_LINES) {
+            let line = Line::from(vec![
+                Span::styled("│ ", border),
+                Span::styled(
+                    line.chars().take(width).collect::<String>(),
+                    Style::default().fg(theme.text),
+                ),
+            ]);
+            lines.push(match search {
+                Some(search) => search.highlight(&line),
+                None => line,
+            });
+        }
+        let shown = skip + FOCUS_PREVIEW_LINES;
+        if content_lines.len() > shown {
+            lines.push(Line::from(vec![
+                Span::styled("│ ", border),
+                Span::styled(
+                    format!("… {} more lines", content_lines.len() - shown),
+                    Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+                ),
+            ]));
+        }
+        lines
+    }
+
     fn tick(&mut self) -> bool {
-        if self.last_tick.elapsed() >= Duration::from_millis(100) {
+        if self.last_tick.elapsed() >= Duration::from_millis(self.spinner.interval_ms.max(30)) {
             self.frame = self.frame.wrapping_add(1);
             self.last_tick = Instant::now();
             return true;
@@ -191,10 +1066,15 @@ impl AppState {
     }
 
     fn render_viewport(&self, f: &mut Frame) {
-        let area = f.area();
+        let mut area = f.area();
+        if self.tool_panel_visible() {
+            let (chat, panel) = tool_panel::split(area);
+            self.render_tool_panel(f, panel);
+            area = chat;
+        }
 
         // Always reserve space for input and info at the bottom
-        let input_height = 1;
+        let input_height = self.input_height();
         let info_height = 1;
 
         // Calculate available space for status (above input and info)
@@ -262,38 +1142,58 @@ impl AppState {
         f.render_widget(ratatui::widgets::Clear, area);
 
         // Create input text with styled prompt
+        let theme = TuiTheme::active();
         let prompt_color = if self.is_waiting {
-            RColor::Yellow
+            theme.warning
         } else {
-            RColor::Cyan
+            theme.accent
         };
 
-        let input_text = Line::from(vec![
-            Span::styled("▶ ", Style::default().fg(prompt_color).add_modifier(Modifier::BOLD)),
-            Span::styled(&self.input, Style::default().fg(RColor::White)),
-        ]);
+        // Scroll long prompts so the cursor's row stays visible
+        let border = if plain_mode() { 0 } else { 1 };
+        let visible_rows = area.height.saturating_sub(border).max(1) as usize;
+        let (cursor_row, cursor_col) = cursor_row_col(&self.input, self.input_cursor);
+        let first_row = (cursor_row + 1).saturating_sub(visible_rows);
+        let input_text: Vec<Line> = self
+            .input
+            .split('\n')
+            .enumerate()
+            .skip(first_row)
+            .take(visible_rows)
+            .map(|(row, text)| {
+                let prefix = if row == 0 {
+                    format!("{} ", Icon::Prompt)
+                } else {
+                    "  ".to_string()
+                };
+                Line::from(vec![
+                    Span::styled(prefix, Style::default().fg(prompt_color).add_modifier(Modifier::BOLD)),
+                    Span::styled(text.to_string(), Style::default().fg(theme.text)),
+                ])
+            })
+            .collect();
 
         let input = Paragraph::new(input_text)
-            .style(Style::default().fg(RColor::White).bg(RColor::Rgb(12, 12, 16)))
+            .style(Style::default().fg(theme.text).bg(theme.background))
             .block(
                 ratatui::widgets::Block::default()
-                    .borders(ratatui::widgets::Borders::TOP)
-                    .border_style(Style::default().fg(RColor::Rgb(80, 80, 80)))
+                    .borders(Self::borders(ratatui::widgets::Borders::TOP))
+                    .border_style(Style::default().fg(theme.border))
             );
 
         f.render_widget(input, area);
 
         // Calculate cursor X position with bounds checking
         let prompt_width = 2; // Width of "▶ "
-        let input_char_count = self.input.chars().take(self.input_cursor).count() as u16;
+        let input_char_count = cursor_col as u16;
 
         // Ensure cursor stays within the input area (minus border)
         let max_cursor_x = area.width.saturating_sub(1); // Leave 1 char for border
         let cursor_offset = input_char_count.min(max_cursor_x.saturating_sub(prompt_width));
         let cursor_x = area.x + prompt_width + cursor_offset;
 
-        // Cursor Y is at the input line (accounting for top border)
-        let cursor_y = area.y;
+        // Cursor Y is at the cursor's row (accounting for top border)
+        let cursor_y = area.y + border + (cursor_row - first_row) as u16;
 
         // Only set cursor if it's within bounds
         if cursor_x < area.x + area.width && cursor_y <= area.y + area.height {
@@ -301,20 +1201,86 @@ impl AppState {
         }
     }
 
+    /// `borders`, or none in plain mode
+    fn borders(borders: ratatui::widgets::Borders) -> ratatui::widgets::Borders {
+        if plain_mode() {
+            ratatui::widgets::Borders::NONE
+        } else {
+            borders
+        }
+    }
+
+    fn render_tool_panel(&self, f: &mut Frame, area: Rect) {
+        let theme = TuiTheme::active();
+        let block = ratatui::widgets::Block::default()
+            .borders(Self::borders(ratatui::widgets::Borders::LEFT))
+            .border_style(Style::default().fg(theme.border))
+            .title(Span::styled(" Tools ", Style::default().fg(theme.muted)));
+        let inner = block.inner(area);
+        let mut lines = self
+            .tool_panel
+            .lines(inner.height, self.spinner.frame(self.frame));
+        if plain_mode() {
+            lines = lines.into_iter().map(plain_line).collect();
+        }
+        f.render_widget(ratatui::widgets::Clear, area);
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+
     fn render_info(&self, f: &mut Frame, area: Rect) {
         // Add a subtle background to the info line
+        let theme = TuiTheme::active();
         let info = Paragraph::new(self.info_line())
-            .style(Style::default().bg(RColor::Rgb(15, 15, 20)).fg(RColor::Rgb(180, 180, 180)));
+            .style(Style::default().bg(theme.surface).fg(theme.muted));
         f.render_widget(info, area);
     }
 
     fn info_line(&self) -> Line<'static> {
-        let spinner = ["◐", "◓", "◑", "◒"][self.frame % 4];
+        let spinner = self.spinner.frame(self.frame);
+        let theme = TuiTheme::active();
+        let separator = Style::default().fg(theme.border);
+        let divider = if plain_mode() { "  |  " } else { "  │  " };
         let mut spans = Vec::new();
 
+        if let Some(search) = &self.search {
+            let cursor = if search.editing { "▏" } else { "" };
+            spans.push(Span::styled(
+                format!("/{}{}", search.query, cursor),
+                Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
+            ));
+            let counter = match search.counter() {
+                Some(counter) => format!("  {}", counter),
+                None if search.query.is_empty() => String::new(),
+                None => "  no matches".to_string(),
+            };
+            spans.push(Span::styled(counter, Style::default().fg(theme.muted)));
+            if search.editing {
+                spans.push(Span::styled(divider, separator));
+                for (key, action) in [("Enter", " done  "), ("Esc", " cancel")] {
+                    spans.push(Span::styled(
+                        key,
+                        Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::styled(
+                        action,
+                        Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+                    ));
+                }
+                return plain_line(Line::from(spans));
+            }
+            spans.push(Span::styled(divider, separator));
+        }
+
         if self.is_waiting {
+            // Background commands show their own progress label.
+            if let Some(status) = &self.background_status {
+                spans.push(Span::styled(
+                    format!("{spinner} "),
+                    Style::default().fg(RColor::Cyan).add_modifier(Modifier::BOLD),
+                ));
+                spans.push(Span::styled(status.clone(), Style::default().fg(RColor::Rgb(180, 220, 240))));
             // Active tools take priority so users see progress.
-            if let Some(tool) = self.active_tools.first() {
+            } else if let Some(tool) = self.active_tools.first() {
                 let name = TuiApp::display_tool_name(&tool.name);
                 let label = if self.active_tools.len() > 1 {
                     format!("{name} (+{})", self.active_tools.len() - 1)
@@ -325,7 +1291,10 @@ impl AppState {
                     format!("{spinner} "),
                     Style::default().fg(RColor::Yellow).add_modifier(Modifier::BOLD),
                 ));
-                spans.push(Span::styled("🔧 ", Style::default().fg(RColor::Yellow)));
+                spans.push(Span::styled(
+                    format!("{} ", Icon::for_tool(&tool.name)),
+                    Style::default().fg(RColor::Yellow),
+                ));
                 spans.push(Span::styled(label, Style::default().fg(RColor::Rgb(220, 220, 150))));
             } else if !self.thinking_content.is_empty() {
                 let preview = TuiApp::thinking_preview(&self.thinking_content, 32)
@@ -334,14 +1303,14 @@ impl AppState {
                     format!("{spinner} "),
                     Style::default().fg(RColor::Magenta).add_modifier(Modifier::BOLD),
                 ));
-                spans.push(Span::styled("💭 ", Style::default().fg(RColor::Magenta)));
+                spans.push(Span::styled(format!("{} ", Icon::Thinking), Style::default().fg(RColor::Magenta)));
                 spans.push(Span::styled(preview, Style::default().fg(RColor::Rgb(200, 180, 220)).add_modifier(Modifier::DIM)));
             } else if !self.current_response.is_empty() {
                 spans.push(Span::styled(
                     format!("{spinner} "),
                     Style::default().fg(RColor::Cyan).add_modifier(Modifier::BOLD),
                 ));
-                spans.push(Span::styled("✨ ", Style::default().fg(RColor::Cyan)));
+                spans.push(Span::styled(format!("{} ", Icon::Response), Style::default().fg(RColor::Cyan)));
                 spans.push(Span::styled("AI typing...", Style::default().fg(RColor::Rgb(180, 220, 240))));
                 let preview = self
                     .current_response
@@ -363,48 +1332,183 @@ impl AppState {
                     format!("{spinner} "),
                     Style::default().fg(RColor::Cyan).add_modifier(Modifier::BOLD),
                 ));
-                spans.push(Span::styled("⚡ Working", Style::default().fg(RColor::Cyan)));
+                spans.push(Span::styled(format!("{} Working", Icon::Working), Style::default().fg(RColor::Cyan)));
             }
+        } else if let Some(recording) = &self.voice {
+            let elapsed = recording.elapsed().as_secs();
+            spans.push(Span::styled(
+                format!("{} ", Icon::Microphone),
+                Style::default().fg(theme.danger).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                format!("Recording {}:{:02}", elapsed / 60, elapsed % 60),
+                Style::default().fg(theme.danger),
+            ));
+        } else if let Some((_, waited)) = self.attention.waiting() {
+            spans.push(Span::styled(
+                format!("{} ", Icon::Question),
+                Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                format!("Waiting for you · {}s", waited.as_secs()),
+                Style::default().fg(theme.warning),
+            ));
+        } else if self.app.is_read_only() {
+            spans.push(Span::styled(
+                format!("{} ", Icon::Lock),
+                Style::default().fg(theme.warning).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                "Read-only · not saved",
+                Style::default().fg(theme.warning),
+            ));
         } else {
             spans.push(Span::styled(
                 "● ",
-                Style::default().fg(RColor::Green).add_modifier(Modifier::BOLD),
+                Style::default().fg(theme.success).add_modifier(Modifier::BOLD),
             ));
-            spans.push(Span::styled("Ready", Style::default().fg(RColor::Rgb(150, 255, 150)).add_modifier(Modifier::DIM)));
+            spans.push(Span::styled("Ready", Style::default().fg(theme.success).add_modifier(Modifier::DIM)));
         }
 
         // Separator
         spans.push(Span::styled(
-            "  │  ",
-            Style::default().fg(RColor::Rgb(60, 60, 60)),
+            divider,
+            separator,
         ));
 
-        // Model badge with improved styling
-        let model = self.app.config.get_model();
-        spans.push(Span::styled(
-            model,
-            Style::default()
-                .fg(RColor::Rgb(100, 140, 180))
-                .add_modifier(Modifier::ITALIC)
-                .add_modifier(Modifier::DIM),
-        ));
+        if let Some(profile) = &self.app.config.active_profile {
+            spans.push(Span::styled(
+                format!("{} {}", Icon::Profile, profile),
+                Style::default().fg(RColor::Rgb(200, 170, 120)).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                divider,
+                separator,
+            ));
+        }
 
-        // Separator
-        spans.push(Span::styled(
-            "  │  ",
-            Style::default().fg(RColor::Rgb(60, 60, 60)),
-        ));
+        if self.editing_prompt {
+            spans.push(Span::styled(
+                format!("{} Editing last message", Icon::FileEdit),
+                Style::default().fg(RColor::Rgb(220, 190, 110)).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                divider,
+                separator,
+            ));
+        }
 
+        if self.grounded_mode {
+            spans.push(Span::styled(
+                format!("{} Grounded", Icon::Grounded),
+                Style::default().fg(RColor::Rgb(150, 200, 150)).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                divider,
+                separator,
+            ));
+        }
+
+        if !self.app.context_files.is_empty() {
+            spans.push(Span::styled(
+                format!("{} {} file(s) attached", Icon::FileRead, self.app.context_files.len()),
+                Style::default().fg(theme.info),
+            ));
+            spans.push(Span::styled(
+                divider,
+                separator,
+            ));
+        }
+
+        // Model badge with improved styling
+        let model = self.app.config.get_model();
+        spans.push(Span::styled(
+            model,
+            Style::default()
+                .fg(theme.info)
+                .add_modifier(Modifier::ITALIC)
+                .add_modifier(Modifier::DIM),
+        ));
+
+        // Separator
         spans.push(Span::styled(
-            "Shift+Tab",
-            Style::default().fg(RColor::Rgb(140, 140, 140)).add_modifier(Modifier::BOLD),
-        ));
-        spans.push(Span::styled(
-            " menu",
-            Style::default().fg(RColor::Rgb(100, 100, 100)).add_modifier(Modifier::DIM),
+            divider,
+            separator,
         ));
 
-        Line::from(spans)
+        let hints: &[(&str, &str)] = if self.focused_message.is_some() && self.search.is_some() {
+            &[
+                ("n/N", " older/newer  "),
+                ("/", " search  "),
+                ("y", " copy  "),
+                ("Esc", " done"),
+            ]
+        } else if self.focused_message.is_some() {
+            &[
+                ("j/k", " move  "),
+                (">", " quote  "),
+                ("y", " copy  "),
+                ("r", " re-run  "),
+                ("e", " edit last  "),
+                ("b", " branch  "),
+                ("Esc", " done"),
+            ]
+        } else if !self.palette().is_empty() {
+            &[
+                ("↑↓", " pick  "),
+                ("Tab", " complete  "),
+                ("Enter", " run  "),
+                ("Esc", " close"),
+            ]
+        } else if !self.mention_suggestions().is_empty() {
+            &[
+                ("↑↓", " pick  "),
+                ("Tab/Enter", " attach file  "),
+                ("Esc", " close"),
+            ]
+        } else if self.read_only_offer.is_some() && self.input.is_empty() {
+            &[("Enter", " open read-only  "), ("Esc", " cancel")]
+        } else if self.voice.is_some() && self.input.is_empty() {
+            &[("Enter", " stop and transcribe  "), ("Esc", " cancel")]
+        } else if self.speech.is_some() && self.input.is_empty() {
+            &[("Esc", " stop reading")]
+        } else if self.loop_paused && self.input.is_empty() && !self.is_waiting {
+            &[
+                ("Enter", " continue  "),
+                ("type", " to change course  "),
+                ("Esc", " stop"),
+            ]
+        } else if self.input.contains('\n') {
+            &[
+                ("Enter", " send  "),
+                ("Shift+Enter", " new line  "),
+                ("Ctrl+E", " editor"),
+            ]
+        } else {
+            &[("Shift+Tab", " menu")]
+        };
+        for (key, action) in hints {
+            spans.push(Span::styled(
+                *key,
+                Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::styled(
+                *action,
+                Style::default().fg(theme.muted).add_modifier(Modifier::DIM),
+            ));
+        }
+
+        plain_line(Line::from(spans))
+    }
+
+    /// Whether the tool activity panel is on and the screen has room for it
+    fn tool_panel_visible(&self) -> bool {
+        self.show_tool_panel && tool_panel::fits(self.screen_width)
+    }
+
+    /// Whether the running tool gets a status line; the panel shows it instead
+    fn tool_status_shown(&self) -> bool {
+        self.is_waiting && !self.active_tools.is_empty() && !self.tool_panel_visible()
     }
 
     fn status_height(&self) -> u16 {
@@ -419,22 +1523,40 @@ impl AppState {
                 height += 1;
             }
         }
-        if self.is_waiting && !self.active_tools.is_empty() {
+        if self.tool_status_shown() {
             height += 1;
         }
+        height += self.stream_preview_lines().len() as u16;
+        height += self.queued_prompts.len() as u16;
+        let focus_lines = (self.focus_lines().len()
+            + self.palette_lines().len()
+            + self.mention_lines().len()) as u16;
+        if focus_lines > 0 {
+            // Preview plus the status box's bottom border
+            height += focus_lines + 1;
+        }
 
         // Limit status height to prevent overflow
-        // We need at least 2 lines for input and info
-        let max_status_height = self.screen_height.saturating_sub(2);
+        // We need room for the input and info lines
+        let max_status_height = self.screen_height.saturating_sub(self.input_height() + 1);
         height.min(max_status_height)
     }
 
+    /// Rows taken by the input: its border and one per line, up to
+    /// `MAX_INPUT_ROWS`
+    fn input_height(&self) -> u16 {
+        let rows = self.input.split('\n').count().min(MAX_INPUT_ROWS) as u16;
+        let border = if plain_mode() { 0 } else { 1 };
+        rows + border
+    }
+
     fn status_lines(&self) -> Vec<Line<'_>> {
         let mut lines = Vec::new();
-        let border = Style::default().fg(RColor::Rgb(100, 100, 120));
+        let theme = TuiTheme::active();
+        let border = Style::default().fg(theme.border);
 
-        if self.is_waiting && !self.active_tools.is_empty() {
-            let spinner = ["◐", "◓", "◑", "◒"][self.frame % 4];
+        if self.tool_status_shown() {
+            let spinner = self.spinner.frame(self.frame);
             let first = &self.active_tools[0];
             let label = TuiApp::display_tool_name(&first.name);
             let active_count = self.active_tools.len();
@@ -449,13 +1571,13 @@ impl AppState {
             spans.push(Span::styled(
                 format!(" {spinner} Tool 1/{} ", active_count),
                 Style::default()
-                    .fg(RColor::Rgb(255, 220, 100))
+                    .fg(theme.warning)
                     .add_modifier(Modifier::BOLD),
             ));
             spans.push(Span::styled("┐ ", border));
             spans.push(Span::styled(
                 label.to_string(),
-                Style::default().fg(RColor::Rgb(255, 220, 100)),
+                Style::default().fg(theme.warning),
             ));
             spans.push(Span::raw("  "));
             if !args_preview.is_empty() {
@@ -473,7 +1595,7 @@ impl AppState {
         }
 
         if self.is_waiting && !self.thinking_content.is_empty() {
-            let spinner = ["◐", "◓", "◑", "◒"][self.frame % 4];
+            let spinner = self.spinner.frame(self.frame);
 
             if self.thinking_expanded {
                 // Expanded mode - show full content
@@ -531,6 +1653,15 @@ impl AppState {
             }
         }
 
+        lines.extend(self.stream_preview_lines());
+        lines.extend(self.queued_lines());
+        lines.extend(self.focus_lines());
+        lines.extend(self.palette_lines());
+        lines.extend(self.mention_lines());
+
+        if plain_mode() {
+            lines = lines.into_iter().map(plain_line).collect();
+        }
         lines
     }
 
@@ -544,7 +1675,7 @@ impl AppState {
         let status = Paragraph::new(lines)
             .block(
                 ratatui::widgets::Block::default()
-                    .borders(ratatui::widgets::Borders::BOTTOM)
+                    .borders(Self::borders(ratatui::widgets::Borders::BOTTOM))
                     .border_style(Style::default().fg(RColor::Rgb(100, 100, 120)))
             );
 
@@ -584,12 +1715,8 @@ impl StreamCollector {
         if let Some(idx) = self.buffer.rfind('\n') {
             let complete = self.buffer[..=idx].to_string();
             self.buffer = self.buffer[idx + 1..].to_string();
-            out.extend(
-                complete
-                    .split('\n')
-                    .filter(|s| !s.is_empty())
-                    .map(|s| s.to_string()),
-            );
+            // Blank lines are kept: they end tables and paragraphs
+            out.extend(complete[..idx].split('\n').map(|s| s.to_string()));
         }
         out
     }
@@ -612,6 +1739,53 @@ fn clean_text(s: &str) -> String {
     re.replace_all(&stripped, "").to_string()
 }
 
+/// `line` without emoji or box drawing
+fn plain_line<'a>(line: Line<'a>) -> Line<'a> {
+    let spans: Vec<Span<'a>> = line
+        .spans
+        .into_iter()
+        .map(|span| Span::styled(to_plain(&span.content), span.style))
+        .collect();
+    Line::from(spans).style(line.style)
+}
+
+/// The configured spinner, or an ASCII one in plain mode
+fn spinner_pack(config: &Config) -> SpinnerPack {
+    let appearance = config.get_appearance();
+    if plain_mode()
+        && let Some(pack) = appearance.find_spinner(PLAIN_SPINNER)
+    {
+        return pack;
+    }
+    appearance.spinner()
+}
+
+/// Number of user and assistant messages in the chat history
+fn conversation_len(messages: &[ChatMessage]) -> usize {
+    messages
+        .iter()
+        .filter(|m| matches!(m.message_type, MessageType::User | MessageType::Arula))
+        .count()
+}
+
+/// Quote a message for the input as a Markdown block quote
+fn quote_message(message: &str) -> String {
+    let mut quoted = clean_text(message)
+        .trim()
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    quoted.push_str("\n\n");
+    quoted
+}
+
 impl TuiApp {
     pub fn new(app: App) -> Result<Self> {
         enable_raw_mode()?;
@@ -629,6 +1803,8 @@ impl TuiApp {
         let viewport = Viewport::Inline(VIEWPORT_HEIGHT);
         let terminal = Terminal::with_options(backend, TerminalOptions { viewport })?;
         let viewport_height = VIEWPORT_HEIGHT;
+        set_mouse_capture(app.config.get_mouse_enabled())?;
+        set_input_modes(true)?;
 
         Ok(Self {
             terminal,
@@ -701,6 +1877,7 @@ impl TuiApp {
 
         // Flush any pending history BEFORE rebuilding to prevent scrollback loss
         if !self.state.pending_history.is_empty() {
+            self.record_history();
             // Force a terminal sync before rebuild
             let lines: Vec<_> = self
                 .state
@@ -736,7 +1913,12 @@ impl TuiApp {
             viewport: Viewport::Inline(viewport_height),
         };
         self.terminal = Terminal::with_options(backend, options)?;
+        let freed = self.viewport_height.saturating_sub(viewport_height);
         self.viewport_height = viewport_height;
+        if freed > 0 {
+            // Rows the viewport gave up are blank history rows now
+            self.state.screen_text.push_blank(freed, self.history_rows());
+        }
 
         // Restore cursor position (ensure it's within screen bounds)
         let cursor_y = cursor_y.min(screen_h - 1);
@@ -750,9 +1932,122 @@ impl TuiApp {
         Ok(())
     }
 
+    /// Rows above the viewport, where history is printed
+    fn history_rows(&self) -> u16 {
+        self.state
+            .screen_height
+            .saturating_sub(self.viewport_height)
+            .max(1)
+    }
+
+    /// Remember the pending history rows about to be printed above the viewport
+    fn record_history(&mut self) {
+        if self.state.screen_height <= self.viewport_height {
+            return;
+        }
+        let visible = self.history_rows();
+        let lines: Vec<_> = self.state.pending_history.iter().map(HistoryLine::to_line).collect();
+        let rows = wrap_history_lines(&lines, self.state.screen_width);
+        self.state.screen_text.push(rows, visible);
+    }
+
+    /// Wheel through the messages like Up/Down in focus mode, and drag over
+    /// the history to select text, copied when the button is released.
+    /// Returns whether the viewport needs a redraw.
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<bool> {
+        let top = self.history_rows();
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.state.move_focus(true),
+            MouseEventKind::ScrollDown => match self.state.focused_message {
+                None => return Ok(false),
+                // Past the newest message, back to the input
+                focused if focused == self.state.focusable_messages().last().copied() => {
+                    self.state.focused_message = None;
+                }
+                Some(_) => self.state.move_focus(false),
+            },
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.clear_selection()?;
+                if mouse.row < top {
+                    self.state.selection = Some(Selection::new(mouse.column, mouse.row));
+                }
+                return Ok(false);
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                let Some(before) = self.state.selection else {
+                    return Ok(false);
+                };
+                let mut selection = before;
+                selection.head = (mouse.column, mouse.row.min(top - 1));
+                self.state.selection = Some(selection);
+                let (old, new) = (before.rows(), selection.rows());
+                self.paint_selection(old.start.min(new.start)..old.end.max(new.end))?;
+                return Ok(false);
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some(selection) = self.state.selection else {
+                    return Ok(false);
+                };
+                let text = self.state.screen_text.text(&selection, top);
+                self.clear_selection()?;
+                if text.trim().is_empty() {
+                    return Ok(false);
+                }
+                self.state.copy_text(&text, "Selection");
+            }
+            _ => return Ok(false),
+        }
+        self.state.last_activity = Instant::now();
+        Ok(true)
+    }
+
+    /// Print the history rows on screen again, highlighting the matches of
+    /// the search, if any
+    fn paint_search(&mut self) -> Result<()> {
+        let top = self.history_rows();
+        let mut out = io::stdout();
+        for row in 0..top {
+            let Some(line) = self.state.screen_text.row(row, top) else {
+                continue;
+            };
+            let line = match &self.state.search {
+                Some(search) => search.highlight(line),
+                None => line.clone(),
+            };
+            repaint_history_row(&mut out, row, &line)?;
+        }
+        Ok(())
+    }
+
+    /// Print history rows `rows` again, highlighting the selection
+    fn paint_selection(&mut self, rows: std::ops::Range<u16>) -> Result<()> {
+        let top = self.history_rows();
+        let mut out = io::stdout();
+        for row in rows {
+            let Some(line) = self.state.screen_text.row(row, top) else {
+                continue;
+            };
+            let line = match (self.state.selection.and_then(|s| s.columns(row)), &self.state.search) {
+                (Some(columns), _) => highlight(line, columns),
+                (None, Some(search)) => search.highlight(line),
+                (None, None) => line.clone(),
+            };
+            repaint_history_row(&mut out, row, &line)?;
+        }
+        Ok(())
+    }
+
+    /// Drop the selection and its highlight
+    fn clear_selection(&mut self) -> Result<()> {
+        if let Some(selection) = self.state.selection.take() {
+            self.paint_selection(selection.rows())?;
+        }
+        Ok(())
+    }
+
     fn required_viewport_height(&self) -> u16 {
-        // Always reserve space for input + info at bottom (2 lines)
-        let bottom_reserved = 2;
+        // Always reserve space for input + info at bottom
+        let bottom_reserved = self.state.input_height() + 1;
 
         // Add status height, but ensure we don't exceed screen
         let status_height = self.state.status_height();
@@ -762,8 +2057,13 @@ impl TuiApp {
         let max_status_height = screen_height.saturating_sub(bottom_reserved);
         let actual_status_height = status_height.min(max_status_height);
 
-        // Total height = status (if any) + bottom reserved
-        actual_status_height + bottom_reserved
+        // Total height = status (if any) + bottom reserved, or the panel's
+        let height = actual_status_height + bottom_reserved;
+        if self.state.tool_panel_visible() {
+            height.max(tool_panel::PANEL_HEIGHT.min(screen_height))
+        } else {
+            height
+        }
     }
 
     fn display_tool_name(name: &str) -> &str {
@@ -777,6 +2077,10 @@ impl TuiApp {
             "web_search" => "Web",
             "mcp_call" => "MCP",
             "visioneer" => "Vision",
+            "run_tests" => "Tests",
+            "git_commit" => "Commit",
+            "find_todos" => "TODOs",
+            "find_symbol" => "Symbols",
             _ => name,
         }
     }
@@ -873,6 +2177,11 @@ impl TuiApp {
     pub async fn run(&mut self) -> Result<()> {
         let mut needs_redraw = true;
 
+        self.state.app.hooks.fire(
+            HookEvent::SessionStart,
+            serde_json::json!({ "frontend": "cli" }),
+        );
+
         // Generate conversation starters on startup (if conversation is empty)
         if self.state.app.messages.is_empty() && self.state.conversation_starters.is_empty() {
             self.generate_conversation_starters();
@@ -881,6 +2190,13 @@ impl TuiApp {
         loop {
             let mut redraw = needs_redraw;
 
+            // SIGTERM or SIGHUP: save and leave; dropping the app restores
+            // the terminal
+            if shutdown::requested() {
+                self.state.app.shutdown();
+                return Ok(());
+            }
+
             // Update screen size
             if let Ok((w, h)) = terminal::size() {
                 // If the terminal is momentarily zero-sized during rotation, skip work this frame.
@@ -898,6 +2214,7 @@ impl TuiApp {
             if !self.state.pending_history.is_empty() {
                 // Ensure the scrollback insertion uses the latest terminal size
                 self.terminal.autoresize()?;
+                self.record_history();
 
                 let lines: Vec<_> = self
                     .state
@@ -970,10 +2287,40 @@ impl TuiApp {
                         if key.kind != KeyEventKind::Press {
                             continue;
                         }
+                        self.state.last_activity = Instant::now();
+                        self.state.attention.acknowledge();
                         match key.code {
                             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.state.app.shutdown();
                                 return Ok(());
                             }
+                            code if self.state.search.as_ref().is_some_and(|s| s.editing) => {
+                                self.handle_search_key(code)?;
+                                redraw = true;
+                            }
+                            code if self.state.focused_message.is_some() => {
+                                self.handle_focus_key(code).await?;
+                                redraw = true;
+                            }
+                            // Command palette: pick a command while typing `/name`
+                            code @ (KeyCode::Up | KeyCode::Down | KeyCode::Tab | KeyCode::Esc)
+                                if !self.state.palette().is_empty() =>
+                            {
+                                self.state.handle_palette_key(code);
+                                redraw = true;
+                            }
+                            // `@path`: pick a file to attach
+                            code @ (KeyCode::Up | KeyCode::Down | KeyCode::Tab | KeyCode::Esc)
+                                if !self.state.mention_suggestions().is_empty() =>
+                            {
+                                self.state.handle_mention_key(code);
+                                redraw = true;
+                            }
+                            // Ctrl+F: search the history
+                            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.start_search()?;
+                                redraw = true;
+                            }
                             // Ctrl+1/2/3: Send conversation starter messages
                             KeyCode::Char('1') | KeyCode::Char('2') | KeyCode::Char('3') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                                 if !self.state.conversation_starters.is_empty() {
@@ -992,12 +2339,105 @@ impl TuiApp {
                                     }
                                 }
                             }
+                            // Ctrl+Y: copy the next code block of the last response
+                            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.state.copy_next_code_block();
+                                redraw = true;
+                            }
+                            // Shift+Enter, Alt+Enter or Ctrl+J: new line
+                            KeyCode::Enter if key.modifiers.intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) => {
+                                self.state.insert_text("\n");
+                                redraw = true;
+                            }
+                            KeyCode::Char('j') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.state.insert_text("\n");
+                                redraw = true;
+                            }
+                            // Ctrl+E: edit the prompt in $EDITOR
+                            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.edit_input_externally()?;
+                                redraw = true;
+                            }
                             KeyCode::Enter => {
-                                if !self.state.input.is_empty() && !self.state.is_waiting {
+                                // Run the palette's pick rather than a partly typed name
+                                let mut completed = false;
+                                // Complete a file mention rather than sending it half typed
+                                let files = self.state.mention_suggestions();
+                                if let Some(path) = files.get(self.state.completion_selected())
+                                    && mention_at(&self.state.input, self.state.input_cursor)
+                                        .is_some_and(|m| m.query != *path)
+                                {
+                                    self.state.complete_mention(path);
+                                    completed = true;
+                                }
+                                let palette = self.state.palette();
+                                if let Some(entry) = palette.get(self.state.completion_selected())
+                                    && typed_name(&self.state.input) != Some(entry.name.as_str())
+                                {
+                                    if entry.needs_args() {
+                                        self.state.complete_palette(entry);
+                                        completed = true;
+                                    } else {
+                                        self.state.input = format!("/{}", entry.name);
+                                        self.state.input_cursor = self.state.input.chars().count();
+                                    }
+                                }
+                                if completed {
+                                    redraw = true;
+                                } else if self.state.input.is_empty()
+                                    && let Some(id) = self.state.read_only_offer.take()
+                                {
+                                    self.load_conversation(&id, true)?;
+                                    self.terminal.clear()?;
+                                    redraw = true;
+                                } else if self.state.input.is_empty() && self.state.voice.is_some() {
+                                    self.stop_voice();
+                                    redraw = true;
+                                } else if self.state.input.is_empty()
+                                    && self.state.loop_paused
+                                    && !self.state.is_waiting
+                                {
+                                    // Resume the run the watchdog paused
+                                    self.state.input = LOOP_CONTINUE_PROMPT.to_string();
+                                    self.submit_message().await?;
+                                    redraw = true;
+                                } else if self.state.input.is_empty() {
+                                    // Nothing to send
+                                } else if self.state.is_waiting
+                                    && let Some(SlashCommand::Steer(message)) =
+                                        parse_slash_command(&self.state.input)
+                                {
+                                    self.state.input.clear();
+                                    self.state.input_cursor = 0;
+                                    self.steer(&message);
+                                    redraw = true;
+                                } else if self.state.is_waiting {
+                                    // Sent once the current turn, tool calls included, ends
+                                    let prompt = std::mem::take(&mut self.state.input);
+                                    self.state.input_cursor = 0;
+                                    self.state.queued_prompts.push_back(prompt);
+                                    redraw = true;
+                                } else {
                                     self.submit_message().await?;
                                     redraw = true;
                                 }
                             }
+                            KeyCode::Tab if self.state.input.is_empty() && !self.state.is_waiting => {
+                                if let Some(fix) = self.state.error_fix.take() {
+                                    self.apply_error_fix(fix).await?;
+                                    redraw = true;
+                                }
+                            }
+                            // Ctrl+O: browse the project and attach files to the next message
+                            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.browse_files()?;
+                                redraw = true;
+                            }
+                            // Ctrl+T: show or hide the tool activity panel
+                            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                self.toggle_tool_panel();
+                                redraw = true;
+                            }
                             KeyCode::Char('t') => {
                                 // Toggle thinking bubble expansion
                                 if !self.state.thinking_content.is_empty() {
@@ -1006,16 +2446,7 @@ impl TuiApp {
                                 }
                             }
                             KeyCode::Char(c) => {
-                                // Insert at byte position corresponding to char position
-                                let byte_pos = self
-                                    .state
-                                    .input
-                                    .char_indices()
-                                    .nth(self.state.input_cursor)
-                                    .map(|(i, _)| i)
-                                    .unwrap_or(self.state.input.len());
-                                self.state.input.insert(byte_pos, c);
-                                self.state.input_cursor += 1;
+                                self.state.insert_text(c.encode_utf8(&mut [0; 4]));
                                 redraw = true;
                             }
                             KeyCode::Backspace => {
@@ -1045,6 +2476,20 @@ impl TuiApp {
                                 self.state.input_cursor = self.state.input_cursor.saturating_sub(1);
                                 redraw = true;
                             }
+                            KeyCode::Up | KeyCode::Down => {
+                                // Move between the rows of a multi-line prompt first
+                                let up = key.code == KeyCode::Up;
+                                match move_vertically(&self.state.input, self.state.input_cursor, up) {
+                                    Some(cursor) => self.state.input_cursor = cursor,
+                                    None => self.state.recall_prompt(up),
+                                }
+                                redraw = true;
+                            }
+                            KeyCode::Home | KeyCode::End => {
+                                let (start, end) = row_bounds(&self.state.input, self.state.input_cursor);
+                                self.state.input_cursor = if key.code == KeyCode::Home { start } else { end };
+                                redraw = true;
+                            }
                             KeyCode::Right => {
                                 let char_count = self.state.input.chars().count();
                                 if self.state.input_cursor < char_count {
@@ -1057,6 +2502,27 @@ impl TuiApp {
                                     self.state.input.clear();
                                     self.state.input_cursor = 0;
                                     redraw = true;
+                                } else if self.state.read_only_offer.take().is_some() {
+                                    redraw = true;
+                                } else if let Some(recording) = self.state.voice.take() {
+                                    recording.cancel();
+                                    self.state.add_system_message("Recording cancelled");
+                                    redraw = true;
+                                } else if let Some(playback) = self.state.speech.take() {
+                                    playback.stop();
+                                    redraw = true;
+                                } else if self.state.loop_paused && !self.state.is_waiting {
+                                    self.state.loop_paused = false;
+                                    self.state.attention.clear();
+                                    self.state.add_system_message("Stopped the paused run");
+                                    redraw = true;
+                                } else if self.state.queued_prompts.pop_back().is_some() {
+                                    // Drop the most recently queued prompt
+                                    redraw = true;
+                                } else if !self.state.is_waiting {
+                                    // Enter focus mode on the latest message
+                                    self.state.move_focus(true);
+                                    redraw = true;
                                 }
                             }
                             KeyCode::BackTab => {
@@ -1070,6 +2536,21 @@ impl TuiApp {
                             _ => {}
                         }
                     }
+                    Event::Mouse(mouse) => redraw |= self.handle_mouse(mouse)?,
+                    Event::FocusGained => self.state.notifier.set_focused(true),
+                    Event::FocusLost => self.state.notifier.set_focused(false),
+                    Event::Paste(text) => {
+                        self.state.last_activity = Instant::now();
+                        if self.state.search.as_ref().is_some_and(|s| s.editing) {
+                            // Only the first line makes sense as a query
+                            for c in text.lines().next().unwrap_or("").chars() {
+                                self.handle_search_key(KeyCode::Char(c))?;
+                            }
+                        } else {
+                            self.state.insert_text(&prepare_paste(&text));
+                        }
+                        redraw = true;
+                    }
                     Event::Resize(w, h) => {
                         // Ignore transient zero-size events that happen during orientation changes.
                         if w == 0 || h == 0 {
@@ -1077,9 +2558,13 @@ impl TuiApp {
                         }
                         self.state.screen_width = w;
                         self.state.screen_height = h;
+                        // The terminal reflows the history rows its own way
+                        self.state.screen_text.clear();
+                        self.state.selection = None;
 
                         // Flush pending history BEFORE resize to preserve scrollback
                         if !self.state.pending_history.is_empty() {
+                            self.record_history();
                             let lines: Vec<_> = self
                                 .state
                                 .pending_history
@@ -1122,9 +2607,99 @@ impl TuiApp {
                 }
             }
 
+            // Send the next queued prompt once the turn is over
+            if !self.state.is_waiting
+                && let Some(prompt) = self.state.queued_prompts.pop_front()
+            {
+                // Keep whatever is being typed in the input
+                let draft = std::mem::replace(&mut self.state.input, prompt);
+                let cursor = self.state.input_cursor;
+                self.submit_message().await?;
+                self.state.input = draft;
+                self.state.input_cursor = cursor;
+                redraw = true;
+            }
+
+            // Poll background walkthrough generation
+            if self.state.walkthrough_rx.is_some() && self.poll_walkthrough()? {
+                redraw = true;
+            }
+
+            // Poll background commit message drafting
+            if self.state.commit_rx.is_some() && self.poll_commit().await? {
+                redraw = true;
+            }
+
+            // Poll background PR description drafting
+            if self.state.pr_description_rx.is_some() && self.poll_pr_description()? {
+                redraw = true;
+            }
+
+            // Tick the recording clock, stopping at the length limit
+            if let Some(recording) = &mut self.state.voice {
+                if recording.is_finished() {
+                    self.stop_voice();
+                }
+                redraw = true;
+            }
+
+            // Put a finished transcription in the input
+            if self.state.voice_rx.is_some() && self.poll_voice() {
+                redraw = true;
+            }
+
+            // Play prepared speech, and forget it once it's said
+            if self.poll_speech() {
+                redraw = true;
+            }
+
+            // Pick up a rebuilt reference checker
+            if let Some(rx) = self.state.reference_checker_rx.as_mut()
+                && let Ok(checker) = rx.try_recv()
+            {
+                self.state.reference_checker = Some(Arc::new(checker));
+                self.state.reference_checker_rx = None;
+            }
+
+            // Show code block lint results for the last response
+            if let Some(rx) = self.state.code_lint_rx.as_mut()
+                && let Ok(lints) = rx.try_recv()
+            {
+                self.state.code_lint_rx = None;
+                if !lints.is_empty() {
+                    self.state.add_code_lint_warnings(lints);
+                    redraw = true;
+                }
+            }
+
+            // Stream output of a running code block
+            if self.state.run_rx.is_some() && self.poll_code_run() {
+                redraw = true;
+            }
+
+            // Poll background grounded answers
+            if self.state.grounded_rx.is_some() && self.poll_grounded_answer() {
+                redraw = true;
+            }
+
+            // Apply edits to config.json made while running
+            if let Some(reload) = self.state.config_watcher.as_ref().and_then(ConfigWatcher::try_recv) {
+                self.apply_config_reload(reload);
+                redraw = true;
+            }
+
+            // Bring the session brief up to date while idle
+            if self.state.is_waiting {
+                self.state.last_activity = Instant::now();
+            }
+            self.update_session_brief();
+
+            self.state.attention.tick();
+
             // Animate while waiting or when active tools/thinking are visible
             if self.state.tick()
                 && (self.state.is_waiting
+                    || self.state.attention.waiting().is_some()
                     || !self.state.active_tools.is_empty()
                     || !self.state.thinking_content.is_empty()
                     || !self.state.current_response.is_empty())
@@ -1141,13 +2716,144 @@ impl TuiApp {
         }
     }
 
+    /// Handle a key in message focus mode
+    async fn handle_focus_key(&mut self, code: KeyCode) -> Result<()> {
+        match code {
+            KeyCode::Char('k') | KeyCode::Up => self.state.move_focus(true),
+            KeyCode::Char('j') | KeyCode::Down => self.state.move_focus(false),
+            KeyCode::Char('>') | KeyCode::Char('q') => {
+                if let Some(message) = self.state.focused() {
+                    let quoted = quote_message(&message.content);
+                    self.state.input.insert_str(0, &quoted);
+                    self.state.input_cursor = self.state.input.chars().count();
+                }
+                self.state.focused_message = None;
+            }
+            KeyCode::Char('y') | KeyCode::Char('c') => {
+                if let Some(content) = self.state.focused().map(|m| m.content.clone()) {
+                    match copy_to_clipboard(&content) {
+                        Ok(()) => self.state.add_system_message("✓ Message copied to clipboard"),
+                        Err(e) => self
+                            .state
+                            .add_error_message(&format!("Failed to copy message: {}", e)),
+                    }
+                }
+            }
+            KeyCode::Char('r') if !self.state.is_waiting => {
+                if let Some(prompt) = self.state.focused_prompt() {
+                    self.state.focused_message = None;
+                    self.state.input = prompt;
+                    self.state.input_cursor = self.state.input.chars().count();
+                    self.submit_message().await?;
+                }
+            }
+            KeyCode::Char('e') if !self.state.is_waiting => {
+                self.state.focused_message = None;
+                self.start_editing_last_prompt();
+            }
+            KeyCode::Char('b') if !self.state.is_waiting => {
+                if let Some(index) = self.state.focused_message.take() {
+                    let dropped = self.state.app.messages.len().saturating_sub(index + 1);
+                    self.state.app.branch_conversation(index);
+                    self.state.add_system_message(&format!(
+                        "⑂ Branched the session here ({} later message{} dropped from context)",
+                        dropped,
+                        if dropped == 1 { "" } else { "s" }
+                    ));
+                }
+            }
+            KeyCode::Char('/') => self.start_search()?,
+            KeyCode::Char('n') if self.state.search.is_some() => self.step_search(true),
+            KeyCode::Char('N') if self.state.search.is_some() => self.step_search(false),
+            KeyCode::Esc => self.state.focused_message = None,
+            _ => {}
+        }
+        if self.state.focused_message.is_none() {
+            self.end_search()?;
+        }
+        Ok(())
+    }
+
+    /// Start typing a new search through the history
+    fn start_search(&mut self) -> Result<()> {
+        self.end_search()?;
+        self.state.search = Some(HistorySearch::new());
+        Ok(())
+    }
+
+    /// Handle a key while the search query is typed
+    fn handle_search_key(&mut self, code: KeyCode) -> Result<()> {
+        let Some(search) = self.state.search.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            KeyCode::Char(c) => search.query.push(c),
+            KeyCode::Backspace => {
+                search.query.pop();
+            }
+            KeyCode::Enter => {
+                search.editing = false;
+                if search.focused().is_none() {
+                    self.end_search()?;
+                }
+                return Ok(());
+            }
+            KeyCode::Esc => return self.end_search(),
+            _ => return Ok(()),
+        }
+        // Incremental: focus the newest match as the query changes
+        let candidates = self.state.focusable_messages();
+        let search = self.state.search.as_mut().expect("search is active");
+        if let Some(index) = search.update(&self.state.app.messages, &candidates) {
+            self.state.focused_message = Some(index);
+        }
+        self.paint_search()
+    }
+
+    /// Focus the next older or newer match
+    fn step_search(&mut self, older: bool) {
+        if let Some(index) = self.state.search.as_mut().and_then(|s| s.step(older)) {
+            self.state.focused_message = Some(index);
+        }
+    }
+
+    /// Drop the search and its highlight in the history
+    fn end_search(&mut self) -> Result<()> {
+        if self.state.search.take().is_some() {
+            self.paint_search()?;
+        }
+        Ok(())
+    }
+
     async fn submit_message(&mut self) -> Result<()> {
         let message = self.state.input.clone();
         self.state.input.clear();
         self.state.input_cursor = 0;
+        let editing = std::mem::take(&mut self.state.editing_prompt);
+        self.state.remember_prompt(&message);
 
         self.state.add_user_message(&message);
         self.state.last_ai_message = None;
+        self.state.error_fix = None;
+        self.state.loop_paused = false;
+        self.state.attention.clear();
+
+        if let Some(command) = parse_slash_command(&message) {
+            return self.handle_slash_command(command).await;
+        }
+
+        if editing && self.state.app.rewind_last_prompt().is_some() {
+            self.state.add_system_message(
+                "✎ Replaced your last message; the old reply was dropped from context",
+            );
+        }
+
+        if self.state.grounded_mode {
+            self.start_grounded_answer(&message);
+            return Ok(());
+        }
+
+        self.refresh_reference_checker();
 
         self.state.is_waiting = true;
         self.state.current_response.clear();
@@ -1155,36 +2861,1822 @@ impl TuiApp {
         self.state.active_tools.clear();
 
         self.state.app.send_to_ai(&message).await?;
+        // The first message creates the conversation the panel choice is saved with
+        self.remember_tool_panel();
         Ok(())
     }
 
-    fn poll_ai_response(&mut self) -> Result<bool> {
-        let mut changed = false;
-        while let Some(response) = self.state.app.check_ai_response_nonblocking() {
-            match response {
-                AiResponse::AgentStreamStart => {}
-                AiResponse::AgentStreamText(text) => {
-                    let clean = clean_text(&text);
-                    self.state.current_response.push_str(&clean);
-                    let completed = self.state.stream_collector.push(&clean);
-                    if !completed.is_empty() {
-                        let joined = completed.join("\n");
-                        self.state.add_ai_message(&joined);
-                    }
-                    changed = true;
+    async fn handle_slash_command(&mut self, command: SlashCommand) -> Result<()> {
+        match command {
+            SlashCommand::Help => {
+                self.state.add_system_message("Available commands:");
+                for (usage, description) in SLASH_COMMANDS {
+                    self.state.push_history(
+                        HistoryKind::System,
+                        HistoryLine::new(vec![
+                            HistorySpan::new(format!("  {:<32}", usage)).fg(Color::Cyan),
+                            HistorySpan::new(*description).dim(),
+                        ]),
+                    );
                 }
-                AiResponse::AgentThinkingStart => {
-                    self.state.thinking_content.clear();
-                    self.state.thinking_expanded = false;
-                    changed = true;
+                let script_commands = self.state.app.scripts.commands().to_vec();
+                for command in script_commands {
+                    self.state.push_history(
+                        HistoryKind::System,
+                        HistoryLine::new(vec![
+                            HistorySpan::new(format!("  {:<32}", format!("/{}", command.name)))
+                                .fg(Color::Cyan),
+                            HistorySpan::new(format!("{} ({})", command.description, command.script))
+                                .dim(),
+                        ]),
+                    );
                 }
-                AiResponse::AgentThinkingContent(content) => {
-                    self.state.thinking_content.push_str(&content);
-                    changed = true;
+                let plugin_commands: Vec<(String, String, String)> = self
+                    .state
+                    .app
+                    .plugins
+                    .plugins()
+                    .filter_map(|p| {
+                        let m = &p.manifest;
+                        Some((m.command.clone()?, m.description.clone(), m.name.clone()))
+                    })
+                    .collect();
+                for (command, description, plugin) in plugin_commands {
+                    self.state.push_history(
+                        HistoryKind::System,
+                        HistoryLine::new(vec![
+                            HistorySpan::new(format!("  {:<32}", format!("/{} [args]", command)))
+                                .fg(Color::Cyan),
+                            HistorySpan::new(format!("{} (plugin {})", description, plugin)).dim(),
+                        ]),
+                    );
                 }
-                AiResponse::AgentThinkingEnd => {
-                    // Reset expansion state when thinking ends
-                    self.state.thinking_expanded = false;
+            }
+            SlashCommand::Walkthrough(range) => self.start_walkthrough(&range)?,
+            SlashCommand::Commit(files) => self.start_commit(files),
+            SlashCommand::Architecture(format) => self.generate_architecture(&format).await,
+            SlashCommand::Grounded(arg) => self.set_grounded_mode(&arg),
+            SlashCommand::Source(number) => self.open_source(number)?,
+            SlashCommand::Run(number) => self.start_code_run(number),
+            SlashCommand::Profile(args) => self.run_profile_command(&args),
+            SlashCommand::Goto(time) => self.goto_message(&time),
+            SlashCommand::Timestamps(arg) => self.set_timestamps(&arg),
+            SlashCommand::Mouse(arg) => self.set_mouse(&arg),
+            SlashCommand::Spinners(args) => self.run_spinners_command(&args),
+            SlashCommand::Theme(name) => self.run_theme_command(&name),
+            SlashCommand::Set(args) => self.run_set_command(&args),
+            SlashCommand::Checkpoint(name) => self.create_checkpoint(&name),
+            SlashCommand::Branch(checkpoint) => self.branch_from_checkpoint(&checkpoint)?,
+            SlashCommand::Scripts(arg) => self.run_scripts_command(&arg),
+            SlashCommand::Plugins(arg) => self.run_plugins_command(&arg),
+            SlashCommand::EditLast(message) => self.edit_last_prompt(&message).await?,
+            SlashCommand::Regenerate(temperature) => self.regenerate(&temperature).await?,
+            SlashCommand::Copy(arg) => self.run_copy_command(&arg),
+            SlashCommand::Debug(arg) => self.run_debug_command(&arg),
+            SlashCommand::Criteria(arg) => self.run_criteria_command(&arg),
+            SlashCommand::Voice => self.toggle_voice(),
+            SlashCommand::Speak(arg) => self.run_speak_command(&arg),
+            SlashCommand::Logs(arg) => self.run_logs_command(&arg)?,
+            SlashCommand::Compact => self.compact_conversation().await,
+            SlashCommand::Steer(message) => self.steer(&message),
+            SlashCommand::Session => self.show_session_info(),
+            SlashCommand::Stats => self.show_stats()?,
+            SlashCommand::Present => self.present()?,
+            SlashCommand::Files => self.browse_files()?,
+            SlashCommand::Export(path) => self.export_conversation(&path),
+            SlashCommand::Unknown(name, args) => self.run_script_command(&name, &args),
+        }
+        Ok(())
+    }
+
+    async fn generate_architecture(&mut self, format: &str) {
+        let Some(format) = DiagramFormat::parse(format) else {
+            self.state
+                .add_error_message("Usage: /architecture [mermaid|dot]");
+            return;
+        };
+
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let result = tokio::task::spawn_blocking(move || {
+            let map = build_architecture_map(&dir)?;
+            let path = map.write_to(&dir, format)?;
+            Ok::<_, anyhow::Error>((map, path))
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r);
+
+        let (map, path) = match result {
+            Ok(generated) => generated,
+            Err(e) => {
+                self.state
+                    .add_error_message(&format!("Architecture map failed: {}", e));
+                return;
+            }
+        };
+
+        let packages: Vec<_> = map
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Package)
+            .collect();
+        self.state.add_system_message(&format!(
+            "🗺  Architecture of {}: {} package(s), {} module(s), {} dependencies",
+            map.project_name,
+            packages.len(),
+            map.nodes.len() - packages.len(),
+            map.edges.len()
+        ));
+        for package in packages {
+            let depends_on: Vec<&str> = map
+                .edges
+                .iter()
+                .filter(|e| e.from == package.id && map.node(&e.to).is_some_and(|n| n.kind == NodeKind::Package))
+                .map(|e| e.to.as_str())
+                .collect();
+            let mut spans = vec![
+                HistorySpan::new(format!("  {} ", package.label)).fg(Color::Cyan),
+                HistorySpan::new(format!("({} modules)", map.modules_of(&package.id).count())).dim(),
+            ];
+            if !depends_on.is_empty() {
+                spans.push(HistorySpan::new(format!(" → {}", depends_on.join(", "))).dim());
+            }
+            self.state
+                .push_history(HistoryKind::System, HistoryLine::new(spans));
+        }
+        self.state
+            .add_system_message(&format!("Written to {}", path.display()));
+    }
+
+    fn start_walkthrough(&mut self, range: &str) -> Result<()> {
+        if range.trim().is_empty() {
+            if let Some(walkthrough) = self.state.last_walkthrough.clone() {
+                WalkthroughView::new(&walkthrough).show()?;
+            } else {
+                self.state
+                    .add_error_message("Usage: /walkthrough <base>..<head> (e.g. /walkthrough main..HEAD)");
+            }
+            return Ok(());
+        }
+
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return Ok(());
+        }
+
+        let range = match parse_range(range) {
+            Ok(range) => range,
+            Err(e) => {
+                self.state.add_error_message(&e.to_string());
+                return Ok(());
+            }
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let task_range = range.clone();
+        tokio::spawn(async move {
+            generate_walkthrough(config, &dir, &task_range, tx).await;
+        });
+
+        self.state.walkthrough_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some(format!("🧭 Walkthrough {}: listing commits...", range));
+        Ok(())
+    }
+
+    fn poll_walkthrough(&mut self) -> Result<bool> {
+        let mut changed = false;
+        let mut finished: Option<Walkthrough> = None;
+
+        while let Some(rx) = self.state.walkthrough_rx.as_mut() {
+            let Ok(progress) = rx.try_recv() else {
+                break;
+            };
+            changed = true;
+            match progress {
+                WalkthroughProgress::Started { total, truncated } => {
+                    let mut message = format!("Summarizing {} commit(s)", total);
+                    if truncated {
+                        message.push_str(" (range truncated to the first commits)");
+                    }
+                    self.state.add_system_message(&message);
+                    self.state.background_status = Some(format!("🧭 Walkthrough 0/{}", total));
+                }
+                WalkthroughProgress::CommitSummarized { index, total, summary } => {
+                    self.state.push_history(
+                        HistoryKind::System,
+                        HistoryLine::new(vec![
+                            HistorySpan::new("  ✓ ").fg(Color::Green),
+                            HistorySpan::new(format!("{} ", summary.commit.short_hash)).fg(Color::Yellow),
+                            HistorySpan::new(summary.commit.subject.clone()).dim(),
+                        ]),
+                    );
+                    self.state.background_status =
+                        Some(format!("🧭 Walkthrough {}/{}", index + 1, total));
+                }
+                WalkthroughProgress::WritingNarrative => {
+                    self.state.background_status = Some("🧭 Walkthrough: writing narrative...".to_string());
+                }
+                WalkthroughProgress::Finished(walkthrough) => {
+                    finished = Some(walkthrough);
+                    self.finish_background_task();
+                }
+                WalkthroughProgress::Failed(error) => {
+                    self.state.add_error_message(&format!("Walkthrough failed: {}", error));
+                    self.finish_background_task();
+                }
+            }
+        }
+
+        if let Some(walkthrough) = finished {
+            self.state.push_history(
+                HistoryKind::Ai,
+                HistoryLine::new(vec![
+                    HistorySpan::new("🧭 Walkthrough of ").fg(Color::Cyan).bold(),
+                    HistorySpan::new(walkthrough.range.clone()).fg(Color::Cyan).bold(),
+                ]),
+            );
+            self.state.add_ai_message(&walkthrough.narrative);
+            self.state
+                .add_system_message("Type /walkthrough to reopen the commit-by-commit view.");
+            WalkthroughView::new(&walkthrough).show()?;
+            self.state.last_walkthrough = Some(walkthrough);
+        }
+
+        Ok(changed)
+    }
+
+    fn start_commit(&mut self, files: Vec<String>) {
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return;
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        tokio::spawn(async move {
+            let draft = prepare_commit(&config, &dir, &files)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(draft);
+        });
+
+        self.state.commit_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some("📝 Drafting commit message...".to_string());
+    }
+
+    async fn poll_commit(&mut self) -> Result<bool> {
+        let Some(rx) = self.state.commit_rx.as_mut() else {
+            return Ok(false);
+        };
+        let Ok(result) = rx.try_recv() else {
+            return Ok(false);
+        };
+        self.finish_background_task();
+
+        let draft = match result {
+            Ok(draft) => draft,
+            Err(error) => {
+                self.state.add_error_message(&format!("Commit failed: {}", error));
+                return Ok(true);
+            }
+        };
+
+        match CommitView::new(&draft).show()? {
+            CommitDecision::Commit(message) => {
+                let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+                match GitOps::new(dir.clone()).commit(&message).await {
+                    Ok(hash) => {
+                        let subject = message.lines().next().unwrap_or("").to_string();
+                        let record = CommitRecord {
+                            timestamp: Utc::now(),
+                            project: dir.display().to_string(),
+                            hash: hash.clone(),
+                            subject: subject.clone(),
+                        };
+                        if let Err(e) = Storage::with(|s| s.record_commit(&record)) {
+                            logger::warn(&format!("Couldn't record the commit: {}", e));
+                        }
+                        self.state.push_history(
+                            HistoryKind::System,
+                            HistoryLine::new(vec![
+                                HistorySpan::new("✓ Committed ").fg(Color::Green),
+                                HistorySpan::new(format!("{} ", hash)).fg(Color::Yellow),
+                                HistorySpan::new(subject),
+                                HistorySpan::new(format!(" ({} file(s))", draft.files.len())).dim(),
+                            ]),
+                        );
+                    }
+                    Err(e) => self.state.add_error_message(&format!("Commit failed: {}", e)),
+                }
+            }
+            CommitDecision::Regenerate => self.start_commit(draft.files),
+            CommitDecision::Cancel => {
+                self.state
+                    .add_system_message("Commit cancelled; changes remain staged.");
+            }
+        }
+        Ok(true)
+    }
+
+    fn start_pr_description(&mut self) {
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return;
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        tokio::spawn(async move {
+            let description = generate_pr_description(&config, &dir, None)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(description);
+        });
+
+        self.state.pr_description_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some("📝 Drafting PR description...".to_string());
+    }
+
+    fn poll_pr_description(&mut self) -> Result<bool> {
+        let Some(rx) = self.state.pr_description_rx.as_mut() else {
+            return Ok(false);
+        };
+        let Ok(result) = rx.try_recv() else {
+            return Ok(false);
+        };
+        self.finish_background_task();
+
+        let description = match result {
+            Ok(description) => description,
+            Err(error) => {
+                self.state
+                    .add_error_message(&format!("PR description failed: {}", error));
+                return Ok(true);
+            }
+        };
+
+        match PrDescriptionView::new(&description).show()? {
+            PrDescriptionAction::Copy => match copy_to_clipboard(&description.markdown) {
+                Ok(()) => self
+                    .state
+                    .add_system_message("✓ PR description copied to clipboard"),
+                Err(e) => self
+                    .state
+                    .add_error_message(&format!("Failed to copy PR description: {}", e)),
+            },
+            PrDescriptionAction::Save => {
+                let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+                match description.write_to(&dir) {
+                    Ok(path) => self
+                        .state
+                        .add_system_message(&format!("✓ PR description written to {}", path.display())),
+                    Err(e) => self
+                        .state
+                        .add_error_message(&format!("Failed to write PR description: {}", e)),
+                }
+            }
+            PrDescriptionAction::Regenerate => self.start_pr_description(),
+            PrDescriptionAction::Close => {}
+        }
+        Ok(true)
+    }
+
+    fn run_profile_command(&mut self, args: &str) {
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+            [] | ["list"] => {
+                let config = &self.state.app.config;
+                let names = config.get_profile_names();
+                if names.is_empty() {
+                    self.state.add_system_message(
+                        "No profiles configured. Add them under \"profiles\" in ~/.arula/config.json.",
+                    );
+                    return;
+                }
+                let active = config.active_profile.clone();
+                self.state.add_system_message("Profiles:");
+                for name in names {
+                    let is_active = active.as_deref() == Some(name.as_str());
+                    let mut span = HistorySpan::new(format!(
+                        "  {} {}",
+                        if is_active { "●" } else { "○" },
+                        name
+                    ));
+                    span = if is_active { span.fg(Color::Green) } else { span.dim() };
+                    self.state
+                        .push_history(HistoryKind::System, HistoryLine::new(vec![span]));
+                }
+            }
+            ["switch" | "use", name] => {
+                let profile = match *name {
+                    "default" | "none" | "off" => None,
+                    name => Some(name),
+                };
+                match self.state.app.switch_profile(profile) {
+                    Ok(()) => {
+                        let config = &self.state.app.config;
+                        let message = format!(
+                            "👤 Using {} ({} · {})",
+                            profile
+                                .map(|name| format!("profile '{}'", name))
+                                .unwrap_or_else(|| "the default configuration".to_string()),
+                            config.active_provider,
+                            config.get_model()
+                        );
+                        self.state.add_system_message(&message);
+                    }
+                    Err(e) => self.state.add_error_message(&e.to_string()),
+                }
+            }
+            _ => self
+                .state
+                .add_error_message("Usage: /profile [list|switch <name|default>]"),
+        }
+    }
+
+    /// Select the message closest to a time in focus mode
+    fn goto_message(&mut self, time: &str) {
+        let Some(target) = parse_time_target(time, Local::now()) else {
+            self.state.add_error_message(
+                "Usage: /goto <time> (e.g. 14:30, 2pm, yesterday 9:00, 2025-10-06, 2h ago)",
+            );
+            return;
+        };
+        let messages = self.state.focusable_messages();
+        let timestamps = messages
+            .iter()
+            .map(|i| self.state.app.messages[*i].timestamp.with_timezone(&Utc));
+        let Some(position) = closest_to(target.with_timezone(&Utc), timestamps) else {
+            self.state.add_system_message("No messages to go to yet");
+            return;
+        };
+
+        let index = messages[position];
+        let timestamp = self.state.app.messages[index].timestamp;
+        self.state.focused_message = Some(index);
+        self.state.add_system_message(&format!(
+            "⏱ Message {}/{} · {} {} ({}) — j/k to move, Esc to leave",
+            position + 1,
+            messages.len(),
+            day_label(timestamp.date_naive(), Local::now().date_naive()),
+            clock_time(timestamp),
+            relative_time(timestamp.with_timezone(&Utc))
+        ));
+    }
+
+    fn apply_config_reload(&mut self, reload: ConfigReload) {
+        let config = match reload {
+            ConfigReload::Loaded(config) => config,
+            ConfigReload::Rejected(reason) => {
+                self.state
+                    .add_error_message(&format!("config.json change not applied: {}", reason));
+                return;
+            }
+        };
+        let icons = self.state.app.config.icons;
+        match self.state.app.apply_reloaded_config(*config) {
+            Ok(changes) => {
+                if self.state.app.config.icons != icons && !plain_mode() {
+                    set_icon_set(self.state.app.config.get_icon_set());
+                }
+                self.state.spinner = spinner_pack(&self.state.app.config);
+                if let Err(e) = set_mouse_capture(self.state.app.config.get_mouse_enabled()) {
+                    logger::warn(&format!("Couldn't change mouse capture: {}", e));
+                }
+                set_code_theme(self.state.app.config.get_appearance().code_theme.as_deref());
+                self.state
+                    .notifier
+                    .set_settings(self.state.app.config.get_notifications());
+                if !plain_mode() {
+                    set_active_theme(self.state.app.config.get_theme());
+                }
+                if !changes.is_empty() {
+                    self.state
+                        .add_system_message(&format!("⟳ Config reloaded: {}", changes.join(", ")));
+                }
+            }
+            Err(e) => self
+                .state
+                .add_error_message(&format!("config.json change not applied: {}", e)),
+        }
+    }
+
+    /// Send a steering message to the running agent
+    fn steer(&mut self, message: &str) {
+        if message.trim().is_empty() {
+            self.state.add_error_message("Usage: /steer <message>");
+        } else if self.state.app.steer(message) {
+            self.state.push_history(
+                HistoryKind::User,
+                HistoryLine::new(vec![
+                    HistorySpan::new("↪ Steering: ").fg(Color::Cyan).bold(),
+                    HistorySpan::new(clean_text(message.trim())),
+                ]),
+            );
+        } else {
+            self.state
+                .add_error_message("Nothing is running to steer; send it as a message instead");
+        }
+    }
+
+    fn set_timestamps(&mut self, arg: &str) {
+        let enabled = match arg {
+            "" => !self.state.app.config.get_show_timestamps_enabled(),
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.state.add_error_message("Usage: /timestamps [on|off]");
+                return;
+            }
+        };
+        if let Err(e) = self.state.app.config.set_show_timestamps_enabled(enabled) {
+            self.state
+                .add_error_message(&format!("Failed to save setting: {}", e));
+            return;
+        }
+        self.state.add_system_message(if enabled {
+            "Message timestamps on"
+        } else {
+            "Message timestamps off"
+        });
+    }
+
+    fn set_mouse(&mut self, arg: &str) {
+        let enabled = match arg {
+            "" => !self.state.app.config.get_mouse_enabled(),
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.state.add_error_message("Usage: /mouse [on|off]");
+                return;
+            }
+        };
+        if let Err(e) = self.state.app.config.set_mouse_enabled(enabled) {
+            self.state
+                .add_error_message(&format!("Failed to save setting: {}", e));
+            return;
+        }
+        if let Err(e) = set_mouse_capture(enabled) {
+            self.state
+                .add_error_message(&format!("Failed to change mouse capture: {}", e));
+            return;
+        }
+        self.state.add_system_message(if enabled {
+            "Mouse on: the wheel moves through messages, dragging over the history copies it"
+        } else {
+            "Mouse off: the terminal handles selection and scrolling"
+        });
+    }
+
+    fn run_set_command(&mut self, args: &str) {
+        let args: Vec<&str> = args.split_whitespace().collect();
+        match args.as_slice() {
+            [] => {
+                let settings = self.state.app.config.get_generation_settings();
+                self.state.add_system_message(&format!(
+                    "Generation settings for {}: {}",
+                    self.state.app.config.active_provider,
+                    settings.summary()
+                ));
+            }
+            [name, value] => match self.state.app.config.set_generation_setting(name, value) {
+                Ok(()) => {
+                    if let Err(e) = self.state.app.initialize_agent_client() {
+                        self.state
+                            .add_error_message(&format!("Failed to apply setting: {}", e));
+                        return;
+                    }
+                    let settings = self.state.app.config.get_generation_settings();
+                    self.state
+                        .add_system_message(&format!("Generation settings: {}", settings.summary()));
+                }
+                Err(e) => self.state.add_error_message(&e.to_string()),
+            },
+            _ => self.state.add_error_message(
+                "Usage: /set <temperature|top_p|max_tokens> <value|default>",
+            ),
+        }
+    }
+
+    fn create_checkpoint(&mut self, name: &str) {
+        let name = (!name.is_empty()).then_some(name);
+        match self.state.app.create_checkpoint(name) {
+            Ok(name) => self.state.add_system_message(&format!(
+                "⚑ Checkpoint '{}' saved at message {}. Branch from it with /branch {}",
+                name,
+                self.state.app.messages.len(),
+                name
+            )),
+            Err(e) => self
+                .state
+                .add_error_message(&format!("Failed to create checkpoint: {}", e)),
+        }
+    }
+
+    /// `/branch <checkpoint>` branches; without a checkpoint it opens the tree picker
+    fn branch_from_checkpoint(&mut self, checkpoint: &str) -> Result<()> {
+        if self.state.is_waiting {
+            self.state
+                .add_error_message("Wait for the current response before branching");
+            return Ok(());
+        }
+
+        if checkpoint.is_empty() {
+            let result = BranchMenu::new().show(&self.state.app)?;
+            self.handle_menu_result(result)?;
+            self.terminal.clear()?;
+            return Ok(());
+        }
+
+        match self.state.app.branch_from_checkpoint(checkpoint) {
+            Ok(id) => {
+                self.handle_menu_result(MenuResult::LoadConversation(id))?;
+                self.terminal.clear()?;
+                self.state.add_system_message(&format!(
+                    "⑂ Branched from checkpoint '{}' into a new conversation",
+                    checkpoint
+                ));
+            }
+            Err(e) => self.state.add_error_message(&e.to_string()),
+        }
+        Ok(())
+    }
+
+    /// Put the last message in the input to edit; sending it replaces the
+    /// message and drops everything after it
+    fn start_editing_last_prompt(&mut self) {
+        let last_prompt = self
+            .state
+            .app
+            .messages
+            .iter()
+            .rev()
+            .find(|m| m.message_type == MessageType::User)
+            .map(|m| m.content.clone());
+        match last_prompt {
+            Some(prompt) => {
+                self.state.input = prompt;
+                self.state.input_cursor = self.state.input.chars().count();
+                self.state.editing_prompt = true;
+            }
+            None => self.state.add_error_message("No message to edit yet"),
+        }
+    }
+
+    /// `/edit-last <message>` replaces the last message and regenerates the
+    /// reply; without a message it opens the last one for editing
+    async fn edit_last_prompt(&mut self, message: &str) -> Result<()> {
+        if self.state.is_waiting {
+            self.state
+                .add_error_message("Wait for the current response before editing");
+            return Ok(());
+        }
+        if message.is_empty() {
+            self.start_editing_last_prompt();
+            return Ok(());
+        }
+
+        self.state.is_waiting = true;
+        self.state.current_response.clear();
+        self.state.thinking_content.clear();
+        self.state.active_tools.clear();
+
+        if let Err(e) = self.state.app.edit_last_prompt(message).await {
+            self.state.is_waiting = false;
+            self.state.add_error_message(&e.to_string());
+            return Ok(());
+        }
+        self.state.add_system_message(
+            "✎ Replaced your last message; the old reply was dropped from context",
+        );
+        Ok(())
+    }
+
+    /// `/regenerate [temperature]` drops the last reply and asks again
+    async fn regenerate(&mut self, temperature: &str) -> Result<()> {
+        if self.state.is_waiting {
+            self.state
+                .add_error_message("Wait for the current response before regenerating");
+            return Ok(());
+        }
+        let temperature = if temperature.is_empty() {
+            None
+        } else {
+            match GenerationSettings::parse_temperature(temperature) {
+                Ok(temperature) => Some(temperature),
+                Err(e) => {
+                    self.state
+                        .add_error_message(&format!("Usage: /regenerate [temperature]. {}", e));
+                    return Ok(());
+                }
+            }
+        };
+
+        self.state.is_waiting = true;
+        self.state.current_response.clear();
+        self.state.thinking_content.clear();
+        self.state.active_tools.clear();
+
+        match self.state.app.regenerate(temperature).await {
+            Ok(_) => self.state.add_system_message(&match temperature {
+                Some(temperature) => {
+                    format!("↻ Regenerating the last reply at temperature {}", temperature)
+                }
+                None => "↻ Regenerating the last reply".to_string(),
+            }),
+            Err(e) => {
+                self.state.is_waiting = false;
+                self.state.add_error_message(&e.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// `/scripts` lists what the user scripts provide; `/scripts reload` reloads them
+    fn run_scripts_command(&mut self, arg: &str) {
+        match arg {
+            "" => {}
+            "reload" => {
+                if let Err(e) = self.state.app.reload_scripts() {
+                    self.state
+                        .add_error_message(&format!("Failed to reinitialize the client: {}", e));
+                }
+            }
+            _ => {
+                self.state.add_error_message("Usage: /scripts [reload]");
+                return;
+            }
+        }
+
+        let scripts = &self.state.app.scripts;
+        let mut lines = Vec::new();
+        if scripts.is_empty() {
+            lines.push(format!(
+                "No scripts loaded. Put *.rhai files in {}",
+                scripts_dir()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "~/.arula/scripts".to_string())
+            ));
+        } else {
+            lines.push(format!("Scripts: {}", scripts.script_names().join(", ")));
+        }
+        for command in scripts.commands() {
+            lines.push(format!("  /{} - {}", command.name, command.description));
+        }
+        for tool in scripts.tools() {
+            lines.push(format!("  tool {} - {}", tool.name, tool.description));
+        }
+        if scripts.transforms_prompts() {
+            lines.push("  rewrites prompts".to_string());
+        }
+        if scripts.transforms_responses() {
+            lines.push("  rewrites responses".to_string());
+        }
+        let errors = scripts.errors.clone();
+        self.state.add_system_message(&lines.join("\n"));
+        for error in errors {
+            self.state.add_error_message(&error);
+        }
+    }
+
+    /// `/plugins` lists the WASM plugins and their access; `/plugins reload` reloads them
+    /// Apply the fix offered for a failed request
+    async fn apply_error_fix(&mut self, fix: ErrorFix) -> Result<()> {
+        let mut output = OutputHandler::new();
+        match fix {
+            ErrorFix::OpenSettings => {
+                let result = ConfigMenu::new().show(&mut self.state.app, &mut output)?;
+                self.handle_menu_result(result)?;
+            }
+            ErrorFix::SwitchModel => {
+                MenuUtils::setup_terminal()?;
+                let result = ModelSelector::new().show_model_selector(&mut self.state.app, &mut output);
+                MenuUtils::restore_terminal()?;
+                result?;
+            }
+            ErrorFix::Compact => self.compact_conversation().await,
+        }
+        Ok(())
+    }
+
+    async fn compact_conversation(&mut self) {
+        match self.state.app.compact_conversation(KEEP_TURNS).await {
+            Ok(Some(compaction)) => self.state.add_system_message(&format!(
+                "🗜  Summarized {} earlier message(s): {} → {} characters. The last {} turn(s) are kept as they were.",
+                compaction.replaced, compaction.chars_before, compaction.chars_after, KEEP_TURNS
+            )),
+            Ok(None) => self
+                .state
+                .add_system_message("Nothing to compact yet: the conversation is only a few turns long"),
+            Err(e) => self
+                .state
+                .add_error_message(&format!("Compaction failed: {}", e)),
+        }
+    }
+
+    /// Store a finished session brief, or start writing one once the
+    /// session has been idle for a while
+    fn update_session_brief(&mut self) {
+        if let Some(rx) = self.state.brief_rx.as_mut() {
+            let Ok(result) = rx.try_recv() else {
+                return;
+            };
+            self.state.brief_rx = None;
+            match result {
+                Ok(brief) => self.state.app.set_session_brief(brief),
+                Err(e) => logger::warn(&format!("Session brief failed: {}", e)),
+            }
+            return;
+        }
+
+        let messages = conversation_len(&self.state.app.messages);
+        if !self.state.app.config.get_session_brief_enabled()
+            || self.state.is_waiting
+            || !self.state.queued_prompts.is_empty()
+            || !brief::is_due(messages, self.state.brief_covers, self.state.last_activity.elapsed())
+        {
+            return;
+        }
+        self.state.brief_covers = messages;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let previous = self.state.app.session_brief().map(str::to_string);
+        let history = self.state.app.messages.clone();
+        tokio::spawn(async move {
+            let brief = brief::write_brief(&config, previous.as_deref(), &history)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(brief);
+        });
+        self.state.brief_rx = Some(rx);
+    }
+
+    /// `/session`: the conversation's title, size and brief
+    /// Show or hide the tool activity panel, remembering it with the conversation
+    fn toggle_tool_panel(&mut self) {
+        self.state.show_tool_panel = !self.state.show_tool_panel;
+        self.remember_tool_panel();
+        if self.state.show_tool_panel && !tool_panel::fits(self.state.screen_width) {
+            self.state
+                .add_system_message("The tool activity panel needs a terminal at least 80 columns wide");
+        }
+    }
+
+    /// Save the panel choice with the conversation once it differs from the default
+    fn remember_tool_panel(&mut self) {
+        let shown = self.state.show_tool_panel;
+        if self.state.app.session_tool_panel().unwrap_or(false) != shown {
+            self.state.app.set_session_tool_panel(shown);
+        }
+    }
+
+    /// Show the conversation full-screen, from the focused message if any
+    fn present(&mut self) -> Result<()> {
+        let mut view = PresentationView::new(&self.state.app.messages, self.state.focused_message);
+        if view.is_empty() {
+            self.state.add_error_message("Nothing to present yet");
+            return Ok(());
+        }
+        view.show()?;
+        // Force a full viewport redraw after leaving the alternate screen
+        self.terminal.clear()?;
+        Ok(())
+    }
+
+    /// Hand the prompt to the user's editor and take back what they saved
+    fn edit_input_externally(&mut self) -> Result<()> {
+        match edit_externally(&self.state.input) {
+            Ok(edited) => {
+                self.state.input_cursor = edited.chars().count();
+                self.state.input = edited;
+            }
+            Err(e) => self
+                .state
+                .add_error_message(&format!("Couldn't edit the prompt: {}", e)),
+        }
+        // The editor drew over the viewport
+        self.terminal.clear()?;
+        Ok(())
+    }
+
+    /// Let the user pick files from the project to send with the next message
+    fn browse_files(&mut self) -> Result<()> {
+        let root = std::env::current_dir()?;
+        let marked = FileTreeView::new(
+            &root,
+            &self.state.touched_files,
+            &self.state.app.context_files,
+        )
+        .show()?;
+        // Force a full viewport redraw after leaving the alternate screen
+        self.terminal.clear()?;
+        if marked != self.state.app.context_files {
+            if marked.is_empty() {
+                self.state.add_system_message("No files attached");
+            } else {
+                let names: Vec<String> = marked.iter().map(|f| f.display().to_string()).collect();
+                self.state.add_system_message(&format!(
+                    "Attached to your next message: {}",
+                    names.join(", ")
+                ));
+            }
+        }
+        self.state.app.context_files = marked;
+        Ok(())
+    }
+
+    fn show_session_info(&mut self) {
+        let app = &self.state.app;
+        let title = app
+            .current_conversation
+            .as_ref()
+            .map(|conv| conv.metadata.title.clone())
+            .unwrap_or_else(|| "New conversation".to_string());
+        let details = format!(
+            "{} message(s) · {} · {}",
+            conversation_len(&app.messages),
+            app.config.active_provider,
+            app.config.get_model()
+        );
+        let brief = match app.session_brief() {
+            Some(brief) => brief.to_string(),
+            None if !app.config.get_session_brief_enabled() => {
+                "Session briefs are off (session_brief in config.json)".to_string()
+            }
+            None => format!(
+                "No brief yet; one is written after {}s without activity",
+                brief::IDLE_AFTER.as_secs()
+            ),
+        };
+
+        self.state.push_history(
+            HistoryKind::System,
+            HistoryLine::new(vec![
+                HistorySpan::new(format!("{} ", Icon::Info)).fg(Color::Cyan),
+                HistorySpan::new(title).bold(),
+            ]),
+        );
+        self.state.push_history(
+            HistoryKind::System,
+            HistoryLine::new(vec![HistorySpan::new(format!("  {}", details)).dim()]),
+        );
+        self.state.push_history(
+            HistoryKind::System,
+            HistoryLine::new(vec![HistorySpan::new(format!("  {}", brief))]),
+        );
+    }
+
+    /// `/stats`: usage dashboard for this session and the last 30 days
+    fn show_stats(&mut self) -> Result<()> {
+        let report = match StatsReport::collect(self.state.app.current_conversation.as_ref()) {
+            Ok(report) => report,
+            Err(e) => {
+                self.state
+                    .add_error_message(&format!("Failed to read statistics: {}", e));
+                return Ok(());
+            }
+        };
+        StatsView::new(&report).show()?;
+        // Force a full viewport redraw after leaving the alternate screen
+        self.terminal.clear()?;
+        Ok(())
+    }
+
+    /// `/export [file]`: write the conversation as Markdown
+    fn export_conversation(&mut self, path: &str) {
+        let path = (!path.is_empty()).then(|| Path::new(path));
+        match self.state.app.export_conversation(path) {
+            Ok(path) => self
+                .state
+                .add_system_message(&format!("✓ Exported the conversation to {}", path.display())),
+            Err(e) => self
+                .state
+                .add_error_message(&format!("Export failed: {}", e)),
+        }
+    }
+
+    /// `/copy [last|code [n]]`
+    fn run_copy_command(&mut self, arg: &str) {
+        let mut args = arg.split_whitespace();
+        match (args.next(), args.next()) {
+            (None | Some("last"), None) => {
+                if self.state.last_response.trim().is_empty() {
+                    self.state.add_error_message("There is no response to copy yet");
+                    return;
+                }
+                let response = self.state.last_response.trim().to_string();
+                self.state.copy_text(&response, "Last response");
+            }
+            (Some("code"), number) => {
+                // Without a number: the last block
+                let count = extract_code_blocks(&self.state.last_response).len();
+                let index = match number.map(str::parse::<usize>) {
+                    None => count.saturating_sub(1),
+                    Some(Ok(n)) if (1..=count).contains(&n) => n - 1,
+                    Some(_) if count == 0 => 0,
+                    Some(_) => {
+                        self.state.add_error_message(&format!(
+                            "No code block {} (the last response has {})",
+                            number.unwrap_or_default(),
+                            count
+                        ));
+                        return;
+                    }
+                };
+                self.state.copy_code_block(index);
+            }
+            _ => self
+                .state
+                .add_error_message("Usage: /copy [last|code [n]]"),
+        }
+    }
+
+    fn run_debug_command(&mut self, arg: &str) {
+        match arg {
+            "last-error" | "" => {}
+            "cache" => {
+                let stats = session_cache().stats();
+                self.state
+                    .add_system_message(&format!("Tool result cache: {}", stats));
+                return;
+            }
+            _ => {
+                self.state
+                    .add_error_message("Usage: /debug [last-error|cache]");
+                return;
+            }
+        }
+        match provider_error::last_error() {
+            Some(error) => self.state.add_system_message(&error.details()),
+            None => self
+                .state
+                .add_system_message("No provider errors since ARULA started"),
+        }
+    }
+
+    /// `/voice`: start recording, or stop and transcribe a running recording
+    fn toggle_voice(&mut self) {
+        if self.state.voice.is_some() {
+            self.stop_voice();
+            return;
+        }
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return;
+        }
+        match Recording::start(&self.state.app.config.get_voice()) {
+            Ok(recording) => {
+                self.state.voice = Some(recording);
+                self.state.add_system_message(&format!(
+                    "{} Recording. Press Enter to stop, Esc to cancel",
+                    Icon::Microphone
+                ));
+            }
+            Err(e) => self
+                .state
+                .add_error_message(&format!("Couldn't start recording: {}", e)),
+        }
+    }
+
+    fn stop_voice(&mut self) {
+        let Some(recording) = self.state.voice.take() else {
+            return;
+        };
+        let audio = match recording.stop() {
+            Ok(audio) => audio,
+            Err(e) => {
+                self.state
+                    .add_error_message(&format!("Recording failed: {}", e));
+                return;
+            }
+        };
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        tokio::spawn(async move {
+            let text = voice::transcribe(&audio, &config)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(text);
+        });
+
+        self.state.voice_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some(format!("{} Transcribing...", Icon::Microphone));
+    }
+
+    fn poll_voice(&mut self) -> bool {
+        let Some(rx) = self.state.voice_rx.as_mut() else {
+            return false;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return false;
+        };
+        self.finish_background_task();
+
+        match result {
+            Ok(text) if text.trim().is_empty() => {
+                self.state.add_system_message("No speech was recognized");
+            }
+            Ok(text) => {
+                if !self.state.input.is_empty() && !self.state.input.ends_with(char::is_whitespace) {
+                    self.state.input.push(' ');
+                }
+                self.state.input.push_str(text.trim());
+                self.state.input_cursor = self.state.input.chars().count();
+                self.state
+                    .add_system_message("Transcript added to the input. Review it, then press Enter to send");
+            }
+            Err(error) => self
+                .state
+                .add_error_message(&format!("Transcription failed: {}", error)),
+        }
+        true
+    }
+
+    /// `/logs [tail|path]`: follow the log file, or show where it is
+    fn run_logs_command(&mut self, arg: &str) -> Result<()> {
+        let path = logger::log_file_path();
+        match arg {
+            "" | "tail" => {
+                LogView::new(&path).show()?;
+                // Force a full viewport redraw after leaving the alternate screen
+                self.terminal.clear()?;
+            }
+            "path" => self
+                .state
+                .add_system_message(&format!("Logs are written to {}", path.display())),
+            other => self.state.add_error_message(&format!(
+                "Unknown /logs option '{}': use tail or path",
+                other
+            )),
+        }
+        Ok(())
+    }
+
+    /// `/speak`: read the last reply aloud, stop, or turn reading every
+    /// reply on or off
+    fn run_speak_command(&mut self, arg: &str) {
+        match arg {
+            "" => {
+                if self.state.last_response.trim().is_empty() {
+                    self.state.add_error_message("There's no reply to read yet");
+                } else {
+                    let reply = self.state.last_response.clone();
+                    self.speak(&reply);
+                }
+            }
+            "stop" => {
+                self.state.speech_rx = None;
+                if let Some(playback) = self.state.speech.take() {
+                    playback.stop();
+                }
+            }
+            "on" | "off" => {
+                let enabled = arg == "on";
+                if let Err(e) = self.state.app.config.set_speech_enabled(enabled) {
+                    self.state
+                        .add_error_message(&format!("Failed to save setting: {}", e));
+                    return;
+                }
+                self.state.add_system_message(if enabled {
+                    "Speech on: replies are read aloud when they finish (Esc stops)"
+                } else {
+                    "Speech off"
+                });
+            }
+            _ => self.state.add_error_message("Usage: /speak [on|off|stop]"),
+        }
+    }
+
+    /// Read `reply` aloud, stopping whatever is being read
+    fn speak(&mut self, reply: &str) {
+        self.state.speech = None;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let reply = reply.to_string();
+        tokio::spawn(async move {
+            let utterance = speech::prepare(&reply, &config)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(utterance);
+        });
+        self.state.speech_rx = Some(rx);
+    }
+
+    fn poll_speech(&mut self) -> bool {
+        if let Some(playback) = &mut self.state.speech
+            && playback.is_finished()
+        {
+            self.state.speech = None;
+            return true;
+        }
+        let Some(rx) = self.state.speech_rx.as_mut() else {
+            return false;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return false;
+        };
+        self.state.speech_rx = None;
+        match result.and_then(|utterance| utterance.play().map_err(|e| e.to_string())) {
+            Ok(playback) => self.state.speech = Some(playback),
+            Err(error) => self
+                .state
+                .add_error_message(&format!("Couldn't read the reply aloud: {}", error)),
+        }
+        true
+    }
+
+    /// `/criteria`: list, add or clear the checks run when a task ends
+    fn run_criteria_command(&mut self, arg: &str) {
+        let (action, spec) = match arg.split_once(char::is_whitespace) {
+            Some((action, spec)) => (action, spec.trim()),
+            None => (arg, ""),
+        };
+        let criteria = &mut self.state.app.success_criteria;
+        match action.to_lowercase().as_str() {
+            "" => {}
+            "add" => match Criterion::parse(spec) {
+                Ok(criterion) => {
+                    if !criteria.contains(&criterion) {
+                        criteria.push(criterion);
+                    }
+                }
+                Err(e) => {
+                    self.state.add_error_message(&e.to_string());
+                    return;
+                }
+            },
+            "clear" => {
+                criteria.clear();
+                self.state.add_system_message("Success criteria cleared");
+                return;
+            }
+            _ => {
+                self.state.add_error_message(
+                    "Usage: /criteria [add tests|add file <path>|add cmd <command>|clear]",
+                );
+                return;
+            }
+        }
+        if criteria.is_empty() {
+            self.state.add_system_message(
+                "No success criteria: runs end when the agent says so. Add one with /criteria add tests, file <path> or cmd <command>",
+            );
+        } else {
+            let list: Vec<String> = criteria.iter().map(|c| format!("  - {}", c)).collect();
+            self.state.add_system_message(&format!(
+                "A task is done when, at the end of each run:\n{}",
+                list.join("\n")
+            ));
+        }
+    }
+
+    fn run_plugins_command(&mut self, arg: &str) {
+        match arg {
+            "" => {}
+            "reload" => {
+                if let Err(e) = self.state.app.reload_plugins() {
+                    self.state
+                        .add_error_message(&format!("Failed to reinitialize the client: {}", e));
+                }
+            }
+            _ => {
+                self.state.add_error_message("Usage: /plugins [reload]");
+                return;
+            }
+        }
+
+        let plugins = &self.state.app.plugins;
+        let mut lines = Vec::new();
+        if plugins.is_empty() {
+            lines.push(format!(
+                "No plugins loaded. Put a directory with {} and a .wasm module in {}",
+                MANIFEST_FILE,
+                plugins_dir()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_else(|| "~/.arula/plugins".to_string())
+            ));
+        } else {
+            lines.push("Plugins:".to_string());
+        }
+        for plugin in plugins.plugins() {
+            lines.push(format!(
+                "  {} - {} ({})",
+                plugin.manifest.name,
+                plugin.manifest.description,
+                plugin.manifest.capabilities.summary()
+            ));
+        }
+        let errors = plugins.errors.clone();
+        self.state.add_system_message(&lines.join("\n"));
+        for error in errors {
+            self.state.add_error_message(&error);
+        }
+    }
+
+    /// Run a command provided by a user script
+    fn run_script_command(&mut self, name: &str, args: &str) {
+        if let Some(plugin) = self.state.app.plugins.command(name) {
+            match plugin.run(&serde_json::json!({ "args": args })) {
+                Ok(Value::String(text)) => self.state.add_system_message(&text),
+                Ok(value) => self.state.add_system_message(
+                    &serde_json::to_string_pretty(&value).unwrap_or_else(|_| value.to_string()),
+                ),
+                Err(e) => self
+                    .state
+                    .add_error_message(&format!("/{} failed: {}", name, e)),
+            }
+            return;
+        }
+        if self.state.app.scripts.command(name).is_none() {
+            self.state
+                .add_error_message(&format!("Unknown command /{}. Type /help for a list.", name));
+            return;
+        }
+        match self.state.app.scripts.run_command(name, args) {
+            Ok(Some(text)) => self.state.add_system_message(&text),
+            Ok(None) => {}
+            Err(e) => self
+                .state
+                .add_error_message(&format!("/{} failed: {}", name, e)),
+        }
+    }
+
+    fn run_spinners_command(&mut self, args: &str) {
+        let args: Vec<&str> = args.split_whitespace().collect();
+        let result = match args.as_slice() {
+            [] => {
+                self.show_style_gallery();
+                return;
+            }
+            ["progress", name] => self
+                .state
+                .app
+                .config
+                .set_progress_style(name)
+                .map(|()| format!("Progress bars now use '{}'", name)),
+            [name] => self.state.app.config.set_spinner_pack(name).map(|()| {
+                self.state.spinner = spinner_pack(&self.state.app.config);
+                format!("Spinner now uses '{}'", name)
+            }),
+            _ => {
+                self.state
+                    .add_error_message("Usage: /spinners [name|progress <name>]");
+                return;
+            }
+        };
+        match result {
+            Ok(message) => self.state.add_system_message(&message),
+            Err(e) => self
+                .state
+                .add_error_message(&format!("{}. Type /spinners to see them all.", e)),
+        }
+    }
+
+    fn run_theme_command(&mut self, name: &str) {
+        if name.is_empty() {
+            let active = self.state.app.config.get_theme();
+            let dir = themes_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_else(|| "~/.arula/themes".to_string());
+            self.state
+                .add_system_message(&format!("Themes (/theme <name>; your own go in {}):", dir));
+            for id in available_themes() {
+                let Ok(theme) = Theme::load(&id) else {
+                    continue;
+                };
+                let is_active = theme.id == active.id;
+                let mut marker = HistorySpan::new(format!(
+                    "  {} {:<14}",
+                    if is_active { "●" } else { "○" },
+                    id
+                ));
+                marker = if is_active { marker.fg(Color::Green) } else { marker.dim() };
+                let c = theme.colors;
+                let mut line = vec![marker];
+                let swatches = [c.background, c.surface, c.text, c.accent, c.success, c.warning, c.danger];
+                for rgb in swatches {
+                    line.push(HistorySpan::new("██").fg(term_color(rgb)));
+                }
+                line.push(HistorySpan::new(format!("  {}", theme.name)).dim());
+                self.state.push_history(HistoryKind::System, HistoryLine::new(line));
+            }
+            return;
+        }
+        match self.state.app.config.set_theme(name) {
+            Ok(()) => {
+                let theme = self.state.app.config.get_theme();
+                let message = format!("Theme switched to {}", theme.name);
+                set_active_theme(theme);
+                self.state.add_system_message(&message);
+            }
+            Err(e) => self
+                .state
+                .add_error_message(&format!("{:#}. Type /theme to see them all.", e)),
+        }
+    }
+
+    /// List every spinner pack and progress style with a preview
+    fn show_style_gallery(&mut self) {
+        let appearance = self.state.app.config.get_appearance();
+        let active_spinner = self.state.spinner.clone();
+        let active_progress = appearance.progress_style();
+
+        self.state.add_system_message("Spinners (/spinners <name>):");
+        for (name, pack) in appearance.spinner_packs() {
+            let is_active = pack == active_spinner;
+            let strip: Vec<&str> = pack.frames.iter().take(12).map(String::as_str).collect();
+            let mut marker = HistorySpan::new(format!(
+                "  {} {:<10}",
+                if is_active { "●" } else { "○" },
+                name
+            ));
+            marker = if is_active { marker.fg(Color::Green) } else { marker.dim() };
+            self.state.push_history(
+                HistoryKind::System,
+                HistoryLine::new(vec![
+                    marker,
+                    HistorySpan::new(strip.join(" ")).fg(Color::Cyan),
+                    HistorySpan::new(format!("  {}ms", pack.interval_ms)).dim(),
+                ]),
+            );
+        }
+
+        self.state
+            .add_system_message("Progress bars (/spinners progress <name>):");
+        for (name, style) in appearance.progress_styles() {
+            let is_active = style == active_progress;
+            let mut marker = HistorySpan::new(format!(
+                "  {} {:<10}",
+                if is_active { "●" } else { "○" },
+                name
+            ));
+            marker = if is_active { marker.fg(Color::Green) } else { marker.dim() };
+            self.state.push_history(
+                HistoryKind::System,
+                HistoryLine::new(vec![
+                    marker,
+                    HistorySpan::new(format!("[{}]", style.render(0.6, 20))).fg(Color::Cyan),
+                    HistorySpan::new(" 60%").dim(),
+                ]),
+            );
+        }
+    }
+
+    fn set_grounded_mode(&mut self, arg: &str) {
+        let enabled = match arg {
+            "" => !self.state.grounded_mode,
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.state.add_error_message("Usage: /grounded [on|off]");
+                return;
+            }
+        };
+        self.state.grounded_mode = enabled;
+        if enabled {
+            self.state.add_system_message(
+                "📚 Grounded mode on: questions are answered only from repository code, with file citations. /grounded off to leave.",
+            );
+        } else {
+            self.state.add_system_message("Grounded mode off");
+        }
+    }
+
+    fn start_grounded_answer(&mut self, question: &str) {
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return;
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let config = self.state.app.config.clone();
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        let question = question.to_string();
+        tokio::spawn(async move {
+            let answer = answer_grounded(&config, &dir, &question)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(answer);
+        });
+
+        self.state.grounded_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some("📚 Searching the codebase...".to_string());
+    }
+
+    fn poll_grounded_answer(&mut self) -> bool {
+        let Some(rx) = self.state.grounded_rx.as_mut() else {
+            return false;
+        };
+        let Ok(result) = rx.try_recv() else {
+            return false;
+        };
+        self.finish_background_task();
+
+        let answer = match result {
+            Ok(answer) => answer,
+            Err(error) => {
+                self.state
+                    .add_error_message(&format!("Grounded answer failed: {}", error));
+                return true;
+            }
+        };
+
+        // Path citations become numbered markers that `/source <n>` opens
+        let (text, cited) = number_citations(&answer.answer, &answer.sources);
+        self.state.add_ai_message(&text);
+        if !answer.sources.is_empty() {
+            let listed: Vec<usize> = if cited.is_empty() {
+                (1..=answer.sources.len()).collect()
+            } else {
+                cited
+            };
+            self.state.add_system_message("Sources:");
+            for n in listed {
+                self.state.push_history(
+                    HistoryKind::System,
+                    HistoryLine::new(vec![
+                        HistorySpan::new(format!("  [{}] ", n)).fg(Color::Cyan),
+                        HistorySpan::new(answer.sources[n - 1].label()).dim(),
+                    ]),
+                );
+            }
+            self.state.push_history(
+                HistoryKind::System,
+                HistoryLine::new(vec![HistorySpan::new("  /source <n> opens a cited region")
+                    .fg(Color::DarkGrey)
+                    .dim()]),
+            );
+        }
+        self.state.last_sources = answer.sources.clone();
+        if !answer.issues.is_empty() {
+            self.state.add_error_message(&format!(
+                "⚠ {} statement(s) could not be verified against the code:",
+                answer.issues.len()
+            ));
+            for issue in &answer.issues {
+                self.state.push_history(
+                    HistoryKind::System,
+                    HistoryLine::new(vec![HistorySpan::new(format!("  {}", issue))
+                        .fg(Color::Yellow)
+                        .dim()]),
+                );
+            }
+        }
+        true
+    }
+
+    fn open_source(&mut self, number: Option<usize>) -> Result<()> {
+        let sources = self.state.last_sources.clone();
+        if sources.is_empty() {
+            self.state
+                .add_error_message("No sources yet. Ask a question in /grounded mode first.");
+            return Ok(());
+        }
+        if let Some(n) = number
+            && !(1..=sources.len()).contains(&n)
+        {
+            self.state.add_error_message(&format!(
+                "No source [{}]; the last answer has {} source(s)",
+                n,
+                sources.len()
+            ));
+            return Ok(());
+        }
+
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        if let SourceAction::Edit(index) =
+            SourceView::new(&sources, &dir, number.map(|n| n - 1)).show()?
+        {
+            let source = &sources[index];
+            if let Err(e) = open_in_editor(&dir.join(&source.path), source.start_line) {
+                self.state
+                    .add_error_message(&format!("Failed to open editor: {}", e));
+            }
+            // Force a full viewport redraw once the editor exits
+            self.terminal.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild the workspace snapshot used by the hallucination guard
+    ///
+    /// Runs in the background; lines streamed before it finishes are checked
+    /// against the previous snapshot. A newer rebuild replaces a pending one.
+    fn refresh_reference_checker(&mut self) {
+        if !self.state.app.config.get_verify_references_enabled() {
+            return;
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(ReferenceChecker::build(&dir));
+        });
+        self.state.reference_checker_rx = Some(rx);
+    }
+
+    /// Syntax-check the code blocks of the finished response in the background
+    fn start_code_lint(&mut self) {
+        if !self.state.app.config.get_lint_code_blocks_enabled()
+            || !self.state.current_response.contains("```")
+        {
+            return;
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        let response = self.state.current_response.clone();
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(lint_code_blocks(&response));
+        });
+        self.state.code_lint_rx = Some(rx);
+    }
+
+    /// Run code block `number` (default: the last runnable one) of the last
+    /// response in a scratch project
+    fn start_code_run(&mut self, number: Option<usize>) {
+        if self.has_background_task() {
+            self.state
+                .add_error_message("Another background command is still running");
+            return;
+        }
+        let blocks = extract_code_blocks(&self.state.last_response);
+        if blocks.is_empty() {
+            self.state
+                .add_error_message("The last response has no code blocks to run");
+            return;
+        }
+        let index = match number {
+            Some(n) if (1..=blocks.len()).contains(&n) => n - 1,
+            Some(n) => {
+                self.state.add_error_message(&format!(
+                    "No code block {} (the last response has {})",
+                    n,
+                    blocks.len()
+                ));
+                return;
+            }
+            None => match blocks
+                .iter()
+                .rposition(|b| RunKind::from_fence(&b.language).is_some())
+            {
+                Some(index) => index,
+                None => {
+                    self.state
+                        .add_error_message("None of the code blocks is in a runnable language");
+                    return;
+                }
+            },
+        };
+
+        let block = blocks[index].clone();
+        self.state.add_system_message(&format!(
+            "▶ Running code block {} ({}) in a scratch project",
+            index + 1,
+            if block.language.is_empty() { "untagged" } else { &block.language }
+        ));
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_code_block(block, tx));
+        self.state.run_rx = Some(rx);
+        self.state.is_waiting = true;
+        self.state.background_status = Some(format!("▶ Running code block {}...", index + 1));
+    }
+
+    fn poll_code_run(&mut self) -> bool {
+        let mut changed = false;
+        while let Some(event) = self.state.run_rx.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            changed = true;
+            match event {
+                RunEvent::Started { command } => {
+                    self.state.push_history(
+                        HistoryKind::Tool,
+                        HistoryLine::new(vec![HistorySpan::new(format!("  $ {}", command)).dim()]),
+                    );
+                }
+                RunEvent::Output { line, stderr } => {
+                    let text = HistorySpan::new(clean_text(&line));
+                    self.state.push_history(
+                        HistoryKind::Tool,
+                        HistoryLine::new(vec![
+                            HistorySpan::new("  │ ").dim(),
+                            if stderr { text.fg(Color::Red) } else { text },
+                        ]),
+                    );
+                }
+                RunEvent::Finished(outcome) => {
+                    self.finish_background_task();
+                    let elapsed = format!("{:.1}s", outcome.elapsed.as_secs_f64());
+                    let (mark, color, status) = if outcome.timed_out {
+                        ("✗", Color::Red, format!("timed out after {}", elapsed))
+                    } else if outcome.success() {
+                        ("✓", Color::Green, format!("exited 0 in {}", elapsed))
+                    } else {
+                        let code = outcome
+                            .exit_code
+                            .map(|c| c.to_string())
+                            .unwrap_or_else(|| "by signal".to_string());
+                        ("✗", Color::Red, format!("exited {} in {}", code, elapsed))
+                    };
+                    let mut spans = vec![
+                        HistorySpan::new(format!("  {} ", mark)).fg(color).bold(),
+                        HistorySpan::new(status).fg(color),
+                    ];
+                    if outcome.truncated {
+                        spans.push(HistorySpan::new(" (output truncated)").dim());
+                    }
+                    self.state
+                        .push_history(HistoryKind::Tool, HistoryLine::new(spans));
+                }
+                RunEvent::Failed(error) => {
+                    self.finish_background_task();
+                    self.state
+                        .add_error_message(&format!("Couldn't run the block: {}", error));
+                }
+            }
+        }
+        changed
+    }
+
+    fn has_background_task(&self) -> bool {
+        self.state.walkthrough_rx.is_some()
+            || self.state.commit_rx.is_some()
+            || self.state.pr_description_rx.is_some()
+            || self.state.grounded_rx.is_some()
+            || self.state.run_rx.is_some()
+            || self.state.voice_rx.is_some()
+    }
+
+    fn finish_background_task(&mut self) {
+        self.state.walkthrough_rx = None;
+        self.state.commit_rx = None;
+        self.state.pr_description_rx = None;
+        self.state.grounded_rx = None;
+        self.state.run_rx = None;
+        self.state.voice_rx = None;
+        self.state.background_status = None;
+        self.state.is_waiting = false;
+    }
+
+    fn poll_ai_response(&mut self) -> Result<bool> {
+        let mut changed = false;
+        while let Some(response) = self.state.app.check_ai_response_nonblocking() {
+            match response {
+                AiResponse::AgentStreamStart => {
+                    self.state.run_started = Instant::now();
+                    self.state.run_error = None;
+                    self.state.stream_collector.buffer.clear();
+                    self.state.markdown.clear();
+                    self.state.postprocessor =
+                        PostProcessor::new(&self.state.app.config.get_postprocess());
+                }
+                AiResponse::AgentError(error) => {
+                    let provider = self.state.app.config.active_provider.clone();
+                    let help = explain(&error, &provider);
+                    self.state.add_error_message(&error);
+                    self.state.run_error = Some(error);
+                    if let Some(help) = help {
+                        let mut message = format!("💡 {}", help.explanation);
+                        if let Some(fix) = help.fix {
+                            message.push_str(&format!(" Press Tab to {}.", fix.label()));
+                        }
+                        self.state.add_system_message(&message);
+                        self.state.error_fix = help.fix;
+                    }
+                    changed = true;
+                }
+                AiResponse::AgentStreamText(text) => {
+                    let clean = clean_text(&text);
+                    self.state.current_response.push_str(&clean);
+                    for line in self.state.stream_collector.push(&clean) {
+                        self.state.add_checked_ai_message(&line);
+                    }
+                    changed = true;
+                }
+                AiResponse::AgentThinkingStart => {
+                    self.state.thinking_content.clear();
+                    self.state.thinking_expanded = false;
+                    changed = true;
+                }
+                AiResponse::AgentThinkingContent(content) => {
+                    self.state.thinking_content.push_str(&content);
+                    changed = true;
+                }
+                AiResponse::AgentThinkingEnd => {
+                    // Reset expansion state when thinking ends
+                    self.state.thinking_expanded = false;
                 }
                 AiResponse::AgentToolCall {
                     id,
@@ -1195,9 +4687,24 @@ impl TuiApp {
                     self.state
                         .active_tools
                         .retain(|t| t.status == ToolState::Running || t.id == id);
+                    self.state.tools_started.get_or_insert_with(Instant::now);
 
-                    // Log tool call to history so it scrolls up
+                    // Log tool call to history so it scrolls up, after the text before it
+                    self.state.flush_stream();
                     self.state.add_tool_message(&name, &arguments);
+                    if let Ok(root) = std::env::current_dir() {
+                        file_tree_view::record_touch(
+                            &mut self.state.touched_files,
+                            &root,
+                            &name,
+                            &arguments,
+                        );
+                    }
+                    self.state.tool_panel.start(
+                        &id,
+                        Self::display_tool_name(&name),
+                        Self::format_args_preview(&arguments),
+                    );
 
                     // Update existing entry or push new
                     if let Some(existing) = self.state.active_tools.iter_mut().find(|t| t.id == id)
@@ -1225,6 +4732,7 @@ impl TuiApp {
                     tool_call_id,
                     success,
                     result,
+                    duration_ms,
                 } => {
                     if let Some(pos) = self
                         .state
@@ -1240,10 +4748,16 @@ impl TuiApp {
                         };
                         tool.finished_at = Some(Instant::now());
                         tool.summary = Some(Self::summarize_tool_result(&result, success));
+                        // Files the agent just wrote may be referenced in the reply
+                        if success && matches!(tool.name.as_str(), "write_file" | "edit_file") {
+                            self.refresh_reference_checker();
+                        }
 
                         // Push a concise result line into history with duration and summary.
                         let mut spans = vec![
-                            HistorySpan::new("🔧 ").fg(Color::Magenta).bold(),
+                            HistorySpan::new(format!("{} ", Icon::for_tool(&tool.name)))
+                                .fg(Color::Magenta)
+                                .bold(),
                             HistorySpan::new(Self::display_tool_name(&tool.name)).bold(),
                         ];
                         let args_preview = Self::format_args_preview(&tool.args);
@@ -1251,7 +4765,11 @@ impl TuiApp {
                             spans.push(HistorySpan::new(" • ").dim());
                             spans.push(HistorySpan::new(args_preview));
                         }
-                        if let Some(summary) = &tool.summary {
+                        // Shell commands get their output and exit status below instead
+                        let report = (tool.name == "execute_bash")
+                            .then(|| BashResult::from_value(&result))
+                            .flatten();
+                        if let Some(summary) = tool.summary.as_ref().filter(|_| report.is_none()) {
                             spans.push(HistorySpan::new(" — ").dim());
                             spans.push(HistorySpan::new(summary.clone()).fg(if success {
                                 Color::Green
@@ -1259,41 +4777,105 @@ impl TuiApp {
                                 Color::Red
                             }));
                         }
-                        if let Some(done_at) = tool.finished_at {
-                            let duration_ms = done_at
-                                .saturating_duration_since(tool.started_at)
-                                .as_millis();
+                        // Prefer the tool's own run time: calls run in parallel may
+                        // finish long after they were requested
+                        let duration_ms = duration_ms.or_else(|| {
+                            tool.finished_at.map(|done_at| {
+                                done_at.saturating_duration_since(tool.started_at).as_millis()
+                                    as u64
+                            })
+                        });
+                        if let Some(duration_ms) = duration_ms {
                             spans.push(HistorySpan::new(format!(" • {}ms", duration_ms)).dim());
                         }
+                        self.state.tool_panel.finish(
+                            &tool.id,
+                            success,
+                            tool.summary.as_deref().unwrap_or_default(),
+                            Duration::from_millis(duration_ms.unwrap_or_default()),
+                        );
                         self.state
                             .push_history(HistoryKind::Tool, HistoryLine::new(spans));
+                        if let Some(report) = &report {
+                            self.state.add_command_report(report);
+                        }
 
                         // Keep only running tools visible in the status list to avoid duplication.
                         self.state
                             .active_tools
                             .retain(|t| t.status == ToolState::Running);
+                        if self.state.active_tools.is_empty()
+                            && let Some(started) = self.state.tools_started.take()
+                        {
+                            let summary = format!(
+                                "{} — {}",
+                                Self::display_tool_name(&tool.name),
+                                tool.summary.as_deref().unwrap_or("done")
+                            );
+                            self.state.notifier.notify_event(
+                                NotificationEvent::ToolsFinished,
+                                started.elapsed(),
+                                summary,
+                            );
+                        }
                     }
                     changed = true;
                 }
-                AiResponse::AgentStreamEnd => {
-                    let remaining = self.state.stream_collector.finalize();
-                    if !remaining.is_empty() {
-                        for line in remaining {
-                            self.state.add_ai_message(&line);
-                        }
+                AiResponse::LoopDetected(diagnosis) => {
+                    self.state.flush_stream();
+                    self.state.add_error_message(&format!(
+                        "Paused: the agent looks stuck in a loop. {}",
+                        diagnosis
+                    ));
+                    self.state.add_system_message(
+                        "Press Enter to continue anyway, type new instructions to change course, or Esc to stop",
+                    );
+                    self.state.loop_paused = true;
+                    self.state.attention.start(
+                        "the run looks stuck in a loop",
+                        &self.state.app.config.get_attention(),
+                    );
+                    self.state.notifier.notify_event(
+                        NotificationEvent::InputNeeded,
+                        self.state.run_started.elapsed(),
+                        format!("Paused: the agent looks stuck in a loop. {}", diagnosis),
+                    );
+                    changed = true;
+                }
+                AiResponse::ContextCompacted { replaced, .. } => {
+                    self.state.add_system_message(&format!(
+                        "🗜  The conversation no longer fit the model's context window; summarized {} earlier message(s) and retried",
+                        replaced
+                    ));
+                    changed = true;
+                }
+                AiResponse::QuestionAsked(question) => {
+                    self.state
+                        .attention
+                        .start(&question, &self.state.app.config.get_attention());
+                    changed = true;
+                }
+                AiResponse::TaskVerdict(verdict) => {
+                    self.state.flush_stream();
+                    if verdict.passed() {
+                        self.state.add_system_message(&verdict.report());
                     } else {
-                        let first_line = self
-                            .state
-                            .current_response
-                            .lines()
-                            .find(|l| !l.trim().is_empty())
-                            .map(|s| clean_text(s));
-                        if let Some(line) = first_line {
-                            self.state.add_ai_message(&line);
-                        }
+                        self.state.add_error_message(&verdict.report());
                     }
-                    self.state.current_response.clear();
-                    self.state.stream_collector.buffer.clear();
+                    changed = true;
+                }
+                AiResponse::AgentStreamEnd => {
+                    self.state.flush_stream();
+                    self.start_code_lint();
+                    self.state.last_response = std::mem::take(&mut self.state.current_response);
+                    self.notify_run_end();
+                    if self.state.app.config.get_speech().is_enabled()
+                        && !self.state.last_response.trim().is_empty()
+                    {
+                        let reply = self.state.last_response.clone();
+                        self.speak(&reply);
+                    }
+                    self.state.copied_block = None;
                     self.state.active_tools.clear();
                     self.state.thinking_content.clear();
                     self.state.is_waiting = false;
@@ -1305,53 +4887,121 @@ impl TuiApp {
         Ok(changed)
     }
 
-    fn handle_menu_result(&mut self, result: MenuResult) -> Result<()> {
-        match result {
-            MenuResult::LoadConversation(id) => {
-                // Clear state
-                self.state.input.clear();
-                self.state.input_cursor = 0;
+    /// Notify about the finished run if it took long and the terminal is
+    /// unfocused
+    fn notify_run_end(&mut self) {
+        self.state.tools_started = None;
+        let elapsed = self.state.run_started.elapsed();
+        match self.state.run_error.take() {
+            Some(error) => {
+                self.state
+                    .notifier
+                    .notify_event(NotificationEvent::RunFailed, elapsed, error)
+            }
+            None => self.state.notifier.notify_event(
+                NotificationEvent::ResponseFinished,
+                elapsed,
+                &self.state.last_response,
+            ),
+        };
+    }
 
-                // Load conversation
-                self.state.app.load_conversation(&id)?;
+    /// Open a saved conversation and reprint its history; when another
+    /// instance has it open, offer to open it read-only instead
+    fn load_conversation(&mut self, id: &str, read_only: bool) -> Result<()> {
+        // Clear state
+        self.state.input.clear();
+        self.state.input_cursor = 0;
 
-                // Clear screen and reprint history
-                let output = OutputHandler::new();
+        // Load conversation
+        let loaded = if read_only {
+            self.state.app.load_conversation_read_only(id)
+        } else {
+            self.state.app.load_conversation(id)
+        };
+        if let Err(e) = loaded {
+            let Some(in_use) = e.downcast_ref::<SessionInUse>() else {
+                return Err(e);
+            };
+            self.state.add_error_message(&format!(
+                "{}. Press Enter to open it read-only, or Esc to cancel",
+                in_use
+            ));
+            self.state.read_only_offer = Some(id.to_string());
+            return Ok(());
+        }
+        self.state.read_only_offer = None;
+        self.state.tool_panel.clear();
+        self.state.touched_files.clear();
+        self.state.show_tool_panel = self.state.app.session_tool_panel().unwrap_or(false);
+        // A saved brief already covers the saved messages
+        self.state.brief_covers = match self.state.app.session_brief() {
+            Some(_) => conversation_len(&self.state.app.messages),
+            None => 0,
+        };
 
-                // Clear terminal (we need to bypass ratatui for a moment or rely on it cleaning up)
-                execute!(
-                    io::stdout(),
-                    terminal::Clear(terminal::ClearType::All),
-                    crossterm::cursor::MoveTo(0, 0)
-                )?;
-                output.print_banner()?;
+        // Clear screen and reprint history
+        let output = OutputHandler::new();
 
-                for msg in self.state.app.get_message_history() {
-                    match msg.message_type {
-                        MessageType::User => output.print_user_message(&msg.content)?,
-                        MessageType::Arula => output.print_ai_message(&msg.content)?,
-                        MessageType::ToolCall => {
-                            // Parse tool call if possible or just print info
-                            // The content is "🔧 Tool call: name(args)"
-                            // We might want to parse it back or just print as system/debug
-                            // output.print_system(&msg.content)?;
-                        }
-                        MessageType::ToolResult => {
-                            // output.print_system(&msg.content)?;
-                        }
-                        _ => {}
-                    }
+        // Clear terminal (we need to bypass ratatui for a moment or rely on it cleaning up)
+        execute!(
+            io::stdout(),
+            terminal::Clear(terminal::ClearType::All),
+            crossterm::cursor::MoveTo(0, 0)
+        )?;
+        self.state.screen_text.clear();
+        output.print_banner()?;
+
+        let today = Local::now().date_naive();
+        let mut last_date = None;
+        for msg in self.state.app.get_message_history() {
+            let date = msg.timestamp.date_naive();
+            if matches!(msg.message_type, MessageType::User | MessageType::Arula)
+                && last_date != Some(date)
+            {
+                output.print_day_separator(&day_label(date, today))?;
+                last_date = Some(date);
+            }
+            match msg.message_type {
+                MessageType::User => output.print_user_message(&msg.content)?,
+                MessageType::Arula => output.print_ai_message(&msg.content)?,
+                MessageType::ToolCall => {
+                    // Parse tool call if possible or just print info
+                    // The content is "🔧 Tool call: name(args)"
+                    // We might want to parse it back or just print as system/debug
+                    // output.print_system(&msg.content)?;
                 }
-                println!(); // Extra space
+                MessageType::ToolResult => {
+                    // output.print_system(&msg.content)?;
+                }
+                _ => {}
             }
+        }
+        println!(); // Extra space
+        self.state.last_message_date = last_date;
+        if read_only {
+            self.state.add_system_message(
+                "Opened read-only: this session is open elsewhere, so nothing here will be saved",
+            );
+        }
+        Ok(())
+    }
+
+    fn handle_menu_result(&mut self, result: MenuResult) -> Result<()> {
+        match result {
+            MenuResult::LoadConversation(id) => self.load_conversation(&id, false)?,
             MenuResult::ClearChat => {
                 self.state.app.clear_conversation();
+                self.state.tool_panel.clear();
+                self.state.touched_files.clear();
+                self.state.brief_covers = 0;
                 // Clear screen
                 execute!(
                     io::stdout(),
                     terminal::Clear(terminal::ClearType::All),
                     crossterm::cursor::MoveTo(0, 0)
                 )?;
+                self.state.screen_text.clear();
                 let output = OutputHandler::new();
                 output.print_banner()?;
                 println!();
@@ -1364,6 +5014,9 @@ impl TuiApp {
                 // New conversation
                 self.state.app.new_conversation();
                 self.state.app.clear_conversation();
+                self.state.tool_panel.clear();
+                self.state.touched_files.clear();
+                self.state.brief_covers = 0;
 
                 // Clear screen
                 execute!(
@@ -1371,10 +5024,20 @@ impl TuiApp {
                     terminal::Clear(terminal::ClearType::All),
                     crossterm::cursor::MoveTo(0, 0)
                 )?;
+                self.state.screen_text.clear();
                 let output = OutputHandler::new();
                 output.print_banner()?;
                 println!();
             }
+            MenuResult::BranchCreated(branch) => {
+                self.state
+                    .add_system_message(&format!("🌿 Created and switched to branch {}", branch));
+            }
+            MenuResult::BranchSwitched(branch) => {
+                self.state
+                    .add_system_message(&format!("🌿 Switched to branch {}", branch));
+            }
+            MenuResult::GeneratePrDescription => self.start_pr_description(),
             _ => {}
         }
         Ok(())
@@ -1384,6 +5047,8 @@ impl TuiApp {
 impl Drop for TuiApp {
     fn drop(&mut self) {
         let _ = self.terminal.clear();
+        let _ = set_mouse_capture(false);
+        let _ = set_input_modes(false);
         let _ = disable_raw_mode();
         let _ = execute!(io::stdout(), Show);
     }
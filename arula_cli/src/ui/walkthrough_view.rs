@@ -0,0 +1,196 @@
+//! Full-screen viewer for branch walkthroughs
+//!
+//! Shows the branch narrative followed by one page per commit summary,
+//! with keys to jump between commits:
+//!
+//! - `→`/`n`/`l` next page, `←`/`p`/`h` previous page
+//! - `g`/`Home` overview, `G`/`End` last commit, `1`-`9` jump to commit
+//! - `↑`/`↓`/`j`/`k` scroll, `s` save as markdown, `q`/`Esc` close
+
+use anyhow::Result;
+use arula_core::utils::walkthrough::Walkthrough;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::{
+    Terminal,
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use std::io::stdout;
+use std::time::Duration;
+
+use crate::ui::menus::common::MenuUtils;
+
+/// Interactive walkthrough pager
+pub struct WalkthroughView<'a> {
+    walkthrough: &'a Walkthrough,
+    /// 0 = overview, 1..=n = commit pages
+    page: usize,
+    scroll: u16,
+    status: Option<String>,
+}
+
+impl<'a> WalkthroughView<'a> {
+    pub fn new(walkthrough: &'a Walkthrough) -> Self {
+        Self {
+            walkthrough,
+            page: 0,
+            scroll: 0,
+            status: None,
+        }
+    }
+
+    fn page_count(&self) -> usize {
+        self.walkthrough.commits.len() + 1
+    }
+
+    fn go_to(&mut self, page: usize) {
+        self.page = page.min(self.page_count() - 1);
+        self.scroll = 0;
+        self.status = None;
+    }
+
+    /// Show the viewer until the user closes it
+    pub fn show(&mut self) -> Result<()> {
+        MenuUtils::run_overlay(|| self.run_loop())
+    }
+
+    fn run_loop(&mut self) -> Result<()> {
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        terminal.clear()?;
+
+        loop {
+            terminal.draw(|f| self.render(f))?;
+
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Right | KeyCode::Char('n') | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                    self.go_to(self.page + 1)
+                }
+                KeyCode::Left | KeyCode::Char('p') | KeyCode::Char('h') => {
+                    self.go_to(self.page.saturating_sub(1))
+                }
+                KeyCode::Home | KeyCode::Char('g') => self.go_to(0),
+                KeyCode::End | KeyCode::Char('G') => self.go_to(self.page_count() - 1),
+                KeyCode::Char(c @ '1'..='9') => {
+                    self.go_to(c.to_digit(10).unwrap_or(1) as usize);
+                }
+                KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Char('s') => self.status = Some(self.save()),
+                _ => {}
+            }
+        }
+    }
+
+    fn save(&self) -> String {
+        let file_name = format!(
+            "walkthrough-{}.md",
+            self.walkthrough
+                .range
+                .replace("..", "-")
+                .replace(['/', '\\', ' '], "_")
+        );
+        match std::fs::write(&file_name, self.walkthrough.to_markdown()) {
+            Ok(()) => format!("Saved to {}", file_name),
+            Err(e) => format!("Failed to save: {}", e),
+        }
+    }
+
+    fn render(&self, f: &mut ratatui::Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(1),
+                Constraint::Length(1),
+            ])
+            .split(f.area());
+
+        let accent = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        let dim = Style::default().fg(Color::DarkGray);
+
+        // Header: range and page position
+        let title = if self.page == 0 {
+            "Overview".to_string()
+        } else {
+            let entry = &self.walkthrough.commits[self.page - 1];
+            format!("{} {}", entry.commit.short_hash, entry.commit.subject)
+        };
+        let header = Paragraph::new(Line::from(vec![
+            Span::styled(format!(" {} ", self.walkthrough.range), accent),
+            Span::styled(format!("[{}/{}] ", self.page + 1, self.page_count()), dim),
+            Span::raw(title),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Rgb(70, 130, 180)))
+                .title(" Walkthrough "),
+        );
+        f.render_widget(header, chunks[0]);
+
+        // Body: narrative or commit summary
+        let mut lines: Vec<Line> = Vec::new();
+        if self.page == 0 {
+            for line in self.walkthrough.narrative.lines() {
+                lines.push(Line::raw(line.to_string()));
+            }
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Commits", accent));
+            for (i, entry) in self.walkthrough.commits.iter().enumerate() {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:>3}. ", i + 1), dim),
+                    Span::styled(
+                        format!("{} ", entry.commit.short_hash),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(entry.commit.subject.clone()),
+                ]));
+            }
+        } else {
+            let entry = &self.walkthrough.commits[self.page - 1];
+            lines.push(Line::from(vec![
+                Span::styled("Author: ", dim),
+                Span::raw(format!("{}  ", entry.commit.author)),
+                Span::styled("Date: ", dim),
+                Span::raw(entry.commit.date.clone()),
+            ]));
+            lines.push(Line::raw(""));
+            for line in entry.summary.lines() {
+                lines.push(Line::raw(line.to_string()));
+            }
+        }
+        let body = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(Block::default().borders(Borders::NONE));
+        f.render_widget(body, chunks[1]);
+
+        // Footer: key help or status
+        let footer = match &self.status {
+            Some(status) => Line::styled(status.clone(), Style::default().fg(Color::Green)),
+            None => Line::styled(
+                "←/→ prev/next  1-9 jump  g/G first/last  ↑/↓ scroll  s save  q close",
+                dim,
+            ),
+        };
+        f.render_widget(Paragraph::new(footer), chunks[2]);
+    }
+}
@@ -1,3 +1,4 @@
+use arula_core::utils::icons::Icon;
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
@@ -45,19 +46,7 @@ impl<'a> ToolStatusWidget<'a> {
     }
 
     fn get_icon(&self) -> &'static str {
-        match self.name.to_lowercase().as_str() {
-            "execute_bash" => "○",
-            "read_file" => "○",
-            "write_file" | "edit_file" => "□",
-            "list_directory" => "◇",
-            "search_files" => "○",
-            "web_search" => "⭕",
-            "mcp_call" => "◊",
-            "visioneer" => "○",
-            "capture_screen" => "●",
-            "analyze_ui" => "◉",
-            _ => "□",
-        }
+        Icon::for_tool(self.name).glyph()
     }
 
     fn get_display_name(&self) -> String {
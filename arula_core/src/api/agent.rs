@@ -3,6 +3,9 @@
 //! This module implements patterns inspired by open-agent-sdk but using
 //! our existing reqwest-based infrastructure to avoid OpenSSL dependencies.
 
+use crate::storage::{Storage, ToolUsageRecord};
+use crate::tools::result_cache::session_cache;
+use crate::utils::debug::debug_print;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -14,6 +17,9 @@ pub struct ToolResult {
     pub success: bool,
     pub data: Value,
     pub error: Option<String>,
+    /// How long the tool took to run, when measured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
 }
 
 impl ToolResult {
@@ -22,6 +28,7 @@ impl ToolResult {
             success: true,
             data,
             error: None,
+            duration_ms: None,
         }
     }
 
@@ -30,8 +37,14 @@ impl ToolResult {
             success: false,
             data: json!(null),
             error: Some(error),
+            duration_ms: None,
         }
     }
+
+    pub fn with_duration(mut self, duration: std::time::Duration) -> Self {
+        self.duration_ms = Some(duration.as_millis() as u64);
+        self
+    }
 }
 
 /// Tool parameter schema builder
@@ -175,6 +188,23 @@ pub trait Tool: Send + Sync {
     fn description(&self) -> &str;
     fn schema(&self) -> ToolSchema;
 
+    /// Whether the tool only reads state, so several calls may run at once
+    fn read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether the tool limits its results to the `_budget` argument (see
+    /// `crate::api::tool_budget`); other tools don't receive it
+    fn budgeted(&self) -> bool {
+        false
+    }
+
+    /// Key under which a successful result may be reused for the rest of
+    /// the session (see `crate::tools::result_cache`); `None` to always run
+    fn cache_key(&self, _params: &Value) -> Option<String> {
+        None
+    }
+
     async fn execute(&self, params: Self::Params) -> Result<Self::Result, String>;
 
     async fn execute_with_result(&self, params: Value) -> ToolResult {
@@ -232,6 +262,27 @@ impl ToolRegistry {
         self.tools.write().unwrap().insert(name, arc_tool);
     }
 
+    /// A copy with the same tools, which tools can be registered into
+    /// without changing this registry (clones share their tools)
+    pub fn detached(&self) -> Self {
+        Self {
+            tools: std::sync::Arc::new(std::sync::RwLock::new(
+                self.tools.read().unwrap().clone(),
+            )),
+        }
+    }
+
+    /// Remove the tool called `name`; whether there was one
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.tools.write().unwrap().remove(name).is_some()
+    }
+
+    /// Add the tools of `other`, replacing any with the same names
+    pub fn extend(&mut self, other: &ToolRegistry) {
+        let tools = other.tools.read().unwrap().clone();
+        self.tools.write().unwrap().extend(tools);
+    }
+
     pub fn get_tools(&self) -> Vec<String> {
         self.tools.read().unwrap().keys().cloned().collect()
     }
@@ -245,14 +296,66 @@ impl ToolRegistry {
             .collect()
     }
 
+    /// Whether `name` is a registered read-only tool
+    pub fn is_read_only(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .unwrap()
+            .get(name)
+            .is_some_and(|tool| tool.read_only())
+    }
+
+    /// Whether `name` is a registered tool that takes a result budget
+    pub fn is_budgeted(&self, name: &str) -> bool {
+        self.tools
+            .read()
+            .unwrap()
+            .get(name)
+            .is_some_and(|tool| tool.budgeted())
+    }
+
+    #[tracing::instrument(
+        name = "tool_execution",
+        skip(self, params),
+        fields(cached = false, success = tracing::field::Empty)
+    )]
     pub async fn execute_tool(&self, name: &str, params: Value) -> Option<ToolResult> {
-        let tool = { self.tools.read().unwrap().get(name).cloned() };
+        let tool = { self.tools.read().unwrap().get(name).cloned() }?;
+
+        let cache = session_cache();
+        let key = tool.cache_key(&params).map(|key| format!("{}:{}", name, key));
+        if let Some(key) = &key
+            && let Some(result) = cache.get(key)
+        {
+            debug_print(&format!("Tool cache hit for {}: {}", name, cache.stats()));
+            tracing::Span::current().record("cached", true);
+            return Some(result);
+        }
 
-        if let Some(tool) = tool {
-            Some(tool.execute_with_result(params).await)
-        } else {
-            None
+        let started = std::time::Instant::now();
+        let result = tool.execute_with_result(params).await;
+        record_tool_usage(name, result.success, started.elapsed());
+        tracing::Span::current().record("success", result.success);
+        match key {
+            Some(key) if result.success => cache.insert(key, result.clone()),
+            // Anything that isn't read-only may have changed the files scans saw
+            None if !tool.read_only() => cache.clear(),
+            _ => {}
         }
+        Some(result)
+    }
+}
+
+/// Add a tool call to the local usage ledger for `/stats`
+fn record_tool_usage(tool: &str, success: bool, duration: std::time::Duration) {
+    let record = ToolUsageRecord {
+        timestamp: chrono::Utc::now(),
+        tool: tool.to_string(),
+        success,
+        duration_ms: duration.as_millis() as u64,
+    };
+    if let Err(e) = Storage::with(|s| s.record_tool_usage(&record)) {
+        debug_print(&format!("Failed to record tool usage: {}", e));
     }
 }
 
@@ -293,6 +396,18 @@ where
         self.inner.schema()
     }
 
+    fn read_only(&self) -> bool {
+        self.inner.read_only()
+    }
+
+    fn budgeted(&self) -> bool {
+        self.inner.budgeted()
+    }
+
+    fn cache_key(&self, params: &Value) -> Option<String> {
+        self.inner.cache_key(params)
+    }
+
     async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
         // Convert the generic Value params to the specific tool's Params type
         let typed_params = match serde_json::from_value(params) {
@@ -449,6 +564,16 @@ pub enum ContentBlock {
         question: String,
         options: Option<Vec<String>>,
     },
+    /// The run was paused because it looks stuck in a loop
+    LoopDetected {
+        diagnosis: String,
+    },
+    /// Older messages were summarized to fit the context window and the
+    /// request was sent again; `replaced` counts the summarized messages
+    ContextCompacted {
+        replaced: usize,
+        summary: String,
+    },
     Error {
         error: String,
     },
@@ -4,17 +4,25 @@
 //! patterns while integrating with the existing reqwest-based API client.
 
 use crate::api::agent::{AgentOptions, ContentBlock, ToolRegistry};
-use crate::api::api::{ApiClient, ChatMessage};
+use crate::api::api::{ApiClient, ChatMessage, Usage};
+use crate::api::provider_error::ProviderError;
+use crate::api::steering::Steering;
+use crate::api::trust::tool_output_block;
+use crate::storage::{Storage, UsageRecord};
 use crate::tools::tools::{create_basic_tool_registry, initialize_mcp_tools};
-use crate::utils::config::Config;
+use crate::tools::injection_guard::InjectionGuard;
+use crate::utils::compaction::recover_overflow;
+use crate::utils::config::{Config, GenerationSettings};
 use crate::utils::debug::debug_print;
 use crate::utils::error_utils::{api_error, stream_error, ErrorContext};
 use anyhow::Result;
 use futures::Stream;
 use serde_json::json;
 use std::pin::Pin;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::Instrument;
 
 /// Modern AI Agent Client
 pub struct AgentClient {
@@ -22,30 +30,20 @@ pub struct AgentClient {
     tool_registry: ToolRegistry,
     options: AgentOptions,
     config: crate::utils::config::Config,
+    /// Messages that redirect the running query
+    steering: Steering,
 }
 
 impl Clone for AgentClient {
     fn clone(&self) -> Self {
-        // For Clone, we need to create a new registry but Clone is sync
-        // So we'll create a minimal registry without MCP tools for cloning
-        let mut registry = crate::api::agent::ToolRegistry::new();
-
-        // Register basic tools (non-MCP)
-        registry.register(crate::tools::tools::BashTool::new());
-        registry.register(crate::tools::tools::FileReadTool::new());
-        registry.register(crate::tools::tools::FileEditTool::new());
-        registry.register(crate::tools::tools::WriteFileTool::new());
-        registry.register(crate::tools::tools::ListDirectoryTool::new());
-        registry.register(crate::tools::tools::SearchTool::new());
-        registry.register(crate::tools::tools::WebSearchTool::new());
-        registry.register(crate::tools::tools::VisioneerTool::new());
-        registry.register(crate::tools::tools::QuestionTool::new());
-
+        // Clones share the registry, so tools registered after creation
+        // (user scripts, plugins) stay available
         Self {
             api_client: self.api_client.clone(),
-            tool_registry: registry,
+            tool_registry: self.tool_registry.clone(),
             options: self.options.clone(),
             config: self.config.clone(),
+            steering: self.steering.clone(),
         }
     }
 }
@@ -60,7 +58,9 @@ impl AgentClient {
         options: AgentOptions,
         config: &crate::utils::config::Config,
     ) -> Self {
-        let api_client = ApiClient::new(provider, endpoint, api_key, model);
+        let api_client = ApiClient::new(provider, endpoint, api_key, model)
+            .with_generation(config.get_generation_settings())
+            .with_options(config.provider_options());
         let tool_registry = create_basic_tool_registry();
 
         Self {
@@ -68,6 +68,7 @@ impl AgentClient {
             tool_registry,
             options,
             config: config.clone(),
+            steering: Steering::new(),
         }
     }
 
@@ -81,13 +82,16 @@ impl AgentClient {
         config: &crate::utils::config::Config,
         tool_registry: crate::api::agent::ToolRegistry,
     ) -> Self {
-        let api_client = ApiClient::new(provider, endpoint, api_key, model);
+        let api_client = ApiClient::new(provider, endpoint, api_key, model)
+            .with_generation(config.get_generation_settings())
+            .with_options(config.provider_options());
 
         Self {
             api_client,
             tool_registry,
             options,
             config: config.clone(),
+            steering: Steering::new(),
         }
     }
 
@@ -98,6 +102,18 @@ impl AgentClient {
         Self::new(provider, endpoint, api_key, model, options, &config)
     }
 
+    /// This client with different sampling settings, for a one-off request
+    pub fn with_generation(mut self, generation: GenerationSettings) -> Self {
+        self.api_client = self.api_client.with_generation(generation);
+        self
+    }
+
+    /// This client with a steering handle the next query reads from
+    pub fn with_steering(mut self, steering: Steering) -> Self {
+        self.steering = steering;
+        self
+    }
+
     /// Check if streaming is enabled in the configuration
     pub fn is_streaming_enabled(&self) -> bool {
         self.config.get_streaming_enabled()
@@ -142,24 +158,20 @@ impl AgentClient {
         let auto_execute_tools = self.options.auto_execute_tools;
         let max_tool_iterations = self.options.max_tool_iterations;
         let config_clone = self.config.clone();
+        let guard = InjectionGuard::new(&self.config.get_injection_guard());
+        let strict_arguments = self.config.get_strict_tool_arguments();
+        let steering = self.steering.clone();
 
         // Get tools from registry
         let tools = self.tool_registry.get_openai_tools();
+        // Tools run from a copy with the MCP tools added
+        let mut execution_registry = self.tool_registry.detached();
 
         // Build messages
-        let messages = self.build_api_messages(message, conversation_history)?;
+        let span = self.request_span(true);
+        let messages = span.in_scope(|| self.build_api_messages(message, conversation_history))?;
 
         tokio::spawn(async move {
-            // Re-initialize MCP tools if needed (though get_openai_tools above implies they are loaded)
-            // But clone needs re-init if it creates a fresh registry? AgentClient::clone does basic registry.
-            // We should use the registry we have.
-            // The previous code created a NEW registry and re-initialized.
-            // But AgentClient::clone method creates a fresh registry with basic tools only.
-
-            // Ideally we'd pass the full registry.
-            // But `StreamWithTools` takes `&ToolRegistry`.
-            // We can reconstruct it:
-            let mut execution_registry = create_basic_tool_registry();
             if let Err(e) = initialize_mcp_tools(&mut execution_registry, &config_clone).await {
                 debug_print(&format!("⚠️ Failed to initialize MCP tools: {}", e));
             }
@@ -167,6 +179,10 @@ impl AgentClient {
             use crate::api::stream::{stream_with_tools, StreamEvent};
 
             let tx_for_callback = tx.clone();
+            let provider = config_clone.active_provider.clone();
+            let model = api_client.model().to_string();
+            // Tool results go out right before the next request
+            let mut request_sent = Instant::now();
             let callback = move |event: StreamEvent| {
                 match event {
                     StreamEvent::Start { .. } => {
@@ -194,9 +210,13 @@ impl AgentClient {
                         tool_call_id,
                         result,
                     } => {
+                        request_sent = Instant::now();
                         let _ =
                             tx_for_callback.send(ContentBlock::tool_result(tool_call_id, result));
                     }
+                    StreamEvent::Finish {
+                        usage: Some(usage), ..
+                    } => record_usage(&provider, &model, &usage, request_sent.elapsed()),
                     StreamEvent::Error(e) => {
                         let _ = tx_for_callback.send(ContentBlock::error(e));
                     }
@@ -222,6 +242,13 @@ impl AgentClient {
                             options,
                         });
                     }
+                    StreamEvent::LoopDetected { diagnosis } => {
+                        let _ = tx_for_callback.send(ContentBlock::LoopDetected { diagnosis });
+                    }
+                    StreamEvent::ContextCompacted { replaced, summary } => {
+                        let _ = tx_for_callback
+                            .send(ContentBlock::ContextCompacted { replaced, summary });
+                    }
                     _ => {}
                 }
             };
@@ -234,19 +261,23 @@ impl AgentClient {
                 messages,
                 &tools,
                 &execution_registry,
+                &guard,
                 auto_execute_tools,
                 max_tool_iterations,
+                strict_arguments,
+                &steering,
                 callback,
             )
             .await;
 
             if let Err(e) = result {
-                let error_context =
-                    ErrorContext::new("Process streaming request").with_anyhow_error(&e);
-                let error_msg = stream_error(error_context);
+                let error_msg = match e.downcast_ref::<ProviderError>() {
+                    Some(provider_error) => provider_error.to_string(),
+                    None => stream_error(ErrorContext::new("Process streaming request").with_anyhow_error(&e)),
+                };
                 let _ = tx.send(ContentBlock::error(error_msg));
             }
-        });
+        }.instrument(span));
 
         Ok(Box::pin(UnboundedReceiverStream::new(rx)))
     }
@@ -277,16 +308,18 @@ impl AgentClient {
         let debug = self.options.debug;
         let config_clone = self.config.clone();
         let tx_clone = tx.clone();
+        let steering = self.steering.clone();
 
         // Get tools from registry
         let tools = self.tool_registry.get_openai_tools();
+        // Tools run from a copy with the MCP tools added
+        let mut execution_registry = self.tool_registry.detached();
 
         // Build messages
-        let messages = self.build_api_messages(message, conversation_history)?;
+        let span = self.request_span(false);
+        let messages = span.in_scope(|| self.build_api_messages(message, conversation_history))?;
 
         tokio::spawn(async move {
-            // Create tool registry for execution
-            let mut execution_registry = create_basic_tool_registry();
             if let Err(e) = initialize_mcp_tools(&mut execution_registry, &config_clone).await {
                 if debug {
                     debug_print(&format!("⚠️ Failed to initialize MCP tools: {}", e));
@@ -295,6 +328,7 @@ impl AgentClient {
 
             if let Err(e) = Self::handle_non_streaming(
                 api_client,
+                &config_clone.active_provider,
                 messages,
                 tools,
                 tx,
@@ -302,15 +336,17 @@ impl AgentClient {
                 max_tool_iterations,
                 debug,
                 &execution_registry,
+                &steering,
             )
             .await
             {
-                let error_context =
-                    ErrorContext::new("Complete non-streaming request").with_anyhow_error(&e);
-                let error_msg = api_error(error_context);
+                let error_msg = match e.downcast_ref::<ProviderError>() {
+                    Some(provider_error) => provider_error.to_string(),
+                    None => api_error(ErrorContext::new("Complete non-streaming request").with_anyhow_error(&e)),
+                };
                 let _ = tx_clone.send(ContentBlock::error(error_msg));
             }
-        });
+        }.instrument(span));
 
         Ok(Box::pin(UnboundedReceiverStream::new(rx)))
     }
@@ -318,6 +354,7 @@ impl AgentClient {
     /// Handle non-streaming API calls with tool execution loop
     async fn handle_non_streaming(
         api_client: ApiClient,
+        provider: &str,
         messages: Vec<ChatMessage>,
         tools: Vec<serde_json::Value>,
         tx: mpsc::UnboundedSender<ContentBlock>,
@@ -325,9 +362,11 @@ impl AgentClient {
         max_tool_iterations: u32,
         debug: bool,
         tool_registry: &crate::api::agent::ToolRegistry,
+        steering: &Steering,
     ) -> Result<()> {
         let mut current_messages = messages;
         let mut iterations = 0;
+        let mut compacted = false;
 
         loop {
             if iterations >= max_tool_iterations {
@@ -335,14 +374,45 @@ impl AgentClient {
                 break;
             }
 
+            // Messages the user sent since the last request redirect this one
+            steering.inject(&mut current_messages);
+
             if debug {
                 debug_print(&format!("Non-streaming iteration {}", iterations + 1));
             }
 
             // Make non-streaming API call using send_message_with_tools_sync
-            let response = api_client
+            let request_sent = Instant::now();
+            let response = match api_client
                 .send_message_with_tools_sync(&current_messages, &tools)
-                .await?;
+                .instrument(tracing::info_span!(
+                    "provider_call",
+                    iteration = iterations + 1,
+                    messages = current_messages.len()
+                ))
+                .await
+            {
+                Ok(response) => response,
+                // Summarize older messages and try once more if the first
+                // request didn't fit
+                Err(e) if iterations == 0 && !compacted => {
+                    compacted = true;
+                    let Some(compaction) =
+                        recover_overflow(&api_client, &e, &mut current_messages).await
+                    else {
+                        return Err(e);
+                    };
+                    let _ = tx.send(ContentBlock::ContextCompacted {
+                        replaced: compaction.replaced,
+                        summary: compaction.summary,
+                    });
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(ref usage) = response.usage {
+                record_usage(provider, api_client.model(), usage, request_sent.elapsed());
+            }
 
             // Send reasoning/thinking content if present
             if let Some(ref reasoning) = response.reasoning_content {
@@ -425,7 +495,7 @@ impl AgentClient {
                                 // Add tool result to messages
                                 current_messages.push(crate::api::api::ChatMessage {
                                     role: "tool".to_string(),
-                                    content: Some(result_content),
+                                    content: Some(tool_output_block(&tool_call.function.name, &result_content)),
                                     tool_calls: None,
                                     tool_call_id: Some(tool_call.id.clone()),
                                     tool_name: Some(tool_call.function.name.clone()),
@@ -522,7 +592,7 @@ impl AgentClient {
                             // Add tool result to messages
                             current_messages.push(crate::api::api::ChatMessage {
                                 role: "tool".to_string(),
-                                content: Some(result_content),
+                                content: Some(tool_output_block(&tool_call.function.name, &result_content)),
                                 tool_calls: None,
                                 tool_call_id: Some(tool_call.id.clone()),
                                 tool_name: Some(tool_call.function.name.clone()),
@@ -622,7 +692,7 @@ impl AgentClient {
                         // Add tool result to messages
                         current_messages.push(ChatMessage {
                             role: "tool".to_string(),
-                            content: Some(result_content),
+                            content: Some(tool_output_block(&tool_call.function.name, &result_content)),
                             tool_calls: None,
                             tool_call_id: Some(tool_call.id.clone()),
                             tool_name: Some(tool_call.function.name.clone()),
@@ -686,7 +756,22 @@ impl AgentClient {
         self.tool_registry.get_tools()
     }
 
+    /// Root span of one request; the steps below it are traced as children
+    fn request_span(&self, streaming: bool) -> tracing::Span {
+        tracing::info_span!(
+            "agent_request",
+            provider = %self.config.active_provider,
+            model = %self.api_client.model(),
+            streaming
+        )
+    }
+
     /// Build API messages from user message and conversation history
+    #[tracing::instrument(
+        name = "prompt_build",
+        skip_all,
+        fields(history = conversation_history.as_ref().map_or(0, Vec::len))
+    )]
     fn build_api_messages(
         &self,
         message: &str,
@@ -754,3 +839,19 @@ impl AgentClient {
         Ok(messages)
     }
 }
+
+/// Add a response's token usage to the local usage ledger
+fn record_usage(provider: &str, model: &str, usage: &Usage, latency: Duration) {
+    let record = UsageRecord {
+        timestamp: chrono::Utc::now(),
+        provider: provider.to_string(),
+        model: model.to_string(),
+        prompt_tokens: usage.prompt_tokens as u64,
+        completion_tokens: usage.completion_tokens as u64,
+        total_tokens: usage.total_tokens as u64,
+        latency_ms: Some(latency.as_millis() as u64),
+    };
+    if let Err(e) = Storage::with(|s| s.record_usage(&record)) {
+        debug_print(&format!("Failed to record usage: {}", e));
+    }
+}
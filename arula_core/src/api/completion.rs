@@ -0,0 +1,54 @@
+//! One-shot model completions
+//!
+//! Helpers for single request/response calls that don't need tools or
+//! conversation history, such as summaries, commit messages and
+//! descriptions generated on behalf of a command.
+
+use crate::api::agent::{AgentOptionsBuilder, ContentBlock, ToolRegistry};
+use crate::api::agent_client::AgentClient;
+use crate::utils::config::Config;
+use anyhow::{Result, bail};
+use futures::StreamExt;
+
+/// Default temperature for one-shot completions (lower = more focused)
+const COMPLETION_TEMPERATURE: f32 = 0.3;
+
+/// Send a single prompt to the active provider and return the full text reply
+///
+/// Uses non-streaming mode and an empty tool registry, so the model can only
+/// answer with text. Reasoning blocks are ignored.
+pub async fn complete(config: &Config, system_prompt: &str, prompt: &str) -> Result<String> {
+    let options = AgentOptionsBuilder::new()
+        .system_prompt(system_prompt)
+        .model(&config.get_model())
+        .temperature(COMPLETION_TEMPERATURE)
+        .auto_execute_tools(false)
+        .streaming(false)
+        .build();
+
+    let client = AgentClient::new_with_registry(
+        config.active_provider.clone(),
+        config.get_api_url(),
+        config.get_api_key(),
+        config.get_model(),
+        options,
+        config,
+        ToolRegistry::new(),
+    );
+
+    let mut stream = client.query(prompt, None).await?;
+    let mut response = String::new();
+    while let Some(block) = stream.next().await {
+        match block {
+            ContentBlock::Text { text } => response.push_str(&text),
+            ContentBlock::Error { error } => bail!(error),
+            _ => {}
+        }
+    }
+
+    let response = response.trim().to_string();
+    if response.is_empty() {
+        bail!("The model returned an empty response");
+    }
+    Ok(response)
+}
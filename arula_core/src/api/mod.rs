@@ -1,25 +1,41 @@
 //! API-related modules for ARULA CLI
 //!
-//! Contains AI client implementations, agent framework, and API communication logic.
+//! Contains the agent framework and the tool loop on top of the provider
+//! layer in `arula_llm`, whose modules are re-exported here.
 //!
 //! # Module Structure
 //!
-//! - `api` - Core API client for AI providers
+//! - `api` - Core API client for AI providers (`arula_llm::client`)
 //! - `agent` - Modern AI agent framework with type-safe tools
 //! - `agent_client` - High-level agent client
+//! - `capabilities` - Bundled model capability and pricing table
+//! - `completion` - One-shot text completions without tools
+//! - `json_repair` - Tolerant parsing of streamed tool-call arguments
 //! - `models` - Unified model caching system
+//! - `provider_error` - Logged provider error responses behind `/debug last-error`
 //! - `http_client` - Optimized HTTP client with connection pooling
+//! - `steering` - Messages that redirect a run without cancelling it
 //! - `stream` - Unified streaming logic with consolidated tool support
+//! - `tool_budget` - Result size budgets for tool calls from the remaining context
+//! - `trust` - Tagged blocks that mark tool output as data in the context
+//! - `watchdog` - Pauses runs that look stuck in a loop
 
 pub mod agent;
 pub mod agent_client;
-pub mod api;
-pub mod http_client;
+pub mod completion;
 pub mod models;
+pub mod steering;
 pub mod stream;
-pub mod xml_toolcall;
+pub mod tool_budget;
+pub mod trust;
+pub mod watchdog;
+
+// The provider layer lives in the `arula_llm` crate
+pub use arula_llm::client as api;
+pub use arula_llm::{capabilities, http_client, json_repair, provider_error, xml_toolcall};
 
 // Note: Types are available via their modules:
+// - capabilities::{lookup, ModelCapabilities, Feature, missing_features}
 // - models::{ModelCacheManager, ModelFetcher, CachedModels}
 // - http_client::{get_ai_client, get_general_client, create_streaming_client}
 // - stream::{StreamEvent, stream_with_tools}
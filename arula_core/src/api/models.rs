@@ -8,6 +8,7 @@
 //! - Uses a single `ModelCacheManager` with trait-based polymorphism
 //! - Caches are time-limited with configurable TTL
 //! - Background fetching support for responsive UI
+//! - Optionally persisted to the local database so lists survive restarts
 //! - Thread-safe using `std::sync::Mutex` (not async mutex, per Tokio best practices)
 //!
 //! # Usage
@@ -21,6 +22,7 @@
 //! let models = cache.get_or_fetch_blocking(&OpenAIFetcher, "api_key", None);
 //! ```
 
+use crate::storage::Storage;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde_json::Value;
@@ -28,6 +30,9 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
+/// Storage cache namespace for model lists, keyed by provider
+pub const MODELS_CACHE: &str = "models";
+
 /// Cached model list with expiration tracking
 #[derive(Clone, Debug)]
 pub struct CachedModels {
@@ -95,6 +100,8 @@ pub struct ModelCacheManager {
     default_ttl: Duration,
     /// HTTP client for fetching models
     client: Client,
+    /// Also keep caches in the local database
+    persist: bool,
 }
 
 impl ModelCacheManager {
@@ -104,6 +111,16 @@ impl ModelCacheManager {
             caches: Mutex::new(HashMap::new()),
             default_ttl: Duration::from_secs(ttl_minutes * 60),
             client: Self::create_client(),
+            persist: false,
+        }
+    }
+
+    /// Create a cache manager that also stores model lists in the local
+    /// database, so a fresh list fetched by an earlier run is reused
+    pub fn persistent(ttl_minutes: u64) -> Self {
+        Self {
+            persist: true,
+            ..Self::new(ttl_minutes)
         }
     }
 
@@ -119,14 +136,20 @@ impl ModelCacheManager {
 
     /// Get cached models for a provider (if not expired)
     pub fn get_cached(&self, provider: &str) -> Option<Vec<String>> {
-        let caches = self.caches.lock().ok()?;
-        let cached = caches.get(provider)?;
-
-        if cached.is_expired() {
-            None
-        } else {
-            Some(cached.models().to_vec())
+        let mut caches = self.caches.lock().ok()?;
+        match caches.get(provider) {
+            Some(cached) if !cached.is_expired() => return Some(cached.models().to_vec()),
+            _ if !self.persist => return None,
+            _ => {}
         }
+
+        let models: Vec<String> =
+            Storage::with(|s| s.cache_get(MODELS_CACHE, provider)).ok().flatten()?;
+        caches.insert(
+            provider.to_string(),
+            CachedModels::new(models.clone(), self.default_ttl),
+        );
+        Some(models)
     }
 
     /// Check if a provider has valid cached models
@@ -136,16 +159,14 @@ impl ModelCacheManager {
 
     /// Cache models for a provider
     pub fn cache(&self, provider: &str, models: Vec<String>) {
-        if let Ok(mut caches) = self.caches.lock() {
-            caches.insert(
-                provider.to_string(),
-                CachedModels::new(models, self.default_ttl),
-            );
-        }
+        self.cache_with_ttl(provider, models, self.default_ttl);
     }
 
     /// Cache models with custom TTL
     pub fn cache_with_ttl(&self, provider: &str, models: Vec<String>, ttl: Duration) {
+        if self.persist && !models.is_empty() {
+            let _ = Storage::with(|s| s.cache_put(MODELS_CACHE, provider, &models, Some(ttl)));
+        }
         if let Ok(mut caches) = self.caches.lock() {
             caches.insert(provider.to_string(), CachedModels::new(models, ttl));
         }
@@ -153,6 +174,9 @@ impl ModelCacheManager {
 
     /// Invalidate cache for a provider
     pub fn invalidate(&self, provider: &str) {
+        if self.persist {
+            let _ = Storage::with(|s| s.cache_remove(MODELS_CACHE, provider));
+        }
         if let Ok(mut caches) = self.caches.lock() {
             caches.remove(provider);
         }
@@ -160,6 +184,9 @@ impl ModelCacheManager {
 
     /// Invalidate all caches
     pub fn invalidate_all(&self) {
+        if self.persist {
+            let _ = Storage::with(|s| s.cache_clear(MODELS_CACHE));
+        }
         if let Ok(mut caches) = self.caches.lock() {
             caches.clear();
         }
@@ -652,6 +679,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[ignore = "calls the live Z.AI API, which needs a valid key"]
     async fn test_zai_fetcher_returns_models() {
         let fetcher = ZaiFetcher;
         let models = fetcher.fetch_models("", None).await;
@@ -0,0 +1,100 @@
+//! Steering messages sent while the agent is working
+//!
+//! A [`Steering`] handle is shared between a frontend and a running agent
+//! loop. Messages sent through it ("skip the docs, focus on the failing
+//! test") are added to the conversation before the loop's next model
+//! request, so the run changes course without being cancelled.
+
+use crate::api::api::ChatMessage;
+use std::sync::{Arc, Mutex};
+
+/// Note the model sees above steering messages
+const STEERING_NOTE: &str = "[The user sent this while you were working. \
+Follow it for the rest of the task; don't redo steps that are already done.]";
+
+/// Messages waiting for the next iteration of a run; clones share them
+#[derive(Debug, Clone, Default)]
+pub struct Steering(Arc<Mutex<Vec<String>>>);
+
+impl Steering {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a message for the next iteration; blank messages are ignored
+    pub fn send(&self, message: impl Into<String>) {
+        let message = message.into();
+        if message.trim().is_empty() {
+            return;
+        }
+        if let Ok(mut pending) = self.0.lock() {
+            pending.push(message.trim().to_string());
+        }
+    }
+
+    /// Whether `other` is a clone of this handle
+    pub fn is_same(&self, other: &Steering) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Whether messages are waiting
+    pub fn is_pending(&self) -> bool {
+        self.0.lock().is_ok_and(|pending| !pending.is_empty())
+    }
+
+    /// Take every waiting message, oldest first
+    pub fn take(&self) -> Vec<String> {
+        self.0
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or_default()
+    }
+
+    /// Move waiting messages into the conversation as one user message,
+    /// returning them
+    pub fn inject(&self, messages: &mut Vec<ChatMessage>) -> Vec<String> {
+        let steering = self.take();
+        if !steering.is_empty() {
+            messages.push(ChatMessage {
+                role: "user".to_string(),
+                content: Some(format!("{}\n\n{}", STEERING_NOTE, steering.join("\n\n"))),
+                tool_calls: None,
+                tool_call_id: None,
+                tool_name: None,
+            });
+        }
+        steering
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_drains_shared_queue() {
+        let steering = Steering::new();
+        let handle = steering.clone();
+        handle.send("skip the docs");
+        handle.send("   ");
+        handle.send(" focus on the failing test ");
+        assert!(steering.is_pending());
+
+        let mut messages = Vec::new();
+        let sent = steering.inject(&mut messages);
+        assert_eq!(sent, vec!["skip the docs", "focus on the failing test"]);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].role, "user");
+        assert!(
+            messages[0]
+                .content
+                .as_deref()
+                .unwrap()
+                .ends_with("skip the docs\n\nfocus on the failing test")
+        );
+
+        assert!(!handle.is_pending());
+        assert!(steering.inject(&mut messages).is_empty());
+        assert_eq!(messages.len(), 1);
+    }
+}
@@ -1,82 +1,38 @@
 //! Unified streaming implementation for AI API responses
 //!
-//! This module provides a consolidated approach to handling streaming responses
-//! from various AI providers, with built-in support for:
-//! - Server-Sent Events (SSE) and NDJSON streams
+//! This module runs the tool loop on top of [`Provider::stream`], which
+//! builds each provider's request and parses its reply in `arula_llm`:
 //! - Automatic tool execution loops
-//! - Provider-specific request formatting (Z.AI, OpenAI, Ollama)
+//! - Tool results, bash output and questions as [`StreamEvent`]s next to
+//!   the provider's own events
 
 use crate::api::agent::ToolResult;
-use crate::api::api::{
-    AIProvider, ApiClient, ApiResponse, ChatMessage, ToolCall, ToolCallFunction, Usage,
-};
-use crate::api::xml_toolcall::extract_tool_call_from_xml;
+use crate::api::api::{ApiResponse, ChatMessage, ToolCall, Usage};
+use crate::api::json_repair::{parse_arguments, reemit_request};
+use crate::api::steering::Steering;
+use crate::api::tool_budget::{ContextManager, ResultBudget};
+use crate::api::trust::tool_output_block;
+use crate::api::watchdog::Watchdog;
 // Bash streaming is accessed via full path: crate::tools::builtin::bash::execute_bash_streaming_channel
-use crate::utils::error_utils::{stream_error, ErrorContext};
+use crate::tools::injection_guard::{
+    self, blocked_message, classifier_input, parse_classifier_reply, wrap_flagged,
+    InjectionGuard, Verdict, CLASSIFIER_PROMPT,
+};
+use crate::utils::compaction::recover_overflow;
+use crate::utils::retry::{Attempt, RetryPolicy, record_attempt};
 use anyhow::{anyhow, Result};
-use futures::StreamExt;
-use reqwest::Response;
-use serde::Deserialize;
+use arula_llm::{Provider, ProviderEvent};
+use futures::future::join_all;
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tracing::Instrument;
 // std::sync no longer needed - using channels for callback
 
 // ============================================================================
 //  Types
 // ============================================================================
 
-/// Represents a streaming chunk from OpenAI-compatible APIs
-#[derive(Debug, Clone, Deserialize)]
-pub struct StreamChunk {
-    pub id: Option<String>,
-    pub object: Option<String>,
-    pub created: Option<u64>,
-    pub model: Option<String>,
-    pub choices: Vec<StreamChoice>,
-    #[serde(default)]
-    pub usage: Option<StreamUsage>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct StreamChoice {
-    pub index: usize,
-    pub delta: StreamDelta,
-    pub finish_reason: Option<String>,
-}
-
-#[derive(Debug, Clone, Default, Deserialize)]
-pub struct StreamDelta {
-    pub role: Option<String>,
-    pub content: Option<String>,
-    #[serde(default)]
-    pub tool_calls: Option<Vec<StreamToolCallDelta>>,
-    /// OpenAI o1/o3 reasoning content
-    pub reasoning_content: Option<String>,
-    /// Ollama thinking content
-    pub thinking: Option<String>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct StreamToolCallDelta {
-    pub index: usize,
-    pub id: Option<String>,
-    pub r#type: Option<String>,
-    pub function: Option<StreamFunctionDelta>,
-}
-
-#[derive(Debug, Clone, Default, Deserialize)]
-pub struct StreamFunctionDelta {
-    pub name: Option<String>,
-    pub arguments: Option<String>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-pub struct StreamUsage {
-    pub prompt_tokens: u32,
-    pub completion_tokens: u32,
-    pub total_tokens: u32,
-}
-
 /// Events emitted during streaming
 #[derive(Debug, Clone)]
 pub enum StreamEvent {
@@ -117,6 +73,11 @@ pub enum StreamEvent {
         question: String,
         options: Option<Vec<String>>,
     },
+    /// The watchdog paused a run that looks stuck in a loop
+    LoopDetected { diagnosis: String },
+    /// Older messages were summarized after a context overflow and the
+    /// request is being sent again
+    ContextCompacted { replaced: usize, summary: String },
     /// Stream finished
     Finish {
         reason: String,
@@ -126,765 +87,325 @@ pub enum StreamEvent {
     Error(String),
 }
 
-/// Accumulator for tool call deltas
-#[derive(Debug, Default)]
-struct ToolCallAccumulator {
-    id: String,
-    name: String,
-    arguments: String,
-}
-
-impl ToolCallAccumulator {
-    fn to_tool_call(&self) -> ToolCall {
-        ToolCall {
-            id: self.id.clone(),
-            r#type: "function".to_string(),
-            function: ToolCallFunction {
-                name: self.name.clone(),
-                arguments: self.arguments.clone(),
-            },
+impl From<ProviderEvent> for StreamEvent {
+    fn from(event: ProviderEvent) -> Self {
+        match event {
+            ProviderEvent::Start { id, model } => StreamEvent::Start { id, model },
+            ProviderEvent::TextDelta(text) => StreamEvent::TextDelta(text),
+            ProviderEvent::ThinkingDelta(text) => StreamEvent::ThinkingDelta(text),
+            ProviderEvent::ToolCallStart { index, id, name } => {
+                StreamEvent::ToolCallStart { index, id, name }
+            }
+            ProviderEvent::ToolCallDelta { index, arguments } => {
+                StreamEvent::ToolCallDelta { index, arguments }
+            }
+            ProviderEvent::ToolCallComplete(call) => StreamEvent::ToolCallComplete(call),
+            ProviderEvent::Finish { reason, usage } => StreamEvent::Finish { reason, usage },
+            ProviderEvent::Error(message) => StreamEvent::Error(message),
         }
     }
 }
 
 // ============================================================================
-//  Request Building
+//  Tool Execution
 // ============================================================================
 
-/// Check if the endpoint URL is an Anthropic-compatible z.ai endpoint
-pub fn is_anthropic_compatible_endpoint(endpoint: &str) -> bool {
-    endpoint.contains("/api/anthropic")
-}
-
-/// Convert OpenAI-format tools to Anthropic format
-fn convert_tools_to_anthropic(tools: &[Value]) -> Vec<Value> {
-    tools
-        .iter()
-        .filter_map(|tool| {
-            // OpenAI format: { "type": "function", "function": { "name", "description", "parameters" } }
-            // Anthropic format: { "name", "description", "input_schema" }
-            let func = tool.get("function")?;
-            let name = func.get("name")?.as_str()?;
-            let description = func.get("description")?.as_str().unwrap_or("");
-            let parameters = func
-                .get("parameters")
-                .cloned()
-                .unwrap_or(json!({"type": "object", "properties": {}}));
-
-            Some(json!({
-                "name": name,
-                "description": description,
-                "input_schema": parameters
-            }))
-        })
-        .collect()
-}
+/// Most read-only tool calls run at the same time
+const MAX_PARALLEL_TOOLS: usize = 4;
 
-/// Build an Anthropic Messages API compatible request body
-pub fn build_anthropic_request(
-    model: &str,
-    messages: &[ChatMessage],
-    tools: Option<&[Value]>,
-    max_tokens: u32,
-) -> Value {
-    // Check if thinking is enabled
-    let thinking_enabled = if let Ok(config) = crate::utils::config::Config::load_or_default() {
-        config.get_thinking_enabled().unwrap_or(false)
+/// Run a tool through the registry: its result and the content for history
+///
+/// Tools the project marks retryable are run again after a failure.
+async fn execute_tool(
+    tool_registry: &crate::api::agent::ToolRegistry,
+    retry: &RetryPolicy,
+    call: &ToolCall,
+    args: Value,
+) -> (Option<ToolResult>, String) {
+    let name = &call.function.name;
+    let max_attempts = if retry.tool_retryable(name) {
+        retry.max_attempts()
     } else {
-        false
+        1
     };
-
-    // Debug output
-    if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-        eprintln!("🧠 DEBUG build_anthropic_request: thinking_enabled = {}, model = {}", thinking_enabled, model);
-    }
-    // Extract system message (first message with role "system")
-    let system_content: Option<String> = messages
-        .iter()
-        .find(|m| m.role == "system")
-        .and_then(|m| m.content.clone());
-
-    // Check if this is Z.AI's Anthropic-compatible endpoint which expects prompt format
-    let is_zai = model.to_lowercase().contains("z.ai")
-        || model.to_lowercase().contains("zai")
-        || std::env::var("ARULA_ZAI_ANTHROPIC").unwrap_or_default() == "1";
-
-    // Z.AI expects legacy Claude format with "prompt" parameter
-    if is_zai {
-        let mut prompt_parts = Vec::new();
-
-        // Add system prompt if present
-        if let Some(system_msg) = messages.iter().find(|m| m.role == "system") {
-            if let Some(content) = &system_msg.content {
-                prompt_parts.push(format!("<admin>\n{}\n</admin>", content));
-            }
-        }
-
-        // Convert messages to prompt format
-        for msg in messages.iter().filter(|m| m.role != "system") {
-            match msg.role.as_str() {
-                "user" => {
-                    if let Some(content) = &msg.content {
-                        prompt_parts.push(format!("\n\nHuman: {}", content));
-                    }
-                }
-                "assistant" => {
-                    if let Some(content) = &msg.content {
-                        prompt_parts.push(format!("\n\nAssistant: {}", content));
-                    }
-                }
-                _ => {}
+    let mut attempt = 1;
+    let result = loop {
+        let started = Instant::now();
+        let result = tool_registry.execute_tool(name, args.clone()).await;
+        if max_attempts > 1 {
+            let success = result.as_ref().is_some_and(|res| res.success);
+            record_attempt(&Attempt {
+                tool: name,
+                target: &call.function.arguments,
+                attempt,
+                max_attempts,
+                success,
+                exit_code: None,
+                duration: started.elapsed(),
+                error: result.as_ref().and_then(|res| res.error.clone()),
+            });
+            // A missing tool won't appear on a second try
+            if result.is_some() && !success && attempt < max_attempts {
+                tokio::time::sleep(retry.delay(attempt)).await;
+                attempt += 1;
+                continue;
             }
         }
+        break result;
+    };
+    let content = match &result {
+        Some(res) if res.success => res.data.to_string(),
+        Some(res) => format!("Error: {}", res.error.clone().unwrap_or_default()),
+        None => format!("Tool not found: {}", call.function.name),
+    };
+    (result, content)
+}
 
-        // End the prompt properly
-        prompt_parts.push("\n\nAssistant:".to_string());
-        let prompt = prompt_parts.concat();
-
-        let mut request = json!({
-            "model": model,
-            "prompt": prompt,
-            "max_tokens": max_tokens,
-            "stream": true
-        });
-
-        // Add temperature
-        request["temperature"] = json!(0.7);
+/// Run `execute_bash` with its output streamed line by line
+///
+/// Commands the project marks retryable are run again after a non-zero exit
+/// or an error, with the attempt count added to the result.
+async fn run_bash<F>(
+    call: &ToolCall,
+    args: &Value,
+    retry: &RetryPolicy,
+    callback: &mut F,
+) -> Result<(Option<ToolResult>, String)>
+where
+    F: FnMut(StreamEvent) + Send,
+{
+    let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
+    let timeout = args.get("timeout_seconds").and_then(|v| v.as_u64());
+    let max_attempts = if retry.command_retryable(command) {
+        retry.max_attempts()
+    } else {
+        1
+    };
 
-        // Add thinking mode if enabled (for Z.AI Anthropic-compatible endpoint)
-        if thinking_enabled {
-            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                eprintln!("🧠 DEBUG: Adding thinking block to Z.AI build_anthropic_request");
-            }
-            request["thinking"] = json!({"type": "enabled"});
-        } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-            eprintln!("🧠 DEBUG: NOT adding thinking block to Z.AI build_anthropic_request - thinking_enabled is false");
+    let mut attempt = 1;
+    loop {
+        let started = Instant::now();
+        // Use the channel-based streaming API
+        let (mut rx, handle) = crate::tools::builtin::bash::execute_bash_streaming_channel(
+            command.to_string(),
+            timeout,
+        );
+
+        // Process streaming output as it arrives
+        while let Some((line, is_stderr)) = rx.recv().await {
+            callback(StreamEvent::BashOutputLine {
+                tool_call_id: call.id.clone(),
+                line,
+                is_stderr,
+            });
         }
 
-        // Add tools if provided (Z.AI supports tools)
-        if let Some(tools) = tools {
-            if !tools.is_empty() {
-                request["tools"] = json!(convert_tools_to_anthropic(tools));
-            }
+        // Wait for bash execution to complete
+        let streaming_result = handle
+            .await
+            .map_err(|e| anyhow!("Task join error: {}", e))?;
+
+        if max_attempts > 1 {
+            record_attempt(&Attempt {
+                tool: &call.function.name,
+                target: command,
+                attempt,
+                max_attempts,
+                success: streaming_result.as_ref().is_ok_and(|res| res.success),
+                exit_code: streaming_result.as_ref().ok().map(|res| res.exit_code),
+                duration: started.elapsed(),
+                error: streaming_result.as_ref().err().cloned(),
+            });
+        }
+        let failed = !streaming_result.as_ref().is_ok_and(|res| res.success);
+        if failed && attempt < max_attempts {
+            let delay = retry.delay(attempt);
+            callback(StreamEvent::BashOutputLine {
+                tool_call_id: call.id.clone(),
+                line: format!(
+                    "↻ Attempt {} of {} failed, retrying in {:.1}s",
+                    attempt,
+                    max_attempts,
+                    delay.as_secs_f64()
+                ),
+                is_stderr: true,
+            });
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            continue;
         }
 
-        return request;
-    }
-
-    // Build messages array, excluding system messages and converting tool messages
-    let anthropic_messages: Vec<Value> = messages
-        .iter()
-        .filter(|msg| msg.role != "system") // System goes in separate param
-        .filter_map(|msg| {
-            match msg.role.as_str() {
-                "user" => Some(json!({
-                    "role": "user",
-                    "content": msg.content.clone().unwrap_or_default()
-                })),
-                "assistant" => {
-                    let mut content_blocks: Vec<Value> = Vec::new();
-
-                    // Add text content if present
-                    if let Some(text) = &msg.content {
-                        if !text.is_empty() {
-                            content_blocks.push(json!({
-                                "type": "text",
-                                "text": text
-                            }));
-                        }
-                    }
-
-                    // Add tool_use blocks if present
-                    if let Some(tool_calls) = &msg.tool_calls {
-                        for tc in tool_calls {
-                            let input: Value =
-                                serde_json::from_str(&tc.function.arguments).unwrap_or(json!({}));
-                            content_blocks.push(json!({
-                                "type": "tool_use",
-                                "id": tc.id,
-                                "name": tc.function.name,
-                                "input": input
-                            }));
-                        }
-                    }
-
-                    if content_blocks.is_empty() {
-                        None
-                    } else {
-                        Some(json!({
-                            "role": "assistant",
-                            "content": content_blocks
-                        }))
-                    }
-                }
-                "tool" => {
-                    // Convert tool results to Anthropic format
-                    Some(json!({
-                        "role": "user",
-                        "content": [{
-                            "type": "tool_result",
-                            "tool_use_id": msg.tool_call_id.clone().unwrap_or_default(),
-                            "content": msg.content.clone().unwrap_or_default()
-                        }]
-                    }))
+        return Ok(match streaming_result {
+            Ok(bash_result) => {
+                // Exit code, run time and both streams go to the model as
+                // separate fields, failed or not
+                let mut result_data = serde_json::to_value(&bash_result)?;
+                if attempt > 1 {
+                    result_data["attempts"] = json!(attempt);
                 }
-                _ => None,
+                let content = result_data.to_string();
+                let mut tool_result = ToolResult::success(result_data);
+                tool_result.success = bash_result.success;
+                (Some(tool_result), content)
             }
-        })
-        .collect();
-
-    let mut request = json!({
-        "model": model,
-        "max_tokens": max_tokens,
-        "messages": anthropic_messages,
-        "stream": true
-    });
-
-    // Add system prompt if present
-    if let Some(system) = system_content {
-        request["system"] = json!(system);
-    }
-
-    // Add thinking mode if enabled (for Anthropic or other compatible endpoints)
-    if thinking_enabled {
-        if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-            eprintln!("🧠 DEBUG: Adding thinking block to non-Z.AI build_anthropic_request");
-        }
-        request["thinking"] = json!({"type": "enabled"});
-    } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-        eprintln!("🧠 DEBUG: NOT adding thinking block to non-Z.AI build_anthropic_request - thinking_enabled is false");
-    }
-
-    // Convert and add tools if present
-    if let Some(t) = tools {
-        if !t.is_empty() {
-            let anthropic_tools = convert_tools_to_anthropic(t);
-            if !anthropic_tools.is_empty() {
-                request["tools"] = json!(anthropic_tools);
+            Err(e) => {
+                let tool_result = ToolResult::error(e.clone());
+                (Some(tool_result), format!("Error: {}", e))
             }
-        }
+        });
     }
+}
 
-    request
+/// Run read-only tool calls concurrently, at most `MAX_PARALLEL_TOOLS` at a
+/// time; results come back in call order
+async fn execute_read_only(
+    tool_registry: &crate::api::agent::ToolRegistry,
+    retry: &RetryPolicy,
+    calls: &[ToolCall],
+    budget: ResultBudget,
+) -> Vec<(Option<ToolResult>, String)> {
+    let permits = Semaphore::new(MAX_PARALLEL_TOOLS);
+    join_all(calls.iter().map(|call| async {
+        let _permit = permits.acquire().await;
+        let started = Instant::now();
+        let args = budgeted_arguments(tool_registry, call, budget);
+        let (result, content) = execute_tool(tool_registry, retry, call, args).await;
+        (result.map(|res| res.with_duration(started.elapsed())), budget.fit(content))
+    }))
+    .await
 }
 
-/// Build a unified request body for streaming, handling provider specifics
-pub fn build_streaming_request(
-    provider: &AIProvider,
-    model: &str,
-    messages: &[ChatMessage],
-    tools: Option<&[Value]>,
-    temperature: f32,
-    max_tokens: u32,
+/// A call's arguments, with the result budget for tools that take one
+fn budgeted_arguments(
+    tool_registry: &crate::api::agent::ToolRegistry,
+    call: &ToolCall,
+    budget: ResultBudget,
 ) -> Value {
-    // Check if thinking is enabled
-    let thinking_enabled = if let Ok(config) = crate::utils::config::Config::load_or_default() {
-        config.get_thinking_enabled().unwrap_or(false)
-    } else {
-        false
-    };
-    let is_zai = matches!(provider, AIProvider::ZAiCoding);
-
-    // Debug output
-    if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-        eprintln!("🧠 DEBUG build_streaming_request: thinking_enabled = {}, provider = {:?}", thinking_enabled, provider);
+    let mut args = serde_json::from_str(&call.function.arguments).unwrap_or(json!({}));
+    if tool_registry.is_budgeted(&call.function.name) {
+        budget.apply(&mut args);
     }
-    let is_ollama = matches!(provider, AIProvider::Ollama);
+    args
+}
 
-    // 1. Process Messages
-    let json_messages: Vec<Value> = messages
+/// Parse every call's arguments, writing repaired JSON back so the history
+/// stays valid; calls with broken arguments get the message asking the
+/// model to send them again. Only read-only tools get their arguments
+/// repaired, and none do in strict mode.
+fn normalize_arguments(
+    calls: &[ToolCall],
+    tool_registry: &crate::api::agent::ToolRegistry,
+    strict: bool,
+) -> (Vec<ToolCall>, Vec<Option<String>>) {
+    calls
         .iter()
-        .filter_map(|msg| {
-            // Z.AI Bug Fix: We DO NOT filter out tool messages anymore!
-            // Z.AI supports tool calling and needs history to continue.
-
-            let mut obj = json!({
-                "role": msg.role,
-            });
-
-            if let Some(content) = &msg.content {
-                obj["content"] = json!(content);
-            } else if is_zai {
-                // Z.AI requires content, use empty string if none
-                obj["content"] = json!("");
-            } else if msg.tool_calls.is_some() {
-                obj["content"] = json!(null);
-            }
-
-            // Add tool-related fields
-            // Z.AI usage: Uses standard OpenAI format for tool_calls/tool_call_id
-            if let Some(tool_calls) = &msg.tool_calls {
-                if is_ollama {
-                    // Ollama specific tool format
-                    let converted: Vec<Value> = tool_calls
-                        .iter()
-                        .map(|tc| {
-                            let args = serde_json::from_str::<Value>(&tc.function.arguments)
-                                .unwrap_or_else(|_| json!({}));
-                            json!({
-                                "function": {
-                                    "name": tc.function.name,
-                                    "arguments": args
-                                }
-                            })
-                        })
-                        .collect();
-                    obj["tool_calls"] = json!(converted);
-                } else {
-                    // Standard OpenAI / Z.AI format
-                    obj["tool_calls"] = json!(tool_calls);
+        .map(|call| {
+            let mut call = call.clone();
+            let allow_repair = !strict && tool_registry.is_read_only(&call.function.name);
+            let rejected = match parse_arguments(&call.function.arguments, allow_repair) {
+                Ok(args) => {
+                    call.function.arguments = args.to_string();
+                    None
                 }
-            }
-
-            if let Some(tool_call_id) = &msg.tool_call_id {
-                if !is_ollama {
-                    obj["tool_call_id"] = json!(tool_call_id);
-                }
-            }
-
-            if is_ollama {
-                if let Some(tool_name) = &msg.tool_name {
-                    obj["tool_name"] = json!(tool_name);
+                Err(error) => {
+                    tracing::warn!(
+                        "Unreadable arguments for {}: {}",
+                        call.function.name,
+                        call.function.arguments
+                    );
+                    call.function.arguments = "{}".to_string();
+                    Some(reemit_request(&call.function.name, &error))
                 }
-            }
-
-            Some(obj)
+            };
+            (call, rejected)
         })
-        .collect();
-
-    // 2. Process Tools (Z.AI specific filtering)
-    let tools_to_send = if is_zai {
-        // Z.AI now supports tools in streaming mode
-        if let Some(t) = tools {
-            if !t.is_empty() {
-                Some(t.to_vec())
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    } else {
-        // Convert tools for other providers if needed
-        if let Some(t) = tools {
-            if !t.is_empty() {
-                Some(t.to_vec())
-            } else {
-                None
-            }
-        } else {
-            None
-        }
-    };
-
-    // 3. Construct Body
-    let mut request = json!({
-        "model": model,
-        "messages": json_messages,
-        "max_tokens": max_tokens,
-        "stream": true
-    });
-
-    // Add temperature separately to avoid type issues
-    if is_zai {
-        request["temperature"] = json!("0.7");
-        // Add thinking parameter for Z.AI if enabled
-        if thinking_enabled {
-            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                eprintln!("🧠 DEBUG: Adding thinking block to Z.AI streaming request");
-            }
-            request["thinking"] = json!({"type": "enabled"});
-        } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-            eprintln!("🧠 DEBUG: NOT adding thinking block to Z.AI streaming request - thinking_enabled is false");
-        }
-    } else {
-        request["temperature"] = json!(temperature);
-        // Add reasoning_effort for other providers if thinking is enabled
-        if thinking_enabled {
-            request["reasoning_effort"] = json!("medium");
-        }
-    }
-
-    // Option flags
-    let include_stream_options = !is_zai && !is_ollama;
-    let include_tool_choice = !is_zai && !is_ollama;
-
-    if include_stream_options {
-        request["stream_options"] = json!({ "include_usage": true });
-    }
-
-    if let Some(t) = tools_to_send {
-        request["tools"] = json!(t);
-        if include_tool_choice {
-            request["tool_choice"] = json!("auto");
-        }
-    }
-
-    // Ollama specific
-    if is_ollama {
-        if let Some(obj) = request.as_object_mut() {
-            let _ = obj.remove("max_tokens");
-            let _ = obj.remove("temperature");
-            obj.insert(
-                "options".to_string(),
-                json!({
-                    "num_predict": max_tokens,
-                    "temperature": temperature
-                }),
-            );
-        }
-    }
-
-    request
+        .unzip()
 }
 
-// ============================================================================
-//  Stream Processing
-// ============================================================================
-
-/// Process a raw HTTP response into a stream of events
-pub async fn process_response<F>(response: Response, callback: F) -> Result<ApiResponse>
-where
-    F: FnMut(StreamEvent),
-{
-    let content_type = response
-        .headers()
-        .get("content-type")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
-
-    if content_type.contains("text/event-stream") {
-        process_sse_stream(response, callback).await
-    } else {
-        process_ndjson_stream(response, callback).await
-    }
+/// Ask the model whether tool output tries to instruct it
+async fn classify_injection(provider: &dyn Provider, text: &str) -> Result<bool> {
+    let messages = [
+        ChatMessage {
+            role: "system".to_string(),
+            content: Some(CLASSIFIER_PROMPT.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: Some(classifier_input(text)),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        },
+    ];
+    let reply = provider.stream(&messages, &[], &mut |_| {}).await?;
+    Ok(parse_classifier_reply(&reply.response))
 }
 
-async fn process_sse_stream<F>(response: Response, mut callback: F) -> Result<ApiResponse>
-where
-    F: FnMut(StreamEvent),
-{
-    use eventsource_stream::Eventsource;
-
-    let mut stream = response.bytes_stream().eventsource();
-    let mut accumulated = String::new();
-    let mut tool_acc: HashMap<usize, ToolCallAccumulator> = HashMap::new();
-    let mut finish_reason = String::new();
-    let mut usage = None;
-    let mut model = String::new();
-    let mut stream_id = String::new();
-    let mut reasoning_buffer = String::new(); // For XML tool call extraction
-
-    while let Some(res) = stream.next().await {
-        match res {
-            Ok(event) => {
-                let data = event.data;
-                if data == "[DONE]" {
-                    break;
-                }
-
-                // Log streaming chunk if debug mode is enabled
-                if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                    crate::utils::debug::debug_print(&format!("Stream Chunk: {}", data));
-                }
-
-                if let Ok(chunk) = serde_json::from_str::<StreamChunk>(&data) {
-                    if let Some(id) = &chunk.id {
-                        if stream_id.is_empty() {
-                            stream_id = id.clone();
-                        }
-                    }
-                    if let Some(m) = &chunk.model {
-                        if model.is_empty() {
-                            model = m.clone();
-                            callback(StreamEvent::Start {
-                                id: stream_id.clone(),
-                                model: model.clone(),
-                            });
-                        }
-                    }
-                    if let Some(u) = chunk.usage {
-                        usage = Some(Usage {
-                            prompt_tokens: u.prompt_tokens,
-                            completion_tokens: u.completion_tokens,
-                            total_tokens: u.total_tokens,
-                        });
-                    }
-
-                    for choice in chunk.choices {
-                        if let Some(r) = choice.finish_reason {
-                            finish_reason = r;
-                        }
-                        let delta = choice.delta;
-
-                        if let Some(c) = delta.content {
-                            if !c.is_empty() {
-                                accumulated.push_str(&c);
-                                callback(StreamEvent::TextDelta(c));
-                            }
-                        }
-
-                        if let Some(think) = delta.reasoning_content.or(delta.thinking) {
-                            if !think.is_empty() {
-                                // Buffer reasoning content for XML tool call detection
-                                reasoning_buffer.push_str(&think);
-                                callback(StreamEvent::ThinkingDelta(think));
-                            }
-                        }
-
-                        if let Some(tcs) = delta.tool_calls {
-                            for tc in tcs {
-                                let idx = tc.index; // Use explicit index
-                                let acc = tool_acc.entry(idx).or_default();
-
-                                if let Some(id) = tc.id {
-                                    acc.id = id;
-                                }
-                                if let Some(func) = tc.function {
-                                    if let Some(n) = func.name {
-                                        acc.name = n.clone();
-                                        callback(StreamEvent::ToolCallStart {
-                                            index: idx,
-                                            id: acc.id.clone(),
-                                            name: n,
-                                        });
-                                    }
-                                    if let Some(a) = func.arguments {
-                                        acc.arguments.push_str(&a);
-                                        callback(StreamEvent::ToolCallDelta {
-                                            index: idx,
-                                            arguments: a,
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                let error_context =
-                    ErrorContext::new("Process SSE stream").with_underlying_error(&e);
-                let msg = stream_error(error_context);
-                callback(StreamEvent::Error(msg.clone()));
-                return Ok(ApiResponse {
-                    response: accumulated,
-                    success: false,
-                    error: Some(msg),
-                    ..Default::default()
-                });
-            }
-        }
+/// Screen a tool's output for prompt injection before it goes back to the
+/// model; withheld output also replaces the result shown to the user
+async fn screen_tool_output(
+    provider: &dyn Provider,
+    guard: &InjectionGuard,
+    call: &ToolCall,
+    result: Option<ToolResult>,
+    content: String,
+) -> (Option<ToolResult>, String) {
+    let tool = call.function.name.as_str();
+    if !guard.applies_to(tool) {
+        return (result, content);
     }
-
-    // Before finalizing, check if reasoning_buffer contains XML tool calls
-    // This handles GLM-4.6 style XML tool calls in reasoning content (Coding Plan endpoint only)
-    // Note: Anthropic-compatible endpoint uses structured tool_use blocks, not XML
-    // The XML check only triggers when tool_acc is empty (no structured tool calls found)
-    if !reasoning_buffer.is_empty() && tool_acc.is_empty() {
-        if let Some(xml_tool_call) = extract_tool_call_from_xml(&reasoning_buffer) {
-            // Convert JSON value to ToolCall
-            if let Ok(tool_call) = serde_json::from_value::<ToolCall>(xml_tool_call) {
-                // Add to tool_acc as if it came from standard tool_calls
-                let idx = 0;
-                tool_acc.insert(
-                    idx,
-                    ToolCallAccumulator {
-                        id: tool_call.id.clone(),
-                        name: tool_call.function.name.clone(),
-                        arguments: tool_call.function.arguments.clone(),
-                    },
-                );
-                // Emit the tool call event
-                callback(StreamEvent::ToolCallStart {
-                    index: idx,
-                    id: tool_call.id.clone(),
-                    name: tool_call.function.name.clone(),
-                });
-                callback(StreamEvent::ToolCallDelta {
-                    index: idx,
-                    arguments: tool_call.function.arguments.clone(),
-                });
-            }
+    let scan = injection_guard::scan(&content);
+    let model_flagged = guard.needs_model_check(&scan)
+        && classify_injection(provider, &content)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Injection check for {} failed: {}", tool, e);
+                false
+            });
+    match guard.verdict(&scan, model_flagged) {
+        Verdict::Clean => (result, content),
+        Verdict::Flagged(signals) => {
+            tracing::warn!("Possible prompt injection in {} output: {:?}", tool, signals);
+            (result, wrap_flagged(tool, &content, &signals))
         }
-    }
-
-    finalize(
-        accumulated,
-        tool_acc,
-        finish_reason,
-        usage,
-        model,
-        &mut callback,
-    )
-}
-
-async fn process_ndjson_stream<F>(response: Response, mut callback: F) -> Result<ApiResponse>
-where
-    F: FnMut(StreamEvent),
-{
-    let mut stream = response.bytes_stream();
-    let mut buffer = String::new();
-    let mut accumulated = String::new();
-    let mut tool_acc: HashMap<usize, ToolCallAccumulator> = HashMap::new();
-    let mut finish_reason = "stop".to_string();
-    let mut usage = None;
-    let mut model = String::new();
-
-    while let Some(item) = stream.next().await {
-        let bytes = item.map_err(|e| {
-            let error_context = ErrorContext::new("Read stream chunk").with_underlying_error(&e);
-            anyhow!("{}", stream_error(error_context))
-        })?;
-        if let Ok(s) = std::str::from_utf8(&bytes) {
-            buffer.push_str(s);
-        }
-
-        while let Some(pos) = buffer.find('\n') {
-            let line = buffer[..pos].trim().to_string();
-            buffer.drain(..pos + 1);
-            if line.is_empty() {
-                continue;
-            }
-
-            // Log streaming chunk if debug mode is enabled
-            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                crate::utils::debug::debug_print(&format!("Stream Chunk (NDJSON): {}", line));
-            }
-
-            if let Ok(json) = serde_json::from_str::<Value>(&line) {
-                // Ollama 'done' check
-                if json.get("done").and_then(|v| v.as_bool()) == Some(true) {
-                    if let Some(c) = json.get("eval_count").and_then(|v| v.as_u64()) {
-                        usage = Some(Usage {
-                            total_tokens: c as u32,
-                            ..Default::default()
-                        });
-                    }
-                    finish_reason = "stop".to_string();
-                }
-
-                // Content
-                let content = json
-                    .get("message")
-                    .and_then(|m| m.get("content"))
-                    .or_else(|| json.get("response"))
-                    .and_then(|v| v.as_str());
-
-                if let Some(c) = content {
-                    if !c.is_empty() {
-                        accumulated.push_str(c);
-                        callback(StreamEvent::TextDelta(c.to_string()));
-                    }
-                }
-
-                // Tools
-                if let Some(tcs) = json
-                    .get("message")
-                    .and_then(|m| m.get("tool_calls"))
-                    .and_then(|v| v.as_array())
-                {
-                    for (i, tc) in tcs.iter().enumerate() {
-                        if let Some(func) = tc.get("function") {
-                            let name = func
-                                .get("name")
-                                .and_then(|s| s.as_str())
-                                .unwrap_or_default()
-                                .to_string();
-                            let args = func
-                                .get("arguments")
-                                .map(|v| v.to_string())
-                                .unwrap_or_default();
-
-                            let acc = tool_acc.entry(i).or_default();
-                            acc.name = name.clone();
-                            acc.arguments = args.clone();
-                            acc.id = format!("call_{}", i);
-
-                            callback(StreamEvent::ToolCallStart {
-                                index: i,
-                                id: acc.id.clone(),
-                                name: acc.name.clone(),
-                            });
-                            callback(StreamEvent::ToolCallDelta {
-                                index: i,
-                                arguments: acc.arguments.clone(),
-                            });
-                            finish_reason = "tool_calls".to_string();
-                        }
-                    }
-                }
-
-                if model.is_empty() {
-                    if let Some(m) = json.get("model").and_then(|s| s.as_str()) {
-                        model = m.to_string();
-                        callback(StreamEvent::Start {
-                            id: "ndjson".into(),
-                            model: model.clone(),
-                        });
-                    }
-                }
-            }
+        Verdict::Blocked(signals) => {
+            tracing::warn!("Blocked {} output: {:?}", tool, signals);
+            let message = blocked_message(tool, &signals);
+            let duration = result.and_then(|res| res.duration_ms);
+            let mut blocked = ToolResult::error(message.clone());
+            blocked.duration_ms = duration;
+            (Some(blocked), message)
         }
     }
-
-    finalize(
-        accumulated,
-        tool_acc,
-        finish_reason,
-        usage,
-        model,
-        &mut callback,
-    )
 }
 
-fn finalize<F>(
+/// Report a tool result and add it to the history
+fn finish_tool_call<F>(
+    call: &ToolCall,
+    result: Option<ToolResult>,
     content: String,
-    acc: HashMap<usize, ToolCallAccumulator>,
-    reason: String,
-    usage: Option<Usage>,
-    model: String,
     callback: &mut F,
-) -> Result<ApiResponse>
-where
+    messages: &mut Vec<ChatMessage>,
+) where
     F: FnMut(StreamEvent),
 {
-    let tool_calls = if acc.is_empty() {
-        None
-    } else {
-        let mut calls: Vec<(usize, ToolCall)> = acc
-            .into_iter()
-            .map(|(i, a)| (i, a.to_tool_call()))
-            .collect();
-        calls.sort_by_key(|(i, _)| *i);
-        // Clean arguments (sometimes they are double-encoded)
-        let cleaned: Vec<ToolCall> = calls
-            .into_iter()
-            .map(|(_, tc)| {
-                // Basic check if args are a string containing json or just json
-                // For now assume standard behavior, maybe add cleanup later if needed
-                callback(StreamEvent::ToolCallComplete(tc.clone()));
-                tc
-            })
-            .collect();
-        Some(cleaned)
-    };
-
-    callback(StreamEvent::Finish {
-        reason: reason.clone(),
-        usage: usage.clone(),
+    let result = result.unwrap_or_else(|| {
+        ToolResult::error(format!("Tool not found: {}", call.function.name))
+    });
+    callback(StreamEvent::ToolResult {
+        tool_call_id: call.id.clone(),
+        result,
+    });
+    messages.push(ChatMessage {
+        role: "tool".to_string(),
+        content: Some(tool_output_block(&call.function.name, &content)),
+        tool_calls: None,
+        tool_call_id: Some(call.id.clone()),
+        tool_name: Some(call.function.name.clone()),
     });
-
-    Ok(ApiResponse {
-        response: content,
-        success: true,
-        error: None,
-        usage,
-        tool_calls,
-        model: Some(model),
-        created: None,
-        reasoning_content: None,
-    })
 }
 
 // ============================================================================
@@ -892,13 +413,17 @@ where
 // ============================================================================
 
 /// Execute a streaming conversation with automatic tool handling
+#[allow(clippy::too_many_arguments)]
 pub async fn stream_with_tools<F>(
-    client: &ApiClient,
+    provider: &dyn Provider,
     messages: Vec<ChatMessage>,
     tools: &[Value],
     tool_registry: &crate::api::agent::ToolRegistry,
+    guard: &InjectionGuard,
     auto_execute_tools: bool,
     max_tool_iterations: u32,
+    strict_arguments: bool,
+    steering: &Steering,
     mut callback: F,
 ) -> Result<ApiResponse>
 where
@@ -906,6 +431,10 @@ where
 {
     let mut current_messages = messages;
     let mut iterations = 0;
+    let mut compacted = false;
+    let retry = RetryPolicy::current();
+    let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let mut watchdog = Watchdog::start(root).await;
 
     loop {
         if iterations >= max_tool_iterations {
@@ -913,31 +442,52 @@ where
             break;
         }
 
-        // Build request - check if we're using Anthropic-compatible endpoint
-        let request_body = if is_anthropic_compatible_endpoint(&client.endpoint) {
-            // Use Anthropic Messages API format
-            build_anthropic_request(client.model(), &current_messages, Some(tools), 4096)
-        } else {
-            // Use standard OpenAI-compatible format (for Coding Plan endpoint)
-            build_streaming_request(
-                &client.provider,
-                client.model(),
-                &current_messages,
-                Some(tools),
-                0.7,
-                4096,
-            )
+        // Messages the user sent since the last request redirect this one
+        steering.inject(&mut current_messages);
+
+        // Send the request and stream the reply
+        let span = tracing::info_span!(
+            "provider_call",
+            iteration = iterations + 1,
+            messages = current_messages.len(),
+            chars = tracing::field::Empty,
+            tool_calls = tracing::field::Empty
+        );
+        let mut on_event = |event: ProviderEvent| callback(event.into());
+        let api_response = match provider
+            .stream(&current_messages, tools, &mut on_event)
+            .instrument(span.clone())
+            .await
+        {
+            Ok(api_response) => api_response,
+            // Summarize older messages and try once more if the first
+            // request didn't fit, before anything was streamed
+            Err(e) if iterations == 0 && !compacted => {
+                compacted = true;
+                let Some(compaction) = recover_overflow(provider, &e, &mut current_messages).await
+                else {
+                    return Err(e);
+                };
+                callback(StreamEvent::ContextCompacted {
+                    replaced: compaction.replaced,
+                    summary: compaction.summary,
+                });
+                continue;
+            }
+            Err(e) => return Err(e),
         };
-
-        // Send request
-        let response = client.make_streaming_request(request_body).await?;
-
-        // Process stream
-        let api_response = process_response(response, &mut callback).await?;
+        span.record("chars", api_response.response.len());
+        span.record(
+            "tool_calls",
+            api_response.tool_calls.as_ref().map_or(0, Vec::len),
+        );
 
         // Check for tools
         if let Some(calls) = &api_response.tool_calls {
             if !calls.is_empty() && auto_execute_tools {
+                let (calls, rejected) =
+                    normalize_arguments(calls, tool_registry, strict_arguments);
+
                 // Add assistant response with tool calls to history
                 current_messages.push(ChatMessage {
                     role: "assistant".to_string(),
@@ -951,59 +501,66 @@ where
                     tool_name: None,
                 });
 
-                // Execute tools
-                for call in calls {
-                    let args: Value =
-                        serde_json::from_str(&call.function.arguments).unwrap_or(json!({}));
+                // Share what's left of the context between this round's results
+                let budget = ContextManager::for_model(provider.model()).budget(
+                    &current_messages,
+                    tools,
+                    calls.len(),
+                );
 
-                    // Check if this is a bash command - use streaming execution
-                    let (result, content) = if call.function.name == "execute_bash" {
-                        // Extract command from args
-                        let command = args.get("command").and_then(|v| v.as_str()).unwrap_or("");
-                        let timeout = args.get("timeout_seconds").and_then(|v| v.as_u64());
-
-                        // Use the channel-based streaming API
-                        let call_id = call.id.clone();
-                        let (mut rx, handle) = crate::tools::builtin::bash::execute_bash_streaming_channel(
-                            command.to_string(),
-                            timeout,
+                // Execute tools: runs of read-only calls together, the rest one at a time
+                let mut index = 0;
+                while index < calls.len() {
+                    let batch_len = calls[index..]
+                        .iter()
+                        .zip(&rejected[index..])
+                        .take_while(|(call, rejected)| {
+                            rejected.is_none() && tool_registry.is_read_only(&call.function.name)
+                        })
+                        .count();
+                    if batch_len > 1 {
+                        let batch = &calls[index..index + batch_len];
+                        index += batch_len;
+                        for (call, (result, content)) in
+                            batch.iter().zip(
+                                execute_read_only(tool_registry, &retry, batch, budget).await,
+                            )
+                        {
+                            let (result, content) =
+                                screen_tool_output(provider, guard, call, result, content).await;
+                            watchdog.record_result(call, result.as_ref());
+                            finish_tool_call(
+                                call,
+                                result,
+                                content,
+                                &mut callback,
+                                &mut current_messages,
+                            );
+                        }
+                        continue;
+                    }
+                    let call = &calls[index];
+                    index += 1;
+                    if let Some(message) = &rejected[index - 1] {
+                        let result = ToolResult::error(message.clone());
+                        finish_tool_call(
+                            call,
+                            Some(result),
+                            message.clone(),
+                            &mut callback,
+                            &mut current_messages,
                         );
+                        continue;
+                    }
+                    let started = Instant::now();
 
-                        // Process streaming output as it arrives
-                        while let Some((line, is_stderr)) = rx.recv().await {
-                            callback(StreamEvent::BashOutputLine {
-                                tool_call_id: call_id.clone(),
-                                line,
-                                is_stderr,
-                            });
-                        }
+                    let args = budgeted_arguments(tool_registry, call, budget);
 
-                        // Wait for bash execution to complete
-                        let streaming_result = handle
-                            .await
-                            .map_err(|e| anyhow!("Task join error: {}", e))?;
-
-                        match streaming_result {
-                            Ok(bash_result) => {
-                                let result_data = json!({
-                                    "stdout": bash_result.stdout,
-                                    "stderr": bash_result.stderr,
-                                    "exit_code": bash_result.exit_code,
-                                    "success": bash_result.success,
-                                });
-                                let tool_result = ToolResult::success(result_data.clone());
-                                let content = if bash_result.success {
-                                    result_data.to_string()
-                                } else {
-                                    format!("Error: exit code {}", bash_result.exit_code)
-                                };
-                                (Some(tool_result), content)
-                            }
-                            Err(e) => {
-                                let tool_result = ToolResult::error(e.clone());
-                                (Some(tool_result), format!("Error: {}", e))
-                            }
-                        }
+                    // Check if this is a bash command - use streaming execution
+                    let (result, content) = if call.function.name == "execute_bash" {
+                        run_bash(call, &args, &retry, &mut callback)
+                            .instrument(tracing::info_span!("tool_execution", tool = "execute_bash"))
+                            .await?
                     } else if call.function.name == "ask_question" {
                         // Special handling for ask_question - pause execution and wait for user
                         let question = args.get("question")
@@ -1061,47 +618,31 @@ where
                         });
                     } else {
                         // Non-bash tools use standard execution
-                        let result = tool_registry.execute_tool(&call.function.name, args).await;
-                        let (content, _success) = match &result {
-                            Some(res) => (
-                                if res.success {
-                                    res.data.to_string()
-                                } else {
-                                    format!("Error: {}", res.error.clone().unwrap_or_default())
-                                },
-                                res.success,
-                            ),
-                            None => (format!("Tool not found: {}", call.function.name), false),
-                        };
-                        (result, content)
+                        execute_tool(tool_registry, &retry, call, args).await
                     };
 
-                    // Emit tool result event
-                    match &result {
-                        Some(res) => {
-                            callback(StreamEvent::ToolResult {
-                                tool_call_id: call.id.clone(),
-                                result: res.clone(),
-                            });
-                        }
-                        None => {
-                            callback(StreamEvent::ToolResult {
-                                tool_call_id: call.id.clone(),
-                                result: ToolResult::error(format!(
-                                    "Tool not found: {}",
-                                    call.function.name
-                                )),
-                            });
-                        }
-                    }
+                    let result = result.map(|res| res.with_duration(started.elapsed()));
+                    let content = budget.fit(content);
+                    let (result, content) =
+                        screen_tool_output(provider, guard, call, result, content).await;
+                    watchdog.record_result(call, result.as_ref());
+                    finish_tool_call(call, result, content, &mut callback, &mut current_messages);
+                }
 
-                    // Add tool result to history
-                    current_messages.push(ChatMessage {
-                        role: "tool".to_string(),
-                        content: Some(content),
-                        tool_calls: None,
-                        tool_call_id: Some(call.id.clone()),
-                        tool_name: Some(call.function.name.clone()),
+                // Pause a run that keeps doing the same thing without progress
+                let attempted_change = calls
+                    .iter()
+                    .any(|call| !tool_registry.is_read_only(&call.function.name));
+                if let Some(diagnosis) = watchdog.end_round(&calls, attempted_change).await {
+                    tracing::warn!("Loop detected: {}", diagnosis);
+                    callback(StreamEvent::LoopDetected { diagnosis });
+                    callback(StreamEvent::Finish {
+                        reason: "loop_detected".to_string(),
+                        usage: None,
+                    });
+                    return Ok(ApiResponse {
+                        success: true,
+                        ..Default::default()
                     });
                 }
 
@@ -1110,6 +651,20 @@ where
             }
         }
 
+        // Steering that arrived during the final answer gets a reply of its own
+        if auto_execute_tools && steering.is_pending() {
+            current_messages.push(ChatMessage {
+                role: "assistant".to_string(),
+                content: Some(api_response.response.clone()),
+                tool_calls: None,
+                tool_call_id: None,
+                tool_name: None,
+            });
+            callback(StreamEvent::TextDelta("\n\n".to_string()));
+            iterations += 1;
+            continue;
+        }
+
         // No tools or auto-execute disabled -> done
         return Ok(api_response);
     }
@@ -1121,3 +676,265 @@ where
         ..Default::default()
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::agent::{Tool, ToolRegistry, ToolSchema, ToolSchemaBuilder};
+    use crate::api::api::{AIProvider, ToolCallFunction};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::sync::Barrier;
+
+    struct SleepTool;
+
+    #[async_trait]
+    impl Tool for SleepTool {
+        type Params = Value;
+        type Result = Value;
+
+        fn name(&self) -> &str {
+            "sleep"
+        }
+
+        fn description(&self) -> &str {
+            "Sleep for `ms` milliseconds"
+        }
+
+        fn schema(&self) -> ToolSchema {
+            ToolSchemaBuilder::new("sleep", "Sleep").build()
+        }
+
+        fn read_only(&self) -> bool {
+            true
+        }
+
+        async fn execute(&self, params: Value) -> Result<Value, String> {
+            let ms = params["ms"].as_u64().unwrap_or(0);
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+            Ok(json!(ms))
+        }
+    }
+
+    /// Returns only once `parties` calls are running at the same time
+    struct MeetTool {
+        barrier: Arc<Barrier>,
+    }
+
+    #[async_trait]
+    impl Tool for MeetTool {
+        type Params = Value;
+        type Result = Value;
+
+        fn name(&self) -> &str {
+            "meet"
+        }
+
+        fn description(&self) -> &str {
+            "Wait for the other calls"
+        }
+
+        fn schema(&self) -> ToolSchema {
+            ToolSchemaBuilder::new("meet", "Meet").build()
+        }
+
+        fn read_only(&self) -> bool {
+            true
+        }
+
+        async fn execute(&self, _params: Value) -> Result<Value, String> {
+            // Run one after another, the first call would wait forever
+            tokio::time::timeout(Duration::from_secs(5), self.barrier.wait())
+                .await
+                .map_err(|_| "ran alone".to_string())?;
+            Ok(json!(true))
+        }
+    }
+
+    fn call(id: &str, ms: u64) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            r#type: "function".to_string(),
+            function: ToolCallFunction {
+                name: "sleep".to_string(),
+                arguments: json!({ "ms": ms }).to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_only_calls_run_concurrently() {
+        let mut registry = ToolRegistry::new();
+        registry.register(SleepTool);
+        registry.register(MeetTool {
+            barrier: Arc::new(Barrier::new(2)),
+        });
+        assert!(registry.is_read_only("sleep"));
+        assert!(!registry.is_read_only("missing"));
+
+        let mut meet = call("a", 0);
+        meet.function.name = "meet".to_string();
+        let calls = vec![meet.clone(), call("b", 20), meet];
+        let budget = ContextManager::new(100_000).budget(&[], &[], calls.len());
+        let results = execute_read_only(&registry, &RetryPolicy::default(), &calls, budget).await;
+
+        let contents: Vec<_> = results.iter().map(|(_, content)| content.as_str()).collect();
+        assert_eq!(contents, vec!["true", "20", "true"]);
+        let slept = results[1].0.as_ref().unwrap();
+        assert!(slept.duration_ms.unwrap() >= 20);
+    }
+
+    /// Replies with a queue of canned responses, recording what it was sent
+    struct Scripted {
+        replies: Mutex<Vec<ApiResponse>>,
+        requests: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    #[async_trait]
+    impl Provider for Scripted {
+        fn kind(&self) -> AIProvider {
+            AIProvider::Custom
+        }
+
+        fn model(&self) -> &str {
+            "scripted"
+        }
+
+        async fn complete(&self, _: Vec<ChatMessage>, _: Vec<Value>) -> Result<ApiResponse> {
+            unimplemented!()
+        }
+
+        async fn stream(
+            &self,
+            messages: &[ChatMessage],
+            _tools: &[Value],
+            on_event: &mut (dyn FnMut(ProviderEvent) + Send),
+        ) -> Result<ApiResponse> {
+            self.requests.lock().unwrap().push(messages.to_vec());
+            let reply = self.replies.lock().unwrap().remove(0);
+            if !reply.response.is_empty() {
+                on_event(ProviderEvent::TextDelta(reply.response.clone()));
+            }
+            for call in reply.tool_calls.iter().flatten() {
+                on_event(ProviderEvent::ToolCallComplete(call.clone()));
+            }
+            Ok(reply)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_loop_runs_through_provider() {
+        let provider = Scripted {
+            replies: Mutex::new(vec![
+                ApiResponse {
+                    tool_calls: Some(vec![call("a", 1)]),
+                    success: true,
+                    ..Default::default()
+                },
+                ApiResponse {
+                    response: "done".to_string(),
+                    success: true,
+                    ..Default::default()
+                },
+            ]),
+            requests: Mutex::new(Vec::new()),
+        };
+        let mut registry = ToolRegistry::new();
+        registry.register(SleepTool);
+
+        let mut events = Vec::new();
+        let reply = stream_with_tools(
+            &provider,
+            vec![ChatMessage::user("nap")],
+            &[],
+            &registry,
+            &InjectionGuard::default(),
+            true,
+            5,
+            false,
+            &Steering::new(),
+            |event| events.push(event),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(reply.response, "done");
+        let requests = provider.requests.into_inner().unwrap();
+        assert_eq!(requests.len(), 2);
+        let result = requests[1].last().unwrap();
+        assert_eq!(result.role, "tool");
+        assert_eq!(result.tool_call_id.as_deref(), Some("a"));
+        assert!(events.iter().any(|e| matches!(e, StreamEvent::ToolResult { .. })));
+        assert!(matches!(events.last(), Some(StreamEvent::TextDelta(text)) if text == "done"));
+    }
+
+    #[test]
+    fn test_only_read_only_calls_are_repaired() {
+        let mut registry = ToolRegistry::new();
+        registry.register(SleepTool);
+        let mut sleep = call("a", 0);
+        sleep.function.arguments = r#"{"ms": 5,}"#.to_string();
+        let mut write = sleep.clone();
+        write.function.name = "write_file".to_string();
+
+        let (calls, rejected) = normalize_arguments(&[sleep.clone(), write], &registry, false);
+        assert_eq!(calls[0].function.arguments, r#"{"ms":5}"#);
+        assert!(rejected[0].is_none());
+        assert!(rejected[1].as_deref().unwrap().contains("write_file"));
+
+        let (_, rejected) = normalize_arguments(&[sleep], &registry, true);
+        assert!(rejected[0].is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_truncated_bash_call_never_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ran");
+        // Completing the cut-off string would run `touch <dir>/ran`
+        let cut = format!(r#"{{"command": "touch {}"#, marker.display());
+        let provider = Scripted {
+            replies: Mutex::new(vec![
+                ApiResponse {
+                    tool_calls: Some(vec![ToolCall {
+                        id: "a".to_string(),
+                        r#type: "function".to_string(),
+                        function: ToolCallFunction {
+                            name: "execute_bash".to_string(),
+                            arguments: cut,
+                        },
+                    }]),
+                    success: true,
+                    ..Default::default()
+                },
+                ApiResponse {
+                    response: "done".to_string(),
+                    success: true,
+                    ..Default::default()
+                },
+            ]),
+            requests: Mutex::new(Vec::new()),
+        };
+
+        stream_with_tools(
+            &provider,
+            vec![ChatMessage::user("clean up")],
+            &[],
+            &ToolRegistry::new(),
+            &InjectionGuard::default(),
+            true,
+            5,
+            false,
+            &Steering::new(),
+            |_| {},
+        )
+        .await
+        .unwrap();
+
+        assert!(!marker.exists());
+        let requests = provider.requests.into_inner().unwrap();
+        let result = requests[1].last().unwrap();
+        assert_eq!(result.tool_call_id.as_deref(), Some("a"));
+        assert!(result.content.as_deref().unwrap().contains("execute_bash"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Result size budgets for tool calls
+//!
+//! Before a round of tool calls runs, the context manager estimates how much
+//! of the model's context window the conversation already takes and shares
+//! part of what is left between the calls. Each call gets its share as the
+//! internal `_budget` parameter: tools with result limits (`find_files`,
+//! `search_files`, ...) lower them to fit, and any output still over the
+//! budget is cut before it goes back to the model. With plenty of room the
+//! tools' own defaults apply unchanged.
+
+use crate::api::api::ChatMessage;
+use crate::api::capabilities;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Argument under which a tool call receives its budget
+pub const BUDGET_PARAM: &str = "_budget";
+
+/// Context window assumed for models missing from the capability table
+const DEFAULT_CONTEXT_WINDOW: usize = 32_768;
+
+/// Rough characters per token
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Share of the remaining context one round of tool calls may fill, in
+/// percent; the rest is left for the reply and later turns
+const ROUND_SHARE_PERCENT: usize = 40;
+
+/// Smallest output a call is allowed, however tight the context
+const MIN_CHARS: usize = 2_000;
+
+/// Largest output a call is allowed, however roomy the context
+const MAX_CHARS: usize = 200_000;
+
+/// Characters a single result (a file path, a match line) roughly takes
+const CHARS_PER_RESULT: usize = 160;
+
+/// Fewest results a limited tool is asked for
+const MIN_RESULTS: usize = 5;
+
+/// How much output one tool call may return
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultBudget {
+    /// Most characters of output
+    pub max_chars: usize,
+    /// Most entries for tools that return a list
+    pub max_results: usize,
+}
+
+impl ResultBudget {
+    fn from_chars(max_chars: usize) -> Self {
+        let max_chars = max_chars.clamp(MIN_CHARS, MAX_CHARS);
+        Self {
+            max_chars,
+            max_results: (max_chars / CHARS_PER_RESULT).max(MIN_RESULTS),
+        }
+    }
+
+    /// A tool's result limit: what the model asked for, or the tool's
+    /// default, lowered to fit the budget
+    pub fn limit(budget: Option<Self>, requested: Option<usize>, default: usize) -> usize {
+        let limit = requested.unwrap_or(default);
+        match budget {
+            Some(budget) => limit.min(budget.max_results).max(1),
+            None => limit,
+        }
+    }
+
+    /// Add the budget to a call's arguments, replacing one the model may
+    /// have made up
+    pub fn apply(&self, args: &mut Value) {
+        if let Some(object) = args.as_object_mut() {
+            object.insert(
+                BUDGET_PARAM.to_string(),
+                serde_json::to_value(self).unwrap_or(Value::Null),
+            );
+        }
+    }
+
+    /// `content` cut to the budget, with a note saying how much was left out
+    pub fn fit(&self, content: String) -> String {
+        let total = content.chars().count();
+        if total <= self.max_chars {
+            return content;
+        }
+        let mut kept: String = content.chars().take(self.max_chars).collect();
+        kept.push_str(&format!(
+            "\n[{} more characters cut to fit the remaining context; narrow the request to see them]",
+            total - self.max_chars
+        ));
+        kept
+    }
+}
+
+/// Tracks how full the context window is and hands out tool budgets
+#[derive(Debug, Clone, Copy)]
+pub struct ContextManager {
+    /// Context window of the model in tokens
+    context_window: usize,
+}
+
+impl ContextManager {
+    pub fn new(context_window: usize) -> Self {
+        Self { context_window }
+    }
+
+    /// A manager for the context window of `model`
+    pub fn for_model(model: &str) -> Self {
+        let window = capabilities::lookup(model)
+            .map_or(DEFAULT_CONTEXT_WINDOW, |caps| caps.context_window as usize);
+        Self::new(window)
+    }
+
+    /// Estimated tokens the messages and tool definitions take
+    pub fn used_tokens(messages: &[ChatMessage], tools: &[Value]) -> usize {
+        let message_chars: usize = messages
+            .iter()
+            .map(|m| {
+                let calls: usize = m
+                    .tool_calls
+                    .iter()
+                    .flatten()
+                    .map(|c| c.function.name.len() + c.function.arguments.len())
+                    .sum();
+                m.content.as_deref().map_or(0, str::len) + calls
+            })
+            .sum();
+        let tool_chars: usize = tools.iter().map(|t| t.to_string().len()).sum();
+        (message_chars + tool_chars) / CHARS_PER_TOKEN
+    }
+
+    /// Tokens left in the context window
+    pub fn remaining_tokens(&self, messages: &[ChatMessage], tools: &[Value]) -> usize {
+        self.context_window
+            .saturating_sub(Self::used_tokens(messages, tools))
+    }
+
+    /// Budget for each of `calls` tool calls about to run
+    pub fn budget(&self, messages: &[ChatMessage], tools: &[Value], calls: usize) -> ResultBudget {
+        let round_chars =
+            self.remaining_tokens(messages, tools) * CHARS_PER_TOKEN * ROUND_SHARE_PERCENT / 100;
+        ResultBudget::from_chars(round_chars / calls.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(content: &str) -> ChatMessage {
+        ChatMessage {
+            role: "user".to_string(),
+            content: Some(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_shrinks_as_context_fills() {
+        let manager = ContextManager::new(100_000);
+        let roomy = manager.budget(&[message("hi")], &[], 1);
+        assert_eq!(roomy.max_chars, 160_000);
+        assert_eq!(ResultBudget::limit(Some(roomy), None, 100), 100);
+
+        // 360k characters is ~90k of the 100k tokens
+        let full = vec![message(&"x".repeat(360_000))];
+        let tight = manager.budget(&full, &[], 1);
+        assert_eq!(tight.max_chars, 16_000);
+        assert_eq!(tight.max_results, 100);
+        let shared = manager.budget(&full, &[], 4);
+        assert_eq!(shared.max_results, 25);
+        assert_eq!(ResultBudget::limit(Some(shared), None, 100), 25);
+        assert_eq!(ResultBudget::limit(Some(shared), Some(10), 100), 10);
+        assert_eq!(ResultBudget::limit(None, None, 100), 100);
+
+        let overflowing = vec![message(&"x".repeat(500_000))];
+        assert_eq!(manager.budget(&overflowing, &[], 1).max_chars, MIN_CHARS);
+
+        let mut args = json!({"pattern": "*.rs", BUDGET_PARAM: {"max_chars": 1, "max_results": 1}});
+        shared.apply(&mut args);
+        assert_eq!(args[BUDGET_PARAM]["max_results"], 25);
+
+        let cut = ResultBudget::from_chars(0).fit("y".repeat(2_500));
+        assert!(cut.starts_with(&"y".repeat(MIN_CHARS)));
+        assert!(cut.ends_with(
+            "[500 more characters cut to fit the remaining context; narrow the request to see them]"
+        ));
+    }
+}
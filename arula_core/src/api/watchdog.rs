@@ -0,0 +1,269 @@
+//! Watchdog for runaway agent loops
+//!
+//! A run is paused when it looks stuck: the same tool is called with the
+//! same arguments `REPEAT_LIMIT` times while nothing in the project changes,
+//! or `STALL_LIMIT` rounds in a row try to change something (edits, commands)
+//! without the project files or the test results moving. The diagnosis goes
+//! to the user, who can stop, continue, or send new instructions.
+
+use crate::api::agent::ToolResult;
+use crate::api::api::ToolCall;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Identical calls allowed while the project stays the same
+const REPEAT_LIMIT: usize = 3;
+
+/// Rounds in a row that may try to change the project without effect
+const STALL_LIMIT: usize = 4;
+
+/// Tools that are never a sign of looping
+const EXEMPT_TOOLS: [&str; 2] = ["ask_question", "task_complete"];
+
+/// Watches the rounds of one run
+#[derive(Debug)]
+pub struct Watchdog {
+    root: PathBuf,
+    /// Times each call (tool and arguments) was made since the project last
+    /// changed
+    repeats: HashMap<String, usize>,
+    /// Fingerprint of the project files after the last round
+    files: Option<u64>,
+    /// Outcome of the latest test run
+    tests: Option<String>,
+    /// Test outcome seen during the current round
+    round_tests: Option<String>,
+    /// Rounds in a row that tried to change something without effect
+    stalled_rounds: usize,
+}
+
+impl Watchdog {
+    /// A watchdog for a run working in `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            repeats: HashMap::new(),
+            files: None,
+            tests: None,
+            round_tests: None,
+            stalled_rounds: 0,
+        }
+    }
+
+    /// A watchdog for a run in `root`, starting from the project as it is now
+    pub async fn start(root: impl Into<PathBuf>) -> Self {
+        let mut watchdog = Self::new(root);
+        watchdog.files = files_fingerprint(&watchdog.root).await;
+        watchdog
+    }
+
+    /// Note a finished tool call; test runs update the test state
+    pub fn record_result(&mut self, call: &ToolCall, result: Option<&ToolResult>) {
+        if call.function.name != "run_tests" {
+            return;
+        }
+        if let Some(result) = result.filter(|r| r.success) {
+            let data = &result.data;
+            self.round_tests = Some(format!(
+                "{} {} {}",
+                data.get("passed").unwrap_or(&Value::Null),
+                data.get("failed").unwrap_or(&Value::Null),
+                data.get("failing_tests").unwrap_or(&Value::Null)
+            ));
+        }
+    }
+
+    /// Look at a finished round; a diagnosis when the run should pause.
+    /// `attempted_change` says whether the round ran tools that may change
+    /// the project.
+    pub async fn end_round(
+        &mut self,
+        calls: &[ToolCall],
+        attempted_change: bool,
+    ) -> Option<String> {
+        let files = files_fingerprint(&self.root).await;
+        self.judge(calls, files, attempted_change)
+    }
+
+    fn judge(
+        &mut self,
+        calls: &[ToolCall],
+        files: Option<u64>,
+        attempted_change: bool,
+    ) -> Option<String> {
+        let tests = self.round_tests.take().or_else(|| self.tests.clone());
+        // Without git there is no file state, so only test results count
+        let changed = (files.is_some() && files != self.files) || tests != self.tests;
+        self.files = files;
+        self.tests = tests;
+
+        if changed {
+            self.repeats.clear();
+            self.stalled_rounds = 0;
+        } else if attempted_change {
+            self.stalled_rounds += 1;
+        }
+
+        for call in calls {
+            if EXEMPT_TOOLS.contains(&call.function.name.as_str()) {
+                continue;
+            }
+            let key = format!(
+                "{}\u{0}{}",
+                call.function.name,
+                canonical(&call.function.arguments)
+            );
+            let count = self.repeats.entry(key).or_insert(0);
+            *count += 1;
+            if *count >= REPEAT_LIMIT {
+                return Some(format!(
+                    "`{}` was called {} times with the same arguments ({}) and the project did not change in between.",
+                    call.function.name,
+                    count,
+                    shorten(&call.function.arguments, 120)
+                ));
+            }
+        }
+
+        if self.stalled_rounds >= STALL_LIMIT {
+            let what = if files.is_some() {
+                "the project files and test results"
+            } else {
+                "the test results"
+            };
+            return Some(format!(
+                "{} rounds of edits and commands in a row left {} unchanged.",
+                self.stalled_rounds, what
+            ));
+        }
+        None
+    }
+}
+
+/// Arguments with keys sorted and whitespace dropped, so equal calls compare
+/// equal however they were formatted
+fn canonical(arguments: &str) -> String {
+    match serde_json::from_str::<Value>(arguments) {
+        // serde_json keeps object keys sorted
+        Ok(value) => value.to_string(),
+        Err(_) => arguments.trim().to_string(),
+    }
+}
+
+fn shorten(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max).collect();
+    format!("{}…", cut)
+}
+
+/// Fingerprint of the working tree: the git status, the diff against HEAD
+/// and the size and time of untracked files. `None` outside git.
+async fn files_fingerprint(root: &Path) -> Option<u64> {
+    let status = git(root, &["status", "--porcelain=v1", "-uall"]).await?;
+    let diff = git(root, &["diff", "HEAD"]).await.unwrap_or_default();
+    let mut hasher = DefaultHasher::new();
+    status.hash(&mut hasher);
+    diff.hash(&mut hasher);
+    for path in status.lines().filter_map(|l| l.strip_prefix("?? ")) {
+        if let Ok(meta) = std::fs::metadata(root.join(path)) {
+            meta.len().hash(&mut hasher);
+            meta.modified().ok().hash(&mut hasher);
+        }
+    }
+    Some(hasher.finish())
+}
+
+async fn git(root: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::api::ToolCallFunction;
+    use serde_json::json;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall {
+            id: "1".to_string(),
+            r#type: "function".to_string(),
+            function: ToolCallFunction {
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_detects_loops() {
+        // The same command with reordered arguments, while nothing changes
+        let mut watchdog = Watchdog::new(".");
+        let build = |args| vec![call("execute_bash", args)];
+        assert_eq!(
+            watchdog.judge(&build(r#"{"command":"make","timeout":5}"#), Some(1), true),
+            None
+        );
+        assert_eq!(
+            watchdog.judge(
+                &build(r#"{"timeout": 5, "command": "make"}"#),
+                Some(1),
+                true
+            ),
+            None
+        );
+        let diagnosis = watchdog
+            .judge(&build(r#"{"command":"make","timeout":5}"#), Some(1), true)
+            .unwrap();
+        assert!(diagnosis.starts_with("`execute_bash` was called 3 times"));
+
+        // A change in between starts the count again
+        let mut watchdog = Watchdog::new(".");
+        let read = vec![call("read_file", r#"{"path":"a.rs"}"#)];
+        for files in [1, 1, 2, 2] {
+            assert_eq!(watchdog.judge(&read, Some(files), false), None);
+        }
+
+        // Edits that never change anything, each different
+        let mut watchdog = Watchdog::new(".");
+        watchdog.files = Some(7);
+        for i in 0..STALL_LIMIT {
+            let edit = vec![call(
+                "edit_file",
+                &format!(r#"{{"path":"a.rs","old":"{}"}}"#, i),
+            )];
+            let diagnosis = watchdog.judge(&edit, Some(7), true);
+            assert_eq!(diagnosis.is_some(), i + 1 == STALL_LIMIT);
+        }
+
+        // New test results count as progress
+        let mut watchdog = Watchdog::new(".");
+        let tests = vec![call("run_tests", "{}")];
+        for failed in [3, 2, 1, 0] {
+            watchdog.record_result(
+                &tests[0],
+                Some(&ToolResult::success(json!({"passed": 1, "failed": failed}))),
+            );
+            assert_eq!(watchdog.judge(&tests, None, true), None);
+        }
+        assert!(
+            watchdog
+                .judge(&[call("task_complete", "{}")], None, false)
+                .is_none()
+        );
+    }
+}
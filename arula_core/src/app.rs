@@ -34,22 +34,41 @@
 
 use crate::api::agent::{AgentOptionsBuilder, ContentBlock};
 use crate::api::agent_client::AgentClient;
+use crate::api::steering::Steering;
+use crate::api::models::MODELS_CACHE;
+use crate::api::trust::{data_block, Trust, DATA_BLOCK_RULES};
+use crate::storage::Storage;
+use crate::tools::wasm_plugins::WasmPlugins;
 use crate::utils::chat::{ChatMessage, MessageType};
-use crate::utils::config::Config;
+use crate::utils::compaction::{self, Compaction};
+use crate::utils::config::{Config, GenerationSettings};
+use crate::utils::config_watcher::{describe_changes, provider_changed};
+use crate::utils::file_context;
+use crate::utils::file_lock::{self, FileLock, SessionInUse};
+use crate::utils::file_mentions::mentioned_files;
 use crate::utils::debug::{
     debug_print, log_ai_interaction, log_ai_response_chunk, log_ai_response_complete,
 };
+use crate::utils::git_context::enrich_message;
 use crate::utils::git_state::GitStateTracker;
+use crate::utils::hooks::{HookEvent, Hooks};
+use crate::utils::memory::memory_context;
+use crate::utils::postprocess::PostProcessor;
+use crate::utils::scripting::Scripts;
+use crate::utils::success_criteria::{self, Criterion, Verdict, TASK_COMPLETE_TOOL};
 use crate::utils::tool_call::{execute_bash_tool, ToolCall, ToolCallResult};
 use anyhow::Result;
 use futures::StreamExt;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::fs;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
+/// How long a fetched model list is reused by later runs
+const STORED_MODELS_TTL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
 #[derive(Debug, Clone)]
 pub enum AiResponse {
     AgentStreamStart,
@@ -71,7 +90,24 @@ pub enum AiResponse {
         tool_call_id: String,
         success: bool,
         result: serde_json::Value,
+        /// Time the tool itself took to run
+        duration_ms: Option<u64>,
+    },
+    /// The request failed; the text is shown in place of a reply
+    AgentError(String),
+    /// The task's success criteria were checked at the end of the run
+    TaskVerdict(Verdict),
+    /// The run was paused because it looks stuck; the text says why
+    LoopDetected(String),
+    /// Older messages were summarized to fit the context window and the
+    /// request was sent again
+    ContextCompacted {
+        /// Messages summarized from the request
+        replaced: usize,
+        summary: String,
     },
+    /// The agent asked the user a question and waits for the answer
+    QuestionAsked(String),
     AgentStreamEnd,
 }
 
@@ -109,6 +145,12 @@ pub struct App {
     pub debug: bool,
     // Cancellation token for stopping API requests
     pub cancellation_token: CancellationToken,
+    // Messages that redirect the running request without cancelling it
+    pub steering: Steering,
+    // Files the user attached to their next message
+    pub context_files: Vec<std::path::PathBuf>,
+    // Checks that decide whether a run finished its task
+    pub success_criteria: Vec<Criterion>,
     // Task handle for aborting in-flight requests
     pub current_task_handle: Option<tokio::task::JoinHandle<()>>,
     // Model caches for all providers
@@ -120,12 +162,22 @@ pub struct App {
     // Conversation tracking
     pub current_conversation: Option<crate::utils::conversation::Conversation>,
     pub auto_save_conversations: bool,
+    // Keeps other instances from writing the current conversation
+    session_lock: Option<FileLock>,
+    // Opened while another instance holds it; never saved
+    read_only: bool,
     tracking_rx: Option<std::sync::mpsc::Receiver<TrackingCommand>>,
     tracking_tx: Option<std::sync::mpsc::Sender<TrackingCommand>>,
     // Shared conversation for immediate saving from background tasks
     pub shared_conversation: Arc<Mutex<Option<crate::utils::conversation::Conversation>>>,
     // Pending init message to be sent to AI
     pub pending_init_message: Option<String>,
+    // User commands run on agent events
+    pub hooks: Hooks,
+    // User scripts from ~/.arula/scripts
+    pub scripts: Scripts,
+    // Sandboxed WASM tools from ~/.arula/plugins
+    pub plugins: WasmPlugins,
 }
 
 impl App {
@@ -134,6 +186,7 @@ impl App {
 
         // Create persistent tracking channel
         let (tracking_tx, tracking_rx) = std::sync::mpsc::channel();
+        let hooks = Hooks::from_config(&config);
 
         Ok(Self {
             config,
@@ -148,6 +201,9 @@ impl App {
             pending_tool_calls: None,
             debug: false,
             cancellation_token: CancellationToken::new(),
+            steering: Steering::new(),
+            context_files: Vec::new(),
+            success_criteria: Vec::new(),
             current_task_handle: None,
             openrouter_models: Arc::new(Mutex::new(None)),
             openai_models: Arc::new(Mutex::new(None)),
@@ -155,11 +211,16 @@ impl App {
             ollama_models: Arc::new(Mutex::new(None)),
             zai_models: Arc::new(Mutex::new(None)),
             current_conversation: None,
+            session_lock: None,
+            read_only: false,
             auto_save_conversations: true, // Default to auto-save
             tracking_rx: Some(tracking_rx),
             tracking_tx: Some(tracking_tx),
             shared_conversation: Arc::new(Mutex::new(None)),
             pending_init_message: None,
+            hooks,
+            scripts: Scripts::load(),
+            plugins: WasmPlugins::load(),
         })
     }
 
@@ -170,8 +231,12 @@ impl App {
 
     /// Reload configuration from file and reinitialize agent client if needed
     pub fn reload_config(&mut self) -> Result<()> {
-        // Reload configuration from file
+        // Reload configuration from file, keeping the active profile
+        let profile = self.config.active_profile.take();
         self.config = Config::load_or_default()?;
+        if let Some(profile) = profile.as_deref() {
+            self.config.use_profile(Some(profile))?;
+        }
 
         // Clear cached tool registry to force refresh with new config
         self.cached_tool_registry = None;
@@ -182,6 +247,32 @@ impl App {
         Ok(())
     }
 
+    /// Apply a config reloaded from disk, keeping the active profile, and
+    /// reinitialize the agent client if its provider settings changed
+    ///
+    /// Returns the settings that changed, for a notice in the chat.
+    pub fn apply_reloaded_config(&mut self, mut config: Config) -> Result<Vec<String>> {
+        if let Some(profile) = self.config.active_profile.as_deref() {
+            config.use_profile(Some(profile))?;
+        }
+        let changes = describe_changes(&self.config, &config);
+        let reinitialize = provider_changed(&self.config, &config);
+        self.config = config;
+        self.hooks = Hooks::from_config(&self.config);
+        if reinitialize {
+            self.initialize_agent_client()?;
+        }
+        Ok(changes)
+    }
+
+    /// Switch to a configuration profile (`None` for the base configuration)
+    /// and reinitialize the agent client with its provider settings
+    pub fn switch_profile(&mut self, name: Option<&str>) -> Result<()> {
+        self.config.use_profile(name)?;
+        self.initialize_agent_client()?;
+        Ok(())
+    }
+
     /// Initialize cached tool registry with MCP discovery (run once at startup)
     pub async fn initialize_tool_registry(&mut self) -> Result<()> {
         if self.cached_tool_registry.is_none() {
@@ -274,6 +365,12 @@ You have access to these tools for file operations and shell commands:
 | `edit_file` | Make targeted edits to existing files |
 | `list_directory` | List files and directories |
 | `search_files` | Search for patterns in files |
+| `run_tests` | Run the project's test suite with pass/fail summary |
+| `git_commit` | Stage files and draft a conventional commit message for `/commit` |
+| `find_todos` | List TODO/FIXME/HACK comments with age and priority |
+| `find_symbol` | Find where a function, method or type is defined |
+| `remember` | Store a user preference or project convention for future sessions |
+| `recall` | Look up facts stored in earlier sessions |
 
 ### Tool Mapping
 - User asks to run a command → `execute_bash`
@@ -281,6 +378,12 @@ You have access to these tools for file operations and shell commands:
 - User asks to list/show files → `list_directory`
 - User asks to edit a file → `read_file` first, then `edit_file`
 - User asks to create a file → `write_file`
+- User asks to run the tests → `run_tests`
+- User asks to commit changes → `git_commit`, then tell them to run `/commit` to confirm
+- User asks about TODOs, tech debt or what to triage → `find_todos`
+- User asks where a function or type is defined → `find_symbol`
+- User states a lasting preference or convention, or asks you to remember something → `remember`
+- User refers to something from an earlier session → `recall`
 
 ### CRITICAL FORMAT WARNING
 - DO NOT output tool calls as text like `<function=tool_name>` or `</function>`
@@ -291,6 +394,9 @@ You have access to these tools for file operations and shell commands:
         // 4. Add built-in tools information (detailed tool schemas)
         prompt_parts.push(self.build_builtin_tools_info());
 
+        // 5. Tool results are data, not instructions
+        prompt_parts.push(DATA_BLOCK_RULES.to_string());
+
         // Read PROJECT.manifest from current directory (project context)
         if let Some(manifest) = Self::read_project_manifest() {
             prompt_parts.push(format!(
@@ -299,6 +405,18 @@ You have access to these tools for file operations and shell commands:
             ));
         }
 
+        // Facts remembered in earlier sessions
+        if self.config.get_memory_enabled()
+            && let Some(memories) = std::env::current_dir()
+                .ok()
+                .and_then(|root| memory_context(&root, self.config.get_memory_max_chars()))
+        {
+            prompt_parts.push(format!(
+                "\n## Remembered Facts\nFacts stored with `remember` in earlier sessions, newest first:\n{}",
+                memories
+            ));
+        }
+
         // Add MCP tool information
         prompt_parts.push(self.build_mcp_tool_info());
 
@@ -423,10 +541,19 @@ You have access to these tools for file operations and shell commands:
     }
 
     pub fn initialize_agent_client(&mut self) -> Result<()> {
-        // Initialize modern agent client with default options
+        crate::api::capabilities::set_overrides(&self.config.models);
+
+        self.agent_client = Some(self.build_agent_client(&self.config));
+        self.hooks = Hooks::from_config(&self.config);
+
+        Ok(())
+    }
+
+    /// An agent client for the provider and model of `config`
+    fn build_agent_client(&self, config: &Config) -> AgentClient {
         let agent_options = AgentOptionsBuilder::new()
             .system_prompt(&self.build_system_prompt())
-            .model(&self.config.get_model())
+            .model(&config.get_model())
             .auto_execute_tools(true)
             .max_tool_iterations(1000)
             .debug(self.debug)
@@ -434,19 +561,19 @@ You have access to these tools for file operations and shell commands:
 
         // Create a new agent client with a basic tool registry
         // MCP tools are handled separately in the streaming response
-        let basic_registry = crate::tools::tools::create_basic_tool_registry();
-
-        self.agent_client = Some(AgentClient::new_with_registry(
-            self.config.active_provider.clone(),
-            self.config.get_api_url(),
-            self.config.get_api_key(),
-            self.config.get_model(),
+        let mut basic_registry = crate::tools::tools::create_basic_tool_registry();
+        self.scripts.register_tools(&mut basic_registry);
+        self.plugins.register_tools(&mut basic_registry);
+
+        AgentClient::new_with_registry(
+            config.active_provider.clone(),
+            config.get_api_url(),
+            config.get_api_key(),
+            config.get_model(),
             agent_options,
-            &self.config,
+            config,
             basic_registry,
-        ));
-
-        Ok(())
+        )
     }
 
     fn initialize_mcp_tools_async(&mut self) {
@@ -461,6 +588,18 @@ You have access to these tools for file operations and shell commands:
         // MCP tools will be initialized lazily when needed to avoid runtime conflicts
     }
 
+    /// Reload the user scripts and rebuild the client so their tools are offered
+    pub fn reload_scripts(&mut self) -> Result<()> {
+        self.scripts = Scripts::load();
+        self.initialize_agent_client()
+    }
+
+    /// Reload the WASM plugins and rebuild the client so their tools are offered
+    pub fn reload_plugins(&mut self) -> Result<()> {
+        self.plugins = WasmPlugins::load();
+        self.initialize_agent_client()
+    }
+
     pub fn get_config(&self) -> &Config {
         &self.config
     }
@@ -469,8 +608,11 @@ You have access to these tools for file operations and shell commands:
         &mut self.config
     }
 
+    /// Switch to a model or model alias (see `model_aliases` in config.json)
     pub fn set_model(&mut self, model: &str) {
-        self.config.set_model(model);
+        if let Err(e) = self.config.use_model(model) {
+            debug_print(&format!("Failed to switch to model {}: {}", model, e));
+        }
         let _ = self.config.save();
         // Reinitialize agent client with new model
         let _ = self.initialize_agent_client();
@@ -495,28 +637,175 @@ You have access to these tools for file operations and shell commands:
             ));
         }
 
+        // Let scripts rewrite the prompt before it is stored or sent
+        let message = self.scripts.transform_prompt(message);
+
+        // `@alias ...` sends this one message to the alias's model
+        let (model, message) = match split_model_override(&message, &self.config) {
+            Some((alias, rest)) => (Some(alias.to_string()), rest.to_string()),
+            None => (None, message),
+        };
+
         // Add user message to history
         self.messages
-            .push(ChatMessage::new(MessageType::User, message.to_string()));
+            .push(ChatMessage::new(MessageType::User, message.clone()));
+        self.track_user_message(&message);
 
         // Send message using the modern agent client
-        self.send_to_ai_with_agent(message).await
+        self.send_to_ai_with_agent(&message, None, model.as_deref())
+            .await
+    }
+
+    /// Replace the last user message with `message` and send it again,
+    /// dropping everything after it
+    pub async fn edit_last_prompt(&mut self, message: &str) -> Result<()> {
+        if self.rewind_last_prompt().is_none() {
+            anyhow::bail!("No message to edit yet");
+        }
+        self.send_to_ai(message).await
+    }
+
+    /// Drop the reply to the last user message and ask for a new one,
+    /// optionally at a different temperature; returns the prompt
+    pub async fn regenerate(&mut self, temperature: Option<f32>) -> Result<String> {
+        if self.agent_client.is_none() {
+            anyhow::bail!("AI client not initialized");
+        }
+        let Some(prompt) = self.rewind_last_prompt() else {
+            anyhow::bail!("No response to regenerate yet");
+        };
+
+        // The prompt already went through the script transforms
+        self.messages
+            .push(ChatMessage::new(MessageType::User, prompt.clone()));
+        self.track_user_message(&prompt);
+
+        let generation = temperature.map(|temperature| GenerationSettings {
+            temperature: Some(temperature),
+            ..self.config.get_generation_settings()
+        });
+        self.send_to_ai_with_agent(&prompt, generation, None).await?;
+        Ok(prompt)
+    }
+
+    /// Summarize all but the last `keep_turns` user turns to free up
+    /// context; `None` when there is nothing old enough to compact
+    pub async fn compact_conversation(&mut self, keep_turns: usize) -> Result<Option<Compaction>> {
+        compaction::compact(&self.config, &mut self.messages, keep_turns).await
+    }
+
+    /// The current conversation's rolling brief
+    pub fn session_brief(&self) -> Option<&str> {
+        self.current_conversation.as_ref()?.metadata.brief.as_deref()
+    }
+
+    /// Keep a new rolling brief with the current conversation
+    pub fn set_session_brief(&mut self, brief: String) {
+        self.update_session_metadata(|conv| conv.set_brief(brief));
+    }
+
+    /// Whether the current conversation was left with the tool activity
+    /// panel shown
+    pub fn session_tool_panel(&self) -> Option<bool> {
+        self.current_conversation.as_ref()?.metadata.tool_panel
+    }
+
+    /// Remember with the current conversation whether the tool activity
+    /// panel is shown
+    pub fn set_session_tool_panel(&mut self, shown: bool) {
+        self.update_session_metadata(|conv| conv.set_tool_panel(shown));
+    }
+
+    /// Change the current conversation's details and save it
+    fn update_session_metadata(
+        &mut self,
+        update: impl FnOnce(&mut crate::utils::conversation::Conversation),
+    ) {
+        self.sync_from_shared_conversation();
+        let Some(ref mut conv) = self.current_conversation else {
+            return;
+        };
+        update(conv);
+        if let Ok(mut shared) = self.shared_conversation.lock() {
+            *shared = Some(conv.clone());
+        }
+        if self.auto_save_conversations {
+            let _ = self.save_conversation();
+        }
     }
 
-    /// Send message using the modern agent client
-    async fn send_to_ai_with_agent(&mut self, message: &str) -> Result<()> {
+    /// Write the current conversation as Markdown to `path`, or to a file
+    /// named after its export title in the working directory
+    pub fn export_conversation(&mut self, path: Option<&Path>) -> Result<std::path::PathBuf> {
+        self.sync_from_shared_conversation();
+        let Some(ref conv) = self.current_conversation else {
+            anyhow::bail!("Nothing to export yet");
+        };
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => std::env::current_dir()?.join(conv.export_file_name()),
+        };
+        fs::write(&path, conv.to_markdown())
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Drop the last user message and everything after it, from the chat
+    /// history and the tracked conversation; returns the dropped message
+    pub fn rewind_last_prompt(&mut self) -> Option<String> {
+        let index = self
+            .messages
+            .iter()
+            .rposition(|m| m.message_type == MessageType::User)?;
+        let prompt = self.messages.split_off(index).swap_remove(0).content;
+
+        self.sync_from_shared_conversation();
+        if let Some(ref mut conv) = self.current_conversation {
+            if let Some(index) = conv.messages.iter().rposition(|m| m.role == "user") {
+                conv.truncate(index);
+            }
+            if let Ok(mut shared) = self.shared_conversation.lock() {
+                *shared = Some(conv.clone());
+            }
+            if self.auto_save_conversations {
+                let _ = self.save_conversation();
+            }
+        }
+        Some(prompt)
+    }
+
+    /// Send message using the modern agent client, with `generation`
+    /// overriding the configured sampling settings and `model` (a model or
+    /// alias) the configured model for this request
+    async fn send_to_ai_with_agent(
+        &mut self,
+        message: &str,
+        generation: Option<GenerationSettings>,
+        model: Option<&str>,
+    ) -> Result<()> {
         // Save current git branch before AI interaction
         if let Err(e) = self.git_state_tracker.save_current_branch().await {
             eprintln!("⚠️ GitState: Failed to save current branch: {}", e);
         }
 
         // Get agent client
-        let agent_client = match &self.agent_client {
-            Some(client) => client.clone(),
-            None => {
+        let mut agent_client = match (&self.agent_client, model) {
+            (None, _) => {
                 return Err(anyhow::anyhow!("Agent client not initialized"));
             }
+            (Some(_), Some(model)) => {
+                let mut config = self.config.clone();
+                config.use_model(model)?;
+                self.build_agent_client(&config)
+            }
+            (Some(client), None) => client.clone(),
         };
+        if let Some(generation) = generation {
+            agent_client = agent_client.with_generation(generation);
+        }
+        // Steering left over from an earlier run doesn't apply to this one
+        self.steering.take();
+        let agent_client = agent_client.with_steering(self.steering.clone());
 
         // Create channel for streaming responses
         let (tx, rx) = mpsc::unbounded_channel();
@@ -562,7 +851,7 @@ You have access to these tools for file operations and shell commands:
 
         // Convert chat messages to API format for agent
         // IMPORTANT: Include tool results so AI knows what tools were already used!
-        let api_messages: Vec<crate::api::api::ChatMessage> = self
+        let mut api_messages: Vec<crate::api::api::ChatMessage> = self
             .messages
             .iter()
             .filter(|m| {
@@ -576,9 +865,15 @@ You have access to these tools for file operations and shell commands:
                     MessageType::ToolResult => "assistant".to_string(), // Tool results go as assistant context
                     _ => "system".to_string(),
                 };
+                // Earlier tool results are data too; their tool isn't known here
+                let content = if m.message_type == MessageType::ToolResult {
+                    data_block("earlier tool call", Trust::External, &m.content)
+                } else {
+                    m.content.clone()
+                };
                 crate::api::api::ChatMessage {
                     role,
-                    content: Some(m.content.clone()),
+                    content: Some(content),
                     tool_calls: None,
                     tool_call_id: None,
                     tool_name: None,
@@ -591,6 +886,44 @@ You have access to these tools for file operations and shell commands:
             api_messages.len()
         ));
 
+        // Attach git history of referenced files to this request only;
+        // the stored conversation keeps the message as typed
+        let mut msg = message.to_string();
+        if self.config.get_git_enrichment_enabled() {
+            let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+            let enriched = enrich_message(&root, message).await;
+            if enriched != message {
+                if let Some(last) = api_messages.last_mut().filter(|m| m.role == "user") {
+                    last.content = Some(enriched.clone());
+                }
+                msg = enriched;
+            }
+        }
+
+        // Attach the files the user picked or @-mentioned, for this request only
+        let mut files = std::mem::take(&mut self.context_files);
+        let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        for file in mentioned_files(&root, message) {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        if let Some(context) = file_context::attach_files(&root, &files) {
+            msg = format!("{}\n\n{}", msg, context);
+            if let Some(last) = api_messages.last_mut().filter(|m| m.role == "user") {
+                last.content = Some(msg.clone());
+            }
+        }
+
+        // Tell the model what will be checked when it finishes
+        if let Some(section) = success_criteria::prompt_section(&self.success_criteria) {
+            msg = format!("{}\n\n{}", msg, section);
+            if let Some(last) = api_messages.last_mut().filter(|m| m.role == "user") {
+                last.content = Some(msg.clone());
+            }
+        }
+        let criteria = self.success_criteria.clone();
+
         // Log the AI interaction for debugging
         log_ai_interaction(message, &api_messages, None);
 
@@ -606,20 +939,28 @@ You have access to these tools for file operations and shell commands:
         ));
 
         // Send message using modern agent in background
-        let msg = message.to_string();
         let cancel_token = self.cancellation_token.clone();
         // Removed external_printer since we're using custom output system
         let shared_conv = self.shared_conversation.clone();
-        let auto_save = self.auto_save_conversations;
+        let auto_save = self.auto_save_conversations && !self.read_only;
+        let hooks = self.hooks.clone();
+        // Response transforms need whole text, so hold it back until a
+        // tool call or the end of the reply
+        let scripts = self.scripts.clone();
+        let hold_text = scripts.transforms_responses();
         let handle = tokio::spawn(async move {
             // Track message content and tool calls for conversation history
             let mut accumulated_text = String::new();
             let mut tool_calls_list: Vec<(String, String, String)> = Vec::new(); // (id, name, args)
             let mut thinking_started = false; // Track if we've started thinking mode
+            let mut run_error: Option<String> = None;
+            let mut held_text = String::new();
+            let mut loop_paused = false;
 
             tokio::select! {
                 _ = cancel_token.cancelled() => {
                     // Request was cancelled
+                    hooks.fire(HookEvent::RunFinished, json!({ "success": false, "cancelled": true }));
                     let _ = tx.send(AiResponse::AgentStreamEnd);
                 }
                 _result = async {
@@ -651,6 +992,11 @@ You have access to these tools for file operations and shell commands:
                                                     thinking_started = false;
                                                 }
 
+                                                if hold_text {
+                                                    held_text.push_str(&text);
+                                                    continue;
+                                                }
+
                                                 // Accumulate text for tracking
                                                 accumulated_text.push_str(&text);
 
@@ -679,6 +1025,8 @@ You have access to these tools for file operations and shell commands:
                                                     thinking_started = false;
                                                 }
 
+                                                flush_held_text(&scripts, &mut held_text, &mut accumulated_text, &tx);
+
                                                 // Track tool call
                                                 tool_calls_list.push((id.clone(), name.clone(), arguments.clone()));
 
@@ -707,31 +1055,57 @@ You have access to these tools for file operations and shell commands:
                                                     tool_call_id: tool_call_id.clone(),
                                                     success: result.success,
                                                     result: result_data.clone(),
+                                                    duration_ms: result.duration_ms,
                                                 });
 
+                                                hooks.fire(HookEvent::ToolExecuted, json!({
+                                                    "tool_call_id": tool_call_id,
+                                                    "tool": tool_name,
+                                                    "success": result.success,
+                                                    "result": result_data,
+                                                }));
+
                                                 // Send tracking command for tool result
                                                 let _ = track_tx.send(TrackingCommand::ToolResult {
                                                     tool_call_id,
                                                     tool_name,
                                                     result: result_data.clone(),
                                                     success: result.success,
-                                                    execution_time_ms: 100,
+                                                    execution_time_ms: result.duration_ms.unwrap_or(0),
                                                 });
 
                                             }
                                             Some(ContentBlock::Error { error }) => {
-                                                // Convert error to AgentStreamText to maintain compatibility
-                                                let error_msg = format!("[Error] {}", error);
-                                                let _ = tx.send(AiResponse::AgentStreamText(error_msg.clone()));
+                                                flush_held_text(&scripts, &mut held_text, &mut accumulated_text, &tx);
+
+                                                let _ = tx.send(AiResponse::AgentError(error.clone()));
+                                                run_error = Some(error);
                                                 break;
                                             }
                                             Some(ContentBlock::BashOutputLine { .. }) => {
                                                 // Ignore streaming bash output in this context (CLI/Legacy)
                                                 // Desktop uses SessionManager which handles this event
                                             }
-                                            Some(ContentBlock::AskQuestion { .. }) => {
+                                            Some(ContentBlock::AskQuestion { tool_call_id, question, options }) => {
                                                 // Ask question is handled by SessionManager in desktop
                                                 // CLI doesn't show interactive question UI
+                                                hooks.fire(HookEvent::ApprovalRequired, json!({
+                                                    "tool_call_id": tool_call_id,
+                                                    "question": question,
+                                                    "options": options,
+                                                }));
+                                                let _ = tx.send(AiResponse::QuestionAsked(question));
+                                            }
+                                            Some(ContentBlock::LoopDetected { diagnosis }) => {
+                                                flush_held_text(&scripts, &mut held_text, &mut accumulated_text, &tx);
+                                                hooks.fire(HookEvent::ApprovalRequired, json!({
+                                                    "loop_detected": diagnosis,
+                                                }));
+                                                let _ = tx.send(AiResponse::LoopDetected(diagnosis));
+                                                loop_paused = true;
+                                            }
+                                            Some(ContentBlock::ContextCompacted { replaced, summary }) => {
+                                                let _ = tx.send(AiResponse::ContextCompacted { replaced, summary });
                                             }
                                             None => {
                                                 // Stream ended
@@ -742,6 +1116,8 @@ You have access to these tools for file operations and shell commands:
                                 }
                             }
 
+                            flush_held_text(&scripts, &mut held_text, &mut accumulated_text, &tx);
+
                             // IMMEDIATELY save AI response to conversation (user's brilliant idea!)
                             // This happens BEFORE printing to ExternalPrinter, ensuring JSON is updated instantly
                             if !accumulated_text.is_empty() {
@@ -758,7 +1134,7 @@ You have access to these tools for file operations and shell commands:
                                         if auto_save {
                                             if let Ok(current_dir) = std::env::current_dir() {
                                                 conv.update_duration();
-                                                let _ = conv.save(&current_dir);
+                                                let _ = save_conversation_in(conv, &current_dir);
                                                 debug_print("DEBUG: Conversation saved to disk immediately from tokio task!");
                                             }
                                         }
@@ -770,6 +1146,10 @@ You have access to these tools for file operations and shell commands:
                                 debug_print("DEBUG: Skipping tracking command - already saved immediately to shared_conversation");
                             }
 
+                            let claimed_complete = tool_calls_list
+                                .iter()
+                                .any(|(_, name, _)| name == TASK_COMPLETE_TOOL);
+                            let tool_call_count = tool_calls_list.len();
                             for (id, name, args) in tool_calls_list {
                                 debug_print(&format!("DEBUG: Sending ToolCall tracking command: {}", name));
                                 if let Err(e) = track_tx.send(TrackingCommand::ToolCall {
@@ -781,11 +1161,37 @@ You have access to these tools for file operations and shell commands:
                                 }
                             }
 
+                            // Judge the run by its success criteria, not by what the model says
+                            let mut verdict = None;
+                            if !criteria.is_empty()
+                                && run_error.is_none()
+                                && !loop_paused
+                                && !cancel_token.is_cancelled()
+                            {
+                                let checked = success_criteria::verify(&root, &criteria, claimed_complete).await;
+                                let _ = tx.send(AiResponse::TaskVerdict(checked.clone()));
+                                verdict = Some(checked);
+                            }
+
+                            hooks.fire(HookEvent::RunFinished, json!({
+                                "success": run_error.is_none() && verdict.as_ref().is_none_or(Verdict::passed),
+                                "cancelled": cancel_token.is_cancelled(),
+                                "error": run_error,
+                                "tool_calls": tool_call_count,
+                                "response": accumulated_text,
+                                "task_complete": claimed_complete,
+                                "criteria": verdict,
+                            }));
                             let _ = tx.send(AiResponse::AgentStreamEnd);
                         }
                         Err(e) => {
-                            let error_msg = format!("**Error:** Failed to send message via agent: {}", e);
-                            let _ = tx.send(AiResponse::AgentStreamText(error_msg.clone()));
+                            hooks.fire(HookEvent::RunFinished, json!({
+                                "success": false,
+                                "cancelled": false,
+                                "error": e.to_string(),
+                            }));
+                            let error_msg = format!("Failed to send message via agent: {:#}", e);
+                            let _ = tx.send(AiResponse::AgentError(error_msg));
                             let _ = tx.send(AiResponse::AgentStreamEnd);
                         }
                     }
@@ -922,6 +1328,7 @@ You have access to these tools for file operations and shell commands:
                             tool_call_id,
                             success,
                             result,
+                            ..
                         } => {
                             // Add tool result message to chat history
                             let status = if *success { "✅" } else { "❌" };
@@ -939,8 +1346,38 @@ You have access to these tools for file operations and shell commands:
                             // Note: Tool result tracking with proper name is handled via TrackingCommand
                             // This is a fallback that shouldn't normally be hit since we track via the async task
                         }
+                        AiResponse::AgentError(_) => {
+                            // Errors stay out of the history sent to the model
+                        }
+                        AiResponse::QuestionAsked(_) => {
+                            // The question is part of the reply text
+                        }
+                        AiResponse::ContextCompacted { summary, .. } => {
+                            // Keep the summary so later turns fit as well
+                            compaction::apply_summary(
+                                &mut self.messages,
+                                compaction::RECOVERY_KEEP_TURNS,
+                                summary.clone(),
+                            );
+                        }
+                        AiResponse::LoopDetected(diagnosis) => {
+                            self.messages.push(ChatMessage::new(
+                                MessageType::Info,
+                                format!("Run paused by the loop watchdog: {}", diagnosis),
+                            ));
+                        }
+                        AiResponse::TaskVerdict(verdict) => {
+                            let kind = if verdict.passed() {
+                                MessageType::Success
+                            } else {
+                                MessageType::Error
+                            };
+                            self.messages.push(ChatMessage::new(kind, verdict.report()));
+                        }
                         AiResponse::AgentStreamEnd => {
                             if let Some(full_message) = self.current_streaming_message.take() {
+                                let full_message = PostProcessor::new(&self.config.get_postprocess())
+                                    .process(&full_message);
                                 self.messages.push(ChatMessage::new(
                                     MessageType::Arula,
                                     full_message.clone(),
@@ -1030,6 +1467,16 @@ You have access to these tools for file operations and shell commands:
         results
     }
 
+    /// Redirect the running request; the message is added to its context
+    /// before the next model call. Returns false if nothing is running.
+    pub fn steer(&self, message: &str) -> bool {
+        if self.ai_response_rx.is_none() {
+            return false;
+        }
+        self.steering.send(message);
+        true
+    }
+
     /// Cancel the current API request
     pub fn cancel_request(&mut self) {
         self.cancellation_token.cancel();
@@ -1049,6 +1496,51 @@ You have access to these tools for file operations and shell commands:
         eprintln!("🔧 GitState: Cancelled - git branch will be restored on next startup");
     }
 
+    /// Get ready to exit: cancel the running request, save the conversation
+    /// and wait for database writes in progress
+    pub fn shutdown(&mut self) {
+        if self.is_waiting_for_response() {
+            self.cancellation_token.cancel();
+            if let Some(handle) = self.current_task_handle.take() {
+                handle.abort();
+            }
+            self.ai_response_rx = None;
+        }
+        if self.auto_save_conversations {
+            self.sync_from_shared_conversation();
+            if let Err(e) = self.save_conversation() {
+                crate::utils::logger::warn(&format!("Couldn't save the conversation: {}", e));
+            }
+        }
+        Storage::flush();
+    }
+
+    /// Model list saved by an earlier fetch, if it's recent enough
+    fn stored_models(provider: &str) -> Option<Vec<String>> {
+        Storage::with(|s| s.cache_get(MODELS_CACHE, provider))
+            .ok()
+            .flatten()
+    }
+
+    /// Show the model list from the last run until the fresh one arrives
+    fn show_stored_models(provider: &str, cache: &Mutex<Option<Vec<String>>>) {
+        if let Some(stored) = Self::stored_models(provider)
+            && let Ok(mut cache) = cache.lock()
+        {
+            *cache = Some(stored);
+        }
+    }
+
+    /// Save a fetched model list for the next run (error placeholders aren't saved)
+    fn store_models(provider: &str, models: &[String]) {
+        if models.is_empty() || models.iter().any(|m| m.starts_with("⚠️")) {
+            return;
+        }
+        let _ = Storage::with(|s| {
+            s.cache_put(MODELS_CACHE, provider, models, Some(STORED_MODELS_TTL))
+        });
+    }
+
     /// Get cached OpenRouter models, returning None if not cached
     pub fn get_cached_openrouter_models(&self) -> Option<Vec<String>> {
         match self.openrouter_models.lock() {
@@ -1077,16 +1569,14 @@ You have access to these tools for file operations and shell commands:
         let api_key = self.config.get_api_key();
         let models_cache = self.openrouter_models.clone();
 
-        // Clear existing cache first
-        if let Ok(mut cache) = models_cache.lock() {
-            *cache = None;
-        }
+        Self::show_stored_models("openrouter", &models_cache);
 
         // Use Handle::current to get current runtime handle
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 // Fetch models in background
                 let result = Self::fetch_openrouter_models_async(&api_key).await;
+                Self::store_models("openrouter", &result);
                 match models_cache.lock() {
                     Ok(mut cache) => *cache = Some(result),
                     Err(_) => {
@@ -1207,10 +1697,13 @@ You have access to these tools for file operations and shell commands:
         let models_cache = self.openai_models.clone();
         let api_key = self.config.get_api_key();
 
+        Self::show_stored_models("openai", &models_cache);
+
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 // Fetch models in background
                 let result = Self::fetch_openai_models_async(&api_key).await;
+                Self::store_models("openai", &result);
                 match models_cache.lock() {
                     Ok(mut cache) => *cache = Some(result),
                     Err(_) => {
@@ -1310,10 +1803,13 @@ You have access to these tools for file operations and shell commands:
         let models_cache = self.anthropic_models.clone();
         let api_key = self.config.get_api_key();
 
+        Self::show_stored_models("anthropic", &models_cache);
+
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 // Fetch models in background
                 let result = Self::fetch_anthropic_models_async(&api_key).await;
+                Self::store_models("anthropic", &result);
                 match models_cache.lock() {
                     Ok(mut cache) => *cache = Some(result),
                     Err(_) => {
@@ -1369,10 +1865,13 @@ You have access to these tools for file operations and shell commands:
         let models_cache = self.ollama_models.clone();
         let api_url = self.config.get_api_url();
 
+        Self::show_stored_models("ollama", &models_cache);
+
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 // Fetch models in background
                 let result = Self::fetch_ollama_models_async(&api_url).await;
+                Self::store_models("ollama", &result);
                 match models_cache.lock() {
                     Ok(mut cache) => *cache = Some(result),
                     Err(_) => {
@@ -1451,10 +1950,13 @@ You have access to these tools for file operations and shell commands:
         let models_cache = self.zai_models.clone();
         let api_key = self.config.get_api_key();
 
+        Self::show_stored_models("zai", &models_cache);
+
         if let Ok(handle) = tokio::runtime::Handle::try_current() {
             handle.spawn(async move {
                 // Fetch models in background
                 let result = Self::fetch_zai_models_async(&api_key).await;
+                Self::store_models("zai", &result);
                 match models_cache.lock() {
                     Ok(mut cache) => *cache = Some(result),
                     Err(_) => {
@@ -1718,21 +2220,85 @@ You have access to these tools for file operations and shell commands:
 
     /// Save current conversation to disk
     pub fn save_conversation(&mut self) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        let current_dir = std::env::current_dir()?;
+        self.hold_session_lock(&current_dir)?;
         if let Some(ref mut conv) = self.current_conversation {
             conv.update_duration();
-            let current_dir = std::env::current_dir()?;
-            conv.save(&current_dir)?;
+            save_conversation_in(conv, &current_dir)?;
         }
         Ok(())
     }
 
+    /// Lock the current conversation's file, unless it's locked already
+    fn hold_session_lock(&mut self, dir: &Path) -> Result<()> {
+        let Some(ref conv) = self.current_conversation else {
+            return Ok(());
+        };
+        let path = conv.get_file_path(dir);
+        if self
+            .session_lock
+            .as_ref()
+            .is_some_and(|lock| lock.path() == file_lock::lock_path(&path))
+        {
+            return Ok(());
+        }
+        match FileLock::try_acquire(&path)? {
+            Some(lock) => {
+                self.session_lock = Some(lock);
+                Ok(())
+            }
+            None => Err(SessionInUse {
+                holder: file_lock::holder(&path),
+            }
+            .into()),
+        }
+    }
+
     /// Load a conversation from disk
+    ///
+    /// Fails with [`SessionInUse`] when another instance has it open; it can
+    /// then be opened with [`App::load_conversation_read_only`].
     pub fn load_conversation(&mut self, conversation_id: &str) -> Result<()> {
-        use crate::utils::chat::MessageType;
         use crate::utils::conversation::Conversation;
 
         let current_dir = std::env::current_dir()?;
         let conversation = Conversation::load(&current_dir, conversation_id)?;
+        let path = conversation.get_file_path(&current_dir);
+        let Some(lock) = FileLock::try_acquire(&path)? else {
+            return Err(SessionInUse {
+                holder: file_lock::holder(&path),
+            }
+            .into());
+        };
+        self.session_lock = Some(lock);
+        self.read_only = false;
+        self.open_conversation(conversation);
+        Ok(())
+    }
+
+    /// Load a conversation another instance has open, without ever saving it
+    pub fn load_conversation_read_only(&mut self, conversation_id: &str) -> Result<()> {
+        use crate::utils::conversation::Conversation;
+
+        let current_dir = std::env::current_dir()?;
+        let conversation = Conversation::load(&current_dir, conversation_id)?;
+        self.session_lock = None;
+        self.read_only = true;
+        self.open_conversation(conversation);
+        Ok(())
+    }
+
+    /// Whether the current conversation was opened read-only
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Make `conversation` current and rebuild the chat history from it
+    fn open_conversation(&mut self, conversation: crate::utils::conversation::Conversation) {
+        use crate::utils::chat::MessageType;
 
         // Convert conversation messages to chat messages
         self.messages.clear();
@@ -1741,16 +2307,20 @@ You have access to these tools for file operations and shell commands:
                 "user" => {
                     if let Some(content) = &msg.content {
                         if let Some(text) = content.as_str() {
-                            self.messages
-                                .push(ChatMessage::new(MessageType::User, text.to_string()));
+                            self.messages.push(
+                                ChatMessage::new(MessageType::User, text.to_string())
+                                    .with_timestamp(msg.timestamp.into()),
+                            );
                         }
                     }
                 }
                 "assistant" => {
                     if let Some(content) = &msg.content {
                         if let Some(text) = content.as_str() {
-                            self.messages
-                                .push(ChatMessage::new(MessageType::Arula, text.to_string()));
+                            self.messages.push(
+                                ChatMessage::new(MessageType::Arula, text.to_string())
+                                    .with_timestamp(msg.timestamp.into()),
+                            );
                         }
                     }
 
@@ -1791,16 +2361,21 @@ You have access to these tools for file operations and shell commands:
             }
         }
 
+        // Orient the model with the brief before the rest of the history
+        if let Some(brief) = &conversation.metadata.brief {
+            self.messages.insert(
+                0,
+                ChatMessage::new(MessageType::System, crate::utils::brief::seed_message(brief)),
+            );
+        }
+
         self.current_conversation = Some(conversation);
-        Ok(())
     }
 
-    /// Track user message in conversation
-    pub fn track_user_message(&mut self, content: &str) {
-        self.ensure_conversation();
-
-        // FIRST: Sync FROM shared_conversation TO current_conversation
-        // Only copy if shared has MORE messages (i.e., new AI responses from tokio task)
+    /// Pick up messages the background task added to the shared conversation
+    ///
+    /// Only copies if shared has MORE messages (i.e., new AI responses from tokio task)
+    fn sync_from_shared_conversation(&mut self) {
         if let Ok(shared) = self.shared_conversation.lock() {
             if let Some(ref shared_conv) = *shared {
                 if let Some(ref mut conv) = self.current_conversation {
@@ -1816,6 +2391,14 @@ You have access to these tools for file operations and shell commands:
                 }
             }
         }
+    }
+
+    /// Track user message in conversation
+    pub fn track_user_message(&mut self, content: &str) {
+        self.ensure_conversation();
+
+        // FIRST: Sync FROM shared_conversation TO current_conversation
+        self.sync_from_shared_conversation();
 
         // THEN: Add user message to current_conversation
         if let Some(ref mut conv) = self.current_conversation {
@@ -1917,6 +2500,95 @@ You have access to these tools for file operations and shell commands:
         let endpoint = provider_config.api_url.clone().unwrap_or_default();
 
         self.current_conversation = Some(Conversation::new(model, provider, endpoint));
+        self.session_lock = None;
+        self.read_only = false;
+    }
+
+    /// Branch the session at `index` in the message history
+    ///
+    /// Messages after `index` are dropped and a new conversation is started
+    /// from the kept user and assistant messages, so the original stays
+    /// saved as it was.
+    pub fn branch_conversation(&mut self, index: usize) {
+        use crate::utils::chat::MessageType;
+
+        let parent = self
+            .current_conversation
+            .as_ref()
+            .map(|conv| (conv.metadata.title.clone(), conv.metadata.conversation_id.clone()));
+        self.messages.truncate(index + 1);
+        self.new_conversation();
+
+        if let Some(ref mut conv) = self.current_conversation {
+            if let Some((title, parent_id)) = parent {
+                conv.set_title(format!("Branch of {}", title));
+                conv.metadata.parent_id = Some(parent_id);
+                conv.metadata.branch_point = Some(format!("message {}", index + 1));
+            }
+            conv.add_tag("branch".to_string());
+            for msg in &self.messages {
+                match msg.message_type {
+                    MessageType::User => {
+                        conv.add_user_message(msg.content.clone());
+                    }
+                    MessageType::Arula => {
+                        conv.add_assistant_message(msg.content.clone(), None);
+                    }
+                    _ => {}
+                }
+            }
+
+            if let Ok(mut shared) = self.shared_conversation.lock() {
+                *shared = Some(conv.clone());
+            }
+        }
+
+        if self.auto_save_conversations {
+            let _ = self.save_conversation();
+        }
+    }
+
+    /// Mark the current point of the conversation so it can be branched from
+    /// later; returns the checkpoint's name
+    pub fn create_checkpoint(&mut self, name: Option<&str>) -> Result<String> {
+        self.ensure_conversation();
+        self.sync_from_shared_conversation();
+
+        let conv = self
+            .current_conversation
+            .as_mut()
+            .expect("conversation was just ensured");
+        let name = conv.add_checkpoint(name)?.name.clone();
+
+        if let Ok(mut shared) = self.shared_conversation.lock() {
+            *shared = Some(conv.clone());
+        }
+        // Checkpoints are always saved, since branching reads them back
+        self.save_conversation()?;
+        Ok(name)
+    }
+
+    /// Branch the session from a checkpoint of the current conversation
+    ///
+    /// The branch is saved as a new conversation and becomes the current one;
+    /// returns its ID.
+    pub fn branch_from_checkpoint(&mut self, checkpoint: &str) -> Result<String> {
+        self.sync_from_shared_conversation();
+        let Some(ref conv) = self.current_conversation else {
+            anyhow::bail!("No conversation to branch from yet");
+        };
+
+        let branch = conv.branch_from(checkpoint)?;
+        let id = branch.metadata.conversation_id.clone();
+        save_conversation_in(&branch, &std::env::current_dir()?)?;
+
+        if let Ok(mut shared) = self.shared_conversation.lock() {
+            *shared = Some(branch.clone());
+        }
+        self.session_lock = None;
+        self.read_only = false;
+        self.open_conversation(branch);
+        Ok(id)
     }
 
     /// Build built-in tools information for the AI
@@ -1979,10 +2651,84 @@ You have access to these tools for file operations and shell commands:
         info.push_str("- `question` (string, required)\n");
         info.push_str("  Example: `ask_question(question=\"Which file should I edit?\")`\n\n");
 
+        info.push_str("11) run_tests — run the project's test suite\n");
+        info.push_str("- `path` (string, optional) — project directory (default: \".\")\n");
+        info.push_str("- `filter` (string, optional) — only run matching tests\n");
+        info.push_str("- `command` (string, optional) — override the detected test command\n");
+        info.push_str("  Example: `run_tests(filter=\"config\")`\n\n");
+
+        info.push_str("12) git_commit — stage files and draft a commit message (the user commits with /commit)\n");
+        info.push_str("- `files` (array, optional) — files to stage (default: staged or tracked changes)\n");
+        info.push_str("- `message` (string, optional) — commit message (default: drafted from the diff)\n");
+        info.push_str("  Example: `git_commit(files=[\"src/lib.rs\"])`\n\n");
+
+        info.push_str("13) find_todos — list TODO/FIXME/HACK comments by priority\n");
+        info.push_str("- `path` (string, optional) — directory to scan (default: \".\")\n");
+        info.push_str("- `tags` (array, optional) — tags to look for (default: TODO, FIXME, HACK)\n");
+        info.push_str("- `max_results` (number, optional) — item cap (default: 100)\n");
+        info.push_str("  Example: `find_todos(path=\"src\", max_results=20)`\n\n");
+
+        info.push_str("14) find_symbol — find function, method and type definitions\n");
+        info.push_str("- `name` (string, required) — symbol name or `Type::method`\n");
+        info.push_str("- `kind` (string, optional) — function, method, struct, enum, trait, interface, class, type, ...\n");
+        info.push_str("- `exact` (boolean, optional) — only exact name matches\n");
+        info.push_str("- `include_source` (boolean, optional) — return each definition's code\n");
+        info.push_str("  Example: `find_symbol(name=\"GitOps::commit\", include_source=true)`\n\n");
+
+        info.push_str("15) remember — store a fact for future sessions\n");
+        info.push_str("- `fact` (string, required) — one short sentence\n");
+        info.push_str("- `scope` (string, optional) — \"user\" or \"project\" (default: project)\n");
+        info.push_str("- `tags` (array, optional) — keywords to find it by\n");
+        info.push_str("  Example: `remember(fact=\"User prefers tabs\", scope=\"user\")`\n\n");
+
+        info.push_str("16) recall — look up remembered facts\n");
+        info.push_str("- `query` (string, optional) — words to look for (default: newest facts)\n");
+        info.push_str("- `scope` (string, optional) — \"user\" or \"project\" (default: both)\n");
+        info.push_str("- `limit` (number, optional) — fact cap (default: 10)\n");
+        info.push_str("  Example: `recall(query=\"test command\")`\n\n");
+
         info
     }
 }
 
+/// Save a conversation under `dir` and add it to the session index
+fn save_conversation_in(
+    conversation: &crate::utils::conversation::Conversation,
+    dir: &Path,
+) -> Result<()> {
+    conversation.save(dir)?;
+    if let Err(e) = Storage::with(|s| s.index_session(conversation, dir)) {
+        debug_print(&format!("DEBUG: Failed to index conversation: {}", e));
+    }
+    Ok(())
+}
+
+/// The alias and the rest of a message starting with `@alias`, when
+/// `alias` is one of the configured model aliases
+fn split_model_override<'a>(message: &'a str, config: &Config) -> Option<(&'a str, &'a str)> {
+    let (alias, rest) = message.strip_prefix('@')?.split_once(char::is_whitespace)?;
+    if !config.model_aliases.contains_key(alias) || rest.trim().is_empty() {
+        return None;
+    }
+    Some((alias, rest.trim_start()))
+}
+
+/// Send text held back for response transforms, transformed
+fn flush_held_text(
+    scripts: &Scripts,
+    held_text: &mut String,
+    accumulated_text: &mut String,
+    tx: &mpsc::UnboundedSender<AiResponse>,
+) {
+    if held_text.is_empty() {
+        return;
+    }
+    let text = scripts.transform_response(&std::mem::take(held_text));
+    accumulated_text.push_str(&text);
+    log_ai_response_chunk(&text);
+    let _ = tx.send(AiResponse::AgentStreamText(text));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1994,6 +2740,7 @@ mod tests {
         App {
             config: Config::default(),
             agent_client: None,
+            pending_init_message: None,
             messages: Vec::new(),
             ai_response_rx: None,
             current_streaming_message: None,
@@ -2002,6 +2749,9 @@ mod tests {
             pending_tool_calls: None,
             debug: false,
             cancellation_token: CancellationToken::new(),
+            steering: Steering::new(),
+            context_files: Vec::new(),
+            success_criteria: Vec::new(),
             current_task_handle: None,
             openrouter_models: Arc::new(Mutex::new(None)),
             openai_models: Arc::new(Mutex::new(None)),
@@ -2009,12 +2759,17 @@ mod tests {
             ollama_models: Arc::new(Mutex::new(None)),
             zai_models: Arc::new(Mutex::new(None)),
             current_conversation: None,
+            session_lock: None,
+            read_only: false,
             auto_save_conversations: false,
             tracking_rx: Some(tracking_rx),
             tracking_tx: None,
             shared_conversation: Arc::new(Mutex::new(None)),
             cached_tool_registry: None,
             git_state_tracker: GitStateTracker::new("."),
+            hooks: Hooks::default(),
+            scripts: Scripts::default(),
+            plugins: WasmPlugins::default(),
         }
     }
 
@@ -2029,6 +2784,22 @@ mod tests {
         assert!(!app.debug);
     }
 
+    #[test]
+    fn test_split_model_override() {
+        let mut config = Config::default();
+        config
+            .model_aliases
+            .insert("fast".to_string(), "groq/llama-3.1-8b".to_string());
+
+        assert_eq!(
+            split_model_override("@fast  summarize this", &config),
+            Some(("fast", "summarize this"))
+        );
+        assert_eq!(split_model_override("@slow summarize this", &config), None);
+        assert_eq!(split_model_override("@fast", &config), None);
+        assert_eq!(split_model_override("ask @fast later", &config), None);
+    }
+
     #[test]
     fn test_debug_print() {
         // Should not panic with debug flag unset
@@ -2060,6 +2831,7 @@ mod tests {
             tool_call_id: "call_1".to_string(),
             success: true,
             result: json!("hello"),
+            duration_ms: None,
         };
         let stream_end = AiResponse::AgentStreamEnd;
 
@@ -2080,6 +2852,7 @@ mod tests {
         let app = App {
             config,
             agent_client: None,
+            pending_init_message: None,
             messages: Vec::new(),
             ai_response_rx: None,
             current_streaming_message: None,
@@ -2088,6 +2861,9 @@ mod tests {
             pending_tool_calls: None,
             debug: true,
             cancellation_token: CancellationToken::new(),
+            steering: Steering::new(),
+            context_files: Vec::new(),
+            success_criteria: Vec::new(),
             current_task_handle: None,
             openrouter_models: Arc::new(Mutex::new(None)),
             openai_models: Arc::new(Mutex::new(None)),
@@ -2095,12 +2871,17 @@ mod tests {
             ollama_models: Arc::new(Mutex::new(None)),
             zai_models: Arc::new(Mutex::new(None)),
             current_conversation: None,
+            session_lock: None,
+            read_only: false,
             auto_save_conversations: false,
             tracking_rx: Some(tracking_rx),
             tracking_tx: None,
             shared_conversation: Arc::new(Mutex::new(None)),
             cached_tool_registry: None,
             git_state_tracker: GitStateTracker::new("."),
+            hooks: Hooks::default(),
+            scripts: Scripts::default(),
+            plugins: WasmPlugins::default(),
         };
 
         assert_eq!(app.config.get_model(), "test-model");
@@ -294,7 +294,8 @@ mod tests {
         }
         drop(tx); // Close the sender
 
-        let stream = channels::batch_collector(rx, 3, std::time::Duration::from_millis(100));
+        let stream = channels::batch_collector(rx, 3, std::time::Duration::from_millis(100)).await;
+        let mut stream = Box::pin(stream);
         let first_batch = stream.next().await.unwrap();
 
         assert_eq!(first_batch.len(), 3);
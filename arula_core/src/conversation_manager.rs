@@ -3,11 +3,14 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use uuid::Uuid;
 
 use crate::session_manager::UiEvent;
+use crate::utils::file_lock::{self, FileLock, SessionInUse};
 
 /// Metadata for a saved conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +89,8 @@ pub struct SavedConversation {
 #[derive(Debug)]
 pub struct ConversationManager {
     storage_dir: PathBuf,
+    /// Conversations this instance writes, locked against other instances
+    locks: Mutex<HashMap<Uuid, FileLock>>,
 }
 
 impl ConversationManager {
@@ -100,7 +105,57 @@ impl ConversationManager {
         fs::create_dir_all(&storage_dir)
             .with_context(|| format!("Failed to create conversations directory: {:?}", storage_dir))?;
 
-        Ok(Self { storage_dir })
+        Ok(Self::in_dir(storage_dir))
+    }
+
+    /// Creates a conversation manager storing conversations in `storage_dir`.
+    pub fn in_dir(storage_dir: PathBuf) -> Self {
+        Self {
+            storage_dir,
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn file_path(&self, id: Uuid) -> PathBuf {
+        self.storage_dir.join(format!("{}.json", id))
+    }
+
+    /// Locks a conversation for this instance, unless it holds the lock already.
+    ///
+    /// Fails with [`SessionInUse`] when another instance has it open.
+    fn hold(&self, id: Uuid, path: &Path) -> Result<()> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        if locks.contains_key(&id) {
+            return Ok(());
+        }
+        match FileLock::try_acquire(path)? {
+            Some(lock) => {
+                locks.insert(id, lock);
+                Ok(())
+            }
+            None => Err(SessionInUse {
+                holder: file_lock::holder(path),
+            }
+            .into()),
+        }
+    }
+
+    /// Lets other instances write a conversation again, e.g. when its tab closes.
+    pub fn release(&self, id: Uuid) {
+        self.locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id);
+    }
+
+    /// Loads a conversation by ID and locks it for this instance.
+    ///
+    /// Fails with [`SessionInUse`] when another instance has it open; it can
+    /// still be read with [`ConversationManager::load_conversation`].
+    pub fn open_conversation(&self, id: Uuid) -> Result<SavedConversation> {
+        let conversation = self.load_conversation(id)?;
+        self.hold(id, &self.file_path(id))?;
+        Ok(conversation)
     }
 
     /// Saves a conversation with the given ID and events.
@@ -112,7 +167,8 @@ impl ConversationManager {
         let metadata = ConversationMetadata::from_events(id, events, model);
         let conversation = SavedConversation { metadata, events: events.to_vec() };
 
-        let file_path = self.storage_dir.join(format!("{}.json", id));
+        let file_path = self.file_path(id);
+        self.hold(id, &file_path)?;
         let json = serde_json::to_string_pretty(&conversation)
             .context("Failed to serialize conversation")?;
 
@@ -124,7 +180,7 @@ impl ConversationManager {
 
     /// Loads a conversation by ID.
     pub fn load_conversation(&self, id: Uuid) -> Result<SavedConversation> {
-        let file_path = self.storage_dir.join(format!("{}.json", id));
+        let file_path = self.file_path(id);
         
         if !file_path.exists() {
             return Err(anyhow::anyhow!("Conversation not found: {}", id));
@@ -172,12 +228,15 @@ impl ConversationManager {
 
     /// Deletes a conversation by ID.
     pub fn delete_conversation(&self, id: Uuid) -> Result<()> {
-        let file_path = self.storage_dir.join(format!("{}.json", id));
+        let file_path = self.file_path(id);
         
         if file_path.exists() {
+            self.hold(id, &file_path)?;
             fs::remove_file(&file_path)
                 .context("Failed to delete conversation file")?;
+            let _ = fs::remove_file(file_lock::lock_path(&file_path));
         }
+        self.release(id);
 
         Ok(())
     }
@@ -187,7 +246,8 @@ impl ConversationManager {
         let mut conversation = self.load_conversation(metadata.id)?;
         conversation.metadata = metadata.clone();
 
-        let file_path = self.storage_dir.join(format!("{}.json", metadata.id));
+        let file_path = self.file_path(metadata.id);
+        self.hold(metadata.id, &file_path)?;
         let json = serde_json::to_string_pretty(&conversation)
             .context("Failed to serialize conversation")?;
 
@@ -225,6 +285,28 @@ mod tests {
         assert_eq!(metadata.title, "Hello, how are you?");
     }
 
+    #[test]
+    fn test_open_conversation_is_exclusive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ours = ConversationManager::in_dir(dir.path().to_path_buf());
+        let theirs = ConversationManager::in_dir(dir.path().to_path_buf());
+        let id = Uuid::new_v4();
+        let events = vec![UiEvent::UserMessage {
+            content: "Hello".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        }];
+
+        ours.save_conversation(id, &events, "gpt-4".to_string()).unwrap();
+        let err = theirs.open_conversation(id).unwrap_err();
+        assert!(err.downcast_ref::<SessionInUse>().is_some());
+        assert!(theirs.save_conversation(id, &events, "gpt-4".to_string()).is_err());
+        // Reading it is still fine
+        assert_eq!(theirs.load_conversation(id).unwrap().events.len(), 1);
+
+        ours.release(id);
+        assert!(theirs.open_conversation(id).is_ok());
+    }
+
     #[test]
     fn test_title_truncation() {
         let id = Uuid::new_v4();
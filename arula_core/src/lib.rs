@@ -6,9 +6,12 @@ pub mod app;
 pub mod async_optimizations;
 pub mod conversation_manager;
 pub mod init;
+pub mod platform;
 pub mod prelude;
 pub mod profiling;
+pub mod sdk;
 pub mod session_manager;
+pub mod storage;
 pub mod tools;
 pub mod utils;
 
@@ -21,6 +24,7 @@ pub use api::api::Usage;
 pub use app::App;
 pub use conversation_manager::{ConversationManager, ConversationMetadata, SavedConversation};
 pub use prelude::*;
+pub use sdk::{ArulaAgent, ArulaAgentBuilder};
 pub use session_manager::{SessionManager, UiEvent};
 pub use tools::*;
 pub use utils::*;
@@ -56,6 +60,16 @@ pub enum StreamEvent {
         question: String,
         options: Option<Vec<String>>,
     },
+    /// The run was paused because it looks stuck in a loop
+    LoopDetected {
+        diagnosis: String,
+    },
+    /// Older messages were summarized to fit the context window and the
+    /// request was sent again
+    ContextCompacted {
+        replaced: usize,
+        summary: String,
+    },
     Finished,
     Error(String),
 }
@@ -76,6 +90,7 @@ pub trait Backend: Send + Sync + Clone + 'static {
         prompt: String,
         history: Option<Vec<api::api::ChatMessage>>,
         config: SessionConfig,
+        steering: api::steering::Steering,
     ) -> anyhow::Result<Pin<Box<dyn Stream<Item = StreamEvent> + Send>>>;
 }
 
@@ -106,8 +121,9 @@ impl<B: Backend> SessionRunner<B> {
         prompt: String,
         history: Option<Vec<api::api::ChatMessage>>,
         config: SessionConfig,
+        steering: api::steering::Steering,
     ) -> anyhow::Result<Pin<Box<dyn Stream<Item = StreamEvent> + Send>>> {
-        self.backend.stream_session(prompt, history, config)
+        self.backend.stream_session(prompt, history, config, steering)
     }
 }
 
@@ -127,7 +143,9 @@ impl AgentBackend {
             .debug(utils::debug::is_debug_enabled())
             .build();
 
-        let tool_registry = tools::tools::create_basic_tool_registry();
+        let mut tool_registry = tools::tools::create_basic_tool_registry();
+        utils::scripting::Scripts::load().register_tools(&mut tool_registry);
+        tools::wasm_plugins::WasmPlugins::load().register_tools(&mut tool_registry);
 
         let client = api::agent_client::AgentClient::new_with_registry(
             config.active_provider.clone(),
@@ -182,8 +200,9 @@ impl Backend for AgentBackend {
         prompt: String,
         history: Option<Vec<api::api::ChatMessage>>,
         config: SessionConfig,
+        steering: api::steering::Steering,
     ) -> anyhow::Result<Pin<Box<dyn Stream<Item = StreamEvent> + Send>>> {
-        let client = self.client.clone();
+        let client = self.client.clone().with_steering(steering);
         let model = config.model.clone();
         let prompt = prompt.clone();
         let stream = async_stream::stream! {
@@ -206,6 +225,8 @@ impl Backend for AgentBackend {
                             ContentBlock::ToolResult { tool_call_id, result } => StreamEvent::ToolResult { tool_call_id, result },
                             ContentBlock::BashOutputLine { tool_call_id, line, is_stderr } => StreamEvent::BashOutputLine { tool_call_id, line, is_stderr },
                             ContentBlock::AskQuestion { tool_call_id, question, options } => StreamEvent::AskQuestion { tool_call_id, question, options },
+                            ContentBlock::LoopDetected { diagnosis } => StreamEvent::LoopDetected { diagnosis },
+                            ContentBlock::ContextCompacted { replaced, summary } => StreamEvent::ContextCompacted { replaced, summary },
                             ContentBlock::Error { error } => StreamEvent::Error(error),
                         };
                         yield ev;
@@ -0,0 +1,155 @@
+//! Platform abstraction shared by the CLI, the desktop app and Android
+//!
+//! Tools reach the terminal, filesystem, shell, config location and
+//! notifications through `PlatformBackend` instead of cfg-gated code.
+//! `DesktopPlatform` (used by the CLI and desktop app) is the default; an
+//! embedder such as the Android app installs its own with `set` before
+//! running the agent.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, LazyLock, RwLock};
+
+static CURRENT: LazyLock<RwLock<Arc<dyn PlatformBackend>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(DesktopPlatform)));
+
+/// What ARULA needs from the platform it runs on
+pub trait PlatformBackend: Send + Sync {
+    /// Short name, e.g. "desktop" or "android"
+    fn name(&self) -> &str;
+
+    /// Size of the platform's own terminal in columns and rows, when it has one
+    fn terminal_size(&self) -> Option<(u16, u16)> {
+        None
+    }
+
+    /// Directory relative paths start from
+    fn working_dir(&self) -> PathBuf;
+
+    /// `path` as tools should open it, or why they may not
+    fn resolve_path(&self, path: &Path) -> Result<PathBuf> {
+        Ok(self.working_dir().join(path))
+    }
+
+    /// A command that runs `script` in the platform's shell
+    fn shell_command(&self, script: &str) -> Command;
+
+    /// Where ARULA's config.json is kept
+    fn config_path(&self) -> PathBuf;
+
+    /// Show a notification outside the app
+    fn notify(&self, title: &str, message: &str) -> Result<()>;
+
+    /// Why the tool called `name` can't run here, or `None` when it can
+    fn unsupported_tool(&self, _name: &str) -> Option<String> {
+        None
+    }
+}
+
+/// The backend in use
+pub fn current() -> Arc<dyn PlatformBackend> {
+    CURRENT.read().unwrap().clone()
+}
+
+/// Use `backend` from now on
+pub fn set(backend: Arc<dyn PlatformBackend>) {
+    *CURRENT.write().unwrap() = backend;
+}
+
+/// Linux, macOS and Windows, for the CLI and the desktop app
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DesktopPlatform;
+
+impl PlatformBackend for DesktopPlatform {
+    fn name(&self) -> &str {
+        "desktop"
+    }
+
+    fn working_dir(&self) -> PathBuf {
+        std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    }
+
+    fn shell_command(&self, script: &str) -> Command {
+        if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.args(["/C", script]);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(script);
+            command
+        }
+    }
+
+    fn config_path(&self) -> PathBuf {
+        // Use cross-platform home directory detection
+        let home = std::env::var("HOME")
+            .or_else(|_| std::env::var("USERPROFILE")) // Windows
+            .unwrap_or_else(|_| ".".to_string());
+        PathBuf::from(home).join(".arula").join("config.json")
+    }
+
+    fn notify(&self, title: &str, message: &str) -> Result<()> {
+        let mut backend = crate::utils::notifications::detect_backend()
+            .ok_or_else(|| anyhow::anyhow!("Notifications aren't supported here"))?;
+        backend.notify(title, message)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sandboxed;
+
+    impl PlatformBackend for Sandboxed {
+        fn name(&self) -> &str {
+            "sandboxed"
+        }
+
+        fn working_dir(&self) -> PathBuf {
+            PathBuf::from("/sandbox")
+        }
+
+        fn shell_command(&self, script: &str) -> Command {
+            let mut command = Command::new("/system/bin/sh");
+            command.arg("-c").arg(script);
+            command
+        }
+
+        fn config_path(&self) -> PathBuf {
+            self.working_dir().join("config.json")
+        }
+
+        fn notify(&self, _title: &str, _message: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn unsupported_tool(&self, name: &str) -> Option<String> {
+            (name == "git_commit").then(|| "no git".to_string())
+        }
+    }
+
+    #[test]
+    fn test_backend_defaults() {
+        let backend = Sandboxed;
+        assert_eq!(
+            backend.resolve_path(Path::new("src/main.rs")).unwrap(),
+            PathBuf::from("/sandbox/src/main.rs")
+        );
+        assert_eq!(backend.terminal_size(), None);
+        assert_eq!(
+            backend.unsupported_tool("git_commit").as_deref(),
+            Some("no git")
+        );
+        assert_eq!(backend.unsupported_tool("read_file"), None);
+        assert_eq!(backend.shell_command("ls").get_program(), "/system/bin/sh");
+
+        let desktop = DesktopPlatform;
+        assert!(desktop.config_path().ends_with(".arula/config.json"));
+        let program = desktop.shell_command("echo hi").get_program().to_owned();
+        assert!(program == "sh" || program == "cmd");
+    }
+}
@@ -31,7 +31,7 @@ pub use crate::api::agent::{ContentBlock, Tool, ToolResult, ToolSchema, ToolSche
 
 // Project context
 pub use crate::utils::project_context::{
-    detect_project, generate_auto_manifest, is_ai_enhanced, manifest_exists,
+    detect_project, generate_auto_manifest, generate_indexed_manifest, is_ai_enhanced, manifest_exists,
     DetectedProject, ProjectType, MANIFEST_MARKER_AI, MANIFEST_MARKER_AUTO,
 };
 
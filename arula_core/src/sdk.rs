@@ -0,0 +1,539 @@
+//! Embedding the ARULA agent in other Rust applications
+//!
+//! [`ArulaAgent`] runs the same agent loop as the CLI and desktop app: the
+//! model is sent the prompt, the tools it calls are run, and their results
+//! go back to it until it answers. The types here ([`Message`],
+//! [`AgentEvent`], [`ToolCallEvent`], [`ToolResultEvent`], [`RunOutput`])
+//! are the stable surface for embedders; the `api` module underneath may
+//! change between releases.
+//!
+//! ```no_run
+//! use arula_core::sdk::{AgentEvent, ArulaAgent};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let output = ArulaAgent::builder()
+//!     .provider("openai")
+//!     .model("gpt-4o-mini")
+//!     .api_key("sk-...")
+//!     .on_event(|event| {
+//!         if let AgentEvent::Text(text) = event {
+//!             print!("{}", text);
+//!         }
+//!     })
+//!     .run("List the Rust files in this directory")
+//!     .await?;
+//! println!("\n{} tool calls", output.tool_calls.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Without [`ArulaAgentBuilder::tools`] the agent gets the built-in tools
+//! except `ask_question`, which needs a UI to answer it.
+
+use anyhow::{Result, bail};
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::api::agent::{AgentOptionsBuilder, ContentBlock, Tool, ToolRegistry};
+use crate::api::agent_client::AgentClient;
+use crate::api::api::ChatMessage;
+use crate::api::trust::DATA_BLOCK_RULES;
+use crate::session_manager::DEFAULT_BASE_PROMPT;
+use crate::tools::tools::create_basic_tool_registry;
+use crate::utils::compaction::SUMMARY_HEADER;
+use crate::utils::config::Config;
+
+/// Tool calls a run may make before it's stopped
+const DEFAULT_MAX_TOOL_ITERATIONS: u32 = 100;
+
+/// A stream of events from one run
+pub type EventStream = Pin<Box<dyn Stream<Item = AgentEvent> + Send>>;
+
+type EventCallback = Arc<dyn Fn(&AgentEvent) + Send + Sync>;
+
+/// Who wrote a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+/// A message of the conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+
+    /// Stands in for the messages summarized in an
+    /// [`AgentEvent::ContextCompacted`] event, so later runs fit as well
+    pub fn summary(summary: &str) -> Self {
+        Self::user(format!("{}\n{}", SUMMARY_HEADER, summary))
+    }
+}
+
+impl From<&Message> for ChatMessage {
+    fn from(message: &Message) -> Self {
+        ChatMessage {
+            role: match message.role {
+                Role::User => "user",
+                Role::Assistant => "assistant",
+            }
+            .to_string(),
+            content: Some(message.content.clone()),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+}
+
+/// The model called a tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCallEvent {
+    pub id: String,
+    pub name: String,
+    /// The arguments, or the raw text as a string if it isn't valid JSON
+    pub arguments: Value,
+}
+
+/// A tool call finished
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolResultEvent {
+    pub id: String,
+    pub name: String,
+    pub success: bool,
+    pub output: Value,
+    pub error: Option<String>,
+    pub duration_ms: Option<u64>,
+}
+
+/// Something that happened during a run, in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum AgentEvent {
+    /// Part of the model's answer
+    Text(String),
+    /// Part of the model's reasoning, for models that show it
+    Reasoning(String),
+    ToolCall(ToolCallEvent),
+    ToolResult(ToolResultEvent),
+    /// A line a running shell command printed
+    ToolOutput {
+        id: String,
+        line: String,
+        is_stderr: bool,
+    },
+    /// The run was stopped because it looks stuck in a loop
+    LoopDetected(String),
+    /// The conversation didn't fit the model's context window, so older
+    /// messages were summarized and the request sent again
+    ContextCompacted {
+        /// Messages replaced by the summary
+        replaced: usize,
+        summary: String,
+    },
+    /// The request failed; the run ends
+    Error(String),
+}
+
+/// What a run produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunOutput {
+    /// The model's answer
+    pub text: String,
+    pub tool_calls: Vec<ToolCallEvent>,
+    pub tool_results: Vec<ToolResultEvent>,
+    /// Why the run was stopped early, if it was
+    pub loop_detected: Option<String>,
+}
+
+/// Turns the agent loop's content blocks into [`AgentEvent`]s
+#[derive(Default)]
+struct EventMapper {
+    /// Tool names by call id, for the results
+    names: HashMap<String, String>,
+}
+
+impl EventMapper {
+    fn map(&mut self, block: ContentBlock) -> Option<AgentEvent> {
+        Some(match block {
+            ContentBlock::Text { text } if text.is_empty() => return None,
+            ContentBlock::Text { text } => AgentEvent::Text(text),
+            ContentBlock::Reasoning { reasoning } => AgentEvent::Reasoning(reasoning),
+            ContentBlock::ToolCall {
+                id,
+                name,
+                arguments,
+            } => {
+                self.names.insert(id.clone(), name.clone());
+                let arguments =
+                    serde_json::from_str(&arguments).unwrap_or(Value::String(arguments));
+                AgentEvent::ToolCall(ToolCallEvent {
+                    id,
+                    name,
+                    arguments,
+                })
+            }
+            ContentBlock::ToolResult {
+                tool_call_id,
+                result,
+            } => AgentEvent::ToolResult(ToolResultEvent {
+                name: self.names.get(&tool_call_id).cloned().unwrap_or_default(),
+                id: tool_call_id,
+                success: result.success,
+                output: result.data,
+                error: result.error,
+                duration_ms: result.duration_ms,
+            }),
+            ContentBlock::BashOutputLine {
+                tool_call_id,
+                line,
+                is_stderr,
+            } => AgentEvent::ToolOutput {
+                id: tool_call_id,
+                line,
+                is_stderr,
+            },
+            // Only the ask_question tool asks, and it isn't given by default
+            ContentBlock::AskQuestion { .. } => return None,
+            ContentBlock::LoopDetected { diagnosis } => AgentEvent::LoopDetected(diagnosis),
+            ContentBlock::ContextCompacted { replaced, summary } => {
+                AgentEvent::ContextCompacted { replaced, summary }
+            }
+            ContentBlock::Error { error } => AgentEvent::Error(error),
+        })
+    }
+}
+
+/// Configures an [`ArulaAgent`]
+#[derive(Default)]
+pub struct ArulaAgentBuilder {
+    config: Option<Config>,
+    provider: Option<String>,
+    model: Option<String>,
+    api_key: Option<String>,
+    api_url: Option<String>,
+    system_prompt: Option<String>,
+    tools: Option<ToolRegistry>,
+    extra_tools: ToolRegistry,
+    max_tool_iterations: Option<u32>,
+    history: Vec<Message>,
+    callbacks: Vec<EventCallback>,
+}
+
+impl ArulaAgentBuilder {
+    /// Start from these settings instead of the defaults, e.g. the user's
+    /// `Config::load_or_default()`
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Provider to use: "openai", "anthropic", "ollama", "z.ai coding plan",
+    /// "openrouter" or "custom"
+    pub fn provider(mut self, provider: &str) -> Self {
+        self.provider = Some(provider.to_string());
+        self
+    }
+
+    pub fn model(mut self, model: &str) -> Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    /// Endpoint for the provider, for self-hosted or compatible APIs
+    pub fn api_url(mut self, api_url: &str) -> Self {
+        self.api_url = Some(api_url.to_string());
+        self
+    }
+
+    /// Replace ARULA's coding assistant prompt
+    pub fn system_prompt(mut self, prompt: &str) -> Self {
+        self.system_prompt = Some(prompt.to_string());
+        self
+    }
+
+    /// Tools the agent may call, instead of the built-in ones
+    pub fn tools(mut self, tools: ToolRegistry) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// Add a tool to the others
+    pub fn tool<T: Tool + 'static>(mut self, tool: T) -> Self {
+        self.extra_tools.register(tool);
+        self
+    }
+
+    pub fn max_tool_iterations(mut self, max: u32) -> Self {
+        self.max_tool_iterations = Some(max);
+        self
+    }
+
+    /// Continue an earlier conversation
+    pub fn history(mut self, history: Vec<Message>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Call `callback` with every event of every run, as it happens
+    pub fn on_event(mut self, callback: impl Fn(&AgentEvent) + Send + Sync + 'static) -> Self {
+        self.callbacks.push(Arc::new(callback));
+        self
+    }
+
+    pub fn build(self) -> Result<ArulaAgent> {
+        let mut config = self.config.unwrap_or_else(Config::default);
+        if let Some(provider) = &self.provider {
+            config.switch_provider(provider)?;
+        }
+        if let Some(model) = &self.model {
+            config.set_model(model);
+        }
+        if let Some(api_key) = &self.api_key {
+            config.set_api_key(api_key);
+        }
+        if let Some(api_url) = &self.api_url {
+            config.set_api_url(api_url);
+        }
+
+        let system_prompt = self
+            .system_prompt
+            .unwrap_or_else(|| format!("{}\n{}", DEFAULT_BASE_PROMPT, DATA_BLOCK_RULES));
+        let options = AgentOptionsBuilder::new()
+            .system_prompt(&system_prompt)
+            .model(&config.get_model())
+            .auto_execute_tools(true)
+            .max_tool_iterations(
+                self.max_tool_iterations
+                    .unwrap_or(DEFAULT_MAX_TOOL_ITERATIONS),
+            )
+            .build();
+
+        let mut tools = match self.tools {
+            Some(tools) => tools.detached(),
+            None => {
+                let mut tools = create_basic_tool_registry();
+                tools.unregister("ask_question");
+                tools
+            }
+        };
+        tools.extend(&self.extra_tools);
+
+        let client = AgentClient::new_with_registry(
+            config.active_provider.clone(),
+            config.get_api_url(),
+            config.get_api_key(),
+            config.get_model(),
+            options,
+            &config,
+            tools,
+        );
+        Ok(ArulaAgent {
+            client,
+            config,
+            history: self.history,
+            callbacks: self.callbacks,
+        })
+    }
+
+    /// Build the agent and run `prompt` once
+    pub async fn run(self, prompt: &str) -> Result<RunOutput> {
+        self.build()?.run(prompt).await
+    }
+}
+
+/// The ARULA agent loop, for embedding
+pub struct ArulaAgent {
+    client: AgentClient,
+    config: Config,
+    history: Vec<Message>,
+    callbacks: Vec<EventCallback>,
+}
+
+impl ArulaAgent {
+    pub fn builder() -> ArulaAgentBuilder {
+        ArulaAgentBuilder::default()
+    }
+
+    /// Run `prompt` to the end, calling the `on_event` callbacks along the
+    /// way; the prompt and answer are added to the history for the next run
+    pub async fn run(&mut self, prompt: &str) -> Result<RunOutput> {
+        let mut events = self.stream(prompt).await?;
+        let mut output = RunOutput::default();
+        let mut compacted = None;
+        while let Some(event) = events.next().await {
+            for callback in &self.callbacks {
+                callback(&event);
+            }
+            match event {
+                AgentEvent::Text(text) => output.text.push_str(&text),
+                AgentEvent::ToolCall(call) => output.tool_calls.push(call),
+                AgentEvent::ToolResult(result) => output.tool_results.push(result),
+                AgentEvent::LoopDetected(diagnosis) => output.loop_detected = Some(diagnosis),
+                AgentEvent::ContextCompacted { summary, .. } => compacted = Some(summary),
+                AgentEvent::Error(error) => bail!("{}", error),
+                AgentEvent::Reasoning(_) | AgentEvent::ToolOutput { .. } => {}
+            }
+        }
+        // Carry the summary forward so the next run fits as well
+        if let Some(summary) = compacted {
+            self.history = vec![Message::summary(&summary)];
+        }
+        self.history.push(Message::user(prompt));
+        self.history.push(Message::assistant(output.text.clone()));
+        Ok(output)
+    }
+
+    /// Run `prompt` and return its events as they happen; neither the
+    /// callbacks nor the history are involved
+    pub async fn stream(&self, prompt: &str) -> Result<EventStream> {
+        let history = self.history.iter().map(ChatMessage::from).collect();
+        let mut blocks = if self.config.get_streaming_enabled() {
+            self.client.query_streaming(prompt, Some(history)).await?
+        } else {
+            self.client
+                .query_non_streaming(prompt, Some(history))
+                .await?
+        };
+        let events = async_stream::stream! {
+            let mut mapper = EventMapper::default();
+            while let Some(block) = blocks.next().await {
+                if let Some(event) = mapper.map(block) {
+                    yield event;
+                }
+            }
+        };
+        Ok(Box::pin(events))
+    }
+
+    /// The conversation so far
+    pub fn history(&self) -> &[Message] {
+        &self.history
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Names of the tools the agent may call
+    pub fn tools(&self) -> Vec<String> {
+        let mut tools = self.client.get_available_tools_sync();
+        tools.sort();
+        tools
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::agent::{ToolResult, ToolSchema, ToolSchemaBuilder};
+    use async_trait::async_trait;
+
+    struct Shout;
+
+    #[async_trait]
+    impl Tool for Shout {
+        type Params = Value;
+        type Result = Value;
+
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn description(&self) -> &str {
+            "Upper-case the text"
+        }
+
+        fn schema(&self) -> ToolSchema {
+            ToolSchemaBuilder::new("shout", "Upper-case the text")
+                .param("text", "string")
+                .required("text")
+                .build()
+        }
+
+        async fn execute(&self, params: Value) -> Result<Value, String> {
+            Ok(Value::String(
+                params["text"].as_str().unwrap_or_default().to_uppercase(),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_builder_and_events() {
+        let agent = ArulaAgent::builder()
+            .provider("ollama")
+            .model("llama3")
+            .tool(Shout)
+            .history(vec![Message::user("hi"), Message::assistant("hello")])
+            .build()
+            .unwrap();
+        assert_eq!(agent.config().active_provider, "ollama");
+        assert_eq!(agent.config().get_model(), "llama3");
+        let tools = agent.tools();
+        assert!(tools.contains(&"shout".to_string()));
+        assert!(tools.contains(&"read_file".to_string()));
+        assert!(!tools.contains(&"ask_question".to_string()));
+        assert_eq!(ChatMessage::from(&agent.history()[1]).role, "assistant");
+
+        let only_shout = ArulaAgent::builder()
+            .tools(ToolRegistry::new())
+            .tool(Shout)
+            .build()
+            .unwrap();
+        assert_eq!(only_shout.tools(), vec!["shout".to_string()]);
+
+        let mut mapper = EventMapper::default();
+        assert_eq!(mapper.map(ContentBlock::text("")), None);
+        assert_eq!(
+            mapper.map(ContentBlock::tool_call(
+                "call_1".into(),
+                "shout".into(),
+                r#"{"text":"hi"}"#.into()
+            )),
+            Some(AgentEvent::ToolCall(ToolCallEvent {
+                id: "call_1".into(),
+                name: "shout".into(),
+                arguments: serde_json::json!({"text": "hi"}),
+            }))
+        );
+        let Some(AgentEvent::ToolResult(result)) = mapper.map(ContentBlock::tool_result(
+            "call_1".into(),
+            ToolResult::success(Value::String("HI".into())),
+        )) else {
+            panic!("expected a tool result");
+        };
+        assert_eq!(result.name, "shout");
+        assert!(result.success);
+    }
+}
@@ -8,10 +8,18 @@ use crate::api::models::{
     AnthropicFetcher, ModelCacheManager, ModelFetcher, OllamaFetcher, OpenAIFetcher,
     OpenRouterFetcher, ZaiFetcher,
 };
+use crate::api::steering::Steering;
+use crate::api::trust::DATA_BLOCK_RULES;
+use crate::tools::builtin::bash::BashResult;
+use crate::utils::brief::write_brief_for_history;
 use crate::utils::config::Config;
+use crate::utils::git_context::enrich_message;
+use crate::utils::hooks::{HookEvent, Hooks};
+use crate::utils::logger::LogLevel;
 use crate::{AgentBackend, SessionConfig, SessionRunner, StreamEvent};
 use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use tokio::runtime::Runtime;
@@ -59,7 +67,7 @@ fn read_project_manifest() -> Option<String> {
 }
 
 /// Default base prompt if ARULA_SYSTEM_PROMPT.md is not found
-const DEFAULT_BASE_PROMPT: &str = r#"# ARULA - Autonomous AI Interface
+pub(crate) const DEFAULT_BASE_PROMPT: &str = r#"# ARULA - Autonomous AI Interface
 
 You are ARULA, an advanced AI coding assistant designed for software engineering tasks.
 
@@ -113,6 +121,9 @@ fn build_system_prompt_with_manifest() -> String {
         ));
     }
 
+    // 3. Tool results are data, not instructions
+    prompt_parts.push(DATA_BLOCK_RULES.to_string());
+
     prompt_parts.join("\n")
 }
 
@@ -136,6 +147,8 @@ pub enum UiEvent {
     ToolCallResult(Uuid, String, bool, String),  // session_id, name, success, summary
     /// Bash output line streamed during command execution
     BashOutputLine(Uuid, String, String, bool), // session_id, tool_call_id, line, is_stderr
+    /// Exit code, run time and output of a finished shell command
+    CommandFinished(Uuid, String, BashResult), // session_id, tool_call_id, result
     /// Ask question - AI needs user input
     AskQuestion {
         session_id: Uuid,
@@ -143,12 +156,47 @@ pub enum UiEvent {
         question: String,
         options: Option<Vec<String>>,
     },
+    /// The run was paused because it looks stuck in a loop
+    LoopDetected(Uuid, String), // session_id, diagnosis
+    /// Older messages were summarized to fit the context window and the
+    /// request was sent again
+    ContextCompacted(Uuid, usize), // session_id, messages summarized
     StreamFinished(Uuid),
     StreamErrored(Uuid, String),
     /// Conversation starters generated
     ConversationStarters(Vec<String>),
     /// Generated title for the conversation
     ConversationTitle(String),
+    /// Title generated for a session from its first prompt
+    SessionTitle(Uuid, String), // session_id, title
+    /// Rolling brief written for a session while it was idle
+    SessionBrief(Uuid, String), // session_id, brief
+}
+
+impl UiEvent {
+    /// The session an event belongs to; `None` for app-wide events
+    pub fn session_id(&self) -> Option<Uuid> {
+        match self {
+            UiEvent::StreamStarted(id)
+            | UiEvent::Token(id, ..)
+            | UiEvent::Thinking(id, _)
+            | UiEvent::ToolCallStart(id, ..)
+            | UiEvent::ToolCallResult(id, ..)
+            | UiEvent::BashOutputLine(id, ..)
+            | UiEvent::CommandFinished(id, ..)
+            | UiEvent::LoopDetected(id, _)
+            | UiEvent::ContextCompacted(id, _)
+            | UiEvent::StreamFinished(id)
+            | UiEvent::StreamErrored(id, _)
+            | UiEvent::SessionTitle(id, _)
+            | UiEvent::SessionBrief(id, _) => Some(*id),
+            UiEvent::AskQuestion { session_id, .. } => Some(*session_id),
+            UiEvent::UserMessage { .. }
+            | UiEvent::AiMessage { .. }
+            | UiEvent::ConversationStarters(_)
+            | UiEvent::ConversationTitle(_) => None,
+        }
+    }
 }
 
 /// Manages AI streaming sessions and communication with UI layers.
@@ -164,6 +212,10 @@ pub struct SessionManager {
     model_cache: Arc<ModelCacheManager>,
     /// Active session cancellation tokens
     cancellation_tokens: Arc<Mutex<HashMap<Uuid, CancellationToken>>>,
+    /// Steering handles of running sessions
+    steering: Arc<Mutex<HashMap<Uuid, Steering>>>,
+    /// User commands run on agent events
+    hooks: Hooks,
 }
 
 impl SessionManager {
@@ -173,13 +225,17 @@ impl SessionManager {
         let runtime = Runtime::new()?;
         let (events, _) = broadcast::channel(128);
         let runner = SessionRunner::new(backend);
+        let hooks = Hooks::from_config(config);
+        hooks.fire(HookEvent::SessionStart, json!({ "frontend": "desktop" }));
         Ok(Self {
             runtime,
             events,
             runner,
             config: config.clone(),
-            model_cache: Arc::new(ModelCacheManager::new(30)), // 30 min TTL
+            model_cache: Arc::new(ModelCacheManager::persistent(30)), // 30 min TTL
             cancellation_tokens: Arc::new(Mutex::new(HashMap::new())),
+            steering: Arc::new(Mutex::new(HashMap::new())),
+            hooks,
         })
     }
 
@@ -193,9 +249,45 @@ impl SessionManager {
         let backend = AgentBackend::new(config, build_system_prompt_with_manifest())?;
         self.runner = SessionRunner::new(backend);
         self.config = config.clone();
+        self.hooks = Hooks::from_config(config);
         Ok(())
     }
 
+    /// Redirects a running session without stopping it; the message is added
+    /// to its context before the next model call. Returns false if the
+    /// session isn't running.
+    pub fn steer(&self, session_id: Uuid, message: &str) -> bool {
+        let Ok(handles) = self.steering.lock() else {
+            return false;
+        };
+        match handles.get(&session_id) {
+            Some(steering) => {
+                steering.send(message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Write a new rolling brief for an idle session in the background,
+    /// sent back as [`UiEvent::SessionBrief`]
+    pub fn write_brief(&self, session_id: Uuid, previous: Option<String>, history: Vec<ChatMessage>) {
+        let config = self.config.clone();
+        let tx = self.events.clone();
+        self.runtime.spawn(async move {
+            match write_brief_for_history(&config, previous.as_deref(), &history).await {
+                Ok(brief) => {
+                    let _ = tx.send(UiEvent::SessionBrief(session_id, brief));
+                }
+                Err(e) => crate::utils::logger::log_module(
+                    LogLevel::Warn,
+                    "session",
+                    &format!("Failed to write session brief: {}", e),
+                ),
+            }
+        });
+    }
+
     /// Signals that streaming should stop for the given session.
     /// This cancels the background task and sends a finished event.
     pub fn stop_stream(&self, session_id: Uuid) {
@@ -222,6 +314,10 @@ impl SessionManager {
             "mcp_call" => "MCP".to_string(),
             "visioneer" => "Vision".to_string(),
             "ask_question" => "Question".to_string(),
+            "run_tests" => "Tests".to_string(),
+            "git_commit" => "Commit".to_string(),
+            "find_todos" => "TODOs".to_string(),
+            "find_symbol" => "Symbols".to_string(),
             _ => name.to_string(),
         }
     }
@@ -305,6 +401,61 @@ impl SessionManager {
             result
         };
 
+        // Check for run_tests results - show pass/fail counts
+        if let Some(failing) = data.get("failing_tests").and_then(|f| f.as_array()) {
+            let passed = data.get("passed").and_then(|p| p.as_u64()).unwrap_or(0);
+            let failed = data.get("failed").and_then(|f| f.as_u64()).unwrap_or(0);
+            let mut summary = format!("{} passed, {} failed", passed, failed);
+            let names: Vec<&str> = failing.iter().filter_map(|n| n.as_str()).take(3).collect();
+            if !names.is_empty() {
+                summary.push_str(&format!(": {}", names.join(", ")));
+            }
+            return summary;
+        }
+
+        // Check for git_commit results - show the drafted subject
+        if data.get("conventional").is_some() {
+            let subject = data
+                .get("message")
+                .and_then(|m| m.as_str())
+                .and_then(|m| m.lines().next())
+                .unwrap_or("");
+            return first_line(&format!("Draft: {}", subject), 80);
+        }
+
+        // Check for find_symbol results - show count and the best match
+        if let (Some(matches), Some(total)) = (
+            data.get("matches").and_then(|m| m.as_array()),
+            data.get("total_found").and_then(|t| t.as_u64()),
+        ) {
+            let Some(best) = matches.first() else {
+                return "No definitions found".to_string();
+            };
+            let name = best.get("name").and_then(|n| n.as_str()).unwrap_or("");
+            let path = best.get("path").and_then(|p| p.as_str()).unwrap_or("");
+            let line = best.get("line").and_then(|l| l.as_u64()).unwrap_or(0);
+            return first_line(
+                &format!("{} definition(s): {} at {}:{}", total, name, path, line),
+                80,
+            );
+        }
+
+        // Check for find_todos results - show count and tag breakdown
+        if let (Some(total), Some(by_tag)) = (
+            data.get("total_found").and_then(|t| t.as_u64()),
+            data.get("by_tag").and_then(|b| b.as_object()),
+        ) {
+            let tags: Vec<String> = by_tag
+                .iter()
+                .map(|(tag, count)| format!("{} {}", count, tag))
+                .collect();
+            return if tags.is_empty() {
+                "No TODOs found".to_string()
+            } else {
+                first_line(&format!("{} found: {}", total, tags.join(", ")), 80)
+            };
+        }
+
         // Check for bash/shell command results with exit_code structure
         // Check for exit_code field (bash command result)
         if let Some(exit_code) = data.get("exit_code").and_then(|c| c.as_i64()) {
@@ -569,13 +720,13 @@ impl SessionManager {
     }
 
     /// Generate a conversation title from the first user message
-    fn generate_conversation_title(tx: broadcast::Sender<UiEvent>, prompt: String) {
+    fn generate_conversation_title(tx: broadcast::Sender<UiEvent>, session_id: Uuid, prompt: String) {
         // Use a simple heuristic-based title generation
         // This is faster than using AI and works well for most cases
         
         // Skip empty or very short messages
         if prompt.trim().len() < 3 {
-            let _ = tx.send(UiEvent::ConversationTitle("New Chat".to_string()));
+            let _ = tx.send(UiEvent::SessionTitle(session_id, "New Chat".to_string()));
             return;
         }
 
@@ -586,7 +737,7 @@ impl SessionManager {
         ];
         let prompt_lower = prompt.trim().to_lowercase();
         if GREETINGS.iter().any(|g| prompt_lower.starts_with(g) || prompt_lower.contains(g)) {
-            let _ = tx.send(UiEvent::ConversationTitle("New Chat".to_string()));
+            let _ = tx.send(UiEvent::SessionTitle(session_id, "New Chat".to_string()));
             return;
         }
 
@@ -598,7 +749,7 @@ impl SessionManager {
             .collect();
 
         if words.is_empty() {
-            let _ = tx.send(UiEvent::ConversationTitle("New Chat".to_string()));
+            let _ = tx.send(UiEvent::SessionTitle(session_id, "New Chat".to_string()));
             return;
         }
 
@@ -629,7 +780,7 @@ impl SessionManager {
             title = chars.into_iter().collect();
         }
 
-        let _ = tx.send(UiEvent::ConversationTitle(title));
+        let _ = tx.send(UiEvent::SessionTitle(session_id, title));
     }
 
     /// Starts a streaming session for the given prompt with conversation history.
@@ -657,15 +808,32 @@ impl SessionManager {
 
         let tokens_ref = self.cancellation_tokens.clone();
 
+        // Steering sent while this session runs goes to its next iteration
+        let steering = Steering::new();
+        if let Ok(mut handles) = self.steering.lock() {
+            handles.insert(session_id, steering.clone());
+        }
+        let steering_ref = self.steering.clone();
+        let git_enrichment = self.config.get_git_enrichment_enabled();
+        let hooks = self.hooks.clone();
+
         self.runtime.spawn(async move {
             let _ = tx.send(UiEvent::StreamStarted(session_id));
 
             // If this is a new conversation, generate a title from the first user message
             if is_new_conversation {
-                Self::generate_conversation_title(tx.clone(), prompt.clone());
+                Self::generate_conversation_title(tx.clone(), session_id, prompt.clone());
             }
 
-            match runner.stream_session(prompt, history, session_config) {
+            // Attach git history of referenced files (the UI keeps the prompt as typed)
+            let prompt = if git_enrichment {
+                let root = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+                enrich_message(&root, &prompt).await
+            } else {
+                prompt
+            };
+
+            match runner.stream_session(prompt, history, session_config, steering.clone()) {
                 Ok(mut stream) => {
                     // Track tool call IDs to names
                     let mut tool_id_to_name: HashMap<String, String> = HashMap::new();
@@ -708,6 +876,24 @@ impl SessionManager {
                                             .cloned()
                                             .unwrap_or_else(|| "unknown".to_string());
 
+                                        hooks.fire(HookEvent::ToolExecuted, json!({
+                                            "session_id": session_id,
+                                            "tool_call_id": tool_call_id,
+                                            "tool": tool_name,
+                                            "success": result.success,
+                                            "result": result.data,
+                                        }));
+
+                                        if tool_name == "execute_bash"
+                                            && let Some(report) = BashResult::from_value(&result.data)
+                                        {
+                                            let _ = tx.send(UiEvent::CommandFinished(
+                                                session_id,
+                                                tool_call_id.clone(),
+                                                report,
+                                            ));
+                                        }
+
                                         let summary =
                                             Self::summarize_tool_result(&result.data, result.success);
                                         let _ = tx.send(UiEvent::ToolCallResult(
@@ -726,6 +912,12 @@ impl SessionManager {
                                         ));
                                     }
                                     Some(StreamEvent::AskQuestion { tool_call_id, question, options }) => {
+                                        hooks.fire(HookEvent::ApprovalRequired, json!({
+                                            "session_id": session_id,
+                                            "tool_call_id": tool_call_id,
+                                            "question": question,
+                                            "options": options,
+                                        }));
                                         let _ = tx.send(UiEvent::AskQuestion {
                                             session_id,
                                             tool_call_id,
@@ -733,12 +925,31 @@ impl SessionManager {
                                             options,
                                         });
                                     }
+                                    Some(StreamEvent::LoopDetected { diagnosis }) => {
+                                        hooks.fire(HookEvent::ApprovalRequired, json!({
+                                            "session_id": session_id,
+                                            "loop_detected": diagnosis,
+                                        }));
+                                        let _ = tx.send(UiEvent::LoopDetected(session_id, diagnosis));
+                                    }
+                                    Some(StreamEvent::ContextCompacted { replaced, .. }) => {
+                                        let _ = tx.send(UiEvent::ContextCompacted(session_id, replaced));
+                                    }
                                     Some(StreamEvent::Finished) => {
+                                        hooks.fire(HookEvent::RunFinished, json!({
+                                            "session_id": session_id,
+                                            "success": true,
+                                        }));
                                         let _ = tx.send(UiEvent::Token(session_id, String::new(), true));
                                         let _ = tx.send(UiEvent::StreamFinished(session_id));
                                         break;
                                     }
                                     Some(StreamEvent::Error(err)) => {
+                                        hooks.fire(HookEvent::RunFinished, json!({
+                                            "session_id": session_id,
+                                            "success": false,
+                                            "error": err,
+                                        }));
                                         let _ = tx.send(UiEvent::StreamErrored(session_id, err));
                                         break;
                                     }
@@ -752,6 +963,11 @@ impl SessionManager {
                     }
                 }
                 Err(err) => {
+                    hooks.fire(HookEvent::RunFinished, json!({
+                        "session_id": session_id,
+                        "success": false,
+                        "error": err.to_string(),
+                    }));
                     let _ = tx.send(UiEvent::StreamErrored(session_id, err.to_string()));
                 }
             }
@@ -760,6 +976,12 @@ impl SessionManager {
             if let Ok(mut tokens) = tokens_ref.lock() {
                 tokens.remove(&session_id);
             }
+            // A follow-up run may already have registered its own handle
+            if let Ok(mut handles) = steering_ref.lock()
+                && handles.get(&session_id).is_some_and(|h| h.is_same(&steering))
+            {
+                handles.remove(&session_id);
+            }
         });
 
         Ok(())
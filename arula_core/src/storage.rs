@@ -0,0 +1,810 @@
+//! Local storage: one SQLite database at `~/.arula/arula.db`
+//!
+//! Holds what used to be kept in memory or scattered over files and is
+//! worth keeping between runs:
+//!
+//! - `cache` — keyed values with an optional expiry (model lists, ...)
+//! - `prompt_history` — prompts sent from the TUI, for Up/Down recall
+//! - `usage` — token usage and latency of every model response
+//! - `tool_usage` — every tool call, for the `/stats` tool frequency
+//! - `sessions` — an index of saved conversations (the conversations
+//!   themselves stay in `.arula/conversations/*.json`)
+//! - `audit_log` — every attempt of a retryable command or tool
+//! - `commits` — commits made with `/commit`, for `arula digest`
+//!
+//! The schema is versioned with `PRAGMA user_version`; opening a database
+//! applies any migrations it hasn't seen yet. Most callers go through
+//! [`Storage::with`], which opens the shared database on first use.
+
+use crate::utils::conversation::Conversation;
+use crate::utils::logger::LogLevel;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// File name of the database inside `~/.arula`
+pub const DATABASE_FILE: &str = "arula.db";
+/// Prompts kept in the history
+const MAX_HISTORY: usize = 1000;
+
+/// Schema migrations; `MIGRATIONS[n]` upgrades version `n` to `n + 1`
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE cache (
+        namespace TEXT NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        expires_at INTEGER,
+        PRIMARY KEY (namespace, key)
+    );
+    CREATE TABLE prompt_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        prompt TEXT NOT NULL,
+        created_at INTEGER NOT NULL
+    );
+    CREATE TABLE usage (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        prompt_tokens INTEGER NOT NULL,
+        completion_tokens INTEGER NOT NULL,
+        total_tokens INTEGER NOT NULL
+    );
+    CREATE INDEX usage_timestamp ON usage (timestamp);
+    CREATE TABLE sessions (
+        id TEXT PRIMARY KEY,
+        title TEXT NOT NULL,
+        project TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        model TEXT NOT NULL,
+        message_count INTEGER NOT NULL,
+        tool_calls INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );",
+    "CREATE TABLE audit_log (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        project TEXT NOT NULL,
+        tool TEXT NOT NULL,
+        target TEXT NOT NULL,
+        attempt INTEGER NOT NULL,
+        max_attempts INTEGER NOT NULL,
+        success INTEGER NOT NULL,
+        exit_code INTEGER,
+        duration_ms INTEGER NOT NULL,
+        error TEXT
+    );
+    CREATE INDEX audit_log_timestamp ON audit_log (timestamp);",
+    "CREATE TABLE commits (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        project TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        subject TEXT NOT NULL
+    );
+    CREATE INDEX commits_timestamp ON commits (timestamp);",
+    "ALTER TABLE usage ADD COLUMN latency_ms INTEGER;
+    CREATE TABLE tool_usage (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        timestamp INTEGER NOT NULL,
+        tool TEXT NOT NULL,
+        success INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL
+    );
+    CREATE INDEX tool_usage_timestamp ON tool_usage (timestamp);",
+];
+
+/// Token usage of one model response
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    /// Time from sending the request to the end of the response
+    pub latency_ms: Option<u64>,
+}
+
+/// Usage totals for one provider and model
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageTotals {
+    pub provider: String,
+    pub model: String,
+    pub responses: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+/// Usage of one provider and model on one local day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    pub day: NaiveDate,
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+    pub responses: u64,
+    /// Summed latency of the responses whose latency was recorded
+    pub latency_ms: u64,
+    pub timed_responses: u64,
+}
+
+/// A saved conversation in the session index
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub id: String,
+    pub title: String,
+    /// Directory the conversation was saved in
+    pub project: String,
+    pub provider: String,
+    pub model: String,
+    pub message_count: u64,
+    pub tool_calls: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One attempt of a retryable command or tool
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Directory the call ran in
+    pub project: String,
+    pub tool: String,
+    /// Command line or arguments
+    pub target: String,
+    /// 1-based attempt number
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// One tool call
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolUsageRecord {
+    pub timestamp: DateTime<Utc>,
+    pub tool: String,
+    pub success: bool,
+    pub duration_ms: u64,
+}
+
+/// Calls of one tool over a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCount {
+    pub tool: String,
+    pub calls: u64,
+    pub failures: u64,
+    pub duration_ms: u64,
+}
+
+/// A commit made with an AI-drafted message
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitRecord {
+    pub timestamp: DateTime<Utc>,
+    /// Repository the commit was made in
+    pub project: String,
+    pub hash: String,
+    pub subject: String,
+}
+
+/// The ARULA database
+pub struct Storage {
+    conn: Connection,
+}
+
+static SHARED: OnceLock<Option<Mutex<Storage>>> = OnceLock::new();
+
+impl Storage {
+    /// Open `~/.arula/arula.db`
+    pub fn open() -> Result<Self> {
+        Self::open_at(&database_path().ok_or_else(|| anyhow!("Could not determine home directory"))?)
+    }
+
+    /// Open (creating if needed) a database file and bring its schema up to date
+    pub fn open_at(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        conn.busy_timeout(Duration::from_secs(2))?;
+        Self::from_connection(conn)
+    }
+
+    /// An empty database that lives only as long as the value
+    pub fn open_in_memory() -> Result<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    fn from_connection(conn: Connection) -> Result<Self> {
+        let storage = Self { conn };
+        storage.migrate()?;
+        Ok(storage)
+    }
+
+    /// Run `f` with the shared database, opening it on first use
+    ///
+    /// Fails without calling `f` if the database couldn't be opened; the
+    /// reason is logged once.
+    pub fn with<T>(f: impl FnOnce(&Storage) -> Result<T>) -> Result<T> {
+        let shared = SHARED.get_or_init(|| match Self::open() {
+            Ok(storage) => Some(Mutex::new(storage)),
+            Err(e) => {
+                crate::utils::logger::log_module(
+                    LogLevel::Warn,
+                    "storage",
+                    &format!("Storage unavailable: {:#}", e),
+                );
+                None
+            }
+        });
+        let storage = shared
+            .as_ref()
+            .ok_or_else(|| anyhow!("Storage unavailable"))?
+            .lock()
+            .map_err(|_| anyhow!("Storage lock poisoned"))?;
+        f(&storage)
+    }
+
+    /// Wait for a write in progress on the shared database to finish, so
+    /// exiting doesn't cut off a usage or audit record. Does nothing if the
+    /// database was never opened.
+    pub fn flush() {
+        if let Some(Some(shared)) = SHARED.get() {
+            drop(shared.lock());
+        }
+    }
+
+    /// Schema version of the open database
+    pub fn schema_version(&self) -> Result<usize> {
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        Ok(version as usize)
+    }
+
+    fn migrate(&self) -> Result<()> {
+        let current = self.schema_version()?;
+        if current > MIGRATIONS.len() {
+            return Err(anyhow!(
+                "Database schema version {} is newer than this ARULA supports ({})",
+                current,
+                MIGRATIONS.len()
+            ));
+        }
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration)
+                .with_context(|| format!("Migration to schema version {} failed", version + 1))?;
+            tx.pragma_update(None, "user_version", (version + 1) as i64)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    /// A cached value, if present and not expired
+    pub fn cache_get<T: DeserializeOwned>(&self, namespace: &str, key: &str) -> Result<Option<T>> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM cache
+                 WHERE namespace = ?1 AND key = ?2
+                   AND (expires_at IS NULL OR expires_at > ?3)",
+                params![namespace, key, Utc::now().timestamp()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        value
+            .map(|v| serde_json::from_str(&v).context("Invalid cached value"))
+            .transpose()
+    }
+
+    /// Store a value, replacing any previous one; it expires after `ttl`
+    pub fn cache_put<T: Serialize + ?Sized>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        let expires_at = ttl.map(|ttl| Utc::now().timestamp() + ttl.as_secs() as i64);
+        self.conn.execute(
+            "INSERT OR REPLACE INTO cache (namespace, key, value, expires_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![namespace, key, serde_json::to_string(value)?, expires_at],
+        )?;
+        Ok(())
+    }
+
+    pub fn cache_remove(&self, namespace: &str, key: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM cache WHERE namespace = ?1 AND key = ?2",
+            params![namespace, key],
+        )?;
+        Ok(())
+    }
+
+    /// Remove every value in a namespace, and expired values everywhere
+    pub fn cache_clear(&self, namespace: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM cache WHERE namespace = ?1 OR expires_at <= ?2",
+            params![namespace, Utc::now().timestamp()],
+        )?;
+        Ok(())
+    }
+
+    /// Append a prompt to the history (repeating the last one is a no-op)
+    pub fn add_history(&self, prompt: &str) -> Result<()> {
+        if prompt.trim().is_empty() || self.history(1)?.first().map(String::as_str) == Some(prompt) {
+            return Ok(());
+        }
+        self.conn.execute(
+            "INSERT INTO prompt_history (prompt, created_at) VALUES (?1, ?2)",
+            params![prompt, Utc::now().timestamp()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM prompt_history WHERE id <= (SELECT MAX(id) FROM prompt_history) - ?1",
+            params![MAX_HISTORY as i64],
+        )?;
+        Ok(())
+    }
+
+    /// The most recent prompts, newest first
+    pub fn history(&self, limit: usize) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT prompt FROM prompt_history ORDER BY id DESC LIMIT ?1")?;
+        let rows = stmt.query_map(params![limit as i64], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn record_usage(&self, usage: &UsageRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO usage (timestamp, provider, model, prompt_tokens, completion_tokens, total_tokens, latency_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                usage.timestamp.timestamp(),
+                usage.provider,
+                usage.model,
+                usage.prompt_tokens as i64,
+                usage.completion_tokens as i64,
+                usage.total_tokens as i64,
+                usage.latency_ms.map(|ms| ms as i64),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Usage since a point in time, per provider and model, heaviest first
+    pub fn usage_totals(&self, since: DateTime<Utc>) -> Result<Vec<UsageTotals>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT provider, model, COUNT(*), SUM(prompt_tokens), SUM(completion_tokens), SUM(total_tokens)
+             FROM usage WHERE timestamp >= ?1
+             GROUP BY provider, model
+             ORDER BY SUM(total_tokens) DESC",
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(UsageTotals {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                responses: row.get::<_, i64>(2)? as u64,
+                prompt_tokens: row.get::<_, i64>(3)? as u64,
+                completion_tokens: row.get::<_, i64>(4)? as u64,
+                total_tokens: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Usage since a point in time per local day, provider and model, oldest first
+    pub fn daily_usage(&self, since: DateTime<Utc>) -> Result<Vec<DailyUsage>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT date(timestamp, 'unixepoch', 'localtime') AS day, provider, model,
+                    SUM(prompt_tokens), SUM(completion_tokens), SUM(total_tokens),
+                    COUNT(*), SUM(COALESCE(latency_ms, 0)), COUNT(latency_ms)
+             FROM usage WHERE timestamp >= ?1
+             GROUP BY day, provider, model
+             ORDER BY day",
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                DailyUsage {
+                    day: NaiveDate::default(),
+                    provider: row.get(1)?,
+                    model: row.get(2)?,
+                    prompt_tokens: row.get::<_, i64>(3)? as u64,
+                    completion_tokens: row.get::<_, i64>(4)? as u64,
+                    total_tokens: row.get::<_, i64>(5)? as u64,
+                    responses: row.get::<_, i64>(6)? as u64,
+                    latency_ms: row.get::<_, i64>(7)? as u64,
+                    timed_responses: row.get::<_, i64>(8)? as u64,
+                },
+            ))
+        })?;
+        rows.map(|row| {
+            let (day, usage) = row?;
+            let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+                .with_context(|| format!("Bad usage date: {}", day))?;
+            Ok(DailyUsage { day, ..usage })
+        })
+        .collect()
+    }
+
+    /// Add or update a conversation in the session index
+    pub fn index_session(&self, conversation: &Conversation, project: &Path) -> Result<()> {
+        let meta = &conversation.metadata;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sessions
+             (id, title, project, provider, model, message_count, tool_calls, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                meta.conversation_id,
+                meta.title,
+                project.to_string_lossy(),
+                meta.provider,
+                meta.model,
+                meta.message_count as i64,
+                conversation.statistics.total_tool_calls as i64,
+                meta.created_at.timestamp(),
+                meta.updated_at.timestamp(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Indexed sessions, most recently updated first
+    pub fn sessions(&self, limit: usize) -> Result<Vec<SessionRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, project, provider, model, message_count, tool_calls, created_at, updated_at
+             FROM sessions ORDER BY updated_at DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], session_record)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Indexed sessions updated since `since`, most recently updated first
+    pub fn sessions_since(&self, since: DateTime<Utc>) -> Result<Vec<SessionRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, title, project, provider, model, message_count, tool_calls, created_at, updated_at
+             FROM sessions WHERE updated_at >= ?1 ORDER BY updated_at DESC",
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], session_record)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Remove a conversation from the session index
+    pub fn remove_session(&self, id: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn record_audit(&self, record: &AuditRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO audit_log
+             (timestamp, project, tool, target, attempt, max_attempts, success, exit_code, duration_ms, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                record.timestamp.timestamp(),
+                record.project,
+                record.tool,
+                record.target,
+                record.attempt,
+                record.max_attempts,
+                record.success,
+                record.exit_code,
+                record.duration_ms as i64,
+                record.error,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn record_tool_usage(&self, record: &ToolUsageRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO tool_usage (timestamp, tool, success, duration_ms) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                record.timestamp.timestamp(),
+                record.tool,
+                record.success,
+                record.duration_ms as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Calls per tool since a point in time, most used first
+    pub fn tool_counts(&self, since: DateTime<Utc>) -> Result<Vec<ToolCount>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT tool, COUNT(*), SUM(NOT success), SUM(duration_ms)
+             FROM tool_usage WHERE timestamp >= ?1
+             GROUP BY tool
+             ORDER BY COUNT(*) DESC, tool",
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(ToolCount {
+                tool: row.get(0)?,
+                calls: row.get::<_, i64>(1)? as u64,
+                failures: row.get::<_, i64>(2)? as u64,
+                duration_ms: row.get::<_, i64>(3)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn record_commit(&self, record: &CommitRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO commits (timestamp, project, hash, subject) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                record.timestamp.timestamp(),
+                record.project,
+                record.hash,
+                record.subject,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Commits made since `since`, oldest first
+    pub fn commits_since(&self, since: DateTime<Utc>) -> Result<Vec<CommitRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, project, hash, subject FROM commits
+             WHERE timestamp >= ?1 ORDER BY timestamp, id",
+        )?;
+        let rows = stmt.query_map(params![since.timestamp()], |row| {
+            Ok(CommitRecord {
+                timestamp: timestamp(row.get(0)?),
+                project: row.get(1)?,
+                hash: row.get(2)?,
+                subject: row.get(3)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Audit log entries, newest first
+    pub fn audit_log(&self, limit: usize) -> Result<Vec<AuditRecord>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp, project, tool, target, attempt, max_attempts, success, exit_code, duration_ms, error
+             FROM audit_log ORDER BY id DESC LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(AuditRecord {
+                timestamp: timestamp(row.get(0)?),
+                project: row.get(1)?,
+                tool: row.get(2)?,
+                target: row.get(3)?,
+                attempt: row.get(4)?,
+                max_attempts: row.get(5)?,
+                success: row.get(6)?,
+                exit_code: row.get(7)?,
+                duration_ms: row.get::<_, i64>(8)? as u64,
+                error: row.get(9)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+/// `~/.arula/arula.db`
+pub fn database_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join(DATABASE_FILE))
+}
+
+fn session_record(row: &rusqlite::Row) -> rusqlite::Result<SessionRecord> {
+    Ok(SessionRecord {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        project: row.get(2)?,
+        provider: row.get(3)?,
+        model: row.get(4)?,
+        message_count: row.get::<_, i64>(5)? as u64,
+        tool_calls: row.get::<_, i64>(6)? as u64,
+        created_at: timestamp(row.get(7)?),
+        updated_at: timestamp(row.get(8)?),
+    })
+}
+
+fn timestamp(secs: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(secs, 0).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migrations_run_once() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(DATABASE_FILE);
+        let storage = Storage::open_at(&path).unwrap();
+        assert_eq!(storage.schema_version().unwrap(), MIGRATIONS.len());
+        storage.add_history("hello").unwrap();
+        drop(storage);
+
+        // Reopening keeps the data and doesn't re-run migrations
+        let storage = Storage::open_at(&path).unwrap();
+        assert_eq!(storage.history(10).unwrap(), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_cache_expiry() {
+        let storage = Storage::open_in_memory().unwrap();
+        let models = vec!["gpt-4o".to_string(), "o3".to_string()];
+        storage.cache_put("models", "openai", &models, None).unwrap();
+        storage
+            .cache_put("models", "stale", &models, Some(Duration::ZERO))
+            .unwrap();
+
+        let cached: Option<Vec<String>> = storage.cache_get("models", "openai").unwrap();
+        assert_eq!(cached, Some(models.clone()));
+        let stale: Option<Vec<String>> = storage.cache_get("models", "stale").unwrap();
+        assert_eq!(stale, None);
+
+        storage.cache_remove("models", "openai").unwrap();
+        let removed: Option<Vec<String>> = storage.cache_get("models", "openai").unwrap();
+        assert_eq!(removed, None);
+
+        storage.cache_put("models", "openai", &models, None).unwrap();
+        storage.cache_clear("models").unwrap();
+        let cleared: Option<Vec<String>> = storage.cache_get("models", "openai").unwrap();
+        assert_eq!(cleared, None);
+    }
+
+    #[test]
+    fn test_history_dedupes_and_orders() {
+        let storage = Storage::open_in_memory().unwrap();
+        for prompt in ["one", "two", "two", "  ", "three"] {
+            storage.add_history(prompt).unwrap();
+        }
+        assert_eq!(storage.history(10).unwrap(), vec!["three", "two", "one"]);
+        assert_eq!(storage.history(1).unwrap(), vec!["three"]);
+    }
+
+    #[test]
+    fn test_audit_log_newest_first() {
+        let storage = Storage::open_in_memory().unwrap();
+        let record = |attempt: u32, success: bool| AuditRecord {
+            timestamp: Utc::now(),
+            project: "/work".to_string(),
+            tool: "execute_bash".to_string(),
+            target: "npm install".to_string(),
+            attempt,
+            max_attempts: 3,
+            success,
+            exit_code: Some(if success { 0 } else { 1 }),
+            duration_ms: 1200,
+            error: None,
+        };
+        storage.record_audit(&record(1, false)).unwrap();
+        storage.record_audit(&record(2, true)).unwrap();
+
+        let log = storage.audit_log(10).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].attempt, 2);
+        assert!(log[0].success);
+        assert_eq!(log[1].exit_code, Some(1));
+    }
+
+    #[test]
+    fn test_commits_since() {
+        let storage = Storage::open_in_memory().unwrap();
+        let commit = |hash: &str, days_ago: i64| CommitRecord {
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            project: "/work".to_string(),
+            hash: hash.to_string(),
+            subject: "fix: parser".to_string(),
+        };
+        storage.record_commit(&commit("old", 10)).unwrap();
+        storage.record_commit(&commit("b", 1)).unwrap();
+        storage.record_commit(&commit("a", 2)).unwrap();
+
+        let commits = storage
+            .commits_since(Utc::now() - chrono::Duration::days(7))
+            .unwrap();
+        let hashes: Vec<&str> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_usage_totals() {
+        let storage = Storage::open_in_memory().unwrap();
+        let record = |model: &str, tokens: u64| UsageRecord {
+            timestamp: Utc::now(),
+            provider: "openai".to_string(),
+            model: model.to_string(),
+            prompt_tokens: tokens,
+            completion_tokens: 10,
+            total_tokens: tokens + 10,
+            latency_ms: Some(tokens),
+        };
+        storage.record_usage(&record("small", 100)).unwrap();
+        storage.record_usage(&record("big", 1000)).unwrap();
+        storage.record_usage(&record("small", 200)).unwrap();
+
+        let totals = storage
+            .usage_totals(Utc::now() - chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].model, "big");
+        assert_eq!(totals[1].responses, 2);
+        assert_eq!(totals[1].total_tokens, 320);
+
+        assert!(storage
+            .usage_totals(Utc::now() + chrono::Duration::days(1))
+            .unwrap()
+            .is_empty());
+
+        let daily = storage
+            .daily_usage(Utc::now() - chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].day, chrono::Local::now().date_naive());
+        assert_eq!(
+            daily.iter().map(|d| d.total_tokens).sum::<u64>(),
+            1330
+        );
+        let small = daily.iter().find(|d| d.model == "small").unwrap();
+        assert_eq!((small.responses, small.latency_ms), (2, 300));
+
+        for (tool, success) in [("read_file", true), ("execute_bash", false), ("read_file", true)] {
+            storage
+                .record_tool_usage(&ToolUsageRecord {
+                    timestamp: Utc::now(),
+                    tool: tool.to_string(),
+                    success,
+                    duration_ms: 10,
+                })
+                .unwrap();
+        }
+        let tools = storage
+            .tool_counts(Utc::now() - chrono::Duration::days(1))
+            .unwrap();
+        assert_eq!((tools[0].tool.as_str(), tools[0].calls), ("read_file", 2));
+        assert_eq!(tools[1].failures, 1);
+    }
+
+    #[test]
+    fn test_session_index() {
+        let storage = Storage::open_in_memory().unwrap();
+        let mut conversation = Conversation::new(
+            "gpt-4o".to_string(),
+            "openai".to_string(),
+            "https://api.openai.com/v1".to_string(),
+        );
+        conversation.add_user_message("Fix the build".to_string());
+        storage
+            .index_session(&conversation, Path::new("/work/project"))
+            .unwrap();
+        storage
+            .index_session(&conversation, Path::new("/work/project"))
+            .unwrap();
+
+        let sessions = storage.sessions(10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].project, "/work/project");
+        assert_eq!(sessions[0].message_count, 1);
+        let hour = chrono::Duration::hours(1);
+        assert_eq!(storage.sessions_since(Utc::now() - hour).unwrap(), sessions);
+        assert!(storage.sessions_since(Utc::now() + hour).unwrap().is_empty());
+
+        storage.remove_session(&sessions[0].id).unwrap();
+        assert!(storage.sessions(10).unwrap().is_empty());
+    }
+}
@@ -452,6 +452,10 @@ impl Tool for AnalyzeContextTool {
         "analyze_context"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Analyze the current repository to summarize languages, frameworks, build systems, and entry points."
     }
@@ -16,7 +16,9 @@
 use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::process::Stdio;
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command as TokioCommand;
 use tokio::sync::mpsc;
@@ -30,8 +32,12 @@ pub struct BashParams {
     pub timeout_seconds: Option<u64>,
 }
 
+/// Characters of stdout or stderr kept in a result; longer output keeps its
+/// start and end
+pub const MAX_OUTPUT_CHARS: usize = 16_000;
+
 /// Result from bash command execution
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct BashResult {
     /// Standard output from the command
     pub stdout: String,
@@ -41,6 +47,69 @@ pub struct BashResult {
     pub exit_code: i32,
     /// Whether the command succeeded (exit code 0)
     pub success: bool,
+    /// How long the command ran, in milliseconds
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Whether stdout was cut to `MAX_OUTPUT_CHARS`
+    #[serde(default)]
+    pub stdout_truncated: bool,
+    /// Whether stderr was cut to `MAX_OUTPUT_CHARS`
+    #[serde(default)]
+    pub stderr_truncated: bool,
+}
+
+impl BashResult {
+    /// Build a result, truncating long output
+    pub fn new(stdout: String, stderr: String, exit_code: i32, elapsed: Duration) -> Self {
+        let (stdout, stdout_truncated) = truncate_output(stdout);
+        let (stderr, stderr_truncated) = truncate_output(stderr);
+        Self {
+            stdout,
+            stderr,
+            exit_code,
+            success: exit_code == 0,
+            duration_ms: elapsed.as_millis() as u64,
+            stdout_truncated,
+            stderr_truncated,
+        }
+    }
+
+    /// Read a result back from tool output, with or without an `Ok` wrapper
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let inner = value.get("Ok").unwrap_or(value);
+        inner.get("exit_code")?;
+        serde_json::from_value(inner.clone()).ok()
+    }
+
+    /// Short status like "exited 1 in 2.3s"
+    pub fn status(&self) -> String {
+        let elapsed = if self.duration_ms < 1000 {
+            format!("{}ms", self.duration_ms)
+        } else {
+            format!("{:.1}s", self.duration_ms as f64 / 1000.0)
+        };
+        format!("exited {} in {}", self.exit_code, elapsed)
+    }
+
+    pub fn truncated(&self) -> bool {
+        self.stdout_truncated || self.stderr_truncated
+    }
+}
+
+/// Keep the first and last part of output longer than `MAX_OUTPUT_CHARS`
+fn truncate_output(output: String) -> (String, bool) {
+    let total = output.chars().count();
+    if total <= MAX_OUTPUT_CHARS {
+        return (output, false);
+    }
+    let keep = MAX_OUTPUT_CHARS / 2;
+    let head: String = output.chars().take(keep).collect();
+    let tail: String = output.chars().skip(total - keep).collect();
+    let omitted = total - 2 * keep;
+    (
+        format!("{}\n[... {} characters omitted ...]\n{}", head, omitted, tail),
+        true,
+    )
 }
 
 /// Bash execution tool with streaming support
@@ -96,27 +165,18 @@ pub async fn execute_bash(
     command: &str,
     timeout_seconds: Option<u64>,
 ) -> Result<BashResult, String> {
-    use tokio::time::Duration;
-
     if command.trim().is_empty() {
         return Err("Command cannot be empty".to_string());
     }
 
     // Build the command
-    let mut cmd = if cfg!(target_os = "windows") {
-        let mut c = TokioCommand::new("cmd");
-        c.args(["/C", command]);
-        c
-    } else {
-        let mut c = TokioCommand::new("sh");
-        c.arg("-c").arg(command);
-        c
-    };
+    let mut cmd = TokioCommand::from(crate::platform::current().shell_command(command));
 
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
+    let started = Instant::now();
     let child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn command '{}': {}", command, e))?;
@@ -131,14 +191,8 @@ pub async fn execute_bash(
                     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
                     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
                     let exit_code = output.status.code().unwrap_or(-1);
-                    let success = output.status.success();
-
-                    Ok(BashResult {
-                        stdout,
-                        stderr,
-                        exit_code,
-                        success,
-                    })
+
+                    Ok(BashResult::new(stdout, stderr, exit_code, started.elapsed()))
                 }
                 Err(e) => Err(format!("Failed to execute command: {}", e)),
             }
@@ -172,26 +226,17 @@ async fn execute_bash_streaming_inner(
     timeout_seconds: Option<u64>,
     tx: mpsc::UnboundedSender<(String, bool)>,
 ) -> Result<BashResult, String> {
-    use tokio::time::Duration;
-
     if command.trim().is_empty() {
         return Err("Command cannot be empty".to_string());
     }
 
-    let mut cmd = if cfg!(target_os = "windows") {
-        let mut c = TokioCommand::new("cmd");
-        c.args(["/C", command]);
-        c
-    } else {
-        let mut c = TokioCommand::new("sh");
-        c.arg("-c").arg(command);
-        c
-    };
+    let mut cmd = TokioCommand::from(crate::platform::current().shell_command(command));
 
     cmd.stdin(Stdio::null());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
+    let started = Instant::now();
     let mut child = cmd
         .spawn()
         .map_err(|e| format!("Failed to spawn command '{}': {}", command, e))?;
@@ -208,12 +253,16 @@ async fn execute_bash_streaming_inner(
     let timeout_secs = timeout_seconds.unwrap_or(30).min(300);
     let timeout_duration = Duration::from_secs(timeout_secs);
 
+    // Finished streams are left out of the select, or their EOF would win
+    // every time and the exit would never be seen
+    let mut stdout_done = false;
+    let mut stderr_done = false;
     let read_result = tokio::time::timeout(timeout_duration, async {
         loop {
             tokio::select! {
                 biased;  // Check in order
                 
-                line = stdout_reader.next_line() => {
+                line = stdout_reader.next_line(), if !stdout_done => {
                     match line {
                         Ok(Some(l)) => {
                             let _ = tx.send((l.clone(), false));
@@ -221,6 +270,7 @@ async fn execute_bash_streaming_inner(
                         }
                         Ok(None) => {
                             // stdout EOF - process might still be running
+                            stdout_done = true;
                         }
                         Err(e) => {
                             let err = format!("Error reading stdout: {}", e);
@@ -230,7 +280,7 @@ async fn execute_bash_streaming_inner(
                     }
                 }
                 
-                line = stderr_reader.next_line() => {
+                line = stderr_reader.next_line(), if !stderr_done => {
                     match line {
                         Ok(Some(l)) => {
                             let _ = tx.send((l.clone(), true));
@@ -238,6 +288,7 @@ async fn execute_bash_streaming_inner(
                         }
                         Ok(None) => {
                             // stderr EOF
+                            stderr_done = true;
                         }
                         Err(e) => {
                             let err = format!("Error reading stderr: {}", e);
@@ -260,13 +311,12 @@ async fn execute_bash_streaming_inner(
                     
                     match status {
                         Ok(s) => {
-                            let exit_code = s.code().unwrap_or(-1);
-                            return Ok(BashResult {
-                                stdout: stdout_lines.join("\n"),
-                                stderr: stderr_lines.join("\n"),
-                                exit_code,
-                                success: s.success(),
-                            });
+                            return Ok(BashResult::new(
+                                stdout_lines.join("\n"),
+                                stderr_lines.join("\n"),
+                                s.code().unwrap_or(-1),
+                                started.elapsed(),
+                            ));
                         }
                         Err(e) => {
                             return Err(format!("Failed to wait for command: {}", e));
@@ -327,6 +377,20 @@ mod tests {
         assert_eq!(result.exit_code, 0);
     }
 
+    #[test]
+    fn test_result_fields() {
+        let long = "x".repeat(MAX_OUTPUT_CHARS + 10);
+        let result = BashResult::new(long, "oops".into(), 2, Duration::from_millis(2300));
+        assert!(result.stdout_truncated && !result.stderr_truncated);
+        assert!(result.stdout.contains("[... 10 characters omitted ...]"));
+        assert!(!result.success);
+        assert_eq!(result.status(), "exited 2 in 2.3s");
+
+        let value = serde_json::json!({ "Ok": serde_json::to_value(&result).unwrap() });
+        assert_eq!(BashResult::from_value(&value), Some(result));
+        assert_eq!(BashResult::from_value(&serde_json::json!({ "lines": 3 })), None);
+    }
+
     #[tokio::test]
     async fn test_empty_command_error() {
         let tool = BashTool::new();
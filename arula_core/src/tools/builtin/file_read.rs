@@ -4,9 +4,11 @@
 //! Uses memory mapping for large files for better performance.
 
 use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::tools::result_cache::file_key;
 use async_trait::async_trait;
 use memmap2::MmapOptions;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Maximum number of characters to return (to prevent context overflow)
 const MAX_CHARS: usize = 5000;
@@ -79,6 +81,14 @@ impl Tool for FileReadTool {
         "read_file"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn cache_key(&self, params: &Value) -> Option<String> {
+        file_key(params.get("path")?.as_str()?, params)
+    }
+
     fn description(&self) -> &str {
         "Read the contents of a file. Supports line range selection for partial reads."
     }
@@ -3,8 +3,11 @@
 //! This tool finds files matching a glob pattern or regex in the file system.
 
 use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::api::tool_budget::ResultBudget;
+use crate::tools::result_cache::scan_key;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs;
 use std::path::Path;
 
@@ -23,6 +26,9 @@ pub struct FindFilesParams {
     pub max_results: Option<usize>,
     /// File extensions to include (e.g., ["rs", "py"])
     pub extensions: Option<Vec<String>>,
+    /// Result budget from the context manager (internal, not in the schema)
+    #[serde(default, rename = "_budget")]
+    pub budget: Option<ResultBudget>,
 }
 
 /// A single found file
@@ -188,6 +194,18 @@ impl Tool for FindFilesTool {
         "find_files"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn budgeted(&self) -> bool {
+        true
+    }
+
+    fn cache_key(&self, params: &Value) -> Option<String> {
+        scan_key(params)
+    }
+
     fn description(&self) -> &str {
         "Find files by name pattern using glob patterns or regex. Results are limited to prevent API errors."
     }
@@ -233,6 +251,7 @@ impl Tool for FindFilesTool {
             recursive,
             max_results,
             extensions,
+            budget,
         } = params;
 
         if pattern.is_empty() {
@@ -242,7 +261,7 @@ impl Tool for FindFilesTool {
         let search_path = path.unwrap_or_else(|| ".".to_string());
         let use_regex = regex.unwrap_or(false);
         let recursive = recursive.unwrap_or(true);
-        let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let max_results = ResultBudget::limit(budget, max_results, DEFAULT_MAX_RESULTS);
 
         let path = Path::new(&search_path);
         if !path.exists() {
@@ -268,9 +287,6 @@ impl Tool for FindFilesTool {
                 if let Ok(entries) = fs::read_dir(path) {
                     for entry in entries.flatten() {
                         let entry_path = entry.path();
-                        if total_count >= max_results {
-                            break;
-                        }
 
                         if entry_path.is_file() {
                             let name = entry_path.file_name().unwrap().to_string_lossy();
@@ -289,6 +305,11 @@ impl Tool for FindFilesTool {
 
                             // Check if name matches pattern
                             if self.matches_pattern(&name, &pattern, use_regex)? {
+                                // Keep counting past the limit for `total_matches`
+                                total_count += 1;
+                                if results.len() >= max_results {
+                                    continue;
+                                }
                                 let metadata = fs::metadata(&entry_path)
                                     .map_err(|e| format!("Failed to read metadata: {}", e))?;
 
@@ -298,7 +319,6 @@ impl Tool for FindFilesTool {
                                     size: metadata.len(),
                                     file_type: "file".to_string(),
                                 });
-                                total_count += 1;
                             }
                         }
                     }
@@ -338,6 +358,7 @@ mod tests {
                 regex: Some(false),
                 recursive: Some(false),
                 max_results: None,
+                budget: None,
                 extensions: None,
             })
             .await
@@ -365,6 +386,7 @@ mod tests {
                 regex: Some(false),
                 recursive: Some(false),
                 max_results: Some(5),
+                budget: None,
                 extensions: None,
             })
             .await
@@ -0,0 +1,261 @@
+//! Symbol lookup tool backed by the workspace symbol index
+//!
+//! Finds functions, methods and types by name across Rust, TypeScript,
+//! JavaScript, Python and Go sources, returning each definition's kind,
+//! signature and location, and optionally its source.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::api::tool_budget::ResultBudget;
+use crate::utils::symbol_index::{Symbol, SymbolIndex, SymbolKind};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Default maximum number of matches to return
+const DEFAULT_MAX_RESULTS: usize = 20;
+/// Longest definition returned with `include_source`
+const MAX_SOURCE_LINES: usize = 80;
+
+/// Parameters for the find_symbol tool
+#[derive(Debug, Deserialize)]
+pub struct FindSymbolParams {
+    /// Symbol name or `Type::method`; matched case-insensitively
+    pub name: String,
+    /// Restrict to one kind (function, method, struct, enum, trait, interface, class, type, constant, module, macro)
+    pub kind: Option<String>,
+    /// Workspace root to index (default: current directory)
+    pub path: Option<String>,
+    /// Only return exact name matches (default: false)
+    pub exact: Option<bool>,
+    /// Include each definition's source code (default: false)
+    pub include_source: Option<bool>,
+    /// Maximum number of matches to return (default: 20)
+    pub max_results: Option<usize>,
+    /// Result budget from the context manager (internal, not in the schema)
+    #[serde(default, rename = "_budget")]
+    pub budget: Option<ResultBudget>,
+}
+
+/// A symbol matching the query
+#[derive(Debug, Clone, Serialize)]
+pub struct SymbolMatch {
+    #[serde(flatten)]
+    pub symbol: Symbol,
+    /// Definition source, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Result of a find_symbol call
+#[derive(Debug, Serialize)]
+pub struct FindSymbolResult {
+    /// Matches, exact names first
+    pub matches: Vec<SymbolMatch>,
+    /// Total number of matches before truncation
+    pub total_found: usize,
+    /// Number of source files in the index
+    pub files_indexed: usize,
+    /// Whether the result limit was reached (more matches exist)
+    pub limit_reached: bool,
+}
+
+/// Tool that looks up symbol definitions in the workspace
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let tool = FindSymbolTool::new();
+/// let result = tool.execute(FindSymbolParams {
+///     name: "GitOps::commit".to_string(),
+///     kind: None,
+///     path: None,
+///     exact: Some(true),
+///     include_source: Some(true),
+///     max_results: None,
+///     budget: None,
+/// }).await?;
+/// for m in result.matches {
+///     println!("{}:{} {}", m.symbol.path, m.symbol.line, m.symbol.signature);
+/// }
+/// ```
+pub struct FindSymbolTool;
+
+impl FindSymbolTool {
+    /// Create a new FindSymbolTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FindSymbolTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for FindSymbolTool {
+    type Params = FindSymbolParams;
+    type Result = FindSymbolResult;
+
+    fn name(&self) -> &str {
+        "find_symbol"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn budgeted(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Find where a function, method or type is defined. Searches a parsed index of Rust, TypeScript/JavaScript, Python and Go sources by name (or Type::method) and returns each definition's kind, signature, file and line range, optionally with its source. Prefer this over text search when looking for a definition."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new("find_symbol", "Find function, method and type definitions by name")
+            .param("name", "string")
+            .description("name", "Symbol name or Type::method (case-insensitive)")
+            .required("name")
+            .param("kind", "string")
+            .description(
+                "kind",
+                "Only this kind: function, method, struct, enum, trait, interface, class, type, constant, module or macro",
+            )
+            .param("path", "string")
+            .description("path", "Workspace root to index (default: current directory)")
+            .param("exact", "boolean")
+            .description("exact", "Only return exact name matches (default: false)")
+            .param("include_source", "boolean")
+            .description(
+                "include_source",
+                "Include each definition's source code (default: false)",
+            )
+            .param("max_results", "integer")
+            .description("max_results", "Maximum number of matches to return (default: 20)")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        if params.name.trim().is_empty() {
+            return Err("Symbol name cannot be empty".to_string());
+        }
+        let root = PathBuf::from(params.path.unwrap_or_else(|| ".".to_string()));
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let kind = match params.kind.as_deref() {
+            Some(name) => Some(
+                SymbolKind::parse(name).ok_or_else(|| format!("Unknown symbol kind: {}", name))?,
+            ),
+            None => None,
+        };
+        let max_results =
+            ResultBudget::limit(params.budget, params.max_results, DEFAULT_MAX_RESULTS);
+        let include_source = params.include_source.unwrap_or(false);
+
+        let index_root = root.clone();
+        let index = tokio::task::spawn_blocking(move || SymbolIndex::shared(&index_root))
+            .await
+            .map_err(|e| format!("Symbol indexing failed: {}", e))?;
+
+        let found = index.find(&params.name, kind, params.exact.unwrap_or(false));
+        let total_found = found.len();
+        let matches = found
+            .into_iter()
+            .take(max_results)
+            .map(|symbol| SymbolMatch {
+                source: include_source
+                    .then(|| read_definition(&index.root().join(&symbol.path), symbol))
+                    .flatten(),
+                symbol: symbol.clone(),
+            })
+            .collect();
+
+        Ok(FindSymbolResult {
+            matches,
+            total_found,
+            files_indexed: index.file_count(),
+            limit_reached: total_found > max_results,
+        })
+    }
+}
+
+/// Read a symbol's definition lines, truncated to `MAX_SOURCE_LINES`
+fn read_definition(path: &std::path::Path, symbol: &Symbol) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let lines: Vec<&str> = content
+        .lines()
+        .skip(symbol.line.saturating_sub(1))
+        .take(symbol.end_line + 1 - symbol.line)
+        .collect();
+    let mut source = lines
+        .iter()
+        .take(MAX_SOURCE_LINES)
+        .copied()
+        .collect::<Vec<_>>()
+        .join("\n");
+    if lines.len() > MAX_SOURCE_LINES {
+        source.push_str(&format!(
+            "\n// ... {} more lines",
+            lines.len() - MAX_SOURCE_LINES
+        ));
+    }
+    Some(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_find_symbol() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("lib.rs"),
+            "pub struct Cache;\n\nimpl Cache {\n    pub fn get(&self) -> u8 {\n        1\n    }\n}\n",
+        )
+        .unwrap();
+
+        let tool = FindSymbolTool::new();
+        let result = tool
+            .execute(FindSymbolParams {
+                name: "cache::get".to_string(),
+                kind: None,
+                path: Some(temp_dir.path().to_string_lossy().to_string()),
+                exact: Some(true),
+                include_source: Some(true),
+                max_results: None,
+                budget: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.total_found, 1);
+        let found = &result.matches[0];
+        assert_eq!(found.symbol.kind, SymbolKind::Method);
+        assert_eq!(found.symbol.line, 4);
+        assert_eq!(
+            found.source.as_deref(),
+            Some("    pub fn get(&self) -> u8 {\n        1\n    }")
+        );
+
+        let result = tool
+            .execute(FindSymbolParams {
+                name: "cache".to_string(),
+                kind: Some("struct".to_string()),
+                path: Some(temp_dir.path().to_string_lossy().to_string()),
+                exact: None,
+                include_source: None,
+                max_results: None,
+                budget: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.matches.len(), 1);
+        assert!(result.matches[0].source.is_none());
+    }
+}
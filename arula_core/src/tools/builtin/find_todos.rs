@@ -0,0 +1,479 @@
+//! TODO/FIXME/HACK harvesting tool
+//!
+//! Scans the workspace (respecting `.gitignore`) for TODO, FIXME and HACK
+//! comments, attaches the surrounding lines and, inside a git repository,
+//! the author and age of each comment from `git blame`. Results are scored
+//! and sorted so the agent can turn them into a triage plan or issues.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::api::tool_budget::ResultBudget;
+use crate::utils::git_ops::GitOps;
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Default maximum number of items to return
+const DEFAULT_MAX_RESULTS: usize = 100;
+/// Default number of context lines around each comment
+const DEFAULT_CONTEXT_LINES: usize = 2;
+/// Files larger than this are skipped
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Tags harvested by default
+const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK"];
+
+/// Words in a comment that raise its priority
+const URGENT_WORDS: &[&str] = &[
+    "urgent",
+    "asap",
+    "security",
+    "crash",
+    "panic",
+    "data loss",
+    "leak",
+    "broken",
+    "bug",
+];
+
+/// Comment markers, one of which must appear before a tag on its line
+const COMMENT_MARKERS: &[&str] = &["//", "#", "/*", "--", "<!--", ";"];
+
+/// Parameters for the find_todos tool
+#[derive(Debug, Deserialize)]
+pub struct FindTodosParams {
+    /// Directory to scan (default: current directory)
+    pub path: Option<String>,
+    /// Tags to look for (default: TODO, FIXME, HACK)
+    pub tags: Option<Vec<String>>,
+    /// Lines of context before and after each comment (default: 2)
+    pub context_lines: Option<usize>,
+    /// Maximum number of items to return (default: 100)
+    pub max_results: Option<usize>,
+    /// Look up author and age with git blame (default: true)
+    pub blame: Option<bool>,
+    /// Result budget from the context manager (internal, not in the schema)
+    #[serde(default, rename = "_budget")]
+    pub budget: Option<ResultBudget>,
+}
+
+/// A harvested TODO-style comment
+#[derive(Debug, Clone, Serialize)]
+pub struct TodoItem {
+    /// File path relative to the scanned directory
+    pub path: String,
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Tag found (TODO, FIXME, HACK)
+    pub tag: String,
+    /// Owner named in the tag, e.g. `TODO(alice)`
+    pub owner: Option<String>,
+    /// Comment text after the tag
+    pub text: String,
+    /// Surrounding lines, prefixed with their line numbers
+    pub context: Vec<String>,
+    /// Author of the line according to git blame
+    pub author: Option<String>,
+    /// Days since the line was last changed according to git blame
+    pub age_days: Option<i64>,
+    /// Priority score used for sorting (higher is more pressing)
+    pub score: u32,
+    /// Priority bucket: "high", "medium" or "low"
+    pub priority: String,
+}
+
+/// Result of a find_todos call
+#[derive(Debug, Serialize)]
+pub struct FindTodosResult {
+    /// Items sorted by priority, most pressing first
+    pub items: Vec<TodoItem>,
+    /// Total number of comments found before truncation
+    pub total_found: usize,
+    /// Number of comments per tag
+    pub by_tag: BTreeMap<String, usize>,
+    /// Number of files scanned
+    pub files_scanned: usize,
+    /// Whether author/age information comes from git blame
+    pub blame_available: bool,
+    /// Whether the result limit was reached (more items exist)
+    pub limit_reached: bool,
+}
+
+/// Tool that harvests TODO/FIXME/HACK comments into a prioritized list
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let tool = FindTodosTool::new();
+/// let result = tool.execute(FindTodosParams {
+///     path: Some("src".to_string()),
+///     tags: None,
+///     context_lines: Some(1),
+///     max_results: Some(20),
+///     budget: None,
+///     blame: Some(true),
+/// }).await?;
+/// for item in result.items {
+///     println!("[{}] {}:{} {}", item.priority, item.path, item.line, item.text);
+/// }
+/// ```
+pub struct FindTodosTool;
+
+impl FindTodosTool {
+    /// Create a new FindTodosTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FindTodosTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for FindTodosTool {
+    type Params = FindTodosParams;
+    type Result = FindTodosResult;
+
+    fn name(&self) -> &str {
+        "find_todos"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn budgeted(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Scan the workspace for TODO, FIXME and HACK comments. Returns each comment with surrounding context, its author and age from git blame, and a priority (FIXME and HACK, urgent wording and old comments rank higher), sorted most pressing first. Use it to build a triage plan or draft tracker issues."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new(
+            "find_todos",
+            "Harvest TODO/FIXME/HACK comments into a prioritized list",
+        )
+        .param("path", "string")
+        .description("path", "Directory to scan (default: current directory)")
+        .param("tags", "array")
+        .description(
+            "tags",
+            "Tags to look for, e.g. [\"TODO\", \"FIXME\"] (default: TODO, FIXME, HACK)",
+        )
+        .param("context_lines", "integer")
+        .description(
+            "context_lines",
+            "Lines of context before and after each comment (default: 2)",
+        )
+        .param("max_results", "integer")
+        .description(
+            "max_results",
+            "Maximum number of items to return (default: 100)",
+        )
+        .param("blame", "boolean")
+        .description(
+            "blame",
+            "Look up author and age with git blame (default: true)",
+        )
+        .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let root = PathBuf::from(params.path.unwrap_or_else(|| ".".to_string()));
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let tags: Vec<String> = params
+            .tags
+            .filter(|t| !t.is_empty())
+            .unwrap_or_else(|| DEFAULT_TAGS.iter().map(|t| t.to_string()).collect())
+            .into_iter()
+            .map(|t| t.to_uppercase())
+            .collect();
+        let context_lines = params
+            .context_lines
+            .unwrap_or(DEFAULT_CONTEXT_LINES)
+            .min(10);
+        let max_results =
+            ResultBudget::limit(params.budget, params.max_results, DEFAULT_MAX_RESULTS);
+
+        let pattern = tag_regex(&tags);
+        let scan_root = root.clone();
+        let (mut items, files_scanned) = tokio::task::spawn_blocking(move || {
+            scan_workspace(&scan_root, &pattern, context_lines)
+        })
+        .await
+        .map_err(|e| format!("TODO scan failed: {}", e))?;
+
+        let git = GitOps::new(&root);
+        let blame_available = params.blame.unwrap_or(true) && git.is_git_repo().await;
+        if blame_available {
+            add_blame_info(&git, &mut items).await;
+        }
+
+        for item in &mut items {
+            item.score = priority_score(item);
+            item.priority = priority_label(item.score).to_string();
+        }
+        items.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.age_days.unwrap_or(0).cmp(&a.age_days.unwrap_or(0)))
+                .then(a.path.cmp(&b.path))
+                .then(a.line.cmp(&b.line))
+        });
+
+        let mut by_tag = BTreeMap::new();
+        for item in &items {
+            *by_tag.entry(item.tag.clone()).or_insert(0) += 1;
+        }
+        let total_found = items.len();
+        let limit_reached = total_found > max_results;
+        items.truncate(max_results);
+
+        Ok(FindTodosResult {
+            items,
+            total_found,
+            by_tag,
+            files_scanned,
+            blame_available,
+            limit_reached,
+        })
+    }
+}
+
+/// Build the regex matching any of the (upper-case) tags
+///
+/// Captures the tag, an optional `(owner)` and the remaining comment text.
+fn tag_regex(tags: &[String]) -> Regex {
+    let alternatives: Vec<String> = tags.iter().map(|t| regex::escape(t)).collect();
+    Regex::new(&format!(
+        r"\b({})\b(?:\(([^)]*)\))?[:\s-]*(.*)",
+        alternatives.join("|")
+    ))
+    .expect("escaped tags form a valid regex")
+}
+
+/// Walk the workspace and collect tagged comments from text files
+fn scan_workspace(root: &Path, pattern: &Regex, context_lines: usize) -> (Vec<TodoItem>, usize) {
+    let mut items = Vec::new();
+    let mut files_scanned = 0;
+
+    for entry in ignore::WalkBuilder::new(root).build().flatten() {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if entry
+            .metadata()
+            .map(|m| m.len() > MAX_FILE_SIZE)
+            .unwrap_or(true)
+        {
+            continue;
+        }
+        // Skips binary and non-UTF-8 files
+        let Ok(content) = std::fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        files_scanned += 1;
+
+        let relative = entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        items.extend(scan_content(&relative, &content, pattern, context_lines));
+    }
+
+    (items, files_scanned)
+}
+
+/// Find tagged comments in a single file's content
+fn scan_content(path: &str, content: &str, pattern: &Regex, context_lines: usize) -> Vec<TodoItem> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        // Matching is case-sensitive so prose like "todo list" is ignored
+        let Some(captures) = pattern.captures(line) else {
+            continue;
+        };
+        // Only count tags inside comments, not in strings or identifiers
+        let prefix = &line[..captures.get(0).map_or(0, |m| m.start())];
+        let in_comment = COMMENT_MARKERS.iter().any(|m| prefix.contains(m))
+            || prefix.trim_start().starts_with('*');
+        if !in_comment {
+            continue;
+        }
+        let tag = &captures[1];
+        let text = captures[3]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim()
+            .to_string();
+
+        let start = index.saturating_sub(context_lines);
+        let end = (index + context_lines + 1).min(lines.len());
+        let context = (start..end)
+            .map(|i| format!("{:>5}: {}", i + 1, lines[i]))
+            .collect();
+
+        items.push(TodoItem {
+            path: path.to_string(),
+            line: index + 1,
+            tag: tag.to_string(),
+            owner: captures
+                .get(2)
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|o| !o.is_empty()),
+            text,
+            context,
+            author: None,
+            age_days: None,
+            score: 0,
+            priority: String::new(),
+        });
+    }
+
+    items
+}
+
+/// Fill in author and age from git blame, one blame per file
+///
+/// Item paths are relative to the scanned directory, which is also where
+/// `git` runs.
+async fn add_blame_info(git: &GitOps, items: &mut [TodoItem]) {
+    let now = chrono::Utc::now().timestamp();
+    let mut by_file: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, item) in items.iter().enumerate() {
+        by_file.entry(item.path.clone()).or_default().push(i);
+    }
+
+    for (path, indices) in by_file {
+        // Untracked files cannot be blamed; leave them without age
+        let Ok(blame) = git.blame(Path::new(&path)).await else {
+            continue;
+        };
+        for i in indices {
+            let item = &mut items[i];
+            let Some(line) = blame.iter().find(|b| b.line == item.line) else {
+                continue;
+            };
+            if line.is_uncommitted() {
+                item.age_days = Some(0);
+            } else {
+                item.author = Some(line.author.clone());
+                item.age_days = Some(((now - line.timestamp) / 86_400).max(0));
+            }
+        }
+    }
+}
+
+/// Score an item: tag severity, urgent wording and age
+fn priority_score(item: &TodoItem) -> u32 {
+    let mut score = match item.tag.as_str() {
+        "FIXME" => 3,
+        "HACK" => 2,
+        _ => 1,
+    };
+    let text = item.text.to_lowercase();
+    if URGENT_WORDS.iter().any(|w| text.contains(w)) || text.contains('!') {
+        score += 2;
+    }
+    score += match item.age_days {
+        Some(days) if days >= 365 => 2,
+        Some(days) if days >= 90 => 1,
+        _ => 0,
+    };
+    score
+}
+
+/// Map a score to a priority bucket
+fn priority_label(score: u32) -> &'static str {
+    match score {
+        5.. => "high",
+        3..=4 => "medium",
+        _ => "low",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn default_pattern() -> Regex {
+        let tags: Vec<String> = DEFAULT_TAGS.iter().map(|t| t.to_string()).collect();
+        tag_regex(&tags)
+    }
+
+    #[test]
+    fn test_scan_content() {
+        let content = "fn main() {\n\
+                       // TODO(alice): handle errors\n\
+                       let todo = \"TODO in a string\";\n\
+                       # FIXME: crash on empty input\n\
+                       /* HACK temporary workaround */\n\
+                       // todo lowercase prose\n\
+                       }\n";
+        let items = scan_content("src/main.rs", content, &default_pattern(), 1);
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].tag, "TODO");
+        assert_eq!(items[0].line, 2);
+        assert_eq!(items[0].owner.as_deref(), Some("alice"));
+        assert_eq!(items[0].text, "handle errors");
+        assert_eq!(items[0].context.len(), 3);
+
+        assert_eq!(items[1].tag, "FIXME");
+        assert_eq!(items[2].text, "temporary workaround");
+    }
+
+    #[test]
+    fn test_priority_score() {
+        let mut item = scan_content(
+            "a.rs",
+            "// FIXME: crash on empty input",
+            &default_pattern(),
+            0,
+        )
+        .remove(0);
+        item.age_days = Some(400);
+        assert_eq!(priority_score(&item), 7);
+        assert_eq!(priority_label(priority_score(&item)), "high");
+
+        let item = scan_content("a.rs", "// TODO: tidy up", &default_pattern(), 0).remove(0);
+        assert_eq!(priority_label(priority_score(&item)), "low");
+    }
+
+    #[tokio::test]
+    async fn test_find_todos_sorted() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.rs"), "// TODO: later\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.py"), "# FIXME: security hole\n").unwrap();
+
+        let result = FindTodosTool::new()
+            .execute(FindTodosParams {
+                path: Some(temp_dir.path().to_string_lossy().to_string()),
+                tags: None,
+                context_lines: None,
+                max_results: Some(1),
+                budget: None,
+                blame: Some(false),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.total_found, 2);
+        assert!(result.limit_reached);
+        assert_eq!(result.items[0].path, "b.py");
+        assert_eq!(result.items[0].priority, "high");
+        assert_eq!(result.by_tag.get("TODO"), Some(&1));
+    }
+}
@@ -0,0 +1,206 @@
+//! Git commit tool with AI-drafted conventional commit messages
+//!
+//! Stages the requested files and proposes a commit message. When no message
+//! is given, the staged diff is sent to the model to draft a conventional
+//! commit message. The tool never commits: the user reviews the draft and
+//! creates the commit with `/commit`, so the model can't commit on its own.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::utils::commit_message::{
+    draft_commit_message, is_conventional_commit, stage_for_commit,
+};
+use crate::utils::config::Config;
+use crate::utils::git_ops::GitOps;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for the git_commit tool
+#[derive(Debug, Deserialize)]
+pub struct GitCommitParams {
+    /// Files to stage (default: already staged files, or all tracked changes)
+    pub files: Option<Vec<String>>,
+    /// Commit message (default: drafted by the model from the staged diff)
+    pub message: Option<String>,
+    /// Repository directory (default: current directory)
+    pub path: Option<String>,
+}
+
+/// Result of a git_commit call
+#[derive(Debug, Serialize, Default)]
+pub struct GitCommitResult {
+    /// The proposed commit message
+    pub message: String,
+    /// Whether the message was drafted by the model
+    pub drafted: bool,
+    /// Whether the subject line follows the conventional commits format
+    pub conventional: bool,
+    /// Files staged for the commit
+    pub files: Vec<String>,
+    /// What the user has to do to create the commit
+    pub next_step: String,
+}
+
+/// Tool that stages files and drafts a conventional commit message for `/commit`
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let tool = GitCommitTool::new();
+/// let result = tool.execute(GitCommitParams {
+///     files: Some(vec!["src/lib.rs".to_string()]),
+///     message: None,
+///     path: None,
+/// }).await?;
+/// println!("Proposed message: {}", result.message);
+/// ```
+pub struct GitCommitTool;
+
+impl GitCommitTool {
+    /// Create a new GitCommitTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for GitCommitTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for GitCommitTool {
+    type Params = GitCommitParams;
+    type Result = GitCommitResult;
+
+    fn name(&self) -> &str {
+        "git_commit"
+    }
+
+    fn description(&self) -> &str {
+        "Stage files and draft a git commit message. If no message is given, a conventional commit message (type(scope): subject) is drafted from the staged diff. This does not commit: show the draft to the user, who creates the commit with /commit."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new(
+            "git_commit",
+            "Stage files and draft a conventional commit message",
+        )
+        .param("files", "array")
+        .description(
+            "files",
+            "Files to stage, e.g. [\"src/main.rs\"] (default: already staged files, or all tracked changes)",
+        )
+        .param("message", "string")
+        .description(
+            "message",
+            "Commit message (default: drafted from the staged diff)",
+        )
+        .param("path", "string")
+        .description("path", "Repository directory (default: current directory)")
+        .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let GitCommitParams {
+            files,
+            message,
+            path,
+        } = params;
+
+        let git = GitOps::new(path.unwrap_or_else(|| ".".to_string()));
+        let files = stage_for_commit(&git, &files.unwrap_or_default())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let (message, drafted) = match message.filter(|m| !m.trim().is_empty()) {
+            Some(message) => (message.trim().to_string(), false),
+            None => {
+                let config = Config::load_or_default().map_err(|e| e.to_string())?;
+                let diff = git.staged_diff().await.map_err(|e| e.to_string())?;
+                let message = draft_commit_message(&config, &diff)
+                    .await
+                    .map_err(|e| format!("Failed to draft commit message: {}", e))?;
+                (message, true)
+            }
+        };
+        let conventional = is_conventional_commit(&message);
+
+        // Committing is left to the user so nothing lands without review
+        let next_step = format!(
+            "Nothing was committed. Run /commit {} to review this message and commit.",
+            files.join(" ")
+        );
+        Ok(GitCommitResult {
+            message,
+            drafted,
+            conventional,
+            files,
+            next_step,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::process::Command as TokioCommand;
+
+    async fn git(dir: &std::path::Path, args: &[&str]) {
+        let status = TokioCommand::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .await
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[tokio::test]
+    async fn test_drafts_without_committing() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "--quiet"]).await;
+        git(dir, &["config", "user.name", "Test"]).await;
+        git(dir, &["config", "user.email", "test@example.com"]).await;
+        std::fs::write(dir.join("notes.md"), "# Notes\n").unwrap();
+
+        let result = GitCommitTool::new()
+            .execute(GitCommitParams {
+                files: Some(vec!["notes.md".to_string()]),
+                message: Some("docs: add notes".to_string()),
+                path: Some(dir.to_string_lossy().to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.files, vec!["notes.md"]);
+        assert!(result.conventional);
+        assert!(!result.drafted);
+        assert!(result.next_step.contains("/commit notes.md"));
+
+        // The file is staged but no commit exists yet
+        let head = TokioCommand::new("git")
+            .args(["rev-parse", "--verify", "HEAD"])
+            .current_dir(dir)
+            .output()
+            .await
+            .unwrap();
+        assert!(!head.status.success());
+    }
+
+    #[tokio::test]
+    async fn test_nothing_to_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        git(temp_dir.path(), &["init", "--quiet"]).await;
+
+        let result = GitCommitTool::new()
+            .execute(GitCommitParams {
+                files: None,
+                message: Some("chore: nothing".to_string()),
+                path: Some(temp_dir.path().to_string_lossy().to_string()),
+            })
+            .await;
+        assert!(result.unwrap_err().contains("Nothing to commit"));
+    }
+}
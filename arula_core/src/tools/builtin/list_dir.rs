@@ -160,6 +160,10 @@ impl Tool for ListDirectoryTool {
         "list_directory"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "List the contents of a directory. Can show hidden files and optionally list recursively."
     }
@@ -0,0 +1,212 @@
+//! Long-term memory tools
+//!
+//! `remember` stores a short fact (a user preference, a project convention)
+//! so later sessions start out knowing it; `recall` looks facts up. Both work
+//! on the stores in `crate::utils::memory`.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::utils::memory::{Memory, MemoryScope, MemoryStore};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Default number of facts returned by recall
+const DEFAULT_LIMIT: usize = 10;
+
+fn parse_scope(scope: Option<&str>, default: MemoryScope) -> Result<MemoryScope, String> {
+    match scope {
+        None => Ok(default),
+        Some(s) => MemoryScope::parse(s)
+            .ok_or_else(|| format!("Unknown scope '{}': use \"user\" or \"project\"", s)),
+    }
+}
+
+fn project_root() -> Result<std::path::PathBuf, String> {
+    std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))
+}
+
+/// Parameters for the remember tool
+#[derive(Debug, Deserialize)]
+pub struct RememberParams {
+    /// The fact to store
+    pub fact: String,
+    /// "user" (all projects) or "project" (default: project)
+    pub scope: Option<String>,
+    /// Tags to find the fact by
+    pub tags: Option<Vec<String>>,
+}
+
+/// Result of a remember call
+#[derive(Debug, Serialize)]
+pub struct RememberResult {
+    pub id: u32,
+    pub scope: String,
+    pub text: String,
+    /// Whether an identical fact was already stored
+    pub already_known: bool,
+}
+
+/// Tool that stores a fact for later sessions
+pub struct RememberTool;
+
+impl RememberTool {
+    /// Create a new RememberTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RememberTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RememberTool {
+    type Params = RememberParams;
+    type Result = RememberResult;
+
+    fn name(&self) -> &str {
+        "remember"
+    }
+
+    fn description(&self) -> &str {
+        "Store a short fact that should be known in future sessions, such as a user preference (\"prefers concise answers\") or a project convention (\"tests use pytest fixtures in conftest.py\"). Stored facts are shown at the start of every session. Only store durable facts the user stated or confirmed, not task progress."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new("remember", "Store a fact for future sessions")
+            .param("fact", "string")
+            .description("fact", "The fact to remember, as one short sentence")
+            .required("fact")
+            .param("scope", "string")
+            .description(
+                "scope",
+                "\"user\" for preferences that apply everywhere, \"project\" for this project only (default: project)",
+            )
+            .param("tags", "array")
+            .description("tags", "Keywords to find the fact by")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let scope = parse_scope(params.scope.as_deref(), MemoryScope::Project)?;
+        let mut store =
+            MemoryStore::open_scope(scope, &project_root()?).map_err(|e| e.to_string())?;
+        let before = store.memories().len();
+        let memory = store
+            .remember(&params.fact, &params.tags.unwrap_or_default())
+            .map_err(|e| e.to_string())?
+            .clone();
+        store.save().map_err(|e| e.to_string())?;
+
+        Ok(RememberResult {
+            id: memory.id,
+            scope: scope.as_str().to_string(),
+            text: memory.text,
+            already_known: store.memories().len() == before,
+        })
+    }
+}
+
+/// Parameters for the recall tool
+#[derive(Debug, Deserialize)]
+pub struct RecallParams {
+    /// Words to look for (default: newest facts)
+    pub query: Option<String>,
+    /// "user" or "project" (default: both)
+    pub scope: Option<String>,
+    /// Maximum number of facts (default: 10)
+    pub limit: Option<usize>,
+}
+
+/// A recalled fact
+#[derive(Debug, Serialize)]
+pub struct RecalledMemory {
+    pub id: u32,
+    pub scope: String,
+    pub text: String,
+    pub tags: Vec<String>,
+    pub created_at: String,
+}
+
+/// Result of a recall call
+#[derive(Debug, Serialize)]
+pub struct RecallResult {
+    pub memories: Vec<RecalledMemory>,
+}
+
+/// Tool that looks up stored facts
+pub struct RecallTool;
+
+impl RecallTool {
+    /// Create a new RecallTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RecallTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RecallTool {
+    type Params = RecallParams;
+    type Result = RecallResult;
+
+    fn name(&self) -> &str {
+        "recall"
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Look up facts stored with `remember` in this or earlier sessions. Matches query words against each fact and its tags, best match first; without a query returns the newest facts."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new("recall", "Look up facts stored in earlier sessions")
+            .param("query", "string")
+            .description("query", "Words to look for (default: newest facts)")
+            .param("scope", "string")
+            .description("scope", "\"user\" or \"project\" (default: both)")
+            .param("limit", "integer")
+            .description("limit", "Maximum number of facts to return (default: 10)")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let scopes = match params.scope.as_deref() {
+            None => vec![MemoryScope::Project, MemoryScope::User],
+            scope => vec![parse_scope(scope, MemoryScope::Project)?],
+        };
+        let query = params.query.unwrap_or_default();
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+        let root = project_root()?;
+
+        let mut memories = Vec::new();
+        for scope in scopes {
+            let store = MemoryStore::open_scope(scope, &root).map_err(|e| e.to_string())?;
+            memories.extend(
+                store
+                    .recall(&query, limit)
+                    .into_iter()
+                    .map(|m: &Memory| RecalledMemory {
+                        id: m.id,
+                        scope: scope.as_str().to_string(),
+                        text: m.text.clone(),
+                        tags: m.tags.clone(),
+                        created_at: m.created_at.to_rfc3339(),
+                    }),
+            );
+        }
+        memories.truncate(limit);
+
+        Ok(RecallResult { memories })
+    }
+}
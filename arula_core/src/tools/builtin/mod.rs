@@ -11,6 +11,12 @@
 //! - `web_search` - Search the web
 //! - `visioneer` - Vision/screenshot capabilities
 //! - `question` - Ask clarifying questions
+//! - `run_tests` - Run the project's test suite with structured results
+//! - `git_commit` - Stage and commit with a conventional commit message
+//! - `find_todos` - Harvest TODO/FIXME/HACK comments into a prioritized list
+//! - `find_symbol` - Find function, method and type definitions by name
+//! - `remember` / `recall` - Store and look up facts across sessions
+//! - `task_complete` - Signal that the task is finished
 //!
 //! # Architecture
 //!
@@ -31,9 +37,15 @@ pub mod file_edit;
 pub mod file_read;
 pub mod file_write;
 pub mod find_files;
+pub mod find_symbol;
+pub mod find_todos;
+pub mod git_commit;
 pub mod list_dir;
+pub mod memory;
 pub mod question;
+pub mod run_tests;
 pub mod search;
+pub mod task_complete;
 pub mod web_search;
 
 // Re-export all tools for public API
@@ -49,10 +61,22 @@ pub use file_write::{WriteFileParams, WriteFileResult, WriteFileTool};
 #[allow(unused_imports)]
 pub use find_files::{FindFilesParams, FindFilesResult, FindFilesTool, FoundFile};
 #[allow(unused_imports)]
+pub use find_symbol::{FindSymbolParams, FindSymbolResult, FindSymbolTool, SymbolMatch};
+#[allow(unused_imports)]
+pub use find_todos::{FindTodosParams, FindTodosResult, FindTodosTool, TodoItem};
+#[allow(unused_imports)]
+pub use git_commit::{GitCommitParams, GitCommitResult, GitCommitTool};
+#[allow(unused_imports)]
 pub use list_dir::{DirectoryEntry, ListDirParams, ListDirResult, ListDirectoryTool};
 #[allow(unused_imports)]
+pub use memory::{RecallParams, RecallResult, RecallTool, RecalledMemory, RememberParams, RememberResult, RememberTool};
+#[allow(unused_imports)]
 pub use question::{QuestionParams, QuestionResult, QuestionTool, QUESTION_HANDLER, QuestionHandler, Question, Answer};
 #[allow(unused_imports)]
+pub use run_tests::{RunTestsParams, RunTestsResult, RunTestsTool};
+#[allow(unused_imports)]
 pub use search::{FileMatch, SearchMatch, SearchParams, SearchResult, SearchTool};
 #[allow(unused_imports)]
+pub use task_complete::{TaskCompleteParams, TaskCompleteResult, TaskCompleteTool};
+#[allow(unused_imports)]
 pub use web_search::{WebSearchParams, WebSearchResult, WebSearchResultItem, WebSearchTool};
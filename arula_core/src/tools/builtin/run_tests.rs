@@ -0,0 +1,593 @@
+//! Project-aware test runner tool
+//!
+//! This tool uses project detection to pick the right test command
+//! (`cargo test`, `npm test`, `pytest`, `go test`), runs it, and returns
+//! structured pass/fail counts together with the names of failing tests.
+//!
+//! Large outputs are truncated around failures so the model sees the
+//! relevant assertion messages instead of thousands of passing lines.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::utils::project_context::{ProjectType, detect_project};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Instant;
+use tokio::process::Command as TokioCommand;
+
+/// Maximum number of characters of test output returned to the model
+const MAX_OUTPUT_CHARS: usize = 8000;
+
+/// Number of lines kept before and after each failure marker when truncating
+const FAILURE_CONTEXT_LINES: usize = 12;
+
+/// Number of trailing lines always kept (summary lines live at the end)
+const TAIL_LINES: usize = 20;
+
+/// Parameters for the run_tests tool
+#[derive(Debug, Deserialize)]
+pub struct RunTestsParams {
+    /// Project directory to run tests in (default: current directory)
+    pub path: Option<String>,
+    /// Optional test name filter passed to the underlying test runner
+    pub filter: Option<String>,
+    /// Optional explicit command overriding the detected one
+    pub command: Option<String>,
+    /// Optional timeout in seconds (default: 300, max: 1800)
+    pub timeout_seconds: Option<u64>,
+}
+
+/// Result from running a project's test suite
+#[derive(Debug, Serialize, Default)]
+pub struct RunTestsResult {
+    /// The command that was executed
+    pub command: String,
+    /// Detected project type (e.g. "Rust", "Node.js")
+    pub project_type: String,
+    /// Whether the test command exited successfully
+    pub success: bool,
+    /// Exit code of the test command
+    pub exit_code: i32,
+    /// Number of passed tests (as reported by the runner)
+    pub passed: usize,
+    /// Number of failed tests (as reported by the runner)
+    pub failed: usize,
+    /// Number of ignored/skipped tests
+    pub ignored: usize,
+    /// Names of the failing tests
+    pub failing_tests: Vec<String>,
+    /// Combined stdout/stderr, truncated around failures if large
+    pub output: String,
+    /// Whether the output was truncated
+    pub truncated: bool,
+    /// Wall-clock duration of the test run in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Parsed summary of a test run
+#[derive(Debug, Default, PartialEq)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub failing_tests: Vec<String>,
+}
+
+/// Test runner tool that understands the project type
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let tool = RunTestsTool::new();
+/// let result = tool.execute(RunTestsParams {
+///     path: None,
+///     filter: Some("config".to_string()),
+///     command: None,
+///     timeout_seconds: None,
+/// }).await?;
+/// println!("{} passed, {} failed", result.passed, result.failed);
+/// ```
+pub struct RunTestsTool;
+
+impl RunTestsTool {
+    /// Create a new RunTestsTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RunTestsTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RunTestsTool {
+    type Params = RunTestsParams;
+    type Result = RunTestsResult;
+
+    fn name(&self) -> &str {
+        "run_tests"
+    }
+
+    fn description(&self) -> &str {
+        "Run the project's test suite using the right command for the detected project type (cargo test, npm test, pytest, go test). Returns pass/fail counts, the names of failing tests, and output truncated around failures. Prefer this over execute_bash for running tests."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new(
+            "run_tests",
+            "Run the project's tests and return structured pass/fail results",
+        )
+        .param("path", "string")
+        .description(
+            "path",
+            "Project directory to run tests in (default: current directory)",
+        )
+        .param("filter", "string")
+        .description("filter", "Only run tests whose name matches this filter")
+        .param("command", "string")
+        .description(
+            "command",
+            "Explicit test command to run instead of the detected one",
+        )
+        .param("timeout_seconds", "integer")
+        .description(
+            "timeout_seconds",
+            "Timeout in seconds for the test run (default: 300, max: 1800)",
+        )
+        .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let RunTestsParams {
+            path,
+            filter,
+            command,
+            timeout_seconds,
+        } = params;
+
+        let path = path.unwrap_or_else(|| ".".to_string());
+        let dir = Path::new(&path);
+        if !dir.is_dir() {
+            return Err(format!("'{}' is not a directory", path));
+        }
+
+        let project_type = detect_project(dir)
+            .map(|p| p.project_type)
+            .unwrap_or(ProjectType::Unknown);
+
+        let command = match command {
+            Some(cmd) if !cmd.trim().is_empty() => cmd,
+            _ => build_test_command(dir, &project_type, filter.as_deref()).ok_or_else(|| {
+                format!(
+                    "Could not detect a test command for '{}'. Pass an explicit 'command'.",
+                    path
+                )
+            })?,
+        };
+
+        let timeout_secs = timeout_seconds.unwrap_or(300).min(1800);
+        let started = Instant::now();
+        let (output, exit_code, success) = run_command(&command, dir, timeout_secs).await?;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let summary = parse_test_output(&project_type, &output);
+        let (output, truncated) = truncate_around_failures(&output, MAX_OUTPUT_CHARS);
+
+        Ok(RunTestsResult {
+            command,
+            project_type: project_type.as_str().to_string(),
+            success,
+            exit_code,
+            passed: summary.passed,
+            failed: summary.failed,
+            ignored: summary.ignored,
+            failing_tests: summary.failing_tests,
+            output,
+            truncated,
+            duration_ms,
+        })
+    }
+}
+
+/// Build the test command for a project type, applying an optional filter
+pub fn build_test_command(
+    dir: &Path,
+    project_type: &ProjectType,
+    filter: Option<&str>,
+) -> Option<String> {
+    let filter = filter.map(str::trim).filter(|f| !f.is_empty());
+
+    let command = match project_type {
+        ProjectType::Rust => {
+            let base = if is_cargo_workspace_root(dir) {
+                "cargo test --workspace".to_string()
+            } else {
+                detect_project(dir)
+                    .and_then(|p| p.test_command)
+                    .unwrap_or_else(|| "cargo test".to_string())
+            };
+            match filter {
+                Some(f) => format!("{} {}", base, f),
+                None => base,
+            }
+        }
+        ProjectType::Node => match filter {
+            Some(f) => format!("npm test -- {}", f),
+            None => "npm test".to_string(),
+        },
+        ProjectType::Python => match filter {
+            Some(f) => format!("pytest -k {}", f),
+            None => "pytest".to_string(),
+        },
+        ProjectType::Go => match filter {
+            Some(f) => format!("go test ./... -run {}", f),
+            None => "go test ./...".to_string(),
+        },
+        ProjectType::Unknown => return None,
+    };
+
+    Some(command)
+}
+
+/// Check whether a Cargo.toml is a virtual workspace manifest (no `[package]`)
+fn is_cargo_workspace_root(dir: &Path) -> bool {
+    std::fs::read_to_string(dir.join("Cargo.toml"))
+        .map(|content| {
+            let has_workspace = content.lines().any(|l| l.trim() == "[workspace]");
+            let has_package = content.lines().any(|l| l.trim() == "[package]");
+            has_workspace && !has_package
+        })
+        .unwrap_or(false)
+}
+
+/// Run a command in a directory, returning combined output, exit code and success
+pub(crate) async fn run_command(
+    command: &str,
+    dir: &Path,
+    timeout_secs: u64,
+) -> Result<(String, i32, bool), String> {
+    use tokio::time::Duration;
+
+    let mut cmd = TokioCommand::from(crate::platform::current().shell_command(command));
+
+    cmd.current_dir(dir);
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd.kill_on_drop(true);
+
+    let child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn test command '{}': {}", command, e))?;
+
+    tokio::select! {
+        result = child.wait_with_output() => {
+            let output = result.map_err(|e| format!("Failed to run tests: {}", e))?;
+            let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                if !combined.is_empty() && !combined.ends_with('\n') {
+                    combined.push('\n');
+                }
+                combined.push_str(&stderr);
+            }
+            Ok((combined, output.status.code().unwrap_or(-1), output.status.success()))
+        }
+        _ = tokio::time::sleep(Duration::from_secs(timeout_secs)) => {
+            Err(format!("Test command '{}' timed out after {} seconds", command, timeout_secs))
+        }
+    }
+}
+
+/// Parse runner output into a structured summary
+pub fn parse_test_output(project_type: &ProjectType, output: &str) -> TestSummary {
+    match project_type {
+        ProjectType::Rust => parse_cargo_output(output),
+        ProjectType::Python => parse_pytest_output(output),
+        ProjectType::Go => parse_go_output(output),
+        ProjectType::Node => parse_node_output(output),
+        ProjectType::Unknown => {
+            // Try each format and keep whichever recognised something
+            [
+                parse_cargo_output(output),
+                parse_pytest_output(output),
+                parse_go_output(output),
+                parse_node_output(output),
+            ]
+            .into_iter()
+            .find(|s| s.passed + s.failed + s.ignored > 0)
+            .unwrap_or_default()
+        }
+    }
+}
+
+/// Extract the number preceding `label` in a line like "3 passed; 1 failed"
+fn count_before(line: &str, label: &str) -> Option<usize> {
+    let idx = line.find(label)?;
+    line[..idx]
+        .trim_end()
+        .rsplit(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|n| n.parse().ok())
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+    let name = name.trim();
+    if !name.is_empty() && !names.iter().any(|n| n == name) {
+        names.push(name.to_string());
+    }
+}
+
+/// Parse `cargo test` output (sums every "test result:" line across test binaries)
+fn parse_cargo_output(output: &str) -> TestSummary {
+    let mut summary = TestSummary::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.starts_with("test result:") {
+            summary.passed += count_before(line, " passed").unwrap_or(0);
+            summary.failed += count_before(line, " failed").unwrap_or(0);
+            summary.ignored += count_before(line, " ignored").unwrap_or(0);
+        } else if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            push_unique(&mut summary.failing_tests, name);
+        }
+    }
+
+    summary
+}
+
+/// Parse pytest output ("FAILED path::test - msg" lines and the final summary)
+fn parse_pytest_output(output: &str) -> TestSummary {
+    let mut summary = TestSummary::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("FAILED ") {
+            let name = rest.split(" - ").next().unwrap_or(rest);
+            push_unique(&mut summary.failing_tests, name);
+        } else if (line.starts_with('=') || line.contains(" in "))
+            && (line.contains(" passed") || line.contains(" failed"))
+        {
+            summary.passed = count_before(line, " passed").unwrap_or(summary.passed);
+            summary.failed = count_before(line, " failed").unwrap_or(summary.failed);
+            summary.ignored = count_before(line, " skipped").unwrap_or(summary.ignored);
+        }
+    }
+
+    summary.failed = summary.failed.max(summary.failing_tests.len());
+    summary
+}
+
+/// Parse `go test` output ("--- FAIL: TestName" / "--- PASS: TestName" lines)
+fn parse_go_output(output: &str) -> TestSummary {
+    let mut summary = TestSummary::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("--- FAIL: ") {
+            let name = rest.split_whitespace().next().unwrap_or(rest);
+            push_unique(&mut summary.failing_tests, name);
+        } else if line.starts_with("--- PASS: ") {
+            summary.passed += 1;
+        } else if line.starts_with("--- SKIP: ") {
+            summary.ignored += 1;
+        }
+    }
+
+    summary.failed = summary.failing_tests.len();
+    summary
+}
+
+/// Parse Jest ("Tests: 1 failed, 5 passed") and Mocha ("5 passing", "1 failing") output
+fn parse_node_output(output: &str) -> TestSummary {
+    let mut summary = TestSummary::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("Tests:") {
+            summary.passed = count_before(rest, " passed").unwrap_or(0);
+            summary.failed = count_before(rest, " failed").unwrap_or(0);
+            summary.ignored = count_before(rest, " skipped").unwrap_or(0);
+        } else if let Some(rest) = line.strip_prefix("● ") {
+            push_unique(&mut summary.failing_tests, rest);
+        } else if line.ends_with(" passing") || line.contains(" passing (") {
+            summary.passed = count_before(line, " passing").unwrap_or(summary.passed);
+        } else if line.ends_with(" failing") {
+            summary.failed = count_before(line, " failing").unwrap_or(summary.failed);
+        } else if line.ends_with(" pending") {
+            summary.ignored = count_before(line, " pending").unwrap_or(summary.ignored);
+        }
+    }
+
+    summary
+}
+
+/// Whether a line marks a failure worth keeping context around
+fn is_failure_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.contains("FAILED")
+        || trimmed.starts_with("FAIL")
+        || trimmed.starts_with("--- FAIL")
+        || trimmed.starts_with("● ")
+        || trimmed.starts_with("error")
+        || trimmed.contains("panicked at")
+        || trimmed.starts_with("E  ")
+        || trimmed.starts_with("AssertionError")
+}
+
+/// Truncate output to roughly `max_chars`, keeping context around failures and the tail
+///
+/// Returns the (possibly) truncated output and whether truncation happened.
+pub fn truncate_around_failures(output: &str, max_chars: usize) -> (String, bool) {
+    if output.len() <= max_chars {
+        return (output.to_string(), false);
+    }
+
+    let lines: Vec<&str> = output.lines().collect();
+    let mut keep = vec![false; lines.len()];
+
+    // Always keep the summary at the end
+    for flag in keep.iter_mut().skip(lines.len().saturating_sub(TAIL_LINES)) {
+        *flag = true;
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        if is_failure_line(line) {
+            let start = i.saturating_sub(FAILURE_CONTEXT_LINES);
+            let end = (i + FAILURE_CONTEXT_LINES + 1).min(lines.len());
+            for flag in &mut keep[start..end] {
+                *flag = true;
+            }
+        }
+    }
+
+    let mut result = String::new();
+    let mut omitted = 0usize;
+    for (line, kept) in lines.iter().zip(&keep) {
+        if *kept {
+            if omitted > 0 {
+                result.push_str(&format!("... [{} lines omitted] ...\n", omitted));
+                omitted = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        } else {
+            omitted += 1;
+        }
+    }
+    if omitted > 0 {
+        result.push_str(&format!("... [{} lines omitted] ...\n", omitted));
+    }
+
+    // Still too large (many failures): keep the head and tail of what we selected
+    if result.len() > max_chars {
+        let half = max_chars / 2;
+        let head_end = floor_char_boundary(&result, half);
+        let tail_start = floor_char_boundary(&result, result.len() - half);
+        result = format!(
+            "{}\n... [output truncated] ...\n{}",
+            &result[..head_end],
+            &result[tail_start..]
+        );
+    }
+
+    (result, true)
+}
+
+fn floor_char_boundary(s: &str, mut idx: usize) -> usize {
+    while idx > 0 && !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_cargo_output() {
+        let output = "\
+running 3 tests
+test config::tests::test_load ... ok
+test config::tests::test_save ... FAILED
+test config::tests::test_slow ... ignored
+
+failures:
+
+---- config::tests::test_save stdout ----
+thread 'config::tests::test_save' panicked at src/config.rs:10:5
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out
+
+running 2 tests
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let summary = parse_test_output(&ProjectType::Rust, output);
+        assert_eq!(summary.passed, 3);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.ignored, 1);
+        assert_eq!(summary.failing_tests, vec!["config::tests::test_save"]);
+    }
+
+    #[test]
+    fn test_parse_pytest_output() {
+        let output = "\
+FAILED tests/test_api.py::test_login - AssertionError: 401 != 200
+FAILED tests/test_api.py::test_logout
+==== 2 failed, 10 passed, 1 skipped in 0.52s ====
+";
+        let summary = parse_test_output(&ProjectType::Python, output);
+        assert_eq!(summary.passed, 10);
+        assert_eq!(summary.failed, 2);
+        assert_eq!(summary.ignored, 1);
+        assert_eq!(
+            summary.failing_tests,
+            vec![
+                "tests/test_api.py::test_login",
+                "tests/test_api.py::test_logout"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_go_and_node_output() {
+        let go = "--- PASS: TestA (0.00s)\n--- FAIL: TestB (0.01s)\nFAIL\texample.com/pkg\t0.02s\n";
+        let summary = parse_test_output(&ProjectType::Go, go);
+        assert_eq!(summary.passed, 1);
+        assert_eq!(summary.failing_tests, vec!["TestB"]);
+
+        let jest = "  ● Math › adds numbers\nTests:       1 failed, 4 passed, 5 total\n";
+        let summary = parse_test_output(&ProjectType::Node, jest);
+        assert_eq!(summary.passed, 4);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.failing_tests, vec!["Math › adds numbers"]);
+    }
+
+    #[test]
+    fn test_truncate_keeps_failures_and_tail() {
+        let mut output = String::new();
+        for i in 0..2000 {
+            output.push_str(&format!("test passing_{} ... ok\n", i));
+        }
+        output.push_str("test broken ... FAILED\n");
+        for i in 0..2000 {
+            output.push_str(&format!("test more_{} ... ok\n", i));
+        }
+        output.push_str("test result: FAILED. 4000 passed; 1 failed; 0 ignored\n");
+
+        let (truncated, was_truncated) = truncate_around_failures(&output, MAX_OUTPUT_CHARS);
+        assert!(was_truncated);
+        assert!(truncated.len() <= MAX_OUTPUT_CHARS + 64);
+        assert!(truncated.contains("test broken ... FAILED"));
+        assert!(truncated.contains("test result: FAILED"));
+        assert!(truncated.contains("lines omitted"));
+    }
+
+    #[test]
+    fn test_build_test_command() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"a\"]\n",
+        )
+        .unwrap();
+
+        let cmd = build_test_command(temp_dir.path(), &ProjectType::Rust, Some("parse"));
+        assert_eq!(cmd.as_deref(), Some("cargo test --workspace parse"));
+
+        let cmd = build_test_command(temp_dir.path(), &ProjectType::Python, Some("login"));
+        assert_eq!(cmd.as_deref(), Some("pytest -k login"));
+
+        assert!(build_test_command(temp_dir.path(), &ProjectType::Unknown, None).is_none());
+    }
+}
@@ -3,8 +3,11 @@
 //! This tool searches for patterns in files using regex or literal matching.
 
 use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::api::tool_budget::ResultBudget;
+use crate::tools::result_cache::scan_key;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs;
 use std::path::Path;
 
@@ -21,6 +24,9 @@ pub struct SearchParams {
     pub max_results: Option<usize>,
     /// File extensions to include (e.g., ["rs", "py"])
     pub extensions: Option<Vec<String>>,
+    /// Result budget from the context manager (internal, not in the schema)
+    #[serde(default, rename = "_budget")]
+    pub budget: Option<ResultBudget>,
 }
 
 /// A single match within a file
@@ -194,6 +200,18 @@ impl Tool for SearchTool {
         "search_files"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn budgeted(&self) -> bool {
+        true
+    }
+
+    fn cache_key(&self, params: &Value) -> Option<String> {
+        scan_key(params)
+    }
+
     fn description(&self) -> &str {
         "Search for patterns in files. Supports literal and regex matching. Results are limited to prevent API errors."
     }
@@ -227,6 +245,7 @@ impl Tool for SearchTool {
             regex,
             max_results,
             extensions,
+            budget,
         } = params;
 
         if pattern.is_empty() {
@@ -235,7 +254,7 @@ impl Tool for SearchTool {
 
         let search_path = path.unwrap_or_else(|| ".".to_string());
         let use_regex = regex.unwrap_or(false);
-        let max_results = max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let max_results = ResultBudget::limit(budget, max_results, DEFAULT_MAX_RESULTS);
 
         let mut results = Vec::new();
         let mut files_searched = 0;
@@ -284,6 +303,7 @@ mod tests {
                 path: Some(temp_dir.path().to_string_lossy().to_string()),
                 regex: Some(false),
                 max_results: None,
+                budget: None,
                 extensions: None,
             })
             .await
@@ -309,6 +329,7 @@ mod tests {
                 path: Some(temp_dir.path().to_string_lossy().to_string()),
                 regex: Some(true),
                 max_results: None,
+                budget: None,
                 extensions: None,
             })
             .await
@@ -0,0 +1,75 @@
+//! Task completion signal
+//!
+//! The model calls `task_complete` when it considers the task done. The call
+//! only records the claim: when the run ends, any success criteria set for
+//! the task are checked (see `crate::utils::success_criteria`) and decide
+//! whether it is reported as done.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::utils::success_criteria::TASK_COMPLETE_TOOL;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Parameters for the task_complete tool
+#[derive(Debug, Deserialize)]
+pub struct TaskCompleteParams {
+    /// What was done, in a sentence or two
+    pub summary: String,
+}
+
+/// Result of a task_complete call
+#[derive(Debug, Serialize)]
+pub struct TaskCompleteResult {
+    pub summary: String,
+    pub note: String,
+}
+
+/// Tool the model uses to say the task is finished
+pub struct TaskCompleteTool;
+
+impl TaskCompleteTool {
+    /// Create a new TaskCompleteTool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for TaskCompleteTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for TaskCompleteTool {
+    type Params = TaskCompleteParams;
+    type Result = TaskCompleteResult;
+
+    fn name(&self) -> &str {
+        TASK_COMPLETE_TOOL
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+
+    fn description(&self) -> &str {
+        "Signal that the task is finished, with a short summary of what was done. Call it once, as the last step, after checking the work (for example by running the tests). If success criteria were given, they are checked automatically afterwards and decide whether the task counts as done."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new(TASK_COMPLETE_TOOL, "Signal that the task is finished")
+            .param("summary", "string")
+            .description("summary", "What was done, in a sentence or two")
+            .required("summary")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        Ok(TaskCompleteResult {
+            summary: params.summary,
+            note: "Recorded. Stop here; any success criteria are checked when the run ends."
+                .to_string(),
+        })
+    }
+}
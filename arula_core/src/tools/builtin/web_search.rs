@@ -65,6 +65,10 @@ impl Tool for WebSearchTool {
         "web_search"
     }
 
+    fn read_only(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> &str {
         "Search the web using DuckDuckGo. Returns titles, URLs, and descriptions."
     }
@@ -87,16 +87,16 @@ impl McpClient {
 
         // Log the outgoing request
         let body_str = serde_json::to_string_pretty(&request_body).unwrap_or_default();
-        crate::utils::logger::info(&format!(
+        log_http(&format!(
             "=== HTTP REQUEST ===\nPOST {}\nHEADERS:\n",
             &self.config.url
         ));
-        crate::utils::logger::info("  Content-Type: application/json\n");
-        crate::utils::logger::info("  Accept: application/json, text/event-stream\n");
+        log_http("  Content-Type: application/json\n");
+        log_http("  Accept: application/json, text/event-stream\n");
         for (key, value) in &self.config.headers {
-            crate::utils::logger::info(&format!("  {}: {}\n", key, value));
+            log_http(&format!("  {}: {}\n", key, value));
         }
-        crate::utils::logger::info(&format!(
+        log_http(&format!(
             "BODY ({} bytes):\n{}\n===================\n",
             body_str.len(),
             body_str
@@ -108,19 +108,19 @@ impl McpClient {
             .map_err(|e| anyhow::anyhow!("Failed to send MCP request: {}", e))?;
 
         // Log the incoming response
-        crate::utils::logger::info(&format!(
+        log_http(&format!(
             "=== HTTP RESPONSE ===\n{} {}\nHEADERS:\n",
             response.status(),
             response.url()
         ));
         for (name, value) in response.headers() {
-            crate::utils::logger::info(&format!(
+            log_http(&format!(
                 "  {}: {}\n",
                 name,
                 value.to_str().unwrap_or("<binary>")
             ));
         }
-        crate::utils::logger::info(
+        log_http(
             "BODY: <not logged to avoid consumption>\n===================\n",
         );
 
@@ -216,6 +216,11 @@ impl McpClient {
     }
 }
 
+/// Log MCP traffic at debug level under the `mcp` module
+fn log_http(message: &str) {
+    crate::utils::logger::log_module(crate::utils::logger::LogLevel::Debug, "mcp", message);
+}
+
 /// Global MCP Manager for managing MCP clients
 pub struct McpManager {
     clients: RwLock<HashMap<String, McpClient>>,
@@ -9,13 +9,19 @@
 //! - `visioneer` - Vision/screenshot capabilities
 //! - `mcp` - Model Context Protocol client
 //! - `mcp_dynamic` - Dynamic MCP tool loading
+//! - `injection_guard` - Prompt-injection screening for tool outputs
+//! - `result_cache` - Session cache for deterministic tool results
+//! - `wasm_plugins` - Sandboxed WebAssembly plugin tools
 
 pub mod analyze_context;
 pub mod builtin;
+pub mod injection_guard;
 pub mod mcp;
 pub mod mcp_dynamic;
+pub mod result_cache;
 pub mod tools;
 pub mod visioneer;
+pub mod wasm_plugins;
 
 // Builtin tools available via:
 // builtin::{BashTool, FileReadTool, WriteFileTool, FileEditTool, etc.}
@@ -0,0 +1,166 @@
+//! Session cache for deterministic tool results
+//!
+//! Tools that return a cache key (see `Tool::cache_key`) have their
+//! successful results kept for the rest of the session, so a model asking
+//! for the same file or scan again doesn't redo the work. `read_file` keys
+//! include the file's mtime; scans are dropped whenever a tool that may
+//! change files runs, and every entry expires after `MAX_AGE`.
+
+use crate::api::agent::ToolResult;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// How long a cached result stays valid, for changes made outside ARULA
+const MAX_AGE: Duration = Duration::from_secs(120);
+
+/// Hit and miss counts of a cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl std::fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lookups = self.hits + self.misses;
+        let rate = if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 * 100.0 / lookups as f64
+        };
+        write!(
+            f,
+            "{} hit(s), {} miss(es) ({:.0}% hit rate), {} cached result(s)",
+            self.hits, self.misses, rate, self.entries
+        )
+    }
+}
+
+/// Cached tool results by key
+#[derive(Debug, Default)]
+pub struct ToolResultCache {
+    entries: Mutex<HashMap<String, (Instant, ToolResult)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ToolResultCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached result for `key`, counting the lookup as a hit or miss
+    pub fn get(&self, key: &str) -> Option<ToolResult> {
+        let mut entries = self.entries.lock().unwrap();
+        let hit = match entries.get(key) {
+            Some((stored, result)) if stored.elapsed() < MAX_AGE => Some(result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if hit.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        hit
+    }
+
+    pub fn insert(&self, key: String, result: ToolResult) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), result));
+    }
+
+    /// Drop every result, e.g. after files may have changed
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().unwrap().len(),
+        }
+    }
+}
+
+/// The cache shared by every tool registry in this process
+pub fn session_cache() -> &'static ToolResultCache {
+    static CACHE: OnceLock<ToolResultCache> = OnceLock::new();
+    CACHE.get_or_init(ToolResultCache::new)
+}
+
+/// Key for a scan whose result only depends on its parameters and the
+/// working directory
+pub fn scan_key(params: &Value) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    Some(format!("{}|{}", cwd.display(), params))
+}
+
+/// Key for reading `path`, valid until the file is modified
+pub fn file_key(path: &str, params: &Value) -> Option<String> {
+    let path = Path::new(path).canonicalize().ok()?;
+    let modified = path.metadata().ok()?.modified().ok()?;
+    let nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+    Some(format!("{}@{}|{}", path.display(), nanos, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_cache_hits_and_misses() {
+        let cache = ToolResultCache::new();
+        assert!(cache.get("a").is_none());
+        cache.insert("a".to_string(), ToolResult::success(json!(1)));
+        assert_eq!(cache.get("a").unwrap().data, json!(1));
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                entries: 1
+            }
+        );
+        cache.clear();
+        assert!(cache.get("a").is_none());
+        assert_eq!(
+            cache.stats().to_string(),
+            "1 hit(s), 2 miss(es) (33% hit rate), 0 cached result(s)"
+        );
+    }
+
+    #[test]
+    fn test_file_key_changes_with_mtime() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "one").unwrap();
+        let path = file.to_str().unwrap();
+        let params = json!({ "path": path });
+
+        let before = file_key(path, &params).unwrap();
+        assert_eq!(file_key(path, &params), Some(before.clone()));
+        let later = std::time::SystemTime::now() + Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_ne!(file_key(path, &params), Some(before));
+        assert!(file_key("/no/such/file", &params).is_none());
+    }
+}
@@ -21,9 +21,10 @@
 pub use crate::tools::builtin::{
     BashParams, BashResult, BashTool, DirectoryEntry, FileEditParams, FileEditResult, FileEditTool,
     FileReadParams, FileReadResult, FileReadTool, FindFilesParams, FindFilesResult, FindFilesTool,
-    FoundFile, ListDirParams, ListDirResult, ListDirectoryTool, QuestionParams, QuestionResult,
-    QuestionTool, QUESTION_HANDLER, QuestionHandler, SearchMatch, SearchParams, SearchResult, 
-    SearchTool, WebSearchParams, WebSearchResult, WebSearchResultItem, WebSearchTool, 
+    FindSymbolParams, FindSymbolResult, FindSymbolTool, FindTodosParams, FindTodosResult, FindTodosTool, FoundFile, GitCommitParams, GitCommitResult, GitCommitTool, ListDirParams, ListDirResult, ListDirectoryTool, QuestionParams, QuestionResult,
+    QuestionTool, RecallParams, RecallResult, RecallTool, RememberParams, RememberResult, RememberTool, QUESTION_HANDLER, QuestionHandler, RunTestsParams, RunTestsResult, RunTestsTool,
+    SearchMatch, SearchParams, SearchResult, SymbolMatch, TodoItem,
+    SearchTool, TaskCompleteParams, TaskCompleteResult, TaskCompleteTool, WebSearchParams, WebSearchResult, WebSearchResultItem, WebSearchTool, 
     WriteFileParams, WriteFileResult, WriteFileTool,
 };
 
@@ -36,6 +37,11 @@ pub use crate::tools::analyze_context::{
 // Re-export Visioneer tool from its own module
 #[allow(unused_imports)]
 pub use crate::tools::visioneer::{VisioneerParams, VisioneerResult, VisioneerTool};
+#[allow(unused_imports)]
+pub use crate::tools::visioneer::macros::{
+    RunMacroParams, RunMacroResult, RunMacroTool, VisioneerMacroParams, VisioneerMacroResult,
+    VisioneerMacroTool,
+};
 
 /// Factory function to create a basic tool registry (without MCP discovery)
 /// Used by AgentClient when a cached registry is already available
@@ -54,8 +60,17 @@ pub fn create_basic_tool_registry() -> crate::api::agent::ToolRegistry {
     registry.register(SearchTool::new());
     registry.register(WebSearchTool::new());
     registry.register(VisioneerTool::new());
+    registry.register(VisioneerMacroTool::new());
+    registry.register(RunMacroTool::new());
     registry.register(QuestionTool::new());
     registry.register(AnalyzeContextTool::new());
+    registry.register(RunTestsTool::new());
+    registry.register(GitCommitTool::new());
+    registry.register(FindTodosTool::new());
+    registry.register(FindSymbolTool::new());
+    registry.register(RememberTool::new());
+    registry.register(RecallTool::new());
+    registry.register(TaskCompleteTool::new());
 
     registry
 }
@@ -114,7 +129,13 @@ mod tests {
         assert!(tools.contains(&"search_files".to_string()));
         assert!(tools.contains(&"web_search".to_string()));
         assert!(tools.contains(&"visioneer".to_string()));
+        assert!(tools.contains(&"visioneer_macro".to_string()));
+        assert!(tools.contains(&"run_macro".to_string()));
         assert!(tools.contains(&"ask_question".to_string()));
         assert!(tools.contains(&"analyze_context".to_string()));
+        assert!(tools.contains(&"run_tests".to_string()));
+        assert!(tools.contains(&"git_commit".to_string()));
+        assert!(tools.contains(&"find_todos".to_string()));
+        assert!(tools.contains(&"find_symbol".to_string()));
     }
 }
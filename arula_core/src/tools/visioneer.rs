@@ -4,6 +4,7 @@
 //! to understand UI and automate interactions with desktop applications and games.
 
 use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use crate::api::capabilities::{Feature, lookup, missing_features};
 use async_trait::async_trait;
 #[cfg(target_os = "windows")]
 use base64::engine::general_purpose::STANDARD;
@@ -11,11 +12,21 @@ use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use elements::ElementStore;
 use std::sync::{Arc, Mutex};
 use tokio::process::Command as TokioCommand;
 
+#[cfg(any(target_os = "linux", all(target_os = "macos", feature = "visioneer-macos")))]
+mod common;
+mod elements;
+pub mod macros;
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(all(target_os = "macos", feature = "visioneer-macos"))]
+mod macos;
+
 /// Visioneer tool parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct VisioneerParams {
     /// Target process ID or window name
     pub target: String,
@@ -28,7 +39,7 @@ pub struct VisioneerParams {
 }
 
 /// Visioneer action types
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum VisioneerAction {
     /// Capture screen region
@@ -88,7 +99,7 @@ pub struct CaptureRegion {
 }
 
 /// Click target specification
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum ClickTarget {
     Coordinates {
@@ -104,13 +115,16 @@ pub enum ClickTarget {
         region: Option<CaptureRegion>,
     },
     Element {
-        selector: String,
+        /// ID of an element from the last analyze action on the same target
+        id: Option<String>,
+        /// Accessibility selector (macOS)
+        selector: Option<String>,
         index: Option<u32>,
     },
 }
 
 /// Mouse button options
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum ClickButton {
     Left,
     Right,
@@ -140,7 +154,7 @@ pub enum WaitCondition {
 }
 
 /// Navigation directions
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub enum NavigationDirection {
     Up,
     Down,
@@ -149,7 +163,7 @@ pub enum NavigationDirection {
 }
 
 /// OCR configuration
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct OcrConfig {
     pub engine: Option<String>, // "tesseract", "easyocr", etc.
     pub language: Option<String>,
@@ -158,7 +172,7 @@ pub struct OcrConfig {
 }
 
 /// OCR preprocessing options
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct OcrPreprocessing {
     pub grayscale: Option<bool>,
     pub threshold: Option<u8>,
@@ -167,7 +181,7 @@ pub struct OcrPreprocessing {
 }
 
 /// Vision-language model configuration
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct VlmConfig {
     pub model: Option<String>, // "gpt-4-vision", "claude-3-vision", "llava", etc.
     pub max_tokens: Option<u32>,
@@ -217,7 +231,7 @@ pub struct TextWord {
 }
 
 /// Bounding box for text regions
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BoundingBox {
     pub x: u32,
     pub y: u32,
@@ -236,8 +250,10 @@ pub struct AnalyzeResult {
 }
 
 /// Detected UI element
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UiElement {
+    /// Reference for clicking it, e.g. "e1"
+    pub id: String,
     pub element_type: String,
     pub text: Option<String>,
     pub bbox: BoundingBox,
@@ -261,6 +277,7 @@ pub struct VisioneerTool {
     vlm_engine: Arc<Mutex<Option<Box<dyn VlmEngine>>>>,
     screen_capture: Box<dyn ScreenCapture>,
     action_executor: Box<dyn ActionExecutor>,
+    elements: ElementStore,
 }
 
 impl std::fmt::Debug for VisioneerTool {
@@ -277,8 +294,9 @@ impl VisioneerTool {
         Self {
             ocr_engine: Some(Box::new(TesseractOcrEngine::new())),
             vlm_engine: Arc::new(Mutex::new(None)),
-            screen_capture: Box::new(WindowsScreenCapture::new()),
-            action_executor: Box::new(WindowsActionExecutor::new()),
+            screen_capture: platform_screen_capture(),
+            action_executor: platform_action_executor(),
+            elements: ElementStore::default(),
         }
     }
 
@@ -288,8 +306,9 @@ impl VisioneerTool {
             vlm_engine: Arc::new(Mutex::new(Some(Box::new(OllamaVlmEngine::new(
                 endpoint, model,
             ))))),
-            screen_capture: Box::new(WindowsScreenCapture::new()),
-            action_executor: Box::new(WindowsActionExecutor::new()),
+            screen_capture: platform_screen_capture(),
+            action_executor: platform_action_executor(),
+            elements: ElementStore::default(),
         }
     }
 
@@ -312,6 +331,26 @@ impl VisioneerTool {
     }
 }
 
+/// The screen capture backend for the platform ARULA was built for
+fn platform_screen_capture() -> Box<dyn ScreenCapture> {
+    #[cfg(target_os = "linux")]
+    return Box::new(linux::LinuxScreenCapture::new());
+    #[cfg(all(target_os = "macos", feature = "visioneer-macos"))]
+    return Box::new(macos::MacScreenCapture::new());
+    #[cfg(not(any(target_os = "linux", all(target_os = "macos", feature = "visioneer-macos"))))]
+    return Box::new(WindowsScreenCapture::new());
+}
+
+/// The input backend for the platform ARULA was built for
+fn platform_action_executor() -> Box<dyn ActionExecutor> {
+    #[cfg(target_os = "linux")]
+    return Box::new(linux::LinuxActionExecutor::new());
+    #[cfg(all(target_os = "macos", feature = "visioneer-macos"))]
+    return Box::new(macos::MacActionExecutor::new());
+    #[cfg(not(any(target_os = "linux", all(target_os = "macos", feature = "visioneer-macos"))))]
+    return Box::new(WindowsActionExecutor::new());
+}
+
 impl Default for VisioneerTool {
     fn default() -> Self {
         Self::new()
@@ -366,8 +405,10 @@ impl Tool for VisioneerTool {
         .description("action.target.text", "Text to find for clicking")
         .param("action.target.pattern", "string")
         .description("action.target.pattern", "Visual pattern to find for clicking")
+        .param("action.target.id", "string")
+        .description("action.target.id", "For element targets: ID of an element from the last analyze action on this target (e.g. 'e3')")
         .param("action.target.selector", "string")
-        .description("action.target.selector", "UI element selector")
+        .description("action.target.selector", "For element targets on macOS: accessibility role, title or description")
         .param("action.button", "string")
         .description("action.button", "Mouse button: left, right, middle")
         .param("action.double_click", "boolean")
@@ -401,6 +442,7 @@ impl Tool for VisioneerTool {
 
     async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
         let start_time = std::time::Instant::now();
+        let step = macros::is_recording().then(|| macros::MacroStep::from_params(&params));
         let target = params.target;
         let action = params.action;
 
@@ -437,6 +479,7 @@ impl Tool for VisioneerTool {
                 let analyze_result = self
                     .analyze_ui(window_handle, &query, region, params.vlm_config)
                     .await?;
+                self.elements.remember(&target, &analyze_result.elements);
                 (
                     "analyze".to_string(),
                     serde_json::to_value(analyze_result).unwrap_or(Value::Null),
@@ -447,6 +490,14 @@ impl Tool for VisioneerTool {
                 button,
                 double_click,
             } => {
+                // Elements from an earlier analysis are clicked at their centre
+                let click_target = match click_target {
+                    ClickTarget::Element { id: Some(id), .. } => {
+                        let (x, y) = self.elements.click_point(&target, &id)?;
+                        ClickTarget::Coordinates { x, y }
+                    }
+                    click_target => click_target,
+                };
                 let action_result = self
                     .execute_click(
                         window_handle,
@@ -522,6 +573,9 @@ impl Tool for VisioneerTool {
             }
         };
 
+        if let Some(step) = step {
+            macros::record(step);
+        }
         let execution_time = start_time.elapsed().as_millis() as u64;
 
         Ok(VisioneerResult {
@@ -556,9 +610,25 @@ impl VisioneerTool {
             }
         }
 
-        #[cfg(not(target_os = "windows"))]
+        #[cfg(target_os = "linux")]
+        {
+            linux::find_window(target)
+        }
+
+        #[cfg(all(target_os = "macos", feature = "visioneer-macos"))]
         {
-            Err("Visioneer currently only supports Windows".to_string())
+            macos::find_window(target)
+        }
+
+        #[cfg(not(any(
+            target_os = "windows",
+            target_os = "linux",
+            all(target_os = "macos", feature = "visioneer-macos")
+        )))]
+        {
+            Err("Visioneer currently only supports Windows and Linux, and macOS when built \
+                 with the visioneer-macos feature"
+                .to_string())
         }
     }
 
@@ -592,9 +662,9 @@ impl VisioneerTool {
         region: Option<CaptureRegion>,
         language: Option<String>,
     ) -> Result<ExtractTextResult, String> {
-        // First capture the screen
+        // First capture the screen; the OCR engine reads the base64 image
         let capture_result = self
-            .capture_screen(window, region.clone(), None, false)
+            .capture_screen(window, region.clone(), None, true)
             .await?;
 
         // Then extract text using OCR
@@ -620,9 +690,17 @@ impl VisioneerTool {
             .base64_data
             .as_ref()
             .ok_or("No base64 image data found in capture result")?;
+        let prompt = elements::prompt(query, capture_result.width, capture_result.height);
 
         // Use VLM if configured, otherwise return mock analysis
         if let Some(config) = vlm_config {
+            // Fail early instead of sending a screenshot to a text-only model
+            let model = config.model.as_deref().unwrap_or("llava");
+            let caps = lookup(model);
+            if let Some(warning) = missing_features(model, caps.as_ref(), &[Feature::Vision]).pop() {
+                return Err(warning);
+            }
+
             // Initialize VLM engine if needed
             let vlm_engine_ref = self.get_or_init_vlm_engine(&config)?;
 
@@ -642,12 +720,27 @@ impl VisioneerTool {
                 }
             };
 
-            if let Some(vlm) = vlm_engine {
-                // Use the VLM to analyze the image
-                vlm.analyze_image(base64_data, query, &config).await
-            } else {
-                Err("Failed to initialize VLM engine".to_string())
+            let Some(vlm) = vlm_engine else {
+                return Err("Failed to initialize VLM engine".to_string());
+            };
+
+            // Use the VLM to analyze the image
+            let mut result = vlm.analyze_image(base64_data, &prompt, &config).await?;
+            if let Some(found) =
+                elements::parse(&result.analysis, capture_result.width, capture_result.height)
+            {
+                result.elements = found;
+            }
+
+            // Check element boxes against the text OCR finds, when it's available
+            if let Some(ocr_engine) = &self.ocr_engine
+                && let Ok(text) = ocr_engine.extract_text(&capture_result, None).await
+            {
+                elements::validate(&mut result.elements, &text.words);
             }
+            elements::finish(&mut result.elements, capture_result.region.as_ref());
+            result.region = capture_result.region.clone();
+            Ok(result)
         } else {
             // Fallback to mock analysis if no VLM config provided
             Ok(AnalyzeResult {
@@ -935,6 +1028,10 @@ impl VisioneerTool {
 pub enum WindowHandle {
     #[cfg(target_os = "windows")]
     Windows(String), // Store window title instead of raw handle for thread safety
+    #[cfg(target_os = "linux")]
+    Linux(Option<String>), // X11 window ID, or None for the whole screen
+    #[cfg(all(target_os = "macos", feature = "visioneer-macos"))]
+    MacOs(Option<u32>), // CoreGraphics window number, or None for the main display
 }
 
 // Trait definitions for the main components
@@ -1113,6 +1210,7 @@ fn parse_ui_elements_from_response(response: &str) -> Vec<UiElement> {
         if line.to_lowercase().contains("button") {
             if let Some(text) = extract_text_after_colon(line) {
                 elements.push(UiElement {
+                    id: String::new(),
                     element_type: "button".to_string(),
                     text: Some(text),
                     bbox: BoundingBox {
@@ -1128,6 +1226,7 @@ fn parse_ui_elements_from_response(response: &str) -> Vec<UiElement> {
         } else if line.to_lowercase().contains("input") || line.to_lowercase().contains("field") {
             if let Some(text) = extract_text_after_colon(line) {
                 elements.push(UiElement {
+                    id: String::new(),
                     element_type: "input".to_string(),
                     text: Some(text),
                     bbox: BoundingBox {
@@ -1143,6 +1242,7 @@ fn parse_ui_elements_from_response(response: &str) -> Vec<UiElement> {
         } else if line.to_lowercase().contains("link") {
             if let Some(text) = extract_text_after_colon(line) {
                 elements.push(UiElement {
+                    id: String::new(),
                     element_type: "link".to_string(),
                     text: Some(text),
                     bbox: BoundingBox {
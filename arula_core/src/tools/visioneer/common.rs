@@ -0,0 +1,129 @@
+//! Pieces shared by the Linux and macOS backends, which both capture real
+//! images and poll them for wait conditions
+
+use super::{ActionResult, CaptureRegion, CaptureResult, NavigationDirection, WaitCondition};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// `image` cut to `region`, kept inside the image
+pub(super) fn crop(image: image::DynamicImage, region: &CaptureRegion) -> image::DynamicImage {
+    let x = region.x.min(image.width());
+    let y = region.y.min(image.height());
+    let width = region.width.min(image.width() - x);
+    let height = region.height.min(image.height() - y);
+    image.crop_imm(x, y, width, height)
+}
+
+/// The capture result for `image`, saved and encoded as asked
+pub(super) fn capture_result(
+    image: image::DynamicImage,
+    region: Option<CaptureRegion>,
+    save_path: Option<String>,
+    encode_base64: bool,
+) -> Result<CaptureResult, String> {
+    let mut result = CaptureResult {
+        image_path: None,
+        base64_data: None,
+        width: image.width(),
+        height: image.height(),
+        format: "png".to_string(),
+        region,
+    };
+
+    if let Some(path) = save_path {
+        image
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to save screenshot: {:?}", e))?;
+        result.image_path = Some(path);
+    }
+    if encode_base64 {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image
+            .write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode screenshot: {:?}", e))?;
+        result.base64_data = Some(format!(
+            "data:image/png;base64,{}",
+            STANDARD.encode(png.into_inner())
+        ));
+    }
+    Ok(result)
+}
+
+/// Whether the pixel at `x`, `y` has the hex colour `color`
+pub(super) fn pixel_matches(
+    image: &image::DynamicImage,
+    x: u32,
+    y: u32,
+    color: &str,
+) -> Result<bool, String> {
+    let image = image.to_rgb8();
+    let pixel = image
+        .get_pixel_checked(x, y)
+        .ok_or_else(|| format!("Pixel ({}, {}) is off screen", x, y))?;
+    let hex = format!("{:02x}{:02x}{:02x}", pixel[0], pixel[1], pixel[2]);
+    Ok(hex == color.trim_start_matches('#').to_lowercase())
+}
+
+/// Pointer movement per step for a navigate action, and the step count
+pub(super) fn navigation_steps(
+    direction: &NavigationDirection,
+    distance: u32,
+    steps: u32,
+) -> (i64, i64, u32) {
+    let steps = steps.max(1);
+    let step = (distance / steps).max(1) as i64;
+    let (dx, dy) = match direction {
+        NavigationDirection::Up => (0, -step),
+        NavigationDirection::Down => (0, step),
+        NavigationDirection::Left => (-step, 0),
+        NavigationDirection::Right => (step, 0),
+    };
+    (dx, dy, steps)
+}
+
+pub(super) fn action_result(
+    action: &str,
+    target: serde_json::Value,
+    started: Instant,
+) -> ActionResult {
+    ActionResult {
+        action: action.to_string(),
+        target,
+        success: true,
+        response_time_ms: started.elapsed().as_millis() as u64,
+        error_message: None,
+    }
+}
+
+/// Check `condition` with `check` every `check_interval_ms` until it holds
+/// or `timeout_ms` passes; `check` gets the time waiting started
+pub(super) async fn wait_until<F, Fut>(
+    condition: &WaitCondition,
+    timeout_ms: u32,
+    check_interval_ms: u32,
+    mut check: F,
+) -> Result<ActionResult, String>
+where
+    F: FnMut(Instant) -> Fut,
+    Fut: Future<Output = Result<bool, String>>,
+{
+    let started = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms as u64);
+    let interval = Duration::from_millis(check_interval_ms.max(50) as u64);
+    let target = serde_json::to_value(condition).unwrap_or_default();
+    loop {
+        if check(started).await? {
+            return Ok(action_result("wait", target, started));
+        }
+        if started.elapsed() >= timeout {
+            return Ok(ActionResult {
+                success: false,
+                error_message: Some(format!("Timed out after {} ms", timeout_ms)),
+                ..action_result("wait", target, started)
+            });
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,295 @@
+//! UI elements found by VLM analysis
+//!
+//! The analyze prompt asks the model for a JSON list of elements with
+//! bounding boxes. Boxes around text are checked against OCR and moved
+//! onto the words OCR found, since VLMs are better at spotting elements
+//! than at placing them. Each element gets an ID ("e1", "e2", ...) that
+//! later click actions on the same target can use instead of coordinates.
+
+use super::{BoundingBox, CaptureRegion, TextWord, UiElement};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// `query` with instructions for returning elements the tool can parse
+pub(super) fn prompt(query: &str, width: u32, height: u32) -> String {
+    format!(
+        "{query}\n\n\
+         After your answer, list the interactive UI elements you can see (buttons, \
+         inputs, links, checkboxes, menus, tabs) as JSON in a ```json block:\n\
+         {{\"elements\": [{{\"type\": \"button\", \"text\": \"Save\", \
+         \"bbox\": [x, y, width, height], \"confidence\": 0.9}}]}}\n\
+         Boxes are in pixels of this {width}x{height} image, from its top-left corner. \
+         Use the element's visible label as its text, or null when it has none."
+    )
+}
+
+#[derive(Deserialize)]
+struct RawElement {
+    #[serde(rename = "type", alias = "element_type", default)]
+    element_type: Option<String>,
+    #[serde(default, alias = "label")]
+    text: Option<String>,
+    #[serde(alias = "box", alias = "bounding_box")]
+    bbox: RawBox,
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawBox {
+    List([f64; 4]),
+    Object {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+    },
+}
+
+/// The JSON in `response`: the first ```json block, or else everything
+/// from the first brace or bracket to the matching last one
+fn json_in(response: &str) -> Option<Value> {
+    if let Some(start) = response.find("```json") {
+        let block = &response[start + "```json".len()..];
+        let block = &block[..block.find("```").unwrap_or(block.len())];
+        if let Ok(value) = serde_json::from_str(block.trim()) {
+            return Some(value);
+        }
+    }
+    [('{', '}'), ('[', ']')].iter().find_map(|(open, close)| {
+        let start = response.find(*open)?;
+        let end = response.rfind(*close)?;
+        (start < end)
+            .then(|| serde_json::from_str(&response[start..=end]).ok())
+            .flatten()
+    })
+}
+
+/// Elements with boxes inside a `width` by `height` image listed in
+/// `response`, or `None` when it has no element list
+pub(super) fn parse(response: &str, width: u32, height: u32) -> Option<Vec<UiElement>> {
+    let value = json_in(response)?;
+    let list = match &value {
+        Value::Array(list) => list,
+        _ => value.get("elements")?.as_array()?,
+    };
+
+    let elements = list
+        .iter()
+        .filter_map(|raw| serde_json::from_value::<RawElement>(raw.clone()).ok())
+        .filter_map(|raw| {
+            let (x, y, w, h) = match raw.bbox {
+                RawBox::List([x, y, w, h]) => (x, y, w, h),
+                RawBox::Object {
+                    x,
+                    y,
+                    width,
+                    height,
+                } => (x, y, width, height),
+            };
+            let inside = x >= 0.0 && y >= 0.0 && w > 0.0 && h > 0.0;
+            if !inside || x >= width as f64 || y >= height as f64 {
+                return None;
+            }
+            Some(UiElement {
+                id: String::new(),
+                element_type: raw.element_type.unwrap_or_else(|| "element".to_string()),
+                text: raw.text.filter(|text| !text.trim().is_empty()),
+                bbox: BoundingBox {
+                    x: x as u32,
+                    y: y as u32,
+                    width: (w as u32).min(width - x as u32),
+                    height: (h as u32).min(height - y as u32),
+                },
+                confidence: raw.confidence.unwrap_or(0.8).clamp(0.0, 1.0),
+                attributes: HashMap::new(),
+            })
+        })
+        .collect();
+    Some(elements)
+}
+
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn union(a: &BoundingBox, b: &BoundingBox) -> BoundingBox {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    BoundingBox {
+        x,
+        y,
+        width: (a.x + a.width).max(b.x + b.width) - x,
+        height: (a.y + a.height).max(b.y + b.height) - y,
+    }
+}
+
+fn center(bbox: &BoundingBox) -> (u32, u32) {
+    (bbox.x + bbox.width / 2, bbox.y + bbox.height / 2)
+}
+
+/// Boxes around each run of OCR words that spells `text`
+fn ocr_matches(text: &str, words: &[TextWord]) -> Vec<BoundingBox> {
+    let wanted = tokens(text);
+    let found: Vec<(String, &BoundingBox)> = words
+        .iter()
+        .flat_map(|word| tokens(&word.text).into_iter().map(|t| (t, &word.bbox)))
+        .collect();
+    if wanted.is_empty() || found.len() < wanted.len() {
+        return Vec::new();
+    }
+
+    found
+        .windows(wanted.len())
+        .filter(|run| {
+            run.iter()
+                .zip(&wanted)
+                .all(|((token, _), want)| token == want)
+        })
+        .map(|run| {
+            run.iter()
+                .skip(1)
+                .fold(run[0].1.clone(), |bbox, (_, next)| union(&bbox, next))
+        })
+        .collect()
+}
+
+/// Check the boxes of elements with text against OCR `words`: a box moves
+/// onto the nearest run of words spelling its text, and elements whose
+/// text OCR can't find lose half their confidence
+pub(super) fn validate(elements: &mut [UiElement], words: &[TextWord]) {
+    for element in elements.iter_mut() {
+        let Some(text) = &element.text else {
+            continue;
+        };
+        let (cx, cy) = center(&element.bbox);
+        let nearest = ocr_matches(text, words).into_iter().min_by_key(|bbox| {
+            let (x, y) = center(bbox);
+            (x.abs_diff(cx) as u64).pow(2) + (y.abs_diff(cy) as u64).pow(2)
+        });
+
+        let verified = nearest.is_some();
+        if let Some(bbox) = nearest {
+            element.bbox = bbox;
+        } else {
+            element.confidence /= 2.0;
+        }
+        element
+            .attributes
+            .insert("ocr_verified".to_string(), Value::Bool(verified));
+    }
+}
+
+/// Give elements their IDs and move their boxes from `region`'s corner to
+/// the window's
+pub(super) fn finish(elements: &mut [UiElement], region: Option<&CaptureRegion>) {
+    for (i, element) in elements.iter_mut().enumerate() {
+        element.id = format!("e{}", i + 1);
+        if let Some(region) = region {
+            element.bbox.x += region.x;
+            element.bbox.y += region.y;
+        }
+    }
+}
+
+/// Elements from the last analysis of each target
+#[derive(Default)]
+pub(super) struct ElementStore {
+    by_target: Mutex<HashMap<String, Vec<UiElement>>>,
+}
+
+impl ElementStore {
+    pub(super) fn remember(&self, target: &str, elements: &[UiElement]) {
+        self.by_target
+            .lock()
+            .unwrap()
+            .insert(target.to_string(), elements.to_vec());
+    }
+
+    /// Where to click element `id` of `target`
+    pub(super) fn click_point(&self, target: &str, id: &str) -> Result<(u32, u32), String> {
+        let by_target = self.by_target.lock().unwrap();
+        let element = by_target
+            .get(target)
+            .and_then(|elements| elements.iter().find(|element| element.id == id))
+            .ok_or_else(|| {
+                format!(
+                    "Element '{}' not found; run an analyze action on '{}' first",
+                    id, target
+                )
+            })?;
+        if element.bbox.width == 0 || element.bbox.height == 0 {
+            return Err(format!("Element '{}' has no bounding box to click", id));
+        }
+        Ok(center(&element.bbox))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, x: u32, y: u32) -> TextWord {
+        TextWord {
+            text: text.to_string(),
+            confidence: 90.0,
+            bbox: BoundingBox {
+                x,
+                y,
+                width: 40,
+                height: 20,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_validate_and_click() {
+        let response = "There is a toolbar.\n```json\n{\"elements\": [\
+            {\"type\": \"button\", \"text\": \"Save As\", \"bbox\": [100, 90, 60, 30]},\
+            {\"type\": \"input\", \"text\": \"Missing\", \"bbox\": {\"x\": 5, \"y\": 5, \"width\": 10, \"height\": 10}, \"confidence\": 0.6},\
+            {\"type\": \"icon\", \"text\": null, \"bbox\": [700, 10, 20, 20]},\
+            {\"type\": \"button\", \"text\": \"Off\", \"bbox\": [900, 10, 20, 20]}\
+            ]}\n```";
+        let mut elements = parse(response, 800, 600).unwrap();
+        assert_eq!(elements.len(), 3, "the off-image box is dropped");
+
+        let words = [
+            word("Save", 20, 400),
+            word("As", 64, 400),
+            word("Save", 110, 100),
+            word("As...", 154, 100),
+        ];
+        validate(&mut elements, &words);
+        finish(
+            &mut elements,
+            Some(&CaptureRegion {
+                x: 1000,
+                y: 0,
+                width: 800,
+                height: 600,
+            }),
+        );
+
+        // Snapped onto the "Save As" nearest the model's box
+        assert_eq!(elements[0].id, "e1");
+        assert_eq!((elements[0].bbox.x, elements[0].bbox.width), (1110, 84));
+        assert_eq!(elements[0].attributes["ocr_verified"], true);
+        assert_eq!(elements[1].attributes["ocr_verified"], false);
+        assert_eq!(elements[1].confidence, 0.3);
+        assert!(!elements[2].attributes.contains_key("ocr_verified"));
+
+        let store = ElementStore::default();
+        store.remember("app", &elements);
+        assert_eq!(store.click_point("app", "e1").unwrap(), (1152, 110));
+        assert!(store.click_point("app", "e9").is_err());
+        assert!(store.click_point("other", "e1").is_err());
+
+        assert!(parse("No elements here.", 800, 600).is_none());
+    }
+}
@@ -0,0 +1,482 @@
+//! Linux backend for Visioneer, on X11 and Wayland
+//!
+//! Like the Windows backend, it drives installed programs rather than
+//! linking to the display server:
+//!
+//! - window lookup: `xdotool search` (X11 and XWayland windows); on
+//!   Wayland without it, actions apply to the whole screen
+//! - capture: `grim`, `gnome-screenshot` or `spectacle` on Wayland, `maim`,
+//!   `import` (ImageMagick) or `scrot` on X11; regions are cropped here
+//! - input: `xdotool` on X11, `ydotool` (pointer) and `wtype` (keyboard) on
+//!   Wayland
+
+use super::common::{
+    action_result, capture_result, crop, navigation_steps, pixel_matches, wait_until,
+};
+use super::{
+    ActionExecutor, ActionResult, CaptureRegion, CaptureResult, ClickButton, ClickTarget,
+    NavigationDirection, OcrEngine, ScreenCapture, TesseractOcrEngine, WaitCondition, WindowHandle,
+};
+use crate::utils::voice::on_path;
+use async_trait::async_trait;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tokio::process::Command as TokioCommand;
+
+/// Targets that mean the whole screen rather than one window
+const SCREEN_TARGETS: [&str; 3] = ["screen", "desktop", "root"];
+
+fn is_wayland() -> bool {
+    std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// The window `target` (a process ID or part of a title) names; `None`
+/// for the whole screen
+pub(super) fn find_window(target: &str) -> Result<WindowHandle, String> {
+    let target = target.trim();
+    if target.is_empty() || SCREEN_TARGETS.contains(&target.to_lowercase().as_str()) {
+        return Ok(WindowHandle::Linux(None));
+    }
+    if !on_path("xdotool") {
+        if is_wayland() {
+            // Wayland doesn't let clients look up other windows
+            return Ok(WindowHandle::Linux(None));
+        }
+        return Err("Finding windows on Linux needs xdotool".to_string());
+    }
+
+    let mut command = Command::new("xdotool");
+    command.args(["search", "--onlyvisible"]);
+    match target.parse::<u32>() {
+        Ok(pid) => command.args(["--pid", &pid.to_string()]),
+        Err(_) => command.args(["--name", target]),
+    };
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run xdotool: {}", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|id| WindowHandle::Linux(Some(id.trim().to_string())))
+        .ok_or_else(|| format!("Window '{}' not found", target))
+}
+
+/// Screenshot of `window` (the whole screen when `None`) as a PNG file
+async fn screenshot(window: Option<&str>, file: &str) -> Result<(), String> {
+    let args: Vec<String> = if is_wayland() {
+        if on_path("grim") {
+            vec!["grim".into(), file.into()]
+        } else if on_path("gnome-screenshot") {
+            vec!["gnome-screenshot".into(), "-f".into(), file.into()]
+        } else if on_path("spectacle") {
+            vec![
+                "spectacle".into(),
+                "-b".into(),
+                "-n".into(),
+                "-o".into(),
+                file.into(),
+            ]
+        } else {
+            return Err(
+                "Screen capture on Wayland needs grim, gnome-screenshot or spectacle".to_string(),
+            );
+        }
+    } else if on_path("maim") {
+        let mut args = vec!["maim".to_string()];
+        if let Some(id) = window {
+            args.extend(["-i".into(), id.into()]);
+        }
+        args.push(file.into());
+        args
+    } else if on_path("import") {
+        vec![
+            "import".into(),
+            "-window".into(),
+            window.unwrap_or("root").into(),
+            file.into(),
+        ]
+    } else if on_path("scrot") {
+        let mut args = vec!["scrot".to_string(), "-o".into()];
+        if window.is_some() {
+            args.push("-u".into());
+        }
+        args.push(file.into());
+        args
+    } else {
+        return Err("Screen capture on X11 needs maim, ImageMagick (import) or scrot".to_string());
+    };
+
+    if let Some(id) = window.filter(|_| !is_wayland()) {
+        // Bring the window forward so it isn't captured covered up
+        let _ = xdotool(&["windowactivate", "--sync", id]).await;
+    }
+    let output = TokioCommand::new(&args[0])
+        .args(&args[1..])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", args[0], e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Capture `window` as an image, cropped to `region`
+async fn capture_image(
+    window: &WindowHandle,
+    region: Option<&CaptureRegion>,
+) -> Result<image::DynamicImage, String> {
+    let WindowHandle::Linux(id) = window;
+    let dir =
+        tempfile::TempDir::new().map_err(|e| format!("Failed to create a temp dir: {}", e))?;
+    let file = dir.path().join("capture.png");
+    let file = file.to_string_lossy();
+    screenshot(id.as_deref(), &file).await?;
+
+    let image = image::open(&*file).map_err(|e| format!("Failed to read the screenshot: {}", e))?;
+    Ok(match region {
+        Some(region) => crop(image, region),
+        None => image,
+    })
+}
+
+pub(super) struct LinuxScreenCapture;
+
+impl LinuxScreenCapture {
+    pub(super) fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ScreenCapture for LinuxScreenCapture {
+    async fn capture(
+        &self,
+        window: WindowHandle,
+        region: Option<CaptureRegion>,
+        save_path: Option<String>,
+        encode_base64: bool,
+    ) -> Result<CaptureResult, String> {
+        let image = capture_image(&window, region.as_ref()).await?;
+        capture_result(image, region, save_path, encode_base64)
+    }
+}
+
+/// Run xdotool with `args`
+async fn xdotool(args: &[&str]) -> Result<(), String> {
+    run("xdotool", args).await
+}
+
+async fn run(program: &str, args: &[&str]) -> Result<(), String> {
+    if !on_path(program) {
+        return Err(format!("{} isn't installed", program));
+    }
+    let output = TokioCommand::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// A key name as an X keysym, e.g. "enter" to "Return"; modifiers become
+/// xdotool's ctrl/alt/shift/super
+fn keysym(key: &str) -> String {
+    let lower = key.to_lowercase();
+    let name = match lower.as_str() {
+        "ctrl" | "control" => "ctrl",
+        "alt" | "option" => "alt",
+        "shift" => "shift",
+        "super" | "win" | "windows" | "cmd" | "command" | "meta" | "logo" => "super",
+        "enter" | "return" => "Return",
+        "esc" | "escape" => "Escape",
+        "tab" => "Tab",
+        "backspace" => "BackSpace",
+        "delete" | "del" => "Delete",
+        "space" => "space",
+        "up" => "Up",
+        "down" => "Down",
+        "left" => "Left",
+        "right" => "Right",
+        "home" => "Home",
+        "end" => "End",
+        "pageup" => "Prior",
+        "pagedown" => "Next",
+        _ if lower.len() > 1 && lower.starts_with('f') && lower[1..].parse::<u8>().is_ok() => {
+            return lower.to_uppercase();
+        }
+        _ => return key.to_string(),
+    };
+    name.to_string()
+}
+
+fn is_modifier(keysym: &str) -> bool {
+    matches!(keysym, "ctrl" | "alt" | "shift" | "super")
+}
+
+/// wtype arguments pressing `keys` together, holding them for `hold_ms`
+fn wtype_chord(keys: &[String], hold_ms: u32) -> Vec<String> {
+    let keys: Vec<String> = keys.iter().map(|key| keysym(key)).collect();
+    let (modifiers, others): (Vec<_>, Vec<_>) = keys.iter().partition(|key| is_modifier(key));
+    let modifier = |key: &String| {
+        if key == "super" {
+            "logo".to_string()
+        } else {
+            key.clone()
+        }
+    };
+
+    let mut args = Vec::new();
+    for key in &modifiers {
+        args.extend(["-M".to_string(), modifier(key)]);
+    }
+    for key in &others {
+        args.extend(["-P".to_string(), key.to_string()]);
+    }
+    args.extend(["-s".to_string(), hold_ms.to_string()]);
+    for key in others.iter().rev() {
+        args.extend(["-p".to_string(), key.to_string()]);
+    }
+    for key in modifiers.iter().rev() {
+        args.extend(["-m".to_string(), modifier(key)]);
+    }
+    args
+}
+
+pub(super) struct LinuxActionExecutor {
+    ocr: TesseractOcrEngine,
+}
+
+impl LinuxActionExecutor {
+    pub(super) fn new() -> Self {
+        Self {
+            ocr: TesseractOcrEngine::new(),
+        }
+    }
+
+    /// Whether `condition` holds on screen now
+    async fn condition_met(
+        &self,
+        condition: &WaitCondition,
+        started: Instant,
+    ) -> Result<bool, String> {
+        let screen = WindowHandle::Linux(None);
+        match condition {
+            WaitCondition::Text { text, appears } => {
+                let capture = LinuxScreenCapture.capture(screen, None, None, true).await?;
+                let found = self
+                    .ocr
+                    .extract_text(&capture, None)
+                    .await?
+                    .text
+                    .to_lowercase()
+                    .contains(&text.to_lowercase());
+                Ok(found == appears.unwrap_or(true))
+            }
+            WaitCondition::Pixel { x, y, color } => {
+                pixel_matches(&capture_image(&screen, None).await?, *x, *y, color)
+            }
+            WaitCondition::Idle { timeout_ms } => {
+                Ok(started.elapsed() >= Duration::from_millis(*timeout_ms as u64))
+            }
+            WaitCondition::Element { .. } => Err(
+                "Waiting for elements isn't supported on Linux; wait for text instead".to_string(),
+            ),
+        }
+    }
+}
+
+#[async_trait]
+impl ActionExecutor for LinuxActionExecutor {
+    async fn click(
+        &self,
+        window: WindowHandle,
+        target: ClickTarget,
+        button: ClickButton,
+        double_click: bool,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        let ClickTarget::Coordinates { x, y } = target else {
+            return Err("Click target not yet implemented".to_string());
+        };
+        let (x, y) = (x.to_string(), y.to_string());
+        let repeat = if double_click { "2" } else { "1" };
+
+        if is_wayland() {
+            let code = match button {
+                ClickButton::Left => "0xC0",
+                ClickButton::Right => "0xC1",
+                ClickButton::Middle => "0xC2",
+            };
+            run("ydotool", &["mousemove", "--absolute", "-x", &x, "-y", &y]).await?;
+            run("ydotool", &["click", "-r", repeat, code]).await?;
+        } else {
+            let number = match button {
+                ClickButton::Left => "1",
+                ClickButton::Middle => "2",
+                ClickButton::Right => "3",
+            };
+            // Coordinates are relative to the target window, if there is one
+            let mut args = vec!["mousemove"];
+            let WindowHandle::Linux(id) = &window;
+            if let Some(id) = id {
+                args.extend(["--window", id.as_str()]);
+            }
+            args.extend([x.as_str(), y.as_str(), "click", "--repeat", repeat, number]);
+            xdotool(&args).await?;
+        }
+        Ok(action_result(
+            "click",
+            serde_json::json!({ "x": x, "y": y }),
+            started,
+        ))
+    }
+
+    async fn type_text(
+        &self,
+        window: WindowHandle,
+        text: &str,
+        clear_first: bool,
+        delay_ms: u32,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        let delay = delay_ms.to_string();
+        if is_wayland() {
+            if clear_first {
+                run(
+                    "wtype",
+                    &["-M", "ctrl", "a", "-m", "ctrl", "-k", "BackSpace"],
+                )
+                .await?;
+            }
+            run("wtype", &["-d", &delay, "--", text]).await?;
+        } else {
+            let WindowHandle::Linux(id) = &window;
+            if let Some(id) = id {
+                xdotool(&["windowactivate", "--sync", id]).await?;
+            }
+            if clear_first {
+                xdotool(&["key", "ctrl+a", "BackSpace"]).await?;
+            }
+            xdotool(&["type", "--delay", &delay, "--", text]).await?;
+        }
+        Ok(action_result(
+            "type",
+            serde_json::json!({ "text": text }),
+            started,
+        ))
+    }
+
+    async fn hotkey(&self, keys: &[String], hold_ms: u32) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        if keys.is_empty() {
+            return Err("No keys to press".to_string());
+        }
+        if is_wayland() {
+            let args = wtype_chord(keys, hold_ms);
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run("wtype", &args).await?;
+        } else {
+            let chord = keys
+                .iter()
+                .map(|key| keysym(key))
+                .collect::<Vec<_>>()
+                .join("+");
+            let seconds = format!("{:.3}", hold_ms as f64 / 1000.0);
+            xdotool(&["keydown", &chord, "sleep", &seconds, "keyup", &chord]).await?;
+        }
+        Ok(action_result(
+            "hotkey",
+            serde_json::json!({ "keys": keys }),
+            started,
+        ))
+    }
+
+    async fn wait(
+        &self,
+        condition: WaitCondition,
+        timeout_ms: u32,
+        check_interval_ms: u32,
+    ) -> Result<ActionResult, String> {
+        wait_until(&condition, timeout_ms, check_interval_ms, |started| {
+            self.condition_met(&condition, started)
+        })
+        .await
+    }
+
+    async fn navigate(
+        &self,
+        _window: WindowHandle,
+        direction: NavigationDirection,
+        distance: u32,
+        steps: u32,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        let (dx, dy, steps) = navigation_steps(&direction, distance, steps);
+        let (dx, dy) = (dx.to_string(), dy.to_string());
+        for _ in 0..steps {
+            if is_wayland() {
+                run("ydotool", &["mousemove", "-x", &dx, "-y", &dy]).await?;
+            } else {
+                xdotool(&["mousemove_relative", "--", &dx, &dy]).await?;
+            }
+        }
+        Ok(action_result(
+            "navigate",
+            serde_json::json!({ "dx": dx, "dy": dy, "steps": steps }),
+            started,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_and_crop() {
+        assert_eq!(keysym("Enter"), "Return");
+        assert_eq!(keysym("Control"), "ctrl");
+        assert_eq!(keysym("cmd"), "super");
+        assert_eq!(keysym("f5"), "F5");
+        assert_eq!(keysym("c"), "c");
+        assert_eq!(
+            wtype_chord(&["ctrl".into(), "shift".into(), "t".into()], 100),
+            [
+                "-M", "ctrl", "-M", "shift", "-P", "t", "-s", "100", "-p", "t", "-m", "shift",
+                "-m", "ctrl"
+            ]
+        );
+        assert_eq!(
+            wtype_chord(&["super".into()], 0),
+            ["-M", "logo", "-s", "0", "-m", "logo"]
+        );
+
+        let image = image::DynamicImage::new_rgb8(100, 50);
+        let region = CaptureRegion {
+            x: 80,
+            y: 40,
+            width: 50,
+            height: 50,
+        };
+        let cropped = crop(image, &region);
+        assert_eq!((cropped.width(), cropped.height()), (20, 10));
+
+        assert!(matches!(
+            find_window("screen"),
+            Ok(WindowHandle::Linux(None))
+        ));
+    }
+}
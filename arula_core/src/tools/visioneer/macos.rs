@@ -0,0 +1,835 @@
+//! macOS backend for Visioneer, built with the `visioneer-macos` feature
+//!
+//! - window lookup and capture: CoreGraphics window lists and
+//!   `CGWindowListCreateImage`, at nominal resolution so image pixels are
+//!   the points clicks use
+//! - input: CoreGraphics events posted to the HID stream
+//! - focusing apps and element targets: the Accessibility API (`AXUIElement`)
+//!
+//! macOS asks the user for two permissions: Screen Recording for captures
+//! and Accessibility for input. The first call that needs one shows the
+//! system prompt; until it's granted, actions fail with instructions.
+
+use super::common::{
+    action_result, capture_result, crop, navigation_steps, pixel_matches, wait_until,
+};
+use super::{
+    ActionExecutor, ActionResult, CaptureRegion, CaptureResult, ClickButton, ClickTarget,
+    NavigationDirection, OcrEngine, ScreenCapture, TesseractOcrEngine, WaitCondition, WindowHandle,
+};
+use async_trait::async_trait;
+use core_foundation::array::CFArray;
+use core_foundation::base::{CFRelease, CFRetain, CFType, CFTypeRef, TCFType};
+use core_foundation::boolean::CFBoolean;
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+use core_foundation::number::CFNumber;
+use core_foundation::string::{CFString, CFStringRef};
+use core_graphics::display::CGDisplay;
+use core_graphics::event::{
+    CGEvent, CGEventFlags, CGEventTapLocation, CGEventType, CGKeyCode, CGMouseButton, EventField,
+};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use core_graphics::geometry::{CGPoint, CGRect};
+use core_graphics::image::CGImage;
+use core_graphics::window::{
+    copy_window_info, create_image, kCGNullWindowID, kCGWindowBounds,
+    kCGWindowImageBoundsIgnoreFraming, kCGWindowImageNominalResolution, kCGWindowLayer,
+    kCGWindowListExcludeDesktopElements, kCGWindowListOptionIncludingWindow,
+    kCGWindowListOptionOnScreenOnly, kCGWindowName, kCGWindowNumber, kCGWindowOwnerName,
+    kCGWindowOwnerPID,
+};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+type AXUIElementRef = *const c_void;
+type AXError = i32;
+
+const AX_SUCCESS: AXError = 0;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+unsafe extern "C" {
+    static kAXTrustedCheckOptionPrompt: CFStringRef;
+
+    fn AXIsProcessTrustedWithOptions(options: CFDictionaryRef) -> u8;
+    fn AXUIElementCreateApplication(pid: i32) -> AXUIElementRef;
+    fn AXUIElementCreateSystemWide() -> AXUIElementRef;
+    fn AXUIElementCopyAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        value: *mut CFTypeRef,
+    ) -> AXError;
+    fn AXUIElementSetAttributeValue(
+        element: AXUIElementRef,
+        attribute: CFStringRef,
+        value: CFTypeRef,
+    ) -> AXError;
+    fn AXUIElementPerformAction(element: AXUIElementRef, action: CFStringRef) -> AXError;
+}
+
+#[link(name = "CoreGraphics", kind = "framework")]
+unsafe extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+/// Targets that mean the whole screen rather than one window
+const SCREEN_TARGETS: [&str; 3] = ["screen", "desktop", "root"];
+
+/// How far element searches go into an app's accessibility tree
+const MAX_ELEMENT_DEPTH: usize = 12;
+const MAX_ELEMENTS: usize = 5000;
+
+static ACCESSIBILITY_PROMPTED: AtomicBool = AtomicBool::new(false);
+static SCREEN_RECORDING_PROMPTED: AtomicBool = AtomicBool::new(false);
+
+/// Check the Accessibility permission input needs, showing the system
+/// prompt the first time it's missing
+fn ensure_accessibility() -> Result<(), String> {
+    let prompt = !ACCESSIBILITY_PROMPTED.swap(true, Ordering::SeqCst);
+    let trusted = unsafe {
+        let key = CFString::wrap_under_get_rule(kAXTrustedCheckOptionPrompt);
+        let value = if prompt {
+            CFBoolean::true_value()
+        } else {
+            CFBoolean::false_value()
+        };
+        let options = CFDictionary::from_CFType_pairs(&[(key, value)]);
+        AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef()) != 0
+    };
+    if trusted {
+        Ok(())
+    } else {
+        Err(
+            "Accessibility permission not granted: allow your terminal (or ARULA) in \
+             System Settings > Privacy & Security > Accessibility, then try again"
+                .to_string(),
+        )
+    }
+}
+
+/// Check the Screen Recording permission captures need, showing the system
+/// prompt the first time it's missing
+fn ensure_screen_recording() -> Result<(), String> {
+    let granted = unsafe { CGPreflightScreenCaptureAccess() };
+    if granted {
+        return Ok(());
+    }
+    if !SCREEN_RECORDING_PROMPTED.swap(true, Ordering::SeqCst) {
+        unsafe { CGRequestScreenCaptureAccess() };
+    }
+    Err(
+        "Screen Recording permission not granted: allow your terminal (or ARULA) in \
+         System Settings > Privacy & Security > Screen Recording, then restart it"
+            .to_string(),
+    )
+}
+
+/// An on-screen window from the CoreGraphics window list
+struct WindowInfo {
+    id: u32,
+    pid: i32,
+    owner: String,
+    title: String,
+    bounds: CGRect,
+}
+
+fn number(dict: &CFDictionary<CFString, CFType>, key: CFStringRef) -> Option<i64> {
+    let key = unsafe { CFString::wrap_under_get_rule(key) };
+    dict.find(&key)?.downcast::<CFNumber>()?.to_i64()
+}
+
+fn string(dict: &CFDictionary<CFString, CFType>, key: CFStringRef) -> Option<String> {
+    let key = unsafe { CFString::wrap_under_get_rule(key) };
+    dict.find(&key)?
+        .downcast::<CFString>()
+        .map(|value| value.to_string())
+}
+
+/// Normal app windows on screen, frontmost first
+fn windows() -> Vec<WindowInfo> {
+    let Some(list) = copy_window_info(
+        kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+        kCGNullWindowID,
+    ) else {
+        return Vec::new();
+    };
+
+    list.iter()
+        .filter_map(|item| {
+            let dict: CFDictionary<CFString, CFType> =
+                unsafe { CFDictionary::wrap_under_get_rule(*item as CFDictionaryRef) };
+            if number(&dict, unsafe { kCGWindowLayer })? != 0 {
+                return None;
+            }
+            let key = unsafe { CFString::wrap_under_get_rule(kCGWindowBounds) };
+            let bounds = dict
+                .find(&key)?
+                .downcast::<CFDictionary>()
+                .and_then(|bounds| CGRect::from_dict_representation(&bounds))?;
+            Some(WindowInfo {
+                id: number(&dict, unsafe { kCGWindowNumber })? as u32,
+                pid: number(&dict, unsafe { kCGWindowOwnerPID })? as i32,
+                owner: string(&dict, unsafe { kCGWindowOwnerName }).unwrap_or_default(),
+                // Titles are empty without the Screen Recording permission
+                title: string(&dict, unsafe { kCGWindowName }).unwrap_or_default(),
+                bounds,
+            })
+        })
+        .collect()
+}
+
+fn window_info(id: u32) -> Result<WindowInfo, String> {
+    windows()
+        .into_iter()
+        .find(|window| window.id == id)
+        .ok_or_else(|| format!("Window {} not found; it may have closed", id))
+}
+
+/// The window `target` (a process ID, or part of a window title or app
+/// name) names; `None` for the whole screen
+pub(super) fn find_window(target: &str) -> Result<WindowHandle, String> {
+    let target = target.trim();
+    if target.is_empty() || SCREEN_TARGETS.contains(&target.to_lowercase().as_str()) {
+        return Ok(WindowHandle::MacOs(None));
+    }
+
+    let windows = windows();
+    let found = match target.parse::<i32>() {
+        Ok(pid) => windows.iter().find(|window| window.pid == pid),
+        Err(_) => {
+            let target = target.to_lowercase();
+            windows
+                .iter()
+                .find(|window| window.title.to_lowercase().contains(&target))
+                .or_else(|| {
+                    windows
+                        .iter()
+                        .find(|window| window.owner.to_lowercase().contains(&target))
+                })
+        }
+    };
+    found
+        .map(|window| WindowHandle::MacOs(Some(window.id)))
+        .ok_or_else(|| format!("Window '{}' not found", target))
+}
+
+/// 32-bit BGRA rows, as CoreGraphics captures them, as an RGBA image
+fn bgra_to_rgba(
+    bytes: &[u8],
+    width: usize,
+    height: usize,
+    bytes_per_row: usize,
+) -> Option<image::RgbaImage> {
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in bytes.chunks(bytes_per_row).take(height) {
+        for pixel in row.get(..width * 4)?.chunks_exact(4) {
+            rgba.extend([pixel[2], pixel[1], pixel[0], pixel[3]]);
+        }
+    }
+    image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+}
+
+fn to_image(capture: &CGImage) -> Result<image::DynamicImage, String> {
+    if capture.bits_per_pixel() != 32 {
+        return Err(format!(
+            "Unexpected capture format ({} bits per pixel)",
+            capture.bits_per_pixel()
+        ));
+    }
+    let data = capture.data();
+    bgra_to_rgba(
+        data.bytes(),
+        capture.width(),
+        capture.height(),
+        capture.bytes_per_row(),
+    )
+    .map(image::DynamicImage::ImageRgba8)
+    .ok_or_else(|| "Failed to read the captured image".to_string())
+}
+
+/// Capture `window` (the main display when `None`), cropped to `region`
+fn capture_image(
+    window_id: Option<u32>,
+    region: Option<&CaptureRegion>,
+) -> Result<image::DynamicImage, String> {
+    ensure_screen_recording()?;
+    let capture = match window_id {
+        Some(id) => create_image(
+            window_info(id)?.bounds,
+            kCGWindowListOptionIncludingWindow,
+            id,
+            kCGWindowImageBoundsIgnoreFraming | kCGWindowImageNominalResolution,
+        ),
+        None => create_image(
+            CGDisplay::main().bounds(),
+            kCGWindowListOptionOnScreenOnly,
+            kCGNullWindowID,
+            kCGWindowImageNominalResolution,
+        ),
+    }
+    .ok_or("Failed to capture the screen")?;
+
+    let image = to_image(&capture)?;
+    Ok(match region {
+        Some(region) => crop(image, region),
+        None => image,
+    })
+}
+
+pub(super) struct MacScreenCapture;
+
+impl MacScreenCapture {
+    pub(super) fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ScreenCapture for MacScreenCapture {
+    async fn capture(
+        &self,
+        window: WindowHandle,
+        region: Option<CaptureRegion>,
+        save_path: Option<String>,
+        encode_base64: bool,
+    ) -> Result<CaptureResult, String> {
+        let WindowHandle::MacOs(id) = window;
+        let image = capture_image(id, region.as_ref())?;
+        capture_result(image, region, save_path, encode_base64)
+    }
+}
+
+/// An accessibility element, released when dropped
+struct AxElement(AXUIElementRef);
+
+impl Drop for AxElement {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) }
+    }
+}
+
+impl AxElement {
+    fn application(pid: i32) -> Option<Self> {
+        let element = unsafe { AXUIElementCreateApplication(pid) };
+        (!element.is_null()).then_some(Self(element))
+    }
+
+    /// The app with keyboard focus
+    fn focused_application() -> Option<Self> {
+        let system = unsafe { AXUIElementCreateSystemWide() };
+        if system.is_null() {
+            return None;
+        }
+        let system = Self(system);
+        let app = system.attribute("AXFocusedApplication")?;
+        let app = app.as_CFTypeRef();
+        unsafe { CFRetain(app) };
+        Some(Self(app))
+    }
+
+    fn attribute(&self, name: &str) -> Option<CFType> {
+        let name = CFString::new(name);
+        let mut value: CFTypeRef = std::ptr::null();
+        let error = unsafe {
+            AXUIElementCopyAttributeValue(self.0, name.as_concrete_TypeRef(), &mut value)
+        };
+        (error == AX_SUCCESS && !value.is_null())
+            .then(|| unsafe { CFType::wrap_under_create_rule(value) })
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.attribute(name)?
+            .downcast::<CFString>()
+            .map(|value| value.to_string())
+    }
+
+    fn children(&self) -> Vec<AxElement> {
+        let Some(children) = self
+            .attribute("AXChildren")
+            .and_then(|value| value.downcast::<CFArray>())
+        else {
+            return Vec::new();
+        };
+        children
+            .iter()
+            .map(|child| {
+                unsafe { CFRetain(*child) };
+                AxElement(*child)
+            })
+            .collect()
+    }
+
+    /// Bring the app this element belongs to to the front
+    fn raise(&self) -> bool {
+        let name = CFString::from_static_string("AXFrontmost");
+        let error = unsafe {
+            AXUIElementSetAttributeValue(
+                self.0,
+                name.as_concrete_TypeRef(),
+                CFBoolean::true_value().as_CFTypeRef(),
+            )
+        };
+        error == AX_SUCCESS
+    }
+
+    fn press(&self) -> bool {
+        let action = CFString::from_static_string("AXPress");
+        unsafe { AXUIElementPerformAction(self.0, action.as_concrete_TypeRef()) == AX_SUCCESS }
+    }
+
+    /// Whether `selector` names this element: its role (e.g. "AXButton"),
+    /// or part of its title, description or identifier
+    fn matches(&self, selector: &str) -> bool {
+        if self.text("AXRole").is_some_and(|role| role == selector) {
+            return true;
+        }
+        let selector = selector.to_lowercase();
+        ["AXTitle", "AXDescription", "AXIdentifier"]
+            .iter()
+            .filter_map(|name| self.text(name))
+            .any(|text| text.to_lowercase().contains(&selector))
+    }
+
+    /// Elements under this one that match `selector`, in tree order
+    fn find(self, selector: &str) -> Vec<AxElement> {
+        let mut found = Vec::new();
+        let mut visited = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((element, depth)) = stack.pop() {
+            visited += 1;
+            if visited > MAX_ELEMENTS {
+                break;
+            }
+            if depth < MAX_ELEMENT_DEPTH {
+                let children = element.children();
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+            if element.matches(selector) {
+                found.push(element);
+            }
+        }
+        found
+    }
+}
+
+/// The app owning `window`, or the focused app for the whole screen
+fn target_application(window: &WindowHandle) -> Result<AxElement, String> {
+    let WindowHandle::MacOs(id) = window;
+    match id {
+        Some(id) => AxElement::application(window_info(*id)?.pid),
+        None => AxElement::focused_application(),
+    }
+    .ok_or_else(|| "Couldn't reach the app through the Accessibility API".to_string())
+}
+
+/// Bring the app owning `window` to the front and return the window's
+/// top-left corner, which window-relative coordinates start from
+fn focus(window: &WindowHandle) -> Result<CGPoint, String> {
+    let WindowHandle::MacOs(Some(id)) = window else {
+        return Ok(CGPoint::new(0.0, 0.0));
+    };
+    let info = window_info(*id)?;
+    if let Some(app) = AxElement::application(info.pid) {
+        app.raise();
+    }
+    Ok(info.bounds.origin)
+}
+
+fn event_source() -> Result<CGEventSource, String> {
+    CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+        .map_err(|_| "Failed to create an event source".to_string())
+}
+
+fn post_mouse(
+    kind: CGEventType,
+    point: CGPoint,
+    button: CGMouseButton,
+    click_state: i64,
+) -> Result<(), String> {
+    let event = CGEvent::new_mouse_event(event_source()?, kind, point, button)
+        .map_err(|_| "Failed to create a mouse event".to_string())?;
+    if click_state > 0 {
+        event.set_integer_value_field(EventField::MOUSE_EVENT_CLICK_STATE, click_state);
+    }
+    event.post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+fn post_click(point: CGPoint, button: &ClickButton, clicks: i64) -> Result<(), String> {
+    let (down, up, button) = match button {
+        ClickButton::Left => (
+            CGEventType::LeftMouseDown,
+            CGEventType::LeftMouseUp,
+            CGMouseButton::Left,
+        ),
+        ClickButton::Right => (
+            CGEventType::RightMouseDown,
+            CGEventType::RightMouseUp,
+            CGMouseButton::Right,
+        ),
+        ClickButton::Middle => (
+            CGEventType::OtherMouseDown,
+            CGEventType::OtherMouseUp,
+            CGMouseButton::Center,
+        ),
+    };
+    post_mouse(CGEventType::MouseMoved, point, button, 0)?;
+    for click in 1..=clicks {
+        post_mouse(down, point, button, click)?;
+        post_mouse(up, point, button, click)?;
+    }
+    Ok(())
+}
+
+/// Move the pointer by `dx`, `dy` points
+fn move_pointer(dx: f64, dy: f64) -> Result<(), String> {
+    let current = CGEvent::new(event_source()?)
+        .map_err(|_| "Failed to read the pointer position".to_string())?
+        .location();
+    let point = CGPoint::new(current.x + dx, current.y + dy);
+    post_mouse(CGEventType::MouseMoved, point, CGMouseButton::Left, 0)
+}
+
+/// Type `text` as-is, whatever the keyboard layout
+fn post_text(text: &str) -> Result<(), String> {
+    for keydown in [true, false] {
+        let event = CGEvent::new_keyboard_event(event_source()?, 0, keydown)
+            .map_err(|_| "Failed to create a keyboard event".to_string())?;
+        event.set_string(text);
+        event.post(CGEventTapLocation::HID);
+    }
+    Ok(())
+}
+
+fn post_key(code: CGKeyCode, flags: CGEventFlags, keydown: bool) -> Result<(), String> {
+    let event = CGEvent::new_keyboard_event(event_source()?, code, keydown)
+        .map_err(|_| "Failed to create a keyboard event".to_string())?;
+    event.set_flags(flags);
+    event.post(CGEventTapLocation::HID);
+    Ok(())
+}
+
+/// The modifier flag and key code for a modifier name
+fn modifier(key: &str) -> Option<(CGEventFlags, CGKeyCode)> {
+    Some(match key.to_lowercase().as_str() {
+        "cmd" | "command" | "super" | "win" | "meta" => (CGEventFlags::CGEventFlagCommand, 55),
+        "shift" => (CGEventFlags::CGEventFlagShift, 56),
+        "alt" | "option" => (CGEventFlags::CGEventFlagAlternate, 58),
+        "ctrl" | "control" => (CGEventFlags::CGEventFlagControl, 59),
+        _ => return None,
+    })
+}
+
+/// The virtual key code (US layout) for a key name
+fn key_code(key: &str) -> Option<CGKeyCode> {
+    let key = key.to_lowercase();
+    Some(match key.as_str() {
+        "a" => 0,
+        "s" => 1,
+        "d" => 2,
+        "f" => 3,
+        "h" => 4,
+        "g" => 5,
+        "z" => 6,
+        "x" => 7,
+        "c" => 8,
+        "v" => 9,
+        "b" => 11,
+        "q" => 12,
+        "w" => 13,
+        "e" => 14,
+        "r" => 15,
+        "y" => 16,
+        "t" => 17,
+        "1" => 18,
+        "2" => 19,
+        "3" => 20,
+        "4" => 21,
+        "6" => 22,
+        "5" => 23,
+        "=" => 24,
+        "9" => 25,
+        "7" => 26,
+        "-" => 27,
+        "8" => 28,
+        "0" => 29,
+        "]" => 30,
+        "o" => 31,
+        "u" => 32,
+        "[" => 33,
+        "i" => 34,
+        "p" => 35,
+        "enter" | "return" => 36,
+        "l" => 37,
+        "j" => 38,
+        "'" => 39,
+        "k" => 40,
+        ";" => 41,
+        "\\" => 42,
+        "," => 43,
+        "/" => 44,
+        "n" => 45,
+        "m" => 46,
+        "." => 47,
+        "tab" => 48,
+        "space" | " " => 49,
+        "`" => 50,
+        "backspace" => 51,
+        "esc" | "escape" => 53,
+        "f5" => 96,
+        "f6" => 97,
+        "f7" => 98,
+        "f3" => 99,
+        "f8" => 100,
+        "f9" => 101,
+        "f11" => 103,
+        "f10" => 109,
+        "f12" => 111,
+        "home" => 115,
+        "pageup" => 116,
+        "delete" | "del" => 117,
+        "f4" => 118,
+        "end" => 119,
+        "f2" => 120,
+        "pagedown" => 121,
+        "f1" => 122,
+        "left" => 123,
+        "right" => 124,
+        "down" => 125,
+        "up" => 126,
+        _ => return None,
+    })
+}
+
+/// The flags and key codes for pressing `keys` together; a chord of only
+/// modifiers presses the modifier keys themselves
+fn chord(keys: &[String]) -> Result<(CGEventFlags, Vec<CGKeyCode>), String> {
+    let mut flags = CGEventFlags::CGEventFlagNull;
+    let mut codes = Vec::new();
+    let mut modifier_codes = Vec::new();
+    for key in keys {
+        if let Some((flag, code)) = modifier(key) {
+            flags |= flag;
+            modifier_codes.push(code);
+        } else {
+            codes.push(key_code(key).ok_or_else(|| format!("Unknown key '{}'", key))?);
+        }
+    }
+    if codes.is_empty() {
+        codes = modifier_codes;
+    }
+    Ok((flags, codes))
+}
+
+pub(super) struct MacActionExecutor {
+    ocr: TesseractOcrEngine,
+}
+
+impl MacActionExecutor {
+    pub(super) fn new() -> Self {
+        Self {
+            ocr: TesseractOcrEngine::new(),
+        }
+    }
+
+    /// Whether `condition` holds on screen now
+    async fn condition_met(
+        &self,
+        condition: &WaitCondition,
+        started: Instant,
+    ) -> Result<bool, String> {
+        match condition {
+            WaitCondition::Text { text, appears } => {
+                let capture = capture_result(capture_image(None, None)?, None, None, true)?;
+                let found = self
+                    .ocr
+                    .extract_text(&capture, None)
+                    .await?
+                    .text
+                    .to_lowercase()
+                    .contains(&text.to_lowercase());
+                Ok(found == appears.unwrap_or(true))
+            }
+            WaitCondition::Element { selector, appears } => {
+                ensure_accessibility()?;
+                let app = target_application(&WindowHandle::MacOs(None))?;
+                let found = !app.find(selector).is_empty();
+                Ok(found == appears.unwrap_or(true))
+            }
+            WaitCondition::Pixel { x, y, color } => {
+                pixel_matches(&capture_image(None, None)?, *x, *y, color)
+            }
+            WaitCondition::Idle { timeout_ms } => {
+                Ok(started.elapsed() >= Duration::from_millis(*timeout_ms as u64))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ActionExecutor for MacActionExecutor {
+    async fn click(
+        &self,
+        window: WindowHandle,
+        target: ClickTarget,
+        button: ClickButton,
+        double_click: bool,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        ensure_accessibility()?;
+        match target {
+            ClickTarget::Coordinates { x, y } => {
+                // Coordinates are relative to the target window, if there is one
+                let origin = focus(&window)?;
+                let point = CGPoint::new(origin.x + x as f64, origin.y + y as f64);
+                post_click(point, &button, if double_click { 2 } else { 1 })?;
+                Ok(action_result(
+                    "click",
+                    serde_json::json!({ "x": x, "y": y }),
+                    started,
+                ))
+            }
+            ClickTarget::Element {
+                selector: Some(selector),
+                index,
+                ..
+            } => {
+                let index = index.unwrap_or(0) as usize;
+                let app = target_application(&window)?;
+                app.raise();
+                let pressed = app
+                    .find(&selector)
+                    .get(index)
+                    .map(AxElement::press)
+                    .ok_or_else(|| format!("Element '{}' not found", selector))?;
+                if !pressed {
+                    return Err(format!("Element '{}' can't be pressed", selector));
+                }
+                Ok(action_result(
+                    "click",
+                    serde_json::json!({ "selector": selector, "index": index }),
+                    started,
+                ))
+            }
+            _ => Err("Click target not yet implemented".to_string()),
+        }
+    }
+
+    async fn type_text(
+        &self,
+        window: WindowHandle,
+        text: &str,
+        clear_first: bool,
+        delay_ms: u32,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        ensure_accessibility()?;
+        focus(&window)?;
+        if clear_first {
+            for keydown in [true, false] {
+                post_key(0, CGEventFlags::CGEventFlagCommand, keydown)?; // Cmd+A
+            }
+            for keydown in [true, false] {
+                post_key(51, CGEventFlags::CGEventFlagNull, keydown)?; // Backspace
+            }
+        }
+        let delay = Duration::from_millis(delay_ms as u64);
+        let mut buffer = [0u8; 4];
+        for character in text.chars() {
+            post_text(character.encode_utf8(&mut buffer))?;
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+        }
+        Ok(action_result(
+            "type",
+            serde_json::json!({ "text": text }),
+            started,
+        ))
+    }
+
+    async fn hotkey(&self, keys: &[String], hold_ms: u32) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        if keys.is_empty() {
+            return Err("No keys to press".to_string());
+        }
+        ensure_accessibility()?;
+        let (flags, codes) = chord(keys)?;
+        for code in &codes {
+            post_key(*code, flags, true)?;
+        }
+        tokio::time::sleep(Duration::from_millis(hold_ms as u64)).await;
+        for code in codes.iter().rev() {
+            post_key(*code, flags, false)?;
+        }
+        Ok(action_result(
+            "hotkey",
+            serde_json::json!({ "keys": keys }),
+            started,
+        ))
+    }
+
+    async fn wait(
+        &self,
+        condition: WaitCondition,
+        timeout_ms: u32,
+        check_interval_ms: u32,
+    ) -> Result<ActionResult, String> {
+        wait_until(&condition, timeout_ms, check_interval_ms, |started| {
+            self.condition_met(&condition, started)
+        })
+        .await
+    }
+
+    async fn navigate(
+        &self,
+        window: WindowHandle,
+        direction: NavigationDirection,
+        distance: u32,
+        steps: u32,
+    ) -> Result<ActionResult, String> {
+        let started = Instant::now();
+        ensure_accessibility()?;
+        focus(&window)?;
+        let (dx, dy, steps) = navigation_steps(&direction, distance, steps);
+        for _ in 0..steps {
+            move_pointer(dx as f64, dy as f64)?;
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        Ok(action_result(
+            "navigate",
+            serde_json::json!({ "dx": dx, "dy": dy, "steps": steps }),
+            started,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_and_pixels() {
+        let (flags, codes) = chord(&["cmd".into(), "shift".into(), "t".into()]).unwrap();
+        assert_eq!(
+            flags,
+            CGEventFlags::CGEventFlagCommand | CGEventFlags::CGEventFlagShift
+        );
+        assert_eq!(codes, [17]);
+        let (_, codes) = chord(&["cmd".into()]).unwrap();
+        assert_eq!(codes, [55]);
+        assert!(chord(&["hyper".into()]).is_err());
+        assert_eq!(key_code("Enter"), Some(36));
+
+        // Two pixels per row, padded to 12 bytes
+        let bgra = [1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0];
+        let image = bgra_to_rgba(&bgra, 2, 1, 12).unwrap();
+        assert_eq!(image.into_raw(), [3, 2, 1, 4, 7, 6, 5, 8]);
+
+        assert!(matches!(
+            find_window("desktop"),
+            Ok(WindowHandle::MacOs(None))
+        ));
+    }
+}
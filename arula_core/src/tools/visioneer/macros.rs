@@ -0,0 +1,692 @@
+//! Recorded Visioneer macros
+//!
+//! A macro is a named list of visioneer calls saved as YAML in
+//! `~/.arula/macros`. `visioneer_macro` records the visioneer calls made
+//! between its start and stop actions, or saves steps the model writes
+//! itself; `run_macro` replays one. String values in steps can hold
+//! `{{param}}` placeholders, filled in from the run's parameters or the
+//! parameter defaults, so one macro can type different text each run.
+
+use super::{VisioneerParams, VisioneerTool};
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use anyhow::{Context, Result, bail};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Steps recorded since `visioneer_macro` started recording, if it has
+static RECORDING: Mutex<Option<Vec<MacroStep>>> = Mutex::new(None);
+
+/// One visioneer call in a macro
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub target: String,
+    pub action: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr_config: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vlm_config: Option<Value>,
+}
+
+impl MacroStep {
+    /// The step that repeats `params`
+    pub fn from_params(params: &VisioneerParams) -> Self {
+        let value = |v: Value| Some(without_nulls(v)).filter(|v| !v.is_null());
+        Self {
+            target: params.target.clone(),
+            action: serde_json::to_value(&params.action)
+                .map(without_nulls)
+                .unwrap_or_default(),
+            ocr_config: params
+                .ocr_config
+                .as_ref()
+                .and_then(|c| value(serde_json::to_value(c).ok()?)),
+            vlm_config: params
+                .vlm_config
+                .as_ref()
+                .and_then(|c| value(serde_json::to_value(c).ok()?)),
+        }
+    }
+
+    /// The visioneer call this step makes
+    pub fn to_params(&self) -> Result<VisioneerParams, String> {
+        serde_json::to_value(self)
+            .and_then(serde_json::from_value)
+            .map_err(|e| format!("Invalid step on '{}': {}", self.target, e))
+    }
+
+    fn map_strings(&self, f: &mut impl FnMut(&str) -> Result<String>) -> Result<Self> {
+        Ok(Self {
+            target: f(&self.target)?,
+            action: map_strings(&self.action, f)?,
+            ocr_config: self
+                .ocr_config
+                .as_ref()
+                .map(|v| map_strings(v, f))
+                .transpose()?,
+            vlm_config: self
+                .vlm_config
+                .as_ref()
+                .map(|v| map_strings(v, f))
+                .transpose()?,
+        })
+    }
+}
+
+/// A value a macro can be run with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroParam {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Used when a run doesn't give the parameter; without one it's required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// A named, replayable list of visioneer calls
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<MacroParam>,
+    pub steps: Vec<MacroStep>,
+}
+
+impl Macro {
+    /// Replace each parameter's default text in the steps with its
+    /// `{{name}}` placeholder, so recorded values become parameters
+    pub fn parameterize(&mut self) -> Result<()> {
+        let params = self.params.clone();
+        for step in &mut self.steps {
+            *step = step.map_strings(&mut |s| {
+                Ok(params
+                    .iter()
+                    .fold(s.to_string(), |s, param| match param.default.as_deref() {
+                        Some(default) if !default.is_empty() => {
+                            s.replace(default, &format!("{{{{{}}}}}", param.name))
+                        }
+                        _ => s,
+                    }))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The steps with placeholders filled in from `values`, falling back to
+    /// parameter defaults
+    pub fn steps_with(&self, values: &HashMap<String, String>) -> Result<Vec<MacroStep>> {
+        if let Some(unknown) = values
+            .keys()
+            .find(|name| !self.params.iter().any(|p| &p.name == *name))
+        {
+            bail!("Macro '{}' has no parameter '{}'", self.name, unknown);
+        }
+        let mut filled = HashMap::new();
+        for param in &self.params {
+            let value = values
+                .get(&param.name)
+                .or(param.default.as_ref())
+                .with_context(|| format!("Missing value for parameter '{}'", param.name))?;
+            filled.insert(param.name.as_str(), value.as_str());
+        }
+
+        self.steps
+            .iter()
+            .map(|step| step.map_strings(&mut |s| substitute(s, &filled)))
+            .collect()
+    }
+}
+
+/// `text` with each `{{name}}` replaced by its value
+fn substitute(text: &str, values: &HashMap<&str, &str>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + end].trim();
+        let value = values
+            .get(name)
+            .with_context(|| format!("Step uses undeclared parameter '{}'", name))?;
+        out.push_str(&rest[..start]);
+        out.push_str(value);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn map_strings(value: &Value, f: &mut impl FnMut(&str) -> Result<String>) -> Result<Value> {
+    Ok(match value {
+        Value::String(s) => Value::String(f(s)?),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| map_strings(item, f))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), map_strings(item, f)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// `value` without null object fields, which are the unset options
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, without_nulls(v)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+        other => other,
+    }
+}
+
+/// Start recording visioneer calls; false if already recording
+pub fn start_recording() -> bool {
+    let mut recording = RECORDING.lock().unwrap();
+    if recording.is_some() {
+        return false;
+    }
+    *recording = Some(Vec::new());
+    true
+}
+
+/// Stop recording and return the recorded steps, if recording
+pub fn stop_recording() -> Option<Vec<MacroStep>> {
+    RECORDING.lock().unwrap().take()
+}
+
+pub fn is_recording() -> bool {
+    RECORDING.lock().unwrap().is_some()
+}
+
+/// Add `step` to the recording, if recording
+pub(super) fn record(step: MacroStep) {
+    if let Some(steps) = RECORDING.lock().unwrap().as_mut() {
+        steps.push(step);
+    }
+}
+
+/// `~/.arula/macros`
+pub fn macros_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join("macros"))
+}
+
+/// Saved macros, one YAML file each
+#[derive(Debug, Clone)]
+pub struct MacroStore {
+    dir: PathBuf,
+}
+
+impl MacroStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The store in `~/.arula/macros`
+    pub fn user() -> Result<Self> {
+        macros_dir()
+            .map(Self::new)
+            .context("Could not find the home directory")
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if name.is_empty() || !valid {
+            bail!(
+                "Invalid macro name '{}': use letters, digits, '-' and '_'",
+                name
+            );
+        }
+        Ok(self.dir.join(format!("{}.yaml", name)))
+    }
+
+    pub fn load(&self, name: &str) -> Result<Macro> {
+        let path = self.path(name)?;
+        let yaml =
+            std::fs::read_to_string(&path).with_context(|| format!("No macro named '{}'", name))?;
+        serde_yaml::from_str(&yaml).with_context(|| format!("Invalid macro {}", path.display()))
+    }
+
+    /// Save `macro_`, replacing a macro with the same name only if `overwrite`
+    pub fn save(&self, macro_: &Macro, overwrite: bool) -> Result<PathBuf> {
+        let path = self.path(&macro_.name)?;
+        if path.exists() && !overwrite {
+            bail!(
+                "A macro named '{}' already exists; set overwrite to replace it",
+                macro_.name
+            );
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(&path, serde_yaml::to_string(macro_)?)?;
+        Ok(path)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        std::fs::remove_file(self.path(name)?).with_context(|| format!("No macro named '{}'", name))
+    }
+
+    /// Saved macros by name; files that don't parse are skipped
+    pub fn list(&self) -> Vec<Macro> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut macros: Vec<Macro> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+            .filter_map(|path| serde_yaml::from_str(&std::fs::read_to_string(path).ok()?).ok())
+            .collect();
+        macros.sort_by(|a, b| a.name.cmp(&b.name));
+        macros
+    }
+}
+
+fn store(dir: &Option<PathBuf>) -> Result<MacroStore, String> {
+    match dir {
+        Some(dir) => Ok(MacroStore::new(dir.clone())),
+        None => MacroStore::user().map_err(|e| e.to_string()),
+    }
+}
+
+/// Parameters for the visioneer_macro tool
+#[derive(Debug, Deserialize)]
+pub struct VisioneerMacroParams {
+    /// "start", "stop", "save", "show", "list" or "delete"
+    pub action: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Parameters; on stop, each default found in the recorded steps
+    /// becomes its placeholder
+    pub params: Option<Vec<MacroParam>>,
+    /// Steps for save
+    pub steps: Option<Vec<MacroStep>>,
+    pub overwrite: Option<bool>,
+}
+
+/// A saved macro in a listing
+#[derive(Debug, Serialize)]
+pub struct MacroSummary {
+    pub name: String,
+    pub description: Option<String>,
+    pub params: Vec<String>,
+    pub steps: usize,
+}
+
+/// Result of a visioneer_macro call
+#[derive(Debug, Serialize)]
+pub struct VisioneerMacroResult {
+    pub action: String,
+    pub recording: bool,
+    pub message: String,
+    #[serde(rename = "macro", skip_serializing_if = "Option::is_none")]
+    pub saved: Option<Macro>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macros: Option<Vec<MacroSummary>>,
+}
+
+/// Tool that records and manages macros
+pub struct VisioneerMacroTool {
+    /// Store directory, if not `~/.arula/macros`
+    dir: Option<PathBuf>,
+}
+
+impl VisioneerMacroTool {
+    pub fn new() -> Self {
+        Self { dir: None }
+    }
+
+    /// A tool that keeps macros in `dir`
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir: Some(dir) }
+    }
+}
+
+impl Default for VisioneerMacroTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for VisioneerMacroTool {
+    type Params = VisioneerMacroParams;
+    type Result = VisioneerMacroResult;
+
+    fn name(&self) -> &str {
+        "visioneer_macro"
+    }
+
+    fn description(&self) -> &str {
+        "Record and manage Visioneer macros. 'start' records every visioneer call until 'stop', which saves them as a named macro; give params with defaults on stop to turn recorded values (such as typed text) into {{param}} placeholders. 'save' stores steps you write yourself, each {target, action} like a visioneer call. 'show', 'list' and 'delete' manage saved macros. Replay them with run_macro."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new("visioneer_macro", "Record and manage Visioneer macros")
+            .param("action", "string")
+            .description("action", "One of: start, stop, save, show, list, delete")
+            .required("action")
+            .param("name", "string")
+            .description(
+                "name",
+                "Macro name (letters, digits, '-' and '_'); needed for stop, save, show and delete",
+            )
+            .param("description", "string")
+            .description("description", "What the macro does")
+            .param("params", "array")
+            .description(
+                "params",
+                "Macro parameters: [{name, description, default}]. Steps refer to them as {{name}}",
+            )
+            .param("steps", "array")
+            .description(
+                "steps",
+                "For save: [{target, action}] with the same shape as visioneer calls",
+            )
+            .param("overwrite", "boolean")
+            .description("overwrite", "Replace a saved macro with the same name")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let action = params.action.to_lowercase();
+        let result = |message: String| VisioneerMacroResult {
+            action: action.clone(),
+            recording: is_recording(),
+            message,
+            saved: None,
+            macros: None,
+        };
+        let name = || {
+            params
+                .name
+                .clone()
+                .ok_or_else(|| format!("The {} action needs a name", action))
+        };
+
+        match action.as_str() {
+            "start" => {
+                if !start_recording() {
+                    return Err("Already recording; stop the current recording first".into());
+                }
+                Ok(result("Recording visioneer calls".into()))
+            }
+            "stop" | "save" => {
+                let name = name()?;
+                let steps = if action == "stop" {
+                    stop_recording().ok_or("Not recording")?
+                } else {
+                    params.steps.clone().ok_or("The save action needs steps")?
+                };
+                if steps.is_empty() {
+                    return Err("The macro has no steps".into());
+                }
+                let mut macro_ = Macro {
+                    name,
+                    description: params.description.clone(),
+                    params: params.params.clone().unwrap_or_default(),
+                    steps,
+                };
+                if action == "stop" {
+                    macro_.parameterize().map_err(|e| e.to_string())?;
+                }
+                for step in &macro_.steps {
+                    step.to_params()?;
+                }
+                let path = store(&self.dir)?
+                    .save(&macro_, params.overwrite.unwrap_or(false))
+                    .map_err(|e| e.to_string())?;
+                Ok(VisioneerMacroResult {
+                    saved: Some(macro_),
+                    ..result(format!("Saved to {}", path.display()))
+                })
+            }
+            "show" => {
+                let macro_ = store(&self.dir)?
+                    .load(&name()?)
+                    .map_err(|e| e.to_string())?;
+                Ok(VisioneerMacroResult {
+                    saved: Some(macro_),
+                    ..result(String::new())
+                })
+            }
+            "list" => {
+                let macros: Vec<MacroSummary> = store(&self.dir)?
+                    .list()
+                    .into_iter()
+                    .map(|m| MacroSummary {
+                        name: m.name,
+                        description: m.description,
+                        params: m.params.into_iter().map(|p| p.name).collect(),
+                        steps: m.steps.len(),
+                    })
+                    .collect();
+                Ok(VisioneerMacroResult {
+                    macros: Some(macros),
+                    ..result(String::new())
+                })
+            }
+            "delete" => {
+                let name = name()?;
+                store(&self.dir)?.delete(&name).map_err(|e| e.to_string())?;
+                Ok(result(format!("Deleted macro '{}'", name)))
+            }
+            other => Err(format!(
+                "Unknown action '{}': use start, stop, save, show, list or delete",
+                other
+            )),
+        }
+    }
+}
+
+/// Parameters for the run_macro tool
+#[derive(Debug, Deserialize)]
+pub struct RunMacroParams {
+    pub name: String,
+    /// Values for the macro's parameters
+    pub params: Option<HashMap<String, String>>,
+    /// List the steps without running them
+    pub dry_run: Option<bool>,
+}
+
+/// What happened to one step of a run
+#[derive(Debug, Serialize)]
+pub struct MacroStepOutcome {
+    pub index: usize,
+    pub target: String,
+    pub action: Value,
+    /// Whether the step ran (or, in a dry run, parsed) without error
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of a run_macro call
+#[derive(Debug, Serialize)]
+pub struct RunMacroResult {
+    pub name: String,
+    pub dry_run: bool,
+    /// Whether every step succeeded; a run stops at the first failure
+    pub success: bool,
+    pub steps: Vec<MacroStepOutcome>,
+}
+
+/// Tool that replays a saved macro
+pub struct RunMacroTool {
+    dir: Option<PathBuf>,
+    visioneer: VisioneerTool,
+}
+
+impl RunMacroTool {
+    pub fn new() -> Self {
+        Self {
+            dir: None,
+            visioneer: VisioneerTool::new(),
+        }
+    }
+
+    /// A tool that runs macros from `dir`
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir: Some(dir),
+            visioneer: VisioneerTool::new(),
+        }
+    }
+}
+
+impl Default for RunMacroTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Tool for RunMacroTool {
+    type Params = RunMacroParams;
+    type Result = RunMacroResult;
+
+    fn name(&self) -> &str {
+        "run_macro"
+    }
+
+    fn description(&self) -> &str {
+        "Replay a Visioneer macro saved with visioneer_macro, running its visioneer calls in order and stopping at the first failure. Pass values for the macro's parameters in params; set dry_run to list the steps with parameters filled in without running them."
+    }
+
+    fn schema(&self) -> ToolSchema {
+        ToolSchemaBuilder::new("run_macro", "Replay a saved Visioneer macro")
+            .param("name", "string")
+            .description("name", "Name of the macro")
+            .required("name")
+            .param("params", "object")
+            .description("params", "Values for the macro's parameters, by name")
+            .param("dry_run", "boolean")
+            .description("dry_run", "List the steps without running them")
+            .build()
+    }
+
+    async fn execute(&self, params: Self::Params) -> Result<Self::Result, String> {
+        let macro_ = store(&self.dir)?
+            .load(&params.name)
+            .map_err(|e| e.to_string())?;
+        let steps = macro_
+            .steps_with(&params.params.unwrap_or_default())
+            .map_err(|e| e.to_string())?;
+        let dry_run = params.dry_run.unwrap_or(false);
+
+        let mut outcomes = Vec::new();
+        for (index, step) in steps.into_iter().enumerate() {
+            let mut outcome = MacroStepOutcome {
+                index,
+                target: step.target.clone(),
+                action: step.action.clone(),
+                success: true,
+                result: None,
+                error: None,
+            };
+            let run = match step.to_params() {
+                Ok(_) if dry_run => Ok(None),
+                Ok(visioneer_params) => self
+                    .visioneer
+                    .execute(visioneer_params)
+                    .await
+                    .map(|result| Some(serde_json::to_value(result).unwrap_or_default())),
+                Err(e) => Err(e),
+            };
+            match run {
+                Ok(result) => outcome.result = result,
+                Err(e) => {
+                    outcome.success = false;
+                    outcome.error = Some(e);
+                }
+            }
+            let failed = !outcome.success;
+            outcomes.push(outcome);
+            if failed && !dry_run {
+                break;
+            }
+        }
+
+        Ok(RunMacroResult {
+            name: macro_.name,
+            dry_run,
+            success: outcomes.iter().all(|o| o.success),
+            steps: outcomes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_record_save_and_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let params: VisioneerParams = serde_json::from_value(json!({
+            "target": "Notepad",
+            "action": {"type": "Type", "text": "hello alice", "clear_first": true}
+        }))
+        .unwrap();
+        let step = MacroStep::from_params(&params);
+        assert_eq!(
+            step.action,
+            json!({"type": "Type", "text": "hello alice", "clear_first": true})
+        );
+
+        let mut macro_ = Macro {
+            name: "greet".into(),
+            description: None,
+            params: vec![MacroParam {
+                name: "who".into(),
+                description: None,
+                default: Some("alice".into()),
+            }],
+            steps: vec![step],
+        };
+        macro_.parameterize().unwrap();
+        assert_eq!(macro_.steps[0].action["text"], "hello {{who}}");
+
+        let store = MacroStore::new(dir.path().to_path_buf());
+        store.save(&macro_, false).unwrap();
+        assert!(store.save(&macro_, false).is_err());
+        assert_eq!(store.load("greet").unwrap(), macro_);
+        assert!(store.load("../greet").is_err());
+
+        let values = HashMap::from([("who".to_string(), "bob".to_string())]);
+        let steps = macro_.steps_with(&values).unwrap();
+        assert_eq!(steps[0].action["text"], "hello bob");
+        let unknown = HashMap::from([("what".to_string(), "x".to_string())]);
+        assert!(macro_.steps_with(&unknown).is_err());
+
+        let result = RunMacroTool::with_dir(dir.path().to_path_buf())
+            .execute(RunMacroParams {
+                name: "greet".into(),
+                params: None,
+                dry_run: Some(true),
+            })
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.steps[0].action["text"], "hello alice");
+    }
+}
@@ -0,0 +1,571 @@
+//! WASM plugin tools
+//!
+//! Third-party tools compiled to WebAssembly (`wasm32-wasip1`) and run in a
+//! wasmtime sandbox, so the same plugin works on every platform ARULA runs
+//! on. Each plugin is a directory in `~/.arula/plugins/` with a
+//! `plugin.json` manifest next to its module:
+//!
+//! ```json
+//! {
+//!   "name": "word_count",
+//!   "description": "Count the words in the files of a directory",
+//!   "module": "word_count.wasm",
+//!   "parameters": {
+//!     "path": { "type": "string", "description": "Directory to count" },
+//!     "min_length": { "type": "integer", "required": false }
+//!   },
+//!   "capabilities": { "read": ["src"], "write": [], "env": ["LANG"] },
+//!   "limits": { "fuel": 1000000000, "memory_mb": 64 }
+//! }
+//! ```
+//!
+//! A call runs the module's `_start` with the tool arguments as JSON on
+//! stdin. Whatever it prints to stdout is the result, parsed as JSON when it
+//! is JSON. A non-zero exit fails the call with stderr as the error.
+//!
+//! A manifest may also name a slash command (`"command": "wc"`). Typing
+//! `/wc src` in the TUI runs the plugin with `{"args": "src"}` and shows the
+//! result.
+//!
+//! Plugins get only what the manifest declares: the `read` directories
+//! read-only, the `write` directories read-write (both relative to the
+//! project, mounted under the same names), and the listed environment
+//! variables. There is no network access, and CPU time (fuel) and memory are
+//! capped.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use wasmtime::{Engine, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::pipe::{MemoryInputPipe, MemoryOutputPipe};
+use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+/// Manifest file name in each plugin directory
+pub const MANIFEST_FILE: &str = "plugin.json";
+
+/// Fuel a call gets when the manifest doesn't say (roughly one per instruction)
+const DEFAULT_FUEL: u64 = 5_000_000_000;
+/// Memory a call gets when the manifest doesn't say
+const DEFAULT_MEMORY_MB: usize = 256;
+/// Most output kept from stdout and stderr
+const MAX_OUTPUT_BYTES: usize = 1 << 20;
+
+/// A plugin's `plugin.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    pub description: String,
+    /// Path of the `.wasm` module, relative to the plugin directory
+    pub module: String,
+    #[serde(default)]
+    pub parameters: BTreeMap<String, PluginParam>,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    #[serde(default)]
+    pub limits: Limits,
+    /// Slash command that runs the plugin with `{"args": ...}`
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
+/// A tool parameter declared by a plugin
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginParam {
+    /// JSON type (default: string)
+    #[serde(rename = "type", default = "default_param_type")]
+    pub param_type: String,
+    #[serde(default)]
+    pub description: String,
+    /// Whether the model must pass it (default: true)
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_param_type() -> String {
+    "string".to_string()
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// What a plugin may access
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Capabilities {
+    /// Project directories mounted read-only
+    #[serde(default)]
+    pub read: Vec<String>,
+    /// Project directories mounted read-write
+    #[serde(default)]
+    pub write: Vec<String>,
+    /// Environment variables passed through
+    #[serde(default)]
+    pub env: Vec<String>,
+}
+
+/// Resource limits for each call
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Limits {
+    pub fuel: Option<u64>,
+    pub memory_mb: Option<usize>,
+}
+
+impl Capabilities {
+    /// One-line summary, e.g. `read src · write out · env LANG`
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            ("read", &self.read),
+            ("write", &self.write),
+            ("env", &self.env),
+        ]
+        .into_iter()
+        .filter(|(_, items)| !items.is_empty())
+        .map(|(label, items)| format!("{} {}", label, items.join(", ")))
+        .collect();
+        if parts.is_empty() {
+            "no access".to_string()
+        } else {
+            parts.join(" · ")
+        }
+    }
+}
+
+/// Whether a plugin or command name is letters, digits, '_' or '-'
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// A loaded plugin
+#[derive(Debug)]
+pub struct WasmPlugin {
+    pub manifest: PluginManifest,
+    /// Path of the module file
+    pub module_path: PathBuf,
+    /// Compiled on first call, since compiling takes a moment
+    module: Mutex<Option<Module>>,
+}
+
+/// The plugins in `~/.arula/plugins/`
+#[derive(Debug, Clone, Default)]
+pub struct WasmPlugins {
+    plugins: Vec<Arc<WasmPlugin>>,
+    /// Plugins that failed to load, with the reason
+    pub errors: Vec<String>,
+}
+
+impl WasmPlugins {
+    /// Load every plugin in `~/.arula/plugins/`
+    pub fn load() -> Self {
+        match plugins_dir() {
+            Some(dir) => Self::load_from(&dir),
+            None => Self::default(),
+        }
+    }
+
+    /// Load every plugin directory in `dir`, in name order
+    pub fn load_from(dir: &Path) -> Self {
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.join(MANIFEST_FILE).is_file())
+                    .collect()
+            })
+            .unwrap_or_default();
+        dirs.sort();
+
+        let mut plugins = Self::default();
+        for dir in dirs {
+            match WasmPlugin::load(&dir) {
+                Ok(plugin) if plugins.get(&plugin.manifest.name).is_some() => {
+                    plugins.errors.push(format!(
+                        "{}: plugin {} is already loaded",
+                        dir.display(),
+                        plugin.manifest.name
+                    ));
+                }
+                Ok(plugin) => plugins.plugins.push(Arc::new(plugin)),
+                Err(e) => plugins.errors.push(format!("{}: {}", dir.display(), e)),
+            }
+        }
+
+        for error in &plugins.errors {
+            crate::utils::logger::log_module(
+                crate::utils::logger::LogLevel::Warn,
+                "plugins",
+                &format!("Plugin failed to load: {}", error),
+            );
+        }
+        plugins
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    pub fn plugins(&self) -> impl Iterator<Item = &WasmPlugin> {
+        self.plugins.iter().map(Arc::as_ref)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&WasmPlugin> {
+        self.plugins().find(|p| p.manifest.name == name)
+    }
+
+    /// The plugin providing the slash command `name`
+    pub fn command(&self, name: &str) -> Option<&WasmPlugin> {
+        self.plugins()
+            .find(|p| p.manifest.command.as_deref() == Some(name))
+    }
+
+    /// Register the plugin tools in a tool registry
+    pub fn register_tools(&self, registry: &mut crate::api::agent::ToolRegistry) {
+        for plugin in &self.plugins {
+            registry.register(WasmTool {
+                plugin: plugin.clone(),
+            });
+        }
+    }
+}
+
+impl WasmPlugin {
+    /// Read and check the manifest in `dir`
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(dir.join(MANIFEST_FILE))
+            .map_err(|e| format!("can't read {}: {}", MANIFEST_FILE, e))?;
+        let manifest: PluginManifest =
+            serde_json::from_str(&text).map_err(|e| format!("invalid {}: {}", MANIFEST_FILE, e))?;
+
+        if !valid_name(&manifest.name) {
+            return Err(format!(
+                "plugin name '{}' must be letters, digits, '_' or '-'",
+                manifest.name
+            ));
+        }
+        if let Some(command) = &manifest.command
+            && !valid_name(command)
+        {
+            return Err(format!(
+                "command '{}' must be letters, digits, '_' or '-'",
+                command
+            ));
+        }
+        for dir in manifest
+            .capabilities
+            .read
+            .iter()
+            .chain(&manifest.capabilities.write)
+        {
+            project_path(dir)?;
+        }
+
+        let module_path = dir.join(&manifest.module);
+        if !module_path.is_file() {
+            return Err(format!("module {} not found", manifest.module));
+        }
+
+        Ok(Self {
+            manifest,
+            module_path,
+            module: Mutex::new(None),
+        })
+    }
+
+    fn compiled_module(&self) -> Result<Module, String> {
+        let mut module = self.module.lock().unwrap();
+        if let Some(module) = module.as_ref() {
+            return Ok(module.clone());
+        }
+        let compiled = Module::from_file(engine(), &self.module_path)
+            .map_err(|e| format!("can't compile {}: {}", self.module_path.display(), e))?;
+        *module = Some(compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Run the plugin with `params` as its input, in the sandbox its
+    /// manifest describes
+    pub fn run(&self, params: &Value) -> Result<Value, String> {
+        let name = &self.manifest.name;
+        let module = self.compiled_module()?;
+        let capabilities = &self.manifest.capabilities;
+
+        let stdout = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+        let stderr = MemoryOutputPipe::new(MAX_OUTPUT_BYTES);
+        let mut wasi = WasiCtxBuilder::new();
+        wasi.args(&[name])
+            .stdin(MemoryInputPipe::new(params.to_string()))
+            .stdout(stdout.clone())
+            .stderr(stderr.clone());
+
+        let mounts = [
+            (&capabilities.read, DirPerms::READ, FilePerms::READ),
+            (&capabilities.write, DirPerms::all(), FilePerms::all()),
+        ];
+        for (dirs, dir_perms, file_perms) in mounts {
+            for dir in dirs {
+                wasi.preopened_dir(project_path(dir)?, dir, dir_perms, file_perms)
+                    .map_err(|e| format!("can't give {} access to {}: {}", name, dir, e))?;
+            }
+        }
+        for var in &capabilities.env {
+            if let Ok(value) = std::env::var(var) {
+                wasi.env(var, value);
+            }
+        }
+
+        let memory_mb = self.manifest.limits.memory_mb.unwrap_or(DEFAULT_MEMORY_MB);
+        let mut store = Store::new(
+            engine(),
+            PluginState {
+                wasi: wasi.build_p1(),
+                limits: StoreLimitsBuilder::new()
+                    .memory_size(memory_mb << 20)
+                    .instances(1)
+                    .build(),
+            },
+        );
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(self.manifest.limits.fuel.unwrap_or(DEFAULT_FUEL))
+            .map_err(|e| e.to_string())?;
+
+        let mut linker = Linker::new(engine());
+        preview1::add_to_linker_sync(&mut linker, |state: &mut PluginState| &mut state.wasi)
+            .map_err(|e| e.to_string())?;
+        let start = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, "_start"))
+            .map_err(|e| format!("can't start {}: {}", name, e))?;
+
+        let exit_code = match start.call(&mut store, ()) {
+            Ok(()) => 0,
+            Err(e) => match (e.downcast_ref::<I32Exit>(), e.downcast_ref::<Trap>()) {
+                (Some(exit), _) => exit.0,
+                (None, Some(Trap::OutOfFuel)) => {
+                    return Err(format!("{} ran out of fuel (raise limits.fuel)", name));
+                }
+                (None, _) => return Err(format!("{} crashed: {}", name, e)),
+            },
+        };
+        drop(store);
+
+        let output = String::from_utf8_lossy(&stdout.contents())
+            .trim()
+            .to_string();
+        if exit_code != 0 {
+            let errors = String::from_utf8_lossy(&stderr.contents())
+                .trim()
+                .to_string();
+            return Err(format!(
+                "{} exited with code {}: {}",
+                name,
+                exit_code,
+                if errors.is_empty() { output } else { errors }
+            ));
+        }
+        Ok(serde_json::from_str(&output).unwrap_or(Value::String(output)))
+    }
+}
+
+/// `~/.arula/plugins`
+pub fn plugins_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join("plugins"))
+}
+
+/// The engine every plugin runs on, with fuel metering on
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut config = wasmtime::Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("fuel metering is supported on every platform")
+    })
+}
+
+/// Store data for a call
+struct PluginState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+}
+
+/// A directory a plugin asked for, resolved against the project. Absolute
+/// paths and `..` are refused so plugins stay inside the project.
+fn project_path(dir: &str) -> Result<PathBuf, String> {
+    let path = Path::new(dir);
+    if path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(format!(
+            "directory '{}' must be inside the project (no absolute paths or '..')",
+            dir
+        ));
+    }
+    let root = std::env::current_dir().map_err(|e| e.to_string())?;
+    Ok(root.join(path))
+}
+
+/// A plugin, as seen by the agent
+struct WasmTool {
+    plugin: Arc<WasmPlugin>,
+}
+
+#[async_trait]
+impl Tool for WasmTool {
+    type Params = Value;
+    type Result = Value;
+
+    fn name(&self) -> &str {
+        &self.plugin.manifest.name
+    }
+
+    fn description(&self) -> &str {
+        &self.plugin.manifest.description
+    }
+
+    fn schema(&self) -> ToolSchema {
+        let manifest = &self.plugin.manifest;
+        let mut builder = ToolSchemaBuilder::new(&manifest.name, &manifest.description);
+        for (name, param) in &manifest.parameters {
+            builder = builder
+                .param(name, &param.param_type)
+                .description(name, &param.description);
+            if param.required {
+                builder = builder.required(name);
+            }
+        }
+        builder.build()
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value, String> {
+        let plugin = self.plugin.clone();
+        tokio::task::spawn_blocking(move || plugin.run(&params))
+            .await
+            .map_err(|e| format!("Plugin panicked: {}", e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Echoes stdin to stdout
+    const ECHO: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+          (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (func (export "_start")
+            (i32.store (i32.const 0) (i32.const 64))
+            (i32.store (i32.const 4) (i32.const 1024))
+            (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+            (i32.store (i32.const 4) (i32.load (i32.const 8)))
+            (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))
+    "#;
+
+    fn install(root: &Path, name: &str, manifest: Value, module: &str) {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+        std::fs::write(dir.join("plugin.wasm"), module).unwrap();
+    }
+
+    fn manifest(name: &str) -> Value {
+        json!({
+            "name": name,
+            "description": "Test plugin",
+            "module": "plugin.wasm",
+            "parameters": {
+                "text": { "description": "Some text" },
+                "count": { "type": "integer", "required": false }
+            }
+        })
+    }
+
+    #[test]
+    fn test_load_and_run() {
+        let root = tempfile::tempdir().unwrap();
+        let mut echo = manifest("echo");
+        echo["command"] = json!("say");
+        install(root.path(), "echo", echo, ECHO);
+        let plugins = WasmPlugins::load_from(root.path());
+        assert!(plugins.errors.is_empty(), "{:?}", plugins.errors);
+        assert!(plugins.command("say").is_some_and(|p| p.manifest.name == "echo"));
+        assert!(plugins.command("echo").is_none());
+
+        let plugin = plugins.get("echo").unwrap();
+        let params = json!({ "text": "hello" });
+        assert_eq!(plugin.run(&params).unwrap(), params);
+
+        let tool = WasmTool {
+            plugin: plugins.plugins[0].clone(),
+        };
+        let schema = tool.schema();
+        assert_eq!(schema.required, vec!["text".to_string()]);
+        assert_eq!(schema.parameters["count"].param_type, "integer");
+    }
+
+    #[test]
+    fn test_limits_and_exit_codes() {
+        let root = tempfile::tempdir().unwrap();
+        let mut spin = manifest("spin");
+        spin["limits"] = json!({ "fuel": 100_000 });
+        install(
+            root.path(),
+            "spin",
+            spin,
+            r#"(module (func (export "_start") (loop $l (br $l))))"#,
+        );
+        install(
+            root.path(),
+            "fail",
+            manifest("fail"),
+            r#"
+            (module
+              (import "wasi_snapshot_preview1" "proc_exit" (func $exit (param i32)))
+              (memory (export "memory") 1)
+              (func (export "_start") (call $exit (i32.const 3))))
+            "#,
+        );
+        let plugins = WasmPlugins::load_from(root.path());
+
+        let error = plugins.get("spin").unwrap().run(&json!({})).unwrap_err();
+        assert!(error.contains("ran out of fuel"), "{}", error);
+        let error = plugins.get("fail").unwrap().run(&json!({})).unwrap_err();
+        assert!(error.contains("exited with code 3"), "{}", error);
+    }
+
+    #[test]
+    fn test_rejected_manifests() {
+        let root = tempfile::tempdir().unwrap();
+        let mut escape = manifest("escape");
+        escape["capabilities"] = json!({ "write": ["../outside"] });
+        install(root.path(), "a", escape, ECHO);
+        let mut absolute = manifest("absolute");
+        absolute["capabilities"] = json!({ "read": ["/etc"] });
+        install(root.path(), "b", absolute, ECHO);
+        install(root.path(), "c", manifest("bad name"), ECHO);
+        install(root.path(), "d", manifest("twice"), ECHO);
+        install(root.path(), "e", manifest("twice"), ECHO);
+        let mut bad_command = manifest("bad_command");
+        bad_command["command"] = json!("two words");
+        install(root.path(), "f", bad_command, ECHO);
+
+        let plugins = WasmPlugins::load_from(root.path());
+        let names: Vec<&str> = plugins
+            .plugins()
+            .map(|p| p.manifest.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["twice"]);
+        assert_eq!(plugins.errors.len(), 5);
+    }
+}
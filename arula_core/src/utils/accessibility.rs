@@ -0,0 +1,96 @@
+//! Plain output for screen readers and limited terminals
+//!
+//! Plain mode is turned on with `"plain": true` in config.json, the `--plain`
+//! flag or `ARULA_PLAIN=1`. Frontends then switch to ASCII icons and the
+//! high-contrast theme, and pass what they print through [`to_plain`] so
+//! emoji and box-drawing characters are never read aloud.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that turns plain mode on
+pub const PLAIN_ENV: &str = "ARULA_PLAIN";
+/// Theme used in plain mode
+pub const PLAIN_THEME: &str = "high-contrast";
+/// ASCII spinner used in plain mode
+pub const PLAIN_SPINNER: &str = "line";
+
+static PLAIN_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Turn plain output on or off for the whole process
+pub fn set_plain_mode(plain: bool) {
+    PLAIN_MODE.store(plain, Ordering::Relaxed);
+}
+
+/// Whether output should be plain
+pub fn plain_mode() -> bool {
+    PLAIN_MODE.load(Ordering::Relaxed)
+}
+
+/// Whether `ARULA_PLAIN` asks for plain mode (`1`, `true`, `yes` or `on`)
+pub fn plain_from_env() -> bool {
+    std::env::var(PLAIN_ENV).is_ok_and(|value| {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Emoji, symbols and box drawing that carry no words
+fn is_decoration(c: char) -> bool {
+    matches!(c,
+        '\u{2500}'..='\u{259F}'     // box drawing, block elements
+        | '\u{25A0}'..='\u{25FF}'   // geometric shapes
+        | '\u{2600}'..='\u{27BF}'   // symbols, dingbats
+        | '\u{2800}'..='\u{28FF}'   // braille spinner frames
+        | '\u{2B00}'..='\u{2BFF}'
+        | '\u{1F000}'..='\u{1FAFF}' // emoji
+        | '\u{FE0F}' | '\u{200D}' | 'ℹ'
+    )
+}
+
+/// `text` without emoji or box-drawing characters
+///
+/// The space that separated a dropped symbol from the text is dropped with
+/// it, so `"✓ Saved"` becomes `"Saved"`; other spacing is kept.
+pub fn to_plain(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_decoration(c) {
+            out.push(c);
+        } else if (out.is_empty() || out.ends_with(' ')) && chars.peek() == Some(&' ') {
+            chars.next();
+        }
+    }
+    out
+}
+
+/// [`to_plain`] when plain mode is on, `text` unchanged otherwise
+pub fn plain_if_enabled(text: &str) -> String {
+    if plain_mode() {
+        to_plain(text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_plain_drops_symbols_and_their_spacing() {
+        assert_eq!(to_plain("✓ Saved"), "Saved");
+        assert_eq!(to_plain("🤖 ARULA: hi 👋"), "ARULA: hi ");
+        assert_eq!(to_plain("──── Today ────"), "Today ");
+        assert_eq!(to_plain("┌ ⠋ Tool 1/2 ┐ read_file"), "Tool 1/2 read_file");
+        assert_eq!(to_plain("⚠️ Config"), "Config");
+        // Code keeps its indentation and ASCII art
+        assert_eq!(
+            to_plain("    let x = a -> b; // +--+"),
+            "    let x = a -> b; // +--+"
+        );
+        assert_eq!(to_plain("naïve café — ok"), "naïve café — ok");
+    }
+}
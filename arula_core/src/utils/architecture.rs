@@ -0,0 +1,928 @@
+//! Architecture map generation
+//!
+//! Builds a module-level dependency graph of the current project by combining
+//! project detection with a lightweight scan of the source tree (workspace
+//! members, path dependencies, top-level modules, imports and symbol counts),
+//! and renders it as a Mermaid or Graphviz diagram.
+//!
+//! The map is regenerated on demand, so it can be refreshed whenever the
+//! project structure changes.
+
+use crate::utils::project_context::{ProjectType, detect_project};
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::LazyLock;
+use walkdir::WalkDir;
+
+/// Directories that never contain project sources
+const SKIP_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "vendor",
+    "dist",
+    "build",
+    "__pycache__",
+    "venv",
+];
+
+/// Files larger than this are not scanned for imports or symbols
+const MAX_SCAN_BYTES: u64 = 1024 * 1024;
+
+static RUST_SYMBOL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?(?:fn|struct|enum|trait|type|const|static)\s+\w")
+        .unwrap()
+});
+static JS_SYMBOL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:export\s+)?(?:default\s+)?(?:async\s+)?(?:function\*?|class|interface|type|enum)\s+\w")
+        .unwrap()
+});
+static PY_SYMBOL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^(?:async\s+)?(?:def|class)\s+\w").unwrap());
+static GO_SYMBOL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?m)^(?:func|type)\s+").unwrap());
+
+static JS_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?:from\s+|require\(\s*|import\s*\(\s*|import\s+)['"](\.{1,2}/[^'"]+)['"]"#)
+        .unwrap()
+});
+static PY_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^\s*(?:from\s+([\w.]+)\s+import|import\s+([\w.]+))").unwrap()
+});
+static GO_IMPORT_PATH: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#""([^"\s]+)""#).unwrap());
+
+/// Kind of node in the architecture graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeKind {
+    /// A crate, package or the project root
+    Package,
+    /// A top-level module within a package
+    Module,
+}
+
+/// A node in the architecture graph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchNode {
+    /// Unique identifier, e.g. `arula_core` or `arula_core::api`
+    pub id: String,
+    /// Short display label
+    pub label: String,
+    /// Whether this is a package or a module
+    pub kind: NodeKind,
+    /// Id of the package containing this module
+    pub parent: Option<String>,
+    /// Path relative to the project root
+    pub path: PathBuf,
+    /// Number of top-level symbols (functions, types, classes) found
+    pub symbols: usize,
+}
+
+/// A dependency between two nodes (`from` uses `to`)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ArchEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Output format for architecture diagrams
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagramFormat {
+    #[default]
+    Mermaid,
+    Graphviz,
+}
+
+impl DiagramFormat {
+    /// Parse a format name (`mermaid`/`mmd`, `dot`/`graphviz`)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "mermaid" | "mmd" => Some(DiagramFormat::Mermaid),
+            "dot" | "graphviz" | "gv" => Some(DiagramFormat::Graphviz),
+            _ => None,
+        }
+    }
+
+    /// File name the diagram is written to in the project root
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            DiagramFormat::Mermaid => "architecture.mmd",
+            DiagramFormat::Graphviz => "architecture.dot",
+        }
+    }
+}
+
+/// Module-level architecture of a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureMap {
+    pub project_name: String,
+    pub project_type: ProjectType,
+    pub nodes: Vec<ArchNode>,
+    pub edges: Vec<ArchEdge>,
+}
+
+impl ArchitectureMap {
+    /// Look up a node by id
+    pub fn node(&self, id: &str) -> Option<&ArchNode> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    /// Modules belonging to a package
+    pub fn modules_of<'a>(&'a self, package: &'a str) -> impl Iterator<Item = &'a ArchNode> + 'a {
+        self.nodes
+            .iter()
+            .filter(move |n| n.parent.as_deref() == Some(package))
+    }
+
+    /// Render the map in the given format
+    pub fn render(&self, format: DiagramFormat) -> String {
+        match format {
+            DiagramFormat::Mermaid => self.to_mermaid(),
+            DiagramFormat::Graphviz => self.to_dot(),
+        }
+    }
+
+    /// Render as a Mermaid flowchart, one subgraph per package
+    pub fn to_mermaid(&self) -> String {
+        let mut out = format!(
+            "%% Architecture of {} ({}) - generated by ARULA\ngraph TD\n",
+            self.project_name,
+            self.project_type.as_str()
+        );
+        for package in self.nodes.iter().filter(|n| n.kind == NodeKind::Package) {
+            let modules: Vec<&ArchNode> = self.modules_of(&package.id).collect();
+            if modules.is_empty() {
+                out.push_str(&format!(
+                    "    {}[\"{}\"]\n",
+                    sanitize_id(&package.id),
+                    node_label(package)
+                ));
+                continue;
+            }
+            out.push_str(&format!(
+                "    subgraph {}_group[\"{}\"]\n",
+                sanitize_id(&package.id),
+                escape_label(&package.label)
+            ));
+            out.push_str(&format!(
+                "        {}[[\"{}\"]]\n",
+                sanitize_id(&package.id),
+                node_label(package)
+            ));
+            for module in modules {
+                out.push_str(&format!(
+                    "        {}[\"{}\"]\n",
+                    sanitize_id(&module.id),
+                    node_label(module)
+                ));
+            }
+            out.push_str("    end\n");
+        }
+        for edge in &self.edges {
+            out.push_str(&format!(
+                "    {} --> {}\n",
+                sanitize_id(&edge.from),
+                sanitize_id(&edge.to)
+            ));
+        }
+        out
+    }
+
+    /// Render as a Graphviz digraph, one cluster per package
+    pub fn to_dot(&self) -> String {
+        let mut out = format!(
+            "// Architecture of {} ({}) - generated by ARULA\ndigraph architecture {{\n    rankdir=TB;\n    node [shape=box, style=rounded];\n",
+            self.project_name,
+            self.project_type.as_str()
+        );
+        for package in self.nodes.iter().filter(|n| n.kind == NodeKind::Package) {
+            let modules: Vec<&ArchNode> = self.modules_of(&package.id).collect();
+            let package_line = format!(
+                "\"{}\" [label=\"{}\", shape=box3d];\n",
+                package.id,
+                node_label(package)
+            );
+            if modules.is_empty() {
+                out.push_str("    ");
+                out.push_str(&package_line);
+                continue;
+            }
+            out.push_str(&format!(
+                "    subgraph \"cluster_{}\" {{\n        label=\"{}\";\n        ",
+                sanitize_id(&package.id),
+                escape_label(&package.label)
+            ));
+            out.push_str(&package_line);
+            for module in modules {
+                out.push_str(&format!(
+                    "        \"{}\" [label=\"{}\"];\n",
+                    module.id,
+                    node_label(module)
+                ));
+            }
+            out.push_str("    }\n");
+        }
+        for edge in &self.edges {
+            out.push_str(&format!("    \"{}\" -> \"{}\";\n", edge.from, edge.to));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Write the diagram to `<root>/architecture.{mmd,dot}` and return its path
+    pub fn write_to(&self, root: &Path, format: DiagramFormat) -> Result<PathBuf> {
+        let path = root.join(format.file_name());
+        fs::write(&path, self.render(format))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Build the architecture map for the project rooted at `root`
+pub fn build_architecture_map(root: &Path) -> Result<ArchitectureMap> {
+    let Some(project) = detect_project(root) else {
+        bail!("No project detected in {}", root.display());
+    };
+
+    let mut graph = GraphBuilder::default();
+    match project.project_type {
+        ProjectType::Rust => scan_rust(root, &mut graph)?,
+        ProjectType::Node => scan_sources(root, &project.name, Language::JavaScript, &mut graph),
+        ProjectType::Python => scan_sources(root, &project.name, Language::Python, &mut graph),
+        ProjectType::Go => scan_sources(root, &project.name, Language::Go, &mut graph),
+        ProjectType::Unknown => {
+            bail!("Architecture maps are supported for Rust, Node.js, Python and Go projects")
+        }
+    }
+
+    Ok(ArchitectureMap {
+        project_name: project.name,
+        project_type: project.project_type,
+        nodes: graph.nodes,
+        edges: graph.edges.into_iter().collect(),
+    })
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    nodes: Vec<ArchNode>,
+    edges: BTreeSet<ArchEdge>,
+}
+
+impl GraphBuilder {
+    fn has_node(&self, id: &str) -> bool {
+        self.nodes.iter().any(|n| n.id == id)
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str) {
+        if from != to && self.has_node(from) && self.has_node(to) {
+            self.edges.insert(ArchEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    Rust,
+    JavaScript,
+    Python,
+    Go,
+}
+
+impl Language {
+    fn matches(&self, path: &Path) -> bool {
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match self {
+            Language::Rust => ext == "rs",
+            Language::JavaScript => {
+                matches!(
+                    ext,
+                    "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "vue" | "svelte"
+                )
+            }
+            Language::Python => ext == "py",
+            Language::Go => ext == "go",
+        }
+    }
+
+    fn count_symbols(&self, content: &str) -> usize {
+        let re = match self {
+            Language::Rust => &RUST_SYMBOL,
+            Language::JavaScript => &JS_SYMBOL,
+            Language::Python => &PY_SYMBOL,
+            Language::Go => &GO_SYMBOL,
+        };
+        re.find_iter(content).count()
+    }
+}
+
+/// A Cargo package discovered in the workspace
+struct RustCrate {
+    name: String,
+    dir: PathBuf,
+    path_deps: Vec<PathBuf>,
+}
+
+fn scan_rust(root: &Path, graph: &mut GraphBuilder) -> Result<()> {
+    let crates = discover_rust_crates(root)?;
+    let crate_dirs: Vec<PathBuf> = crates.iter().map(|c| normalize_path(&c.dir)).collect();
+
+    for krate in &crates {
+        let src = krate.dir.join("src");
+        let root_files: Vec<PathBuf> = ["lib.rs", "main.rs"]
+            .iter()
+            .map(|f| src.join(f))
+            .filter(|p| p.exists())
+            .collect();
+        graph.nodes.push(ArchNode {
+            id: krate.name.clone(),
+            label: krate.name.clone(),
+            kind: NodeKind::Package,
+            parent: None,
+            path: relative_to(&krate.dir, root),
+            symbols: root_files
+                .iter()
+                .filter_map(|f| read_source(f))
+                .map(|c| Language::Rust.count_symbols(&c))
+                .sum(),
+        });
+        for unit in top_level_units(&src, Language::Rust) {
+            graph.nodes.push(ArchNode {
+                id: format!("{}::{}", krate.name, unit.name),
+                label: unit.name.clone(),
+                kind: NodeKind::Module,
+                parent: Some(krate.name.clone()),
+                path: relative_to(&unit.path, root),
+                symbols: unit.symbols,
+            });
+        }
+    }
+
+    for krate in &crates {
+        let deps: Vec<&RustCrate> = krate
+            .path_deps
+            .iter()
+            .filter_map(|dep| {
+                let dep = normalize_path(dep);
+                crate_dirs
+                    .iter()
+                    .position(|d| *d == dep)
+                    .map(|i| &crates[i])
+            })
+            .collect();
+        for dep in &deps {
+            graph.add_edge(&krate.name, &dep.name);
+        }
+
+        // Module-level edges from `crate::x` and `<dep_crate>::x` paths
+        let module_ids: Vec<(String, PathBuf)> = graph
+            .nodes
+            .iter()
+            .filter(|n| n.parent.as_deref() == Some(krate.name.as_str()))
+            .map(|n| (n.id.clone(), root.join(&n.path)))
+            .collect();
+        let root_files = ["lib.rs", "main.rs"].map(|f| krate.dir.join("src").join(f));
+        let sources = module_ids
+            .iter()
+            .map(|(id, path)| (id.clone(), source_files(path, Language::Rust)))
+            .chain(std::iter::once((
+                krate.name.clone(),
+                root_files.into_iter().filter(|p| p.exists()).collect(),
+            )));
+
+        let mut targets = vec![("crate".to_string(), krate.name.clone())];
+        targets.extend(
+            deps.iter()
+                .map(|d| (d.name.replace('-', "_"), d.name.clone())),
+        );
+        let targets = targets
+            .into_iter()
+            .map(|(ident, package)| {
+                Regex::new(&format!(r"\b{}::(\w+)", regex::escape(&ident))).map(|re| (re, package))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut found = Vec::new();
+        for (from, files) in sources {
+            for file in files {
+                let Some(content) = read_source(&file) else {
+                    continue;
+                };
+                for (re, package) in &targets {
+                    for cap in re.captures_iter(&content) {
+                        let module_id = format!("{}::{}", package, &cap[1]);
+                        found.push((from.clone(), module_id, package.clone()));
+                    }
+                }
+            }
+        }
+        for (from, module_id, package) in found {
+            if graph.has_node(&module_id) {
+                graph.add_edge(&from, &module_id);
+            } else if package != krate.name {
+                graph.add_edge(&from, &package);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the packages of a Cargo project (workspace members or a single crate)
+fn discover_rust_crates(root: &Path) -> Result<Vec<RustCrate>> {
+    let manifest = read_cargo_manifest(root)?;
+    let workspace_deps = manifest
+        .get("workspace")
+        .and_then(|w| w.get("dependencies"))
+        .and_then(|d| d.as_table())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut dirs = Vec::new();
+    if manifest.get("package").is_some() {
+        dirs.push(root.to_path_buf());
+    }
+    let members = manifest
+        .get("workspace")
+        .and_then(|w| w.get("members"))
+        .and_then(|m| m.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for member in members.iter().filter_map(|m| m.as_str()) {
+        if let Some(parent) = member.strip_suffix("/*") {
+            let Ok(entries) = fs::read_dir(root.join(parent)) else {
+                continue;
+            };
+            let mut found: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.join("Cargo.toml").exists())
+                .collect();
+            found.sort();
+            dirs.extend(found);
+        } else if root.join(member).join("Cargo.toml").exists() {
+            dirs.push(root.join(member));
+        }
+    }
+
+    let mut crates = Vec::new();
+    for dir in dirs {
+        let manifest = read_cargo_manifest(&dir)?;
+        let name = manifest
+            .get("package")
+            .and_then(|p| p.get("name"))
+            .and_then(|n| n.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| dir_name(&dir));
+        let mut path_deps = Vec::new();
+        if let Some(deps) = manifest.get("dependencies").and_then(|d| d.as_table()) {
+            for (dep_name, spec) in deps {
+                if let Some(path) = spec.get("path").and_then(|p| p.as_str()) {
+                    path_deps.push(dir.join(path));
+                } else if spec.get("workspace").and_then(|w| w.as_bool()) == Some(true)
+                    && let Some(path) = workspace_deps
+                        .get(dep_name)
+                        .and_then(|d| d.get("path"))
+                        .and_then(|p| p.as_str())
+                {
+                    path_deps.push(root.join(path));
+                }
+            }
+        }
+        crates.push(RustCrate {
+            name,
+            dir,
+            path_deps,
+        });
+    }
+    Ok(crates)
+}
+
+fn read_cargo_manifest(dir: &Path) -> Result<toml::Value> {
+    let path = dir.join("Cargo.toml");
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Scan a single-package Node.js, Python or Go project
+fn scan_sources(root: &Path, project_name: &str, language: Language, graph: &mut GraphBuilder) {
+    let base = source_base(root, language);
+    let units = top_level_units(&base, language);
+
+    graph.nodes.push(ArchNode {
+        id: project_name.to_string(),
+        label: project_name.to_string(),
+        kind: NodeKind::Package,
+        parent: None,
+        path: PathBuf::new(),
+        symbols: 0,
+    });
+    for unit in &units {
+        graph.nodes.push(ArchNode {
+            id: unit_id(project_name, &unit.name),
+            label: unit.name.clone(),
+            kind: NodeKind::Module,
+            parent: Some(project_name.to_string()),
+            path: relative_to(&unit.path, root),
+            symbols: unit.symbols,
+        });
+    }
+
+    let go_module = (language == Language::Go)
+        .then(|| fs::read_to_string(root.join("go.mod")).ok())
+        .flatten()
+        .and_then(|content| {
+            content.lines().find_map(|l| {
+                l.trim()
+                    .strip_prefix("module ")
+                    .map(|m| m.trim().to_string())
+            })
+        });
+
+    let unit_names: BTreeSet<&str> = units.iter().map(|u| u.name.as_str()).collect();
+    for unit in &units {
+        let from = unit_id(project_name, &unit.name);
+        for file in source_files(&unit.path, language) {
+            let Some(content) = read_source(&file) else {
+                continue;
+            };
+            let targets: Vec<String> = match language {
+                Language::JavaScript => JS_IMPORT
+                    .captures_iter(&content)
+                    .filter_map(|cap| {
+                        let target = normalize_path(&file.parent()?.join(&cap[1]));
+                        unit_of(&target, &base)
+                    })
+                    .collect(),
+                Language::Python => PY_IMPORT
+                    .captures_iter(&content)
+                    .filter_map(|cap| {
+                        let module = cap.get(1).or_else(|| cap.get(2))?.as_str();
+                        python_import_unit(module, &file, &base)
+                    })
+                    .collect(),
+                Language::Go => go_module
+                    .as_deref()
+                    .map(|module| go_import_units(&content, module))
+                    .unwrap_or_default(),
+                Language::Rust => Vec::new(),
+            };
+            for target in targets {
+                if unit_names.contains(target.as_str()) {
+                    graph.add_edge(&from, &unit_id(project_name, &target));
+                }
+            }
+        }
+    }
+}
+
+fn unit_id(project_name: &str, unit: &str) -> String {
+    format!("{}::{}", project_name, unit)
+}
+
+/// Directory whose children are treated as the project's top-level modules
+fn source_base(root: &Path, language: Language) -> PathBuf {
+    let mut base = match language {
+        Language::Go => root.to_path_buf(),
+        _ if root.join("src").is_dir() => root.join("src"),
+        _ => root.to_path_buf(),
+    };
+    // A Python project with a single package: use the package's subpackages
+    if language == Language::Python {
+        let packages: Vec<PathBuf> = fs::read_dir(&base)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.join("__init__.py").exists() && !is_skipped(p))
+            .collect();
+        if let [package] = packages.as_slice() {
+            base = package.clone();
+        }
+    }
+    base
+}
+
+/// A top-level module: a source file or a directory of source files
+struct SourceUnit {
+    name: String,
+    path: PathBuf,
+    symbols: usize,
+}
+
+fn top_level_units(base: &Path, language: Language) -> Vec<SourceUnit> {
+    let Ok(entries) = fs::read_dir(base) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+
+    let mut units: BTreeMap<String, SourceUnit> = BTreeMap::new();
+    for path in paths {
+        if is_skipped(&path) {
+            continue;
+        }
+        let name = if path.is_dir() {
+            dir_name(&path)
+        } else if language.matches(&path) {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if is_entry_file(&stem, language) {
+                continue;
+            }
+            stem
+        } else {
+            continue;
+        };
+        let files = source_files(&path, language);
+        if files.is_empty() {
+            continue;
+        }
+        let symbols: usize = files
+            .iter()
+            .filter_map(|f| read_source(f))
+            .map(|c| language.count_symbols(&c))
+            .sum();
+        // `foo.rs` and `foo/` together form one module
+        units
+            .entry(name.clone())
+            .and_modify(|u| {
+                u.symbols += symbols;
+                if path.is_dir() {
+                    u.path = path.clone();
+                }
+            })
+            .or_insert(SourceUnit {
+                name,
+                path,
+                symbols,
+            });
+    }
+    units.into_values().collect()
+}
+
+fn is_entry_file(stem: &str, language: Language) -> bool {
+    match language {
+        Language::Rust => matches!(stem, "lib" | "main" | "mod"),
+        Language::Python => matches!(stem, "__init__" | "__main__" | "setup" | "conftest"),
+        Language::JavaScript => false,
+        Language::Go => false,
+    }
+}
+
+/// All source files of a language under a file or directory
+fn source_files(path: &Path, language: Language) -> Vec<PathBuf> {
+    if path.is_file() {
+        return if language.matches(path) {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+    }
+    // A Rust module may be split into `foo.rs` plus `foo/`
+    let mut files: Vec<PathBuf> = Vec::new();
+    if language == Language::Rust {
+        let sibling = path.with_extension("rs");
+        if sibling.is_file() {
+            files.push(sibling);
+        }
+    }
+    files.extend(
+        WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_skipped(e.path()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file() && language.matches(e.path()))
+            .map(|e| e.into_path()),
+    );
+    files
+}
+
+fn is_skipped(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    name.starts_with('.') || SKIP_DIRS.contains(&name.as_ref())
+}
+
+fn read_source(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_SCAN_BYTES {
+        return None;
+    }
+    fs::read_to_string(path).ok()
+}
+
+/// Name of the top-level unit under `base` that contains `target`
+fn unit_of(target: &Path, base: &Path) -> Option<String> {
+    let rel = target.strip_prefix(normalize_path(base)).ok()?;
+    let first = rel.components().next()?;
+    let name = first.as_os_str().to_string_lossy().to_string();
+    // `./utils.js` or `./utils` both name the `utils` unit
+    Some(match name.split_once('.') {
+        Some((stem, _)) if rel.components().count() == 1 => stem.to_string(),
+        _ => name,
+    })
+}
+
+fn python_import_unit(module: &str, file: &Path, base: &Path) -> Option<String> {
+    let dots = module.chars().take_while(|c| *c == '.').count();
+    if dots == 0 {
+        return Some(module.split('.').next()?.to_string());
+    }
+    // Relative import: climb one directory per extra leading dot
+    let mut dir = file.parent()?.to_path_buf();
+    for _ in 1..dots {
+        dir = dir.parent()?.to_path_buf();
+    }
+    let rest = &module[dots..];
+    let target = rest
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .fold(dir, |acc, part| acc.join(part));
+    unit_of(&normalize_path(&target), base)
+}
+
+fn go_import_units(content: &str, module: &str) -> Vec<String> {
+    let prefix = format!("{}/", module);
+    GO_IMPORT_PATH
+        .captures_iter(content)
+        .filter_map(|cap| {
+            cap[1]
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.split('/').next())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Lexically resolve `.` and `..` components
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+fn relative_to(path: &Path, root: &Path) -> PathBuf {
+    path.strip_prefix(root).unwrap_or(path).to_path_buf()
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn node_label(node: &ArchNode) -> String {
+    if node.symbols > 0 {
+        format!("{} ({} symbols)", escape_label(&node.label), node.symbols)
+    } else {
+        escape_label(&node.label)
+    }
+}
+
+fn escape_label(label: &str) -> String {
+    label.replace('"', "'")
+}
+
+/// Turn a node id into an identifier valid in Mermaid
+fn sanitize_id(id: &str) -> String {
+    id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_rust_workspace_map() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"core\", \"cli\"]\n",
+        );
+        write(root, "core/Cargo.toml", "[package]\nname = \"my_core\"\n");
+        write(root, "core/src/lib.rs", "pub mod api;\npub mod utils;\n");
+        write(
+            root,
+            "core/src/api.rs",
+            "use crate::utils::helper;\npub fn call() {}\n",
+        );
+        write(
+            root,
+            "core/src/utils/mod.rs",
+            "pub fn helper() {}\npub struct Cfg;\n",
+        );
+        write(
+            root,
+            "cli/Cargo.toml",
+            "[package]\nname = \"my-cli\"\n\n[dependencies]\nmy_core = { path = \"../core\" }\n",
+        );
+        write(
+            root,
+            "cli/src/main.rs",
+            "use my_core::api::call;\nfn main() {}\n",
+        );
+
+        let map = build_architecture_map(root)?;
+        assert_eq!(map.project_type, ProjectType::Rust);
+        assert!(map.node("my_core::api").is_some());
+        assert_eq!(map.node("my_core::utils").unwrap().symbols, 2);
+        let has_edge =
+            |from: &str, to: &str| map.edges.iter().any(|e| e.from == from && e.to == to);
+        assert!(has_edge("my-cli", "my_core"));
+        assert!(has_edge("my-cli", "my_core::api"));
+        assert!(has_edge("my_core::api", "my_core::utils"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_node_imports() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let root = temp_dir.path();
+        write(root, "package.json", r#"{"name": "web"}"#);
+        write(root, "src/index.ts", "import { db } from './db';\n");
+        write(
+            root,
+            "src/routes/users.ts",
+            "import { query } from '../db/query';\nexport function list() {}\n",
+        );
+        write(root, "src/db/query.ts", "export function query() {}\n");
+
+        let map = build_architecture_map(root)?;
+        assert!(map.node("web::index").is_some());
+        assert!(map.edges.contains(&ArchEdge {
+            from: "web::routes".to_string(),
+            to: "web::db".to_string(),
+        }));
+        assert!(map.edges.contains(&ArchEdge {
+            from: "web::index".to_string(),
+            to: "web::db".to_string(),
+        }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let map = ArchitectureMap {
+            project_name: "demo".to_string(),
+            project_type: ProjectType::Rust,
+            nodes: vec![
+                ArchNode {
+                    id: "demo".to_string(),
+                    label: "demo".to_string(),
+                    kind: NodeKind::Package,
+                    parent: None,
+                    path: PathBuf::new(),
+                    symbols: 0,
+                },
+                ArchNode {
+                    id: "demo::api".to_string(),
+                    label: "api".to_string(),
+                    kind: NodeKind::Module,
+                    parent: Some("demo".to_string()),
+                    path: PathBuf::from("src/api.rs"),
+                    symbols: 3,
+                },
+            ],
+            edges: vec![ArchEdge {
+                from: "demo".to_string(),
+                to: "demo::api".to_string(),
+            }],
+        };
+        let mermaid = map.to_mermaid();
+        assert!(mermaid.contains("graph TD"));
+        assert!(mermaid.contains("demo__api[\"api (3 symbols)\"]"));
+        assert!(mermaid.contains("demo --> demo__api"));
+
+        let dot = map.to_dot();
+        assert!(dot.contains("subgraph \"cluster_demo\""));
+        assert!(dot.contains("\"demo\" -> \"demo::api\";"));
+        assert_eq!(DiagramFormat::parse("dot"), Some(DiagramFormat::Graphviz));
+        assert_eq!(DiagramFormat::parse(""), Some(DiagramFormat::Mermaid));
+        assert_eq!(DiagramFormat::parse("svg"), None);
+    }
+}
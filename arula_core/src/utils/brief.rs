@@ -0,0 +1,146 @@
+//! Rolling session brief
+//!
+//! Once a session has been idle for [`IDLE_AFTER`], the model rewrites a
+//! one-paragraph "what we're doing" brief from the previous brief and the
+//! conversation. Frontends show it with the session's details, title
+//! exports with its first sentence, and send it as context when a saved
+//! session is resumed.
+
+use crate::api::completion::complete;
+use crate::utils::chat::{ChatMessage, MessageType};
+use crate::utils::compaction::transcript;
+use crate::utils::config::Config;
+use anyhow::{Result, bail};
+use std::time::Duration;
+
+/// Idle time before the brief is brought up to date
+pub const IDLE_AFTER: Duration = Duration::from_secs(20);
+
+/// Messages a conversation needs before it gets a brief
+pub const MIN_MESSAGES: usize = 2;
+
+/// Longest brief kept
+const MAX_BRIEF_CHARS: usize = 600;
+
+/// Longest export title taken from a brief
+const MAX_TITLE_CHARS: usize = 60;
+
+const BRIEF_PROMPT: &str = "You keep a brief of a session between a user and a coding \
+assistant. Write one paragraph of at most three sentences saying what the user is trying \
+to do, what has been done so far and what comes next. Update the current brief if there \
+is one rather than starting over. Reply with the paragraph only.";
+
+/// Prefix of the context message sent when a session is resumed
+pub const BRIEF_HEADER: &str = "Brief of this session so far:";
+
+/// Whether a conversation of `messages` messages, `covered` of which the
+/// brief already includes, should get a new brief after being idle for `idle`
+pub fn is_due(messages: usize, covered: usize, idle: Duration) -> bool {
+    messages >= MIN_MESSAGES && messages > covered && idle >= IDLE_AFTER
+}
+
+/// Write a new brief from the `previous` one and the conversation
+pub async fn write_brief(
+    config: &Config,
+    previous: Option<&str>,
+    messages: &[ChatMessage],
+) -> Result<String> {
+    let mut prompt = String::new();
+    if let Some(previous) = previous {
+        prompt.push_str(&format!("Current brief: {}\n\n", previous));
+    }
+    prompt.push_str(&format!("Conversation:\n\n{}", transcript(messages)));
+
+    let brief = clean_brief(&complete(config, BRIEF_PROMPT, &prompt).await?);
+    if brief.is_empty() {
+        bail!("The model returned an empty brief");
+    }
+    Ok(brief)
+}
+
+/// [`write_brief`] for API-format messages (`user`, `assistant`, `tool`)
+pub async fn write_brief_for_history(
+    config: &Config,
+    previous: Option<&str>,
+    history: &[crate::api::api::ChatMessage],
+) -> Result<String> {
+    let messages: Vec<ChatMessage> = history
+        .iter()
+        .filter_map(|message| {
+            let message_type = match message.role.as_str() {
+                "user" => MessageType::User,
+                "assistant" => MessageType::Arula,
+                "tool" => MessageType::ToolResult,
+                _ => return None,
+            };
+            Some(ChatMessage::new(message_type, message.content.clone()?))
+        })
+        .collect();
+    write_brief(config, previous, &messages).await
+}
+
+/// The model's reply as a single paragraph
+fn clean_brief(reply: &str) -> String {
+    let text = reply.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = text
+        .strip_prefix("Brief:")
+        .unwrap_or(&text)
+        .trim()
+        .trim_matches('"');
+    match text.char_indices().nth(MAX_BRIEF_CHARS) {
+        Some((end, _)) => format!("{}...", text[..end].trim_end()),
+        None => text.to_string(),
+    }
+}
+
+/// A title for exports: the brief's first sentence, shortened at a word
+pub fn brief_title(brief: &str) -> String {
+    let sentence = brief
+        .split_inclusive(". ")
+        .next()
+        .unwrap_or(brief)
+        .trim()
+        .trim_end_matches('.');
+    if sentence.chars().count() <= MAX_TITLE_CHARS {
+        return sentence.to_string();
+    }
+    let (end, _) = sentence
+        .char_indices()
+        .nth(MAX_TITLE_CHARS)
+        .unwrap_or_default();
+    let cut = sentence[..end].rfind(' ').unwrap_or(end);
+    format!("{}...", sentence[..cut].trim_end_matches([',', ';', ':']))
+}
+
+/// The context message that seeds a resumed session
+pub fn seed_message(brief: &str) -> String {
+    format!("{}\n{}", BRIEF_HEADER, brief)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brief_cleanup_and_title() {
+        assert_eq!(
+            clean_brief("Brief: \"Adding retries to the\n  HTTP client. Tests next.\"\n"),
+            "Adding retries to the HTTP client. Tests next."
+        );
+        assert_eq!(
+            brief_title("Adding retries to the HTTP client. Tests next."),
+            "Adding retries to the HTTP client"
+        );
+        assert_eq!(
+            brief_title(
+                "Porting the session manager to the new event bus while keeping the old API working"
+            ),
+            "Porting the session manager to the new event bus while..."
+        );
+
+        assert!(is_due(4, 2, IDLE_AFTER));
+        assert!(!is_due(4, 4, IDLE_AFTER));
+        assert!(!is_due(1, 0, IDLE_AFTER));
+        assert!(!is_due(4, 2, Duration::from_secs(1)));
+    }
+}
@@ -1,10 +1,24 @@
 //! Changelog fetcher and parser for ARULA CLI
 //!
 //! Fetches changelog from remote git repository and displays recent changes
+//!
+//! Changes are sorted into categories from their conventional-commit
+//! prefix (`feat:`, `fix(tui):`, `perf!:`), or else from the section they're
+//! listed under. The version ARULA last ran as is kept in storage so
+//! `arula changelog` can show everything since the version before an update.
 
+use crate::storage::Storage;
+use crate::utils::version::is_newer;
 use anyhow::Result;
 use std::process::Command;
 
+/// Storage namespace for app state kept between runs
+const STATE_NAMESPACE: &str = "state";
+/// Version of the latest run
+const LAST_VERSION_KEY: &str = "last_version";
+/// Version that ran before the latest one, recorded on update
+const PREVIOUS_VERSION_KEY: &str = "previous_version";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum ChangelogType {
     Release,
@@ -18,9 +32,157 @@ pub struct ChangelogEntry {
     pub changes: Vec<String>,
 }
 
+/// Kind of change, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Category {
+    Feature,
+    Fix,
+    Performance,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Feature,
+        Category::Fix,
+        Category::Performance,
+        Category::Other,
+    ];
+
+    /// A category from a conventional-commit type or a filter name
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "feat" | "feature" | "features" => Some(Category::Feature),
+            "fix" | "fixes" | "bugfix" => Some(Category::Fix),
+            "perf" | "performance" => Some(Category::Performance),
+            "other" => Some(Category::Other),
+            _ => None,
+        }
+    }
+
+    /// The category of changes listed under a Keep a Changelog section
+    fn from_section(title: &str) -> Self {
+        match title.trim().to_lowercase().as_str() {
+            "added" | "features" => Category::Feature,
+            "fixed" | "fixes" | "security" => Category::Fix,
+            "performance" => Category::Performance,
+            _ => Category::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::Feature => "Features",
+            Category::Fix => "Fixes",
+            Category::Performance => "Performance",
+            Category::Other => "Other",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Category::Feature => "✨",
+            Category::Fix => "🐛",
+            Category::Performance => "⚡",
+            Category::Other => "•",
+        }
+    }
+}
+
+/// A single change with its category
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub category: Category,
+    /// Conventional-commit scope, as in `fix(tui): ...`
+    pub scope: Option<String>,
+    /// Marked breaking with `!`, as in `feat!: ...`
+    pub breaking: bool,
+    pub text: String,
+}
+
+impl Change {
+    /// A change from a bullet listed under section `section`
+    pub fn parse(line: &str, section: &str) -> Self {
+        let fallback = Change {
+            category: Category::from_section(section),
+            scope: None,
+            breaking: false,
+            text: line.trim().to_string(),
+        };
+        let Some((prefix, text)) = line.split_once(':') else {
+            return fallback;
+        };
+        let (prefix, breaking) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => match scope.strip_suffix(')') {
+                Some(scope) => (kind, Some(scope.to_string())),
+                None => return fallback,
+            },
+            None => (prefix, None),
+        };
+        let is_type = !kind.is_empty() && kind.chars().all(|c| c.is_ascii_alphabetic());
+        if !is_type || text.trim().is_empty() {
+            return fallback;
+        }
+        Change {
+            // Known types other than feat/fix/perf (docs, refactor, ...) are "other"
+            category: Category::parse(kind).unwrap_or(Category::Other),
+            scope,
+            breaking,
+            text: text.trim().to_string(),
+        }
+    }
+
+    /// The change as one line, with scope and breaking marker
+    pub fn display(&self) -> String {
+        let mut line = String::new();
+        if self.breaking {
+            line.push_str("[breaking] ");
+        }
+        if let Some(scope) = &self.scope {
+            line.push_str(&format!("{}: ", scope));
+        }
+        line.push_str(&self.text);
+        line
+    }
+}
+
+/// One `## [version]` section
+#[derive(Debug, Clone)]
+pub struct Release {
+    /// "Unreleased" or the version number
+    pub version: String,
+    pub date: Option<String>,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+impl Release {
+    pub fn is_unreleased(&self) -> bool {
+        self.version.eq_ignore_ascii_case("unreleased")
+    }
+
+    pub fn changes(&self) -> Vec<Change> {
+        self.entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .changes
+                    .iter()
+                    .map(|change| Change::parse(change, &entry.title))
+            })
+            .collect()
+    }
+}
+
 pub struct Changelog {
     pub changelog_type: ChangelogType,
+    /// Entries of the Unreleased section
     pub entries: Vec<ChangelogEntry>,
+    /// Every section, in file order (newest first)
+    pub releases: Vec<Release>,
 }
 
 impl Changelog {
@@ -53,9 +215,7 @@ impl Changelog {
     /// Parse changelog content
     pub fn parse(content: &str) -> Self {
         let mut changelog_type = ChangelogType::Development;
-        let mut entries = Vec::new();
-        let mut current_entry: Option<ChangelogEntry> = None;
-        let mut in_unreleased = false;
+        let mut releases: Vec<Release> = Vec::new();
 
         for line in content.lines() {
             // Detect changelog type from header comment
@@ -68,62 +228,94 @@ impl Changelog {
                 continue;
             }
 
-            // Skip until we find [Unreleased] section
-            if line.contains("## [Unreleased]") {
-                in_unreleased = true;
+            // Version sections: "## [Unreleased]", "## [0.2.0] - 2025-01-01"
+            if let Some(header) = line.strip_prefix("## [") {
+                let (version, rest) = header.split_once(']').unwrap_or((header, ""));
+                let date = rest.trim().trim_start_matches('-').trim();
+                releases.push(Release {
+                    version: version.trim().to_string(),
+                    date: (!date.is_empty()).then(|| date.to_string()),
+                    entries: Vec::new(),
+                });
                 continue;
             }
-
-            // Stop at next version section
-            if in_unreleased && line.starts_with("## [") && !line.contains("[Unreleased]") {
-                break;
-            }
-
-            if !in_unreleased {
+            let Some(release) = releases.last_mut() else {
                 continue;
-            }
+            };
 
             // Parse section headers (### Added, ### Changed, etc.)
-            if line.starts_with("### ") {
-                // Save previous entry if exists
-                if let Some(entry) = current_entry.take() {
-                    if !entry.changes.is_empty() {
-                        entries.push(entry);
-                    }
-                }
-
-                let title = line.trim_start_matches("### ").trim().to_string();
-                current_entry = Some(ChangelogEntry {
-                    title,
+            if let Some(title) = line.strip_prefix("### ") {
+                release.entries.push(ChangelogEntry {
+                    title: title.trim().to_string(),
                     changes: Vec::new(),
                 });
                 continue;
             }
 
             // Parse bullet points
-            if let Some(ref mut entry) = current_entry {
-                if line.starts_with("- ") {
-                    let change = line.trim_start_matches("- ").trim().to_string();
-                    if !change.is_empty() {
-                        entry.changes.push(change);
-                    }
+            if let (Some(entry), Some(change)) =
+                (release.entries.last_mut(), line.strip_prefix("- "))
+            {
+                let change = change.trim();
+                if !change.is_empty() {
+                    entry.changes.push(change.to_string());
                 }
             }
         }
 
-        // Add last entry
-        if let Some(entry) = current_entry {
-            if !entry.changes.is_empty() {
-                entries.push(entry);
-            }
+        for release in &mut releases {
+            release.entries.retain(|entry| !entry.changes.is_empty());
         }
+        let entries = releases
+            .iter()
+            .find(|release| release.is_unreleased())
+            .map(|release| release.entries.clone())
+            .unwrap_or_default();
 
         Self {
             changelog_type,
             entries,
+            releases,
         }
     }
 
+    /// Sections newer than `version`, newest first; Unreleased is newer
+    /// than every version. Without a version, every section.
+    pub fn releases_since(&self, version: Option<&str>) -> Vec<&Release> {
+        self.releases
+            .iter()
+            .filter(|release| match version {
+                None => true,
+                Some(since) => release.is_unreleased() || is_newer(&release.version, since),
+            })
+            .collect()
+    }
+
+    /// Changes of `releases` grouped by category in display order, only
+    /// those in `categories` unless it's empty, at most `max_items` in all
+    pub fn group_by_category<'a>(
+        releases: impl IntoIterator<Item = &'a Release>,
+        categories: &[Category],
+        max_items: usize,
+    ) -> Vec<(Category, Vec<Change>)> {
+        let mut changes: Vec<Change> = releases
+            .into_iter()
+            .flat_map(Release::changes)
+            .filter(|change| categories.is_empty() || categories.contains(&change.category))
+            .take(max_items)
+            .collect();
+        changes.sort_by_key(|change| change.category);
+
+        let mut groups: Vec<(Category, Vec<Change>)> = Vec::new();
+        for change in changes {
+            match groups.last_mut() {
+                Some((category, group)) if *category == change.category => group.push(change),
+                _ => groups.push((change.category, vec![change])),
+            }
+        }
+        groups
+    }
+
     /// Get default changelog when file doesn't exist
     pub fn default_changelog() -> String {
         r#"# Changelog
@@ -178,6 +370,25 @@ impl Changelog {
         }
     }
 
+    /// Record that `version` is running. Returns the version that ran
+    /// before it if that was a different one, i.e. after an update.
+    pub fn record_run(storage: &Storage, version: &str) -> Result<Option<String>> {
+        let last: Option<String> = storage.cache_get(STATE_NAMESPACE, LAST_VERSION_KEY)?;
+        if last.as_deref() == Some(version) {
+            return Ok(None);
+        }
+        storage.cache_put(STATE_NAMESPACE, LAST_VERSION_KEY, version, None)?;
+        if let Some(last) = &last {
+            storage.cache_put(STATE_NAMESPACE, PREVIOUS_VERSION_KEY, last, None)?;
+        }
+        Ok(last)
+    }
+
+    /// The version that ran before the latest update, if there was one
+    pub fn previous_version(storage: &Storage) -> Result<Option<String>> {
+        storage.cache_get(STATE_NAMESPACE, PREVIOUS_VERSION_KEY)
+    }
+
     /// Detect if this is a custom build by checking git remote
     pub fn detect_build_type() -> ChangelogType {
         // Check if git remote is the official repo
@@ -281,4 +492,62 @@ mod tests {
         assert_eq!(recent.len(), 3);
         assert!(recent[0].contains("Change 1"));
     }
+
+    #[test]
+    fn test_categories_and_since() {
+        let content = r#"# Changelog
+
+## [Unreleased]
+
+### Changed
+- fix(tui): cursor jumps after paste
+- Reworded the help text
+
+## [0.10.0] - 2025-03-01
+
+### Added
+- perf!: stream tool output instead of buffering it
+- Session export
+
+## [0.9.0] - 2025-02-01
+
+### Added
+- Old feature
+"#;
+        let changelog = Changelog::parse(content);
+        assert_eq!(changelog.releases.len(), 3);
+        assert_eq!(changelog.releases[1].date.as_deref(), Some("2025-03-01"));
+
+        let since = changelog.releases_since(Some("v0.9.0"));
+        assert_eq!(since.len(), 2, "0.10.0 is newer than 0.9.0");
+        let groups = Changelog::group_by_category(since, &[], 10);
+        let categories: Vec<Category> = groups.iter().map(|(c, _)| *c).collect();
+        assert_eq!(
+            categories,
+            [
+                Category::Feature,
+                Category::Fix,
+                Category::Performance,
+                Category::Other
+            ]
+        );
+        assert_eq!(groups[1].1[0].scope.as_deref(), Some("tui"));
+        assert_eq!(groups[1].1[0].text, "cursor jumps after paste");
+        assert!(groups[2].1[0].breaking);
+
+        let fixes = Changelog::group_by_category(&changelog.releases, &[Category::Fix], 10);
+        assert_eq!(fixes.len(), 1);
+
+        let storage = Storage::open_in_memory().unwrap();
+        assert_eq!(Changelog::record_run(&storage, "0.9.0").unwrap(), None);
+        assert_eq!(Changelog::record_run(&storage, "0.9.0").unwrap(), None);
+        assert_eq!(
+            Changelog::record_run(&storage, "0.10.0").unwrap().as_deref(),
+            Some("0.9.0")
+        );
+        assert_eq!(
+            Changelog::previous_version(&storage).unwrap().as_deref(),
+            Some("0.9.0")
+        );
+    }
 }
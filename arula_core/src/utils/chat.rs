@@ -100,6 +100,12 @@ impl ChatMessage {
         }
     }
 
+    /// Replace the timestamp, e.g. when restoring a saved conversation
+    pub fn with_timestamp(mut self, timestamp: DateTime<Local>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
     // Test helper methods
     pub fn new_user_message(content: &str) -> Self {
         Self::new(MessageType::User, content.to_string())
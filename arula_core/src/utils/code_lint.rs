@@ -0,0 +1,435 @@
+//! Syntax checks for code blocks in responses
+//!
+//! Extracts fenced code blocks from a response and checks the ones in a
+//! known language: a tree-sitter parse for Rust, TypeScript/JavaScript,
+//! Python and Go, then `cargo check` in a scratch crate for Rust and
+//! `python3 -m py_compile` for Python. Enabled with the
+//! `context.lint_code_blocks` setting.
+//!
+//! Snippets are rarely complete programs, so errors that only mean "this
+//! depends on code that isn't shown" (unresolved imports and names, missing
+//! `main`) are not reported.
+
+use crate::utils::symbol_index::Language;
+use regex::Regex;
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Longest a single external check may run
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+/// Most issues reported per block
+const MAX_ISSUES: usize = 3;
+/// rustc errors caused by code outside the snippet, not by the snippet itself
+const IGNORED_RUSTC_ERRORS: &[&str] = &[
+    "E0405", // trait not found
+    "E0412", // type not found
+    "E0422", // struct not found
+    "E0425", // value not found
+    "E0432", // unresolved import
+    "E0433", // failed to resolve path
+    "E0463", // can't find crate
+    "E0531", // tuple struct or variant not found
+    "E0601", // no main function
+];
+
+/// A fenced code block in a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// Language tag of the fence (may be empty)
+    pub language: String,
+    pub code: String,
+    /// 1-based line of the opening fence in the response
+    pub line: usize,
+}
+
+/// A problem found in a code block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// 1-based line within the block, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Lint result for a code block that failed its checks
+#[derive(Debug, Clone)]
+pub struct BlockLint {
+    /// 1-based position of the block in the response
+    pub index: usize,
+    pub language: String,
+    /// Check that found the issues
+    pub checker: &'static str,
+    pub issues: Vec<LintIssue>,
+}
+
+impl BlockLint {
+    /// One-line summary, e.g. `code block 2 (rust) fails cargo check`
+    pub fn summary(&self) -> String {
+        format!(
+            "code block {} ({}) fails {}",
+            self.index, self.language, self.checker
+        )
+    }
+}
+
+impl LintIssue {
+    /// The issue with its line prefix, e.g. `line 3: expected `;``
+    pub fn describe(&self) -> String {
+        match self.line {
+            Some(line) => format!("line {}: {}", line, self.message),
+            None => self.message.clone(),
+        }
+    }
+}
+
+/// Extract fenced (```) code blocks from Markdown text
+///
+/// An unterminated block at the end of the text is ignored.
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    let mut blocks = Vec::new();
+    let mut open: Option<(String, usize, Vec<&str>)> = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let trimmed = line.trim_start();
+        match open.take() {
+            None => {
+                if let Some(tag) = trimmed.strip_prefix("```") {
+                    let language = tag.split_whitespace().next().unwrap_or("").to_string();
+                    open = Some((language, number + 1, Vec::new()));
+                }
+            }
+            Some((language, start, lines)) if trimmed.starts_with("```") => {
+                blocks.push(CodeBlock {
+                    language,
+                    code: lines.join("\n"),
+                    line: start,
+                });
+            }
+            Some((language, start, mut lines)) => {
+                lines.push(line);
+                open = Some((language, start, lines));
+            }
+        }
+    }
+    blocks
+}
+
+/// Check every code block in `text`, returning the ones that fail
+///
+/// Runs external compilers, so call it off the UI thread.
+pub fn lint_code_blocks(text: &str) -> Vec<BlockLint> {
+    extract_code_blocks(text)
+        .iter()
+        .enumerate()
+        .filter_map(|(i, block)| {
+            let (checker, issues) = lint_block(block)?;
+            Some(BlockLint {
+                index: i + 1,
+                language: block.language.clone(),
+                checker,
+                issues,
+            })
+        })
+        .collect()
+}
+
+/// Check one code block, returning the failing checker and its issues
+///
+/// Returns `None` when the block passes, isn't in a checked language, or
+/// looks like an elided or interactive snippet.
+pub fn lint_block(block: &CodeBlock) -> Option<(&'static str, Vec<LintIssue>)> {
+    let language = Language::from_fence(&block.language)?;
+    if block.code.trim().is_empty() || is_partial_snippet(&block.code) {
+        return None;
+    }
+
+    if language == Language::Python
+        && let Some(issues) = py_compile(&block.code)
+    {
+        return (!issues.is_empty()).then_some(("py_compile", issues));
+    }
+
+    let issues = parse_issues(language, &block.code);
+    if !issues.is_empty() {
+        return Some(("syntax check", issues));
+    }
+
+    if language == Language::Rust
+        && let Some(issues) = cargo_check(&block.code)
+        && !issues.is_empty()
+    {
+        return Some(("cargo check", issues));
+    }
+    None
+}
+
+/// Snippets with `...` elisions or shell/REPL prompts aren't meant to compile
+fn is_partial_snippet(code: &str) -> bool {
+    code.lines().any(|line| {
+        let line = line.trim();
+        line == "..." || line.starts_with(">>> ") || line.starts_with("$ ")
+    })
+}
+
+/// Syntax errors found by parsing the snippet with tree-sitter
+fn parse_issues(language: Language, code: &str) -> Vec<LintIssue> {
+    let mut parser = tree_sitter::Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return Vec::new();
+    };
+    if !tree.root_node().has_error() {
+        return Vec::new();
+    }
+
+    let mut issues = Vec::new();
+    let mut cursor = tree.walk();
+    let mut visited_children = false;
+    loop {
+        let node = cursor.node();
+        if !visited_children && (node.is_error() || node.is_missing()) {
+            let message = if node.is_missing() {
+                format!("missing `{}`", node.kind())
+            } else {
+                "syntax error".to_string()
+            };
+            issues.push(LintIssue {
+                line: Some(node.start_position().row + 1),
+                message,
+            });
+            if issues.len() == MAX_ISSUES {
+                break;
+            }
+            // Errors nested inside an error node add nothing
+            visited_children = true;
+        }
+        if !visited_children && cursor.goto_first_child() {
+            continue;
+        }
+        if cursor.goto_next_sibling() {
+            visited_children = false;
+            continue;
+        }
+        if !cursor.goto_parent() {
+            break;
+        }
+        visited_children = true;
+    }
+    issues
+}
+
+/// Whether a Rust snippet is bare statements that need wrapping in `fn main`
+fn is_statement_snippet(code: &str) -> bool {
+    let mut parser = tree_sitter::Parser::new();
+    if parser.set_language(&Language::Rust.grammar()).is_err() {
+        return false;
+    }
+    let Some(tree) = parser.parse(code, None) else {
+        return false;
+    };
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.children(&mut cursor)
+        .any(|child| matches!(child.kind(), "let_declaration" | "expression_statement"))
+}
+
+/// Lay out a Rust snippet as a scratch crate in `dir`
+///
+/// Bare statements are wrapped in `fn main`; other snippets become `main.rs`
+/// when they define `main` and `lib.rs` otherwise. Returns the number of
+/// lines added above the snippet.
+pub(crate) fn write_rust_crate(dir: &Path, code: &str) -> std::io::Result<usize> {
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(
+        dir.join("Cargo.toml"),
+        "[package]\nname = \"snippet\"\nversion = \"0.0.0\"\nedition = \"2024\"\n\n[workspace]\n",
+    )?;
+
+    let header = "#![allow(dead_code, unused)]\n";
+    let (file, source, offset) = if is_statement_snippet(code) {
+        (
+            "main.rs",
+            format!("{}fn main() {{\n{}\n}}\n", header, code),
+            2,
+        )
+    } else if code.contains("fn main(") {
+        ("main.rs", format!("{}{}\n", header, code), 1)
+    } else {
+        ("lib.rs", format!("{}{}\n", header, code), 1)
+    };
+    fs::write(dir.join("src").join(file), source)?;
+    Ok(offset)
+}
+
+/// Compile a Rust snippet in a scratch crate with `cargo check`
+///
+/// Returns `None` when cargo is unavailable or the check didn't finish.
+fn cargo_check(code: &str) -> Option<Vec<LintIssue>> {
+    let dir = tempfile::tempdir().ok()?;
+    let offset = write_rust_crate(dir.path(), code).ok()?;
+
+    // Share build artifacts between checks so std metadata is reused
+    let target_dir = std::env::temp_dir().join("arula-lint-target");
+    let mut command = Command::new("cargo");
+    command
+        .args(["check", "--quiet", "--message-format", "json"])
+        .env("CARGO_TARGET_DIR", target_dir)
+        .current_dir(dir.path());
+    let (_, stdout, _) = run_with_timeout(command, dir.path())?;
+
+    let mut issues = Vec::new();
+    for line in stdout.lines() {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if value["reason"] != "compiler-message" || value["message"]["level"] != "error" {
+            continue;
+        }
+        let message = &value["message"];
+        if let Some(code) = message["code"]["code"].as_str()
+            && IGNORED_RUSTC_ERRORS.contains(&code)
+        {
+            continue;
+        }
+        let Some(text) = message["message"].as_str() else {
+            continue;
+        };
+        // Summary lines like "aborting due to previous error" have no spans
+        let Some(spans) = message["spans"].as_array().filter(|s| !s.is_empty()) else {
+            continue;
+        };
+        let line = spans
+            .iter()
+            .find(|s| s["is_primary"] == true)
+            .and_then(|s| s["line_start"].as_u64())
+            .map(|l| (l as usize).saturating_sub(offset))
+            .filter(|l| *l > 0);
+        issues.push(LintIssue {
+            line,
+            message: text.to_string(),
+        });
+        if issues.len() == MAX_ISSUES {
+            break;
+        }
+    }
+    Some(issues)
+}
+
+/// Byte-compile a Python snippet with `python3 -m py_compile`
+///
+/// Returns `None` when Python is unavailable.
+fn py_compile(code: &str) -> Option<Vec<LintIssue>> {
+    static LINE: OnceLock<Regex> = OnceLock::new();
+    let line_re = LINE.get_or_init(|| Regex::new(r", line (\d+)").unwrap());
+
+    let dir = tempfile::tempdir().ok()?;
+    let path = dir.path().join("snippet.py");
+    fs::write(&path, code).ok()?;
+
+    let mut command = Command::new("python3");
+    command
+        .args(["-m", "py_compile"])
+        .arg(&path)
+        .env("PYTHONDONTWRITEBYTECODE", "1");
+    let (success, _, stderr) = run_with_timeout(command, dir.path())?;
+    if success {
+        return Some(Vec::new());
+    }
+
+    let message = stderr
+        .lines()
+        .rev()
+        .find(|l| l.contains("Error"))
+        .unwrap_or("does not compile")
+        .trim()
+        .to_string();
+    let line = line_re.captures(&stderr).and_then(|c| c[1].parse().ok());
+    Some(vec![LintIssue { line, message }])
+}
+
+/// Run a command with output captured to files in `dir`, killing it after
+/// `CHECK_TIMEOUT`
+///
+/// Returns `(success, stdout, stderr)`, or `None` if it couldn't be started
+/// or timed out.
+fn run_with_timeout(mut command: Command, dir: &Path) -> Option<(bool, String, String)> {
+    let stdout_path = dir.join("stdout.log");
+    let stderr_path = dir.join("stderr.log");
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(File::create(&stdout_path).ok()?)
+        .stderr(File::create(&stderr_path).ok()?)
+        .spawn()
+        .ok()?;
+
+    let started = Instant::now();
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if started.elapsed() < CHECK_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    };
+
+    Some((
+        status.success(),
+        fs::read_to_string(stdout_path).unwrap_or_default(),
+        fs::read_to_string(stderr_path).unwrap_or_default(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_code_blocks() {
+        let text = "Try this:\n\n```rust\nfn main() {}\n```\n\nthen\n  ```\nplain\n  ```\n```py\nunterminated";
+        let blocks = extract_code_blocks(text);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].language, "rust");
+        assert_eq!(blocks[0].code, "fn main() {}");
+        assert_eq!(blocks[0].line, 3);
+        assert_eq!(blocks[1].language, "");
+        assert_eq!(blocks[1].code, "plain");
+    }
+
+    #[test]
+    fn test_parse_issues() {
+        let issues = parse_issues(Language::Rust, "fn main() {\n    let x = (1 + 2;\n}\n");
+        assert!(!issues.is_empty());
+        assert_eq!(issues[0].line, Some(2));
+
+        assert!(parse_issues(Language::Go, "package main\n\nfunc main() {}\n").is_empty());
+        assert!(parse_issues(Language::Python, "def f(:\n    pass\n").len() == 1);
+        assert!(is_statement_snippet("let x = 1;\nprintln!(\"{}\", x);"));
+        assert!(!is_statement_snippet("fn main() {}"));
+    }
+
+    #[test]
+    fn test_lint_block_skips() {
+        let block = |language: &str, code: &str| CodeBlock {
+            language: language.to_string(),
+            code: code.to_string(),
+            line: 1,
+        };
+        // Unknown languages, elided snippets and valid code pass
+        assert!(lint_block(&block("text", "fn (")).is_none());
+        assert!(lint_block(&block("ts", "function f( {\n...\n")).is_none());
+        assert!(lint_block(&block("ts", "const x: number = 1;")).is_none());
+
+        let (checker, issues) = lint_block(&block("typescript", "const x = {;")).unwrap();
+        assert_eq!(checker, "syntax check");
+        assert!(issues[0].describe().starts_with("line 1:"));
+    }
+}
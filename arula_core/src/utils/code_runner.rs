@@ -0,0 +1,337 @@
+//! Run code blocks from responses in a scratch project
+//!
+//! Copies a fenced code block into a temporary project for its language and
+//! runs it there: `cargo run` for Rust, `uv run` (or `python3`) for Python,
+//! `node` for JavaScript and TypeScript, `go run` for Go and `sh`/`bash` for
+//! shell. Output is streamed back line by line.
+//!
+//! The run is contained, not isolated: it happens in a throwaway directory
+//! with a scrubbed environment (no API keys or tokens), a time limit and an
+//! output limit, but with the user's own permissions.
+
+use crate::utils::code_lint::{CodeBlock, write_rust_crate};
+use anyhow::{Context, Result, anyhow};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command as TokioCommand;
+use tokio::sync::mpsc;
+
+/// Longest a run may take, including compilation
+pub const RUN_TIMEOUT: Duration = Duration::from_secs(120);
+/// Output lines forwarded before the rest is dropped
+pub const MAX_OUTPUT_LINES: usize = 500;
+/// Environment variables passed through to the run; everything else is cleared
+const PASSED_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "LANG",
+    "CARGO_HOME",
+    "RUSTUP_HOME",
+    "RUSTUP_TOOLCHAIN",
+    "GOPATH",
+    "GOROOT",
+    "GOCACHE",
+    "UV_CACHE_DIR",
+    "XDG_CACHE_HOME",
+];
+
+/// Progress of a code block run
+#[derive(Debug, Clone)]
+pub enum RunEvent {
+    /// The command was started (e.g. `cargo run`)
+    Started { command: String },
+    /// A line of output
+    Output { line: String, stderr: bool },
+    /// The run ended
+    Finished(RunOutcome),
+    /// The run couldn't be started
+    Failed(String),
+}
+
+/// How a run ended
+#[derive(Debug, Clone)]
+pub struct RunOutcome {
+    /// Exit code, `None` when killed or terminated by a signal
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    /// Whether output past `MAX_OUTPUT_LINES` was dropped
+    pub truncated: bool,
+    pub elapsed: Duration,
+}
+
+impl RunOutcome {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Languages that can be run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Shell,
+    Bash,
+}
+
+impl RunKind {
+    /// Detect from a Markdown code fence tag
+    pub fn from_fence(tag: &str) -> Option<Self> {
+        match tag.to_lowercase().as_str() {
+            "rust" | "rs" => Some(RunKind::Rust),
+            "python" | "py" | "python3" => Some(RunKind::Python),
+            "javascript" | "js" | "mjs" => Some(RunKind::JavaScript),
+            "typescript" | "ts" => Some(RunKind::TypeScript),
+            "go" | "golang" => Some(RunKind::Go),
+            "sh" | "shell" => Some(RunKind::Shell),
+            "bash" => Some(RunKind::Bash),
+            _ => None,
+        }
+    }
+
+    /// Write the scratch project into `dir` and build the command that runs it
+    fn prepare(&self, dir: &Path, code: &str) -> Result<(TokioCommand, String)> {
+        let write = |name: &str, source: &str| {
+            std::fs::write(dir.join(name), source)
+                .with_context(|| format!("Failed to write scratch {}", name))
+        };
+        let (mut command, label) = match self {
+            RunKind::Rust => {
+                write_rust_crate(dir, code)?;
+                if !dir.join("src").join("main.rs").exists() {
+                    return Err(anyhow!("Rust block has no `fn main` to run"));
+                }
+                let mut command = scratch_command("cargo");
+                command.args(["run", "--quiet"]).env(
+                    "CARGO_TARGET_DIR",
+                    std::env::temp_dir().join("arula-run-target"),
+                );
+                (command, "cargo run")
+            }
+            RunKind::Python => {
+                write("main.py", code)?;
+                let uv = on_path("uv");
+                let mut command = if uv {
+                    let mut command = scratch_command("uv");
+                    command.args(["run", "--no-project", "--quiet", "main.py"]);
+                    command
+                } else {
+                    let mut command = scratch_command("python3");
+                    command.arg("main.py");
+                    command
+                };
+                command.env("PYTHONUNBUFFERED", "1");
+                let label = if uv { "uv run" } else { "python3" };
+                (command, label)
+            }
+            RunKind::JavaScript => {
+                write("main.mjs", code)?;
+                let mut command = scratch_command("node");
+                command.arg("main.mjs");
+                (command, "node")
+            }
+            RunKind::TypeScript => {
+                write("main.ts", code)?;
+                let mut command = scratch_command("node");
+                command.args(["--experimental-strip-types", "--no-warnings", "main.ts"]);
+                (command, "node --experimental-strip-types")
+            }
+            RunKind::Go => {
+                if code.contains("package ") {
+                    write("main.go", code)?;
+                } else {
+                    write("main.go", &format!("package main\n\n{}", code))?;
+                }
+                let mut command = scratch_command("go");
+                command.args(["run", "main.go"]);
+                (command, "go run")
+            }
+            RunKind::Shell | RunKind::Bash => {
+                write("main.sh", code)?;
+                let shell = if *self == RunKind::Bash { "bash" } else { "sh" };
+                let mut command = scratch_command(shell);
+                command.arg("main.sh");
+                (command, shell)
+            }
+        };
+        command.current_dir(dir);
+        Ok((command, label.to_string()))
+    }
+}
+
+/// Run a code block in a scratch project, streaming events to `tx`
+///
+/// Always ends with `RunEvent::Finished` or `RunEvent::Failed`.
+pub async fn run_code_block(block: CodeBlock, tx: mpsc::UnboundedSender<RunEvent>) {
+    let event = match run(&block, &tx).await {
+        Ok(outcome) => RunEvent::Finished(outcome),
+        Err(e) => RunEvent::Failed(e.to_string()),
+    };
+    let _ = tx.send(event);
+}
+
+async fn run(block: &CodeBlock, tx: &mpsc::UnboundedSender<RunEvent>) -> Result<RunOutcome> {
+    let kind = RunKind::from_fence(&block.language).ok_or_else(|| {
+        anyhow!(
+            "Don't know how to run `{}` blocks",
+            if block.language.is_empty() {
+                "untagged"
+            } else {
+                &block.language
+            }
+        )
+    })?;
+    let dir = tempfile::tempdir().context("Failed to create scratch directory")?;
+    let (mut command, label) = kind.prepare(dir.path(), &block.code)?;
+
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let started = Instant::now();
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("Failed to start {}", label))?;
+    let _ = tx.send(RunEvent::Started { command: label });
+
+    let lines = Arc::new(AtomicUsize::new(0));
+    let readers = [
+        child
+            .stdout
+            .take()
+            .map(|out| tokio::spawn(forward_lines(out, false, tx.clone(), lines.clone()))),
+        child
+            .stderr
+            .take()
+            .map(|err| tokio::spawn(forward_lines(err, true, tx.clone(), lines.clone()))),
+    ];
+
+    let (exit_code, timed_out) = match tokio::time::timeout(RUN_TIMEOUT, child.wait()).await {
+        Ok(status) => (status?.code(), false),
+        Err(_) => {
+            let _ = child.kill().await;
+            (None, true)
+        }
+    };
+    // Background processes may keep the pipes open; don't wait on them long
+    for reader in readers.into_iter().flatten() {
+        let _ = tokio::time::timeout(Duration::from_secs(1), reader).await;
+    }
+
+    Ok(RunOutcome {
+        exit_code,
+        timed_out,
+        truncated: lines.load(Ordering::Relaxed) > MAX_OUTPUT_LINES,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Forward output lines until EOF, dropping them past `MAX_OUTPUT_LINES`
+async fn forward_lines<R: AsyncRead + Unpin>(
+    reader: R,
+    stderr: bool,
+    tx: mpsc::UnboundedSender<RunEvent>,
+    lines: Arc<AtomicUsize>,
+) {
+    let mut reader = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = reader.next_line().await {
+        if lines.fetch_add(1, Ordering::Relaxed) < MAX_OUTPUT_LINES {
+            let _ = tx.send(RunEvent::Output { line, stderr });
+        }
+    }
+}
+
+/// A command with the environment cleared down to `PASSED_ENV`
+fn scratch_command(program: &str) -> TokioCommand {
+    let mut command = TokioCommand::new(program);
+    command.env_clear();
+    for name in PASSED_ENV {
+        if let Ok(value) = std::env::var(name) {
+            command.env(name, value);
+        }
+    }
+    command
+}
+
+/// Whether a program is on PATH
+fn on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path)
+        .any(|dir| dir.join(program).is_file() || dir.join(format!("{}.exe", program)).is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn collect(language: &str, code: &str) -> Vec<RunEvent> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let block = CodeBlock {
+            language: language.to_string(),
+            code: code.to_string(),
+            line: 1,
+        };
+        run_code_block(block, tx).await;
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_run_kind_from_fence() {
+        assert_eq!(RunKind::from_fence("Python"), Some(RunKind::Python));
+        assert_eq!(RunKind::from_fence("ts"), Some(RunKind::TypeScript));
+        assert_eq!(RunKind::from_fence("diff"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_shell_block() {
+        unsafe { std::env::set_var("ARULA_TEST_SECRET", "hidden") };
+        let events = collect(
+            "sh",
+            "echo hello\necho \"${ARULA_TEST_SECRET:-unset}\"\nexit 3",
+        )
+        .await;
+
+        let output: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                RunEvent::Output { line, .. } => Some(line.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(output, ["hello", "unset"]);
+        match events.last() {
+            Some(RunEvent::Finished(outcome)) => {
+                assert_eq!(outcome.exit_code, Some(3));
+                assert!(!outcome.timed_out);
+            }
+            other => panic!("unexpected final event: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_language() {
+        let events = collect("", "hello").await;
+        assert!(matches!(events.as_slice(), [RunEvent::Failed(_)]));
+    }
+}
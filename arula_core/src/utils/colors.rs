@@ -1,6 +1,7 @@
 //! Color constants and styling utilities for ARULA CLI
 //! Defines the consistent color palette used throughout the application
 
+use crate::utils::themes::{Rgb, ThemeColors, active_theme};
 use console::Style;
 
 /// Primary color - Golden yellow (#E8C547)
@@ -24,83 +25,93 @@ pub const MISC_HEX: &str = "#CDD1C4";
 pub const MISC_ANSI: u8 = 251; // ANSI 256 color approximation
 
 /// Color theme struct for consistent styling
+///
+/// Colors come from the active theme (see [`crate::utils::themes`]); the
+/// `*_ANSI` constants above are the 256-color fallbacks of the original
+/// palette.
 pub struct ColorTheme;
 
+/// Foreground color from the active theme
+fn fg(style: Style, pick: fn(&ThemeColors) -> Rgb) -> Style {
+    let Rgb(r, g, b) = pick(&active_theme());
+    style.true_color(r, g, b)
+}
+
+/// Background color from the active theme
+fn bg(style: Style, pick: fn(&ThemeColors) -> Rgb) -> Style {
+    let Rgb(r, g, b) = pick(&active_theme());
+    style.on_true_color(r, g, b)
+}
+
 impl ColorTheme {
-    /// Primary golden yellow style
+    /// Primary (accent) style
     pub fn primary() -> Style {
-        Style::new().color256(PRIMARY_ANSI).bold()
+        fg(Style::new(), |c| c.accent).bold()
     }
 
-    /// Secondary dark gray style
+    /// Secondary (surface) style
     pub fn secondary() -> Style {
-        Style::new().color256(SECONDARY_ANSI)
+        fg(Style::new(), |c| c.surface_raised)
     }
 
-    /// Background medium gray style
+    /// Background (border) style
     pub fn background() -> Style {
-        Style::new().color256(BACKGROUND_ANSI)
+        fg(Style::new(), |c| c.border)
     }
 
-    /// AI highlight steel blue style
+    /// AI highlight style
     pub fn ai_highlight() -> Style {
-        Style::new().color256(AI_HIGHLIGHT_ANSI).bold()
+        fg(Style::new(), |c| c.info).bold()
     }
 
-    /// Misc light gray style
+    /// Misc (muted) style
     pub fn misc() -> Style {
-        Style::new().color256(MISC_ANSI)
+        fg(Style::new(), |c| c.muted)
     }
 
     /// Primary style with background
     pub fn primary_on_background() -> Style {
-        Style::new()
-            .color256(PRIMARY_ANSI)
-            .on_color256(BACKGROUND_ANSI)
-            .bold()
+        bg(fg(Style::new(), |c| c.accent), |c| c.border).bold()
     }
 
     /// Misc style with background for inline code
     pub fn inline_code() -> Style {
-        Style::new().color256(MISC_ANSI).on_color256(SECONDARY_ANSI)
+        bg(fg(Style::new(), |c| c.text), |c| c.surface_raised)
     }
 
     /// AI message style
     pub fn ai_message() -> Style {
-        Style::new().color256(AI_HIGHLIGHT_ANSI).bold()
+        fg(Style::new(), |c| c.info).bold()
     }
 
-    /// Success style (green variant)
+    /// Success style
     pub fn success() -> Style {
-        Style::new().color256(46).bold() // Bright green
+        fg(Style::new(), |c| c.success).bold()
     }
 
-    /// Error style (red variant)
+    /// Error style
     pub fn error() -> Style {
-        Style::new().color256(196).bold() // Bright red
+        fg(Style::new(), |c| c.danger).bold()
     }
 
-    /// Warning style (orange variant)
+    /// Warning style
     pub fn warning() -> Style {
-        Style::new().color256(208).bold() // Orange
+        fg(Style::new(), |c| c.warning).bold()
     }
 
     /// Dim/faded style
     pub fn dim() -> Style {
-        Style::new().color256(244).dim() // Very light gray
+        fg(Style::new(), |c| c.muted).dim()
     }
 
     /// Border/separator style
     pub fn border() -> Style {
-        Style::new().color256(AI_HIGHLIGHT_ANSI).dim()
+        fg(Style::new(), |c| c.border)
     }
 
     /// Cursor/selection style
     pub fn selection() -> Style {
-        Style::new()
-            .color256(PRIMARY_ANSI)
-            .on_color256(SECONDARY_ANSI)
-            .bold()
+        bg(fg(Style::new(), |c| c.accent), |c| c.surface_raised).bold()
     }
 }
 
@@ -122,27 +133,27 @@ pub trait ColorExt {
 
 impl ColorExt for Style {
     fn primary(self) -> Style {
-        self.color256(PRIMARY_ANSI).bold()
+        fg(self, |c| c.accent).bold()
     }
 
     fn secondary(self) -> Style {
-        self.color256(SECONDARY_ANSI)
+        fg(self, |c| c.surface_raised)
     }
 
     fn background(self) -> Style {
-        self.color256(BACKGROUND_ANSI)
+        fg(self, |c| c.border)
     }
 
     fn ai_highlight(self) -> Style {
-        self.color256(AI_HIGHLIGHT_ANSI).bold()
+        fg(self, |c| c.info).bold()
     }
 
     fn misc(self) -> Style {
-        self.color256(MISC_ANSI)
+        fg(self, |c| c.muted)
     }
 
     fn inline_code_style(self) -> Style {
-        self.color256(MISC_ANSI).on_color256(SECONDARY_ANSI)
+        bg(fg(self, |c| c.text), |c| c.surface_raised)
     }
 }
 
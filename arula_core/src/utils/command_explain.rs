@@ -0,0 +1,552 @@
+//! Plain-English explanations of shell commands
+//!
+//! Before a shell command from the model runs, the chat shows what it does
+//! ("Delete build/ and everything in it, without asking") and warns about
+//! risky parts like `sudo`, force pushes or piping a download into a shell.
+//! The explanation comes from rules for common commands, so it is instant
+//! and works offline; unknown programs are named but not described.
+
+/// What a command does
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Explanation {
+    /// One sentence per step of the command line
+    pub steps: Vec<String>,
+    /// Things to look at before approving
+    pub warnings: Vec<String>,
+}
+
+impl Explanation {
+    /// The steps as one line
+    pub fn summary(&self) -> String {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                if i == 0 {
+                    step.clone()
+                } else {
+                    lowercase_first(step)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", then ")
+    }
+}
+
+/// How a step connects to the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Joint {
+    First,
+    Then,
+    IfOk,
+    IfFailed,
+    Pipe,
+}
+
+/// Explain a shell command line
+pub fn explain(command: &str) -> Explanation {
+    let mut explanation = Explanation::default();
+    let segments = split_segments(command);
+    for (index, (joint, segment)) in segments.iter().enumerate() {
+        let words = split_words(segment);
+        let Some(mut step) = explain_segment(&words, &mut explanation.warnings) else {
+            continue;
+        };
+        match joint {
+            Joint::IfOk if index > 0 => {
+                step = format!("if that worked, {}", lowercase_first(&step))
+            }
+            Joint::IfFailed => step = format!("if that failed, {}", lowercase_first(&step)),
+            Joint::Pipe => step = format!("pass the output to: {}", lowercase_first(&step)),
+            _ => {}
+        }
+        explanation.steps.push(step);
+
+        let program = words.iter().find(|w| !w.contains('=')).map(String::as_str);
+        if *joint == Joint::Pipe
+            && matches!(program, Some("sh" | "bash" | "zsh" | "python" | "python3"))
+            && index > 0
+            && split_words(&segments[index - 1].1)
+                .first()
+                .is_some_and(|w| matches!(w.as_str(), "curl" | "wget"))
+        {
+            explanation
+                .warnings
+                .push("runs a script downloaded from the internet".to_string());
+        }
+    }
+    explanation.warnings.dedup();
+    explanation
+}
+
+/// Split on `;`, `&&`, `||` and `|` outside quotes
+fn split_segments(command: &str) -> Vec<(Joint, String)> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut joint = Joint::First;
+    let mut quote = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            current.push(c);
+            continue;
+        }
+        let next = match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                current.push(c);
+                continue;
+            }
+            ';' | '\n' => Joint::Then,
+            '&' if chars.peek() == Some(&'&') => {
+                chars.next();
+                Joint::IfOk
+            }
+            '|' if chars.peek() == Some(&'|') => {
+                chars.next();
+                Joint::IfFailed
+            }
+            '|' => Joint::Pipe,
+            _ => {
+                current.push(c);
+                continue;
+            }
+        };
+        if !current.trim().is_empty() {
+            segments.push((joint, current.trim().to_string()));
+        }
+        current.clear();
+        joint = next;
+    }
+    if !current.trim().is_empty() {
+        segments.push((joint, current.trim().to_string()));
+    }
+    segments
+}
+
+/// Split a simple command into words, dropping quotes
+fn split_words(segment: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+    for c in segment.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Flags like `-rf` and `--force`, split into single letters and long names
+fn has_flag(args: &[&str], short: char, long: &str) -> bool {
+    args.iter().any(|arg| {
+        arg.strip_prefix("--").map_or_else(
+            || arg.starts_with('-') && arg[1..].contains(short),
+            |name| name == long,
+        )
+    })
+}
+
+/// Arguments that aren't flags
+fn operands<'a>(args: &[&'a str]) -> Vec<&'a str> {
+    args.iter()
+        .copied()
+        .filter(|a| !a.starts_with('-'))
+        .collect()
+}
+
+fn list(items: &[&str]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => format!("`{}`", one),
+        [init @ .., last] => format!(
+            "{} and `{}`",
+            init.iter()
+                .map(|i| format!("`{}`", i))
+                .collect::<Vec<_>>()
+                .join(", "),
+            last
+        ),
+    }
+}
+
+/// Explain one simple command, collecting warnings
+fn explain_segment(words: &[String], warnings: &mut Vec<String>) -> Option<String> {
+    let mut words: Vec<&str> = words.iter().map(String::as_str).collect();
+    // Environment assignments and `sudo` prefix the real command
+    while words
+        .first()
+        .is_some_and(|w| w.contains('=') && !w.starts_with('-'))
+    {
+        words.remove(0);
+    }
+    let mut prefix = "";
+    if words.first() == Some(&"sudo") {
+        warnings.push("runs with administrator rights (sudo)".to_string());
+        prefix = "As administrator, ";
+        words.remove(0);
+        while words.first().is_some_and(|w| w.starts_with('-')) {
+            words.remove(0);
+        }
+    }
+
+    // Redirections
+    let mut redirect = None;
+    let mut rest = Vec::new();
+    let mut iter = words.into_iter();
+    while let Some(word) = iter.next() {
+        match word {
+            ">" | "1>" => {
+                redirect = iter
+                    .next()
+                    .map(|f| format!(" and overwrite `{}` with the output", f))
+            }
+            ">>" => {
+                redirect = iter
+                    .next()
+                    .map(|f| format!(" and append the output to `{}`", f))
+            }
+            "2>&1" | "2>/dev/null" | "&>/dev/null" => {}
+            _ if word.starts_with('>') && word.len() > 1 && !word.starts_with(">>") => {
+                redirect = Some(format!(" and overwrite `{}` with the output", &word[1..]));
+            }
+            _ => rest.push(word),
+        }
+    }
+    let (&program, args) = rest.split_first()?;
+    let mut description = describe(program, args, warnings);
+    if !prefix.is_empty() {
+        description = lowercase_first(&description);
+    }
+    Some(format!(
+        "{}{}{}",
+        prefix,
+        description,
+        redirect.unwrap_or_default()
+    ))
+}
+
+fn describe(program: &str, args: &[&str], warnings: &mut Vec<String>) -> String {
+    let files = operands(args);
+    match program {
+        "cd" => format!(
+            "Change to the `{}` directory",
+            files.first().unwrap_or(&"~")
+        ),
+        "ls" | "dir" => match files.as_slice() {
+            [] => "List the files in the current directory".to_string(),
+            paths => format!("List the files in {}", list(paths)),
+        },
+        "pwd" => "Show the current directory".to_string(),
+        "cat" | "less" | "more" | "bat" => format!("Show the contents of {}", list(&files)),
+        "head" | "tail" => {
+            // Skip line counts like `-n 20`
+            let files: Vec<&str> = files
+                .into_iter()
+                .filter(|f| f.parse::<u64>().is_err())
+                .collect();
+            let part = if program == "head" { "first" } else { "last" };
+            match files.as_slice() {
+                [] => format!("Show the {} lines", part),
+                paths => format!("Show the {} lines of {}", part, list(paths)),
+            }
+        }
+        "echo" | "printf" => "Print text".to_string(),
+        "grep" | "rg" | "ag" => match files.split_first() {
+            Some((pattern, [])) => format!("Search for `{}`", pattern),
+            Some((pattern, paths)) => format!("Search {} for `{}`", list(paths), pattern),
+            None => "Search text".to_string(),
+        },
+        "find" | "fd" => format!(
+            "Find files{}",
+            files
+                .first()
+                .map(|p| format!(" under `{}`", p))
+                .unwrap_or_default()
+        ),
+        "wc" => format!("Count lines, words and bytes in {}", list(&files)),
+        "mkdir" => format!("Create the directory {}", list(&files)),
+        "touch" => format!("Create or update the timestamp of {}", list(&files)),
+        "cp" => match files.split_last() {
+            Some((dest, sources)) if !sources.is_empty() => {
+                format!("Copy {} to `{}`", list(sources), dest)
+            }
+            _ => "Copy files".to_string(),
+        },
+        "mv" => match files.split_last() {
+            Some((dest, sources)) if !sources.is_empty() => {
+                format!("Move or rename {} to `{}`", list(sources), dest)
+            }
+            _ => "Move files".to_string(),
+        },
+        "rm" => {
+            let recursive = has_flag(args, 'r', "recursive") || has_flag(args, 'R', "recursive");
+            let force = has_flag(args, 'f', "force");
+            if recursive && force {
+                warnings.push("deletes files permanently without asking".to_string());
+            }
+            if files
+                .iter()
+                .any(|f| matches!(*f, "/" | "~" | "*" | "." | ".." | "/*" | "~/"))
+            {
+                warnings.push("deletes a very broad path".to_string());
+            }
+            format!(
+                "Delete {}{}{}",
+                list(&files),
+                if recursive {
+                    " and everything in it"
+                } else {
+                    ""
+                },
+                if force { ", without asking" } else { "" }
+            )
+        }
+        "chmod" => {
+            if args.iter().any(|a| a.contains("777")) {
+                warnings.push("makes files writable by every user".to_string());
+            }
+            format!(
+                "Change the permissions of {}",
+                list(files.get(1..).unwrap_or_default())
+            )
+        }
+        "chown" => format!(
+            "Change the owner of {}",
+            list(files.get(1..).unwrap_or_default())
+        ),
+        "curl" | "wget" => format!(
+            "Download {}",
+            files
+                .iter()
+                .find(|f| f.contains("://"))
+                .map(|url| format!("`{}`", url))
+                .unwrap_or_else(|| "from the internet".to_string())
+        ),
+        "tar" => {
+            if has_flag(args, 'x', "extract") {
+                "Extract an archive".to_string()
+            } else {
+                "Create an archive".to_string()
+            }
+        }
+        "kill" | "pkill" | "killall" => format!("Stop the process {}", list(&files)),
+        "dd" | "mkfs" | "fdisk" | "shred" => {
+            warnings.push(format!("`{}` can destroy data on a disk", program));
+            format!("Run `{}` to write raw data", program)
+        }
+        "git" => describe_git(args, warnings),
+        "cargo" => describe_tool(
+            "Cargo",
+            args,
+            &[
+                ("build", "Build the Rust project"),
+                ("run", "Build and run the Rust project"),
+                ("test", "Run the Rust tests"),
+                ("check", "Check the Rust project for errors"),
+                ("clippy", "Lint the Rust project"),
+                ("fmt", "Format the Rust code"),
+                ("add", "Add a dependency to the Rust project"),
+                ("install", "Install a Rust program"),
+                ("clean", "Delete the Rust build output"),
+            ],
+        ),
+        "npm" | "pnpm" | "yarn" | "bun" => describe_tool(
+            program,
+            args,
+            &[
+                ("install", "Install the JavaScript dependencies"),
+                ("i", "Install the JavaScript dependencies"),
+                (
+                    "ci",
+                    "Install the JavaScript dependencies from the lock file",
+                ),
+                ("run", "Run a package.json script"),
+                ("test", "Run the JavaScript tests"),
+                ("start", "Start the app"),
+                ("build", "Build the JavaScript project"),
+            ],
+        ),
+        "pip" | "pip3" => describe_tool(
+            "pip",
+            args,
+            &[
+                ("install", "Install Python packages"),
+                ("uninstall", "Remove Python packages"),
+                ("list", "List installed Python packages"),
+            ],
+        ),
+        "docker" | "podman" => describe_tool(
+            program,
+            args,
+            &[
+                ("build", "Build a container image"),
+                ("run", "Start a container"),
+                ("ps", "List running containers"),
+                ("stop", "Stop a container"),
+                ("rm", "Delete a container"),
+                ("pull", "Download a container image"),
+                ("compose", "Manage a multi-container app"),
+            ],
+        ),
+        "python" | "python3" | "node" | "ruby" | "perl" => match files.first() {
+            Some(script) => format!("Run the script `{}` with {}", script, program),
+            None => format!("Run {} code", program),
+        },
+        "sh" | "bash" | "zsh" => match files.first() {
+            Some(script) => format!("Run the shell script `{}`", script),
+            None => "Run shell commands".to_string(),
+        },
+        "make" => format!(
+            "Run the Makefile target {}",
+            files
+                .first()
+                .map(|t| format!("`{}`", t))
+                .unwrap_or_else(|| "(default)".to_string())
+        ),
+        "sort" => "Sort lines".to_string(),
+        "uniq" => "Drop repeated lines".to_string(),
+        "sed" | "awk" => format!("Transform text with {}", program),
+        "xargs" => "Run a command for each input line".to_string(),
+        "which" | "type" | "command" => format!("Find where {} is installed", list(&files)),
+        _ => format!("Run `{}`", program),
+    }
+}
+
+/// A program with subcommands from `table`
+fn describe_tool(name: &str, args: &[&str], table: &[(&str, &str)]) -> String {
+    let sub = args.iter().find(|a| !a.starts_with('-'));
+    match sub.and_then(|sub| table.iter().find(|(s, _)| s == sub)) {
+        Some((_, description)) => description.to_string(),
+        None => match sub {
+            Some(sub) => format!("Run `{} {}`", name, sub),
+            None => format!("Run {}", name),
+        },
+    }
+}
+
+fn describe_git(args: &[&str], warnings: &mut Vec<String>) -> String {
+    let sub = args.iter().position(|a| !a.starts_with('-'));
+    let (sub, rest) = match sub {
+        Some(i) => (args[i], &args[i + 1..]),
+        None => return "Run git".to_string(),
+    };
+    let paths = operands(rest);
+    match sub {
+        "status" => "Show which files changed in the repository".to_string(),
+        "diff" => "Show the uncommitted changes".to_string(),
+        "log" => "Show the commit history".to_string(),
+        "show" => "Show a commit".to_string(),
+        "add" => format!("Stage {} for the next commit", list(&paths)),
+        "commit" => "Record the staged changes as a commit".to_string(),
+        "checkout" | "switch" => format!(
+            "Switch to {}",
+            paths
+                .first()
+                .map(|b| format!("`{}`", b))
+                .unwrap_or_else(|| "another branch".to_string())
+        ),
+        "branch" => "List or manage branches".to_string(),
+        "pull" => "Download and merge new commits from the remote".to_string(),
+        "fetch" => "Download new commits from the remote".to_string(),
+        "push" => {
+            if has_flag(rest, 'f', "force") || rest.contains(&"--force-with-lease") {
+                warnings
+                    .push("force-pushes, which can overwrite commits on the remote".to_string());
+            }
+            "Upload commits to the remote".to_string()
+        }
+        "reset" => {
+            if rest.contains(&"--hard") {
+                warnings.push("discards uncommitted changes for good".to_string());
+            }
+            "Move the current branch to another commit".to_string()
+        }
+        "clean" => {
+            if has_flag(rest, 'f', "force") {
+                warnings.push("deletes untracked files for good".to_string());
+            }
+            "Delete files git doesn't track".to_string()
+        }
+        "stash" => "Set the uncommitted changes aside".to_string(),
+        "merge" => format!("Merge {} into the current branch", list(&paths)),
+        "rebase" => "Replay the current branch's commits on another base".to_string(),
+        "clone" => format!("Copy the repository {}", list(&paths)),
+        other => format!("Run `git {}`", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_common_commands() {
+        let e = explain("cd app && cargo test 2>&1 | tail -n 20");
+        assert_eq!(
+            e.steps,
+            vec![
+                "Change to the `app` directory",
+                "if that worked, run the Rust tests",
+                "pass the output to: show the last lines",
+            ]
+        );
+        assert!(e.warnings.is_empty());
+
+        let e = explain("git add src/main.rs README.md; git commit -m 'fix: a; b'");
+        assert_eq!(
+            e.summary(),
+            "Stage `src/main.rs` and `README.md` for the next commit, then \
+             record the staged changes as a commit"
+        );
+        assert_eq!(
+            explain("grep -rn TODO src > todos.txt").steps,
+            vec!["Search `src` for `TODO` and overwrite `todos.txt` with the output"]
+        );
+    }
+
+    #[test]
+    fn test_warnings() {
+        let e = explain("sudo rm -rf /");
+        assert_eq!(
+            e.steps,
+            vec!["As administrator, delete `/` and everything in it, without asking"]
+        );
+        assert_eq!(e.warnings.len(), 3);
+
+        let e = explain("curl -fsSL https://example.com/install.sh | sh");
+        assert!(e.warnings.iter().any(|w| w.contains("downloaded")));
+        assert!(!explain("git push --force origin main").warnings.is_empty());
+        assert_eq!(explain("frobnicate --all").steps, vec!["Run `frobnicate`"]);
+    }
+}
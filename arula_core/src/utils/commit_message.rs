@@ -0,0 +1,146 @@
+//! AI-drafted conventional commit messages
+//!
+//! Stages changes through `GitOps`, sends the staged diff to the model and
+//! cleans the reply into a conventional-commit message
+//! (`type(scope): subject` followed by an optional body).
+
+use crate::api::completion::complete;
+use crate::utils::config::Config;
+use crate::utils::git_ops::{GitOps, truncate_diff};
+use anyhow::{Result, bail};
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Maximum staged diff size sent to the model
+const MAX_COMMIT_DIFF_CHARS: usize = 16_000;
+
+/// Commit types accepted by the conventional commits convention
+pub const CONVENTIONAL_TYPES: &[&str] = &[
+    "feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert",
+];
+
+const COMMIT_SYSTEM_PROMPT: &str = "You write git commit messages following the Conventional \
+Commits specification. Reply with the commit message only: no code fences, no quotes, no commentary.";
+
+static CONVENTIONAL_SUBJECT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"^(?:{})(?:\([\w\-./ ]+\))?!?: \S.*$",
+        CONVENTIONAL_TYPES.join("|")
+    ))
+    .unwrap()
+});
+
+/// Staged files and the drafted message for a pending commit
+#[derive(Debug, Clone)]
+pub struct CommitDraft {
+    /// Paths staged for the commit
+    pub files: Vec<String>,
+    /// Drafted commit message
+    pub message: String,
+}
+
+/// Stage changes for a commit
+///
+/// Stages `files` when given. Otherwise uses what is already staged, falling
+/// back to all tracked modifications when the index is empty. Returns the
+/// staged paths.
+pub async fn stage_for_commit(git: &GitOps, files: &[String]) -> Result<Vec<String>> {
+    if !git.is_git_repo().await {
+        bail!(
+            "Not a git repository: {}",
+            git.working_directory().display()
+        );
+    }
+    if !files.is_empty() {
+        git.stage(files).await?;
+    } else if git.staged_files().await?.is_empty() {
+        git.stage_tracked().await?;
+    }
+
+    let staged = git.staged_files().await?;
+    if staged.is_empty() {
+        bail!("Nothing to commit: no staged or modified tracked files");
+    }
+    Ok(staged)
+}
+
+/// Stage changes and ask the model for a conventional commit message
+pub async fn prepare_commit(config: &Config, dir: &Path, files: &[String]) -> Result<CommitDraft> {
+    let git = GitOps::new(dir);
+    let files = stage_for_commit(&git, files).await?;
+    let diff = git.staged_diff().await?;
+    let message = draft_commit_message(config, &diff).await?;
+    Ok(CommitDraft { files, message })
+}
+
+/// Ask the model to draft a conventional commit message for a diff
+pub async fn draft_commit_message(config: &Config, diff: &str) -> Result<String> {
+    let prompt = format!(
+        "Write a commit message for this staged diff.\n\n```diff\n{}\n```\n\n\
+         Rules:\n\
+         - First line: `<type>(<optional scope>): <subject>`, where type is one of {}\n\
+         - Subject in the imperative mood, lowercase, no trailing period, at most 72 characters\n\
+         - If the change needs explaining, add a blank line and a short body wrapped at 72 characters",
+        truncate_diff(diff, MAX_COMMIT_DIFF_CHARS),
+        CONVENTIONAL_TYPES.join(", ")
+    );
+    let reply = complete(config, COMMIT_SYSTEM_PROMPT, &prompt).await?;
+    let message = clean_commit_message(&reply);
+    if message.is_empty() {
+        bail!("The model did not return a commit message");
+    }
+    Ok(message)
+}
+
+/// Strip code fences, surrounding quotes and stray whitespace from a model reply
+pub fn clean_commit_message(raw: &str) -> String {
+    let lines: Vec<&str> = raw
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+    let message = lines.join("\n");
+    let message = message.trim().trim_matches(|c| c == '"' || c == '`').trim();
+    message
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether the first line of a message follows the conventional commits format
+pub fn is_conventional_commit(message: &str) -> bool {
+    message
+        .lines()
+        .next()
+        .is_some_and(|subject| CONVENTIONAL_SUBJECT.is_match(subject.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_commit_message() {
+        assert_eq!(
+            clean_commit_message("```\nfeat(cli): add /commit\n\nDrafts messages.  \n```\n"),
+            "feat(cli): add /commit\n\nDrafts messages."
+        );
+        assert_eq!(
+            clean_commit_message("\"fix: handle empty diff\""),
+            "fix: handle empty diff"
+        );
+    }
+
+    #[test]
+    fn test_is_conventional_commit() {
+        assert!(is_conventional_commit("feat: add parser"));
+        assert!(is_conventional_commit(
+            "fix(api)!: drop v1 endpoints\n\nBody"
+        ));
+        assert!(!is_conventional_commit("Add parser"));
+        assert!(!is_conventional_commit("feature: add parser"));
+        assert!(!is_conventional_commit("feat:missing space"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Conversation compaction
+//!
+//! `/compact` replaces the older part of a conversation with a summary
+//! written by the model, so a long conversation fits the context window
+//! again. The most recent turns are kept word for word.
+//!
+//! The agent loop does the same on its own when a provider rejects a
+//! request as too long: everything before the last user message is
+//! summarized and the request is sent once more, so every frontend
+//! recovers from a context overflow.
+
+use crate::api::api::ChatMessage as ApiMessage;
+use crate::api::completion::complete;
+use crate::api::provider_error::ProviderError;
+use crate::utils::chat::{ChatMessage, MessageType};
+use crate::utils::config::Config;
+use crate::utils::error_help::is_context_overflow_text;
+use anyhow::Result;
+use arula_llm::Provider;
+use std::future::Future;
+
+/// User turns `/compact` keeps as they are
+pub const KEEP_TURNS: usize = 2;
+
+/// User turns kept when compacting after a context overflow: only the
+/// prompt that failed
+pub const RECOVERY_KEEP_TURNS: usize = 1;
+
+/// Longest transcript sent for summarizing; older text is dropped first
+const MAX_TRANSCRIPT_CHARS: usize = 60_000;
+
+/// Longest single message in the transcript
+const MAX_MESSAGE_CHARS: usize = 4_000;
+
+const SUMMARY_PROMPT: &str = "You compress conversations between a user and a coding \
+assistant. Summarize the transcript so the assistant can continue the work: the user's \
+goals, decisions made, files and commands involved, results of tool calls, and open \
+questions. Use terse bullet points. Reply with the summary only.";
+
+/// Prefix of the message that replaces the compacted messages
+pub const SUMMARY_HEADER: &str = "Summary of the earlier conversation:";
+
+/// What a compaction did
+#[derive(Debug, Clone)]
+pub struct Compaction {
+    /// Messages replaced by the summary
+    pub replaced: usize,
+    /// Characters in the replaced messages
+    pub chars_before: usize,
+    /// Characters in the summary
+    pub chars_after: usize,
+    /// The summary that replaced them
+    pub summary: String,
+}
+
+/// Index of the first message to keep when keeping the last `keep_turns`
+/// user turns; `None` when there is nothing before them
+pub fn split_point(messages: &[ChatMessage], keep_turns: usize) -> Option<usize> {
+    let keep_turns = keep_turns.max(1);
+    let split = messages
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, m)| m.message_type == MessageType::User)
+        .nth(keep_turns - 1)
+        .map(|(i, _)| i)?;
+    (split > 0).then_some(split)
+}
+
+/// The messages as a plain transcript for the summarizer
+pub fn transcript(messages: &[ChatMessage]) -> String {
+    let mut entries: Vec<String> = messages
+        .iter()
+        .filter(|m| m.message_type != MessageType::ToolCall)
+        .map(|m| {
+            let speaker = match m.message_type {
+                MessageType::User => "User",
+                MessageType::Arula => "Assistant",
+                MessageType::ToolResult => "Tool result",
+                _ => "Note",
+            };
+            let content = match m.content.char_indices().nth(MAX_MESSAGE_CHARS) {
+                Some((end, _)) => format!("{} [...]", &m.content[..end]),
+                None => m.content.clone(),
+            };
+            format!("{}: {}", speaker, content)
+        })
+        .collect();
+
+    let mut total: usize = entries.iter().map(String::len).sum();
+    while total > MAX_TRANSCRIPT_CHARS && entries.len() > 1 {
+        total -= entries.remove(0).len();
+    }
+    entries.join("\n\n")
+}
+
+/// Replace all messages before the last `keep_turns` user turns with a
+/// summary from the model
+pub async fn compact(
+    config: &Config,
+    messages: &mut Vec<ChatMessage>,
+    keep_turns: usize,
+) -> Result<Option<Compaction>> {
+    let Some(split) = split_point(messages, keep_turns) else {
+        return Ok(None);
+    };
+    let summary = complete(config, SUMMARY_PROMPT, &transcript(&messages[..split])).await?;
+    Ok(Some(replace_with_summary(messages, split, summary)))
+}
+
+/// Keep a summary made by the agent loop after a context overflow, so later
+/// turns fit too; `None` when there is nothing before the last
+/// `keep_turns` user turns
+pub fn apply_summary(
+    messages: &mut Vec<ChatMessage>,
+    keep_turns: usize,
+    summary: String,
+) -> Option<Compaction> {
+    let split = split_point(messages, keep_turns)?;
+    Some(replace_with_summary(messages, split, summary))
+}
+
+fn replace_with_summary(messages: &mut Vec<ChatMessage>, split: usize, summary: String) -> Compaction {
+    let chars_before = messages[..split].iter().map(|m| m.content.len()).sum();
+    messages.splice(
+        ..split,
+        [ChatMessage::new(
+            MessageType::System,
+            format!("{}\n{}", SUMMARY_HEADER, summary),
+        )],
+    );
+    Compaction {
+        replaced: split,
+        chars_before,
+        chars_after: summary.len(),
+        summary,
+    }
+}
+
+/// Whether a failed request was rejected for not fitting the context window
+pub fn is_context_overflow(error: &anyhow::Error) -> bool {
+    match error.downcast_ref::<ProviderError>() {
+        Some(e) => is_context_overflow_text(&format!("{}\n{}", e.code, e.body)),
+        None => is_context_overflow_text(&error.to_string()),
+    }
+}
+
+/// Summarize a request's messages after the provider rejected it as too
+/// long, so it can be sent again
+///
+/// Returns `None` when `error` isn't a context overflow or there is nothing
+/// before the last user message to summarize; the error stands then.
+pub async fn recover_overflow(
+    provider: &dyn Provider,
+    error: &anyhow::Error,
+    messages: &mut Vec<ApiMessage>,
+) -> Option<Compaction> {
+    if !is_context_overflow(error) {
+        return None;
+    }
+    let summarize = |transcript: String| async move {
+        provider
+            .ask(&format!("{}\n\n{}", SUMMARY_PROMPT, transcript))
+            .await
+    };
+    match compact_request(messages, summarize).await {
+        Ok(compaction) => compaction,
+        Err(e) => {
+            crate::utils::logger::warn(&format!("Context overflow compaction failed: {}", e));
+            None
+        }
+    }
+}
+
+/// Replace the messages between the system prompt and the last user message
+/// with a summary appended to the system prompt, which keeps the role order
+/// valid for every provider
+async fn compact_request<F, Fut>(
+    messages: &mut Vec<ApiMessage>,
+    summarize: F,
+) -> Result<Option<Compaction>>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let has_system = messages.first().is_some_and(|m| m.role == "system");
+    let start = usize::from(has_system);
+    let Some(end) = messages.iter().rposition(|m| m.role == "user") else {
+        return Ok(None);
+    };
+    if end <= start {
+        return Ok(None);
+    }
+
+    let older: Vec<ChatMessage> = messages[start..end]
+        .iter()
+        .filter_map(|m| {
+            let content = m.content.clone().filter(|c| !c.is_empty())?;
+            let kind = match m.role.as_str() {
+                "user" => MessageType::User,
+                "assistant" => MessageType::Arula,
+                "tool" => MessageType::ToolResult,
+                _ => MessageType::System,
+            };
+            Some(ChatMessage::new(kind, content))
+        })
+        .collect();
+    let summary = summarize(transcript(&older)).await?;
+
+    let chars_before = older.iter().map(|m| m.content.len()).sum();
+    messages.drain(start..end);
+    let note = format!("{}\n{}", SUMMARY_HEADER, summary);
+    if has_system {
+        let prompt = messages[0].content.get_or_insert_with(String::new);
+        prompt.push_str("\n\n");
+        prompt.push_str(&note);
+    } else {
+        messages.insert(
+            0,
+            ApiMessage {
+                role: "system".to_string(),
+                content: Some(note),
+                tool_calls: None,
+                tool_call_id: None,
+                tool_name: None,
+            },
+        );
+    }
+    Ok(Some(Compaction {
+        replaced: end - start,
+        chars_before,
+        chars_after: summary.len(),
+        summary,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::new(MessageType::User, "first".to_string()),
+            ChatMessage::new(MessageType::Arula, "one".to_string()),
+            ChatMessage::new(MessageType::User, "second".to_string()),
+            ChatMessage::new(MessageType::ToolCall, "🔧 Tool call".to_string()),
+            ChatMessage::new(MessageType::ToolResult, "ok".to_string()),
+            ChatMessage::new(MessageType::User, "third".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_split_point() {
+        let messages = conversation();
+        assert_eq!(split_point(&messages, 1), Some(5));
+        assert_eq!(split_point(&messages, 2), Some(2));
+        assert_eq!(split_point(&messages, 3), None);
+        assert_eq!(split_point(&messages[..1], 1), None);
+    }
+
+    fn api_message(role: &str, content: &str) -> ApiMessage {
+        ApiMessage {
+            role: role.to_string(),
+            content: Some(content.to_string()),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recover_from_simulated_overflow() {
+        let body = r#"{"error":{"message":"This model's maximum context length is 8192 tokens","type":"invalid_request_error","code":"context_length_exceeded"}}"#;
+        let overflow = anyhow::Error::new(ProviderError::new(
+            "OpenAI",
+            "https://api.openai.com/v1/chat/completions",
+            400,
+            &reqwest::header::HeaderMap::new(),
+            body.to_string(),
+        ));
+        assert!(is_context_overflow(&overflow));
+        let unauthorized = anyhow::Error::new(ProviderError::new(
+            "OpenAI",
+            "https://api.openai.com/v1/chat/completions",
+            401,
+            &reqwest::header::HeaderMap::new(),
+            r#"{"error":{"type":"invalid_api_key"}}"#.to_string(),
+        ));
+        assert!(!is_context_overflow(&unauthorized));
+
+        let mut messages = vec![
+            api_message("system", "You are ARULA"),
+            api_message("user", "first"),
+            api_message("assistant", "one"),
+            api_message("tool", "ok"),
+            api_message("user", "second"),
+        ];
+        let compaction = compact_request(&mut messages, |transcript| async move {
+            assert_eq!(transcript, "User: first\n\nAssistant: one\n\nTool result: ok");
+            Ok("- said hi".to_string())
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(compaction.replaced, 3);
+        assert_eq!(messages.len(), 2);
+        let system = messages[0].content.as_deref().unwrap();
+        assert!(system.starts_with("You are ARULA"));
+        assert!(system.ends_with(&format!("{}\n- said hi", SUMMARY_HEADER)));
+        assert_eq!(messages[1].content.as_deref(), Some("second"));
+
+        // Only the failing prompt is left, so there is nothing to summarize
+        let again = compact_request(&mut messages, |_| async { Ok(String::new()) }).await;
+        assert!(again.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_apply_summary() {
+        let mut messages = conversation();
+        let compaction = apply_summary(&mut messages, 1, "- earlier work".to_string()).unwrap();
+        assert_eq!(compaction.replaced, 5);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].message_type, MessageType::System);
+        assert_eq!(messages[1].content, "third");
+    }
+
+    #[test]
+    fn test_transcript() {
+        let messages = conversation();
+        assert_eq!(
+            transcript(&messages[..5]),
+            "User: first\n\nAssistant: one\n\nUser: second\n\nTool result: ok"
+        );
+    }
+}
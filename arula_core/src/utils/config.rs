@@ -1,10 +1,39 @@
+use crate::api::capabilities::{self, CapabilityOverride, Feature, ModelCapabilities};
+use crate::tools::injection_guard::InjectionGuardConfig;
+use crate::utils::accessibility::plain_from_env;
+use crate::utils::config_validation::{
+    ConfigIssue, Severity, is_known_provider, validate_config,
+};
+use crate::utils::env_expand::{EnvSource, has_reference};
+pub use arula_llm::generation::GenerationSettings;
+use arula_llm::generation::parse_in_range;
+use arula_llm::ProviderOptions;
+use crate::utils::file_lock::FileLock;
+use crate::utils::hooks::HooksConfig;
+use crate::utils::icons::IconSet;
+use crate::utils::logger;
+use crate::utils::packs::{DEFAULT_INDEX_URL, PacksConfig};
+use crate::utils::telemetry::TelemetryConfig;
+use crate::utils::update::UpdatesConfig;
+use crate::utils::postprocess::PostProcessConfig;
+use crate::utils::secrets::{KeyStorage, SecretStore};
+use crate::utils::style_packs::{AppearanceConfig, ProgressStyle, SpinnerPack};
+use crate::utils::sync::SyncConfig;
+use crate::utils::themes::{SYSTEM_THEME, Theme};
+use crate::utils::speech::SpeechConfig;
+use crate::utils::voice::VoiceConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use serde_yaml;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path; // Only for migration
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long a save waits for another instance writing config.json
+const CONFIG_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -23,9 +52,185 @@ pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub living_background_enabled: Option<bool>,
 
+    /// Show the time next to each chat message (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_timestamps: Option<bool>,
+
+    /// Explain shell commands from the model before they run (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain_commands: Option<bool>,
+
+    /// Never repair broken tool-call arguments, always ask the model to send
+    /// the call again (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strict_tool_arguments: Option<bool>,
+
+    /// Icon glyphs: emoji, nerd, unicode or ascii (default: detected from
+    /// the terminal)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icons: Option<IconSet>,
+
+    /// Plain output for screen readers: ASCII icons, high-contrast colors,
+    /// no emoji or box drawing (default: `ARULA_PLAIN`, else false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plain: Option<bool>,
+
+    /// Keep a rolling brief of each session, written by the model while
+    /// the session is idle (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_brief: Option<bool>,
+
+    /// Capture the mouse in the terminal UI for wheel scrolling, drag
+    /// selection and clickable menus; off keeps the terminal's own
+    /// selection (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mouse: Option<bool>,
+
+    /// Prompt context settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextConfig>,
+
+    /// Spinner and progress bar style packs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appearance: Option<AppearanceConfig>,
+
+    /// Shell commands run on agent events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+
+    /// Post-processing of assistant messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub postprocess: Option<PostProcessConfig>,
+
+    /// Prompt-injection screening of web, MCP and OCR tool outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub injection_guard: Option<InjectionGuardConfig>,
+
+    /// Desktop tray icon and quick-ask hotkey
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tray: Option<TrayConfig>,
+
+    /// Desktop notifications when work finishes while ARULA is unfocused
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationsConfig>,
+
+    /// Bell, title flash and tray badge while the agent waits on the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attention: Option<AttentionConfig>,
+
+    /// Speech-to-text for `/voice` and the desktop mic button
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<VoiceConfig>,
+
+    /// Text-to-speech for `/speak` and the desktop play buttons
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speech: Option<SpeechConfig>,
+
+    /// Community pack index and trusted signing keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packs: Option<PacksConfig>,
+
+    /// Encrypted sync to a self-hosted backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncConfig>,
+
+    /// Update checks for `arula update` and at startup
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updates: Option<UpdatesConfig>,
+
+    /// Log levels per module and log file rotation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingConfig>,
+
+    /// Request tracing exported to an OTLP collector
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<TelemetryConfig>,
+
+    /// Where API keys are stored (default: keychain, with an encrypted-file
+    /// fallback)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_storage: Option<KeyStorage>,
+
+    /// Named profiles overriding provider, model and keys
+    #[serde(skip_serializing_if = "HashMap::is_empty", default = "HashMap::new")]
+    pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Model capabilities added to or correcting the bundled table
+    #[serde(skip_serializing_if = "HashMap::is_empty", default = "HashMap::new")]
+    pub models: HashMap<String, CapabilityOverride>,
+
+    /// Nicknames for models, e.g. `"fast": "groq/llama-3.1-8b"`, accepted
+    /// wherever a model name is; a leading provider name switches provider
+    #[serde(skip_serializing_if = "HashMap::is_empty", default = "HashMap::new")]
+    pub model_aliases: HashMap<String, String>,
+
     /// Legacy field for backward compatibility (deprecated)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ai: Option<AiConfig>,
+
+    /// Profile applied for this session (not saved)
+    #[serde(skip)]
+    pub active_profile: Option<String>,
+
+    /// Base values replaced by the active profile, restored on save
+    #[serde(skip)]
+    profile_base: Option<ProfileBase>,
+
+    /// Values read from `${VAR}` references, written back as references
+    #[serde(skip)]
+    env_references: Vec<EnvReference>,
+
+    /// Secret names known to be in the secret store; only these are
+    /// deleted when their key is cleared. Shared between clones, since
+    /// `save` stores keys from a copy
+    #[serde(skip)]
+    stored_secrets: Arc<Mutex<HashSet<String>>>,
+
+    /// config.json as this instance last read or wrote it, so a save can
+    /// keep changes another instance made since. Shared between clones
+    #[serde(skip)]
+    disk_base: Arc<Mutex<Option<serde_json::Value>>>,
+
+    /// References that couldn't be resolved when loading
+    #[serde(skip)]
+    pub env_errors: Vec<String>,
+
+    /// Problems found in the config file when loading
+    #[serde(skip)]
+    pub validation_issues: Vec<ConfigIssue>,
+}
+
+/// A named set of overrides, selected with `--profile <name>`
+///
+/// Unset fields fall back to the base configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileConfig {
+    /// Provider to use instead of `active_provider`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+}
+
+/// A config value that came from a `${VAR}` reference
+#[derive(Debug, Clone)]
+struct EnvReference {
+    /// Field path, e.g. `providers.openai.api_key`
+    field: String,
+    template: String,
+    value: String,
+}
+
+/// What a profile replaced when it was applied
+#[derive(Debug, Clone)]
+struct ProfileBase {
+    active_provider: String,
+    provider: String,
+    original: Option<ProviderConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,6 +262,167 @@ pub struct ProviderConfig {
     /// Some Ollama models support tool calling, but it may cause issues with others
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools_enabled: Option<bool>,
+
+    /// Sampling temperature, 0.0-2.0 (default: 0.7)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff, 0.0-1.0 (default: not sent)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Maximum tokens per response (default: 4096, or the model's limit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Event hooks that only run while this provider is active
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+}
+
+/// Settings for extra context attached to prompts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// Attach recent git log and diff status of files referenced in a
+    /// message (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_enrichment: Option<bool>,
+    /// Check file paths, symbols and CLI flags mentioned in responses
+    /// against the workspace and flag missing ones (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_references: Option<bool>,
+    /// Regenerate PROJECT.manifest when build files change (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manifest_auto_refresh: Option<bool>,
+    /// Syntax-check code blocks in responses and flag ones that fail
+    /// (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lint_code_blocks: Option<bool>,
+    /// Add remembered facts to the system prompt at session start
+    /// (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<bool>,
+    /// Maximum characters of remembered facts in the system prompt
+    /// (default: 4000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_max_chars: Option<usize>,
+}
+
+/// Hotkey that opens the desktop quick-ask window when none is configured
+pub const DEFAULT_QUICK_ASK_HOTKEY: &str = "CmdOrCtrl+Shift+Space";
+
+/// Log file settings; logs are kept in `~/.arula/logs`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Level of modules without their own: debug, info, warn or error
+    /// (default: info, or debug with `ARULA_DEBUG=1`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    /// Levels of single modules, e.g. `{"api": "debug", "mcp": "warn"}`
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub modules: HashMap<String, String>,
+    /// Size in MB at which the log file is rotated (default: 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_mb: Option<u64>,
+    /// Rotated log files kept (default: 5)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_files: Option<usize>,
+}
+
+impl LoggingConfig {
+    /// Logger settings; `ARULA_DEBUG=1` keeps the default level at debug
+    pub fn settings(&self) -> logger::LogSettings {
+        let mut settings = logger::LogSettings::default();
+        if settings.level != logger::LogLevel::Debug
+            && let Some(level) = self.level.as_deref().and_then(logger::LogLevel::parse)
+        {
+            settings.level = level;
+        }
+        settings.modules = self
+            .modules
+            .iter()
+            .filter_map(|(module, level)| Some((module.clone(), logger::LogLevel::parse(level)?)))
+            .collect();
+        if let Some(mb) = self.max_file_mb {
+            settings.max_bytes = mb.max(1) * 1024 * 1024;
+        }
+        if let Some(files) = self.max_files {
+            settings.max_files = files;
+        }
+        settings
+    }
+}
+
+/// Desktop tray settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrayConfig {
+    /// Show the tray icon and register the hotkey (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Global hotkey for quick ask, e.g. "Alt+Space"
+    /// (default: CmdOrCtrl+Shift+Space)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<String>,
+}
+
+impl TrayConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(true)
+    }
+
+    pub fn hotkey(&self) -> &str {
+        self.hotkey.as_deref().unwrap_or(DEFAULT_QUICK_ASK_HOTKEY)
+    }
+}
+
+/// How ARULA gets the user's attention when the agent waits on them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttentionConfig {
+    /// Ring the terminal bell (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bell: Option<bool>,
+    /// Flash the terminal title, or the window in the desktop app
+    /// (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flash: Option<bool>,
+    /// Badge the desktop tray icon (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tray_badge: Option<bool>,
+}
+
+impl AttentionConfig {
+    pub fn bell(&self) -> bool {
+        self.bell.unwrap_or(true)
+    }
+
+    pub fn flash(&self) -> bool {
+        self.flash.unwrap_or(true)
+    }
+
+    pub fn tray_badge(&self) -> bool {
+        self.tray_badge.unwrap_or(true)
+    }
+}
+
+/// Desktop notification settings; each event can be turned off on its own
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Send notifications at all (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Shortest run or tool batch worth a notification, in seconds
+    /// (default: 20)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_seconds: Option<u64>,
+    /// A response finished (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_finished: Option<bool>,
+    /// A batch of tool calls finished (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools_finished: Option<bool>,
+    /// A run failed (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_failed: Option<bool>,
+    /// The agent is waiting on the user (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_needed: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -212,11 +578,35 @@ pub enum ProviderField {
 impl Config {
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
-        let mut config: Config = serde_json::from_str(&content)?;
+        let issues = validate_config(&content);
+        let mut config: Config = match serde_json::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                let details = issues
+                    .iter()
+                    .filter(|i| i.severity == Severity::Error)
+                    .map(ConfigIssue::describe)
+                    .collect::<Vec<_>>();
+                if details.is_empty() {
+                    return Err(e.into());
+                }
+                return Err(anyhow::anyhow!("{}", details.join("; ")));
+            }
+        };
+        for issue in &issues {
+            logger::warn(&format!("Config: {}", issue.describe()));
+        }
+        config.validation_issues = issues;
 
         // Migrate legacy config if present
         config.migrate_legacy_config();
 
+        // Resolve ${VAR} references from the environment and ./.env
+        let dir = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+        config.expand_env_references(&EnvSource::load(&dir));
+
+        *config.disk_base.lock().unwrap_or_else(|e| e.into_inner()) =
+            serde_json::to_value(config.persisted()).ok();
         Ok(config)
     }
 
@@ -226,17 +616,32 @@ impl Config {
             fs::create_dir_all(parent)?;
         }
 
-        let content = serde_json::to_string_pretty(self)?;
-        fs::write(path, content)?;
+        // Another instance may be saving too. Under the lock, keep what it
+        // changed since we loaded and swap the file in whole so neither
+        // sees a half-written config
+        let path = path.as_ref();
+        let _lock = FileLock::acquire_within(path, CONFIG_LOCK_TIMEOUT)?;
+        let ours = serde_json::to_value(self.persisted())?;
+        let mut base = self.disk_base.lock().unwrap_or_else(|e| e.into_inner());
+        let merged = match (base.as_ref(), read_json(path)) {
+            (Some(base), Some(theirs)) if theirs != *base => merge_json(base, &ours, &theirs),
+            _ => ours,
+        };
+        let content = serde_json::to_string_pretty(&merged)?;
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp = path.with_file_name(tmp_name);
+        write_with_permissions_of(&tmp, path, content.as_bytes())?;
+        fs::rename(&tmp, path)?;
+        *base = Some(merged);
         Ok(())
     }
 
     pub fn get_config_path() -> String {
-        // Use cross-platform home directory detection
-        let home = std::env::var("HOME")
-            .or_else(|_| std::env::var("USERPROFILE")) // Windows
-            .unwrap_or_else(|_| ".".to_string());
-        format!("{}/.arula/config.json", home)
+        crate::platform::current()
+            .config_path()
+            .to_string_lossy()
+            .to_string()
     }
 
     pub fn load_or_default() -> Result<Self> {
@@ -249,9 +654,25 @@ impl Config {
         let old_yaml_path = format!("{}/.arula/config.yaml", home);
 
         // Try to load JSON config first
+        let mut load_issues = Vec::new();
         if config_file.exists() {
-            if let Ok(config) = Self::load_from_file(config_file) {
-                return Ok(config);
+            match Self::load_from_file(config_file) {
+                Ok(mut config) => {
+                    // Move plaintext keys from older configs into the secret store
+                    if config.load_secrets()
+                        && let Err(e) = config.save()
+                    {
+                        logger::warn(&format!("Failed to migrate API keys: {}", e));
+                    }
+                    return Ok(config);
+                }
+                Err(e) => {
+                    logger::warn(&format!("Failed to load {}: {}", config_path, e));
+                    // Report why the defaults are used instead of failing silently
+                    load_issues = fs::read_to_string(config_file)
+                        .map(|content| validate_config(&content))
+                        .unwrap_or_default();
+                }
             }
         }
 
@@ -279,12 +700,346 @@ impl Config {
         }
 
         // Return default config if loading/migration fails
-        Ok(Self::default())
+        let mut config = Self::default();
+        config.validation_issues = load_issues;
+        Ok(config)
+    }
+
+    /// Load config.json again after it changed on disk, with keys from the
+    /// secret store (no migration or saving)
+    pub fn reload_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut config = Self::load_from_file(path)?;
+        config.load_secrets();
+        Ok(config)
     }
 
     pub fn save(&self) -> Result<()> {
         let config_path = Self::get_config_path();
-        self.save_to_file(config_path)
+        let mut config = self.persisted();
+        config.store_secrets();
+        config.save_to_file(config_path)
+    }
+
+    /// Directory holding config.json and the encrypted secrets file
+    fn config_dir() -> std::path::PathBuf {
+        Path::new(&Self::get_config_path())
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+    }
+
+    /// Open the secret store for the `key_storage` setting
+    fn secret_store(&self) -> Option<SecretStore> {
+        SecretStore::open(self.key_storage.unwrap_or_default(), &Self::config_dir())
+    }
+
+    /// Fill in API keys kept in the secret store
+    ///
+    /// Returns true when the file still holds plaintext keys that should be
+    /// moved into the store by saving.
+    fn load_secrets(&mut self) -> bool {
+        let Some(store) = self.secret_store() else {
+            return false;
+        };
+        let mut needs_migration = false;
+        let mut stored = self.stored_secrets.lock().unwrap_or_else(|e| e.into_inner());
+        let env_keys: Vec<String> = self
+            .env_references
+            .iter()
+            .map(|r| r.field.clone())
+            .collect();
+        let mut fill = |name: String, field: String, key: &mut String| {
+            // Keys from ${VAR} references stay where they are
+            if env_keys.contains(&field) {
+                return;
+            }
+            if !key.is_empty() {
+                needs_migration = true;
+                return;
+            }
+            match store.get(&name) {
+                Ok(Some(secret)) => {
+                    *key = secret;
+                    stored.insert(name);
+                }
+                Ok(None) => {}
+                Err(e) => logger::warn(&format!("Failed to read API key for {}: {}", name, e)),
+            }
+        };
+        for (name, provider) in self.providers.iter_mut() {
+            let field = format!("providers.{}.api_key", name);
+            fill(name.clone(), field, &mut provider.api_key);
+        }
+        for (name, profile) in self.profiles.iter_mut() {
+            if let Some(key) = profile.api_key.as_mut() {
+                let field = format!("profiles.{}.api_key", name);
+                fill(format!("profile:{}", name), field, key);
+            }
+        }
+        needs_migration
+    }
+
+    /// Move API keys into the secret store, leaving them blank in the file
+    ///
+    /// Keys that can't be stored stay in the file so they aren't lost.
+    fn store_secrets(&mut self) {
+        let Some(store) = self.secret_store() else {
+            return;
+        };
+        let mut stored = self.stored_secrets.lock().unwrap_or_else(|e| e.into_inner());
+        let mut store_key = |name: String, key: &mut String| {
+            if has_reference(key) {
+                return;
+            }
+            // A blank key that was never read from the store (for example
+            // because the lookup failed) isn't a request to delete it
+            let result = if key.is_empty() {
+                if !stored.contains(&name) {
+                    return;
+                }
+                store.delete(&name).map(|_| {
+                    stored.remove(&name);
+                })
+            } else {
+                store.set(&name, key).map(|_| {
+                    key.clear();
+                    stored.insert(name.clone());
+                })
+            };
+            if let Err(e) = result {
+                logger::warn(&format!(
+                    "Failed to store API key for {} in {}: {}",
+                    name,
+                    store.backend_name(),
+                    e
+                ));
+            }
+        };
+        for (name, provider) in self.providers.iter_mut() {
+            store_key(name.clone(), &mut provider.api_key);
+        }
+        for (name, profile) in self.profiles.iter_mut() {
+            if let Some(key) = profile.api_key.as_mut() {
+                store_key(format!("profile:{}", name), key);
+            }
+        }
+    }
+
+    /// Environment variable selecting a profile when `--profile` isn't given
+    pub const PROFILE_ENV: &'static str = "ARULA_PROFILE";
+
+    /// Get the names of all configured profiles
+    pub fn get_profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Apply a named profile on top of the base configuration
+    ///
+    /// Passing `None` returns to the base configuration. Changes made while a
+    /// profile is active are saved into that profile, not the base.
+    pub fn use_profile(&mut self, name: Option<&str>) -> Result<()> {
+        let profile = match name {
+            Some(name) => Some(self.profiles.get(name).cloned().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown profile '{}' (available: {})",
+                    name,
+                    match self.get_profile_names() {
+                        names if names.is_empty() => "none".to_string(),
+                        names => names.join(", "),
+                    }
+                )
+            })?),
+            None => None,
+        };
+
+        self.leave_profile();
+        let (Some(name), Some(profile)) = (name, profile) else {
+            return Ok(());
+        };
+
+        let provider = profile
+            .provider
+            .clone()
+            .unwrap_or_else(|| self.active_provider.clone());
+        self.profile_base = Some(ProfileBase {
+            active_provider: self.active_provider.clone(),
+            provider: provider.clone(),
+            original: self.providers.get(&provider).cloned(),
+        });
+        self.switch_provider(&provider)?;
+        if let Some(config) = self.get_active_provider_config_mut() {
+            if let Some(model) = &profile.model {
+                config.model = model.clone();
+            }
+            if let Some(api_key) = &profile.api_key {
+                config.api_key = api_key.clone();
+            }
+            if let Some(api_url) = &profile.api_url {
+                config.api_url = Some(api_url.clone());
+            }
+        }
+        self.active_profile = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Record the active profile's current values and restore the base
+    fn leave_profile(&mut self) {
+        let (Some(name), Some(base)) = (self.active_profile.take(), self.profile_base.take())
+        else {
+            return;
+        };
+
+        let current = self.providers.get(&base.provider).cloned();
+        if let Some(profile) = self.profiles.get_mut(&name) {
+            if profile.provider.is_some() {
+                profile.provider = Some(self.active_provider.clone());
+            }
+            if let Some(current) = &current {
+                if profile.model.is_some() {
+                    profile.model = Some(current.model.clone());
+                }
+                if profile.api_key.is_some() {
+                    profile.api_key = Some(current.api_key.clone());
+                }
+                if profile.api_url.is_some() {
+                    profile.api_url = current.api_url.clone();
+                }
+            }
+        }
+
+        self.active_provider = base.active_provider;
+        match (base.original, self.providers.get_mut(&base.provider)) {
+            (Some(original), Some(config)) => {
+                config.model = original.model;
+                config.api_key = original.api_key;
+                config.api_url = original.api_url;
+            }
+            (None, _) => {
+                self.providers.remove(&base.provider);
+            }
+            _ => {}
+        }
+    }
+
+    /// The configuration as written to disk, with profile overrides kept
+    /// out of the base settings and `${VAR}` references restored
+    fn persisted(&self) -> Config {
+        let mut config = self.clone();
+        config.leave_profile();
+        let references = std::mem::take(&mut config.env_references);
+        for (field, value) in config.string_fields_mut() {
+            if let Some(reference) = references.iter().find(|r| r.field == field)
+                && *value == reference.value
+            {
+                *value = reference.template.clone();
+            }
+        }
+        config
+    }
+
+    /// The configuration for a profile archive: as saved to disk, with API
+    /// keys inlined from the secret store or removed
+    ///
+    /// `${VAR}` references are kept either way.
+    pub fn exported(&self, include_keys: bool) -> Config {
+        let mut config = self.persisted();
+        if !include_keys {
+            for provider in config.providers.values_mut() {
+                if !has_reference(&provider.api_key) {
+                    provider.api_key.clear();
+                }
+            }
+            for profile in config.profiles.values_mut() {
+                if profile.api_key.as_deref().is_some_and(|k| !has_reference(k)) {
+                    profile.api_key = None;
+                }
+            }
+        }
+        config
+    }
+
+    /// Every string setting that may hold a `${VAR}` reference, by field path
+    fn string_fields_mut(&mut self) -> Vec<(String, &mut String)> {
+        let mut fields = Vec::new();
+        for (name, provider) in self.providers.iter_mut() {
+            fields.push((format!("providers.{}.model", name), &mut provider.model));
+            fields.push((format!("providers.{}.api_key", name), &mut provider.api_key));
+            if let Some(url) = provider.api_url.as_mut() {
+                fields.push((format!("providers.{}.api_url", name), url));
+            }
+        }
+        for (name, profile) in self.profiles.iter_mut() {
+            for (key, value) in [
+                ("provider", &mut profile.provider),
+                ("model", &mut profile.model),
+                ("api_key", &mut profile.api_key),
+                ("api_url", &mut profile.api_url),
+            ] {
+                if let Some(value) = value.as_mut() {
+                    fields.push((format!("profiles.{}.{}", name, key), value));
+                }
+            }
+        }
+        if let Some(sync) = self.sync.as_mut() {
+            for (key, value) in [
+                ("url", &mut sync.url),
+                ("path", &mut sync.path),
+                ("username", &mut sync.username),
+                ("password", &mut sync.password),
+                ("access_key", &mut sync.access_key),
+                ("secret_key", &mut sync.secret_key),
+            ] {
+                if let Some(value) = value.as_mut() {
+                    fields.push((format!("sync.{}", key), value));
+                }
+            }
+        }
+        for (name, server) in self.mcp_servers.iter_mut() {
+            fields.push((format!("mcpServers.{}.url", name), &mut server.url));
+            for (header, value) in server.headers.iter_mut() {
+                fields.push((format!("mcpServers.{}.headers.{}", name, header), value));
+            }
+        }
+        fields
+    }
+
+    /// Replace `${VAR}` references with their values
+    ///
+    /// Unresolved references are left empty and reported in `env_errors`.
+    fn expand_env_references(&mut self, source: &EnvSource) {
+        let mut references = Vec::new();
+        let mut errors = Vec::new();
+        for (field, value) in self.string_fields_mut() {
+            if !has_reference(value) {
+                continue;
+            }
+            let template = std::mem::take(value);
+            match source.expand(&template) {
+                Ok(resolved) => *value = resolved,
+                Err(missing) => errors.push(format!(
+                    "{} references {} but {} not set in the environment or .env",
+                    field,
+                    missing
+                        .iter()
+                        .map(|name| format!("${{{}}}", name))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    if missing.len() == 1 { "it is" } else { "they are" }
+                )),
+            }
+            references.push(EnvReference {
+                field,
+                template,
+                value: value.clone(),
+            });
+        }
+        for error in &errors {
+            logger::warn(&format!("Config: {}", error));
+        }
+        self.env_references = references;
+        self.env_errors = errors;
     }
 
     /// Migrate legacy ai config to new providers structure
@@ -302,6 +1057,10 @@ impl Config {
                 web_search_enabled: None,
                 streaming: None,
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             };
 
             self.providers
@@ -338,6 +1097,10 @@ impl Config {
                     web_search_enabled: Some(false),
                     streaming: None,
                     tools_enabled: None,
+                    temperature: None,
+                    top_p: None,
+                    max_tokens: None,
+                    hooks: None,
                 },
             );
         }
@@ -394,6 +1157,288 @@ impl Config {
         self.save()
     }
 
+    /// Get show timestamps setting (default: false)
+    pub fn get_show_timestamps_enabled(&self) -> bool {
+        self.show_timestamps.unwrap_or(false)
+    }
+
+    /// Set show timestamps setting
+    pub fn set_show_timestamps_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.show_timestamps = Some(enabled);
+        self.save()
+    }
+
+    /// Get explain commands setting (default: true)
+    pub fn get_explain_commands_enabled(&self) -> bool {
+        self.explain_commands.unwrap_or(true)
+    }
+
+    /// Get strict tool arguments setting (default: false)
+    pub fn get_strict_tool_arguments(&self) -> bool {
+        self.strict_tool_arguments.unwrap_or(false)
+    }
+
+    /// Get the icon set, detecting one when it isn't configured
+    pub fn get_icon_set(&self) -> IconSet {
+        self.icons.unwrap_or_else(IconSet::detect)
+    }
+
+    /// Whether plain output is on (`plain`, default: `ARULA_PLAIN`)
+    pub fn get_plain_mode(&self) -> bool {
+        self.plain.unwrap_or_else(plain_from_env)
+    }
+
+    /// Get session brief setting (default: true)
+    pub fn get_session_brief_enabled(&self) -> bool {
+        self.session_brief.unwrap_or(true)
+    }
+
+    /// Get mouse capture setting (default: true)
+    pub fn get_mouse_enabled(&self) -> bool {
+        self.mouse.unwrap_or(true)
+    }
+
+    /// Turn mouse capture on or off and save
+    pub fn set_mouse_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.mouse = Some(enabled);
+        self.save()
+    }
+
+    /// Set the icon set (`None` to detect it)
+    pub fn set_icon_set(&mut self, icons: Option<IconSet>) -> Result<()> {
+        self.icons = icons;
+        self.save()
+    }
+
+    /// Get the spinner and progress style settings
+    pub fn get_appearance(&self) -> AppearanceConfig {
+        self.appearance.clone().unwrap_or_default()
+    }
+
+    /// Get the desktop tray settings
+    pub fn get_tray(&self) -> TrayConfig {
+        self.tray.clone().unwrap_or_default()
+    }
+
+    /// Get the desktop notification settings
+    pub fn get_notifications(&self) -> NotificationsConfig {
+        self.notifications.clone().unwrap_or_default()
+    }
+
+    /// Get the settings for getting the user's attention
+    pub fn get_attention(&self) -> AttentionConfig {
+        self.attention.clone().unwrap_or_default()
+    }
+
+    /// Get the speech-to-text settings
+    pub fn get_voice(&self) -> VoiceConfig {
+        self.voice.clone().unwrap_or_default()
+    }
+
+    /// Get the text-to-speech settings
+    pub fn get_speech(&self) -> SpeechConfig {
+        self.speech.clone().unwrap_or_default()
+    }
+
+    /// Turn reading replies aloud on or off
+    pub fn set_speech_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.speech.get_or_insert_with(SpeechConfig::default).enabled = Some(enabled);
+        self.save()
+    }
+
+    /// Get the post-processing settings for assistant messages
+    pub fn get_postprocess(&self) -> PostProcessConfig {
+        self.postprocess.clone().unwrap_or_default()
+    }
+
+    /// Get the prompt-injection screening settings
+    pub fn get_injection_guard(&self) -> InjectionGuardConfig {
+        self.injection_guard.clone().unwrap_or_default()
+    }
+
+    /// Get the pack index URL (`packs.index_url`, default: the community index)
+    pub fn get_packs_index_url(&self) -> String {
+        self.packs
+            .as_ref()
+            .and_then(|p| p.index_url.clone())
+            .unwrap_or_else(|| DEFAULT_INDEX_URL.to_string())
+    }
+
+    /// Get the keys trusted to sign packs (`packs.trusted_keys`)
+    pub fn get_trusted_pack_keys(&self) -> Vec<String> {
+        self.packs
+            .as_ref()
+            .map(|p| p.trusted_keys.clone())
+            .unwrap_or_default()
+    }
+
+    /// Get the sync settings, if sync is set up
+    pub fn get_sync_config(&self) -> Option<SyncConfig> {
+        self.sync.clone()
+    }
+
+    /// Get the log level and rotation settings
+    pub fn get_logging(&self) -> LoggingConfig {
+        self.logging.clone().unwrap_or_default()
+    }
+
+    /// Get the trace export settings
+    pub fn get_telemetry(&self) -> TelemetryConfig {
+        self.telemetry.clone().unwrap_or_default()
+    }
+
+    /// Get the update check settings
+    pub fn get_updates(&self) -> UpdatesConfig {
+        self.updates.clone().unwrap_or_default()
+    }
+
+    /// Get the active spinner pack (`appearance.spinner`, default: circle)
+    pub fn get_spinner_pack(&self) -> SpinnerPack {
+        self.get_appearance().spinner()
+    }
+
+    /// Get the active progress bar style (`appearance.progress`, default: blocks)
+    pub fn get_progress_style(&self) -> ProgressStyle {
+        self.get_appearance().progress_style()
+    }
+
+    /// Select a spinner pack by name
+    pub fn set_spinner_pack(&mut self, name: &str) -> Result<()> {
+        let appearance = self.appearance.get_or_insert_with(AppearanceConfig::default);
+        if appearance.find_spinner(name).is_none() {
+            return Err(anyhow::anyhow!("Unknown spinner '{}'", name));
+        }
+        appearance.spinner = Some(name.to_lowercase());
+        self.save()
+    }
+
+    /// Get the active color theme (`appearance.theme`, default: dark)
+    ///
+    /// A theme that can't be loaded is logged and the default is used. When
+    /// following the OS color scheme, which only the desktop app can detect,
+    /// this is the dark theme.
+    pub fn get_theme(&self) -> Theme {
+        if self.follows_system_theme() {
+            return self.get_system_theme(true);
+        }
+        load_theme(self.appearance.as_ref().and_then(|a| a.theme.as_deref()))
+    }
+
+    /// Whether the theme follows the OS light/dark setting (`"theme": "system"`)
+    pub fn follows_system_theme(&self) -> bool {
+        self.appearance
+            .as_ref()
+            .and_then(|a| a.theme.as_deref())
+            .is_some_and(|name| name.eq_ignore_ascii_case(SYSTEM_THEME))
+    }
+
+    /// The theme for a dark or light OS color scheme
+    /// (`appearance.dark_theme` / `appearance.light_theme`)
+    pub fn get_system_theme(&self, dark: bool) -> Theme {
+        let appearance = self.appearance.as_ref();
+        let name = if dark {
+            appearance.and_then(|a| a.dark_theme.as_deref()).unwrap_or("dark")
+        } else {
+            appearance.and_then(|a| a.light_theme.as_deref()).unwrap_or("light")
+        };
+        load_theme(Some(name))
+    }
+
+    /// Select a color theme by name, or `system` to follow the OS
+    pub fn set_theme(&mut self, name: &str) -> Result<()> {
+        let id = if name.eq_ignore_ascii_case(SYSTEM_THEME) {
+            SYSTEM_THEME.to_string()
+        } else {
+            Theme::load(name)?.id
+        };
+        self.appearance
+            .get_or_insert_with(AppearanceConfig::default)
+            .theme = Some(id);
+        self.save()
+    }
+
+    /// Select a progress bar style by name
+    pub fn set_progress_style(&mut self, name: &str) -> Result<()> {
+        let appearance = self.appearance.get_or_insert_with(AppearanceConfig::default);
+        if appearance.find_progress_style(name).is_none() {
+            return Err(anyhow::anyhow!("Unknown progress style '{}'", name));
+        }
+        appearance.progress = Some(name.to_lowercase());
+        self.save()
+    }
+
+    /// Get git enrichment setting (`context.git_enrichment`, default: false)
+    pub fn get_git_enrichment_enabled(&self) -> bool {
+        self.context
+            .as_ref()
+            .and_then(|c| c.git_enrichment)
+            .unwrap_or(false)
+    }
+
+    /// Set git enrichment setting
+    pub fn set_git_enrichment_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.context.get_or_insert_with(ContextConfig::default).git_enrichment = Some(enabled);
+        self.save()
+    }
+
+    /// Get reference verification setting (`context.verify_references`, default: true)
+    pub fn get_verify_references_enabled(&self) -> bool {
+        self.context
+            .as_ref()
+            .and_then(|c| c.verify_references)
+            .unwrap_or(true)
+    }
+
+    /// Set reference verification setting
+    pub fn set_verify_references_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.context.get_or_insert_with(ContextConfig::default).verify_references = Some(enabled);
+        self.save()
+    }
+
+    /// Get manifest auto-refresh setting (`context.manifest_auto_refresh`, default: false)
+    pub fn get_manifest_auto_refresh_enabled(&self) -> bool {
+        self.context
+            .as_ref()
+            .and_then(|c| c.manifest_auto_refresh)
+            .unwrap_or(false)
+    }
+
+    /// Set manifest auto-refresh setting
+    pub fn set_manifest_auto_refresh_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.context.get_or_insert_with(ContextConfig::default).manifest_auto_refresh = Some(enabled);
+        self.save()
+    }
+
+    /// Get code block linting setting (`context.lint_code_blocks`, default: false)
+    pub fn get_lint_code_blocks_enabled(&self) -> bool {
+        self.context
+            .as_ref()
+            .and_then(|c| c.lint_code_blocks)
+            .unwrap_or(false)
+    }
+
+    /// Set code block linting setting
+    pub fn set_lint_code_blocks_enabled(&mut self, enabled: bool) -> Result<()> {
+        self.context.get_or_insert_with(ContextConfig::default).lint_code_blocks = Some(enabled);
+        self.save()
+    }
+
+    /// Get long-term memory setting (`context.memory`, default: true)
+    pub fn get_memory_enabled(&self) -> bool {
+        self.context
+            .as_ref()
+            .and_then(|c| c.memory)
+            .unwrap_or(true)
+    }
+
+    /// Get the memory size cap (`context.memory_max_chars`, default: 4000)
+    pub fn get_memory_max_chars(&self) -> usize {
+        self.context
+            .as_ref()
+            .and_then(|c| c.memory_max_chars)
+            .unwrap_or(4000)
+    }
+
     /// Set Z.AI web search enabled
     pub fn set_zai_web_search_enabled(&mut self, enabled: bool) -> Result<()> {
         if let Some(config) = self.get_active_provider_config_mut() {
@@ -441,6 +1486,71 @@ impl Config {
         Ok(())
     }
 
+    /// Get the sampling parameters of the active provider
+    pub fn get_generation_settings(&self) -> GenerationSettings {
+        self.get_active_provider_config()
+            .map(|config| GenerationSettings {
+                temperature: config.temperature,
+                top_p: config.top_p,
+                max_tokens: config.max_tokens,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set a sampling parameter (`temperature`, `top_p` or `max_tokens`)
+    /// for the active provider; `default` clears it
+    pub fn set_generation_setting(&mut self, name: &str, value: &str) -> Result<()> {
+        self.update_generation_setting(name, value)?;
+        self.save()
+    }
+
+    fn update_generation_setting(&mut self, name: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let reset = matches!(value, "" | "default" | "reset");
+        let config = self
+            .get_active_provider_config_mut()
+            .ok_or_else(|| anyhow::anyhow!("No active provider configured"))?;
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "temperature" | "temp" => {
+                config.temperature = if reset {
+                    None
+                } else {
+                    Some(GenerationSettings::parse_temperature(value)?)
+                };
+            }
+            "top_p" => {
+                config.top_p = if reset {
+                    None
+                } else {
+                    Some(parse_in_range(value, 0.0, 1.0)?)
+                };
+            }
+            "max_tokens" => {
+                config.max_tokens = if reset {
+                    None
+                } else {
+                    match value.parse::<u32>() {
+                        Ok(tokens) if tokens > 0 => Some(tokens),
+                        _ => {
+                            return Err(anyhow::anyhow!(
+                                "max_tokens must be a whole number above 0, got '{}'",
+                                value
+                            ));
+                        }
+                    }
+                };
+            }
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown setting '{}' (expected {})",
+                    other,
+                    GenerationSettings::NAMES.join(", ")
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Get Z.AI usage tracking enabled setting
     pub fn get_zai_usage_tracking_enabled(&self) -> Option<bool> {
         if let Some(config) = self.get_active_provider_config() {
@@ -468,6 +1578,16 @@ impl Config {
         }
     }
 
+    /// Settings the provider client needs, from the active provider
+    pub fn provider_options(&self) -> ProviderOptions {
+        ProviderOptions {
+            thinking: self.get_thinking_enabled().unwrap_or(false),
+            max_retries: self.get_zai_max_retries(),
+            timeout: Duration::from_secs(self.get_zai_timeout_seconds()),
+            usage_tracking: self.get_zai_usage_tracking_enabled().unwrap_or(true),
+        }
+    }
+
     /// Load configuration from environment variables
     pub fn load_from_env() -> Result<Self> {
         let api_key = std::env::var("ZAI_API_KEY")
@@ -512,6 +1632,10 @@ impl Config {
                     .ok()
                     .and_then(|v| v.parse().ok()),
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             },
         );
 
@@ -537,6 +1661,69 @@ impl Config {
             .unwrap_or_else(|| "default".to_string())
     }
 
+    /// Get the capabilities of the current model, if it's known
+    pub fn get_model_capabilities(&self) -> Option<ModelCapabilities> {
+        capabilities::lookup_in(&self.get_model(), &self.models)
+    }
+
+    /// Warnings for settings the current model can't support
+    pub fn model_warnings(&self) -> Vec<String> {
+        let mut features = vec![Feature::Tools];
+        if self.get_streaming_enabled() {
+            features.push(Feature::Streaming);
+        }
+        capabilities::missing_features(
+            &self.get_model(),
+            self.get_model_capabilities().as_ref(),
+            &features,
+        )
+    }
+
+    /// The provider (when given) and model an alias or model name stands
+    /// for: `smart` → the alias's target, `anthropic/claude-sonnet` →
+    /// provider `anthropic`, anything else → a model of the current provider
+    pub fn resolve_model(&self, name: &str) -> (Option<String>, String) {
+        let target = self
+            .model_aliases
+            .get(name.trim())
+            .map(String::as_str)
+            .unwrap_or(name)
+            .trim();
+        match target.split_once('/') {
+            // Model IDs like OpenRouter's `meta-llama/llama-3` contain a slash too
+            Some((provider, model))
+                if self.providers.contains_key(provider) || is_known_provider(provider) =>
+            {
+                (Some(provider.to_string()), model.to_string())
+            }
+            _ => (None, target.to_string()),
+        }
+    }
+
+    /// Switch to the model an alias or model name stands for, changing
+    /// provider if it names one
+    pub fn use_model(&mut self, name: &str) -> Result<()> {
+        let (provider, model) = self.resolve_model(name);
+        if let Some(provider) = provider
+            && provider != self.active_provider
+        {
+            self.switch_provider(&provider)?;
+        }
+        self.set_model(&model);
+        Ok(())
+    }
+
+    /// Configured aliases, sorted by name
+    pub fn get_model_aliases(&self) -> Vec<(String, String)> {
+        let mut aliases: Vec<(String, String)> = self
+            .model_aliases
+            .iter()
+            .map(|(alias, target)| (alias.clone(), target.clone()))
+            .collect();
+        aliases.sort();
+        aliases
+    }
+
     /// Set model for current provider
     pub fn set_model(&mut self, model: &str) {
         if let Some(config) = self.get_active_provider_config_mut() {
@@ -632,6 +1819,10 @@ impl Config {
                 web_search_enabled: None,
                 streaming: None,
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             },
         );
         Ok(())
@@ -655,6 +1846,10 @@ impl Config {
                 web_search_enabled: None,
                 streaming: None, // Defaults to true when not set
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             },
         );
 
@@ -663,7 +1858,40 @@ impl Config {
             providers,
             mcp_servers: HashMap::new(),
             living_background_enabled: None,
+            show_timestamps: None,
+            explain_commands: None,
+            strict_tool_arguments: None,
+            icons: None,
+            plain: None,
+            session_brief: None,
+            mouse: None,
+            appearance: None,
+            hooks: None,
+            postprocess: None,
+            injection_guard: None,
+            tray: None,
+            notifications: None,
+            attention: None,
+            voice: None,
+            speech: None,
+            context: None,
+            packs: None,
+            sync: None,
+            updates: None,
+            telemetry: None,
+            logging: None,
+            key_storage: None,
+            profiles: HashMap::new(),
+            model_aliases: HashMap::new(),
+            models: HashMap::new(),
             ai: None,
+            active_profile: None,
+            profile_base: None,
+            env_references: Vec::new(),
+            stored_secrets: Arc::default(),
+            disk_base: Arc::default(),
+            env_errors: Vec::new(),
+            validation_issues: Vec::new(),
         }
     }
 
@@ -685,6 +1913,10 @@ impl Config {
                 web_search_enabled: None,
                 streaming: None, // Defaults to true when not set
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             },
         );
 
@@ -693,7 +1925,40 @@ impl Config {
             providers,
             mcp_servers: HashMap::new(),
             living_background_enabled: None,
+            show_timestamps: None,
+            explain_commands: None,
+            strict_tool_arguments: None,
+            icons: None,
+            plain: None,
+            session_brief: None,
+            mouse: None,
+            appearance: None,
+            hooks: None,
+            postprocess: None,
+            injection_guard: None,
+            tray: None,
+            notifications: None,
+            attention: None,
+            voice: None,
+            speech: None,
+            context: None,
+            packs: None,
+            sync: None,
+            updates: None,
+            telemetry: None,
+            logging: None,
+            key_storage: None,
+            profiles: HashMap::new(),
+            model_aliases: HashMap::new(),
+            models: HashMap::new(),
             ai: None,
+            active_profile: None,
+            profile_base: None,
+            env_references: Vec::new(),
+            stored_secrets: Arc::default(),
+            disk_base: Arc::default(),
+            env_errors: Vec::new(),
+            validation_issues: Vec::new(),
         }
     }
 
@@ -713,6 +1978,10 @@ impl Config {
                 web_search_enabled: None,
                 streaming: None,
                 tools_enabled: None,
+                temperature: None,
+                top_p: None,
+                max_tokens: None,
+                hooks: None,
             },
         );
 
@@ -721,11 +1990,114 @@ impl Config {
             providers,
             mcp_servers: HashMap::new(),
             living_background_enabled: None,
+            show_timestamps: None,
+            explain_commands: None,
+            strict_tool_arguments: None,
+            icons: None,
+            plain: None,
+            session_brief: None,
+            mouse: None,
+            appearance: None,
+            hooks: None,
+            postprocess: None,
+            injection_guard: None,
+            tray: None,
+            notifications: None,
+            attention: None,
+            voice: None,
+            speech: None,
+            context: None,
+            packs: None,
+            sync: None,
+            updates: None,
+            telemetry: None,
+            logging: None,
+            key_storage: None,
+            profiles: HashMap::new(),
+            model_aliases: HashMap::new(),
+            models: HashMap::new(),
             ai: None,
+            active_profile: None,
+            profile_base: None,
+            env_references: Vec::new(),
+            stored_secrets: Arc::default(),
+            disk_base: Arc::default(),
+            env_errors: Vec::new(),
+            validation_issues: Vec::new(),
         }
     }
 }
 
+/// Load a theme, logging why and using the default if it can't be
+fn load_theme(name: Option<&str>) -> Theme {
+    let Some(name) = name else {
+        return Theme::default();
+    };
+    Theme::load(name).unwrap_or_else(|e| {
+        logger::warn(&format!("Theme not loaded: {:#}", e));
+        Theme::default()
+    })
+}
+
+/// The JSON in `path`, if it can be read and parsed
+fn read_json(path: &Path) -> Option<serde_json::Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Apply the changes from `base` to `ours` on top of `theirs`
+///
+/// Objects merge key by key, so another instance's edits to other settings
+/// survive; where both changed the same value, ours wins.
+fn merge_json(
+    base: &serde_json::Value,
+    ours: &serde_json::Value,
+    theirs: &serde_json::Value,
+) -> serde_json::Value {
+    use serde_json::Value;
+    let (Value::Object(base), Value::Object(ours), Value::Object(theirs)) = (base, ours, theirs)
+    else {
+        return if ours == base { theirs.clone() } else { ours.clone() };
+    };
+    let mut merged = theirs.clone();
+    for (key, value) in ours {
+        match (base.get(key), theirs.get(key)) {
+            (Some(old), _) if old == value => {}
+            (Some(old), Some(current)) => {
+                merged.insert(key.clone(), merge_json(old, value, current));
+            }
+            _ => {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    for key in base.keys().filter(|k| !ours.contains_key(*k)) {
+        merged.remove(key);
+    }
+    Value::Object(merged)
+}
+
+/// Write `content` to `tmp` with the permissions `original` has, so
+/// replacing it keeps a private config private
+fn write_with_permissions_of(tmp: &Path, original: &Path, content: &[u8]) -> Result<()> {
+    use std::io::Write;
+    let permissions = fs::metadata(original).ok().map(|m| m.permissions());
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    if let Some(permissions) = &permissions {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(permissions.mode());
+    }
+    let mut file = options.open(tmp)?;
+    // A leftover tmp file keeps its old mode, and umask trims the new one
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions)?;
+    }
+    file.write_all(content)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -773,6 +2145,30 @@ mod tests {
         assert_eq!(config.get_api_key(), "test-key");
     }
 
+    #[test]
+    fn test_generation_settings() {
+        let mut config = Config::new_for_test("openai", "gpt-4o", "https://api.openai.com/v1", "k");
+        assert_eq!(config.get_generation_settings(), GenerationSettings::default());
+        assert_eq!(config.get_generation_settings().temperature(), 0.7);
+
+        config.update_generation_setting("temperature", "0.2").unwrap();
+        config.update_generation_setting("max-tokens", "1024").unwrap();
+        let settings = config.get_generation_settings();
+        assert_eq!(settings.temperature, Some(0.2));
+        assert_eq!(settings.max_tokens(), 1024);
+        assert_eq!(
+            settings.summary(),
+            "temperature 0.2 · top_p default · max_tokens 1024"
+        );
+
+        assert!(config.update_generation_setting("temperature", "2.5").is_err());
+        assert!(config.update_generation_setting("max_tokens", "0").is_err());
+        assert!(config.update_generation_setting("top_k", "40").is_err());
+
+        config.update_generation_setting("temperature", "default").unwrap();
+        assert_eq!(config.get_generation_settings().temperature, None);
+    }
+
     #[test]
     fn test_save_and_load_config() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -807,6 +2203,49 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_save_keeps_other_instance_changes() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.json");
+        Config::new_for_test("test-provider", "test-model", "https://test.api.com", "key")
+            .save_to_file(&config_path)?;
+
+        let mut first = Config::load_from_file(&config_path)?;
+        let mut second = Config::load_from_file(&config_path)?;
+        first
+            .model_aliases
+            .insert("fast".to_string(), "test-mini".to_string());
+        first.save_to_file(&config_path)?;
+        second.providers.get_mut("test-provider").unwrap().model = "test-large".to_string();
+        second.save_to_file(&config_path)?;
+
+        let saved = Config::load_from_file(&config_path)?;
+        assert_eq!(saved.model_aliases.get("fast").map(String::as_str), Some("test-mini"));
+        assert_eq!(saved.providers["test-provider"].model, "test-large");
+
+        // Removing a setting isn't undone by the merge either
+        second.model_aliases.clear();
+        second.save_to_file(&config_path)?;
+        assert!(Config::load_from_file(&config_path)?.model_aliases.is_empty());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_keeps_file_permissions() -> Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.json");
+        let config = Config::new_for_test("test", "test", "test", "test");
+        config.save_to_file(&config_path)?;
+        fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600))?;
+
+        config.save_to_file(&config_path)?;
+        let mode = fs::metadata(&config_path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        Ok(())
+    }
+
     #[test]
     fn test_save_creates_parent_directories() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -902,7 +2341,7 @@ mod tests {
 
         // Set HOME to a directory without config file
         unsafe { std::env::set_var("HOME", temp_dir.path()); }
-        std::env::remove_var("OPENAI_API_KEY");
+        unsafe { std::env::remove_var("OPENAI_API_KEY"); }
 
         let config = Config::load_or_default()?;
 
@@ -962,6 +2401,118 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_model_aliases() -> Result<()> {
+        let mut config = Config::new_for_test("openai", "gpt-4", "https://openai", "key");
+        config
+            .model_aliases
+            .insert("smart".to_string(), "anthropic/claude-sonnet".to_string());
+        config
+            .model_aliases
+            .insert("cheap".to_string(), "gpt-4o-mini".to_string());
+
+        assert_eq!(
+            config.resolve_model("smart"),
+            (Some("anthropic".to_string()), "claude-sonnet".to_string())
+        );
+        assert_eq!(config.resolve_model("cheap"), (None, "gpt-4o-mini".to_string()));
+        // Not a provider: a model ID with a slash
+        assert_eq!(
+            config.resolve_model("meta-llama/llama-3"),
+            (None, "meta-llama/llama-3".to_string())
+        );
+
+        config.use_model("cheap")?;
+        assert_eq!(config.active_provider, "openai");
+        assert_eq!(config.get_model(), "gpt-4o-mini");
+        config.use_model("smart")?;
+        assert_eq!(config.active_provider, "anthropic");
+        assert_eq!(config.get_model(), "claude-sonnet");
+        Ok(())
+    }
+
+    #[test]
+    fn test_profiles() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config_path = temp_dir.path().join("config.json");
+
+        let mut config = Config::new_for_test("openai", "gpt-4", "https://openai", "personal-key");
+        config.profiles.insert(
+            "work".to_string(),
+            ProfileConfig {
+                provider: Some("anthropic".to_string()),
+                model: Some("claude-work".to_string()),
+                api_key: Some("work-key".to_string()),
+                api_url: None,
+            },
+        );
+        assert!(config.use_profile(Some("missing")).is_err());
+
+        config.use_profile(Some("work"))?;
+        assert_eq!(config.active_profile.as_deref(), Some("work"));
+        assert_eq!(config.active_provider, "anthropic");
+        assert_eq!(config.get_model(), "claude-work");
+        assert_eq!(config.get_api_key(), "work-key");
+
+        // Changes made under a profile are saved into the profile
+        config.set_model("claude-next");
+        config.save_to_file(&config_path)?;
+        let saved = Config::load_from_file(&config_path)?;
+        assert_eq!(saved.active_provider, "openai");
+        assert_eq!(saved.get_api_key(), "personal-key");
+        assert!(!saved.providers.contains_key("anthropic"));
+        assert_eq!(
+            saved.profiles["work"].model.as_deref(),
+            Some("claude-next")
+        );
+
+        config.use_profile(None)?;
+        assert_eq!(config.active_profile, None);
+        assert_eq!(config.active_provider, "openai");
+        assert_eq!(config.get_model(), "gpt-4");
+        assert_eq!(config.get_api_key(), "personal-key");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_env_references() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(
+            temp_dir.path().join(".env"),
+            "ARULA_TEST_DOTENV_KEY=sk-from-dotenv\n",
+        )?;
+
+        let mut config = Config::new_for_test(
+            "openai",
+            "gpt-4",
+            "${ARULA_TEST_UNSET_HOST:-https://api.openai.com/v1}",
+            "${ARULA_TEST_DOTENV_KEY}",
+        );
+        config.providers.insert(
+            "other".to_string(),
+            ProviderConfig {
+                api_key: "${ARULA_TEST_UNSET_KEY}".to_string(),
+                ..config.providers["openai"].clone()
+            },
+        );
+        config.expand_env_references(&EnvSource::load(temp_dir.path()));
+
+        assert_eq!(config.get_api_key(), "sk-from-dotenv");
+        assert_eq!(config.get_api_url(), "https://api.openai.com/v1");
+        assert_eq!(config.providers["other"].api_key, "");
+        assert_eq!(config.env_errors.len(), 1);
+        assert!(config.env_errors[0].contains("providers.other.api_key"));
+        assert!(config.env_errors[0].contains("${ARULA_TEST_UNSET_KEY}"));
+
+        // References, not resolved values, are written back
+        let saved = config.persisted();
+        assert_eq!(saved.providers["openai"].api_key, "${ARULA_TEST_DOTENV_KEY}");
+        assert_eq!(saved.providers["other"].api_key, "${ARULA_TEST_UNSET_KEY}");
+
+        Ok(())
+    }
+
     #[test]
     fn test_ai_config_methods() {
         let ai_config = AiConfig {
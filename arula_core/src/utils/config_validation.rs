@@ -0,0 +1,853 @@
+//! config.json validation
+//!
+//! Checks the raw config file against the fields `Config` understands and
+//! reports problems with the line and field they occur at: unknown keys
+//! (usually typos, which serde silently ignores), values of the wrong type,
+//! missing required provider fields, invalid URLs and references to
+//! providers that don't exist.
+//!
+//! Runs on every load, and on its own with `arula config validate`.
+
+use crate::utils::env_expand::has_reference;
+use crate::utils::style_packs::{AppearanceConfig, DEFAULT_PROGRESS, DEFAULT_SPINNER};
+use crate::utils::themes::{DEFAULT_THEME, SYSTEM_THEME, Theme, available_themes};
+use reqwest::Url;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// How serious a config problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The config won't load, or a setting can't work as written
+    Error,
+    /// Likely a mistake, but the config still loads
+    Warning,
+}
+
+/// A problem found in the config file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Field path, e.g. `providers.openai.api_url` (empty for the whole file)
+    pub field: String,
+    /// 1-based line in the file, when known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    /// The issue with its location, e.g. `line 7: providers.openai.api_url: ...`
+    pub fn describe(&self) -> String {
+        let mut text = String::new();
+        if let Some(line) = self.line {
+            text.push_str(&format!("line {}: ", line));
+        }
+        if !self.field.is_empty() {
+            text.push_str(&format!("{}: ", self.field));
+        }
+        text.push_str(&self.message);
+        text
+    }
+}
+
+/// Expected shape of a config value
+enum Kind {
+    String,
+    Bool,
+    Integer,
+    /// An http(s) or ws(s) URL
+    Url,
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+    /// An object with known fields
+    Object(&'static [Field]),
+    /// An object with arbitrary keys and values of one kind
+    Map(&'static Kind),
+    /// A list of values of one kind
+    List(&'static Kind),
+    /// A number between two bounds (inclusive)
+    Range(f64, f64),
+}
+
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    required: bool,
+}
+
+const fn field(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: false,
+    }
+}
+
+const fn required(name: &'static str, kind: Kind) -> Field {
+    Field {
+        name,
+        kind,
+        required: true,
+    }
+}
+
+const HOOK_FIELDS: &[Field] = &[
+    field("session_start", Kind::List(&Kind::String)),
+    field("tool_executed", Kind::List(&Kind::String)),
+    field("run_finished", Kind::List(&Kind::String)),
+    field("approval_required", Kind::List(&Kind::String)),
+];
+
+const PROVIDER_FIELDS: &[Field] = &[
+    required("model", Kind::String),
+    field("api_url", Kind::Url),
+    required("api_key", Kind::String),
+    field("thinking_enabled", Kind::Bool),
+    field("max_retries", Kind::Integer),
+    field("timeout_seconds", Kind::Integer),
+    field("enable_usage_tracking", Kind::Bool),
+    field("web_search_enabled", Kind::Bool),
+    field("streaming", Kind::Bool),
+    field("tools_enabled", Kind::Bool),
+    field("temperature", Kind::Range(0.0, 2.0)),
+    field("top_p", Kind::Range(0.0, 1.0)),
+    field("max_tokens", Kind::Integer),
+    field("hooks", Kind::Object(HOOK_FIELDS)),
+];
+
+const TRANSFORM_FIELDS: &[Field] = &[
+    required("pattern", Kind::String),
+    required("replace", Kind::String),
+];
+
+const POSTPROCESS_FIELDS: &[Field] = &[
+    field("strip_watermarks", Kind::Bool),
+    field("normalize_headings", Kind::Bool),
+    field("issue_url", Kind::Url),
+    field("transforms", Kind::List(&Kind::Object(TRANSFORM_FIELDS))),
+];
+
+const INJECTION_GUARD_FIELDS: &[Field] = &[
+    field("enabled", Kind::Bool),
+    field("strict", Kind::Bool),
+    field("model_check", Kind::Bool),
+    field("tools", Kind::List(&Kind::String)),
+];
+
+const TRAY_FIELDS: &[Field] = &[
+    field("enabled", Kind::Bool),
+    field("hotkey", Kind::String),
+];
+
+const CONTEXT_FIELDS: &[Field] = &[
+    field("git_enrichment", Kind::Bool),
+    field("verify_references", Kind::Bool),
+    field("manifest_auto_refresh", Kind::Bool),
+    field("lint_code_blocks", Kind::Bool),
+    field("memory", Kind::Bool),
+    field("memory_max_chars", Kind::Integer),
+];
+
+const SPINNER_FIELDS: &[Field] = &[
+    required("frames", Kind::List(&Kind::String)),
+    field("interval_ms", Kind::Integer),
+];
+
+const PROGRESS_STYLE_FIELDS: &[Field] = &[required("chars", Kind::String)];
+
+const APPEARANCE_FIELDS: &[Field] = &[
+    field("spinner", Kind::String),
+    field("progress", Kind::String),
+    field("code_theme", Kind::String),
+    field("theme", Kind::String),
+    field("light_theme", Kind::String),
+    field("dark_theme", Kind::String),
+    field("spinners", Kind::Map(&Kind::Object(SPINNER_FIELDS))),
+    field(
+        "progress_styles",
+        Kind::Map(&Kind::Object(PROGRESS_STYLE_FIELDS)),
+    ),
+];
+
+const PACKS_FIELDS: &[Field] = &[
+    field("index_url", Kind::Url),
+    field("trusted_keys", Kind::List(&Kind::String)),
+];
+
+const UPDATES_FIELDS: &[Field] = &[
+    field("check_on_startup", Kind::Bool),
+    field("repo", Kind::String),
+];
+
+const LOG_LEVELS: &[&str] = &["debug", "info", "warn", "error"];
+
+const LOGGING_FIELDS: &[Field] = &[
+    field("level", Kind::Choice(LOG_LEVELS)),
+    field("modules", Kind::Map(&Kind::Choice(LOG_LEVELS))),
+    field("max_file_mb", Kind::Integer),
+    field("max_files", Kind::Integer),
+];
+
+const TELEMETRY_FIELDS: &[Field] = &[
+    field("enabled", Kind::Bool),
+    field("endpoint", Kind::Url),
+    field("service_name", Kind::String),
+    field("headers", Kind::Map(&Kind::String)),
+    field("sample_ratio", Kind::Range(0.0, 1.0)),
+];
+
+const SYNC_FIELDS: &[Field] = &[
+    required("backend", Kind::Choice(&["folder", "webdav", "s3", "git"])),
+    field("url", Kind::String),
+    field("path", Kind::String),
+    field("username", Kind::String),
+    field("password", Kind::String),
+    field("bucket", Kind::String),
+    field("region", Kind::String),
+    field("prefix", Kind::String),
+    field("access_key", Kind::String),
+    field("secret_key", Kind::String),
+    field("branch", Kind::String),
+    field(
+        "conflict",
+        Kind::Choice(&["newest", "local", "remote", "keep_both"]),
+    ),
+    field("sessions", Kind::Bool),
+];
+
+const MODEL_FIELDS: &[Field] = &[
+    field("context_window", Kind::Integer),
+    field("vision", Kind::Bool),
+    field("tools", Kind::Bool),
+    field("streaming", Kind::Bool),
+    field("input_price", Kind::Range(0.0, 1_000_000.0)),
+    field("output_price", Kind::Range(0.0, 1_000_000.0)),
+];
+
+const MCP_SERVER_FIELDS: &[Field] = &[
+    required("url", Kind::Url),
+    field("headers", Kind::Map(&Kind::String)),
+    field("timeout", Kind::Integer),
+    field("retries", Kind::Integer),
+];
+
+const PROFILE_FIELDS: &[Field] = &[
+    field("provider", Kind::String),
+    field("model", Kind::String),
+    field("api_key", Kind::String),
+    field("api_url", Kind::Url),
+];
+
+const LEGACY_AI_FIELDS: &[Field] = &[
+    required("provider", Kind::String),
+    required("model", Kind::String),
+    required("api_url", Kind::Url),
+    required("api_key", Kind::String),
+];
+
+const CONFIG_FIELDS: &[Field] = &[
+    required("active_provider", Kind::String),
+    required("providers", Kind::Map(&Kind::Object(PROVIDER_FIELDS))),
+    field("mcpServers", Kind::Map(&Kind::Object(MCP_SERVER_FIELDS))),
+    field("living_background_enabled", Kind::Bool),
+    field("show_timestamps", Kind::Bool),
+    field("explain_commands", Kind::Bool),
+    field("strict_tool_arguments", Kind::Bool),
+    field("icons", Kind::Choice(&["emoji", "nerd", "unicode", "ascii"])),
+    field("plain", Kind::Bool),
+    field("session_brief", Kind::Bool),
+    field("mouse", Kind::Bool),
+    field("context", Kind::Object(CONTEXT_FIELDS)),
+    field("appearance", Kind::Object(APPEARANCE_FIELDS)),
+    field("hooks", Kind::Object(HOOK_FIELDS)),
+    field("postprocess", Kind::Object(POSTPROCESS_FIELDS)),
+    field("injection_guard", Kind::Object(INJECTION_GUARD_FIELDS)),
+    field("tray", Kind::Object(TRAY_FIELDS)),
+    field("packs", Kind::Object(PACKS_FIELDS)),
+    field("sync", Kind::Object(SYNC_FIELDS)),
+    field("updates", Kind::Object(UPDATES_FIELDS)),
+    field("logging", Kind::Object(LOGGING_FIELDS)),
+    field("telemetry", Kind::Object(TELEMETRY_FIELDS)),
+    field(
+        "key_storage",
+        Kind::Choice(&["keychain", "file", "plaintext"]),
+    ),
+    field("profiles", Kind::Map(&Kind::Object(PROFILE_FIELDS))),
+    field("models", Kind::Map(&Kind::Object(MODEL_FIELDS))),
+    field("model_aliases", Kind::Map(&Kind::String)),
+    field("ai", Kind::Object(LEGACY_AI_FIELDS)),
+];
+
+/// Providers with a built-in default endpoint
+const KNOWN_PROVIDERS: &[&str] = &[
+    "openai",
+    "anthropic",
+    "ollama",
+    "openrouter",
+    "z.ai coding plan",
+    "z.ai",
+    "zai",
+];
+
+/// Whether `name` is a provider with a built-in default endpoint
+pub fn is_known_provider(name: &str) -> bool {
+    KNOWN_PROVIDERS.contains(&name)
+}
+
+/// Validate config.json content, returning every problem found
+pub fn validate_config(content: &str) -> Vec<ConfigIssue> {
+    let root: Value = match serde_json::from_str(content) {
+        Ok(root) => root,
+        Err(e) => {
+            return vec![ConfigIssue {
+                severity: Severity::Error,
+                field: String::new(),
+                line: Some(e.line()),
+                message: format!("invalid JSON: {}", e),
+            }];
+        }
+    };
+
+    let mut validator = Validator {
+        lines: key_lines(content),
+        issues: Vec::new(),
+    };
+    validator.check(&root, &Kind::Object(CONFIG_FIELDS), "");
+    validator.check_references(&root);
+    validator.check_appearance(&root);
+
+    // Anything the schema missed still has to deserialize
+    if !validator.has_errors()
+        && let Err(e) = serde_json::from_str::<crate::utils::config::Config>(content)
+    {
+        validator.issues.push(ConfigIssue {
+            severity: Severity::Error,
+            field: String::new(),
+            line: Some(e.line()),
+            message: e.to_string(),
+        });
+    }
+    validator.issues
+}
+
+/// Validate a config file on disk
+pub fn validate_config_file(path: &Path) -> anyhow::Result<Vec<ConfigIssue>> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Can't read {}: {}", path.display(), e))?;
+    Ok(validate_config(&content))
+}
+
+struct Validator {
+    lines: HashMap<String, usize>,
+    issues: Vec<ConfigIssue>,
+}
+
+impl Validator {
+    fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    fn report(&mut self, severity: Severity, field: &str, message: String) {
+        self.issues.push(ConfigIssue {
+            severity,
+            field: field.to_string(),
+            line: self.line_of(field),
+            message,
+        });
+    }
+
+    /// Line of a field, or of its closest parent that has one
+    fn line_of(&self, field: &str) -> Option<usize> {
+        let mut path = field;
+        loop {
+            if let Some(line) = self.lines.get(path) {
+                return Some(*line);
+            }
+            path = &path[..path.rfind('.')?];
+        }
+    }
+
+    fn check(&mut self, value: &Value, kind: &Kind, path: &str) {
+        match (kind, value) {
+            (Kind::String, Value::String(_)) | (Kind::Bool, Value::Bool(_)) => {}
+            (Kind::Integer, Value::Number(n)) if n.is_u64() => {}
+            (Kind::Integer, Value::Number(_)) => self.report(
+                Severity::Error,
+                path,
+                format!("expected a whole number of 0 or more, found {}", value),
+            ),
+            (Kind::Range(min, max), Value::Number(n)) => {
+                if !n.as_f64().is_some_and(|n| (*min..=*max).contains(&n)) {
+                    self.report(
+                        Severity::Error,
+                        path,
+                        format!("expected a number from {} to {}, found {}", min, max, n),
+                    );
+                }
+            }
+            (Kind::Url, Value::String(url)) => {
+                if let Err(reason) = check_url(url) {
+                    self.report(
+                        Severity::Error,
+                        path,
+                        format!("invalid URL \"{}\": {}", url, reason),
+                    );
+                }
+            }
+            (Kind::Choice(choices), Value::String(choice)) => {
+                if !choices.contains(&choice.as_str()) {
+                    self.report(
+                        Severity::Error,
+                        path,
+                        format!(
+                            "\"{}\" is not one of {}",
+                            choice,
+                            choices
+                                .iter()
+                                .map(|c| format!("\"{}\"", c))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    );
+                }
+            }
+            (Kind::Object(fields), Value::Object(map)) => {
+                for (key, child) in map {
+                    let child_path = join(path, key);
+                    match fields.iter().find(|f| f.name == key) {
+                        // Unset optional fields may be written as null
+                        Some(f) if child.is_null() && !f.required => {}
+                        Some(f) => self.check(child, &f.kind, &child_path),
+                        None => {
+                            let hint = closest(key, fields.iter().map(|f| f.name))
+                                .map(|name| format!(" (did you mean \"{}\"?)", name))
+                                .unwrap_or_default();
+                            self.report(
+                                Severity::Warning,
+                                &child_path,
+                                format!("unknown key, ignored{}", hint),
+                            );
+                        }
+                    }
+                }
+                for f in fields.iter().filter(|f| f.required) {
+                    if !map.contains_key(f.name) {
+                        self.report(
+                            Severity::Error,
+                            path,
+                            format!("missing required field \"{}\"", f.name),
+                        );
+                    }
+                }
+            }
+            (Kind::Map(inner), Value::Object(map)) => {
+                for (key, child) in map {
+                    self.check(child, inner, &join(path, key));
+                }
+            }
+            (Kind::List(inner), Value::Array(items)) => {
+                for (index, item) in items.iter().enumerate() {
+                    self.check(item, inner, &join(path, &index.to_string()));
+                }
+            }
+            (kind, value) => self.report(
+                Severity::Error,
+                path,
+                format!(
+                    "expected {}, found {}",
+                    kind.describe(),
+                    describe_value(value)
+                ),
+            ),
+        }
+    }
+
+    /// Cross-field checks: providers named elsewhere must exist
+    fn check_references(&mut self, root: &Value) {
+        let Some(providers) = root.get("providers").and_then(Value::as_object) else {
+            return;
+        };
+        let provider_names: Vec<&str> = providers.keys().map(String::as_str).collect();
+
+        if let Some(active) = root.get("active_provider").and_then(Value::as_str)
+            && !providers.contains_key(active)
+            && root.get("ai").is_none()
+        {
+            self.report(
+                Severity::Error,
+                "active_provider",
+                unknown_provider(active, &provider_names),
+            );
+        }
+
+        if let Some(profiles) = root.get("profiles").and_then(Value::as_object) {
+            for (name, profile) in profiles {
+                if let Some(provider) = profile.get("provider").and_then(Value::as_str)
+                    && !providers.contains_key(provider)
+                {
+                    self.report(
+                        Severity::Error,
+                        &format!("profiles.{}.provider", name),
+                        unknown_provider(provider, &provider_names),
+                    );
+                }
+            }
+        }
+
+        for (name, provider) in providers {
+            if !KNOWN_PROVIDERS.contains(&name.to_lowercase().as_str())
+                && provider.get("api_url").is_none_or(Value::is_null)
+            {
+                self.report(
+                    Severity::Warning,
+                    &format!("providers.{}", name),
+                    "custom provider has no \"api_url\"; requests go to http://localhost:8080"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    /// Warn about active style packs that don't exist (the default is used)
+    fn check_appearance(&mut self, root: &Value) {
+        let Some(appearance) = root
+            .get("appearance")
+            .and_then(|v| serde_json::from_value::<AppearanceConfig>(v.clone()).ok())
+        else {
+            return;
+        };
+
+        if let Some(name) = &appearance.spinner
+            && appearance.find_spinner(name).is_none()
+        {
+            let names: Vec<String> = appearance
+                .spinner_packs()
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect();
+            self.report(
+                Severity::Warning,
+                "appearance.spinner",
+                unknown_pack("spinner", name, &names, DEFAULT_SPINNER),
+            );
+        }
+        if let Some(name) = &appearance.progress
+            && appearance.find_progress_style(name).is_none()
+        {
+            let names: Vec<String> = appearance
+                .progress_styles()
+                .into_iter()
+                .map(|(n, _)| n)
+                .collect();
+            self.report(
+                Severity::Warning,
+                "appearance.progress",
+                unknown_pack("progress style", name, &names, DEFAULT_PROGRESS),
+            );
+        }
+        let themes = [
+            ("appearance.theme", &appearance.theme),
+            ("appearance.light_theme", &appearance.light_theme),
+            ("appearance.dark_theme", &appearance.dark_theme),
+        ];
+        for (path, name) in themes {
+            let Some(name) = name else { continue };
+            if path == "appearance.theme" && name.eq_ignore_ascii_case(SYSTEM_THEME) {
+                continue;
+            }
+            if let Err(e) = Theme::load(name) {
+                let message = if available_themes().contains(&name.to_lowercase()) {
+                    format!("{:#}; using \"{}\"", e, DEFAULT_THEME)
+                } else {
+                    unknown_pack("theme", name, &available_themes(), DEFAULT_THEME)
+                };
+                self.report(Severity::Warning, path, message);
+            }
+        }
+    }
+}
+
+impl Kind {
+    fn describe(&self) -> &'static str {
+        match self {
+            Kind::String | Kind::Choice(_) => "a string",
+            Kind::Bool => "true or false",
+            Kind::Integer | Kind::Range(..) => "a number",
+            Kind::Url => "a URL string",
+            Kind::Object(_) | Kind::Map(_) => "an object",
+            Kind::List(_) => "a list",
+        }
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => format!("the number {}", n),
+        Value::String(s) => format!("the string \"{}\"", s),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+fn unknown_pack(kind: &str, name: &str, names: &[String], default: &str) -> String {
+    let hint = closest(name, names.iter().map(String::as_str))
+        .map(|n| format!(" (did you mean \"{}\"?)", n))
+        .unwrap_or_default();
+    format!("unknown {} \"{}\"{}; using \"{}\"", kind, name, hint, default)
+}
+
+fn unknown_provider(name: &str, providers: &[&str]) -> String {
+    let hint = closest(name, providers.iter().copied())
+        .map(|p| format!(" (did you mean \"{}\"?)", p))
+        .unwrap_or_else(|| format!(" (configured: {})", providers.join(", ")));
+    format!(
+        "provider \"{}\" is not configured in \"providers\"{}",
+        name, hint
+    )
+}
+
+/// Why a URL can't be used, if it can't
+fn check_url(url: &str) -> Result<(), String> {
+    // Resolved from the environment at load time
+    if has_reference(url) {
+        return Ok(());
+    }
+    let parsed = Url::parse(url).map_err(|e| e.to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https" | "ws" | "wss") {
+        return Err(format!("unsupported scheme \"{}\"", parsed.scheme()));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err("missing host".to_string());
+    }
+    Ok(())
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// The candidate closest to `name`, if it's a plausible typo of it
+fn closest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    candidates
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), c))
+        .filter(|(distance, c)| *distance > 0 && *distance <= (c.len() / 3).clamp(1, 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Map each object key's dotted path to the line it's written on
+///
+/// Expects valid JSON; array elements are addressed by index.
+fn key_lines(content: &str) -> HashMap<String, usize> {
+    enum Frame {
+        Object(Option<String>),
+        Array(usize),
+    }
+
+    let mut lines = HashMap::new();
+    let mut stack: Vec<(String, Frame)> = Vec::new();
+    let mut line = 1;
+    let mut chars = content.chars().peekable();
+    // Path of the value about to be read
+    let mut pending = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '{' => stack.push((std::mem::take(&mut pending), Frame::Object(None))),
+            '[' => {
+                let path = std::mem::take(&mut pending);
+                pending = join(&path, "0");
+                stack.push((path, Frame::Array(0)));
+            }
+            '}' | ']' => {
+                stack.pop();
+            }
+            ',' => match stack.last_mut() {
+                Some((path, Frame::Array(index))) => {
+                    *index += 1;
+                    pending = join(path, &index.to_string());
+                }
+                Some((_, Frame::Object(key))) => *key = None,
+                None => {}
+            },
+            '"' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                text.push(escaped);
+                            }
+                        }
+                        '\n' => line += 1,
+                        c => text.push(c),
+                    }
+                }
+                if let Some((path, Frame::Object(key @ None))) = stack.last_mut() {
+                    let key_path = join(path, &text);
+                    lines.entry(key_path.clone()).or_insert(line);
+                    pending = key_path;
+                    *key = Some(text);
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{
+  "active_provider": "openai",
+  "providers": {
+    "openai": {
+      "model": "gpt-4o",
+      "api_url": "https://api.openai.com/v1",
+      "api_key": ""
+    }
+  },
+  "context": { "lint_code_blocks": true }
+}"#;
+
+    #[test]
+    fn test_valid_config() {
+        assert_eq!(validate_config(VALID), Vec::new());
+        assert_eq!(
+            validate_config(&VALID.replace("https://api.openai.com/v1", "${OPENAI_URL}")),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_reports_issues_with_lines() {
+        let content = r#"{
+  "active_provider": "opnai",
+  "providers": {
+    "openai": {
+      "model": "gpt-4o",
+      "api_url": "htps//api.openai.com",
+      "max_retries": "3",
+      "temperature": 3
+    }
+  },
+  "contxt": {}
+}"#;
+        let issues = validate_config(content);
+        let find = |field: &str| issues.iter().find(|i| i.field == field).unwrap();
+
+        let typo = find("contxt");
+        assert_eq!(typo.severity, Severity::Warning);
+        assert_eq!(typo.line, Some(11));
+        assert!(typo.message.contains("did you mean \"context\""));
+
+        assert_eq!(find("providers.openai.api_url").line, Some(6));
+        let retries = find("providers.openai.max_retries");
+        assert_eq!(
+            retries.describe(),
+            "line 7: providers.openai.max_retries: expected a number, found the string \"3\""
+        );
+
+        assert_eq!(
+            find("providers.openai.temperature").message,
+            "expected a number from 0 to 2, found 3"
+        );
+
+        let missing = find("providers.openai");
+        assert!(missing.message.contains("\"api_key\""));
+        assert_eq!(missing.line, Some(4));
+
+        assert!(
+            find("active_provider")
+                .message
+                .contains("did you mean \"openai\"")
+        );
+    }
+
+    #[test]
+    fn test_appearance_packs() {
+        let content = r#"{
+  "active_provider": "openai",
+  "providers": { "openai": { "model": "gpt-4o", "api_key": "sk-test" } },
+  "appearance": {
+    "spinner": "mooon",
+    "spinners": { "bars": { "frames": ["▁", "▃", 5] } }
+  }
+}"#;
+        let issues = validate_config(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "appearance.spinners.bars.frames.2");
+        assert_eq!(issues[0].line, Some(6));
+
+        let issues = validate_config(&content.replace(", 5]", "]"));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert!(issues[0].message.contains("did you mean \"moon\""));
+    }
+
+    #[test]
+    fn test_appearance_themes() {
+        let content = r#"{
+  "active_provider": "openai",
+  "providers": { "openai": { "model": "gpt-4o", "api_key": "sk-test" } },
+  "appearance": { "theme": "system", "light_theme": "solarised", "dark_theme": "black" }
+}"#;
+        let issues = validate_config(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "appearance.light_theme");
+        assert!(issues[0].message.contains("did you mean \"solarized\""));
+    }
+
+    #[test]
+    fn test_hooks() {
+        let content = r#"{
+  "active_provider": "openai",
+  "providers": {
+    "openai": {
+      "model": "gpt-4o",
+      "api_key": "sk-test",
+      "hooks": { "run_finished": ["notify-send done"] }
+    }
+  },
+  "hooks": { "tool_executd": ["cat >> tools.log"] }
+}"#;
+        let issues = validate_config(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "hooks.tool_executd");
+        assert!(issues[0].message.contains("did you mean \"tool_executed\""));
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let issues = validate_config("{\n  \"active_provider\": \"openai\",\n}");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(3));
+        assert!(issues[0].message.starts_with("invalid JSON"));
+    }
+}
@@ -0,0 +1,311 @@
+//! config.json hot-reload
+//!
+//! Watches the config file and hands a freshly loaded `Config` to the UI
+//! when it changes on disk, so edits to the model, provider or display
+//! settings apply without a restart.
+//!
+//! Editors often save in several steps (truncate, write, rename), so reloads
+//! wait for a quiet period first. Content that still fails validation after
+//! that is treated as a partial write and retried once before the change is
+//! rejected; the running config stays in place until a valid file appears.
+
+use crate::utils::config::Config;
+use crate::utils::config_validation::{ConfigIssue, Severity, validate_config};
+use crate::utils::style_packs::{DEFAULT_PROGRESS, DEFAULT_SPINNER};
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Quiet period before reloading, so save bursts reload once
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Result of a config file change
+#[derive(Debug)]
+pub enum ConfigReload {
+    /// The file changed and loaded cleanly
+    Loaded(Box<Config>),
+    /// The file changed but can't be used; the current config is kept
+    Rejected(String),
+}
+
+/// Watches config.json and reloads it when it changes
+///
+/// Watching stops when the watcher is dropped.
+pub struct ConfigWatcher {
+    rx: mpsc::Receiver<ConfigReload>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ConfigWatcher {
+    /// Start watching the config file at `path`
+    ///
+    /// The parent directory is watched rather than the file, so saves that
+    /// replace the file (write to temp, then rename) are seen too.
+    pub fn start(path: &Path) -> Result<Self> {
+        let path = path.to_path_buf();
+        let dir = path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let file_name = path.file_name().map(|n| n.to_os_string());
+
+        let (change_tx, change_rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && !matches!(event.kind, EventKind::Access(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|p| p.file_name().map(|n| n.to_os_string()) == file_name)
+                {
+                    let _ = change_tx.send(());
+                }
+            })
+            .context("Failed to create config watcher")?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut last = std::fs::read_to_string(&path).ok();
+            // Ends when the watcher (and with it the sender) is dropped
+            while change_rx.recv().is_ok() {
+                while change_rx.recv_timeout(DEBOUNCE).is_ok() {}
+                let Some(reload) = reload(&path, &mut last, &change_rx) else {
+                    continue;
+                };
+                if tx.send(reload).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            rx,
+            _watcher: watcher,
+        })
+    }
+
+    /// The next reload, if the file changed since the last call
+    pub fn try_recv(&self) -> Option<ConfigReload> {
+        self.rx.try_recv().ok()
+    }
+}
+
+/// Load the changed file, or `None` when its content is unchanged
+fn reload(
+    path: &Path,
+    last: &mut Option<String>,
+    changes: &mpsc::Receiver<()>,
+) -> Option<ConfigReload> {
+    let mut retried = false;
+    loop {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            // Removed or mid-rename; a later event brings the new file
+            Err(_) => return None,
+        };
+        if last.as_deref() == Some(content.as_str()) {
+            return None;
+        }
+
+        let errors: Vec<ConfigIssue> = validate_config(&content)
+            .into_iter()
+            .filter(|i| i.severity == Severity::Error)
+            .collect();
+        if errors.is_empty() {
+            *last = Some(content);
+            return Some(match Config::reload_from_file(path) {
+                Ok(config) => ConfigReload::Loaded(Box::new(config)),
+                Err(e) => ConfigReload::Rejected(e.to_string()),
+            });
+        }
+
+        // Possibly caught mid-write: give the editor another quiet period
+        if !retried {
+            retried = true;
+            std::thread::sleep(DEBOUNCE);
+            while changes.recv_timeout(DEBOUNCE).is_ok() {}
+            continue;
+        }
+        *last = Some(content);
+        return Some(ConfigReload::Rejected(
+            errors
+                .iter()
+                .map(ConfigIssue::describe)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ));
+    }
+}
+
+/// Whether the provider settings the agent client is built from differ
+pub fn provider_changed(old: &Config, new: &Config) -> bool {
+    old.active_provider != new.active_provider
+        || old.get_model() != new.get_model()
+        || old.get_api_url() != new.get_api_url()
+        || old.get_api_key() != new.get_api_key()
+        || old.get_streaming_enabled() != new.get_streaming_enabled()
+        || old.get_tools_enabled() != new.get_tools_enabled()
+        || old.get_thinking_enabled() != new.get_thinking_enabled()
+        || old.get_generation_settings() != new.get_generation_settings()
+}
+
+/// Human-readable list of the settings that differ between two configs
+pub fn describe_changes(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut compare = |name: &str, before: String, after: String| {
+        if before != after {
+            changes.push(format!("{} {} → {}", name, before, after));
+        }
+    };
+
+    compare(
+        "provider",
+        old.active_provider.clone(),
+        new.active_provider.clone(),
+    );
+    compare("model", old.get_model(), new.get_model());
+    compare("API URL", old.get_api_url(), new.get_api_url());
+    compare(
+        "streaming",
+        on_off(old.get_streaming_enabled()),
+        on_off(new.get_streaming_enabled()),
+    );
+    compare(
+        "tools",
+        on_off(old.get_tools_enabled()),
+        on_off(new.get_tools_enabled()),
+    );
+    compare(
+        "thinking",
+        on_off(old.get_thinking_enabled().unwrap_or(false)),
+        on_off(new.get_thinking_enabled().unwrap_or(false)),
+    );
+    let (old_gen, new_gen) = (old.get_generation_settings(), new.get_generation_settings());
+    compare(
+        "temperature",
+        or_default(old_gen.temperature),
+        or_default(new_gen.temperature),
+    );
+    compare("top_p", or_default(old_gen.top_p), or_default(new_gen.top_p));
+    compare(
+        "max_tokens",
+        or_default(old_gen.max_tokens),
+        or_default(new_gen.max_tokens),
+    );
+    compare(
+        "timestamps",
+        on_off(old.get_show_timestamps_enabled()),
+        on_off(new.get_show_timestamps_enabled()),
+    );
+    compare(
+        "living background",
+        on_off(old.get_living_background_enabled()),
+        on_off(new.get_living_background_enabled()),
+    );
+    compare(
+        "mouse",
+        on_off(old.get_mouse_enabled()),
+        on_off(new.get_mouse_enabled()),
+    );
+    compare(
+        "icons",
+        old.get_icon_set().to_string(),
+        new.get_icon_set().to_string(),
+    );
+    let (old_look, new_look) = (old.get_appearance(), new.get_appearance());
+    compare(
+        "spinner",
+        old_look.spinner.unwrap_or_else(|| DEFAULT_SPINNER.to_string()),
+        new_look.spinner.unwrap_or_else(|| DEFAULT_SPINNER.to_string()),
+    );
+    compare(
+        "progress style",
+        old_look.progress.unwrap_or_else(|| DEFAULT_PROGRESS.to_string()),
+        new_look.progress.unwrap_or_else(|| DEFAULT_PROGRESS.to_string()),
+    );
+    compare(
+        "code theme",
+        old_look.code_theme.unwrap_or_else(|| "auto".to_string()),
+        new_look.code_theme.unwrap_or_else(|| "auto".to_string()),
+    );
+    if old_look.spinners != new_look.spinners
+        || old_look.progress_styles != new_look.progress_styles
+    {
+        changes.push("style packs updated".to_string());
+    }
+    let provider_hooks = |config: &Config| {
+        config
+            .get_active_provider_config()
+            .and_then(|p| p.hooks.clone())
+    };
+    if old.hooks != new.hooks || provider_hooks(old) != provider_hooks(new) {
+        changes.push("hooks updated".to_string());
+    }
+
+    // Never show keys, only that one changed
+    if old.get_api_key() != new.get_api_key() {
+        changes.push("API key updated".to_string());
+    }
+    if old.get_mcp_server_names() != new.get_mcp_server_names() {
+        changes.push("MCP servers updated (applies to new sessions)".to_string());
+    }
+    changes
+}
+
+fn on_off(enabled: bool) -> String {
+    if enabled { "on" } else { "off" }.to_string()
+}
+
+fn or_default<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "default".to_string(), |v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_changes() {
+        let old = Config::new_for_test("openai", "gpt-4o", "https://api.openai.com/v1", "sk-a");
+        let mut new = old.clone();
+        assert!(describe_changes(&old, &new).is_empty());
+        assert!(!provider_changed(&old, &new));
+
+        new.set_model("gpt-4.1");
+        new.set_api_key("sk-b");
+        new.show_timestamps = Some(true);
+        let changes = describe_changes(&old, &new);
+        assert_eq!(
+            changes,
+            [
+                "model gpt-4o → gpt-4.1",
+                "timestamps off → on",
+                "API key updated"
+            ]
+        );
+        assert!(changes.iter().all(|c| !c.contains("sk-")));
+        assert!(provider_changed(&old, &new));
+    }
+
+    #[test]
+    fn test_reload_retries_then_rejects_invalid_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(&path, "{ \"active_provider\": ").unwrap();
+        let (_tx, changes) = mpsc::channel();
+
+        let mut last = None;
+        match reload(&path, &mut last, &changes) {
+            Some(ConfigReload::Rejected(reason)) => assert!(reason.contains("invalid JSON")),
+            other => panic!("unexpected reload: {:?}", other),
+        }
+        // The same broken content isn't reported twice
+        assert!(reload(&path, &mut last, &changes).is_none());
+    }
+}
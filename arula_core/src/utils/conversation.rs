@@ -3,7 +3,7 @@
 //! This module provides structures and utilities for saving and loading
 //! conversation history with AI, including messages, tool calls, and metadata.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -24,6 +24,9 @@ pub struct Conversation {
     pub messages: Vec<Message>,
     /// Usage statistics
     pub statistics: Statistics,
+    /// Named points the conversation can be branched from
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checkpoints: Vec<Checkpoint>,
 }
 
 /// Metadata about the conversation
@@ -46,6 +49,27 @@ pub struct ConversationMetadata {
     /// Optional tags for categorization
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Conversation this one was branched from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Where in the parent the branch starts (a checkpoint name or message number)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch_point: Option<String>,
+    /// Rolling "what we're doing" brief, written while the session is idle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brief: Option<String>,
+    /// Whether the TUI shows its tool activity panel for this conversation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_panel: Option<bool>,
+}
+
+/// A named point in a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub name: String,
+    /// Number of messages the conversation had when the checkpoint was made
+    pub message_count: usize,
+    pub created_at: DateTime<Utc>,
 }
 
 /// Snapshot of AI configuration at conversation start
@@ -154,6 +178,10 @@ impl Conversation {
                 model: model.clone(),
                 provider: provider.clone(),
                 tags: Vec::new(),
+                parent_id: None,
+                branch_point: None,
+                brief: None,
+                tool_panel: None,
             },
             config_snapshot: ConfigSnapshot {
                 provider,
@@ -176,6 +204,7 @@ impl Conversation {
                 tool_tokens: 0,
                 duration_seconds: 0,
             },
+            checkpoints: Vec::new(),
         }
     }
 
@@ -370,6 +399,87 @@ impl Conversation {
         self.metadata.updated_at = Utc::now();
     }
 
+    /// Set the rolling brief (doesn't count as an update)
+    pub fn set_brief(&mut self, brief: String) {
+        self.metadata.brief = Some(brief);
+    }
+
+    /// Remember whether the tool activity panel is shown (doesn't count as an update)
+    pub fn set_tool_panel(&mut self, shown: bool) {
+        self.metadata.tool_panel = Some(shown);
+    }
+
+    /// Title for exports: the brief's first sentence, else the title
+    pub fn export_title(&self) -> String {
+        match self.metadata.brief.as_deref() {
+            Some(brief) => crate::utils::brief::brief_title(brief),
+            None => self.metadata.title.clone(),
+        }
+    }
+
+    /// File name for a Markdown export, from the export title
+    pub fn export_file_name(&self) -> String {
+        let slug = self
+            .export_title()
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        if slug.is_empty() {
+            format!("{}.md", self.metadata.conversation_id)
+        } else {
+            format!("{}.md", slug)
+        }
+    }
+
+    /// The conversation as Markdown, titled and introduced by its brief
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.export_title());
+        out.push_str(&format!(
+            "_{} · {} · {}_\n\n",
+            self.metadata.created_at.format("%Y-%m-%d %H:%M UTC"),
+            self.metadata.provider,
+            self.metadata.model
+        ));
+        if let Some(brief) = &self.metadata.brief {
+            out.push_str(&format!("> {}\n\n", brief));
+        }
+        for message in &self.messages {
+            let heading = match message.role.as_str() {
+                "user" => "You",
+                "assistant" => "ARULA",
+                "tool" => "Tool result",
+                _ => continue,
+            };
+            let content = match &message.content {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(value) => format!(
+                    "```json\n{}\n```",
+                    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+                ),
+                None => String::new(),
+            };
+            let calls: Vec<String> = message
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| format!("- `{}` {}", call.name, call.arguments))
+                .collect();
+            if content.trim().is_empty() && calls.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("## {}\n\n", heading));
+            if !content.trim().is_empty() {
+                out.push_str(&format!("{}\n\n", content.trim()));
+            }
+            if !calls.is_empty() {
+                out.push_str(&format!("{}\n\n", calls.join("\n")));
+            }
+        }
+        out
+    }
+
     /// Add a tag to the conversation
     pub fn add_tag(&mut self, tag: String) {
         if !self.metadata.tags.contains(&tag) {
@@ -378,6 +488,100 @@ impl Conversation {
         }
     }
 
+    /// Add a checkpoint at the current message, named `cpN` when no name is given
+    pub fn add_checkpoint(&mut self, name: Option<&str>) -> Result<&Checkpoint> {
+        let name = match name.map(str::trim).filter(|n| !n.is_empty()) {
+            Some(name) => name.to_string(),
+            None => format!("cp{}", self.checkpoints.len() + 1),
+        };
+        if self.find_checkpoint(&name).is_some() {
+            bail!("Checkpoint '{}' already exists", name);
+        }
+
+        self.checkpoints.push(Checkpoint {
+            name,
+            message_count: self.messages.len(),
+            created_at: Utc::now(),
+        });
+        self.metadata.updated_at = Utc::now();
+        Ok(self.checkpoints.last().expect("checkpoint was just added"))
+    }
+
+    /// Look up a checkpoint by name (case-insensitive)
+    pub fn find_checkpoint(&self, name: &str) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .find(|cp| cp.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Start a new conversation from the messages up to a checkpoint
+    ///
+    /// The branch gets its own ID and records this conversation as its
+    /// parent; checkpoints before the branch point carry over.
+    pub fn branch_from(&self, checkpoint: &str) -> Result<Conversation> {
+        let Some(checkpoint) = self.find_checkpoint(checkpoint) else {
+            let known: Vec<&str> = self.checkpoints.iter().map(|cp| cp.name.as_str()).collect();
+            if known.is_empty() {
+                bail!("No checkpoints in this conversation (create one with /checkpoint)");
+            }
+            bail!(
+                "Unknown checkpoint '{}' (available: {})",
+                checkpoint,
+                known.join(", ")
+            );
+        };
+        Ok(self.branch_at(checkpoint.message_count, checkpoint.name.clone()))
+    }
+
+    /// Start a new conversation from the first `message_count` messages
+    pub fn branch_at(&self, message_count: usize, branch_point: String) -> Conversation {
+        let now = Utc::now();
+        let mut branch = self.clone();
+        branch.truncate(message_count);
+        branch.metadata.conversation_id = Self::generate_id();
+        branch.metadata.title = format!("Branch of {}", self.metadata.title);
+        branch.metadata.created_at = now;
+        branch.metadata.updated_at = now;
+        branch.metadata.parent_id = Some(self.metadata.conversation_id.clone());
+        branch.metadata.branch_point = Some(branch_point);
+        branch.add_tag("branch".to_string());
+        branch
+    }
+
+    /// Keep only the first `message_count` messages, dropping checkpoints
+    /// past that point
+    pub fn truncate(&mut self, message_count: usize) {
+        self.messages.truncate(message_count);
+        self.checkpoints.retain(|cp| cp.message_count <= message_count);
+        self.metadata.message_count = self.messages.len();
+        self.metadata.updated_at = Utc::now();
+        self.recount_statistics();
+    }
+
+    /// Recompute message and tool counts from the messages
+    fn recount_statistics(&mut self) {
+        let count = |role: &str| self.messages.iter().filter(|m| m.role == role).count();
+        let tool_results: Vec<&Message> =
+            self.messages.iter().filter(|m| m.role == "tool").collect();
+        let successful = tool_results
+            .iter()
+            .filter(|m| m.metadata.success == Some(true))
+            .count();
+
+        self.statistics.total_user_messages = count("user");
+        self.statistics.total_assistant_messages = count("assistant");
+        self.statistics.total_tool_calls = self
+            .messages
+            .iter()
+            .filter_map(|m| m.tool_calls.as_ref())
+            .map(Vec::len)
+            .sum();
+        self.statistics.total_tool_results = tool_results.len();
+        self.statistics.successful_tool_calls = successful;
+        self.statistics.failed_tool_calls = tool_results.len() - successful;
+        self.update_duration();
+    }
+
     /// Get the conversation file path
     pub fn get_file_path(&self, base_dir: &Path) -> PathBuf {
         base_dir
@@ -441,6 +645,13 @@ impl Conversation {
                             message_count: conv.metadata.message_count,
                             model: conv.metadata.model,
                             provider: conv.metadata.provider,
+                            parent_id: conv.metadata.parent_id,
+                            branch_point: conv.metadata.branch_point,
+                            checkpoints: conv
+                                .checkpoints
+                                .into_iter()
+                                .map(|cp| cp.name)
+                                .collect(),
                         });
                     }
                 }
@@ -486,6 +697,47 @@ pub struct ConversationSummary {
     pub message_count: usize,
     pub model: String,
     pub provider: String,
+    pub parent_id: Option<String>,
+    pub branch_point: Option<String>,
+    pub checkpoints: Vec<String>,
+}
+
+/// Arrange conversations into branch trees
+///
+/// Returns each conversation with its depth, parents before their branches.
+/// Roots keep the order they were given in; branches are ordered oldest
+/// first. A branch whose parent was deleted becomes a root.
+pub fn conversation_tree(summaries: &[ConversationSummary]) -> Vec<(usize, &ConversationSummary)> {
+    let is_known = |id: &str| summaries.iter().any(|s| s.conversation_id == id);
+    let mut tree = Vec::with_capacity(summaries.len());
+
+    fn visit<'a>(
+        node: &'a ConversationSummary,
+        depth: usize,
+        summaries: &'a [ConversationSummary],
+        tree: &mut Vec<(usize, &'a ConversationSummary)>,
+    ) {
+        tree.push((depth, node));
+        let mut children: Vec<&ConversationSummary> = summaries
+            .iter()
+            .filter(|s| s.parent_id.as_deref() == Some(node.conversation_id.as_str()))
+            .collect();
+        children.sort_by_key(|s| s.created_at);
+        for child in children {
+            // Guard against cycles in hand-edited files
+            if tree.iter().all(|(_, s)| s.conversation_id != child.conversation_id) {
+                visit(child, depth + 1, summaries, tree);
+            }
+        }
+    }
+
+    for root in summaries
+        .iter()
+        .filter(|s| s.parent_id.as_deref().is_none_or(|id| !is_known(id)))
+    {
+        visit(root, 0, summaries, &mut tree);
+    }
+    tree
 }
 
 #[cfg(test)]
@@ -549,4 +801,107 @@ mod tests {
         assert_eq!(conv.statistics.total_tool_calls, 1);
         assert_eq!(conv.statistics.successful_tool_calls, 1);
     }
+
+    #[test]
+    fn test_checkpoints_and_branching() {
+        let mut conv = Conversation::new(
+            "claude-sonnet-4-5".to_string(),
+            "anthropic".to_string(),
+            "https://api.anthropic.com/v1".to_string(),
+        );
+        conv.add_user_message("Write a parser".to_string());
+        conv.add_assistant_message("Here is one".to_string(), None);
+        assert_eq!(conv.add_checkpoint(None).unwrap().name, "cp1");
+        conv.add_user_message("Now make it faster".to_string());
+        conv.add_checkpoint(Some("fast")).unwrap();
+        assert!(conv.add_checkpoint(Some("FAST")).is_err());
+
+        let branch = conv.branch_from("cp1").unwrap();
+        assert_eq!(branch.messages.len(), 2);
+        assert_eq!(branch.statistics.total_user_messages, 1);
+        assert_eq!(branch.checkpoints.len(), 1);
+        assert_eq!(
+            branch.metadata.parent_id.as_deref(),
+            Some(conv.metadata.conversation_id.as_str())
+        );
+        assert_ne!(branch.metadata.conversation_id, conv.metadata.conversation_id);
+        assert!(conv.branch_from("missing").is_err());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut conv = Conversation::new(
+            "claude-sonnet-4-5".to_string(),
+            "anthropic".to_string(),
+            "https://api.anthropic.com/v1".to_string(),
+        );
+        conv.add_user_message("Write a parser".to_string());
+        conv.add_checkpoint(Some("start")).unwrap();
+        conv.add_assistant_message("Here is one".to_string(), None);
+        conv.add_checkpoint(Some("answered")).unwrap();
+
+        conv.truncate(1);
+        assert_eq!(conv.messages.len(), 1);
+        assert_eq!(conv.metadata.message_count, 1);
+        assert_eq!(conv.statistics.total_assistant_messages, 0);
+        assert!(conv.find_checkpoint("start").is_some());
+        assert!(conv.find_checkpoint("answered").is_none());
+    }
+
+    #[test]
+    fn test_markdown_export_uses_brief() {
+        let mut conv = Conversation::new(
+            "claude-sonnet-4-5".to_string(),
+            "anthropic".to_string(),
+            "https://api.anthropic.com/v1".to_string(),
+        );
+        conv.add_user_message("Write a parser".to_string());
+        conv.add_assistant_message("Here is one".to_string(), None);
+        assert_eq!(conv.export_title(), conv.metadata.title);
+
+        conv.set_brief("Writing a TOML parser. Error messages are next.".to_string());
+        let markdown = conv.to_markdown();
+        assert!(markdown.starts_with("# Writing a TOML parser\n\n"));
+        assert_eq!(conv.export_file_name(), "writing-a-toml-parser.md");
+        assert!(markdown.contains("> Writing a TOML parser. Error messages are next.\n\n"));
+        assert!(markdown.contains("## You\n\nWrite a parser\n\n## ARULA\n\nHere is one\n\n"));
+    }
+
+    #[test]
+    fn test_conversation_tree() {
+        let summary = |id: &str, parent: Option<&str>, minute: u32| ConversationSummary {
+            conversation_id: id.to_string(),
+            title: id.to_string(),
+            created_at: DateTime::from_timestamp(minute as i64 * 60, 0).unwrap(),
+            updated_at: DateTime::from_timestamp(minute as i64 * 60, 0).unwrap(),
+            message_count: 0,
+            model: String::new(),
+            provider: String::new(),
+            parent_id: parent.map(str::to_string),
+            branch_point: None,
+            checkpoints: Vec::new(),
+        };
+        let summaries = vec![
+            summary("late-branch", Some("root"), 3),
+            summary("root", None, 0),
+            summary("early-branch", Some("root"), 1),
+            summary("nested", Some("early-branch"), 2),
+            summary("orphan", Some("deleted"), 4),
+        ];
+
+        let tree: Vec<(usize, &str)> = conversation_tree(&summaries)
+            .into_iter()
+            .map(|(depth, s)| (depth, s.conversation_id.as_str()))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                (0, "root"),
+                (1, "early-branch"),
+                (2, "nested"),
+                (1, "late-branch"),
+                (0, "orphan"),
+            ]
+        );
+    }
 }
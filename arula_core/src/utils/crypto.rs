@@ -0,0 +1,135 @@
+//! Passphrase encryption and hashing shared by sync, packs, updates and
+//! the secret store
+//!
+//! Sync uploads, profile archives and the encrypted secrets file are sealed
+//! with ChaCha20-Poly1305 under a key derived from a passphrase with
+//! PBKDF2-HMAC-SHA256. The salt is stored next to the data; every sealed
+//! blob starts with its own random nonce.
+
+use anyhow::{Result, anyhow};
+use ring::aead::{Aad, CHACHA20_POLY1305, LessSafeKey, Nonce, UnboundKey};
+use ring::digest::{SHA256, digest};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::num::NonZeroU32;
+
+/// Length of the key derivation salt
+pub const SALT_LEN: usize = 16;
+/// Length of the nonce at the start of every sealed blob
+pub const NONCE_LEN: usize = ring::aead::NONCE_LEN;
+/// Length of a derived key
+pub const KEY_LEN: usize = 32;
+
+const PBKDF2_ITERATIONS: u32 = 210_000;
+
+/// Lowercase hex encoding of `bytes`
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hex-encoded SHA-256 digest of `data`
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(digest(&SHA256, data).as_ref())
+}
+
+/// `N` bytes from the system's secure random source
+pub fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("No secure random source"))?;
+    Ok(bytes)
+}
+
+/// A key derived from a passphrase
+pub struct Cipher {
+    key: LessSafeKey,
+}
+
+impl Cipher {
+    /// Derive the key for `passphrase` and `salt`
+    pub fn derive(passphrase: &str, salt: &[u8]) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(anyhow!("The passphrase is empty"));
+        }
+        Self::derive_from_secret(passphrase.as_bytes(), salt)
+    }
+
+    /// Derive the key for raw secret bytes, such as a random key file
+    pub fn derive_from_secret(secret: &[u8], salt: &[u8]) -> Result<Self> {
+        let mut key = [0u8; KEY_LEN];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+            salt,
+            secret,
+            &mut key,
+        );
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key)
+            .map_err(|_| anyhow!("Invalid encryption key"))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+        })
+    }
+
+    /// A fresh random salt
+    pub fn random_salt() -> Result<[u8; SALT_LEN]> {
+        random_bytes()
+    }
+
+    /// Encrypt `plaintext`, returning the nonce followed by the ciphertext
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce: [u8; NONCE_LEN] = random_bytes()?;
+        let mut data = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| anyhow!("Encryption failed"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&data);
+        Ok(sealed)
+    }
+
+    /// Decrypt a blob made by `seal`; fails on a wrong passphrase or
+    /// tampered data
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(anyhow!("Encrypted data is truncated"));
+        }
+        let (nonce, data) = sealed.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at NONCE_LEN");
+        let mut data = data.to_vec();
+        let plaintext = self
+            .key
+            .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+            .map_err(|_| anyhow!("Can't decrypt (wrong passphrase or corrupted data)"))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() -> Result<()> {
+        let salt = Cipher::random_salt()?;
+        let cipher = Cipher::derive("correct horse", &salt)?;
+        let sealed = cipher.seal(b"hello")?;
+        assert!(!sealed.windows(5).any(|w| w == b"hello"));
+        assert_eq!(cipher.open(&sealed)?, b"hello");
+
+        let wrong = Cipher::derive("battery staple", &salt)?;
+        assert!(wrong.open(&sealed).is_err());
+        assert!(cipher.open(&sealed[..4]).is_err());
+        assert!(Cipher::derive("", &salt).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
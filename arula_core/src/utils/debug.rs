@@ -56,14 +56,16 @@ pub fn reset_debug_state() {
 
 /// Debug print helper that checks ARULA_DEBUG environment variable
 ///
+/// Prints only with `ARULA_DEBUG=1`; the log file gets the line whenever
+/// the logger's level lets debug lines through.
 /// This is the function version for use when macros are not convenient.
 /// Prefer the `debug!` macro in most cases.
 #[inline]
 pub fn debug_print(msg: &str) {
     if is_debug_enabled() {
         println!("🔧 DEBUG: {}", msg);
-        crate::utils::logger::debug(msg);
     }
+    crate::utils::logger::debug(msg);
 }
 
 /// Debug print with module prefix
@@ -74,8 +76,12 @@ pub fn debug_print(msg: &str) {
 pub fn debug_print_module(module: &str, msg: &str) {
     if is_debug_enabled() {
         println!("🔧 [{}] {}", module, msg);
-        crate::utils::logger::debug(&format!("[{}] {}", module, msg));
     }
+    crate::utils::logger::log_module(
+        crate::utils::logger::LogLevel::Debug,
+        &module.to_lowercase(),
+        msg,
+    );
 }
 
 /// Log AI interaction details for debugging
@@ -0,0 +1,419 @@
+//! Weekly digest for `arula digest`
+//!
+//! Sums up a period from what ARULA keeps locally: the session index, the
+//! saved conversations (for the commands the agent ran), commits made with
+//! `/commit`, and token usage with its estimated cost. The result is
+//! Markdown to paste into a status report; nothing is sent anywhere.
+
+use crate::api::capabilities;
+use crate::storage::{CommitRecord, SessionRecord, Storage, UsageTotals};
+use crate::utils::conversation::Conversation;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Duration as ChronoDuration, Local, Utc};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Commands listed as most run
+const MAX_COMMANDS: usize = 10;
+
+/// The period given to `--since`: `7d`, `2w`, `12h` or `30m`
+pub fn parse_since(text: &str) -> Result<ChronoDuration> {
+    let text = text.trim();
+    let split = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (count, unit) = text.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| anyhow!("Invalid period '{}' (e.g. 7d, 2w, 12h)", text))?;
+    match unit {
+        "w" => Ok(ChronoDuration::weeks(count)),
+        "d" | "" => Ok(ChronoDuration::days(count)),
+        "h" => Ok(ChronoDuration::hours(count)),
+        "m" => Ok(ChronoDuration::minutes(count)),
+        _ => Err(anyhow!(
+            "Unknown unit '{}' in '{}' (use w, d, h or m)",
+            unit,
+            text
+        )),
+    }
+}
+
+/// Commands the agent ran in one period
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandStats {
+    pub total: usize,
+    pub failed: usize,
+    /// `git commit` runs that succeeded
+    pub commits: usize,
+    /// Commands by program and subcommand (`cargo test`), most run first
+    pub top: Vec<(String, usize)>,
+}
+
+impl CommandStats {
+    /// Add the `execute_bash` calls of `conversation` made since `since`
+    fn add(
+        &mut self,
+        conversation: &Conversation,
+        since: DateTime<Utc>,
+        counts: &mut HashMap<String, usize>,
+    ) {
+        let mut commands: HashMap<&str, String> = HashMap::new();
+        for message in &conversation.messages {
+            for call in message.tool_calls.iter().flatten() {
+                if call.name != "execute_bash" || call.timestamp < since {
+                    continue;
+                }
+                let Some(command) = serde_json::from_str::<serde_json::Value>(&call.arguments)
+                    .ok()
+                    .and_then(|args| args["command"].as_str().map(str::to_string))
+                else {
+                    continue;
+                };
+                self.total += 1;
+                *counts.entry(command_kind(&command)).or_default() += 1;
+                commands.insert(&call.id, command);
+            }
+            let Some(command) = message
+                .tool_call_id
+                .as_deref()
+                .and_then(|id| commands.get(id))
+            else {
+                continue;
+            };
+            match message.metadata.success {
+                Some(false) => self.failed += 1,
+                _ if command_kind(command) == "git commit" => self.commits += 1,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The program and its subcommand, if it has one: `cargo test`, `ls`
+fn command_kind(command: &str) -> String {
+    let mut words = command
+        .split_whitespace()
+        .skip_while(|word| word.contains('='));
+    let Some(program) = words.next() else {
+        return String::new();
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+    match words.next() {
+        Some(sub)
+            if sub.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !sub.starts_with('-') =>
+        {
+            format!("{} {}", program, sub)
+        }
+        _ => program.to_string(),
+    }
+}
+
+/// Tokens and cost of one model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelCost {
+    pub provider: String,
+    pub model: String,
+    pub responses: u64,
+    pub tokens: u64,
+    /// Estimated cost in USD, when the model's price is known
+    pub cost: Option<f64>,
+}
+
+impl From<UsageTotals> for ModelCost {
+    fn from(usage: UsageTotals) -> Self {
+        let cost = capabilities::lookup(&usage.model)
+            .and_then(|caps| caps.cost(usage.prompt_tokens, usage.completion_tokens));
+        Self {
+            provider: usage.provider,
+            model: usage.model,
+            responses: usage.responses,
+            tokens: usage.total_tokens,
+            cost,
+        }
+    }
+}
+
+/// Everything in a digest
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Sessions updated in the period, most recent first
+    pub sessions: Vec<SessionRecord>,
+    /// Commits made with `/commit`, oldest first
+    pub commits: Vec<CommitRecord>,
+    pub commands: CommandStats,
+    /// Usage by model, most tokens first
+    pub costs: Vec<ModelCost>,
+}
+
+impl Digest {
+    /// The digest of the time since `since`, from the local database
+    pub fn collect(since: DateTime<Utc>) -> Result<Self> {
+        Storage::with(|storage| Self::from_storage(storage, since))
+    }
+
+    pub fn from_storage(storage: &Storage, since: DateTime<Utc>) -> Result<Self> {
+        let sessions = storage.sessions_since(since)?;
+        let mut commands = CommandStats::default();
+        let mut counts = HashMap::new();
+        for session in &sessions {
+            // Sessions whose file is gone still count, without their commands
+            if let Ok(conversation) = Conversation::load(Path::new(&session.project), &session.id) {
+                commands.add(&conversation, since, &mut counts);
+            }
+        }
+        commands.top = counts
+            .into_iter()
+            .filter(|(kind, _)| !kind.is_empty())
+            .collect();
+        commands
+            .top
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        commands.top.truncate(MAX_COMMANDS);
+
+        Ok(Self {
+            since,
+            until: Utc::now(),
+            sessions,
+            commits: storage.commits_since(since)?,
+            commands,
+            costs: storage
+                .usage_totals(since)?
+                .into_iter()
+                .map(ModelCost::from)
+                .collect(),
+        })
+    }
+
+    /// Commits made with `/commit` and by the agent
+    pub fn commits_assisted(&self) -> usize {
+        self.commits.len() + self.commands.commits
+    }
+
+    /// The digest as Markdown
+    pub fn to_markdown(&self) -> String {
+        let day = |time: DateTime<Utc>| time.with_timezone(&Local).format("%Y-%m-%d").to_string();
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# ARULA digest: {} to {}\n",
+            day(self.since),
+            day(self.until)
+        );
+
+        let mut projects: Vec<&str> = self
+            .sessions
+            .iter()
+            .map(|s| project_name(&s.project))
+            .collect();
+        projects.sort_unstable();
+        projects.dedup();
+        let messages: u64 = self.sessions.iter().map(|s| s.message_count).sum();
+        let tokens: u64 = self.costs.iter().map(|c| c.tokens).sum();
+        let cost: f64 = self.costs.iter().filter_map(|c| c.cost).sum();
+        let unpriced = self.costs.iter().filter(|c| c.cost.is_none()).count();
+
+        let _ = writeln!(out, "## Summary\n");
+        let _ = writeln!(
+            out,
+            "- **Sessions:** {} in {} project(s), {} message(s)",
+            self.sessions.len(),
+            projects.len(),
+            messages
+        );
+        let _ = writeln!(
+            out,
+            "- **Commits assisted:** {} ({} with /commit, {} by the agent)",
+            self.commits_assisted(),
+            self.commits.len(),
+            self.commands.commits
+        );
+        let _ = writeln!(
+            out,
+            "- **Commands run:** {} ({} failed)",
+            self.commands.total, self.commands.failed
+        );
+        let _ = writeln!(out, "- **Tokens:** {}", group_thousands(tokens));
+        let _ = write!(out, "- **Estimated cost:** ${:.2}", cost);
+        if unpriced > 0 {
+            let _ = write!(
+                out,
+                " (not counting {} model(s) without a known price)",
+                unpriced
+            );
+        }
+        out.push('\n');
+
+        if !self.sessions.is_empty() {
+            let _ = writeln!(out, "\n## Sessions\n");
+            let _ = writeln!(
+                out,
+                "| Session | Project | Messages | Tool calls | Updated |"
+            );
+            let _ = writeln!(out, "|---|---|---:|---:|---|");
+            for session in &self.sessions {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    session.title.replace('|', "\\|"),
+                    project_name(&session.project),
+                    session.message_count,
+                    session.tool_calls,
+                    day(session.updated_at)
+                );
+            }
+        }
+
+        if !self.commits.is_empty() {
+            let _ = writeln!(out, "\n## Commits\n");
+            for commit in &self.commits {
+                let _ = writeln!(
+                    out,
+                    "- `{}` {} ({})",
+                    commit.hash,
+                    commit.subject,
+                    project_name(&commit.project)
+                );
+            }
+        }
+
+        if !self.commands.top.is_empty() {
+            let _ = writeln!(out, "\n## Most run commands\n");
+            for (kind, count) in &self.commands.top {
+                let _ = writeln!(out, "- `{}` × {}", kind, count);
+            }
+        }
+
+        if !self.costs.is_empty() {
+            let _ = writeln!(out, "\n## Usage by model\n");
+            let _ = writeln!(out, "| Model | Responses | Tokens | Cost |");
+            let _ = writeln!(out, "|---|---:|---:|---:|");
+            for model in &self.costs {
+                let cost = model
+                    .cost
+                    .map_or_else(|| "unknown".to_string(), |cost| format!("${:.2}", cost));
+                let _ = writeln!(
+                    out,
+                    "| {}/{} | {} | {} | {} |",
+                    model.provider,
+                    model.model,
+                    model.responses,
+                    group_thousands(model.tokens),
+                    cost
+                );
+            }
+        }
+        out
+    }
+}
+
+/// The last part of a project path
+fn project_name(project: &str) -> &str {
+    Path::new(project)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(project)
+}
+
+/// `1234567` as `1,234,567`
+fn group_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::new();
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::UsageRecord;
+    use crate::utils::conversation::ToolCall;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("7d").unwrap(), ChronoDuration::days(7));
+        assert_eq!(parse_since("2w").unwrap(), ChronoDuration::days(14));
+        assert_eq!(parse_since("12h").unwrap(), ChronoDuration::hours(12));
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("3y").is_err());
+        assert_eq!(
+            command_kind("RUST_LOG=debug cargo test -p core"),
+            "cargo test"
+        );
+        assert_eq!(command_kind("ls -la"), "ls");
+    }
+
+    #[test]
+    fn test_digest_from_storage() {
+        let dir = TempDir::new().unwrap();
+        let storage = Storage::open_in_memory().unwrap();
+        let since = Utc::now() - ChronoDuration::days(7);
+
+        let mut conversation = Conversation::new(
+            "unpriced-model".to_string(),
+            "custom".to_string(),
+            String::new(),
+        );
+        conversation.add_user_message("Ship the parser fix".to_string());
+        conversation.add_assistant_message("Running".to_string(), None);
+        let commands = ["cargo test", "cargo test --release", "git commit -m fix"];
+        conversation.messages.last_mut().unwrap().tool_calls = Some(
+            commands
+                .iter()
+                .enumerate()
+                .map(|(i, command)| ToolCall {
+                    id: i.to_string(),
+                    name: "execute_bash".to_string(),
+                    arguments: json!({ "command": command }).to_string(),
+                    timestamp: Utc::now(),
+                })
+                .collect(),
+        );
+        for (id, success) in [("0", false), ("1", true), ("2", true)] {
+            conversation.add_tool_result(id.into(), "execute_bash".into(), json!(""), success, 10);
+        }
+        conversation.save(dir.path()).unwrap();
+        storage.index_session(&conversation, dir.path()).unwrap();
+        storage
+            .record_commit(&CommitRecord {
+                timestamp: Utc::now(),
+                project: dir.path().display().to_string(),
+                hash: "abc1234".to_string(),
+                subject: "feat: digest".to_string(),
+            })
+            .unwrap();
+        storage
+            .record_usage(&UsageRecord {
+                timestamp: Utc::now(),
+                provider: "custom".to_string(),
+                model: "unpriced-model".to_string(),
+                prompt_tokens: 1000,
+                completion_tokens: 234,
+                total_tokens: 1234,
+                latency_ms: None,
+            })
+            .unwrap();
+
+        let digest = Digest::from_storage(&storage, since).unwrap();
+        assert_eq!(digest.sessions.len(), 1);
+        assert_eq!(digest.commands.total, 3);
+        assert_eq!(digest.commands.failed, 1);
+        assert_eq!(digest.commands.top[0], ("cargo test".to_string(), 2));
+        assert_eq!(digest.commits_assisted(), 2);
+
+        let markdown = digest.to_markdown();
+        assert!(markdown.contains("- **Commands run:** 3 (1 failed)"));
+        assert!(markdown.contains("- **Tokens:** 1,234"));
+        assert!(markdown.contains("- `abc1234` feat: digest"));
+        assert!(markdown.contains("| custom/unpriced-model | 1 | 1,234 | unknown |"));
+    }
+}
@@ -0,0 +1,424 @@
+//! Setup checks for `arula doctor`
+//!
+//! Looks at what usually goes wrong before the first prompt: a config file
+//! with mistakes, a provider that can't be reached or rejects its key,
+//! missing optional programs, and a terminal without true color or Unicode.
+//! Each check passes, warns or fails, and says how to fix what it found.
+
+use crate::utils::config::{AiConfig, Config};
+use crate::utils::config_validation::{Severity, validate_config_file};
+use crate::utils::icons::IconSet;
+use crate::utils::voice::on_path;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// How long a provider gets to answer
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Providers slower than this to list models get a warning
+const SLOW_LATENCY: Duration = Duration::from_secs(2);
+
+/// Optional programs: name, what needs it, how to get it
+const OPTIONAL_PROGRAMS: &[(&str, &str, &str)] = &[
+    (
+        "git",
+        "commits, diffs and git context",
+        "Install git from https://git-scm.com",
+    ),
+    (
+        "tesseract",
+        "Visioneer text extraction (OCR)",
+        "Install tesseract-ocr with your package manager",
+    ),
+    (
+        "ollama",
+        "local models and Visioneer analysis",
+        "Install Ollama from https://ollama.com",
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// The outcome of one check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    /// Group the check belongs to: "Config", "Provider", "Programs", "Terminal"
+    pub group: &'static str,
+    pub name: String,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn new(
+        group: &'static str,
+        name: impl Into<String>,
+        status: Status,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            group,
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// All checks, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    pub checks: Vec<Check>,
+}
+
+impl Report {
+    /// Run every check against the config file at `config_path`
+    pub async fn run(config_path: &Path) -> Self {
+        let mut checks = config_checks(config_path);
+        match Config::load_or_default() {
+            Ok(config) => {
+                for provider in providers_to_check(&config) {
+                    checks.push(provider_check(&config, &provider).await);
+                }
+            }
+            Err(e) => checks.push(
+                Check::new(
+                    "Provider",
+                    "providers",
+                    Status::Fail,
+                    format!("Config didn't load: {:#}", e),
+                )
+                .fix("Fix the config errors above first"),
+            ),
+        }
+        checks.extend(program_checks());
+        checks.extend(terminal_checks(|name| std::env::var(name).ok()));
+        Self { checks }
+    }
+
+    pub fn count(&self, status: Status) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+
+    /// Whether nothing failed
+    pub fn healthy(&self) -> bool {
+        self.count(Status::Fail) == 0
+    }
+}
+
+/// The config file parses and validates
+fn config_checks(path: &Path) -> Vec<Check> {
+    if !path.exists() {
+        return vec![
+            Check::new(
+                "Config",
+                "config file",
+                Status::Warn,
+                format!("{} doesn't exist; using defaults", path.display()),
+            )
+            .fix("Start arula once and save settings, or create the file by hand"),
+        ];
+    }
+    let issues = match validate_config_file(path) {
+        Ok(issues) => issues,
+        Err(e) => {
+            return vec![
+                Check::new("Config", "config file", Status::Fail, format!("{:#}", e))
+                    .fix("Check the file's permissions"),
+            ];
+        }
+    };
+
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    let status = match (errors, warnings) {
+        (0, 0) => Status::Pass,
+        (0, _) => Status::Warn,
+        _ => Status::Fail,
+    };
+    let detail = if issues.is_empty() {
+        format!("{} is valid", path.display())
+    } else {
+        format!(
+            "{}: {} error(s), {} warning(s)",
+            path.display(),
+            errors,
+            warnings
+        )
+    };
+    let check = Check::new("Config", "config file", status, detail);
+    vec![match status {
+        Status::Pass => check,
+        _ => check.fix(format!(
+            "Run `arula config validate` to see each problem; first: {}",
+            issues[0].message
+        )),
+    }]
+}
+
+/// The active provider, then every other provider with an API key
+fn providers_to_check(config: &Config) -> Vec<String> {
+    let mut providers = vec![config.active_provider.clone()];
+    for name in config.get_provider_names() {
+        let has_key = config
+            .providers
+            .get(&name)
+            .is_some_and(|p| !p.api_key.trim().is_empty());
+        if has_key && !providers.contains(&name) {
+            providers.push(name);
+        }
+    }
+    providers
+}
+
+/// Environment variable read for a provider's key when the config has none
+fn key_variable(provider: &str) -> &'static str {
+    match provider.to_lowercase().as_str() {
+        "openai" => "OPENAI_API_KEY",
+        "anthropic" => "ANTHROPIC_API_KEY",
+        "openrouter" => "OPENROUTER_API_KEY",
+        "ollama" => "OLLAMA_API_KEY",
+        "zai" | "z.ai" | "z.ai coding plan" => "ZAI_API_KEY",
+        _ => "CUSTOM_API_KEY",
+    }
+}
+
+/// The provider answers its model list request with the configured key,
+/// and how long that takes
+async fn provider_check(config: &Config, provider: &str) -> Check {
+    let defaults = AiConfig::get_provider_defaults(provider);
+    let settings = config.providers.get(provider);
+    let api_url = settings
+        .and_then(|p| p.api_url.clone())
+        .unwrap_or(defaults.api_url)
+        .trim_end_matches('/')
+        .to_string();
+    let api_key = settings
+        .map(|p| p.api_key.clone())
+        .filter(|key| !key.trim().is_empty())
+        .unwrap_or(defaults.api_key);
+    let kind = provider.to_lowercase();
+    let name = format!("provider {}", provider);
+
+    let local = kind == "ollama" || defaults.provider == "custom";
+    if api_key.trim().is_empty() && !local {
+        return Check::new("Provider", name, Status::Fail, "No API key").fix(format!(
+            "Set providers.{}.api_key in the config, or {}",
+            provider,
+            key_variable(provider)
+        ));
+    }
+
+    let client = match reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => return Check::new("Provider", name, Status::Fail, e.to_string()),
+    };
+    let request = match kind.as_str() {
+        "ollama" => client.get(format!("{}/api/tags", api_url)),
+        "anthropic" => client
+            .get(format!("{}/v1/models", api_url))
+            .header("x-api-key", &api_key)
+            .header("anthropic-version", "2023-06-01"),
+        _ => client
+            .get(format!("{}/models", api_url))
+            .bearer_auth(&api_key),
+    };
+
+    let started = Instant::now();
+    match request.send().await {
+        Ok(response) => interpret_response(
+            name,
+            provider,
+            &api_url,
+            response.status().as_u16(),
+            started.elapsed(),
+        ),
+        Err(e) => {
+            let fix = if kind == "ollama" {
+                "Start Ollama with `ollama serve`, or fix its api_url".to_string()
+            } else {
+                format!(
+                    "Check providers.{}.api_url and your network or proxy",
+                    provider
+                )
+            };
+            Check::new(
+                "Provider",
+                name,
+                Status::Fail,
+                format!("Can't reach {}: {}", api_url, e),
+            )
+            .fix(fix)
+        }
+    }
+}
+
+/// A check from the HTTP status a provider answered with
+fn interpret_response(
+    name: String,
+    provider: &str,
+    api_url: &str,
+    status: u16,
+    latency: Duration,
+) -> Check {
+    let ms = latency.as_millis();
+    match status {
+        200..=299 if latency > SLOW_LATENCY => Check::new(
+            "Provider",
+            name,
+            Status::Warn,
+            format!("Key accepted, but answering took {} ms", ms),
+        )
+        .fix("Responses will be slow; check your network, or pick a closer endpoint"),
+        200..=299 => Check::new(
+            "Provider",
+            name,
+            Status::Pass,
+            format!("Key accepted, {} ms", ms),
+        ),
+        401 => Check::new("Provider", name, Status::Fail, "API key rejected (401)").fix(format!(
+            "Replace providers.{}.api_key with a current key",
+            provider
+        )),
+        403 => Check::new(
+            "Provider",
+            name,
+            Status::Fail,
+            "API key lacks permission (403); it may be missing the model read scope",
+        )
+        .fix("Create a key with access to models, or enable them for the key's project"),
+        404 => Check::new(
+            "Provider",
+            name,
+            Status::Warn,
+            format!("Reachable in {} ms, but {} has no model list", ms, api_url),
+        )
+        .fix(format!("Check providers.{}.api_url", provider)),
+        429 => Check::new("Provider", name, Status::Warn, "Rate limited (429)")
+            .fix("Wait a moment, or check the account's quota"),
+        _ => Check::new(
+            "Provider",
+            name,
+            Status::Warn,
+            format!("Unexpected status {} after {} ms", status, ms),
+        )
+        .fix("The provider may be having problems; try again later"),
+    }
+}
+
+/// Optional programs are on PATH
+fn program_checks() -> Vec<Check> {
+    OPTIONAL_PROGRAMS
+        .iter()
+        .map(|(program, used_for, install)| {
+            if on_path(program) {
+                Check::new(
+                    "Programs",
+                    *program,
+                    Status::Pass,
+                    format!("Found, used for {}", used_for),
+                )
+            } else {
+                Check::new(
+                    "Programs",
+                    *program,
+                    Status::Warn,
+                    format!("Not found; needed for {}", used_for),
+                )
+                .fix(*install)
+            }
+        })
+        .collect()
+}
+
+/// The terminal shows true color and Unicode, judged from environment
+/// variables looked up with `env`
+fn terminal_checks(env: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let colorterm = env("COLORTERM").unwrap_or_default().to_lowercase();
+    let truecolor = colorterm == "truecolor"
+        || colorterm == "24bit"
+        || env("WT_SESSION").is_some()
+        || matches!(
+            env("TERM_PROGRAM").as_deref(),
+            Some("iTerm.app" | "WezTerm" | "vscode" | "ghostty")
+        );
+    let color = if truecolor {
+        Check::new("Terminal", "true color", Status::Pass, "Supported")
+    } else {
+        Check::new(
+            "Terminal",
+            "true color",
+            Status::Warn,
+            "Not detected; themes fall back to 256 colors",
+        )
+        .fix("Set COLORTERM=truecolor if your terminal supports it")
+    };
+
+    let unicode = if IconSet::detect_from(&env) == IconSet::Ascii {
+        Check::new(
+            "Terminal",
+            "unicode",
+            Status::Warn,
+            "Not detected; icons fall back to ASCII",
+        )
+        .fix("Use a UTF-8 locale, e.g. LANG=en_US.UTF-8")
+    } else {
+        Check::new("Terminal", "unicode", Status::Pass, "Supported")
+    };
+    vec![color, unicode]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_checks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        assert_eq!(config_checks(&path)[0].status, Status::Warn);
+        std::fs::write(&path, "{ not json").unwrap();
+        let broken = &config_checks(&path)[0];
+        assert_eq!(broken.status, Status::Fail);
+        assert!(broken.fix.is_some());
+
+        let check = |status| {
+            interpret_response(
+                "p".into(),
+                "openai",
+                "https://x",
+                status,
+                Duration::from_millis(80),
+            )
+        };
+        assert_eq!(check(200).status, Status::Pass);
+        assert!(check(401).detail.contains("rejected"));
+        assert!(check(403).detail.contains("permission"));
+        assert_eq!(check(404).status, Status::Warn);
+
+        let vars = HashMap::from([
+            ("COLORTERM", "truecolor"),
+            ("TERM", "xterm-256color"),
+            ("LANG", "C"),
+        ]);
+        let terminal = terminal_checks(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(terminal[0].status, Status::Pass);
+        assert_eq!(terminal[1].status, Status::Warn);
+    }
+}
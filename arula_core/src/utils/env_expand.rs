@@ -0,0 +1,156 @@
+//! `${VAR}` expansion for config values
+//!
+//! Config strings may reference environment variables as `${NAME}` or
+//! `${NAME:-default}`. Variables come from the process environment first,
+//! then from a project-local `.env` file, so secrets can stay out of
+//! config.json entirely.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Whether a value contains a `${...}` reference
+pub fn has_reference(value: &str) -> bool {
+    value.contains("${")
+}
+
+/// Variables for expansion: the process environment over a `.env` file
+pub struct EnvSource {
+    dotenv: HashMap<String, String>,
+}
+
+impl EnvSource {
+    /// Environment plus `.env` in `dir`, if there is one
+    pub fn load(dir: &Path) -> Self {
+        let dotenv = std::fs::read_to_string(dir.join(".env"))
+            .map(|content| parse_dotenv(&content))
+            .unwrap_or_default();
+        Self { dotenv }
+    }
+
+    /// Look up a variable, preferring the process environment
+    pub fn get(&self, name: &str) -> Option<String> {
+        std::env::var(name)
+            .ok()
+            .or_else(|| self.dotenv.get(name).cloned())
+    }
+
+    /// Expand every `${NAME}` and `${NAME:-default}` in `value`
+    ///
+    /// Fails with the names of unset variables that have no default.
+    pub fn expand(&self, value: &str) -> Result<String, Vec<String>> {
+        expand_with(value, |name| self.get(name))
+    }
+}
+
+/// Expand references using `lookup` for variable values
+pub fn expand_with(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<String, Vec<String>> {
+    let mut output = String::with_capacity(value.len());
+    let mut missing = Vec::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            // Unterminated: keep the text as-is
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let reference = &after[..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name.trim(), Some(default)),
+            None => (reference.trim(), None),
+        };
+        match lookup(name).filter(|v| !v.is_empty()) {
+            Some(resolved) => output.push_str(&resolved),
+            None => match default {
+                Some(default) => output.push_str(default),
+                None => missing.push(name.to_string()),
+            },
+        }
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(output)
+    } else {
+        Err(missing)
+    }
+}
+
+/// Parse `KEY=value` lines of a `.env` file
+///
+/// Supports comments, blank lines, an `export ` prefix and single or double
+/// quoted values.
+pub fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    let mut vars = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) if value.len() >= 2 && value.ends_with(quote) => {
+                value[1..value.len() - 1].to_string()
+            }
+            // Unquoted values may carry a trailing comment
+            _ => value
+                .split_once(" #")
+                .map_or(value, |(v, _)| v)
+                .trim_end()
+                .to_string(),
+        };
+        vars.insert(key.trim().to_string(), value);
+    }
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_with() {
+        let lookup = |name: &str| match name {
+            "KEY" => Some("sk-123".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(expand_with("${KEY}", lookup), Ok("sk-123".to_string()));
+        assert_eq!(
+            expand_with("Bearer ${ KEY }!", lookup),
+            Ok("Bearer sk-123!".to_string())
+        );
+        assert_eq!(
+            expand_with("${HOST:-localhost}:${EMPTY:-80}", lookup),
+            Ok("localhost:80".to_string())
+        );
+        assert_eq!(
+            expand_with("${A}/${KEY}/${B}", lookup),
+            Err(vec!["A".to_string(), "B".to_string()])
+        );
+        assert_eq!(expand_with("plain ${", lookup), Ok("plain ${".to_string()));
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let vars = parse_dotenv(
+            "# keys\nOPENAI_API_KEY=sk-abc\nexport ZAI_KEY=\"z z\"\nQUOTED='a#b'\nURL=http://x # local\n\nbroken line\n",
+        );
+        assert_eq!(vars["OPENAI_API_KEY"], "sk-abc");
+        assert_eq!(vars["ZAI_KEY"], "z z");
+        assert_eq!(vars["QUOTED"], "a#b");
+        assert_eq!(vars["URL"], "http://x");
+        assert_eq!(vars.len(), 4);
+    }
+}
@@ -0,0 +1,167 @@
+//! Plain-language help for common request failures
+//!
+//! Turns errors like a rejected API key, an unknown model, a stopped Ollama
+//! server or an overlong conversation into a short explanation, plus a fix
+//! the UI can offer behind a single key press.
+
+use crate::api::provider_error;
+
+/// A fix the UI can apply for the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFix {
+    /// Open the settings menu (API key, provider, endpoint)
+    OpenSettings,
+    /// Open the model selector
+    SwitchModel,
+    /// Summarize older messages with `/compact`
+    Compact,
+}
+
+impl ErrorFix {
+    /// What the fix does, as in "Press Tab to <label>"
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorFix::OpenSettings => "open settings",
+            ErrorFix::SwitchModel => "switch model",
+            ErrorFix::Compact => "run /compact",
+        }
+    }
+}
+
+/// An explanation of a failure and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorHelp {
+    pub explanation: String,
+    pub fix: Option<ErrorFix>,
+}
+
+/// Explain an error shown in chat, using the last provider error when the
+/// message refers to it
+pub fn explain(error: &str, provider: &str) -> Option<ErrorHelp> {
+    match provider_error::last_error().filter(|_| error.contains("/debug last-error")) {
+        Some(last) => classify(
+            Some(last.status),
+            &format!("{}\n{}", error, last.body),
+            provider,
+        ),
+        None => classify(None, error, provider),
+    }
+}
+
+/// Match an HTTP status and error text against the failures we know
+pub fn classify(status: Option<u16>, text: &str, provider: &str) -> Option<ErrorHelp> {
+    let text = text.to_lowercase();
+    let mentions = |needles: &[&str]| needles.iter().any(|n| text.contains(n));
+    let help = |explanation: String, fix| {
+        Some(ErrorHelp {
+            explanation,
+            fix: Some(fix),
+        })
+    };
+
+    if is_context_overflow_text(&text) {
+        return help(
+            "The conversation no longer fits in the model's context window.".to_string(),
+            ErrorFix::Compact,
+        );
+    }
+    if matches!(status, Some(401 | 403))
+        || mentions(&[
+            "invalid api key",
+            "invalid_api_key",
+            "incorrect api key",
+            "unauthorized",
+        ])
+    {
+        return help(
+            format!(
+                "{} rejected the API key. Check that it is set and still valid.",
+                provider
+            ),
+            ErrorFix::OpenSettings,
+        );
+    }
+    if status == Some(404) || mentions(&["model_not_found", "model not found", "unknown model"]) {
+        return help(
+            format!(
+                "{} doesn't offer the configured model, or the name is misspelled.",
+                provider
+            ),
+            ErrorFix::SwitchModel,
+        );
+    }
+    if mentions(&["connection refused", "econnrefused"]) {
+        let explanation = if provider.eq_ignore_ascii_case("ollama") || text.contains(":11434") {
+            "Ollama isn't running. Start it with `ollama serve`, or point ARULA at another endpoint."
+                .to_string()
+        } else {
+            format!(
+                "Nothing is listening at the {} endpoint. Check the API URL.",
+                provider
+            )
+        };
+        return help(explanation, ErrorFix::OpenSettings);
+    }
+    None
+}
+
+/// Whether error text says the request didn't fit the context window
+pub fn is_context_overflow_text(text: &str) -> bool {
+    let text = text.to_lowercase();
+    [
+        "context_length_exceeded",
+        "maximum context length",
+        "context length",
+        "context window",
+        "prompt is too long",
+        "too many tokens",
+    ]
+    .iter()
+    .any(|needle| text.contains(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(status: Option<u16>, text: &str, provider: &str) -> Option<ErrorFix> {
+        classify(status, text, provider).and_then(|help| help.fix)
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(
+            fix(
+                Some(401),
+                r#"{"error":{"type":"authentication_error"}}"#,
+                "claude"
+            ),
+            Some(ErrorFix::OpenSettings)
+        );
+        assert_eq!(
+            fix(Some(404), "The model `gpt-9` does not exist", "openai"),
+            Some(ErrorFix::SwitchModel)
+        );
+        assert_eq!(
+            fix(
+                Some(400),
+                "This model's maximum context length is 128000 tokens",
+                "openai"
+            ),
+            Some(ErrorFix::Compact)
+        );
+        assert_eq!(fix(Some(500), "Internal error", "openai"), None);
+    }
+
+    #[test]
+    fn test_ollama_not_running() {
+        let help = classify(
+            None,
+            "error sending request for url (http://localhost:11434/api/chat): tcp connect error: Connection refused (os error 111)",
+            "ollama",
+        )
+        .unwrap();
+        assert!(help.explanation.contains("ollama serve"));
+        assert_eq!(help.fix, Some(ErrorFix::OpenSettings));
+    }
+}
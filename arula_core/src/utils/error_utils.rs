@@ -55,7 +55,7 @@ impl ErrorContext {
     }
 
     pub fn with_anyhow_error(mut self, error: &anyhow::Error) -> Self {
-        self.underlying_error = Some(error.to_string());
+        self.underlying_error = Some(format!("{:#}", error));
         self
     }
 
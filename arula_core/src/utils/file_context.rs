@@ -0,0 +1,73 @@
+//! Files the user attaches as context for their next message
+//!
+//! Frontends let the user pick files (the TUI's file browser, Ctrl+O); their
+//! contents are appended to the next request only, the stored conversation
+//! keeps the message as typed.
+
+use crate::api::trust::{Trust, data_block};
+use std::path::{Path, PathBuf};
+
+/// Longest part of one file that is attached
+pub const MAX_FILE_CHARS: usize = 20_000;
+
+/// The contents of `files` (relative to `root`) as a context block, or
+/// `None` when none of them can be read as text
+pub fn attach_files(root: &Path, files: &[PathBuf]) -> Option<String> {
+    let sections: Vec<String> = files
+        .iter()
+        .filter_map(|file| {
+            let content = std::fs::read_to_string(root.join(file)).ok()?;
+            let shown = match content.char_indices().nth(MAX_FILE_CHARS) {
+                Some((end, _)) => format!(
+                    "{}\n[... truncated after {} characters]",
+                    &content[..end],
+                    MAX_FILE_CHARS
+                ),
+                None => content,
+            };
+            let name = file.to_string_lossy();
+            Some(format!(
+                "### {}\n{}",
+                name,
+                data_block(&name, Trust::Workspace, &shown)
+            ))
+        })
+        .collect();
+
+    if sections.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "[Files attached for context]\n\n{}",
+        sections.join("\n\n")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_attach_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("notes.md"), "Use the v2 API").unwrap();
+        std::fs::write(dir.path().join("blob.bin"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let context = attach_files(
+            dir.path(),
+            &[
+                PathBuf::from("notes.md"),
+                PathBuf::from("blob.bin"),
+                PathBuf::from("missing.rs"),
+            ],
+        )
+        .unwrap();
+        assert!(context.starts_with("[Files attached for context]"));
+        assert!(context.contains("### notes.md"));
+        assert!(context.contains("Use the v2 API"));
+        assert!(!context.contains("blob.bin"));
+
+        assert_eq!(attach_files(dir.path(), &[PathBuf::from("blob.bin")]), None);
+    }
+}
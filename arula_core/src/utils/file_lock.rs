@@ -0,0 +1,174 @@
+//! Advisory locks between ARULA instances
+//!
+//! Two instances (say the CLI and the desktop app) saving the same session
+//! or config.json would overwrite each other's changes. A lock lives in a
+//! `<file>.lock` next to the file it guards and is held through the OS file
+//! lock, so it is released when the holder exits, even if it crashes. The
+//! lock file records the holder's process id and program name so the other
+//! instance can say who has it.
+//!
+//! Sessions are locked for as long as they are open; an instance that finds
+//! a session locked offers to open it read-only. Config saves take the lock
+//! while they merge in the other instance's changes and write.
+
+use anyhow::{Context, Result, bail};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// How often a blocked save tries the lock again
+const RETRY_INTERVAL: Duration = Duration::from_millis(25);
+
+/// The session is open in another instance
+#[derive(Debug, Error)]
+#[error("This session is open in another ARULA instance ({holder})")]
+pub struct SessionInUse {
+    pub holder: String,
+}
+
+/// A held lock; released when dropped
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Lock `path` if no other instance holds it; `None` if one does
+    pub fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        let lock_path = lock_path(path);
+        if let Some(parent) = lock_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", lock_path.display()));
+            }
+        }
+        // Who holds it, for the other instance's message
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{} {}", std::process::id(), program_name())?;
+        file.flush()?;
+        Ok(Some(Self {
+            file,
+            path: lock_path,
+        }))
+    }
+
+    /// Lock `path`, waiting up to `timeout` for another instance to let go
+    ///
+    /// On a multi-threaded tokio runtime the wait is handed off with
+    /// `block_in_place`, so other tasks on this worker keep running.
+    pub fn acquire_within(path: &Path, timeout: Duration) -> Result<Self> {
+        use tokio::runtime::{Handle, RuntimeFlavor};
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| Self::wait_for(path, timeout))
+            }
+            _ => Self::wait_for(path, timeout),
+        }
+    }
+
+    fn wait_for(path: &Path, timeout: Duration) -> Result<Self> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(lock);
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "{} is locked by another ARULA instance ({})",
+                    path.display(),
+                    holder(path)
+                );
+            }
+            std::thread::sleep(RETRY_INTERVAL);
+        }
+    }
+
+    /// The lock file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// The lock file guarding `path`
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+/// Who holds the lock on `path`, e.g. "pid 4242, arula_desktop"
+pub fn holder(path: &Path) -> String {
+    let mut content = String::new();
+    let read = File::open(lock_path(path)).and_then(|mut f| f.read_to_string(&mut content));
+    match read.ok().and_then(|_| content.trim().split_once(' ')) {
+        Some((pid, program)) => format!("pid {}, {}", pid, program),
+        None => "unknown process".to_string(),
+    }
+}
+
+fn program_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "arula".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive() {
+        let dir = TempDir::new().unwrap();
+        let session = dir.path().join("session.json");
+        assert_eq!(lock_path(&session), dir.path().join("session.json.lock"));
+
+        let lock = FileLock::try_acquire(&session).unwrap().unwrap();
+        // A second open of the lock file stands in for another instance
+        assert!(FileLock::try_acquire(&session).unwrap().is_none());
+        assert!(holder(&session).starts_with(&format!("pid {}, ", std::process::id())));
+        let err = FileLock::acquire_within(&session, Duration::from_millis(60)).unwrap_err();
+        assert!(err.to_string().contains("locked by another ARULA instance"));
+
+        drop(lock);
+        assert!(FileLock::try_acquire(&session).unwrap().is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_wait_does_not_stall_runtime() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.path().join("config.json");
+        let lock = FileLock::try_acquire(&config).unwrap().unwrap();
+
+        let ticker = tokio::spawn(async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        });
+        let err = FileLock::acquire_within(&config, Duration::from_millis(100)).unwrap_err();
+        assert!(err.to_string().contains("locked by another ARULA instance"));
+        ticker.await.unwrap();
+        drop(lock);
+    }
+}
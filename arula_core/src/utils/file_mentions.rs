@@ -0,0 +1,221 @@
+//! `@` file mentions in prompts
+//!
+//! Typing `@src/ma` in a composer offers the project files whose paths
+//! fuzzy-match `src/ma`; picking one completes the mention. When the message
+//! is sent, the contents of every mentioned file are attached to that
+//! request (see `file_context`), while the conversation keeps the message as
+//! typed.
+
+use crate::utils::file_context::attach_files;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Most files indexed, so huge trees stay quick to walk
+const MAX_FILES: usize = 20_000;
+
+/// How long an index is reused before the tree is walked again
+const MAX_AGE: Duration = Duration::from_secs(10);
+
+/// Indexes shared between callers, keyed by project root
+static INDEX_CACHE: LazyLock<Mutex<HashMap<PathBuf, Arc<FileIndex>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The files of a project, as `/`-separated paths relative to its root
+#[derive(Debug, Clone)]
+pub struct FileIndex {
+    files: Vec<String>,
+    built: Instant,
+}
+
+impl FileIndex {
+    /// Walk `root`, skipping what `.gitignore` excludes
+    pub fn build(root: &Path) -> Self {
+        let mut files: Vec<String> = ignore::WalkBuilder::new(root)
+            .build()
+            .flatten()
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .filter_map(|entry| {
+                let relative = entry.path().strip_prefix(root).ok()?;
+                Some(relative.to_string_lossy().replace('\\', "/"))
+            })
+            .take(MAX_FILES)
+            .collect();
+        files.sort();
+        Self {
+            files,
+            built: Instant::now(),
+        }
+    }
+
+    /// The index for `root`, walked again when it is older than `MAX_AGE`
+    pub fn shared(root: &Path) -> Arc<FileIndex> {
+        let mut cache = INDEX_CACHE.lock().unwrap();
+        if let Some(index) = cache.get(root)
+            && index.built.elapsed() < MAX_AGE
+        {
+            return Arc::clone(index);
+        }
+        let index = Arc::new(Self::build(root));
+        cache.insert(root.to_path_buf(), Arc::clone(&index));
+        index
+    }
+
+    /// Up to `limit` paths matching `query`, best first
+    pub fn complete(&self, query: &str, limit: usize) -> Vec<String> {
+        let mut scored: Vec<(i32, &String)> = self
+            .files
+            .iter()
+            .filter_map(|path| Some((path_score(query, path)?, path)))
+            .collect();
+        // Best score first, then shorter paths
+        scored.sort_by_key(|(score, path)| (-score, path.len()));
+        scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, path)| path.clone())
+            .collect()
+    }
+}
+
+/// How well `query` matches `path`: its characters must appear in order.
+/// Matches at the start of a path segment, runs of characters and matches in
+/// the file name score higher; skipped characters cost a little.
+fn path_score(query: &str, path: &str) -> Option<i32> {
+    let chars: Vec<char> = path.to_lowercase().chars().collect();
+    let name_start = chars.iter().rposition(|&c| c == '/').map_or(0, |i| i + 1);
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for ch in query.to_lowercase().chars() {
+        let found = position + chars[position..].iter().position(|&c| c == ch)?;
+        score += 10;
+        if previous.is_some_and(|p| p + 1 == found) {
+            score += 15;
+        }
+        if found == 0 || matches!(chars[found - 1], '/' | '_' | '-' | '.') {
+            score += 10;
+        }
+        if found >= name_start {
+            score += 5;
+        }
+        score -= (found - position).min(20) as i32;
+        previous = Some(found);
+        position = found + 1;
+    }
+    Some(score)
+}
+
+/// An `@` mention being typed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mention {
+    /// Character index of the `@`
+    pub start: usize,
+    /// What follows the `@`, up to the cursor
+    pub query: String,
+}
+
+/// The mention the cursor (a character index) is at the end of: an `@` at
+/// the start of the input or after whitespace, then no whitespace
+pub fn mention_at(input: &str, cursor: usize) -> Option<Mention> {
+    let before: Vec<char> = input.chars().take(cursor).collect();
+    let start = before
+        .iter()
+        .rposition(|c| *c == '@' || c.is_whitespace())?;
+    if before[start] != '@' || (start > 0 && !before[start - 1].is_whitespace()) {
+        return None;
+    }
+    Some(Mention {
+        start,
+        query: before[start + 1..].iter().collect(),
+    })
+}
+
+/// `input` with `mention` replaced by `@path `, and the cursor after it
+pub fn complete_mention(input: &str, mention: &Mention, path: &str) -> (String, usize) {
+    let chars: Vec<char> = input.chars().collect();
+    let end = (mention.start + 1 + mention.query.chars().count()).min(chars.len());
+    let head: String = chars[..mention.start].iter().collect();
+    let tail: String = chars[end..].iter().collect();
+    let completed = format!("{}@{} ", head, path);
+    let cursor = completed.chars().count();
+    (format!("{}{}", completed, tail.trim_start()), cursor)
+}
+
+/// Files under `root` mentioned in `message` as `@path`, in order
+pub fn mentioned_files(root: &Path, message: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for word in message.split_whitespace() {
+        let Some(path) = word.strip_prefix('@') else {
+            continue;
+        };
+        // Allow punctuation right after the path, as in "see @a.rs, @b.rs."
+        let path = PathBuf::from(path.trim_end_matches([',', ';', ':', '.', ')', '?', '!']));
+        if !path.as_os_str().is_empty() && root.join(&path).is_file() && !files.contains(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// `message` with the contents of the files it mentions attached
+pub fn attach_mentions(root: &Path, message: &str) -> String {
+    match attach_files(root, &mentioned_files(root, message)) {
+        Some(context) => format!("{}\n\n{}", message, context),
+        None => message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mention_completion() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/ui")).unwrap();
+        for file in [
+            "src/main.rs",
+            "src/ui/menu.rs",
+            "src/manifest.rs",
+            "README.md",
+        ] {
+            std::fs::write(dir.path().join(file), "fn main() {}").unwrap();
+        }
+        let index = FileIndex::build(dir.path());
+        let found = index.complete("src/ma", 10);
+        assert_eq!(found[0], "src/main.rs");
+        assert!(found.contains(&"src/manifest.rs".to_string()));
+        assert_eq!(index.complete("menu", 1), ["src/ui/menu.rs"]);
+        assert!(index.complete("xyz", 10).is_empty());
+
+        let input = "look at @src/ma please";
+        let mention = mention_at(input, 15).unwrap();
+        assert_eq!(
+            mention,
+            Mention {
+                start: 8,
+                query: "src/ma".to_string()
+            }
+        );
+        assert_eq!(
+            complete_mention(input, &mention, "src/main.rs"),
+            ("look at @src/main.rs please".to_string(), 21)
+        );
+        assert_eq!(mention_at("mail me@host", 12), None);
+        assert_eq!(mention_at("@", 1).unwrap().query, "");
+        assert_eq!(mention_at("@src done", 9), None);
+
+        let message = "Compare @src/main.rs and @README.md, not @missing.rs or @src/main.rs";
+        assert_eq!(
+            mentioned_files(dir.path(), message),
+            [PathBuf::from("src/main.rs"), PathBuf::from("README.md")]
+        );
+        let attached = attach_mentions(dir.path(), message);
+        assert!(attached.starts_with(message));
+        assert!(attached.contains("### src/main.rs"));
+        assert_eq!(attach_mentions(dir.path(), "no mentions"), "no mentions");
+    }
+}
@@ -0,0 +1,203 @@
+//! Git context for files referenced in a message
+//!
+//! When `context.git_enrichment` is enabled, file paths mentioned in a user
+//! message are resolved against the working directory and the prompt gets a
+//! short block with each file's recent commits and uncommitted changes, so
+//! questions like "why does src/app.rs do X?" come with their history.
+
+use crate::utils::git_ops::{GitOps, truncate_diff};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of referenced files enriched per message
+const MAX_FILES: usize = 3;
+/// Number of recent commits listed per file
+const MAX_COMMITS: usize = 5;
+/// Maximum uncommitted diff size included per file
+const MAX_FILE_DIFF_CHARS: usize = 2_000;
+
+/// Find existing files referenced in a message, relative to `root`
+///
+/// Tokens are stripped of surrounding quotes, backticks and punctuation, and
+/// an optional `:line` suffix (`src/main.rs:42`). Only tokens that look like
+/// paths (a `/` or an extension) and exist as files are returned.
+pub fn extract_file_references(message: &str, root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for token in message.split_whitespace() {
+        let token = token.trim_matches(|c: char| {
+            matches!(
+                c,
+                '`' | '"' | '\'' | '(' | ')' | '[' | ']' | '<' | '>' | ',' | ';' | '?' | '!'
+            )
+        });
+        let token = token.trim_end_matches(['.', ':']);
+        // Drop a trailing `:line` or `:line:col`
+        let token = token
+            .split_once(':')
+            .filter(|(_, rest)| rest.chars().all(|c| c.is_ascii_digit() || c == ':'))
+            .map_or(token, |(path, _)| path);
+        let token = token.strip_prefix("./").unwrap_or(token);
+
+        let looks_like_path = token.contains('/')
+            || Path::new(token)
+                .extension()
+                .is_some_and(|ext| !ext.is_empty());
+        if token.is_empty() || !looks_like_path || token.contains("://") {
+            continue;
+        }
+
+        let path = PathBuf::from(token);
+        if root.join(&path).is_file() && !files.contains(&path) {
+            files.push(path);
+            if files.len() == MAX_FILES {
+                break;
+            }
+        }
+    }
+
+    files
+}
+
+/// Build the git context block for the files referenced in a message
+///
+/// Returns None when no referenced file has history or changes, or when
+/// `root` is not a git repository.
+pub async fn build_git_context(root: &Path, message: &str) -> Option<String> {
+    let files = extract_file_references(message, root);
+    if files.is_empty() {
+        return None;
+    }
+    let git = GitOps::new(root);
+    if !git.is_git_repo().await {
+        return None;
+    }
+
+    let mut sections = Vec::new();
+    for file in files {
+        let commits = git.file_log(&file, MAX_COMMITS).await.unwrap_or_default();
+        let status = git.file_status(&file).await.ok().flatten();
+        if commits.is_empty() && status.is_none() {
+            continue;
+        }
+
+        let mut section = format!("### {}\n", file.display());
+        if !commits.is_empty() {
+            section.push_str("Recent commits:\n");
+            for commit in &commits {
+                let date = commit.date.get(..10).unwrap_or(&commit.date);
+                section.push_str(&format!(
+                    "- {} {} {}: {}\n",
+                    commit.short_hash, date, commit.author, commit.subject
+                ));
+            }
+        }
+        match status.as_deref() {
+            None => section.push_str("Status: unchanged since the last commit\n"),
+            Some("??") => section.push_str("Status: untracked\n"),
+            Some(code) => {
+                section.push_str(&format!("Status: {}\n", describe_status(code)));
+                if let Ok(diff) = git.file_diff(&file).await
+                    && !diff.trim().is_empty()
+                {
+                    section.push_str(&format!(
+                        "Uncommitted changes:\n```diff\n{}\n```\n",
+                        truncate_diff(diff.trim_end(), MAX_FILE_DIFF_CHARS)
+                    ));
+                }
+            }
+        }
+        sections.push(section);
+    }
+
+    if sections.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "[Git context for files referenced above]\n\n{}",
+        sections.join("\n")
+    ))
+}
+
+/// Append git context for referenced files to a message, if there is any
+pub async fn enrich_message(root: &Path, message: &str) -> String {
+    match build_git_context(root, message).await {
+        Some(context) => format!("{}\n\n{}", message, context),
+        None => message.to_string(),
+    }
+}
+
+/// Describe a porcelain status code in words
+fn describe_status(code: &str) -> &'static str {
+    let mut chars = code.chars();
+    let index = chars.next().unwrap_or(' ');
+    let worktree = chars.next().unwrap_or(' ');
+    match (index, worktree) {
+        ('A', _) => "newly added (staged)",
+        ('D', _) | (_, 'D') => "deleted",
+        ('R', _) => "renamed",
+        (' ', _) => "modified (unstaged changes)",
+        (_, ' ') => "modified (staged changes)",
+        _ => "modified (staged and unstaged changes)",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use tokio::process::Command as TokioCommand;
+
+    async fn git(dir: &Path, args: &[&str]) {
+        let status = TokioCommand::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .await
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_extract_file_references() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("src")).unwrap();
+        std::fs::write(temp_dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "# Readme\n").unwrap();
+
+        let files = extract_file_references(
+            "Why does `src/main.rs:12` panic? See ./README.md, not docs/missing.md or https://x.io/a.rs",
+            temp_dir.path(),
+        );
+        assert_eq!(
+            files,
+            vec![PathBuf::from("src/main.rs"), PathBuf::from("README.md")]
+        );
+        assert!(extract_file_references("no paths here", temp_dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_build_git_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init", "--quiet"]).await;
+        git(dir, &["config", "user.name", "Test"]).await;
+        git(dir, &["config", "user.email", "test@example.com"]).await;
+        std::fs::write(dir.join("lib.rs"), "pub fn a() {}\n").unwrap();
+        git(dir, &["add", "lib.rs"]).await;
+        git(dir, &["commit", "--quiet", "-m", "feat: add a"]).await;
+        std::fs::write(dir.join("lib.rs"), "pub fn b() {}\n").unwrap();
+
+        let context = build_git_context(dir, "What changed in lib.rs?")
+            .await
+            .unwrap();
+        assert!(context.contains("### lib.rs"));
+        assert!(context.contains("Test: feat: add a"));
+        assert!(context.contains("modified (unstaged changes)"));
+        assert!(context.contains("+pub fn b() {}"));
+
+        assert_eq!(
+            enrich_message(dir, "hello there").await,
+            "hello there".to_string()
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! Git repository operations
+//!
+//! This module wraps the `git` command line for the read/write operations
+//! used by commands and tools (commit listing, diffs, staging, commits,
+//! branches, blame).
+//! Like `git_state`, it shells out to `git` so it respects the user's own
+//! git configuration, hooks and credentials.
+
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command as TokioCommand;
+
+/// Field separator used in `git log` format strings
+const FIELD_SEP: char = '\x1f';
+/// Record separator used in `git log` format strings
+const RECORD_SEP: char = '\x1e';
+
+/// Information about a single commit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitInfo {
+    /// Full commit hash
+    pub hash: String,
+    /// Abbreviated commit hash
+    pub short_hash: String,
+    /// Author name
+    pub author: String,
+    /// Author date (ISO 8601)
+    pub date: String,
+    /// First line of the commit message
+    pub subject: String,
+    /// Remaining commit message body
+    pub body: String,
+}
+
+/// Blame information for a single line of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlameLine {
+    /// Line number in the current file (1-indexed)
+    pub line: usize,
+    /// Hash of the commit that last changed the line (all zeros if uncommitted)
+    pub hash: String,
+    /// Author name
+    pub author: String,
+    /// Author time (Unix timestamp)
+    pub timestamp: i64,
+}
+
+impl BlameLine {
+    /// Whether the line has not been committed yet
+    pub fn is_uncommitted(&self) -> bool {
+        self.hash.chars().all(|c| c == '0')
+    }
+}
+
+/// Git operations bound to a working directory
+#[derive(Debug, Clone)]
+pub struct GitOps {
+    working_directory: PathBuf,
+}
+
+impl GitOps {
+    /// Create git operations for the given directory
+    pub fn new<P: AsRef<Path>>(working_dir: P) -> Self {
+        Self {
+            working_directory: working_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The directory git commands run in
+    pub fn working_directory(&self) -> &Path {
+        &self.working_directory
+    }
+
+    /// Run a git command and return its stdout, failing with stderr on error
+    async fn run(&self, args: &[&str]) -> Result<String> {
+        let output = TokioCommand::new("git")
+            .args(args)
+            .current_dir(&self.working_directory)
+            .output()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Check whether the working directory is inside a git repository
+    pub async fn is_git_repo(&self) -> bool {
+        self.run(&["rev-parse", "--git-dir"]).await.is_ok()
+    }
+
+    /// Get the current branch name (None when HEAD is detached)
+    pub async fn current_branch(&self) -> Result<Option<String>> {
+        let branch = self.run(&["rev-parse", "--abbrev-ref", "HEAD"]).await?;
+        let branch = branch.trim();
+        if branch.is_empty() || branch == "HEAD" {
+            Ok(None)
+        } else {
+            Ok(Some(branch.to_string()))
+        }
+    }
+
+    /// List the commits in a revision range (e.g. `main..feature`), oldest first
+    pub async fn list_commits(&self, range: &str) -> Result<Vec<CommitInfo>> {
+        let format = format!(
+            "--format=%H{0}%h{0}%an{0}%aI{0}%s{0}%b{1}",
+            FIELD_SEP, RECORD_SEP
+        );
+        let output = self
+            .run(&["log", "--reverse", "--no-color", &format, range])
+            .await?;
+        Ok(parse_log_output(&output))
+    }
+
+    /// List the most recent commits touching a path, newest first
+    pub async fn file_log(&self, path: &Path, limit: usize) -> Result<Vec<CommitInfo>> {
+        let format = format!(
+            "--format=%H{0}%h{0}%an{0}%aI{0}%s{0}%b{1}",
+            FIELD_SEP, RECORD_SEP
+        );
+        let limit = format!("--max-count={}", limit);
+        let path = path.to_string_lossy();
+        let output = self
+            .run(&["log", "--no-color", &limit, &format, "--", path.as_ref()])
+            .await?;
+        Ok(parse_log_output(&output))
+    }
+
+    /// Get the two-letter `git status --porcelain` code of a path
+    /// (None when the path is unchanged)
+    pub async fn file_status(&self, path: &Path) -> Result<Option<String>> {
+        let path = path.to_string_lossy();
+        let output = self
+            .run(&["status", "--porcelain", "--", path.as_ref()])
+            .await?;
+        Ok(output
+            .lines()
+            .next()
+            .and_then(|line| line.get(..2))
+            .map(str::to_string))
+    }
+
+    /// Get the uncommitted changes (staged and unstaged) of a path
+    pub async fn file_diff(&self, path: &Path) -> Result<String> {
+        let path = path.to_string_lossy();
+        self.run(&["diff", "--no-color", "HEAD", "--", path.as_ref()])
+            .await
+    }
+
+    /// Get the diff introduced by a single commit, with a stat header
+    pub async fn commit_diff(&self, hash: &str) -> Result<String> {
+        self.run(&["show", "--no-color", "--stat", "--patch", "--format=", hash])
+            .await
+    }
+
+    /// Stage the given paths
+    pub async fn stage(&self, paths: &[String]) -> Result<()> {
+        let mut args = vec!["add", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        self.run(&args).await.map(|_| ())
+    }
+
+    /// Stage all modifications and deletions of tracked files
+    pub async fn stage_tracked(&self) -> Result<()> {
+        self.run(&["add", "--update"]).await.map(|_| ())
+    }
+
+    /// List the paths currently staged for commit
+    pub async fn staged_files(&self) -> Result<Vec<String>> {
+        let output = self.run(&["diff", "--cached", "--name-only"]).await?;
+        Ok(output
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Get the staged diff, with a stat header
+    pub async fn staged_diff(&self) -> Result<String> {
+        self.run(&["diff", "--cached", "--no-color", "--stat", "--patch"])
+            .await
+    }
+
+    /// Commit the staged changes and return the new commit's short hash
+    pub async fn commit(&self, message: &str) -> Result<String> {
+        if message.trim().is_empty() {
+            bail!("Commit message is empty");
+        }
+        // `git commit` rather than git2: libgit2 runs no pre-commit or
+        // commit-msg hooks, doesn't sign with `commit.gpgsign` and reads only
+        // part of the user's config (includes, `user.useConfigOnly`), so its
+        // commits could differ from the ones the user makes themselves.
+        self.run(&["commit", "--quiet", "-m", message]).await?;
+        let hash = self.run(&["rev-parse", "--short", "HEAD"]).await?;
+        Ok(hash.trim().to_string())
+    }
+
+    /// List local branch names
+    pub async fn list_branches(&self) -> Result<Vec<String>> {
+        let output = self
+            .run(&["for-each-ref", "--format=%(refname:short)", "refs/heads"])
+            .await?;
+        Ok(output
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Create a branch from HEAD and switch to it
+    pub async fn create_branch(&self, name: &str) -> Result<()> {
+        let name = name.trim();
+        self.run(&["check-ref-format", "--branch", name])
+            .await
+            .map_err(|_| anyhow::anyhow!("Invalid branch name: '{}'", name))?;
+        self.run(&["switch", "--quiet", "--create", name])
+            .await
+            .map(|_| ())
+    }
+
+    /// Switch to an existing branch
+    pub async fn switch_branch(&self, name: &str) -> Result<()> {
+        self.run(&["switch", "--quiet", name.trim()]).await.map(|_| ())
+    }
+
+    /// Guess the branch pull requests target: the remote's default branch,
+    /// otherwise a local `main` or `master`
+    pub async fn default_base_branch(&self) -> Result<String> {
+        if let Ok(head) = self
+            .run(&["symbolic-ref", "--short", "refs/remotes/origin/HEAD"])
+            .await
+        {
+            let head = head.trim();
+            if let Some(branch) = head.strip_prefix("origin/") {
+                return Ok(branch.to_string());
+            }
+        }
+
+        let branches = self.list_branches().await?;
+        ["main", "master"]
+            .into_iter()
+            .find(|b| branches.iter().any(|name| name == b))
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("Could not find a base branch (main or master)"))
+    }
+
+    /// Blame every line of a file
+    pub async fn blame(&self, path: &Path) -> Result<Vec<BlameLine>> {
+        let path = path.to_string_lossy();
+        let output = self
+            .run(&["blame", "--line-porcelain", "--", path.as_ref()])
+            .await?;
+        Ok(parse_blame_porcelain(&output))
+    }
+
+    /// Get the diff of HEAD against its merge base with `base`, with a stat header
+    pub async fn branch_diff(&self, base: &str) -> Result<String> {
+        let range = format!("{}...HEAD", base);
+        self.run(&["diff", "--no-color", "--stat", "--patch", &range])
+            .await
+    }
+}
+
+/// Parse `git blame --line-porcelain` output
+pub fn parse_blame_porcelain(output: &str) -> Vec<BlameLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<BlameLine> = None;
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            // Content line ends the record for this source line
+            if let Some(entry) = current.take() {
+                lines.push(entry);
+            }
+        } else if let Some(entry) = current.as_mut() {
+            if let Some(author) = line.strip_prefix("author ") {
+                entry.author = author.to_string();
+            } else if let Some(time) = line.strip_prefix("author-time ") {
+                entry.timestamp = time.trim().parse().unwrap_or(0);
+            }
+        } else {
+            // Header: <hash> <original line> <final line> [<group size>]
+            let mut parts = line.split_whitespace();
+            if let (Some(hash), Some(_), Some(final_line)) =
+                (parts.next(), parts.next(), parts.next())
+            {
+                current = Some(BlameLine {
+                    line: final_line.parse().unwrap_or(0),
+                    hash: hash.to_string(),
+                    author: String::new(),
+                    timestamp: 0,
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+/// Parse `git log` output produced with the field/record separators above
+pub fn parse_log_output(output: &str) -> Vec<CommitInfo> {
+    output
+        .split(RECORD_SEP)
+        .filter_map(|record| {
+            let record = record.trim_start_matches('\n');
+            if record.trim().is_empty() {
+                return None;
+            }
+            let mut fields = record.splitn(6, FIELD_SEP);
+            Some(CommitInfo {
+                hash: fields.next()?.to_string(),
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+                body: fields.next().unwrap_or("").trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Truncate a diff to at most `max_chars`, cutting on a line boundary
+pub fn truncate_diff(diff: &str, max_chars: usize) -> String {
+    if diff.len() <= max_chars {
+        return diff.to_string();
+    }
+    let mut end = max_chars;
+    while end > 0 && !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    let cut = diff[..end].rfind('\n').unwrap_or(end);
+    format!(
+        "{}\n... [diff truncated, {} more bytes] ...",
+        &diff[..cut],
+        diff.len() - cut
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_log_output() {
+        let output = format!(
+            "abc123{0}abc{0}Alice{0}2024-01-01T10:00:00+00:00{0}Add parser{0}Body line{1}\n\
+             def456{0}def{0}Bob{0}2024-01-02T10:00:00+00:00{0}Fix bug{0}{1}\n",
+            FIELD_SEP, RECORD_SEP
+        );
+        let commits = parse_log_output(&output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].short_hash, "abc");
+        assert_eq!(commits[0].body, "Body line");
+        assert_eq!(commits[1].author, "Bob");
+        assert_eq!(commits[1].subject, "Fix bug");
+        assert!(commits[1].body.is_empty());
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let output = "abc123 1 1 2\n\
+                      author Alice\n\
+                      author-time 1700000000\n\
+                      summary Add parser\n\
+                      \tfn parse() {}\n\
+                      0000000000000000000000000000000000000000 2 2\n\
+                      author Not Committed Yet\n\
+                      author-time 1710000000\n\
+                      \t// TODO: tests\n";
+        let lines = parse_blame_porcelain(output);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].author, "Alice");
+        assert_eq!(lines[0].timestamp, 1_700_000_000);
+        assert!(!lines[0].is_uncommitted());
+        assert_eq!(lines[1].line, 2);
+        assert!(lines[1].is_uncommitted());
+    }
+
+    #[test]
+    fn test_truncate_diff() {
+        let diff = "line one\nline two\nline three\n";
+        assert_eq!(truncate_diff(diff, 100), diff);
+        let truncated = truncate_diff(diff, 12);
+        assert!(truncated.starts_with("line one"));
+        assert!(truncated.contains("diff truncated"));
+    }
+
+    #[tokio::test]
+    async fn test_not_a_git_repo() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let git = GitOps::new(temp_dir.path());
+        assert!(!git.is_git_repo().await);
+        assert!(git.list_commits("HEAD").await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stage_and_commit() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let git = GitOps::new(temp_dir.path());
+        git.run(&["init", "--quiet"]).await?;
+        git.run(&["config", "user.name", "Test"]).await?;
+        git.run(&["config", "user.email", "test@example.com"]).await?;
+
+        std::fs::write(temp_dir.path().join("a.txt"), "hello\n")?;
+        git.stage(&["a.txt".to_string()]).await?;
+        assert_eq!(git.staged_files().await?, vec!["a.txt"]);
+        assert!(git.staged_diff().await?.contains("+hello"));
+
+        let hash = git.commit("feat: add a.txt").await?;
+        assert!(!hash.is_empty());
+        assert!(git.staged_files().await?.is_empty());
+        let commits = git.list_commits("HEAD").await?;
+        assert_eq!(commits[0].subject, "feat: add a.txt");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_branches() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let git = GitOps::new(temp_dir.path());
+        git.run(&["init", "--quiet", "--initial-branch=main"]).await?;
+        git.run(&["config", "user.name", "Test"]).await?;
+        git.run(&["config", "user.email", "test@example.com"]).await?;
+        std::fs::write(temp_dir.path().join("a.txt"), "hello\n")?;
+        git.stage(&["a.txt".to_string()]).await?;
+        git.commit("feat: add a.txt").await?;
+
+        assert!(git.create_branch("bad..name").await.is_err());
+        git.create_branch("feature/greeting").await?;
+        assert_eq!(
+            git.current_branch().await?.as_deref(),
+            Some("feature/greeting")
+        );
+        std::fs::write(temp_dir.path().join("a.txt"), "hello world\n")?;
+        git.stage(&["a.txt".to_string()]).await?;
+        git.commit("feat: greet the world").await?;
+
+        assert_eq!(git.default_base_branch().await?, "main");
+        assert!(git.branch_diff("main").await?.contains("+hello world"));
+        assert_eq!(git.list_branches().await?, vec!["feature/greeting", "main"]);
+
+        git.switch_branch("main").await?;
+        assert_eq!(git.current_branch().await?.as_deref(), Some("main"));
+        Ok(())
+    }
+}
@@ -0,0 +1,567 @@
+//! Grounded question answering about the current repository
+//!
+//! Retrieves the code snippets most relevant to a question, asks the model to
+//! answer from those snippets only with a `[path:start-end]` citation for
+//! every claim, then verifies the citations. Answers with uncited claims or
+//! citations outside the retrieved snippets get one revision pass; anything
+//! still unsupported is reported alongside the answer.
+
+use crate::api::completion::complete;
+use crate::utils::config::Config;
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Number of snippets sent to the model
+pub const MAX_SNIPPETS: usize = 8;
+/// Lines per retrieval window
+const WINDOW_LINES: usize = 40;
+/// Files larger than this are not indexed
+const MAX_FILE_SIZE: u64 = 512 * 1024;
+/// Maximum number of files scanned per question
+const MAX_FILES: usize = 5_000;
+
+/// Reply used when retrieval finds nothing relevant
+pub const NOT_FOUND_ANSWER: &str =
+    "I couldn't find anything in this repository that answers that question.";
+
+/// Words too common to be useful retrieval keywords
+const STOPWORDS: &[&str] = &[
+    "the",
+    "and",
+    "for",
+    "are",
+    "but",
+    "not",
+    "you",
+    "all",
+    "any",
+    "can",
+    "how",
+    "what",
+    "where",
+    "when",
+    "which",
+    "who",
+    "why",
+    "does",
+    "did",
+    "this",
+    "that",
+    "these",
+    "those",
+    "with",
+    "from",
+    "into",
+    "there",
+    "their",
+    "about",
+    "have",
+    "has",
+    "was",
+    "were",
+    "will",
+    "would",
+    "should",
+    "could",
+    "its",
+    "use",
+    "used",
+    "using",
+    "work",
+    "works",
+    "code",
+    "file",
+    "files",
+    "repo",
+    "repository",
+    "project",
+    "explain",
+    "tell",
+    "show",
+    "find",
+    "get",
+    "set",
+];
+
+/// File extensions considered source or documentation
+const INDEXED_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cpp", "hpp", "cs", "rb",
+    "php", "swift", "scala", "sh", "toml", "yaml", "yml", "json", "md", "txt", "sql", "html",
+    "css", "vue", "svelte", "lua", "zig", "dart", "ex", "exs",
+];
+
+const GROUNDED_SYSTEM_PROMPT: &str = "You answer questions about a software repository using \
+ONLY the numbered code snippets provided. Every sentence or bullet that states a fact about the \
+code must end with a citation in the form [path:start-end] taken from the snippet headers. If the \
+snippets do not contain the answer, say so plainly instead of guessing. Never rely on outside \
+knowledge about the project.";
+
+static CITATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[([A-Za-z0-9_./\-]+\.[A-Za-z0-9]+)(?::(\d+)(?:-(\d+))?)?\]").unwrap()
+});
+
+static NUMBERED_CITATION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[(\d{1,2})\]").unwrap());
+
+static WORD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+/// A retrieved region of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Path relative to the repository root
+    pub path: String,
+    /// First line of the snippet (1-indexed)
+    pub start_line: usize,
+    /// Last line of the snippet (inclusive)
+    pub end_line: usize,
+    /// Snippet text
+    pub text: String,
+    /// Retrieval score (higher is more relevant)
+    pub score: f32,
+}
+
+impl Snippet {
+    /// Citation label for this snippet, e.g. `src/app.rs:10-49`
+    pub fn label(&self) -> String {
+        format!("{}:{}-{}", self.path, self.start_line, self.end_line)
+    }
+}
+
+/// A citation found in an answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Citation {
+    pub path: String,
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+}
+
+impl Citation {
+    /// Whether this citation falls inside a snippet's file and line range
+    pub fn matches(&self, snippet: &Snippet) -> bool {
+        snippet.path == self.path
+            && self.start_line.is_none_or(|start| {
+                let end = self.end_line.unwrap_or(start);
+                start <= snippet.end_line && end >= snippet.start_line
+            })
+    }
+
+    /// The citation as written, without brackets
+    pub fn label(&self) -> String {
+        match (self.start_line, self.end_line) {
+            (Some(start), Some(end)) => format!("{}:{}-{}", self.path, start, end),
+            (Some(start), None) => format!("{}:{}", self.path, start),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+/// A verified answer with its sources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroundedAnswer {
+    /// The answer text, with citations
+    pub answer: String,
+    /// Snippets the answer was grounded in
+    pub sources: Vec<Snippet>,
+    /// Problems the verification pass could not resolve
+    pub issues: Vec<String>,
+    /// Whether the answer was revised after verification
+    pub revised: bool,
+}
+
+/// Answer a question about the repository at `root` from retrieved snippets
+pub async fn answer_grounded(
+    config: &Config,
+    root: &Path,
+    question: &str,
+) -> Result<GroundedAnswer> {
+    let retrieval_root = root.to_path_buf();
+    let retrieval_question = question.to_string();
+    let sources = tokio::task::spawn_blocking(move || {
+        retrieve_snippets(&retrieval_root, &retrieval_question, MAX_SNIPPETS)
+    })
+    .await?;
+
+    if sources.is_empty() {
+        return Ok(GroundedAnswer {
+            answer: NOT_FOUND_ANSWER.to_string(),
+            sources,
+            issues: Vec::new(),
+            revised: false,
+        });
+    }
+
+    let prompt = build_grounded_prompt(question, &sources);
+    let mut answer = complete(config, GROUNDED_SYSTEM_PROMPT, &prompt)
+        .await?
+        .trim()
+        .to_string();
+    let mut issues = verify_answer(&answer, &sources);
+    let mut revised = false;
+
+    if !issues.is_empty() {
+        let revision = format!(
+            "{}\n\nYour previous answer:\n{}\n\nVerification found these problems:\n{}\n\n\
+             Rewrite the answer. Remove or fix every unsupported statement and cite a snippet \
+             for each remaining claim.",
+            prompt,
+            answer,
+            issues
+                .iter()
+                .map(|i| format!("- {}", i))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        answer = complete(config, GROUNDED_SYSTEM_PROMPT, &revision)
+            .await?
+            .trim()
+            .to_string();
+        issues = verify_answer(&answer, &sources);
+        revised = true;
+    }
+
+    Ok(GroundedAnswer {
+        answer,
+        sources,
+        issues,
+        revised,
+    })
+}
+
+/// Extract retrieval keywords from a question
+///
+/// Identifiers are split on `_` and camelCase boundaries; the full identifier
+/// is kept too so exact symbol names rank highest.
+pub fn extract_keywords(question: &str) -> Vec<String> {
+    let mut keywords = Vec::new();
+    let mut push = |word: String| {
+        if word.len() >= 3 && !STOPWORDS.contains(&word.as_str()) && !keywords.contains(&word) {
+            keywords.push(word);
+        }
+    };
+
+    for word in WORD.find_iter(question).map(|m| m.as_str()) {
+        push(word.to_lowercase());
+        let mut part = String::new();
+        for c in word.chars() {
+            if c == '_' || (c.is_uppercase() && !part.is_empty()) {
+                push(std::mem::take(&mut part).to_lowercase());
+            }
+            if c != '_' {
+                part.push(c);
+            }
+        }
+        push(part.to_lowercase());
+    }
+
+    keywords
+}
+
+/// Find the snippets of the repository most relevant to a question
+pub fn retrieve_snippets(root: &Path, question: &str, limit: usize) -> Vec<Snippet> {
+    let keywords = extract_keywords(question);
+    if keywords.is_empty() {
+        return Vec::new();
+    }
+
+    let mut snippets = Vec::new();
+    let walker = ignore::WalkBuilder::new(root).build();
+    for entry in walker.flatten().take(MAX_FILES) {
+        let path = entry.path();
+        let indexed = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| INDEXED_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if !indexed || !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if entry
+            .metadata()
+            .map(|m| m.len() > MAX_FILE_SIZE)
+            .unwrap_or(true)
+        {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        snippets.extend(score_file(&relative, &content, &keywords));
+    }
+
+    snippets.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.path.cmp(&b.path))
+            .then(a.start_line.cmp(&b.start_line))
+    });
+    snippets.truncate(limit);
+    snippets
+}
+
+/// Score the windows of one file, returning those that match any keyword
+fn score_file(path: &str, content: &str, keywords: &[String]) -> Vec<Snippet> {
+    let lines: Vec<&str> = content.lines().collect();
+    let path_lower = path.to_lowercase();
+    let path_bonus: f32 = keywords
+        .iter()
+        .filter(|k| path_lower.contains(k.as_str()))
+        .count() as f32
+        * 2.0;
+
+    let mut snippets = Vec::new();
+    // Half-overlapping windows so a match near a boundary stays in context
+    let step = WINDOW_LINES / 2;
+    let mut start = 0;
+    while start < lines.len() {
+        let end = (start + WINDOW_LINES).min(lines.len());
+        let text = lines[start..end].join("\n");
+        let lower = text.to_lowercase();
+
+        let mut matched = 0;
+        let mut hits = 0.0;
+        for keyword in keywords {
+            let count = lower.matches(keyword.as_str()).count();
+            if count > 0 {
+                matched += 1;
+                // Diminishing returns for repeated hits of the same keyword
+                hits += 1.0 + (count as f32).ln();
+            }
+        }
+        if matched > 0 {
+            // Windows covering more distinct keywords win over repetition
+            let score = hits * matched as f32 + path_bonus;
+            snippets.push(Snippet {
+                path: path.to_string(),
+                start_line: start + 1,
+                end_line: end,
+                text,
+                score,
+            });
+        }
+
+        if end == lines.len() {
+            break;
+        }
+        start += step;
+    }
+
+    // Keep only the best window per file so one file can't crowd out the rest
+    snippets.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    snippets.truncate(2);
+    snippets
+}
+
+/// Build the prompt listing the numbered snippets and the question
+pub fn build_grounded_prompt(question: &str, snippets: &[Snippet]) -> String {
+    let mut prompt = String::from("Code snippets:\n\n");
+    for (i, snippet) in snippets.iter().enumerate() {
+        prompt.push_str(&format!(
+            "[{}] {}\n```\n{}\n```\n\n",
+            i + 1,
+            snippet.label(),
+            snippet.text
+        ));
+    }
+    prompt.push_str(&format!(
+        "Question: {}\n\n\
+         Answer from the snippets above only. End every factual sentence or bullet with a \
+         citation like [{}]. If the snippets don't answer the question, say: \"{}\"",
+        question,
+        snippets
+            .first()
+            .map(Snippet::label)
+            .unwrap_or_else(|| "path:1-10".to_string()),
+        NOT_FOUND_ANSWER
+    ));
+    prompt
+}
+
+/// Find `[path:start-end]` citations in an answer
+pub fn parse_citations(answer: &str) -> Vec<Citation> {
+    CITATION
+        .captures_iter(answer)
+        .map(|c| to_citation(&c))
+        .collect()
+}
+
+fn to_citation(captures: &regex::Captures) -> Citation {
+    Citation {
+        path: captures[1].trim_start_matches("./").to_string(),
+        start_line: captures.get(2).and_then(|m| m.as_str().parse().ok()),
+        end_line: captures.get(3).and_then(|m| m.as_str().parse().ok()),
+    }
+}
+
+/// Replace citations with numbered markers (`[1]`, `[2]`) into `sources`
+///
+/// Path citations are mapped to the first source they fall inside; numbered
+/// markers the model wrote itself are kept. Returns the rewritten answer and
+/// the 1-based source numbers in order of first citation. Citations that
+/// match no source are left untouched.
+pub fn number_citations(answer: &str, sources: &[Snippet]) -> (String, Vec<usize>) {
+    let mut cited = Vec::new();
+    let mut cite = |n: usize| {
+        if !cited.contains(&n) {
+            cited.push(n);
+        }
+    };
+
+    let text = CITATION.replace_all(answer, |c: &regex::Captures| {
+        let citation = to_citation(c);
+        match sources.iter().position(|s| citation.matches(s)) {
+            Some(i) => {
+                cite(i + 1);
+                format!("[{}]", i + 1)
+            }
+            None => c[0].to_string(),
+        }
+    });
+
+    for c in NUMBERED_CITATION.captures_iter(&text) {
+        if let Ok(n) = c[1].parse::<usize>()
+            && (1..=sources.len()).contains(&n)
+        {
+            cite(n);
+        }
+    }
+
+    (text.into_owned(), cited)
+}
+
+/// Check an answer's citations against the retrieved snippets
+///
+/// Reports citations of files or lines that were not retrieved, and
+/// statements (sentences outside code blocks, headings excluded) without
+/// any citation.
+pub fn verify_answer(answer: &str, snippets: &[Snippet]) -> Vec<String> {
+    let mut issues = Vec::new();
+    if answer.trim() == NOT_FOUND_ANSWER {
+        return issues;
+    }
+
+    let mut reported = HashSet::new();
+    for citation in parse_citations(answer) {
+        if !snippets.iter().any(|s| citation.matches(s)) {
+            let label = citation.label();
+            if reported.insert(label.clone()) {
+                issues.push(format!(
+                    "Citation [{}] does not match any provided snippet",
+                    label
+                ));
+            }
+        }
+    }
+
+    let mut in_code_block = false;
+    for line in answer.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        // Lead-ins like "Here is how it works:" introduce cited bullets
+        if trimmed.ends_with(':') || trimmed.contains(NOT_FOUND_ANSWER) {
+            continue;
+        }
+        let numbered = NUMBERED_CITATION.captures_iter(trimmed).any(|c| {
+            c[1].parse::<usize>()
+                .is_ok_and(|n| (1..=snippets.len()).contains(&n))
+        });
+        if !CITATION.is_match(trimmed) && !numbered {
+            let preview: String = trimmed.chars().take(60).collect();
+            issues.push(format!("Uncited statement: \"{}\"", preview));
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn snippet(path: &str, start: usize, end: usize) -> Snippet {
+        Snippet {
+            path: path.to_string(),
+            start_line: start,
+            end_line: end,
+            text: String::new(),
+            score: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_extract_keywords() {
+        let keywords = extract_keywords("How does the GitOps stage_tracked function work?");
+        assert!(keywords.contains(&"gitops".to_string()));
+        assert!(keywords.contains(&"stage_tracked".to_string()));
+        assert!(keywords.contains(&"tracked".to_string()));
+        assert!(keywords.contains(&"function".to_string()));
+        assert!(!keywords.contains(&"how".to_string()));
+        assert!(!keywords.contains(&"the".to_string()));
+    }
+
+    #[test]
+    fn test_retrieve_snippets() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("config.rs"),
+            "pub fn load_config() {}\npub fn save_config() {}\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("other.rs"), "fn unrelated() {}\n").unwrap();
+
+        let snippets = retrieve_snippets(temp_dir.path(), "Where is the config loaded?", 5);
+        assert_eq!(snippets.len(), 1);
+        assert_eq!(snippets[0].path, "config.rs");
+        assert_eq!(snippets[0].label(), "config.rs:1-2");
+    }
+
+    #[test]
+    fn test_verify_answer() {
+        let snippets = vec![snippet("src/config.rs", 1, 40)];
+
+        let good = "Config is loaded from JSON [src/config.rs:10-20].\n\nSteps:\n- It reads the file [src/config.rs:12]";
+        assert!(verify_answer(good, &snippets).is_empty());
+
+        let bad = "Config is cached in Redis.\nIt is saved on exit [src/main.rs:5-9].";
+        let issues = verify_answer(bad, &snippets);
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].contains("src/main.rs:5-9"));
+        assert!(issues[1].contains("Uncited statement"));
+
+        assert!(verify_answer(NOT_FOUND_ANSWER, &snippets).is_empty());
+        assert!(verify_answer("It is loaded at startup [1].", &snippets).is_empty());
+    }
+
+    #[test]
+    fn test_number_citations() {
+        let sources = vec![snippet("src/a.rs", 1, 40), snippet("src/b.rs", 21, 60)];
+        let (text, cited) = number_citations(
+            "B runs first [src/b.rs:30-35]. Then A [src/a.rs:2]. Again [src/b.rs:40], \
+             see also [1] and [src/c.rs:1-5].",
+            &sources,
+        );
+        assert_eq!(
+            text,
+            "B runs first [2]. Then A [1]. Again [2], see also [1] and [src/c.rs:1-5]."
+        );
+        assert_eq!(cited, vec![2, 1]);
+    }
+}
@@ -0,0 +1,278 @@
+//! Event hooks: user commands run when the agent does something
+//!
+//! Hooks come from two places:
+//!
+//! - scripts in `~/.arula/hooks/` named after an event (`run_finished`,
+//!   `run_finished.sh`, `run_finished.py`, ...), which must be executable
+//! - shell commands under `hooks` in config.json, either at the top level or
+//!   inside a provider (provider hooks run in addition to the global ones):
+//!
+//! ```json
+//! "hooks": {
+//!   "run_finished": ["notify-send 'ARULA is done'"],
+//!   "tool_executed": ["jq -c . >> ~/.arula/tools.log"]
+//! }
+//! ```
+//!
+//! Each hook gets a JSON object on stdin with `event`, `timestamp`,
+//! `provider`, `model`, `cwd` and event-specific `data`; the event name is
+//! also in `ARULA_HOOK_EVENT`. Hooks run in the background and never block
+//! or fail the session; ones still running after 30 seconds are killed.
+
+use crate::utils::config::Config;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Longest a hook may run before it is killed
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Shell commands to run, by event name (`hooks` in config.json)
+pub type HooksConfig = BTreeMap<String, Vec<String>>;
+
+/// Something the agent did that hooks can react to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A CLI or desktop session started
+    SessionStart,
+    /// A tool call finished (successfully or not)
+    ToolExecuted,
+    /// The agent finished answering a prompt
+    RunFinished,
+    /// The agent is waiting on the user (for example an `ask_question` call)
+    ApprovalRequired,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 4] = [
+        HookEvent::SessionStart,
+        HookEvent::ToolExecuted,
+        HookEvent::RunFinished,
+        HookEvent::ApprovalRequired,
+    ];
+
+    /// Name used in config.json, script names and payloads
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::SessionStart => "session_start",
+            HookEvent::ToolExecuted => "tool_executed",
+            HookEvent::RunFinished => "run_finished",
+            HookEvent::ApprovalRequired => "approval_required",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.name() == name)
+    }
+}
+
+/// How a hook is run
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HookCommand {
+    /// A command line run through the shell
+    Shell(String),
+    /// A script run directly
+    Script(PathBuf),
+}
+
+impl HookCommand {
+    fn command(&self) -> Command {
+        match self {
+            HookCommand::Shell(line) => crate::platform::current().shell_command(line),
+            HookCommand::Script(path) => Command::new(path),
+        }
+    }
+}
+
+/// The hooks configured for a session
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    commands: Vec<(HookEvent, HookCommand)>,
+    provider: String,
+    model: String,
+}
+
+impl Hooks {
+    /// Hooks from config.json (global and active provider) and the hooks directory
+    pub fn from_config(config: &Config) -> Self {
+        let mut hooks = Self {
+            commands: Vec::new(),
+            provider: config.active_provider.clone(),
+            model: config.get_model(),
+        };
+
+        let provider_hooks = config
+            .get_active_provider_config()
+            .and_then(|p| p.hooks.as_ref());
+        for configured in config.hooks.iter().chain(provider_hooks) {
+            for (name, lines) in configured {
+                let Some(event) = HookEvent::from_name(name) else {
+                    continue;
+                };
+                hooks.commands.extend(
+                    lines
+                        .iter()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(|line| (event, HookCommand::Shell(line.clone()))),
+                );
+            }
+        }
+
+        if let Some(dir) = hooks_dir() {
+            hooks.commands.extend(
+                discover_scripts(&dir)
+                    .into_iter()
+                    .map(|(event, path)| (event, HookCommand::Script(path))),
+            );
+        }
+        hooks
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Number of hooks for an event
+    pub fn count(&self, event: HookEvent) -> usize {
+        self.commands.iter().filter(|(e, _)| *e == event).count()
+    }
+
+    /// The JSON a hook receives on stdin
+    pub fn payload(&self, event: HookEvent, data: Value) -> Value {
+        json!({
+            "event": event.name(),
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "provider": self.provider,
+            "model": self.model,
+            "cwd": std::env::current_dir().ok(),
+            "data": data,
+        })
+    }
+
+    /// Run every hook for `event` in the background
+    pub fn fire(&self, event: HookEvent, data: Value) {
+        if self.count(event) == 0 {
+            return;
+        }
+        let payload = self.payload(event, data).to_string();
+        for (_, hook) in self.commands.iter().filter(|(e, _)| *e == event) {
+            let hook = hook.clone();
+            let payload = payload.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = run_hook(&hook, event, &payload) {
+                    crate::utils::logger::warn(&format!(
+                        "{} hook {:?} failed: {}",
+                        event.name(),
+                        hook,
+                        e
+                    ));
+                }
+            });
+        }
+    }
+}
+
+/// `~/.arula/hooks`
+pub fn hooks_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join("hooks"))
+}
+
+/// Executable files in `dir` whose name (without extension) is an event
+fn discover_scripts(dir: &Path) -> Vec<(HookEvent, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<(HookEvent, PathBuf)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_executable(path))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?;
+            Some((HookEvent::from_name(stem)?, path))
+        })
+        .collect();
+    // Run order follows file names
+    scripts.sort_by(|a, b| a.1.cmp(&b.1));
+    scripts
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Run one hook to completion, feeding it the payload
+fn run_hook(hook: &HookCommand, event: HookEvent, payload: &str) -> std::io::Result<()> {
+    let mut child = hook
+        .command()
+        .env("ARULA_HOOK_EVENT", event.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input closes the pipe early; that's fine
+        let _ = stdin.write_all(payload.as_bytes());
+    }
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            if !status.success() {
+                return Err(std::io::Error::other(format!("exited with {}", status)));
+            }
+            return Ok(());
+        }
+        if started.elapsed() > HOOK_TIMEOUT {
+            let _ = child.kill();
+            return Err(std::io::Error::other("timed out"));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_names() {
+        for event in HookEvent::ALL {
+            assert_eq!(HookEvent::from_name(event.name()), Some(event));
+        }
+        assert_eq!(HookEvent::from_name("tool_started"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hook_receives_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.json");
+        let hook = HookCommand::Shell(format!("cat > '{}'", out.display()));
+        let hooks = Hooks {
+            commands: vec![(HookEvent::RunFinished, hook.clone())],
+            provider: "ollama".to_string(),
+            model: "llama3".to_string(),
+        };
+
+        let payload = hooks.payload(HookEvent::RunFinished, json!({ "success": true }));
+        run_hook(&hook, HookEvent::RunFinished, &payload.to_string()).unwrap();
+
+        let written: Value = serde_json::from_str(&std::fs::read_to_string(out).unwrap()).unwrap();
+        assert_eq!(written["event"], "run_finished");
+        assert_eq!(written["model"], "llama3");
+        assert_eq!(written["data"]["success"], true);
+        assert_eq!(hooks.count(HookEvent::ToolExecuted), 0);
+    }
+}
@@ -0,0 +1,284 @@
+//! Icons that render in any terminal or font
+//!
+//! Every icon shown in menus, status bars and message prefixes goes through
+//! [`Icon`], which resolves to emoji, Nerd Font glyphs, plain Unicode symbols
+//! or ASCII depending on the active [`IconSet`]. The set comes from the
+//! `icons` config setting or the `ARULA_ICONS` environment variable, and is
+//! otherwise detected from the terminal and locale.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Which glyphs icons are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IconSet {
+    /// Color emoji (needs a terminal and font that render them)
+    Emoji,
+    /// Nerd Font private-use glyphs (needs a patched font)
+    Nerd,
+    /// Text-presentation Unicode symbols, available in most fonts
+    Unicode,
+    /// Plain ASCII for limited terminals and non-UTF-8 locales
+    Ascii,
+}
+
+impl IconSet {
+    pub const ALL: [IconSet; 4] = [
+        IconSet::Emoji,
+        IconSet::Nerd,
+        IconSet::Unicode,
+        IconSet::Ascii,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IconSet::Emoji => "emoji",
+            IconSet::Nerd => "nerd",
+            IconSet::Unicode => "unicode",
+            IconSet::Ascii => "ascii",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|set| set.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Detect the best set for the current terminal
+    pub fn detect() -> Self {
+        Self::detect_from(|name| std::env::var(name).ok())
+    }
+
+    /// Detect from environment variables looked up with `env`
+    ///
+    /// Nerd Fonts can't be detected from a terminal, so that set is only
+    /// used when asked for.
+    pub fn detect_from(env: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(set) = env("ARULA_ICONS").as_deref().and_then(Self::from_name) {
+            return set;
+        }
+
+        let term = env("TERM").unwrap_or_default();
+        if term == "dumb" || term == "linux" {
+            return IconSet::Ascii;
+        }
+        // Windows Terminal sets WT_SESSION; the legacy console has no emoji font
+        if env("WT_SESSION").is_some() {
+            return IconSet::Emoji;
+        }
+        if cfg!(windows) {
+            return IconSet::Unicode;
+        }
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|name| env(name).filter(|v| !v.is_empty()))
+            .unwrap_or_default()
+            .to_lowercase();
+        if !locale.contains("utf-8") && !locale.contains("utf8") {
+            return IconSet::Ascii;
+        }
+
+        let program = env("TERM_PROGRAM").unwrap_or_default();
+        let emoji_terminal = matches!(
+            program.as_str(),
+            "iTerm.app" | "Apple_Terminal" | "WezTerm" | "vscode" | "ghostty" | "Hyper"
+        ) || term.contains("kitty")
+            || term.contains("ghostty")
+            || env("KITTY_WINDOW_ID").is_some()
+            || env("VTE_VERSION").is_some();
+        if emoji_terminal {
+            IconSet::Emoji
+        } else {
+            IconSet::Unicode
+        }
+    }
+}
+
+impl fmt::Display for IconSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 0 until set or first detected, otherwise the index in `IconSet::ALL` + 1
+static ACTIVE_SET: AtomicU8 = AtomicU8::new(0);
+
+/// Set the icon set used by [`Icon::glyph`] and `Display`
+pub fn set_icon_set(set: IconSet) {
+    let index = IconSet::ALL.iter().position(|s| *s == set).unwrap_or(0);
+    ACTIVE_SET.store(index as u8 + 1, Ordering::Relaxed);
+}
+
+/// The active icon set, detecting it on first use if none was set
+pub fn icon_set() -> IconSet {
+    match ACTIVE_SET.load(Ordering::Relaxed) {
+        0 => {
+            let set = IconSet::detect();
+            set_icon_set(set);
+            set
+        }
+        n => IconSet::ALL[(n - 1) as usize],
+    }
+}
+
+/// An icon with a glyph in every set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    // Tools
+    Shell,
+    FileRead,
+    FileWrite,
+    FileEdit,
+    Directory,
+    Search,
+    Web,
+    Question,
+    Mcp,
+    Vision,
+    Tool,
+    // Status
+    Success,
+    Error,
+    Warning,
+    Info,
+    Working,
+    Thinking,
+    Response,
+    // People and places
+    Prompt,
+    Profile,
+    Grounded,
+    Settings,
+    Key,
+    History,
+    Lock,
+    Microphone,
+}
+
+impl Icon {
+    /// The icon for a tool by its function name
+    pub fn for_tool(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "execute_bash" => Icon::Shell,
+            "read_file" => Icon::FileRead,
+            "write_file" => Icon::FileWrite,
+            "edit_file" => Icon::FileEdit,
+            "list_directory" => Icon::Directory,
+            "search_files" => Icon::Search,
+            "web_search" => Icon::Web,
+            "ask_question" => Icon::Question,
+            "mcp_call" => Icon::Mcp,
+            "visioneer" | "capture_screen" | "analyze_ui" => Icon::Vision,
+            _ => Icon::Tool,
+        }
+    }
+
+    /// The glyph in the active icon set
+    pub fn glyph(self) -> &'static str {
+        self.glyph_in(icon_set())
+    }
+
+    /// The glyph in a given icon set
+    pub fn glyph_in(self, set: IconSet) -> &'static str {
+        let (emoji, nerd, unicode, ascii) = match self {
+            Icon::Shell => ("⚙️", "\u{f489}", "○", "$"),
+            Icon::FileRead => ("📖", "\u{f0f6}", "○", "r"),
+            Icon::FileWrite => ("✍️", "\u{f0c7}", "□", "w"),
+            Icon::FileEdit => ("✏️", "\u{f040}", "□", "e"),
+            Icon::Directory => ("📂", "\u{f07c}", "◇", "d"),
+            Icon::Search => ("🔍", "\u{f002}", "○", "/"),
+            Icon::Web => ("🌐", "\u{f0ac}", "◎", "@"),
+            Icon::Question => ("❓", "\u{f059}", "?", "?"),
+            Icon::Mcp => ("🔌", "\u{f1e6}", "◊", "m"),
+            Icon::Vision => ("👁️", "\u{f06e}", "◉", "v"),
+            Icon::Tool => ("🔧", "\u{f0ad}", "□", "#"),
+            Icon::Success => ("✅", "\u{f00c}", "✓", "+"),
+            Icon::Error => ("❌", "\u{f00d}", "✗", "x"),
+            Icon::Warning => ("⚠️", "\u{f071}", "⚠", "!"),
+            Icon::Info => ("ℹ️", "\u{f05a}", "ℹ", "i"),
+            Icon::Working => ("⚡", "\u{f0e7}", "◆", "*"),
+            Icon::Thinking => ("💭", "\u{f0eb}", "∴", "~"),
+            Icon::Response => ("✨", "\u{f0d0}", "✦", "+"),
+            Icon::Prompt => ("▶", "\u{f054}", "▶", ">"),
+            Icon::Profile => ("👤", "\u{f007}", "◈", "@"),
+            Icon::Grounded => ("📚", "\u{f02d}", "≡", "="),
+            Icon::Settings => ("⚙️", "\u{f013}", "⚙", "*"),
+            Icon::Key => ("🔑", "\u{f084}", "⚷", "k"),
+            Icon::History => ("📚", "\u{f1da}", "↺", "h"),
+            Icon::Lock => ("🔒", "\u{f023}", "⚿", "L"),
+            Icon::Microphone => ("🎙️", "\u{f130}", "●", "o"),
+        };
+        match set {
+            IconSet::Emoji => emoji,
+            IconSet::Nerd => nerd,
+            IconSet::Unicode => unicode,
+            IconSet::Ascii => ascii,
+        }
+    }
+}
+
+impl fmt::Display for Icon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.glyph())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn test_detect_icon_set() {
+        assert_eq!(
+            IconSet::detect_from(env(&[("ARULA_ICONS", "Nerd"), ("TERM", "dumb")])),
+            IconSet::Nerd
+        );
+        assert_eq!(
+            IconSet::detect_from(env(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")])),
+            IconSet::Ascii
+        );
+        if cfg!(not(windows)) {
+            assert_eq!(
+                IconSet::detect_from(env(&[("TERM", "xterm-256color"), ("LANG", "C")])),
+                IconSet::Ascii
+            );
+            assert_eq!(
+                IconSet::detect_from(env(&[
+                    ("TERM", "xterm-256color"),
+                    ("LANG", "en_US.UTF-8"),
+                    ("TERM_PROGRAM", "WezTerm"),
+                ])),
+                IconSet::Emoji
+            );
+            assert_eq!(
+                IconSet::detect_from(env(&[("TERM", "xterm"), ("LC_ALL", "de_DE.utf8")])),
+                IconSet::Unicode
+            );
+        }
+    }
+
+    #[test]
+    fn test_glyphs() {
+        assert_eq!(Icon::for_tool("execute_bash"), Icon::Shell);
+        assert_eq!(Icon::for_tool("unknown_tool"), Icon::Tool);
+        // ASCII glyphs must stay single-width and printable
+        for icon in [Icon::Shell, Icon::Success, Icon::Error, Icon::Prompt] {
+            assert!(icon.glyph_in(IconSet::Ascii).is_ascii());
+            assert!(!icon.glyph_in(IconSet::Unicode).is_empty());
+        }
+        assert_eq!(IconSet::from_name(" ASCII"), Some(IconSet::Ascii));
+        assert_eq!(IconSet::from_name("auto"), None);
+    }
+}
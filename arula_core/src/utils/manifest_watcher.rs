@@ -0,0 +1,261 @@
+//! PROJECT.manifest auto-refresh
+//!
+//! Watches the project's build manifests (Cargo.toml, package.json,
+//! pyproject.toml, go.mod, ...) and regenerates the auto-detected parts of
+//! PROJECT.manifest when they change. Enabled with the
+//! `context.manifest_auto_refresh` setting.
+//!
+//! AI-enhanced manifests (starting with `MANIFEST_MARKER_AI`) are merged
+//! rather than overwritten: only the auto-generated sections (METADATA,
+//! DEPENDENCIES, ENTRY POINTS, WORKFLOW, KEY SYMBOLS) are replaced, and
+//! everything the AI wrote is kept as-is.
+
+use crate::utils::logger;
+use crate::utils::project_context::{
+    MANIFEST_MARKER_AI, detect_project, generate_indexed_manifest,
+};
+use anyhow::{Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Manifest file kept up to date
+pub const MANIFEST_FILE: &str = "PROJECT.manifest";
+
+/// Build files whose changes trigger a refresh
+pub const WATCHED_FILES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "requirements.txt",
+    "setup.py",
+    "go.mod",
+];
+
+/// Section headings written by `generate_auto_manifest`
+const AUTO_SECTIONS: &[&str] = &[
+    "# METADATA",
+    "# DEPENDENCIES",
+    "# ENTRY POINTS",
+    "# WORKFLOW",
+    "# KEY SYMBOLS",
+];
+
+/// Quiet period before refreshing, so editor save bursts refresh once
+const DEBOUNCE: Duration = Duration::from_millis(750);
+/// How deep to look for nested build files (workspace members)
+const MAX_WATCH_DEPTH: usize = 3;
+
+/// Watches build files and refreshes PROJECT.manifest when they change
+///
+/// Watching stops when the watcher is dropped.
+pub struct ManifestWatcher {
+    root: PathBuf,
+    _watcher: RecommendedWatcher,
+}
+
+impl ManifestWatcher {
+    /// Start watching the project at `root`
+    ///
+    /// Only directories that contain a watched build file are watched (not
+    /// recursively), which keeps `target/` and `node_modules/` out of it.
+    pub fn start(root: &Path) -> Result<Self> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let (tx, rx) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                if let Ok(event) = event
+                    && !matches!(event.kind, EventKind::Access(_))
+                    && event.paths.iter().any(|p| is_watched_file(p))
+                {
+                    let _ = tx.send(());
+                }
+            })
+            .context("Failed to create file watcher")?;
+
+        for dir in watched_dirs(&root) {
+            watcher
+                .watch(&dir, RecursiveMode::NonRecursive)
+                .with_context(|| format!("Failed to watch {}", dir.display()))?;
+        }
+
+        let refresh_root = root.clone();
+        std::thread::spawn(move || {
+            // Ends when the watcher (and with it the sender) is dropped
+            while rx.recv().is_ok() {
+                while rx.recv_timeout(DEBOUNCE).is_ok() {}
+                match refresh_manifest(&refresh_root) {
+                    Ok(true) => logger::info(&format!(
+                        "Refreshed {} after a build file change",
+                        MANIFEST_FILE
+                    )),
+                    Ok(false) => {}
+                    Err(e) => logger::warn(&format!("Failed to refresh {}: {}", MANIFEST_FILE, e)),
+                }
+            }
+        });
+
+        Ok(Self {
+            root,
+            _watcher: watcher,
+        })
+    }
+
+    /// Project root being watched
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+/// Regenerate PROJECT.manifest from the current build files
+///
+/// Does nothing (and returns false) when there is no manifest, the project
+/// can't be detected, or the content is unchanged.
+pub fn refresh_manifest(root: &Path) -> Result<bool> {
+    let path = root.join(MANIFEST_FILE);
+    let Ok(existing) = std::fs::read_to_string(&path) else {
+        return Ok(false);
+    };
+    let Some(project) = detect_project(root) else {
+        return Ok(false);
+    };
+
+    let generated = generate_indexed_manifest(&project, root);
+    let updated = merge_manifest(&existing, &generated);
+    if updated == existing {
+        return Ok(false);
+    }
+    std::fs::write(&path, updated)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+/// Merge a freshly generated manifest into an existing one
+///
+/// Auto-generated manifests are replaced outright. In AI-enhanced ones each
+/// auto-generated section is replaced in place (a section runs from its
+/// heading to the next blank line) and sections the file lacks are appended.
+pub fn merge_manifest(existing: &str, generated: &str) -> String {
+    if !existing.starts_with(MANIFEST_MARKER_AI) {
+        return generated.to_string();
+    }
+
+    let fresh = auto_sections(generated);
+    let mut replaced = BTreeSet::new();
+    let mut output = Vec::new();
+    let mut lines = existing.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let heading = line.trim_end();
+        let Some((_, body)) = fresh.iter().find(|(h, _)| *h == heading) else {
+            output.push(line.to_string());
+            continue;
+        };
+        output.push(heading.to_string());
+        output.extend(body.iter().cloned());
+        replaced.insert(heading);
+        // Skip the old section body
+        while let Some(next) = lines.peek() {
+            if next.trim().is_empty() || AUTO_SECTIONS.contains(&next.trim_end()) {
+                break;
+            }
+            lines.next();
+        }
+    }
+
+    for (heading, body) in &fresh {
+        if replaced.contains(heading) {
+            continue;
+        }
+        if output.last().is_some_and(|l| !l.trim().is_empty()) {
+            output.push(String::new());
+        }
+        output.push(heading.to_string());
+        output.extend(body.iter().cloned());
+    }
+
+    let mut merged = output.join("\n");
+    merged.push('\n');
+    merged
+}
+
+/// Split the auto-generated sections out of a generated manifest
+fn auto_sections(generated: &str) -> Vec<(&'static str, Vec<String>)> {
+    let mut sections: Vec<(&'static str, Vec<String>)> = Vec::new();
+    let mut current: Option<usize> = None;
+    for line in generated.lines() {
+        if let Some(heading) = AUTO_SECTIONS.iter().find(|h| **h == line.trim_end()) {
+            sections.push((heading, Vec::new()));
+            current = Some(sections.len() - 1);
+        } else if line.trim().is_empty() {
+            current = None;
+        } else if let Some(index) = current {
+            sections[index].1.push(line.to_string());
+        }
+    }
+    sections
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| WATCHED_FILES.contains(&n))
+}
+
+/// The root plus every directory holding a watched build file
+fn watched_dirs(root: &Path) -> BTreeSet<PathBuf> {
+    let mut dirs = BTreeSet::from([root.to_path_buf()]);
+    let walker = ignore::WalkBuilder::new(root)
+        .max_depth(Some(MAX_WATCH_DEPTH))
+        .build();
+    for entry in walker.flatten() {
+        if is_watched_file(entry.path())
+            && let Some(dir) = entry.path().parent()
+        {
+            dirs.insert(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "# AUTO-GENERATED by ARULA\n\nPROJECT_MANIFEST v1.0\n\n\
+        # METADATA\nname: demo\ntype: Rust\n\n\
+        # DEPENDENCIES\n- serde\n- tokio\n\n\
+        # WORKFLOW\nbuild: cargo build\n\n\
+        # KEY SYMBOLS\n- src/lib.rs:1 pub fn run()\n";
+
+    #[test]
+    fn test_merge_auto_manifest_is_replaced() {
+        let existing = "# AUTO-GENERATED by ARULA\n\n# METADATA\nname: old\n";
+        assert_eq!(merge_manifest(existing, GENERATED), GENERATED);
+    }
+
+    #[test]
+    fn test_merge_preserves_ai_sections() {
+        let existing = "# AI-ENHANCED by ARULA\n# 2025-01-01\n\n\
+            ## Essence\nA demo tool.\n\n\
+            # METADATA\nname: demo\ntype: Rust\n\n\
+            # DEPENDENCIES\n- serde\n# ... and 3 more\n\n\
+            ## Gotchas\nKeep it simple.\n";
+
+        let merged = merge_manifest(existing, GENERATED);
+        assert_eq!(
+            merged,
+            "# AI-ENHANCED by ARULA\n# 2025-01-01\n\n\
+             ## Essence\nA demo tool.\n\n\
+             # METADATA\nname: demo\ntype: Rust\n\n\
+             # DEPENDENCIES\n- serde\n- tokio\n\n\
+             ## Gotchas\nKeep it simple.\n\n\
+             # WORKFLOW\nbuild: cargo build\n\n\
+             # KEY SYMBOLS\n- src/lib.rs:1 pub fn run()\n"
+        );
+        // Merging again is stable
+        assert_eq!(merge_manifest(&merged, GENERATED), merged);
+    }
+}
@@ -0,0 +1,315 @@
+//! Long-term memory: facts the agent keeps across sessions
+//!
+//! The agent saves short facts (user preferences, project conventions) with
+//! the `remember` tool and looks them up with `recall`. Facts live in one of
+//! two JSON stores:
+//!
+//! - `~/.arula/memory.json` — user scope, shared by every project
+//! - `<project>/.arula/memory.json` — project scope
+//!
+//! At session start the newest facts from both stores are added to the
+//! system prompt, up to `context.memory_max_chars` characters.
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// File name of a memory store inside its `.arula` directory
+pub const MEMORY_FILE: &str = "memory.json";
+/// Longest fact that can be stored, in characters
+pub const MAX_FACT_CHARS: usize = 500;
+
+/// Where a fact is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryScope {
+    /// Shared by every project
+    User,
+    /// Only for the current project
+    Project,
+}
+
+impl MemoryScope {
+    /// Parse "user"/"global" or "project"
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "user" | "global" => Some(Self::User),
+            "project" => Some(Self::Project),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Project => "project",
+        }
+    }
+
+    /// Path of the store for this scope
+    pub fn path(&self, project_root: &Path) -> Option<PathBuf> {
+        match self {
+            Self::User => dirs::home_dir().map(|home| home.join(".arula").join(MEMORY_FILE)),
+            Self::Project => Some(project_root.join(".arula").join(MEMORY_FILE)),
+        }
+    }
+}
+
+/// A remembered fact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Memory {
+    pub id: u32,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A JSON file of remembered facts
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    path: PathBuf,
+    memories: Vec<Memory>,
+}
+
+impl MemoryStore {
+    /// Open the store at `path`; a missing file is an empty store
+    pub fn open(path: &Path) -> Result<Self> {
+        let memories = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            memories,
+        })
+    }
+
+    /// Open the store for a scope
+    pub fn open_scope(scope: MemoryScope, project_root: &Path) -> Result<Self> {
+        let path = scope
+            .path(project_root)
+            .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+        Self::open(&path)
+    }
+
+    /// Stored facts, oldest first
+    pub fn memories(&self) -> &[Memory] {
+        &self.memories
+    }
+
+    /// Store a fact; remembering a fact that is already stored returns the
+    /// existing entry, with any new tags added
+    pub fn remember(&mut self, text: &str, tags: &[String]) -> Result<&Memory> {
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            return Err(anyhow!("Nothing to remember"));
+        }
+        if text.chars().count() > MAX_FACT_CHARS {
+            return Err(anyhow!(
+                "Fact is too long ({} characters, max {}); store a shorter summary",
+                text.chars().count(),
+                MAX_FACT_CHARS
+            ));
+        }
+        let tags: Vec<String> = tags
+            .iter()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        if let Some(index) = self
+            .memories
+            .iter()
+            .position(|m| m.text.eq_ignore_ascii_case(&text))
+        {
+            let existing = &mut self.memories[index];
+            for tag in tags {
+                if !existing.tags.contains(&tag) {
+                    existing.tags.push(tag);
+                }
+            }
+            return Ok(&self.memories[index]);
+        }
+
+        let id = self.memories.iter().map(|m| m.id).max().unwrap_or(0) + 1;
+        self.memories.push(Memory {
+            id,
+            text,
+            tags,
+            created_at: Utc::now(),
+        });
+        Ok(self.memories.last().unwrap())
+    }
+
+    /// Remove a fact by id; returns whether it existed
+    pub fn forget(&mut self, id: u32) -> bool {
+        let before = self.memories.len();
+        self.memories.retain(|m| m.id != id);
+        self.memories.len() != before
+    }
+
+    /// Facts matching `query`, best match first
+    ///
+    /// Facts are ranked by how many query words appear in their text or
+    /// tags, newest first on ties. An empty query returns the newest facts.
+    pub fn recall(&self, query: &str, limit: usize) -> Vec<&Memory> {
+        let query = words(query);
+        let mut scored: Vec<(usize, &Memory)> = self
+            .memories
+            .iter()
+            .map(|m| {
+                let mut haystack = words_of(&m.text);
+                haystack.extend(m.tags.iter().flat_map(|t| words(t)));
+                (query.iter().filter(|w| haystack.contains(*w)).count(), m)
+            })
+            .filter(|(score, _)| query.is_empty() || *score > 0)
+            .collect();
+        scored.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then(b.1.created_at.cmp(&a.1.created_at))
+                .then(b.1.id.cmp(&a.1.id))
+        });
+        scored.into_iter().take(limit).map(|(_, m)| m).collect()
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let content = serde_json::to_string_pretty(&self.memories)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// Lowercased words of at least two characters
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.len() > 1)
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn words_of(text: &str) -> HashSet<String> {
+    words(text).into_iter().collect()
+}
+
+/// Remembered facts to add to the system prompt, newest first, trimmed to
+/// `max_chars`; `None` when nothing is stored
+pub fn memory_context(project_root: &Path, max_chars: usize) -> Option<String> {
+    let mut sections = Vec::new();
+    for (scope, title) in [
+        (MemoryScope::Project, "Project"),
+        (MemoryScope::User, "User"),
+    ] {
+        let Ok(store) = MemoryStore::open_scope(scope, project_root) else {
+            continue;
+        };
+        let mut memories: Vec<&Memory> = store.memories().iter().collect();
+        memories.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        let lines: Vec<String> = memories.iter().map(|m| format!("- {}", m.text)).collect();
+        if !lines.is_empty() {
+            sections.push((title, lines));
+        }
+    }
+    format_context(&sections, max_chars)
+}
+
+fn format_context(sections: &[(&str, Vec<String>)], max_chars: usize) -> Option<String> {
+    let mut out = String::new();
+    for (title, lines) in sections {
+        let heading = format!("\n### {}\n", title);
+        let mut section = String::new();
+        for line in lines {
+            if out.len() + heading.len() + section.len() + line.len() + 1 > max_chars {
+                break;
+            }
+            section.push_str(line);
+            section.push('\n');
+        }
+        if !section.is_empty() {
+            out.push_str(&heading);
+            out.push_str(&section);
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store(dir: &TempDir) -> MemoryStore {
+        MemoryStore::open(&dir.path().join(MEMORY_FILE)).unwrap()
+    }
+
+    #[test]
+    fn test_remember_persists_and_dedupes() {
+        let dir = TempDir::new().unwrap();
+        let mut memory = store(&dir);
+        memory
+            .remember("Use  tabs for indentation", &["style".to_string()])
+            .unwrap();
+        memory
+            .remember("use tabs for indentation", &["Format".to_string()])
+            .unwrap();
+        assert!(memory.remember("   ", &[]).is_err());
+        assert!(memory.remember(&"x".repeat(MAX_FACT_CHARS + 1), &[]).is_err());
+        memory.save().unwrap();
+
+        let reopened = store(&dir);
+        assert_eq!(reopened.memories().len(), 1);
+        assert_eq!(reopened.memories()[0].text, "Use tabs for indentation");
+        assert_eq!(reopened.memories()[0].tags, vec!["style", "format"]);
+    }
+
+    #[test]
+    fn test_recall_ranks_by_overlap() {
+        let dir = TempDir::new().unwrap();
+        let mut memory = store(&dir);
+        memory.remember("The user prefers short answers", &[]).unwrap();
+        memory
+            .remember("Run tests with cargo nextest", &["testing".to_string()])
+            .unwrap();
+        memory.remember("Tests live next to the code", &[]).unwrap();
+
+        let hits = memory.recall("how do I run tests", 10);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].text, "Run tests with cargo nextest");
+
+        assert_eq!(memory.recall("testing", 10).len(), 1);
+        assert!(memory.recall("database", 10).is_empty());
+        assert_eq!(memory.recall("", 2).len(), 2);
+
+        let id = hits[0].id;
+        assert!(memory.forget(id));
+        assert!(!memory.forget(id));
+    }
+
+    #[test]
+    fn test_context_respects_cap() {
+        let sections = vec![
+            ("Project", vec!["- first".to_string(), "- second".to_string()]),
+            ("User", vec!["- third".to_string()]),
+        ];
+        let full = format_context(&sections, 1000).unwrap();
+        assert!(full.contains("### Project\n- first\n- second\n"));
+        assert!(full.contains("### User\n- third\n"));
+
+        let capped = format_context(&sections, 25).unwrap();
+        assert!(capped.contains("- first"));
+        assert!(!capped.contains("- second"));
+        assert!(!capped.contains("User"));
+
+        assert!(format_context(&sections, 5).is_none());
+    }
+}
@@ -2,22 +2,102 @@
 //!
 //! Contains shared utilities, configuration management, data structures, and helper functions.
 
+pub mod accessibility;
+pub mod architecture;
+pub mod brief;
 pub mod changelog;
 pub mod chat;
+pub mod code_lint;
+pub mod code_runner;
 pub mod colors;
+pub mod compaction;
+pub mod command_explain;
+pub mod commit_message;
 pub mod config;
+pub mod config_validation;
+pub mod config_watcher;
 pub mod conversation;
+pub mod crypto;
 pub mod debug;
+pub mod digest;
+pub mod doctor;
+pub mod env_expand;
 pub mod error;
+pub mod error_help;
 pub mod error_utils;
+pub mod file_context;
+pub mod file_lock;
+pub mod file_mentions;
+pub mod git_context;
+pub mod git_ops;
 pub mod git_state;
-pub mod logger;
+pub mod grounded;
+pub mod hooks;
+pub mod icons;
+pub use arula_llm::logger;
+pub mod manifest_watcher;
+pub mod memory;
+pub mod notifications;
+pub mod packs;
+pub mod postprocess;
+pub mod pr_description;
+pub mod profile_archive;
 pub mod project_context;
+pub mod reference_check;
+pub mod retry;
+pub mod scripting;
+pub mod secrets;
+pub mod shutdown;
+pub mod stats;
+pub mod style_packs;
+pub mod success_criteria;
+pub mod symbol_index;
+pub mod telemetry;
+pub mod themes;
+pub mod speech;
+pub mod sync;
+pub mod sync_backends;
 pub mod time;
 pub mod tool_call;
+pub mod update;
+pub mod version;
+pub mod voice;
+pub mod walkthrough;
 
 // Available exports via submodules:
+// accessibility::{plain_mode, set_plain_mode, to_plain, plain_if_enabled}
+// architecture::{build_architecture_map, ArchitectureMap, DiagramFormat}
+// code_lint::{lint_code_blocks, extract_code_blocks, BlockLint, CodeBlock, LintIssue}
+// code_runner::{run_code_block, RunEvent, RunKind, RunOutcome}
+// config_watcher::{ConfigWatcher, ConfigReload, describe_changes, provider_changed}
+// crypto::{Cipher, sha256_hex, random_bytes}
 // debug::{is_debug_enabled, debug_print, DebugTimer}
+// env_expand::{EnvSource, expand_with, parse_dotenv}
 // error::{ArulaError, ArulaResult, ApiError, ToolError, ResultExt, OptionExt}
 // error_utils::{ErrorContext, api_error, stream_error, network_error}
-// project_context::{detect_project, generate_auto_manifest, is_ai_enhanced, manifest_exists, DetectedProject, ProjectType}
+// commit_message::{prepare_commit, draft_commit_message, CommitDraft}
+// file_lock::{FileLock, SessionInUse, holder, lock_path}
+// git_context::{build_git_context, enrich_message}
+// git_ops::{GitOps, CommitInfo}
+// grounded::{answer_grounded, number_citations, retrieve_snippets, verify_answer, GroundedAnswer}
+// hooks::{Hooks, HookEvent, HooksConfig, hooks_dir}
+// icons::{Icon, IconSet, icon_set, set_icon_set}
+// manifest_watcher::{ManifestWatcher, refresh_manifest, merge_manifest}
+// memory::{MemoryStore, MemoryScope, Memory, memory_context}
+// packs::{PackIndex, PackManager, PacksConfig, PackKind, DEFAULT_INDEX_URL}
+// pr_description::{generate_pr_description, PrDescription}
+// profile_archive::{ProfileArchive, ProfileLocations, RestoreReport}
+// project_context::{detect_project, generate_auto_manifest, generate_indexed_manifest, is_ai_enhanced, manifest_exists, DetectedProject, ProjectType}
+// reference_check::{ReferenceChecker, Reference, ReferenceKind, annotate}
+// retry::{RetryPolicy, Attempt, record_attempt, RETRY_FILE}
+// scripting::{Scripts, ScriptCommand, ScriptToolDef, scripts_dir}
+// secrets::{SecretStore, KeyStorage}
+// style_packs::{AppearanceConfig, SpinnerPack, ProgressStyle, builtin_spinners, builtin_progress_styles}
+// speech::{prepare, speakable_text, Playback, SpeechBackend, SpeechConfig, Utterance}
+// sync::{Syncer, SyncConfig, SyncReport, ConflictStrategy}
+// sync_backends::{SyncBackend, FolderBackend, WebDavBackend, S3Backend, GitBackend, open_backend}
+// symbol_index::{SymbolIndex, Symbol, SymbolKind, index_source}
+// telemetry::{TelemetryConfig, Telemetry, init}
+// themes::{Theme, ThemeColors, Rgb, available_themes, set_active_theme, active_theme}
+// voice::{Recording, VoiceConfig, VoiceBackend, transcribe}
+// walkthrough::{generate_walkthrough, Walkthrough, WalkthroughProgress}
@@ -1,13 +1,68 @@
 //! Desktop notifications support for terminal unfocused state
 //! Based on codex-rs notifications implementation
+//!
+//! Notifications are only sent while ARULA is unfocused, for work that took
+//! at least `min_seconds`, and each event type can be turned off in the
+//! `notifications` config section.
 
-use std::sync::atomic::{AtomicBool, Ordering};
+use crate::utils::config::NotificationsConfig;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Longest notification body, in characters
+const MAX_BODY_CHARS: usize = 160;
+
+/// Default shortest run worth a notification
+const DEFAULT_MIN_SECONDS: u64 = 20;
+
+/// Something worth telling the user about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    /// The assistant finished a response
+    ResponseFinished,
+    /// A batch of tool calls finished
+    ToolsFinished,
+    /// A run ended with an error
+    RunFailed,
+    /// The agent paused and waits for the user
+    InputNeeded,
+}
+
+impl NotificationEvent {
+    /// Notification title
+    pub fn title(&self) -> &'static str {
+        match self {
+            NotificationEvent::ResponseFinished => "ARULA: response ready",
+            NotificationEvent::ToolsFinished => "ARULA: tools finished",
+            NotificationEvent::RunFailed => "ARULA: run failed",
+            NotificationEvent::InputNeeded => "ARULA: input needed",
+        }
+    }
+
+    /// Whether this event is turned on in `settings`
+    pub fn enabled_in(&self, settings: &NotificationsConfig) -> bool {
+        let (flag, default) = match self {
+            NotificationEvent::ResponseFinished => (settings.response_finished, true),
+            NotificationEvent::ToolsFinished => (settings.tools_finished, false),
+            NotificationEvent::RunFailed => (settings.run_failed, true),
+            NotificationEvent::InputNeeded => (settings.input_needed, true),
+        };
+        settings.enabled.unwrap_or(true) && flag.unwrap_or(default)
+    }
+
+    /// Whether work that took `elapsed` is long enough to notify about.
+    /// Waiting on the user always is.
+    pub fn worth_sending(&self, settings: &NotificationsConfig, elapsed: Duration) -> bool {
+        *self == NotificationEvent::InputNeeded
+            || elapsed.as_secs() >= settings.min_seconds.unwrap_or(DEFAULT_MIN_SECONDS)
+    }
+}
 
 /// Desktop notification backend trait
 pub trait NotificationBackend: Send + Sync {
-    /// Send a notification with the given message
-    fn notify(&mut self, message: &str) -> std::io::Result<()>;
+    /// Send a notification with the given title and message
+    fn notify(&mut self, title: &str, message: &str) -> std::io::Result<()>;
 
     /// Get the backend kind
     fn kind(&self) -> NotificationBackendKind;
@@ -30,21 +85,47 @@ pub enum NotificationBackendKind {
 pub struct NotificationManager {
     backend: Option<Box<dyn NotificationBackend>>,
     terminal_focused: Arc<AtomicBool>,
+    settings: NotificationsConfig,
 }
 
 impl NotificationManager {
     /// Create a new notification manager
-    pub fn new(terminal_focused: Arc<AtomicBool>) -> Self {
+    pub fn new(terminal_focused: Arc<AtomicBool>, settings: NotificationsConfig) -> Self {
         let backend = detect_backend();
         Self {
             backend,
             terminal_focused,
+            settings,
+        }
+    }
+
+    /// Replace the settings, e.g. after the config was reloaded
+    pub fn set_settings(&mut self, settings: NotificationsConfig) {
+        self.settings = settings;
+    }
+
+    /// Send a notification for `event` if it is turned on, the work took
+    /// long enough and ARULA is unfocused. Returns true if one was sent.
+    pub fn notify_event(
+        &mut self,
+        event: NotificationEvent,
+        elapsed: Duration,
+        summary: impl AsRef<str>,
+    ) -> bool {
+        if !event.enabled_in(&self.settings) || !event.worth_sending(&self.settings, elapsed) {
+            return false;
         }
+        let body = summarize(summary.as_ref(), elapsed);
+        self.send_if_unfocused(event.title(), &body)
     }
 
     /// Send a notification if the terminal is unfocused
     /// Returns true if a notification was sent
     pub fn notify_if_unfocused(&mut self, message: impl AsRef<str>) -> bool {
+        self.send_if_unfocused("ARULA", message.as_ref())
+    }
+
+    fn send_if_unfocused(&mut self, title: &str, message: &str) -> bool {
         if self.terminal_focused.load(Ordering::Relaxed) {
             return false;
         }
@@ -53,8 +134,7 @@ impl NotificationManager {
             return false;
         };
 
-        let message = message.as_ref();
-        match backend.notify(message) {
+        match backend.notify(title, message) {
             Ok(()) => true,
             Err(e) => {
                 eprintln!("Failed to send notification: {}", e);
@@ -82,6 +162,28 @@ impl NotificationManager {
     }
 }
 
+/// The first non-empty line of `summary`, shortened, followed by how long
+/// the work took
+pub fn summarize(summary: &str, elapsed: Duration) -> String {
+    let line = summary
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("Done");
+    let mut body: String = line.chars().take(MAX_BODY_CHARS).collect();
+    if line.chars().count() > MAX_BODY_CHARS {
+        body.push('…');
+    }
+    let secs = elapsed.as_secs();
+    if secs >= 60 {
+        format!("{} ({}m {}s)", body, secs / 60, secs % 60)
+    } else if secs > 0 {
+        format!("{} ({}s)", body, secs)
+    } else {
+        body
+    }
+}
+
 /// Detect the appropriate notification backend for this platform
 pub fn detect_backend() -> Option<Box<dyn NotificationBackend>> {
     #[cfg(target_os = "linux")]
@@ -119,19 +221,15 @@ impl LinuxDbusNotifier {
             .map(|o| o.status.success())
             .unwrap_or(false);
 
-        if has_dbus {
-            Some(Self)
-        } else {
-            None
-        }
+        if has_dbus { Some(Self) } else { None }
     }
 }
 
 #[cfg(target_os = "linux")]
 impl NotificationBackend for LinuxDbusNotifier {
-    fn notify(&mut self, message: &str) -> std::io::Result<()> {
+    fn notify(&mut self, title: &str, message: &str) -> std::io::Result<()> {
         std::process::Command::new("notify-send")
-            .arg("ARULA")
+            .arg(title)
             .arg(message)
             .arg("--urgency=low")
             .arg("--app-id=com.arula.cli")
@@ -164,11 +262,11 @@ impl MacOsNotifier {
 
 #[cfg(target_os = "macos")]
 impl NotificationBackend for MacOsNotifier {
-    fn notify(&mut self, message: &str) -> std::io::Result<()> {
+    fn notify(&mut self, title: &str, message: &str) -> std::io::Result<()> {
         // Try terminal-notifier first, fall back to osascript
         let result = std::process::Command::new("terminal-notifier")
             .arg("-title")
-            .arg("ARULA")
+            .arg(title)
             .arg("-message")
             .arg(message)
             .arg("-sound")
@@ -178,8 +276,9 @@ impl NotificationBackend for MacOsNotifier {
         if result.is_err() {
             // Fall back to osascript
             let script = format!(
-                "display notification \"{}\" with title \"ARULA\"",
-                message.replace('"', "\\'")
+                "display notification \"{}\" with title \"{}\"",
+                message.replace('"', "\\'"),
+                title.replace('"', "\\'")
             );
             std::process::Command::new("osascript")
                 .arg("-e")
@@ -215,17 +314,18 @@ impl WindowsNotifier {
 
 #[cfg(target_os = "windows")]
 impl NotificationBackend for WindowsNotifier {
-    fn notify(&mut self, message: &str) -> std::io::Result<()> {
-        let escaped_message = message.replace('"', "\"\"").replace('\'', "\\'");
+    fn notify(&mut self, title: &str, message: &str) -> std::io::Result<()> {
+        let escape = |s: &str| s.replace('"', "\"\"").replace('\'', "\\'");
         let ps_script = format!(
             r#"
 [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime]::CreateToastNotifier("ARULA").Show(
     [Windows.Data.Xml.Dom.XmlDocument, Windows.Data.Xml.Dom.XmlDocument, ContentType = WindowsRuntime]::new().LoadXml(
-        "<toast><visual><binding template='ToastText01'><text id='1'>{}</text></binding></visual></toast>"
+        "<toast><visual><binding template='ToastText02'><text id='1'>{}</text><text id='2'>{}</text></binding></visual></toast>"
     )
 )
 "#,
-            escaped_message
+            escape(title),
+            escape(message)
         );
 
         std::process::Command::new("powershell")
@@ -254,17 +354,50 @@ mod tests {
     #[test]
     fn test_notification_manager_creation() {
         let focus = Arc::new(AtomicBool::new(true));
-        let manager = NotificationManager::new(focus);
+        let manager = NotificationManager::new(focus, NotificationsConfig::default());
         assert!(manager.is_focused());
     }
 
     #[test]
     fn test_notification_manager_unfocused() {
         let focus = Arc::new(AtomicBool::new(false));
-        let mut manager = NotificationManager::new(focus);
+        let mut manager = NotificationManager::new(focus, NotificationsConfig::default());
         assert!(!manager.is_focused());
         // Should not crash even without backend
-        let sent = manager.notify_if_unfocused("Test message");
-        // May be false if no backend available
+        manager.set_focused(true);
+        assert!(!manager.notify_if_unfocused("Test message"));
+    }
+
+    #[test]
+    fn test_event_settings() {
+        let defaults = NotificationsConfig::default();
+        let long = Duration::from_secs(45);
+        let short = Duration::from_secs(3);
+        assert!(NotificationEvent::ResponseFinished.enabled_in(&defaults));
+        assert!(!NotificationEvent::ToolsFinished.enabled_in(&defaults));
+        assert!(NotificationEvent::ResponseFinished.worth_sending(&defaults, long));
+        assert!(!NotificationEvent::ResponseFinished.worth_sending(&defaults, short));
+        assert!(NotificationEvent::InputNeeded.worth_sending(&defaults, short));
+
+        let custom = NotificationsConfig {
+            min_seconds: Some(0),
+            tools_finished: Some(true),
+            run_failed: Some(false),
+            ..Default::default()
+        };
+        assert!(NotificationEvent::ToolsFinished.enabled_in(&custom));
+        assert!(!NotificationEvent::RunFailed.enabled_in(&custom));
+        assert!(NotificationEvent::ResponseFinished.worth_sending(&custom, short));
+        let off = NotificationsConfig {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(!NotificationEvent::InputNeeded.enabled_in(&off));
+
+        assert_eq!(
+            summarize("\n  Fixed the parser.\nMore detail", long),
+            "Fixed the parser. (45s)"
+        );
+        assert_eq!(summarize("", Duration::from_secs(125)), "Done (2m 5s)");
     }
 }
@@ -0,0 +1,651 @@
+//! Community packs: prompts, Rhai scripts and WASM tools from a pack index
+//!
+//! An index is a JSON file served over HTTPS listing packs and their
+//! versions. Each version names its files with a download URL and SHA-256
+//! digest and carries an Ed25519 signature over those digests:
+//!
+//! ```json
+//! {
+//!   "packs": [{
+//!     "name": "word-count",
+//!     "kind": "plugin",
+//!     "description": "Count words in a text",
+//!     "versions": [{
+//!       "version": "1.0.0",
+//!       "files": [
+//!         { "path": "plugin.json", "url": "https://…/plugin.json", "sha256": "…" },
+//!         { "path": "word_count.wasm", "url": "https://…/word_count.wasm", "sha256": "…" }
+//!       ],
+//!       "signature": "<base64 Ed25519 signature of signed_message()>"
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Packs are only installed when the signature verifies against one of the
+//! keys in `packs.trusted_keys` (base64 Ed25519 public keys) in config.json,
+//! unless installing unsigned packs is explicitly allowed. Every downloaded
+//! file must match its digest. Installed files go to:
+//!
+//! - `prompt` packs: `~/.arula/prompts/<name>/`
+//! - `script` packs: `~/.arula/scripts/`
+//! - `plugin` packs: `~/.arula/plugins/<name>/`
+//!
+//! and `~/.arula/packs.lock` records the installed version and files of
+//! every pack so updates can replace them.
+
+use crate::utils::crypto::sha256_hex;
+use crate::utils::version::compare_versions;
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use ring::signature::{ED25519, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Index used when `packs.index_url` isn't set
+pub const DEFAULT_INDEX_URL: &str =
+    "https://raw.githubusercontent.com/CriticalRange/arula-packs/main/index.json";
+/// File name of the lockfile inside `~/.arula`
+pub const LOCKFILE: &str = "packs.lock";
+
+/// Pack settings (`packs` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PacksConfig {
+    /// Pack index URL (default: the community index)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_url: Option<String>,
+    /// Base64 Ed25519 public keys whose signatures are accepted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_keys: Vec<String>,
+}
+
+/// What a pack contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackKind {
+    Prompt,
+    Script,
+    Plugin,
+}
+
+impl PackKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PackKind::Prompt => "prompt",
+            PackKind::Script => "script",
+            PackKind::Plugin => "plugin",
+        }
+    }
+
+    /// Directory a pack's files are installed into, under `~/.arula`
+    fn install_dir(&self, base: &Path, name: &str) -> PathBuf {
+        match self {
+            PackKind::Prompt => base.join("prompts").join(name),
+            PackKind::Script => base.join("scripts"),
+            PackKind::Plugin => base.join("plugins").join(name),
+        }
+    }
+}
+
+/// A file of a pack version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackFile {
+    /// Path relative to the install directory
+    pub path: String,
+    pub url: String,
+    /// Hex SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// A published version of a pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackVersion {
+    pub version: String,
+    pub files: Vec<PackFile>,
+    /// Base64 Ed25519 signature of `signed_message`
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+/// A pack listed in the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackEntry {
+    pub name: String,
+    pub kind: PackKind,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub versions: Vec<PackVersion>,
+}
+
+impl PackEntry {
+    /// The highest version
+    pub fn latest(&self) -> Option<&PackVersion> {
+        self.versions
+            .iter()
+            .max_by(|a, b| compare_versions(&a.version, &b.version))
+    }
+
+    /// A specific version, or the latest when `version` is `None`
+    pub fn version(&self, version: Option<&str>) -> Option<&PackVersion> {
+        match version {
+            Some(v) => self.versions.iter().find(|pv| pv.version == v),
+            None => self.latest(),
+        }
+    }
+
+    /// The bytes a version's signature covers: name, kind, version and
+    /// every file's path and digest
+    pub fn signed_message(&self, version: &PackVersion) -> String {
+        let mut message = format!(
+            "arula-pack-v1\n{}\n{}\n{}\n",
+            self.name,
+            self.kind.as_str(),
+            version.version
+        );
+        for file in &version.files {
+            message.push_str(&format!("{} {}\n", file.sha256.to_lowercase(), file.path));
+        }
+        message
+    }
+
+    /// Check a version's signature against the trusted keys; returns the key
+    /// that signed it
+    pub fn verify(&self, version: &PackVersion, trusted_keys: &[String]) -> Result<String> {
+        let signature = version
+            .signature
+            .as_deref()
+            .ok_or_else(|| anyhow!("{}@{} is not signed", self.name, version.version))?;
+        let signature = STANDARD
+            .decode(signature.trim())
+            .context("Signature is not valid base64")?;
+        if trusted_keys.is_empty() {
+            bail!("No trusted pack keys configured (packs.trusted_keys in config.json)");
+        }
+        let message = self.signed_message(version);
+        for key in trusted_keys {
+            let Ok(key_bytes) = STANDARD.decode(key.trim()) else {
+                continue;
+            };
+            if UnparsedPublicKey::new(&ED25519, key_bytes)
+                .verify(message.as_bytes(), &signature)
+                .is_ok()
+            {
+                return Ok(key.trim().to_string());
+            }
+        }
+        bail!(
+            "{}@{} is not signed by a trusted key",
+            self.name,
+            version.version
+        )
+    }
+}
+
+/// A pack index
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackIndex {
+    pub packs: Vec<PackEntry>,
+}
+
+impl PackIndex {
+    /// Download and parse the index at `url`
+    pub async fn fetch(url: &str) -> Result<Self> {
+        let response = reqwest::get(url)
+            .await
+            .with_context(|| format!("Failed to fetch pack index {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch pack index {}", url))?;
+        let text = response.text().await?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Invalid pack index")
+    }
+
+    pub fn find(&self, name: &str) -> Option<&PackEntry> {
+        self.packs.iter().find(|p| p.name == name)
+    }
+
+    /// Packs whose name, description or kind contains every query word;
+    /// an empty query lists them all
+    pub fn search(&self, query: &str) -> Vec<&PackEntry> {
+        let words: Vec<String> = query.split_whitespace().map(|w| w.to_lowercase()).collect();
+        self.packs
+            .iter()
+            .filter(|p| {
+                let haystack =
+                    format!("{} {} {}", p.name, p.description, p.kind.as_str()).to_lowercase();
+                words.iter().all(|w| haystack.contains(w))
+            })
+            .collect()
+    }
+}
+
+/// An installed pack (an entry in `packs.lock`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledPack {
+    pub version: String,
+    pub kind: PackKind,
+    /// Index it was installed from
+    pub index: String,
+    /// Installed files, relative to `~/.arula`, with their digests
+    pub files: Vec<LockedFile>,
+    /// Key that signed it, or `None` if it was installed unsigned
+    #[serde(default)]
+    pub signed_by: Option<String>,
+    pub installed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedFile {
+    pub path: String,
+    pub sha256: String,
+}
+
+/// The lockfile of installed packs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub packs: BTreeMap<String, InstalledPack>,
+}
+
+/// Installs packs under an ARULA directory and keeps its lockfile
+pub struct PackManager {
+    base: PathBuf,
+    lock: Lockfile,
+    trusted_keys: Vec<String>,
+    /// Install packs without a trusted signature
+    pub allow_unsigned: bool,
+}
+
+impl PackManager {
+    /// Open `~/.arula`
+    pub fn open(trusted_keys: Vec<String>) -> Result<Self> {
+        let base = dirs::home_dir()
+            .map(|home| home.join(".arula"))
+            .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+        Self::open_at(&base, trusted_keys)
+    }
+
+    /// Open an ARULA directory, reading its lockfile if there is one
+    pub fn open_at(base: &Path, trusted_keys: Vec<String>) -> Result<Self> {
+        let lock_path = base.join(LOCKFILE);
+        let lock = if lock_path.exists() {
+            let content = std::fs::read_to_string(&lock_path)
+                .with_context(|| format!("Failed to read {}", lock_path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", lock_path.display()))?
+        } else {
+            Lockfile::default()
+        };
+        Ok(Self {
+            base: base.to_path_buf(),
+            lock,
+            trusted_keys,
+            allow_unsigned: false,
+        })
+    }
+
+    pub fn installed(&self) -> &BTreeMap<String, InstalledPack> {
+        &self.lock.packs
+    }
+
+    /// Installed packs with a newer version in the index: (name, installed,
+    /// available)
+    pub fn updates(&self, index: &PackIndex) -> Vec<(String, String, String)> {
+        self.lock
+            .packs
+            .iter()
+            .filter_map(|(name, installed)| {
+                let latest = index.find(name)?.latest()?;
+                (compare_versions(&latest.version, &installed.version) == Ordering::Greater)
+                    .then(|| (name.clone(), installed.version.clone(), latest.version.clone()))
+            })
+            .collect()
+    }
+
+    /// Download, verify and install a version of a pack, replacing any
+    /// installed version
+    pub async fn install(
+        &mut self,
+        index_url: &str,
+        entry: &PackEntry,
+        version: &PackVersion,
+    ) -> Result<&InstalledPack> {
+        // Check the signature before downloading anything
+        self.check_signature(entry, version)?;
+        let client = reqwest::Client::new();
+        let mut contents = Vec::new();
+        for file in &version.files {
+            let bytes = client
+                .get(&file.url)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .with_context(|| format!("Failed to download {}", file.url))?
+                .bytes()
+                .await?;
+            contents.push(bytes.to_vec());
+        }
+        self.install_files(index_url, entry, version, contents)
+    }
+
+    /// Install already downloaded file contents (in `version.files` order)
+    pub fn install_files(
+        &mut self,
+        index_url: &str,
+        entry: &PackEntry,
+        version: &PackVersion,
+        contents: Vec<Vec<u8>>,
+    ) -> Result<&InstalledPack> {
+        let signed_by = self.check_signature(entry, version)?;
+        if contents.len() != version.files.len() {
+            bail!("Expected {} files, got {}", version.files.len(), contents.len());
+        }
+        if !valid_name(&entry.name) {
+            bail!("Invalid pack name '{}'", entry.name);
+        }
+
+        let dir = entry.kind.install_dir(&self.base, &entry.name);
+        let mut targets = Vec::new();
+        for (file, data) in version.files.iter().zip(&contents) {
+            let relative = safe_relative_path(&file.path)
+                .ok_or_else(|| anyhow!("Unsafe file path '{}' in pack", file.path))?;
+            if entry.kind == PackKind::Script && relative.components().count() != 1 {
+                bail!("Script packs may only contain top-level files ('{}')", file.path);
+            }
+            let actual = sha256_hex(data);
+            if !actual.eq_ignore_ascii_case(&file.sha256) {
+                bail!(
+                    "{} does not match its digest (expected {}, got {})",
+                    file.path,
+                    file.sha256,
+                    actual
+                );
+            }
+            let target = dir.join(relative);
+            if target.exists() && !self.owns(&entry.name, &target) {
+                bail!(
+                    "{} already exists and doesn't belong to {}",
+                    target.display(),
+                    entry.name
+                );
+            }
+            targets.push((target, data, actual));
+        }
+
+        // Everything checked out: replace the old version's files
+        self.remove_files(&entry.name);
+        let mut files = Vec::new();
+        for (target, data, sha256) in targets {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&target, data)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            files.push(LockedFile {
+                path: self.relative(&target),
+                sha256,
+            });
+        }
+
+        self.lock.packs.insert(
+            entry.name.clone(),
+            InstalledPack {
+                version: version.version.clone(),
+                kind: entry.kind,
+                index: index_url.to_string(),
+                files,
+                signed_by,
+                installed_at: Utc::now(),
+            },
+        );
+        self.save()?;
+        Ok(&self.lock.packs[&entry.name])
+    }
+
+    /// Remove an installed pack; returns whether it was installed
+    pub fn uninstall(&mut self, name: &str) -> Result<bool> {
+        if !self.lock.packs.contains_key(name) {
+            return Ok(false);
+        }
+        self.remove_files(name);
+        self.lock.packs.remove(name);
+        self.save()?;
+        Ok(true)
+    }
+
+    fn check_signature(&self, entry: &PackEntry, version: &PackVersion) -> Result<Option<String>> {
+        match entry.verify(version, &self.trusted_keys) {
+            Ok(key) => Ok(Some(key)),
+            Err(_) if self.allow_unsigned => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn owns(&self, name: &str, path: &Path) -> bool {
+        let relative = self.relative(path);
+        self.lock
+            .packs
+            .get(name)
+            .is_some_and(|p| p.files.iter().any(|f| f.path == relative))
+    }
+
+    fn remove_files(&self, name: &str) {
+        let Some(installed) = self.lock.packs.get(name) else {
+            return;
+        };
+        for file in &installed.files {
+            let _ = std::fs::remove_file(self.base.join(&file.path));
+        }
+        if installed.kind != PackKind::Script {
+            let _ = std::fs::remove_dir_all(installed.kind.install_dir(&self.base, name));
+        }
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.base)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn save(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.base)?;
+        let path = self.base.join(LOCKFILE);
+        std::fs::write(&path, serde_json::to_string_pretty(&self.lock)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Split `name[@version]`
+pub fn parse_pack_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.split_once('@') {
+        Some((name, version)) if !version.is_empty() => (name, Some(version)),
+        Some((name, _)) => (name, None),
+        None => (spec, None),
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A relative path that stays inside its directory
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let path = Path::new(path);
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!out.as_os_str().is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use tempfile::TempDir;
+
+    const INDEX: &str = "https://example.com/index.json";
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn entry(kind: PackKind, version: &str, files: &[(&str, &[u8])], key: &Ed25519KeyPair) -> PackEntry {
+        let mut entry = PackEntry {
+            name: "demo".to_string(),
+            kind,
+            description: "A demo pack".to_string(),
+            author: None,
+            versions: vec![PackVersion {
+                version: version.to_string(),
+                files: files
+                    .iter()
+                    .map(|(path, data)| PackFile {
+                        path: path.to_string(),
+                        url: format!("https://example.com/{}", path),
+                        sha256: sha256_hex(data),
+                    })
+                    .collect(),
+                signature: None,
+            }],
+        };
+        let message = entry.signed_message(&entry.versions[0]);
+        entry.versions[0].signature = Some(STANDARD.encode(key.sign(message.as_bytes())));
+        entry
+    }
+
+    fn public(key: &Ed25519KeyPair) -> String {
+        STANDARD.encode(key.public_key().as_ref())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let key = key_pair();
+        let pack = entry(PackKind::Prompt, "1.0.0", &[("a.md", b"hi")], &key);
+        let version = &pack.versions[0];
+        assert_eq!(pack.verify(version, &[public(&key)]).unwrap(), public(&key));
+        assert!(pack.verify(version, &[public(&key_pair())]).is_err());
+        assert!(pack.verify(version, &[]).is_err());
+
+        // Changing a digest invalidates the signature
+        let mut tampered = version.clone();
+        tampered.files[0].sha256 = sha256_hex(b"other");
+        assert!(pack.verify(&tampered, &[public(&key)]).is_err());
+    }
+
+    #[test]
+    fn test_install_update_and_lockfile() {
+        let dir = TempDir::new().unwrap();
+        let key = key_pair();
+        let mut manager = PackManager::open_at(dir.path(), vec![public(&key)]).unwrap();
+
+        let v1 = entry(
+            PackKind::Plugin,
+            "1.0.0",
+            &[("plugin.json", b"{}"), ("old.wasm", b"v1")],
+            &key,
+        );
+        manager
+            .install_files(INDEX, &v1, &v1.versions[0], vec![b"{}".to_vec(), b"v1".to_vec()])
+            .unwrap();
+        assert!(dir.path().join("plugins/demo/old.wasm").exists());
+
+        let v2 = entry(
+            PackKind::Plugin,
+            "1.2.0",
+            &[("plugin.json", b"{}"), ("new.wasm", b"v2")],
+            &key,
+        );
+        let index = PackIndex {
+            packs: vec![v2.clone()],
+        };
+        assert_eq!(
+            manager.updates(&index),
+            vec![("demo".to_string(), "1.0.0".to_string(), "1.2.0".to_string())]
+        );
+
+        // Contents that don't match the signed digests are rejected
+        assert!(manager
+            .install_files(INDEX, &v2, &v2.versions[0], vec![b"{}".to_vec(), b"evil".to_vec()])
+            .is_err());
+
+        manager
+            .install_files(INDEX, &v2, &v2.versions[0], vec![b"{}".to_vec(), b"v2".to_vec()])
+            .unwrap();
+        assert!(!dir.path().join("plugins/demo/old.wasm").exists());
+        assert!(dir.path().join("plugins/demo/new.wasm").exists());
+
+        let reopened = PackManager::open_at(dir.path(), Vec::new()).unwrap();
+        let installed = &reopened.installed()["demo"];
+        assert_eq!(installed.version, "1.2.0");
+        assert_eq!(installed.signed_by, Some(public(&key)));
+        assert!(reopened.updates(&index).is_empty());
+    }
+
+    #[test]
+    fn test_unsigned_and_unsafe_packs() {
+        let dir = TempDir::new().unwrap();
+        let key = key_pair();
+        let mut manager = PackManager::open_at(dir.path(), Vec::new()).unwrap();
+        let pack = entry(PackKind::Script, "1.0.0", &[("demo.rhai", b"1")], &key);
+        assert!(manager
+            .install_files(INDEX, &pack, &pack.versions[0], vec![b"1".to_vec()])
+            .is_err());
+
+        manager.allow_unsigned = true;
+        let installed = manager
+            .install_files(INDEX, &pack, &pack.versions[0], vec![b"1".to_vec()])
+            .unwrap();
+        assert_eq!(installed.signed_by, None);
+        assert!(dir.path().join("scripts/demo.rhai").exists());
+
+        let escape = entry(PackKind::Prompt, "1.0.0", &[("../x.md", b"1")], &key);
+        assert!(manager
+            .install_files(INDEX, &escape, &escape.versions[0], vec![b"1".to_vec()])
+            .is_err());
+
+        assert!(manager.uninstall("demo").unwrap());
+        assert!(!dir.path().join("scripts/demo.rhai").exists());
+    }
+
+    #[test]
+    fn test_search_and_versions() {
+        let key = key_pair();
+        let mut pack = entry(PackKind::Prompt, "1.9.2", &[], &key);
+        pack.versions.push(PackVersion {
+            version: "1.10.0".to_string(),
+            files: Vec::new(),
+            signature: None,
+        });
+        assert_eq!(pack.latest().unwrap().version, "1.10.0");
+        assert_eq!(pack.version(Some("1.9.2")).unwrap().version, "1.9.2");
+
+        let index = PackIndex { packs: vec![pack] };
+        assert_eq!(index.search("demo prompt").len(), 1);
+        assert!(index.search("plugin").is_empty());
+        assert_eq!(index.search("").len(), 1);
+
+        assert_eq!(parse_pack_spec("demo@1.0.0"), ("demo", Some("1.0.0")));
+        assert_eq!(parse_pack_spec("demo"), ("demo", None));
+        assert_eq!(compare_versions("v2.0", "1.99.9"), Ordering::Greater);
+    }
+}
@@ -0,0 +1,218 @@
+//! Post-processing of assistant messages
+//!
+//! Each line of a response passes through a small pipeline before it is
+//! rendered and before it is saved with the conversation:
+//!
+//! - provider watermarks are removed: invisible zero-width characters,
+//!   leaked special tokens like `<|im_end|>` and "Generated by ..." lines
+//! - headings are normalized (`##Title ##` becomes `## Title`)
+//! - issue references like `#123` become links when `issue_url` is set
+//! - custom regex transforms from config.json run last
+//!
+//! ```json
+//! "postprocess": {
+//!   "issue_url": "https://github.com/org/repo/issues/{id}",
+//!   "transforms": [{ "pattern": "\\bcolour\\b", "replace": "color" }]
+//! }
+//! ```
+//!
+//! Fenced code blocks are left as they are.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Post-processing settings (`postprocess` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessConfig {
+    /// Remove provider watermarks (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strip_watermarks: Option<bool>,
+    /// Fix heading markup (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_headings: Option<bool>,
+    /// Link template for issue references, with `{id}` for the number
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issue_url: Option<String>,
+    /// Regex replacements applied to every line, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<RegexTransform>,
+}
+
+/// A regex replacement; `replace` may use `$1`-style groups
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegexTransform {
+    pub pattern: String,
+    pub replace: String,
+}
+
+/// Characters some providers insert to watermark text
+const INVISIBLE_CHARS: &[char] = &['\u{200B}', '\u{200C}', '\u{200D}', '\u{2060}', '\u{FEFF}'];
+
+fn special_token() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"<\|[a-z_]+\|>|</s>\s*$").unwrap())
+}
+
+fn watermark_line() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?i)^\s*[*_(\[]*(generated|written|created|powered)\s+(by|with|using)\s+(an?\s+)?(ai|ai assistant|language model|chatgpt|gpt-?[\d.o]+|openai|gemini|glm[\w.-]*|z\.ai|deepseek|qwen|llama|mistral)(\b|_)[^\n]{0,40}$",
+        )
+        .unwrap()
+    })
+}
+
+fn heading() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^(\s{0,3})(#{1,6})\s*([A-Za-z].*?)(\s+#+)?\s*$").unwrap())
+}
+
+fn issue_ref() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(^|[\s(,])#(\d+)\b").unwrap())
+}
+
+/// Runs the pipeline over a response, line by line
+#[derive(Debug, Clone)]
+pub struct PostProcessor {
+    strip_watermarks: bool,
+    normalize_headings: bool,
+    issue_url: Option<String>,
+    transforms: Vec<(Regex, String)>,
+    /// Fence of the code block the last line was in
+    fence: Option<String>,
+}
+
+impl PostProcessor {
+    /// Build the pipeline; transforms with an invalid pattern are skipped
+    pub fn new(config: &PostProcessConfig) -> Self {
+        let transforms = config
+            .transforms
+            .iter()
+            .filter_map(|t| match Regex::new(&t.pattern) {
+                Ok(re) => Some((re, t.replace.clone())),
+                Err(e) => {
+                    tracing::warn!("Skipping postprocess transform {:?}: {}", t.pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            strip_watermarks: config.strip_watermarks.unwrap_or(true),
+            normalize_headings: config.normalize_headings.unwrap_or(true),
+            issue_url: config.issue_url.clone().filter(|url| url.contains("{id}")),
+            transforms,
+            fence: None,
+        }
+    }
+
+    /// Process a whole message
+    pub fn process(&mut self, text: &str) -> String {
+        self.fence = None;
+        text.split('\n')
+            .filter_map(|line| self.process_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Process the next line of a message; `None` when the line is dropped
+    pub fn process_line(&mut self, line: &str) -> Option<String> {
+        let trimmed = line.trim_start();
+        if let Some(fence) = &self.fence {
+            if trimmed.starts_with(fence.as_str()) && trimmed.trim_end().len() == fence.len() {
+                self.fence = None;
+            }
+            return Some(line.to_string());
+        }
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = trimmed.chars().next().unwrap_or('`');
+            self.fence = Some(trimmed.chars().take_while(|c| *c == marker).collect());
+            return Some(line.to_string());
+        }
+
+        let mut line = line.to_string();
+        if self.strip_watermarks {
+            line.retain(|c| !INVISIBLE_CHARS.contains(&c));
+            line = special_token().replace_all(&line, "").into_owned();
+            if watermark_line().is_match(&line) {
+                return None;
+            }
+        }
+        if self.normalize_headings {
+            line = heading().replace(&line, "$1$2 $3").into_owned();
+        }
+        if let Some(url) = &self.issue_url {
+            line = link_issues(&line, url);
+        }
+        for (pattern, replace) in &self.transforms {
+            line = pattern.replace_all(&line, replace.as_str()).into_owned();
+        }
+        Some(line)
+    }
+}
+
+/// Link `#123` references outside inline code and existing links
+fn link_issues(line: &str, url: &str) -> String {
+    line.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 || part.contains("](") {
+                return part.to_string();
+            }
+            issue_ref()
+                .replace_all(part, |caps: &regex::Captures| {
+                    format!(
+                        "{}[#{}]({})",
+                        &caps[1],
+                        &caps[2],
+                        url.replace("{id}", &caps[2])
+                    )
+                })
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_pipeline() {
+        let mut processor = PostProcessor::new(&PostProcessConfig::default());
+        let text = "##Summary ##\nAll\u{200B} done.<|im_end|>\n\n_Generated by ChatGPT_\n```md\n##raw\n```";
+        assert_eq!(
+            processor.process(text),
+            "## Summary\nAll done.\n\n```md\n##raw\n```"
+        );
+        // Issue numbers are not headings
+        assert_eq!(processor.process("#123 is fixed"), "#123 is fixed");
+    }
+
+    #[test]
+    fn test_issue_links_and_transforms() {
+        let config = PostProcessConfig {
+            issue_url: Some("https://example.com/issues/{id}".to_string()),
+            transforms: vec![
+                RegexTransform {
+                    pattern: r"\bcolour\b".to_string(),
+                    replace: "color".to_string(),
+                },
+                RegexTransform {
+                    pattern: "(".to_string(),
+                    replace: String::new(),
+                },
+            ],
+            ..Default::default()
+        };
+        let mut processor = PostProcessor::new(&config);
+        assert_eq!(
+            processor.process("Fixes #12, see `#13` and (#14). colour"),
+            "Fixes [#12](https://example.com/issues/12), see `#13` and \
+             ([#14](https://example.com/issues/14)). color"
+        );
+    }
+}
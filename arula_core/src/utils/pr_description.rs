@@ -0,0 +1,153 @@
+//! AI-drafted pull request descriptions
+//!
+//! Collects the commits and diff of the current branch against its base
+//! branch and asks the model for a Markdown PR description (summary,
+//! changes, testing notes).
+
+use crate::api::completion::complete;
+use crate::utils::config::Config;
+use crate::utils::git_ops::{CommitInfo, GitOps, truncate_diff};
+use anyhow::{Result, bail};
+use std::path::{Path, PathBuf};
+
+/// Maximum branch diff size sent to the model
+const MAX_PR_DIFF_CHARS: usize = 20_000;
+
+/// Default file name used when saving a description
+pub const PR_DESCRIPTION_FILE: &str = "PR_DESCRIPTION.md";
+
+const PR_SYSTEM_PROMPT: &str = "You write clear, concise pull request descriptions in Markdown \
+for code reviewers. Reply with the description only: no surrounding code fences, no commentary.";
+
+/// A drafted pull request description
+#[derive(Debug, Clone)]
+pub struct PrDescription {
+    /// Branch the pull request is opened from
+    pub branch: String,
+    /// Branch the pull request targets
+    pub base: String,
+    /// Number of commits on the branch
+    pub commit_count: usize,
+    /// Markdown description
+    pub markdown: String,
+}
+
+impl PrDescription {
+    /// Write the description to `PR_DESCRIPTION.md` in `dir`
+    pub fn write_to(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join(PR_DESCRIPTION_FILE);
+        std::fs::write(&path, format!("{}\n", self.markdown.trim_end()))?;
+        Ok(path)
+    }
+}
+
+/// Draft a PR description for the current branch against `base`
+/// (default: the repository's main branch)
+pub async fn generate_pr_description(
+    config: &Config,
+    dir: &Path,
+    base: Option<&str>,
+) -> Result<PrDescription> {
+    let git = GitOps::new(dir);
+    if !git.is_git_repo().await {
+        bail!("Not a git repository: {}", dir.display());
+    }
+    let Some(branch) = git.current_branch().await? else {
+        bail!("HEAD is detached; switch to a branch first");
+    };
+    let base = match base {
+        Some(base) => base.to_string(),
+        None => git.default_base_branch().await?,
+    };
+    if branch == base {
+        bail!(
+            "'{}' is the base branch; switch to a feature branch first",
+            branch
+        );
+    }
+
+    let commits = git.list_commits(&format!("{}..HEAD", base)).await?;
+    if commits.is_empty() {
+        bail!("No commits on '{}' since '{}'", branch, base);
+    }
+    let diff = git.branch_diff(&base).await?;
+
+    let prompt = build_pr_prompt(&branch, &base, &commits, &diff);
+    let reply = complete(config, PR_SYSTEM_PROMPT, &prompt).await?;
+    let markdown = strip_code_fence(&reply);
+    if markdown.is_empty() {
+        bail!("The model did not return a PR description");
+    }
+
+    Ok(PrDescription {
+        branch,
+        base,
+        commit_count: commits.len(),
+        markdown,
+    })
+}
+
+/// Build the prompt describing the branch's commits and diff
+fn build_pr_prompt(branch: &str, base: &str, commits: &[CommitInfo], diff: &str) -> String {
+    let commit_list: String = commits
+        .iter()
+        .map(|c| format!("- {} {}\n", c.short_hash, c.subject))
+        .collect();
+    format!(
+        "Write a pull request description for merging `{}` into `{}`.\n\n\
+         Commits:\n{}\n\
+         Diff:\n```diff\n{}\n```\n\n\
+         Use these sections:\n\
+         ## Summary\nOne or two sentences on what the change does and why.\n\
+         ## Changes\nBullet points of the notable changes.\n\
+         ## Testing\nHow the change can be verified; say so if it is unclear from the diff.",
+        branch,
+        base,
+        commit_list,
+        truncate_diff(diff, MAX_PR_DIFF_CHARS)
+    )
+}
+
+/// Remove a code fence wrapping the whole reply
+fn strip_code_fence(raw: &str) -> String {
+    let trimmed = raw.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return trimmed.to_string();
+    };
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end()
+        .strip_suffix("```")
+        .unwrap_or(body)
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(
+            strip_code_fence("```markdown\n## Summary\nAdds X.\n```\n"),
+            "## Summary\nAdds X."
+        );
+        assert_eq!(strip_code_fence("  ## Summary\n"), "## Summary");
+    }
+
+    #[test]
+    fn test_build_pr_prompt() {
+        let commits = vec![CommitInfo {
+            hash: "abc123".to_string(),
+            short_hash: "abc".to_string(),
+            author: "Alice".to_string(),
+            date: "2024-01-01T10:00:00+00:00".to_string(),
+            subject: "feat: add parser".to_string(),
+            body: String::new(),
+        }];
+        let prompt = build_pr_prompt("feature", "main", &commits, "+fn parse() {}");
+        assert!(prompt.contains("`feature` into `main`"));
+        assert!(prompt.contains("- abc feat: add parser"));
+        assert!(prompt.contains("+fn parse() {}"));
+    }
+}
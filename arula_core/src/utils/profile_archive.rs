@@ -0,0 +1,319 @@
+//! Encrypted archives of a whole ARULA profile
+//!
+//! `arula export-profile` packs everything needed to move to another
+//! machine into one file, and `arula import-profile` unpacks it:
+//!
+//! - `config.json`, with or without API keys
+//! - `prompts/`, `memory.json` and `conversations/` from `~/.arula`
+//! - the current project's `.arula/memory.json` and sessions
+//!
+//! The archive is JSON sealed with a key derived from a passphrase (see
+//! `crate::utils::crypto`), behind a short header holding the salt.
+
+use crate::utils::crypto::{Cipher, SALT_LEN};
+use anyhow::{Context, Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Passphrase used when none is typed in
+pub const PROFILE_PASSPHRASE_ENV: &str = "ARULA_PROFILE_PASSPHRASE";
+
+const MAGIC: &[u8] = b"ARULA-PROFILE\x01";
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Where profile files live on this machine
+#[derive(Debug, Clone)]
+pub struct ProfileLocations {
+    /// `~/.arula`
+    pub base: PathBuf,
+    /// Project whose sessions and memory are included
+    pub project: Option<PathBuf>,
+}
+
+impl ProfileLocations {
+    /// `~/.arula` and the project at `project_root`
+    pub fn current(project_root: &Path) -> Result<Self> {
+        let base = dirs::home_dir()
+            .map(|home| home.join(".arula"))
+            .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+        Ok(Self {
+            base,
+            project: Some(project_root.to_path_buf()),
+        })
+    }
+
+    /// (archive path, local path) of every file or directory in a profile;
+    /// directories end with `/`
+    fn entries(&self) -> Vec<(&'static str, PathBuf)> {
+        let mut entries = vec![
+            ("memory.json", self.base.join("memory.json")),
+            ("prompts/", self.base.join("prompts")),
+            ("sessions/", self.base.join("conversations")),
+        ];
+        if let Some(project) = &self.project {
+            let dir = project.join(".arula");
+            entries.push(("project/memory.json", dir.join("memory.json")));
+            entries.push(("project/sessions/", dir.join("conversations")));
+        }
+        entries
+    }
+
+    /// Local path for a file in the archive
+    fn local_path(&self, archive_path: &str) -> Option<PathBuf> {
+        if archive_path == "config.json" {
+            return Some(self.base.join("config.json"));
+        }
+        let safe = Path::new(archive_path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return None;
+        }
+        self.entries()
+            .into_iter()
+            .find_map(|(entry, local)| match entry.strip_suffix('/') {
+                Some(_) => archive_path
+                    .strip_prefix(entry)
+                    .filter(|rest| !rest.is_empty())
+                    .map(|rest| local.join(rest)),
+                None => (archive_path == entry).then_some(local),
+            })
+    }
+}
+
+/// The unpacked contents of a profile archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileArchive {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Whether config.json holds API keys
+    pub includes_keys: bool,
+    /// File contents (base64) by archive path
+    files: BTreeMap<String, String>,
+}
+
+impl ProfileArchive {
+    /// Collect a profile: `config_json` (see `Config::exported`) plus the
+    /// files under `locations`
+    pub fn collect(
+        locations: &ProfileLocations,
+        config_json: &str,
+        includes_keys: bool,
+    ) -> Result<Self> {
+        let mut files = BTreeMap::new();
+        files.insert("config.json".to_string(), STANDARD.encode(config_json));
+        for (entry, local) in locations.entries() {
+            if let Some(prefix) = entry.strip_suffix('/') {
+                if local.is_dir() {
+                    add_dir(&local, &local, prefix, &mut files)?;
+                }
+            } else if local.is_file() {
+                let data = std::fs::read(&local)
+                    .with_context(|| format!("Failed to read {}", local.display()))?;
+                files.insert(entry.to_string(), STANDARD.encode(data));
+            }
+        }
+        Ok(Self {
+            version: ARCHIVE_VERSION,
+            created_at: Utc::now(),
+            includes_keys,
+            files,
+        })
+    }
+
+    /// Archive paths, sorted
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Encrypt the archive to `path`
+    pub fn write(&self, path: &Path, passphrase: &str) -> Result<()> {
+        let salt = Cipher::random_salt()?;
+        let sealed = Cipher::derive(passphrase, &salt)?.seal(&serde_json::to_vec(self)?)?;
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&salt);
+        data.extend_from_slice(&sealed);
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Read and decrypt an archive
+    pub fn read(path: &Path, passphrase: &str) -> Result<Self> {
+        let data =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let Some(rest) = data.strip_prefix(MAGIC) else {
+            bail!("{} is not an ARULA profile archive", path.display());
+        };
+        if rest.len() < SALT_LEN {
+            bail!("{} is truncated", path.display());
+        }
+        let (salt, sealed) = rest.split_at(SALT_LEN);
+        let json = Cipher::derive(passphrase, salt)?
+            .open(sealed)
+            .context("Can't decrypt the profile (wrong passphrase?)")?;
+        let archive: Self = serde_json::from_slice(&json).context("Corrupt profile archive")?;
+        if archive.version > ARCHIVE_VERSION {
+            bail!(
+                "The profile was made by a newer ARULA (archive version {})",
+                archive.version
+            );
+        }
+        Ok(archive)
+    }
+
+    /// Write the files into `locations`
+    ///
+    /// Existing files with different contents are only replaced with
+    /// `overwrite`; otherwise they're reported as skipped.
+    pub fn restore(&self, locations: &ProfileLocations, overwrite: bool) -> Result<RestoreReport> {
+        let mut report = RestoreReport::default();
+        for (archive_path, encoded) in &self.files {
+            let Some(target) = locations.local_path(archive_path) else {
+                report.skipped.push(archive_path.clone());
+                continue;
+            };
+            let data = STANDARD
+                .decode(encoded)
+                .with_context(|| format!("Corrupt entry {}", archive_path))?;
+            match std::fs::read(&target) {
+                Ok(existing) if existing == data => {
+                    report.unchanged += 1;
+                    continue;
+                }
+                Ok(_) if !overwrite => {
+                    report.skipped.push(archive_path.clone());
+                    continue;
+                }
+                _ => {}
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&target, data)
+                .with_context(|| format!("Failed to write {}", target.display()))?;
+            report.written.push(archive_path.clone());
+        }
+        Ok(report)
+    }
+}
+
+/// Outcome of restoring an archive
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub written: Vec<String>,
+    /// Existing files left alone, and entries with unsafe paths
+    pub skipped: Vec<String>,
+    /// Files that already matched
+    pub unchanged: usize,
+}
+
+fn add_dir(
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            add_dir(root, &path, prefix, files)?;
+        } else if path.is_file() {
+            let relative: Vec<_> = path
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            let data = std::fs::read(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            files.insert(
+                format!("{}/{}", prefix, relative.join("/")),
+                STANDARD.encode(data),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn locations(dir: &TempDir) -> ProfileLocations {
+        ProfileLocations {
+            base: dir.path().join("home"),
+            project: Some(dir.path().join("project")),
+        }
+    }
+
+    fn write(path: PathBuf, content: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_export_and_import() -> Result<()> {
+        let old = TempDir::new()?;
+        let from = locations(&old);
+        write(from.base.join("prompts/review/system.md"), "Be terse");
+        write(from.base.join("memory.json"), "[]");
+        write(from.base.join("secrets.key"), "not exported");
+        write(
+            old.path().join("project/.arula/conversations/abc.json"),
+            "{}",
+        );
+
+        let archive = ProfileArchive::collect(&from, "{\"active_provider\":\"openai\"}", false)?;
+        assert_eq!(
+            archive.paths().collect::<Vec<_>>(),
+            vec![
+                "config.json",
+                "memory.json",
+                "project/sessions/abc.json",
+                "prompts/review/system.md"
+            ]
+        );
+        let file = old.path().join("profile.arula");
+        archive.write(&file, "hunter2")?;
+        assert!(!std::fs::read(&file)?.windows(8).any(|w| w == b"Be terse"));
+        assert!(ProfileArchive::read(&file, "wrong").is_err());
+
+        let new = TempDir::new()?;
+        let to = locations(&new);
+        write(to.base.join("memory.json"), "[\"local\"]");
+        let restored = ProfileArchive::read(&file, "hunter2")?;
+        assert!(!restored.includes_keys);
+
+        let report = restored.restore(&to, false)?;
+        assert_eq!(report.skipped, vec!["memory.json"]);
+        assert_eq!(report.written.len(), 3);
+        assert_eq!(
+            std::fs::read_to_string(new.path().join("project/.arula/conversations/abc.json"))?,
+            "{}"
+        );
+
+        let report = restored.restore(&to, true)?;
+        assert_eq!(report.written, vec!["memory.json"]);
+        assert_eq!(report.unchanged, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_unsafe_paths() {
+        let dir = TempDir::new().unwrap();
+        let locations = locations(&dir);
+        assert!(locations.local_path("prompts/../../etc/passwd").is_none());
+        assert!(locations.local_path("secrets.enc").is_none());
+        assert!(locations.local_path("prompts/").is_none());
+        assert_eq!(
+            locations.local_path("sessions/x.json"),
+            Some(locations.base.join("conversations").join("x.json"))
+        );
+    }
+}
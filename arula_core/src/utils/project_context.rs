@@ -3,6 +3,7 @@
 //! Provides auto-detection of project type (Rust, Node, Python, Go, etc.)
 //! and automatic generation of PROJECT.manifest files.
 
+use crate::utils::symbol_index::SymbolIndex;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -416,6 +417,32 @@ pub fn generate_auto_manifest(project: &DetectedProject) -> String {
     output
 }
 
+/// Maximum number of symbols listed in the KEY SYMBOLS section
+const MAX_MANIFEST_SYMBOLS: usize = 40;
+
+/// Generate auto-manifest content with a KEY SYMBOLS section from the symbol index
+///
+/// Lists the project's public types first, then public functions, with
+/// their signatures and locations, so the manifest describes the code and
+/// not only its dependencies.
+pub fn generate_indexed_manifest(project: &DetectedProject, root: &Path) -> String {
+    let mut output = generate_auto_manifest(project);
+
+    let index = SymbolIndex::shared(root);
+    let symbols = index.key_symbols(MAX_MANIFEST_SYMBOLS);
+    if !symbols.is_empty() {
+        output.push_str("\n# KEY SYMBOLS\n");
+        for symbol in symbols {
+            output.push_str(&format!(
+                "- {}:{} {}\n",
+                symbol.path, symbol.line, symbol.signature
+            ));
+        }
+    }
+
+    output
+}
+
 /// Extract string value from TOML line like: name = "value"
 fn extract_toml_string(line: &str) -> Option<String> {
     let parts: Vec<&str> = line.splitn(2, '=').collect();
@@ -0,0 +1,475 @@
+//! Hallucination guard for model responses
+//!
+//! Checks file paths, function/type names and CLI flags that a response
+//! mentions against the workspace, so references to things that don't exist
+//! can be flagged where they appear.
+//!
+//! Only references written as code are checked, to keep false positives low:
+//!
+//! - files: inline code (or bare tokens with a `/`) that look like paths
+//! - symbols: inline code like `load_config()` or `Config::load`
+//! - flags: `--long-flags` on command lines that start with one of the
+//!   workspace's own binaries (including `cargo run -- ...`)
+//!
+//! A symbol counts as existing when the symbol index has it or the name
+//! appears anywhere in the workspace sources, which covers library items the
+//! project uses. `Type::item` paths on types the workspace never mentions
+//! are assumed to be external and skipped.
+
+use crate::utils::symbol_index::{Language, SymbolIndex};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+/// Files larger than this are not scanned for identifiers and flags
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+static CODE_SPAN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`\n]+)`").unwrap());
+static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+static LONG_FLAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"--[A-Za-z][A-Za-z0-9-]*").unwrap());
+static SYMBOL_REF: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^((?:[A-Za-z_][A-Za-z0-9_]*(?:::|\.))*[A-Za-z_][A-Za-z0-9_]*)(\(.*\))?$").unwrap()
+});
+static PATH_REF: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:\./)?[A-Za-z0-9_.\-]+(?:/[A-Za-z0-9_.\-]+)*\.[A-Za-z][A-Za-z0-9]{0,7}(?::\d+(?::\d+)?)?$")
+        .unwrap()
+});
+
+/// File extensions treated as file references when written bare in code
+const FILE_EXTENSIONS: &[&str] = &[
+    "rs", "toml", "ts", "tsx", "js", "jsx", "py", "go", "json", "yaml", "yml", "md", "lock", "sh",
+    "kt", "java", "c", "h", "cpp", "hpp", "html", "css", "sql",
+];
+
+/// What a reference points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    File,
+    Symbol,
+    Flag,
+}
+
+/// A reference mentioned in a response
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Reference {
+    pub kind: ReferenceKind,
+    /// The reference as written (e.g. `src/app.rs`, `Config::load`, `--fast`)
+    pub text: String,
+}
+
+impl Reference {
+    /// Short explanation of why the reference was flagged
+    pub fn problem(&self) -> &'static str {
+        match self.kind {
+            ReferenceKind::File => "file not found in the workspace",
+            ReferenceKind::Symbol => "no such function or type in the workspace",
+            ReferenceKind::Flag => "flag not defined by this project's CLI",
+        }
+    }
+}
+
+/// Checks references against a snapshot of the workspace
+#[derive(Debug, Clone)]
+pub struct ReferenceChecker {
+    root: PathBuf,
+    index: Arc<SymbolIndex>,
+    /// Every identifier appearing in the workspace sources
+    identifiers: HashSet<String>,
+    /// Every `--long-flag` literal appearing in the workspace sources
+    flags: HashSet<String>,
+    /// Relative paths of all workspace files
+    files: HashSet<String>,
+    /// Binary names the workspace builds
+    binaries: HashSet<String>,
+}
+
+impl ReferenceChecker {
+    /// Snapshot the workspace at `root`
+    ///
+    /// Walks and reads the sources; call from `spawn_blocking` in async code.
+    pub fn build(root: &Path) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let index = SymbolIndex::shared(&root);
+        let mut checker = Self {
+            root: root.clone(),
+            index,
+            identifiers: HashSet::new(),
+            flags: HashSet::new(),
+            files: HashSet::new(),
+            binaries: HashSet::new(),
+        };
+
+        for entry in ignore::WalkBuilder::new(&root).build().flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(&root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if file_name == "Cargo.toml" || file_name == "package.json" {
+                checker.add_binaries(path);
+            }
+            let scanned = Language::from_path(path).is_some()
+                || matches!(file_name, "Cargo.toml" | "package.json")
+                || relative.ends_with(".md");
+            if scanned
+                && metadata.len() <= MAX_FILE_SIZE
+                && let Ok(content) = std::fs::read_to_string(path)
+            {
+                checker.identifiers.extend(
+                    IDENTIFIER
+                        .find_iter(&content)
+                        .map(|m| m.as_str().to_string()),
+                );
+                checker.flags.extend(
+                    LONG_FLAG
+                        .find_iter(&content)
+                        .map(|m| m.as_str().to_string()),
+                );
+            }
+            checker.files.insert(relative);
+        }
+
+        checker
+    }
+
+    /// Workspace root of the snapshot
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Return the references in `text` that don't exist in the workspace
+    pub fn check(&self, text: &str) -> Vec<Reference> {
+        let mut unverified: Vec<Reference> = Vec::new();
+        for reference in extract_references(text, &self.binaries) {
+            if !self.exists(&reference) && !unverified.contains(&reference) {
+                unverified.push(reference);
+            }
+        }
+        unverified
+    }
+
+    fn exists(&self, reference: &Reference) -> bool {
+        match reference.kind {
+            ReferenceKind::File => self.file_exists(&reference.text),
+            ReferenceKind::Symbol => self.symbol_exists(&reference.text),
+            ReferenceKind::Flag => self.flag_exists(&reference.text),
+        }
+    }
+
+    fn file_exists(&self, text: &str) -> bool {
+        // Drop a `:line[:col]` suffix
+        let path = text
+            .split_once(':')
+            .map_or(text, |(path, _)| path)
+            .trim_start_matches("./");
+        if self.files.contains(path) || self.root.join(path).exists() {
+            return true;
+        }
+        // A bare file name or partial path may refer to a nested file
+        let suffix = format!("/{}", path);
+        self.files.iter().any(|f| f.ends_with(&suffix))
+    }
+
+    fn symbol_exists(&self, text: &str) -> bool {
+        let path = text.split('(').next().unwrap_or(text).replace('.', "::");
+        let segments: Vec<&str> = path.split("::").filter(|s| !s.is_empty()).collect();
+        let Some(name) = segments.last() else {
+            return true;
+        };
+        if segments.len() > 1 {
+            let owner = segments[segments.len() - 2];
+            // Paths through types or modules the workspace never mentions are external
+            if !self.identifiers.contains(owner) {
+                return true;
+            }
+            let qualified = format!("{}::{}", owner, name);
+            if self.index.contains_name(&qualified) {
+                return true;
+            }
+        }
+        self.index.contains_name(name) || self.identifiers.contains(*name)
+    }
+
+    fn flag_exists(&self, flag: &str) -> bool {
+        if self.flags.contains(flag) {
+            return true;
+        }
+        // clap derive turns a `dry_run` field into `--dry-run`
+        let field = flag.trim_start_matches('-').replace('-', "_");
+        self.identifiers.contains(&field)
+    }
+
+    /// Record the binary names declared by a Cargo.toml or package.json
+    fn add_binaries(&mut self, manifest: &Path) {
+        let Ok(content) = std::fs::read_to_string(manifest) else {
+            return;
+        };
+        let dir = manifest.parent().unwrap_or(Path::new("."));
+
+        if manifest.ends_with("Cargo.toml") {
+            let Ok(value) = content.parse::<toml::Value>() else {
+                return;
+            };
+            if let Some(name) = value
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                && dir.join("src/main.rs").exists()
+            {
+                self.binaries.insert(name.to_string());
+            }
+            for bin in value
+                .get("bin")
+                .and_then(|b| b.as_array())
+                .into_iter()
+                .flatten()
+            {
+                if let Some(name) = bin.get("name").and_then(|n| n.as_str()) {
+                    self.binaries.insert(name.to_string());
+                }
+            }
+        } else if let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) {
+            match value.get("bin") {
+                Some(serde_json::Value::Object(bins)) => {
+                    self.binaries.extend(bins.keys().cloned());
+                }
+                Some(serde_json::Value::String(_)) => {
+                    if let Some(name) = value.get("name").and_then(|n| n.as_str()) {
+                        // Scoped packages install the binary under the bare name
+                        let name = name.rsplit('/').next().unwrap_or(name);
+                        self.binaries.insert(name.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Extract the checkable references from a piece of response text
+///
+/// `binaries` are the commands whose `--flags` are checked.
+pub fn extract_references(text: &str, binaries: &HashSet<String>) -> Vec<Reference> {
+    let mut references = Vec::new();
+
+    for captures in CODE_SPAN.captures_iter(text) {
+        let code = captures[1].trim();
+        if code.contains(char::is_whitespace) {
+            references.extend(command_flags(code, binaries));
+        } else if let Some(reference) = classify_code(code) {
+            references.push(reference);
+        }
+    }
+
+    // Command lines outside inline code, e.g. inside fenced blocks
+    let prose = CODE_SPAN.replace_all(text, " ");
+    for line in prose.lines() {
+        let line = line.trim().trim_start_matches("$ ");
+        references.extend(command_flags(line, binaries));
+        for token in line.split_whitespace() {
+            let token = token.trim_matches(|c: char| "\"'()[]<>,;!?".contains(c));
+            let token = token.trim_end_matches(['.', ':']);
+            if token.contains('/') && !token.contains("://") && PATH_REF.is_match(token) {
+                references.push(Reference {
+                    kind: ReferenceKind::File,
+                    text: token.to_string(),
+                });
+            }
+        }
+    }
+
+    references
+}
+
+/// Classify a single-token inline code span as a file or symbol reference
+fn classify_code(code: &str) -> Option<Reference> {
+    if code.starts_with('/') || code.starts_with('~') || code.contains("://") {
+        return None;
+    }
+    if PATH_REF.is_match(code) {
+        let path = code.split(':').next().unwrap_or(code);
+        let is_file = path.contains('/')
+            || Path::new(path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| FILE_EXTENSIONS.contains(&e));
+        if is_file {
+            return Some(Reference {
+                kind: ReferenceKind::File,
+                text: code.to_string(),
+            });
+        }
+    }
+
+    let captures = SYMBOL_REF.captures(code)?;
+    let path = &captures[1];
+    // Only calls (`name()`) and qualified paths (`Type::name`) are symbols;
+    // a lone word could be anything
+    let is_call = captures.get(2).is_some();
+    if !is_call && !path.contains("::") {
+        return None;
+    }
+    // `value.method` without a call is usually a field or a file-like name
+    if !is_call && path.contains('.') {
+        return None;
+    }
+    Some(Reference {
+        kind: ReferenceKind::Symbol,
+        text: code.to_string(),
+    })
+}
+
+/// `--flags` passed to one of the workspace binaries on a command line
+fn command_flags(line: &str, binaries: &HashSet<String>) -> Vec<Reference> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let Some(first) = tokens.first() else {
+        return Vec::new();
+    };
+    let program = first.rsplit('/').next().unwrap_or(first);
+
+    let args: &[&str] = if binaries.contains(program) {
+        &tokens[1..]
+    } else if program == "cargo"
+        && tokens.get(1) == Some(&"run")
+        && let Some(separator) = tokens.iter().position(|t| *t == "--")
+    {
+        &tokens[separator + 1..]
+    } else {
+        return Vec::new();
+    };
+
+    args.iter()
+        .filter(|arg| arg.starts_with("--") && arg.len() > 2)
+        .filter_map(|arg| LONG_FLAG.find(arg))
+        .map(|flag| Reference {
+            kind: ReferenceKind::Flag,
+            text: flag.as_str().to_string(),
+        })
+        .collect()
+}
+
+/// Mark flagged references in a line of text with a trailing `⚠`
+///
+/// Inline code spans are marked after the closing backtick; other
+/// occurrences are marked directly after the reference.
+pub fn annotate(text: &str, unverified: &[Reference]) -> String {
+    let mut annotated = text.to_string();
+    for reference in unverified {
+        let span = format!("`{}`", reference.text);
+        let (needle, marked) = if annotated.contains(&span) {
+            (span.clone(), format!("{} ⚠", span))
+        } else {
+            (reference.text.clone(), format!("{} ⚠", reference.text))
+        };
+        if let Some(pos) = annotated.find(&needle) {
+            annotated.replace_range(pos..pos + needle.len(), &marked);
+        }
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn workspace() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        std::fs::create_dir_all(dir.join("src/utils")).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"tool\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/main.rs"),
+            "struct Args { dry_run: bool }\nfn main() { let x = std::env::args(); }\n// usage: tool --verbose\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("src/utils/config.rs"),
+            "pub struct Config;\nimpl Config {\n    pub fn load() -> Self { Config }\n}\n",
+        )
+        .unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_extract_references() {
+        let binaries: HashSet<String> = ["tool".to_string()].into();
+        let references = extract_references(
+            "See `src/lib.rs:10` and `Config::load()`, run `tool --fast x` or `git log --oneline`. \
+             Word `config` and https://example.com/a/b.rs are ignored; docs/guide.md is not.",
+            &binaries,
+        );
+        let texts: Vec<(ReferenceKind, &str)> = references
+            .iter()
+            .map(|r| (r.kind, r.text.as_str()))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                (ReferenceKind::File, "src/lib.rs:10"),
+                (ReferenceKind::Symbol, "Config::load()"),
+                (ReferenceKind::Flag, "--fast"),
+                (ReferenceKind::File, "docs/guide.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_references() {
+        let temp_dir = workspace();
+        let checker = ReferenceChecker::build(temp_dir.path());
+
+        let unverified = checker.check(
+            "`Config::load()` lives in `utils/config.rs`; call `load_remote()` or \
+             `Config::reload()` with `tool --dry-run --verbose --turbo`. `HashMap::new()` is fine. \
+             Also see src/missing.rs.",
+        );
+        let texts: Vec<&str> = unverified.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "load_remote()",
+                "Config::reload()",
+                "--turbo",
+                "src/missing.rs"
+            ]
+        );
+        assert_eq!(unverified[2].kind, ReferenceKind::Flag);
+    }
+
+    #[test]
+    fn test_annotate() {
+        let unverified = vec![
+            Reference {
+                kind: ReferenceKind::Symbol,
+                text: "load_remote()".to_string(),
+            },
+            Reference {
+                kind: ReferenceKind::File,
+                text: "src/missing.rs".to_string(),
+            },
+        ];
+        assert_eq!(
+            annotate("Call `load_remote()` from src/missing.rs.", &unverified),
+            "Call `load_remote()` ⚠ from src/missing.rs ⚠."
+        );
+    }
+}
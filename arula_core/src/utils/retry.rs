@@ -0,0 +1,184 @@
+//! Retrying flaky commands and tools with backoff
+//!
+//! A project marks what is worth retrying in `<project>/.arula/retry.json`:
+//!
+//! ```json
+//! {
+//!   "max_attempts": 3,
+//!   "initial_delay_ms": 1000,
+//!   "max_delay_ms": 30000,
+//!   "commands": ["npm install*", "pip install *", "cargo test*"],
+//!   "tools": ["web_search", "mcp_*"]
+//! }
+//! ```
+//!
+//! `commands` are globs matched against the whole `execute_bash` command
+//! line, `tools` against tool names. A matching call that fails is run
+//! again after a delay that doubles each time, up to `max_attempts` runs in
+//! total. Every attempt of a retryable call is written to the audit log in
+//! the local database.
+
+use crate::storage::{AuditRecord, Storage};
+use crate::utils::debug::debug_print;
+use chrono::Utc;
+use globset::Glob;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// File name of the retry policy inside the project's `.arula` directory
+pub const RETRY_FILE: &str = "retry.json";
+/// Upper bound on `max_attempts`, whatever the file says
+pub const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_INITIAL_DELAY_MS: u64 = 1_000;
+const DEFAULT_MAX_DELAY_MS: u64 = 30_000;
+
+/// Which calls are retried, and how often
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_delay_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_delay_ms: Option<u64>,
+    /// Globs for retryable shell commands
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Globs for retryable tool names
+    #[serde(default)]
+    pub tools: Vec<String>,
+}
+
+impl RetryPolicy {
+    /// Path of the policy file for a project
+    pub fn path(project_root: &Path) -> PathBuf {
+        project_root.join(".arula").join(RETRY_FILE)
+    }
+
+    /// The project's policy; nothing is retried if the file is missing or
+    /// can't be parsed
+    pub fn load(project_root: &Path) -> Self {
+        let path = Self::path(project_root);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            crate::utils::logger::warn(&format!("Ignoring {}: {}", path.display(), e));
+            Self::default()
+        })
+    }
+
+    /// The policy of the current directory's project
+    pub fn current() -> Self {
+        std::env::current_dir()
+            .map(|dir| Self::load(&dir))
+            .unwrap_or_default()
+    }
+
+    /// Runs allowed in total, first one included
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS)
+            .clamp(1, MAX_ATTEMPTS_LIMIT)
+    }
+
+    /// Wait before the run following failed attempt `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let initial = self.initial_delay_ms.unwrap_or(DEFAULT_INITIAL_DELAY_MS);
+        let max = self.max_delay_ms.unwrap_or(DEFAULT_MAX_DELAY_MS).max(initial);
+        let factor = 1u64 << attempt.saturating_sub(1).min(20);
+        Duration::from_millis(initial.saturating_mul(factor).min(max))
+    }
+
+    /// Whether a shell command is marked retryable
+    pub fn command_retryable(&self, command: &str) -> bool {
+        matches_any(&self.commands, command.trim())
+    }
+
+    /// Whether a tool is marked retryable
+    pub fn tool_retryable(&self, tool: &str) -> bool {
+        matches_any(&self.tools, tool)
+    }
+}
+
+fn matches_any(patterns: &[String], text: &str) -> bool {
+    patterns.iter().any(|pattern| match Glob::new(pattern) {
+        Ok(glob) => glob.compile_matcher().is_match(text),
+        Err(_) => pattern == text,
+    })
+}
+
+/// Outcome of one run of a retryable call
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt<'a> {
+    pub tool: &'a str,
+    /// The command line for `execute_bash`, the arguments otherwise
+    pub target: &'a str,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub error: Option<String>,
+}
+
+/// Add an attempt to the audit log; failures are only logged
+pub fn record_attempt(attempt: &Attempt) {
+    let record = AuditRecord {
+        timestamp: Utc::now(),
+        project: std::env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default(),
+        tool: attempt.tool.to_string(),
+        target: attempt.target.to_string(),
+        attempt: attempt.attempt,
+        max_attempts: attempt.max_attempts,
+        success: attempt.success,
+        exit_code: attempt.exit_code,
+        duration_ms: attempt.duration.as_millis() as u64,
+        error: attempt.error.clone(),
+    };
+    if let Err(e) = Storage::with(|s| s.record_audit(&record)) {
+        debug_print(&format!("Failed to record attempt: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_and_match() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(RetryPolicy::load(dir.path()), RetryPolicy::default());
+
+        std::fs::create_dir(dir.path().join(".arula")).unwrap();
+        std::fs::write(
+            RetryPolicy::path(dir.path()),
+            r#"{"max_attempts": 50, "commands": ["npm install*"], "tools": ["mcp_*"]}"#,
+        )
+        .unwrap();
+        let policy = RetryPolicy::load(dir.path());
+        assert_eq!(policy.max_attempts(), MAX_ATTEMPTS_LIMIT);
+        assert!(policy.command_retryable("npm install"));
+        assert!(policy.command_retryable("  npm install --save left-pad"));
+        assert!(!policy.command_retryable("rm -rf node_modules && npm install"));
+        assert!(policy.tool_retryable("mcp_github_search"));
+        assert!(!policy.tool_retryable("write_file"));
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = RetryPolicy {
+            initial_delay_ms: Some(500),
+            max_delay_ms: Some(3_000),
+            ..Default::default()
+        };
+        let delays: Vec<u64> = (1..=5).map(|n| policy.delay(n).as_millis() as u64).collect();
+        assert_eq!(delays, vec![500, 1_000, 2_000, 3_000, 3_000]);
+        assert_eq!(RetryPolicy::default().max_attempts(), DEFAULT_MAX_ATTEMPTS);
+    }
+}
@@ -0,0 +1,576 @@
+//! User scripts: lightweight extensions written in Rhai
+//!
+//! Every `*.rhai` file in `~/.arula/scripts/` is loaded at startup. A script
+//! registers what it provides when it runs:
+//!
+//! ```rhai
+//! // `/shout hello` prints "HELLO!"
+//! register_command("shout", "Repeat the arguments loudly", |args| args.to_upper() + "!");
+//!
+//! // A tool the model can call
+//! register_tool("word_count", "Count the words in a text",
+//!     #{ text: "The text to count" },
+//!     |args| args.text.split(" ").len());
+//!
+//! // Rewrite prompts before they're sent and replies before they're shown
+//! on_prompt(|text| { text.replace("pls", "please"); text });
+//! on_response(|text| { text.replace("\t", "    "); text });
+//! ```
+//!
+//! Scripts run sandboxed: they can't import modules, touch files or start
+//! processes, and each call is limited in operations, depth and data size so
+//! a runaway loop fails instead of hanging the session. `print` and `log`
+//! write to the ARULA log.
+
+use crate::api::agent::{Tool, ToolSchema, ToolSchemaBuilder};
+use async_trait::async_trait;
+use rhai::{Dynamic, Engine, FnPtr, Map, AST};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Operations a single script call may perform before it is stopped
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+/// A script function registered through the script API
+#[derive(Debug, Clone)]
+struct Callback {
+    /// Index of the script that registered it
+    script: usize,
+    function: FnPtr,
+}
+
+/// A slash command provided by a script
+#[derive(Debug, Clone)]
+pub struct ScriptCommand {
+    pub name: String,
+    pub description: String,
+    /// File name of the script that registered it
+    pub script: String,
+    callback: Callback,
+}
+
+/// A tool provided by a script
+#[derive(Debug, Clone)]
+pub struct ScriptToolDef {
+    pub name: String,
+    pub description: String,
+    /// Parameters: name, JSON type, description, required
+    pub params: Vec<(String, String, String, bool)>,
+    pub script: String,
+    callback: Callback,
+}
+
+/// What scripts registered while loading
+#[derive(Debug, Default)]
+struct Registrations {
+    current_script: usize,
+    commands: Vec<(String, String, Callback)>,
+    tools: Vec<(String, String, Map, Callback)>,
+    prompt_transforms: Vec<Callback>,
+    response_transforms: Vec<Callback>,
+}
+
+/// The loaded user scripts and everything they registered
+#[derive(Clone)]
+pub struct Scripts {
+    engine: Arc<Engine>,
+    /// File name and compiled script
+    scripts: Vec<(String, Arc<AST>)>,
+    commands: Vec<ScriptCommand>,
+    tools: Vec<ScriptToolDef>,
+    prompt_transforms: Vec<Callback>,
+    response_transforms: Vec<Callback>,
+    /// Scripts that failed to load, with the reason
+    pub errors: Vec<String>,
+}
+
+impl std::fmt::Debug for Scripts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scripts")
+            .field("scripts", &self.script_names())
+            .field("commands", &self.commands.len())
+            .field("tools", &self.tools.len())
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        Self {
+            engine: Arc::new(sandboxed_engine()),
+            scripts: Vec::new(),
+            commands: Vec::new(),
+            tools: Vec::new(),
+            prompt_transforms: Vec::new(),
+            response_transforms: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl Scripts {
+    /// Load every script in `~/.arula/scripts/`
+    pub fn load() -> Self {
+        match scripts_dir() {
+            Some(dir) => Self::load_from(&dir),
+            None => Self::default(),
+        }
+    }
+
+    /// Load every `*.rhai` file in `dir`, in file name order
+    pub fn load_from(dir: &Path) -> Self {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+
+        let sources = files.iter().filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            match std::fs::read_to_string(path) {
+                Ok(source) => Some((name, source)),
+                Err(e) => {
+                    crate::utils::logger::warn(&format!("Can't read script {}: {}", name, e));
+                    None
+                }
+            }
+        });
+        Self::from_sources(sources)
+    }
+
+    /// Compile and run scripts given as (file name, source)
+    pub fn from_sources(sources: impl IntoIterator<Item = (String, String)>) -> Self {
+        let registrations = Arc::new(Mutex::new(Registrations::default()));
+        let mut engine = sandboxed_engine();
+        register_api(&mut engine, &registrations);
+
+        let mut scripts = Vec::new();
+        let mut errors = Vec::new();
+        for (name, source) in sources {
+            let ast = match engine.compile(&source) {
+                Ok(ast) => ast,
+                Err(e) => {
+                    errors.push(format!("{}: {}", name, e));
+                    continue;
+                }
+            };
+
+            // Registrations made by a script that then fails are dropped
+            let before = {
+                let mut reg = registrations.lock().unwrap();
+                reg.current_script = scripts.len();
+                snapshot(&reg)
+            };
+            if let Err(e) = engine.run_ast(&ast) {
+                errors.push(format!("{}: {}", name, e));
+                truncate(&mut registrations.lock().unwrap(), before);
+                continue;
+            }
+            scripts.push((name, Arc::new(ast)));
+        }
+
+        for error in &errors {
+            crate::utils::logger::warn(&format!("Script failed to load: {}", error));
+        }
+
+        let reg = std::mem::take(&mut *registrations.lock().unwrap());
+        let script_name = |callback: &Callback| scripts[callback.script].0.clone();
+
+        let mut commands: Vec<ScriptCommand> = Vec::new();
+        for (name, description, callback) in reg.commands {
+            let name = name.trim_start_matches('/').to_lowercase();
+            if name.is_empty() || commands.iter().any(|c| c.name == name) {
+                errors.push(format!(
+                    "{}: command /{} is empty or already registered",
+                    script_name(&callback),
+                    name
+                ));
+                continue;
+            }
+            commands.push(ScriptCommand {
+                name,
+                description,
+                script: script_name(&callback),
+                callback,
+            });
+        }
+
+        let mut tools: Vec<ScriptToolDef> = Vec::new();
+        for (name, description, params, callback) in reg.tools {
+            if tools.iter().any(|t| t.name == name) {
+                errors.push(format!(
+                    "{}: tool {} is already registered",
+                    script_name(&callback),
+                    name
+                ));
+                continue;
+            }
+            tools.push(ScriptToolDef {
+                name,
+                description,
+                params: params.iter().map(|(k, v)| param_spec(k, v)).collect(),
+                script: script_name(&callback),
+                callback,
+            });
+        }
+
+        Self {
+            engine: Arc::new(engine),
+            scripts,
+            commands,
+            tools,
+            prompt_transforms: reg.prompt_transforms,
+            response_transforms: reg.response_transforms,
+            errors,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// File names of the loaded scripts
+    pub fn script_names(&self) -> Vec<&str> {
+        self.scripts.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn commands(&self) -> &[ScriptCommand] {
+        &self.commands
+    }
+
+    pub fn command(&self, name: &str) -> Option<&ScriptCommand> {
+        let name = name.to_lowercase();
+        self.commands.iter().find(|c| c.name == name)
+    }
+
+    pub fn tools(&self) -> &[ScriptToolDef] {
+        &self.tools
+    }
+
+    pub fn transforms_prompts(&self) -> bool {
+        !self.prompt_transforms.is_empty()
+    }
+
+    pub fn transforms_responses(&self) -> bool {
+        !self.response_transforms.is_empty()
+    }
+
+    /// Run a script command; returns the text it produced, if any
+    pub fn run_command(&self, name: &str, args: &str) -> Result<Option<String>, String> {
+        let command = self
+            .command(name)
+            .ok_or_else(|| format!("No script provides /{}", name))?;
+        let result = self.call(&command.callback, (args.to_string(),))?;
+        Ok(if result.is_unit() {
+            None
+        } else {
+            Some(result.to_string())
+        })
+    }
+
+    /// Pass a prompt through every `on_prompt` transform
+    pub fn transform_prompt(&self, text: &str) -> String {
+        self.transform(&self.prompt_transforms, text)
+    }
+
+    /// Pass a response through every `on_response` transform
+    pub fn transform_response(&self, text: &str) -> String {
+        self.transform(&self.response_transforms, text)
+    }
+
+    /// Register the script tools in a tool registry
+    pub fn register_tools(&self, registry: &mut crate::api::agent::ToolRegistry) {
+        for index in 0..self.tools.len() {
+            registry.register(ScriptTool {
+                scripts: self.clone(),
+                index,
+            });
+        }
+    }
+
+    /// Transforms chain: each gets the previous one's output. A transform
+    /// that fails or doesn't return a string is skipped.
+    fn transform(&self, transforms: &[Callback], text: &str) -> String {
+        let mut text = text.to_string();
+        for callback in transforms {
+            match self.call(callback, (text.clone(),)) {
+                Ok(result) if result.is_string() => text = result.to_string(),
+                Ok(result) => crate::utils::logger::warn(&format!(
+                    "Transform in {} returned {} instead of a string",
+                    self.scripts[callback.script].0,
+                    result.type_name()
+                )),
+                Err(e) => crate::utils::logger::warn(&e),
+            }
+        }
+        text
+    }
+
+    fn call(&self, callback: &Callback, args: impl rhai::FuncArgs) -> Result<Dynamic, String> {
+        let (name, ast) = &self.scripts[callback.script];
+        callback
+            .function
+            .call::<Dynamic>(&self.engine, ast, args)
+            .map_err(|e| format!("{}: {}", name, e))
+    }
+}
+
+/// `~/.arula/scripts`
+pub fn scripts_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join("scripts"))
+}
+
+/// An engine that can't reach outside the script and stops runaway scripts
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(64)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(100_000)
+        .set_max_map_size(10_000)
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .disable_symbol("eval");
+    engine.on_print(|text| crate::utils::logger::info(&format!("[script] {}", text)));
+    engine.on_debug(|text, _, _| crate::utils::logger::debug(&format!("[script] {}", text)));
+    engine
+}
+
+/// The functions scripts call to register what they provide
+fn register_api(engine: &mut Engine, registrations: &Arc<Mutex<Registrations>>) {
+    let reg = registrations.clone();
+    engine.register_fn(
+        "register_command",
+        move |name: &str, description: &str, function: FnPtr| {
+            let mut reg = reg.lock().unwrap();
+            let script = reg.current_script;
+            reg.commands.push((
+                name.to_string(),
+                description.to_string(),
+                Callback { script, function },
+            ));
+        },
+    );
+
+    let reg = registrations.clone();
+    engine.register_fn(
+        "register_tool",
+        move |name: &str, description: &str, params: Map, function: FnPtr| {
+            let mut reg = reg.lock().unwrap();
+            let script = reg.current_script;
+            reg.tools.push((
+                name.to_string(),
+                description.to_string(),
+                params,
+                Callback { script, function },
+            ));
+        },
+    );
+
+    let reg = registrations.clone();
+    engine.register_fn("on_prompt", move |function: FnPtr| {
+        let mut reg = reg.lock().unwrap();
+        let script = reg.current_script;
+        reg.prompt_transforms.push(Callback { script, function });
+    });
+
+    let reg = registrations.clone();
+    engine.register_fn("on_response", move |function: FnPtr| {
+        let mut reg = reg.lock().unwrap();
+        let script = reg.current_script;
+        reg.response_transforms.push(Callback { script, function });
+    });
+
+    engine.register_fn("log", |text: &str| {
+        crate::utils::logger::info(&format!("[script] {}", text))
+    });
+}
+
+/// Registration counts, to undo a failed script's registrations
+fn snapshot(reg: &Registrations) -> [usize; 4] {
+    [
+        reg.commands.len(),
+        reg.tools.len(),
+        reg.prompt_transforms.len(),
+        reg.response_transforms.len(),
+    ]
+}
+
+fn truncate(reg: &mut Registrations, counts: [usize; 4]) {
+    reg.commands.truncate(counts[0]);
+    reg.tools.truncate(counts[1]);
+    reg.prompt_transforms.truncate(counts[2]);
+    reg.response_transforms.truncate(counts[3]);
+}
+
+/// A tool parameter from its script definition: either a description
+/// (a required string) or a map with `type`, `description` and `required`
+fn param_spec(name: &str, spec: &Dynamic) -> (String, String, String, bool) {
+    match spec.read_lock::<Map>() {
+        Some(map) => {
+            let field = |key: &str| map.get(key).map(|v| v.to_string());
+            (
+                name.to_string(),
+                field("type").unwrap_or_else(|| "string".to_string()),
+                field("description").unwrap_or_default(),
+                map.get("required")
+                    .and_then(|v| v.as_bool().ok())
+                    .unwrap_or(true),
+            )
+        }
+        None => (
+            name.to_string(),
+            "string".to_string(),
+            spec.to_string(),
+            true,
+        ),
+    }
+}
+
+/// A script tool, as seen by the agent
+struct ScriptTool {
+    scripts: Scripts,
+    index: usize,
+}
+
+impl ScriptTool {
+    fn def(&self) -> &ScriptToolDef {
+        &self.scripts.tools[self.index]
+    }
+}
+
+#[async_trait]
+impl Tool for ScriptTool {
+    type Params = Value;
+    type Result = Value;
+
+    fn name(&self) -> &str {
+        &self.def().name
+    }
+
+    fn description(&self) -> &str {
+        &self.def().description
+    }
+
+    fn schema(&self) -> ToolSchema {
+        let def = self.def();
+        let mut builder = ToolSchemaBuilder::new(&def.name, &def.description);
+        for (name, param_type, description, required) in &def.params {
+            builder = builder
+                .param(name, param_type)
+                .description(name, description);
+            if *required {
+                builder = builder.required(name);
+            }
+        }
+        builder.build()
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value, String> {
+        let scripts = self.scripts.clone();
+        let index = self.index;
+        tokio::task::spawn_blocking(move || {
+            let args = rhai::serde::to_dynamic(&params).map_err(|e| e.to_string())?;
+            let result = scripts.call(&scripts.tools[index].callback, (args,))?;
+            rhai::serde::from_dynamic::<Value>(&result).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Script tool panicked: {}", e))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(source: &str) -> Scripts {
+        Scripts::from_sources([("test.rhai".to_string(), source.to_string())])
+    }
+
+    #[test]
+    fn test_commands_and_transforms() {
+        let scripts = load(
+            r#"
+            register_command("/Shout", "Say it loud", |args| args.to_upper() + "!");
+            register_command("quiet", "Say nothing", |args| ());
+            on_prompt(|text| { text.replace("pls", "please"); text });
+            on_prompt(|text| text + "?");
+            on_response(|text| 42);
+            "#,
+        );
+        assert!(scripts.errors.is_empty(), "{:?}", scripts.errors);
+
+        assert_eq!(scripts.commands().len(), 2);
+        assert_eq!(
+            scripts.run_command("SHOUT", "hi").unwrap(),
+            Some("HI!".to_string())
+        );
+        assert_eq!(scripts.run_command("quiet", "hi").unwrap(), None);
+        assert!(scripts.run_command("missing", "").is_err());
+
+        assert_eq!(scripts.transform_prompt("fix it pls"), "fix it please?");
+        // A transform that returns a non-string leaves the text alone
+        assert_eq!(scripts.transform_response("done"), "done");
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let scripts = load(r#"register_command("spin", "", |args| { loop {} });"#);
+        assert!(scripts.run_command("spin", "").is_err());
+
+        let scripts = load(r#"import "other" as other;"#);
+        assert_eq!(scripts.errors.len(), 1);
+        assert!(scripts.is_empty());
+    }
+
+    #[test]
+    fn test_failed_script_registers_nothing() {
+        let scripts = Scripts::from_sources([
+            (
+                "a.rhai".to_string(),
+                r#"register_command("a", "", |args| "a"); throw "broken";"#.to_string(),
+            ),
+            (
+                "b.rhai".to_string(),
+                r#"register_command("b", "", |args| "b");"#.to_string(),
+            ),
+        ]);
+        assert_eq!(scripts.script_names(), vec!["b.rhai"]);
+        assert!(scripts.command("a").is_none());
+        assert_eq!(scripts.command("b").unwrap().script, "b.rhai");
+        assert!(scripts.errors[0].starts_with("a.rhai"));
+    }
+
+    #[tokio::test]
+    async fn test_script_tool() {
+        let scripts = load(
+            r#"
+            register_tool("word_count", "Count words",
+                #{ text: "The text", min: #{ type: "integer", required: false } },
+                |args| #{ words: args.text.split(" ").len() });
+            "#,
+        );
+        let mut registry = crate::api::agent::ToolRegistry::new();
+        scripts.register_tools(&mut registry);
+
+        let tool = ScriptTool { scripts, index: 0 };
+        let schema = tool.schema();
+        assert_eq!(schema.required, vec!["text".to_string()]);
+        assert_eq!(schema.parameters["min"].param_type, "integer");
+
+        let result = registry
+            .execute_tool("word_count", serde_json::json!({ "text": "one two three" }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.data["words"], 3);
+    }
+}
@@ -0,0 +1,296 @@
+//! API key storage outside the config file
+//!
+//! Keys are kept in the platform keychain (macOS Keychain, Windows
+//! Credential Manager, Secret Service on Linux) so config.json never holds
+//! them in plaintext. Where no keychain is available, as on headless
+//! servers, they go to `~/.arula/secrets.enc`, encrypted with
+//! ChaCha20-Poly1305 under a key derived from `ARULA_SECRETS_PASSPHRASE`
+//! or, without one, a random key in `~/.arula/secrets.key`. Linux builds
+//! without the `secret-service` feature always use the encrypted file.
+//!
+//! The backend is chosen with the top-level `key_storage` setting
+//! (`keychain`, `file` or `plaintext`).
+
+use crate::utils::crypto::{Cipher, KEY_LEN, NONCE_LEN, SALT_LEN, random_bytes};
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Keychain service name the keys are stored under
+pub const KEYCHAIN_SERVICE: &str = "arula";
+/// Passphrase for the encrypted-file backend
+pub const PASSPHRASE_ENV: &str = "ARULA_SECRETS_PASSPHRASE";
+
+/// Where API keys are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStorage {
+    /// Platform keychain, falling back to the encrypted file when unavailable
+    #[default]
+    Keychain,
+    /// Encrypted file in `~/.arula`
+    File,
+    /// In config.json, as before (no migration)
+    Plaintext,
+}
+
+/// Secret store backed by the keychain or the encrypted file
+pub struct SecretStore {
+    backend: Backend,
+}
+
+enum Backend {
+    Keychain,
+    File(EncryptedFile),
+}
+
+impl SecretStore {
+    /// Open the store for a storage setting, or `None` for plaintext
+    ///
+    /// The keychain is probed first; when it can't be reached the encrypted
+    /// file in `dir` is used instead.
+    pub fn open(storage: KeyStorage, dir: &Path) -> Option<Self> {
+        let backend = match storage {
+            KeyStorage::Plaintext => return None,
+            KeyStorage::Keychain if keychain_available() => Backend::Keychain,
+            KeyStorage::Keychain | KeyStorage::File => Backend::File(EncryptedFile::new(dir)),
+        };
+        Some(Self { backend })
+    }
+
+    /// Human-readable backend name
+    pub fn backend_name(&self) -> &'static str {
+        match self.backend {
+            Backend::Keychain => "keychain",
+            Backend::File(_) => "encrypted file",
+        }
+    }
+
+    /// Look up a secret, `None` when it isn't stored
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        match &self.backend {
+            Backend::Keychain => match keychain_entry(name)?.get_password() {
+                Ok(secret) => Ok(Some(secret)),
+                Err(keyring::Error::NoEntry) => Ok(None),
+                Err(e) => Err(anyhow!("Keychain lookup failed: {}", e)),
+            },
+            Backend::File(file) => Ok(file.load()?.remove(name)),
+        }
+    }
+
+    /// Store a secret, replacing any previous value
+    pub fn set(&self, name: &str, secret: &str) -> Result<()> {
+        match &self.backend {
+            Backend::Keychain => keychain_entry(name)?
+                .set_password(secret)
+                .map_err(|e| anyhow!("Keychain write failed: {}", e)),
+            Backend::File(file) => {
+                let mut secrets = file.load()?;
+                if secrets.get(name).map(String::as_str) == Some(secret) {
+                    return Ok(());
+                }
+                secrets.insert(name.to_string(), secret.to_string());
+                file.save(&secrets)
+            }
+        }
+    }
+
+    /// Remove a secret; removing a missing one is not an error
+    pub fn delete(&self, name: &str) -> Result<()> {
+        match &self.backend {
+            Backend::Keychain => match keychain_entry(name)?.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+                Err(e) => Err(anyhow!("Keychain delete failed: {}", e)),
+            },
+            Backend::File(file) => {
+                let mut secrets = file.load()?;
+                if secrets.remove(name).is_some() {
+                    file.save(&secrets)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn keychain_entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, name).map_err(|e| anyhow!("Keychain unavailable: {}", e))
+}
+
+/// Whether the platform keychain can be reached (a missing entry is fine)
+fn keychain_available() -> bool {
+    // Without a native backend keyring falls back to an in-memory mock
+    // store, which would silently lose keys on exit
+    if !cfg!(any(
+        target_os = "macos",
+        target_os = "windows",
+        feature = "secret-service"
+    )) {
+        return false;
+    }
+    keyring::Entry::new(KEYCHAIN_SERVICE, "__probe__")
+        .is_ok_and(|entry| matches!(entry.get_password(), Ok(_) | Err(keyring::Error::NoEntry)))
+}
+
+/// On-disk format of the encrypted file
+#[derive(Serialize, Deserialize)]
+struct SealedSecrets {
+    version: u32,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+/// Secrets encrypted with ChaCha20-Poly1305 in a single file
+struct EncryptedFile {
+    path: PathBuf,
+    key_path: PathBuf,
+}
+
+impl EncryptedFile {
+    fn new(dir: &Path) -> Self {
+        Self {
+            path: dir.join("secrets.enc"),
+            key_path: dir.join("secrets.key"),
+        }
+    }
+
+    fn load(&self) -> Result<BTreeMap<String, String>> {
+        let Ok(content) = std::fs::read_to_string(&self.path) else {
+            return Ok(BTreeMap::new());
+        };
+        let sealed: SealedSecrets =
+            serde_json::from_str(&content).context("Corrupt secrets file")?;
+        let salt = STANDARD.decode(&sealed.salt)?;
+        let mut blob = STANDARD.decode(&sealed.nonce)?;
+        if blob.len() != NONCE_LEN {
+            return Err(anyhow!("Corrupt secrets file nonce"));
+        }
+        blob.extend(STANDARD.decode(&sealed.data)?);
+
+        let plaintext = self.cipher(&salt)?.open(&blob).map_err(|_| {
+            anyhow!(
+                "Can't decrypt {} (wrong {} or key file?)",
+                self.path.display(),
+                PASSPHRASE_ENV
+            )
+        })?;
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, String>) -> Result<()> {
+        let salt: [u8; SALT_LEN] = random_bytes()?;
+        let blob = self.cipher(&salt)?.seal(&serde_json::to_vec(secrets)?)?;
+        let (nonce, data) = blob.split_at(NONCE_LEN);
+
+        let sealed = SealedSecrets {
+            version: 1,
+            salt: STANDARD.encode(salt),
+            nonce: STANDARD.encode(nonce),
+            data: STANDARD.encode(data),
+        };
+        write_private(
+            &self.path,
+            serde_json::to_string_pretty(&sealed)?.as_bytes(),
+        )
+    }
+
+    /// Cipher keyed from the passphrase, or from the key file (created on
+    /// first use)
+    fn cipher(&self, salt: &[u8]) -> Result<Cipher> {
+        match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) if !passphrase.is_empty() => Cipher::derive(&passphrase, salt),
+            _ => Cipher::derive_from_secret(&self.key_file()?, salt),
+        }
+    }
+
+    fn key_file(&self) -> Result<Vec<u8>> {
+        if let Ok(key) = std::fs::read(&self.key_path) {
+            return Ok(key);
+        }
+        let key: [u8; KEY_LEN] = random_bytes()?;
+        write_private(&self.key_path, &key)?;
+        Ok(key.to_vec())
+    }
+}
+
+/// Write a file readable only by the current user. The content goes to a
+/// temp file next to `path` that is renamed over it, so a failed write
+/// never leaves the secrets half-written.
+fn write_private(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = path
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent directory", path.display()))?;
+    std::fs::create_dir_all(dir)?;
+    let mut temp = tempfile::Builder::new()
+        .prefix(".secrets")
+        .tempfile_in(dir)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        temp.as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    temp.write_all(content)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    temp.as_file().sync_all()?;
+    temp.persist(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_encrypted_file_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = SecretStore {
+            backend: Backend::File(EncryptedFile::new(temp_dir.path())),
+        };
+
+        assert_eq!(store.get("openai")?, None);
+        store.set("openai", "sk-secret")?;
+        store.set("anthropic", "sk-other")?;
+        assert_eq!(store.get("openai")?.as_deref(), Some("sk-secret"));
+
+        let content = std::fs::read_to_string(temp_dir.path().join("secrets.enc"))?;
+        assert!(!content.contains("sk-secret"));
+
+        store.delete("openai")?;
+        assert_eq!(store.get("openai")?, None);
+        assert_eq!(store.get("anthropic")?.as_deref(), Some("sk-other"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_private_replaces_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("secrets.enc");
+        std::fs::write(&path, "old content that is longer")?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644))?;
+        }
+
+        write_private(&path, b"new")?;
+        assert_eq!(std::fs::read_to_string(&path)?, "new");
+        // Only the target is left, no temp files
+        assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        Ok(())
+    }
+}
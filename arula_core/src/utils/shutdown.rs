@@ -0,0 +1,144 @@
+//! Graceful shutdown on termination signals
+//!
+//! SIGTERM and SIGHUP (and console close/shutdown events on Windows) would
+//! otherwise kill the process mid-write. Once `listen` has installed the
+//! handlers, a signal only marks shutdown as requested; the UI notices on its
+//! next tick, cancels the running request, saves the conversation, waits for
+//! database writes in progress and restores the terminal before exiting.
+
+use anyhow::{Context, Result, anyhow};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Ask the UI to shut down
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Whether a termination signal arrived (or `request` was called)
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+/// Install the signal handlers on a background thread. Returns once they
+/// are in place, so signals from then on are caught.
+pub fn listen() -> Result<()> {
+    let (ready_tx, ready_rx) = mpsc::channel();
+    std::thread::Builder::new()
+        .name("arula-signals".to_string())
+        .spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ = ready_tx.send(Err(anyhow!(e)));
+                    return;
+                }
+            };
+            runtime.block_on(async move {
+                let mut signals = match Signals::install() {
+                    Ok(signals) => signals,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(e));
+                        return;
+                    }
+                };
+                let _ = ready_tx.send(Ok(()));
+                signals.recv().await;
+                request();
+            });
+        })
+        .context("Failed to start the signal thread")?;
+    ready_rx
+        .recv()
+        .context("The signal thread stopped before it was ready")?
+}
+
+#[cfg(unix)]
+struct Signals {
+    terminate: tokio::signal::unix::Signal,
+    hangup: tokio::signal::unix::Signal,
+}
+
+#[cfg(unix)]
+impl Signals {
+    fn install() -> Result<Self> {
+        use tokio::signal::unix::{SignalKind, signal};
+        Ok(Self {
+            terminate: signal(SignalKind::terminate()).context("Failed to catch SIGTERM")?,
+            hangup: signal(SignalKind::hangup()).context("Failed to catch SIGHUP")?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.terminate.recv() => {}
+            _ = self.hangup.recv() => {}
+        }
+    }
+}
+
+#[cfg(windows)]
+struct Signals {
+    close: tokio::signal::windows::CtrlClose,
+    shutdown: tokio::signal::windows::CtrlShutdown,
+}
+
+#[cfg(windows)]
+impl Signals {
+    fn install() -> Result<Self> {
+        use tokio::signal::windows::{ctrl_close, ctrl_shutdown};
+        Ok(Self {
+            close: ctrl_close().context("Failed to catch console close")?,
+            shutdown: ctrl_shutdown().context("Failed to catch system shutdown")?,
+        })
+    }
+
+    async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.close.recv() => {}
+            _ = self.shutdown.recv() => {}
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+struct Signals;
+
+#[cfg(not(any(unix, windows)))]
+impl Signals {
+    fn install() -> Result<Self> {
+        Ok(Self)
+    }
+
+    async fn recv(&mut self) {
+        std::future::pending::<()>().await
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_sigterm_requests_shutdown() {
+        listen().unwrap();
+        assert!(!requested());
+        let status = std::process::Command::new("kill")
+            .args(["-TERM", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !requested() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(requested());
+    }
+}
@@ -0,0 +1,362 @@
+//! Text-to-speech: read assistant replies aloud
+//!
+//! The reply is reduced to its prose (code blocks and Markdown markup are
+//! left out) and spoken either by the system voice (`say` on macOS,
+//! `espeak-ng`/`espeak` on Linux, System.Speech on Windows, or
+//! `speech.speak_command`) or by OpenAI's speech endpoint, whose audio is
+//! played with an installed player. Nothing is spoken unless asked:
+//! `speech.enabled` reads every reply in the CLI (`/speak on`), and the
+//! desktop app has a play button on each reply.
+//!
+//! ```json
+//! "speech": {
+//!   "enabled": true,
+//!   "backend": "openai",
+//!   "voice": "nova"
+//! }
+//! ```
+
+use crate::utils::config::Config;
+use crate::utils::voice::{on_path, openai_credentials};
+use anyhow::{Context, Result, anyhow, bail};
+use arula_llm::provider_error::ProviderError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const OPENAI_MODEL: &str = "gpt-4o-mini-tts";
+const OPENAI_VOICE: &str = "alloy";
+/// Longest input OpenAI's speech endpoint takes
+const OPENAI_MAX_CHARS: usize = 4096;
+
+/// What speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeechBackend {
+    /// The operating system's voice
+    System,
+    /// OpenAI's `/audio/speech` endpoint
+    OpenAi,
+}
+
+/// Text-to-speech settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeechConfig {
+    /// Read every reply aloud in the CLI (default: off)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// What speaks (default: the system voice when one is installed,
+    /// otherwise OpenAI when an OpenAI key is configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<SpeechBackend>,
+    /// System voice name, or OpenAI voice (default: alloy)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    /// OpenAI model (default: gpt-4o-mini-tts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// System speaker to run instead of the detected one; `{file}` is
+    /// replaced by a text file holding what to say
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speak_command: Option<String>,
+    /// Player for OpenAI's audio instead of the detected one; `{file}` is
+    /// replaced by the WAV file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub play_command: Option<String>,
+}
+
+impl SpeechConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+}
+
+/// Speech ready to play: the command that plays it and its file
+#[derive(Debug)]
+pub struct Utterance {
+    args: Vec<String>,
+    _dir: TempDir,
+}
+
+/// Speech being played; dropping it stops playback
+pub struct Playback {
+    child: Child,
+    _utterance: Arc<Utterance>,
+}
+
+impl Utterance {
+    /// Start playing
+    pub fn play(self: Arc<Self>) -> Result<Playback> {
+        let (program, args) = self
+            .args
+            .split_first()
+            .ok_or_else(|| anyhow!("The speech command is empty"))?;
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start {}", program))?;
+        Ok(Playback {
+            child,
+            _utterance: self,
+        })
+    }
+}
+
+impl Playback {
+    /// Whether everything was said
+    pub fn is_finished(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(Some(_)))
+    }
+
+    /// Stop speaking
+    pub fn stop(self) {
+        drop(self);
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Prepare `reply` to be read aloud with the configured backend
+pub async fn prepare(reply: &str, config: &Config) -> Result<Arc<Utterance>> {
+    let settings = config.get_speech();
+    let text = speakable_text(reply);
+    if text.is_empty() {
+        bail!("There's nothing to read aloud; the reply is all code");
+    }
+    let dir = TempDir::new().context("Failed to create a folder for speech")?;
+
+    let has_speaker = settings.speak_command.is_some() || detect_speaker(None).is_some();
+    let args = match backend(&settings, has_speaker, openai_credentials(config).is_some())? {
+        SpeechBackend::System => {
+            let file = dir.path().join("speech.txt");
+            std::fs::write(&file, &text).context("Failed to write the speech text")?;
+            let template = match &settings.speak_command {
+                Some(command) => command.split_whitespace().map(str::to_string).collect(),
+                None => detect_speaker(settings.voice.as_deref()).ok_or_else(|| {
+                    anyhow!("No system voice found. Install espeak-ng, or set speech.speak_command")
+                })?,
+            };
+            fill_file(&template, &file)
+        }
+        SpeechBackend::OpenAi => {
+            let (url, key) = openai_credentials(config)
+                .ok_or_else(|| anyhow!("OpenAI speech needs an OpenAI API key"))?;
+            let file = dir.path().join("speech.wav");
+            let audio = synthesize_openai(&text, &url, &key, &settings).await?;
+            tokio::fs::write(&file, audio)
+                .await
+                .context("Failed to write the speech audio")?;
+            let template = match &settings.play_command {
+                Some(command) => command.split_whitespace().map(str::to_string).collect(),
+                None => detect_player().ok_or_else(|| {
+                    anyhow!("No audio player found. Install ffmpeg (ffplay) or aplay, or set speech.play_command")
+                })?,
+            };
+            fill_file(&template, &file)
+        }
+    };
+    Ok(Arc::new(Utterance { args, _dir: dir }))
+}
+
+/// The backend to use, from the settings and what's available
+fn backend(settings: &SpeechConfig, has_speaker: bool, has_openai: bool) -> Result<SpeechBackend> {
+    if let Some(backend) = settings.backend {
+        return Ok(backend);
+    }
+    if has_speaker {
+        Ok(SpeechBackend::System)
+    } else if has_openai {
+        Ok(SpeechBackend::OpenAi)
+    } else {
+        bail!("Reading aloud needs a voice: install espeak-ng, or configure an OpenAI API key")
+    }
+}
+
+async fn synthesize_openai(
+    text: &str,
+    api_url: &str,
+    api_key: &str,
+    settings: &SpeechConfig,
+) -> Result<Vec<u8>> {
+    let input: String = text.chars().take(OPENAI_MAX_CHARS).collect();
+    let body = serde_json::json!({
+        "model": settings.model.as_deref().unwrap_or(OPENAI_MODEL),
+        "voice": settings.voice.as_deref().unwrap_or(OPENAI_VOICE),
+        "input": input,
+        "response_format": "wav",
+    });
+    let url = format!("{}/audio/speech", api_url.trim_end_matches('/'));
+    let response = arula_llm::http_client::get_ai_client()
+        .post(&url)
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("openai", response)
+            .await
+            .record()
+            .into());
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// The prose of a Markdown reply: code blocks, links' targets and markup
+/// are left out, since they don't read well aloud
+pub fn speakable_text(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line = trimmed
+            .trim_start_matches('#')
+            .trim_start_matches('>')
+            .trim_start();
+        let line = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .unwrap_or(line);
+        if line.chars().all(|c| matches!(c, '-' | '|' | ':' | ' ')) {
+            continue;
+        }
+        let line = strip_links(line).replace(['*', '`'], "").replace('|', " ");
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines.join("\n")
+}
+
+/// `[text](url)` to `text`
+fn strip_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else {
+            break;
+        };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else {
+            break;
+        };
+        out.push_str(&rest[..open]);
+        out.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The first installed system speaker, reading a text file
+fn detect_speaker(voice: Option<&str>) -> Option<Vec<String>> {
+    let mut args: Vec<String> = if cfg!(target_os = "macos") && on_path("say") {
+        vec!["say".into(), "-f".into(), "{file}".into()]
+    } else if cfg!(windows) {
+        vec![
+            "powershell".into(),
+            "-NoProfile".into(),
+            "-Command".into(),
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([IO.File]::ReadAllText('{file}'))"
+                .into(),
+        ]
+    } else if let Some(program) = ["espeak-ng", "espeak"].into_iter().find(|p| on_path(p)) {
+        vec![program.into(), "-f".into(), "{file}".into()]
+    } else {
+        return None;
+    };
+    if let Some(voice) = voice
+        && !cfg!(windows)
+    {
+        args.insert(1, "-v".into());
+        args.insert(2, voice.into());
+    }
+    Some(args)
+}
+
+/// The first installed audio player
+fn detect_player() -> Option<Vec<String>> {
+    let template = if cfg!(target_os = "macos") && on_path("afplay") {
+        "afplay {file}"
+    } else if cfg!(windows) {
+        return Some(vec![
+            "powershell".into(),
+            "-NoProfile".into(),
+            "-Command".into(),
+            "(New-Object Media.SoundPlayer '{file}').PlaySync()".into(),
+        ]);
+    } else if on_path("paplay") {
+        "paplay {file}"
+    } else if on_path("aplay") {
+        "aplay -q {file}"
+    } else if on_path("ffplay") {
+        "ffplay -nodisp -autoexit -loglevel quiet {file}"
+    } else {
+        return None;
+    };
+    Some(template.split_whitespace().map(str::to_string).collect())
+}
+
+/// Fill `{file}` into each argument
+fn fill_file(template: &[String], file: &Path) -> Vec<String> {
+    let file = file.to_string_lossy();
+    template
+        .iter()
+        .map(|arg| arg.replace("{file}", &file))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speakable_text_and_backend() {
+        let reply = "## Done\n\nI fixed **two** bugs in `parser.rs`; see [the docs](https://example.com).\n\n```rust\nfn main() {}\n```\n\n- Ran the tests\n| a | b |\n|---|---|";
+        assert_eq!(
+            speakable_text(reply),
+            "Done\nI fixed two bugs in parser.rs; see the docs.\nRan the tests\na b"
+        );
+        assert_eq!(speakable_text("```\nonly code\n```"), "");
+
+        let file = Path::new("/tmp/a b/speech.txt");
+        assert_eq!(
+            fill_file(&["espeak-ng".into(), "-f".into(), "{file}".into()], file),
+            ["espeak-ng", "-f", "/tmp/a b/speech.txt"]
+        );
+
+        let mut settings = SpeechConfig::default();
+        assert!(!settings.is_enabled());
+        assert_eq!(
+            backend(&settings, true, true).unwrap(),
+            SpeechBackend::System
+        );
+        assert_eq!(
+            backend(&settings, false, true).unwrap(),
+            SpeechBackend::OpenAi
+        );
+        assert!(backend(&settings, false, false).is_err());
+        settings.backend = Some(SpeechBackend::OpenAi);
+        assert_eq!(
+            backend(&settings, true, false).unwrap(),
+            SpeechBackend::OpenAi
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Conversation statistics for `/stats`
+//!
+//! Counts for the current session come from the conversation itself: its
+//! messages, the tools it ran, the files those tools touched and how long
+//! replies took. Daily responses, tokens, cost and latency come from the
+//! `usage` table of the local database, and tool frequency from `tool_usage`.
+
+use crate::api::capabilities;
+use crate::storage::{DailyUsage, SessionRecord, Storage, ToolCount};
+use crate::utils::conversation::Conversation;
+use anyhow::Result;
+use chrono::{Duration as ChronoDuration, Local, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Days covered by the usage trend
+pub const TREND_DAYS: usize = 30;
+
+/// Files listed as busiest
+const MAX_FILES: usize = 5;
+
+/// Sessions listed besides the current one
+const MAX_SESSIONS: usize = 5;
+
+/// Calls of one tool in a session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolUsage {
+    pub name: String,
+    pub calls: usize,
+    pub failures: usize,
+    /// Total run time of the calls
+    pub time: Duration,
+}
+
+/// What happened in one conversation
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionStats {
+    pub user_messages: usize,
+    pub assistant_messages: usize,
+    /// Tools by number of calls, most used first
+    pub tools: Vec<ToolUsage>,
+    /// Files passed to tools and how often, busiest first
+    pub files: Vec<(String, usize)>,
+    /// Mean time from a user message to the first reply after it
+    pub average_latency: Option<Duration>,
+}
+
+impl SessionStats {
+    pub fn from_conversation(conversation: &Conversation) -> Self {
+        let mut stats = SessionStats::default();
+        let mut tools: HashMap<String, ToolUsage> = HashMap::new();
+        let mut files: HashMap<String, usize> = HashMap::new();
+        let mut latencies = Vec::new();
+        let mut waiting_since = None;
+
+        for message in &conversation.messages {
+            match message.role.as_str() {
+                "user" => {
+                    stats.user_messages += 1;
+                    waiting_since = Some(message.timestamp);
+                }
+                "assistant" => {
+                    stats.assistant_messages += 1;
+                    if let Some(sent) = waiting_since.take() {
+                        latencies.push((message.timestamp - sent).to_std().unwrap_or_default());
+                    }
+                    for call in message.tool_calls.iter().flatten() {
+                        if let Some(path) = file_argument(&call.arguments) {
+                            *files.entry(path).or_default() += 1;
+                        }
+                    }
+                }
+                "tool" => {
+                    let name = message
+                        .tool_name
+                        .clone()
+                        .unwrap_or_else(|| "unknown".into());
+                    let usage = tools.entry(name.clone()).or_insert(ToolUsage {
+                        name,
+                        calls: 0,
+                        failures: 0,
+                        time: Duration::ZERO,
+                    });
+                    usage.calls += 1;
+                    if message.metadata.success == Some(false) {
+                        usage.failures += 1;
+                    }
+                    usage.time += Duration::from_millis(
+                        message.metadata.execution_time_ms.unwrap_or_default(),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        stats.tools = tools.into_values().collect();
+        stats
+            .tools
+            .sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+        stats.files = files.into_iter().collect();
+        stats
+            .files
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        stats.files.truncate(MAX_FILES);
+        if !latencies.is_empty() {
+            stats.average_latency =
+                Some(latencies.iter().sum::<Duration>() / latencies.len() as u32);
+        }
+        stats
+    }
+
+    pub fn tool_calls(&self) -> usize {
+        self.tools.iter().map(|tool| tool.calls).sum()
+    }
+
+    pub fn tool_failures(&self) -> usize {
+        self.tools.iter().map(|tool| tool.failures).sum()
+    }
+}
+
+/// The file a tool call works on, from its JSON arguments
+fn file_argument(arguments: &str) -> Option<String> {
+    let args: serde_json::Value = serde_json::from_str(arguments).ok()?;
+    ["path", "file_path"]
+        .iter()
+        .find_map(|key| args[key].as_str())
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+}
+
+/// Responses, tokens, cost and latency of one day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DayUsage {
+    pub day: NaiveDate,
+    pub responses: u64,
+    pub tokens: u64,
+    /// Estimated cost in USD of the responses whose model price is known
+    pub cost: f64,
+    latency_ms: u64,
+    timed_responses: u64,
+}
+
+impl DayUsage {
+    /// Mean time the responses with a recorded latency took
+    pub fn average_latency(&self) -> Option<Duration> {
+        (self.timed_responses > 0)
+            .then(|| Duration::from_millis(self.latency_ms / self.timed_responses))
+    }
+}
+
+/// One entry per day for the `days` days up to `today`, oldest first,
+/// with days without usage at zero
+pub fn usage_trend(rows: &[DailyUsage], today: NaiveDate, days: usize) -> Vec<DayUsage> {
+    let mut trend: Vec<DayUsage> = (0..days)
+        .rev()
+        .map(|ago| DayUsage {
+            day: today - ChronoDuration::days(ago as i64),
+            responses: 0,
+            tokens: 0,
+            cost: 0.0,
+            latency_ms: 0,
+            timed_responses: 0,
+        })
+        .collect();
+    for row in rows {
+        let Some(day) = trend.iter_mut().find(|day| day.day == row.day) else {
+            continue;
+        };
+        day.responses += row.responses;
+        day.tokens += row.total_tokens;
+        day.latency_ms += row.latency_ms;
+        day.timed_responses += row.timed_responses;
+        let cost = capabilities::lookup(&row.model)
+            .and_then(|caps| caps.cost(row.prompt_tokens, row.completion_tokens));
+        day.cost += cost.unwrap_or_default();
+    }
+    trend
+}
+
+/// Everything `/stats` shows
+#[derive(Debug, Clone, Default)]
+pub struct StatsReport {
+    pub session: SessionStats,
+    /// Recently updated saved sessions
+    pub recent_sessions: Vec<SessionRecord>,
+    /// Usage per day for the last [`TREND_DAYS`] days
+    pub trend: Vec<DayUsage>,
+    /// Calls per tool over the last [`TREND_DAYS`] days, most used first
+    pub tools: Vec<ToolCount>,
+}
+
+impl StatsReport {
+    /// Statistics of `conversation` with the trends from the local database
+    pub fn collect(conversation: Option<&Conversation>) -> Result<Self> {
+        let since = Utc::now() - ChronoDuration::days(TREND_DAYS as i64);
+        let (rows, recent_sessions, tools) = Storage::with(|storage| {
+            Ok((
+                storage.daily_usage(since)?,
+                storage.sessions(MAX_SESSIONS)?,
+                storage.tool_counts(since)?,
+            ))
+        })?;
+        Ok(Self {
+            session: conversation
+                .map(SessionStats::from_conversation)
+                .unwrap_or_default(),
+            recent_sessions,
+            trend: usage_trend(&rows, Local::now().date_naive(), TREND_DAYS),
+            tools,
+        })
+    }
+
+    /// Mean response latency over the whole trend
+    pub fn average_latency(&self) -> Option<Duration> {
+        let timed: u64 = self.trend.iter().map(|day| day.timed_responses).sum();
+        let total: u64 = self.trend.iter().map(|day| day.latency_ms).sum();
+        (timed > 0).then(|| Duration::from_millis(total / timed))
+    }
+}
+
+/// A token count shortened to thousands or millions
+pub fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+/// `values` as a row of block characters scaled to the largest
+pub fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = values.iter().copied().max().unwrap_or_default();
+    values
+        .iter()
+        .map(|&value| match value {
+            0 => ' ',
+            _ => BARS[(value * (BARS.len() as u64 - 1) / max) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_session_stats_from_conversation() {
+        let mut conversation =
+            Conversation::new("gpt-4o".to_string(), "openai".to_string(), String::new());
+        conversation.add_user_message("Fix the parser".to_string());
+        conversation.add_assistant_message("Looking".to_string(), None);
+        let sent = conversation.messages[0].timestamp;
+        let last = conversation.messages.last_mut().unwrap();
+        last.timestamp = sent + ChronoDuration::seconds(4);
+        last.tool_calls = Some(
+            ["src/parser.rs", "src/parser.rs", "src/lib.rs"]
+                .iter()
+                .map(|path| crate::utils::conversation::ToolCall {
+                    id: String::new(),
+                    name: "read_file".to_string(),
+                    arguments: json!({ "path": path }).to_string(),
+                    timestamp: Utc::now(),
+                })
+                .collect(),
+        );
+        conversation.add_tool_result("1".into(), "read_file".into(), json!("ok"), true, 30);
+        conversation.add_tool_result("2".into(), "read_file".into(), json!("ok"), true, 20);
+        conversation.add_tool_result("3".into(), "execute_bash".into(), json!("no"), false, 900);
+
+        let stats = SessionStats::from_conversation(&conversation);
+        assert_eq!((stats.user_messages, stats.assistant_messages), (1, 1));
+        assert_eq!(stats.tool_calls(), 3);
+        assert_eq!(stats.tool_failures(), 1);
+        assert_eq!(stats.tools[0].name, "read_file");
+        assert_eq!(stats.tools[0].time, Duration::from_millis(50));
+        assert_eq!(stats.files[0], ("src/parser.rs".to_string(), 2));
+        assert_eq!(stats.average_latency, Some(Duration::from_secs(4)));
+    }
+
+    #[test]
+    fn test_usage_trend_and_sparkline() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let row = |day: u32, tokens: u64| DailyUsage {
+            day: NaiveDate::from_ymd_opt(2026, 3, day).unwrap(),
+            provider: "custom".to_string(),
+            model: "unpriced-model".to_string(),
+            prompt_tokens: tokens,
+            completion_tokens: 0,
+            total_tokens: tokens,
+            responses: 1,
+            latency_ms: tokens * 10,
+            timed_responses: 1,
+        };
+        let trend = usage_trend(
+            &[row(1, 50), row(9, 100), row(9, 100), row(10, 400)],
+            today,
+            3,
+        );
+        assert_eq!(trend.len(), 3);
+        assert_eq!(trend[0].tokens, 0);
+        assert_eq!(trend[1].tokens, 200);
+        assert_eq!(trend[1].responses, 2);
+        assert_eq!(trend[1].average_latency(), Some(Duration::from_secs(1)));
+        assert_eq!(trend[0].average_latency(), None);
+        assert_eq!(trend[2].day, today);
+        assert_eq!(trend[2].cost, 0.0);
+
+        assert_eq!(sparkline(&[0, 200, 400]), " ▄█");
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(format_tokens(850), "850");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(2_500_000), "2.5M");
+    }
+}
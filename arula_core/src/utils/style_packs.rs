@@ -0,0 +1,315 @@
+//! Spinner and progress bar style packs
+//!
+//! A spinner pack is a list of frames and a frame interval; a progress style
+//! is the characters a bar is drawn with. Both come in built-in sets and can
+//! be defined in config.json under `appearance`:
+//!
+//! ```json
+//! "appearance": {
+//!   "spinner": "moon",
+//!   "progress": "thin",
+//!   "spinners": { "moon": { "frames": ["🌑", "🌒", "🌓", "🌔", "🌕"], "interval_ms": 120 } },
+//!   "progress_styles": { "thin": { "chars": "━╸ " } }
+//! }
+//! ```
+//!
+//! User-defined packs take precedence over built-in ones of the same name.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Spinner used when none is configured
+pub const DEFAULT_SPINNER: &str = "circle";
+/// Progress style used when none is configured
+pub const DEFAULT_PROGRESS: &str = "blocks";
+
+const DEFAULT_INTERVAL_MS: u64 = 100;
+
+/// Frames of a spinner animation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpinnerPack {
+    pub frames: Vec<String>,
+    /// Time per frame (default: 100ms)
+    #[serde(
+        default = "default_interval",
+        skip_serializing_if = "is_default_interval"
+    )]
+    pub interval_ms: u64,
+}
+
+fn default_interval() -> u64 {
+    DEFAULT_INTERVAL_MS
+}
+
+fn is_default_interval(interval: &u64) -> bool {
+    *interval == DEFAULT_INTERVAL_MS
+}
+
+impl SpinnerPack {
+    fn builtin(frames: &[&str], interval_ms: u64) -> Self {
+        Self {
+            frames: frames.iter().map(|f| f.to_string()).collect(),
+            interval_ms,
+        }
+    }
+
+    /// The frame for an animation step (wraps around)
+    pub fn frame(&self, step: usize) -> &str {
+        if self.frames.is_empty() {
+            return "";
+        }
+        &self.frames[step % self.frames.len()]
+    }
+
+    /// The frame `elapsed_ms` into the animation
+    pub fn frame_at(&self, elapsed_ms: u128) -> &str {
+        self.frame((elapsed_ms / self.interval_ms.max(1) as u128) as usize)
+    }
+}
+
+/// Characters a progress bar is drawn with
+///
+/// The first character fills completed cells, the last one empty cells, and
+/// any in between mark the partly completed cell from least to most done
+/// (the same format as indicatif's `progress_chars`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgressStyle {
+    pub chars: String,
+}
+
+impl ProgressStyle {
+    fn builtin(chars: &str) -> Self {
+        Self {
+            chars: chars.to_string(),
+        }
+    }
+
+    /// Whether `chars` has at least a fill and an empty character
+    pub fn is_valid(&self) -> bool {
+        self.chars.chars().count() >= 2
+    }
+
+    /// Draw a bar `width` cells wide, `ratio` (0.0–1.0) complete
+    pub fn render(&self, ratio: f32, width: usize) -> String {
+        let chars: Vec<char> = self.chars.chars().collect();
+        let (Some(&fill), Some(&empty)) = (chars.first(), chars.last()) else {
+            return String::new();
+        };
+        let partials = &chars[1..chars.len() - 1];
+
+        let progress = ratio.clamp(0.0, 1.0) * width as f32;
+        let full = (progress.floor() as usize).min(width);
+        let mut bar: String = std::iter::repeat_n(fill, full).collect();
+        if full < width {
+            let fraction = progress - full as f32;
+            if !partials.is_empty() && fraction > 0.0 {
+                // Partial characters run from most to least done
+                let index = ((1.0 - fraction) * partials.len() as f32) as usize;
+                bar.push(partials[index.min(partials.len() - 1)]);
+            } else {
+                bar.push(empty);
+            }
+            bar.extend(std::iter::repeat_n(empty, width - full - 1));
+        }
+        bar
+    }
+}
+
+/// Built-in spinner packs, in gallery order
+pub fn builtin_spinners() -> Vec<(&'static str, SpinnerPack)> {
+    vec![
+        ("circle", SpinnerPack::builtin(&["◐", "◓", "◑", "◒"], 100)),
+        (
+            "dots",
+            SpinnerPack::builtin(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"], 80),
+        ),
+        (
+            "orbit",
+            SpinnerPack::builtin(
+                &[
+                    "⢀⠠", "⡀⢀", "⠄⡀", "⢄⠄", "⡄⢄", "⠌⡄", "⢌⠌", "⡌⢌", "⠎⡌", "⢎⠎", "⡎⢎", "⠱⡎", "⢱⠱",
+                    "⡱⢱", "⠹⡱", "⢹⠹",
+                ],
+                100,
+            ),
+        ),
+        (
+            "arc",
+            SpinnerPack::builtin(&["◜", "◠", "◝", "◞", "◡", "◟"], 100),
+        ),
+        (
+            "quantum",
+            SpinnerPack::builtin(
+                &["◌", "◉", "◎", "●", "○", "◯", "◇", "◆", "⋄", "⋆", "✦", "✧"],
+                120,
+            ),
+        ),
+        (
+            "wave",
+            SpinnerPack::builtin(
+                &["▂", "▃", "▄", "▅", "▆", "▇", "█", "▇", "▆", "▅", "▄", "▃"],
+                80,
+            ),
+        ),
+        ("line", SpinnerPack::builtin(&["-", "\\", "|", "/"], 100)),
+        (
+            "moon",
+            SpinnerPack::builtin(&["🌑", "🌒", "🌓", "🌔", "🌕", "🌖", "🌗", "🌘"], 120),
+        ),
+    ]
+}
+
+/// Built-in progress styles, in gallery order
+pub fn builtin_progress_styles() -> Vec<(&'static str, ProgressStyle)> {
+    vec![
+        ("blocks", ProgressStyle::builtin("█▓░")),
+        ("smooth", ProgressStyle::builtin("█▉▊▋▌▍▎▏ ")),
+        ("thin", ProgressStyle::builtin("━╸ ")),
+        ("dots", ProgressStyle::builtin("⣿⡇ ")),
+        ("ascii", ProgressStyle::builtin("#>-")),
+    ]
+}
+
+/// Spinner, progress bar and theme settings (`appearance` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppearanceConfig {
+    /// Active spinner pack (default: `circle`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spinner: Option<String>,
+    /// Active progress bar style (default: `blocks`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<String>,
+    /// Syntax highlighting theme for code blocks, e.g. `InspiredGitHub`
+    /// (default: picked from the terminal background)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_theme: Option<String>,
+    /// Color theme shared by the CLI and desktop app (default: `dark`);
+    /// `system` follows the OS light/dark setting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Theme used when following a light OS color scheme (default: `light`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light_theme: Option<String>,
+    /// Theme used when following a dark OS color scheme (default: `dark`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dark_theme: Option<String>,
+    /// User-defined spinner packs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub spinners: BTreeMap<String, SpinnerPack>,
+    /// User-defined progress styles
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub progress_styles: BTreeMap<String, ProgressStyle>,
+}
+
+impl AppearanceConfig {
+    /// Every spinner pack by name: built-in ones, then user-defined ones
+    /// (which replace built-ins of the same name)
+    pub fn spinner_packs(&self) -> Vec<(String, SpinnerPack)> {
+        let mut packs: Vec<(String, SpinnerPack)> = builtin_spinners()
+            .into_iter()
+            .filter(|(name, _)| self.spinners.get(*name).is_none_or(|p| p.frames.is_empty()))
+            .map(|(name, pack)| (name.to_string(), pack))
+            .collect();
+        packs.extend(
+            self.spinners
+                .iter()
+                .filter(|(_, pack)| !pack.frames.is_empty())
+                .map(|(name, pack)| (name.clone(), pack.clone())),
+        );
+        packs
+    }
+
+    /// Every progress style by name: built-in ones, then user-defined ones
+    pub fn progress_styles(&self) -> Vec<(String, ProgressStyle)> {
+        let mut styles: Vec<(String, ProgressStyle)> = builtin_progress_styles()
+            .into_iter()
+            .filter(|(name, _)| {
+                self.progress_styles
+                    .get(*name)
+                    .is_none_or(|style| !style.is_valid())
+            })
+            .map(|(name, style)| (name.to_string(), style))
+            .collect();
+        styles.extend(
+            self.progress_styles
+                .iter()
+                .filter(|(_, style)| style.is_valid())
+                .map(|(name, style)| (name.clone(), style.clone())),
+        );
+        styles
+    }
+
+    /// Look up a spinner pack by name
+    pub fn find_spinner(&self, name: &str) -> Option<SpinnerPack> {
+        self.spinner_packs()
+            .into_iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, pack)| pack)
+    }
+
+    /// Look up a progress style by name
+    pub fn find_progress_style(&self, name: &str) -> Option<ProgressStyle> {
+        self.progress_styles()
+            .into_iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, style)| style)
+    }
+
+    /// The active spinner pack, falling back to the default one
+    pub fn spinner(&self) -> SpinnerPack {
+        self.spinner
+            .as_deref()
+            .and_then(|name| self.find_spinner(name))
+            .or_else(|| self.find_spinner(DEFAULT_SPINNER))
+            .expect("default spinner is built in")
+    }
+
+    /// The active progress style, falling back to the default one
+    pub fn progress_style(&self) -> ProgressStyle {
+        self.progress
+            .as_deref()
+            .and_then(|name| self.find_progress_style(name))
+            .or_else(|| self.find_progress_style(DEFAULT_PROGRESS))
+            .expect("default progress style is built in")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_packs_override_builtins() {
+        let mut appearance = AppearanceConfig::default();
+        assert_eq!(appearance.spinner().frame(1), "◓");
+
+        appearance.spinners.insert(
+            "circle".to_string(),
+            SpinnerPack {
+                frames: vec!["a".into(), "b".into()],
+                interval_ms: 50,
+            },
+        );
+        appearance.spinner = Some("Circle".to_string());
+        let pack = appearance.spinner();
+        assert_eq!(pack.frame(3), "b");
+        assert_eq!(pack.frame_at(120), "a");
+
+        // Unknown names fall back to the default
+        appearance.progress = Some("missing".to_string());
+        assert_eq!(appearance.progress_style().chars, "█▓░");
+    }
+
+    #[test]
+    fn test_render_progress() {
+        let blocks = ProgressStyle::builtin("█▓░");
+        assert_eq!(blocks.render(0.0, 4), "░░░░");
+        assert_eq!(blocks.render(0.5, 4), "██░░");
+        assert_eq!(blocks.render(0.6, 4), "██▓░");
+        assert_eq!(blocks.render(1.5, 4), "████");
+
+        let ascii = ProgressStyle::builtin("#-");
+        assert_eq!(ascii.render(0.3, 10), "###-------");
+        assert!(!ProgressStyle::builtin("#").is_valid());
+    }
+}
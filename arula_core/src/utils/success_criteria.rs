@@ -0,0 +1,262 @@
+//! Success criteria for agent runs
+//!
+//! A task can be given checks that decide whether it is done: the tests
+//! pass, a file exists, a command exits 0. The model is told about them and
+//! signals that it is finished with the `task_complete` tool, but the verdict
+//! comes from running the checks when the run ends, not from the model's
+//! claim.
+
+use crate::tools::builtin::run_tests::{build_test_command, parse_test_output, run_command};
+use crate::utils::project_context::{ProjectType, detect_project};
+use anyhow::{Result, bail};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Seconds a test run may take before it counts as failed
+const TESTS_TIMEOUT_SECS: u64 = 900;
+
+/// Seconds any other command may take
+const COMMAND_TIMEOUT_SECS: u64 = 300;
+
+/// Name of the tool the model calls when it considers the task done
+pub const TASK_COMPLETE_TOOL: &str = "task_complete";
+
+/// One machine-checkable condition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "target", rename_all = "snake_case")]
+pub enum Criterion {
+    /// The project's test suite passes
+    TestsPass,
+    /// A file exists, relative to the project root
+    FileExists(PathBuf),
+    /// A shell command exits with status 0
+    CommandSucceeds(String),
+}
+
+impl Criterion {
+    /// Parse `tests`, `file <path>` or `cmd <command>`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let (kind, target) = match spec.split_once(char::is_whitespace) {
+            Some((kind, target)) => (kind, target.trim()),
+            None => (spec, ""),
+        };
+        let criterion = match (kind.to_lowercase().as_str(), target) {
+            ("tests" | "test", "") => Criterion::TestsPass,
+            ("file" | "exists", path) if !path.is_empty() => {
+                Criterion::FileExists(PathBuf::from(path))
+            }
+            ("cmd" | "command" | "run", command) if !command.is_empty() => {
+                Criterion::CommandSucceeds(command.to_string())
+            }
+            _ => bail!(
+                "Unknown criterion '{}': use `tests`, `file <path>` or `cmd <command>`",
+                spec
+            ),
+        };
+        Ok(criterion)
+    }
+
+    /// Run the check in `root`
+    pub async fn check(&self, root: &Path) -> CriterionResult {
+        let (passed, detail) = match self {
+            Criterion::TestsPass => check_tests(root).await,
+            Criterion::FileExists(path) => {
+                if root.join(path).exists() {
+                    (true, "found".to_string())
+                } else {
+                    (false, "missing".to_string())
+                }
+            }
+            Criterion::CommandSucceeds(command) => {
+                match run_command(command, root, COMMAND_TIMEOUT_SECS).await {
+                    Ok((_, 0, _)) => (true, "exit 0".to_string()),
+                    Ok((output, code, _)) => (false, exit_detail(code, &output)),
+                    Err(e) => (false, e),
+                }
+            }
+        };
+        CriterionResult {
+            criterion: self.clone(),
+            passed,
+            detail,
+        }
+    }
+}
+
+impl fmt::Display for Criterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Criterion::TestsPass => write!(f, "the tests pass"),
+            Criterion::FileExists(path) => write!(f, "`{}` exists", path.display()),
+            Criterion::CommandSucceeds(command) => write!(f, "`{}` exits 0", command),
+        }
+    }
+}
+
+async fn check_tests(root: &Path) -> (bool, String) {
+    let project_type = detect_project(root)
+        .map(|p| p.project_type)
+        .unwrap_or(ProjectType::Unknown);
+    let Some(command) = build_test_command(root, &project_type, None) else {
+        return (false, "no test command for this project".to_string());
+    };
+    match run_command(&command, root, TESTS_TIMEOUT_SECS).await {
+        Ok((output, code, success)) => {
+            let summary = parse_test_output(&project_type, &output);
+            if success {
+                (true, format!("{} passed", summary.passed))
+            } else if summary.failing_tests.is_empty() {
+                (false, exit_detail(code, &output))
+            } else {
+                (
+                    false,
+                    format!(
+                        "{} failed: {}",
+                        summary.failed.max(summary.failing_tests.len()),
+                        summary.failing_tests.join(", ")
+                    ),
+                )
+            }
+        }
+        Err(e) => (false, e),
+    }
+}
+
+/// Exit code and the last line of output of a failed command
+fn exit_detail(code: i32, output: &str) -> String {
+    match output.lines().rev().find(|l| !l.trim().is_empty()) {
+        Some(line) => format!("exit {}: {}", code, line.trim()),
+        None => format!("exit {}", code),
+    }
+}
+
+/// Outcome of one check
+#[derive(Debug, Clone, Serialize)]
+pub struct CriterionResult {
+    pub criterion: Criterion,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Outcome of checking all criteria at the end of a run
+#[derive(Debug, Clone, Serialize)]
+pub struct Verdict {
+    pub results: Vec<CriterionResult>,
+    /// Whether the model called `task_complete`
+    pub claimed_complete: bool,
+}
+
+impl Verdict {
+    /// Whether every criterion holds
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(|r| r.passed)
+    }
+
+    /// The verdict, one line per criterion under a summary
+    pub fn report(&self) -> String {
+        let failed = self.results.iter().filter(|r| !r.passed).count();
+        let mut report = if failed == 0 {
+            format!(
+                "Task complete: all {} success criteria met",
+                self.results.len()
+            )
+        } else if self.claimed_complete {
+            format!(
+                "Task NOT complete: {} of {} success criteria failed, although the agent reported it done",
+                failed,
+                self.results.len()
+            )
+        } else {
+            format!(
+                "Task NOT complete: {} of {} success criteria failed",
+                failed,
+                self.results.len()
+            )
+        };
+        for result in &self.results {
+            let mark = if result.passed { "✓" } else { "✗" };
+            report.push_str(&format!(
+                "\n  {} {} ({})",
+                mark, result.criterion, result.detail
+            ));
+        }
+        report
+    }
+}
+
+/// Check every criterion in order
+pub async fn verify(root: &Path, criteria: &[Criterion], claimed_complete: bool) -> Verdict {
+    let mut results = Vec::with_capacity(criteria.len());
+    for criterion in criteria {
+        results.push(criterion.check(root).await);
+    }
+    Verdict {
+        results,
+        claimed_complete,
+    }
+}
+
+/// Instructions telling the model what will be checked, or `None` without
+/// criteria
+pub fn prompt_section(criteria: &[Criterion]) -> Option<String> {
+    if criteria.is_empty() {
+        return None;
+    }
+    let list: Vec<String> = criteria.iter().map(|c| format!("- {}", c)).collect();
+    Some(format!(
+        "## Success Criteria\nThis task is done only when all of these hold; they are checked automatically when you finish:\n{}\nVerify them yourself before you stop, then call `{}` with a short summary.",
+        list.join("\n"),
+        TASK_COMPLETE_TOOL
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_verify_criteria() {
+        assert_eq!(Criterion::parse("tests").unwrap(), Criterion::TestsPass);
+        assert_eq!(
+            Criterion::parse("file  out/report.md").unwrap(),
+            Criterion::FileExists(PathBuf::from("out/report.md"))
+        );
+        assert_eq!(
+            Criterion::parse("cmd test -d src").unwrap(),
+            Criterion::CommandSucceeds("test -d src".to_string())
+        );
+        assert!(Criterion::parse("file").is_err());
+        assert!(Criterion::parse("looks good").is_err());
+
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("done.txt"), "ok").unwrap();
+        let criteria = vec![
+            Criterion::FileExists(PathBuf::from("done.txt")),
+            Criterion::CommandSucceeds("exit 0".to_string()),
+        ];
+        let verdict = verify(dir.path(), &criteria, true).await;
+        assert!(verdict.passed());
+        assert!(verdict.report().starts_with("Task complete: all 2"));
+
+        let criteria = vec![
+            Criterion::FileExists(PathBuf::from("missing.txt")),
+            Criterion::CommandSucceeds("echo broken; exit 3".to_string()),
+        ];
+        let verdict = verify(dir.path(), &criteria, true).await;
+        assert!(!verdict.passed());
+        let report = verdict.report();
+        assert!(report.contains("although the agent reported it done"));
+        assert!(report.contains("✗ `missing.txt` exists (missing)"));
+        assert!(report.contains("(exit 3: broken)"));
+
+        assert_eq!(prompt_section(&[]), None);
+        assert!(
+            prompt_section(&criteria)
+                .unwrap()
+                .contains("call `task_complete`")
+        );
+    }
+}
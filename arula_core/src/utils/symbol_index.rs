@@ -0,0 +1,817 @@
+//! Workspace symbol index built with tree-sitter
+//!
+//! Parses Rust, TypeScript/JavaScript, Python and Go sources and extracts
+//! functions, methods and types with their signatures and locations. The
+//! index backs the `find_symbol` tool and the key symbols section of the
+//! auto-generated project manifest.
+//!
+//! Indexes are cached per workspace root and refreshed incrementally: only
+//! files whose modification time changed are parsed again.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+use tree_sitter::{Node, Parser};
+
+/// Files larger than this are not indexed
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Signatures longer than this are truncated
+const MAX_SIGNATURE_CHARS: usize = 200;
+
+/// Indexes shared between callers, keyed by workspace root
+static INDEX_CACHE: LazyLock<Mutex<HashMap<PathBuf, Arc<SymbolIndex>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Languages the index can parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    Rust,
+    TypeScript,
+    /// TSX, also used for JavaScript and JSX
+    Tsx,
+    Python,
+    Go,
+}
+
+impl Language {
+    /// Detect the language from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Language::Rust),
+            "ts" | "mts" | "cts" => Some(Language::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Language::Tsx),
+            "py" => Some(Language::Python),
+            "go" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    /// Detect the language from a Markdown code fence tag
+    pub fn from_fence(tag: &str) -> Option<Self> {
+        match tag.to_lowercase().as_str() {
+            "rust" | "rs" => Some(Language::Rust),
+            "typescript" | "ts" => Some(Language::TypeScript),
+            "tsx" | "javascript" | "js" | "jsx" => Some(Language::Tsx),
+            "python" | "py" | "python3" => Some(Language::Python),
+            "go" | "golang" => Some(Language::Go),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn grammar(&self) -> tree_sitter::Language {
+        match self {
+            Language::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Language::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Language::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Language::Python => tree_sitter_python::LANGUAGE.into(),
+            Language::Go => tree_sitter_go::LANGUAGE.into(),
+        }
+    }
+}
+
+/// Kind of an indexed symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Trait,
+    Interface,
+    Class,
+    TypeAlias,
+    Constant,
+    Module,
+    Macro,
+}
+
+impl SymbolKind {
+    /// Parse a kind name as used in tool parameters
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "function" | "fn" | "func" => Some(SymbolKind::Function),
+            "method" => Some(SymbolKind::Method),
+            "struct" => Some(SymbolKind::Struct),
+            "enum" => Some(SymbolKind::Enum),
+            "trait" => Some(SymbolKind::Trait),
+            "interface" => Some(SymbolKind::Interface),
+            "class" => Some(SymbolKind::Class),
+            "type" | "type_alias" => Some(SymbolKind::TypeAlias),
+            "const" | "constant" | "static" => Some(SymbolKind::Constant),
+            "module" | "mod" => Some(SymbolKind::Module),
+            "macro" => Some(SymbolKind::Macro),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SymbolKind::Function => "function",
+            SymbolKind::Method => "method",
+            SymbolKind::Struct => "struct",
+            SymbolKind::Enum => "enum",
+            SymbolKind::Trait => "trait",
+            SymbolKind::Interface => "interface",
+            SymbolKind::Class => "class",
+            SymbolKind::TypeAlias => "type",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Module => "module",
+            SymbolKind::Macro => "macro",
+        }
+    }
+
+    /// Whether the kind declares a type (as opposed to a callable or value)
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            SymbolKind::Struct
+                | SymbolKind::Enum
+                | SymbolKind::Trait
+                | SymbolKind::Interface
+                | SymbolKind::Class
+                | SymbolKind::TypeAlias
+        )
+    }
+}
+
+/// A function, method or type found in the workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub language: Language,
+    /// File path relative to the workspace root
+    pub path: String,
+    /// First line of the definition (1-indexed)
+    pub line: usize,
+    /// Last line of the definition (1-indexed)
+    pub end_line: usize,
+    /// Declaration without its body, whitespace collapsed
+    pub signature: String,
+    /// Enclosing impl, class or receiver type for methods
+    pub container: Option<String>,
+    /// Whether the symbol is exported (pub, export, capitalized, no `_` prefix)
+    pub public: bool,
+}
+
+impl Symbol {
+    /// Qualified name, e.g. `GitOps::commit`
+    pub fn qualified_name(&self) -> String {
+        match &self.container {
+            Some(container) => format!("{}::{}", container, self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Symbols of one indexed file
+#[derive(Debug, Clone)]
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    symbols: Vec<Symbol>,
+}
+
+/// Symbol index of a workspace
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    root: PathBuf,
+    files: HashMap<String, IndexedFile>,
+}
+
+impl SymbolIndex {
+    /// Index every supported source file under `root` (respecting `.gitignore`)
+    pub fn build(root: &Path) -> Self {
+        let mut index = Self {
+            root: root.to_path_buf(),
+            files: HashMap::new(),
+        };
+        index.refresh();
+        index
+    }
+
+    /// Return the cached index for `root`, refreshed for changed files
+    ///
+    /// Parsing is synchronous; call from `spawn_blocking` in async code.
+    pub fn shared(root: &Path) -> Arc<SymbolIndex> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let cached = INDEX_CACHE.lock().unwrap().get(&root).cloned();
+        let index = match cached {
+            Some(cached) => {
+                let mut index = (*cached).clone();
+                index.refresh();
+                Arc::new(index)
+            }
+            None => Arc::new(SymbolIndex::build(&root)),
+        };
+        INDEX_CACHE.lock().unwrap().insert(root, Arc::clone(&index));
+        index
+    }
+
+    /// Re-parse files that changed since the last refresh and drop deleted ones
+    pub fn refresh(&mut self) {
+        let mut parsers: HashMap<Language, Parser> = HashMap::new();
+        let mut seen = HashSet::new();
+
+        for entry in ignore::WalkBuilder::new(&self.root).build().flatten() {
+            let path = entry.path();
+            let Some(language) = Language::from_path(path) else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() || metadata.len() > MAX_FILE_SIZE {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(&self.root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            seen.insert(relative.clone());
+
+            let modified = metadata.modified().ok();
+            if let Some(file) = self.files.get(&relative)
+                && modified.is_some()
+                && file.modified == modified
+            {
+                continue;
+            }
+
+            let Ok(source) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let parser = parsers.entry(language).or_insert_with(|| {
+                let mut parser = Parser::new();
+                parser
+                    .set_language(&language.grammar())
+                    .expect("bundled grammar is compatible");
+                parser
+            });
+            let symbols = parse_symbols(parser, language, &relative, &source);
+            self.files
+                .insert(relative, IndexedFile { modified, symbols });
+        }
+
+        self.files.retain(|path, _| seen.contains(path));
+    }
+
+    /// Workspace root of the index
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of indexed files
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// All indexed symbols, ordered by path and line
+    pub fn symbols(&self) -> Vec<&Symbol> {
+        let mut symbols: Vec<&Symbol> = self.files.values().flat_map(|f| &f.symbols).collect();
+        symbols.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        symbols
+    }
+
+    /// Symbols defined in one file
+    pub fn symbols_in(&self, path: &str) -> &[Symbol] {
+        self.files
+            .get(path.trim_start_matches("./"))
+            .map(|f| f.symbols.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Whether any symbol has exactly this name (or qualified name)
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.files
+            .values()
+            .flat_map(|f| &f.symbols)
+            .any(|s| s.name == name || s.qualified_name() == name)
+    }
+
+    /// Find symbols matching a query, best matches first
+    ///
+    /// Matching is case-insensitive against the name and qualified name
+    /// (`Type::method`). Exact matches rank above prefix matches, which rank
+    /// above substring matches; public symbols break ties. With `exact`,
+    /// only exact matches are returned.
+    pub fn find(&self, query: &str, kind: Option<SymbolKind>, exact: bool) -> Vec<&Symbol> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(u8, &Symbol)> = self
+            .files
+            .values()
+            .flat_map(|f| &f.symbols)
+            .filter(|s| kind.is_none_or(|k| s.kind == k))
+            .filter_map(|s| {
+                let name = s.name.to_lowercase();
+                let qualified = s.qualified_name().to_lowercase();
+                let rank = if name == query || qualified == query {
+                    0
+                } else if exact {
+                    return None;
+                } else if name.starts_with(&query) || qualified.starts_with(&query) {
+                    1
+                } else if qualified.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, s))
+            })
+            .collect();
+
+        matches.sort_by(|(rank_a, a), (rank_b, b)| {
+            rank_a
+                .cmp(rank_b)
+                .then(b.public.cmp(&a.public))
+                .then(a.path.cmp(&b.path))
+                .then(a.line.cmp(&b.line))
+        });
+        matches.into_iter().map(|(_, s)| s).collect()
+    }
+
+    /// Public types and functions for a project overview, types first
+    ///
+    /// Methods are left out; at most `limit` symbols are returned.
+    pub fn key_symbols(&self, limit: usize) -> Vec<&Symbol> {
+        let mut symbols: Vec<&Symbol> = self
+            .files
+            .values()
+            .flat_map(|f| &f.symbols)
+            .filter(|s| s.public && s.kind != SymbolKind::Method && s.kind != SymbolKind::Module)
+            .collect();
+        symbols.sort_by(|a, b| {
+            b.kind
+                .is_type()
+                .cmp(&a.kind.is_type())
+                .then(a.path.cmp(&b.path))
+                .then(a.line.cmp(&b.line))
+        });
+        symbols.truncate(limit);
+        symbols
+    }
+}
+
+/// Extract the symbols from one source file
+pub fn index_source(language: Language, path: &str, source: &str) -> Vec<Symbol> {
+    let mut parser = Parser::new();
+    if parser.set_language(&language.grammar()).is_err() {
+        return Vec::new();
+    }
+    parse_symbols(&mut parser, language, path, source)
+}
+
+fn parse_symbols(parser: &mut Parser, language: Language, path: &str, source: &str) -> Vec<Symbol> {
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    let mut extractor = Extractor {
+        language,
+        path,
+        source: source.as_bytes(),
+        symbols: Vec::new(),
+    };
+    extractor.visit(tree.root_node(), None, false);
+    extractor.symbols
+}
+
+/// Walks a syntax tree collecting definitions
+struct Extractor<'a> {
+    language: Language,
+    path: &'a str,
+    source: &'a [u8],
+    symbols: Vec<Symbol>,
+}
+
+impl Extractor<'_> {
+    fn visit(&mut self, node: Node, container: Option<&str>, exported: bool) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match self.language {
+                Language::Rust => self.visit_rust(child, container),
+                Language::TypeScript | Language::Tsx => {
+                    self.visit_typescript(child, container, exported)
+                }
+                Language::Python => self.visit_python(child, container),
+                Language::Go => self.visit_go(child),
+            }
+        }
+    }
+
+    fn visit_rust(&mut self, node: Node, container: Option<&str>) {
+        let kind = match node.kind() {
+            "function_item" | "function_signature_item" if container.is_some() => {
+                SymbolKind::Method
+            }
+            "function_item" | "function_signature_item" => SymbolKind::Function,
+            "struct_item" | "union_item" => SymbolKind::Struct,
+            "enum_item" => SymbolKind::Enum,
+            "trait_item" => SymbolKind::Trait,
+            "type_item" => SymbolKind::TypeAlias,
+            "const_item" | "static_item" => SymbolKind::Constant,
+            "macro_definition" => SymbolKind::Macro,
+            "mod_item" => SymbolKind::Module,
+            "impl_item" => {
+                // Methods are attributed to the implementing type
+                if let (Some(ty), Some(body)) = (
+                    node.child_by_field_name("type"),
+                    node.child_by_field_name("body"),
+                ) {
+                    let name = self.type_name(ty);
+                    self.visit(body, Some(&name), false);
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        // Trait items take the trait's visibility; treat them as public
+        let public = has_child(node, "visibility_modifier") || self.in_trait(node);
+        let name = self.add(node, kind, container, public);
+
+        match kind {
+            SymbolKind::Trait => {
+                if let (Some(name), Some(body)) = (name, node.child_by_field_name("body")) {
+                    self.visit(body, Some(&name), false);
+                }
+            }
+            SymbolKind::Module => {
+                if let Some(body) = node.child_by_field_name("body") {
+                    self.visit(body, None, false);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_typescript(&mut self, node: Node, container: Option<&str>, exported: bool) {
+        let kind = match node.kind() {
+            "export_statement" => {
+                self.visit(node, container, true);
+                return;
+            }
+            "function_declaration" | "generator_function_declaration" | "function_signature" => {
+                SymbolKind::Function
+            }
+            "class_declaration" | "abstract_class_declaration" => SymbolKind::Class,
+            "interface_declaration" => SymbolKind::Interface,
+            "type_alias_declaration" => SymbolKind::TypeAlias,
+            "enum_declaration" => SymbolKind::Enum,
+            "method_definition" | "method_signature" | "abstract_method_signature" => {
+                SymbolKind::Method
+            }
+            "internal_module" | "module" => SymbolKind::Module,
+            "lexical_declaration" => {
+                // `const handler = (..) => ..` and `const f = function ..`
+                let mut cursor = node.walk();
+                for declarator in node.named_children(&mut cursor) {
+                    let is_function = declarator.child_by_field_name("value").is_some_and(|v| {
+                        matches!(
+                            v.kind(),
+                            "arrow_function" | "function_expression" | "function"
+                        )
+                    });
+                    if declarator.kind() == "variable_declarator" && is_function {
+                        self.add_named(node, declarator, SymbolKind::Function, container, exported);
+                    }
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        let public = match kind {
+            // Class members are public unless marked private or `#`-prefixed
+            SymbolKind::Method => {
+                !has_child(node, "accessibility_modifier")
+                    || self.child_text(node, "accessibility_modifier") == Some("public")
+            }
+            _ => exported,
+        };
+        let name = self.add(node, kind, container, public);
+
+        if matches!(kind, SymbolKind::Class | SymbolKind::Interface)
+            && let (Some(name), Some(body)) = (name, node.child_by_field_name("body"))
+        {
+            self.visit(body, Some(&name), false);
+        }
+        if kind == SymbolKind::Module
+            && let Some(body) = node.child_by_field_name("body")
+        {
+            self.visit(body, None, false);
+        }
+    }
+
+    fn visit_python(&mut self, node: Node, container: Option<&str>) {
+        let kind = match node.kind() {
+            "decorated_definition" => {
+                if let Some(definition) = node.child_by_field_name("definition") {
+                    self.visit_python(definition, container);
+                }
+                return;
+            }
+            "function_definition" if container.is_some() => SymbolKind::Method,
+            "function_definition" => SymbolKind::Function,
+            "class_definition" => SymbolKind::Class,
+            _ => return,
+        };
+
+        let public = node
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(self.source).ok())
+            .is_some_and(|n| !n.starts_with('_') || (n.starts_with("__") && n.ends_with("__")));
+        let name = self.add(node, kind, container, public);
+
+        if kind == SymbolKind::Class
+            && let (Some(name), Some(body)) = (name, node.child_by_field_name("body"))
+        {
+            self.visit(body, Some(&name), false);
+        }
+    }
+
+    fn visit_go(&mut self, node: Node) {
+        match node.kind() {
+            "function_declaration" => {
+                self.add(node, SymbolKind::Function, None, self.go_exported(node));
+            }
+            "method_declaration" => {
+                let receiver = node
+                    .child_by_field_name("receiver")
+                    .and_then(|r| self.go_receiver_type(r));
+                self.add(
+                    node,
+                    SymbolKind::Method,
+                    receiver.as_deref(),
+                    self.go_exported(node),
+                );
+            }
+            "type_declaration" => {
+                let mut cursor = node.walk();
+                for spec in node.named_children(&mut cursor) {
+                    if !matches!(spec.kind(), "type_spec" | "type_alias") {
+                        continue;
+                    }
+                    let kind = match spec.child_by_field_name("type").map(|t| t.kind()) {
+                        Some("struct_type") => SymbolKind::Struct,
+                        Some("interface_type") => SymbolKind::Interface,
+                        _ => SymbolKind::TypeAlias,
+                    };
+                    self.add_named(spec, spec, kind, None, self.go_exported(spec));
+                }
+            }
+            "const_declaration" => {
+                let mut cursor = node.walk();
+                for spec in node.named_children(&mut cursor) {
+                    if spec.kind() == "const_spec" {
+                        self.add_named(
+                            spec,
+                            spec,
+                            SymbolKind::Constant,
+                            None,
+                            self.go_exported(spec),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record a definition whose name is its `name` field; returns the name
+    fn add(
+        &mut self,
+        node: Node,
+        kind: SymbolKind,
+        container: Option<&str>,
+        public: bool,
+    ) -> Option<String> {
+        self.add_named(node, node, kind, container, public)
+    }
+
+    /// Record a definition spanning `node` whose name is `named`'s `name` field
+    fn add_named(
+        &mut self,
+        node: Node,
+        named: Node,
+        kind: SymbolKind,
+        container: Option<&str>,
+        public: bool,
+    ) -> Option<String> {
+        let name = named
+            .child_by_field_name("name")
+            .and_then(|n| n.utf8_text(self.source).ok())?
+            .to_string();
+        self.symbols.push(Symbol {
+            name: name.clone(),
+            kind,
+            language: self.language,
+            path: self.path.to_string(),
+            line: node.start_position().row + 1,
+            end_line: node.end_position().row + 1,
+            signature: self.signature(node),
+            container: container.map(str::to_string),
+            public,
+        });
+        Some(name)
+    }
+
+    /// The declaration text before the body, whitespace collapsed
+    fn signature(&self, node: Node) -> String {
+        let end = node
+            .child_by_field_name("body")
+            .map(|b| b.start_byte())
+            .unwrap_or(node.end_byte());
+        let text = String::from_utf8_lossy(&self.source[node.start_byte()..end]);
+        let signature = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim_end_matches([':', '=', '{', ' '])
+            .to_string();
+        if signature.chars().count() > MAX_SIGNATURE_CHARS {
+            let truncated: String = signature.chars().take(MAX_SIGNATURE_CHARS).collect();
+            format!("{}…", truncated)
+        } else {
+            signature
+        }
+    }
+
+    /// The type name of an impl target, without generics or paths
+    fn type_name(&self, node: Node) -> String {
+        let node = match node.kind() {
+            "generic_type" => node.child_by_field_name("type").unwrap_or(node),
+            _ => node,
+        };
+        let text = node.utf8_text(self.source).unwrap_or_default();
+        text.rsplit("::").next().unwrap_or(text).to_string()
+    }
+
+    /// Whether a Rust item sits directly inside a trait definition
+    fn in_trait(&self, node: Node) -> bool {
+        node.parent()
+            .and_then(|body| body.parent())
+            .is_some_and(|item| item.kind() == "trait_item")
+    }
+
+    fn child_text(&self, node: Node, kind: &str) -> Option<&str> {
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .find(|c| c.kind() == kind)
+            .and_then(|c| c.utf8_text(self.source).ok())
+    }
+
+    fn go_exported(&self, node: Node) -> bool {
+        node.child_by_field_name("name")
+            .and_then(|n| n.utf8_text(self.source).ok())
+            .and_then(|n| n.chars().next())
+            .is_some_and(char::is_uppercase)
+    }
+
+    /// The receiver type of a Go method, e.g. `Server` for `(s *Server)`
+    fn go_receiver_type(&self, receiver: Node) -> Option<String> {
+        let mut cursor = receiver.walk();
+        let parameter = receiver
+            .named_children(&mut cursor)
+            .find(|c| c.kind() == "parameter_declaration")?;
+        let text = parameter
+            .child_by_field_name("type")?
+            .utf8_text(self.source)
+            .ok()?;
+        let text = text.trim_start_matches('*');
+        Some(text.split('[').next().unwrap_or(text).to_string())
+    }
+}
+
+fn has_child(node: Node, kind: &str) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|c| c.kind() == kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn find<'a>(symbols: &'a [Symbol], name: &str) -> &'a Symbol {
+        symbols
+            .iter()
+            .find(|s| s.name == name)
+            .unwrap_or_else(|| panic!("{} not indexed", name))
+    }
+
+    #[test]
+    fn test_index_rust() {
+        let source = r#"
+pub struct Server<T> {
+    inner: T,
+}
+
+impl<T: Clone> Server<T> {
+    pub async fn start(&self, port: u16) -> Result<()> {
+        Ok(())
+    }
+
+    fn stop(&self) {}
+}
+
+pub trait Handler {
+    fn handle(&self, request: Request) -> Response;
+}
+
+const LIMIT: usize = 10;
+"#;
+        let symbols = index_source(Language::Rust, "src/server.rs", source);
+
+        let server = find(&symbols, "Server");
+        assert_eq!(server.kind, SymbolKind::Struct);
+        assert_eq!(server.signature, "pub struct Server<T>");
+        assert_eq!(server.line, 2);
+        assert!(server.public);
+
+        let start = find(&symbols, "start");
+        assert_eq!(start.kind, SymbolKind::Method);
+        assert_eq!(start.qualified_name(), "Server::start");
+        assert_eq!(
+            start.signature,
+            "pub async fn start(&self, port: u16) -> Result<()>"
+        );
+        assert!(!find(&symbols, "stop").public);
+
+        let handle = find(&symbols, "handle");
+        assert_eq!(handle.container.as_deref(), Some("Handler"));
+        assert!(handle.public);
+        assert_eq!(find(&symbols, "LIMIT").kind, SymbolKind::Constant);
+    }
+
+    #[test]
+    fn test_index_other_languages() {
+        let ts = "export function greet(name: string): string { return name; }\n\
+                  class Widget { private hide() {} render(): void {} }\n\
+                  export const handler = async (req) => req;\n\
+                  export interface Props { id: number }\n";
+        let symbols = index_source(Language::TypeScript, "app.ts", ts);
+        assert!(find(&symbols, "greet").public);
+        assert_eq!(
+            find(&symbols, "greet").signature,
+            "function greet(name: string): string"
+        );
+        assert!(!find(&symbols, "Widget").public);
+        assert!(!find(&symbols, "hide").public);
+        assert_eq!(find(&symbols, "render").qualified_name(), "Widget::render");
+        assert_eq!(find(&symbols, "handler").kind, SymbolKind::Function);
+        assert_eq!(find(&symbols, "Props").kind, SymbolKind::Interface);
+
+        let py = "class Store:\n    def get(self, key):\n        pass\n\n@cache\ndef _load(path):\n    pass\n";
+        let symbols = index_source(Language::Python, "store.py", py);
+        assert_eq!(find(&symbols, "get").qualified_name(), "Store::get");
+        assert_eq!(find(&symbols, "_load").kind, SymbolKind::Function);
+        assert!(!find(&symbols, "_load").public);
+
+        let go = "package main\n\ntype Server struct { port int }\n\nfunc (s *Server) Start() error { return nil }\n\nfunc helper() {}\n";
+        let symbols = index_source(Language::Go, "main.go", go);
+        assert_eq!(find(&symbols, "Server").kind, SymbolKind::Struct);
+        assert_eq!(find(&symbols, "Start").qualified_name(), "Server::Start");
+        assert!(!find(&symbols, "helper").public);
+    }
+
+    #[test]
+    fn test_find_and_refresh() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("lib.rs");
+        std::fs::write(
+            &file,
+            "pub fn load_config() {}\npub fn load() {}\nfn unload() {}\n",
+        )
+        .unwrap();
+
+        let mut index = SymbolIndex::build(temp_dir.path());
+        assert_eq!(index.file_count(), 1);
+        let names: Vec<&str> = index
+            .find("load", None, false)
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["load", "load_config", "unload"]);
+        assert_eq!(index.find("load", None, true).len(), 1);
+        assert!(
+            index
+                .find("load", Some(SymbolKind::Struct), false)
+                .is_empty()
+        );
+        assert!(index.contains_name("unload"));
+
+        std::fs::remove_file(&file).unwrap();
+        std::fs::write(temp_dir.path().join("other.rs"), "pub struct Config;\n").unwrap();
+        index.refresh();
+        assert!(!index.contains_name("unload"));
+        assert_eq!(index.key_symbols(10)[0].name, "Config");
+    }
+}
@@ -0,0 +1,737 @@
+//! Encrypted sync of config, prompts and sessions to a self-hosted backend
+//!
+//! `arula sync` keeps several machines consistent through storage the user
+//! controls (a folder, WebDAV, S3 or a git remote; see
+//! `crate::utils::sync_backends`). Nothing is sent anywhere else. Synced
+//! files are:
+//!
+//! - `config.json` and `prompts/` from `~/.arula`
+//! - the current project's `.arula/conversations/`, when `sync.sessions`
+//!   is on (stored under `sessions/<project directory name>/`)
+//!
+//! Every blob is encrypted with a key derived from `ARULA_SYNC_PASSPHRASE`
+//! before upload, including file names: the backend only sees `salt`,
+//! `manifest` and `objects/<random id>`. The manifest maps each path to its
+//! object, SHA-256 and modification time; deleted files stay in it as
+//! tombstones so deletions propagate.
+//!
+//! `~/.arula/sync-state.json` remembers each file's hash at the last sync.
+//! A file changed on one side only is copied to the other; a file changed
+//! on both is a conflict, resolved by `sync.conflict`:
+//!
+//! - `newest` (default): keep the most recently modified version
+//! - `local` / `remote`: always keep this machine's / the backend's version
+//! - `keep_both`: keep the local file and save the remote one next to it
+//!   as `<file>.conflict-<timestamp>`
+
+use crate::utils::crypto::{Cipher, SALT_LEN, random_bytes, sha256_hex};
+use crate::utils::sync_backends::SyncBackend;
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Passphrase the sync key is derived from
+pub const SYNC_PASSPHRASE_ENV: &str = "ARULA_SYNC_PASSPHRASE";
+/// File name of the local sync state inside `~/.arula`
+pub const SYNC_STATE_FILE: &str = "sync-state.json";
+
+const SALT: &str = "salt";
+const MANIFEST: &str = "manifest";
+const MANIFEST_VERSION: u32 = 1;
+
+/// Where sync stores data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackendKind {
+    Folder,
+    Webdav,
+    S3,
+    Git,
+}
+
+impl SyncBackendKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Folder => "folder",
+            Self::Webdav => "webdav",
+            Self::S3 => "s3",
+            Self::Git => "git",
+        }
+    }
+}
+
+/// How a file changed on both sides is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    #[default]
+    Newest,
+    Local,
+    Remote,
+    KeepBoth,
+}
+
+impl ConflictStrategy {
+    /// Parse a strategy name (`keep-both` is accepted for `keep_both`)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().replace('-', "_").as_str() {
+            "newest" => Some(Self::Newest),
+            "local" => Some(Self::Local),
+            "remote" => Some(Self::Remote),
+            "keep_both" => Some(Self::KeepBoth),
+            _ => None,
+        }
+    }
+}
+
+/// Sync settings (`sync` in config.json)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncConfig {
+    pub backend: SyncBackendKind,
+    /// WebDAV collection URL, git remote, or S3 endpoint (default: AWS)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Directory for the folder backend
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// WebDAV user name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// WebDAV password
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    /// S3 region (default: us-east-1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Key prefix inside the bucket (default: `arula/`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// S3 access key (default: `AWS_ACCESS_KEY_ID`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_key: Option<String>,
+    /// S3 secret key (default: `AWS_SECRET_ACCESS_KEY`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_key: Option<String>,
+    /// Git branch (default: main)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<ConflictStrategy>,
+    /// Sync the current project's sessions (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sessions: Option<bool>,
+}
+
+/// A local file or directory and the remote path it syncs to
+#[derive(Debug, Clone)]
+pub struct SyncItem {
+    /// Remote path; a directory prefix ends with `/`
+    pub remote: String,
+    pub local: PathBuf,
+}
+
+impl SyncItem {
+    pub fn new(remote: &str, local: PathBuf) -> Self {
+        Self {
+            remote: remote.to_string(),
+            local,
+        }
+    }
+}
+
+/// What `arula sync` covers: config and prompts, plus the sessions of
+/// `project_root` if enabled
+pub fn default_items(config: &SyncConfig, project_root: &Path) -> Result<Vec<SyncItem>> {
+    let base = dirs::home_dir()
+        .map(|home| home.join(".arula"))
+        .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+    let mut items = vec![
+        SyncItem::new("config.json", base.join("config.json")),
+        SyncItem::new("prompts/", base.join("prompts")),
+    ];
+    if config.sessions.unwrap_or(true)
+        && let Some(name) = project_root.file_name().and_then(|n| n.to_str())
+    {
+        items.push(SyncItem::new(
+            &format!("sessions/{}/", name),
+            project_root.join(".arula").join("conversations"),
+        ));
+    }
+    Ok(items)
+}
+
+/// A file as recorded in the remote manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RemoteFile {
+    /// Hex SHA-256 of the plaintext (empty for a tombstone)
+    sha256: String,
+    modified: DateTime<Utc>,
+    /// Object holding the encrypted contents (empty for a tombstone)
+    object: String,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    files: BTreeMap<String, RemoteFile>,
+}
+
+/// Hashes of every file at the last sync
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    files: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_sync: Option<DateTime<Utc>>,
+}
+
+struct LocalFile {
+    path: PathBuf,
+    sha256: String,
+    modified: DateTime<Utc>,
+}
+
+/// What happened to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Uploaded the local version
+    Push,
+    /// Downloaded the remote version
+    Pull,
+    /// Deleted on the backend because it was deleted here
+    DeleteRemote,
+    /// Deleted here because it was deleted on another machine
+    DeleteLocal,
+    /// Kept the local version and saved the remote one as a conflict copy
+    KeepBoth,
+}
+
+impl SyncAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Push => "push",
+            Self::Pull => "pull",
+            Self::DeleteRemote => "delete remote",
+            Self::DeleteLocal => "delete local",
+            Self::KeepBoth => "keep both",
+        }
+    }
+}
+
+/// A file sync changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncChange {
+    pub path: String,
+    pub action: SyncAction,
+    /// The file changed on both sides
+    pub conflict: bool,
+}
+
+/// Result of a sync run
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub changes: Vec<SyncChange>,
+    /// Files already identical on both sides
+    pub unchanged: usize,
+}
+
+impl SyncReport {
+    pub fn conflicts(&self) -> usize {
+        self.changes.iter().filter(|c| c.conflict).count()
+    }
+}
+
+/// Syncs a set of local items with a backend
+pub struct Syncer {
+    backend: Box<dyn SyncBackend>,
+    passphrase: String,
+    items: Vec<SyncItem>,
+    state_path: PathBuf,
+    strategy: ConflictStrategy,
+}
+
+impl Syncer {
+    pub fn new(
+        backend: Box<dyn SyncBackend>,
+        passphrase: String,
+        items: Vec<SyncItem>,
+        state_path: PathBuf,
+        strategy: ConflictStrategy,
+    ) -> Self {
+        Self {
+            backend,
+            passphrase,
+            items,
+            state_path,
+            strategy,
+        }
+    }
+
+    /// Syncer for the configured backend, using `ARULA_SYNC_PASSPHRASE`
+    pub fn from_config(config: &SyncConfig, project_root: &Path) -> Result<Self> {
+        let passphrase = std::env::var(SYNC_PASSPHRASE_ENV)
+            .ok()
+            .filter(|p| !p.is_empty())
+            .ok_or_else(|| anyhow!("Set {} to encrypt synced data", SYNC_PASSPHRASE_ENV))?;
+        let state_path = dirs::home_dir()
+            .map(|home| home.join(".arula").join(SYNC_STATE_FILE))
+            .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+        Ok(Self::new(
+            crate::utils::sync_backends::open_backend(config)?,
+            passphrase,
+            default_items(config, project_root)?,
+            state_path,
+            config.conflict.unwrap_or_default(),
+        ))
+    }
+
+    pub fn backend(&self) -> &dyn SyncBackend {
+        self.backend.as_ref()
+    }
+
+    pub fn set_strategy(&mut self, strategy: ConflictStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Sync both ways; with `dry_run`, only report what would change
+    pub async fn sync(&self, dry_run: bool) -> Result<SyncReport> {
+        self.backend.begin().await?;
+
+        let salt = match self.backend.get(SALT).await? {
+            Some(salt) if salt.len() == SALT_LEN => salt,
+            Some(_) => return Err(anyhow!("The backend's sync salt is corrupt")),
+            None => Cipher::random_salt()?.to_vec(),
+        };
+        let cipher = Cipher::derive(&self.passphrase, &salt)?;
+        let mut manifest = match self.backend.get(MANIFEST).await? {
+            Some(sealed) => {
+                let json = cipher.open(&sealed).context(format!(
+                    "Can't read the sync manifest (wrong {}?)",
+                    SYNC_PASSPHRASE_ENV
+                ))?;
+                serde_json::from_slice(&json).context("Corrupt sync manifest")?
+            }
+            None => Manifest {
+                version: MANIFEST_VERSION,
+                ..Default::default()
+            },
+        };
+        let mut state = self.load_state()?;
+        let local = self.scan()?;
+
+        let paths: BTreeSet<&String> = local
+            .keys()
+            .chain(manifest.files.keys())
+            .chain(state.files.keys())
+            .collect();
+        let mut report = SyncReport::default();
+        for path in paths {
+            let local_file = local.get(path);
+            let remote_file = manifest.files.get(path).filter(|f| !f.deleted);
+            let ours = local_file.map(|f| f.sha256.as_str());
+            let theirs = remote_file.map(|f| f.sha256.as_str());
+            let base = state.files.get(path).map(String::as_str);
+
+            if ours == theirs {
+                report.unchanged += usize::from(ours.is_some());
+                continue;
+            }
+            let (action, conflict) = if ours == base {
+                (pull_action(theirs), false)
+            } else if theirs == base {
+                (push_action(ours), false)
+            } else {
+                (self.resolve(local_file, remote_file), true)
+            };
+            report.changes.push(SyncChange {
+                path: path.clone(),
+                action,
+                conflict,
+            });
+        }
+        if dry_run {
+            return Ok(report);
+        }
+
+        let mut stale_objects = Vec::new();
+        for change in &report.changes {
+            let path = &change.path;
+            match change.action {
+                SyncAction::Push => {
+                    let file = &local[path];
+                    stale_objects.extend(self.upload(&cipher, &mut manifest, path, file).await?);
+                    state.files.insert(path.clone(), file.sha256.clone());
+                }
+                SyncAction::Pull => {
+                    let remote = &manifest.files[path];
+                    let data = self.download(&cipher, remote).await?;
+                    write_file(&self.local_path(path)?, &data)?;
+                    state.files.insert(path.clone(), remote.sha256.clone());
+                }
+                SyncAction::KeepBoth => {
+                    let data = self.download(&cipher, &manifest.files[path]).await?;
+                    write_file(&conflict_copy(&self.local_path(path)?), &data)?;
+                    let file = &local[path];
+                    stale_objects.extend(self.upload(&cipher, &mut manifest, path, file).await?);
+                    state.files.insert(path.clone(), file.sha256.clone());
+                }
+                SyncAction::DeleteRemote => {
+                    let previous = manifest.files.insert(
+                        path.clone(),
+                        RemoteFile {
+                            sha256: String::new(),
+                            modified: Utc::now(),
+                            object: String::new(),
+                            deleted: true,
+                        },
+                    );
+                    stale_objects.extend(previous.map(|f| f.object));
+                    state.files.remove(path);
+                }
+                SyncAction::DeleteLocal => {
+                    if let Some(file) = local.get(path) {
+                        std::fs::remove_file(&file.path)
+                            .with_context(|| format!("Failed to delete {}", file.path.display()))?;
+                    }
+                    state.files.remove(path);
+                }
+            }
+        }
+        // Files already identical everywhere count as synced
+        for (path, file) in &local {
+            if manifest
+                .files
+                .get(path)
+                .is_some_and(|f| !f.deleted && f.sha256 == file.sha256)
+            {
+                state.files.insert(path.clone(), file.sha256.clone());
+            }
+        }
+        state
+            .files
+            .retain(|path, _| local.contains_key(path) || manifest.files.contains_key(path));
+
+        self.backend.put(SALT, salt).await?;
+        let json = serde_json::to_vec(&manifest)?;
+        self.backend.put(MANIFEST, cipher.seal(&json)?).await?;
+        for object in stale_objects.into_iter().filter(|o| !o.is_empty()) {
+            self.backend.delete(&object).await?;
+        }
+        self.backend.finish().await?;
+
+        state.last_sync = Some(Utc::now());
+        self.save_state(&state)?;
+        Ok(report)
+    }
+
+    /// Pick the side that wins a conflict
+    fn resolve(&self, local: Option<&LocalFile>, remote: Option<&RemoteFile>) -> SyncAction {
+        let (Some(local), Some(remote)) = (local, remote) else {
+            // Changed on one side, deleted on the other: keep the change
+            return if local.is_some() {
+                SyncAction::Push
+            } else {
+                SyncAction::Pull
+            };
+        };
+        match self.strategy {
+            ConflictStrategy::Local => SyncAction::Push,
+            ConflictStrategy::Remote => SyncAction::Pull,
+            ConflictStrategy::KeepBoth => SyncAction::KeepBoth,
+            ConflictStrategy::Newest if remote.modified > local.modified => SyncAction::Pull,
+            ConflictStrategy::Newest => SyncAction::Push,
+        }
+    }
+
+    /// Upload a local file as a new object, returning the object it replaces
+    async fn upload(
+        &self,
+        cipher: &Cipher,
+        manifest: &mut Manifest,
+        path: &str,
+        file: &LocalFile,
+    ) -> Result<Option<String>> {
+        let data = std::fs::read(&file.path)
+            .with_context(|| format!("Failed to read {}", file.path.display()))?;
+        let object = format!("objects/{}", random_id()?);
+        self.backend.put(&object, cipher.seal(&data)?).await?;
+        let previous = manifest.files.insert(
+            path.to_string(),
+            RemoteFile {
+                sha256: file.sha256.clone(),
+                modified: file.modified,
+                object,
+                deleted: false,
+            },
+        );
+        Ok(previous.map(|f| f.object))
+    }
+
+    async fn download(&self, cipher: &Cipher, remote: &RemoteFile) -> Result<Vec<u8>> {
+        let sealed = self
+            .backend
+            .get(&remote.object)
+            .await?
+            .ok_or_else(|| anyhow!("Object {} is missing from the backend", remote.object))?;
+        let data = cipher.open(&sealed)?;
+        if sha256_hex(&data) != remote.sha256 {
+            return Err(anyhow!("Object {} doesn't match its hash", remote.object));
+        }
+        Ok(data)
+    }
+
+    /// Every local file under the sync items, by remote path
+    fn scan(&self) -> Result<BTreeMap<String, LocalFile>> {
+        let mut files = BTreeMap::new();
+        for item in &self.items {
+            if item.remote.ends_with('/') {
+                if item.local.is_dir() {
+                    scan_dir(&item.local, &item.local, &item.remote, &mut files)?;
+                }
+            } else if item.local.is_file() {
+                files.insert(item.remote.clone(), local_file(&item.local)?);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Local path for a remote path
+    fn local_path(&self, remote: &str) -> Result<PathBuf> {
+        for item in &self.items {
+            if item.remote == remote {
+                return Ok(item.local.clone());
+            }
+            if let Some(rest) = remote.strip_prefix(item.remote.as_str())
+                && item.remote.ends_with('/')
+                && rest
+                    .split('/')
+                    .all(|part| !part.is_empty() && part != "." && part != "..")
+            {
+                return Ok(item.local.join(rest));
+            }
+        }
+        Err(anyhow!(
+            "{} isn't covered by this machine's sync settings",
+            remote
+        ))
+    }
+
+    fn load_state(&self) -> Result<SyncState> {
+        match std::fs::read_to_string(&self.state_path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", self.state_path.display())),
+            Err(_) => Ok(SyncState::default()),
+        }
+    }
+
+    fn save_state(&self, state: &SyncState) -> Result<()> {
+        write_file(
+            &self.state_path,
+            serde_json::to_string_pretty(state)?.as_bytes(),
+        )
+    }
+}
+
+fn pull_action(theirs: Option<&str>) -> SyncAction {
+    if theirs.is_some() {
+        SyncAction::Pull
+    } else {
+        SyncAction::DeleteLocal
+    }
+}
+
+fn push_action(ours: Option<&str>) -> SyncAction {
+    if ours.is_some() {
+        SyncAction::Push
+    } else {
+        SyncAction::DeleteRemote
+    }
+}
+
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    files: &mut BTreeMap<String, LocalFile>,
+) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            scan_dir(root, &path, prefix, files)?;
+        } else if path.is_file() {
+            let relative = path.strip_prefix(root)?;
+            let relative: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            files.insert(
+                format!("{}{}", prefix, relative.join("/")),
+                local_file(&path)?,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn local_file(path: &Path) -> Result<LocalFile> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let modified = std::fs::metadata(path)?.modified()?;
+    Ok(LocalFile {
+        path: path.to_path_buf(),
+        sha256: sha256_hex(&data),
+        modified: modified.into(),
+    })
+}
+
+/// `<file>.conflict-<timestamp>` next to `path`
+fn conflict_copy(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".conflict-{}", Utc::now().format("%Y%m%d%H%M%S")));
+    path.with_file_name(name)
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, data).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn random_id() -> Result<String> {
+    let id: [u8; 16] = random_bytes()?;
+    Ok(id.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sync_backends::FolderBackend;
+    use tempfile::TempDir;
+
+    /// A machine with its own files and state, syncing to `remote`
+    fn machine(dir: &Path, remote: &Path, strategy: ConflictStrategy) -> Syncer {
+        Syncer::new(
+            Box::new(FolderBackend::new(remote.to_path_buf())),
+            "passphrase".to_string(),
+            vec![
+                SyncItem::new("config.json", dir.join("config.json")),
+                SyncItem::new("prompts/", dir.join("prompts")),
+            ],
+            dir.join(SYNC_STATE_FILE),
+            strategy,
+        )
+    }
+
+    fn actions(report: &SyncReport) -> Vec<(&str, SyncAction)> {
+        report
+            .changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.action))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_push_pull_and_delete() -> Result<()> {
+        let (a, b, remote) = (TempDir::new()?, TempDir::new()?, TempDir::new()?);
+        let one = machine(a.path(), remote.path(), ConflictStrategy::Newest);
+        let two = machine(b.path(), remote.path(), ConflictStrategy::Newest);
+
+        write_file(&a.path().join("config.json"), b"{\"secret\": 1}")?;
+        write_file(&a.path().join("prompts/review/system.md"), b"Be terse")?;
+        let report = one.sync(false).await?;
+        assert_eq!(report.changes.len(), 2);
+
+        // Nothing readable reaches the backend
+        for entry in walk(remote.path()) {
+            let data = std::fs::read(&entry)?;
+            assert!(!data.windows(6).any(|w| w == b"secret"));
+            assert!(!entry.to_string_lossy().contains("review"));
+        }
+
+        let report = two.sync(false).await?;
+        assert_eq!(
+            actions(&report),
+            vec![
+                ("config.json", SyncAction::Pull),
+                ("prompts/review/system.md", SyncAction::Pull)
+            ]
+        );
+        assert_eq!(
+            std::fs::read(b.path().join("prompts/review/system.md"))?,
+            b"Be terse"
+        );
+        assert!(two.sync(false).await?.changes.is_empty());
+
+        std::fs::remove_file(b.path().join("prompts/review/system.md"))?;
+        assert_eq!(
+            actions(&two.sync(false).await?),
+            vec![("prompts/review/system.md", SyncAction::DeleteRemote)]
+        );
+        assert_eq!(
+            actions(&one.sync(false).await?),
+            vec![("prompts/review/system.md", SyncAction::DeleteLocal)]
+        );
+        assert!(!a.path().join("prompts/review/system.md").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_conflicts() -> Result<()> {
+        let (a, b, remote) = (TempDir::new()?, TempDir::new()?, TempDir::new()?);
+        let mut one = machine(a.path(), remote.path(), ConflictStrategy::KeepBoth);
+        let two = machine(b.path(), remote.path(), ConflictStrategy::Local);
+
+        write_file(&a.path().join("config.json"), b"v1")?;
+        one.sync(false).await?;
+        two.sync(false).await?;
+
+        write_file(&a.path().join("config.json"), b"from a")?;
+        write_file(&b.path().join("config.json"), b"from b")?;
+        // The first machine to sync just pushes its change
+        let report = two.sync(false).await?;
+        assert_eq!(report.conflicts(), 0);
+        assert_eq!(actions(&report), vec![("config.json", SyncAction::Push)]);
+
+        let preview = one.sync(true).await?;
+        assert_eq!(preview.conflicts(), 1);
+        assert_eq!(
+            actions(&preview),
+            vec![("config.json", SyncAction::KeepBoth)]
+        );
+        one.sync(false).await?;
+        assert_eq!(std::fs::read(a.path().join("config.json"))?, b"from a");
+        let copies: Vec<_> = std::fs::read_dir(a.path())?
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .starts_with("config.json.conflict-")
+            })
+            .collect();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(std::fs::read(copies[0].path())?, b"from b");
+
+        // A wrong passphrase can't read the manifest
+        one.passphrase = "wrong".to_string();
+        assert!(one.sync(true).await.is_err());
+        Ok(())
+    }
+
+    fn walk(dir: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                files.extend(walk(&path));
+            } else {
+                files.push(path);
+            }
+        }
+        files
+    }
+}
@@ -0,0 +1,618 @@
+//! Storage backends for `crate::utils::sync`
+//!
+//! A backend stores opaque, already encrypted blobs by name (`salt`,
+//! `manifest`, `objects/<id>`). Four are available:
+//!
+//! - `folder` — a local directory, e.g. a mounted network share
+//! - `webdav` — any WebDAV server (Nextcloud, ownCloud, Apache mod_dav)
+//! - `s3` — an S3-compatible bucket (AWS, MinIO, Backblaze B2, R2)
+//! - `git` — a git remote, cloned into `~/.arula/sync/git`
+
+use crate::utils::crypto::{hex, sha256_hex};
+use crate::utils::sync::{SyncBackendKind, SyncConfig};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Method, StatusCode};
+use ring::hmac;
+use std::path::{Path, PathBuf};
+use tokio::process::Command as TokioCommand;
+
+/// Blob storage used by sync
+#[async_trait]
+pub trait SyncBackend: Send + Sync {
+    /// Short description for messages, e.g. `webdav https://…`
+    fn describe(&self) -> String;
+
+    /// Prepare before reading (fetch the latest remote state)
+    async fn begin(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Read a blob; `None` if it doesn't exist
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Create or replace a blob
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()>;
+
+    /// Remove a blob; removing a missing blob is not an error
+    async fn delete(&self, name: &str) -> Result<()>;
+
+    /// Publish the changes made since `begin`
+    async fn finish(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Create the backend configured in `sync`
+pub fn open_backend(config: &SyncConfig) -> Result<Box<dyn SyncBackend>> {
+    let url = || {
+        config.url.clone().filter(|u| !u.is_empty()).ok_or_else(|| {
+            anyhow!(
+                "sync.url is required for the {} backend",
+                config.backend.as_str()
+            )
+        })
+    };
+    Ok(match config.backend {
+        SyncBackendKind::Folder => {
+            let path = config
+                .path
+                .clone()
+                .ok_or_else(|| anyhow!("sync.path is required for the folder backend"))?;
+            Box::new(FolderBackend::new(PathBuf::from(path)))
+        }
+        SyncBackendKind::Webdav => Box::new(WebDavBackend::new(
+            &url()?,
+            config.username.clone(),
+            config.password.clone(),
+        )),
+        SyncBackendKind::S3 => Box::new(S3Backend::new(config)?),
+        SyncBackendKind::Git => {
+            let dir = dirs::home_dir()
+                .map(|home| home.join(".arula").join("sync").join("git"))
+                .ok_or_else(|| anyhow!("Could not determine home directory"))?;
+            Box::new(GitBackend::new(
+                url()?,
+                config.branch.clone().unwrap_or_else(|| "main".to_string()),
+                dir,
+            ))
+        }
+    })
+}
+
+/// Blobs as files in a directory
+pub struct FolderBackend {
+    root: PathBuf,
+}
+
+impl FolderBackend {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+}
+
+#[async_trait]
+impl SyncBackend for FolderBackend {
+    fn describe(&self) -> String {
+        format!("folder {}", self.root.display())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(name)).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", name)),
+        }
+    }
+
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(name)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to delete {}", name))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Blobs on a WebDAV server
+pub struct WebDavBackend {
+    base: String,
+    username: Option<String>,
+    password: Option<String>,
+    client: reqwest::Client,
+}
+
+impl WebDavBackend {
+    pub fn new(url: &str, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            base: format!("{}/", url.trim_end_matches('/')),
+            username,
+            password,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn request(&self, method: Method, name: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .request(method, format!("{}{}", self.base, name));
+        match &self.username {
+            Some(user) => request.basic_auth(user, self.password.as_ref()),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl SyncBackend for WebDavBackend {
+    fn describe(&self) -> String {
+        format!("webdav {}", self.base)
+    }
+
+    async fn begin(&self) -> Result<()> {
+        // Collections must exist before files can be put into them
+        for collection in ["", "objects/"] {
+            let mkcol = Method::from_bytes(b"MKCOL").expect("valid method");
+            let response = self.request(mkcol, collection).send().await?;
+            let status = response.status();
+            // 405: the collection already exists
+            if !status.is_success() && status != StatusCode::METHOD_NOT_ALLOWED {
+                bail!(
+                    "WebDAV MKCOL {}{} failed: {}",
+                    self.base,
+                    collection,
+                    status
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.request(Method::GET, name).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("WebDAV GET {} failed", name))?;
+        Ok(Some(response.bytes().await?.to_vec()))
+    }
+
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        self.request(Method::PUT, name)
+            .body(data)
+            .send()
+            .await?
+            .error_for_status()
+            .with_context(|| format!("WebDAV PUT {} failed", name))?;
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        let response = self.request(Method::DELETE, name).send().await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            response
+                .error_for_status()
+                .with_context(|| format!("WebDAV DELETE {} failed", name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Blobs in an S3-compatible bucket, signed with AWS Signature Version 4
+pub struct S3Backend {
+    endpoint: String,
+    /// `scheme://host[:port]` of the endpoint
+    origin: String,
+    /// `host[:port]` as signed
+    host: String,
+    /// Decoded path segments of the endpoint, for S3 behind a base path
+    base_path: Vec<String>,
+    bucket: String,
+    region: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+    client: reqwest::Client,
+}
+
+impl S3Backend {
+    pub fn new(config: &SyncConfig) -> Result<Self> {
+        let bucket = config
+            .bucket
+            .clone()
+            .ok_or_else(|| anyhow!("sync.bucket is required for the s3 backend"))?;
+        let region = config
+            .region
+            .clone()
+            .unwrap_or_else(|| "us-east-1".to_string());
+        let endpoint = config
+            .url
+            .clone()
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", region));
+        let access_key = config
+            .access_key
+            .clone()
+            .or_else(|| std::env::var("AWS_ACCESS_KEY_ID").ok())
+            .ok_or_else(|| anyhow!("sync.access_key or AWS_ACCESS_KEY_ID is required"))?;
+        let secret_key = config
+            .secret_key
+            .clone()
+            .or_else(|| std::env::var("AWS_SECRET_ACCESS_KEY").ok())
+            .ok_or_else(|| anyhow!("sync.secret_key or AWS_SECRET_ACCESS_KEY is required"))?;
+
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let url = reqwest::Url::parse(&endpoint)
+            .with_context(|| format!("Invalid S3 endpoint {}", endpoint))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("Invalid S3 endpoint {}", endpoint))?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        if url.query().is_some() {
+            bail!("The S3 endpoint {} can't have a query string", endpoint);
+        }
+        let base_path = url
+            .path_segments()
+            .into_iter()
+            .flatten()
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| Ok(urlencoding::decode(segment)?.into_owned()))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            origin: format!("{}://{}", url.scheme(), host),
+            host,
+            base_path,
+            endpoint,
+            bucket,
+            region,
+            prefix: config
+                .prefix
+                .clone()
+                .unwrap_or_else(|| "arula/".to_string()),
+            access_key,
+            secret_key,
+            client: reqwest::Client::new(),
+        })
+    }
+
+    /// URI-encoded path-style path of an object, below the endpoint's own
+    /// path. The same string is signed and requested.
+    fn object_path(&self, name: &str) -> String {
+        let key = format!("{}{}", self.prefix, name);
+        let segments = self
+            .base_path
+            .iter()
+            .map(String::as_str)
+            .chain([self.bucket.as_str()])
+            .chain(key.split('/'));
+        segments.map(|segment| format!("/{}", uri_encode(segment))).collect()
+    }
+
+    /// Send a signed path-style request for an object
+    async fn send(&self, method: Method, name: &str, body: Vec<u8>) -> Result<reqwest::Response> {
+        let path = self.object_path(name);
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = sha256_hex(&body);
+        let authorization = sigv4_authorization(
+            &SigV4Request {
+                method: method.as_str(),
+                path: &path,
+                host: &self.host,
+                amz_date: &amz_date,
+                payload_hash: &payload_hash,
+            },
+            &self.region,
+            &self.access_key,
+            &self.secret_key,
+        );
+
+        let mut request = self
+            .client
+            .request(method, format!("{}{}", self.origin, path))
+            .header("x-amz-date", &amz_date)
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("authorization", authorization);
+        if !body.is_empty() {
+            request = request.body(body);
+        }
+        Ok(request.send().await?)
+    }
+}
+
+#[async_trait]
+impl SyncBackend for S3Backend {
+    fn describe(&self) -> String {
+        format!("s3 {}/{}{}", self.endpoint, self.bucket, self.prefix)
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let response = self.send(Method::GET, name, Vec::new()).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("S3 GET {} failed", name))?;
+        Ok(Some(response.bytes().await?.to_vec()))
+    }
+
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        self.send(Method::PUT, name, data)
+            .await?
+            .error_for_status()
+            .with_context(|| format!("S3 PUT {} failed", name))?;
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        let response = self.send(Method::DELETE, name, Vec::new()).await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            response
+                .error_for_status()
+                .with_context(|| format!("S3 DELETE {} failed", name))?;
+        }
+        Ok(())
+    }
+}
+
+/// The parts of a request covered by the signature
+struct SigV4Request<'a> {
+    method: &'a str,
+    /// URI-encoded absolute path
+    path: &'a str,
+    host: &'a str,
+    amz_date: &'a str,
+    payload_hash: &'a str,
+}
+
+/// `Authorization` header value for an S3 request
+fn sigv4_authorization(
+    request: &SigV4Request,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+) -> String {
+    const SIGNED_HEADERS: &str = "host;x-amz-content-sha256;x-amz-date";
+    let date = &request.amz_date[..8];
+    let canonical = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        request.method,
+        request.path,
+        request.host,
+        request.payload_hash,
+        request.amz_date,
+        SIGNED_HEADERS,
+        request.payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        request.amz_date,
+        scope,
+        sha256_hex(canonical.as_bytes())
+    );
+    let key = signing_key(secret_key, date, region, "s3");
+    let signature = hex(hmac::sign(
+        &hmac::Key::new(hmac::HMAC_SHA256, &key),
+        string_to_sign.as_bytes(),
+    )
+    .as_ref());
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key, scope, SIGNED_HEADERS, signature
+    )
+}
+
+/// SigV4 signing key for a date, region and service
+fn signing_key(secret_key: &str, date: &str, region: &str, service: &str) -> Vec<u8> {
+    let mut key = format!("AWS4{}", secret_key).into_bytes();
+    for part in [date, region, service, "aws4_request"] {
+        key = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), part.as_bytes())
+            .as_ref()
+            .to_vec();
+    }
+    key
+}
+
+/// Percent-encode a path segment as SigV4 expects
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Blobs committed to a git repository
+///
+/// The remote is cloned once; each sync resets the clone to the remote
+/// branch, writes blobs into it and pushes a single commit.
+pub struct GitBackend {
+    url: String,
+    branch: String,
+    folder: FolderBackend,
+}
+
+impl GitBackend {
+    pub fn new(url: String, branch: String, dir: PathBuf) -> Self {
+        Self {
+            url,
+            branch,
+            folder: FolderBackend::new(dir),
+        }
+    }
+
+    fn dir(&self) -> &Path {
+        &self.folder.root
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        let output = TokioCommand::new("git")
+            .args(args)
+            .current_dir(self.dir())
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[async_trait]
+impl SyncBackend for GitBackend {
+    fn describe(&self) -> String {
+        format!("git {} ({})", self.url, self.branch)
+    }
+
+    async fn begin(&self) -> Result<()> {
+        if !self.dir().join(".git").exists() {
+            tokio::fs::create_dir_all(self.dir()).await?;
+            self.git(&["init", "--quiet"]).await?;
+            self.git(&["remote", "add", "origin", &self.url]).await?;
+        }
+        self.git(&["fetch", "--quiet", "origin"]).await?;
+        let remote_branch = format!("origin/{}", self.branch);
+        if self
+            .git(&["rev-parse", "--verify", "--quiet", &remote_branch])
+            .await
+            .is_ok()
+        {
+            self.git(&["checkout", "--quiet", "-B", &self.branch, &remote_branch])
+                .await?;
+            self.git(&["reset", "--quiet", "--hard", &remote_branch])
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.folder.get(name).await
+    }
+
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        self.folder.put(name, data).await
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        self.folder.delete(name).await
+    }
+
+    async fn finish(&self) -> Result<()> {
+        self.git(&["add", "--all"]).await?;
+        if self
+            .git(&["status", "--porcelain"])
+            .await?
+            .trim()
+            .is_empty()
+        {
+            return Ok(());
+        }
+        self.git(&[
+            "-c",
+            "user.name=ARULA",
+            "-c",
+            "user.email=arula@localhost",
+            "commit",
+            "--quiet",
+            "-m",
+            "arula sync",
+        ])
+        .await?;
+        let refspec = format!("HEAD:{}", self.branch);
+        self.git(&["push", "--quiet", "origin", &refspec])
+            .await
+            .context("Push rejected; the remote changed during sync, run it again")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sigv4_signing_key() {
+        // Example from the AWS Signature Version 4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex(&key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+        assert_eq!(uri_encode("a b+c~"), "a%20b%2Bc~");
+    }
+
+    #[test]
+    fn test_s3_path_includes_endpoint_path() -> Result<()> {
+        let config = |url: &str| -> SyncConfig {
+            serde_json::from_value(serde_json::json!({
+                "backend": "s3",
+                "url": url,
+                "bucket": "notes",
+                "access_key": "key",
+                "secret_key": "secret",
+            }))
+            .unwrap()
+        };
+
+        let backend = S3Backend::new(&config("https://minio.example.com:9000/s3%20api/"))?;
+        assert_eq!(backend.host, "minio.example.com:9000");
+        assert_eq!(backend.origin, "https://minio.example.com:9000");
+        assert_eq!(
+            backend.object_path("objects/a b"),
+            "/s3%20api/notes/arula/objects/a%20b"
+        );
+
+        let backend = S3Backend::new(&config("https://s3.us-east-1.amazonaws.com"))?;
+        assert_eq!(backend.object_path("manifest"), "/notes/arula/manifest");
+        assert!(S3Backend::new(&config("https://s3.example.com/?x=1")).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_folder_backend() -> Result<()> {
+        let dir = tempfile::TempDir::new()?;
+        let backend = FolderBackend::new(dir.path().to_path_buf());
+        assert_eq!(backend.get("objects/a").await?, None);
+        backend.put("objects/a", b"data".to_vec()).await?;
+        assert_eq!(backend.get("objects/a").await?, Some(b"data".to_vec()));
+        backend.delete("objects/a").await?;
+        backend.delete("objects/a").await?;
+        assert_eq!(backend.get("objects/a").await?, None);
+        Ok(())
+    }
+}
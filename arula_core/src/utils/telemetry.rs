@@ -0,0 +1,161 @@
+//! Request tracing exported over OTLP
+//!
+//! Each request to the model is an `agent_request` span with children for
+//! building the prompt (`prompt_build`), sending it (`provider_call`),
+//! reading the streamed reply (`stream`) and every tool run
+//! (`tool_execution`). Without an exporter the spans cost next to nothing;
+//! with `telemetry.enabled` they are sent to an OTLP/HTTP collector such as
+//! Jaeger or Grafana Tempo. Exporting needs the `otlp` feature.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Where Jaeger and the OpenTelemetry Collector accept OTLP/HTTP traces
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:4318/v1/traces";
+/// `service.name` traces are reported under
+pub const DEFAULT_SERVICE_NAME: &str = "arula";
+
+/// Trace export settings (`telemetry` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Export request traces (default: false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// OTLP/HTTP traces endpoint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    /// Headers sent with every export, e.g. an API key for a hosted backend
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// Share of requests traced, from 0.0 to 1.0 (default: 1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_ratio: Option<f64>,
+}
+
+impl TelemetryConfig {
+    pub fn enabled(&self) -> bool {
+        self.enabled.unwrap_or(false)
+    }
+
+    pub fn endpoint(&self) -> &str {
+        self.endpoint.as_deref().unwrap_or(DEFAULT_ENDPOINT)
+    }
+
+    pub fn service_name(&self) -> &str {
+        self.service_name.as_deref().unwrap_or(DEFAULT_SERVICE_NAME)
+    }
+
+    pub fn sample_ratio(&self) -> f64 {
+        self.sample_ratio.unwrap_or(1.0).clamp(0.0, 1.0)
+    }
+}
+
+/// Keeps the exporter running; dropping it sends the spans still queued
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        #[cfg(feature = "otlp")]
+        if let Err(e) = self.provider.shutdown() {
+            crate::utils::debug::debug_print(&format!("Failed to flush traces: {}", e));
+        }
+    }
+}
+
+/// Start exporting spans if the config asks for it. Keep the returned
+/// guard alive until the program exits.
+pub fn init(config: &TelemetryConfig) -> Result<Option<Telemetry>> {
+    if !config.enabled() {
+        return Ok(None);
+    }
+    start_exporter(config).map(Some)
+}
+
+#[cfg(feature = "otlp")]
+fn start_exporter(config: &TelemetryConfig) -> Result<Telemetry> {
+    use anyhow::Context;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(config.endpoint())
+        .with_headers(config.headers.clone())
+        .build()
+        .with_context(|| {
+            format!(
+                "Failed to set up the OTLP exporter for {}",
+                config.endpoint()
+            )
+        })?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sample_ratio(),
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(config.service_name().to_string())
+                .build(),
+        )
+        .build();
+
+    // Only ARULA's own spans; the HTTP stack underneath would drown them out
+    let targets = Targets::new()
+        .with_target("arula_core", LevelFilter::INFO)
+        .with_target("arula_llm", LevelFilter::INFO)
+        .with_target("arula_cli", LevelFilter::INFO)
+        .with_target("arula_desktop", LevelFilter::INFO);
+    tracing_subscriber::registry()
+        .with(targets)
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("arula")))
+        .try_init()
+        .context("A tracing subscriber is already installed")?;
+
+    Ok(Telemetry { provider })
+}
+
+#[cfg(not(feature = "otlp"))]
+fn start_exporter(_config: &TelemetryConfig) -> Result<Telemetry> {
+    anyhow::bail!(
+        "This build of ARULA can't export traces; rebuild with the `otlp` feature or set telemetry.enabled to false"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults_and_disabled_init() {
+        let config = TelemetryConfig::default();
+        assert!(!config.enabled());
+        assert_eq!(config.endpoint(), DEFAULT_ENDPOINT);
+        assert_eq!(config.service_name(), "arula");
+        assert_eq!(config.sample_ratio(), 1.0);
+        assert!(init(&config).unwrap().is_none());
+
+        let config: TelemetryConfig = serde_json::from_value(serde_json::json!({
+            "enabled": true,
+            "endpoint": "https://otlp.example.com/v1/traces",
+            "headers": {"x-api-key": "secret"},
+            "sample_ratio": 4.0
+        }))
+        .unwrap();
+        assert!(config.enabled());
+        assert_eq!(config.endpoint(), "https://otlp.example.com/v1/traces");
+        assert_eq!(config.headers["x-api-key"], "secret");
+        assert_eq!(config.sample_ratio(), 1.0);
+    }
+}
@@ -0,0 +1,392 @@
+//! Color themes shared by the CLI and the desktop app
+//!
+//! A theme is a TOML file of named colors. Bundled themes are `dark`,
+//! `light`, `black`, `solarized` and `high-contrast`; user themes live in
+//! `~/.arula/themes/<name>.toml` and replace bundled ones of the same name:
+//!
+//! ```toml
+//! name = "Nord"
+//! base = "dark"          # bundled theme that fills in missing colors
+//!
+//! [colors]
+//! background = "#2e3440"
+//! text = "#eceff4"
+//! accent = "#88c0d0"
+//! ```
+//!
+//! The active theme is picked with `appearance.theme` in config.json. The
+//! desktop app can also follow the OS color scheme (`"theme": "system"`),
+//! using `appearance.light_theme` or `appearance.dark_theme`.
+
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Theme used when none is configured
+pub const DEFAULT_THEME: &str = "dark";
+/// `appearance.theme` value that follows the OS light/dark setting
+pub const SYSTEM_THEME: &str = "system";
+/// Directory of user themes inside `~/.arula`
+pub const THEMES_DIR: &str = "themes";
+
+/// Bundled themes, in picker order
+const BUNDLED: &[(&str, &str)] = &[
+    (
+        "dark",
+        r##"
+name = "Dark"
+[colors]
+background = "#0a080e"
+surface = "#120e18"
+surface_raised = "#1a1422"
+border = "#322846"
+text = "#f0ebff"
+muted = "#968cb4"
+accent = "#b432ff"
+accent_soft = "#8c28c8"
+success = "#64ff8c"
+warning = "#ffc864"
+danger = "#ff6464"
+info = "#64b4ff"
+glow = "#c864ff"
+"##,
+    ),
+    (
+        "light",
+        r##"
+name = "Light"
+[colors]
+background = "#f8faff"
+surface = "#f0f4ff"
+surface_raised = "#ffffff"
+border = "#c8d2e6"
+text = "#141e32"
+muted = "#6e788c"
+accent = "#5078dc"
+accent_soft = "#96b4ff"
+success = "#28a050"
+warning = "#c8820a"
+danger = "#dc3c3c"
+info = "#2878c8"
+glow = "#6496ff"
+"##,
+    ),
+    (
+        "black",
+        r##"
+name = "Black"
+base = "dark"
+[colors]
+background = "#000000"
+surface = "#050508"
+surface_raised = "#0a0a0f"
+border = "#191423"
+text = "#faf5ff"
+muted = "#8c82aa"
+accent = "#c850ff"
+accent_soft = "#a03cdc"
+success = "#78ff96"
+danger = "#ff6e6e"
+glow = "#dc78ff"
+"##,
+    ),
+    (
+        "solarized",
+        r##"
+name = "Solarized"
+[colors]
+background = "#002b36"
+surface = "#073642"
+surface_raised = "#0b4150"
+border = "#586e75"
+text = "#93a1a1"
+muted = "#657b83"
+accent = "#268bd2"
+accent_soft = "#2aa198"
+success = "#859900"
+warning = "#b58900"
+danger = "#dc322f"
+info = "#6c71c4"
+glow = "#2aa198"
+"##,
+    ),
+    (
+        "high-contrast",
+        r##"
+name = "High contrast"
+[colors]
+background = "#000000"
+surface = "#000000"
+surface_raised = "#1a1a1a"
+border = "#ffffff"
+text = "#ffffff"
+muted = "#d0d0d0"
+accent = "#ffff00"
+accent_soft = "#00ffff"
+success = "#00ff00"
+warning = "#ffa500"
+danger = "#ff4040"
+info = "#00bfff"
+glow = "#ffff00"
+"##,
+    ),
+];
+
+/// A color as 8-bit red, green and blue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Parse `#rrggbb` or `#rgb` (the `#` is optional)
+    pub fn parse(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+        match hex.len() {
+            6 => Some(Self(
+                channel(&hex[0..2])?,
+                channel(&hex[2..4])?,
+                channel(&hex[4..6])?,
+            )),
+            3 => {
+                let digit = |i: usize| channel(&hex[i..i + 1]).map(|d| d * 17);
+                Some(Self(digit(0)?, digit(1)?, digit(2)?))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Perceived brightness, 0.0–1.0
+    pub fn luminance(self) -> f32 {
+        (0.299 * self.0 as f32 + 0.587 * self.1 as f32 + 0.114 * self.2 as f32) / 255.0
+    }
+}
+
+/// The named colors of a theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThemeColors {
+    pub background: Rgb,
+    pub surface: Rgb,
+    pub surface_raised: Rgb,
+    pub border: Rgb,
+    pub text: Rgb,
+    pub muted: Rgb,
+    pub accent: Rgb,
+    pub accent_soft: Rgb,
+    pub success: Rgb,
+    pub warning: Rgb,
+    pub danger: Rgb,
+    pub info: Rgb,
+    pub glow: Rgb,
+}
+
+impl ThemeColors {
+    /// Color names accepted under `[colors]`
+    pub const NAMES: [&str; 13] = [
+        "background",
+        "surface",
+        "surface_raised",
+        "border",
+        "text",
+        "muted",
+        "accent",
+        "accent_soft",
+        "success",
+        "warning",
+        "danger",
+        "info",
+        "glow",
+    ];
+
+    fn slot(&mut self, name: &str) -> Option<&mut Rgb> {
+        Some(match name {
+            "background" => &mut self.background,
+            "surface" => &mut self.surface,
+            "surface_raised" => &mut self.surface_raised,
+            "border" => &mut self.border,
+            "text" => &mut self.text,
+            "muted" => &mut self.muted,
+            "accent" => &mut self.accent,
+            "accent_soft" => &mut self.accent_soft,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "danger" => &mut self.danger,
+            "info" => &mut self.info,
+            "glow" => &mut self.glow,
+            _ => return None,
+        })
+    }
+}
+
+/// A loaded theme
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Name it is selected by (file name without `.toml`)
+    pub id: String,
+    /// Display name
+    pub name: String,
+    pub colors: ThemeColors,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: Option<String>,
+    base: Option<String>,
+    #[serde(default)]
+    colors: BTreeMap<String, String>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::bundled(DEFAULT_THEME).expect("default theme is bundled")
+    }
+}
+
+impl Theme {
+    /// Parse a theme file; colors it leaves out come from its `base`
+    /// (default: the dark theme)
+    pub fn parse(id: &str, text: &str) -> Result<Self> {
+        let file: ThemeFile = toml::from_str(text).context("Invalid theme file")?;
+        let base = file.base.as_deref().unwrap_or(DEFAULT_THEME);
+        let base = Self::bundled(base).ok_or_else(|| anyhow!("Unknown base theme '{}'", base))?;
+        Self::from_file(id, file, base.colors)
+    }
+
+    fn from_file(id: &str, file: ThemeFile, mut colors: ThemeColors) -> Result<Self> {
+        for (name, value) in &file.colors {
+            let slot = colors
+                .slot(name)
+                .ok_or_else(|| anyhow!("Unknown color '{}'", name))?;
+            *slot = Rgb::parse(value)
+                .ok_or_else(|| anyhow!("Color '{}' is not a hex color: {}", name, value))?;
+        }
+        Ok(Self {
+            id: id.to_string(),
+            name: file.name.unwrap_or_else(|| id.to_string()),
+            colors,
+        })
+    }
+
+    /// A bundled theme by name
+    pub fn bundled(id: &str) -> Option<Self> {
+        let (id, text) = BUNDLED
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(id))?;
+        let file: ThemeFile = toml::from_str(text).expect("bundled themes parse");
+        // The default theme defines every color; the others build on it
+        let base = match file.base.as_deref() {
+            _ if *id == DEFAULT_THEME => ThemeColors::default(),
+            base => Self::bundled(base.unwrap_or(DEFAULT_THEME))?.colors,
+        };
+        Self::from_file(id, file, base).ok()
+    }
+
+    /// A theme by name: a user theme if there is one, else a bundled one
+    pub fn load(id: &str) -> Result<Self> {
+        let id = id.trim().to_lowercase();
+        if let Some(path) = themes_dir().map(|dir| dir.join(format!("{}.toml", id)))
+            && path.is_file()
+        {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            return Self::parse(&id, &text).with_context(|| format!("In {}", path.display()));
+        }
+        Self::bundled(&id).ok_or_else(|| anyhow!("Unknown theme '{}'", id))
+    }
+
+    /// Whether text should be light on this theme's background
+    pub fn is_dark(&self) -> bool {
+        self.colors.background.luminance() < 0.5
+    }
+}
+
+/// `~/.arula/themes`
+pub fn themes_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".arula").join(THEMES_DIR))
+}
+
+/// Names of every theme: bundled ones, then user ones
+pub fn available_themes() -> Vec<String> {
+    let mut names: Vec<String> = BUNDLED.iter().map(|(name, _)| name.to_string()).collect();
+    let mut user: Vec<String> = themes_dir()
+        .and_then(|dir| std::fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "toml" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_lowercase)
+        })
+        .filter(|name| !names.contains(name))
+        .collect();
+    user.sort();
+    names.extend(user);
+    names
+}
+
+static ACTIVE_THEME: RwLock<Option<Theme>> = RwLock::new(None);
+
+/// Set the theme used by [`active_theme`]
+pub fn set_active_theme(theme: Theme) {
+    if let Ok(mut active) = ACTIVE_THEME.write() {
+        *active = Some(theme);
+    }
+}
+
+/// The active theme's colors (the default theme if none was set)
+pub fn active_theme() -> ThemeColors {
+    ACTIVE_THEME
+        .read()
+        .ok()
+        .and_then(|active| active.as_ref().map(|theme| theme.colors))
+        .unwrap_or_else(|| Theme::default().colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_themes_are_complete() {
+        for (id, text) in BUNDLED {
+            let file: ThemeFile = toml::from_str(text).unwrap();
+            let theme = Theme::parse(id, text).unwrap();
+            // Only themes with a base may leave colors out
+            if file.base.is_none() {
+                assert_eq!(file.colors.len(), ThemeColors::NAMES.len(), "{}", id);
+            }
+            assert_ne!(theme.colors.text, theme.colors.background, "{}", id);
+        }
+        assert!(Theme::bundled("light").is_some_and(|t| !t.is_dark()));
+        assert!(Theme::bundled("High-Contrast").is_some_and(|t| t.is_dark()));
+    }
+
+    #[test]
+    fn test_user_theme_fills_in_from_base() {
+        let theme = Theme::parse(
+            "nord",
+            "name = \"Nord\"\nbase = \"light\"\n[colors]\naccent = \"#88C0D0\"\nmuted = \"#abc\"\n",
+        )
+        .unwrap();
+        let light = Theme::bundled("light").unwrap();
+        assert_eq!(theme.name, "Nord");
+        assert_eq!(theme.colors.accent, Rgb(0x88, 0xc0, 0xd0));
+        assert_eq!(theme.colors.muted, Rgb(0xaa, 0xbb, 0xcc));
+        assert_eq!(theme.colors.background, light.colors.background);
+
+        assert!(Theme::parse("x", "[colors]\naccentt = \"#fff\"\n").is_err());
+        assert!(Theme::parse("x", "[colors]\naccent = \"blue\"\n").is_err());
+        assert!(Theme::parse("x", "base = \"nope\"\n").is_err());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Provides human-readable time formatting shared across CLI and Desktop.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc};
 
 /// Convert a timestamp to a human-readable relative time string.
 ///
@@ -27,6 +27,117 @@ pub fn relative_time(timestamp: DateTime<Utc>) -> String {
     }
 }
 
+/// Label for a day separator in a chat.
+///
+/// "Today" and "Yesterday", the weekday within the last week, and the date
+/// (with the year when it isn't the current one) before that.
+pub fn day_label(date: NaiveDate, today: NaiveDate) -> String {
+    match today.signed_duration_since(date).num_days() {
+        0 => "Today".to_string(),
+        1 => "Yesterday".to_string(),
+        2..=6 => date.format("%A").to_string(),
+        _ if date.year() == today.year() => date.format("%A, %B %-d").to_string(),
+        _ => date.format("%B %-d, %Y").to_string(),
+    }
+}
+
+/// Clock time of a message, e.g. "14:32".
+pub fn clock_time(timestamp: DateTime<Local>) -> String {
+    timestamp.format("%H:%M").to_string()
+}
+
+/// Parse a time to jump to in a chat (`/goto`).
+///
+/// Accepts a clock time ("14:30", "2:30pm", "9am"), optionally after
+/// "today", "yesterday" or a date ("2025-10-06 14:30"), a bare day, or a
+/// duration ago ("10m", "2h ago", "3d"). A clock time later than `now`
+/// means that time yesterday.
+pub fn parse_time_target(input: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let input = input.trim().to_lowercase();
+    let input = input.strip_suffix(" ago").unwrap_or(&input).trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    // "10m", "2 hours", "3d"
+    let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 {
+        let amount: i64 = input[..digits].parse().ok()?;
+        let unit = input[digits..].trim();
+        let ago = match unit {
+            "m" | "min" | "mins" | "minute" | "minutes" => Some(Duration::minutes(amount)),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Some(Duration::hours(amount)),
+            "d" | "day" | "days" => Some(Duration::days(amount)),
+            _ => None,
+        };
+        if let Some(ago) = ago {
+            return Some(now - ago);
+        }
+    }
+
+    let (day, time) = match input.split_once(char::is_whitespace) {
+        Some((first, rest)) => match parse_day(first, now.date_naive()) {
+            Some(day) => (Some(day), Some(rest.trim())),
+            None => (None, Some(input)),
+        },
+        None => match parse_day(input, now.date_naive()) {
+            Some(day) => (Some(day), None),
+            None => (None, Some(input)),
+        },
+    };
+    let time = match time {
+        Some(time) => parse_clock_time(time)?,
+        None => NaiveTime::MIN,
+    };
+
+    let date = day.unwrap_or_else(|| now.date_naive());
+    let mut target = Local.from_local_datetime(&date.and_time(time)).earliest()?;
+    if day.is_none() && target > now {
+        target -= Duration::days(1);
+    }
+    Some(target)
+}
+
+fn parse_day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" => Some(today),
+        "yesterday" => today.pred_opt(),
+        _ => NaiveDate::parse_from_str(word, "%Y-%m-%d").ok(),
+    }
+}
+
+/// "14:30", "14", "2:30pm", "2:30 pm", "9am"
+fn parse_clock_time(text: &str) -> Option<NaiveTime> {
+    let text = text.replace(' ', "");
+    let (text, pm) = match (text.strip_suffix("am"), text.strip_suffix("pm")) {
+        (Some(t), _) => (t, Some(false)),
+        (_, Some(t)) => (t, Some(true)),
+        _ => (text.as_str(), None),
+    };
+    let (hour, minute) = match text.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        None => (text.parse::<u32>().ok()?, 0),
+    };
+    let hour = match pm {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Index of the timestamp closest to `target`.
+pub fn closest_to(
+    target: DateTime<Utc>,
+    timestamps: impl IntoIterator<Item = DateTime<Utc>>,
+) -> Option<usize> {
+    timestamps
+        .into_iter()
+        .enumerate()
+        .min_by_key(|(_, t)| (*t - target).num_seconds().abs())
+        .map(|(i, _)| i)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +166,46 @@ mod tests {
         let timestamp = Utc::now() - Duration::days(2);
         assert_eq!(relative_time(timestamp), "2d ago");
     }
+
+    #[test]
+    fn test_day_label() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 16).unwrap();
+        assert_eq!(day_label(today, today), "Today");
+        assert_eq!(day_label(today.pred_opt().unwrap(), today), "Yesterday");
+        assert_eq!(day_label(NaiveDate::from_ymd_opt(2025, 10, 13).unwrap(), today), "Monday");
+        assert_eq!(
+            day_label(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(), today),
+            "Monday, September 1"
+        );
+        assert_eq!(
+            day_label(NaiveDate::from_ymd_opt(2024, 9, 1).unwrap(), today),
+            "September 1, 2024"
+        );
+    }
+
+    #[test]
+    fn test_parse_time_target() {
+        let now = Local.with_ymd_and_hms(2025, 10, 16, 15, 0, 0).unwrap();
+        let at = |y, m, d, h, min| Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap();
+
+        assert_eq!(parse_time_target("14:30", now), Some(at(2025, 10, 16, 14, 30)));
+        assert_eq!(parse_time_target("2:30 PM", now), Some(at(2025, 10, 16, 14, 30)));
+        // Later than now means yesterday
+        assert_eq!(parse_time_target("9pm", now), Some(at(2025, 10, 15, 21, 0)));
+        assert_eq!(parse_time_target("12am", now), Some(at(2025, 10, 16, 0, 0)));
+        assert_eq!(parse_time_target("yesterday 9:05", now), Some(at(2025, 10, 15, 9, 5)));
+        assert_eq!(parse_time_target("2025-10-01", now), Some(at(2025, 10, 1, 0, 0)));
+        assert_eq!(parse_time_target("10m ago", now), Some(at(2025, 10, 16, 14, 50)));
+        assert_eq!(parse_time_target("2 hours", now), Some(at(2025, 10, 16, 13, 0)));
+        assert_eq!(parse_time_target("13pm", now), None);
+        assert_eq!(parse_time_target("soon", now), None);
+    }
+
+    #[test]
+    fn test_closest_to() {
+        let base = Utc::now();
+        let times = [base, base + Duration::minutes(10), base + Duration::minutes(30)];
+        assert_eq!(closest_to(base + Duration::minutes(12), times), Some(1));
+        assert_eq!(closest_to(base, []), None);
+    }
 }
@@ -0,0 +1,396 @@
+//! Update checks and self-update for `arula update`
+//!
+//! The latest GitHub release is compared with the running version. Only
+//! release builds (see [`Changelog::detect_build_type`]) replace themselves;
+//! custom and development builds are told to rebuild from source instead.
+//! A release ships one binary per platform named `arula-<os>-<arch>`
+//! (`.exe` on Windows), e.g. `arula-linux-x86_64`, and a `<asset>.sha256`
+//! file or a `SHA256SUMS` list the download is checked against before the
+//! running binary is swapped out.
+
+use crate::storage::Storage;
+use crate::utils::changelog::{Changelog, ChangelogType};
+use crate::utils::crypto::sha256_hex;
+use crate::utils::version::is_newer;
+use anyhow::{Context, Result, anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Repository releases are published to
+pub const DEFAULT_REPO: &str = "CriticalRange/arula";
+/// Storage namespace for app state kept between runs
+const STATE_NAMESPACE: &str = "state";
+/// Latest release tag seen by the startup check
+const LATEST_RELEASE_KEY: &str = "latest_release";
+/// How long the startup check trusts the last answer
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// How long the startup check waits on GitHub
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Update settings (`updates` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpdatesConfig {
+    /// Look for a new release at startup, at most once a day (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_on_startup: Option<bool>,
+    /// `owner/name` of the repository to take releases from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+}
+
+impl UpdatesConfig {
+    pub fn check_on_startup(&self) -> bool {
+        self.check_on_startup.unwrap_or(true)
+    }
+
+    pub fn repo(&self) -> &str {
+        self.repo.as_deref().unwrap_or(DEFAULT_REPO)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+/// A GitHub release, as returned by the releases API
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl Release {
+    /// Fetch the latest release of `repo`
+    pub async fn latest(repo: &str) -> Result<Self> {
+        Self::fetch_latest(repo, None).await
+    }
+
+    async fn fetch_latest(repo: &str, timeout: Option<Duration>) -> Result<Self> {
+        let url = format!("https://api.github.com/repos/{}/releases/latest", repo);
+        let mut client =
+            reqwest::Client::builder().user_agent(concat!("arula/", env!("CARGO_PKG_VERSION")));
+        if let Some(timeout) = timeout {
+            client = client.timeout(timeout);
+        }
+        client
+            .build()?
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", url))?
+            .error_for_status()
+            .with_context(|| format!("No releases found for {}", repo))?
+            .json()
+            .await
+            .context("Invalid release response")
+    }
+
+    /// Version without the leading `v`
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    /// Whether this release is newer than `current`
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        is_newer(self.version(), current)
+    }
+
+    /// The binary built for this OS and architecture
+    pub fn binary_asset(&self) -> Option<&Asset> {
+        self.asset_for(std::env::consts::OS, std::env::consts::ARCH)
+    }
+
+    fn asset_for(&self, os: &str, arch: &str) -> Option<&Asset> {
+        let oses: &[&str] = match os {
+            "macos" => &["macos", "darwin"],
+            other => &[other],
+        };
+        let arches: &[&str] = match arch {
+            "x86_64" => &["x86_64", "amd64", "x64"],
+            "aarch64" => &["aarch64", "arm64"],
+            other => &[other],
+        };
+        let extension = if os == "windows" { ".exe" } else { "" };
+        let names: Vec<String> = oses
+            .iter()
+            .flat_map(|os| {
+                arches
+                    .iter()
+                    .map(move |arch| format!("arula-{}-{}{}", os, arch, extension))
+            })
+            .collect();
+        // Only the exact name, so archives and checksums next to it never match
+        self.assets
+            .iter()
+            .find(|asset| names.iter().any(|n| asset.name.eq_ignore_ascii_case(n)))
+    }
+
+    /// The published SHA-256 of `asset`, from `<asset>.sha256` or a
+    /// `SHA256SUMS` list
+    async fn checksum(&self, client: &reqwest::Client, asset: &Asset) -> Result<String> {
+        let own = format!("{}.sha256", asset.name);
+        let list = self.assets.iter().find(|a| a.name == own).or_else(|| {
+            self.assets
+                .iter()
+                .find(|a| a.name.to_lowercase().contains("sha256sums"))
+        });
+        let Some(list) = list else {
+            bail!(
+                "Release {} publishes no checksum for {}",
+                self.tag_name,
+                asset.name
+            );
+        };
+        let text = client
+            .get(&list.browser_download_url)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_checksum(&text, &asset.name)
+            .ok_or_else(|| anyhow!("{} has no checksum for {}", list.name, asset.name))
+    }
+}
+
+/// The hash for `name` in a `sha256sum`-style list; a lone hash counts too
+fn parse_checksum(text: &str, name: &str) -> Option<String> {
+    let is_hash = |s: &str| s.len() == 64 && s.chars().all(|c| c.is_ascii_hexdigit());
+    let mut lines = text.lines().map(str::split_whitespace);
+    let found = lines.find_map(|mut parts| {
+        let hash = parts.next()?;
+        match parts.next() {
+            Some(file) if file.trim_start_matches('*') == name => Some(hash),
+            None => Some(hash),
+            _ => None,
+        }
+    })?;
+    is_hash(found).then(|| found.to_lowercase())
+}
+
+/// What `arula update` found
+#[derive(Debug, Clone)]
+pub struct UpdateCheck {
+    pub current: String,
+    pub release: Release,
+    pub build_type: ChangelogType,
+}
+
+impl UpdateCheck {
+    /// Compare the running version with the latest release of `repo`
+    pub async fn run(repo: &str) -> Result<Self> {
+        Ok(Self {
+            current: env!("CARGO_PKG_VERSION").to_string(),
+            release: Release::latest(repo).await?,
+            build_type: Changelog::detect_build_type(),
+        })
+    }
+
+    pub fn update_available(&self) -> bool {
+        self.release.is_newer_than(&self.current)
+    }
+
+    /// Only release builds replace themselves
+    pub fn can_self_update(&self) -> bool {
+        self.build_type == ChangelogType::Release
+    }
+}
+
+/// The newer release version to mention at startup, if any. GitHub is
+/// asked at most once a day; in between the last answer is reused.
+pub async fn startup_notice(config: &UpdatesConfig) -> Option<String> {
+    if !config.check_on_startup() || Changelog::detect_build_type() != ChangelogType::Release {
+        return None;
+    }
+    let cached: Option<String> =
+        Storage::with(|s| s.cache_get(STATE_NAMESPACE, LATEST_RELEASE_KEY))
+            .ok()
+            .flatten();
+    let latest = match cached {
+        Some(tag) => tag,
+        None => {
+            let release = Release::fetch_latest(config.repo(), Some(STARTUP_TIMEOUT))
+                .await
+                .ok()?;
+            let _ = Storage::with(|s| {
+                s.cache_put(
+                    STATE_NAMESPACE,
+                    LATEST_RELEASE_KEY,
+                    &release.tag_name,
+                    Some(CHECK_INTERVAL),
+                )
+            });
+            release.tag_name
+        }
+    };
+    let latest = latest.trim_start_matches('v');
+    is_newer(latest, env!("CARGO_PKG_VERSION")).then(|| latest.to_string())
+}
+
+/// Download this platform's binary from `release`, check it against the
+/// published checksum and put it in place of the running executable.
+/// Returns the path that was replaced.
+pub async fn install(release: &Release) -> Result<PathBuf> {
+    let asset = release.binary_asset().ok_or_else(|| {
+        anyhow!(
+            "Release {} has no binary for {}-{}",
+            release.tag_name,
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("arula/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let expected = release.checksum(&client, asset).await?;
+    let data = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to download {}", asset.name))?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let actual = sha256_hex(&data);
+    if actual != expected {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            asset.name,
+            expected,
+            actual
+        );
+    }
+
+    let current = std::env::current_exe().context("Can't find the running executable")?;
+    let current = current.canonicalize().unwrap_or(current);
+    replace_binary(&current, &data)?;
+    Ok(current)
+}
+
+/// Write `data` next to `target` and rename it over `target`, so the binary
+/// is never left half-written
+fn replace_binary(target: &Path, data: &[u8]) -> Result<()> {
+    let dir = target
+        .parent()
+        .ok_or_else(|| anyhow!("{} has no parent directory", target.display()))?;
+    let mut temp = tempfile::Builder::new()
+        .prefix(".arula-update")
+        .tempfile_in(dir)
+        .with_context(|| format!("Can't write to {}", dir.display()))?;
+    std::io::Write::write_all(&mut temp, data)?;
+    temp.as_file().sync_all()?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(temp.path(), std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    swap_in(temp, target, cfg!(windows))
+}
+
+/// Rename `temp` over `target`. With `move_aside` (Windows, which can't
+/// replace a running executable) the old binary is renamed out of the way
+/// first, and back again if the new one can't take its place.
+fn swap_in(temp: tempfile::NamedTempFile, target: &Path, move_aside: bool) -> Result<()> {
+    let old = target.with_extension("old.exe");
+    if move_aside {
+        let _ = std::fs::remove_file(&old);
+        std::fs::rename(target, &old)
+            .with_context(|| format!("Failed to move {} aside", target.display()))?;
+    }
+    if let Err(e) = temp.persist(target) {
+        if move_aside {
+            std::fs::rename(&old, target).with_context(|| {
+                format!(
+                    "Failed to replace {} ({}) or to put it back from {}",
+                    target.display(),
+                    e.error,
+                    old.display()
+                )
+            })?;
+        }
+        bail!("Failed to replace {}: {}", target.display(), e.error);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_assets_and_swap() {
+        let asset = |name: &str| Asset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{}", name),
+        };
+        let release = Release {
+            tag_name: "v1.10.0".to_string(),
+            html_url: String::new(),
+            assets: vec![
+                asset("arula-linux-x86_64.sha256"),
+                asset("arula-linux-x86_64.tar.gz"),
+                asset("arula-linux-x86_64"),
+                asset("arula-darwin-arm64"),
+                asset("arula-windows-x86_64.zip"),
+                asset("SHA256SUMS"),
+            ],
+        };
+        assert!(release.is_newer_than("1.9.3"));
+        assert!(!release.is_newer_than("1.10.0"));
+        assert!(!release.is_newer_than("1.10"));
+        assert!(release.is_newer_than("1.10.0-rc.2"));
+        assert_eq!(
+            release.asset_for("linux", "x86_64").unwrap().name,
+            "arula-linux-x86_64"
+        );
+        assert_eq!(
+            release.asset_for("macos", "aarch64").unwrap().name,
+            "arula-darwin-arm64"
+        );
+        assert!(release.asset_for("windows", "x86_64").is_none());
+        assert!(release.asset_for("linux", "aarch64").is_none());
+
+        let hash = sha256_hex(b"new binary");
+        let sums = format!(
+            "{}  arula-macos-arm64\n{} *arula-linux-x86_64\n",
+            "0".repeat(64),
+            hash
+        );
+        assert_eq!(
+            parse_checksum(&sums, "arula-linux-x86_64"),
+            Some(hash.clone())
+        );
+        assert_eq!(parse_checksum(&hash, "anything"), Some(hash));
+        assert_eq!(parse_checksum("not a hash", "x"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("arula");
+        std::fs::write(&target, b"old binary").unwrap();
+        replace_binary(&target, b"new binary").unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"new binary");
+    }
+
+    #[test]
+    fn test_failed_swap_restores_old_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("arula.exe");
+        std::fs::write(&target, b"old binary").unwrap();
+
+        let temp = tempfile::NamedTempFile::new_in(dir.path()).unwrap();
+        // The new binary vanishing makes the rename into place fail
+        std::fs::remove_file(temp.path()).unwrap();
+        let err = swap_in(temp, &target, true).unwrap_err();
+        assert!(err.to_string().contains("Failed to replace"));
+        assert_eq!(std::fs::read(&target).unwrap(), b"old binary");
+        assert!(!dir.path().join("arula.old.exe").exists());
+    }
+}
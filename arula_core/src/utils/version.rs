@@ -0,0 +1,90 @@
+//! Version ordering shared by updates, the changelog and packs
+//!
+//! Versions are compared the way semver orders them, without requiring
+//! three parts: `v1.2` equals `1.2.0`, a prerelease such as `1.2.0-beta`
+//! sorts below its release, and build metadata after `+` is ignored.
+
+use std::cmp::Ordering;
+
+/// Compare dotted version numbers numerically (`1.10.0` > `1.9.2`)
+///
+/// Follows semver precedence for prerelease suffixes, so `1.2.0-beta` sorts
+/// below `1.2.0`; build metadata after `+` is ignored and missing release
+/// parts count as zero.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| -> (Vec<String>, Option<Vec<String>>) {
+        let v = v.trim().trim_start_matches('v');
+        let v = v.split_once('+').map_or(v, |(version, _)| version);
+        let (release, pre) = match v.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (v, None),
+        };
+        let parts = |s: &str| s.split('.').map(str::to_string).collect();
+        (parts(release), pre.map(parts))
+    };
+    let ((a_release, a_pre), (b_release, b_pre)) = (split(a), split(b));
+    compare_identifiers(&a_release, &b_release, Some("0")).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => compare_identifiers(&a, &b, None),
+    })
+}
+
+/// Whether `version` is newer than `than`
+pub fn is_newer(version: &str, than: &str) -> bool {
+    compare_versions(version, than) == Ordering::Greater
+}
+
+/// Compare dot-separated identifiers: numbers numerically and below text.
+/// A missing identifier counts as `missing`; without one, a longer list
+/// wins when the other is a prefix of it.
+fn compare_identifiers(a: &[String], b: &[String], missing: Option<&str>) -> Ordering {
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).map(String::as_str).or(missing);
+        let y = b.get(i).map(String::as_str).or(missing);
+        let ordering = match (x, y) {
+            (Some(x), Some(y)) => match (x.parse::<u64>(), y.parse::<u64>()) {
+                (Ok(x), Ok(y)) => x.cmp(&y),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                _ => x.cmp(y),
+            },
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_versions() {
+        assert_eq!(compare_versions("v2.0", "1.99.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v1.2.0", "1.2"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2", "1.2.1"), Ordering::Less);
+        assert!(is_newer("0.2.0", "0.1.9"));
+        assert!(!is_newer("0.2", "v0.2.0"));
+    }
+
+    #[test]
+    fn test_prerelease_versions_sort_below_releases() {
+        assert_eq!(compare_versions("1.2.0-beta", "1.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.0", "1.2.0-rc.1"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2-rc.1", "1.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.0-beta", "1.1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-alpha", "1.0.0-alpha.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-alpha.1", "1.0.0-alpha.beta"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0-rc.2", "1.0.0-rc.10"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,417 @@
+//! Voice input: record from the microphone and transcribe to text
+//!
+//! Recording runs an installed recorder (SoX `rec`, `arecord` or `ffmpeg`,
+//! or `voice.record_command`) into a 16 kHz mono WAV file. The speech is then
+//! transcribed either by OpenAI's transcription endpoint, with the key of the
+//! configured `openai` provider, or locally by Whisper when ARULA is built
+//! with the `whisper` feature and `voice.model` points at a ggml model file.
+//! The text goes into the composer for review; nothing is sent on its own.
+//!
+//! ```json
+//! "voice": {
+//!   "backend": "local",
+//!   "model": "~/.arula/models/ggml-base.en.bin",
+//!   "language": "en",
+//!   "max_seconds": 120
+//! }
+//! ```
+
+use crate::utils::config::Config;
+use anyhow::{Context, Result, anyhow, bail};
+use arula_llm::provider_error::ProviderError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Longest recording when none is configured
+const DEFAULT_MAX_SECONDS: u64 = 120;
+/// How long a stopped recorder gets to finish the file
+const STOP_GRACE: Duration = Duration::from_secs(3);
+const OPENAI_API_URL: &str = "https://api.openai.com/v1";
+const OPENAI_MODEL: &str = "whisper-1";
+
+/// Where speech is transcribed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoiceBackend {
+    /// OpenAI's `/audio/transcriptions` endpoint
+    OpenAi,
+    /// Whisper on this machine (needs the `whisper` feature)
+    Local,
+}
+
+/// Speech-to-text settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VoiceConfig {
+    /// Where to transcribe (default: local when `model` is a file, otherwise
+    /// OpenAI when an OpenAI key is configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<VoiceBackend>,
+    /// Local ggml model file, or the OpenAI model (default: whisper-1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Spoken language as an ISO code, e.g. "en" (default: detected)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Longest recording in seconds (default: 120)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_seconds: Option<u64>,
+    /// Recorder to run instead of the detected one; `{file}` is replaced by
+    /// the WAV file to write and `{seconds}` by `max_seconds`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub record_command: Option<String>,
+}
+
+impl VoiceConfig {
+    pub fn max_seconds(&self) -> u64 {
+        self.max_seconds.unwrap_or(DEFAULT_MAX_SECONDS)
+    }
+
+    /// The local model file, with `~` expanded
+    fn model_path(&self) -> Option<PathBuf> {
+        let model = self.model.as_deref()?;
+        let path = match model.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir()?.join(rest),
+            None => PathBuf::from(model),
+        };
+        path.is_file().then_some(path)
+    }
+}
+
+/// A recorder writing the microphone to a WAV file; dropping it stops the
+/// recorder and deletes the file
+pub struct Recording {
+    child: Child,
+    path: PathBuf,
+    dir: Option<TempDir>,
+    started: Instant,
+}
+
+/// A finished recording; the file is deleted when this is dropped
+pub struct Audio {
+    path: PathBuf,
+    _dir: TempDir,
+}
+
+impl Audio {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Recording {
+    /// Start recording with the configured or detected recorder
+    pub fn start(settings: &VoiceConfig) -> Result<Self> {
+        let dir = TempDir::new().context("Failed to create a folder for the recording")?;
+        let path = dir.path().join("speech.wav");
+        let template = match &settings.record_command {
+            Some(command) => command.clone(),
+            None => detect_recorder().ok_or_else(|| {
+                anyhow!(
+                    "No audio recorder found. Install SoX (rec), arecord or ffmpeg, or set voice.record_command"
+                )
+            })?,
+        };
+        let args = recorder_args(&template, &path, settings.max_seconds());
+        let (program, args) = args
+            .split_first()
+            .ok_or_else(|| anyhow!("voice.record_command is empty"))?;
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to start {}", program))?;
+        Ok(Self {
+            child,
+            path,
+            dir: Some(dir),
+            started: Instant::now(),
+        })
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Whether the recorder stopped on its own, e.g. at `max_seconds`
+    pub fn is_finished(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(Some(_)))
+    }
+
+    /// Stop recording and return the audio
+    pub fn stop(mut self) -> Result<Audio> {
+        self.interrupt();
+        let deadline = Instant::now() + STOP_GRACE;
+        while self.child.try_wait()?.is_none() {
+            if Instant::now() >= deadline {
+                let _ = self.child.kill();
+                let _ = self.child.wait();
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let size = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        // A WAV header alone is 44 bytes
+        if size <= 44 {
+            bail!("Nothing was recorded. Check the microphone and the recorder");
+        }
+        Ok(Audio {
+            path: self.path.clone(),
+            _dir: self.dir.take().expect("the folder is only taken here"),
+        })
+    }
+
+    /// Stop recording and throw the audio away
+    pub fn cancel(self) {
+        drop(self);
+    }
+
+    /// Ask the recorder to stop, so it finishes the file properly
+    #[cfg(unix)]
+    fn interrupt(&mut self) {
+        let _ = Command::new("kill")
+            .args(["-INT", &self.child.id().to_string()])
+            .status();
+    }
+
+    /// Ask the recorder to stop; ffmpeg quits on `q`, others are killed
+    #[cfg(not(unix))]
+    fn interrupt(&mut self) {
+        use std::io::Write;
+        if let Some(stdin) = self.child.stdin.as_mut() {
+            let _ = stdin.write_all(b"q");
+        }
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Transcribe `audio` with the configured backend
+pub async fn transcribe(audio: &Audio, config: &Config) -> Result<String> {
+    let settings = config.get_voice();
+    let text = match backend(&settings, openai_credentials(config).is_some())? {
+        VoiceBackend::OpenAi => {
+            let (url, key) = openai_credentials(config)
+                .ok_or_else(|| anyhow!("OpenAI transcription needs an OpenAI API key"))?;
+            transcribe_openai(audio.path(), &url, &key, &settings).await?
+        }
+        VoiceBackend::Local => {
+            let model = settings.model_path().ok_or_else(|| {
+                anyhow!("Set voice.model to a Whisper ggml model file for local transcription")
+            })?;
+            let path = audio.path().to_path_buf();
+            let language = settings.language.clone();
+            tokio::task::spawn_blocking(move || {
+                transcribe_local(&path, &model, language.as_deref())
+            })
+            .await??
+        }
+    };
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        bail!("No speech was recognized");
+    }
+    Ok(text)
+}
+
+/// The backend to use, from the settings and whether OpenAI is set up
+fn backend(settings: &VoiceConfig, has_openai: bool) -> Result<VoiceBackend> {
+    if let Some(backend) = settings.backend {
+        return Ok(backend);
+    }
+    if settings.model_path().is_some() {
+        Ok(VoiceBackend::Local)
+    } else if has_openai {
+        Ok(VoiceBackend::OpenAi)
+    } else {
+        bail!(
+            "Voice input needs a transcriber: configure an OpenAI API key, or set voice.model to a local Whisper model"
+        )
+    }
+}
+
+/// Endpoint and key of the configured OpenAI provider, or OPENAI_API_KEY
+pub(crate) fn openai_credentials(config: &Config) -> Option<(String, String)> {
+    if let Some(provider) = config.providers.get("openai")
+        && !provider.api_key.is_empty()
+    {
+        let url = provider
+            .api_url
+            .clone()
+            .unwrap_or_else(|| OPENAI_API_URL.to_string());
+        return Some((url, provider.api_key.clone()));
+    }
+    std::env::var("OPENAI_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(|key| (OPENAI_API_URL.to_string(), key))
+}
+
+async fn transcribe_openai(
+    path: &Path,
+    api_url: &str,
+    api_key: &str,
+    settings: &VoiceConfig,
+) -> Result<String> {
+    let audio = tokio::fs::read(path)
+        .await
+        .context("Failed to read the recording")?;
+    let file = reqwest::multipart::Part::bytes(audio)
+        .file_name("speech.wav")
+        .mime_str("audio/wav")?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file)
+        .text(
+            "model",
+            settings
+                .model
+                .clone()
+                .filter(|m| !m.contains(['/', '\\']))
+                .unwrap_or_else(|| OPENAI_MODEL.to_string()),
+        )
+        .text("response_format", "json");
+    if let Some(language) = &settings.language {
+        form = form.text("language", language.clone());
+    }
+
+    let url = format!("{}/audio/transcriptions", api_url.trim_end_matches('/'));
+    let response = arula_llm::http_client::get_ai_client()
+        .post(&url)
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("openai", response)
+            .await
+            .record()
+            .into());
+    }
+    let body: serde_json::Value = response.json().await?;
+    body["text"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("The transcription response had no text"))
+}
+
+#[cfg(feature = "whisper")]
+fn transcribe_local(path: &Path, model: &Path, language: Option<&str>) -> Result<String> {
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    let mut reader = hound::WavReader::open(path).context("Failed to read the recording")?;
+    let spec = reader.spec();
+    if spec.sample_rate != 16_000 || spec.bits_per_sample != 16 {
+        bail!("Local transcription needs 16 kHz, 16-bit audio");
+    }
+    let samples = reader.samples::<i16>().collect::<Result<Vec<_>, _>>()?;
+    let mut audio = vec![0.0f32; samples.len()];
+    whisper_rs::convert_integer_to_float_audio(&samples, &mut audio)?;
+    if spec.channels == 2 {
+        let mut mono = vec![0.0f32; audio.len() / 2];
+        whisper_rs::convert_stereo_to_mono_audio(&audio, &mut mono)?;
+        audio = mono;
+    }
+
+    let context = WhisperContext::new_with_params(model, WhisperContextParameters::default())?;
+    let mut state = context.create_state()?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(language);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    state.full(params, &audio)?;
+
+    let mut text = String::new();
+    for segment in state.as_iter() {
+        text.push_str(&segment.to_str_lossy()?);
+    }
+    Ok(text)
+}
+
+#[cfg(not(feature = "whisper"))]
+fn transcribe_local(_path: &Path, _model: &Path, _language: Option<&str>) -> Result<String> {
+    bail!(
+        "This build of ARULA can't transcribe locally; rebuild with the `whisper` feature or set voice.backend to \"openai\""
+    )
+}
+
+/// The first installed recorder, as a command template
+fn detect_recorder() -> Option<String> {
+    if on_path("rec") {
+        return Some("rec -q -c 1 -r 16000 -b 16 {file} trim 0 {seconds}".to_string());
+    }
+    if on_path("arecord") {
+        return Some("arecord -q -f S16_LE -c 1 -r 16000 -d {seconds} {file}".to_string());
+    }
+    if on_path("ffmpeg") {
+        let input = if cfg!(target_os = "macos") {
+            "-f avfoundation -i :0"
+        } else if cfg!(windows) {
+            "-f dshow -i audio=default"
+        } else {
+            "-f pulse -i default"
+        };
+        return Some(format!(
+            "ffmpeg -loglevel error -y {} -ac 1 -ar 16000 -t {{seconds}} {{file}}",
+            input
+        ));
+    }
+    None
+}
+
+/// Split a command template into arguments and fill in the placeholders.
+/// Placeholders are filled per argument, so paths with spaces stay whole.
+fn recorder_args(template: &str, file: &Path, max_seconds: u64) -> Vec<String> {
+    let file = file.to_string_lossy();
+    let seconds = max_seconds.to_string();
+    template
+        .split_whitespace()
+        .map(|arg| arg.replace("{file}", &file).replace("{seconds}", &seconds))
+        .collect()
+}
+
+/// Whether a program is on PATH
+pub(crate) fn on_path(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path)
+        .any(|dir| dir.join(program).is_file() || dir.join(format!("{}.exe", program)).is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recorder_args_and_backend() {
+        let file = Path::new("/tmp/my recordings/speech.wav");
+        assert_eq!(
+            recorder_args("arecord -d {seconds} {file}", file, 90),
+            ["arecord", "-d", "90", "/tmp/my recordings/speech.wav"]
+        );
+
+        let mut settings = VoiceConfig::default();
+        assert_eq!(settings.max_seconds(), DEFAULT_MAX_SECONDS);
+        assert!(backend(&settings, false).is_err());
+        assert_eq!(backend(&settings, true).unwrap(), VoiceBackend::OpenAi);
+        // A model name that isn't a file doesn't mean local
+        settings.model = Some("whisper-1".to_string());
+        assert_eq!(backend(&settings, true).unwrap(), VoiceBackend::OpenAi);
+        settings.backend = Some(VoiceBackend::Local);
+        assert_eq!(backend(&settings, true).unwrap(), VoiceBackend::Local);
+
+        let parsed: VoiceConfig = serde_json::from_str(r#"{"backend":"openai"}"#).unwrap();
+        assert_eq!(parsed.backend, Some(VoiceBackend::OpenAi));
+    }
+}
@@ -0,0 +1,229 @@
+//! Commit-by-commit branch walkthroughs
+//!
+//! Iterates the commits in a revision range, asks the model to summarize
+//! each commit's diff, and then stitches the summaries into a narrative of
+//! the branch suitable for review prep or onboarding.
+
+use crate::api::completion::complete;
+use crate::utils::config::Config;
+use crate::utils::git_ops::{CommitInfo, GitOps, truncate_diff};
+use anyhow::{Result, bail};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tokio::sync::mpsc;
+
+/// Maximum diff size sent to the model per commit
+const MAX_DIFF_CHARS: usize = 12_000;
+
+/// Maximum number of commits summarized in one walkthrough
+pub const MAX_WALKTHROUGH_COMMITS: usize = 50;
+
+const COMMIT_SYSTEM_PROMPT: &str = "You are a senior engineer explaining commits to a teammate. \
+Be concrete and concise. Describe what changed and why it matters; do not repeat the diff.";
+
+const NARRATIVE_SYSTEM_PROMPT: &str = "You are a senior engineer preparing a colleague to review \
+or onboard onto a branch. Write clear, well-structured prose.";
+
+/// Summary of one commit in a walkthrough
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSummary {
+    /// The commit being summarized
+    pub commit: CommitInfo,
+    /// Model-written explanation of the commit
+    pub summary: String,
+}
+
+/// A complete walkthrough of a revision range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Walkthrough {
+    /// The revision range, e.g. `main..feature`
+    pub range: String,
+    /// Per-commit summaries, oldest first
+    pub commits: Vec<CommitSummary>,
+    /// Narrative tying the commits together
+    pub narrative: String,
+}
+
+impl Walkthrough {
+    /// Render the walkthrough as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Walkthrough of `{}`\n\n", self.range);
+        out.push_str("## Overview\n\n");
+        out.push_str(self.narrative.trim());
+        out.push_str("\n\n## Commits\n");
+        for (i, entry) in self.commits.iter().enumerate() {
+            out.push_str(&format!(
+                "\n### {}. {} {}\n\n",
+                i + 1,
+                entry.commit.short_hash,
+                entry.commit.subject
+            ));
+            out.push_str(entry.summary.trim());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Progress events emitted while a walkthrough is generated
+#[derive(Debug, Clone)]
+pub enum WalkthroughProgress {
+    /// Commits were listed; summarization is starting
+    Started { total: usize, truncated: bool },
+    /// One commit has been summarized
+    CommitSummarized {
+        index: usize,
+        total: usize,
+        summary: CommitSummary,
+    },
+    /// All commits summarized; writing the narrative
+    WritingNarrative,
+    /// The walkthrough is complete
+    Finished(Walkthrough),
+    /// Generation failed
+    Failed(String),
+}
+
+/// Validate a `<base>..<head>` range argument
+pub fn parse_range(range: &str) -> Result<String> {
+    let range = range.trim();
+    let Some((base, head)) = range.split_once("..") else {
+        bail!("Expected a range like <base>..<head>, got '{}'", range);
+    };
+    let head = head.trim_start_matches('.');
+    if base.trim().is_empty() {
+        bail!("Missing base revision in '{}'", range);
+    }
+    let head = if head.trim().is_empty() { "HEAD" } else { head };
+    Ok(format!("{}..{}", base.trim(), head.trim()))
+}
+
+/// Generate a walkthrough, reporting progress through `tx`
+///
+/// The final result (or failure) is always delivered as the last event.
+pub async fn generate_walkthrough(
+    config: Config,
+    dir: &Path,
+    range: &str,
+    tx: mpsc::UnboundedSender<WalkthroughProgress>,
+) {
+    let event = match run_walkthrough(&config, dir, range, &tx).await {
+        Ok(walkthrough) => WalkthroughProgress::Finished(walkthrough),
+        Err(e) => WalkthroughProgress::Failed(e.to_string()),
+    };
+    let _ = tx.send(event);
+}
+
+async fn run_walkthrough(
+    config: &Config,
+    dir: &Path,
+    range: &str,
+    tx: &mpsc::UnboundedSender<WalkthroughProgress>,
+) -> Result<Walkthrough> {
+    let range = parse_range(range)?;
+    let git = GitOps::new(dir);
+    if !git.is_git_repo().await {
+        bail!("Not a git repository: {}", dir.display());
+    }
+
+    let mut commits = git.list_commits(&range).await?;
+    if commits.is_empty() {
+        bail!("No commits in range {}", range);
+    }
+    let truncated = commits.len() > MAX_WALKTHROUGH_COMMITS;
+    commits.truncate(MAX_WALKTHROUGH_COMMITS);
+    let total = commits.len();
+    let _ = tx.send(WalkthroughProgress::Started { total, truncated });
+
+    let mut summaries = Vec::with_capacity(total);
+    for (index, commit) in commits.into_iter().enumerate() {
+        let diff = git.commit_diff(&commit.hash).await?;
+        let prompt = format!(
+            "Commit {} of {} on this branch.\n\nSubject: {}\n\nMessage body:\n{}\n\nDiff:\n```diff\n{}\n```\n\n\
+             Explain this commit in 2-5 sentences: what it changes, why, and anything a reviewer should look at closely.",
+            index + 1,
+            total,
+            commit.subject,
+            if commit.body.is_empty() {
+                "(none)"
+            } else {
+                &commit.body
+            },
+            truncate_diff(&diff, MAX_DIFF_CHARS),
+        );
+        let summary = complete(config, COMMIT_SYSTEM_PROMPT, &prompt).await?;
+        let entry = CommitSummary { commit, summary };
+        let _ = tx.send(WalkthroughProgress::CommitSummarized {
+            index,
+            total,
+            summary: entry.clone(),
+        });
+        summaries.push(entry);
+    }
+
+    let _ = tx.send(WalkthroughProgress::WritingNarrative);
+    let outline = summaries
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            format!(
+                "{}. {} {}\n{}",
+                i + 1,
+                s.commit.short_hash,
+                s.commit.subject,
+                s.summary
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let prompt = format!(
+        "Here are summaries of every commit on the branch `{}`, oldest first:\n\n{}\n\n\
+         Write a narrative of the branch in 2-4 short paragraphs: the overall goal, how the work \
+         progressed commit by commit, and the areas a reviewer or newcomer should focus on.",
+        range, outline
+    );
+    let narrative = complete(config, NARRATIVE_SYSTEM_PROMPT, &prompt).await?;
+
+    Ok(Walkthrough {
+        range,
+        commits: summaries,
+        narrative,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("main..feature").unwrap(), "main..feature");
+        assert_eq!(parse_range(" main.. ").unwrap(), "main..HEAD");
+        assert_eq!(parse_range("main...feature").unwrap(), "main..feature");
+        assert!(parse_range("main").is_err());
+        assert!(parse_range("..feature").is_err());
+    }
+
+    #[test]
+    fn test_walkthrough_markdown() {
+        let walkthrough = Walkthrough {
+            range: "main..feature".to_string(),
+            commits: vec![CommitSummary {
+                commit: CommitInfo {
+                    hash: "abc123".to_string(),
+                    short_hash: "abc".to_string(),
+                    author: "Alice".to_string(),
+                    date: "2024-01-01".to_string(),
+                    subject: "Add parser".to_string(),
+                    body: String::new(),
+                },
+                summary: "Adds a parser.".to_string(),
+            }],
+            narrative: "The branch adds parsing.".to_string(),
+        };
+        let md = walkthrough.to_markdown();
+        assert!(md.contains("# Walkthrough of `main..feature`"));
+        assert!(md.contains("### 1. abc Add parser"));
+        assert!(md.contains("Adds a parser."));
+    }
+}
@@ -3,6 +3,6 @@ mod states;
 
 pub use spring::Spring;
 pub use states::{
-    LiquidMenuState, LivingBackgroundState, SettingsMenuState, SettingsPage, TiltCardState,
-    TransitionDirection,
+    AgentActivity, LiquidMenuState, LivingBackgroundState, SettingsMenuState, SettingsPage, TiltCardState,
+    TransitionDirection, TypingState,
 };
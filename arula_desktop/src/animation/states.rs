@@ -1,7 +1,12 @@
 use super::Spring;
-use crate::constants::{PAGE_TRANSITION_DAMPING, PAGE_TRANSITION_STIFFNESS, TICK_INCREMENT};
+use crate::constants::{
+    BACKGROUND_BLEND_STEP, BACKGROUND_EASE, BACKGROUND_ERROR_HOLD_TICKS, PAGE_TRANSITION_DAMPING,
+    PAGE_TRANSITION_STIFFNESS, PALETTE_BLEND_STEP, TICK_INCREMENT, TYPING_CATCHUP, TYPING_CURSOR_BLINK_TICKS,
+    TYPING_MIN_CHARS,
+};
+use crate::theme::{mix_colors, PaletteColors};
 use iced::widget::canvas;
-use iced::Point;
+use iced::{Color, Point};
 
 /// Page enum for settings submenu navigation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -10,9 +15,11 @@ pub enum SettingsPage {
     Main, // Category selection
     Provider,      // Provider + Model
     Api,           // API Key + URL (legacy - redirects to Provider)
-    Behavior,      // System prompt, temp, tokens, toggles
+    Behavior,      // System prompt, toggles
+    Generation,    // Temperature, top_p, max tokens
     Appearance,    // Living background, etc.
     ModelSelector, // Model list selector
+    Sessions,      // Open sessions switcher
 }
 
 impl SettingsPage {
@@ -23,8 +30,10 @@ impl SettingsPage {
             SettingsPage::Provider => "Provider & Model",
             SettingsPage::Api => "API Configuration",
             SettingsPage::Behavior => "Behavior",
+            SettingsPage::Generation => "Generation",
             SettingsPage::Appearance => "Appearance",
             SettingsPage::ModelSelector => "Select Model",
+            SettingsPage::Sessions => "Sessions",
         }
     }
 
@@ -35,8 +44,10 @@ impl SettingsPage {
             SettingsPage::Provider => "Select AI provider and model",
             SettingsPage::Api => "Configure API credentials",
             SettingsPage::Behavior => "Adjust AI behavior settings",
+            SettingsPage::Generation => "Temperature, top_p and max tokens",
             SettingsPage::Appearance => "Customize visual settings",
             SettingsPage::ModelSelector => "Choose a model",
+            SettingsPage::Sessions => "Switch between open chats",
         }
     }
 }
@@ -126,7 +137,47 @@ impl SettingsMenuState {
     }
 }
 
-/// State for the living background animation.
+/// What the agent is doing, as reflected by the living background.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgentActivity {
+    #[default]
+    Idle,
+    Streaming,
+    ToolRunning,
+    Error,
+}
+
+impl AgentActivity {
+    /// Forward travel per tick through the grid.
+    fn speed(&self) -> f32 {
+        match self {
+            AgentActivity::Idle => 0.8,
+            AgentActivity::Streaming => 2.4,
+            AgentActivity::ToolRunning => 1.6,
+            AgentActivity::Error => 0.3,
+        }
+    }
+
+    /// Multiplier for line brightness and sway.
+    fn intensity(&self) -> f32 {
+        match self {
+            AgentActivity::Idle => 1.0,
+            AgentActivity::Streaming => 1.5,
+            AgentActivity::ToolRunning => 1.3,
+            AgentActivity::Error => 1.4,
+        }
+    }
+
+    /// The palette color the grid is drawn in.
+    pub fn color(&self, palette: &PaletteColors) -> Color {
+        match self {
+            AgentActivity::Idle | AgentActivity::Streaming => palette.accent,
+            AgentActivity::ToolRunning => palette.glow,
+            AgentActivity::Error => palette.danger,
+        }
+    }
+}
+
 /// State for the living background animation.
 #[derive(Debug)]
 pub struct LivingBackgroundState {
@@ -134,6 +185,21 @@ pub struct LivingBackgroundState {
     pub sway_angle: f32,
     pub travel: f32,
     pub cache: canvas::Cache,
+    /// Current agent activity
+    pub activity: AgentActivity,
+    /// Activity being faded out of
+    pub previous: AgentActivity,
+    /// Progress of the color fade from `previous` to `activity` (0.0 to 1.0)
+    pub blend: f32,
+    /// Eased line brightness multiplier
+    pub intensity: f32,
+    speed: f32,
+    /// Ticks left before an error fades back to idle
+    error_hold: u32,
+    /// Palette being faded out of after a theme switch
+    palette_from: Option<PaletteColors>,
+    /// Progress of the palette fade (0.0 to 1.0)
+    palette_blend: f32,
 }
 
 impl Default for LivingBackgroundState {
@@ -143,6 +209,14 @@ impl Default for LivingBackgroundState {
             sway_angle: 0.0,
             travel: 0.0,
             cache: canvas::Cache::default(),
+            activity: AgentActivity::Idle,
+            previous: AgentActivity::Idle,
+            blend: 1.0,
+            intensity: AgentActivity::Idle.intensity(),
+            speed: AgentActivity::Idle.speed(),
+            error_hold: 0,
+            palette_from: None,
+            palette_blend: 1.0,
         }
     }
 }
@@ -152,15 +226,122 @@ impl LivingBackgroundState {
     pub fn update(&mut self) {
         self.tick += TICK_INCREMENT;
 
+        // Errors flash for a while, then settle back to idle
+        if self.activity == AgentActivity::Error {
+            self.error_hold = self.error_hold.saturating_sub(1);
+            if self.error_hold == 0 {
+                self.set_activity(AgentActivity::Idle);
+            }
+        }
+
+        // Ease towards the activity's pace so changes never jump
+        self.blend = (self.blend + BACKGROUND_BLEND_STEP).min(1.0);
+        if self.palette_from.is_some() {
+            self.palette_blend += PALETTE_BLEND_STEP;
+            if self.palette_blend >= 1.0 {
+                self.palette_from = None;
+            }
+        }
+        self.speed += (self.activity.speed() - self.speed) * BACKGROUND_EASE;
+        self.intensity += (self.activity.intensity() - self.intensity) * BACKGROUND_EASE;
+
         // Gentle sway based on sine wave
         // Provides a floating sensation
-        self.sway_angle = (self.tick * 0.5).sin() * 0.05;
+        self.sway_angle = (self.tick * 0.5).sin() * 0.05 * self.intensity;
 
         // Move forward through 3D space
-        self.travel += 0.8; // Faster travel for exploration feel
+        self.travel += self.speed;
 
         self.cache.clear();
     }
+
+    /// Switches to a new activity, fading from the current one.
+    pub fn set_activity(&mut self, activity: AgentActivity) {
+        if activity == AgentActivity::Error {
+            self.error_hold = BACKGROUND_ERROR_HOLD_TICKS;
+        }
+        if activity == self.activity {
+            return;
+        }
+        self.previous = self.activity;
+        self.activity = activity;
+        self.blend = 0.0;
+    }
+
+    /// The grid color for this frame, mid-fade between activities.
+    pub fn color(&self, palette: &PaletteColors) -> Color {
+        mix_colors(
+            self.previous.color(palette),
+            self.activity.color(palette),
+            self.blend,
+        )
+    }
+
+    /// Starts fading the whole UI to a new theme from `shown`, the palette
+    /// on screen until now.
+    pub fn fade_palette(&mut self, shown: PaletteColors) {
+        self.palette_from = Some(shown);
+        self.palette_blend = 0.0;
+    }
+
+    /// The palette for this frame, mid-fade towards `target` after a theme
+    /// switch. Smoothstep eased so the switch starts and ends gently.
+    pub fn palette(&self, target: PaletteColors) -> PaletteColors {
+        match &self.palette_from {
+            Some(from) => {
+                let t = self.palette_blend.clamp(0.0, 1.0);
+                from.mix(&target, t * t * (3.0 - 2.0 * t))
+            }
+            None => target,
+        }
+    }
+}
+
+/// Typing animation for a message that is still streaming.
+///
+/// The revealed part of the message catches up with the streamed text a few
+/// characters per tick. The cursor is solid while text is arriving and blinks
+/// while the model is quiet.
+#[derive(Debug, Default)]
+pub struct TypingState {
+    /// Bytes of the message shown so far
+    revealed: usize,
+    ticks: u32,
+}
+
+impl TypingState {
+    /// Advances the reveal towards `content`. Returns true if the visible
+    /// text or the cursor changed.
+    pub fn update(&mut self, content: &str) -> bool {
+        self.ticks = self.ticks.wrapping_add(1);
+        let backlog = content.len().saturating_sub(self.revealed);
+        if backlog == 0 {
+            return self.ticks.is_multiple_of(TYPING_CURSOR_BLINK_TICKS);
+        }
+        let step = ((backlog as f32 * TYPING_CATCHUP) as usize).max(TYPING_MIN_CHARS);
+        let mut end = (self.revealed + step).min(content.len());
+        while !content.is_char_boundary(end) {
+            end += 1;
+        }
+        self.revealed = end;
+        // Restart the blink so the cursor stays solid while typing
+        self.ticks = 0;
+        true
+    }
+
+    /// The part of `content` revealed so far.
+    pub fn visible<'a>(&self, content: &'a str) -> &'a str {
+        let mut end = self.revealed.min(content.len());
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        &content[..end]
+    }
+
+    /// Whether the cursor is drawn this frame.
+    pub fn cursor_visible(&self) -> bool {
+        (self.ticks / TYPING_CURSOR_BLINK_TICKS).is_multiple_of(2)
+    }
 }
 
 /// State for the liquid menu overlay animation.
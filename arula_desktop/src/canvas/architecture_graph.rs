@@ -0,0 +1,343 @@
+//! Architecture map rendering for ARULA Desktop
+//!
+//! Lays out an `ArchitectureMap` as one band per package (dependents above
+//! their dependencies) and draws it on a canvas. Hovering a node highlights
+//! its incoming and outgoing dependencies.
+
+use crate::theme::PaletteColors;
+use arula_core::utils::architecture::{ArchitectureMap, NodeKind};
+use iced::mouse;
+use iced::widget::canvas::{self, Geometry, Path, Stroke, Text};
+use iced::{Color, Font, Pixels, Point, Rectangle, Size, Theme, Vector};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+const NODE_WIDTH: f32 = 160.0;
+const NODE_HEIGHT: f32 = 44.0;
+const NODE_GAP: f32 = 14.0;
+const BAND_PADDING: f32 = 14.0;
+const BAND_GAP: f32 = 36.0;
+const NODES_PER_ROW: usize = 5;
+
+/// A positioned node of the architecture graph
+#[derive(Debug, Clone)]
+pub struct LayoutNode {
+    pub label: String,
+    pub detail: String,
+    pub is_package: bool,
+    pub bounds: Rectangle,
+}
+
+/// A positioned package band
+#[derive(Debug, Clone)]
+pub struct LayoutBand {
+    pub bounds: Rectangle,
+}
+
+/// Precomputed layout of an architecture map
+#[derive(Debug, Clone, Default)]
+pub struct ArchitectureLayout {
+    pub nodes: Vec<LayoutNode>,
+    pub bands: Vec<LayoutBand>,
+    /// Edges as (from, to) indices into `nodes`
+    pub edges: Vec<(usize, usize)>,
+    pub size: Size,
+}
+
+impl ArchitectureLayout {
+    /// Compute a layered layout for the map
+    pub fn from_map(map: &ArchitectureMap) -> Self {
+        let packages: Vec<&str> = map
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Package)
+            .map(|n| n.id.as_str())
+            .collect();
+        let package_of = |id: &str| -> Option<usize> {
+            let node = map.node(id)?;
+            let package = node.parent.as_deref().unwrap_or(&node.id);
+            packages.iter().position(|p| *p == package)
+        };
+
+        // Depth = longest chain of package dependencies below a package.
+        // Bounded by the package count so cycles cannot loop forever.
+        let package_edges: Vec<(usize, usize)> = map
+            .edges
+            .iter()
+            .filter_map(|e| Some((package_of(&e.from)?, package_of(&e.to)?)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        let mut depth = vec![0usize; packages.len()];
+        for _ in 0..packages.len() {
+            let mut changed = false;
+            for &(from, to) in &package_edges {
+                if depth[from] < depth[to] + 1 && depth[to] < packages.len() {
+                    depth[from] = depth[to] + 1;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let mut order: Vec<usize> = (0..packages.len()).collect();
+        order.sort_by(|a, b| depth[*b].cmp(&depth[*a]).then(a.cmp(b)));
+
+        let band_width = BAND_PADDING * 2.0
+            + NODES_PER_ROW as f32 * NODE_WIDTH
+            + (NODES_PER_ROW - 1) as f32 * NODE_GAP;
+        let mut layout = ArchitectureLayout::default();
+        let mut index_of: HashMap<&str, usize> = HashMap::new();
+        let mut y = 0.0;
+
+        for package_index in order {
+            let package = map
+                .node(packages[package_index])
+                .expect("package node exists");
+            let members: Vec<_> = std::iter::once(package)
+                .chain(map.modules_of(&package.id))
+                .collect();
+            let rows = members.len().div_ceil(NODES_PER_ROW);
+
+            for (i, node) in members.iter().enumerate() {
+                let column = (i % NODES_PER_ROW) as f32;
+                let row = (i / NODES_PER_ROW) as f32;
+                index_of.insert(node.id.as_str(), layout.nodes.len());
+                layout.nodes.push(LayoutNode {
+                    label: node.label.clone(),
+                    detail: if node.symbols > 0 {
+                        format!("{} symbols", node.symbols)
+                    } else {
+                        node.path.display().to_string()
+                    },
+                    is_package: node.kind == NodeKind::Package,
+                    bounds: Rectangle::new(
+                        Point::new(
+                            BAND_PADDING + column * (NODE_WIDTH + NODE_GAP),
+                            y + BAND_PADDING + row * (NODE_HEIGHT + NODE_GAP),
+                        ),
+                        Size::new(NODE_WIDTH, NODE_HEIGHT),
+                    ),
+                });
+            }
+
+            let band_height = BAND_PADDING * 2.0
+                + rows as f32 * NODE_HEIGHT
+                + rows.saturating_sub(1) as f32 * NODE_GAP;
+            layout.bands.push(LayoutBand {
+                bounds: Rectangle::new(Point::new(0.0, y), Size::new(band_width, band_height)),
+            });
+            y += band_height + BAND_GAP;
+        }
+
+        layout.edges = map
+            .edges
+            .iter()
+            .filter_map(|e| {
+                Some((
+                    *index_of.get(e.from.as_str())?,
+                    *index_of.get(e.to.as_str())?,
+                ))
+            })
+            .collect();
+        layout.size = Size::new(band_width, (y - BAND_GAP).max(0.0));
+        layout
+    }
+}
+
+/// Canvas program drawing an architecture layout
+pub struct ArchitectureGraph<'a, Message> {
+    layout: &'a ArchitectureLayout,
+    palette: PaletteColors,
+    _marker: PhantomData<Message>,
+}
+
+impl<'a, Message> ArchitectureGraph<'a, Message> {
+    pub fn new(layout: &'a ArchitectureLayout, palette: PaletteColors) -> Self {
+        Self {
+            layout,
+            palette,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Message> canvas::Program<Message> for ArchitectureGraph<'a, Message> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let pal = self.palette;
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let hovered = cursor
+            .position_in(bounds)
+            .and_then(|p| self.layout.nodes.iter().position(|n| n.bounds.contains(p)));
+
+        for band in &self.layout.bands {
+            frame.fill(
+                &Path::rounded_rectangle(band.bounds.position(), band.bounds.size(), 14.0.into()),
+                Color {
+                    a: 0.35,
+                    ..pal.surface
+                },
+            );
+        }
+
+        // Edges first so nodes are drawn on top of them
+        for &(from, to) in &self.layout.edges {
+            let highlighted = hovered.is_some_and(|h| h == from || h == to);
+            let both_packages =
+                self.layout.nodes[from].is_package && self.layout.nodes[to].is_package;
+            let color = if highlighted {
+                pal.accent
+            } else if both_packages {
+                Color {
+                    a: 0.6,
+                    ..pal.muted
+                }
+            } else {
+                Color {
+                    a: if hovered.is_some() { 0.08 } else { 0.2 },
+                    ..pal.muted
+                }
+            };
+            let width = if highlighted || both_packages {
+                2.0
+            } else {
+                1.0
+            };
+            draw_arrow(
+                &mut frame,
+                self.layout.nodes[from].bounds,
+                self.layout.nodes[to].bounds,
+                color,
+                width,
+            );
+        }
+
+        for (i, node) in self.layout.nodes.iter().enumerate() {
+            let is_hovered = hovered == Some(i);
+            let fill = if node.is_package {
+                Color {
+                    a: 0.35,
+                    ..pal.accent
+                }
+            } else {
+                Color {
+                    a: 0.9,
+                    ..pal.surface_raised
+                }
+            };
+            let rect =
+                Path::rounded_rectangle(node.bounds.position(), node.bounds.size(), 10.0.into());
+            frame.fill(&rect, fill);
+            frame.stroke(
+                &rect,
+                Stroke::default()
+                    .with_color(if is_hovered {
+                        pal.accent
+                    } else {
+                        Color {
+                            a: 0.5,
+                            ..pal.border
+                        }
+                    })
+                    .with_width(if is_hovered { 2.0 } else { 1.0 }),
+            );
+
+            let center_x = node.bounds.center_x();
+            frame.fill_text(Text {
+                content: node.label.clone(),
+                position: Point::new(center_x, node.bounds.y + 8.0),
+                max_width: NODE_WIDTH - 12.0,
+                color: pal.text,
+                size: Pixels(13.0),
+                font: if node.is_package {
+                    Font::MONOSPACE
+                } else {
+                    Font::default()
+                },
+                align_x: iced::widget::text::Alignment::Center,
+                ..Text::default()
+            });
+            frame.fill_text(Text {
+                content: node.detail.clone(),
+                position: Point::new(center_x, node.bounds.y + 26.0),
+                max_width: NODE_WIDTH - 12.0,
+                color: pal.muted,
+                size: Pixels(10.0),
+                align_x: iced::widget::text::Alignment::Center,
+                ..Text::default()
+            });
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        let over_node = cursor
+            .position_in(bounds)
+            .is_some_and(|p| self.layout.nodes.iter().any(|n| n.bounds.contains(p)));
+        if over_node {
+            mouse::Interaction::Pointer
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Draw an arrow between the borders of two node rectangles
+fn draw_arrow(frame: &mut canvas::Frame, from: Rectangle, to: Rectangle, color: Color, width: f32) {
+    let start = border_point(from, to.center());
+    let end = border_point(to, from.center());
+    let direction = end - start;
+    let length = (direction.x * direction.x + direction.y * direction.y).sqrt();
+    if length < 1.0 {
+        return;
+    }
+    let unit = Vector::new(direction.x / length, direction.y / length);
+    let normal = Vector::new(-unit.y, unit.x);
+    let stroke = Stroke::default().with_color(color).with_width(width);
+
+    frame.stroke(&Path::line(start, end), stroke);
+    let head = Path::new(|builder| {
+        builder.move_to(end);
+        builder.line_to(end - unit * 8.0 + normal * 4.0);
+        builder.line_to(end - unit * 8.0 - normal * 4.0);
+        builder.close();
+    });
+    frame.fill(&head, color);
+}
+
+/// Point where the line from the rectangle's center toward `target` leaves it
+fn border_point(rect: Rectangle, target: Point) -> Point {
+    let center = rect.center();
+    let dx = target.x - center.x;
+    let dy = target.y - center.y;
+    if dx == 0.0 && dy == 0.0 {
+        return center;
+    }
+    let scale_x = if dx != 0.0 {
+        (rect.width / 2.0) / dx.abs()
+    } else {
+        f32::INFINITY
+    };
+    let scale_y = if dy != 0.0 {
+        (rect.height / 2.0) / dy.abs()
+    } else {
+        f32::INFINITY
+    };
+    let scale = scale_x.min(scale_y);
+    Point::new(center.x + dx * scale, center.y + dy * scale)
+}
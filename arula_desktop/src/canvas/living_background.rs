@@ -41,6 +41,9 @@ impl<'a, Message> canvas::Program<Message> for LivingBackground<'a, Message> {
             // Use sway angle from state
             let rotation = self.state.sway_angle;
             let travel = self.state.travel;
+            // Grid color and brightness follow what the agent is doing
+            let grid_color = self.state.color(&self.palette);
+            let intensity = self.state.intensity;
 
             // Interpolate background color based on opacity
             // When opacity is 1.0: show full theme background
@@ -105,8 +108,8 @@ impl<'a, Message> canvas::Program<Message> for LivingBackground<'a, Message> {
                         ) {
                             let stroke = canvas::Stroke {
                                 style: canvas::Style::Solid(Color {
-                                    a: 0.15 * self.opacity,
-                                    ..self.palette.accent
+                                    a: (0.15 * intensity).min(1.0) * self.opacity,
+                                    ..grid_color
                                 }),
                                 width: 1.0,
                                 line_cap: canvas::LineCap::Round,
@@ -130,7 +133,7 @@ impl<'a, Message> canvas::Program<Message> for LivingBackground<'a, Message> {
                         } // Too close/behind
 
                         // Calculate fade based on distance (Linear fog)
-                        let alpha = (1.0 - (z / visibility)).max(0.0) * 0.3 * self.opacity; // Max opacity 0.3 * fade state
+                        let alpha = (1.0 - (z / visibility)).max(0.0) * 0.3 * intensity * self.opacity; // Max opacity 0.3 * fade state
                         if alpha <= 0.01 {
                             continue;
                         }
@@ -142,8 +145,8 @@ impl<'a, Message> canvas::Program<Message> for LivingBackground<'a, Message> {
                         {
                             let stroke = canvas::Stroke {
                                 style: canvas::Style::Solid(Color {
-                                    a: alpha,
-                                    ..self.palette.accent
+                                    a: alpha.min(1.0),
+                                    ..grid_color
                                 }),
                                 width: 1.5, // Slightly thicker horizontal lines
                                 line_cap: canvas::LineCap::Round,
@@ -2,9 +2,10 @@
 //!
 //! Provides multiple animated loading indicators using Iced canvas.
 
+use arula_core::utils::style_packs::SpinnerPack;
 use iced::advanced::graphics::gradient;
-use iced::widget::canvas::{self, Cache, Geometry, Path, Stroke};
-use iced::{Color, Point, Rectangle, Theme};
+use iced::widget::canvas::{self, Cache, Geometry, Path, Stroke, Text};
+use iced::{Color, Pixels, Point, Rectangle, Theme};
 use std::f32::consts::PI;
 
 /// Types of loading animations
@@ -19,6 +20,8 @@ pub enum SpinnerType {
     Morph,
     /// Wave pattern
     Wave,
+    /// Text frames from a spinner style pack
+    Frames(SpinnerPack),
 }
 
 /// Animated loading spinner canvas
@@ -309,6 +312,18 @@ impl<Message> canvas::Program<Message> for LoadingSpinner {
                         );
                     }
                 }
+                SpinnerType::Frames(ref pack) => {
+                    // Style pack frames, centered in the accent color
+                    frame.fill_text(Text {
+                        content: pack.frame_at((time * 1000.0) as u128).to_string(),
+                        position: center,
+                        color: self.state.color,
+                        size: Pixels(self.state.size * 1.4),
+                        align_x: iced::widget::text::Alignment::Center,
+                        align_y: iced::alignment::Vertical::Center,
+                        ..Text::default()
+                    });
+                }
             }
         })]
     }
@@ -1,9 +1,13 @@
+mod architecture_graph;
 mod liquid_menu;
 mod living_background;
 mod loading_spinner;
+mod region_picker;
 mod tilt_card;
 
+pub use architecture_graph::{ArchitectureGraph, ArchitectureLayout};
 pub use liquid_menu::LiquidMenuBackground;
 pub use living_background::LivingBackground;
 pub use loading_spinner::{default_spinner_state, LoadingSpinner, SpinnerState, SpinnerType};
+pub use region_picker::{RegionPicker, ScreenSnapshot};
 pub use tilt_card::TiltCardCanvas;
@@ -0,0 +1,216 @@
+use crate::theme::PaletteColors;
+use arula_core::tools::visioneer::CaptureRegion;
+use iced::mouse;
+use iced::widget::canvas::{self, Event, Geometry, Image, Path, Stroke, Text};
+use iced::widget::image::Handle;
+use iced::{keyboard, Color, Pixels, Point, Rectangle, Size, Theme};
+use std::time::Duration;
+
+/// Selections smaller than this many pixels each way are treated as clicks
+const MIN_SELECTION: f32 = 4.0;
+
+/// A still of one monitor that a region is picked on
+#[derive(Debug, Clone)]
+pub struct ScreenSnapshot {
+    pub handle: Handle,
+    /// Size in physical pixels
+    pub width: u32,
+    pub height: u32,
+    /// Monitor position on the desktop
+    pub origin: (i32, i32),
+}
+
+impl ScreenSnapshot {
+    /// Capture the monitor at `position` (the primary one if `None`) once
+    /// `delay` has passed, giving the window time to get out of the way
+    pub async fn take(position: Option<Point>, delay: Duration) -> Result<Self, String> {
+        tokio::time::sleep(delay).await;
+        tokio::task::spawn_blocking(move || {
+            let screen = match position {
+                Some(p) => screenshots::Screen::from_point(p.x as i32, p.y as i32),
+                None => screenshots::Screen::all().and_then(|screens| {
+                    screens
+                        .into_iter()
+                        .find(|s| s.display_info.is_primary)
+                        .ok_or_else(|| anyhow::anyhow!("No primary display"))
+                }),
+            }
+            .map_err(|e| format!("Failed to find the screen: {}", e))?;
+            let image = screen
+                .capture()
+                .map_err(|e| format!("Failed to capture the screen: {}", e))?;
+            let (width, height) = image.dimensions();
+            Ok(Self {
+                handle: Handle::from_rgba(width, height, image.into_raw()),
+                width,
+                height,
+                origin: (screen.display_info.x, screen.display_info.y),
+            })
+        })
+        .await
+        .map_err(|e| format!("Screen capture failed: {}", e))?
+    }
+
+    /// The screen region under `rect`, a rectangle in a canvas of `size`
+    /// showing the snapshot
+    pub fn region(&self, rect: Rectangle, size: Size) -> CaptureRegion {
+        let scale_x = self.width as f32 / size.width.max(1.0);
+        let scale_y = self.height as f32 / size.height.max(1.0);
+        let offset = |origin: i32, value: f32| (origin + value.round() as i32).max(0) as u32;
+        CaptureRegion {
+            x: offset(self.origin.0, rect.x * scale_x),
+            y: offset(self.origin.1, rect.y * scale_y),
+            width: (rect.width * scale_x).round() as u32,
+            height: (rect.height * scale_y).round() as u32,
+        }
+    }
+}
+
+/// Full-window overlay showing a dimmed snapshot; dragging selects a
+/// region, Escape or a right click cancels
+pub struct RegionPicker<'a, Message> {
+    snapshot: &'a ScreenSnapshot,
+    palette: PaletteColors,
+    on_pick: fn(CaptureRegion) -> Message,
+    on_cancel: Message,
+}
+
+impl<'a, Message> RegionPicker<'a, Message> {
+    pub fn new(
+        snapshot: &'a ScreenSnapshot,
+        palette: PaletteColors,
+        on_pick: fn(CaptureRegion) -> Message,
+        on_cancel: Message,
+    ) -> Self {
+        Self {
+            snapshot,
+            palette,
+            on_pick,
+            on_cancel,
+        }
+    }
+}
+
+/// Where a drag started and where the cursor is now
+#[derive(Debug, Default)]
+pub struct DragState {
+    drag: Option<(Point, Point)>,
+}
+
+fn selection(start: Point, end: Point) -> Rectangle {
+    Rectangle {
+        x: start.x.min(end.x),
+        y: start.y.min(end.y),
+        width: (start.x - end.x).abs(),
+        height: (start.y - end.y).abs(),
+    }
+}
+
+impl<'a, Message: Clone> canvas::Program<Message> for RegionPicker<'a, Message> {
+    type State = DragState;
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<canvas::Action<Message>> {
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let position = cursor.position_in(bounds)?;
+                state.drag = Some((position, position));
+                Some(canvas::Action::request_redraw().and_capture())
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                let (start, _) = state.drag?;
+                let end = cursor.position_in(bounds).unwrap_or(start);
+                state.drag = Some((start, end));
+                Some(canvas::Action::request_redraw().and_capture())
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let (start, end) = state.drag.take()?;
+                let rect = selection(start, end);
+                if rect.width < MIN_SELECTION || rect.height < MIN_SELECTION {
+                    return Some(canvas::Action::request_redraw().and_capture());
+                }
+                let region = self.snapshot.region(rect, bounds.size());
+                Some(canvas::Action::publish((self.on_pick)(region)).and_capture())
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right))
+            | Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(keyboard::key::Named::Escape),
+                ..
+            }) => Some(canvas::Action::publish(self.on_cancel.clone()).and_capture()),
+            _ => None,
+        }
+    }
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &iced::Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let pal = self.palette;
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let full = Rectangle::with_size(bounds.size());
+        frame.draw_image(full, Image::new(self.snapshot.handle.clone()));
+
+        // Dim everything but the selection
+        let dim = Color {
+            a: 0.45,
+            ..Color::BLACK
+        };
+        let selected = state.drag.map(|(start, end)| selection(start, end));
+        match selected {
+            Some(rect) => {
+                let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+                for (x, y, width, height) in [
+                    (0.0, 0.0, full.width, rect.y),
+                    (0.0, bottom, full.width, full.height - bottom),
+                    (0.0, rect.y, rect.x, rect.height),
+                    (right, rect.y, full.width - right, rect.height),
+                ] {
+                    frame.fill_rectangle(Point::new(x, y), Size::new(width, height), dim);
+                }
+                frame.stroke(
+                    &Path::rectangle(rect.position(), rect.size()),
+                    Stroke::default().with_color(pal.accent).with_width(2.0),
+                );
+
+                let region = self.snapshot.region(rect, bounds.size());
+                frame.fill_text(Text {
+                    content: format!("{} × {}", region.width, region.height),
+                    position: Point::new(rect.x, (rect.y - 20.0).max(4.0)),
+                    color: Color::WHITE,
+                    size: Pixels(13.0),
+                    ..Text::default()
+                });
+            }
+            None => frame.fill_rectangle(Point::ORIGIN, full.size(), dim),
+        }
+
+        frame.fill_text(Text {
+            content: "Drag to select a region · Esc to cancel".to_string(),
+            position: Point::new(full.center_x(), 24.0),
+            color: Color::WHITE,
+            size: Pixels(16.0),
+            align_x: iced::widget::text::Alignment::Center,
+            ..Text::default()
+        });
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        mouse::Interaction::Crosshair
+    }
+}
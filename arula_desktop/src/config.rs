@@ -1,5 +1,5 @@
-use arula_core::utils::config::{AiConfig, Config, ZaiEndpoint};
-use crate::theme::ThemeMode;
+use arula_core::utils::config::{AiConfig, Config, GenerationSettings, ZaiEndpoint};
+use arula_core::utils::themes::{available_themes, SYSTEM_THEME};
 
 /// Form state for the settings configuration panel.
 #[derive(Debug, Clone)]
@@ -13,8 +13,12 @@ pub struct ConfigForm {
     pub ollama_tools_enabled: bool,
     pub streaming_enabled: bool,
     pub living_background_enabled: bool,
+    /// Show clock times next to messages
+    pub show_timestamps: bool,
     pub system_prompt: String,
     pub temperature: f32,
+    /// Nucleus sampling cutoff; 1.0 leaves it to the provider
+    pub top_p: f32,
     pub max_tokens: usize,
     pub provider_options: Vec<String>,
     pub status: Option<String>,
@@ -22,9 +26,9 @@ pub struct ConfigForm {
     pub endpoint_name: String,
     /// Available z.ai endpoint options
     pub endpoint_options: Vec<String>,
-    /// Selected theme mode (Light, Dark, Black)
-    pub theme_mode: ThemeMode,
-    /// Available theme mode options
+    /// Selected color theme (`appearance.theme`)
+    pub theme: String,
+    /// `system`, then the bundled and user themes
     pub theme_options: Vec<String>,
 }
 
@@ -58,6 +62,14 @@ impl ConfigForm {
             .unwrap_or(false);
         let streaming_enabled = provider_config.and_then(|p| p.streaming).unwrap_or(true); // Default to true
         let living_background_enabled = config.get_living_background_enabled();
+        let show_timestamps = config.get_show_timestamps_enabled();
+        let generation = provider_config
+            .map(|p| GenerationSettings {
+                temperature: p.temperature,
+                top_p: p.top_p,
+                max_tokens: p.max_tokens,
+            })
+            .unwrap_or_default();
 
         // Determine endpoint selection for z.ai provider
         let endpoint_options = ZaiEndpoint::names();
@@ -80,15 +92,23 @@ impl ConfigForm {
             ollama_tools_enabled,
             streaming_enabled,
             living_background_enabled,
+            show_timestamps,
             system_prompt: "You are ARULA, an Autonomous AI Interface assistant. You help users with coding, shell commands, and general software development tasks. Be concise, helpful, and provide practical solutions.".to_string(),
-            temperature: 0.7,
-            max_tokens: 2048,
+            temperature: generation.temperature(),
+            top_p: generation.top_p.unwrap_or(1.0),
+            max_tokens: generation.max_tokens() as usize,
             provider_options,
             status: None,
             endpoint_name,
             endpoint_options,
-            theme_mode: ThemeMode::default(),
-            theme_options: ThemeMode::all().iter().map(|s| s.to_string()).collect(),
+            theme: if config.follows_system_theme() {
+                SYSTEM_THEME.to_string()
+            } else {
+                config.get_theme().id
+            },
+            theme_options: std::iter::once(SYSTEM_THEME.to_string())
+                .chain(available_themes())
+                .collect(),
         }
     }
 
@@ -98,6 +118,17 @@ impl ConfigForm {
         Self::with_provider_options(config, config.active_provider.clone(), provider_options)
     }
 
+    /// Refreshes the config-backed fields after config.json changed on disk,
+    /// keeping the prompt setting that lives only here.
+    pub fn refresh_from_config(&mut self, config: &Config) {
+        let fresh = Self::from_config(config);
+        *self = Self {
+            system_prompt: std::mem::take(&mut self.system_prompt),
+            status: self.status.take(),
+            ..fresh
+        };
+    }
+
     /// Returns true if the API URL field should be editable.
     /// Now returns true for all providers to allow custom endpoint configuration.
     pub fn api_url_editable(&self) -> bool {
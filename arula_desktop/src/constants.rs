@@ -3,6 +3,22 @@ pub const TICK_INTERVAL_MS: u64 = 16;
 pub const TICK_INCREMENT: f32 = 0.01;
 pub const HOVER_TICK_INCREMENT: f32 = 0.1;
 
+// Living background reactions to agent activity
+pub const BACKGROUND_EASE: f32 = 0.04;
+pub const BACKGROUND_BLEND_STEP: f32 = 0.03;
+pub const BACKGROUND_ERROR_HOLD_TICKS: u32 = 180; // ~3s at 60fps
+
+// Theme switches fade the palette over ~0.6s; the OS scheme is checked every 2s
+pub const PALETTE_BLEND_STEP: f32 = 0.028;
+pub const SYSTEM_THEME_POLL_MS: u64 = 2000;
+
+// Streamed text reveal: characters per tick, and the share of the unrevealed
+// text shown per tick so the display never falls far behind the stream
+pub const TYPING_MIN_CHARS: usize = 2;
+pub const TYPING_CATCHUP: f32 = 0.15;
+pub const TYPING_CURSOR_BLINK_TICKS: u32 = 32; // ~0.5s at 60fps
+pub const TYPING_CURSOR: &str = " ▍";
+
 // Spring physics defaults
 pub const SPRING_STIFFNESS: f32 = 0.03;
 pub const SPRING_DAMPING: f32 = 0.80;
@@ -16,10 +32,18 @@ pub const PAGE_SLIDE_DISTANCE: f32 = 60.0;
 // UI Dimensions
 pub const SETTINGS_CARD_WIDTH: f32 = 300.0;
 pub const MESSAGE_MAX_WIDTH: f32 = 700.0;
+pub const ZEN_CONTENT_WIDTH: f32 = 760.0;
+// Zen mode veils messages before the latest exchange with the background color
+pub const ZEN_DIM_ALPHA: f32 = 0.6;
+// Presentation mode shows one message at a time at this size and width
+pub const PRESENTATION_TEXT_SIZE: f32 = 28.0;
+pub const PRESENTATION_CONTENT_WIDTH: f32 = 1100.0;
 pub const MENU_BUTTON_SIZE: f32 = 48.0;
 pub const INPUT_BORDER_RADIUS: f32 = 24.0;
 pub const BUTTON_BORDER_RADIUS: f32 = 6.0;
 pub const CARD_BORDER_RADIUS: f32 = 16.0;
+pub const MAIN_WINDOW_SIZE: (f32, f32) = (1024.0, 768.0);
+pub const QUICK_ASK_SIZE: (f32, f32) = (560.0, 260.0);
 
 // Particles
 pub const PARTICLE_COUNT: usize = 50;
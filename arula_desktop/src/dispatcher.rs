@@ -50,15 +50,36 @@ impl Dispatcher {
             .start_stream(session_id, prompt, history, session_config)
     }
 
+    /// Redirects a running session without stopping it.
+    pub fn steer(&self, session_id: Uuid, message: &str) -> bool {
+        self.manager.steer(session_id, message)
+    }
+
+    /// Writes a new rolling brief for an idle session in the background.
+    pub fn write_brief(
+        &self,
+        session_id: Uuid,
+        previous: Option<String>,
+        history: Vec<ChatMessage>,
+    ) {
+        self.manager.write_brief(session_id, previous, history);
+    }
+
     /// Returns the broadcast receiver for UI events.
     pub fn subscribe(&self) -> broadcast::Receiver<UiEvent> {
         self.manager.subscribe()
     }
 
-    /// Creates an Iced subscription to receive UI events.
+    /// Creates an Iced subscription to app-wide UI events (starters etc.).
     pub fn subscription(&self) -> Subscription<UiEvent> {
-        let rx = self.manager.subscribe();
-        dispatcher_subscription(rx)
+        dispatcher_subscription(None, self.manager.subscribe())
+    }
+
+    /// Creates an Iced subscription to the UI events of one session.
+    ///
+    /// Each open session gets its own; closing the session drops it.
+    pub fn session_subscription(&self, session_id: Uuid) -> Subscription<UiEvent> {
+        dispatcher_subscription(Some(session_id), self.manager.subscribe())
     }
 
     // ==================== Model Fetching Delegations ====================
@@ -111,19 +132,25 @@ impl Dispatcher {
     }
 }
 
-/// Wrapper to make the receiver hashable for run_with
+/// Receiver for the events of one session (or app-wide ones), hashable for
+/// run_with
 #[derive(Clone)]
-struct ReceiverWrapper(Arc<std::sync::Mutex<Option<broadcast::Receiver<UiEvent>>>>);
+struct ReceiverWrapper {
+    session: Option<Uuid>,
+    rx: Arc<std::sync::Mutex<Option<broadcast::Receiver<UiEvent>>>>,
+}
 
 impl std::hash::Hash for ReceiverWrapper {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        // Use a unique id based on the Arc pointer
-        std::ptr::addr_of!(*self.0).hash(state);
+        // Identity is the scope, so the subscription survives re-renders
+        self.session.hash(state);
     }
 }
 
-/// Creates an Iced subscription to receive UI events from the dispatcher.
+/// Creates an Iced subscription to the UI events of `session`, or to events
+/// that belong to no session when it's `None`.
 pub fn dispatcher_subscription(
+    session: Option<Uuid>,
     rx: broadcast::Receiver<UiEvent>,
 ) -> Subscription<UiEvent> {
     use futures::StreamExt;
@@ -131,20 +158,24 @@ pub fn dispatcher_subscription(
     use iced::stream;
     use tokio_stream::wrappers::BroadcastStream;
     
-    let wrapper = ReceiverWrapper(Arc::new(std::sync::Mutex::new(Some(rx))));
-    
+    let wrapper = ReceiverWrapper {
+        session,
+        rx: Arc::new(std::sync::Mutex::new(Some(rx))),
+    };
+
     Subscription::run_with(wrapper, |wrapper: &ReceiverWrapper| {
-        let rx = wrapper.0.lock().unwrap().take();
+        let session = wrapper.session;
+        let rx = wrapper.rx.lock().unwrap().take();
         stream::channel(100, move |mut output: mpsc::Sender<UiEvent>| async move {
             if let Some(rx) = rx {
                 let mut rx_stream = BroadcastStream::new(rx);
                 loop {
                     match rx_stream.next().await {
-                        Some(Ok(event)) => {
+                        Some(Ok(event)) if event.session_id() == session => {
                             use iced::futures::SinkExt;
                             let _ = output.send(event).await;
                         }
-                        Some(Err(_)) => continue,
+                        Some(_) => continue,
                         None => break,
                     }
                 }
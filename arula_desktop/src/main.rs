@@ -1,12 +1,31 @@
 
 use arula_core::utils::config::Config;
+use arula_core::utils::config_validation::{ConfigIssue, Severity};
+use arula_core::utils::config_watcher::{describe_changes, provider_changed, ConfigReload, ConfigWatcher};
 // Test edit - verifying edit tool functionality
 use arula_core::SessionConfig;
 use arula_core::{ConversationManager, ConversationMetadata};
+use arula_core::tools::builtin::bash::BashResult;
 use arula_core::tools::QUESTION_HANDLER;
+use arula_core::tools::visioneer::{CaptureRegion, VisioneerParams, VisioneerTool};
+use arula_core::api::agent::Tool;
+use arula_core::utils::architecture::{build_architecture_map, ArchitectureMap, DiagramFormat};
+use arula_core::utils::code_lint::extract_code_blocks;
+use arula_core::utils::file_lock::SessionInUse;
+use arula_core::utils::file_mentions::{attach_mentions, complete_mention, mention_at, FileIndex};
+use arula_core::utils::manifest_watcher::ManifestWatcher;
+use arula_core::utils::notifications::{NotificationEvent, NotificationManager};
+use arula_core::utils::shutdown;
+use arula_core::storage::Storage;
+use arula_core::utils::themes::Theme as ArulaTheme;
+use arula_core::utils::icons::{set_icon_set, Icon, IconSet};
+use arula_core::utils::time::{clock_time, closest_to, day_label, parse_time_target};
+use arula_core::utils::speech::{self, Playback, Utterance};
+use arula_core::utils::voice::{self, Recording};
 use arula_desktop::animation::Spring;
 use arula_desktop::canvas::{
-    LiquidMenuBackground, LivingBackground, LoadingSpinner, SpinnerState, SpinnerType,
+    ArchitectureGraph, ArchitectureLayout, LiquidMenuBackground, LivingBackground, LoadingSpinner, RegionPicker,
+    ScreenSnapshot, SpinnerState, SpinnerType,
 };
 use arula_desktop::styles::{
     ai_bubble_style, chat_input_style,
@@ -14,27 +33,34 @@ use arula_desktop::styles::{
     transparent_style, user_bubble_style,
 };
 use arula_desktop::{
-    app_theme_with_mode, collect_provider_options, palette_from_mode, ConfigForm, Dispatcher,
-    LiquidMenuState, LivingBackgroundState, MessageEntry, PaletteColors, Session, SettingsMenuState,
-    SettingsPage, TiltCardState, ThemeMode, UiEvent, MESSAGE_MAX_WIDTH, PAGE_SLIDE_DISTANCE,
-    SETTINGS_CARD_WIDTH, TICK_INTERVAL_MS, TILT_CARD_COUNT,
+    app_theme_with_palette, AgentActivity, collect_provider_options, palette_for, system_prefers_dark, ConfigForm, Dispatcher,
+    markdown_view, LiquidMenuState, LivingBackgroundState, MarkdownAction, MessageEntry, PaletteColors, Session, SettingsMenuState,
+    SettingsPage, SystemThemeWatcher, TiltCardState, Tray, TrayEvent, TypingState, UiEvent, MAIN_WINDOW_SIZE,
+    MESSAGE_MAX_WIDTH, PAGE_SLIDE_DISTANCE, QUICK_ASK_SIZE, SETTINGS_CARD_WIDTH, TICK_INTERVAL_MS,
+    TILT_CARD_COUNT, TYPING_CURSOR, ZEN_CONTENT_WIDTH, ZEN_DIM_ALPHA, PRESENTATION_CONTENT_WIDTH,
+    PRESENTATION_TEXT_SIZE,
     // Project context
-    detect_project, generate_auto_manifest, is_ai_enhanced, DetectedProject,
+    detect_project, generate_indexed_manifest, is_ai_enhanced, DetectedProject,
 };
 use iced_fonts::bootstrap;
 
-use chrono::Utc;
+use chrono::{Local, Utc};
 use iced::alignment::{Horizontal, Vertical};
+use iced::keyboard;
 use iced::time::{self, Duration};
 use iced::widget::canvas::Canvas;
 use iced::widget::text_editor;
 use iced::widget::{
     button, checkbox, column, container, markdown, pick_list, row, scrollable, stack, text, text_input, Space,
 };
-use iced::{Background, Border, Color, Element, Font, Length, Point, Subscription, Task};
+use iced::window;
+use iced::{Background, Border, Color, Element, Font, Length, Point, Size, Subscription, Task};
 use rfd::FileDialog;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Instant;
 
 /// Application state.
 struct App {
@@ -64,6 +90,8 @@ struct App {
     spinner_state: SpinnerState,
     /// Cached parsed markdown for AI messages (keyed by session_index:message_index)
     markdown_cache: HashMap<String, Vec<markdown::Item>>,
+    /// Typing animation of AI messages that are still streaming (same keys)
+    typing: HashMap<String, TypingState>,
     /// Track tool display args from ToolCallStart to show in ToolCallResult (keyed by session_id)
     tool_args_cache: HashMap<uuid::Uuid, String>,
     /// Track expand/collapse animation state for tool messages (keyed by "session_index:message_index")
@@ -75,6 +103,8 @@ struct App {
     error_expanded: bool,
     /// Streaming bash output lines per tool call (keyed by tool_call_id)
     bash_output_lines: HashMap<String, Vec<(String, bool)>>, // (line, is_stderr)
+    /// Exit code, run time and output of finished commands (keyed by tool_call_id)
+    command_reports: HashMap<String, BashResult>,
     /// Current working directory for the session
     current_directory: PathBuf,
     /// Whether the directory popup is shown
@@ -99,8 +129,12 @@ struct App {
     clipboard: Option<arboard::Clipboard>,
     /// Draft value for custom model input in model selector
     custom_model_draft: String,
-    /// Current theme mode (Light, Dark, Black)
-    theme_mode: ThemeMode,
+    /// Active color theme (`appearance.theme`)
+    theme: ArulaTheme,
+    /// Whether the OS color scheme is dark, for `"theme": "system"`
+    system_dark: bool,
+    /// Reports OS light/dark changes
+    system_theme: SystemThemeWatcher,
     /// Detected project info for current directory (cached)
     detected_project: Option<DetectedProject>,
     /// Whether the current PROJECT.manifest was AI-enhanced
@@ -113,6 +147,88 @@ struct App {
     input_bar_height_spring: Spring,
     /// Custom answer drafts per question: (batch_idx, question_idx) -> draft text
     question_answer_drafts: std::collections::HashMap<(usize, usize), String>,
+    /// Whether the architecture map overlay is shown
+    show_architecture: bool,
+    /// Last generated architecture map for the current directory
+    architecture_map: Option<ArchitectureMap>,
+    /// Canvas layout computed from `architecture_map`
+    architecture_layout: ArchitectureLayout,
+    /// Whether the architecture map is being (re)generated
+    architecture_loading: bool,
+    /// Status line for the architecture overlay (errors, saved file paths)
+    architecture_status: Option<String>,
+    /// Keeps PROJECT.manifest in sync with build files (`context.manifest_auto_refresh`)
+    manifest_watcher: Option<ManifestWatcher>,
+    /// Message highlighted by `/goto`: (session index, message index, when it started)
+    goto_highlight: Option<(usize, usize, Instant)>,
+    /// Reloads config.json when it's edited while running
+    config_watcher: Option<ConfigWatcher>,
+    /// Notice shown below the chat after a config reload or a context
+    /// compaction, and when it was posted
+    config_notice: Option<(String, Instant)>,
+    /// Tray icon and quick-ask hotkey (`tray` in config.json)
+    tray: Option<Tray>,
+    /// Set while the window is shrunk to the quick-ask panel
+    quick_ask: Option<QuickAsk>,
+    /// Distraction-free layout: no bars, panels or starters, the conversation
+    /// centered and everything before the latest exchange dimmed (F11)
+    zen_mode: bool,
+    /// Index of the message shown while presenting the conversation one
+    /// message at a time (F5)
+    presentation: Option<usize>,
+    /// Desktop notifications for work finishing while the window is unfocused
+    notifier: NotificationManager,
+    /// When each session's current response started
+    run_started: HashMap<uuid::Uuid, Instant>,
+    /// Tool calls running in each session and when the batch started
+    tools_running: HashMap<uuid::Uuid, (usize, Instant)>,
+    /// Asking whether to quit while a run is still going
+    quit_prompt: bool,
+    /// A saved conversation another instance has open, and who has it:
+    /// asking whether to open it read-only
+    read_only_offer: Option<(uuid::Uuid, String)>,
+    /// A run was paused by the loop watchdog and waits for the next prompt
+    loop_paused: bool,
+    /// Whether the user is being alerted that the agent waits on them
+    attention: bool,
+    /// Microphone recording started with the mic button
+    voice: Option<Recording>,
+    /// Whether a finished recording is being transcribed
+    transcribing: bool,
+    /// The reply being read aloud (keyed like `markdown_cache`) and its playback
+    speaking: Option<(String, Playback)>,
+    /// The reply whose speech is being prepared
+    speech_loading: Option<String>,
+    /// Screen region selection started with `/region`
+    region_pick: Option<RegionPick>,
+}
+
+/// What a region picked with `/region` is used for
+#[derive(Debug, Clone, Copy)]
+enum RegionAction {
+    /// Add the region's coordinates to the draft
+    Insert,
+    /// Save a Visioneer capture of the region and add its path
+    Capture,
+    /// Add the text Visioneer's OCR reads in the region
+    Ocr,
+}
+
+/// A region selection in progress
+#[derive(Debug)]
+struct RegionPick {
+    action: RegionAction,
+    /// The screen being selected on, once captured
+    snapshot: Option<ScreenSnapshot>,
+}
+
+/// The compact window opened from the tray or the quick-ask hotkey
+#[derive(Debug, Clone, Copy)]
+struct QuickAsk {
+    /// Session the question goes to
+    session: usize,
+    /// Session that was current before, restored if nothing was asked
+    previous: usize,
 }
 
 /// A pending question batch from the AI's ask_question tool
@@ -145,7 +261,33 @@ struct PendingQuestionItem {
 enum Message {
     DraftChanged(String),
     SendPrompt,
+    /// Drop a pending prompt from the current session's queue
+    RemoveQueuedPrompt(usize),
+    /// Send the draft to the running agent as a steering message
+    SteerPrompt,
+    /// Complete the `@` mention at the end of the draft with a file path
+    CompleteMention(String),
     Received(UiEvent),
+    /// The window gained (true) or lost (false) focus
+    WindowFocused(bool),
+    /// The window is being closed; asks first while a run is active
+    CloseRequested,
+    /// Quit even though a run is active
+    ConfirmQuit,
+    /// Keep the window open after the quit prompt
+    CancelQuit,
+    /// Open the offered conversation without saving it
+    OpenReadOnly,
+    /// Leave the offered conversation closed
+    CancelReadOnly,
+    /// Start recording, or stop and transcribe the recording
+    ToggleVoice,
+    /// A recording was transcribed (or failed to be)
+    VoiceTranscribed(Result<String, String>),
+    /// Read a reply aloud (message key, content), or stop reading it
+    ToggleSpeech(String, String),
+    /// Speech for a reply is ready to play (or failed)
+    SpeechReady(String, Result<Arc<Utterance>, String>),
     NewTab,
     ToggleSettings,
     CloseSettings,
@@ -158,6 +300,8 @@ enum Message {
     AddCustomModel,
     ConfigStreamingToggled(bool),
     ConfigLivingBackgroundToggled(bool),
+    /// Toggle clock times next to chat messages
+    ConfigShowTimestampsToggled(bool),
     ConfigApiUrlChanged(String),
     /// Handle z.ai endpoint selection change
     ConfigEndpointChanged(String),
@@ -167,6 +311,7 @@ enum Message {
     ConfigOllamaToolsToggled(bool),
     ConfigSystemPromptChanged(String),
     ConfigTemperatureChanged(f32),
+    ConfigTopPChanged(f32),
     ConfigMaxTokensChanged(String),
     SaveConfig,
     CardHovered(usize, bool),
@@ -223,10 +368,8 @@ enum Message {
     CloseConversations,
     /// Initialize project with AI (enhance PROJECT.manifest)
     InitializeProjectWithAI,
-    /// Change theme mode (Light, Dark, Black)
-    ThemeModeChanged(String),
-    /// Theme submenu selection (Dark/Black)
-    ThemeSubmenuChanged(String),
+    /// Switch to a color theme by name
+    ThemeChanged(String),
     /// Click on a conversation starter to use it
     StarterClicked(String),
     /// Answer a pending question with a specific option (batch_idx, question_idx, answer)
@@ -237,6 +380,40 @@ enum Message {
     SubmitQuestionAnswer(usize, usize),
     /// Submit all pending question answers and continue
     SubmitAllQuestionAnswers,
+    /// Toggle the architecture map overlay (generates the map on first open)
+    ToggleArchitecture,
+    /// Enter or leave the distraction-free layout
+    ToggleZenMode,
+    /// Start or stop presenting the conversation one message at a time
+    TogglePresentation,
+    /// Show the next (1) or previous (-1) message while presenting
+    PresentationStep(isize),
+    /// Stop presenting
+    EndPresentation,
+    /// Regenerate the architecture map for the current directory
+    RefreshArchitecture,
+    /// Architecture map generation finished
+    ArchitectureGenerated(Result<ArchitectureMap, String>),
+    /// Write the architecture diagram to the project root in the given format
+    SaveArchitecture(DiagramFormat),
+    /// Switch to the session at this index
+    SelectSession(usize),
+    /// Close the session at this index
+    CloseSession(usize),
+    /// Grow the quick-ask panel back into the main window
+    ExpandQuickAsk,
+    /// Close the quick-ask panel and hide the window
+    DismissQuickAsk,
+    /// Select a screen region by dragging over a screenshot (`/region`)
+    PickRegion(RegionAction),
+    /// The screenshot to select a region on was taken
+    RegionSnapshot(Result<ScreenSnapshot, String>),
+    /// A region was selected, in screen pixels
+    RegionPicked(CaptureRegion),
+    /// Region selection was cancelled
+    CancelRegionPick,
+    /// Text for the draft from a region action
+    RegionActionDone(Result<String, String>),
 }
 
 /// Input field ID for focus management
@@ -244,6 +421,57 @@ fn input_id() -> iced::widget::Id {
     iced::widget::Id::new("chat-input")
 }
 
+/// How long a message stays outlined after `/goto`
+const GOTO_HIGHLIGHT_SECS: f32 = 2.0;
+
+/// How long the config reload notice stays below the chat
+const CONFIG_NOTICE_SECS: f32 = 8.0;
+
+/// Chat message list ID for `/goto` scrolling
+fn chat_scroll_id() -> iced::widget::Id {
+    iced::widget::Id::new("chat-scroll")
+}
+
+/// Window-wide keyboard shortcuts: F11 toggles zen mode, F5 presenting;
+/// while presenting the arrows step through messages and Escape stops
+fn shortcut(event: keyboard::Event) -> Option<Message> {
+    use keyboard::key::Named;
+    let keyboard::Event::KeyPressed {
+        key: keyboard::Key::Named(key),
+        ..
+    } = event
+    else {
+        return None;
+    };
+    match key {
+        Named::F11 => Some(Message::ToggleZenMode),
+        Named::F5 => Some(Message::TogglePresentation),
+        Named::ArrowRight | Named::ArrowDown | Named::PageDown | Named::Space => {
+            Some(Message::PresentationStep(1))
+        }
+        Named::ArrowLeft | Named::ArrowUp | Named::PageUp => Some(Message::PresentationStep(-1)),
+        Named::Escape => Some(Message::EndPresentation),
+        _ => None,
+    }
+}
+
+/// Drop the entries of session `removed` from a map keyed
+/// "session_index:message_index" and move later sessions down one index
+fn shift_session_keys<V>(map: &mut HashMap<String, V>, removed: usize) {
+    for (key, value) in std::mem::take(map) {
+        let session = key.split_once(':').and_then(|(s, rest)| Some((s.parse::<usize>().ok()?, rest)));
+        match session {
+            Some((i, _)) if i == removed => {}
+            Some((i, rest)) if i > removed => {
+                map.insert(format!("{}:{}", i - 1, rest), value);
+            }
+            _ => {
+                map.insert(key, value);
+            }
+        }
+    }
+}
+
 /// Build enhanced system prompt
 /// Note: PROJECT.manifest context is handled by arula_core's build_system_prompt()
 fn build_enhanced_system_prompt(base_prompt: &str) -> String {
@@ -265,14 +493,19 @@ impl App {
 
     /// Attempts to initialize the application, returning errors properly.
     fn try_init() -> anyhow::Result<Self> {
+        let mut config = Config::load_or_default()?;
         // Initialize the global logger for debug file output
-        let _ = arula_core::utils::logger::init_global_logger();
-
-        let config = Config::load_or_default()?;
+        let _ = arula_core::utils::logger::init_global_logger_with(config.get_logging().settings());
+        if let Some(profile) = profile_from_args() {
+            config.use_profile(Some(&profile))?;
+        }
         let dispatcher = Dispatcher::new(&config)?;
         let config_form = ConfigForm::from_config(&config);
         let session = Session::new();
 
+        // Emoji depend on the system fonts; plain symbols render everywhere
+        set_icon_set(config.icons.unwrap_or(IconSet::Unicode));
+
         // Create tilt cards using Vec instead of duplicate fields
         let tilt_cards: Vec<TiltCardState> = (0..TILT_CARD_COUNT)
             .map(|_| TiltCardState::default())
@@ -284,7 +517,47 @@ impl App {
             0.0
         };
 
-        let theme_mode = config_form.theme_mode;
+        let system_dark = system_prefers_dark();
+        let theme = if config.follows_system_theme() {
+            config.get_system_theme(system_dark)
+        } else {
+            config.get_theme()
+        };
+
+        // Surface unresolved ${VAR} references and invalid settings in the config
+        let config_problems: Vec<String> = config
+            .env_errors
+            .iter()
+            .cloned()
+            .chain(
+                config
+                    .validation_issues
+                    .iter()
+                    .filter(|i| i.severity == Severity::Error)
+                    .map(ConfigIssue::describe),
+            )
+            .collect();
+        let config_error = (!config_problems.is_empty())
+            .then(|| format!("Config: {}", config_problems.join("; ")));
+        // Tray icon and quick-ask hotkey
+        let tray_config = config.get_tray();
+        let tray = if tray_config.is_enabled() {
+            Tray::start(&tray_config)
+                .map_err(|e| eprintln!("⚠️ Tray disabled: {e:#}"))
+                .ok()
+        } else {
+            None
+        };
+
+        let notifier = NotificationManager::new(
+            Arc::new(AtomicBool::new(true)),
+            config.get_notifications(),
+        );
+
+        let manifest_watcher = start_manifest_watcher(
+            &config,
+            &std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
+        );
 
         Ok(Self {
             dispatcher,
@@ -310,11 +583,13 @@ impl App {
                 accent_color: Color::from_rgba(0.6, 0.6, 0.6, 1.0),
             },
             markdown_cache: HashMap::new(),
+            typing: HashMap::new(),
             tool_args_cache: HashMap::new(),
             tool_animations: HashMap::new(),
-            stream_error: None,
+            stream_error: config_error,
             error_expanded: false,
             bash_output_lines: HashMap::new(),
+            command_reports: HashMap::new(),
             current_directory: std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
             show_directory_popup: false,
             show_directory_custom_input: false,
@@ -327,7 +602,9 @@ impl App {
             conversations_layout_offset: 0.0,
             clipboard: arboard::Clipboard::new().ok(),
             custom_model_draft: String::new(),
-            theme_mode,
+            theme,
+            system_dark,
+            system_theme: SystemThemeWatcher::start(system_dark),
             detected_project: {
                 // Detect project on startup
                 let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/"));
@@ -338,7 +615,7 @@ impl App {
                     let manifest_path = cwd.join("PROJECT.manifest");
                     if !manifest_path.exists() {
                         if let Some(ref project) = detected {
-                            let content = generate_auto_manifest(project);
+                            let content = generate_indexed_manifest(project, &cwd);
                             let _ = std::fs::write(&manifest_path, content);
                         }
                     }
@@ -353,6 +630,33 @@ impl App {
             pending_question_batches: Vec::new(),
             input_bar_height_spring: Spring::default(),
             question_answer_drafts: std::collections::HashMap::new(),
+            show_architecture: false,
+            architecture_map: None,
+            architecture_layout: ArchitectureLayout::default(),
+            architecture_loading: false,
+            architecture_status: None,
+            manifest_watcher,
+            goto_highlight: None,
+            config_watcher: ConfigWatcher::start(Path::new(&Config::get_config_path()))
+                .map_err(|e| eprintln!("⚠️ Config hot-reload disabled: {}", e))
+                .ok(),
+            config_notice: None,
+            tray,
+            quick_ask: None,
+            zen_mode: false,
+            presentation: None,
+            notifier,
+            run_started: HashMap::new(),
+            tools_running: HashMap::new(),
+            quit_prompt: false,
+            read_only_offer: None,
+            loop_paused: false,
+            attention: false,
+            voice: None,
+            transcribing: false,
+            speaking: None,
+            speech_loading: None,
+            region_pick: None,
         })
     }
 
@@ -390,11 +694,13 @@ impl App {
                 accent_color: Color::from_rgba(0.6, 0.6, 0.6, 1.0),
             },
             markdown_cache: HashMap::new(),
+            typing: HashMap::new(),
             tool_args_cache: HashMap::new(),
             tool_animations: HashMap::new(),
             stream_error: None,
             error_expanded: false,
             bash_output_lines: HashMap::new(),
+            command_reports: HashMap::new(),
             current_directory: std::env::current_dir().unwrap_or_else(|_| PathBuf::from("/")),
             show_directory_popup: false,
             show_directory_custom_input: false,
@@ -411,13 +717,43 @@ impl App {
             conversations_layout_offset: 0.0,
             clipboard: arboard::Clipboard::new().ok(),
             custom_model_draft: String::new(),
-            theme_mode: ThemeMode::default(),
+            theme: ArulaTheme::default(),
+            system_dark: true,
+            system_theme: SystemThemeWatcher::start(true),
             detected_project: None,
             manifest_is_ai_enhanced: false,
             conversation_starters: Vec::new(),
             pending_question_batches: Vec::new(),
             input_bar_height_spring: Spring::default(),
             question_answer_drafts: std::collections::HashMap::new(),
+            show_architecture: false,
+            architecture_map: None,
+            architecture_layout: ArchitectureLayout::default(),
+            architecture_loading: false,
+            architecture_status: None,
+            manifest_watcher: None,
+            goto_highlight: None,
+            config_watcher: None,
+            config_notice: None,
+            tray: None,
+            quick_ask: None,
+            zen_mode: false,
+            presentation: None,
+            notifier: NotificationManager::new(
+                Arc::new(AtomicBool::new(true)),
+                Default::default(),
+            ),
+            run_started: HashMap::new(),
+            tools_running: HashMap::new(),
+            quit_prompt: false,
+            read_only_offer: None,
+            loop_paused: false,
+            attention: false,
+            voice: None,
+            transcribing: false,
+            speaking: None,
+            speech_loading: None,
+            region_pick: None,
         }
     }
 
@@ -425,64 +761,90 @@ impl App {
         match message {
             Message::DraftChanged(s) => self.draft = s,
             Message::SendPrompt => {
-                if let Some(session) = self.sessions.get_mut(self.current) {
-                    if session.is_streaming {
-                        return Task::none();
-                    }
-                    let prompt = std::mem::take(&mut self.draft);
-                    if prompt.trim().is_empty() {
-                        return Task::none();
-                    }
-
-                    session.add_user_message(prompt.clone(), Utc::now().to_rfc3339());
-
-                    // Sync editor content for the new message
-                    let msg_idx = session.messages.len() - 1;
-                    let key = format!("{}:{}", self.current, msg_idx);
-                    self.message_editors.insert(
-                        key,
-                        text_editor::Content::with_text(&session.messages[msg_idx].content),
-                    );
-
-                    session.set_streaming(true);
-
-                    let session_config = SessionConfig {
-                        system_prompt: build_enhanced_system_prompt(
-                            &self.config_form.system_prompt,
-                        ),
-                        model: self.config.get_model(),
-                        max_tokens: self.config_form.max_tokens as u32,
-                        temperature: self.config_form.temperature,
-                    };
-
-                    // Get conversation history for context (excluding the current prompt which is included separately)
-                    let history = session.get_chat_history();
-                    let history_opt = if history.is_empty() {
-                        None
-                    } else {
-                        Some(history)
+                let Some(session) = self.sessions.get_mut(self.current) else {
+                    return Task::none();
+                };
+                let prompt = std::mem::take(&mut self.draft);
+                if prompt.trim().is_empty() {
+                    return Task::none();
+                }
+                // `/goto <time>` jumps within the chat instead of being sent
+                let command = prompt.trim();
+                if command == "/goto" || command.starts_with("/goto ") {
+                    let target = command["/goto".len()..].trim().to_string();
+                    return self.goto_message(&target);
+                }
+                // `/region [capture|ocr]` selects a screen region for Visioneer
+                if command == "/region" || command.starts_with("/region ") {
+                    let action = match command["/region".len()..].trim() {
+                        "" => RegionAction::Insert,
+                        "capture" => RegionAction::Capture,
+                        "ocr" => RegionAction::Ocr,
+                        _ => {
+                            self.stream_error =
+                                Some("Usage: /region [capture|ocr]".to_string());
+                            return Task::none();
+                        }
                     };
-
-                    if let Err(err) = self.dispatcher.start_stream(
-                        session.id,
-                        prompt,
-                        history_opt,
-                        session_config,
-                    ) {
-                        eprintln!("dispatch error: {err}");
-                        session.set_streaming(false);
-                    }
+                    return self.update(Message::PickRegion(action));
+                }
+                // `/steer <message>` redirects the running agent
+                if session.is_streaming
+                    && let Some(message) = command.strip_prefix("/steer ")
+                {
+                    let message = message.to_string();
+                    self.steer(message);
+                    return iced::widget::operation::focus(input_id());
+                }
+                // Sent once the current turn, tool calls included, completes
+                if session.is_streaming {
+                    session.queue_prompt(prompt);
+                } else {
+                    self.start_prompt(self.current, prompt);
                 }
                 // Re-focus input after sending
                 return iced::widget::operation::focus(input_id());
             }
+            Message::SteerPrompt => {
+                let message = std::mem::take(&mut self.draft);
+                self.steer(message);
+                return iced::widget::operation::focus(input_id());
+            }
+            Message::CompleteMention(path) => {
+                if let Some(mention) = mention_at(&self.draft, self.draft.chars().count()) {
+                    self.draft = complete_mention(&self.draft, &mention, &path).0;
+                }
+                return Task::batch([
+                    iced::widget::operation::focus(input_id()),
+                    iced::widget::operation::move_cursor_to_end(input_id()),
+                ]);
+            }
+            Message::RemoveQueuedPrompt(index) => {
+                if let Some(session) = self.sessions.get_mut(self.current) {
+                    session.queued_prompts.remove(index);
+                }
+            }
             Message::Received(ev) => return self.handle_ui_event(ev),
+            Message::ExpandQuickAsk => return self.leave_quick_ask(false),
+            Message::DismissQuickAsk => return self.leave_quick_ask(true),
             Message::NewTab => {
                 self.sessions.push(Session::new());
                 self.current = self.sessions.len() - 1;
+                self.menu_state.close();
+                self.settings_state.reset();
                 // Fetch conversation starters for the new session
                 self.dispatcher.generate_conversation_starters();
+                return iced::widget::operation::focus(input_id());
+            }
+            Message::SelectSession(index) => {
+                if index < self.sessions.len() {
+                    self.current = index;
+                    self.menu_state.close();
+                    self.settings_state.reset();
+                    return iced::widget::operation::focus(input_id());
+                }
             }
+            Message::CloseSession(index) => self.close_session(index),
             Message::ToggleSettings => {
                 self.menu_state.open();
                 self.config_form.clear_status();
@@ -492,7 +854,77 @@ impl App {
                 // Reset settings submenu state when closing
                 self.settings_state.reset();
             }
+            Message::WindowFocused(focused) => self.notifier.set_focused(focused),
+            Message::CloseRequested => return self.request_quit(),
+            Message::ConfirmQuit => return self.quit(),
+            Message::CancelQuit => self.quit_prompt = false,
+            Message::OpenReadOnly => {
+                if let Some((id, _)) = self.read_only_offer.take() {
+                    match self.conversation_manager.load_conversation(id) {
+                        Ok(conversation) => {
+                            return self.open_saved_session(id, &conversation.events, true);
+                        }
+                        Err(err) => eprintln!("Failed to load conversation: {}", err),
+                    }
+                }
+            }
+            Message::CancelReadOnly => self.read_only_offer = None,
+            Message::ToggleVoice => return self.toggle_voice(),
+            Message::ToggleSpeech(key, content) => {
+                let was_speaking = self.speaking.take().is_some_and(|(k, _)| k == key);
+                let was_loading = self.speech_loading.take().is_some_and(|k| k == key);
+                if was_speaking || was_loading {
+                    return Task::none();
+                }
+                self.speech_loading = Some(key.clone());
+                let config = self.config.clone();
+                return Task::perform(
+                    async move {
+                        speech::prepare(&content, &config)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::SpeechReady(key.clone(), result),
+                );
+            }
+            Message::SpeechReady(key, result) => {
+                // Stopped, or another reply was picked, while preparing
+                if self.speech_loading.as_ref() != Some(&key) {
+                    return Task::none();
+                }
+                self.speech_loading = None;
+                match result.and_then(|utterance| utterance.play().map_err(|e| e.to_string())) {
+                    Ok(playback) => self.speaking = Some((key, playback)),
+                    Err(err) => self.stream_error = Some(format!("Couldn't read the reply aloud: {err}")),
+                }
+            }
+            Message::VoiceTranscribed(result) => {
+                self.transcribing = false;
+                match result {
+                    Ok(text) if text.trim().is_empty() => {
+                        self.stream_error = Some("No speech was recognized".to_string());
+                    }
+                    Ok(text) => {
+                        if !self.draft.is_empty() && !self.draft.ends_with(char::is_whitespace) {
+                            self.draft.push(' ');
+                        }
+                        self.draft.push_str(text.trim());
+                    }
+                    Err(err) => self.stream_error = Some(format!("Transcription failed: {err}")),
+                }
+            }
             Message::Tick => {
+                // SIGTERM or SIGHUP: no time to ask
+                if shutdown::requested() {
+                    return self.quit();
+                }
+                // Stop recording at the length limit
+                if self.voice.as_mut().is_some_and(|r| r.is_finished()) {
+                    return self.toggle_voice();
+                }
+                if self.speaking.as_mut().is_some_and(|(_, p)| p.is_finished()) {
+                    self.speaking = None;
+                }
                 self.menu_state.update();
                 self.settings_state.update(); // Update settings page transitions
                 self.bg_state.update();
@@ -500,6 +932,21 @@ impl App {
                 // Update spinner animation
                 self.spinner_state.tick += 0.016; // ~60fps
 
+                // Apply edits to config.json made while running
+                if let Some(reload) = self.config_watcher.as_ref().and_then(ConfigWatcher::try_recv) {
+                    self.apply_config_reload(reload);
+                }
+
+                // Follow the OS light/dark setting when asked to
+                if let Some(dark) = self.system_theme.try_recv() {
+                    self.system_dark = dark;
+                    if self.config.follows_system_theme() {
+                        self.switch_theme(self.configured_theme());
+                    }
+                }
+
+                let tray_event = self.tray.as_ref().and_then(Tray::try_recv);
+
                 // Animate background opacity based on config
                 // We use the *config* value (saved), not the form value, to drive the actual display
                 let target = if self.config.get_living_background_enabled() {
@@ -539,6 +986,17 @@ impl App {
                     }
                 }
 
+                self.update_typing();
+
+                // Bring the briefs of idle sessions up to date
+                if self.config.get_session_brief_enabled() {
+                    for session in &mut self.sessions {
+                        if let Some((previous, history)) = session.take_brief_request() {
+                            self.dispatcher.write_brief(session.id, previous, history);
+                        }
+                    }
+                }
+
                 // Note: This Tick also drives the message bubble fade-in animations
                 // Iced automatically redraws the view after handling a message
 
@@ -611,6 +1069,12 @@ impl App {
                         self.input_bar_height_spring.set_target(1.0);
                     }
                 }
+
+                let attention = self.update_attention();
+                if let Some(event) = tray_event {
+                    return Task::batch([attention, self.handle_tray_event(event)]);
+                }
+                return attention;
             }
             Message::ConfigProviderChanged(provider) => {
                 // Use switch_provider to automatically set defaults (API URL, model)
@@ -671,12 +1135,18 @@ impl App {
             Message::ConfigLivingBackgroundToggled(on) => {
                 self.config_form.living_background_enabled = on;
             }
+            Message::ConfigShowTimestampsToggled(on) => {
+                self.config_form.show_timestamps = on;
+            }
             Message::ConfigSystemPromptChanged(val) => {
                 self.config_form.system_prompt = val;
             }
             Message::ConfigTemperatureChanged(val) => {
                 self.config_form.temperature = val;
             }
+            Message::ConfigTopPChanged(val) => {
+                self.config_form.top_p = val;
+            }
             Message::ConfigMaxTokensChanged(val) => {
                 if let Ok(n) = val.parse() {
                     self.config_form.max_tokens = n;
@@ -685,28 +1155,13 @@ impl App {
             Message::SaveConfig => {
                 self.apply_config_changes();
             }
-            Message::ThemeModeChanged(mode) => {
-                if let Some(theme_mode) = ThemeMode::from_name(&mode) {
-                    println!("Theme mode changed to: {:?}", theme_mode);
-                    self.theme_mode = theme_mode;
-                    self.config_form.theme_mode = theme_mode;
-                    return Task::none();
-                }
-            }
-            Message::ThemeSubmenuChanged(submenu) => {
-                // Handle Dark/Black submenu selection
-                match submenu.as_str() {
-                    "Dark" => {
-                        println!("Theme submenu changed to: Dark");
-                        self.theme_mode = ThemeMode::Dark;
-                        self.config_form.theme_mode = ThemeMode::Dark;
-                    }
-                    "Black" => {
-                        println!("Theme submenu changed to: Black");
-                        self.theme_mode = ThemeMode::Black;
-                        self.config_form.theme_mode = ThemeMode::Black;
+            Message::ThemeChanged(name) => {
+                match self.config.set_theme(&name) {
+                    Ok(()) => {
+                        self.switch_theme(self.configured_theme());
+                        self.config_form.theme = name.to_lowercase();
                     }
-                    _ => {}
+                    Err(e) => self.config_form.set_error(&format!("{e:#}")),
                 }
                 return Task::none();
             }
@@ -841,6 +1296,15 @@ impl App {
                 // Stop the current streaming session
                 if let Some(session) = self.sessions.get_mut(self.current) {
                     if session.is_streaming {
+                        // Queued prompts go back to the input instead of being sent
+                        let queued: Vec<String> = session.queued_prompts.drain(..).collect();
+                        if !queued.is_empty() {
+                            let mut draft = queued.join("\n\n");
+                            if !self.draft.is_empty() {
+                                draft = format!("{}\n\n{}", draft, self.draft);
+                            }
+                            self.draft = draft;
+                        }
                         self.dispatcher.stop_stream(session.id);
                         session.set_streaming(false);
                         // Re-focus the input after stopping
@@ -921,6 +1385,7 @@ impl App {
                 let prefix = format!("{}:", self.current);
                 self.message_editors.retain(|k, _| !k.starts_with(&prefix));
                 self.markdown_cache.retain(|k, _| !k.starts_with(&prefix));
+                self.typing.retain(|k, _| !k.starts_with(&prefix));
                 self.tool_animations.retain(|k, _| !k.starts_with(&prefix));
 
                 if let Some(id) = session_id {
@@ -928,6 +1393,7 @@ impl App {
                 }
                 for tool_call_id in tool_call_ids {
                     self.bash_output_lines.remove(&tool_call_id);
+                    self.command_reports.remove(&tool_call_id);
                 }
 
                 self.stream_error = None;
@@ -950,6 +1416,116 @@ impl App {
                     self.directory_draft.clear();
                 }
             }
+            Message::ToggleZenMode => {
+                self.zen_mode = !self.zen_mode;
+                if self.zen_mode {
+                    self.show_conversations = false;
+                    self.show_architecture = false;
+                    self.show_directory_popup = false;
+                }
+            }
+            Message::TogglePresentation => {
+                self.presentation = match self.presentation {
+                    Some(_) => None,
+                    None => self.presentable_messages().first().copied(),
+                };
+            }
+            Message::PresentationStep(step) => {
+                if let Some(current) = self.presentation {
+                    let slides = self.presentable_messages();
+                    let position = slides.iter().position(|&i| i == current).unwrap_or(0);
+                    let next = position
+                        .saturating_add_signed(step)
+                        .min(slides.len().saturating_sub(1));
+                    self.presentation = slides.get(next).copied().or(self.presentation);
+                }
+            }
+            Message::EndPresentation => self.presentation = None,
+            Message::PickRegion(action) => return self.open_region_picker(action),
+            Message::RegionSnapshot(Ok(snapshot)) => {
+                if let Some(pick) = self.region_pick.as_mut() {
+                    pick.snapshot = Some(snapshot);
+                    return window::latest().and_then(|id| {
+                        Task::batch([
+                            window::minimize(id, false),
+                            window::set_mode(id, window::Mode::Fullscreen),
+                            window::gain_focus(id),
+                        ])
+                    });
+                }
+            }
+            Message::RegionSnapshot(Err(error)) => {
+                self.stream_error = Some(error);
+                return self.close_region_picker();
+            }
+            Message::RegionPicked(region) => {
+                let action = self.region_pick.as_ref().map(|pick| pick.action);
+                let restore = self.close_region_picker();
+                let Some(action) = action else {
+                    return restore;
+                };
+                return restore.chain(Task::perform(
+                    run_region_action(action, region),
+                    Message::RegionActionDone,
+                ));
+            }
+            Message::CancelRegionPick => return self.close_region_picker(),
+            Message::RegionActionDone(Ok(text)) => {
+                if !self.draft.is_empty() && !self.draft.ends_with(char::is_whitespace) {
+                    self.draft.push(' ');
+                }
+                self.draft.push_str(&text);
+                return Task::batch([
+                    iced::widget::operation::focus(input_id()),
+                    iced::widget::operation::move_cursor_to_end(input_id()),
+                ]);
+            }
+            Message::RegionActionDone(Err(error)) => self.stream_error = Some(error),
+            Message::ToggleArchitecture => {
+                self.show_architecture = !self.show_architecture;
+                if self.show_architecture && self.architecture_map.is_none() {
+                    return self.update(Message::RefreshArchitecture);
+                }
+            }
+            Message::RefreshArchitecture => {
+                if self.architecture_loading {
+                    return Task::none();
+                }
+                self.architecture_loading = true;
+                self.architecture_status = None;
+                let dir = self.current_directory.clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || build_architecture_map(&dir))
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|r| r.map_err(|e| e.to_string()))
+                    },
+                    Message::ArchitectureGenerated,
+                );
+            }
+            Message::ArchitectureGenerated(result) => {
+                self.architecture_loading = false;
+                match result {
+                    Ok(map) => {
+                        self.architecture_layout = ArchitectureLayout::from_map(&map);
+                        self.architecture_map = Some(map);
+                    }
+                    Err(e) => {
+                        self.architecture_map = None;
+                        self.architecture_layout = ArchitectureLayout::default();
+                        self.architecture_status = Some(format!("Failed to build map: {}", e));
+                    }
+                }
+            }
+            Message::SaveArchitecture(format) => {
+                if let Some(ref map) = self.architecture_map {
+                    self.architecture_status = Some(match map.write_to(&self.current_directory, format) {
+                        Ok(path) => format!("Saved {}", path.display()),
+                        Err(e) => format!("Failed to save: {}", e),
+                    });
+                }
+            }
             Message::CloseDirectoryPopup => {
                 self.show_directory_popup = false;
                 self.show_directory_custom_input = false;
@@ -996,22 +1572,16 @@ impl App {
                 }
             }
             Message::LoadConversation(conversation_id) => {
-                if let Ok(conversation) = self.conversation_manager.load_conversation(conversation_id) {
-                    // Create a new session from the loaded events
-                    let new_session = Session::from_events(conversation_id, &conversation.events);
-                    
-                    // Add the new session
-                    self.sessions.push(new_session);
-                    self.current = self.sessions.len() - 1;
-                    
-                    // Close the conversations sidebar
-                    self.show_conversations = false;
-                    
-                    // Clear the draft
-                    self.draft.clear();
-                    
-                    // Focus the input
-                    return iced::widget::operation::focus(input_id());
+                match self.conversation_manager.open_conversation(conversation_id) {
+                    Ok(conversation) => {
+                        return self.open_saved_session(conversation_id, &conversation.events, false);
+                    }
+                    Err(err) => match err.downcast_ref::<SessionInUse>() {
+                        Some(in_use) => {
+                            self.read_only_offer = Some((conversation_id, in_use.holder.clone()));
+                        }
+                        None => eprintln!("Failed to load conversation: {}", err),
+                    },
                 }
             }
             Message::DeleteConversation(conversation_id) => {
@@ -1105,29 +1675,20 @@ impl App {
             if !manifest_path.exists() {
                 // Create auto-generated manifest if we detected a project
                 if let Some(ref project) = self.detected_project {
-                    let content = generate_auto_manifest(project);
+                    let content = generate_indexed_manifest(project, &path);
                     let _ = std::fs::write(&manifest_path, content);
                 }
             }
             
             // Check if manifest is AI-enhanced
             self.manifest_is_ai_enhanced = is_ai_enhanced(&manifest_path);
-        }
-    }
+            self.manifest_watcher = start_manifest_watcher(&self.config, &path);
 
-    /// Helper function to get tool icons
-    fn get_tool_icon(&self, name: &str) -> &'static str {
-        match name.to_lowercase().as_str() {
-            "execute_bash" => "○",
-            "read_file" => "○",
-            "write_file" | "edit_file" => "□",
-            "list_directory" => "◇",
-            "search_files" => "○",
-            "web_search" => "⭕",
-            "mcp_call" => "◊",
-            "visioneer" => "○",
-            "ask_question" => "❓",
-            _ => "□",
+            // The architecture map belongs to the previous directory
+            self.architecture_map = None;
+            self.architecture_layout = ArchitectureLayout::default();
+            self.architecture_status = None;
+            self.show_architecture = false;
         }
     }
 
@@ -1137,12 +1698,27 @@ impl App {
                 self.conversation_starters = starters;
                 return Task::none();
             }
-            UiEvent::ConversationTitle(title) => {
-                // Update the current session's title
-                if let Some(s) = self.sessions.get_mut(self.current) {
+            UiEvent::SessionTitle(id, title) => {
+                if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
                     s.set_title(title);
                 }
             }
+            UiEvent::SessionBrief(id, brief) => {
+                if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
+                    s.brief = Some(brief);
+                    let events = s.to_ui_events();
+                    if !s.read_only && let Err(err) = self.conversation_manager.save_conversation(
+                        s.id,
+                        &events,
+                        self.config.get_model(),
+                    ) {
+                        eprintln!("Failed to save conversation: {}", err);
+                    }
+                }
+            }
+            UiEvent::ConversationTitle(_) => {
+                // Only stored in saved conversations; live titles are SessionTitle
+            }
             UiEvent::UserMessage { content: _, timestamp: _ } => {
                 // Clear starters when conversation starts
                 self.conversation_starters.clear();
@@ -1152,9 +1728,11 @@ impl App {
                 // AI messages are handled via Token events, no action needed
             }
             UiEvent::StreamStarted(id) => {
+                self.run_started.insert(id, Instant::now());
                 if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
                     s.set_streaming(true);
                 }
+                self.bg_state.set_activity(AgentActivity::Streaming);
             }
             UiEvent::Token(id, delta, is_final) => {
                 // Find session index for syncing editors
@@ -1201,10 +1779,9 @@ impl App {
                         }
                     }
 
-                    // Update markdown cache for AI messages
-                    // Parse markdown on final token or periodically during streaming
-                    let should_update_md = is_final || !self.markdown_cache.contains_key(&key);
-                    if should_update_md && session.messages[msg_idx].is_ai() {
+                    // Update markdown cache for AI messages on the final token;
+                    // while streaming, the typing animation re-parses on each tick
+                    if is_final && session.messages[msg_idx].is_ai() {
                         let content = &session.messages[msg_idx].content;
                         let items: Vec<markdown::Item> = markdown::parse(content).collect();
                         self.markdown_cache.insert(key, items);
@@ -1217,20 +1794,37 @@ impl App {
                             session.flush_ai_buffer(Utc::now().to_rfc3339());
                         }
                         session.set_streaming(false);
+                        self.settle_background();
                         // Re-focus input when response completes
                         return iced::widget::operation::focus(input_id());
                     }
                 }
             }
             UiEvent::StreamFinished(id) => {
+                self.tools_running.remove(&id);
+                // Runs that failed were already reported
+                if let Some(started) = self.run_started.remove(&id) {
+                    let reply = self
+                        .sessions
+                        .iter()
+                        .find(|s| s.id == id)
+                        .and_then(|s| s.messages.iter().rev().find(|m| m.is_ai()))
+                        .map(|m| m.content.clone())
+                        .unwrap_or_default();
+                    self.notifier.notify_event(
+                        NotificationEvent::ResponseFinished,
+                        started.elapsed(),
+                        reply,
+                    );
+                }
                 if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
                     // Flush any remaining AI content from the buffer
                     s.flush_ai_buffer(Utc::now().to_rfc3339());
                     s.set_streaming(false);
-                    
+
                     // Save the conversation
                     let events = s.to_ui_events();
-                    if let Err(err) = self.conversation_manager.save_conversation(
+                    if !s.read_only && let Err(err) = self.conversation_manager.save_conversation(
                         s.id,
                         &events,
                         self.config.get_model(),
@@ -1238,16 +1832,58 @@ impl App {
                         eprintln!("Failed to save conversation: {}", err);
                     }
                 }
+                self.settle_background();
+                // Send the next queued prompt now that the turn is over
+                if let Some(index) = self.sessions.iter().position(|s| s.id == id)
+                    && let Some(prompt) = self.sessions[index].next_queued_prompt()
+                {
+                    self.start_prompt(index, prompt);
+                }
                 // Re-focus input when stream finishes
                 return iced::widget::operation::focus(input_id());
             }
+            UiEvent::LoopDetected(id, diagnosis) => {
+                // The run ends right after; the next prompt decides how it goes on
+                self.loop_paused = true;
+                let elapsed = self
+                    .run_started
+                    .remove(&id)
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default();
+                self.notifier.notify_event(
+                    NotificationEvent::InputNeeded,
+                    elapsed,
+                    format!("Paused: the agent looks stuck in a loop. {}", diagnosis),
+                );
+                self.stream_error = Some(format!(
+                    "Paused: the agent looks stuck in a loop. {} Send \"continue\" to resume, or new instructions to change course.",
+                    diagnosis
+                ));
+                self.error_expanded = true;
+                return iced::widget::operation::focus(input_id());
+            }
+            UiEvent::ContextCompacted(_id, replaced) => {
+                self.config_notice = Some((
+                    format!(
+                        "🗜 Summarized {} earlier message(s) to fit the model's context window",
+                        replaced
+                    ),
+                    Instant::now(),
+                ));
+            }
             UiEvent::StreamErrored(id, err) => {
                 eprintln!("stream error {id}: {err}");
+                self.tools_running.remove(&id);
+                if let Some(started) = self.run_started.remove(&id) {
+                    self.notifier
+                        .notify_event(NotificationEvent::RunFailed, started.elapsed(), &err);
+                }
                 // Store error for display to user
                 self.stream_error = Some(err);
                 if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
                     s.set_streaming(false);
                 }
+                self.bg_state.set_activity(AgentActivity::Error);
                 // Re-focus input on error
                 return iced::widget::operation::focus(input_id());
             }
@@ -1269,12 +1905,14 @@ impl App {
                 }
             }
             UiEvent::ToolCallStart(id, tool_id, name, display_args) => {
-                let icon = self.get_tool_icon(&name);
+                let icon = Icon::for_tool(&name);
                 // display_args already contains "{display_name} • {formatted_args}"
                 let content = format!("{} {}", icon, display_args);
 
+                self.tools_running.entry(id).or_insert((0, Instant::now())).0 += 1;
                 // Cache the display_args for later use in ToolCallResult
                 self.tool_args_cache.insert(id, display_args);
+                self.bg_state.set_activity(AgentActivity::ToolRunning);
 
                 if let Some(s) = self.sessions.iter_mut().find(|s| s.id == id) {
                     // Pass tool_id so we can look up streaming bash output lines
@@ -1282,10 +1920,11 @@ impl App {
                 }
             }
             UiEvent::ToolCallResult(id, name, success, result_summary) => {
-                let icon = self.get_tool_icon(&name);
+                let icon = Icon::for_tool(&name);
 
                 // Get cached display_args if available (contains formatted args like filename)
                 let display_detail = self.tool_args_cache.remove(&id).unwrap_or_default();
+                self.settle_background();
 
                 let display_name = match name.to_lowercase().as_str() {
                     "execute_bash" => "Shell",
@@ -1330,6 +1969,19 @@ impl App {
                     icon, display_name, extra_info, status, result_summary
                 );
 
+                if let Some((running, started)) = self.tools_running.get_mut(&id) {
+                    *running = running.saturating_sub(1);
+                    if *running == 0 {
+                        let elapsed = started.elapsed();
+                        self.tools_running.remove(&id);
+                        self.notifier.notify_event(
+                            NotificationEvent::ToolsFinished,
+                            elapsed,
+                            format!("{} {} {}", display_name, status, result_summary),
+                        );
+                    }
+                }
+
                 // Find session index first, then update tool message
                 let session_idx = self.sessions.iter().position(|s| s.id == id);
 
@@ -1359,6 +2011,9 @@ impl App {
                     .or_insert_with(Vec::new)
                     .push((line, is_stderr));
             }
+            UiEvent::CommandFinished(_session_id, tool_call_id, report) => {
+                self.command_reports.insert(tool_call_id, report);
+            }
             UiEvent::AskQuestion { session_id: _, tool_call_id: _, question, options } => {
                 // Questions are now handled via polling from QUESTION_HANDLER
                 // This event is kept for backward compatibility but no action needed
@@ -1368,90 +2023,626 @@ impl App {
         Task::none()
     }
 
-    fn apply_config_changes(&mut self) {
-        let selected_provider = self.config_form.provider.clone();
-        if self.config.active_provider != selected_provider {
-            if let Err(err) = self.config.switch_provider(&selected_provider) {
-                self.config_form
-                    .set_error(&format!("Failed to switch provider: {err}"));
-                return;
-            }
+    /// Send `message` to the current session's running agent, showing it in
+    /// the chat; it goes back to the draft if the run already ended
+    fn steer(&mut self, message: String) {
+        let Some(session) = self.sessions.get_mut(self.current) else {
+            return;
+        };
+        if message.trim().is_empty() {
+            return;
         }
-
-        self.config.set_model(&self.config_form.model);
-        self.config.set_api_url(&self.config_form.api_url);
-        self.config.set_api_key(&self.config_form.api_key);
-
-        if let Some(active) = self.config.get_active_provider_config_mut() {
-            active.thinking_enabled = Some(self.config_form.thinking_enabled);
-            active.web_search_enabled = Some(self.config_form.web_search_enabled);
-            active.tools_enabled = Some(self.config_form.ollama_tools_enabled);
-            active.streaming = Some(self.config_form.streaming_enabled);
+        if !session.is_streaming || !self.dispatcher.steer(session.id, &message) {
+            self.draft = message;
+            return;
         }
+        session.flush_ai_buffer(Utc::now().to_rfc3339());
+        session.add_user_message(format!("↪ {}", message.trim()), Utc::now().to_rfc3339());
+        let msg_idx = session.messages.len() - 1;
+        self.message_editors.insert(
+            format!("{}:{}", self.current, msg_idx),
+            text_editor::Content::with_text(&session.messages[msg_idx].content),
+        );
+    }
 
-        // Save global settings
-        self.config.living_background_enabled = Some(self.config_form.living_background_enabled);
+    /// Add `prompt` to a session's chat and start streaming the reply
+    fn start_prompt(&mut self, index: usize, prompt: String) {
+        let Some(session) = self.sessions.get_mut(index) else {
+            return;
+        };
+        self.loop_paused = false;
+        session.add_user_message(prompt.clone(), Utc::now().to_rfc3339());
+
+        // Sync editor content for the new message
+        let msg_idx = session.messages.len() - 1;
+        let key = format!("{}:{}", index, msg_idx);
+        self.message_editors.insert(
+            key,
+            text_editor::Content::with_text(&session.messages[msg_idx].content),
+        );
 
-        match self.config.save() {
-            Ok(_) => {
-                if let Err(err) = self.dispatcher.update_backend(&self.config) {
-                    self.config_form
-                        .set_error(&format!("Saved, but backend failed to refresh: {err}"));
-                    return;
-                }
-                self.config_form = ConfigForm::from_config(&self.config);
-                self.config_form.set_success("Settings saved successfully!");
-            }
-            Err(err) => {
-                self.config_form
-                    .set_error(&format!("Failed to save settings: {err}"));
-            }
-        }
-    }
+        session.set_streaming(true);
 
-    fn subscription(&self) -> Subscription<Message> {
-        let stream = self.dispatcher.subscription().map(Message::Received);
-        let ticks = time::every(Duration::from_millis(TICK_INTERVAL_MS)).map(|_| Message::Tick);
-        Subscription::batch(vec![stream, ticks])
-    }
+        let session_config = SessionConfig {
+            system_prompt: build_enhanced_system_prompt(&self.config_form.system_prompt),
+            model: self.config.get_model(),
+            max_tokens: self.config_form.max_tokens as u32,
+            temperature: self.config_form.temperature,
+        };
 
-    fn view(&self) -> Element<'_, Message> {
-        let pal = palette_from_mode(self.theme_mode);
-        
-        // Debug: print current theme mode
-        static LAST_THEME: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(255);
-        let current_theme_id = match self.theme_mode {
-            ThemeMode::Light => 0,
-            ThemeMode::Dark => 1,
-            ThemeMode::Black => 2,
+        // Get conversation history for context (excluding the current prompt which is included separately)
+        let history = session.get_chat_history();
+        let history_opt = if history.is_empty() {
+            None
+        } else {
+            Some(history)
         };
-        if LAST_THEME.load(std::sync::atomic::Ordering::Relaxed) != current_theme_id {
-            println!("🎨 View rendering with theme: {:?}, background: {:?}", self.theme_mode, pal.background);
-            LAST_THEME.store(current_theme_id, std::sync::atomic::Ordering::Relaxed);
-        }
 
-        // Show error dialog if initialization failed
-        if let Some(ref error) = self.init_error {
-            return self.error_view(error, pal);
+        // Files mentioned as `@path` go to the model with this prompt only
+        let prompt = attach_mentions(&self.current_directory, &prompt);
+        if let Err(err) =
+            self.dispatcher
+                .start_stream(session.id, prompt, history_opt, session_config)
+        {
+            eprintln!("dispatch error: {err}");
+            session.set_streaming(false);
         }
+    }
 
-        let background = Canvas::new(LivingBackground::<Message>::new(
-            &self.bg_state,
-            pal,
-            self.bg_opacity,
-        ))
-        .width(Length::Fill)
-        .height(Length::Fill);
-
-        // Get current session streaming state for typing indicator
+    /// Scroll the chat to the message closest to `target` (e.g. "14:30" or "2h ago")
+    fn goto_message(&mut self, target: &str) -> Task<Message> {
+        let Some(time) = parse_time_target(target, Local::now()) else {
+            self.stream_error = Some(
+                "Usage: /goto <time> (e.g. 14:30, 2pm, yesterday 9:00, 2025-10-06, 2h ago)"
+                    .to_string(),
+            );
+            return Task::none();
+        };
         let session = &self.sessions[self.current];
-        let is_streaming = session.is_streaming;
-
+        let candidates: Vec<usize> = session
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.is_user() || m.is_ai())
+            .map(|(i, _)| i)
+            .collect();
+        let timestamps = candidates
+            .iter()
+            .map(|i| session.messages[*i].local_time().with_timezone(&Utc));
+        let Some(position) = closest_to(time.with_timezone(&Utc), timestamps) else {
+            return Task::none();
+        };
+
+        let index = candidates[position];
+        self.goto_highlight = Some((self.current, index, Instant::now()));
+        // The list is anchored to the bottom, so 0.0 is the newest message
+        let last = session.messages.len().saturating_sub(1).max(1);
+        let y = 1.0 - index as f32 / last as f32;
+        iced::widget::operation::snap_to(
+            chat_scroll_id(),
+            scrollable::RelativeOffset {
+                x: None,
+                y: Some(y),
+            },
+        )
+    }
+
+    /// The theme config.json asks for, resolving `system` to the OS scheme
+    fn configured_theme(&self) -> ArulaTheme {
+        if self.config.follows_system_theme() {
+            self.config.get_system_theme(self.system_dark)
+        } else {
+            self.config.get_theme()
+        }
+    }
+
+    /// Switch themes, fading from the palette currently on screen
+    fn switch_theme(&mut self, theme: ArulaTheme) {
+        if theme == self.theme {
+            return;
+        }
+        let shown = self.bg_state.palette(palette_for(&self.theme));
+        self.bg_state.fade_palette(shown);
+        self.theme = theme;
+    }
+
+    /// Apply a config.json change made outside the app
+    fn apply_config_reload(&mut self, reload: ConfigReload) {
+        let mut config = match reload {
+            ConfigReload::Loaded(config) => *config,
+            ConfigReload::Rejected(reason) => {
+                self.stream_error = Some(format!("config.json change not applied: {reason}"));
+                return;
+            }
+        };
+        if let Some(profile) = self.config.active_profile.as_deref()
+            && let Err(err) = config.use_profile(Some(profile))
+        {
+            self.stream_error = Some(format!("config.json change not applied: {err}"));
+            return;
+        }
+
+        let changes = describe_changes(&self.config, &config);
+        if changes.is_empty() {
+            return;
+        }
+        if provider_changed(&self.config, &config)
+            && let Err(err) = self.dispatcher.update_backend(&config)
+        {
+            self.stream_error = Some(format!("config.json change not applied: {err}"));
+            return;
+        }
+        if config.icons != self.config.icons {
+            set_icon_set(config.icons.unwrap_or(IconSet::Unicode));
+        }
+
+        self.config = config;
+        self.notifier.set_settings(self.config.get_notifications());
+        self.switch_theme(self.configured_theme());
+        self.config_form.refresh_from_config(&self.config);
+        self.config_notice = Some((
+            format!("⟳ Config reloaded: {}", changes.join(", ")),
+            Instant::now(),
+        ));
+    }
+
+    /// Reveals streaming AI messages a little more and re-parses the shown
+    /// part with the cursor. Messages that stopped streaming show in full.
+    fn update_typing(&mut self) {
+        let streaming: Vec<String> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .filter(|(_, session)| session.is_streaming)
+            .filter_map(|(idx, session)| {
+                let last = session.messages.len().checked_sub(1)?;
+                session.messages[last]
+                    .is_ai()
+                    .then(|| format!("{}:{}", idx, last))
+            })
+            .collect();
+
+        let finished: Vec<String> = self
+            .typing
+            .keys()
+            .filter(|key| !streaming.contains(key))
+            .cloned()
+            .collect();
+        for key in finished {
+            self.typing.remove(&key);
+            if let Some(message) = Self::message_for_key(&self.sessions, &key) {
+                let items = markdown::parse(&message.content).collect();
+                self.markdown_cache.insert(key, items);
+            }
+        }
+
+        for key in streaming {
+            let Some(message) = Self::message_for_key(&self.sessions, &key) else {
+                continue;
+            };
+            let typing = self.typing.entry(key.clone()).or_default();
+            if !typing.update(&message.content) {
+                continue;
+            }
+            let mut shown = typing.visible(&message.content).to_string();
+            if typing.cursor_visible() {
+                shown.push_str(TYPING_CURSOR);
+            }
+            let items = markdown::parse(&shown).collect();
+            self.markdown_cache.insert(key, items);
+        }
+    }
+
+    /// The message for a `session_index:message_index` key.
+    fn message_for_key<'a>(sessions: &'a [Session], key: &str) -> Option<&'a MessageEntry> {
+        let (session, message) = key.split_once(':')?;
+        sessions
+            .get(session.parse::<usize>().ok()?)?
+            .messages
+            .get(message.parse::<usize>().ok()?)
+    }
+
+    /// Returns the background to streaming or idle once a tool or stream ends.
+    /// An error keeps showing until it fades out on its own.
+    fn settle_background(&mut self) {
+        if self.sessions.iter().any(|s| s.is_streaming) {
+            self.bg_state.set_activity(AgentActivity::Streaming);
+        } else if self.bg_state.activity != AgentActivity::Error {
+            self.bg_state.set_activity(AgentActivity::Idle);
+        }
+    }
+
+    fn apply_config_changes(&mut self) {
+        let selected_provider = self.config_form.provider.clone();
+        if self.config.active_provider != selected_provider {
+            if let Err(err) = self.config.switch_provider(&selected_provider) {
+                self.config_form
+                    .set_error(&format!("Failed to switch provider: {err}"));
+                return;
+            }
+        }
+
+        // The model field also takes aliases from `model_aliases`
+        let (alias_provider, model) = self.config.resolve_model(&self.config_form.model);
+        if let Some(provider) = alias_provider
+            && provider != selected_provider
+        {
+            self.config_form.set_error(&format!(
+                "'{}' is a {provider} model; select that provider first",
+                self.config_form.model
+            ));
+            return;
+        }
+        self.config.set_model(&model);
+        self.config.set_api_url(&self.config_form.api_url);
+        self.config.set_api_key(&self.config_form.api_key);
+
+        if let Some(active) = self.config.get_active_provider_config_mut() {
+            active.thinking_enabled = Some(self.config_form.thinking_enabled);
+            active.web_search_enabled = Some(self.config_form.web_search_enabled);
+            active.tools_enabled = Some(self.config_form.ollama_tools_enabled);
+            active.streaming = Some(self.config_form.streaming_enabled);
+            active.temperature = Some(self.config_form.temperature);
+            // A top_p of 1.0 is the same as not sending one
+            active.top_p = (self.config_form.top_p < 1.0).then_some(self.config_form.top_p);
+            active.max_tokens = Some(self.config_form.max_tokens as u32);
+        }
+
+        // Save global settings
+        self.config.living_background_enabled = Some(self.config_form.living_background_enabled);
+        self.config.show_timestamps = Some(self.config_form.show_timestamps);
+
+        match self.config.save() {
+            Ok(_) => {
+                if let Err(err) = self.dispatcher.update_backend(&self.config) {
+                    self.config_form
+                        .set_error(&format!("Saved, but backend failed to refresh: {err}"));
+                    return;
+                }
+                self.config_form = ConfigForm::from_config(&self.config);
+                self.config_form.set_success("Settings saved successfully!");
+            }
+            Err(err) => {
+                self.config_form
+                    .set_error(&format!("Failed to save settings: {err}"));
+            }
+        }
+    }
+
+    /// React to the tray menu or the quick-ask hotkey
+    fn handle_tray_event(&mut self, event: TrayEvent) -> Task<Message> {
+        match event {
+            TrayEvent::QuickAsk => self.open_quick_ask(),
+            TrayEvent::Show if self.quick_ask.is_some() => self.leave_quick_ask(false),
+            TrayEvent::Show => window::latest().and_then(|id| {
+                Task::batch([window::minimize(id, false), window::gain_focus(id)])
+            }),
+            TrayEvent::Quit => self.request_quit(),
+        }
+    }
+
+    /// Whether the agent waits on the user: an unanswered question or a
+    /// paused run
+    fn waiting_on_user(&self) -> bool {
+        !self.pending_question_batches.is_empty() || self.loop_paused
+    }
+
+    /// Start or stop alerting the user when the agent begins or stops
+    /// waiting on them: a tray badge, and the window asks for attention
+    fn update_attention(&mut self) -> Task<Message> {
+        let waiting = self.waiting_on_user();
+        if waiting == self.attention {
+            return Task::none();
+        }
+        self.attention = waiting;
+        let settings = self.config.get_attention();
+        if settings.tray_badge()
+            && let Some(tray) = &self.tray
+        {
+            tray.set_attention(waiting);
+        }
+        if !settings.flash() {
+            return Task::none();
+        }
+        let request = waiting.then_some(window::UserAttention::Informational);
+        window::latest().and_then(move |id| window::request_user_attention(id, request))
+    }
+
+    /// Open a saved conversation in a new tab
+    fn open_saved_session(&mut self, id: uuid::Uuid, events: &[UiEvent], read_only: bool) -> Task<Message> {
+        // Create a new session from the loaded events
+        let mut new_session = Session::from_events(id, events);
+        new_session.read_only = read_only;
+
+        // Add the new session
+        self.sessions.push(new_session);
+        self.current = self.sessions.len() - 1;
+
+        // Close the conversations sidebar
+        self.show_conversations = false;
+
+        // Clear the draft
+        self.draft.clear();
+
+        // Focus the input
+        iced::widget::operation::focus(input_id())
+    }
+
+    /// Start recording, or stop the recording and transcribe it into the draft
+    fn toggle_voice(&mut self) -> Task<Message> {
+        let Some(recording) = self.voice.take() else {
+            match Recording::start(&self.config.get_voice()) {
+                Ok(recording) => self.voice = Some(recording),
+                Err(err) => self.stream_error = Some(format!("Couldn't start recording: {err}")),
+            }
+            return Task::none();
+        };
+        let audio = match recording.stop() {
+            Ok(audio) => audio,
+            Err(err) => {
+                self.stream_error = Some(format!("Recording failed: {err}"));
+                return Task::none();
+            }
+        };
+        self.transcribing = true;
+        let config = self.config.clone();
+        Task::perform(
+            async move {
+                voice::transcribe(&audio, &config)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::VoiceTranscribed,
+        )
+    }
+
+    /// Quit, asking first if a run would be cut off
+    fn request_quit(&mut self) -> Task<Message> {
+        if self.sessions.iter().any(|s| s.is_streaming) {
+            self.quit_prompt = true;
+            return window::latest().and_then(|id| {
+                Task::batch([window::minimize(id, false), window::gain_focus(id)])
+            });
+        }
+        self.quit()
+    }
+
+    /// Stop running requests, save every conversation, wait for database
+    /// writes in progress and exit
+    fn quit(&mut self) -> Task<Message> {
+        for session in &mut self.sessions {
+            if session.is_streaming {
+                self.dispatcher.stop_stream(session.id);
+                session.flush_ai_buffer(Utc::now().to_rfc3339());
+                session.set_streaming(false);
+            }
+            if session.messages.is_empty() || session.read_only {
+                continue;
+            }
+            let events = session.to_ui_events();
+            if let Err(err) =
+                self.conversation_manager
+                    .save_conversation(session.id, &events, self.config.get_model())
+            {
+                eprintln!("Failed to save conversation: {}", err);
+            }
+        }
+        Storage::flush();
+        iced::exit()
+    }
+
+    /// Shrink the window to the quick-ask panel, on top of other windows,
+    /// with a fresh session for the question
+    fn open_quick_ask(&mut self) -> Task<Message> {
+        if self.quick_ask.is_none() {
+            self.sessions.push(Session::new());
+            self.quick_ask = Some(QuickAsk {
+                session: self.sessions.len() - 1,
+                previous: self.current,
+            });
+            self.current = self.sessions.len() - 1;
+            self.draft.clear();
+        }
+        let size = Size::new(QUICK_ASK_SIZE.0, QUICK_ASK_SIZE.1);
+        window::latest()
+            .and_then(move |id| {
+                Task::batch([
+                    window::minimize(id, false),
+                    window::resize(id, size),
+                    window::set_level(id, window::Level::AlwaysOnTop),
+                    window::gain_focus(id),
+                ])
+            })
+            .chain(iced::widget::operation::focus(input_id()))
+    }
+
+    /// Hide the window, screenshot the monitor it was on and then show that
+    /// full screen to select a region on
+    fn open_region_picker(&mut self, action: RegionAction) -> Task<Message> {
+        if self.region_pick.is_some() || self.quick_ask.is_some() {
+            return Task::none();
+        }
+        self.region_pick = Some(RegionPick {
+            action,
+            snapshot: None,
+        });
+        window::latest().and_then(|id| {
+            window::position(id).then(move |position| {
+                let capture = Task::perform(
+                    ScreenSnapshot::take(position, Duration::from_millis(REGION_CAPTURE_DELAY_MS)),
+                    Message::RegionSnapshot,
+                );
+                window::minimize(id, true).chain(capture)
+            })
+        })
+    }
+
+    /// Leave region selection and bring the normal window back
+    fn close_region_picker(&mut self) -> Task<Message> {
+        self.region_pick = None;
+        window::latest().and_then(|id| {
+            Task::batch([
+                window::set_mode(id, window::Mode::Windowed),
+                window::minimize(id, false),
+                window::gain_focus(id),
+            ])
+        })
+    }
+
+    /// Restore the main window; `hide` minimizes it as well. A quick-ask
+    /// session that never got a question is dropped again.
+    fn leave_quick_ask(&mut self, hide: bool) -> Task<Message> {
+        let Some(quick) = self.quick_ask.take() else {
+            return Task::none();
+        };
+        let unused = self
+            .sessions
+            .get(quick.session)
+            .is_some_and(|s| s.messages.is_empty() && !s.is_streaming);
+        if unused && quick.session == self.sessions.len() - 1 && self.sessions.len() > 1 {
+            self.sessions.pop();
+            self.current = quick.previous.min(self.sessions.len() - 1);
+        }
+        let size = Size::new(MAIN_WINDOW_SIZE.0, MAIN_WINDOW_SIZE.1);
+        window::latest().and_then(move |id| {
+            let mut tasks = vec![
+                window::set_level(id, window::Level::Normal),
+                window::resize(id, size),
+            ];
+            tasks.push(if hide {
+                window::minimize(id, true)
+            } else {
+                window::gain_focus(id)
+            });
+            Task::batch(tasks)
+        })
+    }
+
+    /// Close the session at `index`, stopping its stream, and move the
+    /// cached UI state of later sessions down one index
+    fn close_session(&mut self, index: usize) {
+        if index >= self.sessions.len() || self.quick_ask.is_some() {
+            return;
+        }
+        let session = self.sessions.remove(index);
+        if session.is_streaming {
+            self.dispatcher.stop_stream(session.id);
+        }
+        if !self.sessions.iter().any(|s| s.id == session.id) {
+            self.conversation_manager.release(session.id);
+        }
+        self.tool_args_cache.remove(&session.id);
+        for tool_call_id in session.messages.iter().filter_map(|m| m.tool_call_id.as_ref()) {
+            self.bash_output_lines.remove(tool_call_id);
+            self.command_reports.remove(tool_call_id);
+        }
+        shift_session_keys(&mut self.message_editors, index);
+        shift_session_keys(&mut self.markdown_cache, index);
+        shift_session_keys(&mut self.typing, index);
+        shift_session_keys(&mut self.tool_animations, index);
+        self.goto_highlight = match self.goto_highlight {
+            Some((i, _, _)) if i == index => None,
+            Some((i, message, started)) if i > index => Some((i - 1, message, started)),
+            other => other,
+        };
+
+        if self.sessions.is_empty() {
+            self.sessions.push(Session::new());
+        }
+        if self.current > index {
+            self.current -= 1;
+        }
+        self.current = self.current.min(self.sessions.len() - 1);
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // App-wide events, plus one feed per open session
+        let app_events = self.dispatcher.subscription().map(Message::Received);
+        let sessions = self
+            .sessions
+            .iter()
+            .map(|s| self.dispatcher.session_subscription(s.id).map(Message::Received));
+        let ticks = time::every(Duration::from_millis(TICK_INTERVAL_MS)).map(|_| Message::Tick);
+        let shortcuts = keyboard::listen().filter_map(shortcut);
+        let window_events = window::events().filter_map(|(_, event)| match event {
+            window::Event::Focused => Some(Message::WindowFocused(true)),
+            window::Event::Unfocused => Some(Message::WindowFocused(false)),
+            window::Event::CloseRequested => Some(Message::CloseRequested),
+            _ => None,
+        });
+        Subscription::batch(
+            std::iter::once(app_events)
+                .chain(sessions)
+                .chain([ticks, shortcuts, window_events]),
+        )
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let pal = self.bg_state.palette(palette_for(&self.theme));
+
+        // Show error dialog if initialization failed
+        if let Some(ref error) = self.init_error {
+            return self.error_view(error, pal);
+        }
+
+        if let Some(quick) = self.quick_ask {
+            return self.quick_ask_view(quick, pal);
+        }
+
+        if let Some(snapshot) = self.region_pick.as_ref().and_then(|pick| pick.snapshot.as_ref()) {
+            return Canvas::new(RegionPicker::new(
+                snapshot,
+                pal,
+                Message::RegionPicked,
+                Message::CancelRegionPick,
+            ))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into();
+        }
+
+        if let Some(index) = self.presentation {
+            return self.presentation_view(index, pal);
+        }
+
+        // Zen mode trades the living background for a plain one
+        let background: Element<'_, Message> = if self.zen_mode {
+            container(Space::new())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(move |_| container::Style {
+                    background: Some(Background::Color(pal.background)),
+                    ..Default::default()
+                })
+                .into()
+        } else {
+            Canvas::new(LivingBackground::<Message>::new(
+                &self.bg_state,
+                pal,
+                self.bg_opacity,
+            ))
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+        };
+
+        // Get current session streaming state for typing indicator
+        let session = &self.sessions[self.current];
+        let is_streaming = session.is_streaming;
+
         // Calculate width offset for top bar and input bar based on layout offset (smooth animation)
         let sidebar_width = 340.0 * self.conversations_layout_offset;
 
         // Build main layer with top bar, chat content, optional typing indicator, and input
-        let mut main_content: Vec<Element<'_, Message>> = vec![self.top_bar(pal, sidebar_width)];
+        let mut main_content: Vec<Element<'_, Message>> = Vec::new();
+        if self.zen_mode {
+            main_content.push(self.zen_bar(pal));
+        } else {
+            main_content.push(self.top_bar(pal, sidebar_width));
+            if self.sessions.len() > 1 {
+                main_content.push(self.session_tabs(pal, sidebar_width));
+            }
+        }
         main_content.push(self.chat_panel(pal));
 
         // Add typing indicator above input when streaming
@@ -1459,7 +2650,17 @@ impl App {
             main_content.push(self.typing_indicator(pal));
         }
 
-        main_content.push(self.input_area(pal, sidebar_width));
+        if self.zen_mode {
+            main_content.push(
+                container(
+                    container(self.input_area(pal, 0.0)).max_width(ZEN_CONTENT_WIDTH + 48.0),
+                )
+                .center_x(Length::Fill)
+                .into(),
+            );
+        } else {
+            main_content.push(self.input_area(pal, sidebar_width));
+        }
 
         let main_layer = column(main_content)
             .width(Length::Fill)
@@ -1617,6 +2818,7 @@ impl App {
         };
 
         let directory_popup = self.directory_popup(pal);
+        let architecture_panel = self.architecture_panel(pal);
         let conversations_sidebar = self.conversations_sidebar(pal);
 
         // Add backdrop overlay for conversations sidebar
@@ -1639,15 +2841,31 @@ impl App {
             Space::new().into()
         };
 
-        let content = stack(vec![
-            background.into(),
-            main_layer.into(),
-            overlay,
-            conversations_backdrop, // Add backdrop behind conversations sidebar
-            directory_popup,
-            conversations_sidebar,
-            error_overlay,
-        ]);
+        let quit_prompt = self.quit_prompt_overlay(pal);
+        let read_only_prompt = self.read_only_prompt_overlay(pal);
+        let content = if self.zen_mode {
+            stack(vec![
+                background,
+                main_layer.into(),
+                overlay,
+                error_overlay,
+                quit_prompt,
+                read_only_prompt,
+            ])
+        } else {
+            stack(vec![
+                background,
+                main_layer.into(),
+                overlay,
+                conversations_backdrop, // Add backdrop behind conversations sidebar
+                directory_popup,
+                architecture_panel,
+                conversations_sidebar,
+                error_overlay,
+                quit_prompt,
+                read_only_prompt,
+            ])
+        };
         container(content)
             .width(Length::Fill)
             .height(Length::Fill)
@@ -1683,6 +2901,169 @@ impl App {
         .into()
     }
 
+    /// Compact panel shown while quick ask is open: an input, and the answer
+    /// as a card below it that can be opened in the full window
+    fn quick_ask_view(&self, quick: QuickAsk, pal: PaletteColors) -> Element<'_, Message> {
+        let session = &self.sessions[quick.session];
+        let input = text_input("Ask ARULA...", &self.draft)
+            .id(input_id())
+            .on_input(Message::DraftChanged)
+            .on_submit(Message::SendPrompt)
+            .padding([12, 8])
+            .style(chat_input_style(pal))
+            .width(Length::Fill);
+
+        let icon_button = |icon: iced::widget::Text<'static>, message: Message| {
+            button(icon.size(14))
+                .on_press(message)
+                .padding([6, 8])
+                .style(move |_, status| button::Style {
+                    background: None,
+                    text_color: if matches!(status, button::Status::Hovered) {
+                        pal.accent
+                    } else {
+                        pal.muted
+                    },
+                    ..Default::default()
+                })
+        };
+        let header = row![
+            text("Quick ask").size(13).color(pal.muted),
+            Space::new().width(Length::Fill),
+            icon_button(bootstrap::arrows_angle_expand(), Message::ExpandQuickAsk),
+            icon_button(bootstrap::x_lg(), Message::DismissQuickAsk),
+        ]
+        .align_y(iced::Alignment::Center);
+
+        // Latest answer, rendered like in the chat
+        let answer = session
+            .messages
+            .iter()
+            .rposition(|m| m.is_ai())
+            .map(|index| {
+                let key = format!("{}:{}", quick.session, index);
+                let body: Element<'_, Message> = match self.markdown_cache.get(&key) {
+                    Some(items) => markdown_view::view(items, pal).map(|action| match action {
+                        MarkdownAction::OpenLink(url) => Message::LinkClicked(url),
+                        MarkdownAction::Copy(code) => Message::CopyToClipboard(code),
+                    }),
+                    None => text(&session.messages[index].content).size(14).into(),
+                };
+                body
+            });
+        let status: Element<'_, Message> = match answer {
+            Some(body) => container(scrollable(body).height(Length::Fill))
+                .padding(12)
+                .height(Length::Fill)
+                .style(move |_| container::Style {
+                    background: Some(Background::Color(pal.surface)),
+                    border: Border {
+                        color: pal.border,
+                        width: 1.0,
+                        radius: 12.0.into(),
+                    },
+                    ..Default::default()
+                })
+                .into(),
+            None if session.is_streaming => text("Thinking...").size(13).color(pal.muted).into(),
+            None => text("Enter to ask · ⤢ opens the full window")
+                .size(12)
+                .color(pal.muted)
+                .into(),
+        };
+
+        container(column![header, input, status].spacing(10))
+            .padding(14)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(move |_| container::Style {
+                background: Some(Background::Color(pal.background)),
+                ..Default::default()
+            })
+            .into()
+    }
+
+    /// Tabs for the open sessions, shown when there's more than one
+    fn session_tabs(&self, pal: PaletteColors, sidebar_width: f32) -> Element<'_, Message> {
+        let tabs = self.sessions.iter().enumerate().map(|(index, session)| {
+            let active = index == self.current;
+            let title: String = session.get_title().chars().take(24).collect();
+            let mut label = row![].spacing(6).align_y(iced::Alignment::Center);
+            if session.is_streaming {
+                label = label.push(bootstrap::circle_fill().size(7).color(pal.accent));
+            }
+            label = label
+                .push(text(title).size(12).color(if active { pal.text } else { pal.muted }))
+                .push(
+                    button(bootstrap::x_lg().size(10))
+                        .on_press(Message::CloseSession(index))
+                        .padding([2, 3])
+                        .style(move |_, status| button::Style {
+                            background: None,
+                            text_color: if matches!(status, button::Status::Hovered) {
+                                pal.text
+                            } else {
+                                Color { a: 0.5, ..pal.muted }
+                            },
+                            ..Default::default()
+                        }),
+                );
+            button(label)
+                .on_press(Message::SelectSession(index))
+                .padding([5, 10])
+                .style(move |_, status| {
+                    let hovered = matches!(status, button::Status::Hovered);
+                    button::Style {
+                        background: Some(Background::Color(Color {
+                            a: if active { 0.18 } else if hovered { 0.08 } else { 0.0 },
+                            ..pal.accent
+                        })),
+                        border: Border {
+                            radius: 10.0.into(),
+                            width: 1.0,
+                            color: Color {
+                                a: if active { 0.35 } else { 0.0 },
+                                ..pal.accent
+                            },
+                        },
+                        text_color: pal.text,
+                        ..Default::default()
+                    }
+                })
+                .into()
+        });
+        let new_tab = button(bootstrap::plus().size(14).color(pal.muted))
+            .on_press(Message::NewTab)
+            .padding([4, 8])
+            .style(move |_, status| button::Style {
+                background: Some(Background::Color(Color {
+                    a: if matches!(status, button::Status::Hovered) { 0.12 } else { 0.0 },
+                    ..pal.accent
+                })),
+                border: Border {
+                    radius: 10.0.into(),
+                    ..Default::default()
+                },
+                text_color: pal.muted,
+                ..Default::default()
+            });
+
+        container(
+            scrollable(row(tabs).push(new_tab).spacing(4).align_y(iced::Alignment::Center))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                )),
+        )
+        .padding(iced::Padding {
+            top: 0.0,
+            right: 16.0,
+            bottom: 4.0,
+            left: 16.0 + sidebar_width,
+        })
+        .width(Length::Fill)
+        .into()
+    }
+
     fn top_bar(&self, pal: PaletteColors, sidebar_width: f32) -> Element<'_, Message> {
         // ─────────────────────────────────────────────────────────────────
         // LEFT SIDE: Navigation buttons (icon-based for clean look)
@@ -1814,6 +3195,36 @@ impl App {
             }
         });
 
+        // Active configuration profile, if any
+        let profile_badge = self.config.active_profile.as_ref().map(|profile| {
+            container(
+                row![
+                    bootstrap::person()
+                        .size(14)
+                        .style(move |_| iced::widget::text::Style {
+                            color: Some(pal.accent)
+                        }),
+                    Space::new().width(Length::Fixed(6.0)),
+                    text(profile.clone())
+                        .size(12)
+                        .style(move |_| iced::widget::text::Style {
+                            color: Some(pal.text)
+                        }),
+                ]
+                .align_y(iced::Alignment::Center),
+            )
+            .padding([8, 12])
+            .style(move |_| container::Style {
+                background: Some(Background::Color(Color { a: 0.12, ..pal.accent })),
+                border: Border {
+                    radius: 12.0.into(),
+                    width: 1.0,
+                    color: Color { a: 0.3, ..pal.accent },
+                },
+                ..Default::default()
+            })
+        });
+
         // ─────────────────────────────────────────────────────────────────
         // RIGHT SIDE: Optional AI Initialize button
         // ─────────────────────────────────────────────────────────────────
@@ -1860,24 +3271,133 @@ impl App {
                 })
                 .into()
             )
-        } else {
-            None
-        };
-
-        // ─────────────────────────────────────────────────────────────────
-        // TOP BAR LAYOUT: Glassmorphism container
-        // ─────────────────────────────────────────────────────────────────
+        } else {
+            None
+        };
+
+        // ─────────────────────────────────────────────────────────────────
+        // TOP BAR LAYOUT: Glassmorphism container
+        // ─────────────────────────────────────────────────────────────────
+
+        let mut top_row = row![
+            left_buttons,
+            Space::new().width(Length::Fixed(12.0)),
+            directory_button,
+        ]
+        .align_y(iced::Alignment::Center);
+
+        if let Some(badge) = profile_badge {
+            top_row = top_row
+                .push(Space::new().width(Length::Fixed(8.0)))
+                .push(badge);
+        }
+        
+        // Push spacer and optional AI button to right
+        top_row = top_row.push(Space::new().width(Length::Fill));
+
+        if self.detected_project.is_some() {
+            let architecture_active = self.show_architecture;
+            let architecture_button = button(
+                container(
+                    bootstrap::diagram_three()
+                        .size(18)
+                        .style(move |_| iced::widget::text::Style {
+                            color: Some(if architecture_active { pal.accent } else { pal.muted })
+                        })
+                )
+                .width(Length::Fixed(36.0))
+                .height(Length::Fixed(36.0))
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center)
+            )
+            .on_press(Message::ToggleArchitecture)
+            .padding(0)
+            .style(move |_theme, status| {
+                let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+                iced::widget::button::Style {
+                    background: Some(Background::Color(Color {
+                        a: if architecture_active { 0.25 } else if is_hovered { 0.15 } else { 0.0 },
+                        ..pal.accent
+                    })),
+                    border: Border {
+                        radius: 10.0.into(),
+                        ..Default::default()
+                    },
+                    text_color: pal.muted,
+                    ..Default::default()
+                }
+            });
+            top_row = top_row
+                .push(architecture_button)
+                .push(Space::new().width(Length::Fixed(8.0)));
+        }
+        
+        let zen_button = button(
+            container(
+                bootstrap::arrows_fullscreen()
+                    .size(16)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    })
+            )
+            .width(Length::Fixed(36.0))
+            .height(Length::Fixed(36.0))
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+        )
+        .on_press(Message::ToggleZenMode)
+        .padding(0)
+        .style(move |_theme, status| {
+            let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+            iced::widget::button::Style {
+                background: Some(Background::Color(Color {
+                    a: if is_hovered { 0.15 } else { 0.0 },
+                    ..pal.accent
+                })),
+                border: Border {
+                    radius: 10.0.into(),
+                    ..Default::default()
+                },
+                text_color: pal.muted,
+                ..Default::default()
+            }
+        });
+        let present_button = button(
+            container(
+                bootstrap::easel()
+                    .size(16)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    })
+            )
+            .width(Length::Fixed(36.0))
+            .height(Length::Fixed(36.0))
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+        )
+        .on_press(Message::TogglePresentation)
+        .padding(0)
+        .style(move |_theme, status| {
+            let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+            iced::widget::button::Style {
+                background: Some(Background::Color(Color {
+                    a: if is_hovered { 0.15 } else { 0.0 },
+                    ..pal.accent
+                })),
+                border: Border {
+                    radius: 10.0.into(),
+                    ..Default::default()
+                },
+                text_color: pal.muted,
+                ..Default::default()
+            }
+        });
+        top_row = top_row
+            .push(zen_button)
+            .push(Space::new().width(Length::Fixed(8.0)))
+            .push(present_button)
+            .push(Space::new().width(Length::Fixed(8.0)));
 
-        let mut top_row = row![
-            left_buttons,
-            Space::new().width(Length::Fixed(12.0)),
-            directory_button,
-        ]
-        .align_y(iced::Alignment::Center);
-        
-        // Push spacer and optional AI button to right
-        top_row = top_row.push(Space::new().width(Length::Fill));
-        
         if let Some(ai_btn) = init_ai_button {
             top_row = top_row.push(ai_btn);
         }
@@ -2395,6 +3915,197 @@ impl App {
         .into()
     }
 
+    /// Creates the architecture map overlay for the current project
+    fn architecture_panel(&self, pal: PaletteColors) -> Element<'_, Message> {
+        if !self.show_architecture {
+            return Space::new().into();
+        }
+
+        let panel_button = |label: &'static str, message: Option<Message>| {
+            button(
+                text(label)
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.text)
+                    }),
+            )
+            .on_press_maybe(message)
+            .padding([6, 12])
+            .style(move |_theme, status| {
+                let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+                button::Style {
+                    background: Some(Background::Color(Color {
+                        a: if is_hovered { 0.25 } else { 0.12 },
+                        ..pal.accent
+                    })),
+                    border: Border {
+                        radius: 8.0.into(),
+                        width: 1.0,
+                        color: Color { a: 0.3, ..pal.accent },
+                    },
+                    text_color: pal.text,
+                    ..Default::default()
+                }
+            })
+        };
+
+        let has_map = self.architecture_map.is_some();
+        let loading = self.architecture_loading;
+        let summary = match self.architecture_map {
+            Some(ref map) => format!(
+                "{} · {} nodes · {} dependencies",
+                map.project_name,
+                map.nodes.len(),
+                map.edges.len()
+            ),
+            None if loading => "Scanning project...".to_string(),
+            None => "No map generated".to_string(),
+        };
+
+        let header = row![
+            bootstrap::diagram_three()
+                .size(16)
+                .style(move |_| iced::widget::text::Style {
+                    color: Some(pal.accent)
+                }),
+            Space::new().width(Length::Fixed(8.0)),
+            column![
+                text("Architecture")
+                    .size(14)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.text)
+                    }),
+                text(summary)
+                    .size(11)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+            ],
+            Space::new().width(Length::Fill),
+            panel_button("Refresh", (!loading).then_some(Message::RefreshArchitecture)),
+            Space::new().width(Length::Fixed(6.0)),
+            panel_button(
+                "Save .mmd",
+                has_map.then_some(Message::SaveArchitecture(DiagramFormat::Mermaid))
+            ),
+            Space::new().width(Length::Fixed(6.0)),
+            panel_button(
+                "Save .dot",
+                has_map.then_some(Message::SaveArchitecture(DiagramFormat::Graphviz))
+            ),
+            Space::new().width(Length::Fixed(8.0)),
+            button(
+                bootstrap::x_lg()
+                    .size(14)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    })
+            )
+            .on_press(Message::ToggleArchitecture)
+            .padding(4)
+            .style(move |_theme, status| {
+                let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+                button::Style {
+                    background: Some(Background::Color(Color {
+                        a: if is_hovered { 0.2 } else { 0.0 },
+                        ..pal.muted
+                    })),
+                    border: Border {
+                        radius: 4.0.into(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }),
+        ]
+        .align_y(iced::Alignment::Center);
+
+        let body: Element<'_, Message> = if has_map {
+            let size = self.architecture_layout.size;
+            scrollable(
+                container(
+                    Canvas::new(ArchitectureGraph::new(&self.architecture_layout, pal))
+                        .width(Length::Fixed(size.width))
+                        .height(Length::Fixed(size.height)),
+                )
+                .width(Length::Fill)
+                .align_x(Horizontal::Center),
+            )
+            .height(Length::Fill)
+            .into()
+        } else if !loading {
+            container(
+                text("Press Refresh to scan the project")
+                    .size(13)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+            .into()
+        } else {
+            container(
+                Canvas::new(LoadingSpinner::new(SpinnerState {
+                    tick: self.spinner_state.tick,
+                    spinner_type: SpinnerType::Orbital,
+                    size: 24.0,
+                    color: pal.muted,
+                    accent_color: pal.accent,
+                }))
+                .width(Length::Fixed(60.0))
+                .height(Length::Fixed(60.0)),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+            .into()
+        };
+
+        let mut panel_content = column![header, Space::new().height(Length::Fixed(12.0)), body];
+        if let Some(ref status) = self.architecture_status {
+            panel_content = panel_content.push(
+                text(status.as_str())
+                    .size(11)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+            );
+        }
+
+        let panel = container(panel_content.spacing(4).padding(16))
+            .width(Length::Fixed(920.0))
+            .height(Length::Fill)
+            .style(move |_| container::Style {
+                background: Some(Background::Color(Color {
+                    a: 0.95,
+                    ..pal.background
+                })),
+                border: Border {
+                    radius: 16.0.into(),
+                    width: 1.0,
+                    color: Color { a: 0.4, ..pal.border },
+                },
+                ..Default::default()
+            });
+
+        // Centered below the top bar
+        container(panel)
+            .padding(iced::padding::Padding {
+                top: 70.0,
+                right: 24.0,
+                bottom: 90.0,
+                left: 24.0,
+            })
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Horizontal::Center)
+            .into()
+    }
+
     /// Creates the conversations sidebar - modern relaxing design
     /// Animations: Staggered Cascade (opacity), Content Parallax (timing), Glow Reveal
     /// Uses SLIDE ANIMATION - sidebar stays full width, slides from off-screen (no squishing!)
@@ -2756,9 +4467,10 @@ impl App {
         let session = &self.sessions[self.current];
 
         if session.messages.is_empty() && !session.is_streaming {
-            // Build starter boxes if available
+            // Build starter boxes if available; zen mode leaves them out
             let starters: Vec<Element<'_, Message>> = self.conversation_starters
                 .iter()
+                .filter(|_| !self.zen_mode)
                 .map(|starter| {
                     button(
                         text(starter)
@@ -2829,38 +4541,443 @@ impl App {
             )
             .width(Length::Fill)
             .height(Length::Fill)
-            .align_x(Horizontal::Center)
-            .align_y(Vertical::Center)
-            .into();
-        }
+            .align_x(Horizontal::Center)
+            .align_y(Vertical::Center)
+            .into();
+        }
+
+        // Build message list, with a day separator wherever the date changes.
+        // Zen mode splits off everything before the latest exchange to dim it.
+        let latest_exchange = if self.zen_mode {
+            session.messages.iter().rposition(|m| m.is_user())
+        } else {
+            None
+        };
+        let today = Local::now().date_naive();
+        let mut last_date = None;
+        let mut earlier: Vec<Element<'_, Message>> = Vec::new();
+        let mut messages: Vec<Element<'_, Message>> = Vec::with_capacity(session.messages.len());
+        for (idx, msg) in session.messages.iter().enumerate() {
+            if Some(idx) == latest_exchange {
+                earlier = std::mem::take(&mut messages);
+            }
+            let date = msg.local_time().date_naive();
+            if last_date != Some(date) {
+                last_date = Some(date);
+                messages.push(Self::day_separator(day_label(date, today), pal));
+            }
+            messages.push(self.message_bubble(idx, msg, pal));
+        }
+        for (index, prompt) in session.queued_prompts.iter().enumerate() {
+            messages.push(Self::queued_bubble(index, prompt, pal));
+        }
+        if let Some((notice, posted)) = &self.config_notice
+            && posted.elapsed().as_secs_f32() < CONFIG_NOTICE_SECS
+        {
+            messages.push(Self::day_separator(notice.clone(), pal));
+        }
+
+        let mut list = column(messages).spacing(16); // Tighter spacing between messages
+        if !earlier.is_empty() {
+            list = column![Self::dimmed(column(earlier).spacing(16).into(), pal), list].spacing(16);
+        }
+        let list: Element<'_, Message> = if self.zen_mode {
+            container(list.padding(24))
+                .max_width(ZEN_CONTENT_WIDTH)
+                .center_x(Length::Fill)
+                .into()
+        } else {
+            list.padding(24).into()
+        };
+
+        // Create scrollable - always anchor to bottom to prevent scroll jumping
+        // when markdown rerenders or streaming ends
+        scrollable(list)
+        .id(chat_scroll_id())
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .anchor_bottom() // Always anchor to bottom like a chat app
+        .into()
+    }
+
+    /// Asks whether to quit while a run is active
+    fn quit_prompt_overlay(&self, pal: PaletteColors) -> Element<'_, Message> {
+        if !self.quit_prompt {
+            return Space::new().into();
+        }
+        Self::prompt_dialog(
+            pal,
+            "Unsaved work",
+            "A response is still running. Quitting stops it; the conversation so far is saved."
+                .to_string(),
+            ("Keep working", Message::CancelQuit),
+            ("Quit", Message::ConfirmQuit, pal.danger),
+        )
+    }
+
+    /// Asks whether to open a conversation another instance has open
+    fn read_only_prompt_overlay(&self, pal: PaletteColors) -> Element<'_, Message> {
+        let Some((_, holder)) = &self.read_only_offer else {
+            return Space::new().into();
+        };
+        Self::prompt_dialog(
+            pal,
+            "Session in use elsewhere",
+            format!(
+                "This conversation is open in another ARULA instance ({}). Open it read-only? Nothing you do in it will be saved.",
+                holder
+            ),
+            ("Cancel", Message::CancelReadOnly),
+            ("Open read-only", Message::OpenReadOnly, pal.accent),
+        )
+    }
+
+    /// A modal question with a cancel and a confirm button
+    fn prompt_dialog<'a>(
+        pal: PaletteColors,
+        title: &'static str,
+        body: String,
+        cancel: (&'static str, Message),
+        confirm: (&'static str, Message, Color),
+    ) -> Element<'a, Message> {
+        let action = move |label: &'static str, message: Message, color: Color| {
+            button(text(label).size(13))
+                .on_press(message)
+                .padding([6, 14])
+                .style(move |_theme, status| button::Style {
+                    background: Some(Background::Color(Color {
+                        a: if status == button::Status::Hovered { 0.35 } else { 0.2 },
+                        ..color
+                    })),
+                    border: Border {
+                        radius: 6.0.into(),
+                        width: 1.0,
+                        color,
+                    },
+                    text_color: pal.text,
+                    ..Default::default()
+                })
+        };
+        let dialog = container(
+            column![
+                text(title).size(16).style(move |_| iced::widget::text::Style {
+                    color: Some(pal.text)
+                }),
+                text(body)
+                    .size(13)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+                row![
+                    Space::new().width(Length::Fill),
+                    action(cancel.0, cancel.1, pal.border),
+                    action(confirm.0, confirm.1, confirm.2),
+                ]
+                .spacing(8),
+            ]
+            .spacing(12),
+        )
+        .padding(20)
+        .max_width(420.0)
+        .style(move |_| container::Style {
+            background: Some(Background::Color(pal.surface_raised)),
+            border: Border {
+                radius: 10.0.into(),
+                width: 1.0,
+                color: pal.border,
+            },
+            ..Default::default()
+        });
+        container(dialog)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x(Length::Fill)
+            .center_y(Length::Fill)
+            .style(move |_| container::Style {
+                background: Some(Background::Color(Color {
+                    a: 0.45,
+                    ..Color::BLACK
+                })),
+                ..Default::default()
+            })
+            .into()
+    }
+
+    /// Veils `content` with the background color so it recedes.
+    fn dimmed<'a>(content: Element<'a, Message>, pal: PaletteColors) -> Element<'a, Message> {
+        let veil = container(Space::new())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(move |_| container::Style {
+                background: Some(Background::Color(Color {
+                    a: ZEN_DIM_ALPHA,
+                    ..pal.background
+                })),
+                ..Default::default()
+            });
+        stack(vec![content, veil.into()]).into()
+    }
 
-        // Build message list
-        let messages: Vec<Element<'_, Message>> = session
+    /// Indices of the user and AI messages of the current session, the ones
+    /// shown when presenting.
+    fn presentable_messages(&self) -> Vec<usize> {
+        self.sessions[self.current]
             .messages
             .iter()
             .enumerate()
-            .map(|(idx, msg)| self.message_bubble(idx, msg, pal))
-            .collect();
+            .filter(|(_, m)| (m.is_user() || m.is_ai()) && !m.content.trim().is_empty())
+            .map(|(i, _)| i)
+            .collect()
+    }
 
-        // Create scrollable - always anchor to bottom to prevent scroll jumping
-        // when markdown rerenders or streaming ends
-        scrollable(
-            column(messages)
-                .spacing(16) // Tighter spacing between messages
-                .padding(24),
+    /// Message `index` of the current session alone on the screen at a large
+    /// size, with its position and a way out.
+    fn presentation_view(&self, index: usize, pal: PaletteColors) -> Element<'_, Message> {
+        let session = &self.sessions[self.current];
+        let Some(message) = session.messages.get(index) else {
+            return Space::new().into();
+        };
+        let slides = self.presentable_messages();
+        let position = slides.iter().position(|&i| i == index).unwrap_or(0);
+
+        let exit = button(
+            row![
+                text("Esc").size(11).color(Color { a: 0.6, ..pal.muted }),
+                bootstrap::x_lg().size(14),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center),
         )
-        .height(Length::Fill)
+        .on_press(Message::EndPresentation)
+        .padding([6, 10])
+        .style(move |_, status| button::Style {
+            background: None,
+            text_color: if matches!(status, button::Status::Hovered) {
+                pal.text
+            } else {
+                Color { a: 0.5, ..pal.muted }
+            },
+            ..Default::default()
+        });
+        let header = row![
+            text(format!("{} / {}", position + 1, slides.len()))
+                .size(14)
+                .color(pal.muted),
+            Space::new().width(Length::Fill),
+            exit,
+        ]
+        .align_y(iced::Alignment::Center)
+        .padding([8, 16]);
+
+        let speaker = if message.is_user() {
+            text("You").size(20).color(pal.accent)
+        } else {
+            text("Arula").size(20).color(pal.muted)
+        };
+        let key = format!("{}:{}", self.current, index);
+        let body: Element<'_, Message> = match self.markdown_cache.get(&key) {
+            Some(items) if message.is_ai() => {
+                markdown_view::view_with_size(items, pal, PRESENTATION_TEXT_SIZE).map(
+                    |action| match action {
+                        MarkdownAction::OpenLink(url) => Message::LinkClicked(url),
+                        MarkdownAction::Copy(code) => Message::CopyToClipboard(code),
+                    },
+                )
+            }
+            _ => text(&message.content)
+                .size(PRESENTATION_TEXT_SIZE)
+                .line_height(1.4)
+                .color(pal.text)
+                .into(),
+        };
+        let slide = container(column![speaker, body].spacing(24))
+            .max_width(PRESENTATION_CONTENT_WIDTH)
+            .padding([32, 48]);
+
+        let footer = container(
+            text("← → step through messages · F5 or Esc to stop")
+                .size(12)
+                .color(Color { a: 0.6, ..pal.muted }),
+        )
+        .center_x(Length::Fill)
+        .padding(12);
+
+        container(column![
+            header,
+            scrollable(container(slide).center_x(Length::Fill)).height(Length::Fill),
+            footer,
+        ])
         .width(Length::Fill)
-        .anchor_bottom() // Always anchor to bottom like a chat app
+        .height(Length::Fill)
+        .style(move |_| container::Style {
+            background: Some(Background::Color(pal.background)),
+            ..Default::default()
+        })
+        .into()
+    }
+
+    /// The only chrome left in zen mode: a quiet way back out.
+    fn zen_bar(&self, pal: PaletteColors) -> Element<'_, Message> {
+        let exit = button(
+            row![
+                text("F11").size(11).color(Color { a: 0.6, ..pal.muted }),
+                bootstrap::fullscreen_exit().size(14),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center),
+        )
+        .on_press(Message::ToggleZenMode)
+        .padding([6, 10])
+        .style(move |_, status| button::Style {
+            background: None,
+            text_color: if matches!(status, button::Status::Hovered) {
+                pal.text
+            } else {
+                Color { a: 0.5, ..pal.muted }
+            },
+            ..Default::default()
+        });
+        container(row![Space::new().width(Length::Fill), exit])
+            .padding([8, 16])
+            .width(Length::Fill)
+            .into()
+    }
+
+    /// Project files matching the `@path` at the end of the draft, as
+    /// buttons that complete it
+    fn mention_suggestions(&self, pal: PaletteColors) -> Option<Element<'_, Message>> {
+        let mention = mention_at(&self.draft, self.draft.chars().count())?;
+        let files = FileIndex::shared(&self.current_directory).complete(&mention.query, 6);
+        if files.is_empty() {
+            return None;
+        }
+        let chips = files.into_iter().map(|path| {
+            button(
+                row![
+                    bootstrap::file_earmark_text().size(12),
+                    text(format!("@{}", path)).size(12),
+                ]
+                .spacing(6)
+                .align_y(iced::Alignment::Center),
+            )
+            .on_press(Message::CompleteMention(path))
+            .padding([4, 10])
+            .style(move |_, status| button::Style {
+                background: Some(Background::Color(Color {
+                    a: if matches!(status, button::Status::Hovered) { 0.3 } else { 0.12 },
+                    ..pal.accent
+                })),
+                text_color: pal.text,
+                border: Border {
+                    radius: 12.0.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .into()
+        });
+        Some(
+            scrollable(row(chips).spacing(6))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Scrollbar::new().width(2).scroller_width(2),
+                ))
+                .width(Length::Fill)
+                .into(),
+        )
+    }
+
+    /// Creates a dimmed user bubble for a prompt waiting on the current turn.
+    fn queued_bubble<'a>(index: usize, prompt: &'a str, pal: PaletteColors) -> Element<'a, Message> {
+        let remove = button(bootstrap::x_lg().size(10))
+            .on_press(Message::RemoveQueuedPrompt(index))
+            .padding([2, 4])
+            .style(move |_, status| button::Style {
+                background: None,
+                text_color: if matches!(status, button::Status::Hovered) {
+                    pal.text
+                } else {
+                    Color { a: 0.6, ..pal.muted }
+                },
+                ..Default::default()
+            });
+        let status = row![
+            bootstrap::clock_history().size(10).color(pal.muted),
+            text(if index == 0 { "Queued · next" } else { "Queued" })
+                .size(10)
+                .color(pal.muted),
+            Space::new().width(Length::Fill),
+            remove,
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center);
+
+        let base_style = user_bubble_style(pal);
+        let bubble = container(
+            column![
+                text(prompt)
+                    .size(16)
+                    .line_height(1.5)
+                    .color(Color { a: 0.6, ..pal.text }),
+                status,
+            ]
+            .spacing(6),
+        )
+        .padding(16)
+        .max_width(MESSAGE_MAX_WIDTH)
+        .style(move |t| {
+            let style = base_style(t);
+            container::Style {
+                background: Some(Background::Color(Color { a: 0.08, ..pal.accent })),
+                border: Border {
+                    color: Color { a: 0.3, ..style.border.color },
+                    ..style.border
+                },
+                ..style
+            }
+        });
+        row![Space::new().width(Length::Fill), bubble].into()
+    }
+
+    /// Creates a centered label between messages ("Today", "Yesterday", a
+    /// date, or a notice such as a config reload).
+    fn day_separator<'a>(label: String, pal: PaletteColors) -> Element<'a, Message> {
+        let rule = move || {
+            container(Space::new().height(Length::Fixed(1.0)))
+                .width(Length::Fill)
+                .style(move |_| container::Style {
+                    background: Some(Background::Color(Color { a: 0.3, ..pal.border })),
+                    ..Default::default()
+                })
+        };
+        row![
+            rule(),
+            text(label)
+                .size(11)
+                .style(move |_| iced::widget::text::Style {
+                    color: Some(pal.muted)
+                }),
+            rule(),
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center)
         .into()
     }
 
     /// Creates an animated typing indicator for AI responses.
     fn typing_indicator(&self, pal: PaletteColors) -> Element<'_, Message> {
-        // Create a loading spinner with orbital animation
+        // Orbital animation, unless a spinner pack is configured
+        let spinner_type = match self
+            .config
+            .appearance
+            .as_ref()
+            .filter(|a| a.spinner.is_some())
+        {
+            Some(appearance) => SpinnerType::Frames(appearance.spinner()),
+            None => SpinnerType::Orbital,
+        };
         let spinner = Canvas::new(LoadingSpinner::new(SpinnerState {
             tick: self.spinner_state.tick,
-            spinner_type: SpinnerType::Orbital,
+            spinner_type,
             size: 12.0,
             color: pal.accent,
             accent_color: Color {
@@ -2954,13 +5071,11 @@ impl App {
             let md_items = self.markdown_cache.get(&key);
 
             if let Some(items) = md_items {
-                // Render cached markdown
-                markdown::view(
-                    items,
-                    markdown::Settings::with_style(markdown::Style::from_palette(iced::Theme::TokyoNightStorm.palette())),
-                )
-                .map(Message::LinkClicked)
-                .into()
+                // Render cached markdown; code blocks carry their own copy buttons
+                markdown_view::view(items, pal).map(|action| match action {
+                    MarkdownAction::OpenLink(url) => Message::LinkClicked(url),
+                    MarkdownAction::Copy(code) => Message::CopyToClipboard(code),
+                })
             } else {
                 // Fallback to simple text while cache is being built
                 // (The cache should be updated in handle_ui_event)
@@ -3046,8 +5161,13 @@ impl App {
                 .into()
         };
 
+        let time_label = if self.config.get_show_timestamps_enabled() {
+            format!("{} · {}", clock_time(message.local_time()), message.relative_time())
+        } else {
+            message.relative_time()
+        };
         let timestamp =
-            text(message.relative_time())
+            text(time_label)
                 .size(10)
                 .style(move |_| iced::widget::text::Style {
                     color: Some(Color {
@@ -3087,14 +5207,98 @@ impl App {
             }
         });
 
-        // Bottom row with timestamp and copy button
-        let bottom_row = row![timestamp, Space::new().width(Length::Fill), copy_button]
+        // Copy button for just the code when the message has code blocks
+        let code_blocks = extract_code_blocks(&message.content);
+        let copy_code_button = (!code_blocks.is_empty()).then(|| {
+            let code = code_blocks
+                .iter()
+                .map(|block| block.code.trim_end())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            button(
+                bootstrap::code_slash()
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(Color {
+                            a: fade_opacity * 0.6,
+                            ..pal.muted
+                        }),
+                    }),
+            )
+            .on_press(Message::CopyToClipboard(code))
+            .padding([2, 4])
+            .style(move |_theme, status| {
+                let hover_opacity = if matches!(status, button::Status::Hovered) {
+                    1.0
+                } else {
+                    0.6
+                };
+                button::Style {
+                    background: Some(Background::Color(Color::TRANSPARENT)),
+                    border: Border::default(),
+                    text_color: Color {
+                        a: fade_opacity * hover_opacity,
+                        ..pal.muted
+                    },
+                    ..Default::default()
+                }
+            })
+        });
+
+        // Play/stop reading a finished reply aloud
+        let speech_button = (is_ai_message && !is_streaming).then(|| {
+            let active = self.speaking.as_ref().is_some_and(|(k, _)| *k == key)
+                || self.speech_loading.as_ref() == Some(&key);
+            let icon = if active {
+                bootstrap::stop_fill()
+            } else {
+                bootstrap::volume_up()
+            };
+            button(icon.size(12))
+                .on_press(Message::ToggleSpeech(key.clone(), message.content.clone()))
+                .padding([2, 4])
+                .style(move |_theme, status| {
+                    let hover_opacity = if matches!(status, button::Status::Hovered) || active {
+                        1.0
+                    } else {
+                        0.6
+                    };
+                    button::Style {
+                        background: Some(Background::Color(Color::TRANSPARENT)),
+                        border: Border::default(),
+                        text_color: Color {
+                            a: fade_opacity * hover_opacity,
+                            ..if active { pal.accent } else { pal.muted }
+                        },
+                        ..Default::default()
+                    }
+                })
+        });
+
+        // Bottom row with timestamp and copy buttons
+        let mut bottom_row = row![timestamp, Space::new().width(Length::Fill)];
+        if let Some(speech_button) = speech_button {
+            bottom_row = bottom_row.push(speech_button);
+        }
+        if let Some(copy_code_button) = copy_code_button {
+            bottom_row = bottom_row.push(copy_code_button);
+        }
+        let bottom_row = bottom_row
+            .push(copy_button)
             .align_y(iced::Alignment::Center);
 
         let bubble = container(column![content_widget, bottom_row].spacing(6))
             .padding(16)
             .max_width(MESSAGE_MAX_WIDTH);
 
+        // Accent outline that fades out after a `/goto` jump
+        let highlight = match self.goto_highlight {
+            Some((session_idx, idx, started)) if session_idx == self.current && idx == msg_idx => {
+                (1.0 - started.elapsed().as_secs_f32() / GOTO_HIGHLIGHT_SECS).max(0.0)
+            }
+            _ => 0.0,
+        };
+
         // Custom style closure that applies the dynamic opacity
         let dynamic_style = move |base_style: container::Style| container::Style {
             background: base_style.background.map(|bg| match bg {
@@ -3108,12 +5312,23 @@ impl App {
                 a: c.a * final_text_multiplier,
                 ..c
             }),
-            border: Border {
-                color: Color {
-                    a: base_style.border.color.a * fade_opacity,
-                    ..base_style.border.color
-                },
-                ..base_style.border
+            border: if highlight > 0.0 {
+                Border {
+                    color: Color {
+                        a: highlight,
+                        ..pal.accent
+                    },
+                    width: 2.0,
+                    ..base_style.border
+                }
+            } else {
+                Border {
+                    color: Color {
+                        a: base_style.border.color.a * fade_opacity,
+                        ..base_style.border.color
+                    },
+                    ..base_style.border
+                }
             },
             ..base_style
         };
@@ -3176,55 +5391,27 @@ impl App {
         // Content format: "{icon} {ToolName}{extra_info} {status} {result}"
         // e.g.: "◇ List ✓ 24 items" or "○ Shell ✓ exit 0"
         // Use starts_with after the icon to avoid false matches with file content
-        let tool_type = if content.starts_with("○ Shell")
-            || content.starts_with("◆ Shell")
-            || content.contains("execute_bash")
-        {
+        // Match on the label after the icon, whichever icon set drew it
+        let label = content.split_once(' ').map_or(content.as_str(), |(_, rest)| rest);
+        let tool_type = if label.starts_with("Shell") || content.contains("execute_bash") {
             ToolType::Shell
-        } else if content.starts_with("□ Edit")
-            || content.starts_with("◆ Edit")
-            || content.contains("edit_file")
-        {
+        } else if label.starts_with("Edit") || content.contains("edit_file") {
             ToolType::EditFile
-        } else if content.starts_with("□ Write")
-            || content.starts_with("◆ Write")
-            || content.contains("write_file")
-        {
+        } else if label.starts_with("Write") || content.contains("write_file") {
             ToolType::WriteFile
-        } else if content.starts_with("○ Read")
-            || content.starts_with("◆ Read")
-            || content.contains("read_file")
-        {
+        } else if label.starts_with("Read") || content.contains("read_file") {
             ToolType::ReadFile
-        } else if content.starts_with("◇ List")
-            || content.starts_with("◆ List")
-            || content.contains("list_directory")
-        {
+        } else if label.starts_with("List") || content.contains("list_directory") {
             ToolType::ListDirectory
-        } else if content.starts_with("○ Search")
-            || content.starts_with("◆ Search")
-            || (content.contains("Search") && !content.contains("Web"))
-        {
+        } else if label.starts_with("Search") || (content.contains("Search") && !content.contains("Web")) {
             ToolType::Search
-        } else if content.starts_with("⭕ Web")
-            || content.starts_with("◆ Web")
-            || content.contains("web_search")
-        {
+        } else if label.starts_with("Web") || content.contains("web_search") {
             ToolType::WebSearch
-        } else if content.starts_with("◊ MCP")
-            || content.starts_with("◆ MCP")
-            || content.contains("mcp_call")
-        {
+        } else if label.starts_with("MCP") || content.contains("mcp_call") {
             ToolType::Mcp
-        } else if content.starts_with("○ Vision")
-            || content.starts_with("◆ Vision")
-            || content.contains("visioneer")
-        {
+        } else if label.starts_with("Vision") || content.contains("visioneer") {
             ToolType::Vision
-        } else if content.starts_with("❓ Question")
-            || content.starts_with("◆ Question")
-            || content.contains("ask_question")
-        {
+        } else if label.starts_with("Question") || content.contains("ask_question") {
             ToolType::AskQuestion
         } else {
             ToolType::Other
@@ -4009,18 +6196,24 @@ impl App {
                 .tool_call_id
                 .as_ref()
                 .and_then(|id| self.bash_output_lines.get(id));
+            // Exit code, run time and output of the finished command
+            let report = message
+                .tool_call_id
+                .as_ref()
+                .and_then(|id| self.command_reports.get(id));
+            let stderr_color = Color {
+                r: 0.72,
+                g: 0.55,
+                b: 0.48,
+                a: content_opacity * 0.9,
+            }; // Muted orange for stderr
 
             if let Some(lines) = streaming_lines {
                 // Use streaming lines - show each with proper color
                 // stdout = muted green, stderr = muted orange
                 for (line, is_stderr) in lines.iter() {
                     let line_color = if *is_stderr {
-                        Color {
-                            r: 0.72,
-                            g: 0.55,
-                            b: 0.48,
-                            a: content_opacity * 0.9,
-                        } // Muted orange for stderr
+                        stderr_color
                     } else {
                         result_glow_color
                     };
@@ -4034,6 +6227,23 @@ impl App {
                             }),
                     );
                 }
+            } else if let Some(report) = report {
+                // Output arrived all at once: stdout, then stderr
+                let lines = report
+                    .stdout
+                    .lines()
+                    .map(|line| (line, result_glow_color))
+                    .chain(report.stderr.lines().map(|line| (line, stderr_color)));
+                for (line, line_color) in lines {
+                    terminal_column = terminal_column.push(
+                        text(line.to_string())
+                            .size(12)
+                            .font(Font::MONOSPACE)
+                            .style(move |_| iced::widget::text::Style {
+                                color: Some(line_color),
+                            }),
+                    );
+                }
             } else if let Some(ref result) = result_text {
                 // Fallback to result text if no streaming lines
                 if !result.is_empty() {
@@ -4208,6 +6418,37 @@ impl App {
                 }
             }
 
+            if let Some(report) = report {
+                let status_color = if report.success {
+                    Color {
+                        r: 0.55,
+                        g: 0.72,
+                        b: 0.58,
+                        a: content_opacity * 0.9,
+                    }
+                } else {
+                    Color {
+                        r: 0.9,
+                        g: 0.45,
+                        b: 0.45,
+                        a: content_opacity,
+                    }
+                };
+                let mut status = report.status();
+                if report.truncated() {
+                    status.push_str(" · output truncated");
+                }
+                terminal_column = terminal_column.push(Space::new().height(Length::Fixed(4.0)));
+                terminal_column = terminal_column.push(
+                    text(status)
+                        .size(11)
+                        .font(Font::MONOSPACE)
+                        .style(move |_| iced::widget::text::Style {
+                            color: Some(status_color),
+                        }),
+                );
+            }
+
             // Wrap in container with tool-themed background
             // Animate background opacity
             let animated_bg = Color {
@@ -4659,7 +6900,12 @@ impl App {
         // CENTER: Text input field
         // ─────────────────────────────────────────────────────────────────
         
-        let input_field = text_input("Message ARULA...", &self.draft)
+        let placeholder = if is_streaming {
+            "Queue a follow-up..."
+        } else {
+            "Message ARULA..."
+        };
+        let input_field = text_input(placeholder, &self.draft)
             .id(input_id())
             .on_input(Message::DraftChanged)
             .on_submit(Message::SendPrompt)
@@ -4790,15 +7036,109 @@ impl App {
                     }
                 }
             })
-            .into()
+            .into()
+        };
+
+        // While streaming, the typed text can steer the run instead of queueing
+        let steer_button = (is_streaming && !self.draft.trim().is_empty()).then(|| {
+            button(
+                container(bootstrap::signpost().size(16))
+                    .width(Length::Fixed(36.0))
+                    .height(Length::Fixed(36.0))
+                    .align_x(Horizontal::Center)
+                    .align_y(Vertical::Center),
+            )
+            .on_press(Message::SteerPrompt)
+            .padding(0)
+            .style(move |_theme, status| {
+                let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+                iced::widget::button::Style {
+                    background: Some(Background::Color(Color {
+                        a: if is_hovered { 0.3 } else { 0.15 },
+                        ..pal.accent
+                    })),
+                    border: Border {
+                        radius: 10.0.into(),
+                        ..Default::default()
+                    },
+                    text_color: pal.accent,
+                    ..Default::default()
+                }
+            })
+        });
+
+        // Mic: records speech into the draft; red while recording
+        let recording = self.voice.is_some();
+        let transcribing = self.transcribing;
+        let mic_icon = if recording {
+            bootstrap::mic_fill()
+        } else {
+            bootstrap::mic()
+        };
+        let mic_button = button(
+            container(mic_icon.size(16))
+                .width(Length::Fixed(36.0))
+                .height(Length::Fixed(36.0))
+                .align_x(Horizontal::Center)
+                .align_y(Vertical::Center),
+        )
+        .on_press_maybe((!transcribing).then_some(Message::ToggleVoice))
+        .padding(0)
+        .style(move |_theme, status| {
+            let is_hovered = matches!(status, iced::widget::button::Status::Hovered);
+            let tint = if recording { pal.danger } else { pal.accent };
+            iced::widget::button::Style {
+                background: Some(Background::Color(Color {
+                    a: if recording { 0.8 } else if is_hovered { 0.2 } else { 0.0 },
+                    ..tint
+                })),
+                border: Border {
+                    radius: 10.0.into(),
+                    ..Default::default()
+                },
+                text_color: if recording {
+                    pal.text
+                } else if transcribing {
+                    Color { a: 0.4, ..pal.muted }
+                } else {
+                    pal.muted
+                },
+                ..Default::default()
+            }
+        });
+        let voice_status = if let Some(recording) = &self.voice {
+            let elapsed = recording.elapsed().as_secs();
+            Some((format!("● {}:{:02}", elapsed / 60, elapsed % 60), pal.danger))
+        } else {
+            transcribing.then(|| ("Transcribing...".to_string(), pal.muted))
+        };
+        let mic_button: Element<'_, Message> = match voice_status {
+            Some((status, color)) => row![
+                text(status).size(12).style(move |_| iced::widget::text::Style {
+                    color: Some(color)
+                }),
+                Space::new().width(Length::Fixed(4.0)),
+                mic_button
+            ]
+            .align_y(iced::Alignment::Center)
+            .into(),
+            None => mic_button.into(),
         };
 
-        let right_buttons = row![
+        let mut right_buttons = row![
             settings_button,
             Space::new().width(Length::Fixed(4.0)),
-            action_button,
-        ]
-        .align_y(iced::Alignment::Center);
+            mic_button,
+            Space::new().width(Length::Fixed(4.0))
+        ];
+        if let Some(steer_button) = steer_button {
+            right_buttons = right_buttons
+                .push(steer_button)
+                .push(Space::new().width(Length::Fixed(4.0)));
+        }
+        let right_buttons = right_buttons
+            .push(action_button)
+            .align_y(iced::Alignment::Center);
 
         // ─────────────────────────────────────────────────────────────────
         // MAIN INPUT BAR: With question UI crossfade
@@ -5094,6 +7434,12 @@ impl App {
                 ..Default::default()
             });
 
+        // Files offered for an `@` mention sit just above the bar
+        let input_bar: Element<'_, Message> = match self.mention_suggestions(pal) {
+            Some(suggestions) => column![suggestions, input_bar].spacing(6).into(),
+            None => input_bar.into(),
+        };
+
         // Outer container with padding - adjust left padding based on sidebar width
         let left_pad = if sidebar_width > 1.0 { sidebar_width } else { 0.0 };
         container(input_bar)
@@ -5138,8 +7484,10 @@ impl App {
                     SettingsPage::Provider => self.settings_provider_page(pal, form),
                     SettingsPage::Api => self.settings_provider_page(pal, form), // Redirect to provider
                     SettingsPage::Behavior => self.settings_behavior_page(pal, form),
+                    SettingsPage::Generation => self.settings_generation_page(pal, form),
                     SettingsPage::Appearance => self.settings_appearance_page(pal, form),
                     SettingsPage::ModelSelector => self.settings_model_selector_page(pal),
+                    SettingsPage::Sessions => self.settings_sessions_page(pal),
                 })
             } else {
                 None
@@ -5314,6 +7662,14 @@ impl App {
             pal,
         );
 
+        let generation_btn = self.category_button(
+            bootstrap::thermometer_half(),
+            "Generation",
+            "Temperature and token limits",
+            Message::SettingsNavigate(SettingsPage::Generation),
+            pal,
+        );
+
         let appearance_btn = self.category_button(
             bootstrap::palette(),
             "Appearance",
@@ -5322,6 +7678,14 @@ impl App {
             pal,
         );
 
+        let sessions_btn = self.category_button(
+            bootstrap::window_stack(),
+            "Sessions",
+            "Switch between open chats",
+            Message::SettingsNavigate(SettingsPage::Sessions),
+            pal,
+        );
+
         // Dim the menu slightly when a submenu is open to show focus shift
         let menu_opacity = if is_on_submenu { 0.6 } else { 1.0 };
 
@@ -5331,7 +7695,9 @@ impl App {
                 Space::new().height(Length::Fixed(16.0)),
                 provider_btn,
                 behavior_btn,
+                generation_btn,
                 appearance_btn,
+                sessions_btn,
             ]
             .spacing(6)
             .width(Length::Fixed(SETTINGS_CARD_WIDTH)),
@@ -5709,36 +8075,6 @@ impl App {
                     .padding(8)
                     .style(input_style(pal)),
                 Space::new().height(Length::Fixed(12.0)),
-                row![
-                    column![
-                        text(format!("Temperature: {:.1}", form.temperature))
-                            .size(12)
-                            .style(move |_| iced::widget::text::Style {
-                                color: Some(pal.muted)
-                            }),
-                        iced::widget::slider(
-                            0.0..=2.0,
-                            form.temperature,
-                            Message::ConfigTemperatureChanged
-                        )
-                        .step(0.1)
-                    ]
-                    .width(Length::Fill),
-                    column![
-                        text("Max Tokens")
-                            .size(12)
-                            .style(move |_| iced::widget::text::Style {
-                                color: Some(pal.muted)
-                            }),
-                        text_input("2048", &form.max_tokens.to_string())
-                            .on_input(Message::ConfigMaxTokensChanged)
-                            .padding(4)
-                            .style(input_style(pal))
-                    ]
-                    .width(Length::Fixed(80.0))
-                ]
-                .spacing(16),
-                Space::new().height(Length::Fixed(12.0)),
                 row![
                     text("Enable Streaming")
                         .size(14)
@@ -5801,6 +8137,186 @@ impl App {
         .into()
     }
 
+    /// Renders the Generation settings page.
+    fn settings_generation_page<'a>(
+        &'a self,
+        pal: PaletteColors,
+        form: &'a ConfigForm,
+    ) -> Element<'a, Message> {
+        let header = text("Generation")
+            .size(18)
+            .style(move |_| iced::widget::text::Style {
+                color: Some(pal.text),
+            });
+
+        let top_p_label = if form.top_p < 1.0 {
+            format!("Top P: {:.2}", form.top_p)
+        } else {
+            "Top P: provider default".to_string()
+        };
+
+        let content = container(
+            column![
+                text(format!("Temperature: {:.1}", form.temperature))
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+                iced::widget::slider(0.0..=2.0, form.temperature, Message::ConfigTemperatureChanged)
+                    .step(0.1),
+                Space::new().height(Length::Fixed(12.0)),
+                text(top_p_label)
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+                iced::widget::slider(0.0..=1.0, form.top_p, Message::ConfigTopPChanged).step(0.05),
+                Space::new().height(Length::Fixed(12.0)),
+                text("Max Tokens")
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+                text_input("4096", &form.max_tokens.to_string())
+                    .on_input(Message::ConfigMaxTokensChanged)
+                    .padding(8)
+                    .style(input_style(pal)),
+                Space::new().height(Length::Fill),
+            ]
+            .spacing(8)
+            .width(Length::Fill),
+        )
+        .padding(16)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(move |_| container::Style {
+            background: Some(Background::Color(Color {
+                a: 0.08,
+                ..pal.accent
+            })),
+            border: Border {
+                radius: 12.0.into(),
+                width: 1.0,
+                color: Color {
+                    a: 0.15,
+                    ..pal.accent
+                },
+            },
+            ..Default::default()
+        });
+
+        let status_text = form.status.clone().unwrap_or_default();
+        let save_btn = button("Save Changes")
+            .on_press(Message::SaveConfig)
+            .padding([10, 20])
+            .style(primary_button_style(pal));
+        let status = text(status_text)
+            .size(12)
+            .style(move |_| iced::widget::text::Style {
+                color: Some(pal.accent),
+            });
+
+        column![
+            header,
+            Space::new().height(Length::Fixed(12.0)),
+            content,
+            Space::new().height(Length::Fixed(12.0)),
+            row![save_btn, Space::new().width(Length::Fixed(12.0)), status]
+                .align_y(iced::Alignment::Center),
+        ]
+        .spacing(4)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
+    /// Renders the session switcher: every open session with its state,
+    /// plus a button for a new one.
+    fn settings_sessions_page(&self, pal: PaletteColors) -> Element<'_, Message> {
+        let header = text("Sessions")
+            .size(18)
+            .style(move |_| iced::widget::text::Style {
+                color: Some(pal.text),
+            });
+
+        let rows = self.sessions.iter().enumerate().map(|(index, session)| {
+            let active = index == self.current;
+            let messages = session.messages.iter().filter(|m| m.is_user()).count();
+            let status = if session.is_streaming {
+                "Responding...".to_string()
+            } else if messages == 1 {
+                "1 message".to_string()
+            } else {
+                format!("{} messages", messages)
+            };
+            let mut details = column![
+                text(session.get_title().to_string())
+                    .size(14)
+                    .color(if active { pal.accent } else { pal.text }),
+                text(status).size(12).color(pal.muted),
+            ]
+            .spacing(2)
+            .width(Length::Fill);
+            if let Some(brief) = &session.brief {
+                details = details.push(text(brief.clone()).size(12).color(pal.text));
+            }
+            let close = button(bootstrap::x_lg().size(12))
+                .on_press(Message::CloseSession(index))
+                .padding([4, 6])
+                .style(move |_, status| button::Style {
+                    background: None,
+                    text_color: if matches!(status, button::Status::Hovered) {
+                        pal.danger
+                    } else {
+                        pal.muted
+                    },
+                    ..Default::default()
+                });
+
+            button(row![details, close].align_y(iced::Alignment::Center))
+                .on_press(Message::SelectSession(index))
+                .padding([8, 12])
+                .width(Length::Fill)
+                .style(move |_, status| {
+                    let hovered = matches!(status, button::Status::Hovered);
+                    button::Style {
+                        background: Some(Background::Color(Color {
+                            a: if active { 0.16 } else if hovered { 0.1 } else { 0.05 },
+                            ..pal.accent
+                        })),
+                        border: Border {
+                            radius: 10.0.into(),
+                            width: 1.0,
+                            color: Color {
+                                a: if active { 0.4 } else { 0.12 },
+                                ..pal.accent
+                            },
+                        },
+                        text_color: pal.text,
+                        ..Default::default()
+                    }
+                })
+                .into()
+        });
+
+        let new_session = button("New Session")
+            .on_press(Message::NewTab)
+            .padding([10, 20])
+            .style(primary_button_style(pal));
+
+        column![
+            header,
+            Space::new().height(Length::Fixed(12.0)),
+            scrollable(column(rows).spacing(6)).height(Length::Fill),
+            Space::new().height(Length::Fixed(12.0)),
+            new_session,
+        ]
+        .spacing(4)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+
     /// Renders the Appearance settings page.
     fn settings_appearance_page<'a>(
         &'a self,
@@ -5813,16 +8329,15 @@ impl App {
                 color: Some(pal.text),
             });
 
-        // Theme mode selection (Light vs Dark)
-        let light_dark_options = vec!["Light".to_string(), "Dark".to_string()];
+        // Color theme selection (bundled and ~/.arula/themes)
         let theme_selector = row![
             column![
-                text("Theme Mode").size(14).style(move |_| {
+                text("Theme").size(14).style(move |_| {
                     iced::widget::text::Style {
                         color: Some(pal.text),
                     }
                 }),
-                text("Choose your preferred color scheme")
+                text("\"system\" follows the OS light/dark setting; add your own in ~/.arula/themes")
                     .size(12)
                     .style(move |_| iced::widget::text::Style {
                         color: Some(pal.muted)
@@ -5830,12 +8345,9 @@ impl App {
             ],
             Space::new().width(Length::Fill),
             pick_list(
-                light_dark_options,
-                Some(match self.theme_mode {
-                    ThemeMode::Light => "Light".to_string(),
-                    _ => "Dark".to_string(),
-                }),
-                Message::ThemeModeChanged,
+                form.theme_options.clone(),
+                Some(form.theme.clone()),
+                Message::ThemeChanged,
             )
             .padding([8, 12])
             .style(move |_theme, _status| iced::widget::pick_list::Style {
@@ -5853,52 +8365,6 @@ impl App {
         .spacing(12)
         .align_y(iced::Alignment::Center);
 
-        // Dark theme submenu (only show when Dark/Black is selected)
-        let dark_submenu = if matches!(self.theme_mode, ThemeMode::Dark | ThemeMode::Black) {
-            let dark_black_options = vec!["Dark".to_string(), "Black".to_string()];
-            let submenu = row![
-                column![
-                    text("Dark Theme Style").size(14).style(move |_| {
-                        iced::widget::text::Style {
-                            color: Some(pal.text),
-                        }
-                    }),
-                    text("Choose between dark or pure black background")
-                        .size(12)
-                        .style(move |_| iced::widget::text::Style {
-                            color: Some(pal.muted)
-                        }),
-                ],
-                Space::new().width(Length::Fill),
-                pick_list(
-                    dark_black_options,
-                    Some(match self.theme_mode {
-                        ThemeMode::Black => "Black".to_string(),
-                        _ => "Dark".to_string(),
-                    }),
-                    Message::ThemeSubmenuChanged,
-                )
-                .padding([8, 12])
-                .style(move |_theme, _status| iced::widget::pick_list::Style {
-                    background: Background::Color(pal.surface),
-                    text_color: pal.text,
-                    placeholder_color: pal.muted,
-                    border: Border {
-                        radius: 8.0.into(),
-                        width: 1.0,
-                        color: pal.border,
-                    },
-                    handle_color: pal.accent,
-                })
-            ]
-            .spacing(12)
-            .align_y(iced::Alignment::Center);
-            
-            Some(submenu)
-        } else {
-            None
-        };
-
         // Living background toggle
         let living_bg_toggle = row![
             column![
@@ -5921,6 +8387,28 @@ impl App {
         .spacing(12)
         .align_y(iced::Alignment::Center);
 
+        // Message timestamps toggle
+        let timestamps_toggle = row![
+            column![
+                text("Message Timestamps").size(14).style(move |_| {
+                    iced::widget::text::Style {
+                        color: Some(pal.text),
+                    }
+                }),
+                text("Show the clock time next to each message")
+                    .size(12)
+                    .style(move |_| iced::widget::text::Style {
+                        color: Some(pal.muted)
+                    }),
+            ],
+            Space::new().width(Length::Fill),
+            iced::widget::toggler(form.show_timestamps)
+                .on_toggle(Message::ConfigShowTimestampsToggled)
+                .width(Length::Shrink)
+        ]
+        .spacing(12)
+        .align_y(iced::Alignment::Center);
+
         // Build the content column
         let mut content_col = column![
             text("Visual Settings")
@@ -5932,15 +8420,11 @@ impl App {
             theme_selector,
         ];
 
-        // Add dark submenu if visible
-        if let Some(submenu) = dark_submenu {
-            content_col = content_col.push(Space::new().height(Length::Fixed(16.0)));
-            content_col = content_col.push(submenu);
-        }
-
         // Add living background toggle
         content_col = content_col.push(Space::new().height(Length::Fixed(16.0)));
         content_col = content_col.push(living_bg_toggle);
+        content_col = content_col.push(Space::new().height(Length::Fixed(12.0)));
+        content_col = content_col.push(timestamps_toggle);
         content_col = content_col.push(Space::new().height(Length::Fill));
 
         let content = container(content_col)
@@ -6307,13 +8791,108 @@ impl App {
     }
 }
 
+/// How long the window gets to hide before the screen is captured for `/region`
+const REGION_CAPTURE_DELAY_MS: u64 = 300;
+
+/// `region` as the JSON a Visioneer action's `region` takes
+fn region_reference(region: &CaptureRegion) -> String {
+    format!(
+        "screen region {}",
+        serde_json::to_string(region).unwrap_or_default()
+    )
+}
+
+/// What to add to the draft for `region`: its coordinates, or the result
+/// of a Visioneer capture or OCR of it
+async fn run_region_action(action: RegionAction, region: CaptureRegion) -> Result<String, String> {
+    let action_json = match action {
+        RegionAction::Capture => {
+            let dir = dirs::home_dir()
+                .ok_or("Could not find the home directory")?
+                .join(".arula")
+                .join("captures");
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+            let path = dir.join(format!("region-{}.png", Local::now().format("%Y%m%d-%H%M%S")));
+            serde_json::json!({"type": "Capture", "region": region, "save_path": path})
+        }
+        RegionAction::Ocr => serde_json::json!({"type": "ExtractText", "region": region}),
+        RegionAction::Insert => return Ok(region_reference(&region)),
+    };
+    let params: VisioneerParams =
+        serde_json::from_value(serde_json::json!({"target": "screen", "action": action_json}))
+            .map_err(|e| e.to_string())?;
+    let result = VisioneerTool::new().execute(params).await?;
+
+    match action {
+        RegionAction::Capture => {
+            let path = result.data["image_path"].as_str().unwrap_or_default();
+            Ok(format!("{} (saved to {})", region_reference(&region), path))
+        }
+        _ => Ok(result.data["text"].as_str().unwrap_or_default().trim().to_string()),
+    }
+}
+
+/// Start the PROJECT.manifest watcher for `dir` if auto-refresh is enabled
+fn start_manifest_watcher(config: &Config, dir: &Path) -> Option<ManifestWatcher> {
+    if !config.get_manifest_auto_refresh_enabled() {
+        return None;
+    }
+    ManifestWatcher::start(dir)
+        .map_err(|e| {
+            arula_core::utils::logger::warn(&format!("Manifest auto-refresh disabled: {}", e))
+        })
+        .ok()
+}
+
+/// Profile selected with `--profile <name>` or the `ARULA_PROFILE` variable
+fn profile_from_args() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    std::env::var(Config::PROFILE_ENV).ok()
+}
+
 fn main() -> iced::Result {
     fn get_theme(app: &App) -> iced::Theme {
-        app_theme_with_mode(app.theme_mode)
+        // Follows the palette while a theme switch fades in
+        app_theme_with_palette(app.bg_state.palette(palette_for(&app.theme)))
     }
     
+    // Save and exit cleanly on SIGTERM/SIGHUP
+    if let Err(e) = shutdown::listen() {
+        eprintln!("⚠️ Graceful shutdown disabled: {e:#}");
+    }
+
+    // Request traces go to the OTLP collector until the window closes
+    let telemetry = Config::load_or_default()
+        .map(|config| config.get_telemetry())
+        .unwrap_or_default();
+    let _telemetry = arula_core::utils::telemetry::init(&telemetry).unwrap_or_else(|e| {
+        eprintln!("⚠️ Tracing disabled: {e:#}");
+        None
+    });
+
+    // Marks the title while the agent waits on the user
+    fn get_title(app: &App) -> String {
+        if app.waiting_on_user() {
+            "⚠ Arula Desktop — waiting for you".to_string()
+        } else if app.sessions.get(app.current).is_some_and(|s| s.read_only) {
+            "Arula Desktop — read-only".to_string()
+        } else {
+            "Arula Desktop".to_string()
+        }
+    }
+
     iced::application(App::init, App::update, App::view)
-        .title("Arula Desktop")
+        .title(get_title)
+        .exit_on_close_request(false)
         .subscription(App::subscription)
         .theme(get_theme)
         .font(iced_fonts::BOOTSTRAP_FONT_BYTES)
@@ -0,0 +1,98 @@
+//! Markdown rendering for assistant messages.
+//!
+//! Builds on iced's markdown widgets: headings, lists, quotes and tables use
+//! the stock look, links report [`MarkdownAction::OpenLink`], and code blocks
+//! get a monospace panel with a language label and a copy button.
+
+use crate::theme::PaletteColors;
+use iced::widget::markdown::{self, Item, Settings, Style, Text, Uri, Viewer};
+use iced::widget::{Space, button, column, container, row, text};
+use iced::{Background, Border, Color, Element, Font, Length, Theme};
+use iced_fonts::bootstrap;
+
+/// What the user asked for by interacting with rendered markdown.
+#[derive(Debug, Clone)]
+pub enum MarkdownAction {
+    /// A link was clicked; open it in the browser.
+    OpenLink(Uri),
+    /// The copy button of a code block was pressed.
+    Copy(String),
+}
+
+/// Viewer that draws code blocks with a header row.
+struct MessageViewer {
+    palette: PaletteColors,
+}
+
+impl<'a> Viewer<'a, MarkdownAction> for MessageViewer {
+    fn on_link_click(url: Uri) -> MarkdownAction {
+        MarkdownAction::OpenLink(url)
+    }
+
+    fn code_block(
+        &self,
+        settings: Settings,
+        language: Option<&'a str>,
+        code: &'a str,
+        lines: &'a [Text],
+    ) -> Element<'a, MarkdownAction> {
+        let pal = self.palette;
+        let label = text(language.filter(|l| !l.is_empty()).unwrap_or("text"))
+            .size(11)
+            .font(Font::MONOSPACE)
+            .color(pal.muted);
+        let copy = button(bootstrap::clipboard().size(12))
+            .on_press(MarkdownAction::Copy(code.trim_end().to_string()))
+            .padding([2, 4])
+            .style(move |_theme, status| button::Style {
+                background: Some(Background::Color(Color::TRANSPARENT)),
+                border: Border::default(),
+                text_color: if matches!(status, button::Status::Hovered) {
+                    pal.accent
+                } else {
+                    pal.muted
+                },
+                ..Default::default()
+            });
+        let header = row![label, Space::new().width(Length::Fill), copy]
+            .align_y(iced::Alignment::Center)
+            .padding([2, 6]);
+
+        container(column![
+            header,
+            markdown::code_block(settings, lines, Self::on_link_click)
+        ])
+        .style(move |_| container::Style {
+            border: Border {
+                color: pal.border,
+                width: 1.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+    }
+}
+
+/// Markdown settings matching the app palette, at base size `text_size`.
+fn settings(palette: PaletteColors, text_size: f32) -> Settings {
+    let mut style = Style::from_palette(Theme::TokyoNightStorm.palette());
+    style.link_color = palette.accent;
+    style.code_block_font = Font::MONOSPACE;
+    style.inline_code_font = Font::MONOSPACE;
+    Settings::with_text_size(text_size, style)
+}
+
+/// Render parsed markdown `items` as widgets.
+pub fn view<'a>(items: &'a [Item], palette: PaletteColors) -> Element<'a, MarkdownAction> {
+    view_with_size(items, palette, 16.0)
+}
+
+/// [`view`] at base text size `text_size`, e.g. for presenting.
+pub fn view_with_size<'a>(
+    items: &'a [Item],
+    palette: PaletteColors,
+    text_size: f32,
+) -> Element<'a, MarkdownAction> {
+    markdown::view_with(items, settings(palette, text_size), &MessageViewer { palette })
+}
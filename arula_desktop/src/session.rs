@@ -1,4 +1,7 @@
-use chrono::{DateTime, Utc};
+use arula_core::utils::brief;
+use arula_core::utils::icons::Icon;
+use chrono::{DateTime, Local, Utc};
+use std::collections::VecDeque;
 use std::time::Instant;
 use uuid::Uuid;
 
@@ -115,6 +118,11 @@ impl MessageEntry {
         arula_core::utils::time::relative_time(self.parsed_timestamp)
     }
 
+    /// Returns when the message was sent, in local time.
+    pub fn local_time(&self) -> DateTime<Local> {
+        self.parsed_timestamp.with_timezone(&Local)
+    }
+
     /// Returns the animation progress (0.0 to 1.0) based on time since added.
     pub fn animation_progress(&self) -> f32 {
         let elapsed = self.added_at.elapsed().as_secs_f32();
@@ -133,6 +141,16 @@ pub struct Session {
     ai_buffer: String,
     /// Title for the conversation
     pub title: String,
+    /// Prompts sent while a response was streaming, oldest first
+    pub queued_prompts: VecDeque<String>,
+    /// Rolling "what we're doing" brief, written while the session is idle
+    pub brief: Option<String>,
+    /// Conversation messages the last brief request covered
+    brief_covers: usize,
+    /// Whether the session was reopened from a saved conversation
+    resumed: bool,
+    /// Opened while another instance has it; never saved
+    pub read_only: bool,
 }
 
 impl Session {
@@ -144,6 +162,11 @@ impl Session {
             is_streaming: false,
             ai_buffer: String::new(),
             title: "New Chat".to_string(),
+            queued_prompts: VecDeque::new(),
+            brief: None,
+            brief_covers: 0,
+            resumed: false,
+            read_only: false,
         }
     }
 
@@ -155,6 +178,11 @@ impl Session {
             is_streaming: false,
             ai_buffer: String::new(),
             title: "New Chat".to_string(),
+            queued_prompts: VecDeque::new(),
+            brief: None,
+            brief_covers: 0,
+            resumed: true,
+            read_only: false,
         };
 
         for event in events {
@@ -168,9 +196,9 @@ impl Session {
                 arula_core::session_manager::UiEvent::Thinking(_, text) => {
                     session.append_thinking_message(text.clone(), Utc::now().to_rfc3339());
                 }
-                arula_core::session_manager::UiEvent::ToolCallStart(_, tool_call_id, _name, display_args) => {
+                arula_core::session_manager::UiEvent::ToolCallStart(_, tool_call_id, name, display_args) => {
                     session.add_tool_message(
-                        format!("{} {}", session.get_tool_icon("tool"), display_args),
+                        format!("{} {}", Icon::for_tool(name), display_args),
                         Utc::now().to_rfc3339(),
                         Some(tool_call_id.clone()),
                     );
@@ -179,32 +207,23 @@ impl Session {
                     // For simplicity, we'll just mark the tool as complete
                     // The actual display is handled by the update_tool_message
                 }
+                arula_core::session_manager::UiEvent::SessionBrief(_, brief) => {
+                    session.brief = Some(brief.clone());
+                }
                 _ => {}
             }
         }
 
         // Flush any remaining AI buffer
         session.flush_ai_buffer(Utc::now().to_rfc3339());
+        // The saved brief already covers the saved messages
+        if session.brief.is_some() {
+            session.brief_covers = session.conversation_len();
+        }
         
         session
     }
 
-    /// Gets a tool icon for a given tool name.
-    fn get_tool_icon(&self, name: &str) -> &'static str {
-        match name.to_lowercase().as_str() {
-            "execute_bash" => "⚡",
-            "read_file" => "📄",
-            "write_file" => "📝",
-            "edit_file" => "✏️",
-            "list_directory" => "📁",
-            "search_files" => "🔍",
-            "web_search" => "🌐",
-            "mcp_call" => "🔌",
-            "visioneer" => "👁️",
-            _ => "🔧",
-        }
-    }
-
     /// Converts session messages to UiEvents for saving conversations.
     pub fn to_ui_events(&self) -> Vec<arula_core::session_manager::UiEvent> {
         let mut events = Vec::new();
@@ -215,6 +234,12 @@ impl Session {
                 self.title.clone()
             ));
         }
+        if let Some(brief) = &self.brief {
+            events.push(arula_core::session_manager::UiEvent::SessionBrief(
+                self.id,
+                brief.clone(),
+            ));
+        }
         
         for msg in &self.messages {
             match msg.role.as_str() {
@@ -391,7 +416,8 @@ impl Session {
     /// Includes user, AI, and tool messages for full conversation context.
     /// Excludes thinking messages as they're internal reasoning.
     pub fn get_chat_history(&self) -> Vec<arula_core::api::api::ChatMessage> {
-        self.messages
+        let mut history: Vec<_> = self
+            .messages
             .iter()
             .filter(|msg| msg.is_user() || msg.is_ai() || msg.is_tool())
             .map(|msg| {
@@ -419,7 +445,44 @@ impl Session {
                     }
                 }
             })
-            .collect()
+            .collect();
+
+        // A resumed session starts with its brief to orient the model
+        if let Some(brief) = self.brief.as_deref().filter(|_| self.resumed) {
+            history.insert(
+                0,
+                arula_core::api::api::ChatMessage {
+                    role: "system".to_string(),
+                    content: Some(brief::seed_message(brief)),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    tool_name: None,
+                },
+            );
+        }
+        history
+    }
+
+    /// Number of user and assistant messages
+    fn conversation_len(&self) -> usize {
+        self.messages.iter().filter(|m| m.is_user() || m.is_ai()).count()
+    }
+
+    /// The previous brief and the history to write a new brief from, when
+    /// the session has been idle long enough since it last got one
+    pub fn take_brief_request(
+        &mut self,
+    ) -> Option<(Option<String>, Vec<arula_core::api::api::ChatMessage>)> {
+        let idle = self.messages.last()?.added_at.elapsed();
+        let len = self.conversation_len();
+        if self.is_streaming
+            || !self.queued_prompts.is_empty()
+            || !brief::is_due(len, self.brief_covers, idle)
+        {
+            return None;
+        }
+        self.brief_covers = len;
+        Some((self.brief.clone(), self.get_chat_history()))
     }
 
     /// Sets the streaming state.
@@ -442,10 +505,23 @@ impl Session {
         self.is_streaming
     }
 
+    /// Queues a prompt to be sent once the current turn completes.
+    pub fn queue_prompt(&mut self, prompt: String) {
+        self.queued_prompts.push_back(prompt);
+    }
+
+    /// Takes the oldest queued prompt, if any.
+    pub fn next_queued_prompt(&mut self) -> Option<String> {
+        self.queued_prompts.pop_front()
+    }
+
       /// Clears all messages from the session.
     pub fn clear_messages(&mut self) {
         self.messages.clear();
         self.ai_buffer.clear();
+        self.queued_prompts.clear();
+        self.brief = None;
+        self.brief_covers = 0;
         self.is_streaming = false;
     }
 }
@@ -1,38 +1,39 @@
-use super::palette::{palette_from_mode, PaletteColors, ThemeMode};
+use super::palette::{palette_for, PaletteColors};
+use arula_core::utils::themes::Theme as ArulaTheme;
 use iced::{theme, Theme};
 
-/// Creates the custom Arula Neon theme with default palette.
+/// Creates the app theme from the default ARULA theme.
 pub fn app_theme() -> Theme {
-    app_theme_with_mode(ThemeMode::default())
+    app_theme_for(&ArulaTheme::default())
 }
 
-/// Creates the custom Arula Neon theme with a specific theme mode.
-pub fn app_theme_with_mode(mode: ThemeMode) -> Theme {
-    let p = palette_from_mode(mode);
+/// Creates the app theme from a shared ARULA theme.
+pub fn app_theme_for(arula_theme: &ArulaTheme) -> Theme {
+    let p = palette_for(arula_theme);
     Theme::custom(
-        format!("Arula Neon - {}", mode.name()),
+        format!("Arula - {}", arula_theme.name),
         theme::Palette {
             background: p.background,
             text: p.text,
             primary: p.accent,
             success: p.success,
             danger: p.danger,
-            warning: p.accent_soft, // Use accent_soft as warning color
+            warning: p.warning,
         },
     )
 }
 
-/// Creates the custom Arula Neon theme with custom palette colors.
+/// Creates the app theme with custom palette colors.
 pub fn app_theme_with_palette(p: PaletteColors) -> Theme {
     Theme::custom(
-        "Arula Neon".to_string(),
+        "Arula".to_string(),
         theme::Palette {
             background: p.background,
             text: p.text,
             primary: p.accent,
             success: p.success,
             danger: p.danger,
-            warning: p.accent_soft, // Use accent_soft as warning color
+            warning: p.warning,
         },
     )
 }
@@ -1,5 +1,7 @@
 mod app_theme;
 mod palette;
+mod system;
 
-pub use app_theme::{app_theme, app_theme_with_mode, app_theme_with_palette};
-pub use palette::{palette, palette_from_mode, PaletteColors, ThemeMode};
+pub use app_theme::{app_theme, app_theme_for, app_theme_with_palette};
+pub use palette::{mix_colors, palette, palette_for, PaletteColors};
+pub use system::{system_prefers_dark, SystemThemeWatcher};
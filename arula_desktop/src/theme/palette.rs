@@ -1,38 +1,7 @@
+use arula_core::utils::themes::{Rgb, Theme};
 use iced::Color;
 
-/// Theme mode enumeration
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum ThemeMode {
-    Light,
-    #[default]
-    Dark,
-    Black,
-}
-
-impl ThemeMode {
-    pub fn name(&self) -> &'static str {
-        match self {
-            ThemeMode::Light => "Light",
-            ThemeMode::Dark => "Dark",
-            ThemeMode::Black => "Black",
-        }
-    }
-
-    pub fn from_name(name: &str) -> Option<Self> {
-        match name.to_lowercase().as_str() {
-            "light" => Some(ThemeMode::Light),
-            "dark" => Some(ThemeMode::Dark),
-            "black" => Some(ThemeMode::Black),
-            _ => None,
-        }
-    }
-
-    pub fn all() -> Vec<&'static str> {
-        vec!["Light", "Dark", "Black"]
-    }
-}
-
-/// Core color palette for the Arula Neon theme.
+/// Core color palette of the app, taken from a theme.
 #[derive(Debug, Clone, Copy)]
 pub struct PaletteColors {
     pub background: Color,
@@ -44,75 +13,64 @@ pub struct PaletteColors {
     pub accent: Color,
     pub accent_soft: Color,
     pub success: Color,
+    pub warning: Color,
     pub danger: Color,
     pub glow: Color,
 }
 
 impl Default for PaletteColors {
     fn default() -> Self {
-        Self::dark()
+        Self::from_theme(&Theme::default())
     }
 }
 
 impl PaletteColors {
-    /// Light theme palette
-    pub fn light() -> Self {
-        Self {
-            background: Color::from_rgb8(248, 250, 255),  // Very light blue
-            surface: Color::from_rgb8(240, 244, 255),     // Light blue-gray
-            surface_raised: Color::from_rgb8(255, 255, 255), // Pure white
-            border: Color::from_rgb8(200, 210, 230),      // Light blue-gray border
-            text: Color::from_rgb8(20, 30, 50),           // Dark blue-gray text
-            muted: Color::from_rgb8(110, 120, 140),       // Medium gray
-            accent: Color::from_rgb8(80, 120, 220),       // Blue accent (very distinct from dark purple)
-            accent_soft: Color::from_rgb8(150, 180, 255), // Light blue
-            success: Color::from_rgb8(40, 160, 80),       // Green
-            danger: Color::from_rgb8(220, 60, 60),        // Red
-            glow: Color::from_rgb8(100, 150, 255),        // Blue glow
-        }
-    }
-
-    /// Dark theme palette
-    pub fn dark() -> Self {
+    /// Palette of a shared ARULA theme
+    pub fn from_theme(theme: &Theme) -> Self {
+        let c = theme.colors;
+        let color = |Rgb(r, g, b): Rgb| Color::from_rgb8(r, g, b);
         Self {
-            background: Color::from_rgb8(10, 8, 14),      // Deep purple-black
-            surface: Color::from_rgb8(18, 14, 24),        // Dark purple-gray
-            surface_raised: Color::from_rgb8(26, 20, 34), // Raised purple-gray
-            border: Color::from_rgb8(50, 40, 70),         // Purple border
-            text: Color::from_rgb8(240, 235, 255),        // Off-white
-            muted: Color::from_rgb8(150, 140, 180),       // Light purple-gray
-            accent: Color::from_rgb8(180, 50, 255),       // Vibrant purple
-            accent_soft: Color::from_rgb8(140, 40, 200),  // Soft purple
-            success: Color::from_rgb8(100, 255, 140),     // Bright green
-            danger: Color::from_rgb8(255, 100, 100),      // Bright red
-            glow: Color::from_rgb8(200, 100, 255),        // Purple glow
+            background: color(c.background),
+            surface: color(c.surface),
+            surface_raised: color(c.surface_raised),
+            border: color(c.border),
+            text: color(c.text),
+            muted: color(c.muted),
+            accent: color(c.accent),
+            accent_soft: color(c.accent_soft),
+            success: color(c.success),
+            warning: color(c.warning),
+            danger: color(c.danger),
+            glow: color(c.glow),
         }
     }
 
-    /// Black theme palette (darker than dark)
-    pub fn black() -> Self {
+    /// This palette `t` (0.0 to 1.0) of the way to `to`, color by color.
+    pub fn mix(&self, to: &PaletteColors, t: f32) -> Self {
         Self {
-            background: Color::from_rgb8(0, 0, 0),        // Pure black (very distinct!)
-            surface: Color::from_rgb8(5, 5, 8),           // Nearly black
-            surface_raised: Color::from_rgb8(10, 10, 15), // Very dark gray
-            border: Color::from_rgb8(25, 20, 35),         // Dark border
-            text: Color::from_rgb8(250, 245, 255),        // Pure white text
-            muted: Color::from_rgb8(140, 130, 170),       // Light purple-gray
-            accent: Color::from_rgb8(200, 80, 255),       // Extra vibrant purple
-            accent_soft: Color::from_rgb8(160, 60, 220),  // Soft purple
-            success: Color::from_rgb8(120, 255, 150),     // Bright green
-            danger: Color::from_rgb8(255, 110, 110),      // Bright red
-            glow: Color::from_rgb8(220, 120, 255),        // Extra bright glow
+            background: mix_colors(self.background, to.background, t),
+            surface: mix_colors(self.surface, to.surface, t),
+            surface_raised: mix_colors(self.surface_raised, to.surface_raised, t),
+            border: mix_colors(self.border, to.border, t),
+            text: mix_colors(self.text, to.text, t),
+            muted: mix_colors(self.muted, to.muted, t),
+            accent: mix_colors(self.accent, to.accent, t),
+            accent_soft: mix_colors(self.accent_soft, to.accent_soft, t),
+            success: mix_colors(self.success, to.success, t),
+            warning: mix_colors(self.warning, to.warning, t),
+            danger: mix_colors(self.danger, to.danger, t),
+            glow: mix_colors(self.glow, to.glow, t),
         }
     }
+}
 
-    /// Create palette from theme mode
-    pub fn from_theme_mode(mode: ThemeMode) -> Self {
-        match mode {
-            ThemeMode::Light => Self::light(),
-            ThemeMode::Dark => Self::dark(),
-            ThemeMode::Black => Self::black(),
-        }
+/// A color `t` (0.0 to 1.0) of the way from `from` to `to`.
+pub fn mix_colors(from: Color, to: Color, t: f32) -> Color {
+    Color {
+        r: from.r + (to.r - from.r) * t,
+        g: from.g + (to.g - from.g) * t,
+        b: from.b + (to.b - from.b) * t,
+        a: from.a + (to.a - from.a) * t,
     }
 }
 
@@ -121,7 +79,7 @@ pub fn palette() -> PaletteColors {
     PaletteColors::default()
 }
 
-/// Returns palette for a specific theme mode
-pub fn palette_from_mode(mode: ThemeMode) -> PaletteColors {
-    PaletteColors::from_theme_mode(mode)
+/// Returns the palette of a theme
+pub fn palette_for(theme: &Theme) -> PaletteColors {
+    PaletteColors::from_theme(theme)
 }
@@ -0,0 +1,42 @@
+use crate::constants::SYSTEM_THEME_POLL_MS;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Whether the OS color scheme is dark (a scheme that isn't set counts as dark).
+pub fn system_prefers_dark() -> bool {
+    !matches!(dark_light::detect(), dark_light::Mode::Light)
+}
+
+/// Reports changes of the OS light/dark setting.
+///
+/// Detection can block briefly (it may ask the desktop portal over D-Bus), so
+/// it runs on a thread of its own; changes are polled from the app tick with
+/// [`SystemThemeWatcher::try_recv`].
+pub struct SystemThemeWatcher {
+    rx: mpsc::Receiver<bool>,
+}
+
+impl SystemThemeWatcher {
+    /// Start watching, with `dark` as the scheme already known.
+    pub fn start(dark: bool) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut last = dark;
+            loop {
+                std::thread::sleep(Duration::from_millis(SYSTEM_THEME_POLL_MS));
+                let dark = system_prefers_dark();
+                // Ends once the watcher is dropped
+                if dark != last && tx.send(dark).is_err() {
+                    break;
+                }
+                last = dark;
+            }
+        });
+        Self { rx }
+    }
+
+    /// The newest scheme change (`true` for dark), if any.
+    pub fn try_recv(&self) -> Option<bool> {
+        self.rx.try_iter().last()
+    }
+}
@@ -0,0 +1,251 @@
+//! System tray icon and quick-ask hotkey.
+//!
+//! The tray menu offers "Quick ask", "Show ARULA" and "Quit", and the global
+//! hotkey from `tray.hotkey` in config.json opens quick ask from anywhere.
+//! Events are polled from the app tick with [`Tray::try_recv`]. While the
+//! agent waits on the user, [`Tray::set_attention`] badges the icon.
+//!
+//! Needs the `tray` cargo feature, which is off by default; on Linux it
+//! pulls in gtk and libappindicator, which run on a thread of their own.
+
+/// Something the user asked for from the tray or the hotkey.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayEvent {
+    /// Open the compact quick-ask window.
+    QuickAsk,
+    /// Bring the main window back.
+    Show,
+    /// Quit the app.
+    Quit,
+}
+
+#[cfg(feature = "tray")]
+pub use imp::Tray;
+
+#[cfg(feature = "tray")]
+mod imp {
+    use super::TrayEvent;
+    use anyhow::{Context, Result, anyhow};
+    use arula_core::utils::config::TrayConfig;
+    use global_hotkey::hotkey::HotKey;
+    use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+    use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+    use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+    #[cfg(target_os = "linux")]
+    use std::sync::Arc;
+    #[cfg(target_os = "linux")]
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    const QUICK_ASK_ID: &str = "quick-ask";
+    const SHOW_ID: &str = "show";
+    const QUIT_ID: &str = "quit";
+    const ICON_SIZE: u32 = 32;
+    const TOOLTIP: &str = "ARULA";
+    const WAITING_TOOLTIP: &str = "ARULA — waiting for you";
+    /// How often the gtk thread picks up badge changes
+    #[cfg(target_os = "linux")]
+    const BADGE_POLL: std::time::Duration = std::time::Duration::from_millis(250);
+
+    /// A running tray icon and registered hotkey; both go away on drop.
+    pub struct Tray {
+        /// Kept on platforms where the icon lives on the UI thread
+        #[cfg(not(target_os = "linux"))]
+        icon: tray_icon::TrayIcon,
+        /// Wanted badge state, picked up by the gtk thread that owns the icon
+        #[cfg(target_os = "linux")]
+        badge: Arc<AtomicBool>,
+        hotkey: Option<(GlobalHotKeyManager, HotKey)>,
+    }
+
+    impl Tray {
+        /// Show the tray icon and register the quick-ask hotkey.
+        ///
+        /// A hotkey that can't be parsed or registered is logged and skipped;
+        /// the tray still works.
+        pub fn start(config: &TrayConfig) -> Result<Self> {
+            #[cfg(target_os = "linux")]
+            let badge = Arc::new(AtomicBool::new(false));
+            #[cfg(target_os = "linux")]
+            {
+                let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+                let badge = badge.clone();
+                std::thread::Builder::new()
+                    .name("arula-tray".into())
+                    .spawn(move || {
+                        let started = gtk::init()
+                            .map_err(|e| anyhow!("gtk: {e}"))
+                            .and_then(|_| build_icon());
+                        match started {
+                            // The icon lives as long as the gtk loop runs
+                            Ok(icon) => {
+                                let _ = ready_tx.send(Ok(()));
+                                let mut shown = false;
+                                gtk::glib::timeout_add_local(BADGE_POLL, move || {
+                                    let wanted = badge.load(Ordering::Relaxed);
+                                    if wanted != shown {
+                                        shown = wanted;
+                                        show_badge(&icon, wanted);
+                                    }
+                                    gtk::glib::ControlFlow::Continue
+                                });
+                                gtk::main();
+                            }
+                            Err(e) => {
+                                let _ = ready_tx.send(Err(e));
+                            }
+                        }
+                    })
+                    .context("Failed to start tray thread")?;
+                ready_rx
+                    .recv()
+                    .context("Tray thread exited before starting")??;
+            }
+            #[cfg(not(target_os = "linux"))]
+            let icon = build_icon()?;
+
+            let hotkey = register_hotkey(config.hotkey())
+                .map_err(|e| eprintln!("⚠️ Quick-ask hotkey disabled: {e:#}"))
+                .ok();
+
+            Ok(Self {
+                #[cfg(not(target_os = "linux"))]
+                icon,
+                #[cfg(target_os = "linux")]
+                badge,
+                hotkey,
+            })
+        }
+
+        /// Badge the icon while the agent waits on the user.
+        pub fn set_attention(&self, waiting: bool) {
+            #[cfg(target_os = "linux")]
+            self.badge.store(waiting, Ordering::Relaxed);
+            #[cfg(not(target_os = "linux"))]
+            show_badge(&self.icon, waiting);
+        }
+
+        /// The next pending tray or hotkey event, if any.
+        pub fn try_recv(&self) -> Option<TrayEvent> {
+            if let Some((_, hotkey)) = &self.hotkey {
+                while let Ok(event) = GlobalHotKeyEvent::receiver().try_recv() {
+                    if event.id == hotkey.id() && event.state == HotKeyState::Pressed {
+                        return Some(TrayEvent::QuickAsk);
+                    }
+                }
+            }
+            while let Ok(event) = MenuEvent::receiver().try_recv() {
+                match event.id.as_ref() {
+                    QUICK_ASK_ID => return Some(TrayEvent::QuickAsk),
+                    SHOW_ID => return Some(TrayEvent::Show),
+                    QUIT_ID => return Some(TrayEvent::Quit),
+                    _ => {}
+                }
+            }
+            while let Ok(event) = TrayIconEvent::receiver().try_recv() {
+                if let TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    return Some(TrayEvent::Show);
+                }
+            }
+            None
+        }
+
+        /// The registered hotkey as text, e.g. "control+shift+Space".
+        pub fn hotkey_label(&self) -> Option<String> {
+            self.hotkey.as_ref().map(|(_, hotkey)| hotkey.into_string())
+        }
+    }
+
+    fn build_icon() -> Result<tray_icon::TrayIcon> {
+        let menu = Menu::new();
+        menu.append_items(&[
+            &MenuItem::with_id(QUICK_ASK_ID, "Quick ask", true, None),
+            &MenuItem::with_id(SHOW_ID, "Show ARULA", true, None),
+            &PredefinedMenuItem::separator(),
+            &MenuItem::with_id(QUIT_ID, "Quit", true, None),
+        ])?;
+        let icon = Icon::from_rgba(icon_rgba(false), ICON_SIZE, ICON_SIZE)?;
+        Ok(TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip(TOOLTIP)
+            .with_icon(icon)
+            .build()?)
+    }
+
+    fn show_badge(tray: &tray_icon::TrayIcon, waiting: bool) {
+        let tooltip = if waiting { WAITING_TOOLTIP } else { TOOLTIP };
+        let updated = Icon::from_rgba(icon_rgba(waiting), ICON_SIZE, ICON_SIZE)
+            .map_err(anyhow::Error::from)
+            .and_then(|icon| Ok(tray.set_icon(Some(icon))?))
+            .and_then(|_| Ok(tray.set_tooltip(Some(tooltip))?));
+        if let Err(e) = updated {
+            eprintln!("⚠️ Couldn't update the tray icon: {e:#}");
+        }
+    }
+
+    fn register_hotkey(spec: &str) -> Result<(GlobalHotKeyManager, HotKey)> {
+        let hotkey: HotKey = spec
+            .parse()
+            .map_err(|e| anyhow!("invalid hotkey {spec:?}: {e}"))?;
+        let manager = GlobalHotKeyManager::new()?;
+        manager
+            .register(hotkey)
+            .with_context(|| format!("couldn't register {spec}"))?;
+        Ok((manager, hotkey))
+    }
+
+    /// A filled accent-colored circle, so no image files need shipping;
+    /// with `badge`, a red dot in the top right corner.
+    fn icon_rgba(badge: bool) -> Vec<u8> {
+        let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+        let radius = ICON_SIZE as f32 / 2.0 - 1.0;
+        let badge_center = ICON_SIZE as f32 - 8.0;
+        (0..ICON_SIZE * ICON_SIZE)
+            .flat_map(|i| {
+                let x = (i % ICON_SIZE) as f32;
+                let y = (i / ICON_SIZE) as f32;
+                let (dx, dy) = (x - center, y - center);
+                // One pixel of antialiasing at the edge
+                let coverage = (radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+                let dot = if badge {
+                    let (dx, dy) = (x - badge_center, y - 7.0);
+                    (7.0 - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let mix = |base: u8, red: u8| (base as f32 * (1.0 - dot) + red as f32 * dot) as u8;
+                [
+                    mix(0x7a, 0xf7),
+                    mix(0xa2, 0x4a),
+                    mix(0xf7, 0x4a),
+                    (coverage.max(dot) * 255.0) as u8,
+                ]
+            })
+            .collect()
+    }
+}
+
+/// Stand-in used when the app is built without the `tray` feature.
+#[cfg(not(feature = "tray"))]
+pub struct Tray;
+
+#[cfg(not(feature = "tray"))]
+impl Tray {
+    pub fn start(_config: &arula_core::utils::config::TrayConfig) -> anyhow::Result<Self> {
+        anyhow::bail!("built without the `tray` feature")
+    }
+
+    pub fn try_recv(&self) -> Option<TrayEvent> {
+        None
+    }
+
+    pub fn hotkey_label(&self) -> Option<String> {
+        None
+    }
+
+    pub fn set_attention(&self, _waiting: bool) {}
+}
@@ -0,0 +1,353 @@
+//! Model capability metadata
+//!
+//! A table of what well-known models can do — context window, image
+//! input, tool calling, streaming — and what they cost, bundled with the
+//! binary. Entries in the `models` section of config.json add models or
+//! correct bundled values:
+//!
+//! ```json
+//! "models": {
+//!   "my-finetune": { "context_window": 32768, "tools": true, "vision": false },
+//!   "gpt-4o": { "input_price": 2.0 }
+//! }
+//! ```
+//!
+//! Model IDs match by prefix at a word boundary, so `gpt-4o` also covers
+//! `gpt-4o-2024-08-06` and `openai/gpt-4o`. Features check the table and
+//! warn when the selected model can't do what they need, instead of
+//! failing with an obscure provider error.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+/// What a model supports
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ModelCapabilities {
+    /// Context window in tokens
+    pub context_window: u32,
+    /// Accepts image input
+    pub vision: bool,
+    /// Supports native tool calling
+    pub tools: bool,
+    pub streaming: bool,
+    /// USD per million input tokens (`None`: unknown or subscription)
+    pub input_price: Option<f64>,
+    /// USD per million output tokens
+    pub output_price: Option<f64>,
+}
+
+impl ModelCapabilities {
+    /// Assumed for models only described in config
+    const DEFAULT: Self = Self {
+        context_window: 8192,
+        vision: false,
+        tools: true,
+        streaming: true,
+        input_price: None,
+        output_price: None,
+    };
+
+    /// Cost in USD of a request, when the price is known
+    pub fn cost(&self, prompt_tokens: u64, completion_tokens: u64) -> Option<f64> {
+        Some(
+            (prompt_tokens as f64 * self.input_price?
+                + completion_tokens as f64 * self.output_price?)
+                / 1_000_000.0,
+        )
+    }
+
+    fn supports(&self, feature: Feature) -> bool {
+        match feature {
+            Feature::Vision => self.vision,
+            Feature::Tools => self.tools,
+            Feature::Streaming => self.streaming,
+        }
+    }
+}
+
+/// Capability settings for a model (`models.<id>` in config.json)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub streaming: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_price: Option<f64>,
+}
+
+impl CapabilityOverride {
+    fn apply(&self, base: ModelCapabilities) -> ModelCapabilities {
+        ModelCapabilities {
+            context_window: self.context_window.unwrap_or(base.context_window),
+            vision: self.vision.unwrap_or(base.vision),
+            tools: self.tools.unwrap_or(base.tools),
+            streaming: self.streaming.unwrap_or(base.streaming),
+            input_price: self.input_price.or(base.input_price),
+            output_price: self.output_price.or(base.output_price),
+        }
+    }
+}
+
+/// Something a feature needs from the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Vision,
+    Tools,
+    Streaming,
+}
+
+impl Feature {
+    fn warning(self, model: &str) -> String {
+        match self {
+            Feature::Vision => format!("{} can't read images; pick a vision model", model),
+            Feature::Tools => format!(
+                "{} doesn't support tool calling; file, shell and search tools won't work",
+                model
+            ),
+            Feature::Streaming => format!(
+                "{} doesn't support streaming; turn streaming off for this provider",
+                model
+            ),
+        }
+    }
+}
+
+const fn model(
+    context_window: u32,
+    vision: bool,
+    tools: bool,
+    prices: Option<(f64, f64)>,
+) -> ModelCapabilities {
+    let (input_price, output_price) = match prices {
+        Some((input, output)) => (Some(input), Some(output)),
+        None => (None, None),
+    };
+    ModelCapabilities {
+        context_window,
+        vision,
+        tools,
+        streaming: true,
+        input_price,
+        output_price,
+    }
+}
+
+/// Bundled capabilities by model ID prefix
+#[rustfmt::skip]
+const BUNDLED: &[(&str, ModelCapabilities)] = &[
+    // OpenAI
+    ("gpt-5", model(400_000, true, true, Some((1.25, 10.0)))),
+    ("gpt-5-mini", model(400_000, true, true, Some((0.25, 2.0)))),
+    ("gpt-5-nano", model(400_000, true, true, Some((0.05, 0.40)))),
+    ("gpt-4.1", model(1_047_576, true, true, Some((2.0, 8.0)))),
+    ("gpt-4.1-mini", model(1_047_576, true, true, Some((0.40, 1.60)))),
+    ("gpt-4.1-nano", model(1_047_576, true, true, Some((0.10, 0.40)))),
+    ("gpt-4o", model(128_000, true, true, Some((2.50, 10.0)))),
+    ("gpt-4o-mini", model(128_000, true, true, Some((0.15, 0.60)))),
+    ("gpt-4-turbo", model(128_000, true, true, Some((10.0, 30.0)))),
+    ("gpt-4", model(8_192, false, true, Some((30.0, 60.0)))),
+    ("gpt-3.5-turbo", model(16_385, false, true, Some((0.50, 1.50)))),
+    ("o1", model(200_000, true, true, Some((15.0, 60.0)))),
+    ("o1-mini", model(128_000, false, false, Some((1.10, 4.40)))),
+    ("o3", model(200_000, true, true, Some((2.0, 8.0)))),
+    ("o3-mini", model(200_000, false, true, Some((1.10, 4.40)))),
+    ("o4-mini", model(200_000, true, true, Some((1.10, 4.40)))),
+    // Anthropic
+    ("claude-opus-4", model(200_000, true, true, Some((15.0, 75.0)))),
+    ("claude-sonnet-4", model(200_000, true, true, Some((3.0, 15.0)))),
+    ("claude-3.7-sonnet", model(200_000, true, true, Some((3.0, 15.0)))),
+    ("claude-3-7-sonnet", model(200_000, true, true, Some((3.0, 15.0)))),
+    ("claude-3.5-sonnet", model(200_000, true, true, Some((3.0, 15.0)))),
+    ("claude-3-5-sonnet", model(200_000, true, true, Some((3.0, 15.0)))),
+    ("claude-3.5-haiku", model(200_000, true, true, Some((0.80, 4.0)))),
+    ("claude-3-5-haiku", model(200_000, true, true, Some((0.80, 4.0)))),
+    ("claude-3-opus", model(200_000, true, true, Some((15.0, 75.0)))),
+    ("claude-3-haiku", model(200_000, true, true, Some((0.25, 1.25)))),
+    // Google
+    ("gemini-2.5-pro", model(1_048_576, true, true, Some((1.25, 10.0)))),
+    ("gemini-2.5-flash", model(1_048_576, true, true, Some((0.30, 2.50)))),
+    ("gemini-2.0-flash", model(1_048_576, true, true, Some((0.10, 0.40)))),
+    ("gemini-1.5-pro", model(2_097_152, true, true, Some((1.25, 5.0)))),
+    ("gemini-1.5-flash", model(1_048_576, true, true, Some((0.075, 0.30)))),
+    // Z.ai (the coding plan is a subscription, so no per-token price)
+    ("glm-4.6", model(200_000, false, true, None)),
+    ("glm-4.5", model(128_000, false, true, None)),
+    ("glm-4.5-air", model(128_000, false, true, None)),
+    ("glm-4.5v", model(64_000, true, true, None)),
+    // DeepSeek
+    ("deepseek-chat", model(128_000, false, true, Some((0.27, 1.10)))),
+    ("deepseek-reasoner", model(128_000, false, false, Some((0.55, 2.19)))),
+    // Local models (Ollama)
+    ("llama3.1", model(128_000, false, true, Some((0.0, 0.0)))),
+    ("llama3.2", model(128_000, false, true, Some((0.0, 0.0)))),
+    ("llama3.2-vision", model(128_000, true, false, Some((0.0, 0.0)))),
+    ("llama3", model(8_192, false, false, Some((0.0, 0.0)))),
+    ("llava", model(4_096, true, false, Some((0.0, 0.0)))),
+    ("qwen2.5-coder", model(32_768, false, true, Some((0.0, 0.0)))),
+    ("qwen2.5", model(32_768, false, true, Some((0.0, 0.0)))),
+    ("qwen3", model(40_960, false, true, Some((0.0, 0.0)))),
+    ("mistral", model(32_768, false, true, Some((0.0, 0.0)))),
+    ("codellama", model(16_384, false, false, Some((0.0, 0.0)))),
+    ("gemma3", model(128_000, true, false, Some((0.0, 0.0)))),
+];
+
+static OVERRIDES: RwLock<BTreeMap<String, CapabilityOverride>> = RwLock::new(BTreeMap::new());
+
+/// Use the `models` section of config.json for `lookup`
+pub fn set_overrides(overrides: &HashMap<String, CapabilityOverride>) {
+    if let Ok(mut current) = OVERRIDES.write() {
+        *current = overrides
+            .iter()
+            .map(|(id, o)| (id.clone(), o.clone()))
+            .collect();
+    }
+}
+
+/// Capabilities of a model, with the overrides from `set_overrides`
+pub fn lookup(model_id: &str) -> Option<ModelCapabilities> {
+    let overrides = OVERRIDES.read().ok()?;
+    lookup_in(model_id, overrides.iter())
+}
+
+/// Capabilities of a model with the given overrides; `None` if the model
+/// is neither bundled nor configured
+pub fn lookup_in<'a>(
+    model_id: &str,
+    overrides: impl IntoIterator<Item = (&'a String, &'a CapabilityOverride)>,
+) -> Option<ModelCapabilities> {
+    let id = normalize(model_id);
+    let bundled = best_match(&id, BUNDLED.iter().map(|(prefix, caps)| (*prefix, caps)));
+    let overrides: Vec<_> = overrides.into_iter().collect();
+    let configured = best_match(
+        &id,
+        overrides.iter().map(|(prefix, o)| (prefix.as_str(), *o)),
+    );
+    match (bundled, configured) {
+        (base, Some(o)) => Some(o.apply(base.copied().unwrap_or(ModelCapabilities::DEFAULT))),
+        (Some(base), None) => Some(*base),
+        (None, None) => None,
+    }
+}
+
+/// Warnings for the features `model_id` lacks; models missing from the
+/// table are assumed capable
+pub fn missing_features(
+    model_id: &str,
+    caps: Option<&ModelCapabilities>,
+    features: &[Feature],
+) -> Vec<String> {
+    let Some(caps) = caps else {
+        return Vec::new();
+    };
+    features
+        .iter()
+        .filter(|f| !caps.supports(**f))
+        .map(|f| f.warning(model_id))
+        .collect()
+}
+
+/// The entry with the longest prefix matching `id` at a word boundary
+fn best_match<'a, T>(id: &str, entries: impl Iterator<Item = (&'a str, T)>) -> Option<T> {
+    entries
+        .filter_map(|(prefix, value)| {
+            let prefix = normalize(prefix);
+            let rest = id.strip_prefix(prefix.as_str())?;
+            matches!(rest.chars().next(), None | Some('-' | ':' | '@' | '_'))
+                .then_some((prefix.len(), value))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, value)| value)
+}
+
+/// Lowercase, without a provider prefix (`openai/gpt-4o`)
+fn normalize(model_id: &str) -> String {
+    let id = model_id.trim().to_lowercase();
+    match id.rsplit_once('/') {
+        Some((_, name)) => name.to_string(),
+        None => id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_matches_prefixes() {
+        let none = HashMap::new();
+        let caps = lookup_in("gpt-4o-2024-08-06", &none).unwrap();
+        assert!(caps.vision && caps.tools);
+        assert_eq!(caps.context_window, 128_000);
+
+        assert_eq!(
+            lookup_in("openai/gpt-4o-mini", &none).unwrap().input_price,
+            Some(0.15)
+        );
+        assert_eq!(
+            lookup_in("anthropic/claude-3.5-sonnet", &none)
+                .unwrap()
+                .output_price,
+            Some(15.0)
+        );
+        assert!(!lookup_in("gpt-4-0613", &none).unwrap().vision);
+        assert!(lookup_in("llava:13b", &none).unwrap().vision);
+        assert!(lookup_in("gpt-4-turbo", &none).unwrap().vision);
+        // No match in the middle of a word
+        assert!(lookup_in("o1x", &none).is_none());
+        assert!(lookup_in("unknown-model", &none).is_none());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "gpt-4o".to_string(),
+            CapabilityOverride {
+                input_price: Some(1.0),
+                ..Default::default()
+            },
+        );
+        overrides.insert(
+            "my-finetune".to_string(),
+            CapabilityOverride {
+                context_window: Some(32_768),
+                ..Default::default()
+            },
+        );
+        let caps = lookup_in("gpt-4o", &overrides).unwrap();
+        assert_eq!(caps.input_price, Some(1.0));
+        assert_eq!(caps.output_price, Some(10.0));
+        assert_eq!(
+            lookup_in("my-finetune-v2", &overrides)
+                .unwrap()
+                .context_window,
+            32_768
+        );
+
+        let cost = caps.cost(1_000_000, 100_000).unwrap();
+        assert!((cost - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_features() {
+        let none = HashMap::new();
+        let caps = lookup_in("o1-mini", &none);
+        let warnings = missing_features(
+            "o1-mini",
+            caps.as_ref(),
+            &[Feature::Vision, Feature::Tools, Feature::Streaming],
+        );
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("can't read images"));
+        assert!(warnings[1].contains("tool calling"));
+        assert!(missing_features("unknown", None, &[Feature::Vision]).is_empty());
+    }
+}
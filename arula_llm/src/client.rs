@@ -1,7 +1,12 @@
-use anyhow::{anyhow, Result};
+use crate::generation::GenerationSettings;
+use crate::logger::LogLevel;
+use crate::provider::ProviderOptions;
+use crate::provider_error::ProviderError;
+use crate::stream;
+use anyhow::{Result, anyhow};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{Value, json};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Z.AI specific error types
@@ -53,12 +58,11 @@ impl ZAIApiError {
 }
 
 /// Debug print helper that checks ARULA_DEBUG environment variable
-fn debug_print(msg: &str) {
+pub(crate) fn debug_print(msg: &str) {
     if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
         println!("🔧 DEBUG: {}", msg);
-        // Also log to file
-        crate::utils::logger::debug(msg);
     }
+    crate::logger::log_module(LogLevel::Debug, "api", msg);
 }
 
 /// Log raw HTTP request details
@@ -93,7 +97,7 @@ fn log_http_request(
 
     log_msg.push_str("===================\n");
 
-    crate::utils::logger::info(&log_msg);
+    crate::logger::log_module(LogLevel::Debug, "http", &log_msg);
 }
 
 /// Log raw HTTP response details (without consuming the body)
@@ -116,7 +120,7 @@ fn log_http_response(response: &reqwest::Response) {
     log_msg.push_str("BODY: <not logged to avoid consumption>\n");
     log_msg.push_str("===================\n");
 
-    crate::utils::logger::info(&log_msg);
+    crate::logger::log_module(LogLevel::Debug, "http", &log_msg);
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,6 +137,19 @@ pub struct ChatMessage {
     pub tool_name: Option<String>,
 }
 
+impl ChatMessage {
+    /// A user message with `content`
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: Some(content.into()),
+            tool_calls: None,
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
@@ -210,6 +227,8 @@ pub struct ApiClient {
     pub endpoint: String,
     api_key: String,
     model: String,
+    generation: GenerationSettings,
+    options: ProviderOptions,
 }
 
 impl ApiClient {
@@ -277,16 +296,57 @@ impl ApiClient {
             endpoint: normalized_endpoint,
             api_key,
             model,
+            generation: GenerationSettings::default(),
+            options: ProviderOptions::default(),
         }
     }
 
+    /// Use configured sampling parameters instead of the defaults
+    pub fn with_generation(mut self, generation: GenerationSettings) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Use the user's provider settings instead of the defaults
+    pub fn with_options(mut self, options: ProviderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
     /// Get the current model name
     pub fn model(&self) -> &str {
         &self.model
     }
 
+    /// Get the sampling parameters sent with requests
+    pub fn generation(&self) -> GenerationSettings {
+        self.generation
+    }
+
+    /// The streaming request body for a conversation, in the format this
+    /// client's API expects
+    pub fn streaming_request(&self, messages: &[ChatMessage], tools: &[Value]) -> Value {
+        if stream::is_anthropic_compatible_endpoint(&self.endpoint) {
+            stream::build_anthropic_request(
+                &self.model,
+                messages,
+                Some(tools),
+                self.generation,
+                self.options.thinking,
+            )
+        } else {
+            stream::build_streaming_request(
+                &self.provider,
+                &self.model,
+                messages,
+                Some(tools),
+                self.generation,
+                self.options.thinking,
+            )
+        }
+    }
+
     /// Send a raw streaming request and return the HTTP response
-    /// Used by the unified stream.rs module
     pub async fn make_streaming_request(
         &self,
         request_body: serde_json::Value,
@@ -360,15 +420,11 @@ impl ApiClient {
         let response = request_builder.json(&request_body).send().await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-
-            // Check for specific Z.AI errors
-            if self.provider == AIProvider::ZAiCoding {
-                return Err(ZAIApiError::from_status_code(status.as_u16(), &text).into());
-            }
-
-            return Err(anyhow!("API Error {}: {}", status, text));
+            let provider = format!("{:?}", self.provider);
+            return Err(ProviderError::from_response(&provider, response)
+                .await
+                .record()
+                .into());
         }
 
         Ok(response)
@@ -418,13 +474,14 @@ impl ApiClient {
         messages: Vec<ChatMessage>,
         tools: Option<Vec<serde_json::Value>>,
     ) -> Result<ApiResponse> {
-        // Load configuration
-        let config = crate::utils::config::Config::load_or_default()?;
-        let thinking_enabled = config.get_thinking_enabled().unwrap_or(false);
+        let thinking_enabled = self.options.thinking;
 
         // Debug: Check if thinking is enabled for Z.AI
         if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-            eprintln!("🧠 DEBUG: thinking_enabled = {} for provider {:?}", thinking_enabled, self.provider);
+            eprintln!(
+                "🧠 DEBUG: thinking_enabled = {} for provider {:?}",
+                thinking_enabled, self.provider
+            );
             eprintln!("🧠 DEBUG: endpoint = {}", self.endpoint);
         }
 
@@ -434,7 +491,7 @@ impl ApiClient {
                 // Claude-specific request format
                 let mut request = json!({
                     "model": self.model,
-                    "max_tokens": 4096,
+                    "max_tokens": self.generation.max_tokens(),
                     "messages": messages.iter().map(|msg| {
                         let mut msg_obj = json!({
                             "role": msg.role,
@@ -490,9 +547,12 @@ impl ApiClient {
 
                 // Add Ollama-specific options
                 request["options"] = json!({
-                    "temperature": 0.7,
-                    "num_predict": 4096
+                    "temperature": self.generation.temperature(),
+                    "num_predict": self.generation.max_tokens()
                 });
+                if let Some(top_p) = self.generation.top_p {
+                    request["options"]["top_p"] = json!(top_p);
+                }
 
                 request
             }
@@ -566,7 +626,7 @@ impl ApiClient {
 
                     let mut request = json!({
                         "model": self.model,
-                        "max_tokens": 4096,
+                        "max_tokens": self.generation.max_tokens(),
                         "messages": anthropic_messages,
                         "stream": false
                     });
@@ -578,13 +638,17 @@ impl ApiClient {
                     // Add thinking mode if enabled (for Z.AI Anthropic-compatible endpoint)
                     if thinking_enabled {
                         if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                            eprintln!("🧠 DEBUG: Adding thinking block to Z.AI Anthropic-compatible request");
+                            eprintln!(
+                                "🧠 DEBUG: Adding thinking block to Z.AI Anthropic-compatible request"
+                            );
                         }
                         request["thinking"] = serde_json::json!({
                             "type": "enabled"
                         });
                     } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                        eprintln!("🧠 DEBUG: NOT adding thinking block - thinking_enabled is false");
+                        eprintln!(
+                            "🧠 DEBUG: NOT adding thinking block - thinking_enabled is false"
+                        );
                     }
 
                     // Convert tools to Anthropic format
@@ -653,13 +717,14 @@ impl ApiClient {
                         .collect();
 
                     // Set up model-specific parameters based on official GLM specs
-                    let max_tokens = match self.model.as_str() {
+                    let model_limit = match self.model.as_str() {
                         "GLM-4.6" => 65536,
                         "GLM-4.5" | "GLM-4.5-AIR" | "GLM-4.5-X" | "GLM-4.5-AIRX"
                         | "GLM-4.5-FLASH" | "GLM-4.5V" => 65536,
                         "GLM-4-32B-0414-128K" => 16384,
                         _ => 2048,
                     };
+                    let max_tokens = self.generation.max_tokens.unwrap_or(model_limit);
 
                     let mut request = json!({
                         "model": self.model,
@@ -716,11 +781,15 @@ impl ApiClient {
 
                         msg_obj
                     }).collect::<Vec<_>>(),
-                    "temperature": 0.7,
-                    "max_tokens": 4096,
+                    "temperature": self.generation.temperature(),
+                    "max_tokens": self.generation.max_tokens(),
                     "stream": false
                 });
 
+                if let Some(top_p) = self.generation.top_p {
+                    request["top_p"] = json!(top_p);
+                }
+
                 // Add tools if provided
                 if let Some(t) = tools {
                     if !t.is_empty() {
@@ -824,19 +893,16 @@ impl ApiClient {
 
         // Handle the response
         if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
+            let error = ProviderError::from_response(&format!("{:?}", self.provider), response)
+                .await
+                .record();
 
             // Log the response for debugging
             if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                println!("🔧 DEBUG: API Response ({}): {}", status, text);
+                println!("🔧 DEBUG: API Response ({}): {}", error.status, error.body);
             }
 
-            return Err(anyhow::anyhow!(
-                "API request failed with status {}: {}",
-                status,
-                text
-            ));
+            return Err(error.into());
         }
 
         // Parse response based on provider
@@ -935,10 +1001,14 @@ impl ApiClient {
                                 "thinking" => {
                                     // GLM 4.7 and Claude use "thinking" content blocks
                                     // The thinking text is in the "thinking" field
-                                    if let Some(thinking) = block.get("thinking").and_then(|t| t.as_str()) {
+                                    if let Some(thinking) =
+                                        block.get("thinking").and_then(|t| t.as_str())
+                                    {
                                         thinking_content = Some(thinking.to_string());
                                         if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
-                                            eprintln!("🧠 DEBUG: Found thinking block in Anthropic-format response");
+                                            eprintln!(
+                                                "🧠 DEBUG: Found thinking block in Anthropic-format response"
+                                            );
                                         }
                                     }
                                 }
@@ -1100,14 +1170,13 @@ impl ApiClient {
         // Tools are only added when explicitly needed via send_message_with_tools
 
         // Check if thinking/reasoning is enabled
-        let config = crate::utils::config::Config::load_or_default()?;
-        let thinking_enabled = config.get_thinking_enabled().unwrap_or(false);
+        let thinking_enabled = self.options.thinking;
 
         let mut request_body = serde_json::json!({
             "model": self.model,
             "messages": messages,
-            "temperature": 0.7,
-            "max_tokens": 2048
+            "temperature": self.generation.temperature(),
+            "max_tokens": self.generation.max_tokens()
         });
 
         // Add reasoning effort when thinking is enabled
@@ -1240,18 +1309,16 @@ impl ApiClient {
                 })
             }
         } else {
-            let error_text = response
-                .text()
+            Err(ProviderError::from_response("OpenAI", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("OpenAI API request failed: {}", error_text))
+                .record()
+                .into())
         }
     }
 
     async fn send_claude_request(&self, messages: Vec<ChatMessage>) -> Result<ApiResponse> {
         // Check if thinking is enabled
-        let config = crate::utils::config::Config::load_or_default()?;
-        let thinking_enabled = config.get_thinking_enabled().unwrap_or(false);
+        let thinking_enabled = self.options.thinking;
 
         let claude_messages: Vec<Value> = messages
             .into_iter()
@@ -1266,8 +1333,8 @@ impl ApiClient {
         let mut request = json!({
             "model": self.model,
             "messages": claude_messages,
-            "max_tokens": 2048,
-            "temperature": 0.7
+            "max_tokens": self.generation.max_tokens(),
+            "temperature": self.generation.temperature()
         });
 
         // Add extended thinking for Claude when enabled
@@ -1369,18 +1436,16 @@ impl ApiClient {
                 reasoning_content: None,
             })
         } else {
-            let error_text = response
-                .text()
+            Err(ProviderError::from_response("Claude", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Claude API request failed: {}", error_text))
+                .record()
+                .into())
         }
     }
 
     async fn send_ollama_request(&self, messages: Vec<ChatMessage>) -> Result<ApiResponse> {
         // Check if thinking is enabled
-        let config = crate::utils::config::Config::load_or_default()?;
-        let thinking_enabled = config.get_thinking_enabled().unwrap_or(false);
+        let thinking_enabled = self.options.thinking;
 
         // Convert messages to Ollama format (compatible with OpenAI format)
         let ollama_messages: Vec<Value> = messages
@@ -1398,8 +1463,8 @@ impl ApiClient {
             "messages": ollama_messages,
             "stream": false,
             "options": {
-                "temperature": 0.7,
-                "num_predict": 2048
+                "temperature": self.generation.temperature(),
+                "num_predict": self.generation.max_tokens()
             }
         });
 
@@ -1492,21 +1557,18 @@ impl ApiClient {
                 })
             }
         } else {
-            let error_text = response
-                .text()
+            Err(ProviderError::from_response("Ollama", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Ollama API request failed: {}", error_text))
+                .record()
+                .into())
         }
     }
 
     async fn send_zai_request(&self, messages: Vec<ChatMessage>) -> Result<ApiResponse> {
-        // Get Z.AI configuration from the config file
-        let config = crate::utils::config::Config::load_or_default()?;
-        let max_retries = config.get_zai_max_retries();
-        let timeout = Duration::from_secs(config.get_zai_timeout_seconds());
-        let thinking_enabled = config.get_thinking_enabled().unwrap_or(false);
-        let usage_tracking = config.get_zai_usage_tracking_enabled().unwrap_or(true);
+        let max_retries = self.options.max_retries;
+        let timeout = self.options.timeout;
+        let thinking_enabled = self.options.thinking;
+        let usage_tracking = self.options.usage_tracking;
 
         // Convert ChatMessage format to plain objects for Z.AI
         // Filter out tool-related messages to avoid error 1210
@@ -1533,13 +1595,14 @@ impl ApiClient {
             .collect();
 
         // Set up model-specific parameters based on official GLM specs
-        let max_tokens = match self.model.as_str() {
+        let model_limit = match self.model.as_str() {
             "GLM-4.6" => 65536, // Official default for GLM-4.6
             "GLM-4.5" | "GLM-4.5-AIR" | "GLM-4.5-X" | "GLM-4.5-AIRX" | "GLM-4.5-FLASH"
             | "GLM-4.5V" => 65536, // Official default for GLM-4.5 series
             "GLM-4-32B-0414-128K" => 16384, // Official default for older model
             _ => 2048,          // Fallback for other models
         };
+        let max_tokens = self.generation.max_tokens.unwrap_or(model_limit);
 
         // Log the model being used for debugging
         debug_print(&format!(
@@ -1551,7 +1614,7 @@ impl ApiClient {
         let mut request = json!({
             "model": &self.model,
             "messages": zai_messages,
-            "temperature": 0.7,   // Use default temperature for GLM models
+            "temperature": self.generation.temperature(),
             "max_tokens": max_tokens,
             "stream": false
         });
@@ -1715,10 +1778,10 @@ impl ApiClient {
                                     tool_calls,
                                     model: Some(self.model.clone()),
                                     created: response_json["created"].as_u64(),
-                                    reasoning_content: response_json["choices"][0]["message"]
-                                        ["reasoning_content"]
-                                        .as_str()
-                                        .map(|s| s.to_string()),
+                                    reasoning_content:
+                                        response_json["choices"][0]["message"]["reasoning_content"]
+                                            .as_str()
+                                            .map(|s| s.to_string()),
                                 });
                             }
                         }
@@ -1726,15 +1789,15 @@ impl ApiClient {
                         return Err(anyhow!("No choices in Z.AI response"));
                     } else {
                         // Handle HTTP errors with Z.AI-specific mapping
-                        let error_body = resp.text().await.unwrap_or_default();
-                        let api_error = ZAIApiError::from_status_code(status.as_u16(), &error_body);
+                        let error = ProviderError::from_response("Z.AI", resp).await.record();
+                        let api_error = ZAIApiError::from_status_code(status.as_u16(), &error.body);
 
                         // Log detailed error information
-                        debug_print(&format!("Z.AI API error ({}): {}", status, error_body));
+                        debug_print(&format!("Z.AI API error ({}): {}", status, error.body));
 
                         // Don't retry on client errors (4xx)
                         if status.is_client_error() {
-                            return Err(anyhow!("Z.AI API error ({}): {}", status, api_error));
+                            return Err(error.into());
                         }
 
                         // Log retry attempt
@@ -1751,11 +1814,7 @@ impl ApiClient {
                             .await;
                             continue;
                         } else {
-                            return Err(anyhow!(
-                                "Z.AI API request failed after {} retries: {}",
-                                max_retries,
-                                api_error
-                            ));
+                            return Err(error.into());
                         }
                     }
                 }
@@ -1805,8 +1864,8 @@ impl ApiClient {
         let request_body = serde_json::json!({
             "model": self.model,
             "messages": messages,
-            "temperature": 0.7,
-            "max_tokens": 2048
+            "temperature": self.generation.temperature(),
+            "max_tokens": self.generation.max_tokens()
         });
 
         // Use provider-specific endpoint
@@ -1929,14 +1988,10 @@ impl ApiClient {
                 })
             }
         } else {
-            let error_text = response
-                .text()
+            Err(ProviderError::from_response("OpenRouter", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!(
-                "OpenRouter API request failed: {}",
-                error_text
-            ))
+                .record()
+                .into())
         }
     }
 
@@ -1952,8 +2007,8 @@ impl ApiClient {
             let request_body = serde_json::json!({
                 "model": self.model,
                 "messages": messages,
-                "temperature": 0.7,
-                "max_tokens": 2048
+                "temperature": self.generation.temperature(),
+                "max_tokens": self.generation.max_tokens()
             });
 
             let mut request_builder = self
@@ -1973,11 +2028,10 @@ impl ApiClient {
                 let api_response: ApiResponse = response.json().await?;
                 Ok(api_response)
             } else {
-                let error_text = response
-                    .text()
+                Err(ProviderError::from_response("Custom", response)
                     .await
-                    .unwrap_or_else(|_| "Unknown error".to_string());
-                Err(anyhow::anyhow!("Custom API request failed: {}", error_text))
+                    .record()
+                    .into())
             }
         }
     }
@@ -2035,8 +2089,8 @@ impl ApiClient {
             let mut req = json!({
                 "model": self.model,
                 "messages": zai_messages,
-                "temperature": 0.7f32,
-                "max_tokens": 2048,
+                "temperature": self.generation.temperature(),
+                "max_tokens": self.generation.max_tokens(),
                 "stream": false
             });
 
@@ -2144,11 +2198,10 @@ impl ApiClient {
 
             Err(anyhow::anyhow!("Invalid response format from Z.AI API"))
         } else {
-            let error_text = response
-                .text()
+            Err(ProviderError::from_response("Z.AI", response)
                 .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            Err(anyhow::anyhow!("Z.AI API request failed: {}", error_text))
+                .record()
+                .into())
         }
     }
 
@@ -2387,6 +2440,7 @@ mod tests {
             content: Some("Command executed successfully".to_string()),
             tool_calls: None,
             tool_call_id: Some("call_1".to_string()),
+            tool_name: None,
         };
 
         let json_str = serde_json::to_string(&message).unwrap();
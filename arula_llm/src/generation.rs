@@ -0,0 +1,59 @@
+//! Sampling parameters sent with requests
+
+use anyhow::Result;
+
+/// Sampling parameters sent with each request
+///
+/// Unset values fall back to the defaults of the request being built.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GenerationSettings {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationSettings {
+    /// Temperature used when none is configured
+    pub const DEFAULT_TEMPERATURE: f32 = 0.7;
+    /// Response length limit used when none is configured
+    pub const DEFAULT_MAX_TOKENS: u32 = 4096;
+    /// Setting names, as accepted by the config's `set_generation_setting`
+    pub const NAMES: [&'static str; 3] = ["temperature", "top_p", "max_tokens"];
+
+    pub fn temperature(&self) -> f32 {
+        self.temperature.unwrap_or(Self::DEFAULT_TEMPERATURE)
+    }
+
+    pub fn max_tokens(&self) -> u32 {
+        self.max_tokens.unwrap_or(Self::DEFAULT_MAX_TOKENS)
+    }
+
+    /// Parse a temperature, which must be from 0 to 2
+    pub fn parse_temperature(value: &str) -> Result<f32> {
+        parse_in_range(value.trim(), 0.0, 2.0)
+    }
+
+    /// One-line summary, e.g. `temperature 0.2 · top_p default · max_tokens 4096`
+    pub fn summary(&self) -> String {
+        let show = |value: Option<String>| value.unwrap_or_else(|| "default".to_string());
+        format!(
+            "temperature {} · top_p {} · max_tokens {}",
+            show(self.temperature.map(|t| t.to_string())),
+            show(self.top_p.map(|p| p.to_string())),
+            show(self.max_tokens.map(|m| m.to_string())),
+        )
+    }
+}
+
+/// Parse a number within `min..=max`
+pub fn parse_in_range(value: &str, min: f32, max: f32) -> Result<f32> {
+    match value.parse::<f32>() {
+        Ok(number) if (min..=max).contains(&number) => Ok(number),
+        _ => Err(anyhow::anyhow!(
+            "Expected a number from {} to {}, got '{}'",
+            min,
+            max,
+            value
+        )),
+    }
+}
@@ -0,0 +1,190 @@
+//! Tolerant parsing of streamed tool-call arguments
+//!
+//! Models stream tool arguments as JSON text in fragments, and the result is
+//! not always valid: a trailing comma, a raw newline inside a string, a
+//! string or object cut off at the end. [`parse_arguments`] can repair the
+//! purely syntactic damage. Arguments that were cut off are never completed,
+//! since the missing rest could change what the call does (a truncated path
+//! deletes its parent). Then, or when repair isn't allowed, the call is not
+//! run and the model gets [`reemit_request`] as the tool result, asking it
+//! to send the call again.
+
+use serde_json::Value;
+
+/// Parse tool-call arguments into a JSON object; with `allow_repair`,
+/// syntactic damage that doesn't change the values is fixed
+pub fn parse_arguments(raw: &str, allow_repair: bool) -> Result<Value, String> {
+    let error = match serde_json::from_str::<Value>(raw) {
+        Ok(value) if value.is_object() => return Ok(value),
+        Ok(value) if raw.trim().is_empty() || value.is_null() => {
+            return Ok(Value::Object(Default::default()));
+        }
+        Ok(_) => return Err("arguments are not a JSON object".to_string()),
+        Err(_) if raw.trim().is_empty() => return Ok(Value::Object(Default::default())),
+        Err(e) => e.to_string(),
+    };
+
+    if !allow_repair {
+        return Err(error);
+    }
+    let Some(repaired) = repair(raw) else {
+        return Err(format!("{}; the arguments were cut off", error));
+    };
+    match serde_json::from_str::<Value>(&repaired) {
+        Ok(value) if value.is_object() => {
+            tracing::debug!("Repaired tool arguments: {} -> {}", raw, repaired);
+            Ok(value)
+        }
+        _ => Err(error),
+    }
+}
+
+/// Tool result that asks the model to send a call with unreadable arguments again
+pub fn reemit_request(tool: &str, error: &str) -> String {
+    format!(
+        "Error: the arguments for {} were not valid JSON ({}). The tool was not run. \
+         Send the call again with the arguments as a single valid JSON object.",
+        tool, error
+    )
+}
+
+/// Drop trailing commas and stray closing brackets, and escape raw control
+/// characters inside strings; `None` when the text was cut off (an
+/// unterminated string, an unclosed bracket or a key without a value)
+pub fn repair(raw: &str) -> Option<String> {
+    let text = strip_code_fence(raw.trim());
+    let mut out = String::with_capacity(text.len());
+    let mut closers = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        if in_string {
+            match c {
+                _ if escaped => {
+                    escaped = false;
+                    out.push(c);
+                }
+                '\\' => {
+                    escaped = true;
+                    out.push(c);
+                }
+                '"' => {
+                    in_string = false;
+                    out.push(c);
+                }
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                _ => out.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' => {
+                closers.push('}');
+                out.push(c);
+            }
+            '[' => {
+                closers.push(']');
+                out.push(c);
+            }
+            '}' | ']' => {
+                // Closers that don't match an open bracket are dropped
+                if closers.last() == Some(&c) {
+                    closers.pop();
+                    trim_trailing_comma(&mut out);
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    if in_string || !closers.is_empty() || out.trim_end().ends_with(':') {
+        return None;
+    }
+    trim_trailing_comma(&mut out);
+    Some(out)
+}
+
+/// Drop whitespace and a comma at the end of `out`
+fn trim_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    if out.ends_with(',') {
+        out.pop();
+        let trimmed = out.trim_end().len();
+        out.truncate(trimmed);
+    }
+}
+
+/// The contents of a ```json fence, or the text itself
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_alphanumeric());
+    rest.trim().strip_suffix("```").unwrap_or(rest).trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_repairs_syntax_damage() {
+        assert_eq!(
+            parse_arguments(r#"{"path": "a.rs", "limit": 10,}"#, true),
+            Ok(json!({"path": "a.rs", "limit": 10}))
+        );
+        assert_eq!(
+            parse_arguments(r#"{"paths": ["a", "b",], "query": "fn main"}"#, true),
+            Ok(json!({"paths": ["a", "b"], "query": "fn main"}))
+        );
+        assert_eq!(
+            parse_arguments("{\"command\": \"echo one\necho two\"}", true),
+            Ok(json!({"command": "echo one\necho two"}))
+        );
+        assert_eq!(
+            parse_arguments("```json\n{\"path\": \"x\"}\n```", true),
+            Ok(json!({"path": "x"}))
+        );
+        assert_eq!(parse_arguments("", true), Ok(json!({})));
+        assert_eq!(parse_arguments("", false), Ok(json!({})));
+    }
+
+    #[test]
+    fn test_truncated_arguments_are_not_completed() {
+        for cut in [
+            r#"{"command": "rm -rf /home/u"#,
+            r#"{"paths": ["a", "b""#,
+            r#"{"path": "x", "limit":"#,
+            r#"{"path": "x""#,
+        ] {
+            let err = parse_arguments(cut, true).unwrap_err();
+            assert!(err.contains("cut off"), "{}: {}", cut, err);
+        }
+    }
+
+    #[test]
+    fn test_no_repair_without_permission() {
+        assert!(parse_arguments(r#"{"path": "a.rs",}"#, false).is_err());
+        assert_eq!(
+            parse_arguments(r#"{"path": "a.rs"}"#, false),
+            Ok(json!({"path": "a.rs"}))
+        );
+    }
+
+    #[test]
+    fn test_unrepairable_arguments() {
+        assert!(parse_arguments(r#"{"path" "x"}"#, true).is_err());
+        assert!(parse_arguments("[1, 2]", true).is_err());
+        assert!(reemit_request("read_file", "expected `:`").contains("read_file"));
+    }
+}
@@ -0,0 +1,40 @@
+//! ARULA's LLM layer
+//!
+//! Talks to the supported providers (OpenAI, Anthropic, Ollama, Z.AI,
+//! OpenRouter and OpenAI-compatible endpoints) without any of ARULA's UI,
+//! tools or storage, so other Rust projects and the ARULA frontends can
+//! depend on it alone.
+//!
+//! # Module Structure
+//!
+//! - `provider` - The [`Provider`] trait and per-client [`ProviderOptions`]
+//! - `client` - [`ApiClient`], the HTTP client for every provider, and the
+//!   message, tool call and usage types
+//! - `stream` - Streaming request bodies and reply parsing for each API
+//! - `generation` - Sampling parameters sent with requests
+//! - `capabilities` - Bundled model capability and pricing table
+//! - `http_client` - Shared HTTP clients with connection pooling
+//! - `provider_error` - Logged provider error responses
+//! - `json_repair` - Tolerant parsing of streamed tool-call arguments
+//! - `xml_toolcall` - Tool calls models write as XML in their text
+//! - `logger` - The log file requests and errors are written to
+
+#![allow(dead_code)]
+
+pub mod capabilities;
+pub mod client;
+pub mod generation;
+pub mod http_client;
+pub mod json_repair;
+pub mod logger;
+pub mod provider;
+pub mod provider_error;
+pub mod stream;
+pub mod xml_toolcall;
+
+pub use client::{
+    AIProvider, ApiClient, ApiResponse, ChatMessage, ToolCall, ToolCallFunction, Usage,
+};
+pub use generation::GenerationSettings;
+pub use provider::{Provider, ProviderOptions};
+pub use stream::ProviderEvent;
@@ -0,0 +1,393 @@
+//! File logger shared by the ARULA crates
+//!
+//! Lines go to `~/.arula/logs/latest.log` as
+//! `[timestamp] [LEVEL] [module] message`. Each module (`api`, `mcp`,
+//! `storage`, ...) can have its own minimum level; everything else uses the
+//! default level, which is `DEBUG` when `ARULA_DEBUG=1` and `INFO` otherwise.
+//! Once the file reaches its size limit it's moved to `latest.1.log`, older
+//! files move up one number, and the oldest is deleted.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Module of lines logged without one
+pub const DEFAULT_MODULE: &str = "arula";
+/// Name of the file being written
+const LOG_FILE: &str = "latest.log";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.3f UTC";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Debug,
+    Warn,
+    Error,
+}
+
+impl std::fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogLevel::Info => write!(f, "INFO"),
+            LogLevel::Debug => write!(f, "DEBUG"),
+            LogLevel::Warn => write!(f, "WARN"),
+            LogLevel::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+impl LogLevel {
+    /// Parse a level name such as `debug` or `WARN`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "debug" => Some(LogLevel::Debug),
+            "info" => Some(LogLevel::Info),
+            "warn" | "warning" => Some(LogLevel::Warn),
+            "error" => Some(LogLevel::Error),
+            _ => None,
+        }
+    }
+
+    /// Rank for filtering: lines below a module's level are dropped
+    pub fn severity(self) -> u8 {
+        match self {
+            LogLevel::Debug => 0,
+            LogLevel::Info => 1,
+            LogLevel::Warn => 2,
+            LogLevel::Error => 3,
+        }
+    }
+}
+
+/// Where and what to log
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogSettings {
+    /// Directory the log files are kept in
+    pub dir: PathBuf,
+    /// Level of modules without their own
+    pub level: LogLevel,
+    /// Levels of single modules; `api` also covers `api::stream`
+    pub modules: HashMap<String, LogLevel>,
+    /// Size at which the file is rotated
+    pub max_bytes: u64,
+    /// Rotated files kept besides the current one
+    pub max_files: usize,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        let debug = std::env::var("ARULA_DEBUG")
+            .map(|v| v == "1" || v.to_lowercase() == "true")
+            .unwrap_or(false);
+        Self {
+            dir: default_log_dir(),
+            level: if debug {
+                LogLevel::Debug
+            } else {
+                LogLevel::Info
+            },
+            modules: HashMap::new(),
+            max_bytes: 5 * 1024 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+impl LogSettings {
+    /// The level that applies to `module`
+    pub fn level_for(&self, module: &str) -> LogLevel {
+        let mut name = module;
+        loop {
+            if let Some(level) = self.modules.get(name) {
+                return *level;
+            }
+            match name.rfind("::") {
+                Some(end) => name = &name[..end],
+                None => return self.level,
+            }
+        }
+    }
+}
+
+/// `~/.arula/logs`, or `.arula/logs` without a home directory
+pub fn default_log_dir() -> PathBuf {
+    dirs::home_dir()
+        .map(|home| home.join(".arula"))
+        .unwrap_or_else(|| PathBuf::from(".arula"))
+        .join("logs")
+}
+
+/// One parsed line of a log file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub module: String,
+    pub message: String,
+}
+
+impl LogLine {
+    /// Parse a `[timestamp] [LEVEL] [module] message` line; lines written
+    /// before modules existed have no module part
+    pub fn parse(line: &str) -> Option<Self> {
+        let (timestamp, rest) = line.strip_prefix('[')?.split_once("] [")?;
+        let (level, rest) = rest.split_once("] ")?;
+        let level = LogLevel::parse(level)?;
+        let (module, message) = match rest.strip_prefix('[').and_then(|r| r.split_once("] ")) {
+            Some((module, message)) if !module.contains(' ') => (module, message),
+            _ => (DEFAULT_MODULE, rest),
+        };
+        Some(Self {
+            timestamp: timestamp.to_string(),
+            level,
+            module: module.to_string(),
+            message: message.to_string(),
+        })
+    }
+}
+
+/// The open log file and how much has been written to it
+struct LogFile {
+    file: Option<fs::File>,
+    size: u64,
+}
+
+#[derive(Clone)]
+pub struct Logger {
+    log_file_path: PathBuf,
+    settings: Arc<LogSettings>,
+    file_handle: Arc<Mutex<LogFile>>,
+}
+
+impl Logger {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_settings(LogSettings::default())
+    }
+
+    pub fn with_settings(settings: LogSettings) -> Result<Self, Box<dyn std::error::Error>> {
+        fs::create_dir_all(&settings.dir)?;
+        let log_file_path = settings.dir.join(LOG_FILE);
+
+        // Open the log file immediately
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_file_path)?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or_default();
+
+        Ok(Self {
+            log_file_path,
+            settings: Arc::new(settings),
+            file_handle: Arc::new(Mutex::new(LogFile {
+                file: Some(file),
+                size,
+            })),
+        })
+    }
+
+    /// The file being written
+    pub fn log_path(&self) -> &Path {
+        &self.log_file_path
+    }
+
+    pub fn settings(&self) -> &LogSettings {
+        &self.settings
+    }
+
+    /// Whether a line of `level` from `module` would be written
+    pub fn enabled(&self, level: LogLevel, module: &str) -> bool {
+        level.severity() >= self.settings.level_for(module).severity()
+    }
+
+    pub fn log(&self, level: LogLevel, message: &str) {
+        self.log_module(level, DEFAULT_MODULE, message);
+    }
+
+    pub fn log_module(&self, level: LogLevel, module: &str, message: &str) {
+        if !self.enabled(level, module) {
+            return;
+        }
+        let timestamp: DateTime<Utc> = Utc::now();
+        let formatted_timestamp = timestamp.format(TIMESTAMP_FORMAT);
+
+        let log_line = format!(
+            "[{}] [{}] [{}] {}\n",
+            formatted_timestamp, level, module, message
+        );
+
+        if let Ok(mut log_file) = self.file_handle.lock() {
+            if log_file.size + log_line.len() as u64 > self.settings.max_bytes && log_file.size > 0
+            {
+                self.rotate(&mut log_file);
+            }
+            if let Some(ref mut file) = log_file.file
+                && file.write_all(log_line.as_bytes()).is_ok()
+            {
+                let _ = file.flush();
+                log_file.size += log_line.len() as u64;
+            }
+        }
+    }
+
+    /// Move `latest.log` to `latest.1.log`, shifting older files up, and
+    /// start a new one
+    fn rotate(&self, log_file: &mut LogFile) {
+        log_file.file = None;
+        let rotated = |n: usize| self.settings.dir.join(format!("latest.{}.log", n));
+        let _ = fs::remove_file(rotated(self.settings.max_files));
+        for n in (1..self.settings.max_files).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        if self.settings.max_files > 0 {
+            let _ = fs::rename(&self.log_file_path, rotated(1));
+        } else {
+            let _ = fs::remove_file(&self.log_file_path);
+        }
+        log_file.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_file_path)
+            .ok();
+        log_file.size = 0;
+    }
+
+    pub fn info(&self, message: &str) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn debug(&self, message: &str) {
+        self.log(LogLevel::Debug, message);
+    }
+
+    pub fn warn(&self, message: &str) {
+        self.log(LogLevel::Warn, message);
+    }
+
+    pub fn error(&self, message: &str) {
+        self.log(LogLevel::Error, message);
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new().unwrap_or_else(|e| {
+            eprintln!("Failed to initialize logger: {}", e);
+            // Create a dummy logger that doesn't write anywhere
+            let settings = LogSettings::default();
+            Self {
+                log_file_path: settings.dir.join(LOG_FILE),
+                settings: Arc::new(settings),
+                file_handle: Arc::new(Mutex::new(LogFile {
+                    file: None,
+                    size: 0,
+                })),
+            }
+        })
+    }
+}
+
+// Global static logger instance using OnceLock for Rust 2024 compatibility
+static GLOBAL_LOGGER: OnceLock<Logger> = OnceLock::new();
+
+pub fn init_global_logger() -> Result<(), Box<dyn std::error::Error>> {
+    init_global_logger_with(LogSettings::default())
+}
+
+/// Start the global logger with settings from the config
+pub fn init_global_logger_with(settings: LogSettings) -> Result<(), Box<dyn std::error::Error>> {
+    let logger = Logger::with_settings(settings)?;
+    GLOBAL_LOGGER
+        .set(logger)
+        .map_err(|_| "Logger already initialized")?;
+    Ok(())
+}
+
+pub fn get_global_logger() -> Option<&'static Logger> {
+    GLOBAL_LOGGER.get()
+}
+
+/// The file the global logger writes, or where it would write by default
+pub fn log_file_path() -> PathBuf {
+    get_global_logger()
+        .map(|logger| logger.log_path().to_path_buf())
+        .unwrap_or_else(|| default_log_dir().join(LOG_FILE))
+}
+
+// Convenience functions for global logging
+pub fn log(level: LogLevel, message: &str) {
+    if let Some(logger) = get_global_logger() {
+        logger.log(level, message);
+    }
+}
+
+pub fn log_module(level: LogLevel, module: &str, message: &str) {
+    if let Some(logger) = get_global_logger() {
+        logger.log_module(level, module, message);
+    }
+}
+
+pub fn info(message: &str) {
+    log(LogLevel::Info, message);
+}
+
+pub fn debug(message: &str) {
+    log(LogLevel::Debug, message);
+}
+
+pub fn warn(message: &str) {
+    log(LogLevel::Warn, message);
+}
+
+pub fn error(message: &str) {
+    log(LogLevel::Error, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_levels_and_rotation() {
+        let dir = std::env::temp_dir().join(format!("arula-logger-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let settings = LogSettings {
+            dir: dir.clone(),
+            level: LogLevel::Warn,
+            modules: HashMap::from([("api".to_string(), LogLevel::Debug)]),
+            max_bytes: 200,
+            max_files: 2,
+        };
+        assert_eq!(settings.level_for("api::stream"), LogLevel::Debug);
+        assert_eq!(settings.level_for("mcp"), LogLevel::Warn);
+
+        let logger = Logger::with_settings(settings).unwrap();
+        logger.log_module(LogLevel::Info, "mcp", "dropped");
+        logger.log_module(LogLevel::Debug, "api::stream", "kept");
+        let content = fs::read_to_string(logger.log_path()).unwrap();
+        assert!(!content.contains("dropped"));
+        let line = LogLine::parse(content.lines().next().unwrap()).unwrap();
+        assert_eq!(line.level, LogLevel::Debug);
+        assert_eq!(
+            (line.module.as_str(), line.message.as_str()),
+            ("api::stream", "kept")
+        );
+
+        for i in 0..20 {
+            logger.error(&format!("line {}", i));
+        }
+        assert!(dir.join("latest.1.log").exists());
+        assert!(dir.join("latest.2.log").exists());
+        assert!(!dir.join("latest.3.log").exists());
+        assert!(fs::metadata(logger.log_path()).unwrap().len() <= 200);
+
+        let old = LogLine::parse("[2025-01-01 10:00:00.000 UTC] [WARN] Plain message").unwrap();
+        assert_eq!(
+            (old.module.as_str(), old.message.as_str()),
+            (DEFAULT_MODULE, "Plain message")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
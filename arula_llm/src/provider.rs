@@ -0,0 +1,179 @@
+//! The interface to an LLM provider
+//!
+//! [`Provider`] is what the rest of ARULA needs from a backend: a whole
+//! reply to a conversation, or one streamed as [`ProviderEvent`]s. The
+//! agent loop in `arula_core` only talks to a `dyn Provider`. [`ApiClient`]
+//! implements it for every built-in provider; other backends (or test
+//! doubles) can implement it too.
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::client::{AIProvider, ApiClient, ApiResponse, ChatMessage};
+use crate::stream::{self, ProviderEvent};
+
+/// Provider behaviour taken from the user's settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderOptions {
+    /// Ask models that support it to think before answering
+    pub thinking: bool,
+    /// Times a failed request is retried (Z.AI)
+    pub max_retries: u32,
+    /// Time a request may take before it's abandoned (Z.AI)
+    pub timeout: Duration,
+    /// Report token usage and estimated cost (Z.AI)
+    pub usage_tracking: bool,
+}
+
+impl Default for ProviderOptions {
+    fn default() -> Self {
+        Self {
+            thinking: false,
+            max_retries: 3,
+            timeout: Duration::from_secs(300),
+            usage_tracking: true,
+        }
+    }
+}
+
+/// An LLM backend
+#[async_trait]
+pub trait Provider: Send + Sync {
+    /// Which API the backend speaks
+    fn kind(&self) -> AIProvider;
+
+    /// The model requests go to
+    fn model(&self) -> &str;
+
+    /// Send a conversation, with tool definitions if any, and wait for the
+    /// whole reply
+    async fn complete(&self, messages: Vec<ChatMessage>, tools: Vec<Value>) -> Result<ApiResponse>;
+
+    /// Send a conversation, with tool definitions if any, and pass each
+    /// event to `on_event` as the reply streams in; returns the whole reply
+    ///
+    /// A request the provider rejects is an error; a stream that breaks
+    /// off is reported as an [`ProviderEvent::Error`] and an unsuccessful
+    /// reply.
+    async fn stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Value],
+        on_event: &mut (dyn FnMut(ProviderEvent) + Send),
+    ) -> Result<ApiResponse>;
+
+    /// Ask a single question and return the answer's text
+    async fn ask(&self, prompt: &str) -> Result<String> {
+        let response = self
+            .complete(vec![ChatMessage::user(prompt)], Vec::new())
+            .await?;
+        if !response.success {
+            bail!(
+                "{}",
+                response
+                    .error
+                    .unwrap_or_else(|| "The provider returned an error".to_string())
+            );
+        }
+        Ok(response.response)
+    }
+}
+
+#[async_trait]
+impl Provider for ApiClient {
+    fn kind(&self) -> AIProvider {
+        self.provider.clone()
+    }
+
+    fn model(&self) -> &str {
+        ApiClient::model(self)
+    }
+
+    async fn complete(&self, messages: Vec<ChatMessage>, tools: Vec<Value>) -> Result<ApiResponse> {
+        self.send_message_with_tools_sync(&messages, &tools).await
+    }
+
+    async fn stream(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[Value],
+        on_event: &mut (dyn FnMut(ProviderEvent) + Send),
+    ) -> Result<ApiResponse> {
+        let request_body = self.streaming_request(messages, tools);
+        let response = self.make_streaming_request(request_body).await?;
+        stream::process_response(response, on_event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers every conversation with its last message
+    struct Echo {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Provider for Echo {
+        fn kind(&self) -> AIProvider {
+            AIProvider::Custom
+        }
+
+        fn model(&self) -> &str {
+            "echo"
+        }
+
+        async fn complete(
+            &self,
+            messages: Vec<ChatMessage>,
+            _tools: Vec<Value>,
+        ) -> Result<ApiResponse> {
+            let last = messages
+                .last()
+                .and_then(|m| m.content.clone())
+                .unwrap_or_default();
+            Ok(ApiResponse {
+                response: last,
+                success: !self.fail,
+                error: self.fail.then(|| "quota exceeded".to_string()),
+                ..Default::default()
+            })
+        }
+
+        async fn stream(
+            &self,
+            messages: &[ChatMessage],
+            tools: &[Value],
+            on_event: &mut (dyn FnMut(ProviderEvent) + Send),
+        ) -> Result<ApiResponse> {
+            let response = self.complete(messages.to_vec(), tools.to_vec()).await?;
+            on_event(ProviderEvent::TextDelta(response.response.clone()));
+            Ok(response)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ask_through_provider() {
+        let provider: &dyn Provider = &Echo { fail: false };
+        assert_eq!(provider.ask("hello").await.unwrap(), "hello");
+
+        let failing: Box<dyn Provider> = Box::new(Echo { fail: true });
+        let err = failing.ask("hello").await.unwrap_err();
+        assert_eq!(err.to_string(), "quota exceeded");
+    }
+
+    #[tokio::test]
+    async fn test_stream_through_provider() {
+        let provider: &dyn Provider = &Echo { fail: false };
+        let mut events = Vec::new();
+        let reply = provider
+            .stream(&[ChatMessage::user("hello")], &[], &mut |e| events.push(e))
+            .await
+            .unwrap();
+        assert_eq!(reply.response, "hello");
+        assert!(matches!(&events[..], [ProviderEvent::TextDelta(text)] if text == "hello"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Failed provider requests
+//!
+//! A non-2xx response from a provider becomes a [`ProviderError`]: the
+//! request ID, rate-limit headers and full body go to the log, the chat only
+//! gets a short code, and `/debug last-error` shows the rest.
+
+use chrono::{DateTime, Local};
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use std::sync::RwLock;
+
+/// Headers providers use for the request ID, in order of preference
+const REQUEST_ID_HEADERS: &[&str] = &["x-request-id", "request-id", "x-amzn-requestid", "cf-ray"];
+
+static LAST_ERROR: RwLock<Option<ProviderError>> = RwLock::new(None);
+
+/// An error response from a provider
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderError {
+    pub provider: String,
+    pub url: String,
+    pub status: u16,
+    /// Short code shown in chat, e.g. `429 rate_limit_error`
+    pub code: String,
+    pub request_id: Option<String>,
+    /// `x-ratelimit-*` and `retry-after` headers
+    pub rate_limits: Vec<(String, String)>,
+    pub body: String,
+    pub at: DateTime<Local>,
+}
+
+impl ProviderError {
+    pub fn new(provider: &str, url: &str, status: u16, headers: &HeaderMap, body: String) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let request_id = REQUEST_ID_HEADERS.iter().find_map(|name| header(name));
+        let rate_limits = headers
+            .iter()
+            .filter(|(name, _)| {
+                let name = name.as_str();
+                name == "retry-after" || name.contains("ratelimit")
+            })
+            .map(|(name, value)| {
+                let value = value.to_str().unwrap_or("<binary>");
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        let code = match error_kind(&body) {
+            Some(kind) => format!("{} {}", status, kind),
+            None => status.to_string(),
+        };
+        Self {
+            provider: provider.to_string(),
+            url: url.to_string(),
+            status,
+            code,
+            request_id,
+            rate_limits,
+            body,
+            at: Local::now(),
+        }
+    }
+
+    /// Read the status, headers and body of a failed response
+    pub async fn from_response(provider: &str, response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let url = response.url().to_string();
+        let headers = response.headers().clone();
+        let body = response.text().await.unwrap_or_default();
+        Self::new(provider, &url, status, &headers, body)
+    }
+
+    /// Log the error and keep it for `/debug last-error`
+    pub fn record(self) -> Self {
+        let fields = serde_json::to_string(&self).unwrap_or_default();
+        crate::logger::log_module(
+            crate::logger::LogLevel::Error,
+            "provider",
+            &format!("provider_error {}", fields),
+        );
+        if let Ok(mut last) = LAST_ERROR.write() {
+            *last = Some(self.clone());
+        }
+        self
+    }
+
+    /// Multi-line report for `/debug last-error`
+    pub fn details(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{} request failed at {}",
+                self.provider,
+                self.at.format("%H:%M:%S")
+            ),
+            format!("  Code: {}", self.code),
+            format!("  URL: {}", self.url),
+            format!(
+                "  Request ID: {}",
+                self.request_id.as_deref().unwrap_or("(none)")
+            ),
+        ];
+        for (name, value) in &self.rate_limits {
+            lines.push(format!("  {}: {}", name, value));
+        }
+        let body = serde_json::from_str::<serde_json::Value>(&self.body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or_else(|| self.body.clone());
+        lines.push(format!("  Body:\n{}", body));
+        lines.join("\n")
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} error {} - run /debug last-error for details",
+            self.provider, self.code
+        )
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// The most recent provider error, if any
+pub fn last_error() -> Option<ProviderError> {
+    LAST_ERROR.read().ok().and_then(|last| last.clone())
+}
+
+/// `error.type`, `error.code` or `code` from a JSON error body
+fn error_kind(body: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = json.get("error").unwrap_or(&json);
+    ["type", "code", "status"]
+        .iter()
+        .filter_map(|key| error.get(key))
+        .find_map(|value| match value {
+            serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_provider_error_fields() {
+        let mut headers = HeaderMap::new();
+        headers.insert("request-id", HeaderValue::from_static("req_123"));
+        headers.insert("retry-after", HeaderValue::from_static("20"));
+        headers.insert(
+            "anthropic-ratelimit-requests-remaining",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        let body = r#"{"type":"error","error":{"type":"rate_limit_error","message":"slow down"}}"#;
+
+        let error = ProviderError::new(
+            "Claude",
+            "https://x/v1/messages",
+            429,
+            &headers,
+            body.into(),
+        );
+        assert_eq!(error.code, "429 rate_limit_error");
+        assert_eq!(error.request_id.as_deref(), Some("req_123"));
+        assert_eq!(error.rate_limits.len(), 2);
+        assert_eq!(
+            error.to_string(),
+            "Claude error 429 rate_limit_error - run /debug last-error for details"
+        );
+        assert!(error.details().contains("slow down"));
+    }
+
+    #[test]
+    fn test_error_kind() {
+        assert_eq!(
+            error_kind(r#"{"error":{"code":1113}}"#),
+            Some("1113".into())
+        );
+        assert_eq!(
+            error_kind(r#"{"code":"invalid_key"}"#),
+            Some("invalid_key".into())
+        );
+        assert_eq!(error_kind("Bad gateway"), None);
+    }
+}
@@ -0,0 +1,926 @@
+//! Streaming requests and replies for every provider
+//!
+//! Builds the request body each API expects (OpenAI-compatible, Anthropic
+//! Messages, Ollama, Z.AI) and reads the reply as server-sent events or
+//! NDJSON, reporting [`ProviderEvent`]s as text and tool calls arrive.
+//! [`Provider::stream`](crate::Provider::stream) puts the two together.
+
+use crate::client::{
+    AIProvider, ApiResponse, ChatMessage, ToolCall, ToolCallFunction, Usage, debug_print,
+};
+use crate::generation::GenerationSettings;
+use crate::xml_toolcall::extract_tool_call_from_xml;
+use anyhow::{Result, anyhow};
+use futures::StreamExt;
+use reqwest::Response;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+// ============================================================================
+//  Types
+// ============================================================================
+
+/// Represents a streaming chunk from OpenAI-compatible APIs
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamChunk {
+    pub id: Option<String>,
+    pub object: Option<String>,
+    pub created: Option<u64>,
+    pub model: Option<String>,
+    pub choices: Vec<StreamChoice>,
+    #[serde(default)]
+    pub usage: Option<StreamUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamChoice {
+    pub index: usize,
+    pub delta: StreamDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamDelta {
+    pub role: Option<String>,
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<StreamToolCallDelta>>,
+    /// OpenAI o1/o3 reasoning content
+    pub reasoning_content: Option<String>,
+    /// Ollama thinking content
+    pub thinking: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamToolCallDelta {
+    pub index: usize,
+    pub id: Option<String>,
+    pub r#type: Option<String>,
+    pub function: Option<StreamFunctionDelta>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamFunctionDelta {
+    pub name: Option<String>,
+    pub arguments: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// What a provider reports while a reply streams in
+#[derive(Debug, Clone)]
+pub enum ProviderEvent {
+    /// Start of stream
+    Start { id: String, model: String },
+    /// Text content chunk
+    TextDelta(String),
+    /// Thinking/reasoning content chunk
+    ThinkingDelta(String),
+    /// Tool call started
+    ToolCallStart {
+        index: usize,
+        id: String,
+        name: String,
+    },
+    /// Tool call arguments chunk
+    ToolCallDelta { index: usize, arguments: String },
+    /// Tool call completed
+    ToolCallComplete(ToolCall),
+    /// Stream finished
+    Finish {
+        reason: String,
+        usage: Option<Usage>,
+    },
+    /// Error occurred
+    Error(String),
+}
+
+/// Accumulator for tool call deltas
+#[derive(Debug, Default)]
+struct ToolCallAccumulator {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl ToolCallAccumulator {
+    fn to_tool_call(&self) -> ToolCall {
+        ToolCall {
+            id: self.id.clone(),
+            r#type: "function".to_string(),
+            function: ToolCallFunction {
+                name: self.name.clone(),
+                arguments: self.arguments.clone(),
+            },
+        }
+    }
+}
+
+// ============================================================================
+//  Request Building
+// ============================================================================
+
+/// Check if the endpoint URL is an Anthropic-compatible z.ai endpoint
+pub fn is_anthropic_compatible_endpoint(endpoint: &str) -> bool {
+    endpoint.contains("/api/anthropic")
+}
+
+/// Convert OpenAI-format tools to Anthropic format
+fn convert_tools_to_anthropic(tools: &[Value]) -> Vec<Value> {
+    tools
+        .iter()
+        .filter_map(|tool| {
+            // OpenAI format: { "type": "function", "function": { "name", "description", "parameters" } }
+            // Anthropic format: { "name", "description", "input_schema" }
+            let func = tool.get("function")?;
+            let name = func.get("name")?.as_str()?;
+            let description = func.get("description")?.as_str().unwrap_or("");
+            let parameters = func
+                .get("parameters")
+                .cloned()
+                .unwrap_or(json!({"type": "object", "properties": {}}));
+
+            Some(json!({
+                "name": name,
+                "description": description,
+                "input_schema": parameters
+            }))
+        })
+        .collect()
+}
+
+/// Build an Anthropic Messages API compatible request body
+pub fn build_anthropic_request(
+    model: &str,
+    messages: &[ChatMessage],
+    tools: Option<&[Value]>,
+    generation: GenerationSettings,
+    thinking_enabled: bool,
+) -> Value {
+    let max_tokens = generation.max_tokens();
+
+    // Debug output
+    if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+        eprintln!(
+            "🧠 DEBUG build_anthropic_request: thinking_enabled = {}, model = {}",
+            thinking_enabled, model
+        );
+    }
+    // Extract system message (first message with role "system")
+    let system_content: Option<String> = messages
+        .iter()
+        .find(|m| m.role == "system")
+        .and_then(|m| m.content.clone());
+
+    // Check if this is Z.AI's Anthropic-compatible endpoint which expects prompt format
+    let is_zai = model.to_lowercase().contains("z.ai")
+        || model.to_lowercase().contains("zai")
+        || std::env::var("ARULA_ZAI_ANTHROPIC").unwrap_or_default() == "1";
+
+    // Z.AI expects legacy Claude format with "prompt" parameter
+    if is_zai {
+        let mut prompt_parts = Vec::new();
+
+        // Add system prompt if present
+        if let Some(system_msg) = messages.iter().find(|m| m.role == "system")
+            && let Some(content) = &system_msg.content
+        {
+            prompt_parts.push(format!("<admin>\n{}\n</admin>", content));
+        }
+
+        // Convert messages to prompt format
+        for msg in messages.iter().filter(|m| m.role != "system") {
+            match msg.role.as_str() {
+                "user" => {
+                    if let Some(content) = &msg.content {
+                        prompt_parts.push(format!("\n\nHuman: {}", content));
+                    }
+                }
+                "assistant" => {
+                    if let Some(content) = &msg.content {
+                        prompt_parts.push(format!("\n\nAssistant: {}", content));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // End the prompt properly
+        prompt_parts.push("\n\nAssistant:".to_string());
+        let prompt = prompt_parts.concat();
+
+        let mut request = json!({
+            "model": model,
+            "prompt": prompt,
+            "max_tokens": max_tokens,
+            "stream": true
+        });
+
+        // Add temperature
+        request["temperature"] = json!(generation.temperature());
+
+        // Add thinking mode if enabled (for Z.AI Anthropic-compatible endpoint)
+        if thinking_enabled {
+            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+                eprintln!("🧠 DEBUG: Adding thinking block to Z.AI build_anthropic_request");
+            }
+            request["thinking"] = json!({"type": "enabled"});
+        } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+            eprintln!(
+                "🧠 DEBUG: NOT adding thinking block to Z.AI build_anthropic_request - thinking_enabled is false"
+            );
+        }
+
+        // Add tools if provided (Z.AI supports tools)
+        if let Some(tools) = tools
+            && !tools.is_empty()
+        {
+            request["tools"] = json!(convert_tools_to_anthropic(tools));
+        }
+
+        return request;
+    }
+
+    // Build messages array, excluding system messages and converting tool messages
+    let anthropic_messages: Vec<Value> = messages
+        .iter()
+        .filter(|msg| msg.role != "system") // System goes in separate param
+        .filter_map(|msg| {
+            match msg.role.as_str() {
+                "user" => Some(json!({
+                    "role": "user",
+                    "content": msg.content.clone().unwrap_or_default()
+                })),
+                "assistant" => {
+                    let mut content_blocks: Vec<Value> = Vec::new();
+
+                    // Add text content if present
+                    if let Some(text) = &msg.content
+                        && !text.is_empty()
+                    {
+                        content_blocks.push(json!({
+                            "type": "text",
+                            "text": text
+                        }));
+                    }
+
+                    // Add tool_use blocks if present
+                    if let Some(tool_calls) = &msg.tool_calls {
+                        for tc in tool_calls {
+                            let input: Value =
+                                serde_json::from_str(&tc.function.arguments).unwrap_or(json!({}));
+                            content_blocks.push(json!({
+                                "type": "tool_use",
+                                "id": tc.id,
+                                "name": tc.function.name,
+                                "input": input
+                            }));
+                        }
+                    }
+
+                    if content_blocks.is_empty() {
+                        None
+                    } else {
+                        Some(json!({
+                            "role": "assistant",
+                            "content": content_blocks
+                        }))
+                    }
+                }
+                "tool" => {
+                    // Convert tool results to Anthropic format
+                    Some(json!({
+                        "role": "user",
+                        "content": [{
+                            "type": "tool_result",
+                            "tool_use_id": msg.tool_call_id.clone().unwrap_or_default(),
+                            "content": msg.content.clone().unwrap_or_default()
+                        }]
+                    }))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+
+    let mut request = json!({
+        "model": model,
+        "max_tokens": max_tokens,
+        "messages": anthropic_messages,
+        "stream": true
+    });
+
+    // Add system prompt if present
+    if let Some(system) = system_content {
+        request["system"] = json!(system);
+    }
+
+    // Sampling parameters are only sent when configured, and extended
+    // thinking doesn't accept them
+    if !thinking_enabled {
+        if let Some(temperature) = generation.temperature {
+            request["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = generation.top_p {
+            request["top_p"] = json!(top_p);
+        }
+    }
+
+    // Add thinking mode if enabled (for Anthropic or other compatible endpoints)
+    if thinking_enabled {
+        if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+            eprintln!("🧠 DEBUG: Adding thinking block to non-Z.AI build_anthropic_request");
+        }
+        request["thinking"] = json!({"type": "enabled"});
+    } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+        eprintln!(
+            "🧠 DEBUG: NOT adding thinking block to non-Z.AI build_anthropic_request - thinking_enabled is false"
+        );
+    }
+
+    // Convert and add tools if present
+    if let Some(t) = tools
+        && !t.is_empty()
+    {
+        let anthropic_tools = convert_tools_to_anthropic(t);
+        if !anthropic_tools.is_empty() {
+            request["tools"] = json!(anthropic_tools);
+        }
+    }
+
+    request
+}
+
+/// Build a unified request body for streaming, handling provider specifics
+pub fn build_streaming_request(
+    provider: &AIProvider,
+    model: &str,
+    messages: &[ChatMessage],
+    tools: Option<&[Value]>,
+    generation: GenerationSettings,
+    thinking_enabled: bool,
+) -> Value {
+    let temperature = generation.temperature();
+    let max_tokens = generation.max_tokens();
+    let is_zai = matches!(provider, AIProvider::ZAiCoding);
+
+    // Debug output
+    if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+        eprintln!(
+            "🧠 DEBUG build_streaming_request: thinking_enabled = {}, provider = {:?}",
+            thinking_enabled, provider
+        );
+    }
+    let is_ollama = matches!(provider, AIProvider::Ollama);
+
+    // 1. Process Messages
+    let json_messages: Vec<Value> = messages
+        .iter()
+        .map(|msg| {
+            // Z.AI Bug Fix: We DO NOT filter out tool messages anymore!
+            // Z.AI supports tool calling and needs history to continue.
+
+            let mut obj = json!({
+                "role": msg.role,
+            });
+
+            if let Some(content) = &msg.content {
+                obj["content"] = json!(content);
+            } else if is_zai {
+                // Z.AI requires content, use empty string if none
+                obj["content"] = json!("");
+            } else if msg.tool_calls.is_some() {
+                obj["content"] = json!(null);
+            }
+
+            // Add tool-related fields
+            // Z.AI usage: Uses standard OpenAI format for tool_calls/tool_call_id
+            if let Some(tool_calls) = &msg.tool_calls {
+                if is_ollama {
+                    // Ollama specific tool format
+                    let converted: Vec<Value> = tool_calls
+                        .iter()
+                        .map(|tc| {
+                            let args = serde_json::from_str::<Value>(&tc.function.arguments)
+                                .unwrap_or_else(|_| json!({}));
+                            json!({
+                                "function": {
+                                    "name": tc.function.name,
+                                    "arguments": args
+                                }
+                            })
+                        })
+                        .collect();
+                    obj["tool_calls"] = json!(converted);
+                } else {
+                    // Standard OpenAI / Z.AI format
+                    obj["tool_calls"] = json!(tool_calls);
+                }
+            }
+
+            if let Some(tool_call_id) = &msg.tool_call_id
+                && !is_ollama
+            {
+                obj["tool_call_id"] = json!(tool_call_id);
+            }
+
+            if is_ollama && let Some(tool_name) = &msg.tool_name {
+                obj["tool_name"] = json!(tool_name);
+            }
+
+            obj
+        })
+        .collect();
+
+    // 2. Process Tools (Z.AI specific filtering)
+    let tools_to_send = if is_zai {
+        // Z.AI now supports tools in streaming mode
+        if let Some(t) = tools {
+            if !t.is_empty() {
+                Some(t.to_vec())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    } else {
+        // Convert tools for other providers if needed
+        if let Some(t) = tools {
+            if !t.is_empty() {
+                Some(t.to_vec())
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    };
+
+    // 3. Construct Body
+    let mut request = json!({
+        "model": model,
+        "messages": json_messages,
+        "max_tokens": max_tokens,
+        "stream": true
+    });
+
+    // Add temperature separately to avoid type issues
+    if is_zai {
+        request["temperature"] = json!(temperature.to_string());
+        // Add thinking parameter for Z.AI if enabled
+        if thinking_enabled {
+            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+                eprintln!("🧠 DEBUG: Adding thinking block to Z.AI streaming request");
+            }
+            request["thinking"] = json!({"type": "enabled"});
+        } else if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+            eprintln!(
+                "🧠 DEBUG: NOT adding thinking block to Z.AI streaming request - thinking_enabled is false"
+            );
+        }
+    } else {
+        request["temperature"] = json!(temperature);
+        if let Some(top_p) = generation.top_p {
+            request["top_p"] = json!(top_p);
+        }
+        // Add reasoning_effort for other providers if thinking is enabled
+        if thinking_enabled {
+            request["reasoning_effort"] = json!("medium");
+        }
+    }
+
+    // Option flags
+    let include_stream_options = !is_zai && !is_ollama;
+    let include_tool_choice = !is_zai && !is_ollama;
+
+    if include_stream_options {
+        request["stream_options"] = json!({ "include_usage": true });
+    }
+
+    if let Some(t) = tools_to_send {
+        request["tools"] = json!(t);
+        if include_tool_choice {
+            request["tool_choice"] = json!("auto");
+        }
+    }
+
+    // Ollama specific
+    if is_ollama && let Some(obj) = request.as_object_mut() {
+        let _ = obj.remove("max_tokens");
+        let _ = obj.remove("temperature");
+        let top_p = obj.remove("top_p");
+        let mut options = json!({
+            "num_predict": max_tokens,
+            "temperature": temperature
+        });
+        if let Some(top_p) = top_p {
+            options["top_p"] = top_p;
+        }
+        obj.insert("options".to_string(), options);
+    }
+
+    request
+}
+
+// ============================================================================
+//  Stream Processing
+// ============================================================================
+
+/// Process a raw HTTP response into a stream of events
+pub async fn process_response<F>(response: Response, callback: F) -> Result<ApiResponse>
+where
+    F: FnMut(ProviderEvent),
+{
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if content_type.contains("text/event-stream") {
+        process_sse_stream(response, callback).await
+    } else {
+        process_ndjson_stream(response, callback).await
+    }
+}
+
+async fn process_sse_stream<F>(response: Response, mut callback: F) -> Result<ApiResponse>
+where
+    F: FnMut(ProviderEvent),
+{
+    use eventsource_stream::Eventsource;
+
+    let mut stream = response.bytes_stream().eventsource();
+    let mut accumulated = String::new();
+    let mut tool_acc: HashMap<usize, ToolCallAccumulator> = HashMap::new();
+    let mut finish_reason = String::new();
+    let mut usage = None;
+    let mut model = String::new();
+    let mut stream_id = String::new();
+    let mut reasoning_buffer = String::new(); // For XML tool call extraction
+
+    while let Some(res) = stream.next().await {
+        match res {
+            Ok(event) => {
+                let data = event.data;
+                if data == "[DONE]" {
+                    break;
+                }
+
+                // Log streaming chunk if debug mode is enabled
+                if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+                    debug_print(&format!("Stream Chunk: {}", data));
+                }
+
+                if let Ok(chunk) = serde_json::from_str::<StreamChunk>(&data) {
+                    if let Some(id) = &chunk.id
+                        && stream_id.is_empty()
+                    {
+                        stream_id = id.clone();
+                    }
+                    if let Some(m) = &chunk.model
+                        && model.is_empty()
+                    {
+                        model = m.clone();
+                        callback(ProviderEvent::Start {
+                            id: stream_id.clone(),
+                            model: model.clone(),
+                        });
+                    }
+                    if let Some(u) = chunk.usage {
+                        usage = Some(Usage {
+                            prompt_tokens: u.prompt_tokens,
+                            completion_tokens: u.completion_tokens,
+                            total_tokens: u.total_tokens,
+                        });
+                    }
+
+                    for choice in chunk.choices {
+                        if let Some(r) = choice.finish_reason {
+                            finish_reason = r;
+                        }
+                        let delta = choice.delta;
+
+                        if let Some(c) = delta.content
+                            && !c.is_empty()
+                        {
+                            accumulated.push_str(&c);
+                            callback(ProviderEvent::TextDelta(c));
+                        }
+
+                        if let Some(think) = delta.reasoning_content.or(delta.thinking)
+                            && !think.is_empty()
+                        {
+                            // Buffer reasoning content for XML tool call detection
+                            reasoning_buffer.push_str(&think);
+                            callback(ProviderEvent::ThinkingDelta(think));
+                        }
+
+                        if let Some(tcs) = delta.tool_calls {
+                            for tc in tcs {
+                                let idx = tc.index; // Use explicit index
+                                let acc = tool_acc.entry(idx).or_default();
+
+                                if let Some(id) = tc.id {
+                                    acc.id = id;
+                                }
+                                if let Some(func) = tc.function {
+                                    if let Some(n) = func.name {
+                                        acc.name = n.clone();
+                                        callback(ProviderEvent::ToolCallStart {
+                                            index: idx,
+                                            id: acc.id.clone(),
+                                            name: n,
+                                        });
+                                    }
+                                    if let Some(a) = func.arguments {
+                                        acc.arguments.push_str(&a);
+                                        callback(ProviderEvent::ToolCallDelta {
+                                            index: idx,
+                                            arguments: a,
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                let msg = format!("Stream error: Process SSE stream failed\n  Cause: {}", e);
+                callback(ProviderEvent::Error(msg.clone()));
+                return Ok(ApiResponse {
+                    response: accumulated,
+                    success: false,
+                    error: Some(msg),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    // Before finalizing, check if reasoning_buffer contains XML tool calls
+    // This handles GLM-4.6 style XML tool calls in reasoning content (Coding Plan endpoint only)
+    // Note: Anthropic-compatible endpoint uses structured tool_use blocks, not XML
+    // The XML check only triggers when tool_acc is empty (no structured tool calls found)
+    if !reasoning_buffer.is_empty()
+        && tool_acc.is_empty()
+        && let Some(xml_tool_call) = extract_tool_call_from_xml(&reasoning_buffer)
+    {
+        // Convert JSON value to ToolCall
+        if let Ok(tool_call) = serde_json::from_value::<ToolCall>(xml_tool_call) {
+            // Add to tool_acc as if it came from standard tool_calls
+            let idx = 0;
+            tool_acc.insert(
+                idx,
+                ToolCallAccumulator {
+                    id: tool_call.id.clone(),
+                    name: tool_call.function.name.clone(),
+                    arguments: tool_call.function.arguments.clone(),
+                },
+            );
+            // Emit the tool call event
+            callback(ProviderEvent::ToolCallStart {
+                index: idx,
+                id: tool_call.id.clone(),
+                name: tool_call.function.name.clone(),
+            });
+            callback(ProviderEvent::ToolCallDelta {
+                index: idx,
+                arguments: tool_call.function.arguments.clone(),
+            });
+        }
+    }
+
+    finalize(
+        accumulated,
+        tool_acc,
+        finish_reason,
+        usage,
+        model,
+        &mut callback,
+    )
+}
+
+async fn process_ndjson_stream<F>(response: Response, mut callback: F) -> Result<ApiResponse>
+where
+    F: FnMut(ProviderEvent),
+{
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+    let mut accumulated = String::new();
+    let mut tool_acc: HashMap<usize, ToolCallAccumulator> = HashMap::new();
+    let mut finish_reason = "stop".to_string();
+    let mut usage = None;
+    let mut model = String::new();
+
+    while let Some(item) = stream.next().await {
+        let bytes =
+            item.map_err(|e| anyhow!("Stream error: Read stream chunk failed\n  Cause: {}", e))?;
+        if let Ok(s) = std::str::from_utf8(&bytes) {
+            buffer.push_str(s);
+        }
+
+        while let Some(pos) = buffer.find('\n') {
+            let line = buffer[..pos].trim().to_string();
+            buffer.drain(..pos + 1);
+            if line.is_empty() {
+                continue;
+            }
+
+            // Log streaming chunk if debug mode is enabled
+            if std::env::var("ARULA_DEBUG").unwrap_or_default() == "1" {
+                debug_print(&format!("Stream Chunk (NDJSON): {}", line));
+            }
+
+            if let Ok(json) = serde_json::from_str::<Value>(&line) {
+                // Ollama 'done' check
+                if json.get("done").and_then(|v| v.as_bool()) == Some(true) {
+                    if let Some(c) = json.get("eval_count").and_then(|v| v.as_u64()) {
+                        usage = Some(Usage {
+                            total_tokens: c as u32,
+                            ..Default::default()
+                        });
+                    }
+                    finish_reason = "stop".to_string();
+                }
+
+                // Content
+                let content = json
+                    .get("message")
+                    .and_then(|m| m.get("content"))
+                    .or_else(|| json.get("response"))
+                    .and_then(|v| v.as_str());
+
+                if let Some(c) = content
+                    && !c.is_empty()
+                {
+                    accumulated.push_str(c);
+                    callback(ProviderEvent::TextDelta(c.to_string()));
+                }
+
+                // Tools
+                if let Some(tcs) = json
+                    .get("message")
+                    .and_then(|m| m.get("tool_calls"))
+                    .and_then(|v| v.as_array())
+                {
+                    for (i, tc) in tcs.iter().enumerate() {
+                        if let Some(func) = tc.get("function") {
+                            let name = func
+                                .get("name")
+                                .and_then(|s| s.as_str())
+                                .unwrap_or_default()
+                                .to_string();
+                            let args = func
+                                .get("arguments")
+                                .map(|v| v.to_string())
+                                .unwrap_or_default();
+
+                            let acc = tool_acc.entry(i).or_default();
+                            acc.name = name.clone();
+                            acc.arguments = args.clone();
+                            acc.id = format!("call_{}", i);
+
+                            callback(ProviderEvent::ToolCallStart {
+                                index: i,
+                                id: acc.id.clone(),
+                                name: acc.name.clone(),
+                            });
+                            callback(ProviderEvent::ToolCallDelta {
+                                index: i,
+                                arguments: acc.arguments.clone(),
+                            });
+                            finish_reason = "tool_calls".to_string();
+                        }
+                    }
+                }
+
+                if model.is_empty()
+                    && let Some(m) = json.get("model").and_then(|s| s.as_str())
+                {
+                    model = m.to_string();
+                    callback(ProviderEvent::Start {
+                        id: "ndjson".into(),
+                        model: model.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    finalize(
+        accumulated,
+        tool_acc,
+        finish_reason,
+        usage,
+        model,
+        &mut callback,
+    )
+}
+
+fn finalize<F>(
+    content: String,
+    acc: HashMap<usize, ToolCallAccumulator>,
+    reason: String,
+    usage: Option<Usage>,
+    model: String,
+    callback: &mut F,
+) -> Result<ApiResponse>
+where
+    F: FnMut(ProviderEvent),
+{
+    let tool_calls = if acc.is_empty() {
+        None
+    } else {
+        let mut calls: Vec<(usize, ToolCall)> = acc
+            .into_iter()
+            .map(|(i, a)| (i, a.to_tool_call()))
+            .collect();
+        calls.sort_by_key(|(i, _)| *i);
+        // Clean arguments (sometimes they are double-encoded)
+        let cleaned: Vec<ToolCall> = calls
+            .into_iter()
+            .map(|(_, tc)| {
+                // Basic check if args are a string containing json or just json
+                // For now assume standard behavior, maybe add cleanup later if needed
+                callback(ProviderEvent::ToolCallComplete(tc.clone()));
+                tc
+            })
+            .collect();
+        Some(cleaned)
+    };
+
+    callback(ProviderEvent::Finish {
+        reason: reason.clone(),
+        usage: usage.clone(),
+    });
+
+    Ok(ApiResponse {
+        response: content,
+        success: true,
+        error: None,
+        usage,
+        tool_calls,
+        model: Some(model),
+        created: None,
+        reasoning_content: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thinking_comes_from_the_caller() {
+        let messages = [ChatMessage::user("hi")];
+        let generation = GenerationSettings::default();
+
+        let openai = build_streaming_request(
+            &AIProvider::OpenAI,
+            "gpt",
+            &messages,
+            None,
+            generation,
+            true,
+        );
+        assert_eq!(openai["reasoning_effort"], "medium");
+        assert_eq!(openai["stream_options"]["include_usage"], true);
+        let plain = build_streaming_request(
+            &AIProvider::OpenAI,
+            "gpt",
+            &messages,
+            None,
+            generation,
+            false,
+        );
+        assert!(plain.get("reasoning_effort").is_none());
+
+        let anthropic = build_anthropic_request("claude", &messages, None, generation, true);
+        assert_eq!(anthropic["thinking"]["type"], "enabled");
+    }
+
+    #[test]
+    fn test_ollama_sampling_goes_in_options() {
+        let messages = [ChatMessage::user("hi")];
+        let request = build_streaming_request(
+            &AIProvider::Ollama,
+            "llama",
+            &messages,
+            None,
+            GenerationSettings::default(),
+            false,
+        );
+        assert!(request.get("temperature").is_none());
+        assert!(request["options"].get("num_predict").is_some());
+        assert!(request.get("stream_options").is_none());
+    }
+}
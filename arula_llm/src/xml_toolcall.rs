@@ -10,8 +10,8 @@
 //! - Always takes the LAST tool_call seen
 //! - Produces stable OpenAI-style output
 
-use quick_xml::events::Event;
 use quick_xml::Reader;
+use quick_xml::events::Event;
 use serde_json::json;
 
 /// State machine for XML tool call extraction
@@ -201,6 +201,10 @@ fn extract_glm46_format(xml_text: &str) -> Option<serde_json::Value> {
 
                 match tag_name.as_str() {
                     "tool_call" => {
+                        // A name attribute is the standard format
+                        if e.attributes().flatten().any(|a| a.key.as_ref() == b"name") {
+                            return None;
+                        }
                         in_tool_call = true;
                         tool_name.clear();
                         args_map.clear();
@@ -293,10 +297,12 @@ mod tests {
         let call = result.unwrap();
         assert_eq!(call["type"], "function");
         assert_eq!(call["function"]["name"], "list_directory");
-        assert!(call["function"]["arguments"]
-            .as_str()
-            .unwrap()
-            .contains("path"));
+        assert!(
+            call["function"]["arguments"]
+                .as_str()
+                .unwrap()
+                .contains("path")
+        );
     }
 
     #[test]